@@ -316,8 +316,8 @@ export interface SharedFile {
   content_type: string;
   /** File size in bytes */
   size_bytes?: number;
-  /** File kind for rendering hints: "image", "document", "archive", "code", "other" */
-  kind: "image" | "document" | "archive" | "code" | "other";
+  /** File kind for rendering hints: "image", "document", "archive", "code", "directory", "diff", "other" */
+  kind: "image" | "document" | "archive" | "code" | "directory" | "diff" | "other";
 }
 
 export type ControlAgentEvent =
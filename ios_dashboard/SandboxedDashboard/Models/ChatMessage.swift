@@ -71,6 +71,8 @@ enum SharedFileKind: String, Codable {
     case document
     case archive
     case code
+    case directory
+    case diff
     case other
 
     var iconName: String {
@@ -79,6 +81,8 @@ enum SharedFileKind: String, Codable {
         case .document: return "doc.text"
         case .archive: return "archivebox"
         case .code: return "chevron.left.forwardslash.chevron.right"
+        case .directory: return "folder"
+        case .diff: return "plusminus"
         case .other: return "doc"
         }
     }
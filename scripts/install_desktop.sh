@@ -15,7 +15,7 @@ apt install -y xvfb i3 x11-utils
 
 # Install automation tools
 echo "Installing xdotool and screenshot tools..."
-apt install -y xdotool scrot imagemagick
+apt install -y xdotool xclip scrot imagemagick
 
 # Install Chromium browser
 echo "Installing Chromium..."
@@ -0,0 +1,244 @@
+//! Structured error codes.
+//!
+//! Errors reach clients as free-form messages from providers, CLIs and the
+//! server itself. An [`ErrorCode`] names the kind of failure so clients can
+//! react programmatically (back off on `provider_rate_limited`, compact the
+//! conversation on `context_overflow`, ...). Codes are carried on `error`
+//! events and on missions that ended in failure (derived from their
+//! [`TerminalReason`]).
+
+use serde::{Deserialize, Serialize};
+
+use super::types::TerminalReason;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    /// Provider rate limit hit (HTTP 429)
+    ProviderRateLimited,
+    /// Provider refused the turn for concurrent mission capacity
+    ProviderCapacityLimited,
+    /// Provider overloaded (HTTP 529)
+    ProviderOverloaded,
+    /// Provider rejected the credentials (HTTP 401)
+    ProviderAuthFailed,
+    /// Any other provider or backend failure
+    ProviderError,
+    /// Conversation no longer fits the model's context window
+    ContextOverflow,
+    /// A tool call was refused by the tool policy
+    ToolDeniedByPolicy,
+    /// The sandbox (workspace, egress policy) refused an operation
+    SandboxViolation,
+    /// Provider credits or spend limit exhausted
+    BudgetExceeded,
+    MaxIterations,
+    Stalled,
+    InfiniteLoop,
+    VerificationFailed,
+    Cancelled,
+    /// Server-side failure unrelated to the provider
+    Internal,
+}
+
+/// Message fragments (lowercase) identifying each classifiable code, most
+/// specific first: a context overflow often arrives as a plain 400.
+const PATTERNS: &[(ErrorCode, &[&str])] = &[
+    (
+        ErrorCode::ContextOverflow,
+        &[
+            "prompt is too long",
+            "context_length_exceeded",
+            "context length",
+            "context window",
+            "maximum context",
+            "too many tokens",
+        ],
+    ),
+    (
+        ErrorCode::ProviderAuthFailed,
+        &[
+            "invalid api key",
+            "invalid x-api-key",
+            "authentication_error",
+            "unauthorized",
+            "status 401",
+        ],
+    ),
+    (
+        ErrorCode::BudgetExceeded,
+        &[
+            "budget exceeded",
+            "credit balance is too low",
+            "insufficient credit",
+            "insufficient_quota",
+            "exceeded your current quota",
+            "spend limit",
+        ],
+    ),
+    (
+        ErrorCode::ProviderOverloaded,
+        &["overloaded", "status 529", "error 529", "(529)"],
+    ),
+    (
+        ErrorCode::ProviderRateLimited,
+        &[
+            "rate limit",
+            "rate_limit",
+            "ratelimit",
+            "too many requests",
+            "status 429",
+            "error 429",
+            "(429)",
+        ],
+    ),
+    (
+        ErrorCode::SandboxViolation,
+        &["sandbox violation", "egress denied", "egress policy"],
+    ),
+    (
+        ErrorCode::ToolDeniedByPolicy,
+        &[
+            "denied by policy",
+            "denied by tool policy",
+            "not allowed by policy",
+            "blocked by tool policy",
+        ],
+    ),
+];
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ProviderRateLimited => "provider_rate_limited",
+            Self::ProviderCapacityLimited => "provider_capacity_limited",
+            Self::ProviderOverloaded => "provider_overloaded",
+            Self::ProviderAuthFailed => "provider_auth_failed",
+            Self::ProviderError => "provider_error",
+            Self::ContextOverflow => "context_overflow",
+            Self::ToolDeniedByPolicy => "tool_denied_by_policy",
+            Self::SandboxViolation => "sandbox_violation",
+            Self::BudgetExceeded => "budget_exceeded",
+            Self::MaxIterations => "max_iterations",
+            Self::Stalled => "stalled",
+            Self::InfiniteLoop => "infinite_loop",
+            Self::VerificationFailed => "verification_failed",
+            Self::Cancelled => "cancelled",
+            Self::Internal => "internal",
+        }
+    }
+
+    /// Code for an error message, when it is recognizable.
+    pub fn classify(message: &str) -> Option<Self> {
+        let message = message.to_ascii_lowercase();
+        PATTERNS
+            .iter()
+            .find(|(_, fragments)| fragments.iter().any(|f| message.contains(f)))
+            .map(|(code, _)| *code)
+    }
+
+    /// Code for an error message, falling back to `fallback`.
+    pub fn classify_or(message: &str, fallback: Self) -> Self {
+        Self::classify(message).unwrap_or(fallback)
+    }
+
+    /// Code for a mission's stored terminal reason.
+    pub fn for_terminal_reason(terminal_reason: &str) -> Option<Self> {
+        TerminalReason::parse(terminal_reason).and_then(TerminalReason::error_code)
+    }
+
+    /// Terminal reason a turn failing with this code ends with.
+    pub fn terminal_reason(self) -> Option<TerminalReason> {
+        Some(match self {
+            Self::ProviderRateLimited => TerminalReason::RateLimited,
+            Self::ProviderCapacityLimited => TerminalReason::CapacityLimited,
+            Self::ProviderOverloaded => TerminalReason::ProviderOverloaded,
+            Self::ProviderAuthFailed => TerminalReason::ProviderAuthFailed,
+            Self::ProviderError => TerminalReason::LlmError,
+            Self::ContextOverflow => TerminalReason::ContextOverflow,
+            Self::ToolDeniedByPolicy => TerminalReason::ToolDeniedByPolicy,
+            Self::SandboxViolation => TerminalReason::SandboxViolation,
+            Self::BudgetExceeded => TerminalReason::BudgetExceeded,
+            Self::MaxIterations => TerminalReason::MaxIterations,
+            Self::Stalled => TerminalReason::Stalled,
+            Self::InfiniteLoop => TerminalReason::InfiniteLoop,
+            Self::VerificationFailed => TerminalReason::VerificationFailed,
+            Self::Cancelled => TerminalReason::Cancelled,
+            Self::Internal => return None,
+        })
+    }
+}
+
+impl std::fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_provider_messages() {
+        let cases = [
+            (
+                "API Error: 529 {\"type\":\"overloaded_error\",\"message\":\"Overloaded\"}",
+                Some(ErrorCode::ProviderOverloaded),
+            ),
+            (
+                "Provider returned status 429: Too Many Requests",
+                Some(ErrorCode::ProviderRateLimited),
+            ),
+            (
+                "invalid_request_error: prompt is too long: 210000 tokens > 200000 maximum",
+                Some(ErrorCode::ContextOverflow),
+            ),
+            (
+                "Your credit balance is too low to access the API",
+                Some(ErrorCode::BudgetExceeded),
+            ),
+            ("Invalid API key", Some(ErrorCode::ProviderAuthFailed)),
+            (
+                "Bash denied by policy: rm -rf /",
+                Some(ErrorCode::ToolDeniedByPolicy),
+            ),
+            ("Claude Code exited with code 1", None),
+        ];
+        for (message, expected) in cases {
+            assert_eq!(ErrorCode::classify(message), expected, "{}", message);
+        }
+    }
+
+    #[test]
+    fn terminal_reasons_round_trip_to_codes() {
+        assert_eq!(
+            ErrorCode::for_terminal_reason("rate_limited"),
+            Some(ErrorCode::ProviderRateLimited)
+        );
+        assert_eq!(
+            ErrorCode::for_terminal_reason("llm_error"),
+            Some(ErrorCode::ProviderError)
+        );
+        assert_eq!(ErrorCode::for_terminal_reason("completed"), None);
+        assert_eq!(ErrorCode::for_terminal_reason("unknown"), None);
+
+        assert_eq!(
+            TerminalReason::LlmError.refine("529 Overloaded"),
+            TerminalReason::ProviderOverloaded
+        );
+        assert_eq!(
+            TerminalReason::LlmError.refine("exit code 1"),
+            TerminalReason::LlmError
+        );
+        // Only generic failures are narrowed down
+        assert_eq!(
+            TerminalReason::MaxIterations.refine("prompt is too long"),
+            TerminalReason::MaxIterations
+        );
+        assert_eq!(
+            serde_json::to_value(ErrorCode::ToolDeniedByPolicy).unwrap(),
+            "tool_denied_by_policy"
+        );
+    }
+}
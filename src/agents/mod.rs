@@ -4,6 +4,7 @@
 //! - **OpenCodeAgent**: Delegates task execution to an OpenCode server
 
 mod context;
+mod error_code;
 mod opencode;
 mod types;
 
@@ -12,6 +13,7 @@ use std::sync::Arc;
 pub use opencode::OpenCodeAgent;
 
 pub use context::AgentContext;
+pub use error_code::ErrorCode;
 pub use types::{AgentError, AgentId, AgentResult, AgentType, CostSource, TerminalReason};
 
 use crate::task::Task;
@@ -7,8 +7,11 @@ use async_trait::async_trait;
 use serde_json::json;
 use std::sync::Arc;
 
-use crate::agents::{Agent, AgentContext, AgentId, AgentResult, AgentType, TerminalReason};
+use crate::agents::{
+    Agent, AgentContext, AgentId, AgentResult, AgentType, ErrorCode, TerminalReason,
+};
 use crate::api::control::{AgentEvent, AgentTreeNode, ControlRunState};
+use crate::api::user_question;
 use crate::config::Config;
 use crate::opencode::{extract_reasoning, extract_text, OpenCodeClient, OpenCodeEvent};
 use crate::task::Task;
@@ -110,8 +113,21 @@ impl OpenCodeAgent {
                 result: result.clone(),
                 mission_id: ctx.mission_id,
             },
+            OpenCodeEvent::ToolOutputDelta {
+                id,
+                name,
+                stream,
+                content,
+            } => AgentEvent::ToolOutputDelta {
+                tool_call_id: id.clone(),
+                name: name.clone(),
+                stream: *stream,
+                content: content.clone(),
+                mission_id: ctx.mission_id,
+            },
             OpenCodeEvent::Error { message } => AgentEvent::Error {
                 message: message.clone(),
+                code: Some(ErrorCode::classify_or(message, ErrorCode::ProviderError)),
                 mission_id: ctx.mission_id,
                 resumable: ctx.mission_id.is_some(), // Can resume if within a mission
             },
@@ -214,6 +230,7 @@ impl OpenCodeAgent {
         &self,
         tool_call_id: &str,
         name: &str,
+        args: &serde_json::Value,
         session_id: &str,
         directory: &str,
         ctx: &AgentContext,
@@ -228,12 +245,14 @@ impl OpenCodeAgent {
 
         let client = self.client.clone();
         let tool_call_id = tool_call_id.to_string();
+        let args = args.clone();
         let session_id = session_id.to_string();
         let directory = directory.to_string();
         let events_tx = ctx.control_events.clone();
         let control_status = ctx.control_status.clone();
         let mission_id = ctx.mission_id;
         let resumable = ctx.mission_id.is_some();
+        let cancel = ctx.cancel_token.clone();
 
         tokio::spawn(async move {
             if let (Some(status), Some(events), Some(mid)) =
@@ -262,9 +281,24 @@ impl OpenCodeAgent {
                     });
                 }
             }
-            let rx = tool_hub.register(tool_call_id.clone()).await;
-            let Ok(result) = rx.await else {
-                return;
+            let result = match &events_tx {
+                Some(events) => match user_question::ask(
+                    &tool_hub,
+                    events,
+                    mission_id,
+                    &tool_call_id,
+                    &args,
+                    cancel.as_ref(),
+                )
+                .await
+                {
+                    user_question::Outcome::Answered(result) => result,
+                    user_question::Outcome::Cancelled | user_question::Outcome::Closed => return,
+                },
+                None => match tool_hub.register(tool_call_id.clone()).await.await {
+                    Ok(result) => result,
+                    Err(_) => return,
+                },
             };
             if let (Some(status), Some(events), Some(mid)) =
                 (&control_status, &events_tx, mission_id)
@@ -313,6 +347,7 @@ impl OpenCodeAgent {
                     if let Some(tx) = &events_tx {
                         let _ = tx.send(AgentEvent::Error {
                             message: format!("Failed to list OpenCode questions: {}", e),
+                            code: Some(ErrorCode::Internal),
                             mission_id,
                             resumable,
                         });
@@ -328,6 +363,7 @@ impl OpenCodeAgent {
                             "No pending question found for tool_call_id {}",
                             tool_call_id
                         ),
+                        code: Some(ErrorCode::Internal),
                         mission_id,
                         resumable,
                     });
@@ -342,6 +378,7 @@ impl OpenCodeAgent {
                 if let Some(tx) = &events_tx {
                     let _ = tx.send(AgentEvent::Error {
                         message: format!("Failed to reply to question: {}", e),
+                        code: Some(ErrorCode::Internal),
                         mission_id,
                         resumable,
                     });
@@ -479,10 +516,11 @@ impl Agent for OpenCodeAgent {
                                     }
                                 }
 
-                                if let OpenCodeEvent::ToolCall { id, name, .. } = &oc_event {
+                                if let OpenCodeEvent::ToolCall { id, name, args } = &oc_event {
                                     self.handle_frontend_tool_call(
                                         id,
                                         name,
+                                        args,
                                         &session.id,
                                         &directory,
                                         ctx,
@@ -545,10 +583,11 @@ impl Agent for OpenCodeAgent {
                                         sse_text_buffer = content.clone();
                                     }
                                 }
-                                if let OpenCodeEvent::ToolCall { id, name, .. } = &oc_event {
+                                if let OpenCodeEvent::ToolCall { id, name, args } = &oc_event {
                                     self.handle_frontend_tool_call(
                                         id,
                                         name,
+                                        args,
                                         &session.id,
                                         &directory,
                                         ctx,
@@ -3,6 +3,8 @@
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
+use super::error_code::ErrorCode;
+
 /// Unique identifier for an agent.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct AgentId(Uuid);
@@ -155,6 +157,15 @@ impl AgentResult {
         self.terminal_reason = Some(reason);
         self
     }
+
+    /// Refine a generic `LlmError` terminal reason from the failure output
+    /// (see [`TerminalReason::refine`]).
+    pub fn with_classified_failure(mut self) -> Self {
+        if !self.success {
+            self.terminal_reason = self.terminal_reason.map(|r| r.refine(&self.output));
+        }
+        self
+    }
 }
 
 /// Reason why agent execution terminated.
@@ -176,6 +187,98 @@ pub enum TerminalReason {
     RateLimited,
     /// Provider rejected turn due to concurrent mission capacity exhaustion
     CapacityLimited,
+    /// Completion claim failed verification after all retries
+    VerificationFailed,
+    /// Provider overloaded (e.g. HTTP 529)
+    ProviderOverloaded,
+    /// Provider rejected the credentials
+    ProviderAuthFailed,
+    /// Conversation no longer fits the model's context window
+    ContextOverflow,
+    /// A tool call was refused by the tool policy
+    ToolDeniedByPolicy,
+    /// The sandbox (workspace, egress policy) refused an operation
+    SandboxViolation,
+    /// Provider credits or spend limit exhausted
+    BudgetExceeded,
+}
+
+impl TerminalReason {
+    /// Stored form of the reason (`missions.terminal_reason`).
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Completed => "completed",
+            Self::Cancelled => "cancelled",
+            Self::LlmError => "llm_error",
+            Self::Stalled => "stalled",
+            Self::InfiniteLoop => "infinite_loop",
+            Self::MaxIterations => "max_iterations",
+            Self::RateLimited => "rate_limited",
+            Self::CapacityLimited => "capacity_limited",
+            Self::VerificationFailed => "verification_failed",
+            Self::ProviderOverloaded => "provider_overloaded",
+            Self::ProviderAuthFailed => "provider_auth_failed",
+            Self::ContextOverflow => "context_overflow",
+            Self::ToolDeniedByPolicy => "tool_denied_by_policy",
+            Self::SandboxViolation => "sandbox_violation",
+            Self::BudgetExceeded => "budget_exceeded",
+        }
+    }
+
+    /// Parse a stored reason.
+    pub fn parse(value: &str) -> Option<Self> {
+        [
+            Self::Completed,
+            Self::Cancelled,
+            Self::LlmError,
+            Self::Stalled,
+            Self::InfiniteLoop,
+            Self::MaxIterations,
+            Self::RateLimited,
+            Self::CapacityLimited,
+            Self::VerificationFailed,
+            Self::ProviderOverloaded,
+            Self::ProviderAuthFailed,
+            Self::ContextOverflow,
+            Self::ToolDeniedByPolicy,
+            Self::SandboxViolation,
+            Self::BudgetExceeded,
+        ]
+        .into_iter()
+        .find(|reason| reason.as_str() == value)
+    }
+
+    /// Narrow a generic `LlmError` down to the failure its message describes.
+    pub fn refine(self, message: &str) -> Self {
+        if self != Self::LlmError {
+            return self;
+        }
+        ErrorCode::classify(message)
+            .and_then(ErrorCode::terminal_reason)
+            .unwrap_or(self)
+    }
+
+    /// Error code for a mission that ended for this reason (`None` when it
+    /// completed).
+    pub fn error_code(self) -> Option<ErrorCode> {
+        Some(match self {
+            Self::Completed => return None,
+            Self::Cancelled => ErrorCode::Cancelled,
+            Self::LlmError => ErrorCode::ProviderError,
+            Self::Stalled => ErrorCode::Stalled,
+            Self::InfiniteLoop => ErrorCode::InfiniteLoop,
+            Self::MaxIterations => ErrorCode::MaxIterations,
+            Self::RateLimited => ErrorCode::ProviderRateLimited,
+            Self::CapacityLimited => ErrorCode::ProviderCapacityLimited,
+            Self::VerificationFailed => ErrorCode::VerificationFailed,
+            Self::ProviderOverloaded => ErrorCode::ProviderOverloaded,
+            Self::ProviderAuthFailed => ErrorCode::ProviderAuthFailed,
+            Self::ContextOverflow => ErrorCode::ContextOverflow,
+            Self::ToolDeniedByPolicy => ErrorCode::ToolDeniedByPolicy,
+            Self::SandboxViolation => ErrorCode::SandboxViolation,
+            Self::BudgetExceeded => ErrorCode::BudgetExceeded,
+        })
+    }
 }
 
 /// Errors that can occur in agent operations.
@@ -0,0 +1,187 @@
+//! Audit log for actions missions take outside their workspace.
+//!
+//! Remote operations (e.g. SSH commands) are appended as JSON lines to
+//! `{working_dir}/.sandboxed-sh/audit.jsonl`, independent of mission history,
+//! so they survive mission deletion. `GET /api/audit` returns recent entries.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    response::Json,
+    routing::get,
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use super::routes::AppState;
+
+/// One audited action.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub id: Uuid,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    /// Subsystem that performed the action (e.g. `ssh`).
+    pub kind: String,
+    #[serde(default)]
+    pub mission_id: Option<Uuid>,
+    #[serde(default)]
+    pub workspace: Option<String>,
+    /// Where the action ran (e.g. `deploy@host:22`).
+    pub target: String,
+    /// What was run (e.g. the command line).
+    pub action: String,
+    pub success: bool,
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn new(kind: &str, target: String, action: String) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            timestamp: chrono::Utc::now(),
+            kind: kind.to_string(),
+            mission_id: None,
+            workspace: None,
+            target,
+            action,
+            success: false,
+            exit_code: None,
+            duration_ms: 0,
+            detail: None,
+        }
+    }
+}
+
+/// Append-only audit log file.
+pub struct AuditLog {
+    path: PathBuf,
+    write_lock: Mutex<()>,
+}
+
+pub type SharedAuditLog = Arc<AuditLog>;
+
+impl AuditLog {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    /// Append an entry. Failures are logged rather than returned so auditing
+    /// never masks the result of the action itself.
+    pub async fn record(&self, entry: &AuditEntry) {
+        tracing::info!(
+            target: "audit",
+            kind = %entry.kind,
+            mission_id = ?entry.mission_id,
+            target_host = %entry.target,
+            action = %entry.action,
+            success = entry.success,
+            exit_code = ?entry.exit_code,
+            "Audited action"
+        );
+        let _guard = self.write_lock.lock().await;
+        let result = async {
+            if let Some(parent) = self.path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            let mut line = serde_json::to_string(entry)?;
+            line.push('\n');
+            let mut file = tokio::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)
+                .await?;
+            file.write_all(line.as_bytes()).await?;
+            anyhow::Ok(())
+        }
+        .await;
+        if let Err(e) = result {
+            tracing::warn!(path = %self.path.display(), error = %e, "Failed to write audit entry");
+        }
+    }
+
+    /// Most recent entries first, optionally filtered.
+    pub async fn recent(
+        &self,
+        kind: Option<&str>,
+        mission_id: Option<Uuid>,
+        limit: usize,
+    ) -> Vec<AuditEntry> {
+        let Ok(content) = tokio::fs::read_to_string(&self.path).await else {
+            return Vec::new();
+        };
+        content
+            .lines()
+            .rev()
+            .filter_map(|line| serde_json::from_str::<AuditEntry>(line).ok())
+            .filter(|e| !matches!(kind, Some(k) if e.kind != k))
+            .filter(|e| mission_id.is_none() || e.mission_id == mission_id)
+            .take(limit)
+            .collect()
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AuditQuery {
+    pub kind: Option<String>,
+    pub mission_id: Option<Uuid>,
+    pub limit: Option<usize>,
+}
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new().route("/", get(list_entries))
+}
+
+async fn list_entries(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<AuditQuery>,
+) -> Json<Vec<AuditEntry>> {
+    Json(
+        state
+            .audit
+            .recent(
+                query.kind.as_deref(),
+                query.mission_id,
+                query.limit.unwrap_or(100).min(1000),
+            )
+            .await,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn entries_are_appended_and_filtered() {
+        let temp = tempfile::tempdir().unwrap();
+        let log = AuditLog::new(temp.path().join("nested/audit.jsonl"));
+        let mission = Uuid::new_v4();
+
+        let mut first = AuditEntry::new("ssh", "deploy@a:22".into(), "uptime".into());
+        first.mission_id = Some(mission);
+        first.success = true;
+        log.record(&first).await;
+        log.record(&AuditEntry::new("ssh", "deploy@b:22".into(), "ls".into()))
+            .await;
+
+        let all = log.recent(None, None, 10).await;
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].action, "ls");
+        let mine = log.recent(Some("ssh"), Some(mission), 10).await;
+        assert_eq!(mine.len(), 1);
+        assert_eq!(mine[0].target, "deploy@a:22");
+        assert!(log.recent(Some("k8s"), None, 10).await.is_empty());
+    }
+}
@@ -1,17 +1,24 @@
-//! Minimal JWT auth for the dashboard (single-tenant).
+//! Minimal JWT auth for the dashboard.
 //!
 //! - Dashboard submits a password to `/api/auth/login`
 //! - Server returns a JWT valid for ~30 days
 //! - When `DEV_MODE=false`, all API endpoints require `Authorization: Bearer <jwt>`
+//! - The JWT's `tnt` claim carries the user's tenant (see `crate::tenant`); the
+//!   middleware resolves it and passes it on in the trusted `x-sandboxed-tenant`
+//!   header
 //!
 //! # Security notes
-//! - This is intentionally minimal; it is NOT multi-tenant and does not implement RLS.
+//! - This is intentionally minimal; tenant isolation covers stores, workspaces,
+//!   libraries and file access, not the host itself (agents still run with
+//!   full access). Host-level endpoints (console, secrets, settings, system,
+//!   provider credentials, harness configuration, audit log, admin) are
+//!   closed to tenant users.
 //! - Use a strong `JWT_SECRET` in production.
 
 use axum::{
     body::Body,
     extract::State,
-    http::{Request, StatusCode},
+    http::{HeaderValue, Request, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
     Extension, Json,
@@ -22,6 +29,7 @@ use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
 use super::routes::AppState;
 use super::types::{LoginRequest, LoginResponse};
 use crate::config::{AuthMode, Config, UserAccount};
+use crate::tenant::TENANT_HEADER;
 use crate::util::internal_error;
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
@@ -31,6 +39,9 @@ struct Claims {
     /// Username (for display/auditing)
     #[serde(default)]
     usr: String,
+    /// Tenant id, when the user belongs to one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tnt: Option<String>,
     /// Issued-at unix seconds
     iat: i64,
     /// Expiration unix seconds
@@ -41,6 +52,22 @@ struct Claims {
 pub struct AuthUser {
     pub id: String,
     pub username: String,
+    /// Tenant the user belongs to, if any.
+    pub tenant: Option<String>,
+}
+
+impl AuthUser {
+    /// The instance user that requests get when they are not tied to an
+    /// account: `dev` in dev mode, `default` otherwise. Command-line modes
+    /// act as this user so their missions show up in the dashboard.
+    pub(crate) fn local(config: &Config) -> Self {
+        let id = if config.dev_mode { "dev" } else { "default" };
+        Self {
+            id: id.to_string(),
+            username: id.to_string(),
+            tenant: None,
+        }
+    }
 }
 
 pub(crate) fn constant_time_eq(a: &str, b: &str) -> bool {
@@ -118,6 +145,7 @@ fn issue_jwt(secret: &str, ttl_days: i64, user: &AuthUser) -> anyhow::Result<(St
     let claims = Claims {
         sub: user.id.clone(),
         usr: user.username.clone(),
+        tnt: user.tenant.clone(),
         iat: now.timestamp(),
         exp: exp.timestamp(),
     };
@@ -139,26 +167,49 @@ fn verify_jwt(token: &str, secret: &str) -> anyhow::Result<Claims> {
     Ok(token_data.claims)
 }
 
+/// The user a verified token belongs to; `None` for an unknown user or tenant.
+fn user_for_token_claims(claims: Claims, config: &Config) -> Option<AuthUser> {
+    let user = match config.auth.auth_mode(config.dev_mode) {
+        AuthMode::MultiUser => user_for_claims(&claims, &config.auth.users)?,
+        AuthMode::SingleTenant => AuthUser {
+            id: claims.sub,
+            username: claims.usr,
+            tenant: claims.tnt,
+        },
+        AuthMode::Disabled => AuthUser {
+            id: "default".to_string(),
+            username: "default".to_string(),
+            tenant: None,
+        },
+    };
+    if let Some(tenant) = user.tenant.as_deref() {
+        config.find_tenant(tenant)?;
+    }
+    Some(user)
+}
+
+/// Resolve the user of a JWT the way `require_auth` does, for endpoints that
+/// authenticate on their own (websockets). Without required auth (dev mode)
+/// every caller is the instance-level dev user.
+pub fn user_for_token(token: &str, config: &Config) -> Option<AuthUser> {
+    if !config.auth.auth_required(config.dev_mode) {
+        return Some(AuthUser {
+            id: "dev".to_string(),
+            username: "dev".to_string(),
+            tenant: None,
+        });
+    }
+    let secret = config.auth.jwt_secret.as_deref()?;
+    let claims = verify_jwt(token, secret).ok()?;
+    user_for_token_claims(claims, config)
+}
+
 /// Verify a JWT against the server config.
 /// Returns true iff:
 /// - auth is not required (dev mode), OR
 /// - auth is required and the token is valid.
 pub fn verify_token_for_config(token: &str, config: &Config) -> bool {
-    if !config.auth.auth_required(config.dev_mode) {
-        return true;
-    }
-    let secret = match config.auth.jwt_secret.as_deref() {
-        Some(s) => s,
-        None => return false,
-    };
-    let Ok(claims) = verify_jwt(token, secret) else {
-        return false;
-    };
-    match config.auth.auth_mode(config.dev_mode) {
-        AuthMode::MultiUser => user_for_claims(&claims, &config.auth.users).is_some(),
-        AuthMode::SingleTenant => true,
-        AuthMode::Disabled => true,
-    }
+    user_for_token(token, config).is_some()
 }
 
 pub async fn login(
@@ -208,6 +259,7 @@ pub async fn login(
             AuthUser {
                 id: effective_id,
                 username: account.username.clone(),
+                tenant: account.tenant.clone(),
             }
         }
         AuthMode::SingleTenant | AuthMode::Disabled => {
@@ -245,6 +297,7 @@ pub async fn login(
             AuthUser {
                 id: "default".to_string(),
                 username: "default".to_string(),
+                tenant: None,
             }
         }
     };
@@ -269,9 +322,11 @@ pub async fn require_auth(
 ) -> Response {
     // Dev mode => no auth checks.
     if state.config.dev_mode {
+        req.headers_mut().remove(TENANT_HEADER);
         req.extensions_mut().insert(AuthUser {
             id: "dev".to_string(),
             username: "dev".to_string(),
+            tenant: None,
         });
         return next.run(req).await;
     }
@@ -315,12 +370,28 @@ pub async fn require_auth(
                 AuthMode::SingleTenant => AuthUser {
                     id: claims.sub,
                     username: claims.usr,
+                    tenant: claims.tnt,
                 },
                 AuthMode::Disabled => AuthUser {
                     id: "default".to_string(),
                     username: "default".to_string(),
+                    tenant: None,
                 },
             };
+            req.headers_mut().remove(TENANT_HEADER);
+            if let Some(tenant) = user.tenant.as_deref() {
+                if state.config.find_tenant(tenant).is_none() {
+                    return (StatusCode::UNAUTHORIZED, "Unknown tenant").into_response();
+                }
+                match HeaderValue::from_str(tenant) {
+                    Ok(value) => {
+                        req.headers_mut().insert(TENANT_HEADER, value);
+                    }
+                    Err(_) => {
+                        return (StatusCode::UNAUTHORIZED, "Invalid tenant").into_response();
+                    }
+                }
+            }
             req.extensions_mut().insert(user);
             next.run(req).await
         }
@@ -328,6 +399,21 @@ pub async fn require_auth(
     }
 }
 
+/// Reject tenant users from host-level endpoints (secrets, settings, system
+/// components, provider credentials, harness configuration, instance-wide
+/// stores and admin endpoints), which tenant isolation doesn't cover. Runs
+/// after `require_auth`.
+pub async fn require_instance_user(
+    Extension(user): Extension<AuthUser>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    if user.tenant.is_some() {
+        return (StatusCode::FORBIDDEN, "Not available to tenant users").into_response();
+    }
+    next.run(req).await
+}
+
 /// Returns the effective user ID (id if non-empty, otherwise username).
 fn effective_user_id(user: &UserAccount) -> String {
     if user.id.is_empty() {
@@ -337,13 +423,16 @@ fn effective_user_id(user: &UserAccount) -> String {
     }
 }
 
+/// Resolve the account for a token. The tenant comes from the account, so a
+/// token issued before the user moved tenants is rejected.
 fn user_for_claims(claims: &Claims, users: &[UserAccount]) -> Option<AuthUser> {
     users
         .iter()
-        .find(|u| effective_user_id(u) == claims.sub)
+        .find(|u| effective_user_id(u) == claims.sub && u.tenant == claims.tnt)
         .map(|u| AuthUser {
             id: effective_user_id(u),
             username: u.username.clone(),
+            tenant: u.tenant.clone(),
         })
 }
 
@@ -355,10 +444,14 @@ pub struct AuthStatusResponse {
     pub password_source: String, // "dashboard", "environment", "none"
     pub password_changed_at: Option<String>,
     pub dev_mode: bool,
+    /// The caller's tenant, with its overrides and quotas
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<crate::tenant::TenantConfig>,
 }
 
 pub async fn auth_status(
     State(state): State<std::sync::Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
 ) -> Json<AuthStatusResponse> {
     let auth_mode = match state.config.auth.auth_mode(state.config.dev_mode) {
         AuthMode::Disabled => "disabled",
@@ -388,6 +481,11 @@ pub async fn auth_status(
         password_source: password_source.to_string(),
         password_changed_at,
         dev_mode: state.config.dev_mode,
+        tenant: user
+            .tenant
+            .as_deref()
+            .and_then(|id| state.config.find_tenant(id))
+            .cloned(),
     })
 }
 
@@ -495,3 +593,72 @@ pub async fn change_password(
         "password_changed_at": now
     })))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{
+        middleware,
+        routing::{get, put},
+        Router,
+    };
+    use tower::ServiceExt;
+
+    async fn status(router: &Router, method: &str, uri: &str) -> StatusCode {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .body(Body::empty())
+            .unwrap();
+        router.clone().oneshot(request).await.unwrap().status()
+    }
+
+    #[tokio::test]
+    async fn tenant_users_get_403_on_instance_routes() {
+        // Mirrors how `routes.rs` gates whole nests and single methods.
+        let router = |user: AuthUser| {
+            Router::new()
+                .nest(
+                    "/api/audit",
+                    Router::new()
+                        .route("/", get(|| async { "entries" }))
+                        .route_layer(middleware::from_fn(require_instance_user)),
+                )
+                .route("/api/amp/config", get(|| async { "config" }))
+                .route(
+                    "/api/amp/config",
+                    put(|| async { "saved" })
+                        .route_layer(middleware::from_fn(require_instance_user)),
+                )
+                .layer(Extension(user))
+        };
+        let tenant = router(AuthUser {
+            id: "alice".to_string(),
+            username: "alice".to_string(),
+            tenant: Some("acme".to_string()),
+        });
+        let instance = router(AuthUser {
+            id: "admin".to_string(),
+            username: "admin".to_string(),
+            tenant: None,
+        });
+
+        assert_eq!(
+            status(&tenant, "GET", "/api/audit").await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(&tenant, "PUT", "/api/amp/config").await,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            status(&tenant, "GET", "/api/amp/config").await,
+            StatusCode::OK
+        );
+        assert_eq!(status(&instance, "GET", "/api/audit").await, StatusCode::OK);
+        assert_eq!(
+            status(&instance, "PUT", "/api/amp/config").await,
+            StatusCode::OK
+        );
+    }
+}
@@ -0,0 +1,384 @@
+//! iCalendar feed of upcoming automation runs.
+//!
+//! `POST /api/workspaces/:id/calendar` returns a subscription URL that calendar
+//! apps can poll without logging in (`GET /api/calendar/:token.ics`). The feed
+//! lists the next runs of the active automations whose missions run in the
+//! workspace, so a team calendar shows when recurring missions will start.
+//!
+//! Only interval triggers have predictable run times; webhook and
+//! agent-finished automations are left out. Runs are projected from the last
+//! trigger, so a busy mission or a restart can shift them slightly, and runs
+//! that fall in a blackout window are omitted.
+
+use std::collections::{hash_map::Entry, HashMap};
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
+use chrono::{DateTime, Duration, Utc};
+use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::mission_share::{redact_secrets, signing_key};
+use super::mission_store::{Automation, CommandSource, TriggerType};
+use super::routes::AppState;
+use super::workspaces::require_workspace;
+use crate::blackout::active_window;
+use crate::util::internal_error;
+
+const CALENDAR_PURPOSE: &str = "automation-calendar";
+
+/// How long subscription links stay valid.
+const LINK_TTL_DAYS: i64 = 365;
+
+/// How far ahead the feed lists runs.
+const HORIZON_DAYS: i64 = 14;
+
+/// Most runs listed per automation, so short intervals don't flood calendars.
+const MAX_RUNS_PER_AUTOMATION: usize = 50;
+
+/// Length of each calendar entry (runs have no known duration up front).
+const RUN_DURATION_MINUTES: i64 = 15;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CalendarClaims {
+    /// Workspace whose automations are listed
+    wid: Uuid,
+    /// Owner's user id, username and tenant, to find the automations
+    uid: String,
+    usr: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tnt: Option<String>,
+    iat: i64,
+    exp: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CalendarLink {
+    pub token: String,
+    /// Path of the feed, relative to the server's address
+    pub url: String,
+    pub expires_at: String,
+}
+
+/// One upcoming run in the feed.
+#[derive(Debug)]
+pub struct CalendarRun {
+    pub automation_id: Uuid,
+    pub start: DateTime<Utc>,
+    pub summary: String,
+    pub description: String,
+}
+
+/// Start times of an automation's runs between `now` and `until`.
+pub fn upcoming_runs(
+    automation: &Automation,
+    now: DateTime<Utc>,
+    until: DateTime<Utc>,
+) -> Vec<DateTime<Utc>> {
+    let TriggerType::Interval { seconds } = automation.trigger else {
+        return Vec::new();
+    };
+    if seconds == 0 {
+        return Vec::new();
+    }
+    let interval = Duration::seconds(seconds as i64);
+    // Overdue (or never run) automations start on the scheduler's next tick
+    let mut next = automation
+        .last_triggered_at
+        .as_deref()
+        .and_then(|t| DateTime::parse_from_rfc3339(t).ok())
+        .map(|t| t.with_timezone(&Utc) + interval)
+        .filter(|t| *t > now)
+        .unwrap_or(now);
+
+    let mut runs = Vec::new();
+    while next <= until && runs.len() < MAX_RUNS_PER_AUTOMATION {
+        runs.push(next);
+        next += interval;
+    }
+    runs
+}
+
+fn describe_command(source: &CommandSource) -> String {
+    match source {
+        CommandSource::Library { name } => format!("Library command: {}", name),
+        CommandSource::LocalFile { path } => format!("Command file: {}", path),
+        CommandSource::Inline { content } => {
+            let first_line = content.lines().next().unwrap_or_default();
+            let mut line: String = first_line.chars().take(200).collect();
+            if line.len() < content.trim_end().len() {
+                line.push('…');
+            }
+            redact_secrets(&line)
+        }
+    }
+}
+
+/// Escape a TEXT value (RFC 5545 §3.3.11).
+fn escape_text(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Append a content line, folded at 75 octets (RFC 5545 §3.1).
+fn push_line(ics: &mut String, line: &str) {
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > 75 {
+            ics.push_str("\r\n ");
+            width = 1;
+        }
+        ics.push(c);
+        width += c.len_utf8();
+    }
+    ics.push_str("\r\n");
+}
+
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Render runs as an iCalendar document.
+pub fn render_calendar(name: &str, runs: &[CalendarRun], now: DateTime<Utc>) -> String {
+    let mut ics = String::new();
+    push_line(&mut ics, "BEGIN:VCALENDAR");
+    push_line(&mut ics, "VERSION:2.0");
+    push_line(&mut ics, "PRODID:-//sandboxed.sh//Automations//EN");
+    push_line(&mut ics, "CALSCALE:GREGORIAN");
+    push_line(&mut ics, &format!("X-WR-CALNAME:{}", escape_text(name)));
+    for run in runs {
+        push_line(&mut ics, "BEGIN:VEVENT");
+        push_line(
+            &mut ics,
+            &format!(
+                "UID:{}-{}@sandboxed.sh",
+                run.automation_id,
+                run.start.timestamp()
+            ),
+        );
+        push_line(&mut ics, &format!("DTSTAMP:{}", format_time(now)));
+        push_line(&mut ics, &format!("DTSTART:{}", format_time(run.start)));
+        push_line(&mut ics, &format!("DURATION:PT{}M", RUN_DURATION_MINUTES));
+        push_line(&mut ics, &format!("SUMMARY:{}", escape_text(&run.summary)));
+        push_line(
+            &mut ics,
+            &format!("DESCRIPTION:{}", escape_text(&run.description)),
+        );
+        push_line(&mut ics, "END:VEVENT");
+    }
+    push_line(&mut ics, "END:VCALENDAR");
+    ics
+}
+
+/// POST /api/workspaces/:id/calendar - Create a subscription link for the
+/// workspace's automation calendar.
+pub async fn create_calendar_link(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<CalendarLink>, (StatusCode, String)> {
+    let key = signing_key(&state, CALENDAR_PURPOSE)?;
+    require_workspace(&state.workspaces, id, user.tenant.as_deref()).await?;
+
+    let now = Utc::now();
+    let expires_at = now + Duration::days(LINK_TTL_DAYS);
+    let claims = CalendarClaims {
+        wid: id,
+        uid: user.id.clone(),
+        usr: user.username.clone(),
+        tnt: user.tenant.clone(),
+        iat: now.timestamp(),
+        exp: expires_at.timestamp(),
+    };
+    let token = jsonwebtoken::encode(&Header::default(), &claims, &EncodingKey::from_secret(&key))
+        .map_err(internal_error)?;
+    Ok(Json(CalendarLink {
+        url: format!("/api/calendar/{}.ics", token),
+        token,
+        expires_at: expires_at.to_rfc3339(),
+    }))
+}
+
+/// GET /api/calendar/:token - The automation calendar (no login required).
+pub async fn automation_calendar(
+    State(state): State<Arc<AppState>>,
+    Path(token): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let key = signing_key(&state, CALENDAR_PURPOSE)?;
+    let token = token.strip_suffix(".ics").unwrap_or(&token);
+    let claims = jsonwebtoken::decode::<CalendarClaims>(
+        token,
+        &DecodingKey::from_secret(&key),
+        &Validation::default(),
+    )
+    .map_err(|_| {
+        (
+            StatusCode::NOT_FOUND,
+            "Calendar link is invalid or has expired".to_string(),
+        )
+    })?
+    .claims;
+    let workspace = require_workspace(&state.workspaces, claims.wid, claims.tnt.as_deref()).await?;
+
+    let owner = AuthUser {
+        id: claims.uid,
+        username: claims.usr,
+        tenant: claims.tnt,
+    };
+    let store = state.control.get_or_spawn(&owner).await.mission_store;
+    let automations = store
+        .list_active_automations()
+        .await
+        .map_err(internal_error)?;
+
+    let now = Utc::now();
+    let until = now + Duration::days(HORIZON_DAYS);
+    let mut missions = HashMap::new();
+    let mut runs = Vec::new();
+    for automation in automations {
+        // Runs falling in a blackout window don't happen at that time
+        let mut starts = upcoming_runs(&automation, now, until);
+        starts.retain(|start| {
+            active_window(
+                automation
+                    .blackout_windows
+                    .iter()
+                    .chain(&workspace.automation_blackouts),
+                *start,
+            )
+            .is_none()
+        });
+        if starts.is_empty() {
+            continue;
+        }
+        if let Entry::Vacant(slot) = missions.entry(automation.mission_id) {
+            slot.insert(
+                store
+                    .get_mission(automation.mission_id)
+                    .await
+                    .map_err(internal_error)?,
+            );
+        }
+        let Some(mission) = missions
+            .get(&automation.mission_id)
+            .and_then(|m| m.as_ref())
+            .filter(|m| m.workspace_id == workspace.id)
+        else {
+            continue;
+        };
+        let summary = mission
+            .title
+            .as_deref()
+            .filter(|t| !t.trim().is_empty())
+            .map(redact_secrets)
+            .unwrap_or_else(|| format!("Mission {}", mission.id));
+        let description = describe_command(&automation.command_source);
+        runs.extend(starts.into_iter().map(|start| CalendarRun {
+            automation_id: automation.id,
+            start,
+            summary: summary.clone(),
+            description: description.clone(),
+        }));
+    }
+    runs.sort_by_key(|run| run.start);
+
+    let body = render_calendar(&format!("{} automations", workspace.name), &runs, now);
+    Ok((
+        [(header::CONTENT_TYPE, "text/calendar; charset=utf-8")],
+        body,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mission_store::{FreshSession, RetryConfig, StopPolicy};
+
+    fn automation(trigger: TriggerType, last_triggered_at: Option<String>) -> Automation {
+        Automation {
+            id: Uuid::new_v4(),
+            mission_id: Uuid::new_v4(),
+            command_source: CommandSource::Library {
+                name: "nightly-refactor".to_string(),
+            },
+            trigger,
+            variables: HashMap::new(),
+            active: true,
+            created_at: Utc::now().to_rfc3339(),
+            last_triggered_at,
+            retry_config: RetryConfig::default(),
+            stop_policy: StopPolicy::Never,
+            fresh_session: FreshSession::Keep,
+            mission_retry_policy: None,
+            blackout_windows: Vec::new(),
+            consecutive_failures: 0,
+        }
+    }
+
+    #[test]
+    fn interval_runs_follow_the_last_trigger() {
+        let now = Utc::now();
+        let day = TriggerType::Interval { seconds: 86_400 };
+        let last = (now - Duration::hours(6)).to_rfc3339();
+        let runs = upcoming_runs(
+            &automation(day.clone(), Some(last)),
+            now,
+            now + Duration::days(3),
+        );
+        assert_eq!(runs.len(), 3);
+        assert_eq!(runs[0].timestamp(), (now + Duration::hours(18)).timestamp());
+
+        // Never triggered: runs on the next scheduler tick
+        let runs = upcoming_runs(&automation(day, None), now, now + Duration::days(1));
+        assert_eq!(runs, vec![now, now + Duration::days(1)]);
+
+        let minutely = automation(TriggerType::Interval { seconds: 60 }, None);
+        assert_eq!(
+            upcoming_runs(&minutely, now, now + Duration::days(1)).len(),
+            MAX_RUNS_PER_AUTOMATION
+        );
+        assert!(upcoming_runs(
+            &automation(TriggerType::AgentFinished, None),
+            now,
+            now + Duration::days(1)
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn calendar_escapes_and_folds_lines() {
+        let start = DateTime::parse_from_rfc3339("2026-10-20T02:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let runs = [CalendarRun {
+            automation_id: Uuid::nil(),
+            start,
+            summary: "Deps; weekly, bot".to_string(),
+            description: "x".repeat(100),
+        }];
+        let ics = render_calendar("Team automations", &runs, start);
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(ics.ends_with("END:VCALENDAR\r\n"));
+        assert!(ics.contains("DTSTART:20261020T020000Z\r\n"));
+        assert!(ics.contains("SUMMARY:Deps\\; weekly\\, bot\r\n"));
+        assert!(ics.split("\r\n").all(|line| line.len() <= 75));
+        assert!(ics.contains("\r\n x"));
+    }
+}
@@ -0,0 +1,226 @@
+//! Load-aware throttling of interval automations.
+//!
+//! Automations compete with interactive missions for the same parallel slots
+//! and provider quota. While the session is busy the scheduler defers interval
+//! triggers instead of piling more work on: when at least
+//! `AUTOMATION_THROTTLE_RUNNING` missions are running, or for
+//! `AUTOMATION_RATE_LIMIT_BACKOFF_SECS` after a mission hit a provider rate
+//! limit. A deferred run keeps its schedule and fires on the first check after
+//! the load drops.
+//!
+//! Deferrals are counted once per automation per episode and exposed with
+//! `GET /api/control/automations/throttle`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+use axum::{extract::State, Extension, Json};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::control::AgentEvent;
+use super::routes::AppState;
+use crate::agents::ErrorCode;
+
+pub type SharedAutomationThrottle = Arc<AutomationThrottle>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeferReason {
+    /// Too many missions running
+    Load,
+    /// A provider recently rate-limited a mission
+    RateLimit,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ThrottleStats {
+    /// Running missions at which triggers are deferred (0 = never)
+    pub running_threshold: usize,
+    /// Seconds triggers are deferred after a rate limit (0 = never)
+    pub rate_limit_backoff_secs: u64,
+    /// Runs deferred because of load, since startup
+    pub deferred_load: u64,
+    /// Runs deferred because of rate limits, since startup
+    pub deferred_rate_limit: u64,
+    /// Automations waiting right now
+    pub deferred_now: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_deferred_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limited_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Default)]
+struct ThrottleState {
+    deferred_load: u64,
+    deferred_rate_limit: u64,
+    last_deferred_at: Option<DateTime<Utc>>,
+    rate_limited_until: Option<DateTime<Utc>>,
+    /// Automations currently deferred, so an episode is counted once
+    deferred: HashMap<Uuid, DeferReason>,
+}
+
+#[derive(Debug)]
+pub struct AutomationThrottle {
+    running_threshold: usize,
+    rate_limit_backoff: chrono::Duration,
+    state: Mutex<ThrottleState>,
+}
+
+impl AutomationThrottle {
+    pub fn new(running_threshold: usize, rate_limit_backoff_secs: u64) -> Self {
+        Self {
+            running_threshold,
+            rate_limit_backoff: chrono::Duration::seconds(
+                rate_limit_backoff_secs.min(u32::MAX as u64) as i64,
+            ),
+            state: Mutex::new(ThrottleState::default()),
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, ThrottleState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Start (or extend) the rate-limit backoff.
+    pub fn note_rate_limit(&self, at: DateTime<Utc>) {
+        if self.rate_limit_backoff <= chrono::Duration::zero() {
+            return;
+        }
+        let until = at + self.rate_limit_backoff;
+        let mut state = self.state();
+        if state
+            .rate_limited_until
+            .is_none_or(|current| current < until)
+        {
+            state.rate_limited_until = Some(until);
+        }
+    }
+
+    /// Why a trigger due now should wait, given the running mission count.
+    pub fn defer_reason(&self, running: usize, now: DateTime<Utc>) -> Option<DeferReason> {
+        if self.running_threshold > 0 && running >= self.running_threshold {
+            return Some(DeferReason::Load);
+        }
+        self.state()
+            .rate_limited_until
+            .is_some_and(|until| now < until)
+            .then_some(DeferReason::RateLimit)
+    }
+
+    /// Record that an automation's trigger was deferred. Returns whether this
+    /// starts a new deferral, which is when it is counted.
+    pub fn record_deferred(
+        &self,
+        automation_id: Uuid,
+        reason: DeferReason,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let mut state = self.state();
+        if state.deferred.insert(automation_id, reason) == Some(reason) {
+            return false;
+        }
+        match reason {
+            DeferReason::Load => state.deferred_load += 1,
+            DeferReason::RateLimit => state.deferred_rate_limit += 1,
+        }
+        state.last_deferred_at = Some(now);
+        true
+    }
+
+    /// The automation got past the throttle.
+    pub fn clear(&self, automation_id: Uuid) {
+        self.state().deferred.remove(&automation_id);
+    }
+
+    pub fn stats(&self, now: DateTime<Utc>) -> ThrottleStats {
+        let state = self.state();
+        ThrottleStats {
+            running_threshold: self.running_threshold,
+            rate_limit_backoff_secs: self.rate_limit_backoff.num_seconds().max(0) as u64,
+            deferred_load: state.deferred_load,
+            deferred_rate_limit: state.deferred_rate_limit,
+            deferred_now: state.deferred.len(),
+            last_deferred_at: state.last_deferred_at,
+            rate_limited_until: state.rate_limited_until.filter(|until| now < *until),
+        }
+    }
+}
+
+/// Background task that starts the backoff whenever a mission reports a
+/// provider rate limit.
+pub async fn rate_limit_watch(
+    throttle: SharedAutomationThrottle,
+    mut events_rx: broadcast::Receiver<AgentEvent>,
+) {
+    loop {
+        match events_rx.recv().await {
+            Ok(AgentEvent::Error {
+                code: Some(ErrorCode::ProviderRateLimited),
+                mission_id,
+                ..
+            }) => {
+                tracing::info!(
+                    ?mission_id,
+                    "Provider rate limit reported, deferring automations"
+                );
+                throttle.note_rate_limit(Utc::now());
+            }
+            Ok(_) => {}
+            Err(broadcast::error::RecvError::Lagged(_)) => {}
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+/// GET /api/control/automations/throttle - Deferral settings and counters.
+pub async fn get_throttle_stats(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+) -> Json<ThrottleStats> {
+    let control = state.control.get_or_spawn(&user).await;
+    Json(control.automation_throttle.stats(Utc::now()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn defers_on_load_and_during_rate_limit_backoff() {
+        let throttle = AutomationThrottle::new(3, 60);
+        let now = Utc::now();
+        assert_eq!(throttle.defer_reason(2, now), None);
+        assert_eq!(throttle.defer_reason(3, now), Some(DeferReason::Load));
+
+        throttle.note_rate_limit(now);
+        assert_eq!(throttle.defer_reason(0, now), Some(DeferReason::RateLimit));
+        let later = now + chrono::Duration::seconds(61);
+        assert_eq!(throttle.defer_reason(0, later), None);
+        assert!(throttle.stats(later).rate_limited_until.is_none());
+
+        let disabled = AutomationThrottle::new(0, 0);
+        disabled.note_rate_limit(now);
+        assert_eq!(disabled.defer_reason(100, now), None);
+    }
+
+    #[test]
+    fn deferrals_are_counted_once_per_episode() {
+        let throttle = AutomationThrottle::new(1, 60);
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        assert!(throttle.record_deferred(id, DeferReason::Load, now));
+        assert!(!throttle.record_deferred(id, DeferReason::Load, now));
+        assert!(throttle.record_deferred(id, DeferReason::RateLimit, now));
+        assert_eq!(throttle.stats(now).deferred_now, 1);
+
+        throttle.clear(id);
+        assert!(throttle.record_deferred(id, DeferReason::Load, now));
+        let stats = throttle.stats(now);
+        assert_eq!((stats.deferred_load, stats.deferred_rate_limit), (2, 1));
+    }
+}
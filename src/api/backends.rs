@@ -1,5 +1,6 @@
 //! Backend management API endpoints.
 
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use axum::{
@@ -10,8 +11,10 @@ use axum::{
 use serde::{Deserialize, Serialize};
 
 use crate::backend::registry::BackendInfo;
+use crate::backend::BackendCapabilities;
 
 use super::auth::AuthUser;
+use super::providers::{backend_model_options, BackendModelOption};
 use super::routes::AppState;
 
 /// Backend information returned by API
@@ -19,13 +22,30 @@ use super::routes::AppState;
 pub struct BackendResponse {
     pub id: String,
     pub name: String,
+    /// Used when a mission doesn't name a backend
+    pub default: bool,
+    pub capabilities: BackendCapabilities,
+    /// Model overrides offered for this backend (from configured providers)
+    pub models: Vec<BackendModelOption>,
 }
 
-impl From<BackendInfo> for BackendResponse {
-    fn from(info: BackendInfo) -> Self {
+impl BackendResponse {
+    fn new(
+        info: BackendInfo,
+        default_id: &str,
+        models: &mut HashMap<String, Vec<BackendModelOption>>,
+    ) -> Self {
+        let models = if info.capabilities.model_override {
+            models.remove(&info.id).unwrap_or_default()
+        } else {
+            Vec::new()
+        };
         Self {
+            default: info.id == default_id,
             id: info.id,
             name: info.name,
+            capabilities: info.capabilities,
+            models,
         }
     }
 }
@@ -37,13 +57,19 @@ pub struct AgentResponse {
     pub name: String,
 }
 
-/// List all available backends
+/// List all available backends with their capabilities
 pub async fn list_backends(
     State(state): State<Arc<AppState>>,
     Extension(_user): Extension<AuthUser>,
 ) -> Json<Vec<BackendResponse>> {
+    let mut models = backend_model_options(&state, false).await;
     let registry = state.backend_registry.read().await;
-    let backends: Vec<BackendResponse> = registry.list().into_iter().map(Into::into).collect();
+    let default_id = registry.default_id().to_string();
+    let backends: Vec<BackendResponse> = registry
+        .list()
+        .into_iter()
+        .map(|info| BackendResponse::new(info, &default_id, &mut models))
+        .collect();
     Json(backends)
 }
 
@@ -53,14 +79,20 @@ pub async fn get_backend(
     Extension(_user): Extension<AuthUser>,
     Path(id): Path<String>,
 ) -> Result<Json<BackendResponse>, (StatusCode, String)> {
-    let registry = state.backend_registry.read().await;
-    match registry.get(&id) {
-        Some(backend) => Ok(Json(BackendResponse {
+    let (info, default_id) = {
+        let registry = state.backend_registry.read().await;
+        let backend = registry
+            .get(&id)
+            .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Backend {} not found", id)))?;
+        let info = BackendInfo {
             id: backend.id().to_string(),
             name: backend.name().to_string(),
-        })),
-        None => Err((StatusCode::NOT_FOUND, format!("Backend {} not found", id))),
-    }
+            capabilities: backend.capabilities(),
+        };
+        (info, registry.default_id().to_string())
+    };
+    let mut models = backend_model_options(&state, false).await;
+    Ok(Json(BackendResponse::new(info, &default_id, &mut models)))
 }
 
 /// List agents for a specific backend
@@ -277,6 +309,16 @@ pub async fn update_backend_config(
                 "permissive": permissive,
             })
         }
+        "ollama" => {
+            let base_url = req
+                .settings
+                .get("base_url")
+                .and_then(|v| v.as_str())
+                .map(|s| s.trim().trim_end_matches('/').to_string())
+                .filter(|s| !s.is_empty())
+                .ok_or_else(|| (StatusCode::BAD_REQUEST, "base_url is required".to_string()))?;
+            serde_json::json!({ "base_url": base_url })
+        }
         "claudecode" => {
             let mut settings = req.settings.clone();
             if let Some(api_key) = settings.get("api_key").and_then(|v| v.as_str()) {
@@ -0,0 +1,436 @@
+//! Best-of-N sampling for hard mission turns.
+//!
+//! When enabled, a turn that looks hard (multi-step, or a long request) first
+//! samples N candidate approaches in parallel through the local
+//! OpenAI-compatible proxy, each with its own model/temperature. A verifier
+//! prompt scores the candidates and the best one is handed to the backend
+//! agent as the approach to follow.
+//!
+//! The strategy is opt-in and configured from the environment:
+//!
+//! - `SANDBOXED_SH_BEST_OF_N` - number of candidates (unset or below 2 disables it)
+//! - `SANDBOXED_SH_BEST_OF_N_MODELS` - comma-separated models, cycled per candidate
+//!   (default `builtin/smart`)
+//! - `SANDBOXED_SH_BEST_OF_N_TEMPERATURES` - comma-separated temperatures, cycled
+//!   per candidate (default `0.2,0.7,1.0`)
+//! - `SANDBOXED_SH_BEST_OF_N_VERIFIER_MODEL` - model used for scoring (default: the
+//!   first sampling model)
+//! - `SANDBOXED_SH_BEST_OF_N_MAX_COST_CENTS` - cost cap for sampling and
+//!   verification combined (default 100)
+//! - `SANDBOXED_SH_BEST_OF_N_MIN_CHARS` - requests at least this long count as
+//!   hard even when they are not multi-step (default 400)
+//!
+//! Every candidate and the final selection are emitted as events
+//! (`sampling_candidate`, `sampling_result`) and each LLM call reports its
+//! usage, so sampling cost shows up in the mission's cost breakdown.
+
+use serde_json::Value;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use super::control::AgentEvent;
+use super::llm_client::{Completion, ProxyClient};
+use crate::cost::TokenUsage;
+
+const MAX_CANDIDATES: usize = 8;
+const DEFAULT_MODEL: &str = "builtin/smart";
+const DEFAULT_TEMPERATURES: &[f64] = &[0.2, 0.7, 1.0];
+const DEFAULT_MAX_COST_CENTS: u64 = 100;
+const DEFAULT_MIN_CHARS: usize = 400;
+/// Output token limit per candidate; also used to estimate worst-case cost.
+const CANDIDATE_MAX_TOKENS: u64 = 1500;
+const VERIFIER_MAX_TOKENS: u64 = 600;
+
+/// Best-of-N sampling settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BestOfNConfig {
+    pub candidates: usize,
+    pub models: Vec<String>,
+    pub temperatures: Vec<f64>,
+    pub verifier_model: String,
+    pub max_cost_cents: u64,
+    pub min_chars: usize,
+}
+
+fn parse_list<T: std::str::FromStr>(value: Option<String>) -> Vec<T> {
+    value
+        .map(|v| {
+            v.split(',')
+                .filter_map(|item| item.trim().parse().ok())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+impl BestOfNConfig {
+    /// Read the configuration; `None` when the strategy is disabled.
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.trim().is_empty());
+        Self::from_values(
+            var("SANDBOXED_SH_BEST_OF_N"),
+            var("SANDBOXED_SH_BEST_OF_N_MODELS"),
+            var("SANDBOXED_SH_BEST_OF_N_TEMPERATURES"),
+            var("SANDBOXED_SH_BEST_OF_N_VERIFIER_MODEL"),
+            var("SANDBOXED_SH_BEST_OF_N_MAX_COST_CENTS"),
+            var("SANDBOXED_SH_BEST_OF_N_MIN_CHARS"),
+        )
+    }
+
+    fn from_values(
+        candidates: Option<String>,
+        models: Option<String>,
+        temperatures: Option<String>,
+        verifier_model: Option<String>,
+        max_cost_cents: Option<String>,
+        min_chars: Option<String>,
+    ) -> Option<Self> {
+        let candidates = candidates
+            .and_then(|v| v.trim().parse::<usize>().ok())
+            .filter(|n| *n >= 2)?
+            .min(MAX_CANDIDATES);
+        let mut models: Vec<String> = parse_list(models);
+        if models.is_empty() {
+            models.push(DEFAULT_MODEL.to_string());
+        }
+        let mut temperatures: Vec<f64> = parse_list(temperatures);
+        if temperatures.is_empty() {
+            temperatures = DEFAULT_TEMPERATURES.to_vec();
+        }
+        Some(Self {
+            candidates,
+            verifier_model: verifier_model
+                .map(|v| v.trim().to_string())
+                .unwrap_or_else(|| models[0].clone()),
+            models,
+            temperatures,
+            max_cost_cents: max_cost_cents
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(DEFAULT_MAX_COST_CENTS),
+            min_chars: min_chars
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(DEFAULT_MIN_CHARS),
+        })
+    }
+
+    /// Whether a turn is hard enough to be worth sampling.
+    pub fn applies_to(&self, user_message: &str, is_multi_step: bool) -> bool {
+        is_multi_step || user_message.chars().count() >= self.min_chars
+    }
+
+    /// Model and temperature for candidate `index`.
+    fn candidate_settings(&self, index: usize) -> (&str, f64) {
+        (
+            &self.models[index % self.models.len()],
+            self.temperatures[index % self.temperatures.len()],
+        )
+    }
+}
+
+/// Worst-case cost of one call, or 0 when the model has no known pricing.
+fn estimate_cost_cents(model: &str, prompt_chars: usize, max_tokens: u64) -> u64 {
+    if crate::cost::pricing_for_model(model).is_none() {
+        return 0;
+    }
+    let usage = TokenUsage {
+        input_tokens: (prompt_chars / 4) as u64,
+        output_tokens: max_tokens,
+        cache_creation_input_tokens: None,
+        cache_read_input_tokens: None,
+    };
+    crate::cost::cost_cents_from_usage(model, &usage)
+}
+
+/// How many candidates fit in the budget, leaving room for verification.
+fn affordable_candidates(config: &BestOfNConfig, prompt_chars: usize) -> usize {
+    let verifier_prompt_chars =
+        prompt_chars + config.candidates * CANDIDATE_MAX_TOKENS as usize * 4;
+    let mut spent = estimate_cost_cents(
+        &config.verifier_model,
+        verifier_prompt_chars,
+        VERIFIER_MAX_TOKENS,
+    );
+    let mut count = 0;
+    for index in 0..config.candidates {
+        let (model, _) = config.candidate_settings(index);
+        spent += estimate_cost_cents(model, prompt_chars, CANDIDATE_MAX_TOKENS);
+        if spent > config.max_cost_cents {
+            break;
+        }
+        count += 1;
+    }
+    count
+}
+
+const CANDIDATE_SYSTEM_PROMPT: &str = "You are planning how an autonomous coding agent should \
+tackle a task. Do not carry out the task. Reply with a concise, concrete plan: the approach, \
+the ordered steps, and the main risks to check.";
+
+const VERIFIER_SYSTEM_PROMPT: &str = "You review candidate plans for an autonomous coding \
+agent. Score each candidate from 0 to 10 for correctness, completeness and risk. Reply with \
+JSON only: {\"scores\": [<one number per candidate, in order>], \"best\": <index>, \
+\"rationale\": \"<one sentence>\"}";
+
+fn verifier_prompt(task: &str, candidates: &[(usize, String)]) -> String {
+    let mut prompt = format!("Task:\n{}\n", task);
+    for (position, (_, content)) in candidates.iter().enumerate() {
+        prompt.push_str(&format!("\nCandidate {}:\n{}\n", position, content));
+    }
+    prompt
+}
+
+/// Scores and rationale from a verifier reply.
+#[derive(Debug, Default, PartialEq)]
+struct Verdict {
+    scores: Vec<Option<f64>>,
+    best: Option<usize>,
+    rationale: Option<String>,
+}
+
+fn parse_verdict(reply: &str, count: usize) -> Verdict {
+    // Tolerate prose or code fences around the JSON object.
+    let json = match (reply.find('{'), reply.rfind('}')) {
+        (Some(start), Some(end)) if end > start => &reply[start..=end],
+        _ => return Verdict::default(),
+    };
+    let Ok(value) = serde_json::from_str::<Value>(json) else {
+        return Verdict::default();
+    };
+    let mut scores: Vec<Option<f64>> = value
+        .get("scores")
+        .and_then(|v| v.as_array())
+        .map(|items| items.iter().map(|v| v.as_f64()).collect())
+        .unwrap_or_default();
+    scores.resize(count, None);
+
+    let best_scored = scores
+        .iter()
+        .enumerate()
+        .filter_map(|(i, s)| s.map(|s| (i, s)))
+        .fold(None, |best: Option<(usize, f64)>, (i, s)| match best {
+            Some((_, top)) if top >= s => best,
+            _ => Some((i, s)),
+        })
+        .map(|(i, _)| i);
+    let best = best_scored.or_else(|| {
+        value
+            .get("best")
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize)
+            .filter(|i| *i < count)
+    });
+
+    Verdict {
+        scores,
+        best,
+        rationale: value
+            .get("rationale")
+            .and_then(|v| v.as_str())
+            .map(str::to_string),
+    }
+}
+
+fn usage_event(completion: &Completion, mission_id: Uuid) -> AgentEvent {
+    AgentEvent::LlmUsage {
+        node_id: None,
+        usage: completion.usage.clone(),
+        cost_cents: completion.cost_cents,
+        model: Some(completion.model.clone()),
+        tool_calls: Vec::new(),
+        mission_id: Some(mission_id),
+    }
+}
+
+/// Sample candidate approaches for `task`, score them, and return the best one.
+///
+/// Returns `None` when nothing usable was produced (all candidates failed, the
+/// budget allows fewer than two candidates, or the turn was cancelled); the
+/// turn then proceeds as usual.
+pub async fn select_approach(
+    config: &BestOfNConfig,
+    task: &str,
+    events_tx: &broadcast::Sender<AgentEvent>,
+    cancel: &CancellationToken,
+    mission_id: Uuid,
+) -> Option<String> {
+    let count = affordable_candidates(config, task.len());
+    if count < 2 {
+        tracing::info!(
+            mission_id = %mission_id,
+            max_cost_cents = config.max_cost_cents,
+            "Skipping best-of-N sampling: budget allows fewer than two candidates"
+        );
+        return None;
+    }
+
+    let run_id = Uuid::new_v4();
+    let client = ProxyClient::from_env().for_mission(mission_id);
+    let _ = events_tx.send(AgentEvent::AgentPhase {
+        phase: "sampling".to_string(),
+        detail: Some(format!("Sampling {} candidate approaches", count)),
+        agent: None,
+        mission_id: Some(mission_id),
+    });
+
+    let requests = (0..count).map(|index| {
+        let (model, temperature) = config.candidate_settings(index);
+        let client = &client;
+        async move {
+            let result = client
+                .complete(
+                    model,
+                    temperature,
+                    CANDIDATE_MAX_TOKENS,
+                    CANDIDATE_SYSTEM_PROMPT,
+                    task,
+                )
+                .await;
+            (index, model, temperature, result)
+        }
+    });
+    let results = tokio::select! {
+        _ = cancel.cancelled() => return None,
+        results = futures::future::join_all(requests) => results,
+    };
+
+    let mut total_cost_cents = 0;
+    let mut candidates: Vec<(usize, String)> = Vec::new();
+    for (index, model, temperature, result) in results {
+        let (content, cost_cents, error) = match result {
+            Ok(completion) => {
+                let _ = events_tx.send(usage_event(&completion, mission_id));
+                total_cost_cents += completion.cost_cents;
+                if !completion.content.is_empty() {
+                    candidates.push((index, completion.content.clone()));
+                }
+                (completion.content, completion.cost_cents, None)
+            }
+            Err(e) => {
+                tracing::warn!(mission_id = %mission_id, candidate = index, error = %e, "Best-of-N candidate failed");
+                (String::new(), 0, Some(e))
+            }
+        };
+        let _ = events_tx.send(AgentEvent::SamplingCandidate {
+            run_id,
+            index,
+            model: model.to_string(),
+            temperature,
+            content,
+            cost_cents,
+            error,
+            mission_id: Some(mission_id),
+        });
+    }
+
+    let mut verdict = Verdict::default();
+    let budget_exhausted = total_cost_cents >= config.max_cost_cents;
+    if candidates.len() > 1 && !budget_exhausted {
+        let _ = events_tx.send(AgentEvent::AgentPhase {
+            phase: "verifying".to_string(),
+            detail: Some(format!("Scoring {} candidates", candidates.len())),
+            agent: None,
+            mission_id: Some(mission_id),
+        });
+        let prompt = verifier_prompt(task, &candidates);
+        let reply = tokio::select! {
+            _ = cancel.cancelled() => return None,
+            reply = client.complete(
+                &config.verifier_model,
+                0.0,
+                VERIFIER_MAX_TOKENS,
+                VERIFIER_SYSTEM_PROMPT,
+                &prompt,
+            ) => reply,
+        };
+        match reply {
+            Ok(completion) => {
+                let _ = events_tx.send(usage_event(&completion, mission_id));
+                total_cost_cents += completion.cost_cents;
+                verdict = parse_verdict(&completion.content, candidates.len());
+            }
+            Err(e) => {
+                tracing::warn!(mission_id = %mission_id, error = %e, "Best-of-N verifier failed");
+            }
+        }
+    }
+
+    // Without a usable verdict, fall back to the first successful candidate.
+    let selected = candidates
+        .get(verdict.best.unwrap_or(0))
+        .map(|(index, content)| (*index, content.clone()));
+    let mut scores = vec![None; count];
+    for ((index, _), score) in candidates.iter().zip(&verdict.scores) {
+        scores[*index] = *score;
+    }
+    let _ = events_tx.send(AgentEvent::SamplingResult {
+        run_id,
+        candidates: count,
+        selected: selected.as_ref().map(|(index, _)| *index),
+        scores,
+        rationale: verdict.rationale,
+        total_cost_cents,
+        budget_exhausted,
+        mission_id: Some(mission_id),
+    });
+
+    selected.map(|(_, content)| content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(candidates: &str) -> Option<BestOfNConfig> {
+        BestOfNConfig::from_values(
+            Some(candidates.to_string()),
+            Some("claude-sonnet-4, gpt-5".to_string()),
+            None,
+            None,
+            Some("1".to_string()),
+            None,
+        )
+    }
+
+    #[test]
+    fn config_is_opt_in_and_cycles_settings() {
+        assert!(config("1").is_none());
+        assert!(BestOfNConfig::from_values(None, None, None, None, None, None).is_none());
+
+        let config = config("20").unwrap();
+        assert_eq!(config.candidates, MAX_CANDIDATES);
+        assert_eq!(config.verifier_model, "claude-sonnet-4");
+        assert_eq!(config.candidate_settings(2), ("claude-sonnet-4", 1.0));
+        assert_eq!(config.candidate_settings(3), ("gpt-5", 0.2));
+        assert!(config.applies_to("short", true));
+        assert!(!config.applies_to("short", false));
+    }
+
+    #[test]
+    fn budget_limits_candidates() {
+        let mut config = config("4").unwrap();
+        assert_eq!(affordable_candidates(&config, 1000), 0);
+        config.max_cost_cents = 10_000;
+        assert_eq!(affordable_candidates(&config, 1000), 4);
+
+        // Unpriced models (e.g. proxy chains) are not limited.
+        config.models = vec![DEFAULT_MODEL.to_string()];
+        config.verifier_model = DEFAULT_MODEL.to_string();
+        config.max_cost_cents = 0;
+        assert_eq!(affordable_candidates(&config, 1000), 4);
+    }
+
+    #[test]
+    fn parses_verifier_verdict() {
+        let verdict = parse_verdict(
+            "```json\n{\"scores\": [6, 9.5, \"n/a\"], \"best\": 0, \"rationale\": \"B is safest\"}\n```",
+            3,
+        );
+        assert_eq!(verdict.scores, vec![Some(6.0), Some(9.5), None]);
+        assert_eq!(verdict.best, Some(1));
+        assert_eq!(verdict.rationale.as_deref(), Some("B is safest"));
+
+        let verdict = parse_verdict("{\"best\": 1}", 2);
+        assert_eq!(verdict.best, Some(1));
+        assert_eq!(parse_verdict("no idea", 2), Verdict::default());
+    }
+}
@@ -0,0 +1,128 @@
+//! Request body size limits.
+//!
+//! JSON endpoints, file uploads and the OpenAI-compatible proxy each get their
+//! own maximum body size. Requests whose `Content-Length` exceeds the limit for
+//! their route are rejected with `413 Payload Too Large` before any of the body
+//! is read; bodies without a length are still capped by axum's
+//! `DefaultBodyLimit` while streaming.
+//!
+//! Limits are bytes and can be tuned with `SANDBOXED_SH_MAX_JSON_BODY_BYTES`,
+//! `SANDBOXED_SH_MAX_UPLOAD_BYTES`, `SANDBOXED_SH_MAX_PROXY_BODY_BYTES` and
+//! `SANDBOXED_SH_MAX_EMAIL_BODY_BYTES`.
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{header, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+const DEFAULT_JSON_BYTES: usize = 2 * 1024 * 1024;
+const DEFAULT_UPLOAD_BYTES: usize = 10 * 1024 * 1024 * 1024;
+const DEFAULT_PROXY_BYTES: usize = 50 * 1024 * 1024;
+const DEFAULT_EMAIL_BYTES: usize = 35 * 1024 * 1024;
+
+/// Maximum body sizes per route class.
+#[derive(Debug, Clone, Copy)]
+pub struct BodyLimits {
+    /// Regular API requests (JSON bodies).
+    pub json: usize,
+    /// Multipart file uploads.
+    pub upload: usize,
+    /// OpenAI-compatible proxy (large LLM payloads).
+    pub proxy: usize,
+    /// Inbound emails (attachments arrive base64-encoded).
+    pub email: usize,
+}
+
+impl BodyLimits {
+    pub fn from_env() -> Self {
+        fn limit(var: &str, default: usize) -> usize {
+            std::env::var(var)
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .filter(|v| *v > 0)
+                .unwrap_or(default)
+        }
+        Self {
+            json: limit("SANDBOXED_SH_MAX_JSON_BODY_BYTES", DEFAULT_JSON_BYTES),
+            upload: limit("SANDBOXED_SH_MAX_UPLOAD_BYTES", DEFAULT_UPLOAD_BYTES),
+            proxy: limit("SANDBOXED_SH_MAX_PROXY_BODY_BYTES", DEFAULT_PROXY_BYTES),
+            email: limit("SANDBOXED_SH_MAX_EMAIL_BODY_BYTES", DEFAULT_EMAIL_BYTES),
+        }
+    }
+
+    /// Limit that applies to a request path.
+    pub fn for_path(&self, path: &str) -> usize {
+        if path.starts_with("/v1/") {
+            self.proxy
+        } else if path == "/api/email/inbound" {
+            self.email
+        } else if is_upload_path(path) {
+            self.upload
+        } else {
+            self.json
+        }
+    }
+}
+
+fn is_upload_path(path: &str) -> bool {
+    matches!(path, "/api/fs/upload" | "/api/fs/upload-chunk")
+        || (path.starts_with("/api/control/missions/") && path.ends_with("/upload"))
+        || (path.starts_with("/api/desktop/sessions/") && path.ends_with("/files"))
+}
+
+fn too_large(length: u64, limit: usize) -> Response {
+    (
+        StatusCode::PAYLOAD_TOO_LARGE,
+        format!(
+            "Request body too large: {} bytes exceeds the {} byte limit for this endpoint",
+            length, limit
+        ),
+    )
+        .into_response()
+}
+
+/// Middleware: reject requests whose declared length exceeds the route limit.
+pub async fn reject_oversized(
+    State(limits): State<BodyLimits>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let limit = limits.for_path(req.uri().path());
+    let length = req
+        .headers()
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    if let Some(length) = length {
+        if length > limit as u64 {
+            tracing::debug!(path = %req.uri().path(), length, limit, "Rejected oversized body");
+            return too_large(length, limit);
+        }
+    }
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_limit_by_route() {
+        let limits = BodyLimits {
+            json: 1,
+            upload: 2,
+            proxy: 3,
+            email: 4,
+        };
+        assert_eq!(limits.for_path("/api/control/message"), 1);
+        assert_eq!(limits.for_path("/api/fs/upload"), 2);
+        assert_eq!(limits.for_path("/api/control/missions/abc/upload"), 2);
+        assert_eq!(limits.for_path("/api/control/missions/abc/title"), 1);
+        assert_eq!(limits.for_path("/api/desktop/sessions/:99/files"), 2);
+        assert_eq!(limits.for_path("/v1/chat/completions"), 3);
+        assert_eq!(limits.for_path("/api/email/inbound"), 4);
+    }
+}
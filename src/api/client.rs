@@ -0,0 +1,401 @@
+//! Embeddable mission API.
+//!
+//! [`MissionClient`] drives the mission lifecycle (create a mission, send it
+//! messages, follow its events, cancel it, read its report) through the same
+//! control session actor the HTTP handlers use, so Rust programs can embed
+//! the orchestrator without going through axum:
+//!
+//! ```no_run
+//! # async fn example() -> anyhow::Result<()> {
+//! use sandboxed_sh::api::{MissionClient, MissionOptions};
+//!
+//! let client = MissionClient::start(sandboxed_sh::Config::from_env()?).await?;
+//! let mission = client
+//!     .create_mission(&MissionOptions::default())
+//!     .await
+//!     .map_err(anyhow::Error::msg)?;
+//! let turn = client
+//!     .run_turn(mission.id, "Fix the failing test", None)
+//!     .await
+//!     .map_err(anyhow::Error::msg)?;
+//! println!("success={} cost={}c", turn.success, turn.cost_cents);
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! The client acts as a user of the instance: [`MissionClient::local`] uses
+//! the local instance user (the one command-line modes and the control
+//! socket use), so missions show up in the dashboard like any other.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::stream::Stream;
+use tokio::sync::{broadcast, oneshot};
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::control::{
+    send_user_message, AgentEvent, ControlCommand, ControlMessageResponse, ControlState,
+    ControlStatus, MissionStatus,
+};
+use super::mission_report;
+use super::mission_store::{Mission, MissionReport, MissionStore};
+use super::routes::{init_state, AppState};
+use crate::config::Config;
+
+/// How often the store is checked for the end of a turn once the event
+/// stream dropped events.
+const LAGGED_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How a mission is created.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MissionOptions {
+    pub title: Option<String>,
+    pub workspace_id: Option<Uuid>,
+    pub backend: Option<String>,
+    pub model: Option<String>,
+}
+
+/// How a mission turn ended.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Turn {
+    pub success: bool,
+    pub cost_cents: u64,
+    /// The final assistant message (the error when the turn failed)
+    pub content: String,
+}
+
+/// Handle on one user's control session.
+#[derive(Clone)]
+pub struct MissionClient {
+    state: Arc<AppState>,
+    control: ControlState,
+    user: AuthUser,
+}
+
+impl MissionClient {
+    /// Initialize the application state from `config` (stores, backends,
+    /// workspaces, background tasks; no HTTP server) and connect as the
+    /// local instance user.
+    pub async fn start(config: Config) -> anyhow::Result<Self> {
+        let state = init_state(config).await?;
+        Ok(Self::local(&state).await)
+    }
+
+    /// Connect to `state` as the local instance user.
+    pub async fn local(state: &Arc<AppState>) -> Self {
+        Self::for_user(state, AuthUser::local(&state.config)).await
+    }
+
+    pub(crate) async fn for_user(state: &Arc<AppState>, user: AuthUser) -> Self {
+        let control = state.control.get_or_spawn(&user).await;
+        Self {
+            state: Arc::clone(state),
+            control,
+            user,
+        }
+    }
+
+    pub fn state(&self) -> &Arc<AppState> {
+        &self.state
+    }
+
+    pub fn mission_store(&self) -> &Arc<dyn MissionStore> {
+        &self.control.mission_store
+    }
+
+    pub async fn status(&self) -> ControlStatus {
+        self.control.status.read().await.clone()
+    }
+
+    pub async fn create_mission(&self, options: &MissionOptions) -> Result<Mission, String> {
+        let (tx, rx) = oneshot::channel();
+        self.control
+            .cmd_tx
+            .send(ControlCommand::CreateMission {
+                title: options.title.clone(),
+                workspace_id: options.workspace_id,
+                agent: None,
+                model_override: options.model.clone(),
+                model_effort: None,
+                backend: options.backend.clone(),
+                config_profile: None,
+                respond: tx,
+            })
+            .await
+            .map_err(|_| "Control session unavailable".to_string())?;
+        rx.await
+            .map_err(|_| "Control session dropped the request".to_string())?
+    }
+
+    pub async fn get_mission(&self, mission_id: Uuid) -> Result<Option<Mission>, String> {
+        self.control.mission_store.get_mission(mission_id).await
+    }
+
+    /// Queue a message for the mission; it starts running if it is idle.
+    pub async fn send_message(
+        &self,
+        mission_id: Uuid,
+        content: &str,
+    ) -> Result<ControlMessageResponse, String> {
+        send_user_message(
+            &self.control,
+            &self.user,
+            content.to_string(),
+            None,
+            Some(mission_id),
+        )
+        .await
+        .map(|response| response.0)
+        .map_err(|(_, e)| e)
+    }
+
+    /// All events of the control session, as they are emitted.
+    pub fn subscribe(&self) -> broadcast::Receiver<AgentEvent> {
+        self.control.events_tx.subscribe()
+    }
+
+    /// The mission's events from now on. Events missed because the consumer
+    /// fell behind are skipped.
+    pub fn mission_events(&self, mission_id: Uuid) -> impl Stream<Item = AgentEvent> {
+        let mut events = self.subscribe();
+        async_stream::stream! {
+            loop {
+                match events.recv().await {
+                    Ok(event) if event.mission_id() == Some(mission_id) => yield event,
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        }
+    }
+
+    /// Send `prompt` to the mission and wait for the end of the turn. A turn
+    /// still running after `timeout` is cancelled and reported as an error.
+    pub async fn run_turn(
+        &self,
+        mission_id: Uuid,
+        prompt: &str,
+        timeout: Option<Duration>,
+    ) -> Result<Turn, String> {
+        // Subscribe before sending so the turn's final message can't be missed.
+        let mut events = self.subscribe();
+        let store = self.control.mission_store.as_ref();
+        let after_seq = store
+            .get_recent_events(mission_id, 1)
+            .await?
+            .last()
+            .map_or(0, |event| event.sequence);
+        self.send_message(mission_id, prompt).await?;
+
+        let turn = wait_for_turn(&mut events, store, mission_id, after_seq);
+        let Some(timeout) = timeout else {
+            return turn.await;
+        };
+        match tokio::time::timeout(timeout, turn).await {
+            Ok(turn) => turn,
+            Err(_) => {
+                if let Err(e) = self.cancel(mission_id).await {
+                    tracing::warn!("Failed to cancel timed out mission {}: {}", mission_id, e);
+                }
+                Err(format!("Mission timed out after {}s", timeout.as_secs()))
+            }
+        }
+    }
+
+    pub async fn cancel(&self, mission_id: Uuid) -> Result<(), String> {
+        let (tx, rx) = oneshot::channel();
+        self.control
+            .cmd_tx
+            .send(ControlCommand::CancelMission {
+                mission_id,
+                respond: tx,
+            })
+            .await
+            .map_err(|_| "Control session unavailable".to_string())?;
+        rx.await
+            .map_err(|_| "Control session dropped the request".to_string())?
+    }
+
+    pub async fn set_status(&self, mission_id: Uuid, status: MissionStatus) -> Result<(), String> {
+        let (tx, rx) = oneshot::channel();
+        self.control
+            .cmd_tx
+            .send(ControlCommand::SetMissionStatus {
+                id: mission_id,
+                status,
+                respond: tx,
+            })
+            .await
+            .map_err(|_| "Control session unavailable".to_string())?;
+        rx.await
+            .map_err(|_| "Control session dropped the request".to_string())?
+    }
+
+    /// Structured report of the mission's events so far.
+    pub async fn report(&self, mission_id: Uuid) -> Result<MissionReport, String> {
+        let status = self
+            .get_mission(mission_id)
+            .await?
+            .ok_or_else(|| format!("Mission {} not found", mission_id))?
+            .status;
+        mission_report::build_report(&self.control.mission_store, mission_id, status).await
+    }
+}
+
+/// Wait for the assistant message that ends the mission's turn. Once the
+/// event stream dropped events (the message may be among them), the store's
+/// event log after `after_seq` is checked for it as well.
+async fn wait_for_turn(
+    events: &mut broadcast::Receiver<AgentEvent>,
+    store: &dyn MissionStore,
+    mission_id: Uuid,
+    after_seq: i64,
+) -> Result<Turn, String> {
+    let mut lagged = false;
+    let mut poll = tokio::time::interval(LAGGED_POLL_INTERVAL);
+    loop {
+        let event = tokio::select! {
+            event = events.recv() => event,
+            _ = poll.tick(), if lagged => {
+                if let Some(turn) = stored_turn(store, mission_id, after_seq).await? {
+                    return Ok(turn);
+                }
+                continue;
+            }
+        };
+        match event {
+            Ok(AgentEvent::AssistantMessage {
+                success,
+                cost_cents,
+                content,
+                mission_id: Some(id),
+                ..
+            }) if id == mission_id => {
+                return Ok(Turn {
+                    success,
+                    cost_cents,
+                    content,
+                })
+            }
+            Ok(_) => {}
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::debug!(%mission_id, skipped, "Turn waiter lagged; checking the store");
+                if let Some(turn) = stored_turn(store, mission_id, after_seq).await? {
+                    return Ok(turn);
+                }
+                lagged = true;
+            }
+            Err(broadcast::error::RecvError::Closed) => {
+                return Err("Control session closed".to_string())
+            }
+        }
+    }
+}
+
+/// The turn ended by a stored assistant message logged after `after_seq`.
+async fn stored_turn(
+    store: &dyn MissionStore,
+    mission_id: Uuid,
+    after_seq: i64,
+) -> Result<Option<Turn>, String> {
+    let events = store
+        .get_events_in_range(mission_id, Some(after_seq + 1), None)
+        .await?;
+    Ok(events
+        .into_iter()
+        .find(|event| event.event_type == "assistant_message")
+        .map(|event| Turn {
+            success: event
+                .metadata
+                .get("success")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            cost_cents: event
+                .metadata
+                .get("cost_cents")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0),
+            content: event.content,
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(mission_id: Uuid, content: &str) -> AgentEvent {
+        AgentEvent::AssistantMessage {
+            id: Uuid::new_v4(),
+            content: content.to_string(),
+            success: true,
+            cost_cents: 7,
+            cost_source: crate::agents::CostSource::Actual,
+            usage: None,
+            model: None,
+            model_normalized: None,
+            mission_id: Some(mission_id),
+            shared_files: None,
+            resumable: false,
+            cancelled: false,
+            tool_calls: Vec::new(),
+        }
+    }
+
+    #[tokio::test]
+    async fn wait_for_turn_ignores_other_missions() {
+        let store = super::super::mission_store::InMemoryMissionStore::new();
+        let (tx, mut rx) = broadcast::channel(16);
+        let mission_id = Uuid::new_v4();
+        tx.send(message(Uuid::new_v4(), "other")).unwrap();
+        tx.send(message(mission_id, "done")).unwrap();
+
+        let turn = wait_for_turn(&mut rx, &store, mission_id, 0).await.unwrap();
+        assert_eq!(turn.content, "done");
+        assert_eq!(turn.cost_cents, 7);
+
+        drop(tx);
+        assert!(wait_for_turn(&mut rx, &store, mission_id, 0).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn wait_for_turn_finds_a_missed_message_in_the_store() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = super::super::mission_store::SqliteMissionStore::new(
+            temp_dir.path().to_path_buf(),
+            "test-user",
+        )
+        .await
+        .unwrap();
+        let mission = store
+            .create_mission(None, None, None, None, None, None, None)
+            .await
+            .unwrap();
+        store
+            .log_event(mission.id, &message(mission.id, "previous turn"))
+            .await
+            .unwrap();
+        let after_seq = store.get_recent_events(mission.id, 1).await.unwrap()[0].sequence;
+
+        // The turn's final message is stored but pushed out of the channel.
+        let (tx, mut rx) = broadcast::channel(1);
+        tx.send(message(mission.id, "done")).unwrap();
+        tx.send(message(Uuid::new_v4(), "other")).unwrap();
+        store
+            .log_event(mission.id, &message(mission.id, "done"))
+            .await
+            .unwrap();
+
+        let turn = tokio::time::timeout(
+            Duration::from_secs(5),
+            wait_for_turn(&mut rx, &store, mission.id, after_seq),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(turn.content, "done");
+        assert!(turn.success);
+        assert_eq!(turn.cost_cents, 7);
+    }
+}
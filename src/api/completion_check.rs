@@ -0,0 +1,444 @@
+//! Self-verification before a mission is marked complete.
+//!
+//! When enabled, a turn that ends with the agent claiming completion is
+//! checked by a separate verifier call before the mission may transition to
+//! `Completed`. The verifier derives a checklist from the original request,
+//! compares it with the agent's final answer and (optionally) the output of a
+//! test command run in the mission workspace. If anything is missing, the gaps
+//! are fed back to the agent as a follow-up turn; after the retry budget is
+//! spent the turn fails with `TerminalReason::VerificationFailed`.
+//!
+//! Configuration:
+//!
+//! - `SANDBOXED_SH_VERIFY_COMPLETION` - enable the verification pass
+//! - `SANDBOXED_SH_VERIFY_MODEL` - verifier model (default `builtin/smart`)
+//! - `SANDBOXED_SH_VERIFY_COMMAND` - optional shell command (e.g. `cargo test`)
+//!   run in the mission directory before verifying
+//! - `SANDBOXED_SH_VERIFY_MAX_RETRIES` - follow-up turns allowed after a failed
+//!   verification (default 2)
+//!
+//! If the verifier itself is unavailable the claim is accepted, so an outage
+//! of the verifier model never blocks missions from completing.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use super::control::AgentEvent;
+use super::llm_client::ProxyClient;
+use crate::agents::{AgentResult, TerminalReason};
+use crate::workspace::Workspace;
+use crate::workspace_exec::WorkspaceExec;
+
+const DEFAULT_MODEL: &str = "builtin/smart";
+const DEFAULT_MAX_RETRIES: u32 = 2;
+const VERIFIER_MAX_TOKENS: u64 = 1200;
+/// Characters of the agent's answer and test output shown to the verifier.
+const MAX_EXCERPT_CHARS: usize = 12_000;
+
+const VERIFIER_SYSTEM_PROMPT: &str = "You verify whether an autonomous coding agent really \
+finished a task. Derive a checklist of concrete requirements from the original request, then \
+check each item against the agent's final answer and the test output (if any). Only mark an \
+item done when there is evidence for it. Reply with JSON only: {\"checklist\": [{\"item\": \
+\"...\", \"done\": true|false, \"note\": \"...\"}], \"passed\": true|false, \"gaps\": [\"what is \
+still missing\"]}";
+
+/// Verification settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VerifyConfig {
+    pub model: String,
+    pub command: Option<String>,
+    pub max_retries: u32,
+}
+
+impl VerifyConfig {
+    /// Read the configuration; `None` when verification is disabled.
+    pub fn from_env() -> Option<Self> {
+        let enabled = std::env::var("SANDBOXED_SH_VERIFY_COMPLETION")
+            .map(|v| {
+                matches!(
+                    v.trim().to_lowercase().as_str(),
+                    "1" | "true" | "yes" | "on"
+                )
+            })
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        Some(Self {
+            model: var("SANDBOXED_SH_VERIFY_MODEL").unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+            command: var("SANDBOXED_SH_VERIFY_COMMAND"),
+            max_retries: var("SANDBOXED_SH_VERIFY_MAX_RETRIES")
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(DEFAULT_MAX_RETRIES),
+        })
+    }
+
+    pub fn enabled() -> bool {
+        Self::from_env().is_some()
+    }
+}
+
+/// One requirement from the verifier's checklist.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub item: String,
+    #[serde(default)]
+    pub done: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+/// Result of the optional test command.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestRun {
+    pub command: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// Tail of combined stdout/stderr
+    pub output: String,
+}
+
+impl TestRun {
+    fn passed(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+}
+
+/// The verifier's judgement of a completion claim.
+#[derive(Debug, Clone, PartialEq)]
+struct Verdict {
+    passed: bool,
+    checklist: Vec<ChecklistItem>,
+    gaps: Vec<String>,
+}
+
+fn excerpt(text: &str) -> String {
+    let count = text.chars().count();
+    if count <= MAX_EXCERPT_CHARS {
+        return text.to_string();
+    }
+    let tail: String = text.chars().skip(count - MAX_EXCERPT_CHARS).collect();
+    format!("[...truncated...]\n{}", tail)
+}
+
+fn parse_verdict(reply: &str) -> Option<Verdict> {
+    let start = reply.find('{')?;
+    let end = reply.rfind('}')?;
+    let value: Value = serde_json::from_str(reply.get(start..=end)?).ok()?;
+    let checklist: Vec<ChecklistItem> = value
+        .get("checklist")
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default();
+    let mut gaps: Vec<String> = value
+        .get("gaps")
+        .and_then(|v| v.as_array())
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    // Unchecked items count as gaps even if the verifier forgot to list them.
+    for item in checklist.iter().filter(|item| !item.done) {
+        if !gaps.iter().any(|gap| gap == &item.item) {
+            gaps.push(item.item.clone());
+        }
+    }
+    let passed = value
+        .get("passed")
+        .and_then(|v| v.as_bool())
+        .unwrap_or(gaps.is_empty())
+        && gaps.is_empty();
+    Some(Verdict {
+        passed,
+        checklist,
+        gaps,
+    })
+}
+
+fn verifier_prompt(request: &str, answer: &str, test: Option<&TestRun>) -> String {
+    let mut prompt = format!(
+        "Original request:\n{}\n\nAgent's final answer:\n{}\n",
+        request,
+        excerpt(answer)
+    );
+    if let Some(test) = test {
+        prompt.push_str(&format!(
+            "\nTest command `{}` exited with {}:\n{}\n",
+            test.command,
+            test.exit_code
+                .map(|c| c.to_string())
+                .unwrap_or_else(|| "no exit code".to_string()),
+            test.output
+        ));
+    }
+    prompt
+}
+
+fn feedback_message(gaps: &[String], test: Option<&TestRun>, attempt: u32) -> String {
+    let mut message = format!(
+        "Verification of your completion claim failed (attempt {}). The following is still \
+         missing or unproven:\n",
+        attempt
+    );
+    for gap in gaps {
+        message.push_str(&format!("- {}\n", gap));
+    }
+    if let Some(test) = test.filter(|t| !t.passed()) {
+        message.push_str(&format!(
+            "\nThe test command `{}` failed:\n{}\n",
+            test.command, test.output
+        ));
+    }
+    message.push_str("\nAddress these gaps, then state clearly when the task is complete.");
+    message
+}
+
+async fn run_tests(workspace: &Workspace, work_dir: &Path, command: &str) -> TestRun {
+    let exec = WorkspaceExec::new(workspace.clone());
+    let args = vec!["-lc".to_string(), command.to_string()];
+    match exec.output(work_dir, "bash", &args, HashMap::new()).await {
+        Ok(output) => {
+            let mut combined = String::from_utf8_lossy(&output.stdout).to_string();
+            combined.push_str(&String::from_utf8_lossy(&output.stderr));
+            TestRun {
+                command: command.to_string(),
+                exit_code: output.status.code(),
+                output: excerpt(&combined),
+            }
+        }
+        Err(e) => TestRun {
+            command: command.to_string(),
+            exit_code: None,
+            output: format!("Failed to run test command: {}", e),
+        },
+    }
+}
+
+/// Everything the verification loop needs besides the turn itself.
+pub struct VerifyContext<'a> {
+    pub config: &'a VerifyConfig,
+    pub mission_id: Uuid,
+    pub events_tx: &'a broadcast::Sender<AgentEvent>,
+    pub cancel: &'a CancellationToken,
+    /// Workspace and mission directory for the test command
+    pub workspace: Option<(Workspace, std::path::PathBuf)>,
+}
+
+/// Workspace and mission directory to run the test command in, if one is set.
+pub async fn test_workspace(
+    config: &VerifyConfig,
+    workspaces: &crate::workspace::SharedWorkspaceStore,
+    app_config: &crate::config::Config,
+    workspace_id: Option<Uuid>,
+    mission_id: Uuid,
+) -> Option<(Workspace, std::path::PathBuf)> {
+    config.command.as_ref()?;
+    let workspace = crate::workspace::resolve_workspace(workspaces, app_config, workspace_id).await;
+    let work_dir = crate::workspace::mission_workspace_dir_for_root(&workspace.path, mission_id);
+    Some((workspace, work_dir))
+}
+
+/// The original request for a turn: the mission's first user message, plus
+/// the current message when it is a later instruction.
+pub fn original_request(history: &[(String, String)], user_message: &str) -> String {
+    match history.iter().find(|(role, _)| role == "user") {
+        Some((_, first)) if first != user_message => {
+            format!("{}\n\nLatest instruction:\n{}", first, user_message)
+        }
+        _ => user_message.to_string(),
+    }
+}
+
+/// Verify a turn that claims completion, running follow-up turns with the
+/// verifier's feedback until it passes or the retry budget is exhausted.
+///
+/// `followup(message, history)` runs one more turn of the same mission.
+pub async fn verify_completion<F, Fut>(
+    ctx: VerifyContext<'_>,
+    request: &str,
+    mut history: Vec<(String, String)>,
+    user_message: &str,
+    mut result: AgentResult,
+    mut followup: F,
+) -> AgentResult
+where
+    F: FnMut(String, Vec<(String, String)>) -> Fut,
+    Fut: Future<Output = AgentResult>,
+{
+    if history.last().map(|(_, content)| content.as_str()) != Some(user_message) {
+        history.push(("user".to_string(), user_message.to_string()));
+    }
+    let max_attempts = ctx.config.max_retries + 1;
+    let mut spent_cents = 0;
+
+    for attempt in 1..=max_attempts {
+        let claims_completion =
+            result.success && result.terminal_reason == Some(TerminalReason::Completed);
+        if !claims_completion || ctx.cancel.is_cancelled() {
+            break;
+        }
+
+        let _ = ctx.events_tx.send(AgentEvent::AgentPhase {
+            phase: "verifying_completion".to_string(),
+            detail: Some(format!("Checking completion (attempt {})", attempt)),
+            agent: None,
+            mission_id: Some(ctx.mission_id),
+        });
+        let test = match (&ctx.config.command, &ctx.workspace) {
+            (Some(command), Some((workspace, work_dir))) => {
+                Some(run_tests(workspace, work_dir, command).await)
+            }
+            _ => None,
+        };
+        let reply = ProxyClient::from_env()
+            .for_mission(ctx.mission_id)
+            .complete(
+                &ctx.config.model,
+                0.0,
+                VERIFIER_MAX_TOKENS,
+                VERIFIER_SYSTEM_PROMPT,
+                &verifier_prompt(request, &result.output, test.as_ref()),
+            )
+            .await;
+        let verdict = match reply {
+            Ok(completion) => {
+                let _ = ctx.events_tx.send(AgentEvent::LlmUsage {
+                    node_id: None,
+                    usage: completion.usage.clone(),
+                    cost_cents: completion.cost_cents,
+                    model: Some(completion.model.clone()),
+                    tool_calls: Vec::new(),
+                    mission_id: Some(ctx.mission_id),
+                });
+                spent_cents += completion.cost_cents;
+                parse_verdict(&completion.content)
+            }
+            Err(e) => {
+                tracing::warn!(mission_id = %ctx.mission_id, error = %e, "Completion verifier unavailable");
+                None
+            }
+        };
+        let Some(mut verdict) = verdict else {
+            tracing::warn!(
+                mission_id = %ctx.mission_id,
+                "No usable verification verdict; accepting completion claim"
+            );
+            break;
+        };
+        if let Some(test) = test.as_ref().filter(|t| !t.passed()) {
+            verdict.passed = false;
+            verdict
+                .gaps
+                .push(format!("Test command `{}` did not pass", test.command));
+        }
+
+        let _ = ctx.events_tx.send(AgentEvent::CompletionVerification {
+            attempt,
+            max_attempts,
+            passed: verdict.passed,
+            checklist: verdict.checklist.clone(),
+            gaps: verdict.gaps.clone(),
+            test: test.clone(),
+            mission_id: Some(ctx.mission_id),
+        });
+        if verdict.passed {
+            break;
+        }
+        if attempt == max_attempts {
+            let gaps = verdict.gaps.join("; ");
+            result.success = false;
+            result.terminal_reason = Some(TerminalReason::VerificationFailed);
+            result.output = format!(
+                "{}\n\nVerification failed after {} attempts. Remaining gaps: {}",
+                result.output, max_attempts, gaps
+            );
+            break;
+        }
+
+        let message = feedback_message(&verdict.gaps, test.as_ref(), attempt);
+        history.push(("assistant".to_string(), result.output.clone()));
+        let next = followup(message.clone(), history.clone()).await;
+        history.push(("user".to_string(), message));
+        spent_cents += result.cost_cents;
+        result = next;
+    }
+
+    result.cost_cents += spent_cents;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_verdict_and_collects_gaps() {
+        let verdict = parse_verdict(
+            "Result:\n{\"checklist\": [{\"item\": \"Add endpoint\", \"done\": true}, \
+             {\"item\": \"Write tests\", \"done\": false, \"note\": \"none found\"}], \
+             \"passed\": true, \"gaps\": []}",
+        )
+        .unwrap();
+        assert!(!verdict.passed);
+        assert_eq!(verdict.gaps, vec!["Write tests".to_string()]);
+        assert_eq!(verdict.checklist[1].note.as_deref(), Some("none found"));
+
+        let verdict = parse_verdict("{\"passed\": true}").unwrap();
+        assert!(verdict.passed);
+        assert!(parse_verdict("looks good").is_none());
+    }
+
+    #[test]
+    fn original_request_uses_first_user_message() {
+        let history = vec![
+            ("user".to_string(), "Build the CLI".to_string()),
+            ("assistant".to_string(), "Done".to_string()),
+        ];
+        assert_eq!(
+            original_request(&history, "Also add docs"),
+            "Build the CLI\n\nLatest instruction:\nAlso add docs"
+        );
+        assert_eq!(original_request(&[], "Build the CLI"), "Build the CLI");
+    }
+
+    #[tokio::test]
+    async fn skips_turns_that_do_not_claim_completion() {
+        let config = VerifyConfig {
+            model: DEFAULT_MODEL.to_string(),
+            command: None,
+            max_retries: 1,
+        };
+        let (events_tx, _rx) = broadcast::channel(4);
+        let cancel = CancellationToken::new();
+        let ctx = VerifyContext {
+            config: &config,
+            mission_id: Uuid::new_v4(),
+            events_tx: &events_tx,
+            cancel: &cancel,
+            workspace: None,
+        };
+        let result = AgentResult::failure("boom", 3);
+        let result = verify_completion(ctx, "task", Vec::new(), "task", result, |_, _| async {
+            panic!("no follow-up expected")
+        })
+        .await;
+        assert!(!result.success);
+        assert_eq!(result.cost_cents, 3);
+    }
+}
@@ -152,8 +152,12 @@ pub async fn console_ws(
             Some(t) => t,
             None => return (StatusCode::UNAUTHORIZED, "Missing websocket JWT").into_response(),
         };
-        if !auth::verify_token_for_config(&token, &state.config) {
+        let Some(user) = auth::user_for_token(&token, &state.config) else {
             return (StatusCode::UNAUTHORIZED, "Invalid or expired token").into_response();
+        };
+        // The host shell is outside tenant isolation
+        if user.tenant.is_some() {
+            return (StatusCode::FORBIDDEN, "Not available to tenant users").into_response();
         }
         // Use token hash as session key for authenticated users
         format!("auth:{:x}", md5::compute(&token))
@@ -507,17 +511,20 @@ pub async fn workspace_shell_ws(
     headers: HeaderMap,
 ) -> impl IntoResponse {
     // Enforce auth in non-dev mode
-    let session_key = if state.config.auth.auth_required(state.config.dev_mode) {
+    let (session_key, tenant) = if state.config.auth.auth_required(state.config.dev_mode) {
         let token = match extract_jwt_from_protocols(&headers) {
             Some(t) => t,
             None => return (StatusCode::UNAUTHORIZED, "Missing websocket JWT").into_response(),
         };
-        if !auth::verify_token_for_config(&token, &state.config) {
+        let Some(user) = auth::user_for_token(&token, &state.config) else {
             return (StatusCode::UNAUTHORIZED, "Invalid or expired token").into_response();
-        }
-        format!("workspace:{}:{:x}", workspace_id, md5::compute(&token))
+        };
+        (
+            format!("workspace:{}:{:x}", workspace_id, md5::compute(&token)),
+            user.tenant,
+        )
     } else {
-        format!("workspace:{}:dev", workspace_id)
+        (format!("workspace:{}:dev", workspace_id), None)
     };
 
     tracing::info!(
@@ -525,16 +532,16 @@ pub async fn workspace_shell_ws(
         workspace_id = %workspace_id,
         "Workspace shell websocket upgrade requested"
     );
-    // Verify workspace exists
-    let workspace = match state.workspaces.get(workspace_id).await {
-        Some(ws) => ws,
-        None => {
-            return (
-                StatusCode::NOT_FOUND,
-                format!("Workspace {} not found", workspace_id),
-            )
-                .into_response()
-        }
+    // Verify workspace exists and belongs to the caller's tenant
+    let workspace = match super::workspaces::require_workspace(
+        &state.workspaces,
+        workspace_id,
+        tenant.as_deref(),
+    )
+    .await
+    {
+        Ok(ws) => ws,
+        Err(e) => return e.into_response(),
     };
 
     // For container workspaces, verify it's ready
@@ -0,0 +1,184 @@
+//! Context window usage after each LLM call.
+//!
+//! While a turn runs, a reporter watches the mission's `LlmUsage` events from
+//! the root agent and follows each with a `ContextUsage` event: the prompt and
+//! completion tokens of the call, how much of the model's context window they
+//! fill, and how much history and how many context files the turn started
+//! with. Clients can warn before the model limit is hit instead of after.
+
+use std::path::Path;
+
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use super::control::AgentEvent;
+use crate::config::Config;
+use crate::cost::{context_window_for_model, TokenUsage};
+
+/// Emits `ContextUsage` events for one turn; stops when dropped.
+pub struct ContextUsageReporter {
+    handle: JoinHandle<()>,
+}
+
+impl Drop for ContextUsageReporter {
+    fn drop(&mut self) {
+        self.handle.abort();
+    }
+}
+
+impl ContextUsageReporter {
+    /// Start reporting for `mission_id`. `history_entries` counts the mission
+    /// history preceding the current message and `files_included` the files
+    /// in the mission's context directory.
+    pub fn spawn(
+        events_tx: &broadcast::Sender<AgentEvent>,
+        mission_id: Uuid,
+        history_entries: usize,
+        files_included: usize,
+    ) -> Self {
+        let mut rx = events_tx.subscribe();
+        let events_tx = events_tx.clone();
+        let handle = tokio::spawn(async move {
+            loop {
+                match rx.recv().await {
+                    Ok(AgentEvent::LlmUsage {
+                        node_id: None,
+                        usage,
+                        model,
+                        mission_id: Some(mid),
+                        ..
+                    }) if mid == mission_id => {
+                        let _ = events_tx.send(context_usage_event(
+                            mission_id,
+                            &usage,
+                            model,
+                            history_entries,
+                            files_included,
+                        ));
+                    }
+                    Ok(_) | Err(broadcast::error::RecvError::Lagged(_)) => {}
+                    Err(broadcast::error::RecvError::Closed) => break,
+                }
+            }
+        });
+        Self { handle }
+    }
+
+    /// Start reporting for a turn answering `user_message` with `history`.
+    pub fn for_turn(
+        events_tx: &broadcast::Sender<AgentEvent>,
+        config: &Config,
+        mission_id: Uuid,
+        history: &[(String, String)],
+        user_message: &str,
+    ) -> Self {
+        let history_entries = match history.last() {
+            Some((role, content)) if role == "user" && content == user_message => history.len() - 1,
+            _ => history.len(),
+        };
+        let context_dir = config
+            .working_dir
+            .join(&config.context.context_dir_name)
+            .join(mission_id.to_string());
+        Self::spawn(
+            events_tx,
+            mission_id,
+            history_entries,
+            count_context_files(&context_dir),
+        )
+    }
+}
+
+fn context_usage_event(
+    mission_id: Uuid,
+    usage: &TokenUsage,
+    model: Option<String>,
+    history_entries: usize,
+    files_included: usize,
+) -> AgentEvent {
+    // Cached input is still part of the prompt the model sees.
+    let prompt_tokens = usage.input_tokens
+        + usage.cache_creation_input_tokens.unwrap_or(0)
+        + usage.cache_read_input_tokens.unwrap_or(0);
+    let completion_tokens = usage.output_tokens;
+    let context_window = model.as_deref().and_then(context_window_for_model);
+    let utilization_pct = context_window.map(|window| {
+        let used = (prompt_tokens + completion_tokens) as f64 / window as f64 * 100.0;
+        (used * 10.0).round() / 10.0
+    });
+    AgentEvent::ContextUsage {
+        prompt_tokens,
+        completion_tokens,
+        context_window,
+        utilization_pct,
+        history_entries,
+        files_included,
+        model,
+        mission_id: Some(mission_id),
+    }
+}
+
+/// Number of files in a mission's context directory (user uploads).
+pub fn count_context_files(dir: &Path) -> usize {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.file_type() {
+            Ok(t) if t.is_dir() => count_context_files(&entry.path()),
+            Ok(_) => 1,
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn utilization_includes_cached_prompt_tokens() {
+        let usage = TokenUsage {
+            input_tokens: 1_000,
+            output_tokens: 2_000,
+            cache_creation_input_tokens: Some(7_000),
+            cache_read_input_tokens: Some(90_000),
+        };
+        let event = context_usage_event(
+            Uuid::new_v4(),
+            &usage,
+            Some("claude-sonnet-4-20250514".to_string()),
+            6,
+            2,
+        );
+        let AgentEvent::ContextUsage {
+            prompt_tokens,
+            completion_tokens,
+            context_window,
+            utilization_pct,
+            history_entries,
+            files_included,
+            ..
+        } = event
+        else {
+            panic!("expected ContextUsage");
+        };
+        assert_eq!(prompt_tokens, 98_000);
+        assert_eq!(completion_tokens, 2_000);
+        assert_eq!(context_window, Some(200_000));
+        assert_eq!(utilization_pct, Some(50.0));
+        assert_eq!((history_entries, files_included), (6, 2));
+
+        let unknown = context_usage_event(Uuid::new_v4(), &usage, None, 0, 0);
+        assert!(matches!(
+            unknown,
+            AgentEvent::ContextUsage {
+                context_window: None,
+                utilization_pct: None,
+                ..
+            }
+        ));
+    }
+}
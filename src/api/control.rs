@@ -10,23 +10,24 @@
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::convert::Infallible;
 use std::hash::{Hash, Hasher};
-use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
 use axum::{
     body::Bytes,
     extract::{Extension, Path, Query, State},
     http::{HeaderMap, StatusCode},
-    response::sse::{Event, Sse},
+    response::{
+        sse::{Event, Sse},
+        IntoResponse,
+    },
     Json,
 };
-use futures::stream::Stream;
 use serde::{Deserialize, Serialize};
 use tokio::sync::{broadcast, mpsc, oneshot, Mutex, RwLock};
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
-use crate::agents::{AgentContext, AgentRef, TerminalReason};
+use crate::agents::{AgentContext, AgentRef, ErrorCode, TerminalReason};
 use crate::config::Config;
 use crate::mcp::McpRegistry;
 use crate::secrets::SecretsStore;
@@ -34,13 +35,35 @@ use crate::util::{build_history_context, internal_error};
 use crate::workspace;
 
 use super::auth::AuthUser;
+use super::automation_throttle;
+use super::command_approval;
+use super::cost_breakdown::{self, CostTracker};
 use super::desktop;
+use super::event_bus::{self, EventLog, LogItem};
+use super::event_schema;
+use super::file_conflicts::{ConflictPolicy, FileConflictTracker};
+use super::health;
+use super::idempotency::{IdempotencyCache, IdempotencyClaim};
 use super::library::SharedLibrary;
+use super::metadata_refresh;
+use super::mission_checklist;
+use super::mission_state::{
+    needs_activation, route_message, MainSessionStep, MessageRoute, MissionRunEvent,
+    MissionRunState, RoutingSnapshot,
+};
 use super::mission_store::{
-    self, create_mission_store, now_string, Mission, MissionHistoryEntry, MissionStore,
-    MissionStoreType, StoredEvent,
+    self, conversation_history, create_mission_store, normalize_tags, now_string, Keyset, Mission,
+    MissionFilter, MissionHistoryEntry, MissionStore, MissionStoreType, StoredEvent, StreamEvent,
 };
+use super::mission_stream;
+use super::pagination::{page_headers, paginate, split_page, Cursor, Page, PageQuery};
+use super::partial_turn::{PartialToolCall, PartialTurn, PartialTurns};
+use super::presence;
 use super::routes::AppState;
+use super::stall_watch::{self, StallAction, StallPolicy, StallWatcher};
+use super::status_requests;
+use super::thinking_log::{ThinkingCollector, ThinkingPersistence};
+use super::turn_journal;
 
 /// Returns a safe index to truncate a string at, ensuring we don't cut UTF-8 characters.
 pub(super) fn safe_truncate_index(s: &str, max: usize) -> usize {
@@ -56,7 +79,20 @@ pub(super) fn safe_truncate_index(s: &str, max: usize) -> usize {
 }
 
 /// Derive a human-readable activity label from a tool call.
-fn activity_label_from_tool_call(tool_name: &str, args: &serde_json::Value) -> String {
+pub(crate) fn activity_label_from_tool_call(tool_name: &str, args: &serde_json::Value) -> String {
+    localized_activity_label(
+        tool_name,
+        args,
+        crate::i18n::catalog(crate::i18n::DEFAULT_LOCALE),
+    )
+}
+
+/// [`activity_label_from_tool_call`] in the language of `catalog`.
+pub(crate) fn localized_activity_label(
+    tool_name: &str,
+    args: &serde_json::Value,
+    catalog: &crate::i18n::Catalog,
+) -> String {
     fn extract_str<'a>(args: &'a serde_json::Value, keys: &[&str]) -> Option<&'a str> {
         for key in keys {
             if let Some(v) = args.get(*key).and_then(|v| v.as_str()) {
@@ -82,63 +118,91 @@ fn activity_label_from_tool_call(tool_name: &str, args: &serde_json::Value) -> S
         }
     }
 
+    let path_label = |key: &str, path_keys: &[&str]| {
+        let path = extract_str(args, path_keys).unwrap_or("…");
+        catalog.text(key, &[("file", basename(path))])
+    };
+
     match tool_name {
         "Bash" | "bash" => {
             let cmd = extract_str(args, &["command"]).unwrap_or("…");
             let first_line = cmd.lines().next().unwrap_or(cmd);
-            format!("Running: {}", truncate(first_line, 60))
-        }
-        "Read" | "read_file" => {
-            let path = extract_str(args, &["file_path", "path"]).unwrap_or("…");
-            format!("Reading: {}", basename(path))
-        }
-        "Edit" | "edit_file" => {
-            let path = extract_str(args, &["file_path", "path"]).unwrap_or("…");
-            format!("Editing: {}", basename(path))
-        }
-        "Write" | "write_file" => {
-            let path = extract_str(args, &["file_path", "path"]).unwrap_or("…");
-            format!("Writing: {}", basename(path))
+            catalog.text(
+                "activity.running",
+                &[("command", &truncate(first_line, 60))],
+            )
         }
+        "Read" | "read_file" => path_label("activity.reading", &["file_path", "path"]),
+        "Edit" | "edit_file" => path_label("activity.editing", &["file_path", "path"]),
+        "Write" | "write_file" => path_label("activity.writing", &["file_path", "path"]),
         "Grep" | "grep" | "search" => {
             let pattern = extract_str(args, &["pattern"]).unwrap_or("…");
-            format!("Searching: {}", truncate(pattern, 40))
+            catalog.text("activity.searching", &[("pattern", &truncate(pattern, 40))])
         }
         "Glob" | "glob" => {
             let pattern = extract_str(args, &["pattern"]).unwrap_or("…");
-            format!("Finding: {}", truncate(pattern, 50))
+            catalog.text("activity.finding", &[("pattern", &truncate(pattern, 50))])
         }
         "WebSearch" | "web_search" => {
             let query = extract_str(args, &["query"]).unwrap_or("…");
-            format!("Searching web: {}", truncate(query, 40))
+            catalog.text("activity.searching_web", &[("query", &truncate(query, 40))])
         }
-        "WebFetch" | "web_fetch" => "Fetching web page".to_string(),
+        "WebFetch" | "web_fetch" => catalog.text("activity.fetching_web", &[]),
         "Task" | "delegate_task" => {
             let desc = extract_str(args, &["description", "prompt", "subject"]).unwrap_or("…");
-            format!("Subtask: {}", truncate(desc, 80))
+            catalog.text("activity.subtask", &[("description", &truncate(desc, 80))])
         }
         "TaskCreate" => {
             let desc = extract_str(args, &["subject", "description"]).unwrap_or("…");
-            format!("Creating task: {}", truncate(desc, 80))
+            catalog.text(
+                "activity.creating_task",
+                &[("description", &truncate(desc, 80))],
+            )
         }
         "Skill" => {
             let skill = extract_str(args, &["skill"]).unwrap_or("…");
-            format!("Running skill: {}", skill)
+            catalog.text("activity.running_skill", &[("skill", skill)])
+        }
+        "AskUserQuestion" => catalog.text("activity.waiting_for_input", &[]),
+        "NotebookEdit" => path_label("activity.editing_notebook", &["notebook_path"]),
+        "docker" => {
+            let action = extract_str(args, &["action"]).unwrap_or("run");
+            let target = extract_str(args, &["image", "tag", "container", "file"]).unwrap_or("…");
+            catalog.text(
+                "activity.docker",
+                &[("action", action), ("target", &truncate(target, 80))],
+            )
+        }
+        "ssh_exec" => {
+            let host = extract_str(args, &["host"]).unwrap_or("…");
+            let command = extract_str(args, &["command"]).unwrap_or("…");
+            catalog.text(
+                "activity.ssh",
+                &[("host", host), ("command", &truncate(command, 80))],
+            )
         }
-        "AskUserQuestion" => "Waiting for input".to_string(),
-        "NotebookEdit" => {
-            let path = extract_str(args, &["notebook_path"]).unwrap_or("…");
-            format!("Editing notebook: {}", basename(path))
+        "kubernetes" => {
+            let verb = extract_str(args, &["verb"]).unwrap_or("get");
+            let target = extract_str(args, &["name", "resource"]).unwrap_or("pods");
+            catalog.text(
+                "activity.kubernetes",
+                &[("verb", verb), ("target", &truncate(target, 80))],
+            )
+        }
+        "http_request" => {
+            let target = extract_str(args, &["operation_id", "path", "url"]).unwrap_or("…");
+            catalog.text("activity.calling_api", &[("target", &truncate(target, 80))])
         }
+        "notebook_execute" => path_label("activity.running_notebook", &["notebook_path"]),
         name if name.starts_with("mcp__") => {
             let parts: Vec<&str> = name.splitn(3, "__").collect();
             if parts.len() == 3 {
                 format!("{}: {}", parts[1], parts[2])
             } else {
-                format!("Tool: {}", name)
+                catalog.text("activity.tool", &[("name", name)])
             }
         }
-        other => format!("Tool: {}", other),
+        other => catalog.text("activity.tool", &[("name", other)]),
     }
 }
 
@@ -384,125 +448,11 @@ fn normalize_metadata_text(text: &str) -> String {
 }
 
 const METADATA_SOURCE_BACKEND_HEURISTIC: &str = "backend_heuristic";
+const METADATA_SOURCE_BACKEND_LLM: &str = "backend_llm";
 const METADATA_SOURCE_USER: &str = "user";
 const METADATA_VERSION_V1: &str = "v1";
 static MISSION_TITLE_UPDATE_LOCK: std::sync::LazyLock<Mutex<()>> =
     std::sync::LazyLock::new(|| Mutex::new(()));
-struct MetadataRefreshTaskEntry {
-    handle: tokio::task::JoinHandle<()>,
-    force_refresh: bool,
-    task_id: u64,
-}
-
-struct MetadataRefreshTaskRegistration {
-    superseded: Option<tokio::task::JoinHandle<()>>,
-}
-
-static MISSION_METADATA_REFRESH_TASKS: std::sync::LazyLock<
-    std::sync::Mutex<HashMap<Uuid, MetadataRefreshTaskEntry>>,
-> = std::sync::LazyLock::new(|| std::sync::Mutex::new(HashMap::new()));
-static MISSION_METADATA_REFRESH_TASK_ID: AtomicU64 = AtomicU64::new(1);
-static MISSION_METADATA_REFRESH_BASELINES: std::sync::LazyLock<
-    std::sync::Mutex<HashMap<Uuid, usize>>,
-> = std::sync::LazyLock::new(|| std::sync::Mutex::new(HashMap::new()));
-
-fn register_metadata_refresh_task(
-    tasks: &mut HashMap<Uuid, MetadataRefreshTaskEntry>,
-    mission_id: Uuid,
-    force_refresh: bool,
-    task_id: u64,
-    handle: tokio::task::JoinHandle<()>,
-) -> MetadataRefreshTaskRegistration {
-    tasks.retain(|_, existing| !existing.handle.is_finished());
-
-    if let Some(existing) = tasks.get(&mission_id) {
-        if existing.force_refresh && !force_refresh {
-            return MetadataRefreshTaskRegistration {
-                superseded: Some(handle),
-            };
-        }
-    }
-
-    let replaced = tasks.insert(
-        mission_id,
-        MetadataRefreshTaskEntry {
-            handle,
-            force_refresh,
-            task_id,
-        },
-    );
-    MetadataRefreshTaskRegistration {
-        superseded: replaced.map(|entry| entry.handle),
-    }
-}
-
-fn should_skip_metadata_refresh_schedule(
-    tasks: &mut HashMap<Uuid, MetadataRefreshTaskEntry>,
-    mission_id: Uuid,
-    force_refresh: bool,
-) -> bool {
-    tasks.retain(|_, existing| !existing.handle.is_finished());
-    matches!(
-        tasks.get(&mission_id),
-        Some(existing) if existing.force_refresh && !force_refresh
-    )
-}
-
-fn complete_metadata_refresh_task(
-    tasks: &mut HashMap<Uuid, MetadataRefreshTaskEntry>,
-    mission_id: Uuid,
-    task_id: u64,
-) {
-    if tasks
-        .get(&mission_id)
-        .map(|entry| entry.task_id == task_id)
-        .unwrap_or(false)
-    {
-        tasks.remove(&mission_id);
-    }
-}
-
-fn clear_mission_metadata_refresh_state(mission_id: Uuid) {
-    let stale_task = {
-        let mut tasks = MISSION_METADATA_REFRESH_TASKS
-            .lock()
-            .expect("metadata refresh task registry lock poisoned");
-        tasks.remove(&mission_id)
-    };
-    if let Some(stale_task) = stale_task {
-        stale_task.handle.abort();
-    }
-
-    let mut baselines = MISSION_METADATA_REFRESH_BASELINES
-        .lock()
-        .expect("metadata refresh baseline lock poisoned");
-    baselines.remove(&mission_id);
-}
-
-async fn clear_stale_mission_metadata_refresh_state(mission_store: &Arc<dyn MissionStore>) {
-    let tracked_ids: std::collections::HashSet<Uuid> = {
-        let tasks = MISSION_METADATA_REFRESH_TASKS
-            .lock()
-            .expect("metadata refresh task registry lock poisoned");
-        let baselines = MISSION_METADATA_REFRESH_BASELINES
-            .lock()
-            .expect("metadata refresh baseline lock poisoned");
-
-        tasks.keys().chain(baselines.keys()).copied().collect()
-    };
-
-    for mission_id in tracked_ids {
-        match mission_store.get_mission(mission_id).await {
-            Ok(Some(_)) => {}
-            Ok(None) => clear_mission_metadata_refresh_state(mission_id),
-            Err(err) => tracing::warn!(
-                "Failed to verify mission {} while clearing stale metadata refresh state: {}",
-                mission_id,
-                err
-            ),
-        }
-    }
-}
 
 fn normalize_raw_title_for_dedupe(text: &str) -> String {
     text.split_whitespace()
@@ -530,6 +480,22 @@ fn canonical_title_key(title: &str) -> String {
     normalize_metadata_text(strip_numeric_title_suffix(title))
 }
 
+/// Lookup key under which stores index mission titles for dedupe. Titles that
+/// collide (same words up to case, punctuation, plurals, stopwords and a
+/// ` (n)` suffix) share a key, so candidates are found with one indexed lookup
+/// instead of scanning every mission.
+pub(crate) fn normalized_title_key(title: &str) -> String {
+    let tokens = title_similarity_tokens(title);
+    if !tokens.is_empty() {
+        return tokens.join(" ");
+    }
+    let canonical = canonical_title_key(title);
+    if !canonical.is_empty() {
+        return canonical;
+    }
+    normalize_raw_title_for_dedupe(strip_numeric_title_suffix(title))
+}
+
 fn title_similarity_token(token: &str) -> String {
     if token.len() > 4 && token.ends_with("ies") {
         return format!("{}y", &token[..token.len() - 3]);
@@ -599,60 +565,38 @@ fn title_collides_with_existing(candidate: &str, existing: &str) -> bool {
     is_near_duplicate_title(candidate, existing)
 }
 
-async fn load_recent_mission_titles(
+/// Titles of other missions that `candidate` collides with.
+async fn load_colliding_mission_titles(
     mission_store: &Arc<dyn MissionStore>,
     mission_id: Uuid,
+    candidate: &str,
 ) -> Vec<String> {
-    const RECENT_TITLE_SCAN_PAGE_SIZE: usize = 200;
-    const RECENT_TITLE_SCAN_MAX: usize = 2_000;
-
-    let mut titles = Vec::new();
-    let mut offset = 0;
-
-    while offset < RECENT_TITLE_SCAN_MAX {
-        match mission_store
-            .list_missions(RECENT_TITLE_SCAN_PAGE_SIZE, offset)
-            .await
-        {
-            Ok(missions) => {
-                if missions.is_empty() {
-                    break;
-                }
-                let page_len = missions.len();
-                titles.extend(
-                    missions
-                        .into_iter()
-                        .filter(|mission| mission.id != mission_id)
-                        .filter_map(|mission| mission.title)
-                        .map(|title| title.trim().to_string())
-                        .filter(|title| !title.is_empty()),
-                );
-
-                if page_len < RECENT_TITLE_SCAN_PAGE_SIZE {
-                    break;
-                }
-                offset += RECENT_TITLE_SCAN_PAGE_SIZE;
-            }
-            Err(err) => {
-                tracing::warn!(
-                    "Failed to load recent mission titles for metadata generation: {err}"
-                );
-                break;
-            }
-        }
+    let key = normalized_title_key(candidate);
+    if key.is_empty() {
+        return Vec::new();
     }
-
-    if offset >= RECENT_TITLE_SCAN_MAX {
-        tracing::warn!(
-            "Recent-title scan hit cap ({} missions); some older titles may be skipped",
-            RECENT_TITLE_SCAN_MAX
-        );
+    match mission_store.find_missions_by_normalized_title(&key).await {
+        Ok(missions) => missions
+            .into_iter()
+            .filter(|mission| mission.id != mission_id)
+            .filter_map(|mission| mission.title)
+            .map(|title| title.trim().to_string())
+            .filter(|title| !title.is_empty() && title_collides_with_existing(candidate, title))
+            .collect(),
+        Err(err) => {
+            tracing::warn!("Failed to look up mission titles for metadata generation: {err}");
+            Vec::new()
+        }
     }
-
-    titles
 }
 
-fn derive_title_qualifier_from_text(base_title: &str, text: &str) -> Option<String> {
+fn derive_title_qualifier_from_text(
+    base_title: &str,
+    text: &str,
+    catalog: &crate::i18n::Catalog,
+) -> Option<String> {
+    // Stopwords of the mission's language, on top of the English search ones
+    let localized_stopwords = catalog.list("metadata.stopwords");
     let base_tokens: HashSet<String> = normalize_metadata_text(base_title)
         .split_whitespace()
         .map(ToString::to_string)
@@ -660,7 +604,7 @@ fn derive_title_qualifier_from_text(base_title: &str, text: &str) -> Option<Stri
     let qualifier_tokens: Vec<String> = normalize_metadata_text(text)
         .split_whitespace()
         .filter(|token| !base_tokens.contains(*token))
-        .filter(|token| !is_search_stopword(token))
+        .filter(|token| !is_search_stopword(token) && !localized_stopwords.contains(token))
         .take(4)
         .map(ToString::to_string)
         .collect();
@@ -688,12 +632,11 @@ async fn diversify_generated_title_with_recent_context(
     title_candidate: String,
     history: &[(String, String)],
     fallback_user_content: Option<&str>,
+    catalog: &crate::i18n::Catalog,
 ) -> String {
-    let recent_titles = load_recent_mission_titles(mission_store, mission_id).await;
-    if recent_titles.is_empty()
-        || !recent_titles
-            .iter()
-            .any(|existing| title_collides_with_existing(&title_candidate, existing))
+    if load_colliding_mission_titles(mission_store, mission_id, &title_candidate)
+        .await
+        .is_empty()
     {
         return title_candidate;
     }
@@ -715,11 +658,12 @@ async fn diversify_generated_title_with_recent_context(
     }
 
     for source in qualifier_sources {
-        if let Some(qualifier) = derive_title_qualifier_from_text(&title_candidate, source) {
+        if let Some(qualifier) = derive_title_qualifier_from_text(&title_candidate, source, catalog)
+        {
             let diversified = append_title_qualifier(&title_candidate, &qualifier);
-            if !recent_titles
-                .iter()
-                .any(|existing| title_collides_with_existing(&diversified, existing))
+            if load_colliding_mission_titles(mission_store, mission_id, &diversified)
+                .await
+                .is_empty()
             {
                 return diversified;
             }
@@ -1063,6 +1007,7 @@ struct MissionMomentMatch {
     snippet: String,
     rationale: String,
     relevance_score: f64,
+    highlight: Option<String>,
 }
 
 fn mission_moment_snippet(content: &str, max_chars: usize) -> String {
@@ -1181,9 +1126,127 @@ fn mission_moment_rationale(role: &str, content: &str, search_query: &str) -> St
     format!("Keyword match in {} message", role)
 }
 
+const SEARCH_HIGHLIGHT_START: &str = "<mark>";
+const SEARCH_HIGHLIGHT_END: &str = "</mark>";
+
+/// Characters of context kept in a highlighted snippet.
+const SEARCH_HIGHLIGHT_CHARS: usize = 180;
+
+/// Whether a word of the searched text matches one of the query's terms.
+fn search_word_matches(word: &str, query_terms: &SearchQueryTerms) -> bool {
+    let word = word.to_lowercase();
+    query_terms.query_groups.iter().flatten().any(|candidate| {
+        !candidate.is_empty()
+            && (search_token_match_strength(&word, candidate) > 0.0
+                || search_token_match_strength(candidate, &word) > 0.0)
+    })
+}
+
+fn escape_search_html(text: &str, out: &mut String) {
+    for ch in text.chars() {
+        match ch {
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '&' => out.push_str("&amp;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(ch),
+        }
+    }
+}
+
+/// HTML-escaped snippet of `text` around its first match of `search_query`,
+/// with every matching word wrapped in `<mark>`; `None` when nothing matches.
+fn highlight_search_matches(text: &str, search_query: &str, max_chars: usize) -> Option<String> {
+    let query_terms = build_search_query_terms(search_query)?;
+    let chars: Vec<char> = text
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .chars()
+        .collect();
+
+    // Words as char ranges, keeping the ones that match.
+    let mut matches = Vec::new();
+    let mut start = None;
+    for i in 0..=chars.len() {
+        let in_word = chars.get(i).is_some_and(|ch| ch.is_alphanumeric());
+        match (start, in_word) {
+            (None, true) => start = Some(i),
+            (Some(s), false) => {
+                let word: String = chars[s..i].iter().collect();
+                if search_word_matches(&word, &query_terms) {
+                    matches.push((s, i));
+                }
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    let first = matches.first()?.0;
+
+    // Keep some context before the first match.
+    let mut window_start = first.saturating_sub(max_chars / 4);
+    let window_end = (window_start + max_chars).min(chars.len());
+    window_start = window_end.saturating_sub(max_chars).min(window_start);
+
+    let mut snippet = String::new();
+    if window_start > 0 {
+        snippet.push('…');
+    }
+    let mut pos = window_start;
+    for &(s, e) in &matches {
+        if e <= window_start || s >= window_end {
+            continue;
+        }
+        let (s, e) = (s.max(window_start), e.min(window_end));
+        escape_search_html(&chars[pos..s].iter().collect::<String>(), &mut snippet);
+        snippet.push_str(SEARCH_HIGHLIGHT_START);
+        escape_search_html(&chars[s..e].iter().collect::<String>(), &mut snippet);
+        snippet.push_str(SEARCH_HIGHLIGHT_END);
+        pos = e;
+    }
+    escape_search_html(
+        &chars[pos..window_end].iter().collect::<String>(),
+        &mut snippet,
+    );
+    if window_end < chars.len() {
+        snippet.push('…');
+    }
+    Some(snippet)
+}
+
+/// Highlighted title and description matches of a mission search result.
+fn mission_search_highlights(mission: &Mission, search_query: &str) -> Vec<SearchHighlight> {
+    [
+        ("title", mission.title.as_deref()),
+        ("short_description", mission.short_description.as_deref()),
+    ]
+    .into_iter()
+    .filter_map(|(field, text)| {
+        let snippet = highlight_search_matches(text?, search_query, SEARCH_HIGHLIGHT_CHARS)?;
+        Some(SearchHighlight {
+            field: field.to_string(),
+            snippet,
+        })
+    })
+    .collect()
+}
+
+/// Keyset ordering search results by relevance, then recency.
+fn search_result_keyset(relevance_score: f64, mission: &Mission) -> Keyset {
+    Keyset {
+        // Zero-padded so the string order follows the score.
+        sort_key: format!("{:020.6}|{}", relevance_score.max(0.0), mission.updated_at),
+        id: mission.id,
+    }
+}
+
 fn best_mission_moment(mission: &Mission, search_query: &str) -> Option<MissionMomentMatch> {
     let mut best: Option<MissionMomentMatch> = None;
     for (idx, entry) in mission.history.iter().enumerate() {
+        if !entry.is_conversation() {
+            continue;
+        }
         let score = mission_moment_relevance_score(&entry.role, &entry.content, search_query);
         if score <= 0.0 {
             continue;
@@ -1194,13 +1257,22 @@ fn best_mission_moment(mission: &Mission, search_query: &str) -> Option<MissionM
             snippet: mission_moment_snippet(&entry.content, 180),
             rationale: mission_moment_rationale(&entry.role, &entry.content, search_query),
             relevance_score: score,
+            highlight: None,
         };
         match &best {
             Some(existing) if existing.relevance_score >= candidate.relevance_score => {}
             _ => best = Some(candidate),
         }
     }
-    best
+    // Only the best moment is returned, so only it gets highlighted.
+    best.map(|mut best| {
+        best.highlight = highlight_search_matches(
+            &mission.history[best.entry_index].content,
+            search_query,
+            SEARCH_HIGHLIGHT_CHARS,
+        );
+        best
+    })
 }
 
 async fn disambiguate_generated_title(
@@ -1208,9 +1280,6 @@ async fn disambiguate_generated_title(
     mission_id: Uuid,
     title: &str,
 ) -> String {
-    const TITLE_DEDUPE_PAGE_SIZE: usize = 200;
-    const TITLE_DEDUPE_MAX_SCAN: usize = 5_000;
-
     let base_title = title.trim();
     if base_title.is_empty() {
         return title.to_string();
@@ -1222,61 +1291,44 @@ async fn disambiguate_generated_title(
     let mut canonical_raw_titles = HashSet::new();
     let mut dedupe_probe_titles = Vec::new();
 
-    let mut offset = 0;
-    while offset < TITLE_DEDUPE_MAX_SCAN {
-        let missions = match mission_store
-            .list_missions(TITLE_DEDUPE_PAGE_SIZE, offset)
-            .await
-        {
-            Ok(missions) => missions,
-            Err(err) => {
-                tracing::warn!("Failed to load mission titles for dedupe guard: {}", err);
-                return base_title.to_string();
-            }
-        };
-
-        if missions.is_empty() {
-            break;
+    // Every `base (n)` candidate shares the base title's key, so one lookup
+    // finds all titles that can collide.
+    let missions = match mission_store
+        .find_missions_by_normalized_title(&normalized_title_key(base_title))
+        .await
+    {
+        Ok(missions) => missions,
+        Err(err) => {
+            tracing::warn!("Failed to load mission titles for dedupe guard: {}", err);
+            return base_title.to_string();
         }
+    };
 
-        for mission in &missions {
-            if mission.id == mission_id {
-                continue;
+    for mission in &missions {
+        if mission.id == mission_id {
+            continue;
+        }
+        if let Some(existing_title) = &mission.title {
+            let raw = normalize_raw_title_for_dedupe(existing_title);
+            if !raw.is_empty() {
+                raw_titles.insert(raw);
             }
-            if let Some(existing_title) = &mission.title {
-                let raw = normalize_raw_title_for_dedupe(existing_title);
-                if !raw.is_empty() {
-                    raw_titles.insert(raw);
-                }
-                let canonical_raw =
-                    normalize_raw_title_for_dedupe(strip_numeric_title_suffix(existing_title));
-                if !canonical_raw.is_empty() {
-                    canonical_raw_titles.insert(canonical_raw);
-                }
-
-                let normalized = normalize_metadata_text(existing_title);
-                if !normalized.is_empty() {
-                    exact_titles.insert(normalized);
-                }
-                let canonical = canonical_title_key(existing_title);
-                if !canonical.is_empty() {
-                    canonical_titles.insert(canonical);
-                }
-                dedupe_probe_titles.push(existing_title.clone());
+            let canonical_raw =
+                normalize_raw_title_for_dedupe(strip_numeric_title_suffix(existing_title));
+            if !canonical_raw.is_empty() {
+                canonical_raw_titles.insert(canonical_raw);
             }
-        }
 
-        if missions.len() < TITLE_DEDUPE_PAGE_SIZE {
-            break;
+            let normalized = normalize_metadata_text(existing_title);
+            if !normalized.is_empty() {
+                exact_titles.insert(normalized);
+            }
+            let canonical = canonical_title_key(existing_title);
+            if !canonical.is_empty() {
+                canonical_titles.insert(canonical);
+            }
+            dedupe_probe_titles.push(existing_title.clone());
         }
-        offset += TITLE_DEDUPE_PAGE_SIZE;
-    }
-
-    if offset >= TITLE_DEDUPE_MAX_SCAN {
-        tracing::warn!(
-            "Title dedupe scan hit cap ({} missions); duplicates may still exist",
-            TITLE_DEDUPE_MAX_SCAN
-        );
     }
 
     let candidate_exact = normalize_metadata_text(base_title);
@@ -1331,6 +1383,7 @@ async fn generate_mission_metadata_updates(
     history: &[(String, String)],
     fallback_user_content: Option<&str>,
     should_refresh: bool,
+    generated: Option<&super::metadata_llm::GeneratedMetadata>,
 ) -> (Option<String>, Option<String>) {
     if history.is_empty() {
         return (None, None);
@@ -1367,6 +1420,10 @@ async fn generate_mission_metadata_updates(
         return (None, None);
     }
 
+    // Model-generated metadata replaces the heuristics where it has a value.
+    let generated_title = generated.and_then(|g| g.title.clone());
+    let generated_short_description = generated.and_then(|g| g.short_description.clone());
+
     let title_candidate = if (title_missing || should_refresh) && !title_user_managed {
         let assistant_title_candidate = if should_bootstrap_title_from_first_assistant {
             history
@@ -1381,7 +1438,7 @@ async fn generate_mission_metadata_updates(
                 .find_map(|(_, content)| extract_title_from_assistant(content))
         };
 
-        assistant_title_candidate.or_else(|| {
+        generated_title.or(assistant_title_candidate).or_else(|| {
             if should_bootstrap_title_from_first_assistant {
                 fallback_user_content.map(|user_content| {
                     if user_content.len() > 100 {
@@ -1406,6 +1463,7 @@ async fn generate_mission_metadata_updates(
                 candidate,
                 history,
                 fallback_user_content,
+                crate::i18n::catalog(&crate::i18n::workspace_locale(mission.workspace_id)),
             )
             .await,
         ),
@@ -1416,7 +1474,9 @@ async fn generate_mission_metadata_updates(
         || should_refresh
         || should_bootstrap_short_description_from_first_assistant
     {
-        if should_bootstrap_short_description_from_first_assistant {
+        if generated_short_description.is_some() {
+            generated_short_description
+        } else if should_bootstrap_short_description_from_first_assistant {
             extract_short_description_from_first_successful_assistant(history, 160)
                 .or_else(|| extract_short_description_from_history(history, 160))
         } else if should_refresh {
@@ -1565,57 +1625,10 @@ fn conversational_message_count(history: &[(String, String)]) -> usize {
         .count()
 }
 
-fn should_refresh_metadata_by_cadence(
-    mission_id: Uuid,
-    mission: &Mission,
-    conversational_count: usize,
-    force_refresh: bool,
-) -> bool {
-    if force_refresh {
-        return true;
-    }
-    if conversational_count == 0 {
-        return false;
-    }
-
-    let mut baselines = MISSION_METADATA_REFRESH_BASELINES
-        .lock()
-        .expect("metadata refresh baseline lock poisoned");
-    let baseline = baselines.entry(mission_id).or_insert_with(|| {
-        if mission.metadata_updated_at.is_some() {
-            conversational_count
-        } else {
-            0
-        }
-    });
-
-    if conversational_count < *baseline {
-        *baseline = conversational_count;
-        return false;
-    }
-
-    conversational_count.saturating_sub(*baseline) >= 10
-}
-
-fn record_metadata_refresh_baseline(mission_id: Uuid, conversational_count: usize) {
-    let mut baselines = MISSION_METADATA_REFRESH_BASELINES
-        .lock()
-        .expect("metadata refresh baseline lock poisoned");
-    baselines.insert(mission_id, conversational_count);
-}
-
-fn record_metadata_refresh_baseline_from_mission(mission_id: Uuid, mission: &Mission) {
-    let conversational_count = mission
-        .history
-        .iter()
-        .filter(|entry| entry.role == "user" || entry.role == "assistant")
-        .count();
-    record_metadata_refresh_baseline(mission_id, conversational_count);
-}
-
 async fn persist_mission_history_and_schedule_metadata_refresh(
     mission_store: &Arc<dyn MissionStore>,
     events_tx: &broadcast::Sender<AgentEvent>,
+    metadata_refresh: &metadata_refresh::SharedMetadataRefresh,
     mission_id: Uuid,
     entries: &[MissionHistoryEntry],
 ) {
@@ -1626,12 +1639,19 @@ async fn persist_mission_history_and_schedule_metadata_refresh(
         tracing::warn!("Failed to persist mission history: {}", e);
         return;
     }
-    schedule_mission_metadata_refresh(mission_store, events_tx, mission_id, false);
+    schedule_mission_metadata_refresh(
+        mission_store,
+        events_tx,
+        metadata_refresh,
+        mission_id,
+        false,
+    );
 }
 
 async fn refresh_mission_metadata_from_store(
     mission_store: &Arc<dyn MissionStore>,
     events_tx: &broadcast::Sender<AgentEvent>,
+    metadata_refresh: &metadata_refresh::MetadataRefreshScheduler,
     mission_id: Uuid,
     force_refresh: bool,
 ) {
@@ -1657,6 +1677,7 @@ async fn refresh_mission_metadata_from_store(
     let history_pairs: Vec<(String, String)> = mission
         .history
         .iter()
+        .filter(|entry| entry.is_conversation())
         .map(|entry| (entry.role.clone(), entry.content.clone()))
         .collect();
     let fallback_user_content = history_pairs
@@ -1664,13 +1685,31 @@ async fn refresh_mission_metadata_from_store(
         .find(|(role, _)| role == "user")
         .map(|(_, content)| content.as_str());
     let conversational_count = conversational_message_count(&history_pairs);
-    let should_refresh = should_refresh_metadata_by_cadence(
+    let should_refresh = metadata_refresh.should_refresh_by_cadence(
         mission_id,
         &mission,
         conversational_count,
         force_refresh,
     );
 
+    let metadata_needed = should_refresh
+        || [
+            mission.title.as_deref(),
+            mission.short_description.as_deref(),
+        ]
+        .iter()
+        .any(|value| value.map(|v| v.trim().is_empty()).unwrap_or(true));
+    let llm_metadata = if metadata_needed {
+        super::metadata_llm::generate(
+            mission_id,
+            &history_pairs,
+            &crate::i18n::workspace_locale(mission.workspace_id),
+        )
+        .await
+    } else {
+        None
+    };
+
     let (generated_title, generated_short_description) = generate_mission_metadata_updates(
         mission_store,
         mission_id,
@@ -1678,6 +1717,7 @@ async fn refresh_mission_metadata_from_store(
         &history_pairs,
         fallback_user_content,
         should_refresh,
+        llm_metadata.as_ref(),
     )
     .await;
     let metadata_source_update = if generated_title.is_none()
@@ -1689,6 +1729,8 @@ async fn refresh_mission_metadata_from_store(
             .unwrap_or(false)
     {
         None
+    } else if llm_metadata.is_some() {
+        Some(Some(METADATA_SOURCE_BACKEND_LLM))
     } else {
         Some(Some(METADATA_SOURCE_BACKEND_HEURISTIC))
     };
@@ -1703,94 +1745,39 @@ async fn refresh_mission_metadata_from_store(
     )
     .await;
     if should_refresh || metadata_updated {
-        record_metadata_refresh_baseline(mission_id, conversational_count);
+        metadata_refresh.record_baseline(mission_id, conversational_count);
     }
 }
 
 fn schedule_mission_metadata_refresh(
     mission_store: &Arc<dyn MissionStore>,
     events_tx: &broadcast::Sender<AgentEvent>,
+    metadata_refresh: &metadata_refresh::SharedMetadataRefresh,
     mission_id: Uuid,
     force_refresh: bool,
 ) {
-    {
-        let mut tasks = MISSION_METADATA_REFRESH_TASKS
-            .lock()
-            .expect("metadata refresh task registry lock poisoned");
-        if should_skip_metadata_refresh_schedule(&mut tasks, mission_id, force_refresh) {
-            return;
-        }
-    }
-
-    let task_id = MISSION_METADATA_REFRESH_TASK_ID.fetch_add(1, Ordering::Relaxed);
     let mission_store = Arc::clone(mission_store);
     let events_tx = events_tx.clone();
-    let background_refresh = tokio::spawn(async move {
-        refresh_mission_metadata_from_store(&mission_store, &events_tx, mission_id, force_refresh)
-            .await;
-        let mut tasks = MISSION_METADATA_REFRESH_TASKS
-            .lock()
-            .expect("metadata refresh task registry lock poisoned");
-        complete_metadata_refresh_task(&mut tasks, mission_id, task_id);
-    });
-    let registration = {
-        let mut tasks = MISSION_METADATA_REFRESH_TASKS
-            .lock()
-            .expect("metadata refresh task registry lock poisoned");
-        register_metadata_refresh_task(
-            &mut tasks,
+    let scheduler = Arc::clone(metadata_refresh);
+    metadata_refresh.schedule(mission_id, force_refresh, async move {
+        refresh_mission_metadata_from_store(
+            &mission_store,
+            &events_tx,
+            &scheduler,
             mission_id,
             force_refresh,
-            task_id,
-            background_refresh,
         )
-    };
-    if let Some(superseded) = registration.superseded {
-        superseded.abort();
-    }
-}
-
-async fn refresh_mission_metadata_for_milestone(
-    mission_store: &Arc<dyn MissionStore>,
-    events_tx: &broadcast::Sender<AgentEvent>,
-    mission_id: Uuid,
-) {
-    refresh_mission_metadata_from_store(mission_store, events_tx, mission_id, true).await;
+        .await;
+    });
 }
 
 fn schedule_mission_metadata_refresh_for_milestone(
     mission_store: &Arc<dyn MissionStore>,
     events_tx: &broadcast::Sender<AgentEvent>,
+    metadata_refresh: &metadata_refresh::SharedMetadataRefresh,
     mission_id: Uuid,
 ) {
-    {
-        let mut tasks = MISSION_METADATA_REFRESH_TASKS
-            .lock()
-            .expect("metadata refresh task registry lock poisoned");
-        if should_skip_metadata_refresh_schedule(&mut tasks, mission_id, true) {
-            return;
-        }
-    }
-
-    let task_id = MISSION_METADATA_REFRESH_TASK_ID.fetch_add(1, Ordering::Relaxed);
-    let mission_store = Arc::clone(mission_store);
-    let events_tx = events_tx.clone();
-    let background_refresh = tokio::spawn(async move {
-        refresh_mission_metadata_for_milestone(&mission_store, &events_tx, mission_id).await;
-        let mut tasks = MISSION_METADATA_REFRESH_TASKS
-            .lock()
-            .expect("metadata refresh task registry lock poisoned");
-        complete_metadata_refresh_task(&mut tasks, mission_id, task_id);
-    });
-    let registration = {
-        let mut tasks = MISSION_METADATA_REFRESH_TASKS
-            .lock()
-            .expect("metadata refresh task registry lock poisoned");
-        register_metadata_refresh_task(&mut tasks, mission_id, true, task_id, background_refresh)
-    };
-    if let Some(superseded) = registration.superseded {
-        superseded.abort();
-    }
+    schedule_mission_metadata_refresh(mission_store, events_tx, metadata_refresh, mission_id, true);
 }
 
 fn status_requires_metadata_milestone_refresh(status: MissionStatus) -> bool {
@@ -1800,11 +1787,25 @@ fn status_requires_metadata_milestone_refresh(status: MissionStatus) -> bool {
 fn maybe_schedule_mission_metadata_refresh_for_status(
     mission_store: &Arc<dyn MissionStore>,
     events_tx: &broadcast::Sender<AgentEvent>,
+    metadata_refresh: &metadata_refresh::SharedMetadataRefresh,
     mission_id: Uuid,
     status: MissionStatus,
 ) {
     if status_requires_metadata_milestone_refresh(status) {
-        schedule_mission_metadata_refresh_for_milestone(mission_store, events_tx, mission_id);
+        schedule_mission_metadata_refresh_for_milestone(
+            mission_store,
+            events_tx,
+            metadata_refresh,
+            mission_id,
+        );
+    }
+    if status == MissionStatus::Completed {
+        let mission_store = Arc::clone(mission_store);
+        let events_tx = events_tx.clone();
+        tokio::spawn(async move {
+            super::mission_report::generate_report(&mission_store, mission_id).await;
+            super::follow_ups::suggest_follow_ups(&mission_store, &events_tx, mission_id).await;
+        });
     }
 }
 
@@ -1824,23 +1825,117 @@ fn recv_failed<T>(_: T) -> (StatusCode, String) {
     )
 }
 
-/// Shorthand for a `{ "ok": true }` JSON response.
-fn ok_json() -> Json<serde_json::Value> {
-    Json(serde_json::json!({ "ok": true }))
+/// How to handle a request that may carry an `Idempotency-Key`.
+enum IdempotentRequest {
+    /// Execute the request, completing the claimed key (if any) afterwards.
+    Execute(ClaimedIdempotencyKey),
+    /// Return the original response.
+    Replay(serde_json::Value),
 }
 
-/// Unwrap a mission ID or emit an error event and return a failure result.
-#[allow(clippy::result_large_err)]
-fn require_mission_id(
-    mission_id: Option<Uuid>,
-    backend: &str,
-    events_tx: &broadcast::Sender<AgentEvent>,
-) -> Result<Uuid, crate::agents::AgentResult> {
-    mission_id.ok_or_else(|| {
-        let msg = format!("{} backend requires a mission ID", backend);
-        let _ = events_tx.send(AgentEvent::Error {
-            message: msg.clone(),
-            mission_id: None,
+/// A scoped key claimed for a request being executed. A claim that is
+/// dropped without being completed (the handler errored out early, was
+/// cancelled or panicked) releases the key so the client can retry.
+struct ClaimedIdempotencyKey {
+    claim: Option<(mpsc::Sender<ControlCommand>, String)>,
+}
+
+impl ClaimedIdempotencyKey {
+    /// Store the response so replays return it; failed requests release the key.
+    async fn complete<T: Serialize>(mut self, result: &Result<Json<T>, (StatusCode, String)>) {
+        let Some((cmd_tx, key)) = self.claim.take() else {
+            return;
+        };
+        let response = match result {
+            Ok(Json(value)) => serde_json::to_value(value).ok(),
+            Err(_) => None,
+        };
+        let _ = cmd_tx
+            .send(ControlCommand::CompleteIdempotencyKey { key, response })
+            .await;
+    }
+}
+
+impl Drop for ClaimedIdempotencyKey {
+    fn drop(&mut self) {
+        let Some((cmd_tx, key)) = self.claim.take() else {
+            return;
+        };
+        let release = ControlCommand::CompleteIdempotencyKey {
+            key,
+            response: None,
+        };
+        if let Err(mpsc::error::TrySendError::Full(release)) = cmd_tx.try_send(release) {
+            tokio::spawn(async move {
+                let _ = cmd_tx.send(release).await;
+            });
+        }
+    }
+}
+
+/// Claim the request's `Idempotency-Key` with the control actor.
+async fn claim_idempotency_key(
+    control: &ControlState,
+    headers: &HeaderMap,
+    scope: &str,
+) -> Result<IdempotentRequest, (StatusCode, String)> {
+    let Some(key) =
+        super::idempotency::scoped_key(headers, scope).map_err(|e| (StatusCode::BAD_REQUEST, e))?
+    else {
+        return Ok(IdempotentRequest::Execute(ClaimedIdempotencyKey {
+            claim: None,
+        }));
+    };
+    let (tx, rx) = oneshot::channel();
+    control
+        .cmd_tx
+        .send(ControlCommand::ClaimIdempotencyKey {
+            key: key.clone(),
+            respond: tx,
+        })
+        .await
+        .map_err(session_unavailable)?;
+    match rx.await.map_err(recv_failed)? {
+        IdempotencyClaim::Fresh => Ok(IdempotentRequest::Execute(ClaimedIdempotencyKey {
+            claim: Some((control.cmd_tx.clone(), key)),
+        })),
+        IdempotencyClaim::Replay(response) => {
+            tracing::info!(key = %key, "Replaying idempotent request");
+            Ok(IdempotentRequest::Replay(response))
+        }
+        IdempotencyClaim::InProgress => Err((
+            StatusCode::CONFLICT,
+            "A request with this Idempotency-Key is still in progress".to_string(),
+        )),
+    }
+}
+
+fn replay_response<T: serde::de::DeserializeOwned>(
+    response: serde_json::Value,
+) -> Result<Json<T>, (StatusCode, String)> {
+    serde_json::from_value(response)
+        .map(Json)
+        .map_err(internal_error)
+}
+
+/// Shorthand for a `{ "ok": true }` JSON response.
+fn ok_json() -> Json<serde_json::Value> {
+    Json(serde_json::json!({ "ok": true }))
+}
+
+/// Unwrap a mission ID or emit an error event and return a failure result.
+#[allow(clippy::result_large_err)]
+fn require_mission_id(
+    mission_id: Option<Uuid>,
+    backend: &str,
+    events_tx: &broadcast::Sender<AgentEvent>,
+) -> Result<Uuid, crate::agents::AgentResult> {
+    mission_id.ok_or_else(|| {
+        let msg = format!("{} backend requires a mission ID", backend);
+        let _ = events_tx.send(AgentEvent::Error {
+            message: msg.clone(),
+            code: Some(ErrorCode::Internal),
+            mission_id: None,
             resumable: false,
         });
         crate::agents::AgentResult::failure(msg, 0).with_terminal_reason(TerminalReason::LlmError)
@@ -1848,7 +1943,7 @@ fn require_mission_id(
 }
 
 /// Query the control actor for the list of currently running missions.
-async fn get_running_missions(
+pub(super) async fn get_running_missions(
     control: &ControlState,
 ) -> Result<Vec<super::mission_runner::RunningMissionInfo>, (StatusCode, String)> {
     let (tx, rx) = oneshot::channel();
@@ -1923,6 +2018,47 @@ async fn mission_has_active_automation(
     }
 }
 
+/// Running missions split into interactive and automated. `main_automated`
+/// is `Some` when the main runner is busy.
+fn parallel_slot_allocation(
+    max_parallel: usize,
+    parallel_runners: &HashMap<Uuid, super::mission_runner::MissionRunner>,
+    main_automated: Option<bool>,
+) -> crate::tenant::SlotAllocation {
+    let (automated, interactive): (Vec<bool>, Vec<bool>) = parallel_runners
+        .values()
+        .filter(|r| r.is_running())
+        .map(|r| r.automated)
+        .chain(main_automated)
+        .partition(|automated| *automated);
+    crate::tenant::SlotAllocation::new(
+        max_parallel,
+        crate::settings::reserved_interactive_slots(),
+        interactive.len(),
+        automated.len(),
+    )
+}
+
+/// Why a mission cannot start, or None when a slot is free.
+fn slot_unavailable_reason(
+    slots: &crate::tenant::SlotAllocation,
+    automated: bool,
+) -> Option<String> {
+    if slots.can_start(automated) {
+        None
+    } else if slots.running() >= slots.max_parallel_missions {
+        Some(format!(
+            "max parallel missions ({}) reached",
+            slots.max_parallel_missions
+        ))
+    } else {
+        Some(format!(
+            "all {} automation slots are in use ({} reserved for interactive missions)",
+            slots.automation_slots, slots.reserved_interactive_slots
+        ))
+    }
+}
+
 async fn stop_policy_matches_status(
     stop_policy: &mission_store::StopPolicy,
     _status: MissionStatus,
@@ -1951,7 +2087,7 @@ async fn consecutive_failure_count_for_automation(
     }
 
     let executions = mission_store
-        .get_automation_executions(automation.id, Some(20))
+        .get_automation_executions(automation.id, Some(20), None)
         .await
         .unwrap_or_default();
     let mut count = 0u32;
@@ -2040,11 +2176,16 @@ pub(crate) async fn resolve_claudecode_default_model(
     }
 }
 
-async fn close_mission_desktop_sessions(
+/// Close the desktop sessions, notebook kernels and Docker containers a
+/// mission left running.
+async fn close_mission_sessions(
     mission_store: &Arc<dyn MissionStore>,
     mission_id: Uuid,
     working_dir: &std::path::Path,
 ) {
+    crate::tools::notebook::shutdown_mission_kernels(mission_id).await;
+    crate::tools::docker::cleanup_mission_containers(mission_id).await;
+
     let Ok(Some(mission)) = mission_store.get_mission(mission_id).await else {
         return;
     };
@@ -2087,6 +2228,100 @@ async fn close_mission_desktop_sessions(
     }
 }
 
+/// Emit a cancelled parallel turn's partial reply and append it to the
+/// mission's history. (The main runner's completion path does this itself.)
+async fn persist_cancelled_turn(
+    mission_store: &Arc<dyn MissionStore>,
+    events_tx: &broadcast::Sender<AgentEvent>,
+    mission_id: Uuid,
+    turn: PartialTurn,
+) {
+    match mission_store.get_mission(mission_id).await {
+        Ok(Some(mission)) => {
+            let mut entries = mission.history;
+            entries.push(MissionHistoryEntry {
+                role: "assistant".to_string(),
+                content: turn.history_content(),
+                tool_name: None,
+            });
+            if let Err(e) = mission_store
+                .update_mission_history(mission_id, &entries)
+                .await
+            {
+                tracing::warn!("Failed to persist cancelled turn: {}", e);
+            }
+        }
+        Ok(None) => {}
+        Err(e) => tracing::warn!("Failed to load mission {}: {}", mission_id, e),
+    }
+    let _ = events_tx.send(AgentEvent::AssistantMessage {
+        id: Uuid::new_v4(),
+        content: turn.content(),
+        success: false,
+        cost_cents: 0,
+        cost_source: crate::agents::CostSource::Unknown,
+        usage: None,
+        model: None,
+        model_normalized: None,
+        mission_id: Some(mission_id),
+        shared_files: None,
+        resumable: true,
+        cancelled: true,
+        tool_calls: turn.tool_calls,
+    });
+}
+
+/// Cancel a parallel mission runner, mark the mission Interrupted (resumable)
+/// and drop the runner.
+#[allow(clippy::too_many_arguments)]
+async fn cancel_parallel_runner(
+    parallel_runners: &mut std::collections::HashMap<Uuid, super::mission_runner::MissionRunner>,
+    mission_id: Uuid,
+    reason: &str,
+    mission_store: &Arc<dyn MissionStore>,
+    events_tx: &broadcast::Sender<AgentEvent>,
+    metadata_refresh: &metadata_refresh::SharedMetadataRefresh,
+    working_dir: &std::path::Path,
+    partial_turns: &mut PartialTurns,
+) -> bool {
+    let Some(mut runner) = parallel_runners.remove(&mission_id) else {
+        return false;
+    };
+    runner.cancel();
+    if let Some(turn) = partial_turns.take(mission_id) {
+        persist_cancelled_turn(mission_store, events_tx, mission_id, turn).await;
+    }
+    // Update status to Interrupted so the mission can be
+    // resumed later (fixes #149: cancel left status as pending).
+    if let Err(e) = mission_store
+        .update_mission_status(mission_id, MissionStatus::Interrupted)
+        .await
+    {
+        tracing::warn!("Failed to update cancelled parallel mission status: {}", e);
+    } else {
+        maybe_schedule_mission_metadata_refresh_for_status(
+            mission_store,
+            events_tx,
+            metadata_refresh,
+            mission_id,
+            MissionStatus::Interrupted,
+        );
+    }
+    let _ = events_tx.send(AgentEvent::Error {
+        message: reason.to_string(),
+        code: Some(ErrorCode::Cancelled),
+        mission_id: Some(mission_id),
+        resumable: true, // Cancelled missions can be resumed
+    });
+    let _ = events_tx.send(AgentEvent::MissionStatusChanged {
+        mission_id,
+        status: MissionStatus::Interrupted,
+        summary: None,
+    });
+    close_mission_sessions(mission_store, mission_id, working_dir).await;
+    true
+}
+
 /// Message posted by a user to the control session.
 #[derive(Debug, Clone, Deserialize)]
 pub struct ControlMessageRequest {
@@ -2100,7 +2335,7 @@ pub struct ControlMessageRequest {
     pub mission_id: Option<Uuid>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ControlMessageResponse {
     pub id: Uuid,
     pub queued: bool,
@@ -2145,8 +2380,12 @@ pub struct SharedFile {
     /// File size in bytes (optional)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub size_bytes: Option<u64>,
-    /// File kind for rendering hints: "image", "document", "archive", "code", "other"
+    /// File kind for rendering hints: "image", "document", "archive", "code",
+    /// "directory", "diff", "other"
     pub kind: SharedFileKind,
+    /// Server-side preview (PDF thumbnail, table or code excerpt)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub preview: Option<super::file_preview::SharedFilePreview>,
 }
 
 /// Kind of shared file (determines how it renders in the UI).
@@ -2161,6 +2400,10 @@ pub enum SharedFileKind {
     Archive,
     /// Code/text files - shown as download card with syntax hint
     Code,
+    /// Whole directories - downloaded as a ZIP archive
+    Directory,
+    /// Patches (unified diffs) - rendered as a diff
+    Diff,
     /// Other files - generic download card
     Other,
 }
@@ -2181,6 +2424,7 @@ impl SharedFile {
             content_type,
             size_bytes,
             kind,
+            preview: None,
         }
     }
 
@@ -2188,6 +2432,8 @@ impl SharedFile {
     fn infer_kind(content_type: &str) -> SharedFileKind {
         if content_type.starts_with("image/") {
             SharedFileKind::Image
+        } else if content_type.starts_with("text/x-diff") {
+            SharedFileKind::Diff
         } else if content_type.starts_with("text/")
             || content_type.contains("json")
             || content_type.contains("xml")
@@ -2211,14 +2457,19 @@ impl SharedFile {
 }
 
 // ---------------------------------------------------------------------------
-// Rich tag parsing: extract <image path="..." /> and <file path="..." /> from
-// agent output so we can validate referenced files and populate shared_files.
+// Rich tag parsing: extract <image path="..." />, <file path="..." />,
+// <dir path="..." /> and <diff path="..." /> from agent output so we can
+// validate referenced files and populate shared_files.
 // ---------------------------------------------------------------------------
 
 #[derive(Debug, Clone, PartialEq)]
 enum RichTagType {
     Image,
     File,
+    /// A directory, shared as a ZIP download
+    Dir,
+    /// A patch file
+    Diff,
 }
 
 #[derive(Debug, Clone)]
@@ -2229,13 +2480,14 @@ struct RichTagRef {
     name: Option<String>,
 }
 
-/// Parse `<image path="..." />` and `<file path="..." />` tags from content.
+/// Parse `<image path="..." />`, `<file path="..." />`, `<dir path="..." />`
+/// and `<diff path="..." />` tags from content.
 fn parse_rich_tags(content: &str) -> Vec<RichTagRef> {
     use regex::Regex;
     use std::sync::LazyLock;
 
     static TAG_RE: LazyLock<Regex> =
-        LazyLock::new(|| Regex::new(r#"<(image|file)\s+([^>]*?)\s*/>"#).unwrap());
+        LazyLock::new(|| Regex::new(r#"<(image|file|dir|diff)\s+([^>]*?)\s*/>"#).unwrap());
     static ATTR_RE: LazyLock<Regex> =
         LazyLock::new(|| Regex::new(r#"(\w+)\s*=\s*"([^"]*)""#).unwrap());
 
@@ -2244,6 +2496,8 @@ fn parse_rich_tags(content: &str) -> Vec<RichTagRef> {
         let tag_type = match cap[1].to_ascii_lowercase().as_str() {
             "image" => RichTagType::Image,
             "file" => RichTagType::File,
+            "dir" => RichTagType::Dir,
+            "diff" => RichTagType::Diff,
             _ => continue,
         };
         let attr_str = &cap[2];
@@ -2282,7 +2536,22 @@ async fn validate_rich_tags(
     // Only allow files that resolve within the mission working directory. This keeps the
     // "shared files" surface area consistent with what the agent produced in its workspace,
     // and avoids emitting links that would be rejected by the download endpoint anyway.
-    let canonical_working_dir = working_dir.canonicalize().ok();
+    let canonical_working_dir = crate::util::canonicalize_path(working_dir).ok();
+    let previews_dir = working_dir.join(super::file_preview::PREVIEWS_DIR);
+    let download_url = |endpoint: &str, path: &std::path::Path| {
+        let mut url = format!(
+            "/api/fs/{}?path={}",
+            endpoint,
+            urlencoding::encode(path.to_string_lossy().as_ref())
+        );
+        if let Some(ws_id) = workspace_id {
+            url.push_str(&format!("&workspace_id={}", ws_id));
+        }
+        if let Some(mid) = mission_id {
+            url.push_str(&format!("&mission_id={}", mid));
+        }
+        url
+    };
 
     let mut files = Vec::new();
     for tag in tags {
@@ -2300,7 +2569,7 @@ async fn validate_rich_tags(
             Err(_) => continue, // skip non-existent files
         };
 
-        let canon_resolved = match resolved.canonicalize() {
+        let canon_resolved = match crate::util::canonicalize_path(&resolved) {
             Ok(p) => p,
             Err(_) => continue,
         };
@@ -2311,36 +2580,61 @@ async fn validate_rich_tags(
             }
         }
 
-        let size = Some(meta.len());
-        let content_type = super::fs::content_type_for_path(&canon_resolved).to_string();
+        // Directories only come from <dir>, and <dir> only shares directories
+        if meta.is_dir() != (tag.tag_type == RichTagType::Dir) {
+            continue;
+        }
 
         let display_name = match &tag.tag_type {
-            RichTagType::Image => tag
-                .alt
-                .clone()
-                .or_else(|| tag.path.rsplit('/').next().map(|s| s.to_string()))
-                .unwrap_or_else(|| tag.path.clone()),
-            RichTagType::File => tag
-                .name
-                .clone()
-                .or_else(|| tag.path.rsplit('/').next().map(|s| s.to_string()))
-                .unwrap_or_else(|| tag.path.clone()),
-        };
+            RichTagType::Image => tag.alt.clone(),
+            RichTagType::File | RichTagType::Dir | RichTagType::Diff => tag.name.clone(),
+        }
+        .unwrap_or_else(|| crate::util::path_file_name(&tag.path).to_string());
 
-        // Build a download URL for the file
-        let canon_str = canon_resolved.to_string_lossy();
-        let mut url = format!(
-            "/api/fs/download?path={}",
-            urlencoding::encode(canon_str.as_ref())
-        );
-        if let Some(ws_id) = workspace_id {
-            url.push_str(&format!("&workspace_id={}", ws_id));
+        if tag.tag_type == RichTagType::Dir {
+            let mut file = SharedFile::new(
+                display_name,
+                download_url("download-dir", &canon_resolved),
+                "application/zip",
+                None,
+            );
+            file.kind = SharedFileKind::Directory;
+            files.push(file);
+            continue;
         }
-        if let Some(mid) = mission_id {
-            url.push_str(&format!("&mission_id={}", mid));
+
+        let size = Some(meta.len());
+        let content_type = match tag.tag_type {
+            RichTagType::Diff => "text/x-diff; charset=utf-8",
+            _ => super::fs::content_type_for_path(&canon_resolved),
         }
+        .to_string();
+
+        let preview = match super::file_preview::generate(
+            &canon_resolved,
+            &content_type,
+            &previews_dir,
+        )
+        .await
+        {
+            Some(super::file_preview::Preview::Inline(preview)) => Some(preview),
+            Some(super::file_preview::Preview::Rendered(image)) => {
+                Some(super::file_preview::SharedFilePreview::Thumbnail {
+                    url: download_url("download", &image),
+                    content_type: "image/png".to_string(),
+                })
+            }
+            None => None,
+        };
 
-        files.push(SharedFile::new(display_name, url, content_type, size));
+        let mut file = SharedFile::new(
+            display_name,
+            download_url("download", &canon_resolved),
+            content_type,
+            size,
+        );
+        file.preview = preview;
+        files.push(file);
     }
     files
 }
@@ -2386,6 +2680,12 @@ pub enum AgentEvent {
         /// Whether the mission can be resumed after this failure (only relevant when success=false)
         #[serde(default, skip_serializing_if = "std::ops::Not::not")]
         resumable: bool,
+        /// Partial reply of a turn that was cancelled before finishing
+        #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+        cancelled: bool,
+        /// Tool calls that completed before the cancellation
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tool_calls: Vec<PartialToolCall>,
     },
     /// Agent thinking/reasoning (streaming)
     Thinking {
@@ -2421,8 +2721,39 @@ pub enum AgentEvent {
         #[serde(skip_serializing_if = "Option::is_none")]
         mission_id: Option<Uuid>,
     },
+    /// The agent asked the user structured questions and waits for the answer
+    UserQuestion {
+        tool_call_id: String,
+        questions: Vec<super::user_question::Question>,
+        /// Seconds until the questions' defaults are used
+        #[serde(skip_serializing_if = "Option::is_none")]
+        timeout_secs: Option<u64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mission_id: Option<Uuid>,
+    },
+    /// Questions were answered, by the user or by their defaults on timeout
+    UserQuestionAnswered {
+        tool_call_id: String,
+        questions: Vec<super::user_question::Question>,
+        answers: serde_json::Value,
+        timed_out: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mission_id: Option<Uuid>,
+    },
+    /// Output produced by a tool that is still running (not persisted)
+    ToolOutputDelta {
+        tool_call_id: String,
+        name: String,
+        stream: crate::tools::OutputStream,
+        content: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mission_id: Option<Uuid>,
+    },
     Error {
         message: String,
+        /// Machine-readable kind of error, when known
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        code: Option<ErrorCode>,
         /// Mission this error belongs to (for parallel execution)
         #[serde(skip_serializing_if = "Option::is_none")]
         mission_id: Option<Uuid>,
@@ -2490,6 +2821,173 @@ pub enum AgentEvent {
         /// Mission this session ID belongs to
         mission_id: Uuid,
     },
+    /// Token usage and cost of a single LLM call
+    LlmUsage {
+        /// Agent tree node that made the call (e.g. the subagent's Task tool call id);
+        /// None for the mission's root agent
+        #[serde(skip_serializing_if = "Option::is_none")]
+        node_id: Option<String>,
+        usage: crate::cost::TokenUsage,
+        cost_cents: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        model: Option<String>,
+        /// Tool calls requested by this LLM response: (tool_call_id, name)
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        tool_calls: Vec<(String, String)>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mission_id: Option<Uuid>,
+    },
+    /// Context window usage after an LLM call by the mission's root agent
+    ContextUsage {
+        /// Prompt tokens, including cached input
+        prompt_tokens: u64,
+        completion_tokens: u64,
+        /// Context window of the model, when known
+        #[serde(skip_serializing_if = "Option::is_none")]
+        context_window: Option<u64>,
+        /// Share of the context window used by the call, in percent
+        #[serde(skip_serializing_if = "Option::is_none")]
+        utilization_pct: Option<f64>,
+        /// Mission history entries preceding the current message
+        history_entries: usize,
+        /// Files in the mission's context directory
+        files_included: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        model: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mission_id: Option<Uuid>,
+    },
+    /// One candidate from best-of-N sampling
+    SamplingCandidate {
+        run_id: Uuid,
+        index: usize,
+        model: String,
+        temperature: f64,
+        content: String,
+        cost_cents: u64,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mission_id: Option<Uuid>,
+    },
+    /// Outcome of best-of-N sampling: verifier scores and the selected candidate
+    SamplingResult {
+        run_id: Uuid,
+        candidates: usize,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        selected: Option<usize>,
+        /// Verifier score per candidate index (None if unscored)
+        scores: Vec<Option<f64>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        rationale: Option<String>,
+        total_cost_cents: u64,
+        /// The cost cap was reached before verification
+        budget_exhausted: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mission_id: Option<Uuid>,
+    },
+    /// Outcome of a completion verification pass
+    CompletionVerification {
+        attempt: u32,
+        max_attempts: u32,
+        passed: bool,
+        checklist: Vec<super::completion_check::ChecklistItem>,
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        gaps: Vec<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        test: Option<super::completion_check::TestRun>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mission_id: Option<Uuid>,
+    },
+    /// A running mission made no progress for the configured stall period
+    MissionStalled {
+        mission_id: Uuid,
+        /// Seconds since the mission's last tool call or LLM output
+        idle_seconds: u64,
+        /// The stuck turn was interrupted and a status nudge queued
+        nudged: bool,
+        /// Minutes until the mission is cancelled if it stays stalled
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cancel_after_minutes: Option<u64>,
+    },
+    /// Tool processes left behind by a cancelled turn were terminated
+    ProcessesTerminated {
+        mission_id: Uuid,
+        /// PIDs that were signalled and have exited
+        reaped: Vec<u32>,
+        /// PIDs among `reaped` that ignored SIGTERM and were sent SIGKILL
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        killed: Vec<u32>,
+        /// PIDs still alive after SIGKILL
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        survivors: Vec<u32>,
+    },
+    /// A tool process tried to reach a host outside the workspace egress allowlist
+    EgressViolation {
+        mission_id: Uuid,
+        host: String,
+        port: u16,
+        /// The connection was refused (false when the policy only reports)
+        blocked: bool,
+    },
+    /// Clients viewing a mission changed (joined, left, started or stopped composing)
+    Presence {
+        mission_id: Uuid,
+        viewers: Vec<super::presence::Viewer>,
+    },
+    /// A mission needs the attention of some users (assigned, watched mission stopped)
+    MissionNotification {
+        mission_id: Uuid,
+        /// Usernames the notification is addressed to
+        recipients: Vec<String>,
+        kind: super::mission_assignment::MissionNotificationKind,
+        message: String,
+    },
+    /// A user's number of unread events on a mission changed (new output,
+    /// or the user read the mission)
+    MissionUnreadChanged {
+        mission_id: Uuid,
+        /// User the count is for
+        username: String,
+        unread_count: u64,
+    },
+    /// A comment on the mission was added, edited or deleted
+    MissionComment {
+        action: super::mission_comments::CommentAction,
+        comment: super::mission_store::MissionComment,
+    },
+    /// The mission was handed off to another backend
+    BackendSwitched {
+        mission_id: Uuid,
+        from: String,
+        to: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+    /// A file edit overlaps files written by other running missions
+    ConflictDetected {
+        path: String,
+        tool_call_id: String,
+        tool_name: String,
+        /// Running missions that already wrote `path`
+        other_missions: Vec<Uuid>,
+        /// The editing mission's turn was cancelled (policy `block`)
+        blocked: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mission_id: Option<Uuid>,
+    },
+    /// File upload progress (streamed multipart uploads into a mission workspace)
+    UploadProgress {
+        upload_id: Uuid,
+        file_name: String,
+        bytes_received: u64,
+        /// Declared request size (includes multipart framing), if known
+        #[serde(skip_serializing_if = "Option::is_none")]
+        total_bytes: Option<u64>,
+        done: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        mission_id: Option<Uuid>,
+    },
     /// Live activity label derived from the current tool call
     MissionActivity {
         /// Human-readable activity label (e.g., "Reading: main.rs")
@@ -2500,6 +2998,46 @@ pub enum AgentEvent {
         #[serde(skip_serializing_if = "Option::is_none")]
         mission_id: Option<Uuid>,
     },
+    /// A mission's desktop session was opened, kept alive, captured or closed
+    DesktopSession {
+        mission_id: Uuid,
+        display: String,
+        action: super::desktop::DesktopSessionAction,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        keep_alive_until: Option<String>,
+    },
+    /// The agent asked to change the mission's status; waiting for the
+    /// user's confirmation
+    StatusChangeRequested {
+        mission_id: Uuid,
+        status: MissionStatus,
+        justification: crate::tools::mission::StatusJustification,
+    },
+    /// A requested status change was confirmed or declined
+    StatusChangeResolved {
+        mission_id: Uuid,
+        status: MissionStatus,
+        confirmed: bool,
+    },
+    /// Follow-ups were suggested for a completed mission
+    FollowUpsSuggested {
+        mission_id: Uuid,
+        follow_ups: Vec<super::mission_store::FollowUp>,
+    },
+    /// A dangerous shell command is waiting for the user's approval; the
+    /// mission's turn was cancelled before it ran
+    ApprovalRequired {
+        tool_call_id: String,
+        command: String,
+        reason: String,
+        mission_id: Uuid,
+    },
+    /// The user approved or denied a command
+    ApprovalResolved {
+        tool_call_id: String,
+        approved: bool,
+        mission_id: Uuid,
+    },
 }
 
 /// A node in the agent tree (for visualization)
@@ -2517,10 +3055,34 @@ pub struct AgentTreeNode {
     pub complexity: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub selected_model: Option<String>,
+    /// Cost of LLM calls attributed to this node (excluding children), in cents
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub cost_cents: u64,
+    /// Token usage of LLM calls attributed to this node (excluding children)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub usage: Option<crate::cost::TokenUsage>,
+    /// Number of LLM calls attributed to this node
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub llm_calls: u64,
+    /// Cost of the LLM calls that requested each tool call made by this node
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_costs: Vec<ToolCallCost>,
     #[serde(default)]
     pub children: Vec<AgentTreeNode>,
 }
 
+fn is_zero(value: &u64) -> bool {
+    *value == 0
+}
+
+/// Cost attributed to a single tool call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCallCost {
+    pub tool_call_id: String,
+    pub name: String,
+    pub cost_cents: u64,
+}
+
 impl AgentTreeNode {
     pub fn new(id: &str, node_type: &str, name: &str, description: &str) -> Self {
         Self {
@@ -2533,6 +3095,10 @@ impl AgentTreeNode {
             budget_spent: 0,
             complexity: None,
             selected_model: None,
+            cost_cents: 0,
+            usage: None,
+            llm_calls: 0,
+            tool_costs: Vec::new(),
             children: Vec::new(),
         }
     }
@@ -2573,6 +3139,9 @@ impl AgentEvent {
             AgentEvent::TextDelta { .. } => "text_delta",
             AgentEvent::ToolCall { .. } => "tool_call",
             AgentEvent::ToolResult { .. } => "tool_result",
+            AgentEvent::UserQuestion { .. } => "user_question",
+            AgentEvent::UserQuestionAnswered { .. } => "user_question_answered",
+            AgentEvent::ToolOutputDelta { .. } => "tool_output_delta",
             AgentEvent::Error { .. } => "error",
             AgentEvent::MissionStatusChanged { .. } => "mission_status_changed",
             AgentEvent::AgentPhase { .. } => "agent_phase",
@@ -2580,8 +3149,29 @@ impl AgentEvent {
             AgentEvent::Progress { .. } => "progress",
             AgentEvent::SessionIdUpdate { .. } => "session_id_update",
             AgentEvent::MissionActivity { .. } => "mission_activity",
+            AgentEvent::UploadProgress { .. } => "upload_progress",
+            AgentEvent::LlmUsage { .. } => "llm_usage",
+            AgentEvent::ContextUsage { .. } => "context_usage",
+            AgentEvent::SamplingCandidate { .. } => "sampling_candidate",
+            AgentEvent::SamplingResult { .. } => "sampling_result",
+            AgentEvent::CompletionVerification { .. } => "completion_verification",
+            AgentEvent::ConflictDetected { .. } => "conflict_detected",
+            AgentEvent::MissionStalled { .. } => "mission_stalled",
+            AgentEvent::ProcessesTerminated { .. } => "processes_terminated",
+            AgentEvent::EgressViolation { .. } => "egress_violation",
+            AgentEvent::Presence { .. } => "presence",
+            AgentEvent::MissionNotification { .. } => "mission_notification",
+            AgentEvent::MissionUnreadChanged { .. } => "mission_unread_changed",
+            AgentEvent::MissionComment { .. } => "mission_comment",
+            AgentEvent::BackendSwitched { .. } => "backend_switched",
             AgentEvent::MissionTitleChanged { .. } => "mission_title_changed",
             AgentEvent::MissionMetadataUpdated { .. } => "mission_metadata_updated",
+            AgentEvent::DesktopSession { .. } => "desktop_session",
+            AgentEvent::ApprovalRequired { .. } => "approval_required",
+            AgentEvent::ApprovalResolved { .. } => "approval_resolved",
+            AgentEvent::StatusChangeRequested { .. } => "status_change_requested",
+            AgentEvent::StatusChangeResolved { .. } => "status_change_resolved",
+            AgentEvent::FollowUpsSuggested { .. } => "follow_ups_suggested",
         }
     }
 
@@ -2594,6 +3184,9 @@ impl AgentEvent {
             AgentEvent::TextDelta { mission_id, .. } => *mission_id,
             AgentEvent::ToolCall { mission_id, .. } => *mission_id,
             AgentEvent::ToolResult { mission_id, .. } => *mission_id,
+            AgentEvent::UserQuestion { mission_id, .. } => *mission_id,
+            AgentEvent::UserQuestionAnswered { mission_id, .. } => *mission_id,
+            AgentEvent::ToolOutputDelta { mission_id, .. } => *mission_id,
             AgentEvent::Error { mission_id, .. } => *mission_id,
             AgentEvent::MissionStatusChanged { mission_id, .. } => Some(*mission_id),
             AgentEvent::AgentPhase { mission_id, .. } => *mission_id,
@@ -2601,8 +3194,29 @@ impl AgentEvent {
             AgentEvent::Progress { mission_id, .. } => *mission_id,
             AgentEvent::SessionIdUpdate { mission_id, .. } => Some(*mission_id),
             AgentEvent::MissionActivity { mission_id, .. } => *mission_id,
+            AgentEvent::UploadProgress { mission_id, .. } => *mission_id,
+            AgentEvent::LlmUsage { mission_id, .. } => *mission_id,
+            AgentEvent::ContextUsage { mission_id, .. } => *mission_id,
+            AgentEvent::SamplingCandidate { mission_id, .. } => *mission_id,
+            AgentEvent::SamplingResult { mission_id, .. } => *mission_id,
+            AgentEvent::CompletionVerification { mission_id, .. } => *mission_id,
+            AgentEvent::ConflictDetected { mission_id, .. } => *mission_id,
+            AgentEvent::MissionStalled { mission_id, .. } => Some(*mission_id),
+            AgentEvent::ProcessesTerminated { mission_id, .. } => Some(*mission_id),
+            AgentEvent::EgressViolation { mission_id, .. } => Some(*mission_id),
+            AgentEvent::Presence { mission_id, .. } => Some(*mission_id),
+            AgentEvent::MissionNotification { mission_id, .. } => Some(*mission_id),
+            AgentEvent::MissionUnreadChanged { mission_id, .. } => Some(*mission_id),
+            AgentEvent::MissionComment { comment, .. } => Some(comment.mission_id),
+            AgentEvent::BackendSwitched { mission_id, .. } => Some(*mission_id),
             AgentEvent::MissionTitleChanged { mission_id, .. } => Some(*mission_id),
             AgentEvent::MissionMetadataUpdated { mission_id, .. } => Some(*mission_id),
+            AgentEvent::DesktopSession { mission_id, .. } => Some(*mission_id),
+            AgentEvent::ApprovalRequired { mission_id, .. } => Some(*mission_id),
+            AgentEvent::ApprovalResolved { mission_id, .. } => Some(*mission_id),
+            AgentEvent::StatusChangeRequested { mission_id, .. } => Some(*mission_id),
+            AgentEvent::StatusChangeResolved { mission_id, .. } => Some(*mission_id),
+            AgentEvent::FollowUpsSuggested { mission_id, .. } => Some(*mission_id),
         }
     }
 }
@@ -2625,6 +3239,16 @@ pub enum ControlCommand {
         name: String,
         result: serde_json::Value,
     },
+    /// Claim an `Idempotency-Key` before executing a request
+    ClaimIdempotencyKey {
+        key: String,
+        respond: oneshot::Sender<IdempotencyClaim>,
+    },
+    /// Record the response for a claimed key (None releases the key)
+    CompleteIdempotencyKey {
+        key: String,
+        response: Option<serde_json::Value>,
+    },
     Cancel,
     /// Load a mission (switch to it)
     LoadMission {
@@ -2700,6 +3324,18 @@ pub enum ControlCommand {
     ClearQueue {
         respond: oneshot::Sender<usize>, // number of messages cleared
     },
+    /// Move a queued message to a new position (0 = next to run) in its queue
+    ReorderQueue {
+        message_id: Uuid,
+        position: usize,
+        respond: oneshot::Sender<bool>, // true if moved, false if not found
+    },
+    /// Replace the content of a queued message before it runs
+    EditQueuedMessage {
+        message_id: Uuid,
+        content: String,
+        respond: oneshot::Sender<bool>, // true if edited, false if not found
+    },
 }
 
 // ==================== Mission Types ====================
@@ -2721,6 +3357,18 @@ pub enum MissionStatus {
     NotFeasible,
 }
 
+impl From<crate::tools::mission::MissionStatusValue> for MissionStatus {
+    fn from(value: crate::tools::mission::MissionStatusValue) -> Self {
+        use crate::tools::mission::MissionStatusValue;
+        match value {
+            MissionStatusValue::Completed => MissionStatus::Completed,
+            MissionStatusValue::Failed => MissionStatus::Failed,
+            MissionStatusValue::Blocked => MissionStatus::Blocked,
+            MissionStatusValue::NotFeasible => MissionStatus::NotFeasible,
+        }
+    }
+}
+
 impl std::fmt::Display for MissionStatus {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -2837,6 +3485,12 @@ impl FrontendToolHub {
         early.insert(tool_call_id.to_string(), result);
         Ok(())
     }
+
+    /// Drop a pending tool call nobody will answer any more (timed out or
+    /// cancelled), so a late result is not delivered to a closed receiver.
+    pub async fn forget(&self, tool_call_id: &str) {
+        self.pending.lock().await.remove(tool_call_id);
+    }
 }
 
 impl Default for FrontendToolHub {
@@ -2850,6 +3504,8 @@ impl Default for FrontendToolHub {
 pub struct ControlState {
     pub cmd_tx: mpsc::Sender<ControlCommand>,
     pub events_tx: broadcast::Sender<AgentEvent>,
+    /// Sequenced, bounded copy of `events_tx` that SSE clients read from
+    pub event_log: Arc<EventLog>,
     pub tool_hub: Arc<FrontendToolHub>,
     pub status: Arc<RwLock<ControlStatus>>,
     /// Current mission ID (if any) - primary mission in the old sequential model
@@ -2866,6 +3522,24 @@ pub struct ControlState {
     pub mission_store: Arc<dyn MissionStore>,
     /// Cache for semantic mission search results keyed by normalized query hash
     pub mission_search_cache: Arc<RwLock<HashMap<u64, MissionSearchCacheEntry>>>,
+    /// Clients viewing each mission
+    pub presence: presence::SharedPresence,
+    /// Load-aware deferral of interval automations
+    pub automation_throttle: automation_throttle::SharedAutomationThrottle,
+    /// Dangerous shell commands waiting for the user's approval
+    pub approvals: command_approval::SharedCommandApprovals,
+    /// Workspaces of the session's missions (approvals are recorded in the
+    /// mission directory)
+    pub workspaces: workspace::SharedWorkspaceStore,
+    /// Agent status changes waiting for the user's confirmation
+    pub status_requests: status_requests::SharedStatusRequests,
+    /// Missions whose progress comes from their checklist
+    pub checklists: mission_checklist::SharedChecklists,
+    /// Agent-initiated mission control commands (confirmed status changes
+    /// are applied through it)
+    pub mission_cmd_tx: mpsc::Sender<crate::tools::mission::MissionControlCommand>,
+    /// Background refreshes of mission titles and short descriptions
+    pub metadata_refresh: metadata_refresh::SharedMetadataRefresh,
 }
 
 /// Control session manager for per-user sessions.
@@ -2877,6 +3551,7 @@ pub struct ControlHub {
     mcp: Arc<McpRegistry>,
     workspaces: workspace::SharedWorkspaceStore,
     library: SharedLibrary,
+    tenant_libraries: crate::tenant::SharedTenantLibraries,
     secrets: Option<Arc<SecretsStore>>,
 }
 
@@ -2887,6 +3562,7 @@ impl ControlHub {
         mcp: Arc<McpRegistry>,
         workspaces: workspace::SharedWorkspaceStore,
         library: SharedLibrary,
+        tenant_libraries: crate::tenant::SharedTenantLibraries,
         secrets: Option<Arc<SecretsStore>>,
     ) -> Self {
         Self {
@@ -2896,29 +3572,47 @@ impl ControlHub {
             mcp,
             workspaces,
             library,
+            tenant_libraries,
             secrets,
         }
     }
 
     pub async fn get_or_spawn(&self, user: &AuthUser) -> ControlState {
-        if let Some(existing) = self.sessions.read().await.get(&user.id).cloned() {
+        let key = crate::tenant::session_key(&user.id, user.tenant.as_deref());
+        if let Some(existing) = self.sessions.read().await.get(&key).cloned() {
             return existing;
         }
         let mut sessions = self.sessions.write().await;
-        if let Some(existing) = sessions.get(&user.id).cloned() {
+        if let Some(existing) = sessions.get(&key).cloned() {
             return existing;
         }
 
-        // Get mission store type from environment (default: SQLite)
-        let store_type = std::env::var("MISSION_STORE_TYPE")
-            .map(|s| MissionStoreType::from_str(&s))
-            .unwrap_or(MissionStoreType::Sqlite);
+        // Tenant users get the tenant's config overrides, stores and library.
+        let tenant = user
+            .tenant
+            .as_deref()
+            .and_then(|id| self.config.find_tenant(id));
+        let (config, library, base_dir) = match tenant {
+            Some(tenant) => (
+                tenant.scoped_config(&self.config),
+                self.tenant_libraries.slot(&tenant.id).await,
+                tenant.missions_dir(&self.config.working_dir),
+            ),
+            None => (
+                self.config.clone(),
+                Arc::clone(&self.library),
+                self.config
+                    .working_dir
+                    .join(".sandboxed-sh")
+                    .join("missions"),
+            ),
+        };
+
+        // Get mission store type from environment (default: SQLite)
+        let store_type = std::env::var("MISSION_STORE_TYPE")
+            .map(|s| MissionStoreType::from_str(&s))
+            .unwrap_or(MissionStoreType::Sqlite);
 
-        let base_dir = self
-            .config
-            .working_dir
-            .join(".sandboxed-sh")
-            .join("missions");
         let mission_store: Arc<dyn MissionStore> =
             match create_mission_store(store_type, base_dir, &user.id).await {
                 Ok(store) => Arc::from(store),
@@ -2933,15 +3627,16 @@ impl ControlHub {
             };
 
         let state = spawn_control_session(
-            self.config.clone(),
+            &key,
+            config,
             Arc::clone(&self.root_agent),
             Arc::clone(&self.mcp),
             Arc::clone(&self.workspaces),
-            Arc::clone(&self.library),
+            library,
             mission_store,
             self.secrets.clone(),
         );
-        sessions.insert(user.id.clone(), state.clone());
+        sessions.insert(key, state.clone());
         state
     }
 
@@ -2949,6 +3644,17 @@ impl ControlHub {
         self.sessions.read().await.values().cloned().collect()
     }
 
+    /// All sessions with their session keys (user id, prefixed by the tenant
+    /// for tenant users).
+    pub async fn sessions_by_key(&self) -> Vec<(String, ControlState)> {
+        self.sessions
+            .read()
+            .await
+            .iter()
+            .map(|(key, state)| (key.clone(), state.clone()))
+            .collect()
+    }
+
     /// Get a mission store for desktop management.
     /// Uses the default user's store if available, or creates a temporary one.
     pub async fn get_mission_store(&self) -> Arc<dyn MissionStore> {
@@ -3025,12 +3731,35 @@ async fn control_for_user(state: &Arc<AppState>, user: &AuthUser) -> ControlStat
     state.control.get_or_spawn(user).await
 }
 
+/// The user's control session, once `mission_id` is known to be one of the
+/// user's missions (`404` otherwise).
+pub(crate) async fn control_for_mission(
+    state: &Arc<AppState>,
+    user: &AuthUser,
+    mission_id: Uuid,
+) -> Result<ControlState, (StatusCode, String)> {
+    let control = control_for_user(state, user).await;
+    control
+        .mission_store
+        .get_mission(mission_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Mission {} not found", mission_id),
+            )
+        })?;
+    Ok(control)
+}
+
 /// Enqueue a user message for the global control session.
 /// If mission_id is provided and differs from the currently running mission,
 /// the backend will automatically start it in parallel (if capacity allows).
 pub async fn post_message(
     State(state): State<Arc<AppState>>,
     Extension(user): Extension<AuthUser>,
+    headers: HeaderMap,
     Json(req): Json<ControlMessageRequest>,
 ) -> Result<Json<ControlMessageResponse>, (StatusCode, String)> {
     let content = req.content.trim().to_string();
@@ -3038,10 +3767,24 @@ pub async fn post_message(
         return Err((StatusCode::BAD_REQUEST, "content is required".to_string()));
     }
 
-    let id = Uuid::new_v4();
-    let agent = req.agent;
-    let target_mission_id = req.mission_id;
     let control = control_for_user(&state, &user).await;
+    let idempotency_key = match claim_idempotency_key(&control, &headers, "message").await? {
+        IdempotentRequest::Replay(response) => return replay_response(response),
+        IdempotentRequest::Execute(key) => key,
+    };
+    let result = send_user_message(&control, &user, content, req.agent, req.mission_id).await;
+    idempotency_key.complete(&result).await;
+    result
+}
+
+pub(crate) async fn send_user_message(
+    control: &ControlState,
+    user: &AuthUser,
+    content: String,
+    agent: Option<String>,
+    target_mission_id: Option<Uuid>,
+) -> Result<Json<ControlMessageResponse>, (StatusCode, String)> {
+    let id = Uuid::new_v4();
     let (queued_tx, queued_rx) = oneshot::channel();
     tracing::info!(
         user_id = %user.id,
@@ -3190,21 +3933,549 @@ pub async fn clear_queue(
     Ok(Json(serde_json::json!({ "ok": true, "cleared": cleared })))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ReorderQueueRequest {
+    /// New position in the queue; 0 runs the message next
+    pub position: usize,
+}
+
+/// Move a queued message ahead of (or behind) other queued messages.
+pub async fn reorder_queue(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(message_id): Path<Uuid>,
+    Json(req): Json<ReorderQueueRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let control = control_for_user(&state, &user).await;
+    let (tx, rx) = oneshot::channel();
+    control
+        .cmd_tx
+        .send(ControlCommand::ReorderQueue {
+            message_id,
+            position: req.position,
+            respond: tx,
+        })
+        .await
+        .map_err(session_unavailable)?;
+    let moved = rx.await.map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "failed to reorder queue".to_string(),
+        )
+    })?;
+    if moved {
+        Ok(ok_json())
+    } else {
+        Err((StatusCode::NOT_FOUND, "message not in queue".to_string()))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EditQueuedMessageRequest {
+    pub content: String,
+}
+
+/// Edit a queued message before it runs.
+pub async fn edit_queued_message(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(message_id): Path<Uuid>,
+    Json(req): Json<EditQueuedMessageRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    if req.content.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "content must not be empty".to_string(),
+        ));
+    }
+    let control = control_for_user(&state, &user).await;
+    let (tx, rx) = oneshot::channel();
+    control
+        .cmd_tx
+        .send(ControlCommand::EditQueuedMessage {
+            message_id,
+            content: req.content,
+            respond: tx,
+        })
+        .await
+        .map_err(session_unavailable)?;
+    let edited = rx.await.map_err(|_| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "failed to edit queued message".to_string(),
+        )
+    })?;
+    if edited {
+        Ok(ok_json())
+    } else {
+        Err((StatusCode::NOT_FOUND, "message not in queue".to_string()))
+    }
+}
+
 // ==================== Mission Endpoints ====================
 
-/// List all missions.
+#[derive(Debug, Deserialize, Default)]
+pub struct ListMissionsQuery {
+    /// Comma-separated tags; missions must carry all of them
+    pub tag: Option<String>,
+    pub status: Option<MissionStatus>,
+    /// List archived missions instead of unarchived ones
+    #[serde(default)]
+    pub archived: bool,
+    /// Missions assigned to this user (`me` for the current user, `none` for unassigned)
+    pub assignee: Option<String>,
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+}
+
+/// Build a mission filter from `tag` (comma-separated) and `status` query params.
+fn mission_filter(tag: Option<&str>, status: Option<MissionStatus>) -> MissionFilter {
+    let tags: Vec<&str> = tag.map(|t| t.split(',').collect()).unwrap_or_default();
+    MissionFilter {
+        tags: normalize_tags(&tags),
+        status,
+        ..Default::default()
+    }
+}
+
+fn mission_keyset(mission: &Mission) -> Keyset {
+    Keyset {
+        sort_key: mission.updated_at.clone(),
+        id: mission.id,
+    }
+}
+
+/// List missions, pinned first, optionally filtered by `?tag=deploy&status=failed`.
+/// Archived missions are excluded unless `archived=true`. Paginated with
+/// `limit`/`cursor` (see [`super::pagination`]).
 pub async fn list_missions(
     State(state): State<Arc<AppState>>,
     Extension(user): Extension<AuthUser>,
-) -> Result<Json<Vec<Mission>>, (StatusCode, String)> {
+    Query(params): Query<ListMissionsQuery>,
+) -> Result<Page<Mission>, (StatusCode, String)> {
     let control = control_for_user(&state, &user).await;
-    let mut missions = control
-        .mission_store
-        .list_missions(50, 0)
+    let store = &control.mission_store;
+    let page = PageQuery {
+        limit: params.limit,
+        cursor: params.cursor,
+    };
+    let limit = page.limit(50);
+    let cursor = page.cursor()?;
+    let filter = MissionFilter {
+        archived: Some(params.archived),
+        assignee: params.assignee.as_deref().map(|assignee| match assignee {
+            "me" => user.username.clone(),
+            "none" => String::new(),
+            other => other.to_string(),
+        }),
+        ..mission_filter(params.tag.as_deref(), params.status)
+    };
+    let total = store
+        .count_missions_filtered(&filter)
         .await
         .map_err(internal_error)?;
+
+    // Pinned missions come first; a cursor outside the pinned section skips it.
+    let mut missions = Vec::new();
+    let mut next = None;
+    let in_pinned_section = !matches!(&cursor, Some(c) if !c.pinned);
+    if in_pinned_section {
+        let pinned_filter = MissionFilter {
+            pinned: Some(true),
+            before: cursor.as_ref().map(|c| c.keyset.clone()),
+            ..filter.clone()
+        };
+        let page = store
+            .list_missions_filtered(&pinned_filter, limit + 1, 0)
+            .await
+            .map_err(internal_error)?;
+        let (page, more) = split_page(page, limit, mission_keyset);
+        missions = page;
+        next = more.map(|keyset| Cursor {
+            keyset,
+            pinned: true,
+        });
+    }
+    if next.is_none() {
+        let remaining = limit - missions.len();
+        let unpinned_filter = MissionFilter {
+            pinned: Some(false),
+            before: cursor.filter(|c| !c.pinned).map(|c| c.keyset),
+            ..filter
+        };
+        let page = store
+            .list_missions_filtered(&unpinned_filter, remaining + 1, 0)
+            .await
+            .map_err(internal_error)?;
+        let has_more = page.len() > remaining;
+        let (page, more) = split_page(page, remaining, mission_keyset);
+        next = match (more, missions.last()) {
+            (Some(keyset), _) => Some(Cursor::new(keyset)),
+            // The pinned section filled this page exactly; resume after its
+            // last mission so the next page starts at the unpinned ones.
+            (None, Some(last)) if has_more => Some(Cursor {
+                keyset: mission_keyset(last),
+                pinned: true,
+            }),
+            (None, _) => None,
+        };
+        missions.extend(page);
+    }
     populate_workspace_names(&state, &mut missions).await;
-    Ok(Json(missions))
+    if store.is_persistent() {
+        if let Err(e) = super::mission_reads::populate_unread_counts(
+            store.as_ref(),
+            &user.username,
+            &mut missions,
+        )
+        .await
+        {
+            tracing::warn!("Failed to count unread mission events: {}", e);
+        }
+    }
+    Ok((page_headers(total, next.as_ref()), Json(missions)))
+}
+
+/// Set a mission's pinned/archived flags and return the updated mission.
+async fn set_mission_flags(
+    state: &Arc<AppState>,
+    user: &AuthUser,
+    id: Uuid,
+    pinned: Option<bool>,
+    archived: Option<bool>,
+) -> Result<Json<Mission>, (StatusCode, String)> {
+    let control = control_for_user(state, user).await;
+    let store = &control.mission_store;
+    let mut mission = store
+        .get_mission(id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Mission {} not found", id)))?;
+    store
+        .update_mission_flags(id, pinned, archived)
+        .await
+        .map_err(internal_error)?;
+    mission.pinned = pinned.unwrap_or(mission.pinned);
+    mission.archived = archived.unwrap_or(mission.archived);
+    Ok(Json(mission))
+}
+
+/// Pin a mission to the top of the missions list.
+pub async fn pin_mission(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Mission>, (StatusCode, String)> {
+    set_mission_flags(&state, &user, id, Some(true), None).await
+}
+
+/// Unpin a mission.
+pub async fn unpin_mission(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Mission>, (StatusCode, String)> {
+    set_mission_flags(&state, &user, id, Some(false), None).await
+}
+
+/// Archive a mission (hidden from the missions list by default).
+pub async fn archive_mission(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Mission>, (StatusCode, String)> {
+    set_mission_flags(&state, &user, id, None, Some(true)).await
+}
+
+/// Unarchive a mission.
+pub async fn unarchive_mission(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Mission>, (StatusCode, String)> {
+    set_mission_flags(&state, &user, id, None, Some(false)).await
+}
+
+/// Request body for archiving missions in bulk.
+#[derive(Debug, Deserialize, Default)]
+pub struct BulkArchiveRequest {
+    /// Only archive missions not updated for this many days (default 30)
+    pub older_than_days: Option<u64>,
+    /// Only archive missions in these statuses (default: all terminal statuses)
+    pub statuses: Option<Vec<MissionStatus>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkArchiveResponse {
+    pub archived: usize,
+    pub mission_ids: Vec<Uuid>,
+}
+
+/// Statuses a mission can be bulk-archived in; pending and active missions
+/// are never archived in bulk.
+fn bulk_archivable(status: MissionStatus) -> bool {
+    !matches!(status, MissionStatus::Pending | MissionStatus::Active)
+}
+
+/// Archive unpinned missions by age and status.
+pub async fn bulk_archive_missions(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    body: Option<Json<BulkArchiveRequest>>,
+) -> Result<Json<BulkArchiveResponse>, (StatusCode, String)> {
+    const PAGE_SIZE: usize = 200;
+    let req = body.map(|Json(req)| req).unwrap_or_default();
+    if let Some(status) = req
+        .statuses
+        .iter()
+        .flatten()
+        .find(|status| !bulk_archivable(**status))
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Missions with status '{}' cannot be bulk-archived", status),
+        ));
+    }
+    let days = i64::try_from(req.older_than_days.unwrap_or(30)).unwrap_or(i64::MAX);
+    let cutoff = chrono::Utc::now() - chrono::Duration::days(days.min(365_000));
+
+    let control = control_for_user(&state, &user).await;
+    let store = &control.mission_store;
+    let filter = MissionFilter {
+        pinned: Some(false),
+        archived: Some(false),
+        ..Default::default()
+    };
+    let mut mission_ids = Vec::new();
+    let mut offset = 0;
+    loop {
+        let page = store
+            .list_missions_filtered(&filter, PAGE_SIZE, offset)
+            .await
+            .map_err(internal_error)?;
+        let page_len = page.len();
+        mission_ids.extend(
+            page.into_iter()
+                .filter(|mission| match &req.statuses {
+                    Some(statuses) => statuses.contains(&mission.status),
+                    None => bulk_archivable(mission.status),
+                })
+                .filter(|mission| {
+                    chrono::DateTime::parse_from_rfc3339(&mission.updated_at)
+                        .is_ok_and(|updated| updated < cutoff)
+                })
+                .map(|mission| mission.id),
+        );
+        if page_len < PAGE_SIZE {
+            break;
+        }
+        offset += page_len;
+    }
+
+    for id in &mission_ids {
+        store
+            .update_mission_flags(*id, None, Some(true))
+            .await
+            .map_err(internal_error)?;
+    }
+    Ok(Json(BulkArchiveResponse {
+        archived: mission_ids.len(),
+        mission_ids,
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetMissionTagsRequest {
+    pub tags: Vec<String>,
+}
+
+/// Replace a mission's tags.
+pub async fn set_mission_tags(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<SetMissionTagsRequest>,
+) -> Result<Json<Mission>, (StatusCode, String)> {
+    let control = control_for_user(&state, &user).await;
+    let store = &control.mission_store;
+    let mut mission = store
+        .get_mission(id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Mission {} not found", id)))?;
+    let tags = normalize_tags(&req.tags);
+    store
+        .update_mission_tags(id, &tags)
+        .await
+        .map_err(internal_error)?;
+    mission.tags = tags;
+    Ok(Json(mission))
+}
+
+/// Run a Kubernetes operation for a mission, using the cluster access
+/// configured on the mission's workspace. The kubeconfig is read from the
+/// secrets vault here and never leaves the backend.
+pub async fn mission_kubernetes(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<crate::kubernetes::KubeRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let control = control_for_user(&state, &user).await;
+    let mission = control
+        .mission_store
+        .get_mission(id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Mission {} not found", id)))?;
+    let workspace = state
+        .workspaces
+        .get(mission.workspace_id)
+        .await
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Workspace {} not found", mission.workspace_id),
+            )
+        })?;
+    let access = workspace.kubernetes.ok_or_else(|| {
+        (
+            StatusCode::FORBIDDEN,
+            format!(
+                "Workspace '{}' has no Kubernetes access configured",
+                workspace.name
+            ),
+        )
+    })?;
+    crate::kubernetes::authorize(&access, &req)
+        .map_err(|e| (StatusCode::FORBIDDEN, e.to_string()))?;
+
+    let secrets = state.secrets.as_ref().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Secrets store is not available".to_string(),
+        )
+    })?;
+    let kubeconfig = secrets
+        .get_secret(crate::kubernetes::KUBECONFIG_REGISTRY, &access.secret)
+        .await
+        .map_err(|e| {
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                format!("Failed to load kubeconfig '{}': {}", access.secret, e),
+            )
+        })?;
+    let client =
+        crate::kubernetes::KubeClient::from_kubeconfig(&kubeconfig, access.context.as_deref())
+            .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+
+    tracing::info!(
+        mission_id = %id,
+        workspace = %workspace.name,
+        verb = ?req.verb,
+        resource = ?req.resource,
+        namespace = ?req.namespace,
+        "Kubernetes request"
+    );
+    let result = crate::kubernetes::execute(&client, &access, &req)
+        .await
+        .map_err(|e| (StatusCode::BAD_GATEWAY, e.to_string()))?;
+    Ok(Json(result))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct MissionSshRequest {
+    /// Allowlisted host name from the workspace's SSH settings.
+    pub host: String,
+    pub command: String,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+/// Run a command on one of the workspace's allowlisted SSH hosts. The private
+/// key is read from the secrets vault here, and every attempt is written to
+/// the audit log.
+pub async fn mission_ssh(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<MissionSshRequest>,
+) -> Result<Json<crate::ssh::SshOutput>, (StatusCode, String)> {
+    let control = control_for_user(&state, &user).await;
+    let mission = control
+        .mission_store
+        .get_mission(id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Mission {} not found", id)))?;
+    let workspace = state
+        .workspaces
+        .get(mission.workspace_id)
+        .await
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Workspace {} not found", mission.workspace_id),
+            )
+        })?;
+    if req.command.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "Command is empty".to_string()));
+    }
+    let access = workspace.ssh.clone().unwrap_or_default();
+    let host = match access.host(&req.host) {
+        Ok(host) => host,
+        Err(e) => {
+            let mut entry =
+                super::audit::AuditEntry::new("ssh", req.host.clone(), req.command.clone());
+            entry.mission_id = Some(id);
+            entry.workspace = Some(workspace.name.clone());
+            entry.detail = Some("host not allowlisted".to_string());
+            state.audit.record(&entry).await;
+            return Err((StatusCode::FORBIDDEN, e.to_string()));
+        }
+    };
+
+    let mut entry = super::audit::AuditEntry::new("ssh", host.target(), req.command.clone());
+    entry.mission_id = Some(id);
+    entry.workspace = Some(workspace.name.clone());
+
+    let key = match state.secrets.as_ref() {
+        Some(secrets) => secrets
+            .get_secret(crate::ssh::SSH_KEY_REGISTRY, &host.key_secret)
+            .await
+            .map_err(|e| format!("Failed to load SSH key '{}': {}", host.key_secret, e)),
+        None => Err("Secrets store is not available".to_string()),
+    };
+    let key = match key {
+        Ok(key) => key,
+        Err(message) => {
+            entry.detail = Some(message.clone());
+            state.audit.record(&entry).await;
+            return Err((StatusCode::SERVICE_UNAVAILABLE, message));
+        }
+    };
+
+    let timeout = std::time::Duration::from_secs(
+        req.timeout_secs
+            .unwrap_or(crate::ssh::DEFAULT_TIMEOUT_SECS)
+            .clamp(1, crate::ssh::MAX_TIMEOUT_SECS),
+    );
+    let result = crate::ssh::execute(host, &key, &req.command, timeout).await;
+    match &result {
+        Ok(output) => {
+            entry.success = output.exit_code == Some(0);
+            entry.exit_code = output.exit_code;
+            entry.duration_ms = output.duration_ms;
+            if output.timed_out {
+                entry.detail = Some("timed out".to_string());
+            }
+        }
+        Err(e) => entry.detail = Some(e.to_string()),
+    }
+    state.audit.record(&entry).await;
+    result
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_GATEWAY, e.to_string()))
 }
 
 async fn populate_workspace_names(state: &Arc<AppState>, missions: &mut [Mission]) {
@@ -3284,10 +4555,57 @@ async fn list_missions_for_search(
     Ok(all)
 }
 
+/// Ranked results a mission search pages through, at most.
+const SEARCH_RESULTS_MAX: usize = 500;
+
 #[derive(Debug, Deserialize)]
 pub struct SearchMissionsQuery {
     pub q: String,
     pub limit: Option<usize>,
+    /// `X-Next-Cursor` of the previous page
+    pub cursor: Option<String>,
+    /// Comma-separated tags; results must carry all of them
+    pub tag: Option<String>,
+    pub status: Option<MissionStatus>,
+    /// Return `{ results, facets }` with tag counts instead of a bare list
+    #[serde(default)]
+    pub facets: bool,
+}
+
+/// Number of search results carrying a tag.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TagFacet {
+    pub tag: String,
+    pub count: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum SearchMissionsResponse {
+    Results(Vec<MissionSearchResult>),
+    WithFacets {
+        results: Vec<MissionSearchResult>,
+        facets: Vec<TagFacet>,
+    },
+}
+
+/// Tag counts across `missions`, most common first.
+fn tag_facets<'a>(missions: impl Iterator<Item = &'a Mission>) -> Vec<TagFacet> {
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for mission in missions {
+        for tag in &mission.tags {
+            *counts.entry(tag.as_str()).or_default() += 1;
+        }
+    }
+    let mut facets: Vec<TagFacet> = counts
+        .into_iter()
+        .map(|(tag, count)| TagFacet {
+            tag: tag.to_string(),
+            count,
+        })
+        .collect();
+    facets.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.tag.cmp(&b.tag)));
+    facets
 }
 
 #[derive(Debug, Deserialize)]
@@ -3295,12 +4613,25 @@ pub struct SearchMissionMomentsQuery {
     pub q: String,
     pub mission_id: Option<Uuid>,
     pub limit: Option<usize>,
+    /// `X-Next-Cursor` of the previous page
+    pub cursor: Option<String>,
+}
+
+/// Why a search result matched: a snippet of the matching field, HTML-escaped
+/// with the matched words wrapped in `<mark>`.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SearchHighlight {
+    /// `title`, `short_description` or `history`
+    pub field: String,
+    pub snippet: String,
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct MissionSearchResult {
     pub mission: Mission,
     pub relevance_score: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub highlights: Vec<SearchHighlight>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -3311,6 +4642,8 @@ pub struct MissionMomentSearchResult {
     pub snippet: String,
     pub rationale: String,
     pub relevance_score: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub highlights: Vec<SearchHighlight>,
 }
 
 #[derive(Debug, Clone)]
@@ -3379,20 +4712,58 @@ async fn mission_search_recency_fingerprint(
     Ok(hasher.finish())
 }
 
-/// Search missions with semantic-aware ranking.
+/// Search missions with semantic-aware ranking, optionally filtered by tag
+/// and status, with tag facets when `facets=true`.
 pub async fn search_missions(
     State(state): State<Arc<AppState>>,
     Extension(user): Extension<AuthUser>,
     Query(params): Query<SearchMissionsQuery>,
-) -> Result<Json<Vec<MissionSearchResult>>, (StatusCode, String)> {
+) -> Result<(HeaderMap, Json<SearchMissionsResponse>), (StatusCode, String)> {
     let query = params.q.trim();
-    if query.is_empty() {
-        return Ok(Json(Vec::new()));
-    }
-
     let limit = params.limit.unwrap_or(20).clamp(1, 100);
+    let after = params
+        .cursor
+        .as_deref()
+        .map(Cursor::decode)
+        .transpose()?
+        .map(|cursor| cursor.keyset);
+    let mut results = if query.is_empty() {
+        Vec::new()
+    } else {
+        let control = control_for_user(&state, &user).await;
+        ranked_mission_search(&state, &control, query, SEARCH_RESULTS_MAX).await?
+    };
+
+    let filter = mission_filter(params.tag.as_deref(), params.status);
+    results.retain(|result| filter.matches(&result.mission));
+    let facets = params
+        .facets
+        .then(|| tag_facets(results.iter().map(|result| &result.mission)));
+    let total = results.len();
+    let (mut results, next) = paginate(results, after.as_ref(), limit, |result| {
+        search_result_keyset(result.relevance_score, &result.mission)
+    });
+    for result in &mut results {
+        result.highlights = mission_search_highlights(&result.mission, query);
+    }
+    let headers = page_headers(total, next.map(Cursor::new).as_ref());
+    Ok((
+        headers,
+        Json(match facets {
+            Some(facets) => SearchMissionsResponse::WithFacets { results, facets },
+            None => SearchMissionsResponse::Results(results),
+        }),
+    ))
+}
+
+/// All missions matching `query`, best first (cached per query).
+async fn ranked_mission_search(
+    state: &Arc<AppState>,
+    control: &ControlState,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<MissionSearchResult>, (StatusCode, String)> {
     let query_hash = mission_search_query_hash(query);
-    let control = control_for_user(&state, &user).await;
     let recency_fingerprint = mission_search_recency_fingerprint(&control.mission_store)
         .await
         .map_err(internal_error)?;
@@ -3406,13 +4777,11 @@ pub async fn search_missions(
             }
         })
     } {
-        let mut results = cached_results;
-        results.truncate(limit);
-        return Ok(Json(results));
+        return Ok(cached_results);
     }
 
     let page_size = (limit.saturating_mul(5)).clamp(50, 200);
-    let mission_candidates = list_missions_for_search(&state, &control, query, limit).await?;
+    let mission_candidates = list_missions_for_search(state, control, query, limit).await?;
     let freshness_key = mission_search_freshness_key(&mission_candidates, page_size);
 
     if let Some(cached_results) = {
@@ -3425,9 +4794,7 @@ pub async fn search_missions(
             }
         })
     } {
-        let mut results = cached_results;
-        results.truncate(limit);
-        return Ok(Json(results));
+        return Ok(cached_results);
     }
 
     let mut scored: Vec<MissionSearchResult> = mission_candidates
@@ -3437,6 +4804,7 @@ pub async fn search_missions(
                 Some(MissionSearchResult {
                     mission: candidate.mission,
                     relevance_score: candidate.relevance_score,
+                    highlights: Vec::new(),
                 })
             } else {
                 None
@@ -3468,8 +4836,7 @@ pub async fn search_missions(
         );
     }
 
-    scored.truncate(limit);
-    Ok(Json(scored))
+    Ok(scored)
 }
 
 /// Search mission history and return the best matching moment per mission.
@@ -3477,13 +4844,19 @@ pub async fn search_mission_moments(
     State(state): State<Arc<AppState>>,
     Extension(user): Extension<AuthUser>,
     Query(params): Query<SearchMissionMomentsQuery>,
-) -> Result<Json<Vec<MissionMomentSearchResult>>, (StatusCode, String)> {
+) -> Result<Page<MissionMomentSearchResult>, (StatusCode, String)> {
     let query = params.q.trim();
     if query.is_empty() {
-        return Ok(Json(Vec::new()));
+        return Ok((page_headers(0, None), Json(Vec::new())));
     }
 
     let limit = params.limit.unwrap_or(10).clamp(1, 50);
+    let after = params
+        .cursor
+        .as_deref()
+        .map(Cursor::decode)
+        .transpose()?
+        .map(|cursor| cursor.keyset);
     let control = control_for_user(&state, &user).await;
 
     let mut missions: Vec<Mission> = if let Some(mission_id) = params.mission_id {
@@ -3493,7 +4866,7 @@ pub async fn search_mission_moments(
             .await
             .map_err(internal_error)?
         else {
-            return Ok(Json(Vec::new()));
+            return Ok((page_headers(0, None), Json(Vec::new())));
         };
         vec![mission]
     } else {
@@ -3528,7 +4901,7 @@ pub async fn search_mission_moments(
 
     populate_workspace_names(&state, &mut missions).await;
 
-    let mut results: Vec<MissionMomentSearchResult> = missions
+    let results: Vec<MissionMomentSearchResult> = missions
         .into_iter()
         .filter_map(|mission| {
             let best = best_mission_moment(&mission, query)?;
@@ -3539,25 +4912,42 @@ pub async fn search_mission_moments(
                 snippet: best.snippet,
                 rationale: best.rationale,
                 relevance_score: best.relevance_score,
+                highlights: best
+                    .highlight
+                    .map(|snippet| SearchHighlight {
+                        field: "history".to_string(),
+                        snippet,
+                    })
+                    .into_iter()
+                    .collect(),
             })
         })
         .collect();
 
-    results.sort_by(|a, b| {
-        b.relevance_score
-            .total_cmp(&a.relevance_score)
-            .then_with(|| b.mission.updated_at.cmp(&a.mission.updated_at))
-            .then_with(|| a.entry_index.cmp(&b.entry_index))
+    let total = results.len();
+    let (results, next) = paginate(results, after.as_ref(), limit, |result| {
+        search_result_keyset(result.relevance_score, &result.mission)
     });
-    results.truncate(limit);
-    Ok(Json(results))
+    Ok((
+        page_headers(total, next.map(Cursor::new).as_ref()),
+        Json(results),
+    ))
 }
 
 /// Get a specific mission.
+/// Query params for fetching a mission.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct GetMissionQuery {
+    /// Whether `tool` entries are included in the history (default: false)
+    #[serde(default)]
+    pub include_tools: Option<bool>,
+}
+
 pub async fn get_mission(
     State(state): State<Arc<AppState>>,
     Extension(user): Extension<AuthUser>,
     Path(id): Path<Uuid>,
+    Query(query): Query<GetMissionQuery>,
 ) -> Result<Json<Mission>, (StatusCode, String)> {
     let control = control_for_user(&state, &user).await;
     match control
@@ -3567,6 +4957,9 @@ pub async fn get_mission(
         .map_err(internal_error)?
     {
         Some(mut mission) => {
+            if !query.include_tools.unwrap_or(false) {
+                mission.history = conversation_history(&mission.history);
+            }
             // Populate workspace_name
             if let Some(workspace) = state.workspaces.get(mission.workspace_id).await {
                 mission.workspace_name = Some(workspace.name);
@@ -3594,6 +4987,17 @@ pub struct CreateMissionRequest {
     pub config_profile: Option<String>,
     /// Backend to use for this mission ("opencode" or "claudecode")
     pub backend: Option<String>,
+    /// Library agent version to pin (defaults to the agent's current version)
+    pub agent_version: Option<String>,
+    /// Free-form tags for filtering
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Simulate Bash/Edit/Write tool calls instead of running them
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Library environment profile (overrides the workspace's profile)
+    #[serde(default)]
+    pub env_profile: Option<String>,
 }
 
 fn normalize_model_effort(raw: &str) -> Option<String> {
@@ -3605,12 +5009,18 @@ fn normalize_model_effort(raw: &str) -> Option<String> {
     }
 }
 
-fn normalize_model_override_for_backend(backend: Option<&str>, raw_model: &str) -> Option<String> {
+/// Normalize a model override for a backend: `provider/model` is kept when the
+/// backend takes provider-prefixed ids (`provider_model_ids`), otherwise the
+/// provider prefix is stripped.
+fn normalize_model_override_for_backend(
+    provider_model_ids: bool,
+    raw_model: &str,
+) -> Option<String> {
     let trimmed = raw_model.trim();
     if trimmed.is_empty() {
         return None;
     }
-    if backend != Some("opencode") {
+    if !provider_model_ids {
         if let Some((_, model_id)) = trimmed.split_once('/') {
             return Some(model_id.to_string());
         }
@@ -3621,10 +5031,26 @@ fn normalize_model_override_for_backend(backend: Option<&str>, raw_model: &str)
 pub async fn create_mission(
     State(state): State<Arc<AppState>>,
     Extension(user): Extension<AuthUser>,
+    headers: HeaderMap,
     body: Option<Json<CreateMissionRequest>>,
 ) -> Result<Json<Mission>, (StatusCode, String)> {
     let (tx, rx) = oneshot::channel();
 
+    let requested_agent_version = body
+        .as_ref()
+        .and_then(|b| b.agent_version.clone())
+        .filter(|v| !v.trim().is_empty());
+    let tags = body
+        .as_ref()
+        .map(|b| normalize_tags(&b.tags))
+        .unwrap_or_default();
+    let dry_run = body.as_ref().is_some_and(|b| b.dry_run);
+    let env_profile = body
+        .as_ref()
+        .and_then(|b| b.env_profile.as_deref())
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string);
     let (title, workspace_id, agent, model_override, model_effort, config_profile, mut backend) =
         body.map(|b| {
             (
@@ -3665,22 +5091,75 @@ pub async fn create_mission(
         }
     }
 
-    // If no backend specified, use the default from registry
-    // This needs to happen BEFORE agent validation so we validate against the correct backend
+    // Tenant users can only use their tenant's workspaces (and never the
+    // shared default host workspace).
+    let tenant = user
+        .tenant
+        .as_deref()
+        .and_then(|id| state.config.find_tenant(id));
+    match workspace_id {
+        Some(ws_id) => {
+            if let Some(ws) = state.workspaces.get(ws_id).await {
+                if ws.tenant != user.tenant {
+                    return Err((
+                        StatusCode::NOT_FOUND,
+                        format!("Workspace {} not found", ws_id),
+                    ));
+                }
+            }
+        }
+        None if tenant.is_some() => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                "workspace_id is required for tenant missions".to_string(),
+            ));
+        }
+        None => {}
+    }
     if backend.is_none() {
-        let registry = state.backend_registry.read().await;
-        backend = Some(registry.default_id().to_string());
+        backend = tenant.and_then(|t| t.default_backend.clone());
     }
 
-    // Model effort is currently supported for Codex missions.
-    if backend.as_deref() != Some("codex") {
+    // If no backend specified, use the default from registry, then look up what it
+    // supports. This needs to happen BEFORE agent validation so we validate against
+    // the correct backend.
+    let (backend_id, capabilities) = {
+        let registry = state.backend_registry.read().await;
+        let backend_id = backend.unwrap_or_else(|| registry.default_id().to_string());
+        let capabilities = registry
+            .get(&backend_id)
+            .map(|b| b.capabilities())
+            .ok_or_else(|| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    format!("Unknown backend: {}", backend_id),
+                )
+            })?;
+        (backend_id, capabilities)
+    };
+    let backend = Some(backend_id.clone());
+
+    // Model effort is ignored by backends without effort levels.
+    if capabilities.effort_levels.is_empty() {
         model_effort = None;
+    } else if let Some(effort) = model_effort.as_ref() {
+        if !capabilities.effort_levels.contains(effort) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Invalid model_effort for {}. Supported values: {}",
+                    backend_id,
+                    capabilities.effort_levels.join(", ")
+                ),
+            ));
+        }
     }
 
-    // Normalize model override based on backend expectations.
-    // OpenCode expects provider/model; Claude Code and Codex expect raw model IDs.
+    // Normalize model override based on backend expectations
+    // (provider/model or raw model IDs).
     if let Some(ref raw_model) = model_override {
-        model_override = normalize_model_override_for_backend(backend.as_deref(), raw_model);
+        model_override =
+            normalize_model_override_for_backend(capabilities.provider_model_ids, raw_model);
     }
 
     // Resolve the effective config profile:
@@ -3699,12 +5178,42 @@ pub async fn create_mission(
         None
     };
 
-    // Validate agent exists before creating mission (fail fast with clear error)
-    // Skip validation for Claude Code, Amp, and Codex - they have their own built-in agents
+    let library = super::library::library_for_tenant(&state, user.tenant.as_deref()).await;
+
+    // The config profile must exist and its inheritance chain must resolve.
+    let lib = library.read().await.clone();
+    let profile_settings = match (effective_config_profile.as_deref(), lib) {
+        (Some(profile), Some(lib)) => {
+            if !lib.config_profile_exists(profile) {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    format!("Config profile '{}' not found", profile),
+                ));
+            }
+            let resolved = lib
+                .resolve_config_profile_settings(profile)
+                .await
+                .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?;
+            Some(resolved.settings)
+        }
+        _ => None,
+    };
+
+    // A profile's per-backend model fills in a missing override.
+    if model_override.is_none() && capabilities.model_override {
+        if let Some(model) = profile_settings
+            .as_ref()
+            .and_then(|settings| settings.models.get(&backend_id))
+        {
+            model_override =
+                normalize_model_override_for_backend(capabilities.provider_model_ids, model);
+        }
+    }
+
+    // Validate agent exists before creating mission (fail fast with clear error).
+    // Only library agents are checked; other backends have their own built-in agents.
     if let Some(ref agent_name) = agent {
-        let backend_id = backend.as_deref();
-        let skip_validation = matches!(backend_id, Some("claudecode" | "amp" | "codex"));
-        if !skip_validation {
+        if capabilities.library_agents {
             super::library::validate_agent_exists(
                 &state,
                 agent_name,
@@ -3715,55 +5224,140 @@ pub async fn create_mission(
         }
     }
 
-    // Validate backend exists
-    if let Some(ref backend_id) = backend {
-        let registry = state.backend_registry.read().await;
-        if registry.get(backend_id).is_none() {
-            return Err((
-                StatusCode::BAD_REQUEST,
-                format!("Unknown backend: {}", backend_id),
-            ));
+    // Pin the library agent version so later edits don't change this mission's behavior
+    let agent_version = match agent.as_deref() {
+        Some(agent_name) => {
+            resolve_mission_agent_version(&library, agent_name, requested_agent_version.as_deref())
+                .await?
         }
-    }
+        None => None,
+    };
 
-    // Validate model override if provided
+    // Validate model override if the backend honours one
     if let Some(ref model) = model_override {
-        let backend_id = backend.as_deref().unwrap_or("claudecode");
-        if let Err(e) = super::providers::validate_model_override(&state, backend_id, model).await {
-            return Err((StatusCode::BAD_REQUEST, e));
+        if capabilities.model_override {
+            if let Err(e) =
+                super::providers::validate_model_override(&state, &backend_id, model).await
+            {
+                return Err((StatusCode::BAD_REQUEST, e));
+            }
         }
     }
 
     // If no model_override specified, resolve from config profile for Claude Code
     if backend.as_deref() == Some("claudecode") && model_override.is_none() {
         if let Some(default_model) =
-            resolve_claudecode_default_model(&state.library, effective_config_profile.as_deref())
-                .await
+            resolve_claudecode_default_model(&library, effective_config_profile.as_deref()).await
         {
             model_override = Some(default_model);
         }
     }
 
     let control = control_for_user(&state, &user).await;
-    control
-        .cmd_tx
-        .send(ControlCommand::CreateMission {
-            title,
-            workspace_id,
-            agent,
-            model_override,
-            model_effort,
-            backend,
-            config_profile: effective_config_profile,
-            respond: tx,
-        })
-        .await
-        .map_err(session_unavailable)?;
+    if let Some(limit) = tenant.and_then(|t| t.max_missions) {
+        let count = control
+            .mission_store
+            .count_missions_filtered(&MissionFilter::default())
+            .await
+            .map_err(internal_error)?;
+        if count >= limit {
+            return Err((
+                StatusCode::FORBIDDEN,
+                format!("Tenant mission quota reached ({} missions)", limit),
+            ));
+        }
+    }
+    let idempotency_key = match claim_idempotency_key(&control, &headers, "create_mission").await? {
+        IdempotentRequest::Replay(response) => return replay_response(response),
+        IdempotentRequest::Execute(key) => key,
+    };
+    let result = async {
+        control
+            .cmd_tx
+            .send(ControlCommand::CreateMission {
+                title,
+                workspace_id,
+                agent,
+                model_override,
+                model_effort,
+                backend,
+                config_profile: effective_config_profile,
+                respond: tx,
+            })
+            .await
+            .map_err(session_unavailable)?;
 
-    rx.await
-        .map_err(recv_failed)?
-        .map(Json)
-        .map_err(internal_error)
+        let mut mission = rx.await.map_err(recv_failed)?.map_err(internal_error)?;
+        if let Some(version) = agent_version {
+            control
+                .mission_store
+                .update_mission_agent_version(mission.id, Some(&version))
+                .await
+                .map_err(internal_error)?;
+            mission.agent_version = Some(version);
+        }
+        if !tags.is_empty() {
+            control
+                .mission_store
+                .update_mission_tags(mission.id, &tags)
+                .await
+                .map_err(internal_error)?;
+            mission.tags = tags;
+        }
+        if dry_run {
+            control
+                .mission_store
+                .update_mission_dry_run(mission.id, true)
+                .await
+                .map_err(internal_error)?;
+            mission.dry_run = true;
+        }
+        if let Some(env_profile) = env_profile {
+            control
+                .mission_store
+                .update_mission_env_profile(mission.id, Some(&env_profile))
+                .await
+                .map_err(internal_error)?;
+            mission.env_profile = Some(env_profile);
+        }
+        Ok(Json(mission))
+    }
+    .await;
+    idempotency_key.complete(&result).await;
+    result
+}
+
+/// Resolve which library agent version a new mission should pin.
+///
+/// An explicit version must exist in the library; otherwise the agent's current
+/// version is used. Agents that aren't in the library (e.g. backend built-ins)
+/// are left unpinned.
+async fn resolve_mission_agent_version(
+    library: &super::library::SharedLibrary,
+    agent: &str,
+    requested: Option<&str>,
+) -> Result<Option<String>, (StatusCode, String)> {
+    let Some(lib) = library.read().await.clone() else {
+        return match requested {
+            Some(_) => Err((
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Library not initialized".to_string(),
+            )),
+            None => Ok(None),
+        };
+    };
+    match requested {
+        Some(version) => lib
+            .get_library_agent_version(agent, version)
+            .await
+            .map(|a| a.version)
+            .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string())),
+        None => Ok(lib
+            .get_library_agent(agent)
+            .await
+            .ok()
+            .and_then(|a| a.version)),
+    }
 }
 
 /// Load/switch to a mission.
@@ -3934,6 +5528,16 @@ pub struct GetEventsQuery {
     /// Offset for pagination
     #[serde(default)]
     pub offset: Option<usize>,
+    /// First event `sequence` to return (e.g. an execution's `first_event_seq`)
+    #[serde(default)]
+    pub from_seq: Option<i64>,
+    /// Last event `sequence` to return
+    #[serde(default)]
+    pub to_seq: Option<i64>,
+    /// Whether `thinking` events (the agent's reasoning) are returned
+    /// (default: true)
+    #[serde(default)]
+    pub include_thinking: Option<bool>,
 }
 
 /// Get events for a mission (for debugging/replay).
@@ -3957,16 +5561,45 @@ pub async fn get_mission_events(
     }
 
     // Parse event types filter
-    let types: Option<Vec<&str>> = query
-        .types
-        .as_ref()
-        .map(|s| s.split(',').map(|t| t.trim()).collect());
+    let include_thinking = query.include_thinking.unwrap_or(true);
+    let types: Option<Vec<&str>> = query.types.as_ref().map(|s| {
+        s.split(',')
+            .map(|t| t.trim())
+            .filter(|t| include_thinking || *t != "thinking")
+            .collect()
+    });
 
-    let events = control
-        .mission_store
-        .get_events(mission_id, types.as_deref(), query.limit, query.offset)
-        .await
-        .map_err(internal_error)?;
+    if query.from_seq.is_some() || query.to_seq.is_some() {
+        let events = control
+            .mission_store
+            .get_events_in_range(mission_id, query.from_seq, query.to_seq)
+            .await
+            .map_err(internal_error)?
+            .into_iter()
+            .filter(|e| {
+                types
+                    .as_ref()
+                    .is_none_or(|types| types.contains(&e.event_type.as_str()))
+                    && (include_thinking || e.event_type != "thinking")
+            })
+            .skip(query.offset.unwrap_or(0))
+            .take(query.limit.unwrap_or(usize::MAX))
+            .collect();
+        return Ok(Json(events));
+    }
+
+    let events = if types.is_none() && !include_thinking {
+        control
+            .mission_store
+            .get_events_excluding(mission_id, &["thinking"], query.limit, query.offset)
+            .await
+    } else {
+        control
+            .mission_store
+            .get_events(mission_id, types.as_deref(), query.limit, query.offset)
+            .await
+    }
+    .map_err(internal_error)?;
 
     Ok(Json(events))
 }
@@ -4123,11 +5756,29 @@ pub async fn get_parallel_config(
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
     let control = control_for_user(&state, &user).await;
     let running = get_running_missions(&control).await?;
-    let max_parallel = crate::settings::max_parallel_missions_cached_or(control.max_parallel);
+    let tenant = user
+        .tenant
+        .as_deref()
+        .and_then(|id| state.config.find_tenant(id));
+    let max_parallel = crate::tenant::effective_max_parallel(control.max_parallel, tenant);
+
+    let mut running_automated = 0;
+    for mission in &running {
+        if mission_has_active_automation(&control.mission_store, mission.mission_id).await {
+            running_automated += 1;
+        }
+    }
+    let slots = crate::tenant::SlotAllocation::new(
+        max_parallel,
+        crate::settings::reserved_interactive_slots(),
+        running.len() - running_automated,
+        running_automated,
+    );
 
     Ok(Json(serde_json::json!({
         "max_parallel_missions": max_parallel,
         "running_count": running.len(),
+        "slots": slots,
     })))
 }
 
@@ -4155,7 +5806,7 @@ pub async fn delete_mission(
         .map_err(internal_error)?;
 
     if deleted {
-        clear_mission_metadata_refresh_state(mission_id);
+        control.metadata_refresh.clear(mission_id);
         Ok(Json(serde_json::json!({
             "ok": true,
             "deleted": mission_id
@@ -4165,6 +5816,120 @@ pub async fn delete_mission(
     }
 }
 
+#[derive(Debug, Deserialize)]
+pub struct SwitchBackendRequest {
+    pub backend: String,
+    /// Model for the new backend (the previous override is dropped otherwise)
+    #[serde(default)]
+    pub model_override: Option<String>,
+    #[serde(default)]
+    pub model_effort: Option<String>,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Hand an idle mission off to another backend (see `handoff`).
+pub async fn switch_mission_backend(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+    Json(req): Json<SwitchBackendRequest>,
+) -> Result<Json<Mission>, (StatusCode, String)> {
+    let backend = req.backend.trim().to_string();
+    let capabilities = state
+        .backend_registry
+        .read()
+        .await
+        .get(&backend)
+        .map(|b| b.capabilities())
+        .ok_or_else(|| {
+            (
+                StatusCode::BAD_REQUEST,
+                format!("Unknown backend: {}", backend),
+            )
+        })?;
+    let model_override = req
+        .model_override
+        .as_deref()
+        .and_then(|raw| normalize_model_override_for_backend(capabilities.provider_model_ids, raw));
+    if let Some(ref model) = model_override {
+        if capabilities.model_override {
+            super::providers::validate_model_override(&state, &backend, model)
+                .await
+                .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+        }
+    }
+    // Model effort is ignored by backends without effort levels.
+    let model_effort = req
+        .model_effort
+        .as_deref()
+        .and_then(normalize_model_effort)
+        .filter(|effort| capabilities.effort_levels.contains(effort));
+
+    let control = control_for_user(&state, &user).await;
+    let running = get_running_missions(&control).await?;
+    if running.iter().any(|m| m.mission_id == mission_id) {
+        return Err((
+            StatusCode::CONFLICT,
+            "Cannot switch the backend of a running mission. Wait for the turn to finish or cancel it first.".to_string(),
+        ));
+    }
+
+    let mission = control
+        .mission_store
+        .get_mission(mission_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Mission not found".to_string()))?;
+    if mission.backend == backend {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Mission already uses the {} backend", backend),
+        ));
+    }
+
+    let workspace_root = state
+        .workspaces
+        .get(mission.workspace_id)
+        .await
+        .map(|ws| ws.path)
+        .unwrap_or_else(|| state.config.working_dir.clone());
+    let mission_dir = workspace::mission_workspace_dir_for_root(&workspace_root, mission_id);
+    let reason = req.reason.filter(|r| !r.trim().is_empty());
+    super::handoff::switch_backend(
+        control.mission_store.as_ref(),
+        &mission,
+        &mission_dir,
+        &backend,
+        model_override.as_deref(),
+        model_effort.as_deref(),
+        reason.clone(),
+    )
+    .await
+    .map_err(internal_error)?;
+
+    tracing::info!(
+        mission_id = %mission_id,
+        from = %mission.backend,
+        to = %backend,
+        "Mission handed off to another backend"
+    );
+    let _ = control.events_tx.send(AgentEvent::BackendSwitched {
+        mission_id,
+        from: mission.backend.clone(),
+        to: backend,
+        reason,
+    });
+
+    let updated = control
+        .mission_store
+        .get_mission(mission_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Mission not found".to_string()))?;
+    Ok(Json(updated))
+}
+
 /// Delete all empty "Untitled" missions.
 /// Returns the count of deleted missions.
 /// Note: This excludes any currently running missions to prevent data loss.
@@ -4183,7 +5948,10 @@ pub async fn cleanup_empty_missions(
         .map_err(internal_error)?;
 
     if count > 0 {
-        clear_stale_mission_metadata_refresh_state(&control.mission_store).await;
+        control
+            .metadata_refresh
+            .clear_stale(&control.mission_store)
+            .await;
     }
 
     Ok(Json(serde_json::json!({
@@ -4192,18 +5960,94 @@ pub async fn cleanup_empty_missions(
     })))
 }
 
+/// Maximum number of stored events replayed to a reconnecting SSE client.
+const REPLAY_LIMIT: usize = 5000;
+
+/// Query params for the SSE stream.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct StreamQuery {
+    /// Event schema version the client understands (defaults to the current one)
+    #[serde(default)]
+    pub schema_version: Option<u32>,
+    /// Comma-separated mission ids: only stream the events of these missions
+    #[serde(default)]
+    pub missions: Option<String>,
+}
+
 /// Stream control session events via SSE.
+///
+/// Each event carries its sequence number as the SSE id. Clients reconnect
+/// with `Last-Event-ID` to resume where they left off: buffered events are
+/// replayed from the event log, older ones from the mission store, and both
+/// are tagged `"replayed": true` before the stream switches to live events.
+/// Events that can't be recovered are reported with a `gap` event.
+///
+/// Payloads carry the event `schema_version`; clients built against an
+/// older schema pass `?schema_version=<n>` to get events downleveled to it.
+///
+/// With `?missions=<id>,<id>` only those missions' events are streamed, from
+/// their per-mission channels (see `mission_stream`).
 pub async fn stream(
     State(state): State<Arc<AppState>>,
     Extension(user): Extension<AuthUser>,
-) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, (StatusCode, String)> {
+    headers: HeaderMap,
+    axum::extract::Query(query): axum::extract::Query<StreamQuery>,
+) -> Result<axum::response::Response, (StatusCode, String)> {
+    let schema_version = event_schema::validate_version(
+        query
+            .schema_version
+            .unwrap_or(event_schema::CURRENT_VERSION),
+    )
+    .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
     let control = control_for_user(&state, &user).await;
-    let mut rx = control.events_tx.subscribe();
+    let resume_from =
+        event_bus::resume_cursor(headers.get("last-event-id").and_then(|v| v.to_str().ok()));
+    if let Some(missions) = query.missions.as_deref() {
+        let missions = mission_stream::parse_mission_ids(missions)?;
+        let sse = mission_stream::mission_events_sse(
+            &control,
+            missions,
+            mission_stream::StreamStart::resume_or_live(resume_from),
+            schema_version,
+            state.config.stream.keepalive_interval(),
+        )
+        .await;
+        return Ok(sse.into_response());
+    }
+    let log = Arc::clone(&control.event_log);
+    let mut head_rx = log.subscribe();
+    let live_from = log.next_seq();
+    let mut cursor = resume_from.unwrap_or(live_from);
+
+    // Events that already left the ring are backfilled from the mission store.
+    let mut backfill = Vec::new();
+    if let Some(from) = resume_from {
+        let first_buffered = log.first_seq();
+        if from < first_buffered && control.mission_store.is_persistent() {
+            match control
+                .mission_store
+                .get_events_after_stream_seq(from.saturating_sub(1), REPLAY_LIMIT)
+                .await
+            {
+                Ok(events) => {
+                    backfill = events
+                        .into_iter()
+                        .filter(|e| e.stream_seq.is_some_and(|seq| seq < first_buffered))
+                        .collect();
+                    cursor = first_buffered;
+                }
+                Err(e) => {
+                    tracing::warn!(error = %e, "Failed to load stored events for SSE replay");
+                }
+            }
+        }
+    }
     let stream_id = Uuid::new_v4();
     tracing::info!(
         stream_id = %stream_id,
         user_id = %user.id,
         username = %user.username,
+        resume_from = ?resume_from,
         "Control SSE stream opened"
     );
 
@@ -4232,105 +6076,184 @@ pub async fn stream(
         user_id: user.id.clone(),
         username: user.username.clone(),
     };
+    let keepalive = state.config.stream.keepalive_interval();
+    let coalesce_window = state.config.stream.coalesce_window();
 
     let stream = async_stream::stream! {
         let _guard = drop_guard;
-        match Event::default().event("status").json_data(AgentEvent::Status {
+        let initial = AgentEvent::Status {
             state: initial.state,
             queue_len: initial.queue_len,
             mission_id: initial.mission_id,
-        }) {
-            Ok(init_ev) => yield Ok(init_ev),
+        };
+        // `status` exists in every schema version, so it is never dropped.
+        let payload = serde_json::to_value(&initial)
+            .ok()
+            .and_then(|v| event_schema::for_client("status", v, schema_version))
+            .unwrap_or_default();
+        match Event::default().event("status").json_data(payload) {
+            Ok(init_ev) => yield Ok::<_, Infallible>(init_ev),
             Err(e) => {
                 tracing::error!("Failed to serialize initial SSE status event: {e}");
             }
         }
 
+        for stored in backfill {
+            let seq = stored.stream_seq.unwrap_or_default();
+            let Some(payload) = event_schema::for_client(
+                &stored.event_type,
+                event_bus::replayed_stored_event(&stored),
+                schema_version,
+            ) else {
+                continue;
+            };
+            match Event::default()
+                .id(seq.to_string())
+                .event(stored.event_type.clone())
+                .json_data(payload)
+            {
+                Ok(sse) => yield Ok(sse),
+                Err(e) => {
+                    tracing::error!(
+                        stream_id = %stream_id,
+                        error = %e,
+                        "Failed to serialize replayed SSE event"
+                    );
+                }
+            }
+        }
+
         // Keepalive interval to prevent connection timeouts during long LLM calls
-        let mut keepalive_interval = tokio::time::interval(std::time::Duration::from_secs(15));
+        let mut keepalive_interval = tokio::time::interval(keepalive);
         keepalive_interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
 
         loop {
-            tokio::select! {
-                result = rx.recv() => {
-                    match result {
-                        Ok(ev) => {
-                            let mission_id = ev.mission_id();
-                            match &ev {
-                                AgentEvent::Thinking { .. } => {
-                                    tracing::trace!(
-                                        stream_id = %stream_id,
-                                        event = %ev.event_name(),
-                                        mission_id = ?mission_id,
-                                        "Control SSE event"
-                                    );
-                                }
-                                _ => {
-                                    tracing::debug!(
-                                        stream_id = %stream_id,
-                                        event = %ev.event_name(),
-                                        mission_id = ?mission_id,
-                                        "Control SSE event"
-                                    );
-                                }
+            head_rx.borrow_and_update();
+            let (mut items, next) = log.read_from(cursor, 256);
+            cursor = next;
+            if items.is_empty() {
+                tokio::select! {
+                    changed = head_rx.changed() => {
+                        if changed.is_err() {
+                            break;
+                        }
+                    }
+                    _ = keepalive_interval.tick() => {
+                        // Send SSE comment as keepalive (: comment\n\n)
+                        let sse = Event::default().comment("keepalive");
+                        yield Ok(sse);
+                    }
+                }
+                continue;
+            }
+
+            if let Some(window) = coalesce_window {
+                let has_text_delta = items.iter().any(|item| {
+                    matches!(item, LogItem::Event { event, .. }
+                        if matches!(**event, AgentEvent::TextDelta { .. }))
+                });
+                if has_text_delta {
+                    // Let the rest of the burst arrive, then send only the
+                    // latest delta per mission
+                    tokio::time::sleep(window).await;
+                    head_rx.borrow_and_update();
+                    let (more, next) = log.read_from(cursor, 256);
+                    cursor = next;
+                    items.extend(more);
+                }
+                items = event_bus::coalesce_text_deltas(items);
+            }
+
+            for item in items {
+                match item {
+                    LogItem::Event { seq, event: ev } => {
+                        let mission_id = ev.mission_id();
+                        match &*ev {
+                            AgentEvent::Thinking { .. } => {
+                                tracing::trace!(
+                                    stream_id = %stream_id,
+                                    seq,
+                                    event = %ev.event_name(),
+                                    mission_id = ?mission_id,
+                                    "Control SSE event"
+                                );
                             }
-                            match Event::default().event(ev.event_name()).json_data(&ev) {
-                                Ok(sse) => yield Ok(sse),
-                                Err(e) => {
-                                    tracing::error!(
-                                        stream_id = %stream_id,
-                                        event = %ev.event_name(),
-                                        error = %e,
-                                        "Failed to serialize SSE event; dropping"
-                                    );
-                                }
+                            _ => {
+                                tracing::debug!(
+                                    stream_id = %stream_id,
+                                    seq,
+                                    event = %ev.event_name(),
+                                    mission_id = ?mission_id,
+                                    "Control SSE event"
+                                );
                             }
                         }
-                        Err(broadcast::error::RecvError::Lagged(_)) => {
-                            tracing::warn!(
-                                stream_id = %stream_id,
-                                "Control SSE stream lagged; events dropped"
-                            );
-                            match Event::default()
-                                .event("error")
-                                .json_data(AgentEvent::Error {
-                                    message:
-                                        "event stream lagged; some events were dropped"
-                                            .to_string(),
-                                    mission_id: None,
-                                    resumable: false,
-                                }) {
-                                Ok(sse) => yield Ok(sse),
-                                Err(e) => {
-                                    tracing::error!(
-                                        stream_id = %stream_id,
-                                        error = %e,
-                                        "Failed to serialize SSE lag error event"
-                                    );
-                                }
+                        let payload = if seq < live_from {
+                            event_bus::replayed_event(&ev)
+                        } else {
+                            serde_json::to_value(&*ev).unwrap_or_else(|_| serde_json::json!({}))
+                        };
+                        let Some(payload) =
+                            event_schema::for_client(ev.event_name(), payload, schema_version)
+                        else {
+                            continue;
+                        };
+                        let sse = Event::default()
+                            .id(seq.to_string())
+                            .event(ev.event_name())
+                            .json_data(payload);
+                        match sse {
+                            Ok(sse) => yield Ok(sse),
+                            Err(e) => {
+                                tracing::error!(
+                                    stream_id = %stream_id,
+                                    event = %ev.event_name(),
+                                    error = %e,
+                                    "Failed to serialize SSE event; dropping"
+                                );
+                            }
+                        }
+                    }
+                    LogItem::Gap(gap) => {
+                        tracing::warn!(
+                            stream_id = %stream_id,
+                            from_seq = gap.from_seq,
+                            to_seq = gap.to_seq,
+                            "Control SSE stream fell behind; events no longer buffered"
+                        );
+                        match Event::default()
+                            .id(gap.to_seq.to_string())
+                            .event("gap")
+                            .json_data(&gap)
+                        {
+                            Ok(sse) => yield Ok(sse),
+                            Err(e) => {
+                                tracing::error!(
+                                    stream_id = %stream_id,
+                                    error = %e,
+                                    "Failed to serialize SSE gap event"
+                                );
                             }
                         }
-                        Err(broadcast::error::RecvError::Closed) => break,
                     }
-                }
-                _ = keepalive_interval.tick() => {
-                    // Send SSE comment as keepalive (: comment\n\n)
-                    let sse = Event::default().comment("keepalive");
-                    yield Ok(sse);
                 }
             }
         }
     };
 
-    Ok(Sse::new(stream).keep_alive(
-        axum::response::sse::KeepAlive::new()
-            .interval(std::time::Duration::from_secs(15))
-            .text("keepalive"),
-    ))
+    Ok(Sse::new(stream)
+        .keep_alive(
+            axum::response::sse::KeepAlive::new()
+                .interval(keepalive)
+                .text("keepalive"),
+        )
+        .into_response())
 }
 
 /// Spawn the global control session actor.
+#[allow(clippy::too_many_arguments)]
 fn spawn_control_session(
+    session_key: &str,
     config: Config,
     root_agent: AgentRef,
     mcp: Arc<McpRegistry>,
@@ -4340,7 +6263,8 @@ fn spawn_control_session(
     secrets: Option<Arc<SecretsStore>>,
 ) -> ControlState {
     let (cmd_tx, cmd_rx) = mpsc::channel::<ControlCommand>(256);
-    let (events_tx, events_rx) = broadcast::channel::<AgentEvent>(1024);
+    let (events_tx, events_rx) =
+        broadcast::channel::<AgentEvent>(config.stream.broadcast_capacity.max(1));
     let tool_hub = Arc::new(FrontendToolHub::new());
     let status = Arc::new(RwLock::new(ControlStatus {
         state: ControlRunState::Idle,
@@ -4357,12 +6281,25 @@ fn spawn_control_session(
     let progress = Arc::new(RwLock::new(ExecutionProgress::default()));
     let running_missions = Arc::new(RwLock::new(Vec::new()));
     let mission_search_cache = Arc::new(RwLock::new(HashMap::new()));
+    let presence = presence::SharedPresence::default();
+    let approvals = command_approval::SharedCommandApprovals::default();
+    let status_requests = status_requests::SharedStatusRequests::default();
+    let checklists = mission_checklist::SharedChecklists::default();
+    let metadata_refresh = metadata_refresh::SharedMetadataRefresh::default();
+    let automation_throttle = Arc::new(automation_throttle::AutomationThrottle::new(
+        config.automation_throttle_running,
+        config.automation_rate_limit_backoff_secs,
+    ));
     let max_parallel =
-        crate::settings::max_parallel_missions_cached_or(config.max_parallel_missions);
+        crate::tenant::effective_max_parallel(config.max_parallel_missions, config.tenant.as_ref());
+
+    let event_log = Arc::new(EventLog::from_env());
+    event_bus::spawn_sequencer(&events_tx, Arc::clone(&event_log));
 
     let state = ControlState {
         cmd_tx,
         events_tx: events_tx.clone(),
+        event_log: Arc::clone(&event_log),
         tool_hub: Arc::clone(&tool_hub),
         status: Arc::clone(&status),
         current_mission: Arc::clone(&current_mission),
@@ -4372,6 +6309,14 @@ fn spawn_control_session(
         max_parallel,
         mission_store: Arc::clone(&mission_store),
         mission_search_cache,
+        presence: Arc::clone(&presence),
+        automation_throttle: Arc::clone(&automation_throttle),
+        approvals: Arc::clone(&approvals),
+        workspaces: Arc::clone(&workspaces),
+        status_requests: Arc::clone(&status_requests),
+        checklists: Arc::clone(&checklists),
+        mission_cmd_tx: mission_cmd_tx.clone(),
+        metadata_refresh: Arc::clone(&metadata_refresh),
     };
 
     // Spawn the main control actor
@@ -4393,6 +6338,10 @@ fn spawn_control_session(
         progress,
         mission_store,
         secrets,
+        approvals,
+        status_requests,
+        checklists,
+        Arc::clone(&metadata_refresh),
     ));
 
     // Recover orphaned missions from previous run.
@@ -4401,6 +6350,7 @@ fn spawn_control_session(
     if state.mission_store.is_persistent() {
         let store = Arc::clone(&state.mission_store);
         let tx = events_tx.clone();
+        let metadata_refresh = Arc::clone(&metadata_refresh);
         tokio::spawn(async move {
             match store.get_all_active_missions().await {
                 Ok(orphans) if !orphans.is_empty() => {
@@ -4428,6 +6378,7 @@ fn spawn_control_session(
                             maybe_schedule_mission_metadata_refresh_for_status(
                                 &store,
                                 &tx,
+                                &metadata_refresh,
                                 mission.id,
                                 MissionStatus::Interrupted,
                             );
@@ -4455,56 +6406,287 @@ fn spawn_control_session(
         });
     }
 
-    // Spawn background stale mission cleanup task (if enabled)
-    if config.stale_mission_hours > 0 && state.mission_store.is_persistent() {
-        tokio::spawn(stale_mission_cleanup_loop(
-            Arc::clone(&state.mission_store),
-            config.stale_mission_hours,
-            state.cmd_tx.clone(),
-            events_tx.clone(),
-        ));
-    }
-
+    // Background loops are supervised: restarted after a panic and stopped
+    // on shutdown. Each restart builds the loop again from clones (and new
+    // event subscriptions).
+    let supervisor = super::task_supervisor::global();
+    let policy = super::task_supervisor::RestartPolicy::on_panic();
+    let task_name = |task: &str| format!("control/{}/{}", session_key, task);
+
+    // Spawn background stale mission cleanup task (if enabled)
+    if config.stale_mission_hours > 0 && state.mission_store.is_persistent() {
+        let store = Arc::clone(&state.mission_store);
+        let stale_hours = config.stale_mission_hours;
+        let cmd_tx = state.cmd_tx.clone();
+        let events_tx = events_tx.clone();
+        let metadata_refresh = Arc::clone(&metadata_refresh);
+        supervisor.spawn(task_name("stale_mission_cleanup"), policy, move || {
+            stale_mission_cleanup_loop(
+                Arc::clone(&store),
+                stale_hours,
+                cmd_tx.clone(),
+                events_tx.clone(),
+                Arc::clone(&metadata_refresh),
+            )
+        });
+    }
+
+    // Spawn store maintenance task (WAL checkpoint, vacuum, ANALYZE)
+    if config.store_maintenance_hours > 0 && state.mission_store.is_persistent() {
+        let store = Arc::clone(&state.mission_store);
+        let hours = config.store_maintenance_hours;
+        supervisor.spawn(task_name("store_maintenance"), policy, move || {
+            super::store_maintenance::maintenance_loop(Arc::clone(&store), hours)
+        });
+    }
+
+    // Spawn turn journal task (crash-safe record of in-flight turns for resume)
+    if state.mission_store.is_persistent() {
+        let store = Arc::clone(&state.mission_store);
+        let events_tx = events_tx.clone();
+        supervisor.spawn(task_name("turn_journal"), policy, move || {
+            turn_journal::journal_loop(Arc::clone(&store), events_tx.subscribe())
+        });
+    }
+
+    // Spawn question history task (records answered questions in mission history)
+    {
+        let store = Arc::clone(&state.mission_store);
+        let events_tx = events_tx.clone();
+        supervisor.spawn(task_name("question_history"), policy, move || {
+            super::user_question::history_loop(Arc::clone(&store), events_tx.subscribe())
+        });
+    }
+
     // Spawn event logger task (logs all events to SQLite for debugging/replay)
     if state.mission_store.is_persistent() {
         let store = Arc::clone(&state.mission_store);
-        let mut event_rx = events_tx.subscribe();
-        tokio::spawn(async move {
-            loop {
-                match event_rx.recv().await {
-                    Ok(event) => {
-                        // Extract mission_id from event
-                        if let Some(mid) = event.mission_id() {
-                            if let Err(e) = store.log_event(mid, &event).await {
-                                tracing::warn!("Failed to log event: {}", e);
-                            }
-                        }
-                    }
-                    Err(broadcast::error::RecvError::Lagged(n)) => {
-                        tracing::warn!("Event logger lagged by {} events", n);
-                    }
-                    Err(broadcast::error::RecvError::Closed) => break,
-                }
-            }
-            tracing::info!("Event logger task stopped");
+        let event_log = Arc::clone(&event_log);
+        let events_tx = events_tx.clone();
+        supervisor.spawn_with_cancel(task_name("event_logger"), policy, move |cancel| {
+            event_logger_loop(
+                Arc::clone(&store),
+                Arc::clone(&event_log),
+                events_tx.clone(),
+                cancel,
+            )
         });
     }
 
     // Spawn automation scheduler task
     if state.mission_store.is_persistent() && config.automations_enabled {
-        tokio::spawn(automation_scheduler_loop(
-            Arc::clone(&state.mission_store),
-            library.clone(),
-            state.cmd_tx.clone(),
-            workspaces.clone(),
-        ));
+        {
+            let throttle = Arc::clone(&automation_throttle);
+            let events_tx = events_tx.clone();
+            supervisor.spawn(
+                task_name("automation_rate_limit_watch"),
+                policy,
+                move || {
+                    automation_throttle::rate_limit_watch(
+                        Arc::clone(&throttle),
+                        events_tx.subscribe(),
+                    )
+                },
+            );
+        }
+        let store = Arc::clone(&state.mission_store);
+        let library = library.clone();
+        let cmd_tx = state.cmd_tx.clone();
+        let workspaces = workspaces.clone();
+        supervisor.spawn(task_name("automation_scheduler"), policy, move || {
+            automation_scheduler_loop(
+                Arc::clone(&store),
+                library.clone(),
+                cmd_tx.clone(),
+                workspaces.clone(),
+                Arc::clone(&automation_throttle),
+            )
+        });
     } else if state.mission_store.is_persistent() {
         tracing::info!("Automation scheduler disabled by config");
     }
 
+    if state.mission_store.is_persistent() {
+        let store = Arc::clone(&state.mission_store);
+        let cmd_tx = state.cmd_tx.clone();
+        supervisor.spawn(task_name("scheduled_messages"), policy, move || {
+            super::scheduled_messages::scheduler_loop(Arc::clone(&store), cmd_tx.clone())
+        });
+    }
+
+    // Spawn notification task (tells watchers when their missions stop)
+    {
+        let store = Arc::clone(&state.mission_store);
+        let events_tx = events_tx.clone();
+        supervisor.spawn(task_name("watcher_notifications"), policy, move || {
+            super::mission_assignment::notification_loop(
+                Arc::clone(&store),
+                events_tx.clone(),
+                events_tx.subscribe(),
+            )
+        });
+    }
+
+    // Spawn presence task (drops viewers whose heartbeats stopped)
+    {
+        let events_tx = events_tx.clone();
+        supervisor.spawn(task_name("presence"), policy, move || {
+            presence::presence_loop(presence.clone(), events_tx.clone())
+        });
+    }
+
+    // Spawn mission retry task (resumes failed missions under their retry policy)
+    if state.mission_store.is_persistent() {
+        let store = Arc::clone(&state.mission_store);
+        let cmd_tx = state.cmd_tx.clone();
+        let events_tx = events_tx.clone();
+        supervisor.spawn(task_name("mission_retry"), policy, move || {
+            super::mission_retry::retry_loop(
+                Arc::clone(&store),
+                cmd_tx.clone(),
+                events_tx.subscribe(),
+            )
+        });
+    }
+
     state
 }
 
+/// Events the event logger writes in one transaction, at most.
+const EVENT_LOG_BATCH: usize = 128;
+
+/// How long the event logger holds buffered events before writing them.
+const EVENT_LOG_FLUSH_INTERVAL: std::time::Duration = std::time::Duration::from_millis(50);
+
+/// Whether an event should be persisted right away rather than wait for the
+/// batch to fill: turn boundaries, which readers of the store act on.
+fn flushes_event_log(event: &AgentEvent) -> bool {
+    matches!(
+        event,
+        AgentEvent::UserMessage { .. }
+            | AgentEvent::AssistantMessage { .. }
+            | AgentEvent::MissionStatusChanged { .. }
+            | AgentEvent::Error { .. }
+    )
+}
+
+/// Persist mission events from the control stream's event log. Bursts (tool
+/// output, streaming text) are written in batches, one transaction each, in
+/// stream order. Once new output is stored, its missions' unread counts are
+/// published.
+async fn event_logger_loop(
+    store: Arc<dyn MissionStore>,
+    log: Arc<EventLog>,
+    events_tx: broadcast::Sender<AgentEvent>,
+    cancel: CancellationToken,
+) {
+    let mut head_rx = log.subscribe();
+    let mut cursor = log.next_seq();
+    let mut batch: Vec<StreamEvent> = Vec::new();
+    let mut deadline: Option<tokio::time::Instant> = None;
+    let mut thinking = ThinkingCollector::new(ThinkingPersistence::from_env());
+
+    let flush = |batch: Vec<StreamEvent>| {
+        let store = Arc::clone(&store);
+        let events_tx = events_tx.clone();
+        async move {
+            for chunk in batch.chunks(EVENT_LOG_BATCH) {
+                let Err(e) = store.log_stream_events(chunk).await else {
+                    continue;
+                };
+                // The batch was rolled back: write its events one by one so a
+                // single bad event doesn't lose the others.
+                tracing::warn!(
+                    "Failed to log {} events, retrying one by one: {}",
+                    chunk.len(),
+                    e
+                );
+                for event in chunk {
+                    if let Err(e) = store
+                        .log_stream_event(event.mission_id, event.stream_seq, &event.event)
+                        .await
+                    {
+                        tracing::warn!(
+                            mission_id = %event.mission_id,
+                            stream_seq = event.stream_seq,
+                            "Skipping event that failed to log: {}",
+                            e
+                        );
+                    }
+                }
+            }
+            super::mission_reads::publish_unread_counts(store.as_ref(), &events_tx, &batch).await;
+        }
+    };
+
+    loop {
+        head_rx.borrow_and_update();
+        let (items, next) = log.read_from(cursor, 256);
+        cursor = next;
+        if items.is_empty() {
+            // Caught up: stop here on shutdown, flushing the batch below
+            let closed = match deadline {
+                Some(at) => tokio::select! {
+                    changed = head_rx.changed() => changed.is_err(),
+                    _ = cancel.cancelled() => true,
+                    _ = tokio::time::sleep_until(at) => {
+                        flush(std::mem::take(&mut batch)).await;
+                        deadline = None;
+                        false
+                    }
+                },
+                None => tokio::select! {
+                    changed = head_rx.changed() => changed.is_err(),
+                    _ = cancel.cancelled() => true,
+                },
+            };
+            if closed {
+                break;
+            }
+            continue;
+        }
+
+        let mut flush_now = false;
+        for item in items {
+            match item {
+                LogItem::Event { seq, event } => {
+                    let Some(mission_id) = event.mission_id() else {
+                        continue;
+                    };
+                    flush_now |= flushes_event_log(&event);
+                    thinking.route(
+                        StreamEvent {
+                            mission_id,
+                            stream_seq: seq,
+                            event,
+                        },
+                        &mut batch,
+                    );
+                }
+                LogItem::Gap(gap) => {
+                    tracing::warn!(
+                        from_seq = gap.from_seq,
+                        to_seq = gap.to_seq,
+                        "Event logger fell behind; events not persisted"
+                    );
+                }
+            }
+        }
+        if flush_now || batch.len() >= EVENT_LOG_BATCH {
+            flush(std::mem::take(&mut batch)).await;
+            deadline = None;
+        } else if !batch.is_empty() && deadline.is_none() {
+            deadline = Some(tokio::time::Instant::now() + EVENT_LOG_FLUSH_INTERVAL);
+        }
+    }
+
+    thinking.drain(&mut batch);
+    if !batch.is_empty() {
+        flush(batch).await;
+    }
+    tracing::info!("Event logger task stopped");
+}
+
 /// Background task that periodically cleans up missions that are no longer running.
 ///
 /// Two checks on each tick:
@@ -4518,6 +6700,7 @@ async fn stale_mission_cleanup_loop(
     stale_hours: u64,
     cmd_tx: mpsc::Sender<ControlCommand>,
     events_tx: broadcast::Sender<AgentEvent>,
+    metadata_refresh: metadata_refresh::SharedMetadataRefresh,
 ) {
     // Check every 5 minutes (fast enough to catch orphans promptly).
     let check_interval = std::time::Duration::from_secs(300);
@@ -4556,8 +6739,15 @@ async fn stale_mission_cleanup_loop(
                 };
 
                 if let Some(running_ids) = running_ids {
+                    // Missions still heartbeating from another replica aren't orphans.
+                    let remote_ids = health::live_remote_missions(
+                        mission_store
+                            .list_runner_heartbeats()
+                            .await
+                            .unwrap_or_default(),
+                    );
                     for mission in &active_missions {
-                        if !running_ids.contains(&mission.id) {
+                        if !running_ids.contains(&mission.id) && !remote_ids.contains(&mission.id) {
                             tracing::info!(
                                 "Orphan detected: mission {} '{}' is active in DB but has no running process (last update: {})",
                                 mission.id,
@@ -4574,9 +6764,11 @@ async fn stale_mission_cleanup_loop(
                                     e
                                 );
                             } else {
+                                let _ = mission_store.delete_runner_heartbeat(mission.id).await;
                                 maybe_schedule_mission_metadata_refresh_for_status(
                                     &mission_store,
                                     &events_tx,
+                                    &metadata_refresh,
                                     mission.id,
                                     MissionStatus::Interrupted,
                                 );
@@ -4623,6 +6815,7 @@ async fn stale_mission_cleanup_loop(
                         maybe_schedule_mission_metadata_refresh_for_status(
                             &mission_store,
                             &events_tx,
+                            &metadata_refresh,
                             mission.id,
                             MissionStatus::Completed,
                         );
@@ -4650,6 +6843,7 @@ async fn automation_scheduler_loop(
     library: SharedLibrary,
     cmd_tx: mpsc::Sender<ControlCommand>,
     workspaces: workspace::SharedWorkspaceStore,
+    throttle: automation_throttle::SharedAutomationThrottle,
 ) {
     use super::automation_variables::{substitute_variables, SubstitutionContext};
     use super::mission_store::{AutomationExecution, CommandSource, ExecutionStatus, TriggerType};
@@ -4660,6 +6854,8 @@ async fn automation_scheduler_loop(
     tracing::info!("Automation scheduler task started");
 
     let mut logged_unsupported = false;
+    // Automations whose current blackout window was already recorded
+    let mut blacked_out = std::collections::HashSet::new();
 
     loop {
         tokio::time::sleep(check_interval).await;
@@ -4758,8 +6954,58 @@ async fn automation_scheduler_loop(
                 continue;
             }
 
+            // Get workspace for blackout windows and reading local files
+            let workspace = workspaces.get(mission.workspace_id).await;
+
+            // Hold runs that fall due in a blackout window until it ends,
+            // recording the skip once per window
+            let workspace_blackouts = workspace
+                .as_ref()
+                .map(|ws| ws.automation_blackouts.as_slice())
+                .unwrap_or_default();
+            if let Some(window) = crate::blackout::active_window(
+                automation
+                    .blackout_windows
+                    .iter()
+                    .chain(workspace_blackouts),
+                chrono::Utc::now(),
+            ) {
+                if blacked_out.insert(automation.id) {
+                    tracing::info!(
+                        "Automation {} is in blackout window {}, skipping trigger",
+                        automation.id,
+                        window.describe()
+                    );
+                    let now = mission_store::now_string();
+                    let skipped = AutomationExecution {
+                        id: Uuid::new_v4(),
+                        automation_id: automation.id,
+                        mission_id: mission.id,
+                        triggered_at: now.clone(),
+                        trigger_source: "interval".to_string(),
+                        status: ExecutionStatus::Skipped,
+                        webhook_payload: None,
+                        variables_used: automation.variables.clone(),
+                        completed_at: Some(now),
+                        error: Some(format!("Blackout window: {}", window.describe())),
+                        retry_count: 0,
+                        first_event_seq: None,
+                        last_event_seq: None,
+                    };
+                    if let Err(e) = mission_store.create_automation_execution(skipped).await {
+                        tracing::warn!(
+                            "Failed to record skipped execution for automation {}: {}",
+                            automation.id,
+                            e
+                        );
+                    }
+                }
+                continue;
+            }
+            blacked_out.remove(&automation.id);
+
             // Check if the mission is currently busy (has a running task or queued messages)
-            let is_busy = {
+            let running = {
                 let (tx, rx) = tokio::sync::oneshot::channel();
                 if cmd_tx
                     .send(ControlCommand::ListRunning { respond: tx })
@@ -4770,11 +7016,7 @@ async fn automation_scheduler_loop(
                     continue;
                 }
                 match rx.await {
-                    Ok(running) => running.iter().any(|r| {
-                        r.mission_id == mission.id
-                            && (r.queue_len > 0
-                                || matches!(r.state.as_str(), "running" | "waiting_for_tool"))
-                    }),
+                    Ok(running) => running,
                     Err(_) => {
                         tracing::warn!(
                             "Failed to receive ListRunning response for automation busy check"
@@ -4784,6 +7026,13 @@ async fn automation_scheduler_loop(
                 }
             };
 
+            let is_active = |r: &super::mission_runner::RunningMissionInfo| {
+                matches!(r.state.as_str(), "running" | "waiting_for_tool")
+            };
+            let is_busy = running
+                .iter()
+                .any(|r| r.mission_id == mission.id && (r.queue_len > 0 || is_active(r)));
+
             if is_busy {
                 tracing::debug!(
                     "Mission {} is busy, skipping automation trigger",
@@ -4792,8 +7041,21 @@ async fn automation_scheduler_loop(
                 continue;
             }
 
-            // Get workspace for reading local files
-            let workspace = workspaces.get(mission.workspace_id).await;
+            // Defer while the session is loaded or rate-limited
+            let now = chrono::Utc::now();
+            let running_count = running.iter().filter(|r| is_active(r)).count();
+            if let Some(reason) = throttle.defer_reason(running_count, now) {
+                if throttle.record_deferred(automation.id, reason, now) {
+                    tracing::info!(
+                        "Deferring automation {} ({:?}, {} missions running)",
+                        automation.id,
+                        reason,
+                        running_count
+                    );
+                }
+                continue;
+            }
+            throttle.clear(automation.id);
 
             // Fetch the command content based on the command source
             let command_content = match &automation.command_source {
@@ -4872,6 +7134,8 @@ async fn automation_scheduler_loop(
                 completed_at: None,
                 error: None,
                 retry_count: 0,
+                first_event_seq: None,
+                last_event_seq: None,
             };
 
             let execution = match mission_store.create_automation_execution(execution).await {
@@ -5221,6 +7485,8 @@ async fn agent_finished_automation_messages(
             completed_at: None,
             error: None,
             retry_count: 0,
+            first_event_seq: None,
+            last_event_seq: None,
         };
 
         if mission_store
@@ -5279,6 +7545,10 @@ async fn control_actor_loop(
     progress: Arc<RwLock<ExecutionProgress>>,
     mission_store: Arc<dyn MissionStore>,
     secrets: Option<Arc<SecretsStore>>,
+    approvals: command_approval::SharedCommandApprovals,
+    status_requests: status_requests::SharedStatusRequests,
+    checklists: mission_checklist::SharedChecklists,
+    metadata_refresh: metadata_refresh::SharedMetadataRefresh,
 ) {
     // Queue stores (id, content, agent, target_mission_id) for the current/primary mission
     // The target_mission_id tracks which mission each queued message is intended for
@@ -5290,12 +7560,27 @@ async fn control_actor_loop(
     // Track which mission the main `running` task is actually working on.
     // This is different from `current_mission` which can change when user creates a new mission.
     let mut running_mission_id: Option<Uuid> = None;
+    // Execution state of the main session's mission
+    let mut main_state = MissionRunState::Idle;
     // Track last activity for the main runner (for stall detection)
     let mut main_runner_last_activity: std::time::Instant = std::time::Instant::now();
     // Track current activity label for the main runner
     let mut main_runner_activity: Option<String> = None;
     // Track subtasks for the main runner
     let mut main_runner_subtasks: Vec<super::mission_runner::SubtaskInfo> = Vec::new();
+    // (mission, workspace) of the main runner, for localized activity labels
+    let mut main_runner_workspace: Option<(Uuid, Uuid)> = None;
+    // Recently seen Idempotency-Key values for message submission / mission creation
+    let mut idempotency = IdempotencyCache::from_env();
+    let mut cost_tracker = CostTracker::new();
+    let mut conflict_tracker = FileConflictTracker::new(ConflictPolicy::from_env());
+    let approval_required = command_approval::approval_required_from_env();
+    let mut partial_turns = PartialTurns::new(events_tx.subscribe());
+    let mut stall_watcher = StallPolicy::from_env().map(StallWatcher::new);
+    let mut stall_tick = tokio::time::interval(stall_watch::CHECK_INTERVAL);
+    stall_tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    let mut heartbeat_tick = tokio::time::interval(health::HEARTBEAT_INTERVAL);
+    heartbeat_tick.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
 
     // Parallel mission runners - each runs independently
     let mut parallel_runners: std::collections::HashMap<
@@ -5326,6 +7611,7 @@ async fn control_actor_loop(
     async fn persist_mission_history_to(
         mission_store: &Arc<dyn MissionStore>,
         events_tx: &broadcast::Sender<AgentEvent>,
+        metadata_refresh: &metadata_refresh::SharedMetadataRefresh,
         mission_id: Option<Uuid>,
         history: &[(String, String)],
     ) {
@@ -5335,11 +7621,13 @@ async fn control_actor_loop(
                 .map(|(role, content)| MissionHistoryEntry {
                     role: role.clone(),
                     content: content.clone(),
+                    tool_name: None,
                 })
                 .collect();
             persist_mission_history_and_schedule_metadata_refresh(
                 mission_store,
                 events_tx,
+                metadata_refresh,
                 mid,
                 &entries,
             )
@@ -5351,11 +7639,19 @@ async fn control_actor_loop(
     async fn persist_mission_history(
         mission_store: &Arc<dyn MissionStore>,
         events_tx: &broadcast::Sender<AgentEvent>,
+        metadata_refresh: &metadata_refresh::SharedMetadataRefresh,
         current_mission: &Arc<RwLock<Option<Uuid>>>,
         history: &[(String, String)],
     ) {
         let mission_id = *current_mission.read().await;
-        persist_mission_history_to(mission_store, events_tx, mission_id, history).await;
+        persist_mission_history_to(
+            mission_store,
+            events_tx,
+            metadata_refresh,
+            mission_id,
+            history,
+        )
+        .await;
     }
 
     fn parse_tool_result_object(result: &serde_json::Value) -> Option<serde_json::Value> {
@@ -5381,6 +7677,30 @@ async fn control_actor_loop(
             .ok_or_else(|| format!("Mission {} not found", id))
     }
 
+    // Helper to set a mission that is about to run back to active
+    async fn activate_mission(
+        mission_store: &Arc<dyn MissionStore>,
+        events_tx: &broadcast::Sender<AgentEvent>,
+        mission: &Mission,
+    ) {
+        if !needs_activation(mission.status) {
+            return;
+        }
+        tracing::info!("Activating mission {} (was {})", mission.id, mission.status);
+        if let Err(e) = mission_store
+            .update_mission_status(mission.id, MissionStatus::Active)
+            .await
+        {
+            tracing::warn!("Failed to activate mission {}: {}", mission.id, e);
+        } else {
+            let _ = events_tx.send(AgentEvent::MissionStatusChanged {
+                mission_id: mission.id,
+                status: MissionStatus::Active,
+                summary: None,
+            });
+        }
+    }
+
     // Helper to create a new mission
     async fn create_new_mission(mission_store: &Arc<dyn MissionStore>) -> Result<Mission, String> {
         create_new_mission_with_title(mission_store, None, None, None, None, None, None, None).await
@@ -5508,6 +7828,12 @@ async fn control_actor_loop(
             }
         }
 
+        // Tool activity from the turn that was cut off, journaled as it happened
+        match mission_store.get_turn_journal(mission_id).await {
+            Ok(journal) => resume_parts.extend(turn_journal::format_turn_journal(&journal)),
+            Err(e) => tracing::warn!("Failed to load turn journal for {}: {}", mission_id, e),
+        }
+
         // Scan work directory for artifacts (shared workspace root)
         if workspace_root.exists() {
             resume_parts.push("\n## Work Directory Contents".to_string());
@@ -5583,45 +7909,25 @@ async fn control_actor_loop(
                     ControlCommand::UserMessage { id, content, agent: msg_agent, target_mission_id, respond } => {
                         // Smart routing: decide where to send this message based on target_mission_id
                         // and what's currently running.
-
-                        let current_mission_id = *current_mission.read().await;
-                        let running_mid = running_mission_id;
-                        let main_mission_id = if running_mid.is_some() {
-                            running_mid
-                        } else {
-                            current_mission_id
+                        let snapshot = RoutingSnapshot {
+                            target: target_mission_id,
+                            current: *current_mission.read().await,
+                            running: running_mission_id,
+                            main_is_running: running.is_some(),
+                            has_assistant_history: history.iter().any(|(role, _)| role == "assistant"),
                         };
-                        let main_is_running = running.is_some();
-
-                        // If no explicit target but current_mission differs from the
-                        // running mission (i.e., CreateMission switched the pointer),
-                        // infer the target as current_mission so it auto-starts in parallel.
-                        let effective_target = target_mission_id.or_else(|| {
-                            if main_is_running {
-                                if let Some(cid) = current_mission_id {
-                                    if running_mid != Some(cid) {
-                                        tracing::info!(
-                                            "Inferred target mission {} (current differs from running {:?})",
-                                            cid, running_mid
-                                        );
-                                        return Some(cid);
-                                    }
-                                }
+                        if target_mission_id.is_none() {
+                            if let Some(tid) = snapshot.effective_target() {
+                                tracing::info!(
+                                    "Inferred target mission {} (current differs from running {:?})",
+                                    tid, running_mission_id
+                                );
                             }
-                            None
-                        });
+                        }
 
-                        // Determine if target is already running somewhere
-                        let target_in_parallel = effective_target
-                            .map(|tid| parallel_runners.contains_key(&tid))
-                            .unwrap_or(false);
-                        let target_is_main = effective_target
-                            .map(|tid| main_mission_id == Some(tid))
-                            .unwrap_or(true); // No target = use main
-
-                        // Case 1: Target is already running in parallel_runners - queue to it
-                        if let Some(tid) = effective_target {
-                            if target_in_parallel {
+                        match route_message(&snapshot, |tid| parallel_runners.contains_key(&tid)) {
+                            // Case 1: Target is already running in parallel_runners - queue to it
+                            MessageRoute::QueueToParallel(tid) => {
                                 if let Some(runner) = parallel_runners.get_mut(&tid) {
                                     let was_running = runner.is_running();
                                     runner.queue_message(id, content.clone(), msg_agent);
@@ -5633,6 +7939,7 @@ async fn control_actor_loop(
                                     });
                                     // Try to start if not already running
                                     if !runner.is_running() {
+                                        runner.automated = mission_has_active_automation(&mission_store, tid).await;
                                         runner.start_next(
                                             config.clone(),
                                             Arc::clone(&root_agent),
@@ -5648,259 +7955,158 @@ async fn control_actor_loop(
                                         );
                                     }
                                     let _ = respond.send(was_running);
-                                    continue;
                                 }
+                                continue;
                             }
-                        }
-
-                        // Case 2: Target differs from main AND main is running → start parallel
-                        if let Some(tid) = effective_target {
-                            if !target_is_main && main_is_running {
-                                // Check capacity
-                                let parallel_running = parallel_runners.values().filter(|r| r.is_running()).count();
-                                let total_running = parallel_running + 1; // +1 for main
-                                let max_parallel = crate::settings::max_parallel_missions_cached_or(config.max_parallel_missions);
+                            // Case 2: Target differs from main AND main is running → start parallel
+                            MessageRoute::StartParallel(tid) => {
+                                // Check capacity, keeping reserved slots for interactive missions
+                                let max_parallel = crate::tenant::effective_max_parallel(config.max_parallel_missions, config.tenant.as_ref());
+                                let main_automated = match running_mission_id {
+                                    Some(mid) => mission_has_active_automation(&mission_store, mid).await,
+                                    None => false,
+                                };
+                                let slots = parallel_slot_allocation(max_parallel, &parallel_runners, Some(main_automated));
+                                let target_automated = mission_has_active_automation(&mission_store, tid).await;
 
-                                if total_running >= max_parallel {
+                                if let Some(reason) = slot_unavailable_reason(&slots, target_automated) {
                                     tracing::warn!(
-                                        "Cannot start parallel mission {}: max {} reached. \
+                                        "Cannot start parallel mission {}: {}. \
                                          Dropping targeted message to avoid sending to wrong mission.",
-                                        tid, max_parallel
+                                        tid, reason
                                     );
                                     let _ = events_tx.send(AgentEvent::Error {
-                                        message: format!(
-                                            "Cannot start mission {}: max parallel missions ({}) reached",
-                                            tid, max_parallel
-                                        ),
+                                        message: format!("Cannot start mission {}: {}", tid, reason),
+                                        code: None,
                                         mission_id: Some(tid),
                                         resumable: true,
                                     });
                                     let _ = respond.send(false);
                                     continue;
-                                } else {
-                                    // Load mission and start in parallel
-                                    match load_mission_record(&mission_store, tid).await {
-                                        Ok(mission) => {
-                                            // Activate mission: if pending, interrupted, blocked, completed, or failed, update status to active
-                                            if matches!(
-                                                mission.status,
-                                                MissionStatus::Pending
-                                                    | MissionStatus::Interrupted
-                                                    | MissionStatus::Blocked
-                                                    | MissionStatus::Completed
-                                                    | MissionStatus::Failed
-                                            ) {
-                                                tracing::info!(
-                                                    "Activating parallel mission {} (was {})",
-                                                    tid, mission.status
-                                                );
-                                                if let Err(e) = mission_store.update_mission_status(tid, MissionStatus::Active).await {
-                                                    tracing::warn!("Failed to activate parallel mission {}: {}", tid, e);
-                                                } else {
-                                                    let _ = events_tx.send(AgentEvent::MissionStatusChanged {
-                                                        mission_id: tid,
-                                                        status: MissionStatus::Active,
-                                                        summary: None,
-                                                    });
-                                                }
-                                            }
-                                            let mut runner = super::mission_runner::MissionRunner::new(
-                                                tid,
-                                                mission.workspace_id,
-                                                mission.agent.clone(),
-                                                Some(mission.backend.clone()),
-                                                mission.session_id.clone(),
-                                                mission.config_profile.clone(),
-                                                mission.model_override.clone(),
-                                                mission.model_effort.clone(),
-                                            );
-                                            // Load existing history
-                                            for entry in &mission.history {
-                                                runner.history.push((entry.role.clone(), entry.content.clone()));
-                                            }
-                                            // Queue the message
-                                            runner.queue_message(id, content.clone(), msg_agent);
-                                            // Emit user message event
-                                            let _ = events_tx.send(AgentEvent::UserMessage {
-                                                id,
-                                                content: content.clone(),
-                                                queued: false,
-                                                mission_id: Some(tid),
-                                            });
-                                            // Start execution
-                                            runner.start_next(
-                                                config.clone(),
-                                                Arc::clone(&root_agent),
-                                                Arc::clone(&mcp),
-                                                Arc::clone(&workspaces),
-                                                library.clone(),
-                                                events_tx.clone(),
-                                                Arc::clone(&tool_hub),
-                                                Arc::clone(&status),
-                                                mission_cmd_tx.clone(),
-                                                Arc::new(RwLock::new(Some(tid))),
-                                                secrets.clone(),
-                                            );
-                                            tracing::info!("Auto-started mission {} in parallel", tid);
-                                            parallel_runners.insert(tid, runner);
-                                            let _ = respond.send(false);
-                                            continue;
+                                }
+                                // Load mission and start in parallel
+                                match load_mission_record(&mission_store, tid).await {
+                                    Ok(mission) => {
+                                        activate_mission(&mission_store, &events_tx, &mission).await;
+                                        let mut runner = super::mission_runner::MissionRunner::new(
+                                            tid,
+                                            mission.workspace_id,
+                                            mission.agent.clone(),
+                                            Some(mission.backend.clone()),
+                                            mission.session_id.clone(),
+                                            mission.config_profile.clone(),
+                                            mission.model_override.clone(),
+                                            mission.model_effort.clone(),
+                                        );
+                                        runner.agent_version = mission.agent_version.clone();
+                                        runner.dry_run = mission.dry_run;
+                                        runner.env_profile = mission.env_profile.clone();
+                                        // Load existing history
+                                        for entry in &mission.history {
+                                            runner.history.push(entry.context_pair());
                                         }
-                                        Err(e) => {
-                                            tracing::error!(
-                                                "Failed to load mission {} for parallel: {}. \
-                                                 Dropping targeted message to avoid sending to wrong mission.",
+                                        // Queue the message
+                                        runner.queue_message(id, content.clone(), msg_agent);
+                                        // Emit user message event
+                                        let _ = events_tx.send(AgentEvent::UserMessage {
+                                            id,
+                                            content: content.clone(),
+                                            queued: false,
+                                            mission_id: Some(tid),
+                                        });
+                                        // Start execution
+                                        runner.automated = mission_has_active_automation(&mission_store, tid).await;
+                                        runner.start_next(
+                                            config.clone(),
+                                            Arc::clone(&root_agent),
+                                            Arc::clone(&mcp),
+                                            Arc::clone(&workspaces),
+                                            library.clone(),
+                                            events_tx.clone(),
+                                            Arc::clone(&tool_hub),
+                                            Arc::clone(&status),
+                                            mission_cmd_tx.clone(),
+                                            Arc::new(RwLock::new(Some(tid))),
+                                            secrets.clone(),
+                                        );
+                                        tracing::info!("Auto-started mission {} in parallel", tid);
+                                        parallel_runners.insert(tid, runner);
+                                    }
+                                    Err(e) => {
+                                        tracing::error!(
+                                            "Failed to load mission {} for parallel: {}. \
+                                             Dropping targeted message to avoid sending to wrong mission.",
+                                            tid, e
+                                        );
+                                        let _ = events_tx.send(AgentEvent::Error {
+                                            message: format!(
+                                                "Failed to load mission {}: {}",
                                                 tid, e
-                                            );
-                                            let _ = events_tx.send(AgentEvent::Error {
-                                                message: format!(
-                                                    "Failed to load mission {}: {}",
-                                                    tid, e
-                                                ),
-                                                mission_id: Some(tid),
-                                                resumable: true,
-                                            });
-                                            let _ = respond.send(false);
-                                            continue;
-                                        }
+                                            ),
+                                            code: Some(ErrorCode::Internal),
+                                            mission_id: Some(tid),
+                                            resumable: true,
+                                        });
                                     }
                                 }
+                                let _ = respond.send(false);
+                                continue;
                             }
-                        }
-
-                        // Case 3: Queue to main session (default behavior)
-                        // Auto-create mission on first message if none exists
-                        {
-                            let mission_id = *current_mission.read().await;
-                            if mission_id.is_none() {
-                                // Use effective_target if available, otherwise create new
-                                if let Some(tid) = effective_target {
-                                    // Load mission history from DB so continuation detection
-                                    // works correctly (e.g., after server restart when
-                                    // current_mission is None but the mission has prior turns).
-                                    if let Ok(mission) = load_mission_record(&mission_store, tid).await {
-                                        if !mission.history.is_empty() {
-                                            history.clear();
-                                            for entry in &mission.history {
-                                                history.push((entry.role.clone(), entry.content.clone()));
-                                            }
-                                            tracing::info!(
-                                                "Loaded {} history entries for target mission {} (first message after session start)",
-                                                mission.history.len(), tid
-                                            );
-                                        }
-                                        // Activate mission if it was pending/interrupted/blocked/completed
-                                        if matches!(
-                                            mission.status,
-                                            MissionStatus::Pending
-                                                | MissionStatus::Interrupted
-                                                | MissionStatus::Blocked
-                                                | MissionStatus::Completed
-                                                | MissionStatus::Failed
-                                        ) {
-                                            tracing::info!(
-                                                "Activating main mission {} (was {})",
-                                                tid, mission.status
-                                            );
-                                            if let Err(e) = mission_store.update_mission_status(tid, MissionStatus::Active).await {
-                                                tracing::warn!("Failed to activate main mission {}: {}", tid, e);
-                                            } else {
-                                                let _ = events_tx.send(AgentEvent::MissionStatusChanged {
-                                                    mission_id: tid,
-                                                    status: MissionStatus::Active,
-                                                    summary: None,
-                                                });
-                                            }
-                                        }
+                            // Case 3: Queue to main session (default behavior)
+                            MessageRoute::Main(MainSessionStep::Keep) => {}
+                            MessageRoute::Main(MainSessionStep::Adopt(tid)) => {
+                                // Load mission history from DB so continuation detection
+                                // works correctly (e.g., after server restart when
+                                // current_mission is None but the mission has prior turns).
+                                if let Ok(mission) = load_mission_record(&mission_store, tid).await {
+                                    if !mission.history.is_empty() {
+                                        history = mission.history.iter().map(MissionHistoryEntry::context_pair).collect();
+                                        tracing::info!(
+                                            "Loaded {} history entries for target mission {} (first message after session start)",
+                                            mission.history.len(), tid
+                                        );
                                     }
-                                    *current_mission.write().await = Some(tid);
-                                    tracing::info!("Set current mission to target: {}", tid);
-                                } else if let Ok(new_mission) = create_new_mission(&mission_store).await {
+                                    activate_mission(&mission_store, &events_tx, &mission).await;
+                                }
+                                *current_mission.write().await = Some(tid);
+                                tracing::info!("Set current mission to target: {}", tid);
+                            }
+                            // Auto-create mission on first message if none exists
+                            MessageRoute::Main(MainSessionStep::CreateMission) => {
+                                if let Ok(new_mission) = create_new_mission(&mission_store).await {
                                     *current_mission.write().await = Some(new_mission.id);
                                     tracing::info!("Auto-created mission: {}", new_mission.id);
                                 }
-                            } else if let Some(tid) = effective_target {
-                                if !main_is_running {
-                                    if mission_id != Some(tid) {
-                                        // Switch main session to target mission
-                                        persist_mission_history(
-                                            &mission_store,
-                                            &events_tx,
-                                            &current_mission,
-                                            &history,
-                                        )
-                                        .await;
-                                        if let Ok(mission) = load_mission_record(&mission_store, tid).await {
-                                            history.clear();
-                                            for entry in &mission.history {
-                                                history.push((entry.role.clone(), entry.content.clone()));
-                                            }
-                                            // Activate mission if it was pending/interrupted/blocked/completed
-                                            if matches!(
-                                                mission.status,
-                                                MissionStatus::Pending
-                                                    | MissionStatus::Interrupted
-                                                    | MissionStatus::Blocked
-                                                    | MissionStatus::Completed
-                                                    | MissionStatus::Failed
-                                            ) {
-                                                tracing::info!(
-                                                    "Activating switched mission {} (was {})",
-                                                    tid, mission.status
-                                                );
-                                                if let Err(e) = mission_store.update_mission_status(tid, MissionStatus::Active).await {
-                                                    tracing::warn!("Failed to activate switched mission {}: {}", tid, e);
-                                                } else {
-                                                    let _ = events_tx.send(AgentEvent::MissionStatusChanged {
-                                                        mission_id: tid,
-                                                        status: MissionStatus::Active,
-                                                        summary: None,
-                                                    });
-                                                }
-                                            }
-                                        }
-                                        *current_mission.write().await = Some(tid);
-                                        tracing::info!("Switched main session to mission: {}", tid);
-                                    } else if !history.iter().any(|(role, _)| role == "assistant") {
-                                        // Same mission but no assistant history in memory
-                                        // (e.g., after server restart). Reload from database
-                                        // so Claude Code continuation detection works correctly.
-                                        if let Ok(mission) = load_mission_record(&mission_store, tid).await {
-                                            if !mission.history.is_empty() {
-                                                history.clear();
-                                                for entry in &mission.history {
-                                                    history.push((entry.role.clone(), entry.content.clone()));
-                                                }
-                                                tracing::info!(
-                                                    "Reloaded {} history entries for mission {} (session continuity)",
-                                                    mission.history.len(), tid
-                                                );
-                                            }
-                                            // Activate mission if it was pending/interrupted/blocked/completed (same mission, reloading)
-                                            if matches!(
-                                                mission.status,
-                                                MissionStatus::Pending
-                                                    | MissionStatus::Interrupted
-                                                    | MissionStatus::Blocked
-                                                    | MissionStatus::Completed
-                                                    | MissionStatus::Failed
-                                            ) {
-                                                tracing::info!(
-                                                    "Activating reloaded mission {} (was {})",
-                                                    tid, mission.status
-                                                );
-                                                if let Err(e) = mission_store.update_mission_status(tid, MissionStatus::Active).await {
-                                                    tracing::warn!("Failed to activate reloaded mission {}: {}", tid, e);
-                                                } else {
-                                                    let _ = events_tx.send(AgentEvent::MissionStatusChanged {
-                                                        mission_id: tid,
-                                                        status: MissionStatus::Active,
-                                                        summary: None,
-                                                    });
-                                                }
-                                            }
-                                        }
+                            }
+                            MessageRoute::Main(MainSessionStep::SwitchTo(tid)) => {
+                                persist_mission_history(
+                                    &mission_store,
+                                    &events_tx,
+                                    &metadata_refresh,
+                                    &current_mission,
+                                    &history,
+                                )
+                                .await;
+                                if let Ok(mission) = load_mission_record(&mission_store, tid).await {
+                                    history = mission.history.iter().map(MissionHistoryEntry::context_pair).collect();
+                                    activate_mission(&mission_store, &events_tx, &mission).await;
+                                }
+                                *current_mission.write().await = Some(tid);
+                                tracing::info!("Switched main session to mission: {}", tid);
+                            }
+                            // Same mission but no assistant history in memory
+                            // (e.g., after server restart). Reload from database
+                            // so Claude Code continuation detection works correctly.
+                            MessageRoute::Main(MainSessionStep::ReloadHistory(tid)) => {
+                                if let Ok(mission) = load_mission_record(&mission_store, tid).await {
+                                    if !mission.history.is_empty() {
+                                        history = mission.history.iter().map(MissionHistoryEntry::context_pair).collect();
+                                        tracing::info!(
+                                            "Reloaded {} history entries for mission {} (session continuity)",
+                                            mission.history.len(), tid
+                                        );
                                     }
+                                    activate_mission(&mission_store, &events_tx, &mission).await;
                                 }
                             }
                         }
@@ -5911,6 +8117,7 @@ async fn control_actor_loop(
                         // This ensures we use the same mission_id for events and execution
                         let target_mission_id = *current_mission.read().await;
                         queue.push_back((id, content, msg_agent, target_mission_id));
+                        main_state.advance(MissionRunEvent::Queued);
                         let status_mission_id = if running.is_some() {
                             running_mission_id
                         } else {
@@ -5947,6 +8154,7 @@ async fn control_actor_loop(
                                 persist_mission_history_to(
                                     &mission_store,
                                     &events_tx,
+                                    &metadata_refresh,
                                     msg_target_mid,
                                     &history,
                                 )
@@ -5973,31 +8181,8 @@ async fn control_actor_loop(
                                 let mission_id = msg_target_mid;
                                 let (workspace_id, model_override, model_effort, mission_agent, backend_id, session_id, mission_config_profile) = if let Some(mid) = mission_id {
                                     match mission_store.get_mission(mid).await {
-                                        Ok(Some(mission)) => {
-                                            // Activate mission: if pending, interrupted, blocked, completed, or failed, update status to active
-                                            if matches!(
-                                                mission.status,
-                                                MissionStatus::Pending
-                                                    | MissionStatus::Interrupted
-                                                    | MissionStatus::Blocked
-                                                    | MissionStatus::Completed
-                                                    | MissionStatus::Failed
-                                            ) {
-                                                tracing::info!(
-                                                    "Activating mission {} (was {})",
-                                                    mid, mission.status
-                                                );
-                                                if let Err(e) = mission_store.update_mission_status(mid, MissionStatus::Active).await {
-                                                    tracing::warn!("Failed to activate mission {}: {}", mid, e);
-                                                } else {
-                                                    // Notify frontend of status change
-                                                    let _ = events_tx.send(AgentEvent::MissionStatusChanged {
-                                                        mission_id: mid,
-                                                        status: MissionStatus::Active,
-                                                        summary: None,
-                                                    });
-                                                }
-                                            }
+                                        Ok(Some(mission)) => {
+                                            activate_mission(&mission_store, &events_tx, &mission).await;
                                             (
                                                 Some(mission.workspace_id),
                                                 mission.model_override.clone(),
@@ -6030,6 +8215,7 @@ async fn control_actor_loop(
                                 // Per-message agent overrides mission agent
                                 let agent_override = per_msg_agent.or(mission_agent);
                                 running_cancel = Some(cancel.clone());
+                                main_state.advance(MissionRunEvent::Started);
                                 running_mission_id = mission_id;
                                 // Reset activity tracking when new task starts
                                 main_runner_last_activity = std::time::Instant::now();
@@ -6076,14 +8262,21 @@ async fn control_actor_loop(
                         let _ = tool_hub.resolve(&tool_call_id, result).await;
                         tracing::debug!(tool_call_id = %tool_call_id, name = %name, "ToolResult delivered to hub");
                     }
+                    ControlCommand::ClaimIdempotencyKey { key, respond } => {
+                        let _ = respond.send(idempotency.claim(&key));
+                    }
+                    ControlCommand::CompleteIdempotencyKey { key, response } => {
+                        idempotency.complete(&key, response);
+                    }
                     ControlCommand::Cancel => {
                         if let Some(token) = &running_cancel {
                             token.cancel();
+                            main_state.advance(MissionRunEvent::CancelRequested);
                             // Don't send Error event here - the task will complete and send
                             // an AssistantMessage with the cancellation result when it finishes.
                             // Sending both causes duplicate UI messages.
                         } else {
-                            let _ = events_tx.send(AgentEvent::Error { message: "No running task to cancel".to_string(), mission_id: None, resumable: false });
+                            let _ = events_tx.send(AgentEvent::Error { message: "No running task to cancel".to_string(), code: None, mission_id: None, resumable: false });
                         }
                     }
                     ControlCommand::LoadMission { id, respond } => {
@@ -6091,6 +8284,7 @@ async fn control_actor_loop(
                         persist_mission_history(
                             &mission_store,
                             &events_tx,
+                            &metadata_refresh,
                             &current_mission,
                             &history,
                         )
@@ -6105,7 +8299,7 @@ async fn control_actor_loop(
                             Ok(mission) => {
                                 // Update history from loaded mission
                                 history = mission.history.iter()
-                                    .map(|e| (e.role.clone(), e.content.clone()))
+                                    .map(MissionHistoryEntry::context_pair)
                                     .collect();
                                 *current_mission.write().await = Some(id);
 
@@ -6138,6 +8332,7 @@ async fn control_actor_loop(
                         persist_mission_history(
                             &mission_store,
                             &events_tx,
+                            &metadata_refresh,
                             &current_mission,
                             &history,
                         )
@@ -6185,7 +8380,12 @@ async fn control_actor_loop(
                     ControlCommand::SetMissionStatus { id, status: new_status, respond } => {
                         let current_id = *current_mission.read().await;
                         if current_id == Some(id) {
-                            if let Some(tree) = current_tree.read().await.clone() {
+                            let tree = cost_breakdown::tree_with_costs(
+                                current_tree.read().await.clone(),
+                                cost_tracker.tree(id),
+                            );
+                            cost_tracker.forget(id);
+                            if let Some(tree) = tree {
                                 if let Err(e) = mission_store.update_mission_tree(id, &tree).await
                                 {
                                     tracing::warn!("Failed to save mission tree: {}", e);
@@ -6200,6 +8400,7 @@ async fn control_actor_loop(
                             maybe_schedule_mission_metadata_refresh_for_status(
                                 &mission_store,
                                 &events_tx,
+                                &metadata_refresh,
                                 id,
                                 new_status,
                             );
@@ -6220,7 +8421,7 @@ async fn control_actor_loop(
                             });
                             match mission_store.get_mission(id).await {
                                 Ok(Some(updated)) => {
-                                    record_metadata_refresh_baseline_from_mission(id, &updated);
+                                    metadata_refresh.record_baseline_from_mission(id, &updated);
                                     emit_mission_metadata_updated_event(&events_tx, id, &updated);
                                 }
                                 Ok(None) => {
@@ -6243,16 +8444,21 @@ async fn control_actor_loop(
                     ControlCommand::StartParallel { mission_id, content, respond } => {
                         tracing::info!("StartParallel requested for mission {}", mission_id);
 
-                        // Count currently running parallel missions
-                        let parallel_running = parallel_runners.values().filter(|r| r.is_running()).count();
-                        let main_running = if running.is_some() { 1 } else { 0 };
-                        let total_running = parallel_running + main_running;
-                        let max_parallel = crate::settings::max_parallel_missions_cached_or(config.max_parallel_missions);
+                        // Check capacity, keeping reserved slots for interactive missions
+                        let max_parallel = crate::tenant::effective_max_parallel(config.max_parallel_missions, config.tenant.as_ref());
+                        let main_automated = match (running.is_some(), running_mission_id) {
+                            (true, Some(mid)) => Some(mission_has_active_automation(&mission_store, mid).await),
+                            (true, None) => Some(false),
+                            (false, _) => None,
+                        };
+                        let slots = parallel_slot_allocation(max_parallel, &parallel_runners, main_automated);
+                        let target_automated = mission_has_active_automation(&mission_store, mission_id).await;
 
-                        if total_running >= max_parallel {
+                        if let Some(reason) = slot_unavailable_reason(&slots, target_automated) {
                             let _ = respond.send(Err(format!(
-                                "Maximum parallel missions ({}) reached. {} running.",
-                                max_parallel, total_running
+                                "Cannot start mission: {}. {} running.",
+                                reason,
+                                slots.running()
                             )));
                         } else if let std::collections::hash_map::Entry::Vacant(entry) =
                             parallel_runners.entry(mission_id)
@@ -6277,16 +8483,20 @@ async fn control_actor_loop(
                                 mission.model_override.clone(),
                                 mission.model_effort.clone(),
                             );
+                            runner.agent_version = mission.agent_version.clone();
+                            runner.dry_run = mission.dry_run;
+                            runner.env_profile = mission.env_profile.clone();
 
                             // Load existing history into runner to preserve conversation context
                             for entry in &mission.history {
-                                runner.history.push((entry.role.clone(), entry.content.clone()));
+                                runner.history.push(entry.context_pair());
                             }
 
                             // Queue the initial message (no per-message agent override for parallel start)
                             runner.queue_message(Uuid::new_v4(), content, None);
 
                             // Start execution
+                            runner.automated = mission_has_active_automation(&mission_store, mission_id).await;
                             let started = runner.start_next(
                                 config.clone(),
                                 Arc::clone(&root_agent),
@@ -6317,43 +8527,18 @@ async fn control_actor_loop(
                     }
                     ControlCommand::CancelMission { mission_id, respond } => {
                         // First check parallel runners
-                        if let Some(runner) = parallel_runners.get_mut(&mission_id) {
-                            runner.cancel();
-                            // Update status to Interrupted so the mission can be
-                            // resumed later (fixes #149: cancel left status as pending).
-                            if let Err(e) = mission_store
-                                .update_mission_status(mission_id, MissionStatus::Interrupted)
-                                .await
-                            {
-                                tracing::warn!(
-                                    "Failed to update cancelled parallel mission status: {}",
-                                    e
-                                );
-                            } else {
-                                maybe_schedule_mission_metadata_refresh_for_status(
-                                    &mission_store,
-                                    &events_tx,
-                                    mission_id,
-                                    MissionStatus::Interrupted,
-                                );
-                            }
-                            let _ = events_tx.send(AgentEvent::Error {
-                                message: format!("Parallel mission {} cancelled", mission_id),
-                                mission_id: Some(mission_id),
-                                resumable: true, // Cancelled missions can be resumed
-                            });
-                            let _ = events_tx.send(AgentEvent::MissionStatusChanged {
-                                mission_id,
-                                status: MissionStatus::Interrupted,
-                                summary: None,
-                            });
-                            parallel_runners.remove(&mission_id);
-                            close_mission_desktop_sessions(
-                                &mission_store,
-                                mission_id,
-                                &config.working_dir,
-                            )
-                            .await;
+                        if cancel_parallel_runner(
+                            &mut parallel_runners,
+                            mission_id,
+                            &format!("Parallel mission {} cancelled", mission_id),
+                            &mission_store,
+                            &events_tx,
+                            &metadata_refresh,
+                            &config.working_dir,
+                            &mut partial_turns,
+                        )
+                        .await
+                        {
                             let _ = respond.send(Ok(()));
                         } else {
                             // Check if this is the currently executing mission
@@ -6363,7 +8548,8 @@ async fn control_actor_loop(
                                 // Cancel the current execution
                                 if let Some(token) = &running_cancel {
                                     token.cancel();
-                                    close_mission_desktop_sessions(
+                                    main_state.advance(MissionRunEvent::CancelRequested);
+                                    close_mission_sessions(
                                         &mission_store,
                                         mission_id,
                                         &config.working_dir,
@@ -6391,24 +8577,19 @@ async fn control_actor_loop(
                             if let Some(mission_id) = running_mission_id {
                                 let seconds_since_activity =
                                     main_runner_last_activity.elapsed().as_secs();
-                                let state_label = {
+                                let mission_state = {
                                     let status_guard = status.read().await;
                                     if status_guard.mission_id == Some(mission_id)
                                         && status_guard.state == ControlRunState::WaitingForTool
                                     {
-                                        "waiting_for_tool"
+                                        MissionRunState::WaitingForTool
                                     } else {
-                                        "running"
+                                        main_state
                                     }
                                 };
-                                let mission_state = if state_label == "waiting_for_tool" {
-                                    super::mission_runner::MissionRunState::WaitingForTool
-                                } else {
-                                    super::mission_runner::MissionRunState::Running
-                                };
                                 running_list.push(super::mission_runner::RunningMissionInfo {
                                     mission_id,
-                                    state: state_label.to_string(),
+                                    state: mission_state.label().to_string(),
                                     queue_len: queue.len(),
                                     history_len: history.len(),
                                     seconds_since_activity,
@@ -6446,6 +8627,7 @@ async fn control_actor_loop(
                                 persist_mission_history(
                                     &mission_store,
                                     &events_tx,
+                                    &metadata_refresh,
                                     &current_mission,
                                     &history,
                                 )
@@ -6453,7 +8635,7 @@ async fn control_actor_loop(
 
                                 // Load the mission's history into current state
                                 history = mission.history.iter()
-                                    .map(|e| (e.role.clone(), e.content.clone()))
+                                    .map(MissionHistoryEntry::context_pair)
                                     .collect();
                                 *current_mission.write().await = Some(mission_id);
 
@@ -6467,6 +8649,7 @@ async fn control_actor_loop(
                                     maybe_schedule_mission_metadata_refresh_for_status(
                                         &mission_store,
                                         &events_tx,
+                                        &metadata_refresh,
                                         mission_id,
                                         MissionStatus::Active,
                                     );
@@ -6522,6 +8705,7 @@ async fn control_actor_loop(
                                         let session_id = mission.session_id.clone();
                                         let mission_config_profile = mission.config_profile.clone();
                                         running_cancel = Some(cancel.clone());
+                                        main_state.advance(MissionRunEvent::Started);
                                         // Capture which mission this task is working on (the resumed mission)
                                         running_mission_id = Some(mission_id);
                                         // Reset activity tracking so stall detection starts fresh
@@ -6592,6 +8776,7 @@ async fn control_actor_loop(
                                     persist_mission_history(
                                         &mission_store,
                                         &events_tx,
+                                        &metadata_refresh,
                                         &current_mission,
                                         &history,
                                     )
@@ -6608,6 +8793,7 @@ async fn control_actor_loop(
                                     maybe_schedule_mission_metadata_refresh_for_status(
                                         &mission_store,
                                         &events_tx,
+                                        &metadata_refresh,
                                         mission_id,
                                         MissionStatus::Interrupted,
                                     );
@@ -6618,6 +8804,7 @@ async fn control_actor_loop(
                                 // Cancel execution
                                 if let Some(token) = &running_cancel {
                                     token.cancel();
+                                    main_state.advance(MissionRunEvent::CancelRequested);
                                 }
                             }
                         }
@@ -6631,11 +8818,13 @@ async fn control_actor_loop(
                                 .map(|(role, content)| MissionHistoryEntry {
                                     role: role.clone(),
                                     content: content.clone(),
+                                    tool_name: None,
                                 })
                                 .collect();
                             persist_mission_history_and_schedule_metadata_refresh(
                                 &mission_store,
                                 &events_tx,
+                                &metadata_refresh,
                                 *mission_id,
                                 &entries,
                             )
@@ -6648,6 +8837,7 @@ async fn control_actor_loop(
                                 maybe_schedule_mission_metadata_refresh_for_status(
                                     &mission_store,
                                     &events_tx,
+                                    &metadata_refresh,
                                     *mission_id,
                                     MissionStatus::Interrupted,
                                 );
@@ -6658,6 +8848,10 @@ async fn control_actor_loop(
                             runner.cancel();
                         }
 
+                        // The process is exiting; don't leave refreshes
+                        // writing to the store behind.
+                        metadata_refresh.shutdown();
+
                         let _ = respond.send(interrupted_ids);
                     }
                     ControlCommand::GetQueue { respond } => {
@@ -6718,6 +8912,45 @@ async fn control_actor_loop(
 
                         let _ = respond.send(removed);
                     }
+                    ControlCommand::ReorderQueue { message_id, position, respond } => {
+                        let mut moved = false;
+                        if let Some(index) = queue.iter().position(|(id, _, _, _)| *id == message_id) {
+                            if let Some(entry) = queue.remove(index) {
+                                let position = position.min(queue.len());
+                                queue.insert(position, entry);
+                            }
+                            moved = true;
+                        }
+                        for runner in parallel_runners.values_mut() {
+                            moved |= runner.move_in_queue(message_id, position);
+                        }
+                        let _ = respond.send(moved);
+                    }
+                    ControlCommand::EditQueuedMessage { message_id, content, respond } => {
+                        let mut edited_in = None;
+                        if let Some((_, queued_content, _, target_mid)) =
+                            queue.iter_mut().find(|(id, _, _, _)| *id == message_id)
+                        {
+                            *queued_content = content.clone();
+                            edited_in = Some(*target_mid);
+                        }
+                        for (mid, runner) in parallel_runners.iter_mut() {
+                            if runner.edit_queued_message(message_id, &content) {
+                                edited_in = Some(Some(*mid));
+                            }
+                        }
+                        if let Some(mission_id) = edited_in {
+                            // Re-emit the queued message; the store updates the
+                            // persisted event in place since the id is unchanged.
+                            let _ = events_tx.send(AgentEvent::UserMessage {
+                                id: message_id,
+                                content,
+                                queued: true,
+                                mission_id,
+                            });
+                        }
+                        let _ = respond.send(edited_in.is_some());
+                    }
                     ControlCommand::ClearQueue { respond } => {
                         let mut cleared = queue.len();
                         queue.clear();
@@ -6751,13 +8984,32 @@ async fn control_actor_loop(
             mission_cmd = mission_cmd_rx.recv() => {
                 if let Some(cmd) = mission_cmd {
                     match cmd {
+                        crate::tools::mission::MissionControlCommand::RequestStatus { mission_id: id, status, justification } => {
+                            let request = status_requests.write().await.request(id, status, justification);
+                            tracing::info!(
+                                "Mission {} requested status {}; waiting for the user's confirmation",
+                                id,
+                                status
+                            );
+                            let _ = events_tx.send(AgentEvent::StatusChangeRequested {
+                                mission_id: id,
+                                status: MissionStatus::from(status),
+                                justification: request.justification,
+                            });
+                        }
+                        crate::tools::mission::MissionControlCommand::UpdateChecklist { mission_id: id, changes, respond } => {
+                            let result = mission_checklist::update_from_agent(
+                                &mission_store,
+                                &events_tx,
+                                &checklists,
+                                id,
+                                &changes,
+                            )
+                            .await;
+                            let _ = respond.send(result);
+                        }
                         crate::tools::mission::MissionControlCommand::SetStatus { mission_id: id, status, summary } => {
-                            let new_status = match status {
-                                crate::tools::mission::MissionStatusValue::Completed => MissionStatus::Completed,
-                                crate::tools::mission::MissionStatusValue::Failed => MissionStatus::Failed,
-                                crate::tools::mission::MissionStatusValue::Blocked => MissionStatus::Blocked,
-                                crate::tools::mission::MissionStatusValue::NotFeasible => MissionStatus::NotFeasible,
-                            };
+                            let new_status = MissionStatus::from(status);
                             let success = matches!(status, crate::tools::mission::MissionStatusValue::Completed);
                             if new_status == MissionStatus::Completed
                                 && mission_has_active_automation(&mission_store, id).await
@@ -6768,8 +9020,27 @@ async fn control_actor_loop(
                                 );
                                 continue;
                             }
-                            // Save the final tree before updating status
-                            if let Some(tree) = current_tree.read().await.clone() {
+                            // With completion verification enabled, the claim is checked when the
+                            // turn ends; the verified result then completes the mission.
+                            let turn_running = running_mission_id == Some(id)
+                                || parallel_runners.get(&id).is_some_and(|r| r.is_running());
+                            if new_status == MissionStatus::Completed
+                                && turn_running
+                                && super::completion_check::VerifyConfig::enabled()
+                            {
+                                tracing::info!(
+                                    "Deferring completion of mission {} until it is verified",
+                                    id
+                                );
+                                continue;
+                            }
+                            // Save the final tree (with attributed costs) before updating status
+                            let tree = cost_breakdown::tree_with_costs(
+                                current_tree.read().await.clone(),
+                                cost_tracker.tree(id),
+                            );
+                            cost_tracker.forget(id);
+                            if let Some(tree) = tree {
                                 if let Err(e) = mission_store.update_mission_tree(id, &tree).await {
                                     tracing::warn!("Failed to save mission tree: {}", e);
                                 } else {
@@ -6785,6 +9056,7 @@ async fn control_actor_loop(
                                 maybe_schedule_mission_metadata_refresh_for_status(
                                     &mission_store,
                                     &events_tx,
+                                    &metadata_refresh,
                                     id,
                                     new_status,
                                 );
@@ -6832,9 +9104,20 @@ async fn control_actor_loop(
                     running = None;
                     running_cancel = None;
                     running_mission_id = None;
+                    main_state.advance(MissionRunEvent::Finished {
+                        queued: !queue.is_empty(),
+                    });
                     main_runner_activity = None;
                     match res {
-                        Ok((_mid, _user_msg, agent_result)) => {
+                        Ok((_mid, _user_msg, mut agent_result)) => {
+                            // A cancelled turn keeps what it streamed so far
+                            let cancelled_turn = match (completed_mission_id, agent_result.terminal_reason) {
+                                (Some(mid), Some(TerminalReason::Cancelled)) => partial_turns.take(mid),
+                                _ => None,
+                            };
+                            if let Some(turn) = &cancelled_turn {
+                                agent_result.output = turn.history_content();
+                            }
                             // Only append assistant to local history if this mission is still the current mission.
                             // Note: User message was already added before execution started.
                             // If the user created a new mission mid-execution, history was cleared for that new mission,
@@ -6858,6 +9141,7 @@ async fn control_actor_loop(
                                         entries.push(MissionHistoryEntry {
                                             role: "assistant".to_string(),
                                             content: agent_result.output.clone(),
+                                            tool_name: None,
                                         });
                                         if let Err(e) =
                                             mission_store.update_mission_history(mid, &entries).await
@@ -6867,6 +9151,7 @@ async fn control_actor_loop(
                                             schedule_mission_metadata_refresh(
                                                 &mission_store,
                                                 &events_tx,
+                                                &metadata_refresh,
                                                 mid,
                                                 false,
                                             );
@@ -6906,21 +9191,12 @@ async fn control_actor_loop(
                                                 let new_status = match agent_result.terminal_reason {
                                                     Some(TerminalReason::Completed) => MissionStatus::Completed,
                                                     Some(TerminalReason::Cancelled) => MissionStatus::Interrupted,
-                                                    Some(TerminalReason::MaxIterations) => MissionStatus::Blocked,
+                                                    Some(TerminalReason::MaxIterations | TerminalReason::VerificationFailed) => MissionStatus::Blocked,
                                                     _ if agent_result.success => MissionStatus::Completed,
                                                     _ => MissionStatus::Failed,
                                                 };
                                                 // Convert terminal_reason to string for storage
-                                                let terminal_reason_str = agent_result.terminal_reason.map(|r| match r {
-                                                    TerminalReason::Completed => "completed",
-                                                    TerminalReason::Cancelled => "cancelled",
-                                                    TerminalReason::LlmError => "llm_error",
-                                                    TerminalReason::Stalled => "stalled",
-                                                    TerminalReason::InfiniteLoop => "infinite_loop",
-                                                    TerminalReason::MaxIterations => "max_iterations",
-                                                    TerminalReason::RateLimited => "rate_limited",
-                                                    TerminalReason::CapacityLimited => "capacity_limited",
-                                                });
+                                                let terminal_reason_str = agent_result.terminal_reason.map(TerminalReason::as_str);
                                                 if new_status == MissionStatus::Completed
                                                     && mission_has_active_automation(&mission_store, mission_id).await
                                                 {
@@ -6942,6 +9218,7 @@ async fn control_actor_loop(
                                                         maybe_schedule_mission_metadata_refresh_for_status(
                                                             &mission_store,
                                                             &events_tx,
+                                                            &metadata_refresh,
                                                             mission_id,
                                                             new_status,
                                                         );
@@ -6957,6 +9234,13 @@ async fn control_actor_loop(
                                                             Some(TerminalReason::LlmError) => Some("Model error".to_string()),
                                                             Some(TerminalReason::RateLimited) => Some("Provider rate limited".to_string()),
                                                             Some(TerminalReason::CapacityLimited) => Some("Provider capacity limit reached".to_string()),
+                                                            Some(TerminalReason::VerificationFailed) => Some("Completion could not be verified".to_string()),
+                                                            Some(TerminalReason::ProviderOverloaded) => Some("Provider overloaded".to_string()),
+                                                            Some(TerminalReason::ProviderAuthFailed) => Some("Provider rejected the credentials".to_string()),
+                                                            Some(TerminalReason::ContextOverflow) => Some("Conversation exceeds the model's context window".to_string()),
+                                                            Some(TerminalReason::ToolDeniedByPolicy) => Some("Tool call denied by policy".to_string()),
+                                                            Some(TerminalReason::SandboxViolation) => Some("Sandbox violation".to_string()),
+                                                            Some(TerminalReason::BudgetExceeded) => Some("Budget exceeded".to_string()),
                                                             None if agent_result.success => None,
                                                             None => Some("Unexpected termination".to_string()),
                                                         };
@@ -7031,9 +9315,13 @@ async fn control_actor_loop(
                             // Mark failures as resumable so UI can show a resume button
                             let resumable = !agent_result.success && completed_mission_id.is_some();
                             let model_used = agent_result.model_used.clone();
+                            let (content, cancelled, tool_calls) = match cancelled_turn {
+                                Some(turn) => (turn.content(), true, turn.tool_calls),
+                                None => (agent_result.output.clone(), false, Vec::new()),
+                            };
                             let _ = events_tx.send(AgentEvent::AssistantMessage {
                                 id: Uuid::new_v4(),
-                                content: agent_result.output.clone(),
+                                content,
                                 success: agent_result.success,
                                 cost_cents: agent_result.cost_cents,
                                 cost_source: agent_result.cost_source,
@@ -7045,6 +9333,8 @@ async fn control_actor_loop(
                                 mission_id: completed_mission_id,
                                 shared_files,
                                 resumable,
+                                cancelled,
+                                tool_calls,
                             });
                             if let Some(mission_id) = completed_mission_id {
                                 // Update automation executions based on agent outcome
@@ -7072,7 +9362,7 @@ async fn control_actor_loop(
                                     );
                                 }
 
-                                close_mission_desktop_sessions(
+                                close_mission_sessions(
                                     &mission_store,
                                     mission_id,
                                     &config.working_dir,
@@ -7083,6 +9373,7 @@ async fn control_actor_loop(
                         Err(e) => {
                             let _ = events_tx.send(AgentEvent::Error {
                                 message: format!("Control session task join failed: {}", e),
+                                code: Some(ErrorCode::Internal),
                                 mission_id: completed_mission_id,
                                 resumable: completed_mission_id.is_some(), // Can resume if mission exists
                             });
@@ -7114,6 +9405,7 @@ async fn control_actor_loop(
                                     maybe_schedule_mission_metadata_refresh_for_status(
                                         &mission_store,
                                         &events_tx,
+                                        &metadata_refresh,
                                         mission_id,
                                         MissionStatus::Failed,
                                     );
@@ -7123,7 +9415,7 @@ async fn control_actor_loop(
                                         summary: Some("Task execution failed unexpectedly".to_string()),
                                     });
                                 }
-                                close_mission_desktop_sessions(
+                                close_mission_sessions(
                                     &mission_store,
                                     mission_id,
                                     &config.working_dir,
@@ -7172,6 +9464,7 @@ async fn control_actor_loop(
                     persist_mission_history_to(
                         &mission_store,
                         &events_tx,
+                        &metadata_refresh,
                         msg_target_mid,
                         &history,
                     )
@@ -7194,6 +9487,7 @@ async fn control_actor_loop(
                     let tree_ref = Arc::clone(&current_tree);
                     let progress_ref = Arc::clone(&progress);
                     running_cancel = Some(cancel.clone());
+                    main_state.advance(MissionRunEvent::Started);
                     // Use the mission ID that was captured when message was queued
                     // This prevents race conditions where current_mission changes between queueing and execution
                     let mission_id = msg_target_mid;
@@ -7268,6 +9562,139 @@ async fn control_actor_loop(
                 }
             }
             // Poll parallel runners for completion
+            _ = heartbeat_tick.tick() => {
+                let mut running_ids: Vec<Uuid> = Vec::new();
+                if running.is_some() {
+                    running_ids.extend(running_mission_id);
+                }
+                running_ids.extend(
+                    parallel_runners
+                        .iter()
+                        .filter(|(_, runner)| runner.is_running())
+                        .map(|(mission_id, _)| *mission_id),
+                );
+                // Written off the actor so a slow store doesn't delay commands.
+                let store = Arc::clone(&mission_store);
+                tokio::spawn(async move {
+                    if let Err(e) = store
+                        .record_runner_heartbeats(
+                            health::instance_id(),
+                            std::process::id(),
+                            &running_ids,
+                        )
+                        .await
+                    {
+                        tracing::warn!("Failed to record runner heartbeats: {}", e);
+                    }
+                });
+            }
+            _ = stall_tick.tick(), if stall_watcher.is_some() => {
+                // Idle time of every mission that is running and not waiting on a
+                // frontend tool (waiting on the user isn't a stall).
+                let mut idle = Vec::new();
+                if running.is_some() {
+                    if let Some(mission_id) = running_mission_id {
+                        let waiting = {
+                            let status_guard = status.read().await;
+                            status_guard.mission_id == Some(mission_id)
+                                && status_guard.state == ControlRunState::WaitingForTool
+                        };
+                        if !waiting {
+                            idle.push((mission_id, main_runner_last_activity.elapsed()));
+                        }
+                    }
+                }
+                for (mission_id, runner) in parallel_runners.iter() {
+                    if runner.is_running()
+                        && runner.state != super::mission_runner::MissionRunState::WaitingForTool
+                    {
+                        idle.push((*mission_id, runner.last_activity.elapsed()));
+                    }
+                }
+
+                let Some(watcher) = stall_watcher.as_mut() else {
+                    continue;
+                };
+                let policy = watcher.policy().clone();
+                for action in watcher.observe(stall_watch::CHECK_INTERVAL, &idle) {
+                    match action {
+                        StallAction::Stalled { mission_id, idle } => {
+                            tracing::warn!(
+                                "Mission {} stalled ({}s without progress)",
+                                mission_id,
+                                idle.as_secs()
+                            );
+                            if policy.nudge {
+                                // Interrupt the stuck turn; the nudge runs next in
+                                // the same session.
+                                if running_mission_id == Some(mission_id) {
+                                    queue.push_front((
+                                        Uuid::new_v4(),
+                                        stall_watch::NUDGE_MESSAGE.to_string(),
+                                        None,
+                                        Some(mission_id),
+                                    ));
+                                    if let Some(token) = &running_cancel {
+                                        token.cancel();
+                                        main_state.advance(MissionRunEvent::CancelRequested);
+                                    }
+                                } else if let Some(runner) = parallel_runners.get_mut(&mission_id) {
+                                    runner.queue.push_front(super::mission_runner::QueuedMessage {
+                                        id: Uuid::new_v4(),
+                                        content: stall_watch::NUDGE_MESSAGE.to_string(),
+                                        agent: None,
+                                    });
+                                    runner.cancel();
+                                }
+                            }
+                            let _ = events_tx.send(AgentEvent::MissionStalled {
+                                mission_id,
+                                idle_seconds: idle.as_secs(),
+                                nudged: policy.nudge,
+                                cancel_after_minutes: policy
+                                    .cancel_after
+                                    .map(|d| d.as_secs() / 60),
+                            });
+                        }
+                        StallAction::Cancel { mission_id, idle } => {
+                            tracing::warn!(
+                                "Cancelling stalled mission {} ({}s without progress)",
+                                mission_id,
+                                idle.as_secs()
+                            );
+                            if running_mission_id == Some(mission_id) {
+                                // The completion path marks the mission Interrupted.
+                                if let Some(token) = &running_cancel {
+                                    token.cancel();
+                                    main_state.advance(MissionRunEvent::CancelRequested);
+                                }
+                                close_mission_sessions(
+                                    &mission_store,
+                                    mission_id,
+                                    &config.working_dir,
+                                )
+                                .await;
+                            } else {
+                                cancel_parallel_runner(
+                                    &mut parallel_runners,
+                                    mission_id,
+                                    &format!(
+                                        "Mission {} cancelled after stalling for {} minutes",
+                                        mission_id,
+                                        idle.as_secs() / 60
+                                    ),
+                                    &mission_store,
+                                    &events_tx,
+                                    &metadata_refresh,
+                                    &config.working_dir,
+                                    &mut partial_turns,
+                                )
+                                .await;
+                            }
+                        }
+                    }
+                }
+            }
             _ = tokio::time::sleep(std::time::Duration::from_millis(100)) => {
                 let mut completed_missions = Vec::new();
 
@@ -7329,6 +9756,8 @@ async fn control_actor_loop(
                                 mission_id: Some(*mission_id),
                                 shared_files,
                                 resumable,
+                                cancelled: false,
+                                tool_calls: Vec::new(),
                             });
 
                             // Update automation executions based on agent outcome
@@ -7365,11 +9794,13 @@ async fn control_actor_loop(
                                 .map(|(role, content)| MissionHistoryEntry {
                                     role: role.clone(),
                                     content: content.clone(),
+                                    tool_name: None,
                                 })
                                 .collect();
                             persist_mission_history_and_schedule_metadata_refresh(
                                 &mission_store,
                                 &events_tx,
+                                &metadata_refresh,
                                 *mission_id,
                                 &entries,
                             )
@@ -7408,6 +9839,7 @@ async fn control_actor_loop(
                                         runner.session_id = m.session_id;
                                     }
                                 }
+                                runner.automated = mission_has_active_automation(&mission_store, *mission_id).await;
                                 let started = runner.start_next(
                                     config.clone(),
                                     Arc::clone(&root_agent),
@@ -7446,7 +9878,11 @@ async fn control_actor_loop(
                                                     mission_id
                                                 );
                                             } else if let Err(e) = mission_store
-                                                .update_mission_status(*mission_id, new_status)
+                                                .update_mission_status_with_reason(
+                                                    *mission_id,
+                                                    new_status,
+                                                    result.terminal_reason.map(TerminalReason::as_str),
+                                                )
                                                 .await
                                             {
                                                 tracing::warn!(
@@ -7457,6 +9893,7 @@ async fn control_actor_loop(
                                                 maybe_schedule_mission_metadata_refresh_for_status(
                                                     &mission_store,
                                                     &events_tx,
+                                                    &metadata_refresh,
                                                     *mission_id,
                                                     new_status,
                                                 );
@@ -7478,7 +9915,7 @@ async fn control_actor_loop(
                 // Remove completed runners and clean up their desktop sessions
                 for mid in completed_missions {
                     parallel_runners.remove(&mid);
-                    close_mission_desktop_sessions(
+                    close_mission_sessions(
                         &mission_store,
                         mid,
                         &config.working_dir,
@@ -7489,11 +9926,13 @@ async fn control_actor_loop(
             }
             // Update last_activity for runners when we receive events for them
             event = events_rx.recv() => {
+                partial_turns.sync();
                 if let Ok(event) = event {
                     // Extract mission_id from event if present
                     let mission_id = match &event {
                         AgentEvent::ToolCall { mission_id, .. } => *mission_id,
                         AgentEvent::ToolResult { mission_id, .. } => *mission_id,
+                        AgentEvent::ToolOutputDelta { mission_id, .. } => *mission_id,
                         AgentEvent::Thinking { mission_id, .. } => *mission_id,
                         AgentEvent::TextDelta { mission_id, .. } => *mission_id,
                         AgentEvent::UserMessage { mission_id, .. } => *mission_id,
@@ -7518,11 +9957,148 @@ async fn control_actor_loop(
                         }
                     }
 
+                    // --- Cost attribution per agent-tree node ---
+                    if let AgentEvent::LlmUsage { mission_id: Some(mid), .. }
+                    | AgentEvent::ToolCall { mission_id: Some(mid), .. } = &event
+                    {
+                        if !cost_tracker.is_tracking(*mid) {
+                            let stored = mission_store.get_mission_tree(*mid).await.ok().flatten();
+                            cost_tracker.seed(*mid, stored);
+                        }
+                    }
+                    if let Some(mid) = cost_tracker.observe(&event) {
+                        let live = if *current_mission.read().await == Some(mid) {
+                            current_tree.read().await.clone()
+                        } else {
+                            None
+                        };
+                        if let Some(tree) =
+                            cost_breakdown::tree_with_costs(live, cost_tracker.tree(mid))
+                        {
+                            if let Err(e) = mission_store.update_mission_tree(mid, &tree).await {
+                                tracing::warn!("Failed to save mission cost tree: {}", e);
+                            }
+                        }
+                    }
+
+                    // --- File conflicts between running missions ---
+                    if let AgentEvent::ToolCall {
+                        name,
+                        args,
+                        tool_call_id,
+                        mission_id: Some(mid),
+                    } = &event
+                    {
+                        let running_ids: Vec<Uuid> = running_mission_id
+                            .into_iter()
+                            .chain(
+                                parallel_runners
+                                    .iter()
+                                    .filter(|(_, runner)| runner.is_running())
+                                    .map(|(id, _)| *id),
+                            )
+                            .collect();
+                        let conflicts = conflict_tracker.observe(*mid, name, args, &running_ids);
+                        let blocked = !conflicts.is_empty()
+                            && conflict_tracker.policy() == ConflictPolicy::Block;
+                        if blocked {
+                            if running_mission_id == Some(*mid) {
+                                if let Some(token) = &running_cancel {
+                                    token.cancel();
+                                    main_state.advance(MissionRunEvent::CancelRequested);
+                                }
+                            } else if let Some(runner) = parallel_runners.get_mut(mid) {
+                                runner.cancel();
+                            }
+                        }
+                        for conflict in conflicts {
+                            tracing::warn!(
+                                "Mission {} is editing {} already written by {:?}{}",
+                                mid,
+                                conflict.path,
+                                conflict.other_missions,
+                                if blocked { "; cancelling its turn" } else { "" }
+                            );
+                            let _ = events_tx.send(AgentEvent::ConflictDetected {
+                                path: conflict.path,
+                                tool_call_id: tool_call_id.clone(),
+                                tool_name: name.clone(),
+                                other_missions: conflict.other_missions,
+                                blocked,
+                                mission_id: Some(*mid),
+                            });
+                        }
+                    }
+
+                    // --- Dangerous commands waiting for approval ---
+                    // The approval hook already refused to run the command;
+                    // stop the turn until the user decides.
+                    if let AgentEvent::ToolCall {
+                        name,
+                        args,
+                        tool_call_id,
+                        mission_id: Some(mid),
+                    } = &event
+                    {
+                        let pending = match super::mission_report::shell_command(name, args) {
+                            Some(command) if approval_required => {
+                                approvals.write().await.check(*mid, tool_call_id, command)
+                            }
+                            _ => None,
+                        };
+                        if let Some(request) = pending {
+                            tracing::warn!(
+                                "Mission {} was held from running `{}` ({}); cancelling its turn until approved",
+                                mid,
+                                request.command,
+                                request.reason
+                            );
+                            if running_mission_id == Some(*mid) {
+                                if let Some(token) = &running_cancel {
+                                    token.cancel();
+                                    main_state.advance(MissionRunEvent::CancelRequested);
+                                }
+                            } else if let Some(runner) = parallel_runners.get_mut(mid) {
+                                runner.cancel();
+                            }
+                            let _ = events_tx.send(AgentEvent::ApprovalRequired {
+                                tool_call_id: request.tool_call_id,
+                                command: request.command,
+                                reason: request.reason,
+                                mission_id: *mid,
+                            });
+                        }
+                    }
+
                     // --- Activity tracking & subtask detection ---
                     match &event {
                         AgentEvent::ToolCall { name, args, tool_call_id, mission_id } => {
                             if let Some(mid) = mission_id {
-                                let label = activity_label_from_tool_call(name, args);
+                                // Labels use the language of the mission's workspace
+                                let workspace_id = match parallel_runners.get(mid) {
+                                    Some(runner) => Some(runner.workspace_id),
+                                    None => match main_runner_workspace {
+                                        Some((cached_mid, ws)) if cached_mid == *mid => Some(ws),
+                                        _ => {
+                                            let ws = mission_store
+                                                .get_mission(*mid)
+                                                .await
+                                                .ok()
+                                                .flatten()
+                                                .map(|m| m.workspace_id);
+                                            main_runner_workspace = ws.map(|ws| (*mid, ws));
+                                            ws
+                                        }
+                                    },
+                                };
+                                let locale = workspace_id
+                                    .map(crate::i18n::workspace_locale)
+                                    .unwrap_or_else(crate::i18n::default_locale);
+                                let label = localized_activity_label(
+                                    name,
+                                    args,
+                                    crate::i18n::catalog(&locale),
+                                );
 
                                 // Update activity on runner
                                 if running_mission_id == Some(*mid) {
@@ -7563,7 +10139,10 @@ async fn control_actor_loop(
                                     } else {
                                         (0, 0)
                                     };
-                                    if total > 0 {
+                                    // A checklist reports the mission's progress instead
+                                    if total > 0
+                                        && !mission_checklist::has_checklist(&checklists, &mission_store, *mid).await
+                                    {
                                         let _ = events_tx.send(AgentEvent::Progress {
                                             total_subtasks: total,
                                             completed_subtasks: completed,
@@ -7674,7 +10253,9 @@ async fn control_actor_loop(
                                             break;
                                         }
                                     }
-                                    if changed {
+                                    if changed
+                                        && !mission_checklist::has_checklist(&checklists, &mission_store, *mid).await
+                                    {
                                         let total = subtasks.len();
                                         let completed = subtasks.iter().filter(|s| s.completed).count();
                                         let _ = events_tx.send(AgentEvent::Progress {
@@ -7777,7 +10358,7 @@ async fn control_actor_loop(
                                 existing.started_at = now.clone();
                             } else {
                                 sessions.push(DesktopSessionInfo {
-                                    display,
+                                    display: display.clone(),
                                     resolution,
                                     started_at: now.clone(),
                                     stopped_at: None,
@@ -7806,6 +10387,17 @@ async fn control_actor_loop(
                                 err
                             );
                         }
+
+                        let _ = events_tx.send(AgentEvent::DesktopSession {
+                            mission_id: *mid,
+                            display,
+                            action: if is_start {
+                                super::desktop::DesktopSessionAction::Opened
+                            } else {
+                                super::desktop::DesktopSessionAction::Closed
+                            },
+                            keep_alive_until: None,
+                        });
                     }
 
                     // Handle session ID updates (for backends like Amp that generate their own IDs)
@@ -7838,8 +10430,107 @@ async fn control_actor_loop(
     }
 }
 
+/// Run one control turn, verifying completion claims when enabled (see
+/// `completion_check`).
 #[allow(clippy::too_many_arguments)]
 async fn run_single_control_turn(
+    config: Config,
+    root_agent: AgentRef,
+    mcp: Arc<McpRegistry>,
+    workspaces: workspace::SharedWorkspaceStore,
+    library: SharedLibrary,
+    events_tx: broadcast::Sender<AgentEvent>,
+    tool_hub: Arc<FrontendToolHub>,
+    status: Arc<RwLock<ControlStatus>>,
+    cancel: CancellationToken,
+    history: Vec<(String, String)>,
+    user_message: String,
+    mission_control: Option<crate::tools::mission::MissionControl>,
+    tree_snapshot: Arc<RwLock<Option<AgentTreeNode>>>,
+    progress_snapshot: Arc<RwLock<ExecutionProgress>>,
+    mission_id: Option<Uuid>,
+    workspace_id: Option<Uuid>,
+    backend_id: Option<String>,
+    model_override: Option<String>,
+    model_effort: Option<String>,
+    agent_override: Option<String>,
+    session_id: Option<String>,
+    force_session_resume: bool,
+    mission_config_profile: Option<String>,
+) -> crate::agents::AgentResult {
+    let run_turn = |history: Vec<(String, String)>, user_message: String| {
+        Box::pin(run_single_control_turn_once(
+            config.clone(),
+            Arc::clone(&root_agent),
+            Arc::clone(&mcp),
+            Arc::clone(&workspaces),
+            library.clone(),
+            events_tx.clone(),
+            Arc::clone(&tool_hub),
+            Arc::clone(&status),
+            cancel.clone(),
+            history,
+            user_message,
+            mission_control.clone(),
+            Arc::clone(&tree_snapshot),
+            Arc::clone(&progress_snapshot),
+            mission_id,
+            workspace_id,
+            backend_id.clone(),
+            model_override.clone(),
+            model_effort.clone(),
+            agent_override.clone(),
+            session_id.clone(),
+            force_session_resume,
+            mission_config_profile.clone(),
+        ))
+    };
+    let _context_usage = mission_id.map(|mid| {
+        super::context_usage::ContextUsageReporter::for_turn(
+            &events_tx,
+            &config,
+            mid,
+            &history,
+            &user_message,
+        )
+    });
+    let result = run_turn(history.clone(), user_message.clone()).await;
+    let (Some(verify), Some(mission_id)) = (
+        super::completion_check::VerifyConfig::from_env(),
+        mission_id,
+    ) else {
+        return result.with_classified_failure();
+    };
+
+    let ctx = super::completion_check::VerifyContext {
+        config: &verify,
+        mission_id,
+        events_tx: &events_tx,
+        cancel: &cancel,
+        workspace: super::completion_check::test_workspace(
+            &verify,
+            &workspaces,
+            &config,
+            workspace_id,
+            mission_id,
+        )
+        .await,
+    };
+    let request = super::completion_check::original_request(&history, &user_message);
+    super::completion_check::verify_completion(
+        ctx,
+        &request,
+        history,
+        &user_message,
+        result,
+        |message, history| run_turn(history, message),
+    )
+    .await
+    .with_classified_failure()
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_single_control_turn_once(
     mut config: Config,
     _root_agent: AgentRef,
     mcp: Arc<McpRegistry>,
@@ -7947,6 +10638,21 @@ async fn run_single_control_turn(
             tracing::warn!("Failed to write runtime workspace state: {}", e);
         }
     }
+    if let (Some(mid), Some(ws)) = (mission_id, runtime_workspace.as_ref()) {
+        let mut environment = super::mission_environment::MissionEnvironment::new(
+            mid,
+            backend_id.as_deref().unwrap_or("opencode"),
+            ws,
+            &working_dir_path,
+            mcp.list_configs().await,
+        );
+        environment.model = config.default_model.clone();
+        environment.model_effort = requested_model_effort.clone();
+        environment.config_profile = effective_config_profile.clone();
+        environment.agent = agent_override.clone();
+        environment.env_profile = ws.env_profile.clone();
+        super::mission_environment::capture(&working_dir_path, &environment).await;
+    }
 
     // Build a task prompt that includes conversation context with size limits.
     let history_for_prompt = match history.last() {
@@ -7985,6 +10691,20 @@ async fn run_single_control_turn(
     let fallback_workspace = workspace::Workspace::default_host(config.working_dir.clone());
     let exec_workspace = runtime_workspace.as_ref().unwrap_or(&fallback_workspace);
 
+    // First turn after a backend handoff: only Codex gets the conversation in its
+    // prompt here, so carry it over for the others and start a fresh session.
+    let mut user_message = user_message;
+    let mut handed_off = false;
+    if let Some(backend) = backend_id.as_deref() {
+        if let Some(snapshot) = super::handoff::take_snapshot(&ctx.working_dir, backend).await {
+            if backend != "codex" {
+                user_message =
+                    snapshot.prompt(&user_message, config.context.max_history_total_chars);
+                handed_off = true;
+            }
+        }
+    }
+
     // Execute based on backend
     let result = match backend_id.as_deref() {
         Some("claudecode") => {
@@ -7997,8 +10717,8 @@ async fn run_single_control_turn(
             // so we check for assistant messages to determine if this is truly a continuation.
             // Also use --resume if force_session_resume is set (e.g., for mission resume operations
             // where the session exists but history may not have assistant messages yet).
-            let is_continuation =
-                force_session_resume || history.iter().any(|(role, _)| role == "assistant");
+            let is_continuation = !handed_off
+                && (force_session_resume || history.iter().any(|(role, _)| role == "assistant"));
             let mut result = Box::pin(super::mission_runner::run_claudecode_turn(
                 exec_workspace,
                 &ctx.working_dir,
@@ -8100,8 +10820,8 @@ async fn run_single_control_turn(
                 Ok(id) => id,
                 Err(r) => return r,
             };
-            let is_continuation =
-                force_session_resume || history.iter().any(|(role, _)| role == "assistant");
+            let is_continuation = !handed_off
+                && (force_session_resume || history.iter().any(|(role, _)| role == "assistant"));
             let api_key = super::mission_runner::get_amp_api_key_from_config();
             Box::pin(super::mission_runner::run_amp_turn(
                 exec_workspace,
@@ -8135,15 +10855,37 @@ async fn run_single_control_turn(
                 mid,
                 events_tx.clone(),
                 cancel,
-                &config.working_dir,
-                session_id.as_deref(),
-                None,
+                &config.working_dir,
+                session_id.as_deref(),
+                None,
+            ))
+            .await
+        }
+        Some("ollama") => {
+            let mid = match require_mission_id(mission_id, "Ollama", &events_tx) {
+                Ok(id) => id,
+                Err(r) => return r,
+            };
+            // Only an explicit override applies; DEFAULT_MODEL names a hosted model.
+            let mut ollama_config = config.clone();
+            ollama_config.default_model = requested_model.clone();
+            Box::pin(super::mission_runner::run_ollama_turn(
+                &ollama_config,
+                &library,
+                effective_config_profile.as_deref(),
+                &ctx.working_dir,
+                &history,
+                &user_message,
+                mid,
+                events_tx.clone(),
+                cancel,
             ))
             .await
         }
         Some(backend) if backend != "opencode" => {
             let _ = events_tx.send(AgentEvent::Error {
                 message: format!("Unsupported backend: {}", backend),
+                code: Some(ErrorCode::Internal),
                 mission_id,
                 resumable: mission_id.is_some(),
             });
@@ -8185,6 +10927,12 @@ pub struct CreateAutomationRequest {
     pub stop_policy: Option<mission_store::StopPolicy>,
     #[serde(default)]
     pub fresh_session: Option<mission_store::FreshSession>,
+    /// Automatic retry policy for the mission's failed runs
+    #[serde(default)]
+    pub mission_retry_policy: Option<mission_store::MissionRetryPolicy>,
+    /// Windows during which the automation isn't triggered
+    #[serde(default)]
+    pub blackout_windows: Vec<crate::blackout::BlackoutWindow>,
     /// When true, trigger the first execution immediately after creation.
     #[serde(default)]
     pub start_immediately: bool,
@@ -8198,15 +10946,53 @@ pub struct UpdateAutomationRequest {
     pub retry_config: Option<mission_store::RetryConfig>,
     pub stop_policy: Option<mission_store::StopPolicy>,
     pub fresh_session: Option<mission_store::FreshSession>,
+    /// Replaces the retry policy (`max_attempts: 0` disables retries)
+    pub mission_retry_policy: Option<mission_store::MissionRetryPolicy>,
+    /// Replaces the blackout windows (`[]` removes them)
+    pub blackout_windows: Option<Vec<crate::blackout::BlackoutWindow>>,
     pub active: Option<bool>,
 }
 
+fn automation_keyset(automation: &mission_store::Automation) -> Keyset {
+    Keyset {
+        sort_key: automation.created_at.clone(),
+        id: automation.id,
+    }
+}
+
+fn execution_keyset(execution: &mission_store::AutomationExecution) -> Keyset {
+    Keyset {
+        sort_key: execution.triggered_at.clone(),
+        id: execution.id,
+    }
+}
+
+/// Page an in-memory automation list, newest first.
+fn automation_page(
+    automations: Vec<mission_store::Automation>,
+    page: &PageQuery,
+) -> Result<Page<mission_store::Automation>, (StatusCode, String)> {
+    let total = automations.len();
+    let cursor = page.cursor()?;
+    let (automations, next) = paginate(
+        automations,
+        cursor.as_ref().map(|c| &c.keyset),
+        page.limit(100),
+        automation_keyset,
+    );
+    Ok((
+        page_headers(total, next.map(Cursor::new).as_ref()),
+        Json(automations),
+    ))
+}
+
 /// List all automations for a mission.
 pub async fn list_mission_automations(
     State(state): State<Arc<AppState>>,
     Extension(user): Extension<AuthUser>,
     Path(mission_id): Path<Uuid>,
-) -> Result<Json<Vec<mission_store::Automation>>, (StatusCode, String)> {
+    Query(page): Query<PageQuery>,
+) -> Result<Page<mission_store::Automation>, (StatusCode, String)> {
     let control = control_for_user(&state, &user).await;
 
     let automations = control
@@ -8215,14 +11001,15 @@ pub async fn list_mission_automations(
         .await
         .map_err(internal_error)?;
 
-    Ok(Json(automations))
+    automation_page(automations, &page)
 }
 
 /// List all active automations across missions.
 pub async fn list_active_automations(
     State(state): State<Arc<AppState>>,
     Extension(user): Extension<AuthUser>,
-) -> Result<Json<Vec<mission_store::Automation>>, (StatusCode, String)> {
+    Query(page): Query<PageQuery>,
+) -> Result<Page<mission_store::Automation>, (StatusCode, String)> {
     let control = control_for_user(&state, &user).await;
 
     let automations = control
@@ -8231,7 +11018,7 @@ pub async fn list_active_automations(
         .await
         .map_err(internal_error)?;
 
-    Ok(Json(automations))
+    automation_page(automations, &page)
 }
 
 /// Create an automation for a mission.
@@ -8247,6 +11034,8 @@ pub async fn create_automation(
     if let mission_store::CommandSource::Library { ref name } = req.command_source {
         validate_library_command(&state, name).await?;
     }
+    crate::blackout::validate_windows(&req.blackout_windows)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
 
     // Generate webhook_id if trigger type is Webhook
     let trigger = match req.trigger {
@@ -8288,6 +11077,8 @@ pub async fn create_automation(
         created_at: mission_store::now_string(),
         last_triggered_at,
         retry_config: req.retry_config.unwrap_or_default(),
+        mission_retry_policy: req.mission_retry_policy,
+        blackout_windows: req.blackout_windows,
         consecutive_failures: 0,
     };
 
@@ -8344,6 +11135,8 @@ pub async fn create_automation(
                 completed_at: None,
                 error: None,
                 retry_count: 0,
+                first_event_seq: None,
+                last_event_seq: None,
             };
             let _ = control
                 .mission_store
@@ -8435,6 +11228,15 @@ pub async fn update_automation(
         automation.fresh_session = fresh_session;
     }
 
+    if let Some(policy) = req.mission_retry_policy {
+        automation.mission_retry_policy = Some(policy);
+    }
+
+    if let Some(windows) = req.blackout_windows {
+        crate::blackout::validate_windows(&windows).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+        automation.blackout_windows = windows;
+    }
+
     if let Some(active) = req.active {
         automation.active = active;
     }
@@ -8473,40 +11275,72 @@ pub async fn delete_automation(
     }
 }
 
-/// Get execution history for an automation.
+/// Get execution history for an automation, paginated with `limit`/`cursor`.
 pub async fn get_automation_executions(
     State(state): State<Arc<AppState>>,
     Extension(user): Extension<AuthUser>,
     Path(automation_id): Path<Uuid>,
-) -> Result<Json<Vec<mission_store::AutomationExecution>>, (StatusCode, String)> {
+    Query(page): Query<PageQuery>,
+) -> Result<Page<mission_store::AutomationExecution>, (StatusCode, String)> {
     let control = control_for_user(&state, &user).await;
 
     let _automation = require_automation(&control.mission_store, automation_id).await?;
 
+    let limit = page.limit(100);
+    let cursor = page.cursor()?;
+    let total = control
+        .mission_store
+        .count_automation_executions(automation_id)
+        .await
+        .map_err(internal_error)?;
     let executions = control
         .mission_store
-        .get_automation_executions(automation_id, Some(100))
+        .get_automation_executions(
+            automation_id,
+            Some(limit + 1),
+            cursor.as_ref().map(|c| &c.keyset),
+        )
         .await
         .map_err(internal_error)?;
 
-    Ok(Json(executions))
+    let (executions, next) = split_page(executions, limit, execution_keyset);
+    Ok((
+        page_headers(total, next.map(Cursor::new).as_ref()),
+        Json(executions),
+    ))
 }
 
-/// Get all automation executions for a mission.
+/// Get all automation executions for a mission, paginated with `limit`/`cursor`.
 pub async fn get_mission_automation_executions(
     State(state): State<Arc<AppState>>,
     Extension(user): Extension<AuthUser>,
     Path(mission_id): Path<Uuid>,
-) -> Result<Json<Vec<mission_store::AutomationExecution>>, (StatusCode, String)> {
+    Query(page): Query<PageQuery>,
+) -> Result<Page<mission_store::AutomationExecution>, (StatusCode, String)> {
     let control = control_for_user(&state, &user).await;
 
+    let limit = page.limit(100);
+    let cursor = page.cursor()?;
+    let total = control
+        .mission_store
+        .count_mission_automation_executions(mission_id)
+        .await
+        .map_err(internal_error)?;
     let executions = control
         .mission_store
-        .get_mission_automation_executions(mission_id, Some(100))
+        .get_mission_automation_executions(
+            mission_id,
+            Some(limit + 1),
+            cursor.as_ref().map(|c| &c.keyset),
+        )
         .await
         .map_err(internal_error)?;
 
-    Ok(Json(executions))
+    let (executions, next) = split_page(executions, limit, execution_keyset);
+    Ok((
+        page_headers(total, next.map(Cursor::new).as_ref()),
+        Json(executions),
+    ))
 }
 
 /// Webhook receiver endpoint for triggering automations.
@@ -8761,6 +11595,8 @@ pub async fn webhook_receiver(
         completed_at: None,
         error: None,
         retry_count: 0,
+        first_event_seq: None,
+        last_event_seq: None,
     };
 
     let mut execution = match control
@@ -8863,6 +11699,34 @@ mod tests {
     use super::*;
     use std::sync::Arc;
 
+    #[tokio::test]
+    async fn dropped_idempotency_claims_release_their_key() {
+        let (cmd_tx, mut cmd_rx) = mpsc::channel(4);
+        drop(ClaimedIdempotencyKey {
+            claim: Some((cmd_tx.clone(), "message:a".to_string())),
+        });
+        assert!(matches!(
+            cmd_rx.recv().await,
+            Some(ControlCommand::CompleteIdempotencyKey { key, response: None }) if key == "message:a"
+        ));
+
+        let claim = ClaimedIdempotencyKey {
+            claim: Some((cmd_tx, "message:b".to_string())),
+        };
+        claim
+            .complete(&Ok(Json(serde_json::json!({ "id": 1 }))))
+            .await;
+        assert!(matches!(
+            cmd_rx.recv().await,
+            Some(ControlCommand::CompleteIdempotencyKey {
+                response: Some(_),
+                ..
+            })
+        ));
+        // Completing consumed the claim, so nothing else is sent.
+        assert!(cmd_rx.recv().await.is_none());
+    }
+
     #[test]
     fn test_parse_image_tag() {
         let tags = parse_rich_tags(r#"<image path="./chart.png" alt="My Chart" />"#);
@@ -9099,6 +11963,7 @@ And the report:
             &history,
             Some("Hi"),
             false,
+            None,
         )
         .await;
 
@@ -9131,6 +11996,7 @@ And the report:
             &history,
             history.first().map(|(_, content)| content.as_str()),
             false,
+            None,
         )
         .await;
 
@@ -9144,6 +12010,50 @@ And the report:
         );
     }
 
+    #[tokio::test]
+    async fn test_generate_mission_metadata_updates_prefers_llm_generated_metadata() {
+        let store: Arc<dyn MissionStore> = Arc::new(mission_store::InMemoryMissionStore::new());
+        let mission = store
+            .create_mission(None, None, None, None, None, None, None)
+            .await
+            .expect("create mission");
+        let history = vec![
+            (
+                "user".to_string(),
+                "Investigate oauth callback timeout in production".to_string(),
+            ),
+            (
+                "assistant".to_string(),
+                "Investigate oauth callback timeout root cause\nStarting with logs.".to_string(),
+            ),
+        ];
+        let generated = super::super::metadata_llm::GeneratedMetadata {
+            title: Some("Debug OAuth callback timeouts".to_string()),
+            short_description: None,
+        };
+
+        let (updated_title, updated_short_description) = generate_mission_metadata_updates(
+            &store,
+            mission.id,
+            &mission,
+            &history,
+            history.first().map(|(_, content)| content.as_str()),
+            false,
+            Some(&generated),
+        )
+        .await;
+
+        assert_eq!(
+            updated_title.as_deref(),
+            Some("Debug OAuth callback timeouts")
+        );
+        // Fields the model left out still come from the heuristics
+        assert_eq!(
+            updated_short_description.as_deref(),
+            Some("Investigate oauth callback timeout root cause")
+        );
+    }
+
     #[tokio::test]
     async fn test_generate_mission_metadata_updates_bootstrap_title_uses_first_assistant_response()
     {
@@ -9174,6 +12084,7 @@ And the report:
             &history,
             history.first().map(|(_, content)| content.as_str()),
             false,
+            None,
         )
         .await;
 
@@ -9217,6 +12128,7 @@ And the report:
             &history,
             history.first().map(|(_, content)| content.as_str()),
             false,
+            None,
         )
         .await;
 
@@ -9256,6 +12168,7 @@ And the report:
             &history,
             history.first().map(|(_, content)| content.as_str()),
             false,
+            None,
         )
         .await;
 
@@ -9301,6 +12214,7 @@ And the report:
             &history,
             history.first().map(|(_, c)| c.as_str()),
             true,
+            None,
         )
         .await;
 
@@ -9315,6 +12229,7 @@ And the report:
     async fn test_refresh_mission_metadata_preserves_user_source_when_only_short_description_changes(
     ) {
         let store: Arc<dyn MissionStore> = Arc::new(mission_store::InMemoryMissionStore::new());
+        let metadata_refresh = metadata_refresh::SharedMetadataRefresh::default();
         let mission = store
             .create_mission(
                 Some("User chosen title"),
@@ -9338,11 +12253,13 @@ And the report:
                     MissionHistoryEntry {
                         role: "user".to_string(),
                         content: "Investigate oauth callback timeout".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "assistant".to_string(),
                         content: "Root cause is stale oauth callback cache state across retries."
                             .to_string(),
+                        tool_name: None,
                     },
                 ],
             )
@@ -9350,7 +12267,14 @@ And the report:
             .expect("seed history");
 
         let (events_tx, _events_rx) = broadcast::channel::<AgentEvent>(16);
-        refresh_mission_metadata_for_milestone(&store, &events_tx, mission.id).await;
+        refresh_mission_metadata_from_store(
+            &store,
+            &events_tx,
+            &metadata_refresh,
+            mission.id,
+            true,
+        )
+        .await;
 
         let refreshed = store
             .get_mission(mission.id)
@@ -9403,6 +12327,7 @@ And the report:
             &history,
             history.first().map(|(_, content)| content.as_str()),
             false,
+            None,
         )
         .await;
 
@@ -9452,6 +12377,7 @@ And the report:
             &history,
             history.first().map(|(_, content)| content.as_str()),
             false,
+            None,
         )
         .await;
 
@@ -9507,6 +12433,7 @@ And the report:
             &history,
             history.first().map(|(_, content)| content.as_str()),
             false,
+            None,
         )
         .await;
 
@@ -9557,6 +12484,7 @@ And the report:
             &history,
             history.first().map(|(_, content)| content.as_str()),
             false,
+            None,
         )
         .await;
 
@@ -9623,6 +12551,7 @@ And the report:
             &history,
             history.first().map(|(_, content)| content.as_str()),
             false,
+            None,
         )
         .await;
 
@@ -9676,6 +12605,7 @@ And the report:
             &history,
             history.first().map(|(_, content)| content.as_str()),
             false,
+            None,
         )
         .await;
         assert_eq!(without_milestone, (None, None));
@@ -9687,6 +12617,7 @@ And the report:
             &history,
             history.first().map(|(_, content)| content.as_str()),
             true,
+            None,
         )
         .await;
         assert_eq!(
@@ -9774,6 +12705,7 @@ And the report:
             &history,
             history.first().map(|(_, content)| content.as_str()),
             true,
+            None,
         )
         .await;
 
@@ -9786,6 +12718,7 @@ And the report:
     #[tokio::test]
     async fn test_refresh_mission_metadata_for_milestone_updates_store_and_emits_event() {
         let store: Arc<dyn MissionStore> = Arc::new(mission_store::InMemoryMissionStore::new());
+        let metadata_refresh = metadata_refresh::SharedMetadataRefresh::default();
         let model_override = "openai/gpt-5";
         let mission = store
             .create_mission(
@@ -9817,12 +12750,14 @@ And the report:
                     MissionHistoryEntry {
                         role: "user".to_string(),
                         content: "Investigate oauth callback timeout".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "assistant".to_string(),
                         content:
                             "Investigate oauth callback timeout root cause\nStarting with ingress logs."
                                 .to_string(),
+                        tool_name: None,
                     },
                 ],
             )
@@ -9830,7 +12765,14 @@ And the report:
             .expect("seed history");
 
         let (events_tx, mut events_rx) = broadcast::channel::<AgentEvent>(16);
-        refresh_mission_metadata_for_milestone(&store, &events_tx, mission.id).await;
+        refresh_mission_metadata_from_store(
+            &store,
+            &events_tx,
+            &metadata_refresh,
+            mission.id,
+            true,
+        )
+        .await;
 
         let refreshed = store
             .get_mission(mission.id)
@@ -9881,6 +12823,7 @@ And the report:
     #[tokio::test]
     async fn test_schedule_mission_metadata_refresh_for_milestone_updates_store_and_emits_event() {
         let store: Arc<dyn MissionStore> = Arc::new(mission_store::InMemoryMissionStore::new());
+        let metadata_refresh = metadata_refresh::SharedMetadataRefresh::default();
         let mission = store
             .create_mission(Some("Legacy title"), None, None, None, None, None, None)
             .await
@@ -9903,10 +12846,12 @@ And the report:
                     MissionHistoryEntry {
                         role: "user".to_string(),
                         content: "Investigate websocket reconnect loop".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "assistant".to_string(),
                         content: "Investigate websocket reconnect loop root cause".to_string(),
+                        tool_name: None,
                     },
                 ],
             )
@@ -9914,7 +12859,12 @@ And the report:
             .expect("seed history");
 
         let (events_tx, mut events_rx) = broadcast::channel::<AgentEvent>(16);
-        schedule_mission_metadata_refresh_for_milestone(&store, &events_tx, mission.id);
+        schedule_mission_metadata_refresh_for_milestone(
+            &store,
+            &events_tx,
+            &metadata_refresh,
+            mission.id,
+        );
 
         let saw_metadata_event = tokio::time::timeout(std::time::Duration::from_secs(2), async {
             loop {
@@ -9954,6 +12904,7 @@ And the report:
     #[tokio::test]
     async fn test_maybe_schedule_mission_metadata_refresh_for_status_forces_terminal_statuses() {
         let store: Arc<dyn MissionStore> = Arc::new(mission_store::InMemoryMissionStore::new());
+        let metadata_refresh = metadata_refresh::SharedMetadataRefresh::default();
         let mission = store
             .create_mission(Some("Legacy title"), None, None, None, None, None, None)
             .await
@@ -9976,14 +12927,17 @@ And the report:
                     MissionHistoryEntry {
                         role: "user".to_string(),
                         content: "Investigate websocket reconnect loop".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "assistant".to_string(),
                         content: "Checking ingress timeout settings".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "assistant".to_string(),
                         content: "Investigate websocket reconnect loop root cause".to_string(),
+                        tool_name: None,
                     },
                 ],
             )
@@ -9994,6 +12948,7 @@ And the report:
         maybe_schedule_mission_metadata_refresh_for_status(
             &store,
             &events_tx,
+            &metadata_refresh,
             mission.id,
             MissionStatus::Completed,
         );
@@ -10037,6 +12992,7 @@ And the report:
     async fn test_maybe_schedule_mission_metadata_refresh_for_status_skips_non_milestone_statuses()
     {
         let store: Arc<dyn MissionStore> = Arc::new(mission_store::InMemoryMissionStore::new());
+        let metadata_refresh = metadata_refresh::SharedMetadataRefresh::default();
         let mission = store
             .create_mission(
                 Some("Existing mission title"),
@@ -10077,14 +13033,17 @@ And the report:
                     MissionHistoryEntry {
                         role: "user".to_string(),
                         content: "Investigate websocket reconnect loop".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "assistant".to_string(),
                         content: "Investigate websocket reconnect loop root cause".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "assistant".to_string(),
                         content: "Collecting additional traces".to_string(),
+                        tool_name: None,
                     },
                 ],
             )
@@ -10095,6 +13054,7 @@ And the report:
         maybe_schedule_mission_metadata_refresh_for_status(
             &store,
             &events_tx,
+            &metadata_refresh,
             mission.id,
             MissionStatus::Active,
         );
@@ -10139,6 +13099,7 @@ And the report:
     #[tokio::test]
     async fn test_schedule_mission_metadata_refresh_updates_store_without_force_refresh() {
         let store: Arc<dyn MissionStore> = Arc::new(mission_store::InMemoryMissionStore::new());
+        let metadata_refresh = metadata_refresh::SharedMetadataRefresh::default();
         let mission = store
             .create_mission(None, None, None, None, None, None, None)
             .await
@@ -10150,10 +13111,12 @@ And the report:
                     MissionHistoryEntry {
                         role: "user".to_string(),
                         content: "Investigate websocket reconnect loop".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "assistant".to_string(),
                         content: "Investigate websocket reconnect loop root cause".to_string(),
+                        tool_name: None,
                     },
                 ],
             )
@@ -10161,7 +13124,7 @@ And the report:
             .expect("seed history");
 
         let (events_tx, mut events_rx) = broadcast::channel::<AgentEvent>(16);
-        schedule_mission_metadata_refresh(&store, &events_tx, mission.id, false);
+        schedule_mission_metadata_refresh(&store, &events_tx, &metadata_refresh, mission.id, false);
 
         let saw_metadata_event = tokio::time::timeout(std::time::Duration::from_secs(2), async {
             loop {
@@ -10201,6 +13164,7 @@ And the report:
     #[tokio::test]
     async fn test_persist_mission_history_and_schedule_metadata_refresh_emits_metadata_update() {
         let store: Arc<dyn MissionStore> = Arc::new(mission_store::InMemoryMissionStore::new());
+        let metadata_refresh = metadata_refresh::SharedMetadataRefresh::default();
         let mission = store
             .create_mission(None, None, None, None, None, None, None)
             .await
@@ -10209,16 +13173,22 @@ And the report:
             MissionHistoryEntry {
                 role: "user".to_string(),
                 content: "Debug websocket reconnect loop".to_string(),
+                tool_name: None,
             },
             MissionHistoryEntry {
                 role: "assistant".to_string(),
                 content: "Root cause is stale session token refresh ordering".to_string(),
+                tool_name: None,
             },
         ];
 
         let (events_tx, mut events_rx) = broadcast::channel::<AgentEvent>(16);
         persist_mission_history_and_schedule_metadata_refresh(
-            &store, &events_tx, mission.id, &entries,
+            &store,
+            &events_tx,
+            &metadata_refresh,
+            mission.id,
+            &entries,
         )
         .await;
 
@@ -10261,6 +13231,7 @@ And the report:
     async fn test_schedule_mission_metadata_refresh_skips_non_cadence_updates_without_force_refresh(
     ) {
         let store: Arc<dyn MissionStore> = Arc::new(mission_store::InMemoryMissionStore::new());
+        let metadata_refresh = metadata_refresh::SharedMetadataRefresh::default();
         let mission = store
             .create_mission(None, None, None, None, None, None, None)
             .await
@@ -10292,18 +13263,22 @@ And the report:
                     MissionHistoryEntry {
                         role: "user".to_string(),
                         content: "Initial request".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "assistant".to_string(),
                         content: "Initial response".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "user".to_string(),
                         content: "Follow-up request".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "assistant".to_string(),
                         content: "Refined response".to_string(),
+                        tool_name: None,
                     },
                 ],
             )
@@ -10311,7 +13286,7 @@ And the report:
             .expect("seed history");
 
         let (events_tx, mut events_rx) = broadcast::channel::<AgentEvent>(16);
-        schedule_mission_metadata_refresh(&store, &events_tx, mission.id, false);
+        schedule_mission_metadata_refresh(&store, &events_tx, &metadata_refresh, mission.id, false);
 
         let saw_metadata_event =
             tokio::time::timeout(std::time::Duration::from_millis(300), async {
@@ -10354,6 +13329,7 @@ And the report:
     async fn test_schedule_mission_metadata_refresh_ignores_non_conversational_entries_for_cadence()
     {
         let store: Arc<dyn MissionStore> = Arc::new(mission_store::InMemoryMissionStore::new());
+        let metadata_refresh = metadata_refresh::SharedMetadataRefresh::default();
         let mission = store
             .create_mission(None, None, None, None, None, None, None)
             .await
@@ -10385,42 +13361,52 @@ And the report:
                     MissionHistoryEntry {
                         role: "user".to_string(),
                         content: "Initial request".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "assistant".to_string(),
                         content: "Initial response".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "tool".to_string(),
                         content: "tool_call: inspect logs".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "user".to_string(),
                         content: "Follow-up request".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "assistant".to_string(),
                         content: "Follow-up response".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "tool".to_string(),
                         content: "tool_result: log output".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "user".to_string(),
                         content: "Investigate retries".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "assistant".to_string(),
                         content: "Retries are triggered by 502s".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "user".to_string(),
                         content: "Patch retry jitter".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "assistant".to_string(),
                         content: "Added jitter and bounded retries".to_string(),
+                        tool_name: None,
                     },
                 ],
             )
@@ -10428,7 +13414,7 @@ And the report:
             .expect("seed history");
 
         let (events_tx, mut events_rx) = broadcast::channel::<AgentEvent>(16);
-        schedule_mission_metadata_refresh(&store, &events_tx, mission.id, false);
+        schedule_mission_metadata_refresh(&store, &events_tx, &metadata_refresh, mission.id, false);
 
         let saw_metadata_event =
             tokio::time::timeout(std::time::Duration::from_millis(300), async {
@@ -10470,6 +13456,7 @@ And the report:
     #[tokio::test]
     async fn test_schedule_mission_metadata_refresh_uses_last_refresh_baseline_not_global_modulo() {
         let store: Arc<dyn MissionStore> = Arc::new(mission_store::InMemoryMissionStore::new());
+        let metadata_refresh = metadata_refresh::SharedMetadataRefresh::default();
         let mission = store
             .create_mission(Some("Legacy title"), None, None, None, None, None, None)
             .await
@@ -10481,6 +13468,7 @@ And the report:
             history.push(MissionHistoryEntry {
                 role: role.to_string(),
                 content: format!("history entry {}", idx),
+                tool_name: None,
             });
         }
         store
@@ -10489,7 +13477,14 @@ And the report:
             .expect("seed history");
 
         let (forced_events_tx, _forced_events_rx) = broadcast::channel::<AgentEvent>(16);
-        refresh_mission_metadata_for_milestone(&store, &forced_events_tx, mission.id).await;
+        refresh_mission_metadata_from_store(
+            &store,
+            &forced_events_tx,
+            &metadata_refresh,
+            mission.id,
+            true,
+        )
+        .await;
 
         let after_forced = store
             .get_mission(mission.id)
@@ -10506,6 +13501,7 @@ And the report:
             history.push(MissionHistoryEntry {
                 role: role.to_string(),
                 content: format!("post-forced history entry {}", idx),
+                tool_name: None,
             });
         }
         store
@@ -10514,7 +13510,7 @@ And the report:
             .expect("update history");
 
         let (events_tx, mut events_rx) = broadcast::channel::<AgentEvent>(16);
-        schedule_mission_metadata_refresh(&store, &events_tx, mission.id, false);
+        schedule_mission_metadata_refresh(&store, &events_tx, &metadata_refresh, mission.id, false);
 
         let saw_metadata_event =
             tokio::time::timeout(std::time::Duration::from_millis(300), async {
@@ -10551,6 +13547,7 @@ And the report:
     #[tokio::test]
     async fn test_should_refresh_metadata_by_cadence_rebases_when_history_is_rewritten_shorter() {
         let store: Arc<dyn MissionStore> = Arc::new(mission_store::InMemoryMissionStore::new());
+        let metadata_refresh = metadata_refresh::SharedMetadataRefresh::default();
         let mission = store
             .create_mission(Some("Existing mission"), None, None, None, None, None, None)
             .await
@@ -10572,27 +13569,20 @@ And the report:
             .expect("get mission")
             .expect("mission exists");
 
-        clear_mission_metadata_refresh_state(mission.id);
+        metadata_refresh.clear(mission.id);
 
-        assert!(!should_refresh_metadata_by_cadence(
-            mission.id, &mission, 24, false
-        ));
-        assert!(!should_refresh_metadata_by_cadence(
-            mission.id, &mission, 4, false
-        ));
-        assert!(!should_refresh_metadata_by_cadence(
-            mission.id, &mission, 13, false
-        ));
-        assert!(should_refresh_metadata_by_cadence(
-            mission.id, &mission, 14, false
-        ));
+        assert!(!metadata_refresh.should_refresh_by_cadence(mission.id, &mission, 24, false));
+        assert!(!metadata_refresh.should_refresh_by_cadence(mission.id, &mission, 4, false));
+        assert!(!metadata_refresh.should_refresh_by_cadence(mission.id, &mission, 13, false));
+        assert!(metadata_refresh.should_refresh_by_cadence(mission.id, &mission, 14, false));
 
-        clear_mission_metadata_refresh_state(mission.id);
+        metadata_refresh.clear(mission.id);
     }
 
     #[tokio::test]
     async fn test_record_metadata_refresh_baseline_from_mission_rebases_manual_title_updates() {
         let store: Arc<dyn MissionStore> = Arc::new(mission_store::InMemoryMissionStore::new());
+        let metadata_refresh = metadata_refresh::SharedMetadataRefresh::default();
         let mission = store
             .create_mission(Some("Existing mission"), None, None, None, None, None, None)
             .await
@@ -10604,18 +13594,22 @@ And the report:
                     MissionHistoryEntry {
                         role: "user".to_string(),
                         content: "one".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "assistant".to_string(),
                         content: "two".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "tool".to_string(),
                         content: "{}".to_string(),
+                        tool_name: None,
                     },
                     MissionHistoryEntry {
                         role: "assistant".to_string(),
                         content: "three".to_string(),
+                        tool_name: None,
                     },
                 ],
             )
@@ -10638,329 +13632,16 @@ And the report:
             .expect("get mission")
             .expect("mission exists");
 
-        clear_mission_metadata_refresh_state(mission.id);
-        record_metadata_refresh_baseline(mission.id, 0);
-
-        record_metadata_refresh_baseline_from_mission(mission.id, &mission);
-
-        assert!(!should_refresh_metadata_by_cadence(
-            mission.id, &mission, 11, false
-        ));
-        assert!(!should_refresh_metadata_by_cadence(
-            mission.id, &mission, 12, false
-        ));
-        assert!(should_refresh_metadata_by_cadence(
-            mission.id, &mission, 13, false
-        ));
-
-        clear_mission_metadata_refresh_state(mission.id);
-    }
-
-    #[tokio::test]
-    async fn test_register_metadata_refresh_task_replaces_existing_task_for_same_mission() {
-        let mission_id = Uuid::new_v4();
-        let mut tasks: HashMap<Uuid, MetadataRefreshTaskEntry> = HashMap::new();
-
-        let first = tokio::spawn(async {
-            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
-        });
-        let first_task_id = first.id();
-        let registration = register_metadata_refresh_task(&mut tasks, mission_id, false, 1, first);
-        assert!(registration.superseded.is_none());
-        assert_eq!(tasks.len(), 1);
-        assert_eq!(
-            tasks.get(&mission_id).map(|entry| entry.handle.id()),
-            Some(first_task_id)
-        );
-
-        let second = tokio::spawn(async {
-            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
-        });
-        let second_task_id = second.id();
-        let registration = register_metadata_refresh_task(&mut tasks, mission_id, false, 2, second);
-        let replaced = registration
-            .superseded
-            .expect("expected previous task to be replaced");
-        assert_eq!(replaced.id(), first_task_id);
-        replaced.abort();
-
-        assert_eq!(tasks.len(), 1);
-        assert_eq!(
-            tasks.get(&mission_id).map(|entry| entry.handle.id()),
-            Some(second_task_id)
-        );
-
-        if let Some(active) = tasks.remove(&mission_id) {
-            active.handle.abort();
-        }
-    }
-
-    #[tokio::test]
-    async fn test_register_metadata_refresh_task_keeps_in_flight_forced_task_over_non_forced() {
-        let mission_id = Uuid::new_v4();
-        let mut tasks: HashMap<Uuid, MetadataRefreshTaskEntry> = HashMap::new();
-
-        let forced = tokio::spawn(async {
-            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
-        });
-        let forced_task_id = forced.id();
-        let forced_registration =
-            register_metadata_refresh_task(&mut tasks, mission_id, true, 1, forced);
-        assert!(forced_registration.superseded.is_none());
-
-        let non_forced = tokio::spawn(async {
-            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
-        });
-        let non_forced_task_id = non_forced.id();
-        let non_forced_registration =
-            register_metadata_refresh_task(&mut tasks, mission_id, false, 2, non_forced);
-        let rejected = non_forced_registration
-            .superseded
-            .expect("new non-forced task should be rejected");
-        assert_eq!(rejected.id(), non_forced_task_id);
-        rejected.abort();
-
-        assert_eq!(tasks.len(), 1);
-        assert_eq!(
-            tasks.get(&mission_id).map(|entry| entry.handle.id()),
-            Some(forced_task_id)
-        );
-        assert!(tasks
-            .get(&mission_id)
-            .is_some_and(|entry| entry.force_refresh));
-
-        if let Some(active) = tasks.remove(&mission_id) {
-            active.handle.abort();
-        }
-    }
-
-    #[tokio::test]
-    async fn test_register_metadata_refresh_task_forced_replaces_non_forced() {
-        let mission_id = Uuid::new_v4();
-        let mut tasks: HashMap<Uuid, MetadataRefreshTaskEntry> = HashMap::new();
-
-        let non_forced = tokio::spawn(async {
-            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
-        });
-        let non_forced_task_id = non_forced.id();
-        let non_forced_registration =
-            register_metadata_refresh_task(&mut tasks, mission_id, false, 1, non_forced);
-        assert!(non_forced_registration.superseded.is_none());
-
-        let forced = tokio::spawn(async {
-            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
-        });
-        let forced_task_id = forced.id();
-        let forced_registration =
-            register_metadata_refresh_task(&mut tasks, mission_id, true, 2, forced);
-        let replaced = forced_registration
-            .superseded
-            .expect("existing non-forced task should be replaced");
-        assert_eq!(replaced.id(), non_forced_task_id);
-        replaced.abort();
-
-        assert_eq!(tasks.len(), 1);
-        assert_eq!(
-            tasks.get(&mission_id).map(|entry| entry.handle.id()),
-            Some(forced_task_id)
-        );
-        assert!(tasks
-            .get(&mission_id)
-            .is_some_and(|entry| entry.force_refresh));
-
-        if let Some(active) = tasks.remove(&mission_id) {
-            active.handle.abort();
-        }
-    }
-
-    #[tokio::test]
-    async fn test_should_skip_metadata_refresh_schedule_rejects_non_forced_when_forced_in_flight() {
-        let mission_id = Uuid::new_v4();
-        let mut tasks: HashMap<Uuid, MetadataRefreshTaskEntry> = HashMap::new();
-
-        let forced = tokio::spawn(async {
-            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
-        });
-        let registration = register_metadata_refresh_task(&mut tasks, mission_id, true, 1, forced);
-        assert!(registration.superseded.is_none());
-
-        assert!(should_skip_metadata_refresh_schedule(
-            &mut tasks, mission_id, false
-        ));
-        assert!(!should_skip_metadata_refresh_schedule(
-            &mut tasks, mission_id, true
-        ));
-
-        if let Some(active) = tasks.remove(&mission_id) {
-            active.handle.abort();
-        }
-    }
-
-    #[tokio::test]
-    async fn test_should_skip_metadata_refresh_schedule_drops_finished_tasks() {
-        let mission_id = Uuid::new_v4();
-        let mut tasks: HashMap<Uuid, MetadataRefreshTaskEntry> = HashMap::new();
-
-        let finished = tokio::spawn(async {});
-        let registration =
-            register_metadata_refresh_task(&mut tasks, mission_id, true, 1, finished);
-        assert!(registration.superseded.is_none());
-        tokio::task::yield_now().await;
-
-        assert!(!should_skip_metadata_refresh_schedule(
-            &mut tasks, mission_id, false
-        ));
-        assert!(tasks.is_empty());
-    }
-
-    #[tokio::test]
-    async fn test_complete_metadata_refresh_task_only_removes_matching_generation() {
-        let mission_id = Uuid::new_v4();
-        let mut tasks: HashMap<Uuid, MetadataRefreshTaskEntry> = HashMap::new();
-
-        let task = tokio::spawn(async {});
-        let registration = register_metadata_refresh_task(&mut tasks, mission_id, false, 42, task);
-        assert!(registration.superseded.is_none());
-        assert_eq!(tasks.len(), 1);
-
-        complete_metadata_refresh_task(&mut tasks, mission_id, 41);
-        assert_eq!(tasks.len(), 1);
-
-        complete_metadata_refresh_task(&mut tasks, mission_id, 42);
-        assert!(tasks.is_empty());
-    }
-
-    #[tokio::test]
-    async fn test_clear_mission_metadata_refresh_state_removes_task_and_baseline() {
-        let mission_id = Uuid::new_v4();
-        let other_mission_id = Uuid::new_v4();
-
-        clear_mission_metadata_refresh_state(mission_id);
-
-        let task = tokio::spawn(async {
-            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
-        });
-
-        {
-            let mut tasks = MISSION_METADATA_REFRESH_TASKS
-                .lock()
-                .expect("metadata refresh task registry lock poisoned");
-            tasks.insert(
-                mission_id,
-                MetadataRefreshTaskEntry {
-                    handle: task,
-                    force_refresh: false,
-                    task_id: 1,
-                },
-            );
-            tasks.insert(
-                other_mission_id,
-                MetadataRefreshTaskEntry {
-                    handle: tokio::spawn(async {
-                        tokio::time::sleep(std::time::Duration::from_secs(5)).await;
-                    }),
-                    force_refresh: false,
-                    task_id: 2,
-                },
-            );
-        }
-
-        {
-            let mut baselines = MISSION_METADATA_REFRESH_BASELINES
-                .lock()
-                .expect("metadata refresh baseline lock poisoned");
-            baselines.insert(mission_id, 10);
-            baselines.insert(other_mission_id, 20);
-        }
-
-        clear_mission_metadata_refresh_state(mission_id);
-
-        {
-            let tasks = MISSION_METADATA_REFRESH_TASKS
-                .lock()
-                .expect("metadata refresh task registry lock poisoned");
-            assert!(!tasks.contains_key(&mission_id));
-            assert!(tasks.contains_key(&other_mission_id));
-        }
-        {
-            let baselines = MISSION_METADATA_REFRESH_BASELINES
-                .lock()
-                .expect("metadata refresh baseline lock poisoned");
-            assert!(!baselines.contains_key(&mission_id));
-            assert_eq!(baselines.get(&other_mission_id), Some(&20usize));
-        }
-
-        clear_mission_metadata_refresh_state(other_mission_id);
-    }
-
-    #[tokio::test]
-    async fn test_clear_stale_mission_metadata_refresh_state_prunes_deleted_missions() {
-        let store: Arc<dyn MissionStore> = Arc::new(mission_store::InMemoryMissionStore::new());
-
-        let deleted_mission = store
-            .create_mission(None, None, None, None, None, None, None)
-            .await
-            .expect("create deleted mission");
-        let existing_mission = store
-            .create_mission(None, None, None, None, None, None, None)
-            .await
-            .expect("create existing mission");
-
-        let deleted_task = tokio::spawn(std::future::pending::<()>());
-        let existing_task = tokio::spawn(std::future::pending::<()>());
-
-        {
-            let mut tasks = MISSION_METADATA_REFRESH_TASKS
-                .lock()
-                .expect("metadata refresh task registry lock poisoned");
-            tasks.insert(
-                deleted_mission.id,
-                MetadataRefreshTaskEntry {
-                    task_id: 1,
-                    force_refresh: false,
-                    handle: deleted_task,
-                },
-            );
-            tasks.insert(
-                existing_mission.id,
-                MetadataRefreshTaskEntry {
-                    task_id: 2,
-                    force_refresh: false,
-                    handle: existing_task,
-                },
-            );
-
-            let mut baselines = MISSION_METADATA_REFRESH_BASELINES
-                .lock()
-                .expect("metadata refresh baseline lock poisoned");
-            baselines.insert(deleted_mission.id, 10);
-            baselines.insert(existing_mission.id, 20);
-        }
-
-        store
-            .delete_mission(deleted_mission.id)
-            .await
-            .expect("delete mission should succeed");
-
-        clear_stale_mission_metadata_refresh_state(&store).await;
+        metadata_refresh.clear(mission.id);
+        metadata_refresh.record_baseline(mission.id, 0);
 
-        {
-            let tasks = MISSION_METADATA_REFRESH_TASKS
-                .lock()
-                .expect("metadata refresh task registry lock poisoned");
-            assert!(!tasks.contains_key(&deleted_mission.id));
-            assert!(tasks.contains_key(&existing_mission.id));
-        }
+        metadata_refresh.record_baseline_from_mission(mission.id, &mission);
 
-        {
-            let baselines = MISSION_METADATA_REFRESH_BASELINES
-                .lock()
-                .expect("metadata refresh baseline lock poisoned");
-            assert!(!baselines.contains_key(&deleted_mission.id));
-            assert_eq!(baselines.get(&existing_mission.id), Some(&20usize));
-        }
+        assert!(!metadata_refresh.should_refresh_by_cadence(mission.id, &mission, 11, false));
+        assert!(!metadata_refresh.should_refresh_by_cadence(mission.id, &mission, 12, false));
+        assert!(metadata_refresh.should_refresh_by_cadence(mission.id, &mission, 13, false));
 
-        clear_mission_metadata_refresh_state(existing_mission.id);
+        metadata_refresh.clear(mission.id);
     }
 
     #[test]
@@ -11104,27 +13785,24 @@ And the report:
     }
 
     #[test]
-    fn test_normalize_model_override_for_backend_keeps_provider_prefix_for_opencode() {
+    fn test_normalize_model_override_keeps_provider_prefix_for_provider_model_ids() {
         assert_eq!(
-            normalize_model_override_for_backend(Some("opencode"), " openai/gpt-5-codex "),
+            normalize_model_override_for_backend(true, " openai/gpt-5-codex "),
             Some("openai/gpt-5-codex".to_string())
         );
     }
 
     #[test]
-    fn test_normalize_model_override_for_backend_strips_provider_prefix_for_non_opencode() {
+    fn test_normalize_model_override_strips_provider_prefix_for_raw_model_ids() {
         assert_eq!(
-            normalize_model_override_for_backend(Some("codex"), "openai/gpt-5-codex"),
+            normalize_model_override_for_backend(false, "openai/gpt-5-codex"),
             Some("gpt-5-codex".to_string())
         );
         assert_eq!(
-            normalize_model_override_for_backend(Some("claudecode"), "anthropic/claude-opus-4-6"),
+            normalize_model_override_for_backend(false, "anthropic/claude-opus-4-6"),
             Some("claude-opus-4-6".to_string())
         );
-        assert_eq!(
-            normalize_model_override_for_backend(Some("codex"), "   "),
-            None
-        );
+        assert_eq!(normalize_model_override_for_backend(false, "   "), None);
     }
 
     #[test]
@@ -11144,6 +13822,7 @@ And the report:
             workspace_id: crate::workspace::DEFAULT_WORKSPACE_ID,
             workspace_name: Some("Sandboxed".to_string()),
             agent: None,
+            agent_version: None,
             model_override: None,
             model_effort: None,
             backend: "claudecode".to_string(),
@@ -11156,6 +13835,17 @@ And the report:
             desktop_sessions: Vec::new(),
             session_id: None,
             terminal_reason: None,
+            error_code: None,
+            tags: Vec::new(),
+            pinned: false,
+            archived: false,
+            dry_run: false,
+            env_profile: None,
+            assignee: None,
+            watchers: Vec::new(),
+            checklist: Vec::new(),
+            follow_ups: Vec::new(),
+            unread_count: None,
         };
         let weak = Mission {
             id: Uuid::new_v4(),
@@ -11169,6 +13859,7 @@ And the report:
             workspace_id: crate::workspace::DEFAULT_WORKSPACE_ID,
             workspace_name: Some("Sandboxed".to_string()),
             agent: None,
+            agent_version: None,
             model_override: None,
             model_effort: None,
             backend: "claudecode".to_string(),
@@ -11181,6 +13872,17 @@ And the report:
             desktop_sessions: Vec::new(),
             session_id: None,
             terminal_reason: None,
+            error_code: None,
+            tags: Vec::new(),
+            pinned: false,
+            archived: false,
+            dry_run: false,
+            env_profile: None,
+            assignee: None,
+            watchers: Vec::new(),
+            checklist: Vec::new(),
+            follow_ups: Vec::new(),
+            unread_count: None,
         };
 
         let strong_score = mission_search_relevance_score(
@@ -11209,6 +13911,7 @@ And the report:
             workspace_id: crate::workspace::DEFAULT_WORKSPACE_ID,
             workspace_name: Some("Sandboxed".to_string()),
             agent: None,
+            agent_version: None,
             model_override: None,
             model_effort: None,
             backend: "codex".to_string(),
@@ -11221,6 +13924,17 @@ And the report:
             desktop_sessions: Vec::new(),
             session_id: None,
             terminal_reason: None,
+            error_code: None,
+            tags: Vec::new(),
+            pinned: false,
+            archived: false,
+            dry_run: false,
+            env_profile: None,
+            assignee: None,
+            watchers: Vec::new(),
+            checklist: Vec::new(),
+            follow_ups: Vec::new(),
+            unread_count: None,
         };
 
         let score = mission_search_relevance_score(
@@ -11246,6 +13960,7 @@ And the report:
             workspace_id: crate::workspace::DEFAULT_WORKSPACE_ID,
             workspace_name: Some("Sandboxed".to_string()),
             agent: None,
+            agent_version: None,
             model_override: None,
             model_effort: None,
             backend: "codex".to_string(),
@@ -11258,6 +13973,17 @@ And the report:
             desktop_sessions: Vec::new(),
             session_id: None,
             terminal_reason: None,
+            error_code: None,
+            tags: Vec::new(),
+            pinned: false,
+            archived: false,
+            dry_run: false,
+            env_profile: None,
+            assignee: None,
+            watchers: Vec::new(),
+            checklist: Vec::new(),
+            follow_ups: Vec::new(),
+            unread_count: None,
         };
 
         let score = mission_search_relevance_score(
@@ -11283,6 +14009,7 @@ And the report:
             workspace_id: crate::workspace::DEFAULT_WORKSPACE_ID,
             workspace_name: Some("Sandboxed".to_string()),
             agent: None,
+            agent_version: None,
             model_override: None,
             model_effort: None,
             backend: "codex".to_string(),
@@ -11295,6 +14022,17 @@ And the report:
             desktop_sessions: Vec::new(),
             session_id: None,
             terminal_reason: None,
+            error_code: None,
+            tags: Vec::new(),
+            pinned: false,
+            archived: false,
+            dry_run: false,
+            env_profile: None,
+            assignee: None,
+            watchers: Vec::new(),
+            checklist: Vec::new(),
+            follow_ups: Vec::new(),
+            unread_count: None,
         };
 
         let score = mission_search_relevance_score(
@@ -11303,6 +14041,42 @@ And the report:
             mission.workspace_name.as_deref(),
         );
         assert_eq!(score, 0.0);
+        assert!(mission_search_highlights(&mission, "kubernetes autoscaling").is_empty());
+
+        let highlights = mission_search_highlights(&mission, "webhook retry");
+        assert_eq!(
+            highlights,
+            vec![
+                SearchHighlight {
+                    field: "title".to_string(),
+                    snippet: "Implement payment <mark>retry</mark> flow".to_string(),
+                },
+                SearchHighlight {
+                    field: "short_description".to_string(),
+                    snippet: "Handle <mark>webhook</mark> retries for failed invoices".to_string(),
+                },
+            ]
+        );
+
+        // Search pages are ordered by score, then recency.
+        let mut older = mission.clone();
+        older.updated_at = "2020-01-01T00:00:00Z".to_string();
+        let best = search_result_keyset(10.0, &mission);
+        assert!(best.sort_key > search_result_keyset(9.5, &mission).sort_key);
+        assert!(best.sort_key > search_result_keyset(10.0, &older).sort_key);
+    }
+
+    #[test]
+    fn test_highlight_search_matches_escapes_and_windows_long_text() {
+        let text = format!(
+            "{} <b>deploy</b> & rollback {}",
+            "x ".repeat(200),
+            "y ".repeat(200)
+        );
+        let snippet = highlight_search_matches(&text, "deploy", 40).unwrap();
+        assert!(snippet.starts_with('…') && snippet.ends_with('…'));
+        assert!(snippet.contains("&lt;b&gt;<mark>deploy</mark>&lt;/b&gt; &amp; rollback"));
+        assert!(highlight_search_matches("nothing here", "deploy", 40).is_none());
     }
 
     #[test]
@@ -11320,6 +14094,7 @@ And the report:
             workspace_id: crate::workspace::DEFAULT_WORKSPACE_ID,
             workspace_name: Some("Sandboxed".to_string()),
             agent: None,
+            agent_version: None,
             model_override: None,
             model_effort: None,
             backend: "codex".to_string(),
@@ -11332,6 +14107,17 @@ And the report:
             desktop_sessions: Vec::new(),
             session_id: None,
             terminal_reason: None,
+            error_code: None,
+            tags: Vec::new(),
+            pinned: false,
+            archived: false,
+            dry_run: false,
+            env_profile: None,
+            assignee: None,
+            watchers: Vec::new(),
+            checklist: Vec::new(),
+            follow_ups: Vec::new(),
+            unread_count: None,
         };
 
         let score = mission_search_relevance_score(
@@ -11441,6 +14227,7 @@ And the report:
             workspace_id: crate::workspace::DEFAULT_WORKSPACE_ID,
             workspace_name: Some("Sandboxed".to_string()),
             agent: None,
+            agent_version: None,
             model_override: None,
             model_effort: None,
             backend: "codex".to_string(),
@@ -11453,6 +14240,17 @@ And the report:
             desktop_sessions: Vec::new(),
             session_id: None,
             terminal_reason: None,
+            error_code: None,
+            tags: Vec::new(),
+            pinned: false,
+            archived: false,
+            dry_run: false,
+            env_profile: None,
+            assignee: None,
+            watchers: Vec::new(),
+            checklist: Vec::new(),
+            follow_ups: Vec::new(),
+            unread_count: None,
         };
         let before = mission_search_freshness_key(
             &[MissionSearchCandidate {
@@ -11680,4 +14478,37 @@ Investigate <service/> failures.
         let files = validate_rich_tags(&tags, root, None, None).await;
         assert!(files.is_empty());
     }
+
+    #[tokio::test]
+    async fn test_validate_rich_tags_shares_dirs_and_diffs() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let root = dir.path();
+        tokio::fs::create_dir_all(root.join("site")).await.unwrap();
+        tokio::fs::write(root.join("site/index.html"), b"<html></html>")
+            .await
+            .unwrap();
+        tokio::fs::write(root.join("fix.patch"), b"--- a/x\n+++ b/x\n")
+            .await
+            .unwrap();
+
+        let tags = parse_rich_tags(
+            r#"<dir path="site" name="Built site" /> <diff path="./fix.patch" />
+               <file path="site" /> <dir path="fix.patch" />"#,
+        );
+        assert_eq!(tags.len(), 4);
+        let files = validate_rich_tags(&tags, root, None, None).await;
+        assert_eq!(files.len(), 2, "mismatched tag and file types are dropped");
+
+        assert_eq!(files[0].name, "Built site");
+        assert_eq!(files[0].kind, SharedFileKind::Directory);
+        assert!(files[0].url.starts_with("/api/fs/download-dir?path="));
+
+        assert_eq!(files[1].name, "fix.patch");
+        assert_eq!(files[1].kind, SharedFileKind::Diff);
+        assert!(matches!(
+            &files[1].preview,
+            Some(super::super::file_preview::SharedFilePreview::Code { language, .. })
+                if language.as_deref() == Some("diff")
+        ));
+    }
 }
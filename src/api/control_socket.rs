@@ -0,0 +1,360 @@
+//! Unix domain socket transport for the control session.
+//!
+//! Set `SANDBOXED_SH_CONTROL_SOCKET` to a path and the server also listens
+//! there (the socket is created with mode 0600). Local tools connect and
+//! speak newline-delimited JSON-RPC 2.0 as the local instance user, without
+//! HTTP or tokens:
+//!
+//! - `message` `{content, agent?, mission_id?}` - same as
+//!   `POST /api/control/message`; returns `{id, queued}`
+//! - `cancel` `{mission_id?}` - cancel a mission, or the current task
+//! - `tool_result` `{tool_call_id, name, result}` - same as
+//!   `POST /api/control/tool_result`
+//! - `status` - the control session status
+//!
+//! Every connection also receives the control session's events as
+//! notifications, in the same order and with the same payloads as the SSE
+//! stream: `{"jsonrpc":"2.0","method":"event","params":{"seq":..,"event":"assistant_message","data":{..}}}`.
+//! A `gap` event marks events the connection fell too far behind to receive.
+
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{mpsc, oneshot};
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::control::{
+    send_user_message, AgentEvent, ControlCommand, ControlMessageRequest, ControlState,
+    ControlToolResultRequest,
+};
+use super::event_bus::LogItem;
+use super::routes::AppState;
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const SERVER_ERROR: i64 = -32000;
+
+/// Events read from the log per batch.
+const EVENT_BATCH: usize = 256;
+
+/// Socket path from the environment, if the transport is enabled.
+pub fn socket_path() -> Option<PathBuf> {
+    std::env::var("SANDBOXED_SH_CONTROL_SOCKET")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+/// Bind the socket and accept connections in the background.
+pub fn spawn(state: Arc<AppState>, path: &Path) -> anyhow::Result<()> {
+    // A socket file left behind by a previous run would make bind fail.
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let listener = UnixListener::bind(path)?;
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    }
+    tracing::info!("Control socket listening on {}", path.display());
+
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    let state = Arc::clone(&state);
+                    tokio::spawn(async move {
+                        let control = state
+                            .control
+                            .get_or_spawn(&AuthUser::local(&state.config))
+                            .await;
+                        serve_connection(control, AuthUser::local(&state.config), stream).await;
+                    });
+                }
+                Err(e) => {
+                    tracing::warn!("Control socket accept failed: {}", e);
+                }
+            }
+        }
+    });
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Option<Value>,
+    method: String,
+    #[serde(default)]
+    params: Value,
+}
+
+#[derive(Debug)]
+struct RpcError {
+    code: i64,
+    message: String,
+}
+
+impl RpcError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+fn response(id: Value, result: Result<Value, RpcError>) -> Value {
+    match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(e) => json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "error": { "code": e.code, "message": e.message },
+        }),
+    }
+}
+
+fn event_notification(seq: u64, name: &str, data: Value) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "method": "event",
+        "params": { "seq": seq, "event": name, "data": data },
+    })
+}
+
+fn params<T: serde::de::DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    let params = if params.is_null() { json!({}) } else { params };
+    serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e.to_string()))
+}
+
+#[derive(Debug, Deserialize)]
+struct CancelParams {
+    #[serde(default)]
+    mission_id: Option<Uuid>,
+}
+
+async fn call(
+    control: &ControlState,
+    user: &AuthUser,
+    method: &str,
+    raw: Value,
+) -> Result<Value, RpcError> {
+    let unavailable = |_| RpcError::new(SERVER_ERROR, "control session unavailable");
+    match method {
+        "message" => {
+            let req: ControlMessageRequest = params(raw)?;
+            let content = req.content.trim().to_string();
+            if content.is_empty() {
+                return Err(RpcError::new(INVALID_PARAMS, "content is required"));
+            }
+            let response = send_user_message(control, user, content, req.agent, req.mission_id)
+                .await
+                .map_err(|(_, e)| RpcError::new(SERVER_ERROR, e))?;
+            Ok(serde_json::to_value(response.0).unwrap_or_default())
+        }
+        "cancel" => {
+            let req: CancelParams = params(raw)?;
+            let Some(mission_id) = req.mission_id else {
+                control
+                    .cmd_tx
+                    .send(ControlCommand::Cancel)
+                    .await
+                    .map_err(unavailable)?;
+                return Ok(json!({ "ok": true }));
+            };
+            let (tx, rx) = oneshot::channel();
+            control
+                .cmd_tx
+                .send(ControlCommand::CancelMission {
+                    mission_id,
+                    respond: tx,
+                })
+                .await
+                .map_err(unavailable)?;
+            rx.await
+                .map_err(|_| RpcError::new(SERVER_ERROR, "Failed to receive response"))?
+                .map_err(|e| RpcError::new(SERVER_ERROR, e))?;
+            Ok(json!({ "ok": true, "cancelled": mission_id }))
+        }
+        "tool_result" => {
+            let req: ControlToolResultRequest = params(raw)?;
+            if req.tool_call_id.trim().is_empty() || req.name.trim().is_empty() {
+                return Err(RpcError::new(
+                    INVALID_PARAMS,
+                    "tool_call_id and name are required",
+                ));
+            }
+            control
+                .cmd_tx
+                .send(ControlCommand::ToolResult {
+                    tool_call_id: req.tool_call_id,
+                    name: req.name,
+                    result: req.result,
+                })
+                .await
+                .map_err(unavailable)?;
+            Ok(json!({ "ok": true }))
+        }
+        "status" => {
+            Ok(serde_json::to_value(control.status.read().await.clone()).unwrap_or_default())
+        }
+        other => Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            format!("Unknown method: {}", other),
+        )),
+    }
+}
+
+/// Handle one request line; returns the response, if the request wants one.
+async fn handle_line(control: &ControlState, user: &AuthUser, line: &str) -> Option<Value> {
+    let value: Value = match serde_json::from_str(line) {
+        Ok(value) => value,
+        Err(e) => {
+            return Some(response(
+                Value::Null,
+                Err(RpcError::new(PARSE_ERROR, e.to_string())),
+            ))
+        }
+    };
+    let request: Request = match serde_json::from_value(value) {
+        Ok(request) => request,
+        Err(e) => {
+            return Some(response(
+                Value::Null,
+                Err(RpcError::new(INVALID_REQUEST, e.to_string())),
+            ))
+        }
+    };
+    let result = call(control, user, &request.method, request.params).await;
+    match request.id {
+        Some(id) => Some(response(id, result)),
+        // Notifications get no response, but failures are still worth a log line.
+        None => {
+            if let Err(e) = result {
+                tracing::debug!("Control socket notification failed: {}", e.message);
+            }
+            None
+        }
+    }
+}
+
+async fn serve_connection(control: ControlState, user: AuthUser, stream: UnixStream) {
+    let (reader, mut writer) = stream.into_split();
+    let (out_tx, mut out_rx) = mpsc::unbounded_channel::<Value>();
+
+    let writer_task = tokio::spawn(async move {
+        while let Some(message) = out_rx.recv().await {
+            let mut line = message.to_string();
+            line.push('\n');
+            if writer.write_all(line.as_bytes()).await.is_err() {
+                break;
+            }
+        }
+    });
+    let events_task = tokio::spawn(forward_events(control.clone(), out_tx.clone()));
+
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(reply) = handle_line(&control, &user, &line).await {
+            if out_tx.send(reply).is_err() {
+                break;
+            }
+        }
+    }
+
+    events_task.abort();
+    drop(out_tx);
+    let _ = writer_task.await;
+}
+
+/// Stream the control session's events to the connection, starting with a
+/// status snapshot and then live events.
+async fn forward_events(control: ControlState, out_tx: mpsc::UnboundedSender<Value>) {
+    let log = Arc::clone(&control.event_log);
+    let mut head_rx = log.subscribe();
+    let mut cursor = log.next_seq();
+
+    let status = control.status.read().await.clone();
+    let initial = AgentEvent::Status {
+        state: status.state,
+        queue_len: status.queue_len,
+        mission_id: status.mission_id,
+    };
+    let data = serde_json::to_value(&initial).unwrap_or_default();
+    if out_tx
+        .send(event_notification(0, initial.event_name(), data))
+        .is_err()
+    {
+        return;
+    }
+
+    loop {
+        head_rx.borrow_and_update();
+        let (items, next) = log.read_from(cursor, EVENT_BATCH);
+        cursor = next;
+        if items.is_empty() {
+            if head_rx.changed().await.is_err() {
+                return;
+            }
+            continue;
+        }
+        for item in items {
+            let message = match item {
+                LogItem::Event { seq, event } => match serde_json::to_value(&*event) {
+                    Ok(data) => event_notification(seq, event.event_name(), data),
+                    Err(e) => {
+                        tracing::error!("Failed to serialize control socket event: {}", e);
+                        continue;
+                    }
+                },
+                LogItem::Gap(gap) => event_notification(
+                    gap.to_seq,
+                    "gap",
+                    serde_json::to_value(&gap).unwrap_or_default(),
+                ),
+            };
+            if out_tx.send(message).is_err() {
+                return;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn responses_follow_json_rpc() {
+        let ok = response(json!(1), Ok(json!({ "ok": true })));
+        assert_eq!(ok["jsonrpc"], "2.0");
+        assert_eq!(ok["id"], 1);
+        assert_eq!(ok["result"]["ok"], true);
+
+        let err = response(json!("a"), Err(RpcError::new(METHOD_NOT_FOUND, "nope")));
+        assert_eq!(err["error"]["code"], METHOD_NOT_FOUND);
+        assert!(err.get("result").is_none());
+    }
+
+    #[test]
+    fn params_default_to_empty_object() {
+        let cancel: CancelParams = params(Value::Null).unwrap();
+        assert!(cancel.mission_id.is_none());
+        let err = params::<ControlMessageRequest>(json!({ "agent": "x" })).unwrap_err();
+        assert_eq!(err.code, INVALID_PARAMS);
+    }
+}
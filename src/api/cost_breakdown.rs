@@ -0,0 +1,406 @@
+//! Cost attribution per agent-tree node and per tool call.
+//!
+//! Backends report token usage for every LLM call (`AgentEvent::LlmUsage`),
+//! tagged with the tree node that made it: the mission's root agent, or the
+//! subagent spawned by a `Task`-style tool call. The control actor feeds those
+//! events into a [`CostTracker`], which adds each call's usage and cost to its
+//! node and splits the cost evenly across the tool calls that response
+//! requested. The costs are persisted in the mission's agent tree
+//! (`update_mission_tree`) and flattened by
+//! `GET /api/missions/:id/cost-breakdown`.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::control::{AgentEvent, AgentTreeNode, ToolCallCost};
+use super::routes::AppState;
+use crate::cost::TokenUsage;
+use crate::util::internal_error;
+
+/// Tool calls that spawn a subagent with its own tree node.
+const SUBAGENT_TOOLS: &[&str] = &["Task", "Agent", "delegate_task"];
+
+fn add_usage(total: &mut Option<TokenUsage>, usage: &TokenUsage) {
+    let total = total.get_or_insert_with(TokenUsage::default);
+    total.input_tokens += usage.input_tokens;
+    total.output_tokens += usage.output_tokens;
+    if let Some(n) = usage.cache_creation_input_tokens {
+        *total.cache_creation_input_tokens.get_or_insert(0) += n;
+    }
+    if let Some(n) = usage.cache_read_input_tokens {
+        *total.cache_read_input_tokens.get_or_insert(0) += n;
+    }
+}
+
+fn find_node_mut<'a>(node: &'a mut AgentTreeNode, id: &str) -> Option<&'a mut AgentTreeNode> {
+    if node.id == id {
+        return Some(node);
+    }
+    node.children
+        .iter_mut()
+        .find_map(|child| find_node_mut(child, id))
+}
+
+/// Split `cost_cents` evenly across `count` shares, giving the remainder to
+/// the first shares so the parts add up to the total.
+fn split_cost(cost_cents: u64, count: usize) -> impl Iterator<Item = u64> {
+    let count = count.max(1) as u64;
+    let (share, remainder) = (cost_cents / count, cost_cents % count);
+    (0..count).map(move |i| share + u64::from(i < remainder))
+}
+
+/// Per-mission cost trees, maintained from the session's event stream.
+#[derive(Debug, Default)]
+pub struct CostTracker {
+    trees: HashMap<Uuid, AgentTreeNode>,
+}
+
+impl CostTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_tracking(&self, mission_id: Uuid) -> bool {
+        self.trees.contains_key(&mission_id)
+    }
+
+    /// Start tracking a mission, continuing from its stored tree if any.
+    pub fn seed(&mut self, mission_id: Uuid, stored: Option<AgentTreeNode>) {
+        let tree = stored.unwrap_or_else(|| {
+            AgentTreeNode::new("root", "Root", "Mission agent", "").with_status("running")
+        });
+        self.trees.insert(mission_id, tree);
+    }
+
+    pub fn tree(&self, mission_id: Uuid) -> Option<&AgentTreeNode> {
+        self.trees.get(&mission_id)
+    }
+
+    pub fn forget(&mut self, mission_id: Uuid) {
+        self.trees.remove(&mission_id);
+    }
+
+    /// Apply an event. Returns the mission whose costs changed, if any.
+    pub fn observe(&mut self, event: &AgentEvent) -> Option<Uuid> {
+        match event {
+            AgentEvent::ToolCall {
+                tool_call_id,
+                name,
+                args,
+                mission_id: Some(mid),
+            } if SUBAGENT_TOOLS.contains(&name.as_str()) => {
+                let tree = self.trees.get_mut(mid)?;
+                if find_node_mut(tree, tool_call_id).is_none() {
+                    let description = args
+                        .get("description")
+                        .and_then(|v| v.as_str())
+                        .unwrap_or(name);
+                    tree.add_child(
+                        AgentTreeNode::new(tool_call_id, "Subagent", name, description)
+                            .with_status("running"),
+                    );
+                }
+                None
+            }
+            AgentEvent::LlmUsage {
+                node_id,
+                usage,
+                cost_cents,
+                model,
+                tool_calls,
+                mission_id: Some(mid),
+            } => {
+                let tree = self.trees.get_mut(mid)?;
+                let node = match node_id {
+                    Some(id) if find_node_mut(tree, id).is_some() => find_node_mut(tree, id)?,
+                    _ => tree,
+                };
+                node.cost_cents += cost_cents;
+                node.llm_calls += 1;
+                add_usage(&mut node.usage, usage);
+                if node.selected_model.is_none() {
+                    node.selected_model = model.clone();
+                }
+                for ((tool_call_id, name), cost) in tool_calls
+                    .iter()
+                    .zip(split_cost(*cost_cents, tool_calls.len()))
+                {
+                    node.tool_costs.push(ToolCallCost {
+                        tool_call_id: tool_call_id.clone(),
+                        name: name.clone(),
+                        cost_cents: cost,
+                    });
+                }
+                Some(*mid)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Copy attributed costs from `costs` into `target`, matching nodes by id.
+/// Nodes that only exist in `costs` (subagents the tracker discovered) are
+/// added under the target's root.
+pub fn merge_costs(target: &mut AgentTreeNode, costs: &AgentTreeNode) {
+    fn apply(node: &mut AgentTreeNode, from: &AgentTreeNode) {
+        node.cost_cents = from.cost_cents;
+        node.usage = from.usage.clone();
+        node.llm_calls = from.llm_calls;
+        node.tool_costs = from.tool_costs.clone();
+    }
+
+    fn walk(target: &mut AgentTreeNode, node: &AgentTreeNode, is_root: bool) {
+        let found = if is_root {
+            apply(target, node);
+            true
+        } else if let Some(existing) = find_node_mut(target, &node.id) {
+            apply(existing, node);
+            true
+        } else {
+            false
+        };
+        if !found {
+            target.add_child(node.clone());
+            return;
+        }
+        for child in &node.children {
+            walk(target, child, false);
+        }
+    }
+
+    walk(target, costs, true);
+}
+
+/// The tree to persist for a mission: the agent's live tree (if it has one)
+/// with the tracked costs merged in, or the tracked cost tree alone.
+pub fn tree_with_costs(
+    live: Option<AgentTreeNode>,
+    costs: Option<&AgentTreeNode>,
+) -> Option<AgentTreeNode> {
+    match (live, costs) {
+        (Some(mut tree), Some(costs)) => {
+            merge_costs(&mut tree, costs);
+            Some(tree)
+        }
+        (live, costs) => live.or_else(|| costs.cloned()),
+    }
+}
+
+/// Cost attributed to one tree node.
+#[derive(Debug, Serialize)]
+pub struct NodeCost {
+    pub id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent_id: Option<String>,
+    pub name: String,
+    pub node_type: String,
+    pub depth: usize,
+    /// Cost of this node's own LLM calls
+    pub cost_cents: u64,
+    /// Cost of this node and all of its descendants
+    pub total_cost_cents: u64,
+    pub llm_calls: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
+    pub tool_calls: Vec<ToolCallCost>,
+}
+
+/// Cost of all calls to one tool across the mission.
+#[derive(Debug, Serialize)]
+pub struct ToolCost {
+    pub name: String,
+    pub calls: u64,
+    pub cost_cents: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CostBreakdown {
+    pub mission_id: Uuid,
+    pub total_cost_cents: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
+    /// Tree nodes in depth-first order
+    pub nodes: Vec<NodeCost>,
+    /// Per-tool totals, most expensive first
+    pub tools: Vec<ToolCost>,
+}
+
+impl CostBreakdown {
+    pub fn from_tree(mission_id: Uuid, tree: Option<&AgentTreeNode>) -> Self {
+        fn flatten(
+            node: &AgentTreeNode,
+            parent_id: Option<&str>,
+            depth: usize,
+            nodes: &mut Vec<NodeCost>,
+        ) -> u64 {
+            let index = nodes.len();
+            nodes.push(NodeCost {
+                id: node.id.clone(),
+                parent_id: parent_id.map(str::to_string),
+                name: node.name.clone(),
+                node_type: node.node_type.clone(),
+                depth,
+                cost_cents: node.cost_cents,
+                total_cost_cents: 0,
+                llm_calls: node.llm_calls,
+                usage: node.usage.clone(),
+                tool_calls: node.tool_costs.clone(),
+            });
+            let total = node.cost_cents
+                + node
+                    .children
+                    .iter()
+                    .map(|child| flatten(child, Some(&node.id), depth + 1, nodes))
+                    .sum::<u64>();
+            nodes[index].total_cost_cents = total;
+            total
+        }
+
+        let mut nodes = Vec::new();
+        if let Some(tree) = tree {
+            flatten(tree, None, 0, &mut nodes);
+        }
+
+        let mut usage = None;
+        let mut tools: BTreeMap<&str, ToolCost> = BTreeMap::new();
+        for node in &nodes {
+            if let Some(node_usage) = &node.usage {
+                add_usage(&mut usage, node_usage);
+            }
+            for call in &node.tool_calls {
+                let entry = tools.entry(call.name.as_str()).or_insert_with(|| ToolCost {
+                    name: call.name.clone(),
+                    calls: 0,
+                    cost_cents: 0,
+                });
+                entry.calls += 1;
+                entry.cost_cents += call.cost_cents;
+            }
+        }
+        let mut tools: Vec<ToolCost> = tools.into_values().collect();
+        tools.sort_by_key(|tool| std::cmp::Reverse(tool.cost_cents));
+
+        Self {
+            mission_id,
+            total_cost_cents: nodes.first().map(|n| n.total_cost_cents).unwrap_or(0),
+            usage,
+            nodes,
+            tools,
+        }
+    }
+}
+
+/// GET /api/missions/:id/cost-breakdown - Cost per agent-tree node and tool.
+pub async fn get_mission_cost_breakdown(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+) -> Result<Json<CostBreakdown>, (StatusCode, String)> {
+    let control = state.control.get_or_spawn(&user).await;
+    let store = &control.mission_store;
+    if store
+        .get_mission(mission_id)
+        .await
+        .map_err(internal_error)?
+        .is_none()
+    {
+        return Err((StatusCode::NOT_FOUND, "Mission not found".to_string()));
+    }
+    let tree = store
+        .get_mission_tree(mission_id)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(CostBreakdown::from_tree(mission_id, tree.as_ref())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn usage_event(mid: Uuid, node_id: Option<&str>, cost: u64, tools: &[&str]) -> AgentEvent {
+        AgentEvent::LlmUsage {
+            node_id: node_id.map(str::to_string),
+            usage: TokenUsage {
+                input_tokens: 100,
+                output_tokens: 10,
+                cache_creation_input_tokens: None,
+                cache_read_input_tokens: Some(5),
+            },
+            cost_cents: cost,
+            model: Some("claude-sonnet-4".to_string()),
+            tool_calls: tools
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (format!("call-{}", i), name.to_string()))
+                .collect(),
+            mission_id: Some(mid),
+        }
+    }
+
+    #[test]
+    fn attributes_costs_to_nodes_and_tools() {
+        let mid = Uuid::new_v4();
+        let mut tracker = CostTracker::new();
+        tracker.seed(mid, None);
+
+        assert_eq!(
+            tracker.observe(&usage_event(mid, None, 7, &["Read", "Task"])),
+            Some(mid)
+        );
+        tracker.observe(&AgentEvent::ToolCall {
+            tool_call_id: "task-1".to_string(),
+            name: "Task".to_string(),
+            args: json!({ "description": "Write tests" }),
+            mission_id: Some(mid),
+        });
+        tracker.observe(&usage_event(mid, Some("task-1"), 4, &["Bash"]));
+        tracker.observe(&usage_event(mid, Some("task-1"), 2, &[]));
+        // Unknown nodes fall back to the root.
+        tracker.observe(&usage_event(mid, Some("missing"), 1, &[]));
+        // Other missions are ignored until seeded.
+        assert_eq!(
+            tracker.observe(&usage_event(Uuid::new_v4(), None, 50, &[])),
+            None
+        );
+
+        let breakdown = CostBreakdown::from_tree(mid, tracker.tree(mid));
+        assert_eq!(breakdown.total_cost_cents, 14);
+        assert_eq!(breakdown.usage.as_ref().unwrap().input_tokens, 400);
+        assert_eq!(
+            breakdown.usage.as_ref().unwrap().cache_read_input_tokens,
+            Some(20)
+        );
+
+        let root = &breakdown.nodes[0];
+        assert_eq!(
+            (root.cost_cents, root.total_cost_cents, root.llm_calls),
+            (8, 14, 2)
+        );
+        let costs: Vec<u64> = root.tool_calls.iter().map(|c| c.cost_cents).collect();
+        assert_eq!(costs, vec![4, 3]);
+
+        let task = &breakdown.nodes[1];
+        assert_eq!(task.parent_id.as_deref(), Some("root"));
+        assert_eq!(task.name, "Task");
+        assert_eq!((task.cost_cents, task.depth, task.llm_calls), (6, 1, 2));
+
+        assert_eq!(breakdown.tools[0].name, "Bash");
+        assert_eq!(breakdown.tools[0].cost_cents, 4);
+
+        // Costs survive a tree saved by the agent itself.
+        let mut agent_tree = AgentTreeNode::new("root", "Root", "Agent", "");
+        merge_costs(&mut agent_tree, tracker.tree(mid).unwrap());
+        assert_eq!(agent_tree.cost_cents, 8);
+        assert_eq!(agent_tree.children[0].cost_cents, 6);
+    }
+}
@@ -4,6 +4,7 @@ use axum::http::header;
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
+use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
 use super::routes::AppState;
@@ -280,27 +281,37 @@ impl DeferredRequestStore {
 }
 
 pub fn start_worker(state: Arc<AppState>) {
-    tokio::spawn(async move {
-        worker_loop(state).await;
-    });
+    super::task_supervisor::global().spawn_with_cancel(
+        "deferred_proxy_worker",
+        super::task_supervisor::RestartPolicy::on_panic(),
+        move |cancel| worker_loop(Arc::clone(&state), cancel),
+    );
 }
 
-async fn worker_loop(state: Arc<AppState>) {
+/// Process due jobs until `cancel` fires; the job in progress is finished
+/// first.
+async fn worker_loop(state: Arc<AppState>, cancel: CancellationToken) {
     let worker_client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(330))
         .build()
         .unwrap_or_else(|_| reqwest::Client::new());
 
-    loop {
+    while !cancel.is_cancelled() {
         let mut processed_any = false;
 
-        while let Some(job) = state.deferred_requests.claim_due_job().await {
+        while !cancel.is_cancelled() {
+            let Some(job) = state.deferred_requests.claim_due_job().await else {
+                break;
+            };
             processed_any = true;
             process_one_job(&worker_client, &state, job).await;
         }
 
         if !processed_any {
-            tokio::time::sleep(std::time::Duration::from_secs(WORKER_POLL_INTERVAL_SECS)).await;
+            tokio::select! {
+                _ = tokio::time::sleep(std::time::Duration::from_secs(WORKER_POLL_INTERVAL_SECS)) => {}
+                _ = cancel.cancelled() => {}
+            }
         }
     }
 }
@@ -8,19 +8,29 @@ use std::sync::Arc;
 use std::time::Duration;
 
 use axum::{
-    extract::{Path, State},
-    http::StatusCode,
-    response::Json,
+    extract::{Multipart, Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
     Router,
 };
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
 use tokio::process::Command;
 use uuid::Uuid;
 
+use super::control::{AgentEvent, DesktopSessionInfo};
 use super::library::SharedLibrary;
 use super::routes::AppState;
+use crate::tools::desktop_macos;
+use crate::util::internal_error;
+
+/// Folder, next to a session's screenshots, that shared files are dropped into.
+const SHARED_DIR_NAME: &str = "desktop-shared";
+
+/// Most text accepted by the clipboard endpoint.
+const MAX_CLIPBOARD_BYTES: usize = 1024 * 1024;
 
 /// Status of a desktop session.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -36,6 +46,18 @@ pub enum DesktopSessionStatus {
     Unknown,
 }
 
+/// What happened to a desktop session, as reported by
+/// [`AgentEvent::DesktopSession`].
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DesktopSessionAction {
+    Opened,
+    KeepAliveExtended,
+    /// A screenshot was taken on demand; clients can refresh their thumbnail.
+    ScreenshotTaken,
+    Closed,
+}
+
 /// Extended desktop session information for the API response.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DesktopSessionDetail {
@@ -59,6 +81,13 @@ pub struct DesktopSessionDetail {
     pub process_running: bool,
 }
 
+/// Query for listing desktop sessions.
+#[derive(Debug, Deserialize)]
+pub struct ListSessionsQuery {
+    /// Only sessions with this status (e.g. `active`).
+    pub status: Option<DesktopSessionStatus>,
+}
+
 /// Response for listing desktop sessions.
 #[derive(Debug, Serialize)]
 pub struct ListSessionsResponse {
@@ -77,12 +106,21 @@ fn default_keep_alive_extension() -> u64 {
     7200 // 2 hours
 }
 
+/// Request to set the session clipboard.
+#[derive(Debug, Deserialize)]
+pub struct ClipboardRequest {
+    pub text: String,
+}
+
 /// Response for close/keep-alive operations.
 #[derive(Debug, Serialize)]
 pub struct OperationResponse {
     pub success: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub message: Option<String>,
+    /// New keep-alive deadline (keep-alive only).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub keep_alive_until: Option<String>,
 }
 
 /// Create desktop management routes.
@@ -91,33 +129,138 @@ pub fn routes() -> Router<Arc<AppState>> {
         .route("/sessions", get(list_sessions))
         .route("/sessions/:display/close", post(close_session))
         .route("/sessions/:display/keep-alive", post(keep_alive_session))
+        .route("/sessions/:display/screenshot", get(screenshot_session))
+        .route("/sessions/:display/clipboard", post(set_session_clipboard))
         .route("/sessions/cleanup", post(cleanup_orphaned_sessions))
         .route("/sessions/cleanup-stopped", post(cleanup_stopped_sessions))
 }
 
-/// List all desktop sessions across all missions.
-async fn list_sessions(State(state): State<Arc<AppState>>) -> Json<ListSessionsResponse> {
-    let sessions = collect_desktop_sessions(&state).await;
+/// List all desktop sessions across all missions, optionally by status.
+async fn list_sessions(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ListSessionsQuery>,
+) -> Json<ListSessionsResponse> {
+    let mut sessions = collect_desktop_sessions(&state).await;
+    if let Some(status) = query.status {
+        sessions.retain(|s| s.status == status);
+    }
     Json(ListSessionsResponse { sessions })
 }
 
+/// Normalize a display path parameter: `1` becomes `:1`, macOS display ids
+/// are kept as they are.
+fn normalize_display(display_id: String) -> String {
+    if display_id.starts_with(':') || desktop_macos::is_macos_display(&display_id) {
+        display_id
+    } else {
+        format!(":{}", display_id)
+    }
+}
+
+/// The recorded session for a display and the mission it belongs to,
+/// preferring a session that is still open.
+async fn find_session(
+    state: &Arc<AppState>,
+    display_id: &str,
+) -> Option<(Uuid, DesktopSessionInfo)> {
+    let mission_store = state.control.get_mission_store().await;
+    let missions = mission_store.list_missions(1000, 0).await.ok()?;
+    let mut found = None;
+    for mission in missions {
+        for session in mission
+            .desktop_sessions
+            .iter()
+            .filter(|s| s.display == display_id)
+        {
+            let owner = (session.mission_id.unwrap_or(mission.id), session.clone());
+            if session.stopped_at.is_none() {
+                return Some(owner);
+            }
+            found.get_or_insert(owner);
+        }
+    }
+    found
+}
+
+/// The mission a display belongs to.
+async fn find_session_mission(state: &Arc<AppState>, display_id: &str) -> Option<Uuid> {
+    find_session(state, display_id)
+        .await
+        .map(|(mission_id, _)| mission_id)
+}
+
+/// Shared folder of a session: next to its screenshots folder (the session's
+/// working directory), or under `fallback` when that is unknown.
+fn shared_dir_for(screenshots_dir: Option<&str>, fallback: &std::path::Path) -> std::path::PathBuf {
+    screenshots_dir
+        .and_then(|dir| std::path::Path::new(dir).parent())
+        .unwrap_or(fallback)
+        .join(SHARED_DIR_NAME)
+}
+
+/// Return a 404 unless the session on `display_id` is running.
+async fn require_running(
+    state: &Arc<AppState>,
+    display_id: &str,
+) -> Result<(), (StatusCode, String)> {
+    if is_session_running(display_id, &state.config.working_dir).await {
+        Ok(())
+    } else {
+        Err((
+            StatusCode::NOT_FOUND,
+            format!("Desktop session {} is not running", display_id),
+        ))
+    }
+}
+
+/// Send a desktop session event to the control session that owns the mission.
+async fn emit_session_event(
+    state: &Arc<AppState>,
+    mission_id: Uuid,
+    display: &str,
+    action: DesktopSessionAction,
+    keep_alive_until: Option<String>,
+) {
+    for control in state.control.all_sessions().await {
+        if matches!(
+            control.mission_store.get_mission(mission_id).await,
+            Ok(Some(_))
+        ) {
+            let _ = control.events_tx.send(AgentEvent::DesktopSession {
+                mission_id,
+                display: display.to_string(),
+                action,
+                keep_alive_until: keep_alive_until.clone(),
+            });
+        }
+    }
+}
+
 /// Close a specific desktop session.
 async fn close_session(
     State(state): State<Arc<AppState>>,
     Path(display_id): Path<String>,
 ) -> Result<Json<OperationResponse>, (StatusCode, String)> {
-    // Normalize display format
-    let display_id = if display_id.starts_with(':') {
-        display_id
-    } else {
-        format!(":{}", display_id)
-    };
+    let display_id = normalize_display(display_id);
+
+    let mission_id = find_session_mission(&state, &display_id).await;
 
     // Try to close the desktop session
     match close_desktop_session(&display_id, &state.config.working_dir).await {
         Ok(()) => {
             tracing::info!(display_id = %display_id, "Desktop session closed via API");
 
+            if let Some(mission_id) = mission_id {
+                emit_session_event(
+                    &state,
+                    mission_id,
+                    &display_id,
+                    DesktopSessionAction::Closed,
+                    None,
+                )
+                .await;
+            }
+
             // Also remove the session record from storage
             if let Err(e) = remove_session_from_storage(&state, &display_id).await {
                 tracing::warn!(display_id = %display_id, error = %e, "Failed to remove session from storage");
@@ -126,6 +269,7 @@ async fn close_session(
             Ok(Json(OperationResponse {
                 success: true,
                 message: Some(format!("Desktop session {} closed", display_id)),
+                keep_alive_until: None,
             }))
         }
         Err(e) => {
@@ -144,12 +288,7 @@ async fn keep_alive_session(
     Path(display_id): Path<String>,
     Json(req): Json<KeepAliveRequest>,
 ) -> Result<Json<OperationResponse>, (StatusCode, String)> {
-    // Normalize display format
-    let display_id = if display_id.starts_with(':') {
-        display_id
-    } else {
-        format!(":{}", display_id)
-    };
+    let display_id = normalize_display(display_id);
 
     // Find and update the session
     let mission_store = state.control.get_mission_store().await;
@@ -194,9 +333,19 @@ async fn keep_alive_session(
                     "Desktop session keep-alive extended"
                 );
 
+                emit_session_event(
+                    &state,
+                    session.mission_id.unwrap_or(mission.id),
+                    &display_id,
+                    DesktopSessionAction::KeepAliveExtended,
+                    Some(new_keep_alive_str.clone()),
+                )
+                .await;
+
                 return Ok(Json(OperationResponse {
                     success: true,
                     message: Some(format!("Keep-alive extended to {}", new_keep_alive_str)),
+                    keep_alive_until: Some(new_keep_alive_str),
                 }));
             }
         }
@@ -208,6 +357,173 @@ async fn keep_alive_session(
     ))
 }
 
+/// Capture the current screen of a running desktop session as a PNG.
+async fn screenshot_session(
+    State(state): State<Arc<AppState>>,
+    Path(display_id): Path<String>,
+) -> Result<Response, (StatusCode, String)> {
+    let display_id = normalize_display(display_id);
+    require_running(&state, &display_id).await?;
+
+    let path = std::env::temp_dir().join(format!("desktop-screenshot-{}.png", Uuid::new_v4()));
+    let captured = match crate::tools::desktop::capture_display(&display_id, &path, None).await {
+        Ok(()) => tokio::fs::read(&path).await.map_err(anyhow::Error::from),
+        Err(e) => Err(e),
+    };
+    let _ = tokio::fs::remove_file(&path).await;
+    let png = captured.map_err(|e| {
+        tracing::warn!(display_id = %display_id, error = %e, "Failed to capture desktop session");
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to capture desktop session: {}", e),
+        )
+    })?;
+
+    if let Some(mission_id) = find_session_mission(&state, &display_id).await {
+        emit_session_event(
+            &state,
+            mission_id,
+            &display_id,
+            DesktopSessionAction::ScreenshotTaken,
+            None,
+        )
+        .await;
+    }
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "image/png"),
+            (header::CACHE_CONTROL, "no-store"),
+        ],
+        png,
+    )
+        .into_response())
+}
+
+/// Put text on the clipboard of a running desktop session, e.g. for the
+/// agent's browser to paste.
+async fn set_session_clipboard(
+    State(state): State<Arc<AppState>>,
+    Path(display_id): Path<String>,
+    Json(req): Json<ClipboardRequest>,
+) -> Result<Json<OperationResponse>, (StatusCode, String)> {
+    let display_id = normalize_display(display_id);
+    if req.text.len() > MAX_CLIPBOARD_BYTES {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Clipboard text is limited to {} bytes", MAX_CLIPBOARD_BYTES),
+        ));
+    }
+    require_running(&state, &display_id).await?;
+
+    crate::tools::desktop::set_clipboard(&display_id, &req.text)
+        .await
+        .map_err(|e| {
+            tracing::warn!(display_id = %display_id, error = %e, "Failed to set desktop clipboard");
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to set clipboard: {}", e),
+            )
+        })?;
+
+    // The text may be a credential, so only its size is logged
+    tracing::info!(display_id = %display_id, bytes = req.text.len(), "Desktop clipboard set via API");
+    Ok(Json(OperationResponse {
+        success: true,
+        message: Some(format!("Clipboard of {} set", display_id)),
+        keep_alive_until: None,
+    }))
+}
+
+/// POST /api/desktop/sessions/:display/files - Drop multipart files into the
+/// session's shared folder, where its browser can open or upload them.
+pub async fn upload_session_files(
+    State(state): State<Arc<AppState>>,
+    Path(display_id): Path<String>,
+    mut multipart: Multipart,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let display_id = normalize_display(display_id);
+    require_running(&state, &display_id).await?;
+
+    let session = find_session(&state, &display_id).await;
+    let shared_dir = shared_dir_for(
+        session
+            .as_ref()
+            .and_then(|(_, s)| s.screenshots_dir.as_deref()),
+        &state.config.working_dir,
+    );
+    tokio::fs::create_dir_all(&shared_dir).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to create directory: {}", e),
+        )
+    })?;
+
+    let mut files = Vec::new();
+    while let Some(mut field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+    {
+        let Some(raw_name) = field.file_name() else {
+            // Skip non-file form fields
+            continue;
+        };
+        let file_name = super::fs::sanitize_path_component(raw_name);
+        if file_name.is_empty() {
+            return Err((StatusCode::BAD_REQUEST, "Invalid file name".to_string()));
+        }
+
+        let dest = shared_dir.join(&file_name);
+        let partial = shared_dir.join(format!(".{}.{}.part", file_name, Uuid::new_v4()));
+        let mut f = tokio::fs::File::create(&partial)
+            .await
+            .map_err(internal_error)?;
+        let mut received: u64 = 0;
+        let written: Result<(), (StatusCode, String)> = async {
+            while let Some(chunk) = field
+                .chunk()
+                .await
+                .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+            {
+                f.write_all(&chunk).await.map_err(internal_error)?;
+                received += chunk.len() as u64;
+            }
+            f.flush().await.map_err(internal_error)
+        }
+        .await;
+        drop(f);
+        if let Err(e) = written {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(e);
+        }
+        tokio::fs::rename(&partial, &dest)
+            .await
+            .map_err(internal_error)?;
+
+        tracing::info!(
+            display_id = %display_id,
+            path = %dest.display(),
+            bytes = received,
+            "Shared file with desktop session"
+        );
+        files.push(serde_json::json!({
+            "path": dest,
+            "name": file_name,
+            "size": received,
+        }));
+    }
+
+    if files.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "missing file".to_string()));
+    }
+    Ok(Json(serde_json::json!({
+        "ok": true,
+        "shared_dir": shared_dir,
+        "files": files,
+    })))
+}
+
 /// Close all orphaned desktop sessions.
 async fn cleanup_orphaned_sessions(State(state): State<Arc<AppState>>) -> Json<OperationResponse> {
     let sessions = collect_desktop_sessions(&state).await;
@@ -257,6 +573,7 @@ async fn cleanup_orphaned_sessions(State(state): State<Arc<AppState>>) -> Json<O
                 String::new()
             }
         )),
+        keep_alive_until: None,
     })
 }
 
@@ -269,6 +586,7 @@ async fn cleanup_stopped_sessions(State(state): State<Arc<AppState>>) -> Json<Op
             return Json(OperationResponse {
                 success: false,
                 message: Some(format!("Failed to list missions: {}", e)),
+                keep_alive_until: None,
             });
         }
     };
@@ -288,7 +606,7 @@ async fn cleanup_stopped_sessions(State(state): State<Arc<AppState>>) -> Json<Op
             }
 
             // Check if process is actually running
-            if is_xvfb_running(&session.display).await {
+            if is_session_running(&session.display, &state.config.working_dir).await {
                 truly_active.push(session.clone());
             } else {
                 removed_count += 1;
@@ -318,6 +636,7 @@ async fn cleanup_stopped_sessions(State(state): State<Arc<AppState>>) -> Json<Op
     Json(OperationResponse {
         success: true,
         message: Some(format!("Removed {} stopped session records", removed_count)),
+        keep_alive_until: None,
     })
 }
 
@@ -375,7 +694,8 @@ async fn collect_desktop_sessions(state: &Arc<AppState>) -> Vec<DesktopSessionDe
     // Collect sessions from missions
     for mission in missions {
         for session in &mission.desktop_sessions {
-            let process_running = is_xvfb_running(&session.display).await;
+            let process_running =
+                is_session_running(&session.display, &state.config.working_dir).await;
 
             // Determine session status
             let status = if session.stopped_at.is_some() || !process_running {
@@ -509,6 +829,14 @@ async fn get_desktop_config(library: &SharedLibrary) -> crate::library::types::D
     }
 }
 
+/// Check if a session is still running: its Xvfb on X11, its session file on macOS.
+async fn is_session_running(display: &str, working_dir: &std::path::Path) -> bool {
+    if desktop_macos::is_macos_display(display) {
+        return desktop_macos::session_active(display, working_dir);
+    }
+    is_xvfb_running(display).await
+}
+
 /// Check if Xvfb is running on a specific display.
 async fn is_xvfb_running(display: &str) -> bool {
     let output = Command::new("pgrep")
@@ -551,6 +879,10 @@ pub(crate) async fn close_desktop_session(
     display: &str,
     working_dir: &std::path::Path,
 ) -> anyhow::Result<()> {
+    if desktop_macos::is_macos_display(display) {
+        return desktop_macos::stop_session(display, working_dir).map(|_| ());
+    }
+
     // Extract display number
     let display_num: u32 = display
         .trim_start_matches(':')
@@ -566,12 +898,8 @@ pub(crate) async fn close_desktop_session(
                 // Kill processes by PID
                 for pid_key in ["xvfb_pid", "i3_pid", "browser_pid"] {
                     if let Some(pid) = session_info[pid_key].as_u64() {
-                        let pid = pid as i32;
-                        // SAFETY: PIDs are read from a session file we wrote;
-                        // SIGTERM is a safe signal to send to any process.
-                        unsafe {
-                            libc::kill(pid, libc::SIGTERM);
-                        }
+                        // PIDs are read from a session file we wrote.
+                        crate::process_tree::terminate_process(pid as u32);
                     }
                 }
             }
@@ -655,3 +983,33 @@ pub async fn start_cleanup_task(state: Arc<AppState>) {
         tokio::time::sleep(Duration::from_secs(interval_secs)).await;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_ids_are_normalized_and_actions_are_snake_case() {
+        assert_eq!(normalize_display("99".to_string()), ":99");
+        assert_eq!(normalize_display(":99".to_string()), ":99");
+        assert_eq!(
+            serde_json::to_value(DesktopSessionAction::KeepAliveExtended).unwrap(),
+            "keep_alive_extended"
+        );
+        let query: ListSessionsQuery = serde_json::from_str(r#"{"status":"active"}"#).unwrap();
+        assert_eq!(query.status, Some(DesktopSessionStatus::Active));
+    }
+
+    #[test]
+    fn shared_folder_sits_next_to_the_screenshots() {
+        let fallback = std::path::Path::new("/srv/work");
+        assert_eq!(
+            shared_dir_for(Some("/ws/mission/screenshots"), fallback),
+            std::path::Path::new("/ws/mission/desktop-shared")
+        );
+        assert_eq!(
+            shared_dir_for(None, fallback),
+            std::path::Path::new("/srv/work/desktop-shared")
+        );
+    }
+}
@@ -0,0 +1,450 @@
+//! Inbound email gateway.
+//!
+//! `POST /api/email/inbound` turns an email forwarded by a mail provider's
+//! inbound webhook into a mission: the subject becomes the title, the plain
+//! text body the first message, and attachments are saved to the mission
+//! directory under `attachments/`. The response carries a reply with the
+//! mission link, which is also posted to `SANDBOXED_SH_EMAIL_REPLY_URL` when
+//! set so a relay can send it back to the sender.
+//!
+//! The gateway is off unless `SANDBOXED_SH_EMAIL_TOKEN` is set; requests must
+//! present that token in the `X-Email-Token` header or the `token` query
+//! parameter. Missions are created for the instance user in
+//! `SANDBOXED_SH_EMAIL_WORKSPACE` (the host workspace by default), and
+//! `SANDBOXED_SH_EMAIL_ALLOWED_SENDERS` restricts who may create them.
+//!
+//! The payload is JSON. Postmark's inbound format is accepted as is; other
+//! providers can map their fields to `from`, `subject`, `text`, `message_id`
+//! and `attachments` (`name` and base64 `content`).
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    http::{HeaderMap, StatusCode},
+    Extension, Json,
+};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use super::audit::AuditEntry;
+use super::auth::{constant_time_eq, AuthUser};
+use super::control::{create_mission, ControlCommand, CreateMissionRequest};
+use super::fs::sanitize_path_component;
+use super::routes::AppState;
+use crate::util::{env_var_trimmed, internal_error};
+use crate::workspace;
+
+/// Directory, relative to the mission directory, that attachments go to.
+const ATTACHMENTS_DIR: &str = "attachments";
+
+const MAX_ATTACHMENTS: usize = 20;
+const MAX_TITLE_CHARS: usize = 120;
+
+/// Gateway settings, read from the environment on each request.
+#[derive(Debug, Clone, Default)]
+pub struct EmailGatewayConfig {
+    pub token: Option<String>,
+    pub workspace_id: Option<Uuid>,
+    /// Addresses (`jane@example.com`) or domains (`@example.com`); empty
+    /// allows any sender
+    pub allowed_senders: Vec<String>,
+    pub reply_url: Option<String>,
+    /// Dashboard base URL for mission links (relative links when unset)
+    pub public_url: Option<String>,
+}
+
+impl EmailGatewayConfig {
+    pub fn from_env() -> Self {
+        Self {
+            token: env_var_trimmed("SANDBOXED_SH_EMAIL_TOKEN"),
+            workspace_id: env_var_trimmed("SANDBOXED_SH_EMAIL_WORKSPACE")
+                .and_then(|v| v.parse().ok()),
+            allowed_senders: env_var_trimmed("SANDBOXED_SH_EMAIL_ALLOWED_SENDERS")
+                .map(|v| {
+                    v.split(',')
+                        .map(|s| s.trim().to_ascii_lowercase())
+                        .filter(|s| !s.is_empty())
+                        .collect()
+                })
+                .unwrap_or_default(),
+            reply_url: env_var_trimmed("SANDBOXED_SH_EMAIL_REPLY_URL"),
+            public_url: env_var_trimmed("SANDBOXED_SH_PUBLIC_URL"),
+        }
+    }
+
+    fn sender_allowed(&self, address: &str) -> bool {
+        self.allowed_senders.is_empty()
+            || self.allowed_senders.iter().any(|allowed| {
+                if allowed.starts_with('@') {
+                    address.ends_with(allowed.as_str())
+                } else {
+                    address == allowed
+                }
+            })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InboundEmail {
+    #[serde(alias = "From")]
+    pub from: String,
+    #[serde(default, alias = "Subject")]
+    pub subject: String,
+    #[serde(default, alias = "TextBody", alias = "body-plain")]
+    pub text: String,
+    #[serde(default, alias = "MessageID", alias = "Message-Id")]
+    pub message_id: Option<String>,
+    #[serde(default, alias = "Attachments")]
+    pub attachments: Vec<InboundAttachment>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InboundAttachment {
+    #[serde(alias = "Name", alias = "filename")]
+    pub name: String,
+    /// Base64-encoded file content
+    #[serde(alias = "Content")]
+    pub content: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InboundQuery {
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+/// Reply to send back to the sender.
+#[derive(Debug, Clone, Serialize)]
+pub struct EmailReply {
+    pub to: String,
+    pub subject: String,
+    pub text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub in_reply_to: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InboundEmailResponse {
+    pub mission_id: Uuid,
+    pub url: String,
+    /// Saved attachments, relative to the mission directory
+    pub attachments: Vec<String>,
+    pub reply: EmailReply,
+}
+
+/// The bare address of a `From` header (`Jane <jane@example.com>`), lowercased.
+pub fn sender_address(from: &str) -> Option<String> {
+    let address = match (from.rfind('<'), from.rfind('>')) {
+        (Some(start), Some(end)) if start < end => &from[start + 1..end],
+        _ => from,
+    };
+    let address = address.trim().to_ascii_lowercase();
+    (address.contains('@') && !address.contains(char::is_whitespace)).then_some(address)
+}
+
+fn mission_title(subject: &str, sender: &str) -> String {
+    let subject = subject.trim();
+    if subject.is_empty() {
+        return format!("Email from {}", sender);
+    }
+    subject.chars().take(MAX_TITLE_CHARS).collect()
+}
+
+/// First message of the mission: the email body, then where the attachments
+/// were saved.
+pub fn first_message(email: &InboundEmail, attachments: &[String]) -> String {
+    let body = email.text.trim();
+    let mut message = if body.is_empty() {
+        email.subject.trim().to_string()
+    } else {
+        body.to_string()
+    };
+    if !attachments.is_empty() {
+        message.push_str("\n\nAttachments saved in the mission directory:\n");
+        for path in attachments {
+            message.push_str(&format!("- {}\n", path));
+        }
+    }
+    message
+}
+
+fn reply_subject(subject: &str) -> String {
+    let subject = subject.trim();
+    if subject.to_ascii_lowercase().starts_with("re:") {
+        subject.to_string()
+    } else if subject.is_empty() {
+        "Re: your request".to_string()
+    } else {
+        format!("Re: {}", subject)
+    }
+}
+
+/// An attachment's file name and decoded content.
+type DecodedAttachment = (String, Vec<u8>);
+
+/// Decode attachments into unique file names and contents, so a malformed
+/// email is rejected before any mission is created.
+fn decode_attachments(
+    attachments: &[InboundAttachment],
+) -> Result<Vec<DecodedAttachment>, (StatusCode, String)> {
+    if attachments.len() > MAX_ATTACHMENTS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("At most {} attachments are accepted", MAX_ATTACHMENTS),
+        ));
+    }
+    let mut decoded: Vec<DecodedAttachment> = Vec::new();
+    for attachment in attachments {
+        let mut name = sanitize_path_component(&attachment.name);
+        if name.is_empty() {
+            name = format!("attachment-{}", decoded.len() + 1);
+        }
+        let encoded: String = attachment
+            .content
+            .chars()
+            .filter(|c| !c.is_whitespace())
+            .collect();
+        let bytes = base64::engine::general_purpose::STANDARD
+            .decode(encoded)
+            .map_err(|e| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    format!("Attachment {} is not valid base64: {}", name, e),
+                )
+            })?;
+        // Keep both files when two attachments share a name
+        let mut unique = name.clone();
+        let mut n = 1;
+        while decoded.iter().any(|(existing, _)| *existing == unique) {
+            n += 1;
+            unique = format!("{}-{}", n, name);
+        }
+        decoded.push((unique, bytes));
+    }
+    Ok(decoded)
+}
+
+/// Write decoded attachments into `dir`, returning their paths relative to
+/// the mission directory.
+async fn save_attachments(
+    dir: &std::path::Path,
+    attachments: Vec<DecodedAttachment>,
+) -> std::io::Result<Vec<String>> {
+    let mut saved = Vec::new();
+    for (name, bytes) in attachments {
+        tokio::fs::create_dir_all(dir).await?;
+        tokio::fs::write(dir.join(&name), bytes).await?;
+        saved.push(format!("{}/{}", ATTACHMENTS_DIR, name));
+    }
+    Ok(saved)
+}
+
+async fn send_reply(url: &str, reply: &EmailReply) {
+    let result = reqwest::Client::new()
+        .post(url)
+        .json(reply)
+        .timeout(std::time::Duration::from_secs(15))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status());
+    if let Err(e) = result {
+        tracing::warn!(to = %reply.to, error = %e, "Failed to send email reply");
+    }
+}
+
+/// POST /api/email/inbound - Create a mission from an inbound email.
+pub async fn receive_email(
+    State(state): State<Arc<AppState>>,
+    Query(q): Query<InboundQuery>,
+    headers: HeaderMap,
+    Json(email): Json<InboundEmail>,
+) -> Result<Json<InboundEmailResponse>, (StatusCode, String)> {
+    let config = EmailGatewayConfig::from_env();
+    let Some(expected) = config.token.as_deref() else {
+        return Err((
+            StatusCode::NOT_FOUND,
+            "Email gateway is not configured".to_string(),
+        ));
+    };
+    let token = headers
+        .get("x-email-token")
+        .and_then(|v| v.to_str().ok())
+        .or(q.token.as_deref())
+        .unwrap_or_default();
+    if !constant_time_eq(token, expected) {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            "Invalid email gateway token".to_string(),
+        ));
+    }
+
+    let sender = sender_address(&email.from).ok_or_else(|| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("Invalid sender address: {}", email.from),
+        )
+    })?;
+    if !config.sender_allowed(&sender) {
+        tracing::info!(sender = %sender, "Rejected email from a sender that is not allowed");
+        return Err((
+            StatusCode::FORBIDDEN,
+            format!("{} may not create missions by email", sender),
+        ));
+    }
+
+    let workspace_root = match config.workspace_id {
+        Some(id) => state
+            .workspaces
+            .get(id)
+            .await
+            .map(|ws| ws.path)
+            .ok_or_else(|| {
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    format!("Email gateway workspace {} not found", id),
+                )
+            })?,
+        None => state.config.working_dir.clone(),
+    };
+
+    let decoded = decode_attachments(&email.attachments)?;
+
+    let user = AuthUser::local(&state.config);
+    let request = CreateMissionRequest {
+        title: Some(mission_title(&email.subject, &sender)),
+        workspace_id: config.workspace_id,
+        agent: None,
+        model_override: None,
+        model_effort: None,
+        config_profile: None,
+        backend: None,
+        agent_version: None,
+        tags: vec!["email".to_string()],
+        dry_run: false,
+        env_profile: None,
+    };
+    let Json(mission) = create_mission(
+        State(Arc::clone(&state)),
+        Extension(user.clone()),
+        HeaderMap::new(),
+        Some(Json(request)),
+    )
+    .await?;
+
+    let mission_dir = workspace::mission_workspace_dir_for_root(&workspace_root, mission.id);
+    let attachments = save_attachments(&mission_dir.join(ATTACHMENTS_DIR), decoded)
+        .await
+        .map_err(|e| internal_error(format!("Failed to save attachments: {}", e)))?;
+
+    let control = state.control.get_or_spawn(&user).await;
+    let (respond, _) = oneshot::channel();
+    control
+        .cmd_tx
+        .send(ControlCommand::UserMessage {
+            id: Uuid::new_v4(),
+            content: first_message(&email, &attachments),
+            agent: None,
+            target_mission_id: Some(mission.id),
+            respond,
+        })
+        .await
+        .map_err(|e| internal_error(format!("Failed to start mission: {}", e)))?;
+
+    let url =
+        crate::util::mission_url(config.public_url.as_deref().unwrap_or_default(), mission.id);
+    let reply = EmailReply {
+        to: sender.clone(),
+        subject: reply_subject(&email.subject),
+        text: format!(
+            "Your request has been started as a mission.\n\nFollow its progress at {}\n",
+            url
+        ),
+        in_reply_to: email.message_id.clone(),
+    };
+    if let Some(reply_url) = config.reply_url {
+        let reply = reply.clone();
+        tokio::spawn(async move { send_reply(&reply_url, &reply).await });
+    }
+
+    let mut entry = AuditEntry::new(
+        "email",
+        sender.clone(),
+        format!("create mission from email: {}", email.subject.trim()),
+    );
+    entry.mission_id = Some(mission.id);
+    entry.success = true;
+    state.audit.record(&entry).await;
+    tracing::info!(sender = %sender, mission_id = %mission.id, "Created mission from email");
+
+    Ok(Json(InboundEmailResponse {
+        mission_id: mission.id,
+        url,
+        attachments,
+        reply,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn senders_are_parsed_and_checked_against_the_allow_list() {
+        assert_eq!(
+            sender_address("Jane Doe <Jane@Example.com>").as_deref(),
+            Some("jane@example.com")
+        );
+        assert_eq!(
+            sender_address("ops@corp.io").as_deref(),
+            Some("ops@corp.io")
+        );
+        assert_eq!(sender_address("not an address"), None);
+
+        let config = EmailGatewayConfig {
+            allowed_senders: vec!["@example.com".to_string(), "ops@corp.io".to_string()],
+            ..Default::default()
+        };
+        assert!(config.sender_allowed("jane@example.com"));
+        assert!(config.sender_allowed("ops@corp.io"));
+        assert!(!config.sender_allowed("dev@corp.io"));
+        assert!(!config.sender_allowed("jane@notexample.com.evil"));
+        assert!(EmailGatewayConfig::default().sender_allowed("anyone@anywhere.org"));
+    }
+
+    #[tokio::test]
+    async fn postmark_payloads_become_a_first_message_with_attachments() {
+        let email: InboundEmail = serde_json::from_value(serde_json::json!({
+            "From": "Jane <jane@example.com>",
+            "Subject": "Fix the login page",
+            "TextBody": "The button is broken.\n",
+            "MessageID": "abc@mail",
+            "Attachments": [
+                {"Name": "../shot.png", "ContentType": "image/png", "Content": "aGVs\nbG8="},
+                {"Name": "shot.png", "Content": "aGk="}
+            ]
+        }))
+        .unwrap();
+        assert_eq!(email.message_id.as_deref(), Some("abc@mail"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let decoded = decode_attachments(&email.attachments).unwrap();
+        let saved = save_attachments(dir.path(), decoded).await.unwrap();
+        assert_eq!(saved, ["attachments/shot.png", "attachments/2-shot.png"]);
+        assert_eq!(
+            std::fs::read(dir.path().join("shot.png")).unwrap(),
+            b"hello"
+        );
+
+        let message = first_message(&email, &["attachments/shot.png".to_string()]);
+        assert!(message.starts_with("The button is broken."));
+        assert!(message.ends_with("- attachments/shot.png\n"));
+        assert_eq!(reply_subject(&email.subject), "Re: Fix the login page");
+        assert_eq!(
+            mission_title("  ", "jane@example.com"),
+            "Email from jane@example.com"
+        );
+    }
+}
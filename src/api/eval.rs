@@ -0,0 +1,603 @@
+//! Evaluation harness for catching agent regressions.
+//!
+//! A suite is a directory with one sub-directory per benchmark task:
+//!
+//! - `task.json` - `{"prompt": "...", "timeout_secs": 1800}` (`timeout_secs`
+//!   is optional)
+//! - `fixture/` - optional files copied into the workspace before the run
+//! - `check.sh` - success checker, run with `sh` in the workspace once the
+//!   mission's turn has finished; exit code 0 is a pass
+//!
+//! Every task runs as a normal mission (same control session, backends and
+//! events as a mission started from the dashboard) in its own temporary host
+//! workspace under `.sandboxed-sh/eval/`, removed afterwards. Missions are
+//! tagged `eval`. The report gives each task's outcome and cost plus the
+//! suite's pass rate and total cost.
+//!
+//! Runs are started by instance users with `POST /api/admin/eval` and polled
+//! with `GET /api/admin/eval/:id`. From the command line,
+//! `sandboxed-sh --eval <suite> [--backend <id>] [--model <model>]` runs a
+//! suite without serving the API and prints the report as JSON.
+
+use std::collections::HashMap;
+use std::path::{Path as FsPath, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::client::{MissionClient, MissionOptions};
+use super::mission_store::now_string;
+use super::routes::AppState;
+use crate::workspace::{self, SharedWorkspaceStore, Workspace};
+
+/// Directory under the working directory that API-started suites must live in.
+const EVALS_DIR: &str = "evals";
+const TASK_FILE: &str = "task.json";
+const FIXTURE_DIR: &str = "fixture";
+const CHECK_SCRIPT: &str = "check.sh";
+const DEFAULT_TASK_TIMEOUT_SECS: u64 = 1800;
+const CHECK_TIMEOUT_SECS: u64 = 300;
+/// Checker output kept in the report, in characters (the end of the output).
+const MAX_DETAIL_CHARS: usize = 2000;
+const EVAL_TAG: &str = "eval";
+
+/// Eval runs started through the API, by run id.
+pub type SharedEvalRuns = Arc<RwLock<HashMap<Uuid, EvalReport>>>;
+
+#[derive(Debug, Deserialize)]
+struct TaskFile {
+    prompt: String,
+    #[serde(default)]
+    timeout_secs: Option<u64>,
+}
+
+/// A benchmark task loaded from a suite directory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvalTask {
+    pub name: String,
+    pub dir: PathBuf,
+    pub prompt: String,
+    pub timeout_secs: u64,
+}
+
+/// Load the tasks of a suite, ordered by name.
+pub fn load_suite(dir: &FsPath) -> Result<Vec<EvalTask>, String> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| format!("Cannot read suite {}: {}", dir.display(), e))?;
+    let mut tasks = Vec::new();
+    for entry in entries.flatten() {
+        let task_dir = entry.path();
+        let task_file = task_dir.join(TASK_FILE);
+        if !task_file.is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().to_string();
+        let content = std::fs::read_to_string(&task_file)
+            .map_err(|e| format!("Cannot read {}: {}", task_file.display(), e))?;
+        let file: TaskFile = serde_json::from_str(&content)
+            .map_err(|e| format!("Invalid {}: {}", task_file.display(), e))?;
+        if file.prompt.trim().is_empty() {
+            return Err(format!("Task {} has an empty prompt", name));
+        }
+        if !task_dir.join(CHECK_SCRIPT).is_file() {
+            return Err(format!("Task {} has no {}", name, CHECK_SCRIPT));
+        }
+        tasks.push(EvalTask {
+            name,
+            dir: task_dir,
+            prompt: file.prompt,
+            timeout_secs: file.timeout_secs.unwrap_or(DEFAULT_TASK_TIMEOUT_SECS),
+        });
+    }
+    if tasks.is_empty() {
+        return Err(format!(
+            "Suite {} has no tasks (sub-directories with a {})",
+            dir.display(),
+            TASK_FILE
+        ));
+    }
+    tasks.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(tasks)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskOutcome {
+    /// The checker exited with 0
+    Passed,
+    /// The checker ran and failed
+    Failed,
+    /// The task could not be run or checked
+    Error,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskResult {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mission_id: Option<Uuid>,
+    pub outcome: TaskOutcome,
+    pub cost_cents: u64,
+    pub duration_secs: u64,
+    /// Checker output or the error that stopped the task
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvalStatus {
+    Running,
+    Completed,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EvalReport {
+    pub id: Uuid,
+    pub suite: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backend: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub status: EvalStatus,
+    pub started_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed_at: Option<String>,
+    /// Tasks in the suite
+    pub total: usize,
+    pub passed: usize,
+    /// Passed share of the tasks run so far (0.0 - 1.0)
+    pub pass_rate: f64,
+    pub cost_cents: u64,
+    pub tasks: Vec<TaskResult>,
+}
+
+impl EvalReport {
+    pub fn new(
+        suite: &FsPath,
+        total: usize,
+        backend: Option<String>,
+        model: Option<String>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            suite: suite.display().to_string(),
+            backend,
+            model,
+            status: EvalStatus::Running,
+            started_at: now_string(),
+            completed_at: None,
+            total,
+            passed: 0,
+            pass_rate: 0.0,
+            cost_cents: 0,
+            tasks: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, result: TaskResult) {
+        if result.outcome == TaskOutcome::Passed {
+            self.passed += 1;
+        }
+        self.cost_cents += result.cost_cents;
+        self.tasks.push(result);
+        self.pass_rate = self.passed as f64 / self.tasks.len() as f64;
+    }
+
+    pub fn finish(&mut self) {
+        self.status = EvalStatus::Completed;
+        self.completed_at = Some(now_string());
+    }
+
+    /// Whether every task passed.
+    pub fn all_passed(&self) -> bool {
+        self.passed == self.total
+    }
+}
+
+/// Runs eval tasks as missions in one user's control session.
+pub struct Evaluator {
+    client: MissionClient,
+    workspaces: SharedWorkspaceStore,
+    root: PathBuf,
+    backend: Option<String>,
+    model: Option<String>,
+}
+
+impl Evaluator {
+    pub fn new(client: MissionClient, backend: Option<String>, model: Option<String>) -> Self {
+        let state = client.state();
+        Self {
+            workspaces: Arc::clone(&state.workspaces),
+            root: state.config.working_dir.join(".sandboxed-sh").join("eval"),
+            client,
+            backend,
+            model,
+        }
+    }
+
+    /// Run every task in order, adding results to `report`.
+    pub async fn run_suite(&self, tasks: &[EvalTask], report: &mut EvalReport) {
+        for task in tasks {
+            report.push(self.run_task(task).await);
+        }
+        report.finish();
+    }
+
+    /// Run one task in a fresh temporary workspace and check the result.
+    pub async fn run_task(&self, task: &EvalTask) -> TaskResult {
+        let started = Instant::now();
+        let root = self.root.join(Uuid::new_v4().to_string());
+        let mut workspace = Workspace::default_host(root.clone());
+        workspace.id = Uuid::new_v4();
+        workspace.name = format!("eval-{}", task.name);
+
+        let mut result = TaskResult {
+            name: task.name.clone(),
+            mission_id: None,
+            outcome: TaskOutcome::Error,
+            cost_cents: 0,
+            duration_secs: 0,
+            detail: None,
+        };
+        if let Err(e) = tokio::fs::create_dir_all(&root).await {
+            result.detail = Some(format!("Failed to create workspace: {}", e));
+            return result;
+        }
+        self.workspaces.add(workspace.clone()).await;
+
+        match self.run_in_workspace(task, &workspace, &mut result).await {
+            Ok((passed, output)) => {
+                result.outcome = if passed {
+                    TaskOutcome::Passed
+                } else {
+                    TaskOutcome::Failed
+                };
+                result.detail = Some(output).filter(|o| !o.is_empty());
+            }
+            Err(e) => result.detail = Some(e),
+        }
+
+        self.workspaces.delete(workspace.id).await;
+        if let Err(e) = tokio::fs::remove_dir_all(&root).await {
+            tracing::warn!("Failed to remove eval workspace {}: {}", root.display(), e);
+        }
+        result.duration_secs = started.elapsed().as_secs();
+        tracing::info!(
+            task = %task.name,
+            outcome = ?result.outcome,
+            cost_cents = result.cost_cents,
+            "Eval task finished"
+        );
+        result
+    }
+
+    /// Returns whether the checker passed, with its output.
+    async fn run_in_workspace(
+        &self,
+        task: &EvalTask,
+        workspace: &Workspace,
+        result: &mut TaskResult,
+    ) -> Result<(bool, String), String> {
+        let options = MissionOptions {
+            title: Some(format!("Eval: {}", task.name)),
+            workspace_id: Some(workspace.id),
+            backend: self.backend.clone(),
+            model: self.model.clone(),
+        };
+        let mission = self.client.create_mission(&options).await?;
+        result.mission_id = Some(mission.id);
+        if let Err(e) = self
+            .client
+            .mission_store()
+            .update_mission_tags(mission.id, &[EVAL_TAG.to_string()])
+            .await
+        {
+            tracing::warn!("Failed to tag eval mission {}: {}", mission.id, e);
+        }
+
+        let work_dir = workspace::mission_workspace_dir_for_root(&workspace.path, mission.id);
+        let fixture = task.dir.join(FIXTURE_DIR);
+        if fixture.is_dir() {
+            let (from, to) = (fixture.clone(), work_dir.clone());
+            tokio::task::spawn_blocking(move || copy_dir(&from, &to))
+                .await
+                .map_err(|e| format!("Task join error: {}", e))?
+                .map_err(|e| format!("Failed to copy fixture: {}", e))?;
+        } else {
+            tokio::fs::create_dir_all(&work_dir)
+                .await
+                .map_err(|e| format!("Failed to create workspace: {}", e))?;
+        }
+
+        let timeout = Duration::from_secs(task.timeout_secs);
+        let turn = self
+            .client
+            .run_turn(mission.id, &task.prompt, Some(timeout))
+            .await?;
+        result.cost_cents = turn.cost_cents;
+        if !turn.success {
+            tracing::info!(
+                task = %task.name,
+                "Eval mission turn failed: {}",
+                truncate_start(&turn.content, 200)
+            );
+        }
+
+        run_check(&task.dir.join(CHECK_SCRIPT), &work_dir).await
+    }
+}
+
+/// Run the checker in `work_dir`; returns whether it passed and its output.
+async fn run_check(script: &FsPath, work_dir: &FsPath) -> Result<(bool, String), String> {
+    let mut command = tokio::process::Command::new("sh");
+    command.arg(script).current_dir(work_dir).kill_on_drop(true);
+    let output = tokio::time::timeout(Duration::from_secs(CHECK_TIMEOUT_SECS), command.output())
+        .await
+        .map_err(|_| format!("Checker timed out after {}s", CHECK_TIMEOUT_SECS))?
+        .map_err(|e| format!("Failed to run checker: {}", e))?;
+    let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    Ok((
+        output.status.success(),
+        truncate_start(text.trim(), MAX_DETAIL_CHARS),
+    ))
+}
+
+/// Keep the last `max_chars` characters, where test failures usually are.
+fn truncate_start(text: &str, max_chars: usize) -> String {
+    let count = text.chars().count();
+    if count <= max_chars {
+        return text.to_string();
+    }
+    let tail: String = text.chars().skip(count - max_chars).collect();
+    format!("...{}", tail)
+}
+
+fn copy_dir(from: &FsPath, to: &FsPath) -> std::io::Result<()> {
+    for entry in walkdir::WalkDir::new(from) {
+        let entry = entry?;
+        let relative = entry.path().strip_prefix(from).unwrap_or(entry.path());
+        let target = to.join(relative);
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Run a suite from the command line as the local instance user.
+pub async fn run_local(
+    state: &Arc<AppState>,
+    suite: &FsPath,
+    backend: Option<String>,
+    model: Option<String>,
+) -> Result<EvalReport, String> {
+    let tasks = load_suite(suite)?;
+    let client = MissionClient::local(state).await;
+    let mut report = EvalReport::new(suite, tasks.len(), backend.clone(), model.clone());
+    Evaluator::new(client, backend, model)
+        .run_suite(&tasks, &mut report)
+        .await;
+    Ok(report)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StartEvalRequest {
+    /// Suite directory, relative to the `evals/` directory of the working directory
+    pub suite: PathBuf,
+    #[serde(default)]
+    pub backend: Option<String>,
+    #[serde(default)]
+    pub model: Option<String>,
+}
+
+/// Resolve a requested suite inside `<working_dir>/evals`. Absolute paths and
+/// `..` are rejected, as are symlinks leading out of the directory.
+fn resolve_suite(working_dir: &FsPath, suite: &FsPath) -> Result<PathBuf, (StatusCode, String)> {
+    let bad_request = |msg: &str| (StatusCode::BAD_REQUEST, msg.to_string());
+    if suite.as_os_str().is_empty()
+        || !suite
+            .components()
+            .all(|c| matches!(c, std::path::Component::Normal(_)))
+    {
+        return Err(bad_request(
+            "Suite must be a relative path inside the evals directory",
+        ));
+    }
+    let root = working_dir
+        .join(EVALS_DIR)
+        .canonicalize()
+        .map_err(|_| bad_request("No evals directory on the server"))?;
+    let resolved = root
+        .join(suite)
+        .canonicalize()
+        .map_err(|_| bad_request("Suite not found"))?;
+    if !resolved.starts_with(&root) {
+        return Err(bad_request(
+            "Suite must be a relative path inside the evals directory",
+        ));
+    }
+    Ok(resolved)
+}
+
+/// POST /api/admin/eval - Start an eval run in the background.
+pub async fn start_eval(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Json(req): Json<StartEvalRequest>,
+) -> Result<(StatusCode, Json<EvalReport>), (StatusCode, String)> {
+    let suite = resolve_suite(&state.config.working_dir, &req.suite)?;
+    let tasks = load_suite(&suite).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    let mut report = EvalReport::new(&suite, tasks.len(), req.backend.clone(), req.model.clone());
+    let run_id = report.id;
+    state.eval_runs.write().await.insert(run_id, report.clone());
+
+    let client = MissionClient::for_user(&state, user).await;
+    let evaluator = Evaluator::new(client, req.backend, req.model);
+    let runs = Arc::clone(&state.eval_runs);
+    tokio::spawn(async move {
+        for task in &tasks {
+            report.push(evaluator.run_task(task).await);
+            runs.write().await.insert(run_id, report.clone());
+        }
+        report.finish();
+        tracing::info!(
+            run_id = %run_id,
+            passed = report.passed,
+            total = report.total,
+            cost_cents = report.cost_cents,
+            "Eval run finished"
+        );
+        runs.write().await.insert(run_id, report);
+    });
+
+    Ok((
+        StatusCode::ACCEPTED,
+        Json(state.eval_runs.read().await[&run_id].clone()),
+    ))
+}
+
+/// GET /api/admin/eval - Eval runs since the server started, newest first.
+pub async fn list_evals(State(state): State<Arc<AppState>>) -> Json<Vec<EvalReport>> {
+    let mut runs: Vec<EvalReport> = state.eval_runs.read().await.values().cloned().collect();
+    runs.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    Json(runs)
+}
+
+/// GET /api/admin/eval/:id - Report of an eval run (partial while running).
+pub async fn get_eval(
+    State(state): State<Arc<AppState>>,
+    Path(run_id): Path<Uuid>,
+) -> Result<Json<EvalReport>, (StatusCode, String)> {
+    state
+        .eval_runs
+        .read()
+        .await
+        .get(&run_id)
+        .cloned()
+        .map(Json)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Eval run {} not found", run_id),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_task(suite: &FsPath, name: &str, task_json: &str, check: bool) {
+        let dir = suite.join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join(TASK_FILE), task_json).unwrap();
+        if check {
+            std::fs::write(dir.join(CHECK_SCRIPT), "test -f done.txt\n").unwrap();
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn suites_must_stay_inside_the_evals_directory() {
+        let working_dir = tempfile::tempdir().unwrap();
+        let evals = working_dir.path().join(EVALS_DIR);
+        std::fs::create_dir_all(evals.join("smoke")).unwrap();
+        std::fs::create_dir_all(working_dir.path().join("secret")).unwrap();
+        std::os::unix::fs::symlink(working_dir.path().join("secret"), evals.join("link")).unwrap();
+
+        let resolved = resolve_suite(working_dir.path(), FsPath::new("smoke")).unwrap();
+        assert!(resolved.ends_with("evals/smoke"));
+        for suite in [
+            "",
+            "/etc",
+            "../secret",
+            "smoke/../../secret",
+            "link",
+            "missing",
+        ] {
+            let (status, _) = resolve_suite(working_dir.path(), FsPath::new(suite)).unwrap_err();
+            assert_eq!(status, StatusCode::BAD_REQUEST, "{}", suite);
+        }
+    }
+
+    #[test]
+    fn load_suite_reads_tasks_in_order() {
+        let suite = tempfile::tempdir().unwrap();
+        write_task(
+            suite.path(),
+            "b-fix-lint",
+            r#"{"prompt": "fix lint"}"#,
+            true,
+        );
+        write_task(
+            suite.path(),
+            "a-add-test",
+            r#"{"prompt": "add a test", "timeout_secs": 60}"#,
+            true,
+        );
+        std::fs::create_dir_all(suite.path().join("notes")).unwrap();
+
+        let tasks = load_suite(suite.path()).unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[0].name, "a-add-test");
+        assert_eq!(tasks[0].timeout_secs, 60);
+        assert_eq!(tasks[1].timeout_secs, DEFAULT_TASK_TIMEOUT_SECS);
+    }
+
+    #[test]
+    fn load_suite_rejects_tasks_without_checker() {
+        let suite = tempfile::tempdir().unwrap();
+        write_task(suite.path(), "broken", r#"{"prompt": "x"}"#, false);
+        assert!(load_suite(suite.path()).unwrap_err().contains(CHECK_SCRIPT));
+
+        let empty = tempfile::tempdir().unwrap();
+        assert!(load_suite(empty.path()).is_err());
+    }
+
+    #[test]
+    fn report_tracks_pass_rate_and_cost() {
+        let mut report = EvalReport::new(FsPath::new("suite"), 2, None, None);
+        for (outcome, cost_cents) in [(TaskOutcome::Passed, 12), (TaskOutcome::Failed, 30)] {
+            report.push(TaskResult {
+                name: "t".to_string(),
+                mission_id: None,
+                outcome,
+                cost_cents,
+                duration_secs: 1,
+                detail: None,
+            });
+        }
+        report.finish();
+        assert_eq!(report.passed, 1);
+        assert_eq!(report.pass_rate, 0.5);
+        assert_eq!(report.cost_cents, 42);
+        assert!(!report.all_passed());
+        assert_eq!(report.status, EvalStatus::Completed);
+    }
+
+    #[tokio::test]
+    async fn checker_runs_in_workspace() {
+        let suite = tempfile::tempdir().unwrap();
+        write_task(suite.path(), "t", r#"{"prompt": "x"}"#, true);
+        let script = suite.path().join("t").join(CHECK_SCRIPT);
+        let work = tempfile::tempdir().unwrap();
+
+        assert!(!run_check(&script, work.path()).await.unwrap().0);
+        std::fs::write(work.path().join("done.txt"), "").unwrap();
+        assert!(run_check(&script, work.path()).await.unwrap().0);
+    }
+}
@@ -0,0 +1,514 @@
+//! Sequenced event log for control sessions.
+//!
+//! Every event published on a session's broadcast channel is assigned a
+//! monotonically increasing sequence number and appended to a bounded ring
+//! buffer (`SANDBOXED_SH_EVENT_BUFFER` entries, default 4096). Consumers read
+//! the ring at their own pace with a cursor instead of holding a broadcast
+//! receiver, so a slow SSE client never causes events to be dropped for
+//! anyone else, and whatever a client does miss is reported as an explicit
+//! gap (`from_seq..=to_seq`) rather than a vague "lagged" error.
+//!
+//! Sequence numbers double as SSE event ids, which lets clients resume a
+//! stream with `Last-Event-ID`. They start from the wall clock (microseconds
+//! since the Unix epoch) so ids keep increasing across server restarts. The
+//! mission store logger is also a cursor reader and persists each event with
+//! its sequence number, so a reconnecting client whose position has already
+//! left the ring is backfilled from the store. Replayed events carry
+//! `"replayed": true`.
+//!
+//! Clients watching specific missions don't need to wake up for every event
+//! of the session: the log also fans each event out to a broadcast channel
+//! per mission, created when a mission-scoped stream subscribes and dropped
+//! once its last subscriber is gone.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::{broadcast, watch};
+use uuid::Uuid;
+
+use super::control::AgentEvent;
+use super::mission_store::StoredEvent;
+
+const DEFAULT_CAPACITY: usize = 4096;
+/// Events buffered per mission channel before a subscriber lags.
+const MISSION_CHANNEL_CAPACITY: usize = 256;
+
+/// An item read from the log.
+#[derive(Debug, Clone)]
+pub enum LogItem {
+    Event {
+        seq: u64,
+        event: Arc<AgentEvent>,
+    },
+    /// Events `from_seq..=to_seq` are no longer available.
+    Gap(EventGap),
+}
+
+/// Marker sent to clients for a range of lost events.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EventGap {
+    pub from_seq: u64,
+    pub to_seq: u64,
+    pub missed: u64,
+}
+
+impl EventGap {
+    fn new(from_seq: u64, to_seq: u64) -> Self {
+        Self {
+            from_seq,
+            to_seq,
+            missed: to_seq - from_seq + 1,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Ring {
+    entries: VecDeque<(u64, Arc<AgentEvent>)>,
+    next_seq: u64,
+}
+
+/// Broadcast channels carrying the events of single missions.
+#[derive(Debug, Default)]
+struct MissionChannels {
+    channels: Mutex<HashMap<Uuid, broadcast::Sender<LogItem>>>,
+}
+
+impl MissionChannels {
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Uuid, broadcast::Sender<LogItem>>> {
+        self.channels.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn subscribe(&self, mission_id: Uuid) -> broadcast::Receiver<LogItem> {
+        self.lock()
+            .entry(mission_id)
+            .or_insert_with(|| broadcast::channel(MISSION_CHANNEL_CAPACITY).0)
+            .subscribe()
+    }
+
+    fn publish(&self, mission_id: Uuid, item: LogItem) {
+        let mut channels = self.lock();
+        if let Some(tx) = channels.get(&mission_id) {
+            if tx.send(item).is_err() {
+                // Nobody listens anymore
+                channels.remove(&mission_id);
+            }
+        }
+    }
+
+    /// Tell every mission's subscribers about events lost before reaching
+    /// the log (their mission is unknown).
+    fn publish_gap(&self, gap: EventGap) {
+        self.lock()
+            .retain(|_, tx| tx.send(LogItem::Gap(gap.clone())).is_ok());
+    }
+}
+
+/// Bounded, sequenced event log shared by all consumers of a session.
+#[derive(Debug)]
+pub struct EventLog {
+    capacity: usize,
+    ring: Mutex<Ring>,
+    /// Next sequence number; consumers wait on changes.
+    head: watch::Sender<u64>,
+    missions: MissionChannels,
+}
+
+impl EventLog {
+    pub fn new(capacity: usize) -> Self {
+        Self::with_start_seq(capacity, 0)
+    }
+
+    pub fn with_start_seq(capacity: usize, start_seq: u64) -> Self {
+        let (head, _) = watch::channel(start_seq);
+        Self {
+            capacity: capacity.max(1),
+            ring: Mutex::new(Ring {
+                entries: VecDeque::new(),
+                next_seq: start_seq,
+            }),
+            head,
+            missions: MissionChannels::default(),
+        }
+    }
+
+    pub fn from_env() -> Self {
+        let capacity = std::env::var("SANDBOXED_SH_EVENT_BUFFER")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_CAPACITY);
+        let start_seq = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_micros() as u64)
+            .unwrap_or(0);
+        Self::with_start_seq(capacity, start_seq)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Ring> {
+        self.ring.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Append an event and return its sequence number.
+    pub fn push(&self, event: AgentEvent) -> u64 {
+        let event = Arc::new(event);
+        let mut ring = self.lock();
+        let seq = ring.next_seq;
+        ring.entries.push_back((seq, Arc::clone(&event)));
+        while ring.entries.len() > self.capacity {
+            ring.entries.pop_front();
+        }
+        ring.next_seq += 1;
+        self.head.send_replace(ring.next_seq);
+        // Published under the ring lock so mission channels see events in
+        // sequence order.
+        if let Some(mission_id) = event.mission_id() {
+            self.missions
+                .publish(mission_id, LogItem::Event { seq, event });
+        }
+        seq
+    }
+
+    /// Reserve sequence numbers for `count` events that were lost before
+    /// reaching the log, so readers see them as a gap.
+    pub fn skip(&self, count: u64) {
+        if count == 0 {
+            return;
+        }
+        let mut ring = self.lock();
+        let from_seq = ring.next_seq;
+        ring.next_seq += count;
+        self.head.send_replace(ring.next_seq);
+        self.missions
+            .publish_gap(EventGap::new(from_seq, ring.next_seq - 1));
+    }
+
+    /// Receive the events of `mission_id` (and gaps of the log) as they are
+    /// appended.
+    pub fn subscribe_mission(&self, mission_id: Uuid) -> broadcast::Receiver<LogItem> {
+        self.missions.subscribe(mission_id)
+    }
+
+    /// Number of missions with a live channel.
+    pub fn mission_channel_count(&self) -> usize {
+        self.missions.lock().len()
+    }
+
+    /// Sequence number the next event will get.
+    pub fn next_seq(&self) -> u64 {
+        self.lock().next_seq
+    }
+
+    /// Oldest sequence number still buffered.
+    pub fn first_seq(&self) -> u64 {
+        let ring = self.lock();
+        ring.entries
+            .front()
+            .map(|(seq, _)| *seq)
+            .unwrap_or(ring.next_seq)
+    }
+
+    /// Watch the head of the log to wait for new events.
+    pub fn subscribe(&self) -> watch::Receiver<u64> {
+        self.head.subscribe()
+    }
+
+    /// Read up to `max` events starting at `cursor`. Returns the items (with
+    /// gap markers for missing ranges) and the cursor to continue from.
+    ///
+    /// A cursor past the head (an id this log never issued, e.g. after the
+    /// clock moved backwards) replays whatever is still buffered.
+    pub fn read_from(&self, cursor: u64, max: usize) -> (Vec<LogItem>, u64) {
+        let ring = self.lock();
+        let mut expected = if cursor > ring.next_seq {
+            ring.entries.front().map(|(seq, _)| *seq).unwrap_or(0)
+        } else {
+            cursor
+        };
+        let start = ring.entries.partition_point(|(seq, _)| *seq < expected);
+
+        let mut items = Vec::new();
+        let mut truncated = false;
+        for (seq, event) in ring.entries.iter().skip(start) {
+            if *seq > expected {
+                if items.len() >= max {
+                    truncated = true;
+                    break;
+                }
+                items.push(LogItem::Gap(EventGap::new(expected, seq - 1)));
+                expected = *seq;
+            }
+            if items.len() >= max {
+                truncated = true;
+                break;
+            }
+            items.push(LogItem::Event {
+                seq: *seq,
+                event: Arc::clone(event),
+            });
+            expected = seq + 1;
+        }
+        if !truncated && items.len() < max && expected < ring.next_seq {
+            items.push(LogItem::Gap(EventGap::new(expected, ring.next_seq - 1)));
+            expected = ring.next_seq;
+        }
+        (items, expected)
+    }
+
+    /// Like `read_from`, keeping only the events of `missions` (and gaps).
+    pub fn read_missions_from(
+        &self,
+        cursor: u64,
+        missions: &[Uuid],
+        max: usize,
+    ) -> (Vec<LogItem>, u64) {
+        let (items, next) = self.read_from(cursor, max);
+        let items = items
+            .into_iter()
+            .filter(|item| match item {
+                LogItem::Event { event, .. } => {
+                    event.mission_id().is_some_and(|id| missions.contains(&id))
+                }
+                LogItem::Gap(_) => true,
+            })
+            .collect();
+        (items, next)
+    }
+}
+
+/// Drop `text_delta` events that a later `text_delta` of the same mission in
+/// `items` supersedes. Deltas carry the accumulated text, so only the latest
+/// of a burst needs to reach the client; a delta followed by another event of
+/// its mission (e.g. a tool call) is kept.
+pub fn coalesce_text_deltas(items: Vec<LogItem>) -> Vec<LogItem> {
+    let mut superseded = std::collections::HashSet::new();
+    let mut kept: Vec<LogItem> = items
+        .into_iter()
+        .rev()
+        .filter(|item| {
+            let LogItem::Event { event, .. } = item else {
+                return true;
+            };
+            let mission_id = event.mission_id();
+            if matches!(**event, AgentEvent::TextDelta { .. }) {
+                superseded.insert(mission_id)
+            } else {
+                superseded.remove(&mission_id);
+                true
+            }
+        })
+        .collect();
+    kept.reverse();
+    kept
+}
+
+/// Feed a session's broadcast channel into its event log.
+///
+/// This is the only broadcast receiver on the hot path and it never blocks,
+/// so it keeps up with the producers; if it ever does lag, the lost events are
+/// recorded as a gap in the log. Credentials in `http_request` tool calls are
+/// redacted here, before the event is streamed or persisted.
+pub fn spawn_sequencer(events_tx: &broadcast::Sender<AgentEvent>, log: Arc<EventLog>) {
+    let mut rx = events_tx.subscribe();
+    tokio::spawn(async move {
+        loop {
+            match rx.recv().await {
+                Ok(mut event) => {
+                    if let AgentEvent::ToolCall { name, args, .. } = &mut event {
+                        crate::tools::http_request::redact_tool_args(name, args);
+                    }
+                    log.push(event);
+                }
+                Err(broadcast::error::RecvError::Lagged(n)) => {
+                    tracing::warn!("Event sequencer lagged by {} events", n);
+                    log.skip(n);
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+/// Parse a `Last-Event-ID` value into the cursor to resume from.
+pub fn resume_cursor(last_event_id: Option<&str>) -> Option<u64> {
+    last_event_id
+        .and_then(|id| id.trim().parse::<u64>().ok())
+        .map(|seq| seq + 1)
+}
+
+/// Serialize a buffered event, tagging it as replayed.
+pub fn replayed_event(event: &AgentEvent) -> Value {
+    let mut value = serde_json::to_value(event).unwrap_or_else(|_| json!({}));
+    if let Value::Object(map) = &mut value {
+        map.insert("replayed".to_string(), Value::Bool(true));
+    }
+    value
+}
+
+/// Rebuild the SSE payload for an event persisted in the mission store.
+///
+/// Stored events keep the text content and type-specific metadata, which is
+/// enough to recreate the shape of the original `AgentEvent` for the UI.
+pub fn replayed_stored_event(event: &StoredEvent) -> Value {
+    let mut value = match &event.metadata {
+        Value::Object(map) => Value::Object(map.clone()),
+        _ => json!({}),
+    };
+    let parse = |s: &str| serde_json::from_str::<Value>(s).unwrap_or_else(|_| json!(s));
+    let fields = match event.event_type.as_str() {
+        "user_message" | "assistant_message" => json!({
+            "id": event.event_id,
+            "content": event.content,
+        }),
+        "tool_call" => json!({
+            "tool_call_id": event.tool_call_id,
+            "name": event.tool_name,
+            "args": parse(&event.content),
+        }),
+        "tool_result" => json!({
+            "tool_call_id": event.tool_call_id,
+            "name": event.tool_name,
+            "result": parse(&event.content),
+        }),
+        "error" => json!({ "message": event.content }),
+        "mission_status_changed" => json!({
+            "summary": (!event.content.is_empty()).then(|| event.content.clone()),
+        }),
+        "mission_metadata_updated" => json!({}),
+        _ => json!({ "content": event.content }),
+    };
+    if let (Value::Object(map), Value::Object(fields)) = (&mut value, fields) {
+        map.extend(fields);
+        map.insert("type".to_string(), json!(event.event_type));
+        map.insert("mission_id".to_string(), json!(event.mission_id));
+        map.insert("replayed".to_string(), Value::Bool(true));
+    }
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(n: usize) -> AgentEvent {
+        AgentEvent::Status {
+            state: super::super::control::ControlRunState::Idle,
+            queue_len: n,
+            mission_id: None,
+        }
+    }
+
+    fn seqs(items: &[LogItem]) -> Vec<String> {
+        items
+            .iter()
+            .map(|item| match item {
+                LogItem::Event { seq, .. } => seq.to_string(),
+                LogItem::Gap(gap) => format!("gap {}-{}", gap.from_seq, gap.to_seq),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn slow_reader_gets_gap_for_evicted_events() {
+        let log = EventLog::new(3);
+        for n in 0..5 {
+            log.push(event(n));
+        }
+        let (items, cursor) = log.read_from(0, 100);
+        assert_eq!(seqs(&items), vec!["gap 0-1", "2", "3", "4"]);
+        assert_eq!(cursor, 5);
+
+        // Resuming from the last seen id only returns newer events.
+        log.push(event(5));
+        let (items, cursor) = log.read_from(resume_cursor(Some("4")).unwrap(), 100);
+        assert_eq!(seqs(&items), vec!["5"]);
+        assert_eq!(cursor, 6);
+    }
+
+    #[test]
+    fn skipped_events_and_batches() {
+        let log = EventLog::new(10);
+        log.push(event(0));
+        log.skip(2);
+        log.push(event(3));
+        log.skip(1);
+
+        let (items, cursor) = log.read_from(0, 1);
+        assert_eq!(seqs(&items), vec!["0"]);
+        assert_eq!(cursor, 1);
+
+        let (items, cursor) = log.read_from(cursor, 10);
+        assert_eq!(seqs(&items), vec!["gap 1-2", "3", "gap 4-4"]);
+        assert_eq!(cursor, 5);
+
+        // Batches never exceed the limit, gaps included.
+        let (items, cursor) = log.read_from(1, 1);
+        assert_eq!(seqs(&items), vec!["gap 1-2"]);
+        let (items, cursor) = log.read_from(cursor, 1);
+        assert_eq!(seqs(&items), vec!["3"]);
+        let (items, cursor) = log.read_from(cursor, 1);
+        assert_eq!(seqs(&items), vec!["gap 4-4"]);
+        assert_eq!(cursor, 5);
+        let (items, cursor) = log.read_from(1, 2);
+        assert_eq!(seqs(&items), vec!["gap 1-2", "3"]);
+        assert_eq!(cursor, 4);
+
+        // A cursor past the head replays the buffer.
+        let (items, _) = log.read_from(99, 10);
+        assert_eq!(seqs(&items)[0], "0");
+    }
+
+    #[test]
+    fn mission_channels_carry_only_their_mission() {
+        let (a, b) = (uuid::Uuid::new_v4(), uuid::Uuid::new_v4());
+        let message = |mission_id| AgentEvent::TextDelta {
+            content: "hi".to_string(),
+            mission_id: Some(mission_id),
+        };
+        let log = EventLog::new(10);
+        log.push(message(a));
+        let mut rx = log.subscribe_mission(a);
+        log.push(message(b));
+        log.push(message(a));
+        log.push(event(0));
+        log.skip(2);
+
+        let mut received = Vec::new();
+        while let Ok(item) = rx.try_recv() {
+            received.push(item);
+        }
+        assert_eq!(seqs(&received), vec!["2", "gap 4-5"]);
+
+        let (items, _) = log.read_missions_from(0, &[a], 10);
+        assert_eq!(seqs(&items), vec!["0", "2", "gap 4-5"]);
+
+        drop(rx);
+        log.push(message(a));
+        assert_eq!(log.mission_channel_count(), 0);
+    }
+
+    #[test]
+    fn text_delta_bursts_coalesce_per_mission() {
+        let (a, b) = (Some(uuid::Uuid::new_v4()), Some(uuid::Uuid::new_v4()));
+        let delta = |content: &str, mission_id| AgentEvent::TextDelta {
+            content: content.to_string(),
+            mission_id,
+        };
+        let log = EventLog::new(10);
+        log.push(delta("H", a));
+        log.push(delta("x", b));
+        log.push(delta("He", a));
+        log.push(AgentEvent::Thinking {
+            content: "checking".to_string(),
+            done: true,
+            mission_id: a,
+        });
+        log.push(delta("Hel", a));
+        log.push(delta("Hell", a));
+
+        let (items, _) = log.read_from(0, 10);
+        assert_eq!(seqs(&coalesce_text_deltas(items)), vec!["1", "2", "3", "5"]);
+    }
+}
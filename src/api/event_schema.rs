@@ -0,0 +1,188 @@
+//! Versioning of the mission event schema.
+//!
+//! Events are persisted (`mission_events`) and streamed (SSE) as JSON whose
+//! shape follows `AgentEvent`. Each stored row records the schema version it
+//! was written with, and rows from older versions are upgraded when read, so
+//! consumers of the mission store only ever see the current shape.
+//!
+//! Versions:
+//! - 1: events stored before versioning. Assistant messages may only carry
+//!   the legacy flat `cost_cents` metadata.
+//! - 2: assistant messages carry the normalized `cost` object
+//!   (`amount_cents`, `currency`, `source`); SSE payloads carry
+//!   `schema_version`, and the stream includes the event types added after
+//!   version 1 (user questions, usage, sampling, verification, ...).
+//!
+//! Clients built against an older version pass `?schema_version=<n>` to the
+//! SSE stream and get events downleveled to that version.
+
+use serde_json::{json, Value};
+
+use super::mission_store::StoredEvent;
+
+/// Schema version of the events this build writes and emits.
+pub const CURRENT_VERSION: u32 = 2;
+
+/// Oldest schema version the SSE stream can downlevel to.
+pub const MIN_VERSION: u32 = 1;
+
+/// Version assumed for stored events that don't record one.
+pub(crate) fn legacy_version() -> u32 {
+    MIN_VERSION
+}
+
+/// Event types a version 1 client knows about.
+const V1_EVENT_TYPES: &[&str] = &[
+    "status",
+    "user_message",
+    "assistant_message",
+    "thinking",
+    "text_delta",
+    "tool_call",
+    "tool_result",
+    "error",
+    "mission_status_changed",
+    "agent_phase",
+    "agent_tree",
+    "progress",
+    "session_id_update",
+    "mission_activity",
+    "mission_title_changed",
+    "mission_metadata_updated",
+];
+
+/// Check a client-requested schema version.
+pub fn validate_version(version: u32) -> Result<u32, String> {
+    if (MIN_VERSION..=CURRENT_VERSION).contains(&version) {
+        Ok(version)
+    } else {
+        Err(format!(
+            "Unsupported schema_version {} (supported: {}-{})",
+            version, MIN_VERSION, CURRENT_VERSION
+        ))
+    }
+}
+
+/// Upgrade a stored event to the current schema version.
+pub fn upgrade(event: &mut StoredEvent) {
+    if event.schema_version < 2 {
+        upgrade_v1(event);
+    }
+    event.schema_version = CURRENT_VERSION;
+}
+
+/// Version 1 assistant messages only recorded the flat `cost_cents`.
+fn upgrade_v1(event: &mut StoredEvent) {
+    if event.event_type != "assistant_message" {
+        return;
+    }
+    let Value::Object(map) = &mut event.metadata else {
+        return;
+    };
+    if map.get("cost").is_some_and(Value::is_object) {
+        return;
+    }
+    // Malformed negative costs are clamped, like the cost aggregates do.
+    let amount_cents = map
+        .get("cost_cents")
+        .and_then(Value::as_i64)
+        .unwrap_or(0)
+        .max(0);
+    map.insert("cost_cents".to_string(), json!(amount_cents));
+    map.insert(
+        "cost".to_string(),
+        json!({
+            "amount_cents": amount_cents,
+            "currency": "USD",
+            "source": "unknown",
+        }),
+    );
+}
+
+/// Shape an SSE payload of type `event_type` for a client of `version`.
+///
+/// Returns `None` when the event has no equivalent in that version and
+/// should not be sent.
+pub fn for_client(event_type: &str, mut payload: Value, version: u32) -> Option<Value> {
+    if version < 2 && !V1_EVENT_TYPES.contains(&event_type) {
+        return None;
+    }
+    if let Value::Object(map) = &mut payload {
+        if version >= 2 {
+            map.insert("schema_version".to_string(), json!(version));
+        } else {
+            map.remove("schema_version");
+        }
+    }
+    Some(payload)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn stored(event_type: &str, metadata: Value, schema_version: u32) -> StoredEvent {
+        StoredEvent {
+            id: 1,
+            mission_id: Uuid::nil(),
+            sequence: 1,
+            event_type: event_type.to_string(),
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            event_id: None,
+            tool_call_id: None,
+            tool_name: None,
+            content: "done".to_string(),
+            metadata,
+            stream_seq: None,
+            schema_version,
+        }
+    }
+
+    #[test]
+    fn upgrades_legacy_assistant_cost() {
+        let mut event = stored("assistant_message", json!({ "cost_cents": 12 }), 1);
+        upgrade(&mut event);
+        assert_eq!(event.schema_version, CURRENT_VERSION);
+        assert_eq!(event.metadata["cost"]["amount_cents"], 12);
+        assert_eq!(event.metadata["cost"]["source"], "unknown");
+
+        let mut negative = stored("assistant_message", json!({ "cost_cents": -3 }), 1);
+        upgrade(&mut negative);
+        assert_eq!(negative.metadata["cost_cents"], 0);
+    }
+
+    #[test]
+    fn upgrade_keeps_normalized_cost() {
+        let metadata = json!({
+            "cost_cents": 5,
+            "cost": { "amount_cents": 5, "currency": "USD", "source": "actual" },
+        });
+        let mut event = stored("assistant_message", metadata.clone(), 1);
+        upgrade(&mut event);
+        assert_eq!(event.metadata, metadata);
+
+        let mut current = stored("assistant_message", json!({}), CURRENT_VERSION);
+        upgrade(&mut current);
+        assert!(current.metadata.get("cost").is_none());
+    }
+
+    #[test]
+    fn downlevels_payloads_for_old_clients() {
+        let payload = json!({ "type": "assistant_message", "content": "hi" });
+        let current = for_client("assistant_message", payload.clone(), CURRENT_VERSION).unwrap();
+        assert_eq!(current["schema_version"], CURRENT_VERSION);
+
+        let v1 = for_client("assistant_message", current, 1).unwrap();
+        assert_eq!(v1, payload);
+        assert!(for_client("user_question", json!({}), 1).is_none());
+        assert!(for_client("user_question", json!({}), 2).is_some());
+    }
+
+    #[test]
+    fn rejects_unknown_versions() {
+        assert!(validate_version(0).is_err());
+        assert!(validate_version(CURRENT_VERSION + 1).is_err());
+        assert_eq!(validate_version(1), Ok(1));
+    }
+}
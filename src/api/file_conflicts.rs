@@ -0,0 +1,180 @@
+//! Conflict detection for missions editing the same files.
+//!
+//! Parallel runners share the filesystem, so two missions can silently
+//! overwrite each other's edits. The control actor feeds every tool call into
+//! a [`FileConflictTracker`], which records the files each running mission
+//! writes (via `Write`/`Edit`/`apply_patch`-style tools) and reports an edit
+//! that targets a file another running mission already wrote.
+//!
+//! Backends stream a tool call before executing it, so a conflict is usually
+//! seen before the edit lands. What happens next depends on
+//! `SANDBOXED_SH_FILE_CONFLICT_POLICY`:
+//!
+//! - `warn` (default) - emit `AgentEvent::ConflictDetected` and continue
+//! - `block` - also cancel the editing mission's turn, leaving it resumable so
+//!   the user can decide which mission proceeds
+//!
+//! Only absolute paths are tracked: relative paths depend on each mission's
+//! working directory and can't be compared across missions.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+
+use uuid::Uuid;
+
+use super::mission_report::edited_paths;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    Warn,
+    Block,
+}
+
+impl ConflictPolicy {
+    pub fn from_env() -> Self {
+        Self::parse(
+            std::env::var("SANDBOXED_SH_FILE_CONFLICT_POLICY")
+                .ok()
+                .as_deref(),
+        )
+    }
+
+    fn parse(value: Option<&str>) -> Self {
+        match value.map(|v| v.trim().to_ascii_lowercase()).as_deref() {
+            Some("block") => Self::Block,
+            _ => Self::Warn,
+        }
+    }
+}
+
+/// An edit overlapping files written by other running missions.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileConflict {
+    pub path: String,
+    pub other_missions: Vec<Uuid>,
+}
+
+/// Lexically normalize an absolute path (resolving `.` and `..`).
+fn normalize(path: &str) -> Option<String> {
+    let path = Path::new(path.trim());
+    if !path.is_absolute() {
+        return None;
+    }
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            Component::CurDir => {}
+            other => normalized.push(other),
+        }
+    }
+    Some(normalized.to_string_lossy().into_owned())
+}
+
+/// Files written per running mission.
+pub struct FileConflictTracker {
+    policy: ConflictPolicy,
+    files: HashMap<Uuid, HashSet<String>>,
+}
+
+impl FileConflictTracker {
+    pub fn new(policy: ConflictPolicy) -> Self {
+        Self {
+            policy,
+            files: HashMap::new(),
+        }
+    }
+
+    pub fn policy(&self) -> ConflictPolicy {
+        self.policy
+    }
+
+    /// Record a tool call by `mission_id` and return the files it edits that
+    /// other missions in `running` already wrote. Missions that are no longer
+    /// running are forgotten.
+    pub fn observe(
+        &mut self,
+        mission_id: Uuid,
+        tool_name: &str,
+        args: &serde_json::Value,
+        running: &[Uuid],
+    ) -> Vec<FileConflict> {
+        let paths: Vec<String> = edited_paths(tool_name, args)
+            .iter()
+            .filter_map(|path| normalize(path))
+            .collect();
+        if paths.is_empty() {
+            return Vec::new();
+        }
+        self.files
+            .retain(|id, _| *id == mission_id || running.contains(id));
+
+        let mut conflicts = Vec::new();
+        for path in paths {
+            let mut other_missions: Vec<Uuid> = self
+                .files
+                .iter()
+                .filter(|(id, files)| **id != mission_id && files.contains(&path))
+                .map(|(id, _)| *id)
+                .collect();
+            other_missions.sort();
+            self.files
+                .entry(mission_id)
+                .or_default()
+                .insert(path.clone());
+            if !other_missions.is_empty() {
+                conflicts.push(FileConflict {
+                    path,
+                    other_missions,
+                });
+            }
+        }
+        conflicts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn policy_defaults_to_warn() {
+        assert_eq!(ConflictPolicy::parse(None), ConflictPolicy::Warn);
+        assert_eq!(ConflictPolicy::parse(Some("BLOCK")), ConflictPolicy::Block);
+        assert_eq!(ConflictPolicy::parse(Some("other")), ConflictPolicy::Warn);
+    }
+
+    #[test]
+    fn detects_overlap_only_between_running_missions() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut tracker = FileConflictTracker::new(ConflictPolicy::Warn);
+        let edit = |path: &str| json!({ "file_path": path });
+
+        assert!(tracker
+            .observe(a, "Edit", &edit("/repo/src/lib.rs"), &[a, b])
+            .is_empty());
+        assert!(tracker
+            .observe(b, "Read", &edit("/repo/src/lib.rs"), &[a, b])
+            .is_empty());
+        assert!(tracker
+            .observe(b, "Write", &edit("relative/lib.rs"), &[a, b])
+            .is_empty());
+
+        let conflicts = tracker.observe(b, "Write", &edit("/repo/src/../src/lib.rs"), &[a, b]);
+        assert_eq!(
+            conflicts,
+            vec![FileConflict {
+                path: "/repo/src/lib.rs".to_string(),
+                other_missions: vec![a],
+            }]
+        );
+
+        // Once `b` stops running, `a` can edit the file without a conflict.
+        assert!(tracker
+            .observe(a, "Edit", &edit("/repo/src/lib.rs"), &[a])
+            .is_empty());
+    }
+}
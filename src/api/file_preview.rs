@@ -0,0 +1,307 @@
+//! Server-side previews for shared files.
+//!
+//! When an agent shares a file with a rich tag, a small preview is attached
+//! to the [`SharedFile`](super::control::SharedFile) so the UI can show it
+//! without downloading the whole file:
+//!
+//! - PDFs: a PNG thumbnail of the first page, rendered with `pdftoppm`
+//!   (poppler-utils) into the mission's `.sandboxed-sh/previews/` and cached
+//!   by path, size and modification time
+//! - CSV/TSV: the first rows as a table
+//! - Code and text: the first lines with a syntax highlighting hint
+//!
+//! Previews are best effort: a missing tool or unreadable file only means the
+//! file is shared without one.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncReadExt;
+
+use super::mission_files::language_for_path;
+
+/// Derived artifacts, relative to the mission directory.
+pub const PREVIEWS_DIR: &str = ".sandboxed-sh/previews";
+
+/// Bytes read from a text file for its preview.
+const MAX_READ_BYTES: usize = 64 * 1024;
+const MAX_EXCERPT_LINES: usize = 40;
+const MAX_TABLE_ROWS: usize = 20;
+const MAX_TABLE_COLUMNS: usize = 20;
+const MAX_CELL_CHARS: usize = 200;
+/// Thumbnail size in pixels along the longer side.
+const THUMBNAIL_PX: u32 = 480;
+const RENDER_TIMEOUT: Duration = Duration::from_secs(20);
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SharedFilePreview {
+    /// Rendered image of the file (first page of a PDF)
+    Thumbnail { url: String, content_type: String },
+    /// First lines of a code or text file
+    Code {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        language: Option<String>,
+        excerpt: String,
+        /// More lines follow the excerpt
+        truncated: bool,
+    },
+    /// First rows of a CSV or TSV file
+    Table {
+        columns: Vec<String>,
+        rows: Vec<Vec<String>>,
+        /// More rows or columns follow
+        truncated: bool,
+    },
+}
+
+/// Result of [`generate`]: an inline preview, or a rendered image that still
+/// needs a download URL.
+pub enum Preview {
+    Inline(SharedFilePreview),
+    Rendered(PathBuf),
+}
+
+/// Build the preview of `path` (canonical, inside the mission directory),
+/// rendering artifacts into `previews_dir`.
+pub async fn generate(path: &Path, content_type: &str, previews_dir: &Path) -> Option<Preview> {
+    let ext = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    match ext.as_deref() {
+        Some("pdf") => render_pdf(path, previews_dir).await.map(Preview::Rendered),
+        Some("csv") => read_head(path)
+            .await
+            .map(|(text, more)| Preview::Inline(table_preview(&text, ',', more))),
+        Some("tsv") => read_head(path)
+            .await
+            .map(|(text, more)| Preview::Inline(table_preview(&text, '\t', more))),
+        _ if language_for_path(path).is_some() || content_type.starts_with("text/") => {
+            let (text, more) = read_head(path).await?;
+            Some(Preview::Inline(code_preview(
+                &text,
+                more,
+                language_for_path(path),
+            )))
+        }
+        _ => None,
+    }
+}
+
+/// The start of a text file, and whether there is more. `None` for binary
+/// files.
+async fn read_head(path: &Path) -> Option<(String, bool)> {
+    let mut file = tokio::fs::File::open(path).await.ok()?;
+    let mut buf = Vec::with_capacity(MAX_READ_BYTES);
+    (&mut file)
+        .take(MAX_READ_BYTES as u64 + 1)
+        .read_to_end(&mut buf)
+        .await
+        .ok()?;
+    let more = buf.len() > MAX_READ_BYTES;
+    buf.truncate(MAX_READ_BYTES);
+    if buf.contains(&0) {
+        return None;
+    }
+    // A cut may split a multi-byte character; drop the partial one
+    let text = match String::from_utf8(buf) {
+        Ok(text) => text,
+        Err(e) if more && e.utf8_error().error_len().is_none() => {
+            let valid = e.utf8_error().valid_up_to();
+            let mut bytes = e.into_bytes();
+            bytes.truncate(valid);
+            String::from_utf8(bytes).ok()?
+        }
+        Err(_) => return None,
+    };
+    Some((text, more))
+}
+
+fn code_preview(text: &str, more: bool, language: Option<&str>) -> SharedFilePreview {
+    let mut lines = text.lines();
+    let excerpt: Vec<&str> = lines.by_ref().take(MAX_EXCERPT_LINES).collect();
+    SharedFilePreview::Code {
+        language: language.map(str::to_string),
+        excerpt: excerpt.join("\n"),
+        truncated: more || lines.next().is_some(),
+    }
+}
+
+/// Split CSV text into records, honouring quoted fields (with `""` escapes
+/// and embedded newlines). Stops after `max_records`; the flag tells whether
+/// anything was left.
+fn parse_records(text: &str, delimiter: char, max_records: usize) -> (Vec<Vec<String>>, bool) {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            match c {
+                '"' if chars.peek() == Some(&'"') => {
+                    field.push('"');
+                    chars.next();
+                }
+                '"' => in_quotes = false,
+                _ => field.push(c),
+            }
+            continue;
+        }
+        match c {
+            '"' if field.is_empty() => in_quotes = true,
+            '\r' => {}
+            '\n' => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+                if records.len() == max_records {
+                    return (records, chars.peek().is_some());
+                }
+            }
+            c if c == delimiter => record.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    (records, false)
+}
+
+fn table_preview(text: &str, delimiter: char, more: bool) -> SharedFilePreview {
+    let (records, truncated) = parse_records(text, delimiter, MAX_TABLE_ROWS + 1);
+    let mut truncated = truncated || more;
+    let mut cells = records.into_iter().map(|record| {
+        if record.len() > MAX_TABLE_COLUMNS {
+            truncated = true;
+        }
+        record
+            .into_iter()
+            .take(MAX_TABLE_COLUMNS)
+            .map(|cell| cell.chars().take(MAX_CELL_CHARS).collect())
+            .collect::<Vec<String>>()
+    });
+    let columns = cells.next().unwrap_or_default();
+    let rows = cells.collect();
+    SharedFilePreview::Table {
+        columns,
+        rows,
+        truncated,
+    }
+}
+
+/// Render the first page of a PDF to a PNG, reusing an earlier rendering of
+/// the same file.
+async fn render_pdf(path: &Path, previews_dir: &Path) -> Option<PathBuf> {
+    let metadata = tokio::fs::metadata(path).await.ok()?;
+    let mut hasher = Sha256::new();
+    hasher.update(path.to_string_lossy().as_bytes());
+    hasher.update(metadata.len().to_le_bytes());
+    hasher.update(super::fs::mtime_secs(&metadata).to_le_bytes());
+    let key = hex::encode(&hasher.finalize()[..12]);
+    let prefix = previews_dir.join(&key);
+    let output = prefix.with_extension("png");
+    if output.exists() {
+        return Some(output);
+    }
+
+    tokio::fs::create_dir_all(previews_dir).await.ok()?;
+    let size = THUMBNAIL_PX.to_string();
+    let rendered = tokio::time::timeout(
+        RENDER_TIMEOUT,
+        tokio::process::Command::new("pdftoppm")
+            .args(["-png", "-f", "1", "-l", "1", "-singlefile", "-scale-to"])
+            .arg(&size)
+            .arg(path)
+            .arg(&prefix)
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await;
+    match rendered {
+        Ok(Ok(out)) if out.status.success() && output.exists() => Some(output),
+        Ok(Ok(out)) => {
+            tracing::debug!(
+                path = %path.display(),
+                stderr = %String::from_utf8_lossy(&out.stderr).trim(),
+                "pdftoppm could not render a preview"
+            );
+            None
+        }
+        Ok(Err(e)) => {
+            tracing::debug!(error = %e, "pdftoppm unavailable, skipping PDF preview");
+            None
+        }
+        Err(_) => {
+            tracing::debug!(path = %path.display(), "PDF preview timed out");
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_honour_quotes_and_limits() {
+        let csv = "name,notes\n\"Smith, J\",\"said \"\"hi\"\"\nthen left\"\nDoe,\n";
+        let SharedFilePreview::Table {
+            columns,
+            rows,
+            truncated,
+        } = table_preview(csv, ',', false)
+        else {
+            panic!("expected a table");
+        };
+        assert_eq!(columns, ["name", "notes"]);
+        assert_eq!(
+            rows,
+            [
+                vec!["Smith, J".to_string(), "said \"hi\"\nthen left".to_string()],
+                vec!["Doe".to_string(), String::new()],
+            ]
+        );
+        assert!(!truncated);
+
+        let long: String = (0..50).map(|i| format!("{}\t{}\n", i, i * 2)).collect();
+        let SharedFilePreview::Table {
+            rows, truncated, ..
+        } = table_preview(&long, '\t', false)
+        else {
+            panic!("expected a table");
+        };
+        assert_eq!(rows.len(), MAX_TABLE_ROWS);
+        assert!(truncated);
+    }
+
+    #[tokio::test]
+    async fn code_files_get_an_excerpt_with_their_language() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("main.rs");
+        let source: String = (0..100).map(|i| format!("// line {}\n", i)).collect();
+        tokio::fs::write(&path, &source).await.unwrap();
+
+        let Some(Preview::Inline(SharedFilePreview::Code {
+            language,
+            excerpt,
+            truncated,
+        })) = generate(&path, "application/octet-stream", dir.path()).await
+        else {
+            panic!("expected a code preview");
+        };
+        assert_eq!(language.as_deref(), Some("rust"));
+        assert_eq!(excerpt.lines().count(), MAX_EXCERPT_LINES);
+        assert!(truncated);
+
+        let binary = dir.path().join("blob.bin");
+        tokio::fs::write(&binary, [0u8, 1, 2]).await.unwrap();
+        assert!(generate(&binary, "application/octet-stream", dir.path())
+            .await
+            .is_none());
+    }
+}
@@ -0,0 +1,230 @@
+//! Follow-ups suggested when a mission completes.
+//!
+//! When enabled, completing a mission asks a small model for a few next steps
+//! (e.g. "Add tests for the new module"). Each one is either a new mission or
+//! a message to send to the completed mission. They are stored on the mission
+//! as `follow_ups` and broadcast in an [`AgentEvent::FollowUpsSuggested`], so
+//! the UI can offer them as one-click actions. Suggestions are best effort: a
+//! disabled generator, failed call or unusable reply leaves the mission as is.
+//!
+//! Configuration:
+//!
+//! - `SANDBOXED_SH_FOLLOW_UPS` - enable follow-up suggestions
+//! - `SANDBOXED_SH_FOLLOW_UPS_MODEL` - model (default `builtin/cheap`)
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use serde_json::Value;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use super::control::AgentEvent;
+use super::llm_client::ProxyClient;
+use super::metadata_llm::{transcript, truncate_chars};
+use super::mission_store::{FollowUp, FollowUpKind, MissionStore};
+
+const DEFAULT_MODEL: &str = "builtin/cheap";
+const MAX_TOKENS: u64 = 300;
+const MAX_FOLLOW_UPS: usize = 4;
+const MAX_FOLLOW_UP_CHARS: usize = 200;
+
+const SYSTEM_PROMPT: &str = "You suggest next steps after an autonomous coding agent finished \
+a mission. From the conversation, suggest up to 4 short, specific follow-ups the user is likely \
+to want, such as adding tests for new code or updating documentation. Use kind \"message\" for \
+small additions the same agent should make in this mission, and kind \"mission\" for separate \
+pieces of work. Write each one as an instruction to the agent. Reply with JSON only: \
+{\"follow_ups\": [{\"kind\": \"mission\", \"text\": \"...\"}]}";
+
+/// Generator settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FollowUpsConfig {
+    pub model: String,
+}
+
+impl FollowUpsConfig {
+    /// Read the configuration; `None` when follow-up suggestions are disabled.
+    pub fn from_env() -> Option<Self> {
+        let enabled = std::env::var("SANDBOXED_SH_FOLLOW_UPS")
+            .map(|v| {
+                matches!(
+                    v.trim().to_lowercase().as_str(),
+                    "1" | "true" | "yes" | "on"
+                )
+            })
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+        Some(Self {
+            model: std::env::var("SANDBOXED_SH_FOLLOW_UPS_MODEL")
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+                .unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+        })
+    }
+}
+
+fn build_prompt(history: &[(String, String)], locale: &str) -> String {
+    format!(
+        "Write the follow-ups in the language with code \"{}\".\n\n\
+         Conversation:\n{}",
+        locale,
+        transcript(history)
+    )
+}
+
+/// Usable follow-ups of the reply: known kinds, non-empty and distinct texts,
+/// at most [`MAX_FOLLOW_UPS`].
+fn parse_reply(reply: &str) -> Vec<FollowUp> {
+    let value: Option<Value> = reply
+        .find('{')
+        .zip(reply.rfind('}'))
+        .and_then(|(start, end)| reply.get(start..=end))
+        .and_then(|json| serde_json::from_str(json).ok());
+    let Some(items) = value
+        .as_ref()
+        .and_then(|v| v.get("follow_ups"))
+        .and_then(Value::as_array)
+    else {
+        return Vec::new();
+    };
+
+    let mut seen = HashSet::new();
+    items
+        .iter()
+        .filter_map(|item| {
+            let kind = match item.get("kind").and_then(Value::as_str)?.trim() {
+                "mission" => FollowUpKind::Mission,
+                "message" => FollowUpKind::Message,
+                _ => return None,
+            };
+            let text = item.get("text").and_then(Value::as_str)?.trim();
+            (!text.is_empty()).then(|| FollowUp {
+                kind,
+                text: truncate_chars(text, MAX_FOLLOW_UP_CHARS),
+            })
+        })
+        .filter(|follow_up| seen.insert(follow_up.text.to_lowercase()))
+        .take(MAX_FOLLOW_UPS)
+        .collect()
+}
+
+/// Suggest follow-ups for the completed mission, store them and broadcast
+/// them. Does nothing when disabled.
+pub async fn suggest_follow_ups(
+    store: &Arc<dyn MissionStore>,
+    events_tx: &broadcast::Sender<AgentEvent>,
+    mission_id: Uuid,
+) {
+    let Some(config) = FollowUpsConfig::from_env() else {
+        return;
+    };
+    let mission = match store.get_mission(mission_id).await {
+        Ok(Some(mission)) => mission,
+        Ok(None) => return,
+        Err(e) => {
+            tracing::warn!(%mission_id, "Failed to load mission for follow-ups: {}", e);
+            return;
+        }
+    };
+    let history: Vec<(String, String)> = mission
+        .history
+        .iter()
+        .filter(|entry| entry.is_conversation())
+        .map(|entry| (entry.role.clone(), entry.content.clone()))
+        .collect();
+    if history.is_empty() {
+        return;
+    }
+
+    let completion = ProxyClient::from_env()
+        .for_mission(mission_id)
+        .complete(
+            &config.model,
+            0.3,
+            MAX_TOKENS,
+            SYSTEM_PROMPT,
+            &build_prompt(
+                &history,
+                &crate::i18n::workspace_locale(mission.workspace_id),
+            ),
+        )
+        .await;
+    let follow_ups = match completion {
+        Ok(completion) => parse_reply(&completion.content),
+        Err(e) => {
+            tracing::warn!(%mission_id, "Follow-up suggestion failed: {}", e);
+            return;
+        }
+    };
+    if follow_ups.is_empty() {
+        tracing::debug!(%mission_id, "Follow-up reply had no usable suggestions");
+        return;
+    }
+
+    if let Err(e) = store
+        .update_mission_follow_ups(mission_id, &follow_ups)
+        .await
+    {
+        tracing::warn!(%mission_id, "Failed to store follow-ups: {}", e);
+        return;
+    }
+    let _ = events_tx.send(AgentEvent::FollowUpsSuggested {
+        mission_id,
+        follow_ups,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_filters_suggestions() {
+        let reply = "```json\n{\"follow_ups\": [\
+            {\"kind\": \"mission\", \"text\": \" Add tests for the new module \"},\
+            {\"kind\": \"message\", \"text\": \"Update the README\"},\
+            {\"kind\": \"mission\", \"text\": \"add tests for the new module\"},\
+            {\"kind\": \"deploy\", \"text\": \"Ship it\"},\
+            {\"kind\": \"message\", \"text\": \"\"}\
+        ]}\n```";
+        assert_eq!(
+            parse_reply(reply),
+            vec![
+                FollowUp {
+                    kind: FollowUpKind::Mission,
+                    text: "Add tests for the new module".to_string(),
+                },
+                FollowUp {
+                    kind: FollowUpKind::Message,
+                    text: "Update the README".to_string(),
+                },
+            ]
+        );
+        assert!(parse_reply("{\"follow_ups\": \"none\"}").is_empty());
+        assert!(parse_reply("no json here").is_empty());
+
+        let many = (0..10)
+            .map(|i| format!("{{\"kind\": \"mission\", \"text\": \"step {}\"}}", i))
+            .collect::<Vec<_>>()
+            .join(",");
+        assert_eq!(
+            parse_reply(&format!("{{\"follow_ups\": [{}]}}", many)).len(),
+            MAX_FOLLOW_UPS
+        );
+    }
+
+    #[test]
+    fn prompt_asks_for_the_workspace_language() {
+        let history = vec![
+            ("user".to_string(), "Add a CSV exporter".to_string()),
+            ("assistant".to_string(), "Added src/export.rs".to_string()),
+        ];
+        let prompt = build_prompt(&history, "fr");
+        assert!(prompt.contains("\"fr\""));
+        assert!(prompt.contains("user: Add a CSV exporter"));
+        assert!(prompt.contains("assistant: Added src/export.rs"));
+    }
+}
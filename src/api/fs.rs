@@ -6,15 +6,17 @@ use std::sync::Arc;
 
 use axum::{
     body::Body,
-    extract::{Multipart, Query, State},
+    extract::{Multipart, Path as AxumPath, Query, State},
     http::{header, header::HeaderValue, HeaderMap, StatusCode},
     response::{IntoResponse, Response},
-    Json,
+    Extension, Json,
 };
 use serde::{Deserialize, Serialize};
 use tokio::io::AsyncWriteExt;
 use tokio_util::io::ReaderStream;
 
+use super::auth::AuthUser;
+use super::control::AgentEvent;
 use super::routes::AppState;
 use crate::util::{home_dir, internal_error};
 use crate::workspace::WorkspaceType;
@@ -178,26 +180,41 @@ pub fn content_type_for_path(path: &Path) -> &'static str {
         Some("md") => "text/markdown; charset=utf-8",
         Some("json") => "application/json",
         Some("csv") => "text/csv; charset=utf-8",
+        Some("diff") | Some("patch") => "text/x-diff; charset=utf-8",
         _ => "application/octet-stream",
     }
 }
 
 /// Resolve a path relative to a specific workspace.
 /// If mission_id is provided and path is a context path, resolves to mission-specific context.
+/// Workspaces and missions of other tenants are reported as not found.
 pub async fn resolve_path_for_workspace(
     state: &Arc<AppState>,
+    user: &AuthUser,
     workspace_id: uuid::Uuid,
     path: &str,
     mission_id: Option<uuid::Uuid>,
 ) -> Result<PathBuf, (StatusCode, String)> {
-    let workspace = state.workspaces.get(workspace_id).await.ok_or_else(|| {
-        (
-            StatusCode::NOT_FOUND,
-            format!("Workspace {} not found", workspace_id),
-        )
-    })?;
+    let workspace = super::workspaces::require_workspace(
+        &state.workspaces,
+        workspace_id,
+        user.tenant.as_deref(),
+    )
+    .await?;
+    if let (Some(_), Some(mission_id)) = (user.tenant.as_deref(), mission_id) {
+        // The context directory is shared by all tenants' missions
+        state
+            .control
+            .get_or_spawn(user)
+            .await
+            .mission_store
+            .get_mission(mission_id)
+            .await
+            .map_err(internal_error)?
+            .ok_or_else(|| (StatusCode::NOT_FOUND, "Mission not found".to_string()))?;
+    }
 
-    let workspace_root = workspace.path.canonicalize().map_err(|e| {
+    let workspace_root = crate::util::canonicalize_path(&workspace.path).map_err(|e| {
         (
             StatusCode::INTERNAL_SERVER_ERROR,
             format!("Failed to canonicalize workspace path: {}", e),
@@ -249,7 +266,7 @@ pub async fn resolve_path_for_workspace(
     // Canonicalize to resolve ".." and symlinks, then validate within workspace
     // For non-existent paths, we validate the parent directory exists and is within workspace
     let canonical = if resolved.exists() {
-        resolved.canonicalize().map_err(|e| {
+        crate::util::canonicalize_path(&resolved).map_err(|e| {
             (
                 StatusCode::BAD_REQUEST,
                 format!("Failed to resolve path: {}", e),
@@ -288,7 +305,7 @@ pub async fn resolve_path_for_workspace(
                 ));
             }
         }
-        let canonical_parent = parent.canonicalize().map_err(|e| {
+        let canonical_parent = crate::util::canonicalize_path(parent).map_err(|e| {
             (
                 StatusCode::BAD_REQUEST,
                 format!("Failed to resolve parent path: {}", e),
@@ -362,9 +379,93 @@ fn resolve_upload_base(path: &str) -> Result<PathBuf, (StatusCode, String)> {
     ))
 }
 
+/// The directory a tenant user's raw paths are confined to (created on first
+/// use); `None` for instance users, who may address any host path.
+async fn tenant_root(
+    state: &AppState,
+    user: &AuthUser,
+) -> Result<Option<PathBuf>, (StatusCode, String)> {
+    let Some(tenant) = user.tenant.as_deref() else {
+        return Ok(None);
+    };
+    let tenant = state
+        .config
+        .find_tenant(tenant)
+        .ok_or_else(|| (StatusCode::FORBIDDEN, "Unknown tenant".to_string()))?;
+    let root = tenant.data_dir(&state.config.working_dir);
+    tokio::fs::create_dir_all(&root)
+        .await
+        .map_err(internal_error)?;
+    Ok(Some(root))
+}
+
+/// Resolve `path` inside `root`: relative paths are joined to it, absolute
+/// ones must already point into it. `..` components are rejected, and
+/// symlinks are resolved before the check so they can't lead out of `root`.
+fn confine_path(root: &Path, path: &str) -> Result<PathBuf, (StatusCode, String)> {
+    let input = Path::new(path);
+    if input
+        .components()
+        .any(|c| matches!(c, std::path::Component::ParentDir))
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Path must not contain '..'".to_string(),
+        ));
+    }
+    let root = crate::util::canonicalize_path(root).map_err(internal_error)?;
+    let joined = if input.is_absolute() {
+        input.to_path_buf()
+    } else {
+        root.join(input)
+    };
+
+    // Canonicalize the longest existing prefix; the rest doesn't exist yet.
+    let mut existing = joined.as_path();
+    let mut missing = Vec::new();
+    while !existing.exists() {
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name.to_os_string());
+                existing = parent;
+            }
+            _ => break,
+        }
+    }
+    let mut resolved = crate::util::canonicalize_path(existing).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            format!("Failed to resolve path: {}", e),
+        )
+    })?;
+    resolved.extend(missing.iter().rev());
+
+    if !resolved.starts_with(&root) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            format!("{} is outside the tenant directory", path),
+        ));
+    }
+    Ok(resolved)
+}
+
+/// Resolve a path given without a workspace: confined to the tenant directory
+/// for tenant users, resolved with `host` for everyone else.
+async fn resolve_raw_path(
+    state: &AppState,
+    user: &AuthUser,
+    path: &str,
+    host: impl FnOnce(&str) -> Result<PathBuf, (StatusCode, String)>,
+) -> Result<PathBuf, (StatusCode, String)> {
+    match tenant_root(state, user).await? {
+        Some(root) => confine_path(&root, path),
+        None => host(path),
+    }
+}
+
 /// Sanitize a path component to prevent path traversal attacks.
 /// Removes directory separators and path traversal sequences.
-fn sanitize_path_component(s: &str) -> String {
+pub(crate) fn sanitize_path_component(s: &str) -> String {
     // Take only the filename portion (after any path separator)
     let filename = s.rsplit(['/', '\\']).next().unwrap_or(s);
 
@@ -498,19 +599,35 @@ pub struct FsEntry {
 }
 
 pub async fn list(
-    State(_state): State<Arc<AppState>>,
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
     Query(q): Query<PathQuery>,
 ) -> Result<Json<Vec<FsEntry>>, (StatusCode, String)> {
-    let entries = list_directory_local(&q.path)
-        .await
-        .map_err(internal_error)?;
+    let path = resolve_raw_path(&state, &user, &q.path, |p| Ok(PathBuf::from(p))).await?;
+    let entries = list_directory_local(&path).await.map_err(internal_error)?;
     Ok(Json(entries))
 }
 
-/// List directory contents locally (for localhost optimization)
-async fn list_directory_local(path: &str) -> anyhow::Result<Vec<FsEntry>> {
-    use std::os::unix::fs::MetadataExt;
+/// Modification time in seconds since the Unix epoch.
+pub(crate) fn mtime_secs(metadata: &std::fs::Metadata) -> i64 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        metadata.mtime()
+    }
+    #[cfg(not(unix))]
+    {
+        metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|age| age.as_secs() as i64)
+            .unwrap_or(0)
+    }
+}
 
+/// List directory contents locally (for localhost optimization)
+async fn list_directory_local(path: &Path) -> anyhow::Result<Vec<FsEntry>> {
     let mut entries = Vec::new();
     let mut dir = tokio::fs::read_dir(path).await?;
 
@@ -530,7 +647,7 @@ async fn list_directory_local(path: &str) -> anyhow::Result<Vec<FsEntry>> {
             "other"
         };
 
-        let mtime = metadata.mtime();
+        let mtime = mtime_secs(&metadata);
 
         entries.push(FsEntry {
             name: entry.file_name().to_string_lossy().to_string(),
@@ -545,27 +662,31 @@ async fn list_directory_local(path: &str) -> anyhow::Result<Vec<FsEntry>> {
 }
 
 pub async fn mkdir(
-    State(_state): State<Arc<AppState>>,
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
     Json(req): Json<MkdirRequest>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
-    tokio::fs::create_dir_all(&req.path)
+    let path = resolve_raw_path(&state, &user, &req.path, |p| Ok(PathBuf::from(p))).await?;
+    tokio::fs::create_dir_all(&path)
         .await
         .map_err(internal_error)?;
     Ok(Json(serde_json::json!({ "ok": true })))
 }
 
 pub async fn rm(
-    State(_state): State<Arc<AppState>>,
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
     Json(req): Json<RmRequest>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
     let recursive = req.recursive.unwrap_or(false);
+    let path = resolve_raw_path(&state, &user, &req.path, |p| Ok(PathBuf::from(p))).await?;
 
     if recursive {
-        tokio::fs::remove_dir_all(&req.path)
+        tokio::fs::remove_dir_all(&path)
             .await
             .map_err(internal_error)?;
     } else {
-        tokio::fs::remove_file(&req.path)
+        tokio::fs::remove_file(&path)
             .await
             .map_err(internal_error)?;
     }
@@ -585,12 +706,16 @@ pub struct ValidateResponse {
 
 pub async fn validate(
     State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
     Query(q): Query<PathQuery>,
 ) -> Result<Json<ValidateResponse>, (StatusCode, String)> {
     let resolved_path = if let Some(workspace_id) = q.workspace_id {
-        resolve_path_for_workspace(&state, workspace_id, &q.path, q.mission_id).await?
+        resolve_path_for_workspace(&state, &user, workspace_id, &q.path, q.mission_id).await?
     } else {
-        resolve_download_path(&q.path, Some(&state.config.working_dir))?
+        resolve_raw_path(&state, &user, &q.path, |p| {
+            resolve_download_path(p, Some(&state.config.working_dir))
+        })
+        .await?
     };
 
     if !resolved_path.exists() {
@@ -621,17 +746,18 @@ pub async fn validate(
 
 pub async fn download(
     State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
     Query(q): Query<PathQuery>,
 ) -> Result<Response, (StatusCode, String)> {
     let resolved_path = if let Some(workspace_id) = q.workspace_id {
-        resolve_path_for_workspace(&state, workspace_id, &q.path, q.mission_id).await?
+        resolve_path_for_workspace(&state, &user, workspace_id, &q.path, q.mission_id).await?
     } else {
-        resolve_download_path(&q.path, Some(&state.config.working_dir))?
+        resolve_raw_path(&state, &user, &q.path, |p| {
+            resolve_download_path(p, Some(&state.config.working_dir))
+        })
+        .await?
     };
-    let filename = q
-        .path
-        .split('/')
-        .next_back()
+    let filename = Some(crate::util::path_file_name(&q.path))
         .filter(|name| !name.is_empty())
         .unwrap_or("download");
     let mut headers = HeaderMap::new();
@@ -662,17 +788,131 @@ pub async fn download(
     Ok((headers, body).into_response())
 }
 
+/// Largest total size of the files `download-dir` will archive.
+const MAX_DIR_ARCHIVE_BYTES: u64 = 1024 * 1024 * 1024;
+
+/// GET /api/fs/download-dir - Download a directory as a ZIP archive.
+///
+/// Paths resolve like `/api/fs/download`. Symlinks inside the directory are
+/// left out so the archive cannot reach outside it.
+pub async fn download_dir(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Query(q): Query<PathQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    let resolved_path = if let Some(workspace_id) = q.workspace_id {
+        resolve_path_for_workspace(&state, &user, workspace_id, &q.path, q.mission_id).await?
+    } else {
+        resolve_raw_path(&state, &user, &q.path, |p| {
+            resolve_download_path(p, Some(&state.config.working_dir))
+        })
+        .await?
+    };
+    if !resolved_path.is_dir() {
+        return Err((
+            StatusCode::NOT_FOUND,
+            format!("Directory not found: {}", q.path),
+        ));
+    }
+    let name = Some(crate::util::path_file_name(&q.path))
+        .filter(|name| !name.is_empty())
+        .unwrap_or("download")
+        .replace('"', "");
+
+    let prefix = name.clone();
+    let archive = tokio::task::spawn_blocking(move || {
+        zip_directory(&resolved_path, &prefix, MAX_DIR_ARCHIVE_BYTES)
+    })
+    .await
+    .map_err(internal_error)?
+    .map_err(internal_error)?
+    .ok_or_else(|| {
+        (
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!(
+                "Directory is larger than {} bytes; download its files individually",
+                MAX_DIR_ARCHIVE_BYTES
+            ),
+        )
+    })?;
+
+    let headers = [
+        (header::CONTENT_TYPE, "application/zip".to_string()),
+        (
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"{}.zip\"", name),
+        ),
+    ];
+    Ok((headers, Body::from(archive)).into_response())
+}
+
+/// Zip the regular files under `dir` into `prefix/`. `None` when they add up
+/// to more than `max_bytes`.
+fn zip_directory(dir: &Path, prefix: &str, max_bytes: u64) -> std::io::Result<Option<Vec<u8>>> {
+    use std::io::Write;
+
+    let files: Vec<_> = walkdir::WalkDir::new(dir)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .collect();
+    let total: u64 = files
+        .iter()
+        .filter_map(|e| e.metadata().ok())
+        .map(|m| m.len())
+        .sum();
+    if total > max_bytes {
+        return Ok(None);
+    }
+
+    let mut buffer = Vec::new();
+    {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(&mut buffer));
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated)
+            .large_file(true);
+        for entry in files {
+            let Ok(relative) = entry.path().strip_prefix(dir) else {
+                continue;
+            };
+            let contents = match std::fs::read(entry.path()) {
+                Ok(contents) => contents,
+                Err(e) => {
+                    tracing::warn!(
+                        path = %entry.path().display(),
+                        error = %e,
+                        "Skipping unreadable file in directory archive"
+                    );
+                    continue;
+                }
+            };
+            let name = format!(
+                "{}/{}",
+                prefix,
+                relative.to_string_lossy().replace('\\', "/")
+            );
+            zip.start_file(name, options)
+                .map_err(std::io::Error::other)?;
+            zip.write_all(&contents)?;
+        }
+        zip.finish().map_err(std::io::Error::other)?;
+    }
+    Ok(Some(buffer))
+}
+
 pub async fn upload(
     State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
     Query(q): Query<PathQuery>,
     mut multipart: Multipart,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
     // If workspace_id is provided, resolve path relative to that workspace
     // If mission_id is also provided, context paths resolve to mission-specific directory
     let base = if let Some(workspace_id) = q.workspace_id {
-        resolve_path_for_workspace(&state, workspace_id, &q.path, q.mission_id).await?
+        resolve_path_for_workspace(&state, &user, workspace_id, &q.path, q.mission_id).await?
     } else {
-        resolve_upload_base(&q.path)?
+        resolve_raw_path(&state, &user, &q.path, resolve_upload_base).await?
     };
 
     // Expect one file field.
@@ -729,6 +969,145 @@ pub async fn upload(
     Err((StatusCode::BAD_REQUEST, "missing file".to_string()))
 }
 
+/// Emit an upload progress event at most once per this many bytes.
+const UPLOAD_PROGRESS_INTERVAL: u64 = 4 * 1024 * 1024;
+
+#[derive(Debug, Deserialize)]
+pub struct MissionUploadQuery {
+    /// Destination directory relative to the mission workspace (default: the
+    /// mission's context directory)
+    #[serde(default = "default_mission_upload_path")]
+    pub path: String,
+}
+
+fn default_mission_upload_path() -> String {
+    "context".to_string()
+}
+
+/// POST /api/control/missions/:id/upload - Stream multipart files into a mission workspace.
+///
+/// Each file field is written straight to a partial file next to its
+/// destination (no temp copy, no in-memory buffering) and renamed into place
+/// once complete. Progress is published as `upload_progress` events on the
+/// control stream.
+pub async fn upload_to_mission(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    AxumPath(mission_id): AxumPath<uuid::Uuid>,
+    Query(q): Query<MissionUploadQuery>,
+    headers: HeaderMap,
+    mut multipart: Multipart,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let control = state.control.get_or_spawn(&user).await;
+    let mission = control
+        .mission_store
+        .get_mission(mission_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Mission {} not found", mission_id),
+            )
+        })?;
+    let base = resolve_path_for_workspace(
+        &state,
+        &user,
+        mission.workspace_id,
+        &q.path,
+        Some(mission_id),
+    )
+    .await?;
+    tokio::fs::create_dir_all(&base).await.map_err(|e| {
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            format!("Failed to create directory: {}", e),
+        )
+    })?;
+
+    let total_bytes = headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok());
+    let progress = |upload_id, file_name: &str, bytes_received, done| {
+        let _ = control.events_tx.send(AgentEvent::UploadProgress {
+            upload_id,
+            file_name: file_name.to_string(),
+            bytes_received,
+            total_bytes,
+            done,
+            mission_id: Some(mission_id),
+        });
+    };
+
+    let mut files = Vec::new();
+    while let Some(mut field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+    {
+        let Some(raw_name) = field.file_name() else {
+            // Skip non-file form fields
+            continue;
+        };
+        let file_name = sanitize_path_component(raw_name);
+        if file_name.is_empty() {
+            return Err((StatusCode::BAD_REQUEST, "Invalid file name".to_string()));
+        }
+
+        let upload_id = uuid::Uuid::new_v4();
+        let dest = base.join(&file_name);
+        let partial = base.join(format!(".{}.{}.part", file_name, upload_id));
+        let mut f = tokio::fs::File::create(&partial)
+            .await
+            .map_err(internal_error)?;
+
+        let mut received: u64 = 0;
+        let mut reported: u64 = 0;
+        progress(upload_id, &file_name, 0, false);
+        let written: Result<(), (StatusCode, String)> = async {
+            while let Some(chunk) = field
+                .chunk()
+                .await
+                .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))?
+            {
+                f.write_all(&chunk).await.map_err(internal_error)?;
+                received += chunk.len() as u64;
+                if received - reported >= UPLOAD_PROGRESS_INTERVAL {
+                    reported = received;
+                    progress(upload_id, &file_name, received, false);
+                }
+            }
+            f.flush().await.map_err(internal_error)
+        }
+        .await;
+        if let Err(e) = written {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(e);
+        }
+        drop(f);
+
+        move_file(&partial, &dest).await?;
+        progress(upload_id, &file_name, received, true);
+        tracing::info!(
+            mission_id = %mission_id,
+            path = %dest.display(),
+            bytes = received,
+            "Uploaded file to mission workspace"
+        );
+        files.push(serde_json::json!({
+            "path": dest,
+            "name": file_name,
+            "size": received,
+        }));
+    }
+
+    if files.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "missing file".to_string()));
+    }
+    Ok(Json(serde_json::json!({ "ok": true, "files": files })))
+}
+
 // Chunked upload query params
 #[derive(Debug, Deserialize)]
 pub struct ChunkUploadQuery {
@@ -810,14 +1189,15 @@ pub struct FinalizeUploadRequest {
 // Finalize chunked upload by assembling chunks
 pub async fn upload_finalize(
     State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
     Json(req): Json<FinalizeUploadRequest>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
     // If workspace_id is provided, resolve path relative to that workspace
     // If mission_id is also provided, context paths resolve to mission-specific directory
     let base = if let Some(workspace_id) = req.workspace_id {
-        resolve_path_for_workspace(&state, workspace_id, &req.path, req.mission_id).await?
+        resolve_path_for_workspace(&state, &user, workspace_id, &req.path, req.mission_id).await?
     } else {
-        resolve_upload_base(&req.path)?
+        resolve_raw_path(&state, &user, &req.path, resolve_upload_base).await?
     };
 
     // Sanitize upload_id and file_name to prevent path traversal attacks
@@ -911,6 +1291,7 @@ pub struct DownloadUrlRequest {
 // Download file from URL to server filesystem
 pub async fn download_from_url(
     State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
     Json(req): Json<DownloadUrlRequest>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
     // Validate URL to prevent SSRF attacks
@@ -1023,9 +1404,9 @@ pub async fn download_from_url(
     // Move to destination
     // If mission_id is provided, context paths resolve to mission-specific directory
     let base = if let Some(workspace_id) = req.workspace_id {
-        resolve_path_for_workspace(&state, workspace_id, &req.path, req.mission_id).await?
+        resolve_path_for_workspace(&state, &user, workspace_id, &req.path, req.mission_id).await?
     } else {
-        resolve_upload_base(&req.path)?
+        resolve_raw_path(&state, &user, &req.path, resolve_upload_base).await?
     };
     let remote_path = base.join(&file_name);
     let target_dir = remote_path
@@ -1046,3 +1427,70 @@ pub async fn download_from_url(
         serde_json::json!({ "ok": true, "path": remote_path, "name": file_name }),
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directory_archives_skip_symlinks_and_respect_the_size_cap() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("site/css")).unwrap();
+        std::fs::write(dir.path().join("site/index.html"), "<html></html>").unwrap();
+        std::fs::write(dir.path().join("site/css/app.css"), "body {}").unwrap();
+        #[cfg(unix)]
+        std::os::unix::fs::symlink("/etc/hostname", dir.path().join("site/host")).unwrap();
+
+        let site = dir.path().join("site");
+        let bytes = zip_directory(&site, "site", 1024).unwrap().unwrap();
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(bytes)).unwrap();
+        let mut names: Vec<_> = archive.file_names().map(str::to_string).collect();
+        names.sort();
+        assert_eq!(names, ["site/css/app.css", "site/index.html"]);
+        assert_eq!(archive.by_name("site/css/app.css").unwrap().size(), 7);
+
+        assert!(zip_directory(&site, "site", 10).unwrap().is_none());
+    }
+
+    #[test]
+    fn tenant_paths_stay_inside_the_tenant_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let tenants = dir.path().join(".sandboxed-sh/tenants");
+        let (a, b) = (tenants.join("a"), tenants.join("b"));
+        std::fs::create_dir_all(a.join("notes")).unwrap();
+        std::fs::create_dir_all(&b).unwrap();
+        std::fs::write(b.join("secret.txt"), "b only").unwrap();
+        let a = crate::util::canonicalize_path(&a).unwrap();
+
+        assert_eq!(
+            confine_path(&a, "notes/todo.md").unwrap(),
+            a.join("notes/todo.md")
+        );
+        assert_eq!(
+            confine_path(&a, &a.join("new/dir").to_string_lossy()).unwrap(),
+            a.join("new/dir")
+        );
+
+        let other = b.join("secret.txt");
+        assert_eq!(
+            confine_path(&a, &other.to_string_lossy()).unwrap_err().0,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            confine_path(&a, "/etc/passwd").unwrap_err().0,
+            StatusCode::FORBIDDEN
+        );
+        assert_eq!(
+            confine_path(&a, "../b/secret.txt").unwrap_err().0,
+            StatusCode::BAD_REQUEST
+        );
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&b, a.join("link")).unwrap();
+            assert_eq!(
+                confine_path(&a, "link/secret.txt").unwrap_err().0,
+                StatusCode::FORBIDDEN
+            );
+        }
+    }
+}
@@ -0,0 +1,189 @@
+//! Backend handoff: moving an idle mission to another backend.
+//!
+//! `POST /api/control/missions/:id/backend` switches `mission.backend` instead
+//! of forcing a new mission when a provider degrades. The mission history is
+//! written as a backend-agnostic snapshot to [`HANDOFF_FILE`] in the mission
+//! directory, the session id is replaced (a Claude Code session can't be
+//! resumed by Codex and vice versa) and a `backend_switched` event is emitted.
+//!
+//! The first turn on the new backend consumes the snapshot: backends that are
+//! only sent the current message (Claude Code, Amp and, on the control path,
+//! OpenCode) get the prior conversation prepended to it and start a fresh
+//! session.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::mission_store::{Mission, MissionHistoryEntry, MissionStore};
+use crate::util::build_history_context;
+
+/// Snapshot file left in the mission directory until the next turn runs.
+pub const HANDOFF_FILE: &str = ".sandboxed-handoff.json";
+
+/// Conversation state carried from one backend to another.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HandoffSnapshot {
+    pub mission_id: Uuid,
+    pub from_backend: String,
+    pub to_backend: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    pub created_at: String,
+    pub messages: Vec<MissionHistoryEntry>,
+}
+
+impl HandoffSnapshot {
+    pub fn new(mission: &Mission, to_backend: &str, reason: Option<String>) -> Self {
+        Self {
+            mission_id: mission.id,
+            from_backend: mission.backend.clone(),
+            to_backend: to_backend.to_string(),
+            reason,
+            created_at: chrono::Utc::now().to_rfc3339(),
+            messages: mission.history.clone(),
+        }
+    }
+
+    /// Prompt for the first turn on the new backend: the prior conversation
+    /// (bounded by `max_chars`) followed by the current message.
+    pub fn prompt(&self, user_message: &str, max_chars: usize) -> String {
+        let history: Vec<(String, String)> = self
+            .messages
+            .iter()
+            .map(MissionHistoryEntry::context_pair)
+            .collect();
+        // The current message may already be recorded in history.
+        let history = match history.last() {
+            Some((role, content)) if role == "user" && content == user_message => {
+                &history[..history.len() - 1]
+            }
+            _ => history.as_slice(),
+        };
+        if history.is_empty() {
+            return user_message.to_string();
+        }
+        format!(
+            "## Prior conversation (continued from the {} backend)\n\n\
+             {}\
+             ## Current message\n\n\
+             {}",
+            self.from_backend,
+            build_history_context(history, max_chars),
+            user_message
+        )
+    }
+}
+
+pub async fn write_snapshot(dir: &Path, snapshot: &HandoffSnapshot) -> Result<(), String> {
+    tokio::fs::create_dir_all(dir)
+        .await
+        .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let json = serde_json::to_vec_pretty(snapshot).map_err(|e| e.to_string())?;
+    tokio::fs::write(dir.join(HANDOFF_FILE), json)
+        .await
+        .map_err(|e| format!("Failed to write handoff snapshot: {}", e))
+}
+
+/// Remove and return the pending snapshot for `backend`, if any. Snapshots
+/// written for a different backend (superseded by another switch) are dropped.
+pub async fn take_snapshot(dir: &Path, backend: &str) -> Option<HandoffSnapshot> {
+    let path = dir.join(HANDOFF_FILE);
+    let raw = tokio::fs::read(&path).await.ok()?;
+    if let Err(e) = tokio::fs::remove_file(&path).await {
+        tracing::warn!(path = %path.display(), error = %e, "Failed to remove handoff snapshot");
+    }
+    match serde_json::from_slice::<HandoffSnapshot>(&raw) {
+        Ok(snapshot) if snapshot.to_backend == backend => Some(snapshot),
+        Ok(_) => None,
+        Err(e) => {
+            tracing::warn!(path = %path.display(), error = %e, "Ignoring invalid handoff snapshot");
+            None
+        }
+    }
+}
+
+/// Switch an idle mission to `backend`. Callers check that the mission is not
+/// running and that the backend and model are valid.
+pub async fn switch_backend(
+    store: &dyn MissionStore,
+    mission: &Mission,
+    mission_dir: &Path,
+    backend: &str,
+    model_override: Option<&str>,
+    model_effort: Option<&str>,
+    reason: Option<String>,
+) -> Result<(), String> {
+    let snapshot = HandoffSnapshot::new(mission, backend, reason);
+    write_snapshot(mission_dir, &snapshot).await?;
+
+    // The old backend's session can't be resumed by the new one.
+    let session_marker = mission_dir.join(".claude-session-initiated");
+    if session_marker.exists() {
+        if let Err(e) = tokio::fs::remove_file(&session_marker).await {
+            tracing::warn!(error = %e, "Failed to remove session marker during handoff");
+        }
+    }
+    store
+        .update_mission_session_id(mission.id, &Uuid::new_v4().to_string())
+        .await?;
+    store
+        .update_mission_backend(mission.id, backend, model_override, model_effort)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(messages: &[(&str, &str)]) -> HandoffSnapshot {
+        HandoffSnapshot {
+            mission_id: Uuid::new_v4(),
+            from_backend: "claudecode".to_string(),
+            to_backend: "codex".to_string(),
+            reason: None,
+            created_at: String::new(),
+            messages: messages
+                .iter()
+                .map(|(role, content)| MissionHistoryEntry {
+                    role: role.to_string(),
+                    content: content.to_string(),
+                    tool_name: None,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn prompt_carries_prior_conversation() {
+        let snap = snapshot(&[
+            ("user", "fix the build"),
+            ("assistant", "done"),
+            ("user", "now test"),
+        ]);
+        let prompt = snap.prompt("now test", 10_000);
+        assert!(prompt.starts_with("## Prior conversation (continued from the claudecode backend)"));
+        assert!(prompt.contains("fix the build"));
+        assert!(prompt.ends_with("## Current message\n\nnow test"));
+        assert_eq!(prompt.matches("now test").count(), 1);
+
+        assert_eq!(snapshot(&[]).prompt("hello", 10_000), "hello");
+    }
+
+    #[tokio::test]
+    async fn snapshot_is_consumed_once_by_target_backend() {
+        let dir = tempfile::tempdir().unwrap();
+        write_snapshot(dir.path(), &snapshot(&[("user", "hi")]))
+            .await
+            .unwrap();
+        assert!(take_snapshot(dir.path(), "codex").await.is_some());
+        assert!(take_snapshot(dir.path(), "codex").await.is_none());
+
+        write_snapshot(dir.path(), &snapshot(&[("user", "hi")]))
+            .await
+            .unwrap();
+        assert!(take_snapshot(dir.path(), "amp").await.is_none());
+        assert!(!dir.path().join(HANDOFF_FILE).exists());
+    }
+}
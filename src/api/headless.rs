@@ -0,0 +1,108 @@
+//! Headless one-shot missions.
+//!
+//! `sandboxed-sh --task "fix the lint errors"` runs a single mission to the
+//! end of its first turn without serving the API, then prints a JSON result
+//! with the mission's structured report. The process exits non-zero unless
+//! the mission completed, which makes it usable as a CI step.
+//!
+//! Options: `--backend <id>`, `--model <model>`, `--workspace <id>` (default:
+//! the host workspace) and `--timeout <secs>` (the mission is cancelled and
+//! counted as failed when it runs longer).
+//!
+//! The mission is created in the local instance user's control session like
+//! any other, so it shows up in the dashboard afterwards.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::client::{MissionClient, MissionOptions};
+use super::control::MissionStatus;
+use super::mission_store::MissionReport;
+use super::routes::AppState;
+
+/// Result of a headless run, printed as JSON.
+#[derive(Debug, Serialize)]
+pub struct HeadlessResult {
+    pub mission_id: Uuid,
+    pub success: bool,
+    pub status: MissionStatus,
+    pub cost_cents: u64,
+    /// Why the run failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub report: MissionReport,
+}
+
+/// Status a one-shot mission is left in once its turn is over.
+fn final_status(current: MissionStatus, success: bool) -> MissionStatus {
+    match current {
+        MissionStatus::Active | MissionStatus::Pending if success => MissionStatus::Completed,
+        MissionStatus::Active | MissionStatus::Pending => MissionStatus::Failed,
+        other => other,
+    }
+}
+
+/// Run `prompt` as a one-shot mission as the local instance user.
+pub async fn run_local(
+    state: &Arc<AppState>,
+    prompt: &str,
+    options: &MissionOptions,
+    timeout: Option<Duration>,
+) -> Result<HeadlessResult, String> {
+    let client = MissionClient::local(state).await;
+    let mission = client.create_mission(options).await?;
+    tracing::info!(mission_id = %mission.id, "Running headless mission");
+
+    let (success, cost_cents, error) = match client.run_turn(mission.id, prompt, timeout).await {
+        Ok(turn) if turn.success => (true, turn.cost_cents, None),
+        Ok(turn) => (false, turn.cost_cents, Some(turn.content)),
+        Err(e) => (false, 0, Some(e)),
+    };
+
+    let current = client
+        .get_mission(mission.id)
+        .await?
+        .map(|m| m.status)
+        .unwrap_or(MissionStatus::Failed);
+    let status = final_status(current, success);
+    if status != current {
+        if let Err(e) = client.set_status(mission.id, status).await {
+            tracing::warn!("Failed to set headless mission status: {}", e);
+        }
+    }
+
+    let report = client.report(mission.id).await?;
+    Ok(HeadlessResult {
+        mission_id: mission.id,
+        success: success && status == MissionStatus::Completed,
+        status,
+        cost_cents,
+        error,
+        report,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn final_status_closes_open_missions() {
+        assert_eq!(
+            final_status(MissionStatus::Active, true),
+            MissionStatus::Completed
+        );
+        assert_eq!(
+            final_status(MissionStatus::Active, false),
+            MissionStatus::Failed
+        );
+        // The agent already set a status (e.g. blocked via complete_mission).
+        assert_eq!(
+            final_status(MissionStatus::Blocked, true),
+            MissionStatus::Blocked
+        );
+    }
+}
@@ -0,0 +1,310 @@
+//! Runner heartbeats and detailed health checks.
+//!
+//! Every control actor writes a heartbeat for each mission it is running to
+//! the mission store every [`HEARTBEAT_INTERVAL`]. The heartbeat comes from the
+//! actor itself, so a crashed process or panicked actor stops beating and its
+//! missions show up as dead to other replicas sharing the store.
+//!
+//! `GET /api/health/detailed` is meant for load balancer checks. It reports
+//! runner liveness, store connectivity, MCP server status and LLM provider
+//! reachability. It returns 503 when a mission store is unreachable and
+//! `"degraded"` (still 200) when anything else is unhealthy.
+
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+use axum::{extract::State, http::StatusCode, Json};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::mission_store::RunnerHeartbeat;
+use super::routes::AppState;
+use crate::ai_providers::{AIProvider, ProviderType};
+use crate::mcp::McpStatus;
+
+/// How often control actors record heartbeats for their running missions.
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// A runner whose last heartbeat is older than this is considered dead.
+pub const HEARTBEAT_STALE_AFTER: Duration = Duration::from_secs(60);
+
+/// Timeout for each LLM provider reachability probe.
+const PROVIDER_PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Identifier of this server process in heartbeats
+/// (`SANDBOXED_SH_INSTANCE_ID`, or `<hostname>-<pid>`).
+pub fn instance_id() -> &'static str {
+    static INSTANCE_ID: OnceLock<String> = OnceLock::new();
+    INSTANCE_ID.get_or_init(|| {
+        std::env::var("SANDBOXED_SH_INSTANCE_ID")
+            .ok()
+            .filter(|id| !id.trim().is_empty())
+            .unwrap_or_else(|| {
+                let host = std::env::var("HOSTNAME")
+                    .ok()
+                    .or_else(|| std::fs::read_to_string("/etc/hostname").ok())
+                    .map(|host| host.trim().to_string())
+                    .filter(|host| !host.is_empty())
+                    .unwrap_or_else(|| "localhost".to_string());
+                format!("{}-{}", host, std::process::id())
+            })
+    })
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RunnerLiveness {
+    pub mission_id: Uuid,
+    pub instance_id: String,
+    pub pid: u32,
+    pub beat_at: String,
+    pub age_seconds: u64,
+    pub alive: bool,
+    /// Runner belongs to this process
+    pub local: bool,
+}
+
+/// Liveness of every runner with a heartbeat. Unparseable timestamps count
+/// as dead.
+pub fn runner_liveness(beats: Vec<RunnerHeartbeat>, now: DateTime<Utc>) -> Vec<RunnerLiveness> {
+    beats
+        .into_iter()
+        .map(|beat| {
+            let age = DateTime::parse_from_rfc3339(&beat.beat_at)
+                .ok()
+                .and_then(|at| (now - at.with_timezone(&Utc)).to_std().ok());
+            let alive = age.is_some_and(|age| age < HEARTBEAT_STALE_AFTER);
+            RunnerLiveness {
+                mission_id: beat.mission_id,
+                local: beat.instance_id == instance_id(),
+                instance_id: beat.instance_id,
+                pid: beat.pid,
+                beat_at: beat.beat_at,
+                age_seconds: age.map(|age| age.as_secs()).unwrap_or(u64::MAX),
+                alive,
+            }
+        })
+        .collect()
+}
+
+/// Missions whose runner on another instance is still heartbeating.
+pub fn live_remote_missions(beats: Vec<RunnerHeartbeat>) -> Vec<Uuid> {
+    runner_liveness(beats, Utc::now())
+        .into_iter()
+        .filter(|runner| runner.alive && !runner.local)
+        .map(|runner| runner.mission_id)
+        .collect()
+}
+
+#[derive(Debug, Serialize)]
+pub struct StoreHealth {
+    pub ok: bool,
+    pub sessions: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct McpServerHealth {
+    pub id: Uuid,
+    pub name: String,
+    pub status: McpStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProviderHealth {
+    pub id: Uuid,
+    pub name: String,
+    pub provider_type: ProviderType,
+    /// Whether the provider's API answered (any HTTP status); `None` when
+    /// there is no known endpoint to probe
+    pub reachable: Option<bool>,
+    /// Not in cooldown after recent failures
+    pub healthy: bool,
+    pub degraded: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DetailedHealthResponse {
+    /// "ok", "degraded" or "unavailable"
+    pub status: &'static str,
+    pub version: String,
+    pub instance_id: String,
+    pub store: StoreHealth,
+    pub runners: Vec<RunnerLiveness>,
+    pub mcp_servers: Vec<McpServerHealth>,
+    pub providers: Vec<ProviderHealth>,
+}
+
+/// Endpoint to probe for a provider: its custom base URL, or the provider's
+/// public API.
+fn provider_probe_url(provider: &AIProvider) -> Option<String> {
+    if let Some(base_url) = provider.base_url.as_deref().filter(|url| !url.is_empty()) {
+        return Some(base_url.to_string());
+    }
+    let url = match provider.provider_type {
+        ProviderType::Anthropic => "https://api.anthropic.com/v1/models",
+        ProviderType::OpenAI => "https://api.openai.com/v1/models",
+        ProviderType::Google => "https://generativelanguage.googleapis.com/",
+        ProviderType::OpenRouter => "https://openrouter.ai/api/v1/models",
+        ProviderType::Mistral => "https://api.mistral.ai/v1/models",
+        ProviderType::Groq => "https://api.groq.com/openai/v1/models",
+        ProviderType::Xai => "https://api.x.ai/v1/models",
+        ProviderType::DeepInfra => "https://api.deepinfra.com/v1/openai/models",
+        ProviderType::Cerebras => "https://api.cerebras.ai/v1/models",
+        ProviderType::TogetherAI => "https://api.together.xyz/v1/models",
+        ProviderType::Zai => "https://api.z.ai/",
+        ProviderType::Minimax => "https://api.minimax.io/",
+        _ => return None,
+    };
+    Some(url.to_string())
+}
+
+async fn probe_provider(state: &AppState, provider: AIProvider) -> ProviderHealth {
+    let health = state.health_tracker.get_health(provider.id).await;
+    let (reachable, error) = match provider_probe_url(&provider) {
+        Some(url) => match state
+            .http_client
+            .get(&url)
+            .timeout(PROVIDER_PROBE_TIMEOUT)
+            .send()
+            .await
+        {
+            Ok(_) => (Some(true), None),
+            Err(e) => (Some(false), Some(e.to_string())),
+        },
+        None => (None, None),
+    };
+    ProviderHealth {
+        id: provider.id,
+        name: provider.name,
+        provider_type: provider.provider_type,
+        reachable,
+        healthy: health.is_healthy,
+        degraded: health.is_degraded,
+        error: error.or(health.last_failure_reason.filter(|_| !health.is_healthy)),
+    }
+}
+
+/// Detailed health check for load balancers and monitoring.
+pub async fn detailed_health(
+    State(state): State<Arc<AppState>>,
+) -> (StatusCode, Json<DetailedHealthResponse>) {
+    let sessions = state.control.all_sessions().await;
+    let stores = if sessions.is_empty() {
+        vec![state.control.get_mission_store().await]
+    } else {
+        sessions
+            .into_iter()
+            .map(|session| session.mission_store)
+            .collect()
+    };
+
+    let mut store = StoreHealth {
+        ok: true,
+        sessions: stores.len(),
+        error: None,
+    };
+    let mut beats = Vec::new();
+    for mission_store in &stores {
+        let result = match mission_store.ping().await {
+            Ok(()) => mission_store.list_runner_heartbeats().await,
+            Err(e) => Err(e),
+        };
+        match result {
+            Ok(store_beats) => beats.extend(store_beats),
+            Err(e) => {
+                store.ok = false;
+                store.error = Some(e);
+            }
+        }
+    }
+    let runners = runner_liveness(beats, Utc::now());
+
+    let mcp_servers: Vec<McpServerHealth> = state
+        .mcp
+        .list()
+        .await
+        .into_iter()
+        .filter(|server| server.config.enabled)
+        .map(|server| McpServerHealth {
+            id: server.config.id,
+            name: server.config.name,
+            status: server.status,
+            error: server.error,
+        })
+        .collect();
+
+    let enabled_providers = state
+        .ai_providers
+        .list()
+        .await
+        .into_iter()
+        .filter(|provider| provider.enabled);
+    let providers = futures::future::join_all(
+        enabled_providers.map(|provider| probe_provider(&state, provider)),
+    )
+    .await;
+
+    let degraded = runners.iter().any(|runner| !runner.alive)
+        || mcp_servers
+            .iter()
+            .any(|server| server.status == McpStatus::Error)
+        || providers
+            .iter()
+            .any(|provider| provider.reachable == Some(false) || provider.degraded);
+    let (code, status) = if !store.ok {
+        (StatusCode::SERVICE_UNAVAILABLE, "unavailable")
+    } else if degraded {
+        (StatusCode::OK, "degraded")
+    } else {
+        (StatusCode::OK, "ok")
+    };
+
+    (
+        code,
+        Json(DetailedHealthResponse {
+            status,
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            instance_id: instance_id().to_string(),
+            store,
+            runners,
+            mcp_servers,
+            providers,
+        }),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stale_heartbeats_are_dead() {
+        let now = DateTime::parse_from_rfc3339("2026-01-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let beat = |instance: &str, beat_at: &str| RunnerHeartbeat {
+            mission_id: Uuid::new_v4(),
+            instance_id: instance.to_string(),
+            pid: 1,
+            beat_at: beat_at.to_string(),
+        };
+        let runners = runner_liveness(
+            vec![
+                beat(instance_id(), "2026-01-01T11:59:50Z"),
+                beat("other", "2026-01-01T11:58:00Z"),
+                beat("other", "garbage"),
+            ],
+            now,
+        );
+        assert!(runners[0].alive && runners[0].local);
+        assert_eq!(runners[0].age_seconds, 10);
+        assert!(!runners[1].alive && !runners[1].local);
+        assert!(!runners[2].alive);
+    }
+}
@@ -0,0 +1,173 @@
+//! Idempotency keys for message submission and mission creation.
+//!
+//! Clients may send an `Idempotency-Key` header with `POST /api/control/message`
+//! and `POST /api/control/missions`. The control actor remembers each key for a
+//! window (`SANDBOXED_SH_IDEMPOTENCY_WINDOW_SECS`, default 10 minutes) so a
+//! retried request returns the original response instead of posting twice.
+//!
+//! A key is claimed before the request is executed and completed with the
+//! response afterwards. A replay that arrives while the original is still in
+//! flight is rejected with `409 Conflict`. A request that fails, or is dropped
+//! before it completes (client disconnect, panic), releases its key so it can
+//! be retried.
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use axum::http::HeaderMap;
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+const DEFAULT_WINDOW_SECS: u64 = 600;
+const MAX_KEY_LEN: usize = 255;
+
+/// Outcome of claiming a key.
+#[derive(Debug, Clone, PartialEq)]
+pub enum IdempotencyClaim {
+    /// First time this key is seen; the caller should execute the request.
+    Fresh,
+    /// The original request is still being processed.
+    InProgress,
+    /// The original request completed with this response.
+    Replay(serde_json::Value),
+}
+
+#[derive(Debug)]
+struct Entry {
+    created: Instant,
+    response: Option<serde_json::Value>,
+}
+
+/// Per-session cache of recently seen keys.
+#[derive(Debug)]
+pub struct IdempotencyCache {
+    window: Duration,
+    entries: HashMap<String, Entry>,
+}
+
+impl IdempotencyCache {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Build a cache using `SANDBOXED_SH_IDEMPOTENCY_WINDOW_SECS`.
+    pub fn from_env() -> Self {
+        let secs = std::env::var("SANDBOXED_SH_IDEMPOTENCY_WINDOW_SECS")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_WINDOW_SECS);
+        Self::new(Duration::from_secs(secs))
+    }
+
+    pub fn claim(&mut self, key: &str) -> IdempotencyClaim {
+        self.claim_at(key, Instant::now())
+    }
+
+    fn claim_at(&mut self, key: &str, now: Instant) -> IdempotencyClaim {
+        let window = self.window;
+        self.entries
+            .retain(|_, entry| now.duration_since(entry.created) < window);
+
+        if let Some(entry) = self.entries.get(key) {
+            return match &entry.response {
+                Some(response) => IdempotencyClaim::Replay(response.clone()),
+                None => IdempotencyClaim::InProgress,
+            };
+        }
+        if !window.is_zero() {
+            self.entries.insert(
+                key.to_string(),
+                Entry {
+                    created: now,
+                    response: None,
+                },
+            );
+        }
+        IdempotencyClaim::Fresh
+    }
+
+    /// Record the response for a claimed key, or release it when `None`.
+    pub fn complete(&mut self, key: &str, response: Option<serde_json::Value>) {
+        match response {
+            Some(response) => {
+                if let Some(entry) = self.entries.get_mut(key) {
+                    entry.response = Some(response);
+                }
+            }
+            None => {
+                self.entries.remove(key);
+            }
+        }
+    }
+}
+
+/// Read the `Idempotency-Key` header, scoped to an endpoint so the same key
+/// can't replay a different operation's response.
+pub fn scoped_key(headers: &HeaderMap, scope: &str) -> Result<Option<String>, String> {
+    let Some(value) = headers.get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+    let key = value
+        .to_str()
+        .map_err(|_| "Idempotency-Key must be ASCII".to_string())?
+        .trim();
+    if key.is_empty() {
+        return Ok(None);
+    }
+    if key.len() > MAX_KEY_LEN {
+        return Err(format!(
+            "Idempotency-Key must be at most {} characters",
+            MAX_KEY_LEN
+        ));
+    }
+    Ok(Some(format!("{}:{}", scope, key)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn replays_completed_and_releases_failed_keys() {
+        let mut cache = IdempotencyCache::new(Duration::from_secs(60));
+        let start = Instant::now();
+
+        assert_eq!(cache.claim_at("message:a", start), IdempotencyClaim::Fresh);
+        assert_eq!(
+            cache.claim_at("message:a", start),
+            IdempotencyClaim::InProgress
+        );
+        cache.complete("message:a", Some(json!({ "id": 1 })));
+        assert_eq!(
+            cache.claim_at("message:a", start),
+            IdempotencyClaim::Replay(json!({ "id": 1 }))
+        );
+
+        assert_eq!(cache.claim_at("message:b", start), IdempotencyClaim::Fresh);
+        cache.complete("message:b", None);
+        assert_eq!(cache.claim_at("message:b", start), IdempotencyClaim::Fresh);
+
+        // Keys expire after the window.
+        let later = start + Duration::from_secs(61);
+        assert_eq!(cache.claim_at("message:a", later), IdempotencyClaim::Fresh);
+    }
+
+    #[test]
+    fn scopes_and_validates_header() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(scoped_key(&headers, "message").unwrap(), None);
+
+        headers.insert(IDEMPOTENCY_KEY_HEADER, " abc ".parse().unwrap());
+        assert_eq!(
+            scoped_key(&headers, "message").unwrap(),
+            Some("message:abc".to_string())
+        );
+
+        headers.insert(IDEMPOTENCY_KEY_HEADER, "x".repeat(300).parse().unwrap());
+        assert!(scoped_key(&headers, "message").is_err());
+    }
+}
@@ -6,6 +6,7 @@
 //! - Skills CRUD
 //! - Commands CRUD
 //! - Library Agents CRUD
+//! - System prompt layers CRUD and preview
 //! - OpenCode settings (oh-my-opencode.json)
 //! - Sandboxed config (agent visibility, defaults)
 //! - Migration
@@ -16,17 +17,19 @@ use axum::{
     routing::{delete, get, post, put},
     Json, Router,
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
 use crate::library::{
     rename::{ItemType, RenameResult},
-    AmpCodeConfig, ClaudeCodeConfig, Command, CommandSummary, ConfigProfile, ConfigProfileSummary,
-    GitAuthor, InitScript, InitScriptSummary, LibraryAgent, LibraryAgentSummary, LibraryStatus,
-    LibraryStore, McpServer, MigrationReport, SandboxedConfig, Skill, SkillSummary,
-    WorkspaceTemplate, WorkspaceTemplateSummary,
+    AmpCodeConfig, ClaudeCodeConfig, Command, CommandSummary, ConfigProfile, ConfigProfileSettings,
+    ConfigProfileSummary, EnvProfile, EnvProfileSummary, GitAuthor, InitScript, InitScriptSummary,
+    LibraryAgent, LibraryAgentSummary, LibraryAgentVersion, LibraryStatus, LibraryStore, McpServer,
+    MigrationReport, OllamaConfig, PromptLayer, PromptPreview, PromptScope,
+    ResolvedConfigProfileSettings, SandboxedConfig, Skill, SkillSummary, WorkspaceTemplate,
+    WorkspaceTemplateSummary,
 };
 use crate::nspawn::NspawnDistro;
 use crate::util::{internal_error, not_found_or_internal, sanitize_skill_list};
@@ -48,6 +51,25 @@ fn extract_library_remote(headers: &HeaderMap) -> Option<String> {
         .map(|value| value.to_string())
 }
 
+/// Tenant resolved by the auth middleware (see `crate::tenant::TENANT_HEADER`).
+fn request_tenant(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(crate::tenant::TENANT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+}
+
+/// Library slot for a tenant, or the instance library.
+pub(crate) async fn library_for_tenant(
+    state: &super::routes::AppState,
+    tenant: Option<&str>,
+) -> SharedLibrary {
+    match tenant {
+        Some(tenant) => state.tenant_libraries.slot(tenant).await,
+        None => Arc::clone(&state.library),
+    }
+}
+
 fn extract_git_author(headers: &HeaderMap) -> Option<GitAuthor> {
     let name = headers
         .get(GIT_AUTHOR_NAME_HEADER)
@@ -72,9 +94,16 @@ fn is_default_host_workspace(workspace: &workspace::Workspace) -> bool {
     workspace.id == DEFAULT_WORKSPACE_ID && workspace.workspace_type == WorkspaceType::Host
 }
 
-async fn sync_all_workspaces(state: &super::routes::AppState, library: &LibraryStore) {
+async fn sync_all_workspaces(
+    state: &super::routes::AppState,
+    library: &LibraryStore,
+    tenant: Option<&str>,
+) {
     let workspaces = state.workspaces.list().await;
     for workspace in workspaces {
+        if workspace.tenant.as_deref() != tenant {
+            continue;
+        }
         if is_default_host_workspace(&workspace) || !workspace.skills.is_empty() {
             if let Err(e) = workspace::sync_workspace_skills(&workspace, library).await {
                 tracing::warn!(
@@ -89,11 +118,15 @@ async fn sync_all_workspaces(state: &super::routes::AppState, library: &LibraryS
 
 async fn sync_skill_to_workspaces(
     state: &super::routes::AppState,
+    tenant: Option<&str>,
     library: &LibraryStore,
     skill_name: &str,
 ) {
     let workspaces = state.workspaces.list().await;
     for workspace in workspaces {
+        if workspace.tenant.as_deref() != tenant {
+            continue;
+        }
         if is_default_host_workspace(&workspace) || workspace.skills.iter().any(|s| s == skill_name)
         {
             if let Err(e) = workspace::sync_workspace_skills(&workspace, library).await {
@@ -112,10 +145,27 @@ async fn ensure_library(
     state: &super::routes::AppState,
     headers: &HeaderMap,
 ) -> Result<Arc<LibraryStore>, (StatusCode, String)> {
-    // Check HTTP header override first, then fall back to settings store
+    // Tenants get their own checkout, of their own remote when configured.
+    let tenant = request_tenant(headers).and_then(|id| state.config.find_tenant(id));
+    let (slot, library_path) = match tenant {
+        Some(tenant) => (
+            state.tenant_libraries.slot(&tenant.id).await,
+            tenant.library_path(&state.config.working_dir),
+        ),
+        None => (
+            Arc::clone(&state.library),
+            state.config.library_path.clone(),
+        ),
+    };
+
+    // Check HTTP header override first, then the tenant's remote, then fall
+    // back to settings store
     let remote = match extract_library_remote(headers) {
         Some(r) => Some(r),
-        None => state.settings.get_library_remote().await,
+        None => match tenant.and_then(|t| t.library_remote.clone()) {
+            Some(r) => Some(r),
+            None => state.settings.get_library_remote().await,
+        },
     };
     let remote = remote.ok_or_else(|| {
         (
@@ -125,7 +175,7 @@ async fn ensure_library(
     })?;
 
     {
-        let library_guard = state.library.read().await;
+        let library_guard = slot.read().await;
         if let Some(library) = library_guard.as_ref() {
             if library.remote() == remote {
                 return Ok(Arc::clone(library));
@@ -133,19 +183,19 @@ async fn ensure_library(
         }
     }
 
-    let mut library_guard = state.library.write().await;
+    let mut library_guard = slot.write().await;
     if let Some(library) = library_guard.as_ref() {
         if library.remote() == remote {
             return Ok(Arc::clone(library));
         }
     }
 
-    match LibraryStore::new(state.config.library_path.clone(), &remote).await {
+    match LibraryStore::new(library_path, &remote).await {
         Ok(store) => {
             let store = Arc::new(store);
             *library_guard = Some(Arc::clone(&store));
             drop(library_guard);
-            sync_all_workspaces(state, store.as_ref()).await;
+            sync_all_workspaces(state, store.as_ref(), tenant.map(|t| t.id.as_str())).await;
             Ok(store)
         }
         Err(e) => Err((
@@ -171,15 +221,18 @@ pub fn routes() -> Router<Arc<super::routes::AppState>> {
         // Skills
         .route("/skill", get(list_skills))
         .route("/skill/import", post(import_skill))
+        .route("/skill/install", post(install_skill_package))
         .route("/skill/:name", get(get_skill))
         .route("/skill/:name", put(save_skill))
         .route("/skill/:name", delete(delete_skill))
+        .route("/skill/:name/invoke", post(invoke_skill))
         .route("/skill/:name/files/*path", get(get_skill_reference))
         .route("/skill/:name/files/*path", put(save_skill_reference))
         .route("/skill/:name/files/*path", delete(delete_skill_reference))
         // Legacy skills routes (dashboard still calls /skills)
         .route("/skills", get(list_skills))
         .route("/skills/import", post(import_skill))
+        .route("/skills/install", post(install_skill_package))
         .route("/skills/:name", get(get_skill))
         .route("/skills/:name", put(save_skill))
         .route("/skills/:name", delete(delete_skill))
@@ -206,6 +259,20 @@ pub fn routes() -> Router<Arc<super::routes::AppState>> {
         .route("/agent/:name", get(get_library_agent))
         .route("/agent/:name", put(save_library_agent))
         .route("/agent/:name", delete(delete_library_agent))
+        .route("/agent/:name/versions", get(list_library_agent_versions))
+        .route(
+            "/agent/:name/versions/:version",
+            get(get_library_agent_version),
+        )
+        .route("/agent/:name/rollback", post(rollback_library_agent))
+        // System prompt layers
+        .route("/prompt", get(list_prompt_layers))
+        .route("/prompt/base", get(get_base_prompt))
+        .route("/prompt/base", put(save_base_prompt))
+        .route("/prompt/base", delete(delete_base_prompt))
+        .route("/prompt/:scope/:name", get(get_prompt_layer))
+        .route("/prompt/:scope/:name", put(save_prompt_layer))
+        .route("/prompt/:scope/:name", delete(delete_prompt_layer))
         // Workspace Templates
         .route("/workspace-template", get(list_workspace_templates))
         .route("/workspace-template/:name", get(get_workspace_template))
@@ -219,6 +286,11 @@ pub fn routes() -> Router<Arc<super::routes::AppState>> {
         .route("/init-script/:name", get(get_init_script))
         .route("/init-script/:name", put(save_init_script))
         .route("/init-script/:name", delete(delete_init_script))
+        // Environment Profiles
+        .route("/env-profile", get(list_env_profiles))
+        .route("/env-profile/:name", get(get_env_profile))
+        .route("/env-profile/:name", put(save_env_profile))
+        .route("/env-profile/:name", delete(delete_env_profile))
         // Migration
         .route("/migrate", post(migrate_library))
         // Rename (works for all item types)
@@ -272,6 +344,26 @@ pub fn routes() -> Router<Arc<super::routes::AppState>> {
             "/config-profile/:name/ampcode/config",
             put(save_ampcode_config_for_profile),
         )
+        .route(
+            "/config-profile/:name/settings",
+            get(get_config_profile_settings),
+        )
+        .route(
+            "/config-profile/:name/settings",
+            put(save_config_profile_settings),
+        )
+        .route(
+            "/config-profile/:name/resolved",
+            get(resolve_config_profile_settings),
+        )
+        .route(
+            "/config-profile/:name/ollama/config",
+            get(get_ollama_config_for_profile),
+        )
+        .route(
+            "/config-profile/:name/ollama/config",
+            put(save_ollama_config_for_profile),
+        )
         // File-based config profile editing
         .route(
             "/config-profile/:name/files",
@@ -319,12 +411,45 @@ pub struct SaveContentRequest {
     content: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct PromptPreviewQuery {
+    /// Workspace name (or ID) whose prompt layer should be included
+    workspace: Option<String>,
+    /// Agent name whose persona layer should be included
+    agent: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct ImportSkillRequest {
     /// Skill name (required for file upload)
     name: String,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct InstallSkillPackageRequest {
+    /// https or SSH git URL, or https `.tar.gz`/`.tgz` tarball URL
+    source: String,
+    /// Subdirectory within the package that holds the skill
+    path: Option<String>,
+    /// Target name in the library (defaults to the manifest or folder name)
+    name: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InvokeSkillRequest {
+    /// Values for the inputs declared in the skill manifest
+    #[serde(default)]
+    inputs: HashMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InvokeSkillResponse {
+    /// Skill name
+    name: String,
+    /// Rendered skill prompt
+    prompt: String,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct RegistrySearchQuery {
     /// Search query
@@ -383,6 +508,9 @@ pub struct CreateConfigProfileRequest {
     /// Optional base profile to copy settings from
     #[serde(default)]
     pub base_profile: Option<String>,
+    /// Optional parent profile to inherit structured settings from
+    #[serde(default)]
+    pub extends: Option<String>,
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -394,7 +522,15 @@ pub struct CreateConfigProfileRequest {
 async fn sync_library_configs(
     state: &Arc<super::routes::AppState>,
     library: &LibraryStore,
+    tenant: Option<&str>,
 ) -> Result<(), (StatusCode, String)> {
+    // Tenant libraries only feed the tenant's workspaces, never host-wide
+    // configuration.
+    if tenant.is_some() {
+        sync_all_workspaces(state, library, tenant).await;
+        return Ok(());
+    }
+
     // Sync plugins to global OpenCode config
     let plugins = library.get_plugins().await.map_err(internal_error)?;
     crate::opencode_config::sync_global_plugins(&plugins)
@@ -412,7 +548,7 @@ async fn sync_library_configs(
     }
 
     // Sync skills and tools to workspaces
-    sync_all_workspaces(state, library).await;
+    sync_all_workspaces(state, library, None).await;
 
     Ok(())
 }
@@ -450,7 +586,7 @@ async fn sync_library(
     }
 
     // Sync all library configurations
-    sync_library_configs(&state, library.as_ref()).await?;
+    sync_library_configs(&state, library.as_ref(), request_tenant(&headers)).await?;
 
     Ok((StatusCode::OK, "Synced successfully".to_string()))
 }
@@ -467,7 +603,7 @@ async fn force_sync_library(
     library.force_sync().await.map_err(internal_error)?;
 
     // Sync all library configurations
-    sync_library_configs(&state, library.as_ref()).await?;
+    sync_library_configs(&state, library.as_ref(), request_tenant(&headers)).await?;
 
     Ok((
         StatusCode::OK,
@@ -598,7 +734,7 @@ async fn save_skill(
         .save_skill(&name, &req.content)
         .await
         .map_err(internal_error)?;
-    sync_skill_to_workspaces(&state, library.as_ref(), &name).await;
+    sync_skill_to_workspaces(&state, request_tenant(&headers), library.as_ref(), &name).await;
     Ok((StatusCode::OK, "Skill saved successfully".to_string()))
 }
 
@@ -610,7 +746,7 @@ async fn delete_skill(
 ) -> Result<(StatusCode, String), (StatusCode, String)> {
     let library = ensure_library(&state, &headers).await?;
     library.delete_skill(&name).await.map_err(internal_error)?;
-    sync_skill_to_workspaces(&state, library.as_ref(), &name).await;
+    sync_skill_to_workspaces(&state, request_tenant(&headers), library.as_ref(), &name).await;
     Ok((StatusCode::OK, "Skill deleted successfully".to_string()))
 }
 
@@ -640,7 +776,7 @@ async fn save_skill_reference(
         .save_skill_reference(&name, &path, &req.content)
         .await
         .map_err(internal_error)?;
-    sync_skill_to_workspaces(&state, library.as_ref(), &name).await;
+    sync_skill_to_workspaces(&state, request_tenant(&headers), library.as_ref(), &name).await;
     Ok((StatusCode::OK, "Reference saved successfully".to_string()))
 }
 
@@ -663,7 +799,7 @@ async fn delete_skill_reference(
                 (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
             }
         })?;
-    sync_skill_to_workspaces(&state, library.as_ref(), &name).await;
+    sync_skill_to_workspaces(&state, request_tenant(&headers), library.as_ref(), &name).await;
     Ok((StatusCode::OK, "Reference deleted successfully".to_string()))
 }
 
@@ -792,10 +928,77 @@ async fn import_skill(
         )
     })?;
 
-    sync_skill_to_workspaces(&state, library.as_ref(), &skill_name).await;
+    sync_skill_to_workspaces(
+        &state,
+        request_tenant(&headers),
+        library.as_ref(),
+        &skill_name,
+    )
+    .await;
+    Ok(Json(skill))
+}
+
+/// POST /api/library/skills/install - Install a packaged skill from a git URL or tarball.
+async fn install_skill_package(
+    State(state): State<Arc<super::routes::AppState>>,
+    headers: HeaderMap,
+    Json(req): Json<InstallSkillPackageRequest>,
+) -> Result<Json<Skill>, (StatusCode, String)> {
+    let library = ensure_library(&state, &headers).await?;
+    let source = req.source.trim();
+    if source.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Package source is required".to_string(),
+        ));
+    }
+
+    let skill = library
+        .install_skill_package(source, req.path.as_deref(), req.name.as_deref())
+        .await
+        .map_err(|e| {
+            let msg = e.to_string();
+            if msg.contains("already exists") {
+                (StatusCode::CONFLICT, msg)
+            } else {
+                (StatusCode::BAD_REQUEST, format!("{:#}", e))
+            }
+        })?;
+
+    sync_skill_to_workspaces(
+        &state,
+        request_tenant(&headers),
+        library.as_ref(),
+        &skill.name,
+    )
+    .await;
     Ok(Json(skill))
 }
 
+/// POST /api/library/skill/:name/invoke - Render a skill prompt with inputs.
+async fn invoke_skill(
+    State(state): State<Arc<super::routes::AppState>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    Json(req): Json<InvokeSkillRequest>,
+) -> Result<Json<InvokeSkillResponse>, (StatusCode, String)> {
+    let library = ensure_library(&state, &headers).await?;
+    let prompt = library
+        .invoke_skill(&name, &req.inputs)
+        .await
+        .map_err(|e| {
+            let msg = e.to_string();
+            if msg.contains("not found") {
+                (StatusCode::NOT_FOUND, msg)
+            } else if msg.contains("Missing required skill input") {
+                (StatusCode::BAD_REQUEST, msg)
+            } else {
+                (StatusCode::INTERNAL_SERVER_ERROR, msg)
+            }
+        })?;
+    Ok(Json(InvokeSkillResponse { name, prompt }))
+}
+
 /// Extract a ZIP file into the skill directory.
 async fn import_skill_from_zip(skill_dir: &std::path::Path, data: &[u8]) -> Result<(), String> {
     use std::io::{Cursor, Read};
@@ -1120,10 +1323,73 @@ async fn save_library_agent(
     library
         .save_library_agent(&name, &agent)
         .await
-        .map(|_| (StatusCode::OK, "Agent saved successfully".to_string()))
+        .map(|version| {
+            (
+                StatusCode::OK,
+                format!("Agent saved successfully (version {})", version),
+            )
+        })
+        .map_err(|e| {
+            let msg = e.to_string();
+            if msg.contains("already exists") {
+                (StatusCode::CONFLICT, msg)
+            } else if msg.contains("Invalid agent version") {
+                (StatusCode::BAD_REQUEST, msg)
+            } else {
+                internal_error(e)
+            }
+        })
+}
+
+/// GET /api/library/agent/:name/versions - List recorded versions of an agent.
+async fn list_library_agent_versions(
+    State(state): State<Arc<super::routes::AppState>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<LibraryAgentVersion>>, (StatusCode, String)> {
+    let library = ensure_library(&state, &headers).await?;
+    library
+        .list_library_agent_versions(&name)
+        .await
+        .map(Json)
         .map_err(internal_error)
 }
 
+/// GET /api/library/agent/:name/versions/:version - Get a specific agent version.
+async fn get_library_agent_version(
+    State(state): State<Arc<super::routes::AppState>>,
+    Path((name, version)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Json<LibraryAgent>, (StatusCode, String)> {
+    let library = ensure_library(&state, &headers).await?;
+    library
+        .get_library_agent_version(&name, &version)
+        .await
+        .map(Json)
+        .map_err(not_found_or_internal)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RollbackAgentRequest {
+    /// Version to restore as the current definition
+    version: String,
+}
+
+/// POST /api/library/agent/:name/rollback - Restore an earlier agent version.
+async fn rollback_library_agent(
+    State(state): State<Arc<super::routes::AppState>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    Json(req): Json<RollbackAgentRequest>,
+) -> Result<Json<LibraryAgent>, (StatusCode, String)> {
+    let library = ensure_library(&state, &headers).await?;
+    library
+        .rollback_library_agent(&name, &req.version)
+        .await
+        .map(Json)
+        .map_err(not_found_or_internal)
+}
+
 /// DELETE /api/library/agent/:name - Delete a library agent.
 async fn delete_library_agent(
     State(state): State<Arc<super::routes::AppState>>,
@@ -1138,6 +1404,152 @@ async fn delete_library_agent(
         .map_err(internal_error)
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// System Prompts
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn parse_prompt_scope(scope: &str) -> Result<PromptScope, (StatusCode, String)> {
+    match scope.parse::<PromptScope>() {
+        Ok(PromptScope::Base) | Err(_) => Err((
+            StatusCode::BAD_REQUEST,
+            format!("Invalid prompt scope: {}", scope),
+        )),
+        Ok(scope) => Ok(scope),
+    }
+}
+
+/// GET /api/library/prompt - List all system prompt layers.
+async fn list_prompt_layers(
+    State(state): State<Arc<super::routes::AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<PromptLayer>>, (StatusCode, String)> {
+    let library = ensure_library(&state, &headers).await?;
+    library
+        .list_prompt_layers()
+        .await
+        .map(Json)
+        .map_err(internal_error)
+}
+
+/// GET /api/library/prompt/base - Get the global base prompt.
+async fn get_base_prompt(
+    State(state): State<Arc<super::routes::AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<PromptLayer>, (StatusCode, String)> {
+    let library = ensure_library(&state, &headers).await?;
+    library
+        .get_prompt_layer(PromptScope::Base, None)
+        .await
+        .map_err(internal_error)?
+        .map(Json)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Base prompt not found".to_string()))
+}
+
+/// PUT /api/library/prompt/base - Save the global base prompt.
+async fn save_base_prompt(
+    State(state): State<Arc<super::routes::AppState>>,
+    headers: HeaderMap,
+    Json(req): Json<SaveContentRequest>,
+) -> Result<(StatusCode, String), (StatusCode, String)> {
+    let library = ensure_library(&state, &headers).await?;
+    library
+        .save_prompt_layer(PromptScope::Base, None, &req.content)
+        .await
+        .map(|_| (StatusCode::OK, "Prompt saved successfully".to_string()))
+        .map_err(internal_error)
+}
+
+/// DELETE /api/library/prompt/base - Delete the global base prompt.
+async fn delete_base_prompt(
+    State(state): State<Arc<super::routes::AppState>>,
+    headers: HeaderMap,
+) -> Result<(StatusCode, String), (StatusCode, String)> {
+    let library = ensure_library(&state, &headers).await?;
+    library
+        .delete_prompt_layer(PromptScope::Base, None)
+        .await
+        .map(|_| (StatusCode::OK, "Prompt deleted successfully".to_string()))
+        .map_err(internal_error)
+}
+
+/// GET /api/library/prompt/:scope/:name - Get a workspace or agent prompt layer.
+async fn get_prompt_layer(
+    State(state): State<Arc<super::routes::AppState>>,
+    Path((scope, name)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<Json<PromptLayer>, (StatusCode, String)> {
+    let scope = parse_prompt_scope(&scope)?;
+    let library = ensure_library(&state, &headers).await?;
+    library
+        .get_prompt_layer(scope, Some(&name))
+        .await
+        .map_err(internal_error)?
+        .map(Json)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Prompt not found: {}/{}", scope.as_str(), name),
+            )
+        })
+}
+
+/// PUT /api/library/prompt/:scope/:name - Save a workspace or agent prompt layer.
+async fn save_prompt_layer(
+    State(state): State<Arc<super::routes::AppState>>,
+    Path((scope, name)): Path<(String, String)>,
+    headers: HeaderMap,
+    Json(req): Json<SaveContentRequest>,
+) -> Result<(StatusCode, String), (StatusCode, String)> {
+    let scope = parse_prompt_scope(&scope)?;
+    let library = ensure_library(&state, &headers).await?;
+    library
+        .save_prompt_layer(scope, Some(&name), &req.content)
+        .await
+        .map(|_| (StatusCode::OK, "Prompt saved successfully".to_string()))
+        .map_err(internal_error)
+}
+
+/// DELETE /api/library/prompt/:scope/:name - Delete a workspace or agent prompt layer.
+async fn delete_prompt_layer(
+    State(state): State<Arc<super::routes::AppState>>,
+    Path((scope, name)): Path<(String, String)>,
+    headers: HeaderMap,
+) -> Result<(StatusCode, String), (StatusCode, String)> {
+    let scope = parse_prompt_scope(&scope)?;
+    let library = ensure_library(&state, &headers).await?;
+    library
+        .delete_prompt_layer(scope, Some(&name))
+        .await
+        .map(|_| (StatusCode::OK, "Prompt deleted successfully".to_string()))
+        .map_err(internal_error)
+}
+
+/// GET /api/prompts/preview?workspace=...&agent=... - Preview the merged system prompt.
+///
+/// The workspace may be given by name or ID; an ID is resolved to the workspace name
+/// since prompt layers are keyed by name in the library.
+pub async fn preview_system_prompt(
+    State(state): State<Arc<super::routes::AppState>>,
+    Query(query): Query<PromptPreviewQuery>,
+    headers: HeaderMap,
+) -> Result<Json<PromptPreview>, (StatusCode, String)> {
+    let library = ensure_library(&state, &headers).await?;
+
+    let workspace_name = match query.workspace.as_deref() {
+        Some(raw) => match raw.parse::<uuid::Uuid>() {
+            Ok(id) => state.workspaces.get(id).await.map(|ws| ws.name),
+            Err(_) => Some(raw.to_string()),
+        },
+        None => None,
+    };
+
+    library
+        .compose_system_prompt(workspace_name.as_deref(), query.agent.as_deref())
+        .await
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string()))
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Workspace Templates
 // ─────────────────────────────────────────────────────────────────────────────
@@ -1302,6 +1714,76 @@ async fn delete_init_script(
         .map_err(internal_error)
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// Environment Profiles
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// GET /api/library/env-profile - List all environment profiles.
+async fn list_env_profiles(
+    State(state): State<Arc<super::routes::AppState>>,
+    headers: HeaderMap,
+) -> Result<Json<Vec<EnvProfileSummary>>, (StatusCode, String)> {
+    let library = ensure_library(&state, &headers).await?;
+    library
+        .list_env_profiles()
+        .await
+        .map(Json)
+        .map_err(internal_error)
+}
+
+/// GET /api/library/env-profile/:name - Get an environment profile by name.
+async fn get_env_profile(
+    State(state): State<Arc<super::routes::AppState>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<EnvProfile>, (StatusCode, String)> {
+    let library = ensure_library(&state, &headers).await?;
+    library
+        .get_env_profile(&name)
+        .await
+        .map(Json)
+        .map_err(not_found_or_internal)
+}
+
+/// PUT /api/library/env-profile/:name - Save an environment profile.
+async fn save_env_profile(
+    State(state): State<Arc<super::routes::AppState>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    Json(profile): Json<EnvProfile>,
+) -> Result<(StatusCode, String), (StatusCode, String)> {
+    let library = ensure_library(&state, &headers).await?;
+    library
+        .save_env_profile(&name, &profile)
+        .await
+        .map(|_| {
+            (
+                StatusCode::OK,
+                "Environment profile saved successfully".to_string(),
+            )
+        })
+        .map_err(internal_error)
+}
+
+/// DELETE /api/library/env-profile/:name - Delete an environment profile.
+async fn delete_env_profile(
+    State(state): State<Arc<super::routes::AppState>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> Result<(StatusCode, String), (StatusCode, String)> {
+    let library = ensure_library(&state, &headers).await?;
+    library
+        .delete_env_profile(&name)
+        .await
+        .map(|_| {
+            (
+                StatusCode::OK,
+                "Environment profile deleted successfully".to_string(),
+            )
+        })
+        .map_err(internal_error)
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Migration
 // ─────────────────────────────────────────────────────────────────────────────
@@ -1689,7 +2171,13 @@ async fn rename_item(
                 // Update workspace skill lists
                 update_workspace_skill_references(&state, &name, &req.new_name).await;
                 // Sync skills to workspaces
-                sync_skill_to_workspaces(&state, library.as_ref(), &req.new_name).await;
+                sync_skill_to_workspaces(
+                    &state,
+                    request_tenant(&headers),
+                    library.as_ref(),
+                    &req.new_name,
+                )
+                .await;
             }
             ItemType::WorkspaceTemplate => {
                 // Update workspace template references
@@ -1828,17 +2316,35 @@ async fn create_config_profile(
     Json(req): Json<CreateConfigProfileRequest>,
 ) -> Result<Json<ConfigProfile>, (StatusCode, String)> {
     let library = ensure_library(&state, &headers).await?;
-    library
+    if let Some(parent) = req.extends.as_deref() {
+        if !library.config_profile_exists(parent) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!("Parent profile '{}' not found", parent),
+            ));
+        }
+    }
+    let profile = library
         .create_config_profile(&req.name, req.base_profile.as_deref())
         .await
-        .map(Json)
         .map_err(|e| {
             if e.to_string().contains("already exists") {
                 (StatusCode::CONFLICT, e.to_string())
             } else {
                 (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
             }
-        })
+        })?;
+    if let Some(parent) = req.extends {
+        let settings = ConfigProfileSettings {
+            extends: Some(parent),
+            ..Default::default()
+        };
+        library
+            .save_config_profile_settings(&req.name, &settings)
+            .await
+            .map_err(internal_error)?;
+    }
+    Ok(Json(profile))
 }
 
 /// GET /api/library/config-profile/:name - Get a config profile by name.
@@ -1892,7 +2398,9 @@ async fn delete_config_profile(
             )
         })
         .map_err(|e| {
-            if e.to_string().contains("Cannot delete") {
+            if e.to_string().contains("extended by") {
+                (StatusCode::CONFLICT, e.to_string())
+            } else if e.to_string().contains("Cannot delete") {
                 (StatusCode::BAD_REQUEST, e.to_string())
             } else {
                 (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
@@ -2044,6 +2552,101 @@ async fn save_ampcode_config_for_profile(
         .map_err(internal_error)
 }
 
+/// GET /api/library/config-profile/:name/ollama/config - Get Ollama config for a profile.
+async fn get_ollama_config_for_profile(
+    State(state): State<Arc<super::routes::AppState>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<OllamaConfig>, (StatusCode, String)> {
+    let library = ensure_library(&state, &headers).await?;
+    library
+        .get_ollama_config_for_profile(&name)
+        .await
+        .map(Json)
+        .map_err(internal_error)
+}
+
+/// PUT /api/library/config-profile/:name/ollama/config - Save Ollama config for a profile.
+async fn save_ollama_config_for_profile(
+    State(state): State<Arc<super::routes::AppState>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    Json(config): Json<OllamaConfig>,
+) -> Result<(StatusCode, String), (StatusCode, String)> {
+    let library = ensure_library(&state, &headers).await?;
+    library
+        .save_ollama_config_for_profile(&name, &config)
+        .await
+        .map(|_| {
+            (
+                StatusCode::OK,
+                "Ollama config saved successfully".to_string(),
+            )
+        })
+        .map_err(internal_error)
+}
+
+/// GET /api/library/config-profile/:name/settings - Get a profile's own settings.
+async fn get_config_profile_settings(
+    State(state): State<Arc<super::routes::AppState>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<ConfigProfileSettings>, (StatusCode, String)> {
+    let library = ensure_library(&state, &headers).await?;
+    library
+        .get_config_profile_settings(&name)
+        .await
+        .map(Json)
+        .map_err(not_found_or_internal)
+}
+
+/// PUT /api/library/config-profile/:name/settings - Save a profile's settings.
+async fn save_config_profile_settings(
+    State(state): State<Arc<super::routes::AppState>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+    Json(settings): Json<ConfigProfileSettings>,
+) -> Result<(StatusCode, String), (StatusCode, String)> {
+    let library = ensure_library(&state, &headers).await?;
+    library
+        .save_config_profile_settings(&name, &settings)
+        .await
+        .map(|_| {
+            (
+                StatusCode::OK,
+                "Profile settings saved successfully".to_string(),
+            )
+        })
+        .map_err(profile_settings_error)
+}
+
+/// GET /api/library/config-profile/:name/resolved - Get a profile's settings
+/// after applying its inheritance chain.
+async fn resolve_config_profile_settings(
+    State(state): State<Arc<super::routes::AppState>>,
+    Path(name): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<ResolvedConfigProfileSettings>, (StatusCode, String)> {
+    let library = ensure_library(&state, &headers).await?;
+    library
+        .resolve_config_profile_settings(&name)
+        .await
+        .map(Json)
+        .map_err(profile_settings_error)
+}
+
+/// A missing profile is 404; a broken `extends` chain is the caller's error.
+fn profile_settings_error(e: anyhow::Error) -> (StatusCode, String) {
+    let msg = e.to_string();
+    if msg.starts_with("Config profile") && msg.contains("not found") {
+        (StatusCode::NOT_FOUND, msg)
+    } else if msg.contains("Parent profile") || msg.contains("inheritance") {
+        (StatusCode::BAD_REQUEST, msg)
+    } else {
+        (StatusCode::INTERNAL_SERVER_ERROR, msg)
+    }
+}
+
 /// GET /api/library/config-profile/:name/files - List all files in a config profile.
 async fn list_config_profile_files(
     State(state): State<Arc<super::routes::AppState>>,
@@ -2307,7 +2910,7 @@ async fn install_from_registry(
         .map_err(internal_error)?;
 
     // Sync to workspaces
-    sync_skill_to_workspaces(&state, &library, &skill_name).await;
+    sync_skill_to_workspaces(&state, request_tenant(&headers), &library, &skill_name).await;
 
     Ok(Json(skill))
 }
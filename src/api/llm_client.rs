@@ -0,0 +1,359 @@
+//! Minimal client for the local OpenAI-compatible proxy.
+//!
+//! Server-side helpers (best-of-N sampling, completion verification) call
+//! models through `/v1/chat/completions` on this server, so they get the same
+//! model chains, failover and provider credentials as the agents do.
+//!
+//! Requests made for a mission can be recorded to a cassette and served back
+//! later, for reproducible bug reports and offline regression tests:
+//!
+//! - `SANDBOXED_SH_LLM_CASSETTE=record` appends each request/response pair to
+//!   `<dir>/<mission_id>.jsonl`
+//! - `SANDBOXED_SH_LLM_CASSETTE=replay` answers from those files and fails
+//!   requests that were never recorded, without contacting the proxy
+//! - `SANDBOXED_SH_LLM_CASSETTE_DIR` overrides the directory (default
+//!   `$WORKING_DIR/.sandboxed-sh/cassettes`)
+//!
+//! Replay matches requests by a hash of their body. Identical requests are
+//! answered in the order they were recorded.
+
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use crate::cost::TokenUsage;
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(180);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    Record,
+    Replay,
+}
+
+impl CassetteMode {
+    fn from_env() -> Option<Self> {
+        match std::env::var("SANDBOXED_SH_LLM_CASSETTE")
+            .ok()?
+            .trim()
+            .to_ascii_lowercase()
+            .as_str()
+        {
+            "record" => Some(Self::Record),
+            "replay" => Some(Self::Replay),
+            _ => None,
+        }
+    }
+}
+
+/// One recorded request/response pair (a line of a cassette file).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CassetteEntry {
+    /// Hash of the request body, used to match requests on replay
+    pub key: String,
+    pub request: Value,
+    pub status: u16,
+    pub response: Value,
+    pub recorded_at: String,
+}
+
+/// Recorded LLM interactions of one mission.
+pub struct Cassette {
+    mode: CassetteMode,
+    path: PathBuf,
+    /// Recorded entries (replay only)
+    entries: Vec<CassetteEntry>,
+    /// Entries already served (replay)
+    used: Mutex<HashSet<usize>>,
+    /// Serializes appends from concurrent requests (record)
+    write_lock: Mutex<()>,
+}
+
+fn cassette_dir() -> PathBuf {
+    if let Ok(dir) = std::env::var("SANDBOXED_SH_LLM_CASSETTE_DIR") {
+        if !dir.trim().is_empty() {
+            return PathBuf::from(dir);
+        }
+    }
+    let working_dir = std::env::var("WORKING_DIR").unwrap_or_else(|_| ".".to_string());
+    Path::new(&working_dir)
+        .join(".sandboxed-sh")
+        .join("cassettes")
+}
+
+fn request_key(request: &Value) -> String {
+    hex::encode(Sha256::digest(request.to_string().as_bytes()))
+}
+
+impl Cassette {
+    /// Open the cassette of `mission_id` in `dir`. A replay cassette that
+    /// can't be read has no entries, so every request fails.
+    pub fn open(mode: CassetteMode, dir: &Path, mission_id: Uuid) -> Self {
+        let path = dir.join(format!("{}.jsonl", mission_id));
+        let entries = match mode {
+            CassetteMode::Record => Vec::new(),
+            CassetteMode::Replay => match std::fs::read_to_string(&path) {
+                Ok(content) => content
+                    .lines()
+                    .filter(|line| !line.trim().is_empty())
+                    .filter_map(|line| match serde_json::from_str(line) {
+                        Ok(entry) => Some(entry),
+                        Err(e) => {
+                            tracing::warn!(
+                                "Skipping bad cassette line in {}: {}",
+                                path.display(),
+                                e
+                            );
+                            None
+                        }
+                    })
+                    .collect(),
+                Err(e) => {
+                    tracing::warn!("Failed to read cassette {}: {}", path.display(), e);
+                    Vec::new()
+                }
+            },
+        };
+        Self {
+            mode,
+            path,
+            entries,
+            used: Mutex::new(HashSet::new()),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The next unserved response recorded for `request`.
+    pub async fn replay(&self, request: &Value) -> Option<(u16, Value)> {
+        let key = request_key(request);
+        let mut used = self.used.lock().await;
+        let (index, entry) = self
+            .entries
+            .iter()
+            .enumerate()
+            .find(|(index, entry)| entry.key == key && !used.contains(index))?;
+        used.insert(index);
+        Some((entry.status, entry.response.clone()))
+    }
+
+    /// Append a request/response pair to the cassette file.
+    pub async fn record(
+        &self,
+        request: &Value,
+        status: u16,
+        response: &Value,
+    ) -> Result<(), String> {
+        let entry = CassetteEntry {
+            key: request_key(request),
+            request: request.clone(),
+            status,
+            response: response.clone(),
+            recorded_at: chrono::Utc::now().to_rfc3339(),
+        };
+        let mut line = serde_json::to_string(&entry).map_err(|e| e.to_string())?;
+        line.push('\n');
+
+        let _guard = self.write_lock.lock().await;
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .map_err(|e| e.to_string())?;
+        }
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(|e| e.to_string())?;
+        file.write_all(line.as_bytes())
+            .await
+            .map_err(|e| e.to_string())?;
+        // tokio writes in the background; make sure the line has landed
+        file.flush().await.map_err(|e| e.to_string())
+    }
+}
+
+/// A non-streaming chat completion.
+pub struct Completion {
+    pub content: String,
+    /// Model reported by the upstream provider
+    pub model: String,
+    pub usage: TokenUsage,
+    pub cost_cents: u64,
+}
+
+/// Client for the local OpenAI-compatible proxy.
+pub struct ProxyClient {
+    http: reqwest::Client,
+    url: String,
+    secret: String,
+    cassette: Option<Arc<Cassette>>,
+}
+
+impl ProxyClient {
+    pub fn from_env() -> Self {
+        let port = std::env::var("PORT").unwrap_or_else(|_| "3000".to_string());
+        Self {
+            http: reqwest::Client::builder()
+                .timeout(REQUEST_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+            url: format!("http://127.0.0.1:{}/v1/chat/completions", port),
+            secret: std::env::var("SANDBOXED_PROXY_SECRET").unwrap_or_default(),
+            cassette: None,
+        }
+    }
+
+    /// Record or replay this client's requests in the mission's cassette when
+    /// `SANDBOXED_SH_LLM_CASSETTE` is set.
+    pub fn for_mission(mut self, mission_id: Uuid) -> Self {
+        self.cassette = CassetteMode::from_env()
+            .map(|mode| Arc::new(Cassette::open(mode, &cassette_dir(), mission_id)));
+        self
+    }
+
+    async fn send(&self, body: &Value) -> Result<(u16, Value), String> {
+        if let Some(cassette) = self.cassette.as_ref() {
+            if cassette.mode == CassetteMode::Replay {
+                return cassette.replay(body).await.ok_or_else(|| {
+                    format!(
+                        "No recorded response for this request in cassette {}",
+                        cassette.path().display()
+                    )
+                });
+            }
+        }
+
+        let response = self
+            .http
+            .post(&self.url)
+            .bearer_auth(&self.secret)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?;
+        let status = response.status().as_u16();
+        let value: Value = response
+            .json()
+            .await
+            .map_err(|e| format!("Invalid response: {}", e))?;
+
+        if let Some(cassette) = self.cassette.as_ref() {
+            if let Err(e) = cassette.record(body, status, &value).await {
+                tracing::warn!(
+                    "Failed to record cassette {}: {}",
+                    cassette.path().display(),
+                    e
+                );
+            }
+        }
+        Ok((status, value))
+    }
+
+    /// Send a system + user prompt and return the reply.
+    pub async fn complete(
+        &self,
+        model: &str,
+        temperature: f64,
+        max_tokens: u64,
+        system: &str,
+        prompt: &str,
+    ) -> Result<Completion, String> {
+        let body = json!({
+            "model": model,
+            "temperature": temperature,
+            "max_tokens": max_tokens,
+            "stream": false,
+            "messages": [
+                { "role": "system", "content": system },
+                { "role": "user", "content": prompt },
+            ],
+        });
+        let (status, value) = self.send(&body).await?;
+        let status =
+            reqwest::StatusCode::from_u16(status).unwrap_or(reqwest::StatusCode::BAD_GATEWAY);
+        if !status.is_success() {
+            let message = value
+                .pointer("/error/message")
+                .and_then(|v| v.as_str())
+                .unwrap_or("unknown error");
+            return Err(format!("{}: {}", status, message));
+        }
+
+        let content = value
+            .pointer("/choices/0/message/content")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .trim()
+            .to_string();
+        let token_count = |key: &str| {
+            value
+                .pointer(&format!("/usage/{}", key))
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0)
+        };
+        let usage = TokenUsage {
+            input_tokens: token_count("prompt_tokens"),
+            output_tokens: token_count("completion_tokens"),
+            cache_creation_input_tokens: None,
+            cache_read_input_tokens: None,
+        };
+        let model = value
+            .get("model")
+            .and_then(|v| v.as_str())
+            .unwrap_or(model)
+            .to_string();
+        let (cost_cents, _) =
+            crate::cost::resolve_cost_cents_and_source(None, Some(&model), &usage);
+        Ok(Completion {
+            content,
+            model,
+            usage,
+            cost_cents,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn recorded_requests_replay_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let mission_id = Uuid::new_v4();
+        let request = json!({"model": "m", "messages": [{"role": "user", "content": "hi"}]});
+        let other = json!({"model": "m", "messages": [{"role": "user", "content": "bye"}]});
+
+        let recorder = Cassette::open(CassetteMode::Record, dir.path(), mission_id);
+        recorder
+            .record(&request, 200, &json!({"n": 1}))
+            .await
+            .unwrap();
+        recorder
+            .record(&request, 429, &json!({"n": 2}))
+            .await
+            .unwrap();
+
+        let player = Cassette::open(CassetteMode::Replay, dir.path(), mission_id);
+        assert_eq!(player.replay(&request).await, Some((200, json!({"n": 1}))));
+        assert_eq!(player.replay(&request).await, Some((429, json!({"n": 2}))));
+        assert_eq!(player.replay(&request).await, None);
+        assert_eq!(player.replay(&other).await, None);
+
+        let empty = Cassette::open(CassetteMode::Replay, dir.path(), Uuid::new_v4());
+        assert_eq!(empty.replay(&request).await, None);
+    }
+}
@@ -162,6 +162,8 @@ pub enum ToolSource {
     Builtin,
     /// Tool from an MCP server
     Mcp { id: Uuid, name: String },
+    /// Remote HTTP tool proxied by the server
+    Remote { url: String },
 }
 
 /// List all available tools (built-in + MCP).
@@ -206,6 +208,18 @@ pub async fn list_tools(State(state): State<Arc<AppState>>) -> Json<Vec<ToolInfo
         }
     }
 
+    // Add remote (HTTP) tools
+    for t in state.remote_tools.list().await {
+        if seen.insert(t.name.clone()) {
+            tools.push(ToolInfo {
+                name: t.name,
+                description: t.description,
+                source: ToolSource::Remote { url: t.url },
+                enabled: t.enabled,
+            });
+        }
+    }
+
     // Sort by name for stable ordering
     tools.sort_by(|a, b| a.name.cmp(&b.name));
 
@@ -0,0 +1,312 @@
+//! Thumbs up/down feedback on assistant messages.
+//!
+//! Users rate an assistant message with
+//! `POST /api/missions/:id/messages/:msg_id/feedback`, where `msg_id` is the
+//! `id` of the `assistant_message` event. The rating is stored against that
+//! message together with the backend and model that produced it, so quality
+//! can be compared per backend/model (`GET /api/analytics/feedback`) and rated
+//! exchanges exported as an evaluation dataset
+//! (`GET /api/analytics/feedback/dataset`, one JSON object per line).
+
+use std::collections::hash_map::Entry;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::mission_store::{now_string, FeedbackRating, MessageFeedback, StoredEvent};
+use super::routes::AppState;
+use crate::util::internal_error;
+
+/// Longest comment accepted, in characters.
+const MAX_COMMENT_CHARS: usize = 4000;
+
+#[derive(Debug, Deserialize)]
+pub struct FeedbackRequest {
+    pub rating: FeedbackRating,
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+/// POST /api/missions/:id/messages/:msg_id/feedback
+pub async fn post_message_feedback(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path((mission_id, message_id)): Path<(Uuid, Uuid)>,
+    Json(req): Json<FeedbackRequest>,
+) -> Result<Json<MessageFeedback>, (StatusCode, String)> {
+    let comment = req
+        .comment
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty());
+    if comment
+        .as_ref()
+        .is_some_and(|c| c.chars().count() > MAX_COMMENT_CHARS)
+    {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("comment is longer than {} characters", MAX_COMMENT_CHARS),
+        ));
+    }
+
+    let store = state.control.get_or_spawn(&user).await.mission_store;
+    let mission = store
+        .get_mission(mission_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Mission {} not found", mission_id),
+            )
+        })?;
+    let messages = store
+        .get_events(mission_id, Some(&["assistant_message"]), None, None)
+        .await
+        .map_err(internal_error)?;
+    let message = find_message(&messages, message_id).ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            format!(
+                "Assistant message {} not found in mission {}",
+                message_id, mission_id
+            ),
+        )
+    })?;
+
+    let feedback = MessageFeedback {
+        mission_id,
+        message_id,
+        rating: req.rating,
+        comment,
+        backend: mission.backend,
+        model: message
+            .metadata
+            .get("model")
+            .and_then(|m| m.as_str())
+            .map(str::to_string),
+        created_at: now_string(),
+    };
+    store
+        .set_message_feedback(feedback.clone())
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(feedback))
+}
+
+fn find_message(events: &[StoredEvent], message_id: Uuid) -> Option<&StoredEvent> {
+    let id = message_id.to_string();
+    events
+        .iter()
+        .find(|e| e.event_type == "assistant_message" && e.event_id.as_deref() == Some(&id))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct FeedbackQuery {
+    /// RFC3339 lower bound on when the feedback was given
+    pub since: Option<String>,
+}
+
+/// Ratings for one backend/model pair.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct FeedbackTotals {
+    pub backend: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub up: usize,
+    pub down: usize,
+    /// Share of ratings that are thumbs up (0.0 - 1.0)
+    pub approval_rate: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FeedbackAnalytics {
+    pub totals: Vec<FeedbackTotals>,
+    pub feedback: Vec<MessageFeedback>,
+}
+
+/// Tally ratings per backend/model, ordered by backend then model.
+pub fn totals(feedback: &[MessageFeedback]) -> Vec<FeedbackTotals> {
+    let mut counts: BTreeMap<(&str, Option<&str>), (usize, usize)> = BTreeMap::new();
+    for f in feedback {
+        let entry = counts
+            .entry((f.backend.as_str(), f.model.as_deref()))
+            .or_default();
+        match f.rating {
+            FeedbackRating::Up => entry.0 += 1,
+            FeedbackRating::Down => entry.1 += 1,
+        }
+    }
+    counts
+        .into_iter()
+        .map(|((backend, model), (up, down))| FeedbackTotals {
+            backend: backend.to_string(),
+            model: model.map(str::to_string),
+            up,
+            down,
+            approval_rate: up as f64 / (up + down) as f64,
+        })
+        .collect()
+}
+
+/// GET /api/analytics/feedback
+pub async fn get_feedback_analytics(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Query(params): Query<FeedbackQuery>,
+) -> Result<Json<FeedbackAnalytics>, (StatusCode, String)> {
+    let store = state.control.get_or_spawn(&user).await.mission_store;
+    let feedback = store
+        .list_message_feedback(None, params.since.as_deref())
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(FeedbackAnalytics {
+        totals: totals(&feedback),
+        feedback,
+    }))
+}
+
+/// A rated exchange for evaluation datasets.
+#[derive(Debug, Serialize)]
+pub struct FeedbackSample {
+    #[serde(flatten)]
+    pub feedback: MessageFeedback,
+    /// The user message the rated response answered
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+    pub response: String,
+}
+
+/// Pair a rated message with the user message preceding it.
+fn sample(events: &[StoredEvent], feedback: MessageFeedback) -> Option<FeedbackSample> {
+    let id = feedback.message_id.to_string();
+    let index = events
+        .iter()
+        .position(|e| e.event_type == "assistant_message" && e.event_id.as_deref() == Some(&id))?;
+    let prompt = events[..index]
+        .iter()
+        .rev()
+        .find(|e| e.event_type == "user_message")
+        .map(|e| e.content.clone());
+    Some(FeedbackSample {
+        response: events[index].content.clone(),
+        prompt,
+        feedback,
+    })
+}
+
+/// GET /api/analytics/feedback/dataset
+pub async fn get_feedback_dataset(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Query(params): Query<FeedbackQuery>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let store = state.control.get_or_spawn(&user).await.mission_store;
+    let feedback = store
+        .list_message_feedback(None, params.since.as_deref())
+        .await
+        .map_err(internal_error)?;
+
+    let mut conversations: HashMap<Uuid, Vec<StoredEvent>> = HashMap::new();
+    let mut body = String::new();
+    for f in feedback {
+        if let Entry::Vacant(slot) = conversations.entry(f.mission_id) {
+            let events = store
+                .get_events(
+                    f.mission_id,
+                    Some(&["user_message", "assistant_message"]),
+                    None,
+                    None,
+                )
+                .await
+                .map_err(internal_error)?;
+            slot.insert(events);
+        }
+        let Some(sample) = sample(&conversations[&f.mission_id], f) else {
+            continue;
+        };
+        body.push_str(&serde_json::to_string(&sample).map_err(internal_error)?);
+        body.push('\n');
+    }
+    Ok(([(header::CONTENT_TYPE, "application/x-ndjson")], body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rating(backend: &str, model: Option<&str>, rating: FeedbackRating) -> MessageFeedback {
+        MessageFeedback {
+            mission_id: Uuid::new_v4(),
+            message_id: Uuid::new_v4(),
+            rating,
+            comment: None,
+            backend: backend.to_string(),
+            model: model.map(str::to_string),
+            created_at: now_string(),
+        }
+    }
+
+    fn event(event_type: &str, event_id: Option<Uuid>, content: &str) -> StoredEvent {
+        StoredEvent {
+            id: 0,
+            mission_id: Uuid::nil(),
+            sequence: 0,
+            event_type: event_type.to_string(),
+            timestamp: now_string(),
+            event_id: event_id.map(|id| id.to_string()),
+            tool_call_id: None,
+            tool_name: None,
+            content: content.to_string(),
+            metadata: serde_json::json!({}),
+            stream_seq: None,
+            schema_version: crate::api::event_schema::CURRENT_VERSION,
+        }
+    }
+
+    #[test]
+    fn totals_group_by_backend_and_model() {
+        let feedback = vec![
+            rating("opencode", Some("gpt-5"), FeedbackRating::Down),
+            rating("claudecode", Some("claude-sonnet-4"), FeedbackRating::Up),
+            rating("claudecode", Some("claude-sonnet-4"), FeedbackRating::Up),
+            rating("claudecode", Some("claude-sonnet-4"), FeedbackRating::Down),
+            rating("opencode", Some("gpt-5"), FeedbackRating::Up),
+        ];
+        let totals = totals(&feedback);
+        assert_eq!(totals.len(), 2);
+        assert_eq!(totals[0].backend, "claudecode");
+        assert_eq!((totals[0].up, totals[0].down), (2, 1));
+        assert!((totals[0].approval_rate - 2.0 / 3.0).abs() < f64::EPSILON);
+        assert_eq!(totals[1].model.as_deref(), Some("gpt-5"));
+        assert_eq!(totals[1].approval_rate, 0.5);
+    }
+
+    #[test]
+    fn sample_pairs_response_with_preceding_prompt() {
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+        let events = vec![
+            event("user_message", Some(Uuid::new_v4()), "fix the build"),
+            event("assistant_message", Some(first), "fixed"),
+            event("user_message", Some(Uuid::new_v4()), "now add tests"),
+            event("assistant_message", Some(second), "added"),
+        ];
+        let mut feedback = rating("claudecode", None, FeedbackRating::Down);
+        feedback.message_id = second;
+        let sample = sample(&events, feedback).expect("sample");
+        assert_eq!(sample.prompt.as_deref(), Some("now add tests"));
+        assert_eq!(sample.response, "added");
+
+        assert!(find_message(&events, first).is_some());
+        assert!(find_message(&events, Uuid::new_v4()).is_none());
+    }
+}
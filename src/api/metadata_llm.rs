@@ -0,0 +1,306 @@
+//! LLM-generated mission titles and short descriptions.
+//!
+//! When enabled, metadata refreshes ask a small model to summarize the
+//! conversation instead of relying only on the text heuristics in
+//! `control.rs`. Results are cached by a hash of the history (and locale), so
+//! refreshes without new messages never call the model again, and calls are
+//! rate limited per mission. Whenever the generator is disabled, rate limited
+//! or fails, the heuristics are used as before.
+//!
+//! Configuration:
+//!
+//! - `SANDBOXED_SH_METADATA_LLM` - enable LLM metadata generation
+//! - `SANDBOXED_SH_METADATA_MODEL` - model (default `builtin/cheap`)
+//! - `SANDBOXED_SH_METADATA_MIN_INTERVAL_SECS` - minimum time between calls
+//!   for the same mission (default 60)
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use super::llm_client::ProxyClient;
+
+const DEFAULT_MODEL: &str = "builtin/cheap";
+const DEFAULT_MIN_INTERVAL_SECS: u64 = 60;
+const MAX_TOKENS: u64 = 200;
+/// Characters of conversation shown to the model (the most recent ones).
+const MAX_PROMPT_CHARS: usize = 6_000;
+/// Characters kept per message, so one long reply can't crowd out the rest.
+const MAX_MESSAGE_CHARS: usize = 1_500;
+const MAX_TITLE_CHARS: usize = 100;
+const MAX_SHORT_DESCRIPTION_CHARS: usize = 160;
+/// Cached results kept before the cache is cleared.
+const MAX_CACHE_ENTRIES: usize = 512;
+
+const SYSTEM_PROMPT: &str = "You name tasks given to an autonomous coding agent. From the \
+conversation, write a specific title (at most 8 words, no trailing punctuation) and a one-sentence \
+short description of what the mission is about and where it stands. Reply with JSON only: \
+{\"title\": \"...\", \"short_description\": \"...\"}";
+
+/// Generator settings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataLlmConfig {
+    pub model: String,
+    pub min_interval: Duration,
+}
+
+impl MetadataLlmConfig {
+    /// Read the configuration; `None` when LLM metadata is disabled.
+    pub fn from_env() -> Option<Self> {
+        let enabled = std::env::var("SANDBOXED_SH_METADATA_LLM")
+            .map(|v| {
+                matches!(
+                    v.trim().to_lowercase().as_str(),
+                    "1" | "true" | "yes" | "on"
+                )
+            })
+            .unwrap_or(false);
+        if !enabled {
+            return None;
+        }
+        let var = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+        Some(Self {
+            model: var("SANDBOXED_SH_METADATA_MODEL").unwrap_or_else(|| DEFAULT_MODEL.to_string()),
+            min_interval: Duration::from_secs(
+                var("SANDBOXED_SH_METADATA_MIN_INTERVAL_SECS")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(DEFAULT_MIN_INTERVAL_SECS),
+            ),
+        })
+    }
+}
+
+/// Metadata suggested by the model. Either field may be missing when the
+/// reply didn't contain a usable value.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GeneratedMetadata {
+    pub title: Option<String>,
+    pub short_description: Option<String>,
+}
+
+static CACHE: LazyLock<Mutex<HashMap<String, GeneratedMetadata>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Time of the last model call per mission.
+static LAST_CALL: LazyLock<Mutex<HashMap<Uuid, Instant>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+fn history_key(history: &[(String, String)], locale: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(locale.as_bytes());
+    for (role, content) in history {
+        hasher.update([0]);
+        hasher.update(role.as_bytes());
+        hasher.update([0]);
+        hasher.update(content.as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+pub(super) fn truncate_chars(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(max.saturating_sub(3)).collect();
+    format!("{}...", kept.trim_end())
+}
+
+/// The conversation as a transcript, keeping the first user message and as
+/// many of the latest messages as fit.
+pub(super) fn transcript(history: &[(String, String)]) -> String {
+    let messages: Vec<String> = history
+        .iter()
+        .filter(|(role, content)| {
+            matches!(role.as_str(), "user" | "assistant") && !content.trim().is_empty()
+        })
+        .map(|(role, content)| {
+            format!(
+                "{}: {}",
+                role,
+                truncate_chars(content.trim(), MAX_MESSAGE_CHARS)
+            )
+        })
+        .collect();
+
+    let mut budget = MAX_PROMPT_CHARS;
+    let mut included: Vec<&str> = Vec::new();
+    if let Some(first) = messages.first() {
+        budget = budget.saturating_sub(first.len());
+        for message in messages.iter().skip(1).rev() {
+            if message.len() > budget {
+                break;
+            }
+            budget -= message.len();
+            included.push(message);
+        }
+        included.push(first);
+    }
+    included.reverse();
+    included.join("\n\n")
+}
+
+fn build_prompt(history: &[(String, String)], locale: &str) -> String {
+    format!(
+        "Write the title and short description in the language with code \"{}\".\n\n\
+         Conversation:\n{}",
+        locale,
+        transcript(history)
+    )
+}
+
+/// A trimmed, unquoted and length-limited reply field (`None` when empty).
+fn clean_field(value: Option<&Value>, max: usize, strip_trailing: &[char]) -> Option<String> {
+    let text = value?
+        .as_str()?
+        .trim()
+        .trim_matches(|c| c == '"' || c == '\'')
+        .trim_end_matches(strip_trailing)
+        .trim();
+    (!text.is_empty()).then(|| truncate_chars(text, max))
+}
+
+fn parse_reply(reply: &str) -> Option<GeneratedMetadata> {
+    let start = reply.find('{')?;
+    let end = reply.rfind('}')?;
+    let value: Value = serde_json::from_str(reply.get(start..=end)?).ok()?;
+    let metadata = GeneratedMetadata {
+        title: clean_field(value.get("title"), MAX_TITLE_CHARS, &['.', '!']),
+        short_description: clean_field(
+            value.get("short_description"),
+            MAX_SHORT_DESCRIPTION_CHARS,
+            &[],
+        ),
+    };
+    (metadata.title.is_some() || metadata.short_description.is_some()).then_some(metadata)
+}
+
+/// Claim the mission's call slot, or `false` if it was used too recently.
+fn try_acquire_slot(mission_id: Uuid, min_interval: Duration) -> bool {
+    let Ok(mut last_call) = LAST_CALL.lock() else {
+        return false;
+    };
+    let now = Instant::now();
+    if last_call
+        .get(&mission_id)
+        .is_some_and(|at| now.duration_since(*at) < min_interval)
+    {
+        return false;
+    }
+    last_call.insert(mission_id, now);
+    true
+}
+
+/// Ask the model for a title and short description of `history`, written in
+/// `locale`. `None` when disabled, rate limited or the call fails, in which
+/// case callers fall back to the heuristics.
+pub async fn generate(
+    mission_id: Uuid,
+    history: &[(String, String)],
+    locale: &str,
+) -> Option<GeneratedMetadata> {
+    let config = MetadataLlmConfig::from_env()?;
+    if history.is_empty() {
+        return None;
+    }
+    let key = history_key(history, locale);
+    if let Some(cached) = CACHE.lock().ok()?.get(&key).cloned() {
+        return Some(cached);
+    }
+    if !try_acquire_slot(mission_id, config.min_interval) {
+        tracing::debug!(%mission_id, "LLM metadata generation rate limited");
+        return None;
+    }
+
+    let completion = ProxyClient::from_env()
+        .for_mission(mission_id)
+        .complete(
+            &config.model,
+            0.2,
+            MAX_TOKENS,
+            SYSTEM_PROMPT,
+            &build_prompt(history, locale),
+        )
+        .await;
+    let metadata = match completion {
+        Ok(completion) => parse_reply(&completion.content),
+        Err(e) => {
+            tracing::warn!(%mission_id, "LLM metadata generation failed: {}", e);
+            return None;
+        }
+    };
+    let Some(metadata) = metadata else {
+        tracing::warn!(%mission_id, "LLM metadata reply had no usable title or description");
+        return None;
+    };
+
+    if let Ok(mut cache) = CACHE.lock() {
+        if cache.len() >= MAX_CACHE_ENTRIES {
+            cache.clear();
+        }
+        cache.insert(key, metadata.clone());
+    }
+    Some(metadata)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_cleans_replies() {
+        let reply = "Sure:\n```json\n{\"title\": \" \\\"Fix flaky CI tests.\\\" \", \
+                     \"short_description\": \"Stabilizes the integration suite\"}\n```";
+        assert_eq!(
+            parse_reply(reply),
+            Some(GeneratedMetadata {
+                title: Some("Fix flaky CI tests".to_string()),
+                short_description: Some("Stabilizes the integration suite".to_string()),
+            })
+        );
+        assert_eq!(parse_reply("{\"title\": \"\"}"), None);
+        assert_eq!(parse_reply("no json here"), None);
+
+        let long = format!("{{\"title\": \"{}\"}}", "word ".repeat(40));
+        let title = parse_reply(&long).unwrap().title.unwrap();
+        assert!(title.chars().count() <= MAX_TITLE_CHARS);
+        assert!(title.ends_with("..."));
+    }
+
+    #[test]
+    fn prompt_keeps_first_and_latest_messages() {
+        let mut history = vec![(
+            "user".to_string(),
+            "Migrate the billing service".to_string(),
+        )];
+        for i in 0..20 {
+            history.push((
+                "assistant".to_string(),
+                format!("step {} {}", i, "x".repeat(900)),
+            ));
+        }
+        let prompt = build_prompt(&history, "de");
+        assert!(prompt.contains("\"de\""));
+        assert!(prompt.contains("Migrate the billing service"));
+        assert!(prompt.contains("step 19"));
+        assert!(!prompt.contains("step 0 "));
+        assert_ne!(history_key(&history, "de"), history_key(&history, "en"));
+    }
+
+    #[test]
+    fn calls_are_rate_limited_per_mission() {
+        let mission = Uuid::new_v4();
+        assert!(try_acquire_slot(mission, Duration::from_secs(60)));
+        assert!(!try_acquire_slot(mission, Duration::from_secs(60)));
+        assert!(try_acquire_slot(Uuid::new_v4(), Duration::from_secs(60)));
+        assert!(try_acquire_slot(mission, Duration::ZERO));
+    }
+}
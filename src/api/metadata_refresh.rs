@@ -0,0 +1,571 @@
+//! Background refreshes of mission titles and short descriptions.
+//!
+//! Each control session owns a `MetadataRefreshScheduler`, which tracks the
+//! refresh task in flight per mission and the conversation length at the last
+//! refresh (the cadence baseline). Scheduling rules:
+//!
+//! - a newer refresh of a mission replaces (aborts) the one in flight, except
+//!   that a forced refresh (status milestones) is never replaced by a regular
+//!   one
+//! - at most `max_concurrent` refreshes run at once; the rest wait for a slot
+//! - after `shutdown` all tasks are aborted and new ones are rejected
+
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use tokio::sync::Semaphore;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use super::mission_store::{Mission, MissionStore};
+
+/// Refreshes running at once per control session.
+pub const DEFAULT_MAX_CONCURRENT_REFRESHES: usize = 4;
+/// Conversational messages since the last refresh that trigger another one.
+const REFRESH_CADENCE_MESSAGES: usize = 10;
+
+struct TaskEntry {
+    handle: JoinHandle<()>,
+    force_refresh: bool,
+    task_id: u64,
+}
+
+struct TaskRegistration {
+    superseded: Option<JoinHandle<()>>,
+}
+
+pub struct MetadataRefreshScheduler {
+    tasks: Mutex<HashMap<Uuid, TaskEntry>>,
+    baselines: Mutex<HashMap<Uuid, usize>>,
+    next_task_id: AtomicU64,
+    permits: Arc<Semaphore>,
+    shut_down: AtomicBool,
+}
+
+pub type SharedMetadataRefresh = Arc<MetadataRefreshScheduler>;
+
+fn register_task(
+    tasks: &mut HashMap<Uuid, TaskEntry>,
+    mission_id: Uuid,
+    force_refresh: bool,
+    task_id: u64,
+    handle: JoinHandle<()>,
+) -> TaskRegistration {
+    tasks.retain(|_, existing| !existing.handle.is_finished());
+
+    if let Some(existing) = tasks.get(&mission_id) {
+        if existing.force_refresh && !force_refresh {
+            return TaskRegistration {
+                superseded: Some(handle),
+            };
+        }
+    }
+
+    let replaced = tasks.insert(
+        mission_id,
+        TaskEntry {
+            handle,
+            force_refresh,
+            task_id,
+        },
+    );
+    TaskRegistration {
+        superseded: replaced.map(|entry| entry.handle),
+    }
+}
+
+fn should_skip_schedule(
+    tasks: &mut HashMap<Uuid, TaskEntry>,
+    mission_id: Uuid,
+    force_refresh: bool,
+) -> bool {
+    tasks.retain(|_, existing| !existing.handle.is_finished());
+    matches!(
+        tasks.get(&mission_id),
+        Some(existing) if existing.force_refresh && !force_refresh
+    )
+}
+
+fn complete_task(tasks: &mut HashMap<Uuid, TaskEntry>, mission_id: Uuid, task_id: u64) {
+    if tasks
+        .get(&mission_id)
+        .map(|entry| entry.task_id == task_id)
+        .unwrap_or(false)
+    {
+        tasks.remove(&mission_id);
+    }
+}
+
+impl MetadataRefreshScheduler {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            tasks: Mutex::new(HashMap::new()),
+            baselines: Mutex::new(HashMap::new()),
+            next_task_id: AtomicU64::new(1),
+            permits: Arc::new(Semaphore::new(max_concurrent.max(1))),
+            shut_down: AtomicBool::new(false),
+        }
+    }
+
+    fn tasks(&self) -> MutexGuard<'_, HashMap<Uuid, TaskEntry>> {
+        self.tasks
+            .lock()
+            .expect("metadata refresh task registry lock poisoned")
+    }
+
+    fn baselines(&self) -> MutexGuard<'_, HashMap<Uuid, usize>> {
+        self.baselines
+            .lock()
+            .expect("metadata refresh baseline lock poisoned")
+    }
+
+    /// Run `refresh` in the background for `mission_id`. Returns `false` when
+    /// it was not scheduled (a forced refresh is in flight, or the scheduler
+    /// was shut down).
+    pub fn schedule<F>(self: &Arc<Self>, mission_id: Uuid, force_refresh: bool, refresh: F) -> bool
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        if self.shut_down.load(Ordering::Acquire)
+            || should_skip_schedule(&mut self.tasks(), mission_id, force_refresh)
+        {
+            return false;
+        }
+
+        let task_id = self.next_task_id.fetch_add(1, Ordering::Relaxed);
+        let scheduler = Arc::downgrade(self);
+        let permits = Arc::clone(&self.permits);
+        let handle = tokio::spawn(async move {
+            // Superseded tasks are aborted while they wait, so only the
+            // latest refresh of a mission takes a slot.
+            let Ok(_permit) = permits.acquire_owned().await else {
+                return;
+            };
+            refresh.await;
+            if let Some(scheduler) = scheduler.upgrade() {
+                complete_task(&mut scheduler.tasks(), mission_id, task_id);
+            }
+        });
+        let handle_id = handle.id();
+        let registration = register_task(
+            &mut self.tasks(),
+            mission_id,
+            force_refresh,
+            task_id,
+            handle,
+        );
+        match registration.superseded {
+            Some(superseded) => {
+                let rejected = superseded.id() == handle_id;
+                superseded.abort();
+                !rejected
+            }
+            None => true,
+        }
+    }
+
+    /// Whether a refresh of the mission is in flight.
+    pub fn is_scheduled(&self, mission_id: Uuid) -> bool {
+        let mut tasks = self.tasks();
+        tasks.retain(|_, existing| !existing.handle.is_finished());
+        tasks.contains_key(&mission_id)
+    }
+
+    /// Whether enough conversation happened since the last refresh to
+    /// refresh again. The first call for a mission with metadata starts
+    /// counting from `conversational_count`.
+    pub fn should_refresh_by_cadence(
+        &self,
+        mission_id: Uuid,
+        mission: &Mission,
+        conversational_count: usize,
+        force_refresh: bool,
+    ) -> bool {
+        if force_refresh {
+            return true;
+        }
+        if conversational_count == 0 {
+            return false;
+        }
+
+        let mut baselines = self.baselines();
+        let baseline = baselines.entry(mission_id).or_insert_with(|| {
+            if mission.metadata_updated_at.is_some() {
+                conversational_count
+            } else {
+                0
+            }
+        });
+
+        if conversational_count < *baseline {
+            *baseline = conversational_count;
+            return false;
+        }
+
+        conversational_count.saturating_sub(*baseline) >= REFRESH_CADENCE_MESSAGES
+    }
+
+    pub fn record_baseline(&self, mission_id: Uuid, conversational_count: usize) {
+        self.baselines().insert(mission_id, conversational_count);
+    }
+
+    /// Restart the cadence from the mission's current conversation, e.g.
+    /// after the user renamed it.
+    pub fn record_baseline_from_mission(&self, mission_id: Uuid, mission: &Mission) {
+        let conversational_count = mission
+            .history
+            .iter()
+            .filter(|entry| entry.role == "user" || entry.role == "assistant")
+            .count();
+        self.record_baseline(mission_id, conversational_count);
+    }
+
+    /// Forget a mission (deleted): abort its refresh and drop its baseline.
+    pub fn clear(&self, mission_id: Uuid) {
+        let stale_task = self.tasks().remove(&mission_id);
+        if let Some(stale_task) = stale_task {
+            stale_task.handle.abort();
+        }
+        self.baselines().remove(&mission_id);
+    }
+
+    /// Forget missions that no longer exist in the store.
+    pub async fn clear_stale(&self, mission_store: &Arc<dyn MissionStore>) {
+        let tracked_ids: HashSet<Uuid> = {
+            let tasks = self.tasks();
+            let baselines = self.baselines();
+            tasks.keys().chain(baselines.keys()).copied().collect()
+        };
+
+        for mission_id in tracked_ids {
+            match mission_store.get_mission(mission_id).await {
+                Ok(Some(_)) => {}
+                Ok(None) => self.clear(mission_id),
+                Err(err) => tracing::warn!(
+                    "Failed to verify mission {} while clearing stale metadata refresh state: {}",
+                    mission_id,
+                    err
+                ),
+            }
+        }
+    }
+
+    /// Abort all refreshes and reject new ones.
+    pub fn shutdown(&self) {
+        self.shut_down.store(true, Ordering::Release);
+        self.permits.close();
+        for (_, task) in self.tasks().drain() {
+            task.handle.abort();
+        }
+        self.baselines().clear();
+    }
+}
+
+impl Default for MetadataRefreshScheduler {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_CONCURRENT_REFRESHES)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn pending_task() -> JoinHandle<()> {
+        tokio::spawn(std::future::pending::<()>())
+    }
+
+    #[tokio::test]
+    async fn register_task_replaces_existing_task_for_same_mission() {
+        let mission_id = Uuid::new_v4();
+        let mut tasks: HashMap<Uuid, TaskEntry> = HashMap::new();
+
+        let first = tokio::spawn(async {
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        });
+        let first_task_id = first.id();
+        let registration = register_task(&mut tasks, mission_id, false, 1, first);
+        assert!(registration.superseded.is_none());
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(
+            tasks.get(&mission_id).map(|entry| entry.handle.id()),
+            Some(first_task_id)
+        );
+
+        let second = tokio::spawn(async {
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        });
+        let second_task_id = second.id();
+        let registration = register_task(&mut tasks, mission_id, false, 2, second);
+        let replaced = registration
+            .superseded
+            .expect("expected previous task to be replaced");
+        assert_eq!(replaced.id(), first_task_id);
+        replaced.abort();
+
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(
+            tasks.get(&mission_id).map(|entry| entry.handle.id()),
+            Some(second_task_id)
+        );
+
+        if let Some(active) = tasks.remove(&mission_id) {
+            active.handle.abort();
+        }
+    }
+
+    #[tokio::test]
+    async fn register_task_keeps_in_flight_forced_task_over_non_forced() {
+        let mission_id = Uuid::new_v4();
+        let mut tasks: HashMap<Uuid, TaskEntry> = HashMap::new();
+
+        let forced = tokio::spawn(async {
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        });
+        let forced_task_id = forced.id();
+        let forced_registration = register_task(&mut tasks, mission_id, true, 1, forced);
+        assert!(forced_registration.superseded.is_none());
+
+        let non_forced = tokio::spawn(async {
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        });
+        let non_forced_task_id = non_forced.id();
+        let non_forced_registration = register_task(&mut tasks, mission_id, false, 2, non_forced);
+        let rejected = non_forced_registration
+            .superseded
+            .expect("new non-forced task should be rejected");
+        assert_eq!(rejected.id(), non_forced_task_id);
+        rejected.abort();
+
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(
+            tasks.get(&mission_id).map(|entry| entry.handle.id()),
+            Some(forced_task_id)
+        );
+        assert!(tasks
+            .get(&mission_id)
+            .is_some_and(|entry| entry.force_refresh));
+
+        if let Some(active) = tasks.remove(&mission_id) {
+            active.handle.abort();
+        }
+    }
+
+    #[tokio::test]
+    async fn register_task_forced_replaces_non_forced() {
+        let mission_id = Uuid::new_v4();
+        let mut tasks: HashMap<Uuid, TaskEntry> = HashMap::new();
+
+        let non_forced = tokio::spawn(async {
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        });
+        let non_forced_task_id = non_forced.id();
+        let non_forced_registration = register_task(&mut tasks, mission_id, false, 1, non_forced);
+        assert!(non_forced_registration.superseded.is_none());
+
+        let forced = tokio::spawn(async {
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        });
+        let forced_task_id = forced.id();
+        let forced_registration = register_task(&mut tasks, mission_id, true, 2, forced);
+        let replaced = forced_registration
+            .superseded
+            .expect("existing non-forced task should be replaced");
+        assert_eq!(replaced.id(), non_forced_task_id);
+        replaced.abort();
+
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(
+            tasks.get(&mission_id).map(|entry| entry.handle.id()),
+            Some(forced_task_id)
+        );
+        assert!(tasks
+            .get(&mission_id)
+            .is_some_and(|entry| entry.force_refresh));
+
+        if let Some(active) = tasks.remove(&mission_id) {
+            active.handle.abort();
+        }
+    }
+
+    #[tokio::test]
+    async fn should_skip_schedule_rejects_non_forced_when_forced_in_flight() {
+        let mission_id = Uuid::new_v4();
+        let mut tasks: HashMap<Uuid, TaskEntry> = HashMap::new();
+
+        let forced = tokio::spawn(async {
+            tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+        });
+        let registration = register_task(&mut tasks, mission_id, true, 1, forced);
+        assert!(registration.superseded.is_none());
+
+        assert!(should_skip_schedule(&mut tasks, mission_id, false));
+        assert!(!should_skip_schedule(&mut tasks, mission_id, true));
+
+        if let Some(active) = tasks.remove(&mission_id) {
+            active.handle.abort();
+        }
+    }
+
+    #[tokio::test]
+    async fn should_skip_schedule_drops_finished_tasks() {
+        let mission_id = Uuid::new_v4();
+        let mut tasks: HashMap<Uuid, TaskEntry> = HashMap::new();
+
+        let finished = tokio::spawn(async {});
+        let registration = register_task(&mut tasks, mission_id, true, 1, finished);
+        assert!(registration.superseded.is_none());
+        tokio::task::yield_now().await;
+
+        assert!(!should_skip_schedule(&mut tasks, mission_id, false));
+        assert!(tasks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn complete_task_only_removes_matching_generation() {
+        let mission_id = Uuid::new_v4();
+        let mut tasks: HashMap<Uuid, TaskEntry> = HashMap::new();
+
+        let task = tokio::spawn(async {});
+        let registration = register_task(&mut tasks, mission_id, false, 42, task);
+        assert!(registration.superseded.is_none());
+        assert_eq!(tasks.len(), 1);
+
+        complete_task(&mut tasks, mission_id, 41);
+        assert_eq!(tasks.len(), 1);
+
+        complete_task(&mut tasks, mission_id, 42);
+        assert!(tasks.is_empty());
+    }
+
+    #[tokio::test]
+    async fn clear_removes_task_and_baseline() {
+        let scheduler = MetadataRefreshScheduler::default();
+        let mission_id = Uuid::new_v4();
+        let other_mission_id = Uuid::new_v4();
+
+        {
+            let mut tasks = scheduler.tasks();
+            tasks.insert(
+                mission_id,
+                TaskEntry {
+                    handle: pending_task(),
+                    force_refresh: false,
+                    task_id: 1,
+                },
+            );
+            tasks.insert(
+                other_mission_id,
+                TaskEntry {
+                    handle: pending_task(),
+                    force_refresh: false,
+                    task_id: 2,
+                },
+            );
+        }
+        scheduler.record_baseline(mission_id, 10);
+        scheduler.record_baseline(other_mission_id, 20);
+
+        scheduler.clear(mission_id);
+
+        assert!(!scheduler.tasks().contains_key(&mission_id));
+        assert!(scheduler.tasks().contains_key(&other_mission_id));
+        assert!(!scheduler.baselines().contains_key(&mission_id));
+        assert_eq!(scheduler.baselines().get(&other_mission_id), Some(&20usize));
+
+        scheduler.shutdown();
+    }
+
+    #[tokio::test]
+    async fn clear_stale_prunes_deleted_missions() {
+        let scheduler = MetadataRefreshScheduler::default();
+        let store: Arc<dyn MissionStore> =
+            Arc::new(crate::api::mission_store::InMemoryMissionStore::new());
+
+        let deleted_mission = store
+            .create_mission(None, None, None, None, None, None, None)
+            .await
+            .expect("create deleted mission");
+        let existing_mission = store
+            .create_mission(None, None, None, None, None, None, None)
+            .await
+            .expect("create existing mission");
+
+        {
+            let mut tasks = scheduler.tasks();
+            tasks.insert(
+                deleted_mission.id,
+                TaskEntry {
+                    task_id: 1,
+                    force_refresh: false,
+                    handle: pending_task(),
+                },
+            );
+            tasks.insert(
+                existing_mission.id,
+                TaskEntry {
+                    task_id: 2,
+                    force_refresh: false,
+                    handle: pending_task(),
+                },
+            );
+        }
+        scheduler.record_baseline(deleted_mission.id, 10);
+        scheduler.record_baseline(existing_mission.id, 20);
+
+        store
+            .delete_mission(deleted_mission.id)
+            .await
+            .expect("delete mission should succeed");
+
+        scheduler.clear_stale(&store).await;
+
+        assert!(!scheduler.tasks().contains_key(&deleted_mission.id));
+        assert!(scheduler.tasks().contains_key(&existing_mission.id));
+        assert!(!scheduler.baselines().contains_key(&deleted_mission.id));
+        assert_eq!(
+            scheduler.baselines().get(&existing_mission.id),
+            Some(&20usize)
+        );
+
+        scheduler.shutdown();
+    }
+
+    #[tokio::test]
+    async fn concurrency_is_bounded_and_shutdown_aborts_tasks() {
+        let scheduler = Arc::new(MetadataRefreshScheduler::new(1));
+        let (release_tx, release_rx) = tokio::sync::oneshot::channel::<()>();
+        let (second_tx, mut second_rx) = tokio::sync::oneshot::channel::<()>();
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+
+        assert!(scheduler.schedule(first, false, async move {
+            let _ = release_rx.await;
+        }));
+        assert!(scheduler.schedule(second, false, async move {
+            let _ = second_tx.send(());
+        }));
+
+        // The second refresh waits for the first one's slot
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(second_rx.try_recv().is_err());
+        release_tx.send(()).unwrap();
+        tokio::time::timeout(Duration::from_secs(5), second_rx)
+            .await
+            .expect("second refresh should run")
+            .unwrap();
+        tokio::task::yield_now().await;
+        assert!(!scheduler.is_scheduled(first));
+
+        assert!(scheduler.schedule(first, true, std::future::pending::<()>()));
+        assert!(!scheduler.schedule(first, false, async {}));
+        scheduler.record_baseline(first, 3);
+        scheduler.shutdown();
+        assert!(!scheduler.is_scheduled(first));
+        assert!(!scheduler.schedule(second, true, async {}));
+        assert!(scheduler.baselines().is_empty());
+    }
+}
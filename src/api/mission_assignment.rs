@@ -0,0 +1,335 @@
+//! Mission ownership: an assignee and watchers.
+//!
+//! A mission can be assigned to one user and watched by several. Assigning a
+//! mission also makes the assignee a watcher. When a watched mission stops
+//! (completed, failed, blocked, interrupted) [`notification_loop`] broadcasts
+//! an [`AgentEvent::MissionNotification`] addressed to its watchers, and
+//! assigning a mission notifies the new assignee; clients show the ones
+//! addressed to their user.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::control::{AgentEvent, ControlState, MissionStatus};
+use super::mission_store::{normalize_watchers, Mission, MissionStore};
+use super::routes::AppState;
+use crate::util::internal_error;
+
+/// Longest accepted assignee or watcher name.
+const MAX_USERNAME_LEN: usize = 100;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MissionNotificationKind {
+    /// The recipient was assigned the mission
+    Assigned,
+    /// A watched mission stopped
+    StatusChanged,
+    /// The recipient was mentioned in a comment
+    Mentioned,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AssignRequest {
+    pub assignee: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WatchersRequest {
+    pub watchers: Vec<String>,
+}
+
+/// Statuses watchers are notified about.
+fn notifies_watchers(status: MissionStatus) -> bool {
+    matches!(
+        status,
+        MissionStatus::Completed
+            | MissionStatus::Failed
+            | MissionStatus::Blocked
+            | MissionStatus::NotFeasible
+            | MissionStatus::Interrupted
+    )
+}
+
+/// Users a mission's notifications go to: its watchers and its assignee.
+pub fn recipients(mission: &Mission) -> Vec<String> {
+    let mut users = mission.watchers.clone();
+    users.extend(mission.assignee.clone());
+    normalize_watchers(&users)
+}
+
+pub(crate) fn mission_label(mission: &Mission) -> String {
+    mission
+        .title
+        .clone()
+        .filter(|t| !t.trim().is_empty())
+        .unwrap_or_else(|| format!("Mission {}", mission.id))
+}
+
+fn status_message(mission: &Mission, status: MissionStatus, summary: Option<&str>) -> String {
+    match summary.filter(|s| !s.is_empty()) {
+        Some(summary) => format!("{} is {}: {}", mission_label(mission), status, summary),
+        None => format!("{} is {}", mission_label(mission), status),
+    }
+}
+
+/// Background task that notifies watchers when their missions stop.
+pub async fn notification_loop(
+    mission_store: Arc<dyn MissionStore>,
+    events_tx: broadcast::Sender<AgentEvent>,
+    mut events_rx: broadcast::Receiver<AgentEvent>,
+) {
+    loop {
+        let event = match events_rx.recv().await {
+            Ok(event) => event,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::warn!("Mission notifications skipped {} events", skipped);
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        let AgentEvent::MissionStatusChanged {
+            mission_id,
+            status,
+            summary,
+        } = event
+        else {
+            continue;
+        };
+        if !notifies_watchers(status) {
+            continue;
+        }
+        let mission = match mission_store.get_mission(mission_id).await {
+            Ok(Some(mission)) => mission,
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to load mission {} for notifications: {}",
+                    mission_id,
+                    e
+                );
+                continue;
+            }
+        };
+        let recipients = recipients(&mission);
+        if recipients.is_empty() {
+            continue;
+        }
+        let _ = events_tx.send(AgentEvent::MissionNotification {
+            mission_id,
+            recipients,
+            kind: MissionNotificationKind::StatusChanged,
+            message: status_message(&mission, status, summary.as_deref()),
+        });
+    }
+}
+
+fn validate_username(name: &str) -> Result<String, (StatusCode, String)> {
+    let name = name.trim();
+    if name.is_empty() || name.chars().count() > MAX_USERNAME_LEN {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "User names must be between 1 and {} characters",
+                MAX_USERNAME_LEN
+            ),
+        ));
+    }
+    Ok(name.to_string())
+}
+
+async fn load_mission(
+    state: &Arc<AppState>,
+    user: &AuthUser,
+    id: Uuid,
+) -> Result<(ControlState, Mission), (StatusCode, String)> {
+    let control = state.control.get_or_spawn(user).await;
+    let mission = control
+        .mission_store
+        .get_mission(id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Mission {} not found", id)))?;
+    Ok((control, mission))
+}
+
+async fn save_watchers(
+    control: &ControlState,
+    mut mission: Mission,
+    watchers: Vec<String>,
+) -> Result<Json<Mission>, (StatusCode, String)> {
+    let watchers = normalize_watchers(&watchers);
+    control
+        .mission_store
+        .update_mission_watchers(mission.id, &watchers)
+        .await
+        .map_err(internal_error)?;
+    mission.watchers = watchers;
+    Ok(Json(mission))
+}
+
+/// POST /api/control/missions/:id/assign - Assign the mission to a user.
+pub async fn assign_mission(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<AssignRequest>,
+) -> Result<Json<Mission>, (StatusCode, String)> {
+    let assignee = validate_username(&req.assignee)?;
+    let (control, mut mission) = load_mission(&state, &user, id).await?;
+    let store = &control.mission_store;
+    store
+        .update_mission_assignee(id, Some(&assignee))
+        .await
+        .map_err(internal_error)?;
+    let mut watchers = mission.watchers.clone();
+    watchers.push(assignee.clone());
+    let watchers = normalize_watchers(&watchers);
+    store
+        .update_mission_watchers(id, &watchers)
+        .await
+        .map_err(internal_error)?;
+
+    let reassigned = mission.assignee.as_deref() != Some(assignee.as_str());
+    mission.assignee = Some(assignee.clone());
+    mission.watchers = watchers;
+    if reassigned && assignee != user.username {
+        let _ = control.events_tx.send(AgentEvent::MissionNotification {
+            mission_id: id,
+            recipients: vec![assignee],
+            kind: MissionNotificationKind::Assigned,
+            message: format!("{} assigned you {}", user.username, mission_label(&mission)),
+        });
+    }
+    Ok(Json(mission))
+}
+
+/// POST /api/control/missions/:id/unassign - Clear the mission's assignee.
+/// The former assignee keeps watching.
+pub async fn unassign_mission(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Mission>, (StatusCode, String)> {
+    let (control, mut mission) = load_mission(&state, &user, id).await?;
+    control
+        .mission_store
+        .update_mission_assignee(id, None)
+        .await
+        .map_err(internal_error)?;
+    mission.assignee = None;
+    Ok(Json(mission))
+}
+
+/// POST /api/control/missions/:id/watch - Watch the mission as the current user.
+pub async fn watch_mission(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Mission>, (StatusCode, String)> {
+    let (control, mission) = load_mission(&state, &user, id).await?;
+    let mut watchers = mission.watchers.clone();
+    watchers.push(user.username.clone());
+    save_watchers(&control, mission, watchers).await
+}
+
+/// POST /api/control/missions/:id/unwatch - Stop watching the mission.
+pub async fn unwatch_mission(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Mission>, (StatusCode, String)> {
+    let (control, mission) = load_mission(&state, &user, id).await?;
+    let watchers = mission
+        .watchers
+        .iter()
+        .filter(|w| **w != user.username)
+        .cloned()
+        .collect();
+    save_watchers(&control, mission, watchers).await
+}
+
+/// PUT /api/control/missions/:id/watchers - Replace the mission's watchers.
+pub async fn set_watchers(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<WatchersRequest>,
+) -> Result<Json<Mission>, (StatusCode, String)> {
+    let watchers = req
+        .watchers
+        .iter()
+        .filter(|w| !w.trim().is_empty())
+        .map(|w| validate_username(w))
+        .collect::<Result<Vec<_>, _>>()?;
+    let (control, mission) = load_mission(&state, &user, id).await?;
+    save_watchers(&control, mission, watchers).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mission_store::{InMemoryMissionStore, MissionFilter};
+
+    #[tokio::test]
+    async fn assignee_filter_and_recipients() {
+        let store = InMemoryMissionStore::new();
+        let mission = store
+            .create_mission(Some("Deploy"), None, None, None, None, None, None)
+            .await
+            .unwrap();
+        let other = store
+            .create_mission(Some("Other"), None, None, None, None, None, None)
+            .await
+            .unwrap();
+        store
+            .update_mission_assignee(mission.id, Some("alice"))
+            .await
+            .unwrap();
+        store
+            .update_mission_watchers(mission.id, &normalize_watchers(&["bob", " alice "]))
+            .await
+            .unwrap();
+
+        let assigned = MissionFilter {
+            assignee: Some("alice".to_string()),
+            ..Default::default()
+        };
+        let missions = store
+            .list_missions_filtered(&assigned, 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(missions.len(), 1);
+        assert_eq!(missions[0].id, mission.id);
+        assert_eq!(recipients(&missions[0]), vec!["alice", "bob"]);
+
+        let unassigned = MissionFilter {
+            assignee: Some(String::new()),
+            ..Default::default()
+        };
+        let missions = store
+            .list_missions_filtered(&unassigned, 10, 0)
+            .await
+            .unwrap();
+        assert_eq!(missions.len(), 1);
+        assert_eq!(missions[0].id, other.id);
+        assert!(recipients(&missions[0]).is_empty());
+    }
+
+    #[test]
+    fn only_stopped_missions_notify() {
+        assert!(notifies_watchers(MissionStatus::Failed));
+        assert!(notifies_watchers(MissionStatus::Completed));
+        assert!(!notifies_watchers(MissionStatus::Active));
+        assert!(!notifies_watchers(MissionStatus::Pending));
+    }
+}
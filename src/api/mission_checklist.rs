@@ -0,0 +1,426 @@
+//! Mission checklists: explicit acceptance criteria.
+//!
+//! A mission's checklist is a list of items, each done or not. Users edit it
+//! through the API, the agent with the `checklist_update` tool. Whenever it
+//! changes, a `progress` event reports the checked items as completed
+//! subtasks and the first unchecked one as the current subtask. While a
+//! mission has a checklist, the control actor doesn't report progress
+//! inferred from the agent's subtasks.
+//!
+//! - `GET /api/missions/:id/checklist`
+//! - `PUT /api/missions/:id/checklist` - replace the checklist
+//! - `POST /api/missions/:id/checklist/items` - add an item
+//! - `PATCH /api/missions/:id/checklist/items/:item_id` - check, uncheck or
+//!   rename an item
+//! - `DELETE /api/missions/:id/checklist/items/:item_id`
+//!
+//! Each returns the updated checklist.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::Deserialize;
+use tokio::sync::{broadcast, RwLock};
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::control::{AgentEvent, ControlState};
+use super::mission_store::{ChecklistItem, Mission, MissionStore};
+use super::routes::AppState;
+use crate::tools::mission::ChecklistChanges;
+use crate::util::internal_error;
+
+/// Most items on a checklist.
+const MAX_CHECKLIST_ITEMS: usize = 50;
+/// Longest item text, in characters.
+const MAX_ITEM_CHARS: usize = 500;
+
+/// Whether each mission seen so far has a checklist.
+pub type SharedChecklists = Arc<RwLock<HashMap<Uuid, bool>>>;
+
+fn validate_text(text: &str) -> Result<String, String> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err("Checklist items need a text".to_string());
+    }
+    if text.chars().count() > MAX_ITEM_CHARS {
+        return Err(format!(
+            "Checklist items are limited to {} characters",
+            MAX_ITEM_CHARS
+        ));
+    }
+    Ok(text.to_string())
+}
+
+fn validate_len(items: &[ChecklistItem]) -> Result<(), String> {
+    if items.len() > MAX_CHECKLIST_ITEMS {
+        return Err(format!(
+            "A checklist has at most {} items",
+            MAX_CHECKLIST_ITEMS
+        ));
+    }
+    Ok(())
+}
+
+/// Find an item by id or by its text (ignoring case and surrounding spaces).
+fn find_item<'a>(items: &'a mut [ChecklistItem], name: &str) -> Option<&'a mut ChecklistItem> {
+    let name = name.trim();
+    items
+        .iter_mut()
+        .find(|item| item.id.to_string() == name || item.text.eq_ignore_ascii_case(name))
+}
+
+/// Apply the agent's changes to a checklist.
+pub fn apply_changes(
+    items: &mut Vec<ChecklistItem>,
+    changes: &ChecklistChanges,
+) -> Result<(), String> {
+    for text in &changes.add {
+        let text = validate_text(text)?;
+        if find_item(items, &text).is_none() {
+            items.push(ChecklistItem {
+                id: Uuid::new_v4(),
+                text,
+                done: false,
+            });
+        }
+    }
+    validate_len(items)?;
+    for (names, done) in [(&changes.check, true), (&changes.uncheck, false)] {
+        for name in names {
+            let item = find_item(items, name)
+                .ok_or_else(|| format!("No checklist item '{}'", name.trim()))?;
+            item.done = done;
+        }
+    }
+    Ok(())
+}
+
+/// The `progress` event of a mission's checklist.
+pub fn progress_event(mission_id: Uuid, items: &[ChecklistItem]) -> AgentEvent {
+    AgentEvent::Progress {
+        total_subtasks: items.len(),
+        completed_subtasks: items.iter().filter(|item| item.done).count(),
+        current_subtask: items
+            .iter()
+            .find(|item| !item.done)
+            .map(|item| item.text.clone()),
+        depth: 0,
+        mission_id: Some(mission_id),
+    }
+}
+
+/// The checklist as shown to the agent.
+pub fn render(items: &[ChecklistItem]) -> String {
+    if items.is_empty() {
+        return "The mission has no checklist.".to_string();
+    }
+    let done = items.iter().filter(|item| item.done).count();
+    let mut text = format!("Checklist ({}/{} done):", done, items.len());
+    for item in items {
+        let mark = if item.done { "x" } else { " " };
+        text.push_str(&format!("\n- [{}] {} (id: {})", mark, item.text, item.id));
+    }
+    text
+}
+
+/// Whether a mission has a checklist, loading it on first use.
+pub async fn has_checklist(
+    checklists: &SharedChecklists,
+    store: &Arc<dyn MissionStore>,
+    mission_id: Uuid,
+) -> bool {
+    if let Some(known) = checklists.read().await.get(&mission_id) {
+        return *known;
+    }
+    let has = match store.get_mission(mission_id).await {
+        Ok(mission) => mission.is_some_and(|m| !m.checklist.is_empty()),
+        Err(e) => {
+            tracing::warn!("Failed to load checklist of mission {}: {}", mission_id, e);
+            return false;
+        }
+    };
+    checklists.write().await.insert(mission_id, has);
+    has
+}
+
+/// Store a mission's checklist and report its progress.
+pub async fn save(
+    store: &Arc<dyn MissionStore>,
+    events_tx: &broadcast::Sender<AgentEvent>,
+    checklists: &SharedChecklists,
+    mission_id: Uuid,
+    items: &[ChecklistItem],
+) -> Result<(), String> {
+    store.update_mission_checklist(mission_id, items).await?;
+    checklists
+        .write()
+        .await
+        .insert(mission_id, !items.is_empty());
+    let _ = events_tx.send(progress_event(mission_id, items));
+    Ok(())
+}
+
+/// Apply the agent's `checklist_update` call; returns the checklist as text.
+pub async fn update_from_agent(
+    store: &Arc<dyn MissionStore>,
+    events_tx: &broadcast::Sender<AgentEvent>,
+    checklists: &SharedChecklists,
+    mission_id: Uuid,
+    changes: &ChecklistChanges,
+) -> Result<String, String> {
+    let mission = store
+        .get_mission(mission_id)
+        .await?
+        .ok_or_else(|| format!("Mission {} not found", mission_id))?;
+    let mut items = mission.checklist;
+    if *changes == ChecklistChanges::default() {
+        return Ok(render(&items));
+    }
+    apply_changes(&mut items, changes)?;
+    save(store, events_tx, checklists, mission_id, &items).await?;
+    Ok(render(&items))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChecklistItemInput {
+    /// Kept when given (to preserve an existing item's id)
+    #[serde(default)]
+    pub id: Option<Uuid>,
+    pub text: String,
+    #[serde(default)]
+    pub done: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetChecklistRequest {
+    pub items: Vec<ChecklistItemInput>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddItemRequest {
+    pub text: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateItemRequest {
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub done: Option<bool>,
+}
+
+fn bad_request(message: String) -> (StatusCode, String) {
+    (StatusCode::BAD_REQUEST, message)
+}
+
+async fn load_mission(
+    state: &Arc<AppState>,
+    user: &AuthUser,
+    mission_id: Uuid,
+) -> Result<(ControlState, Mission), (StatusCode, String)> {
+    let control = state.control.get_or_spawn(user).await;
+    let mission = control
+        .mission_store
+        .get_mission(mission_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Mission not found".to_string()))?;
+    Ok((control, mission))
+}
+
+async fn store_checklist(
+    control: &ControlState,
+    mission_id: Uuid,
+    items: Vec<ChecklistItem>,
+) -> Result<Json<Vec<ChecklistItem>>, (StatusCode, String)> {
+    save(
+        &control.mission_store,
+        &control.events_tx,
+        &control.checklists,
+        mission_id,
+        &items,
+    )
+    .await
+    .map_err(internal_error)?;
+    Ok(Json(items))
+}
+
+/// GET /api/missions/:id/checklist
+pub async fn get_checklist(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+) -> Result<Json<Vec<ChecklistItem>>, (StatusCode, String)> {
+    let (_, mission) = load_mission(&state, &user, mission_id).await?;
+    Ok(Json(mission.checklist))
+}
+
+/// PUT /api/missions/:id/checklist - Replace the checklist.
+pub async fn set_checklist(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+    Json(req): Json<SetChecklistRequest>,
+) -> Result<Json<Vec<ChecklistItem>>, (StatusCode, String)> {
+    let (control, _) = load_mission(&state, &user, mission_id).await?;
+    let items = req
+        .items
+        .into_iter()
+        .map(|item| {
+            Ok(ChecklistItem {
+                id: item.id.unwrap_or_else(Uuid::new_v4),
+                text: validate_text(&item.text)?,
+                done: item.done,
+            })
+        })
+        .collect::<Result<Vec<_>, String>>()
+        .map_err(bad_request)?;
+    validate_len(&items).map_err(bad_request)?;
+    store_checklist(&control, mission_id, items).await
+}
+
+/// POST /api/missions/:id/checklist/items - Add an item.
+pub async fn add_item(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+    Json(req): Json<AddItemRequest>,
+) -> Result<Json<Vec<ChecklistItem>>, (StatusCode, String)> {
+    let (control, mission) = load_mission(&state, &user, mission_id).await?;
+    let mut items = mission.checklist;
+    items.push(ChecklistItem {
+        id: Uuid::new_v4(),
+        text: validate_text(&req.text).map_err(bad_request)?,
+        done: false,
+    });
+    validate_len(&items).map_err(bad_request)?;
+    store_checklist(&control, mission_id, items).await
+}
+
+/// PATCH /api/missions/:id/checklist/items/:item_id - Check, uncheck or
+/// rename an item.
+pub async fn update_item(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path((mission_id, item_id)): Path<(Uuid, Uuid)>,
+    Json(req): Json<UpdateItemRequest>,
+) -> Result<Json<Vec<ChecklistItem>>, (StatusCode, String)> {
+    let (control, mission) = load_mission(&state, &user, mission_id).await?;
+    let mut items = mission.checklist;
+    let item = items
+        .iter_mut()
+        .find(|item| item.id == item_id)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                "Checklist item not found".to_string(),
+            )
+        })?;
+    if let Some(text) = req.text {
+        item.text = validate_text(&text).map_err(bad_request)?;
+    }
+    if let Some(done) = req.done {
+        item.done = done;
+    }
+    store_checklist(&control, mission_id, items).await
+}
+
+/// DELETE /api/missions/:id/checklist/items/:item_id
+pub async fn delete_item(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path((mission_id, item_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<Vec<ChecklistItem>>, (StatusCode, String)> {
+    let (control, mission) = load_mission(&state, &user, mission_id).await?;
+    let mut items = mission.checklist;
+    let len = items.len();
+    items.retain(|item| item.id != item_id);
+    if items.len() == len {
+        return Err((
+            StatusCode::NOT_FOUND,
+            "Checklist item not found".to_string(),
+        ));
+    }
+    store_checklist(&control, mission_id, items).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(text: &str, done: bool) -> ChecklistItem {
+        ChecklistItem {
+            id: Uuid::new_v4(),
+            text: text.to_string(),
+            done,
+        }
+    }
+
+    #[test]
+    fn agent_changes_add_and_check_items_by_id_or_text() {
+        let mut items = vec![item("Tests pass", false), item("Docs updated", true)];
+        let docs_id = items[1].id.to_string();
+        let changes = ChecklistChanges {
+            add: vec!["  Changelog entry ".to_string(), "tests pass".to_string()],
+            check: vec!["TESTS PASS".to_string()],
+            uncheck: vec![docs_id],
+        };
+        apply_changes(&mut items, &changes).unwrap();
+
+        let state: Vec<_> = items.iter().map(|i| (i.text.as_str(), i.done)).collect();
+        assert_eq!(
+            state,
+            vec![
+                ("Tests pass", true),
+                ("Docs updated", false),
+                ("Changelog entry", false)
+            ]
+        );
+
+        let unknown = ChecklistChanges {
+            check: vec!["Deployed".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            apply_changes(&mut items, &unknown).unwrap_err(),
+            "No checklist item 'Deployed'"
+        );
+        let blank = ChecklistChanges {
+            add: vec![" ".to_string()],
+            ..Default::default()
+        };
+        assert!(apply_changes(&mut items, &blank).is_err());
+    }
+
+    #[test]
+    fn progress_counts_checked_items() {
+        let mission_id = Uuid::new_v4();
+        let items = vec![
+            item("Build", true),
+            item("Deploy", false),
+            item("Verify", false),
+        ];
+        let AgentEvent::Progress {
+            total_subtasks,
+            completed_subtasks,
+            current_subtask,
+            mission_id: event_mission,
+            ..
+        } = progress_event(mission_id, &items)
+        else {
+            panic!("expected a progress event");
+        };
+        assert_eq!((total_subtasks, completed_subtasks), (3, 1));
+        assert_eq!(current_subtask.as_deref(), Some("Deploy"));
+        assert_eq!(event_mission, Some(mission_id));
+
+        assert!(render(&items).starts_with("Checklist (1/3 done):\n- [x] Build (id: "));
+        assert_eq!(render(&[]), "The mission has no checklist.");
+    }
+}
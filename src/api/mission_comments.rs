@@ -0,0 +1,388 @@
+//! Discussion threads on missions.
+//!
+//! Comments let people talk about a mission without talking to its agent:
+//! they live in their own table, never enter the mission's history and are
+//! never sent to the backend. A comment either starts a thread or replies to
+//! one (`thread_id`). Bodies are markdown; `@name` mentions are extracted on
+//! write and the mentioned users get an [`AgentEvent::MissionNotification`].
+//! Every change is broadcast as an [`AgentEvent::MissionComment`] so open
+//! discussion sidebars stay current.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::control::{AgentEvent, ControlState};
+use super::mission_assignment::{mission_label, MissionNotificationKind};
+use super::mission_store::{now_string, Mission, MissionComment};
+use super::routes::AppState;
+use crate::util::internal_error;
+
+/// Longest accepted comment body, in characters.
+const MAX_COMMENT_BODY_CHARS: usize = 10_000;
+
+/// Most mentions recorded per comment.
+const MAX_MENTIONS: usize = 20;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CommentAction {
+    Created,
+    Updated,
+    Deleted,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateCommentRequest {
+    pub body: String,
+    /// Thread to reply to; omitted to start a new thread
+    #[serde(default)]
+    pub thread_id: Option<Uuid>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateCommentRequest {
+    pub body: String,
+}
+
+/// A thread: its first comment and the replies, oldest first.
+#[derive(Debug, Serialize)]
+pub struct CommentThread {
+    pub root: MissionComment,
+    pub replies: Vec<MissionComment>,
+}
+
+/// Users mentioned in a markdown body, sorted and deduplicated. Mentions
+/// inside code spans and code blocks are ignored, as are e-mail addresses.
+pub fn parse_mentions(body: &str) -> Vec<String> {
+    let mut mentions = Vec::new();
+    let mut in_block = false;
+    for line in body.lines() {
+        if line.trim_start().starts_with("```") {
+            in_block = !in_block;
+            continue;
+        }
+        if in_block {
+            continue;
+        }
+        // Odd-numbered segments between backticks are code spans
+        for segment in line.split('`').step_by(2) {
+            let mut prev: Option<char> = None;
+            for (i, c) in segment.char_indices() {
+                let starts_word = prev.is_none_or(|p| !is_name_char(p));
+                prev = Some(c);
+                if c != '@' || !starts_word {
+                    continue;
+                }
+                let rest = &segment[i + 1..];
+                let end = rest
+                    .find(|ch: char| !is_name_char(ch))
+                    .unwrap_or(rest.len());
+                // Trailing dots end sentences, not names
+                let name = rest[..end].trim_end_matches('.');
+                if !name.is_empty() {
+                    mentions.push(name.to_string());
+                }
+            }
+        }
+    }
+    mentions.sort();
+    mentions.dedup();
+    mentions.truncate(MAX_MENTIONS);
+    mentions
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || matches!(c, '_' | '-' | '.')
+}
+
+/// Group comments (oldest first) into threads, oldest thread first. Replies
+/// whose root is gone are dropped.
+pub fn group_threads(comments: Vec<MissionComment>) -> Vec<CommentThread> {
+    let mut threads: Vec<CommentThread> = Vec::new();
+    let mut replies = Vec::new();
+    for comment in comments {
+        if comment.is_thread_root() {
+            threads.push(CommentThread {
+                root: comment,
+                replies: Vec::new(),
+            });
+        } else {
+            replies.push(comment);
+        }
+    }
+    for reply in replies {
+        if let Some(thread) = threads.iter_mut().find(|t| t.root.id == reply.thread_id) {
+            thread.replies.push(reply);
+        }
+    }
+    threads
+}
+
+fn validate_body(body: &str) -> Result<String, (StatusCode, String)> {
+    let body = body.trim();
+    if body.is_empty() || body.chars().count() > MAX_COMMENT_BODY_CHARS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "Comments must be between 1 and {} characters",
+                MAX_COMMENT_BODY_CHARS
+            ),
+        ));
+    }
+    Ok(body.to_string())
+}
+
+async fn load_mission(
+    state: &Arc<AppState>,
+    user: &AuthUser,
+    id: Uuid,
+) -> Result<(ControlState, Mission), (StatusCode, String)> {
+    let control = state.control.get_or_spawn(user).await;
+    let mission = control
+        .mission_store
+        .get_mission(id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Mission {} not found", id)))?;
+    Ok((control, mission))
+}
+
+/// Load a comment of `mission_id` that `user` wrote.
+async fn load_own_comment(
+    control: &ControlState,
+    user: &AuthUser,
+    mission_id: Uuid,
+    comment_id: Uuid,
+) -> Result<MissionComment, (StatusCode, String)> {
+    let comment = control
+        .mission_store
+        .get_mission_comment(comment_id)
+        .await
+        .map_err(internal_error)?
+        .filter(|c| c.mission_id == mission_id)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Comment {} not found", comment_id),
+            )
+        })?;
+    if comment.author != user.username {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "Only the author can change a comment".to_string(),
+        ));
+    }
+    Ok(comment)
+}
+
+/// Notify users newly mentioned by `user` in a comment.
+fn notify_mentions(
+    control: &ControlState,
+    user: &AuthUser,
+    mission: &Mission,
+    mentions: &[String],
+    already_mentioned: &[String],
+) {
+    let recipients: Vec<String> = mentions
+        .iter()
+        .filter(|m| **m != user.username && !already_mentioned.contains(m))
+        .cloned()
+        .collect();
+    if recipients.is_empty() {
+        return;
+    }
+    let _ = control.events_tx.send(AgentEvent::MissionNotification {
+        mission_id: mission.id,
+        recipients,
+        kind: MissionNotificationKind::Mentioned,
+        message: format!(
+            "{} mentioned you on {}",
+            user.username,
+            mission_label(mission)
+        ),
+    });
+}
+
+/// GET /api/missions/:id/comments - The mission's comment threads.
+pub async fn list_comments(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<CommentThread>>, (StatusCode, String)> {
+    let (control, _) = load_mission(&state, &user, id).await?;
+    let comments = control
+        .mission_store
+        .list_mission_comments(id)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(group_threads(comments)))
+}
+
+/// POST /api/missions/:id/comments - Start a thread or reply to one.
+pub async fn create_comment(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+    Json(req): Json<CreateCommentRequest>,
+) -> Result<(StatusCode, Json<MissionComment>), (StatusCode, String)> {
+    let body = validate_body(&req.body)?;
+    let (control, mission) = load_mission(&state, &user, id).await?;
+    let store = &control.mission_store;
+
+    let comment_id = Uuid::new_v4();
+    let thread_id = match req.thread_id {
+        Some(thread_id) => {
+            let root = store
+                .get_mission_comment(thread_id)
+                .await
+                .map_err(internal_error)?
+                .filter(|c| c.mission_id == id && c.is_thread_root())
+                .ok_or_else(|| {
+                    (
+                        StatusCode::NOT_FOUND,
+                        format!("Thread {} not found", thread_id),
+                    )
+                })?;
+            root.id
+        }
+        None => comment_id,
+    };
+
+    let comment = MissionComment {
+        id: comment_id,
+        mission_id: id,
+        thread_id,
+        author: user.username.clone(),
+        mentions: parse_mentions(&body),
+        body,
+        created_at: now_string(),
+        updated_at: None,
+    };
+    store
+        .create_mission_comment(comment.clone())
+        .await
+        .map_err(internal_error)?;
+
+    notify_mentions(&control, &user, &mission, &comment.mentions, &[]);
+    let _ = control.events_tx.send(AgentEvent::MissionComment {
+        action: CommentAction::Created,
+        comment: comment.clone(),
+    });
+    Ok((StatusCode::CREATED, Json(comment)))
+}
+
+/// PATCH /api/missions/:id/comments/:comment_id - Edit one of your comments.
+pub async fn update_comment(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path((id, comment_id)): Path<(Uuid, Uuid)>,
+    Json(req): Json<UpdateCommentRequest>,
+) -> Result<Json<MissionComment>, (StatusCode, String)> {
+    let body = validate_body(&req.body)?;
+    let (control, mission) = load_mission(&state, &user, id).await?;
+    let mut comment = load_own_comment(&control, &user, id, comment_id).await?;
+
+    let previous_mentions = std::mem::take(&mut comment.mentions);
+    comment.mentions = parse_mentions(&body);
+    comment.body = body;
+    comment.updated_at = Some(now_string());
+    control
+        .mission_store
+        .update_mission_comment(
+            comment_id,
+            &comment.body,
+            &comment.mentions,
+            comment.updated_at.as_deref().unwrap_or_default(),
+        )
+        .await
+        .map_err(internal_error)?;
+
+    notify_mentions(
+        &control,
+        &user,
+        &mission,
+        &comment.mentions,
+        &previous_mentions,
+    );
+    let _ = control.events_tx.send(AgentEvent::MissionComment {
+        action: CommentAction::Updated,
+        comment: comment.clone(),
+    });
+    Ok(Json(comment))
+}
+
+/// DELETE /api/missions/:id/comments/:comment_id - Delete one of your
+/// comments. Deleting the first comment of a thread deletes the thread.
+pub async fn delete_comment(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path((id, comment_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let (control, _) = load_mission(&state, &user, id).await?;
+    let comment = load_own_comment(&control, &user, id, comment_id).await?;
+    control
+        .mission_store
+        .delete_mission_comment(comment_id)
+        .await
+        .map_err(internal_error)?;
+
+    let _ = control.events_tx.send(AgentEvent::MissionComment {
+        action: CommentAction::Deleted,
+        comment,
+    });
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn comment(id: Uuid, thread_id: Uuid, body: &str) -> MissionComment {
+        MissionComment {
+            id,
+            mission_id: Uuid::nil(),
+            thread_id,
+            author: "alice".to_string(),
+            body: body.to_string(),
+            mentions: Vec::new(),
+            created_at: now_string(),
+            updated_at: None,
+        }
+    }
+
+    #[test]
+    fn mentions_skip_code_and_email_addresses() {
+        let body = "@bob can you check this? cc @carol.\n\
+                    Mail ops@example.com, not `@dave`.\n\
+                    ```\n@erin\n```\n\
+                    Thanks @bob";
+        assert_eq!(parse_mentions(body), vec!["bob", "carol"]);
+        assert!(parse_mentions("no mentions @ all").is_empty());
+    }
+
+    #[test]
+    fn replies_are_grouped_under_their_thread() {
+        let first = Uuid::new_v4();
+        let second = Uuid::new_v4();
+        let reply = Uuid::new_v4();
+        let threads = group_threads(vec![
+            comment(first, first, "first"),
+            comment(second, second, "second"),
+            comment(reply, first, "reply"),
+            comment(Uuid::new_v4(), Uuid::new_v4(), "orphan"),
+        ]);
+        assert_eq!(threads.len(), 2);
+        assert_eq!(threads[0].root.id, first);
+        assert_eq!(threads[0].replies.len(), 1);
+        assert_eq!(threads[0].replies[0].id, reply);
+        assert!(threads[1].replies.is_empty());
+    }
+}
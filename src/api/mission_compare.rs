@@ -0,0 +1,353 @@
+//! Side-by-side comparison of two missions.
+//!
+//! `GET /api/missions/compare?a=<id>&b=<id>` folds both missions' stored
+//! events into summaries (prompts, models, tool calls, files changed, cost,
+//! duration) and diffs them, e.g. to see why two runs of the same automation
+//! ended differently. Tool call sequences are aligned on tool names (longest
+//! common subsequence), so a run that took one extra step shows up as a
+//! single one-sided entry rather than a shifted sequence.
+
+use std::collections::BTreeSet;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::control::{activity_label_from_tool_call, MissionStatus};
+use super::mission_report::ReportBuilder;
+use super::mission_store::{Mission, MissionStore, StoredEvent};
+use super::routes::AppState;
+use super::timeline::TimelineBuilder;
+use crate::util::internal_error;
+
+const PAGE_SIZE: usize = 2000;
+
+/// Tool calls per mission taken into the alignment (it is quadratic).
+const MAX_ALIGNED_TOOL_CALLS: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+pub struct CompareQuery {
+    pub a: Uuid,
+    pub b: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ComparedToolCall {
+    pub name: String,
+    /// Activity label (e.g. "Reading: src/main.rs")
+    pub label: String,
+}
+
+/// One mission's side of the comparison.
+#[derive(Debug, Clone, Serialize)]
+pub struct MissionSummary {
+    pub mission_id: Uuid,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    pub status: MissionStatus,
+    pub backend: String,
+    /// Models that answered, in first-use order (the override when none did)
+    pub models: Vec<String>,
+    /// User messages, in order
+    pub prompts: Vec<String>,
+    pub tool_calls: usize,
+    pub files_changed: Vec<String>,
+    pub cost_cents: u64,
+    pub duration_secs: f64,
+}
+
+/// Which missions an aligned tool call step appears in.
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StepPresence {
+    Both,
+    OnlyA,
+    OnlyB,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AlignedToolCall {
+    pub presence: StepPresence,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub a: Option<ComparedToolCall>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub b: Option<ComparedToolCall>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct FileDiff {
+    pub both: Vec<String>,
+    pub only_a: Vec<String>,
+    pub only_b: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MissionComparison {
+    pub a: MissionSummary,
+    pub b: MissionSummary,
+    pub same_prompts: bool,
+    pub same_models: bool,
+    pub tool_calls: Vec<AlignedToolCall>,
+    /// Whether tool calls past the alignment limit were left out
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub tool_calls_truncated: bool,
+    pub files: FileDiff,
+    /// `b` minus `a`
+    pub cost_delta_cents: i64,
+    pub duration_delta_secs: f64,
+}
+
+/// Folds a mission's events (in order) into its summary and tool calls.
+struct SummaryBuilder {
+    timeline: TimelineBuilder,
+    report: ReportBuilder,
+    models: Vec<String>,
+    prompts: Vec<String>,
+    tool_calls: Vec<ComparedToolCall>,
+}
+
+impl SummaryBuilder {
+    fn new() -> Self {
+        Self {
+            timeline: TimelineBuilder::new(),
+            report: ReportBuilder::default(),
+            models: Vec::new(),
+            prompts: Vec::new(),
+            tool_calls: Vec::new(),
+        }
+    }
+
+    fn push(&mut self, event: &StoredEvent) {
+        self.timeline.push(event);
+        self.report.push(event);
+        match event.event_type.as_str() {
+            "user_message" => self.prompts.push(event.content.clone()),
+            "assistant_message" => {
+                if let Some(model) = event.metadata.get("model").and_then(|v| v.as_str()) {
+                    if !self.models.iter().any(|m| m == model) {
+                        self.models.push(model.to_string());
+                    }
+                }
+            }
+            "tool_call" => {
+                let name = event.tool_name.clone().unwrap_or_default();
+                let args = serde_json::from_str(&event.content).unwrap_or(serde_json::Value::Null);
+                self.tool_calls.push(ComparedToolCall {
+                    label: activity_label_from_tool_call(&name, &args),
+                    name,
+                });
+            }
+            _ => {}
+        }
+    }
+
+    fn finish(mut self, mission: Mission) -> (MissionSummary, Vec<ComparedToolCall>) {
+        let timeline = self.timeline.finish(mission.id);
+        let report = self.report.finish(mission.id, mission.status);
+        if self.models.is_empty() {
+            self.models.extend(mission.model_override.clone());
+        }
+        let summary = MissionSummary {
+            mission_id: mission.id,
+            title: mission.title,
+            status: mission.status,
+            backend: mission.backend,
+            models: self.models,
+            prompts: self.prompts,
+            tool_calls: self.tool_calls.len(),
+            files_changed: report.files_touched.into_iter().map(|f| f.path).collect(),
+            cost_cents: timeline.total_cost_cents,
+            duration_secs: timeline.total_duration_secs,
+        };
+        (summary, self.tool_calls)
+    }
+}
+
+/// Align two tool call sequences on their tool names.
+fn align_tool_calls(a: &[ComparedToolCall], b: &[ComparedToolCall]) -> Vec<AlignedToolCall> {
+    // lcs[i][j]: longest common subsequence of a[i..] and b[j..]
+    let width = b.len() + 1;
+    let mut lcs = vec![0u32; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i * width + j] = if a[i].name == b[j].name {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut aligned = Vec::with_capacity(a.len().max(b.len()));
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        let step = if i < a.len() && j < b.len() && a[i].name == b[j].name {
+            StepPresence::Both
+        } else if j == b.len()
+            || (i < a.len() && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1])
+        {
+            StepPresence::OnlyA
+        } else {
+            StepPresence::OnlyB
+        };
+        let (call_a, call_b) = match step {
+            StepPresence::Both => (Some(a[i].clone()), Some(b[j].clone())),
+            StepPresence::OnlyA => (Some(a[i].clone()), None),
+            StepPresence::OnlyB => (None, Some(b[j].clone())),
+        };
+        if call_a.is_some() {
+            i += 1;
+        }
+        if call_b.is_some() {
+            j += 1;
+        }
+        aligned.push(AlignedToolCall {
+            presence: step,
+            a: call_a,
+            b: call_b,
+        });
+    }
+    aligned
+}
+
+fn diff_files(a: &[String], b: &[String]) -> FileDiff {
+    let a: BTreeSet<&String> = a.iter().collect();
+    let b: BTreeSet<&String> = b.iter().collect();
+    FileDiff {
+        both: a.intersection(&b).map(|p| p.to_string()).collect(),
+        only_a: a.difference(&b).map(|p| p.to_string()).collect(),
+        only_b: b.difference(&a).map(|p| p.to_string()).collect(),
+    }
+}
+
+fn compare(
+    (a, a_calls): (MissionSummary, Vec<ComparedToolCall>),
+    (b, b_calls): (MissionSummary, Vec<ComparedToolCall>),
+) -> MissionComparison {
+    let tool_calls_truncated =
+        a_calls.len() > MAX_ALIGNED_TOOL_CALLS || b_calls.len() > MAX_ALIGNED_TOOL_CALLS;
+    let tool_calls = align_tool_calls(
+        &a_calls[..a_calls.len().min(MAX_ALIGNED_TOOL_CALLS)],
+        &b_calls[..b_calls.len().min(MAX_ALIGNED_TOOL_CALLS)],
+    );
+    MissionComparison {
+        same_prompts: a.prompts == b.prompts,
+        same_models: a.models == b.models,
+        tool_calls,
+        tool_calls_truncated,
+        files: diff_files(&a.files_changed, &b.files_changed),
+        cost_delta_cents: b.cost_cents as i64 - a.cost_cents as i64,
+        duration_delta_secs: b.duration_secs - a.duration_secs,
+        a,
+        b,
+    }
+}
+
+async fn summarize(
+    store: &Arc<dyn MissionStore>,
+    mission_id: Uuid,
+) -> Result<(MissionSummary, Vec<ComparedToolCall>), (StatusCode, String)> {
+    let mission = store
+        .get_mission(mission_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Mission {} not found", mission_id),
+            )
+        })?;
+
+    let mut builder = SummaryBuilder::new();
+    let mut offset = 0;
+    loop {
+        let page = store
+            .get_events(mission_id, None, Some(PAGE_SIZE), Some(offset))
+            .await
+            .map_err(internal_error)?;
+        for event in &page {
+            builder.push(event);
+        }
+        if page.len() < PAGE_SIZE {
+            break;
+        }
+        offset += page.len();
+    }
+    Ok(builder.finish(mission))
+}
+
+/// GET /api/missions/compare?a=&b= - Structured comparison of two missions.
+pub async fn compare_missions(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Query(query): Query<CompareQuery>,
+) -> Result<Json<MissionComparison>, (StatusCode, String)> {
+    let control = state.control.get_or_spawn(&user).await;
+    let store = &control.mission_store;
+    let a = summarize(store, query.a).await?;
+    let b = summarize(store, query.b).await?;
+    Ok(Json(compare(a, b)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn calls(names: &[&str]) -> Vec<ComparedToolCall> {
+        names
+            .iter()
+            .map(|name| ComparedToolCall {
+                name: name.to_string(),
+                label: name.to_string(),
+            })
+            .collect()
+    }
+
+    fn presence(aligned: &[AlignedToolCall]) -> Vec<(StepPresence, &str)> {
+        aligned
+            .iter()
+            .map(|step| {
+                let call = step.a.as_ref().or(step.b.as_ref()).unwrap();
+                (step.presence, call.name.as_str())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn aligns_extra_and_missing_steps() {
+        let a = calls(&["Read", "Edit", "Bash"]);
+        let b = calls(&["Read", "Grep", "Edit", "Write"]);
+        let aligned = align_tool_calls(&a, &b);
+        assert_eq!(
+            presence(&aligned),
+            vec![
+                (StepPresence::Both, "Read"),
+                (StepPresence::OnlyB, "Grep"),
+                (StepPresence::Both, "Edit"),
+                (StepPresence::OnlyA, "Bash"),
+                (StepPresence::OnlyB, "Write"),
+            ]
+        );
+        assert!(align_tool_calls(&[], &[]).is_empty());
+    }
+
+    #[test]
+    fn diffs_changed_files() {
+        let a = vec!["src/a.rs".to_string(), "src/both.rs".to_string()];
+        let b = vec!["src/both.rs".to_string(), "src/b.rs".to_string()];
+        assert_eq!(
+            diff_files(&a, &b),
+            FileDiff {
+                both: vec!["src/both.rs".to_string()],
+                only_a: vec!["src/a.rs".to_string()],
+                only_b: vec!["src/b.rs".to_string()],
+            }
+        );
+    }
+}
@@ -0,0 +1,255 @@
+//! What a mission ran with.
+//!
+//! The first turn of a mission records the resolved backend, model, config
+//! profile, agent version, workspace, sandbox settings and the tools available
+//! to it in [`ENVIRONMENT_FILE`] in the mission directory. Later turns leave
+//! the snapshot alone, so it keeps describing the mission's start even after a
+//! backend handoff or a workspace edit. Served by
+//! `GET /api/missions/:id/environment`.
+
+use std::path::{Path as FsPath, PathBuf};
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::routes::AppState;
+use crate::egress::EgressPolicy;
+use crate::mcp::McpServerConfig;
+use crate::util::internal_error;
+use crate::workspace::{self, TailscaleMode, Workspace, WorkspaceType};
+
+/// Snapshot file, relative to the mission directory.
+pub const ENVIRONMENT_FILE: &str = ".sandboxed-sh/environment.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissionEnvironment {
+    pub mission_id: Uuid,
+    pub captured_at: String,
+    pub backend: String,
+    /// Model after applying the mission override and profile defaults
+    /// (`None` when the backend picks its own)
+    pub model: Option<String>,
+    pub model_effort: Option<String>,
+    pub config_profile: Option<String>,
+    pub agent: Option<String>,
+    pub agent_version: Option<String>,
+    pub env_profile: Option<String>,
+    pub workspace: WorkspaceSnapshot,
+    pub sandbox: SandboxSnapshot,
+    pub tools: ToolSnapshot,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceSnapshot {
+    pub id: Uuid,
+    pub name: String,
+    pub workspace_type: WorkspaceType,
+    pub path: PathBuf,
+    pub mission_dir: PathBuf,
+    pub distro: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SandboxSnapshot {
+    pub dry_run: bool,
+    pub shared_network: Option<bool>,
+    pub tailscale_mode: Option<TailscaleMode>,
+    pub egress: Option<EgressPolicy>,
+    /// Kubernetes namespaces the mission may access
+    #[serde(default)]
+    pub kubernetes_namespaces: Vec<String>,
+    /// Names of the SSH hosts the mission may run commands on
+    #[serde(default)]
+    pub ssh_hosts: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolSnapshot {
+    /// Whether the backend's CLI was found on the host (`None` for backends
+    /// that don't use one)
+    pub backend_cli_available: Option<bool>,
+    /// MCP servers enabled for the workspace, with the tools each exposed
+    pub mcp_servers: Vec<McpServerSnapshot>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct McpServerSnapshot {
+    pub name: String,
+    pub tools: Vec<String>,
+}
+
+impl MissionEnvironment {
+    /// Snapshot of a mission about to run in `workspace`. Callers fill in the
+    /// model, profile and agent fields they resolved.
+    pub fn new(
+        mission_id: Uuid,
+        backend: &str,
+        workspace: &Workspace,
+        mission_dir: &FsPath,
+        mcp_configs: Vec<McpServerConfig>,
+    ) -> Self {
+        let mcp_servers = workspace::filter_mcp_configs_for_workspace(mcp_configs, &workspace.mcps)
+            .into_iter()
+            .map(|config| McpServerSnapshot {
+                name: config.name,
+                tools: config.tools,
+            })
+            .collect();
+        Self {
+            mission_id,
+            captured_at: chrono::Utc::now().to_rfc3339(),
+            backend: backend.to_string(),
+            model: None,
+            model_effort: None,
+            config_profile: None,
+            agent: None,
+            agent_version: None,
+            env_profile: None,
+            workspace: WorkspaceSnapshot {
+                id: workspace.id,
+                name: workspace.name.clone(),
+                workspace_type: workspace.workspace_type,
+                path: workspace.path.clone(),
+                mission_dir: mission_dir.to_path_buf(),
+                distro: workspace.distro.clone(),
+            },
+            sandbox: SandboxSnapshot {
+                dry_run: false,
+                shared_network: workspace.shared_network,
+                tailscale_mode: workspace.tailscale_mode,
+                egress: workspace.egress.clone(),
+                kubernetes_namespaces: workspace
+                    .kubernetes
+                    .as_ref()
+                    .map(|k| k.namespaces.clone())
+                    .unwrap_or_default(),
+                ssh_hosts: workspace
+                    .ssh
+                    .as_ref()
+                    .map(|s| s.hosts.iter().map(|h| h.name.clone()).collect())
+                    .unwrap_or_default(),
+            },
+            tools: ToolSnapshot {
+                backend_cli_available: backend_cli(backend).map(cli_on_path),
+                mcp_servers,
+            },
+        }
+    }
+}
+
+/// CLI binary a backend runs, if any.
+fn backend_cli(backend: &str) -> Option<&'static str> {
+    match backend {
+        "claudecode" => Some("claude"),
+        "codex" => Some("codex"),
+        "amp" => Some("amp"),
+        "opencode" => Some("opencode"),
+        _ => None,
+    }
+}
+
+fn cli_on_path(name: &str) -> bool {
+    std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join(name).is_file()))
+}
+
+/// Record the snapshot unless the mission already has one.
+pub async fn capture(mission_dir: &FsPath, environment: &MissionEnvironment) {
+    let path = mission_dir.join(ENVIRONMENT_FILE);
+    if path.exists() {
+        return;
+    }
+    let result = async {
+        if let Some(parent) = path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let json = serde_json::to_vec_pretty(environment).map_err(std::io::Error::other)?;
+        tokio::fs::write(&path, json).await
+    }
+    .await;
+    if let Err(e) = result {
+        tracing::warn!(
+            mission_id = %environment.mission_id,
+            error = %e,
+            "Failed to record mission environment"
+        );
+    }
+}
+
+pub async fn load(mission_dir: &FsPath) -> Option<MissionEnvironment> {
+    let raw = tokio::fs::read(mission_dir.join(ENVIRONMENT_FILE))
+        .await
+        .ok()?;
+    serde_json::from_slice(&raw).ok()
+}
+
+/// GET /api/missions/:id/environment
+pub async fn get_mission_environment(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+) -> Result<Json<MissionEnvironment>, (StatusCode, String)> {
+    let control = state.control.get_or_spawn(&user).await;
+    let mission = control
+        .mission_store
+        .get_mission(mission_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Mission {} not found", mission_id),
+            )
+        })?;
+
+    let workspace_root = state
+        .workspaces
+        .get(mission.workspace_id)
+        .await
+        .map(|ws| ws.path)
+        .unwrap_or_else(|| state.config.working_dir.clone());
+    let mission_dir = workspace::mission_workspace_dir_for_root(&workspace_root, mission_id);
+    load(&mission_dir).await.map(Json).ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            format!(
+                "No environment recorded for mission {} (it has not run yet)",
+                mission_id
+            ),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn first_capture_wins() {
+        let dir = tempfile::tempdir().unwrap();
+        let workspace = Workspace::default_host(dir.path().to_path_buf());
+        let mission_id = Uuid::new_v4();
+
+        let mut first =
+            MissionEnvironment::new(mission_id, "claudecode", &workspace, dir.path(), vec![]);
+        first.model = Some("claude-sonnet-4".to_string());
+        capture(dir.path(), &first).await;
+
+        let mut second =
+            MissionEnvironment::new(mission_id, "codex", &workspace, dir.path(), vec![]);
+        second.model = Some("gpt-5-codex".to_string());
+        capture(dir.path(), &second).await;
+
+        let loaded = load(dir.path()).await.unwrap();
+        assert_eq!(loaded.backend, "claudecode");
+        assert_eq!(loaded.model.as_deref(), Some("claude-sonnet-4"));
+        assert_eq!(loaded.workspace.workspace_type, WorkspaceType::Host);
+    }
+}
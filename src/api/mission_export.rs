@@ -0,0 +1,809 @@
+//! Standalone HTML and PDF exports of missions.
+//!
+//! `GET /api/missions/:id/export` renders a mission as a single self-contained
+//! HTML page for people who will never open the app:
+//!
+//! - `content=transcript` (default) - the conversation, with agent messages
+//!   rendered from Markdown and each tool call collapsed into a `<details>`
+//!   block holding its arguments and result
+//! - `content=report` - the mission report (summary, files touched, tests run,
+//!   follow-ups)
+//!
+//! Images the agent shared from the mission directory are inlined as data
+//! URIs, so the page needs no server. `format=pdf` prints the same page with
+//! headless Chromium. Like share links, exports are redacted.
+
+use std::collections::HashMap;
+use std::path::{Path as FsPath, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Extension,
+};
+use base64::Engine;
+use serde::Deserialize;
+use uuid::Uuid;
+
+use super::audit::AuditEntry;
+use super::auth::AuthUser;
+use super::control::{MissionStatus, SharedFile, SharedFileKind};
+use super::mission_files::{mission_root, resolve_in_root};
+use super::mission_report::build_report;
+use super::mission_share::redact_secrets;
+use super::mission_store::{Mission, MissionReport, StoredEvent};
+use super::routes::AppState;
+use crate::util::internal_error;
+
+const PAGE_SIZE: usize = 2000;
+
+/// Tool arguments and results are cut to this many characters.
+const MAX_TOOL_TEXT_CHARS: usize = 4000;
+
+/// Largest image inlined into an export.
+const MAX_INLINE_IMAGE_BYTES: u64 = 5 * 1024 * 1024;
+
+const PDF_TIMEOUT: Duration = Duration::from_secs(90);
+
+const TRANSCRIPT_EVENTS: &[&str] = &[
+    "user_message",
+    "assistant_message",
+    "tool_call",
+    "tool_result",
+];
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, "Segoe UI", Helvetica, Arial, sans-serif; color: #1f2328; max-width: 860px; margin: 2rem auto; padding: 0 1.25rem; line-height: 1.55; }
+header { border-bottom: 1px solid #d0d7de; margin-bottom: 1.5rem; }
+header h1 { margin-bottom: 0.25rem; }
+.meta { color: #656d76; font-size: 0.9rem; margin-top: 0; }
+.message { margin: 1.25rem 0; padding: 0.75rem 1rem; border-radius: 8px; }
+.message.user { background: #f6f8fa; border-left: 4px solid #0969da; }
+.message.assistant { border-left: 4px solid #8250df; }
+.role { font-size: 0.8rem; font-weight: 600; text-transform: uppercase; color: #656d76; }
+details.tool { margin: 0.5rem 0; border: 1px solid #d0d7de; border-radius: 6px; padding: 0.25rem 0.75rem; font-size: 0.9rem; }
+details.tool summary { cursor: pointer; color: #656d76; }
+pre { background: #f6f8fa; padding: 0.75rem; border-radius: 6px; overflow-x: auto; white-space: pre-wrap; word-break: break-word; font-size: 0.85rem; }
+code { font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; }
+:not(pre) > code { background: #eff1f3; padding: 0.1em 0.3em; border-radius: 4px; }
+table { border-collapse: collapse; margin: 0.75rem 0; }
+th, td { border: 1px solid #d0d7de; padding: 0.3rem 0.6rem; text-align: left; }
+blockquote { margin: 0.5rem 0; padding-left: 1rem; border-left: 3px solid #d0d7de; color: #656d76; }
+img { max-width: 100%; border: 1px solid #d0d7de; border-radius: 6px; }
+.files { font-size: 0.9rem; color: #656d76; }
+@media print { details.tool { break-inside: avoid; } }
+"#;
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    #[default]
+    Html,
+    Pdf,
+}
+
+#[derive(Debug, Clone, Copy, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportContent {
+    #[default]
+    Transcript,
+    Report,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExportQuery {
+    #[serde(default)]
+    pub format: ExportFormat,
+    #[serde(default)]
+    pub content: ExportContent,
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Links are kept only for schemes that are safe in a standalone page.
+fn safe_url(url: &str) -> bool {
+    let lower = url.trim().to_ascii_lowercase();
+    ["http://", "https://", "mailto:", "#"]
+        .iter()
+        .any(|scheme| lower.starts_with(scheme))
+}
+
+/// Render inline Markdown: code spans, bold, italics, links and images.
+fn render_inline(text: &str) -> String {
+    let mut out = String::new();
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        if c == '`' {
+            if let Some(end) = rest[1..].find('`') {
+                out.push_str("<code>");
+                out.push_str(&escape_html(&rest[1..1 + end]));
+                out.push_str("</code>");
+                rest = &rest[end + 2..];
+                continue;
+            }
+        }
+        if let Some(inner) = rest.strip_prefix("**") {
+            if let Some(end) = inner.find("**").filter(|&end| end > 0) {
+                out.push_str("<strong>");
+                out.push_str(&render_inline(&inner[..end]));
+                out.push_str("</strong>");
+                rest = &inner[end + 2..];
+                continue;
+            }
+        }
+        if c == '*' {
+            if let Some(end) = rest[1..].find('*').filter(|&end| end > 0) {
+                out.push_str("<em>");
+                out.push_str(&render_inline(&rest[1..1 + end]));
+                out.push_str("</em>");
+                rest = &rest[end + 2..];
+                continue;
+            }
+        }
+        let (is_image, link) = match rest.strip_prefix("![") {
+            Some(link) => (true, Some(link)),
+            None => (false, rest.strip_prefix('[')),
+        };
+        if let Some((label, url, after)) = link.and_then(parse_link) {
+            if !safe_url(url) {
+                out.push_str(&render_inline(label));
+            } else if is_image {
+                out.push_str(&format!(
+                    "<img src=\"{}\" alt=\"{}\">",
+                    escape_html(url),
+                    escape_html(label)
+                ));
+            } else {
+                out.push_str(&format!(
+                    "<a href=\"{}\">{}</a>",
+                    escape_html(url),
+                    render_inline(label)
+                ));
+            }
+            rest = after;
+            continue;
+        }
+        out.push_str(&escape_html(&rest[..c.len_utf8()]));
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+/// Split `label](url)rest` (the text after `[`).
+fn parse_link(text: &str) -> Option<(&str, &str, &str)> {
+    let (label, after) = text.split_once("](")?;
+    let (url, rest) = after.split_once(')')?;
+    if label.contains('\n') || url.contains(char::is_whitespace) {
+        return None;
+    }
+    Some((label, url, rest))
+}
+
+fn list_item(line: &str) -> Option<(bool, &str)> {
+    let trimmed = line.trim_start();
+    for marker in ["- ", "* ", "+ "] {
+        if let Some(item) = trimmed.strip_prefix(marker) {
+            return Some((false, item));
+        }
+    }
+    let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 {
+        if let Some(item) = trimmed[digits..].strip_prefix(". ") {
+            return Some((true, item));
+        }
+    }
+    None
+}
+
+fn table_cells(line: &str) -> Vec<&str> {
+    line.trim()
+        .trim_start_matches('|')
+        .trim_end_matches('|')
+        .split('|')
+        .map(str::trim)
+        .collect()
+}
+
+fn is_table_separator(line: &str) -> bool {
+    let line = line.trim();
+    line.starts_with('|')
+        && line.contains('-')
+        && line.chars().all(|c| matches!(c, '|' | '-' | ':' | ' '))
+}
+
+/// Render agent Markdown to HTML: headings, paragraphs, fenced code, lists,
+/// block quotes, rules and pipe tables. Raw HTML is escaped.
+pub fn markdown_to_html(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut out = String::new();
+    let mut paragraph: Vec<&str> = Vec::new();
+    let flush = |paragraph: &mut Vec<&str>, out: &mut String| {
+        if !paragraph.is_empty() {
+            out.push_str(&format!(
+                "<p>{}</p>\n",
+                render_inline(&paragraph.join("\n"))
+            ));
+            paragraph.clear();
+        }
+    };
+
+    let mut i = 0;
+    while i < lines.len() {
+        let line = lines[i];
+        let trimmed = line.trim();
+
+        if let Some(info) = trimmed.strip_prefix("```") {
+            flush(&mut paragraph, &mut out);
+            let mut code = Vec::new();
+            i += 1;
+            while i < lines.len() && !lines[i].trim_start().starts_with("```") {
+                code.push(lines[i]);
+                i += 1;
+            }
+            let class = info
+                .split_whitespace()
+                .next()
+                .map(|lang| format!(" class=\"language-{}\"", escape_html(lang)))
+                .unwrap_or_default();
+            out.push_str(&format!(
+                "<pre><code{}>{}</code></pre>\n",
+                class,
+                escape_html(&code.join("\n"))
+            ));
+            i += 1;
+            continue;
+        }
+
+        if trimmed.is_empty() {
+            flush(&mut paragraph, &mut out);
+            i += 1;
+            continue;
+        }
+
+        let level = trimmed.chars().take_while(|&c| c == '#').count();
+        if (1..=6).contains(&level) && trimmed[level..].starts_with(' ') {
+            flush(&mut paragraph, &mut out);
+            out.push_str(&format!(
+                "<h{level}>{}</h{level}>\n",
+                render_inline(trimmed[level..].trim())
+            ));
+            i += 1;
+            continue;
+        }
+
+        if trimmed.len() >= 3
+            && ['-', '*', '_']
+                .iter()
+                .any(|&rule| trimmed.chars().all(|c| c == rule))
+        {
+            flush(&mut paragraph, &mut out);
+            out.push_str("<hr>\n");
+            i += 1;
+            continue;
+        }
+
+        if trimmed.starts_with('|') && lines.get(i + 1).is_some_and(|l| is_table_separator(l)) {
+            flush(&mut paragraph, &mut out);
+            out.push_str("<table>\n<tr>");
+            for cell in table_cells(trimmed) {
+                out.push_str(&format!("<th>{}</th>", render_inline(cell)));
+            }
+            out.push_str("</tr>\n");
+            i += 2;
+            while i < lines.len() && lines[i].trim().starts_with('|') {
+                out.push_str("<tr>");
+                for cell in table_cells(lines[i]) {
+                    out.push_str(&format!("<td>{}</td>", render_inline(cell)));
+                }
+                out.push_str("</tr>\n");
+                i += 1;
+            }
+            out.push_str("</table>\n");
+            continue;
+        }
+
+        if trimmed.starts_with('>') {
+            flush(&mut paragraph, &mut out);
+            let mut quote = Vec::new();
+            while i < lines.len() && lines[i].trim().starts_with('>') {
+                quote.push(lines[i].trim().trim_start_matches('>').trim_start());
+                i += 1;
+            }
+            out.push_str(&format!(
+                "<blockquote>{}</blockquote>\n",
+                markdown_to_html(&quote.join("\n"))
+            ));
+            continue;
+        }
+
+        if let Some((ordered, _)) = list_item(line) {
+            flush(&mut paragraph, &mut out);
+            let tag = if ordered { "ol" } else { "ul" };
+            out.push_str(&format!("<{}>\n", tag));
+            while let Some((item_ordered, item)) = lines.get(i).and_then(|l| list_item(l)) {
+                if item_ordered != ordered {
+                    break;
+                }
+                out.push_str(&format!("<li>{}</li>\n", render_inline(item)));
+                i += 1;
+            }
+            out.push_str(&format!("</{}>\n", tag));
+            continue;
+        }
+
+        paragraph.push(trimmed);
+        i += 1;
+    }
+    flush(&mut paragraph, &mut out);
+    out
+}
+
+fn truncate_chars(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((cut, _)) => format!(
+            "{}\n… ({} more characters)",
+            &text[..cut],
+            text[cut..].chars().count()
+        ),
+        None => text.to_string(),
+    }
+}
+
+/// Tool arguments or result as display text: JSON strings are unwrapped and
+/// other JSON is pretty-printed.
+fn tool_text(content: &str) -> String {
+    let text = match serde_json::from_str::<serde_json::Value>(content) {
+        Ok(serde_json::Value::String(text)) => text,
+        Ok(value) => serde_json::to_string_pretty(&value).unwrap_or_else(|_| content.to_string()),
+        Err(_) => content.to_string(),
+    };
+    truncate_chars(&redact_secrets(&text), MAX_TOOL_TEXT_CHARS)
+}
+
+fn shared_files(event: &StoredEvent) -> Vec<SharedFile> {
+    event
+        .metadata
+        .get("shared_files")
+        .and_then(|files| serde_json::from_value(files.clone()).ok())
+        .unwrap_or_default()
+}
+
+/// Render a list of shared files; images with an inlined copy are shown.
+fn render_files(files: &[SharedFile], images: &HashMap<String, String>) -> String {
+    let mut out = String::new();
+    let mut others = Vec::new();
+    for file in files {
+        match images.get(&file.url) {
+            Some(data_uri) if file.kind == SharedFileKind::Image => out.push_str(&format!(
+                "<figure><img src=\"{}\" alt=\"{}\"><figcaption class=\"files\">{}</figcaption></figure>\n",
+                data_uri,
+                escape_html(&file.name),
+                escape_html(&file.name)
+            )),
+            _ => others.push(format!("<li>{}</li>", escape_html(&file.name))),
+        }
+    }
+    if !others.is_empty() {
+        out.push_str(&format!("<ul class=\"files\">{}</ul>\n", others.concat()));
+    }
+    out
+}
+
+/// The transcript body: messages in order, tool calls collapsed with their
+/// results.
+pub fn render_transcript(events: &[StoredEvent], images: &HashMap<String, String>) -> String {
+    let results: HashMap<&str, &str> = events
+        .iter()
+        .filter(|e| e.event_type == "tool_result")
+        .filter_map(|e| Some((e.tool_call_id.as_deref()?, e.content.as_str())))
+        .collect();
+
+    let mut out = String::new();
+    for event in events {
+        match event.event_type.as_str() {
+            "user_message" | "assistant_message" => {
+                let (class, role) = if event.event_type == "user_message" {
+                    ("user", "User")
+                } else {
+                    ("assistant", "Agent")
+                };
+                out.push_str(&format!(
+                    "<section class=\"message {}\">\n<div class=\"role\">{} · {}</div>\n{}{}</section>\n",
+                    class,
+                    role,
+                    escape_html(&event.timestamp),
+                    markdown_to_html(&redact_secrets(&event.content)),
+                    render_files(&shared_files(event), images)
+                ));
+            }
+            "tool_call" => {
+                let name = event.tool_name.as_deref().unwrap_or("tool");
+                let result = event
+                    .tool_call_id
+                    .as_deref()
+                    .and_then(|id| results.get(id))
+                    .map(|result| format!("<pre>{}</pre>\n", escape_html(&tool_text(result))))
+                    .unwrap_or_default();
+                out.push_str(&format!(
+                    "<details class=\"tool\"><summary>Tool call: {}</summary>\n<pre>{}</pre>\n{}</details>\n",
+                    escape_html(name),
+                    escape_html(&tool_text(&event.content)),
+                    result
+                ));
+            }
+            _ => {}
+        }
+    }
+    out
+}
+
+/// The report body: summary, files touched, tests run and follow-ups.
+pub fn render_report(
+    report: &MissionReport,
+    files: &[SharedFile],
+    images: &HashMap<String, String>,
+) -> String {
+    let mut out = String::new();
+    out.push_str("<h2>Summary</h2>\n");
+    match report.summary.as_deref() {
+        Some(summary) => out.push_str(&markdown_to_html(&redact_secrets(summary))),
+        None => out.push_str("<p>No summary.</p>\n"),
+    }
+    if !report.files_touched.is_empty() {
+        out.push_str("<h2>Files touched</h2>\n<ul>\n");
+        for file in &report.files_touched {
+            out.push_str(&format!(
+                "<li><code>{}</code> ({})</li>\n",
+                escape_html(&file.path),
+                escape_html(&file.tools.join(", "))
+            ));
+        }
+        out.push_str("</ul>\n");
+    }
+    if !report.tests_run.is_empty() {
+        out.push_str("<h2>Tests run</h2>\n<ul>\n");
+        for test in &report.tests_run {
+            let outcome = match test.passed {
+                Some(true) => "passed",
+                Some(false) => "failed",
+                None => "unknown",
+            };
+            out.push_str(&format!(
+                "<li><code>{}</code> - {}</li>\n",
+                escape_html(&redact_secrets(&test.command)),
+                outcome
+            ));
+        }
+        out.push_str("</ul>\n");
+    }
+    if !report.follow_ups.is_empty() {
+        out.push_str("<h2>Follow-ups</h2>\n<ul>\n");
+        for follow_up in &report.follow_ups {
+            out.push_str(&format!("<li>{}</li>\n", render_inline(follow_up)));
+        }
+        out.push_str("</ul>\n");
+    }
+    if !files.is_empty() {
+        out.push_str("<h2>Shared files</h2>\n");
+        out.push_str(&render_files(files, images));
+    }
+    out
+}
+
+/// Wrap a body into a standalone page.
+fn page(mission: &Mission, body: &str) -> String {
+    let title = mission
+        .title
+        .as_deref()
+        .map(redact_secrets)
+        .unwrap_or_else(|| format!("Mission {}", mission.id));
+    format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<header>\n<h1>{title}</h1>\n<p class=\"meta\">Status: {status} · Created {created} · Exported {exported}</p>\n</header>\n{body}</body>\n</html>\n",
+        title = escape_html(&title),
+        status = mission.status,
+        created = escape_html(&mission.created_at),
+        exported = chrono::Utc::now().format("%Y-%m-%d %H:%M UTC"),
+    )
+}
+
+/// The file path named by a shared file's download URL.
+fn shared_file_path(url: &str) -> Option<String> {
+    let (_, query) = url.split_once('?')?;
+    query
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("path="))
+        .and_then(|path| urlencoding::decode(path).ok())
+        .map(|path| path.into_owned())
+}
+
+/// Data URIs for the shared images that live in the mission directory, keyed
+/// by their URL.
+async fn inline_images(root: Option<&FsPath>, files: &[SharedFile]) -> HashMap<String, String> {
+    let mut images = HashMap::new();
+    let Some(root) = root else {
+        return images;
+    };
+    for file in files.iter().filter(|f| f.kind == SharedFileKind::Image) {
+        let Some(path) = shared_file_path(&file.url) else {
+            continue;
+        };
+        let Ok(resolved) = resolve_in_root(root, &path) else {
+            continue;
+        };
+        let fits = tokio::fs::metadata(&resolved)
+            .await
+            .is_ok_and(|m| m.is_file() && m.len() <= MAX_INLINE_IMAGE_BYTES);
+        if !fits {
+            continue;
+        }
+        if let Ok(bytes) = tokio::fs::read(&resolved).await {
+            let encoded = base64::engine::general_purpose::STANDARD.encode(bytes);
+            images.insert(
+                file.url.clone(),
+                format!(
+                    "data:{};base64,{}",
+                    escape_html(&file.content_type),
+                    encoded
+                ),
+            );
+        }
+    }
+    images
+}
+
+/// Print an HTML page to PDF with headless Chromium.
+async fn print_pdf(html: &str) -> Result<Vec<u8>, (StatusCode, String)> {
+    let browser = crate::tools::desktop::find_browser_command().ok_or_else(|| {
+        (
+            StatusCode::SERVICE_UNAVAILABLE,
+            "PDF export needs a Chromium-compatible browser (set CHROMIUM_BIN or install \
+             chromium); use format=html instead"
+                .to_string(),
+        )
+    })?;
+    let dir = std::env::temp_dir().join(format!("sandboxed-export-{}", Uuid::new_v4()));
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .map_err(internal_error)?;
+    let printed = print_pdf_in(&browser, &dir, html).await;
+    let _ = tokio::fs::remove_dir_all(&dir).await;
+    printed
+}
+
+async fn print_pdf_in(
+    browser: &str,
+    dir: &FsPath,
+    html: &str,
+) -> Result<Vec<u8>, (StatusCode, String)> {
+    let input: PathBuf = dir.join("export.html");
+    let output = dir.join("export.pdf");
+    tokio::fs::write(&input, html)
+        .await
+        .map_err(internal_error)?;
+
+    let printed = tokio::time::timeout(
+        PDF_TIMEOUT,
+        tokio::process::Command::new(browser)
+            .args([
+                "--headless",
+                "--no-sandbox",
+                "--disable-gpu",
+                "--no-pdf-header-footer",
+            ])
+            .arg(format!("--print-to-pdf={}", output.display()))
+            .arg(format!("file://{}", input.display()))
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .map_err(|_| internal_error("Headless browser timed out printing the export"))?
+    .map_err(|e| internal_error(format!("Failed to start {}: {}", browser, e)))?;
+
+    if !printed.status.success() || !output.exists() {
+        return Err(internal_error(format!(
+            "Headless browser failed to print the export: {}",
+            String::from_utf8_lossy(&printed.stderr).trim()
+        )));
+    }
+    tokio::fs::read(&output).await.map_err(internal_error)
+}
+
+/// GET /api/missions/:id/export - Download a mission as standalone HTML or PDF.
+pub async fn export_mission(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+    Query(q): Query<ExportQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    let control = state.control.get_or_spawn(&user).await;
+    let store = &control.mission_store;
+    let mission = store
+        .get_mission(mission_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Mission {} not found", mission_id),
+            )
+        })?;
+
+    let event_types: &[&str] = match q.content {
+        ExportContent::Transcript => TRANSCRIPT_EVENTS,
+        ExportContent::Report => &["assistant_message"],
+    };
+    let mut events = Vec::new();
+    loop {
+        let page = store
+            .get_events(
+                mission_id,
+                Some(event_types),
+                Some(PAGE_SIZE),
+                Some(events.len()),
+            )
+            .await
+            .map_err(internal_error)?;
+        let done = page.len() < PAGE_SIZE;
+        events.extend(page);
+        if done {
+            break;
+        }
+    }
+
+    let files: Vec<SharedFile> = events.iter().flat_map(shared_files).collect();
+    let root = mission_root(&state, &user, mission_id).await.ok();
+    let images = inline_images(root.as_deref(), &files).await;
+
+    let body = match q.content {
+        ExportContent::Transcript => render_transcript(&events, &images),
+        ExportContent::Report => {
+            let report = match store
+                .get_mission_report(mission_id)
+                .await
+                .map_err(internal_error)?
+            {
+                Some(report) => report,
+                None if mission.status == MissionStatus::Completed => {
+                    build_report(store, mission_id, mission.status)
+                        .await
+                        .map_err(internal_error)?
+                }
+                None => {
+                    return Err((
+                        StatusCode::NOT_FOUND,
+                        "Mission has no report until it completes".to_string(),
+                    ))
+                }
+            };
+            render_report(&report, &files, &images)
+        }
+    };
+    let html = page(&mission, &body);
+
+    let (bytes, content_type, extension) = match q.format {
+        ExportFormat::Html => (html.into_bytes(), "text/html; charset=utf-8", "html"),
+        ExportFormat::Pdf => (print_pdf(&html).await?, "application/pdf", "pdf"),
+    };
+
+    let mut entry = AuditEntry::new(
+        "export",
+        user.username.clone(),
+        format!("export mission as {}", extension),
+    );
+    entry.mission_id = Some(mission_id);
+    entry.success = true;
+    state.audit.record(&entry).await;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static(content_type));
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        format!(
+            "attachment; filename=\"mission-{}.{}\"",
+            &mission_id.to_string()[..8],
+            extension
+        )
+        .parse()
+        .unwrap_or(HeaderValue::from_static("attachment")),
+    );
+    Ok((headers, bytes).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mission_store::now_string;
+    use serde_json::json;
+
+    fn event(event_type: &str, tool: Option<(&str, &str)>, content: &str) -> StoredEvent {
+        StoredEvent {
+            id: 0,
+            mission_id: Uuid::nil(),
+            sequence: 0,
+            event_type: event_type.to_string(),
+            timestamp: now_string(),
+            event_id: None,
+            tool_call_id: tool.map(|(id, _)| id.to_string()),
+            tool_name: tool.map(|(_, name)| name.to_string()),
+            content: content.to_string(),
+            metadata: json!({}),
+            stream_seq: None,
+            schema_version: crate::api::event_schema::CURRENT_VERSION,
+        }
+    }
+
+    #[test]
+    fn markdown_is_rendered_and_html_escaped() {
+        let html = markdown_to_html(
+            "# Done <b>\n\nFixed **the** `a<b` bug, see [docs](https://example.com) \
+             and [bad](javascript:alert(1)).\n\n- one\n- two\n\n```rust\nfn main() {}\n```\n\n\
+             | a | b |\n|---|---|\n| 1 | 2 |\n",
+        );
+        assert!(html.contains("<h1>Done &lt;b&gt;</h1>"));
+        assert!(html.contains("<strong>the</strong>"));
+        assert!(html.contains("<code>a&lt;b</code>"));
+        assert!(html.contains("<a href=\"https://example.com\">docs</a>"));
+        assert!(!html.contains("javascript:"));
+        assert!(html.contains("<ul>\n<li>one</li>\n<li>two</li>\n</ul>"));
+        assert!(html.contains("<pre><code class=\"language-rust\">fn main() {}</code></pre>"));
+        assert!(html.contains("<th>a</th><th>b</th>"));
+        assert!(html.contains("<td>1</td><td>2</td>"));
+    }
+
+    #[test]
+    fn tool_calls_are_collapsed_with_their_results() {
+        let mut assistant = event("assistant_message", None, "All set.");
+        assistant.metadata = json!({
+            "shared_files": [{
+                "name": "chart.png",
+                "url": "/api/fs/download?path=%2Fm%2Fchart.png",
+                "content_type": "image/png",
+                "kind": "image"
+            }]
+        });
+        let events = vec![
+            event("user_message", None, "Plot it"),
+            event(
+                "tool_call",
+                Some(("t1", "Bash")),
+                r#"{"command":"python plot.py"}"#,
+            ),
+            event("tool_result", Some(("t1", "Bash")), r#""saved <chart>""#),
+            assistant,
+        ];
+        let images = HashMap::from([(
+            "/api/fs/download?path=%2Fm%2Fchart.png".to_string(),
+            "data:image/png;base64,AAAA".to_string(),
+        )]);
+
+        let html = render_transcript(&events, &images);
+        assert!(html.contains("<details class=\"tool\"><summary>Tool call: Bash</summary>"));
+        assert!(html.contains("python plot.py"));
+        assert!(html.contains("<pre>saved &lt;chart&gt;</pre>"));
+        assert_eq!(html.matches("<details").count(), 1);
+        assert!(html.contains("<img src=\"data:image/png;base64,AAAA\""));
+        assert_eq!(
+            shared_file_path("/api/fs/download?path=%2Fm%2Fchart.png").as_deref(),
+            Some("/m/chart.png")
+        );
+    }
+}
@@ -0,0 +1,402 @@
+//! Read-only browser for a mission's working directory.
+//!
+//! `GET /api/missions/:id/files` lists a directory, `/files/stat` describes
+//! one entry, `/files/content` returns a small text file with a syntax
+//! highlighting hint, and `/files/download` streams any file. Paths are
+//! relative to the mission directory; like rich-tag validation, every path is
+//! canonicalized and rejected unless it stays inside that directory, so
+//! symlinks cannot reach the rest of the host.
+
+use std::path::{Path as FsPath, PathBuf};
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use tokio_util::io::ReaderStream;
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::fs::{content_type_for_path, mtime_secs, FsEntry};
+use super::routes::AppState;
+use crate::util::internal_error;
+use crate::workspace;
+
+/// Largest file `/files/content` returns inline.
+const MAX_CONTENT_BYTES: u64 = 512 * 1024;
+
+/// Most entries returned for one directory.
+const MAX_DIR_ENTRIES: usize = 2000;
+
+#[derive(Debug, Deserialize)]
+pub struct MissionFileQuery {
+    /// Path relative to the mission directory (the directory itself when empty)
+    #[serde(default)]
+    pub path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MissionDirListing {
+    pub path: String,
+    /// Directories first, then files, by name
+    pub entries: Vec<FsEntry>,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MissionFileStat {
+    pub path: String,
+    pub kind: String,
+    pub size: u64,
+    pub mtime: i64,
+    pub content_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MissionFileContent {
+    pub path: String,
+    pub size: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<&'static str>,
+    /// True when the file is not text; `content` is then omitted
+    pub binary: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+}
+
+/// Syntax highlighting hint for a file, from its name.
+pub fn language_for_path(path: &FsPath) -> Option<&'static str> {
+    let name = path.file_name()?.to_str()?;
+    match name {
+        "Dockerfile" | "Containerfile" => return Some("dockerfile"),
+        "Makefile" | "makefile" | "GNUmakefile" => return Some("makefile"),
+        _ => {}
+    }
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    Some(match ext.as_str() {
+        "rs" => "rust",
+        "ts" | "mts" | "cts" => "typescript",
+        "tsx" => "tsx",
+        "js" | "mjs" | "cjs" => "javascript",
+        "jsx" => "jsx",
+        "py" => "python",
+        "go" => "go",
+        "java" => "java",
+        "kt" | "kts" => "kotlin",
+        "swift" => "swift",
+        "rb" => "ruby",
+        "php" => "php",
+        "c" | "h" => "c",
+        "cc" | "cpp" | "cxx" | "hpp" | "hh" => "cpp",
+        "cs" => "csharp",
+        "sh" | "bash" | "zsh" => "bash",
+        "sql" => "sql",
+        "html" | "htm" => "html",
+        "css" => "css",
+        "scss" => "scss",
+        "json" => "json",
+        "toml" => "toml",
+        "yaml" | "yml" => "yaml",
+        "xml" => "xml",
+        "md" | "markdown" => "markdown",
+        "nix" => "nix",
+        "proto" => "protobuf",
+        "diff" | "patch" => "diff",
+        _ => return None,
+    })
+}
+
+/// Resolve `path` inside `root` (already canonical). Missing paths are 404,
+/// paths that leave the root (through `..`, an absolute path or a symlink)
+/// are 403.
+pub fn resolve_in_root(root: &FsPath, path: &str) -> Result<PathBuf, (StatusCode, String)> {
+    let trimmed = path.trim();
+    let input = FsPath::new(trimmed);
+    let joined = if trimmed.is_empty() {
+        root.to_path_buf()
+    } else if input.is_absolute() {
+        input.to_path_buf()
+    } else {
+        root.join(input)
+    };
+    let canonical = crate::util::canonicalize_path(&joined)
+        .map_err(|_| (StatusCode::NOT_FOUND, format!("{} not found", trimmed)))?;
+    if !canonical.starts_with(root) {
+        return Err((
+            StatusCode::FORBIDDEN,
+            format!("{} is outside the mission directory", trimmed),
+        ));
+    }
+    Ok(canonical)
+}
+
+/// Path of `resolved` relative to `root`, for responses.
+fn relative(root: &FsPath, resolved: &FsPath) -> String {
+    resolved
+        .strip_prefix(root)
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn kind_of(metadata: &std::fs::Metadata) -> &'static str {
+    if metadata.is_dir() {
+        "dir"
+    } else if metadata.is_symlink() {
+        "link"
+    } else if metadata.is_file() {
+        "file"
+    } else {
+        "other"
+    }
+}
+
+/// Canonical working directory of a mission the user can see.
+pub(super) async fn mission_root(
+    state: &Arc<AppState>,
+    user: &AuthUser,
+    mission_id: Uuid,
+) -> Result<PathBuf, (StatusCode, String)> {
+    let control = state.control.get_or_spawn(user).await;
+    let mission = control
+        .mission_store
+        .get_mission(mission_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Mission {} not found", mission_id),
+            )
+        })?;
+    let workspace_root = state
+        .workspaces
+        .get(mission.workspace_id)
+        .await
+        .map(|ws| ws.path)
+        .unwrap_or_else(|| state.config.working_dir.clone());
+    let mission_dir = workspace::mission_workspace_dir_for_root(&workspace_root, mission_id);
+    crate::util::canonicalize_path(&mission_dir).map_err(|_| {
+        (
+            StatusCode::NOT_FOUND,
+            format!(
+                "Mission {} has no working directory yet (it has not run)",
+                mission_id
+            ),
+        )
+    })
+}
+
+/// List the entries of `dir` with paths relative to `root`.
+async fn list_dir(root: &FsPath, dir: &FsPath) -> std::io::Result<MissionDirListing> {
+    let mut entries = Vec::new();
+    let mut truncated = false;
+    let mut read_dir = tokio::fs::read_dir(dir).await?;
+    while let Some(entry) = read_dir.next_entry().await? {
+        if entries.len() >= MAX_DIR_ENTRIES {
+            truncated = true;
+            break;
+        }
+        let Ok(metadata) = entry.metadata().await else {
+            continue;
+        };
+        entries.push(FsEntry {
+            name: entry.file_name().to_string_lossy().to_string(),
+            path: relative(root, &entry.path()),
+            kind: kind_of(&metadata).to_string(),
+            size: metadata.len(),
+            mtime: mtime_secs(&metadata),
+        });
+    }
+    entries.sort_by(|a, b| (a.kind != "dir", &a.name).cmp(&(b.kind != "dir", &b.name)));
+    Ok(MissionDirListing {
+        path: relative(root, dir),
+        entries,
+        truncated,
+    })
+}
+
+/// GET /api/missions/:id/files - List a directory of the mission.
+pub async fn list_mission_files(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+    Query(q): Query<MissionFileQuery>,
+) -> Result<Json<MissionDirListing>, (StatusCode, String)> {
+    let root = mission_root(&state, &user, mission_id).await?;
+    let dir = resolve_in_root(&root, &q.path)?;
+    if !dir.is_dir() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("{} is not a directory", q.path),
+        ));
+    }
+    list_dir(&root, &dir)
+        .await
+        .map(Json)
+        .map_err(internal_error)
+}
+
+/// GET /api/missions/:id/files/stat - Describe a file or directory.
+pub async fn stat_mission_file(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+    Query(q): Query<MissionFileQuery>,
+) -> Result<Json<MissionFileStat>, (StatusCode, String)> {
+    let root = mission_root(&state, &user, mission_id).await?;
+    let resolved = resolve_in_root(&root, &q.path)?;
+    let metadata = tokio::fs::metadata(&resolved)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(MissionFileStat {
+        path: relative(&root, &resolved),
+        kind: kind_of(&metadata).to_string(),
+        size: metadata.len(),
+        mtime: mtime_secs(&metadata),
+        content_type: content_type_for_path(&resolved).to_string(),
+        language: language_for_path(&resolved),
+    }))
+}
+
+/// GET /api/missions/:id/files/content - Return a small file's text.
+pub async fn read_mission_file(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+    Query(q): Query<MissionFileQuery>,
+) -> Result<Json<MissionFileContent>, (StatusCode, String)> {
+    let root = mission_root(&state, &user, mission_id).await?;
+    let resolved = resolve_in_root(&root, &q.path)?;
+    let metadata = tokio::fs::metadata(&resolved)
+        .await
+        .map_err(internal_error)?;
+    if !metadata.is_file() {
+        return Err((StatusCode::BAD_REQUEST, format!("{} is not a file", q.path)));
+    }
+    if metadata.len() > MAX_CONTENT_BYTES {
+        return Err((
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!(
+                "{} is {} bytes; files over {} bytes must be downloaded",
+                q.path,
+                metadata.len(),
+                MAX_CONTENT_BYTES
+            ),
+        ));
+    }
+
+    let bytes = tokio::fs::read(&resolved).await.map_err(internal_error)?;
+    let content = (!bytes.contains(&0))
+        .then(|| String::from_utf8(bytes).ok())
+        .flatten();
+    Ok(Json(MissionFileContent {
+        path: relative(&root, &resolved),
+        size: metadata.len(),
+        language: language_for_path(&resolved),
+        binary: content.is_none(),
+        content,
+    }))
+}
+
+/// GET /api/missions/:id/files/download - Stream a file as an attachment.
+pub async fn download_mission_file(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+    Query(q): Query<MissionFileQuery>,
+) -> Result<Response, (StatusCode, String)> {
+    let root = mission_root(&state, &user, mission_id).await?;
+    let resolved = resolve_in_root(&root, &q.path)?;
+    if !resolved.is_file() {
+        return Err((StatusCode::BAD_REQUEST, format!("{} is not a file", q.path)));
+    }
+
+    let filename = crate::util::path_file_name(&q.path).replace('"', "");
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_DISPOSITION,
+        format!("attachment; filename=\"{}\"", filename)
+            .parse()
+            .unwrap_or(HeaderValue::from_static("attachment")),
+    );
+    headers.insert(
+        header::CONTENT_TYPE,
+        content_type_for_path(&resolved)
+            .parse()
+            .unwrap_or(HeaderValue::from_static("application/octet-stream")),
+    );
+    let file = tokio::fs::File::open(&resolved)
+        .await
+        .map_err(internal_error)?;
+    Ok((headers, Body::from_stream(ReaderStream::new(file))).into_response())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_must_stay_inside_the_mission_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = crate::util::canonicalize_path(dir.path()).unwrap();
+        let outside = tempfile::tempdir().unwrap();
+        std::fs::create_dir(root.join("src")).unwrap();
+        std::fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+        std::fs::write(outside.path().join("secret"), "x").unwrap();
+
+        assert_eq!(resolve_in_root(&root, "").unwrap(), root);
+        assert_eq!(
+            resolve_in_root(&root, "src/../src/main.rs").unwrap(),
+            root.join("src/main.rs")
+        );
+        assert_eq!(
+            resolve_in_root(&root, "missing.txt").unwrap_err().0,
+            StatusCode::NOT_FOUND
+        );
+        let secret = outside.path().join("secret");
+        assert_eq!(
+            resolve_in_root(&root, &secret.to_string_lossy())
+                .unwrap_err()
+                .0,
+            StatusCode::FORBIDDEN
+        );
+        #[cfg(unix)]
+        {
+            std::os::unix::fs::symlink(&secret, root.join("link")).unwrap();
+            assert_eq!(
+                resolve_in_root(&root, "link").unwrap_err().0,
+                StatusCode::FORBIDDEN
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn listings_are_relative_with_directories_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = crate::util::canonicalize_path(dir.path()).unwrap();
+        std::fs::write(root.join("a.py"), "print()").unwrap();
+        std::fs::create_dir(root.join("z")).unwrap();
+        std::fs::write(root.join("z/Dockerfile"), "FROM scratch").unwrap();
+
+        let listing = list_dir(&root, &root).await.unwrap();
+        let names: Vec<_> = listing.entries.iter().map(|e| e.name.as_str()).collect();
+        assert_eq!(names, ["z", "a.py"]);
+        assert_eq!(listing.path, "");
+
+        let nested = list_dir(&root, &root.join("z")).await.unwrap();
+        assert_eq!(nested.entries[0].path, "z/Dockerfile");
+        assert_eq!(
+            language_for_path(FsPath::new("z/Dockerfile")),
+            Some("dockerfile")
+        );
+        assert_eq!(language_for_path(FsPath::new("a.py")), Some("python"));
+        assert_eq!(language_for_path(FsPath::new("notes.bin")), None);
+    }
+}
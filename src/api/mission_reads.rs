@@ -0,0 +1,164 @@
+//! Read markers: which missions produced output a user hasn't seen.
+//!
+//! `POST /api/missions/:id/read` marks a mission as read by the requesting
+//! user up to its latest stored event. Assistant messages and questions to the
+//! user stored after the marker are unread: the missions list reports them
+//! as `unread_count`, and [`publish_unread_counts`] broadcasts an
+//! [`AgentEvent::MissionUnreadChanged`] to every user who has read the
+//! mission before whenever new ones are stored.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::Serialize;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::control::AgentEvent;
+use super::mission_store::{now_string, Mission, MissionStore, StreamEvent, UNREAD_EVENT_TYPES};
+use super::routes::AppState;
+use crate::util::internal_error;
+
+#[derive(Debug, Serialize)]
+pub struct ReadMarker {
+    pub mission_id: Uuid,
+    pub username: String,
+    pub read_at: String,
+    pub unread_count: u64,
+}
+
+/// POST /api/missions/:id/read - Mark a mission as read by the current user.
+pub async fn mark_read(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+) -> Result<Json<ReadMarker>, (StatusCode, String)> {
+    let control = state.control.get_or_spawn(&user).await;
+    let store = &control.mission_store;
+    if store
+        .get_mission(mission_id)
+        .await
+        .map_err(internal_error)?
+        .is_none()
+    {
+        return Err((StatusCode::NOT_FOUND, "Mission not found".to_string()));
+    }
+    let read_at = now_string();
+    store
+        .mark_mission_read(mission_id, &user.username, &read_at)
+        .await
+        .map_err(internal_error)?;
+    let _ = control.events_tx.send(AgentEvent::MissionUnreadChanged {
+        mission_id,
+        username: user.username.clone(),
+        unread_count: 0,
+    });
+    Ok(Json(ReadMarker {
+        mission_id,
+        username: user.username,
+        read_at,
+        unread_count: 0,
+    }))
+}
+
+/// Fill in `unread_count` of listed missions for `username`.
+pub async fn populate_unread_counts(
+    store: &dyn MissionStore,
+    username: &str,
+    missions: &mut [Mission],
+) -> Result<(), String> {
+    let ids: Vec<Uuid> = missions.iter().map(|m| m.id).collect();
+    let counts = store.unread_counts(username, &ids).await?;
+    for mission in missions {
+        mission.unread_count = Some(counts.get(&mission.id).copied().unwrap_or(0));
+    }
+    Ok(())
+}
+
+/// Missions of `events` with new unread output, in order of first appearance.
+fn missions_with_output(events: &[StreamEvent]) -> Vec<Uuid> {
+    let mut seen = HashSet::new();
+    events
+        .iter()
+        .filter(|e| UNREAD_EVENT_TYPES.contains(&e.event.event_name()))
+        .map(|e| e.mission_id)
+        .filter(|id| seen.insert(*id))
+        .collect()
+}
+
+/// After `events` were stored, send the new unread counts of the missions
+/// they added output to.
+pub async fn publish_unread_counts(
+    store: &dyn MissionStore,
+    events_tx: &broadcast::Sender<AgentEvent>,
+    events: &[StreamEvent],
+) {
+    for mission_id in missions_with_output(events) {
+        let counts = match store.mission_unread_counts(mission_id).await {
+            Ok(counts) => counts,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to count unread events of mission {}: {}",
+                    mission_id,
+                    e
+                );
+                continue;
+            }
+        };
+        for (username, unread_count) in counts {
+            let _ = events_tx.send(AgentEvent::MissionUnreadChanged {
+                mission_id,
+                username,
+                unread_count,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_assistant_output_and_questions_count_as_unread() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let event = |seq, mission_id, event| StreamEvent {
+            mission_id,
+            stream_seq: seq,
+            event: Arc::new(event),
+        };
+        let text = |mission_id: Uuid| AgentEvent::TextDelta {
+            content: "partial".to_string(),
+            mission_id: Some(mission_id),
+        };
+        let reply = |mission_id: Uuid| AgentEvent::AssistantMessage {
+            id: Uuid::new_v4(),
+            content: "done".to_string(),
+            success: true,
+            cost_cents: 0,
+            cost_source: crate::agents::CostSource::Actual,
+            usage: None,
+            model: None,
+            model_normalized: None,
+            mission_id: Some(mission_id),
+            shared_files: None,
+            resumable: false,
+            cancelled: false,
+            tool_calls: Vec::new(),
+        };
+        let events = [
+            event(1, a, text(a)),
+            event(2, b, reply(b)),
+            event(3, a, reply(a)),
+            event(4, b, reply(b)),
+        ];
+        assert_eq!(missions_with_output(&events), vec![b, a]);
+        assert!(missions_with_output(&events[..1]).is_empty());
+    }
+}
@@ -0,0 +1,565 @@
+//! Structured mission reports.
+//!
+//! When a mission completes, its stored events are folded into a report so
+//! users don't have to scroll the chat to see what happened:
+//!
+//! - `summary` - the `complete_mission` summary, or the agent's final message
+//! - `files_touched` - paths from file-editing tool calls (`Write`, `Edit`,
+//!   `apply_patch`, ...)
+//! - `tests_run` - test commands from shell tool calls and completion
+//!   verification runs
+//! - `follow_ups` - the final message's "Next steps"/"Follow-ups" section plus
+//!   any gaps left by a failed completion verification
+//!
+//! Reports are stored with `MissionStore::update_mission_report` and served by
+//! `GET /api/missions/:id/report`. Completed missions without a stored report
+//! (e.g. completed before reports existed) get one generated on first request.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::control::MissionStatus;
+use super::mission_store::{
+    now_string, MissionReport, MissionStore, ReportFile, ReportTestRun, StoredEvent,
+};
+use super::routes::AppState;
+use crate::util::internal_error;
+
+const PAGE_SIZE: usize = 2000;
+
+/// Tools that modify the file named by their path argument.
+const FILE_EDIT_TOOLS: &[&str] = &[
+    "Write",
+    "Edit",
+    "MultiEdit",
+    "NotebookEdit",
+    "notebook_execute",
+    "write",
+    "edit",
+    "write_file",
+    "edit_file",
+];
+
+/// Tools whose patch argument lists the files it touches.
+const PATCH_TOOLS: &[&str] = &["apply_patch", "patch"];
+
+const SHELL_TOOLS: &[&str] = &["Bash", "bash", "shell", "exec_command"];
+
+/// Command fragments that identify a test run.
+const TEST_COMMANDS: &[&str] = &[
+    "cargo test",
+    "cargo nextest",
+    "npm test",
+    "npm run test",
+    "pnpm test",
+    "pnpm run test",
+    "yarn test",
+    "bun test",
+    "pytest",
+    "go test",
+    "make test",
+    "jest",
+    "vitest",
+    "mvn test",
+    "gradle test",
+    "./gradlew test",
+    "rspec",
+    "phpunit",
+    "ctest",
+];
+
+/// Headings that introduce a follow-up list in the agent's final message.
+const FOLLOW_UP_HEADINGS: &[&str] = &[
+    "follow-up",
+    "follow up",
+    "followup",
+    "next step",
+    "todo",
+    "to do",
+    "remaining",
+    "known issue",
+    "open question",
+];
+
+fn is_test_command(command: &str) -> bool {
+    let command = command.to_lowercase();
+    TEST_COMMANDS.iter().any(|marker| command.contains(marker))
+}
+
+fn file_path_arg(args: &serde_json::Value) -> Option<&str> {
+    ["file_path", "filePath", "path", "notebook_path"]
+        .iter()
+        .find_map(|key| args.get(*key).and_then(|v| v.as_str()))
+}
+
+/// Paths named by `*** Add/Update/Delete File:` lines in an apply_patch body.
+fn patch_paths(patch: &str) -> Vec<String> {
+    patch
+        .lines()
+        .filter_map(|line| {
+            ["*** Add File:", "*** Update File:", "*** Delete File:"]
+                .iter()
+                .find_map(|prefix| line.strip_prefix(prefix))
+        })
+        .map(|path| path.trim().to_string())
+        .filter(|path| !path.is_empty())
+        .collect()
+}
+
+/// The command line run by a shell tool call, if `tool_name` is one.
+pub(crate) fn shell_command<'a>(tool_name: &str, args: &'a serde_json::Value) -> Option<&'a str> {
+    if !SHELL_TOOLS.contains(&tool_name) {
+        return None;
+    }
+    Some(
+        args.get("command")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default(),
+    )
+}
+
+/// Files a tool call edits, from its path argument or patch body.
+pub(crate) fn edited_paths(tool_name: &str, args: &serde_json::Value) -> Vec<String> {
+    if FILE_EDIT_TOOLS.contains(&tool_name) {
+        return file_path_arg(args)
+            .map(str::to_string)
+            .into_iter()
+            .collect();
+    }
+    if PATCH_TOOLS.contains(&tool_name) {
+        let patch = ["input", "patch"]
+            .iter()
+            .find_map(|key| args.get(*key).and_then(|v| v.as_str()))
+            .or_else(|| args.as_str())
+            .unwrap_or_default();
+        return patch_paths(patch);
+    }
+    Vec::new()
+}
+
+/// Text of a stored tool result: JSON strings are unwrapped, anything else is
+/// kept as serialized.
+fn result_text(content: &str) -> String {
+    match serde_json::from_str::<serde_json::Value>(content) {
+        Ok(serde_json::Value::String(text)) => text,
+        _ => content.to_string(),
+    }
+}
+
+/// Exit code reported at the start of a failed shell result ("Exit code 1",
+/// "Error: Exit code 1"). Successful runs don't report one.
+fn failed_exit_code(result: &str) -> Option<i32> {
+    let line = result.trim_start().lines().next()?;
+    let line = line.strip_prefix("Error:").unwrap_or(line).trim_start();
+    let rest = line
+        .strip_prefix("Exit code")
+        .or_else(|| line.strip_prefix("exit code"))?;
+    rest.trim_start_matches([' ', ':'])
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+fn is_follow_up_heading(line: &str) -> bool {
+    let heading = line.trim().trim_start_matches('#').trim();
+    let is_heading = line.trim_start().starts_with('#')
+        || heading.ends_with(':')
+        || (heading.starts_with("**") && heading.ends_with("**"));
+    let lower = heading.to_lowercase();
+    is_heading && FOLLOW_UP_HEADINGS.iter().any(|h| lower.contains(h))
+}
+
+fn list_item(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    if let Some(item) = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .or_else(|| line.strip_prefix("• "))
+    {
+        return Some(item);
+    }
+    let digits = line.len() - line.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+    if digits == 0 {
+        return None;
+    }
+    line[digits..]
+        .strip_prefix(". ")
+        .or_else(|| line[digits..].strip_prefix(") "))
+}
+
+/// List items under follow-up headings ("Next steps", "TODO", ...) in `text`.
+pub fn extract_follow_ups(text: &str) -> Vec<String> {
+    let mut follow_ups = Vec::new();
+    let mut in_section = false;
+    for line in text.lines() {
+        if is_follow_up_heading(line) {
+            in_section = true;
+            continue;
+        }
+        if !in_section || line.trim().is_empty() {
+            continue;
+        }
+        match list_item(line) {
+            Some(item) => {
+                let item = item.trim().replace("[ ] ", "");
+                if !item.is_empty() {
+                    follow_ups.push(item);
+                }
+            }
+            None => in_section = false,
+        }
+    }
+    follow_ups
+}
+
+/// Folds stored mission events into a [`MissionReport`].
+#[derive(Default)]
+pub struct ReportBuilder {
+    files: Vec<ReportFile>,
+    file_index: HashMap<String, usize>,
+    tests: Vec<ReportTestRun>,
+    /// Agent test runs by tool call ID, awaiting their result
+    pending_tests: HashMap<String, usize>,
+    completion_summary: Option<String>,
+    last_assistant_message: Option<String>,
+    verification_gaps: Vec<String>,
+    tool_calls: usize,
+    cost_cents: u64,
+}
+
+impl ReportBuilder {
+    fn touch(&mut self, path: &str, tool: &str) {
+        let index = *self.file_index.entry(path.to_string()).or_insert_with(|| {
+            self.files.push(ReportFile {
+                path: path.to_string(),
+                tools: Vec::new(),
+                changes: 0,
+            });
+            self.files.len() - 1
+        });
+        let file = &mut self.files[index];
+        file.changes += 1;
+        if !file.tools.iter().any(|t| t == tool) {
+            file.tools.push(tool.to_string());
+        }
+    }
+
+    fn push_tool_call(&mut self, event: &StoredEvent) {
+        self.tool_calls += 1;
+        let Some(name) = event.tool_name.as_deref() else {
+            return;
+        };
+        let args: serde_json::Value =
+            serde_json::from_str(&event.content).unwrap_or(serde_json::Value::Null);
+
+        for path in edited_paths(name, &args) {
+            self.touch(&path, name);
+        }
+        if let Some(command) = shell_command(name, &args) {
+            if is_test_command(command) {
+                self.tests.push(ReportTestRun {
+                    command: command.trim().to_string(),
+                    source: "agent".to_string(),
+                    exit_code: None,
+                    passed: None,
+                });
+                if let Some(id) = &event.tool_call_id {
+                    self.pending_tests.insert(id.clone(), self.tests.len() - 1);
+                }
+            }
+        }
+    }
+
+    fn push_tool_result(&mut self, event: &StoredEvent) {
+        let Some(index) = event
+            .tool_call_id
+            .as_ref()
+            .and_then(|id| self.pending_tests.remove(id))
+        else {
+            return;
+        };
+        if let Some(code) = failed_exit_code(&result_text(&event.content)) {
+            let test = &mut self.tests[index];
+            test.exit_code = Some(code);
+            test.passed = Some(code == 0);
+        }
+    }
+
+    fn push_verification(&mut self, event: &StoredEvent) {
+        let passed = event
+            .metadata
+            .get("passed")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+        self.verification_gaps = if passed {
+            Vec::new()
+        } else {
+            event
+                .content
+                .lines()
+                .map(str::trim)
+                .filter(|gap| !gap.is_empty())
+                .map(str::to_string)
+                .collect()
+        };
+        let Some(test) = event.metadata.get("test").filter(|t| !t.is_null()) else {
+            return;
+        };
+        let Some(command) = test.get("command").and_then(|v| v.as_str()) else {
+            return;
+        };
+        let exit_code = test
+            .get("exit_code")
+            .and_then(|v| v.as_i64())
+            .and_then(|code| i32::try_from(code).ok());
+        self.tests.push(ReportTestRun {
+            command: command.to_string(),
+            source: "verification".to_string(),
+            exit_code,
+            passed: exit_code.map(|code| code == 0),
+        });
+    }
+
+    pub fn push(&mut self, event: &StoredEvent) {
+        match event.event_type.as_str() {
+            "tool_call" => self.push_tool_call(event),
+            "tool_result" => self.push_tool_result(event),
+            "assistant_message" => {
+                if !event.content.trim().is_empty() {
+                    self.last_assistant_message = Some(event.content.clone());
+                }
+                self.cost_cents += event
+                    .metadata
+                    .get("cost_cents")
+                    .and_then(|v| v.as_u64())
+                    .unwrap_or(0);
+            }
+            "completion_verification" => self.push_verification(event),
+            "mission_status_changed" => {
+                let status = event.metadata.get("status").and_then(|v| v.as_str());
+                if status == Some("completed") && !event.content.trim().is_empty() {
+                    self.completion_summary = Some(event.content.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub fn finish(self, mission_id: Uuid, status: MissionStatus) -> MissionReport {
+        let mut follow_ups = self
+            .last_assistant_message
+            .as_deref()
+            .map(extract_follow_ups)
+            .unwrap_or_default();
+        for gap in self.verification_gaps {
+            if !follow_ups.contains(&gap) {
+                follow_ups.push(gap);
+            }
+        }
+        MissionReport {
+            mission_id,
+            status,
+            generated_at: now_string(),
+            summary: self.completion_summary.or(self.last_assistant_message),
+            files_touched: self.files,
+            tests_run: self.tests,
+            follow_ups,
+            tool_calls: self.tool_calls,
+            cost_cents: self.cost_cents,
+        }
+    }
+}
+
+/// Build a report from the mission's stored events.
+pub async fn build_report(
+    store: &Arc<dyn MissionStore>,
+    mission_id: Uuid,
+    status: MissionStatus,
+) -> Result<MissionReport, String> {
+    let mut builder = ReportBuilder::default();
+    let mut offset = 0;
+    loop {
+        let page = store
+            .get_events(mission_id, None, Some(PAGE_SIZE), Some(offset))
+            .await?;
+        for event in &page {
+            builder.push(event);
+        }
+        if page.len() < PAGE_SIZE {
+            break;
+        }
+        offset += page.len();
+    }
+    Ok(builder.finish(mission_id, status))
+}
+
+/// Build and store the report for a mission that just completed.
+pub async fn generate_report(store: &Arc<dyn MissionStore>, mission_id: Uuid) {
+    let result = match build_report(store, mission_id, MissionStatus::Completed).await {
+        Ok(report) => store.update_mission_report(&report).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = result {
+        tracing::warn!(
+            "Failed to generate report for mission {}: {}",
+            mission_id,
+            e
+        );
+    }
+}
+
+/// GET /api/missions/:id/report - Structured report of a completed mission.
+pub async fn get_mission_report(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+) -> Result<Json<MissionReport>, (StatusCode, String)> {
+    let control = state.control.get_or_spawn(&user).await;
+    let store = &control.mission_store;
+    let mission = store
+        .get_mission(mission_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Mission not found".to_string()))?;
+    if let Some(report) = store
+        .get_mission_report(mission_id)
+        .await
+        .map_err(internal_error)?
+    {
+        return Ok(Json(report));
+    }
+    if mission.status != MissionStatus::Completed {
+        return Err((
+            StatusCode::NOT_FOUND,
+            "Mission has no report until it completes".to_string(),
+        ));
+    }
+    let report = build_report(store, mission_id, mission.status)
+        .await
+        .map_err(internal_error)?;
+    if let Err(e) = store.update_mission_report(&report).await {
+        tracing::warn!("Failed to store report for mission {}: {}", mission_id, e);
+    }
+    Ok(Json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(
+        event_type: &str,
+        tool: Option<(&str, &str)>,
+        content: &str,
+        metadata: serde_json::Value,
+    ) -> StoredEvent {
+        StoredEvent {
+            id: 0,
+            mission_id: Uuid::nil(),
+            sequence: 0,
+            event_type: event_type.to_string(),
+            timestamp: now_string(),
+            event_id: None,
+            tool_call_id: tool.map(|(id, _)| id.to_string()),
+            tool_name: tool.map(|(_, name)| name.to_string()),
+            content: content.to_string(),
+            metadata,
+            stream_seq: None,
+            schema_version: crate::api::event_schema::CURRENT_VERSION,
+        }
+    }
+
+    #[test]
+    fn extract_follow_ups_reads_list_under_heading() {
+        let text = "Done.\n\n## Next steps\n- Add docs\n2. Benchmark the parser\n\nThanks!\n- not a follow-up";
+        assert_eq!(
+            extract_follow_ups(text),
+            vec!["Add docs".to_string(), "Benchmark the parser".to_string()]
+        );
+        assert!(extract_follow_ups("- just a list").is_empty());
+    }
+
+    #[test]
+    fn builder_collects_files_tests_and_follow_ups() {
+        let mut builder = ReportBuilder::default();
+        let events = [
+            event(
+                "tool_call",
+                Some(("t1", "Write")),
+                &json!({"file_path": "src/lib.rs"}).to_string(),
+                json!({}),
+            ),
+            event(
+                "tool_call",
+                Some(("t2", "Edit")),
+                &json!({"file_path": "src/lib.rs"}).to_string(),
+                json!({}),
+            ),
+            event(
+                "tool_call",
+                Some(("t3", "apply_patch")),
+                &json!({"input": "*** Begin Patch\n*** Update File: README.md\n*** End Patch"})
+                    .to_string(),
+                json!({}),
+            ),
+            event(
+                "tool_call",
+                Some(("t4", "Bash")),
+                &json!({"command": "cargo test --workspace"}).to_string(),
+                json!({}),
+            ),
+            event(
+                "tool_result",
+                Some(("t4", "Bash")),
+                &json!("Error: Exit code 101\ntest result: FAILED").to_string(),
+                json!({}),
+            ),
+            event(
+                "completion_verification",
+                None,
+                "Docs not updated",
+                json!({"passed": false, "test": {"command": "cargo test", "exit_code": 0, "output": ""}}),
+            ),
+            event(
+                "assistant_message",
+                None,
+                "Implemented the parser.\n\nFollow-ups:\n- Docs not updated\n- Add fuzzing",
+                json!({"cost_cents": 12}),
+            ),
+        ];
+        for e in &events {
+            builder.push(e);
+        }
+        let report = builder.finish(Uuid::nil(), MissionStatus::Completed);
+
+        assert_eq!(report.files_touched.len(), 2);
+        assert_eq!(report.files_touched[0].path, "src/lib.rs");
+        assert_eq!(report.files_touched[0].tools, vec!["Write", "Edit"]);
+        assert_eq!(report.files_touched[0].changes, 2);
+        assert_eq!(report.files_touched[1].path, "README.md");
+
+        assert_eq!(report.tests_run.len(), 2);
+        assert_eq!(report.tests_run[0].exit_code, Some(101));
+        assert_eq!(report.tests_run[0].passed, Some(false));
+        assert_eq!(report.tests_run[1].source, "verification");
+        assert_eq!(report.tests_run[1].passed, Some(true));
+
+        assert_eq!(report.follow_ups, vec!["Docs not updated", "Add fuzzing"]);
+        assert_eq!(report.tool_calls, 4);
+        assert_eq!(report.cost_cents, 12);
+        assert!(report
+            .summary
+            .unwrap()
+            .starts_with("Implemented the parser"));
+    }
+}
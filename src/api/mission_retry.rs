@@ -0,0 +1,413 @@
+//! Automatic retries of failed missions.
+//!
+//! A mission whose run fails for a transient reason (e.g. a provider 529,
+//! `llm_error`) can be retried without anyone clicking "resume": a
+//! [`MissionRetryPolicy`] set on the mission, or on one of its automations,
+//! says how many times, after what backoff and for which terminal reasons.
+//! [`retry_loop`], run by each control session, watches for missions turning
+//! failed, records a retry against the mission in the store and resumes the
+//! mission once it is due, as `POST /api/control/missions/:id/resume` would.
+//!
+//! Retries count per failure streak: a run that fails right after a retry
+//! started is the next attempt, any other failure starts again at attempt 1.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use tokio::sync::{broadcast, mpsc, oneshot};
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::control::{AgentEvent, ControlCommand, MissionStatus};
+use super::mission_store::{
+    now_string, Mission, MissionRetry, MissionRetryPolicy, MissionRetryStatus, MissionStore,
+};
+use super::routes::AppState;
+use crate::util::internal_error;
+
+/// How often the retry task checks for due retries.
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Policy in effect for a mission: its own, else that of its first active
+/// automation with one.
+pub async fn effective_policy(
+    store: &Arc<dyn MissionStore>,
+    mission_id: Uuid,
+) -> Result<Option<MissionRetryPolicy>, String> {
+    if let Some(policy) = store.get_mission_retry_policy(mission_id).await? {
+        return Ok(Some(policy));
+    }
+    let automations = store.get_mission_automations(mission_id).await?;
+    Ok(automations
+        .into_iter()
+        .filter(|automation| automation.active)
+        .find_map(|automation| automation.mission_retry_policy))
+}
+
+/// The retry to schedule for a mission that just failed, given its earlier
+/// retries (oldest first), if the policy allows one.
+fn plan_retry(
+    policy: &MissionRetryPolicy,
+    mission: &Mission,
+    previous: &[MissionRetry],
+    now: DateTime<Utc>,
+) -> Option<MissionRetry> {
+    if mission.status != MissionStatus::Failed
+        || !policy.retries(mission.terminal_reason.as_deref())
+        || previous
+            .iter()
+            .any(|retry| retry.status == MissionRetryStatus::Scheduled)
+    {
+        return None;
+    }
+    let attempt = match previous.last() {
+        Some(last) if last.status == MissionRetryStatus::Started => last.attempt + 1,
+        _ => 1,
+    };
+    if attempt > policy.max_attempts {
+        return None;
+    }
+    let delay = chrono::Duration::from_std(policy.delay_for(attempt)).ok()?;
+    Some(MissionRetry {
+        id: Uuid::new_v4(),
+        mission_id: mission.id,
+        attempt,
+        terminal_reason: mission.terminal_reason.clone(),
+        due_at: (now + delay).to_rfc3339(),
+        created_at: now_string(),
+        status: MissionRetryStatus::Scheduled,
+        started_at: None,
+        error: None,
+    })
+}
+
+/// Whether a scheduled retry is due at `now`. Unparseable times are due so
+/// they surface instead of lingering forever.
+fn is_due(retry: &MissionRetry, now: DateTime<Utc>) -> bool {
+    DateTime::parse_from_rfc3339(&retry.due_at)
+        .map(|due_at| due_at <= now)
+        .unwrap_or(true)
+}
+
+/// Settle the retry running for a mission whose status just changed to
+/// `status`, then schedule the next one if the mission failed.
+async fn on_status_changed(
+    store: &Arc<dyn MissionStore>,
+    mission_id: Uuid,
+    status: MissionStatus,
+) -> Result<(), String> {
+    if matches!(status, MissionStatus::Pending | MissionStatus::Active) {
+        return Ok(());
+    }
+    let previous = store.list_mission_retries(Some(mission_id), None).await?;
+    if let Some(running) = previous
+        .iter()
+        .find(|retry| retry.status == MissionRetryStatus::Started)
+    {
+        let (to, error) = if status == MissionStatus::Completed {
+            (MissionRetryStatus::Succeeded, None)
+        } else {
+            (
+                MissionRetryStatus::Failed,
+                Some(format!("Mission {}", status)),
+            )
+        };
+        store
+            .transition_mission_retry(running.id, MissionRetryStatus::Started, to, error)
+            .await?;
+    }
+    if status != MissionStatus::Failed {
+        return Ok(());
+    }
+
+    let Some(policy) = effective_policy(store, mission_id).await? else {
+        return Ok(());
+    };
+    let Some(mission) = store.get_mission(mission_id).await? else {
+        return Ok(());
+    };
+    let Some(retry) = plan_retry(&policy, &mission, &previous, Utc::now()) else {
+        return Ok(());
+    };
+    tracing::info!(
+        mission_id = %mission_id,
+        attempt = retry.attempt,
+        max_attempts = policy.max_attempts,
+        due_at = %retry.due_at,
+        "Scheduling mission retry"
+    );
+    store.create_mission_retry(retry).await?;
+    Ok(())
+}
+
+/// Resume the missions whose retries are due.
+async fn run_due_retries(
+    store: &Arc<dyn MissionStore>,
+    cmd_tx: &mpsc::Sender<ControlCommand>,
+) -> Result<(), String> {
+    let scheduled = store
+        .list_mission_retries(None, Some(MissionRetryStatus::Scheduled))
+        .await?;
+    let now = Utc::now();
+    for retry in scheduled.into_iter().filter(|r| is_due(r, now)) {
+        if !store
+            .transition_mission_retry(
+                retry.id,
+                MissionRetryStatus::Scheduled,
+                MissionRetryStatus::Started,
+                None,
+            )
+            .await?
+        {
+            continue;
+        }
+
+        // Resumed or deleted by hand in the meantime
+        let status = store.get_mission(retry.mission_id).await?.map(|m| m.status);
+        if status != Some(MissionStatus::Failed) {
+            store
+                .transition_mission_retry(
+                    retry.id,
+                    MissionRetryStatus::Started,
+                    MissionRetryStatus::Cancelled,
+                    Some("Mission is no longer failed".to_string()),
+                )
+                .await?;
+            continue;
+        }
+
+        tracing::info!(
+            mission_id = %retry.mission_id,
+            attempt = retry.attempt,
+            "Retrying failed mission"
+        );
+        let (respond, rx) = oneshot::channel();
+        let resumed = match cmd_tx
+            .send(ControlCommand::ResumeMission {
+                mission_id: retry.mission_id,
+                clean_workspace: false,
+                skip_message: false,
+                respond,
+            })
+            .await
+        {
+            Ok(()) => rx
+                .await
+                .unwrap_or_else(|_| Err("Control session unavailable".to_string())),
+            Err(_) => Err("Control session unavailable".to_string()),
+        };
+        if let Err(e) = resumed {
+            tracing::warn!("Failed to retry mission {}: {}", retry.mission_id, e);
+            store
+                .transition_mission_retry(
+                    retry.id,
+                    MissionRetryStatus::Started,
+                    MissionRetryStatus::Failed,
+                    Some(e),
+                )
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Background task that schedules retries for failed missions and resumes
+/// them once due.
+pub async fn retry_loop(
+    store: Arc<dyn MissionStore>,
+    cmd_tx: mpsc::Sender<ControlCommand>,
+    mut events_rx: broadcast::Receiver<AgentEvent>,
+) {
+    tracing::info!("Mission retry task started");
+    let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+    loop {
+        tokio::select! {
+            event = events_rx.recv() => match event {
+                Ok(AgentEvent::MissionStatusChanged { mission_id, status, .. }) => {
+                    if let Err(e) = on_status_changed(&store, mission_id, status).await {
+                        tracing::warn!("Failed to handle retries for mission {}: {}", mission_id, e);
+                    }
+                }
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!("Mission retry task lagged, skipped {} events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return,
+            },
+            _ = interval.tick() => {
+                if let Err(e) = run_due_retries(&store, &cmd_tx).await {
+                    tracing::warn!("Failed to run due mission retries: {}", e);
+                }
+            }
+        }
+    }
+}
+
+/// GET /api/control/missions/:id/retry-policy - The mission's own policy and
+/// the one in effect (which may come from an automation).
+pub async fn get_retry_policy(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let control = state.control.get_or_spawn(&user).await;
+    let store = &control.mission_store;
+    let own = store
+        .get_mission_retry_policy(mission_id)
+        .await
+        .map_err(internal_error)?;
+    let effective = effective_policy(store, mission_id)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(serde_json::json!({
+        "policy": own,
+        "effective": effective,
+    })))
+}
+
+/// PUT /api/control/missions/:id/retry-policy - Set the mission's policy.
+pub async fn set_retry_policy(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+    Json(policy): Json<MissionRetryPolicy>,
+) -> Result<Json<MissionRetryPolicy>, (StatusCode, String)> {
+    if !(policy.backoff_multiplier.is_finite() && policy.backoff_multiplier >= 1.0) {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "backoff_multiplier must be at least 1.0".to_string(),
+        ));
+    }
+    let control = state.control.get_or_spawn(&user).await;
+    let store = &control.mission_store;
+    store
+        .get_mission(mission_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Mission not found".to_string()))?;
+    store
+        .set_mission_retry_policy(mission_id, Some(policy.clone()))
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(policy))
+}
+
+/// DELETE /api/control/missions/:id/retry-policy - Clear the mission's policy.
+pub async fn clear_retry_policy(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    let control = state.control.get_or_spawn(&user).await;
+    control
+        .mission_store
+        .set_mission_retry_policy(mission_id, None)
+        .await
+        .map_err(internal_error)?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// GET /api/control/missions/:id/retries - The mission's automatic retries,
+/// oldest first.
+pub async fn list_retries(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+) -> Result<Json<Vec<MissionRetry>>, (StatusCode, String)> {
+    let control = state.control.get_or_spawn(&user).await;
+    let retries = control
+        .mission_store
+        .list_mission_retries(Some(mission_id), None)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(retries))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::mission_store::{InMemoryMissionStore, MissionStore};
+
+    async fn failed_mission(terminal_reason: Option<&str>) -> Mission {
+        let store = InMemoryMissionStore::new();
+        let mut mission = store
+            .create_mission(None, None, None, None, None, None, None)
+            .await
+            .unwrap();
+        mission.status = MissionStatus::Failed;
+        mission.terminal_reason = terminal_reason.map(String::from);
+        mission
+    }
+
+    fn policy(max_attempts: u32) -> MissionRetryPolicy {
+        serde_json::from_value(serde_json::json!({ "max_attempts": max_attempts })).unwrap()
+    }
+
+    fn retry(mission_id: Uuid, attempt: u32, status: MissionRetryStatus) -> MissionRetry {
+        MissionRetry {
+            id: Uuid::new_v4(),
+            mission_id,
+            attempt,
+            terminal_reason: Some("llm_error".to_string()),
+            due_at: now_string(),
+            created_at: now_string(),
+            status,
+            started_at: None,
+            error: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn plans_retries_with_backoff_until_exhausted() {
+        let mission = failed_mission(Some("llm_error")).await;
+        let policy = policy(2);
+        let now = Utc::now();
+
+        let first = plan_retry(&policy, &mission, &[], now).unwrap();
+        assert_eq!(first.attempt, 1);
+        assert_eq!(
+            first.due_at,
+            (now + chrono::Duration::seconds(60)).to_rfc3339()
+        );
+
+        let started = [retry(mission.id, 1, MissionRetryStatus::Started)];
+        let second = plan_retry(&policy, &mission, &started, now).unwrap();
+        assert_eq!(second.attempt, 2);
+        assert_eq!(
+            second.due_at,
+            (now + chrono::Duration::seconds(120)).to_rfc3339()
+        );
+
+        let exhausted = [retry(mission.id, 2, MissionRetryStatus::Started)];
+        assert!(plan_retry(&policy, &mission, &exhausted, now).is_none());
+
+        // A failure after a settled streak starts over
+        let settled = [retry(mission.id, 2, MissionRetryStatus::Succeeded)];
+        assert_eq!(
+            plan_retry(&policy, &mission, &settled, now)
+                .unwrap()
+                .attempt,
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn only_retries_listed_reasons_once_at_a_time() {
+        let policy = policy(3);
+        let now = Utc::now();
+
+        let max_iterations = failed_mission(Some("max_iterations")).await;
+        assert!(plan_retry(&policy, &max_iterations, &[], now).is_none());
+        assert!(plan_retry(&policy, &failed_mission(None).await, &[], now).is_none());
+
+        let mission = failed_mission(Some("rate_limited")).await;
+        let pending = [retry(mission.id, 1, MissionRetryStatus::Scheduled)];
+        assert!(plan_retry(&policy, &mission, &pending, now).is_none());
+    }
+}
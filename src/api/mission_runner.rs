@@ -20,7 +20,7 @@ use tokio::sync::{broadcast, mpsc, OwnedSemaphorePermit, RwLock, Semaphore};
 use tokio_util::sync::CancellationToken;
 use uuid::Uuid;
 
-use crate::agents::{AgentRef, AgentResult, TerminalReason};
+use crate::agents::{AgentRef, AgentResult, ErrorCode, TerminalReason};
 use crate::backend::claudecode::client::{ClaudeEvent, ContentBlock, StreamEvent};
 use crate::config::Config;
 use crate::mcp::McpRegistry;
@@ -39,6 +39,7 @@ use super::control::{
     ControlRunState, ControlStatus, ExecutionProgress, FrontendToolHub,
 };
 use super::library::SharedLibrary;
+pub use super::mission_state::{MissionRunEvent, MissionRunState};
 
 #[derive(Debug, Default)]
 struct OpencodeSseState {
@@ -265,6 +266,44 @@ fn strip_think_tags(text: &str) -> String {
     result
 }
 
+/// Build the per-call usage event for an assistant message, so its cost can be
+/// attributed to the agent tree node (root agent or subagent) that made the call.
+fn llm_usage_event(
+    evt: &crate::backend::shared::AssistantEvent,
+    model: Option<&str>,
+    mission_id: Uuid,
+) -> Option<AgentEvent> {
+    let usage = evt.message.usage.as_ref()?;
+    let usage = crate::cost::TokenUsage {
+        input_tokens: usage.input_tokens.unwrap_or(0),
+        output_tokens: usage.output_tokens.unwrap_or(0),
+        cache_creation_input_tokens: usage.cache_creation_input_tokens,
+        cache_read_input_tokens: usage.cache_read_input_tokens,
+    };
+    if !usage.has_usage() {
+        return None;
+    }
+    let model = evt.message.model.as_deref().or(model);
+    let (cost_cents, _) = resolve_cost_cents_and_source(None, model, &usage);
+    let tool_calls = evt
+        .message
+        .content
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::ToolUse { id, name, .. } => Some((id.clone(), name.clone())),
+            _ => None,
+        })
+        .collect();
+    Some(AgentEvent::LlmUsage {
+        node_id: evt.parent_tool_use_id.clone(),
+        usage,
+        cost_cents,
+        model: model.map(str::to_string),
+        tool_calls,
+        mission_id: Some(mission_id),
+    })
+}
+
 /// Prefixes that indicate a thought/reasoning line
 const THOUGHT_PREFIXES: &[&str] = &["thought:", "thoughts:", "thinking:"];
 
@@ -809,6 +848,7 @@ fn parse_opencode_sse_event(
                 })
                 .unwrap_or_else(|| "Unknown session error".to_string());
             Some(AgentEvent::Error {
+                code: Some(ErrorCode::classify_or(&message, ErrorCode::ProviderError)),
                 message,
                 mission_id: Some(mission_id),
                 resumable: true,
@@ -822,6 +862,7 @@ fn parse_opencode_sse_event(
                 .unwrap_or("Unknown error")
                 .to_string();
             Some(AgentEvent::Error {
+                code: Some(ErrorCode::classify_or(&message, ErrorCode::ProviderError)),
                 message,
                 mission_id: Some(mission_id),
                 resumable: true,
@@ -860,19 +901,6 @@ fn parse_opencode_sse_event(
     })
 }
 
-/// State of a running mission.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum MissionRunState {
-    /// Waiting in queue
-    Queued,
-    /// Currently executing
-    Running,
-    /// Waiting for frontend tool input
-    WaitingForTool,
-    /// Finished (check result)
-    Finished,
-}
-
 const STALL_WARN_SECS: u64 = 120;
 const STALL_SEVERE_SECS: u64 = 300;
 
@@ -912,10 +940,7 @@ fn stall_severity(seconds_since_activity: u64) -> Option<MissionStallSeverity> {
 }
 
 pub fn running_health(state: MissionRunState, seconds_since_activity: u64) -> MissionHealth {
-    if matches!(
-        state,
-        MissionRunState::Running | MissionRunState::WaitingForTool
-    ) {
+    if state.is_executing() {
         if let Some(severity) = stall_severity(seconds_since_activity) {
             return MissionHealth::Stalled {
                 seconds_since_activity,
@@ -967,12 +992,24 @@ pub struct MissionRunner {
     /// Agent override for this mission
     pub agent_override: Option<String>,
 
+    /// Library agent version pinned for this mission
+    pub agent_version: Option<String>,
+
     /// Model override for this mission (e.g. "zai/glm-5")
     pub model_override: Option<String>,
 
     /// Model effort override for this mission (e.g. low/medium/high)
     pub model_effort: Option<String>,
 
+    /// Simulate Bash/Edit/Write tool calls instead of running them
+    pub dry_run: bool,
+
+    /// Library environment profile from the mission (overrides the workspace's)
+    pub env_profile: Option<String>,
+
+    /// The mission has active automations (selects the stricter egress policy)
+    pub automated: bool,
+
     /// Message queue for this mission
     pub queue: VecDeque<QueuedMessage>,
 
@@ -1026,10 +1063,14 @@ impl MissionRunner {
             backend_id: backend_id.unwrap_or_else(|| "opencode".to_string()),
             session_id,
             config_profile,
-            state: MissionRunState::Queued,
+            state: MissionRunState::Idle,
             agent_override,
+            agent_version: None,
             model_override,
             model_effort,
+            dry_run: false,
+            env_profile: None,
+            automated: false,
             queue: VecDeque::new(),
             history: Vec::new(),
             cancel_token: None,
@@ -1046,15 +1087,7 @@ impl MissionRunner {
 
     /// Check if this runner is currently executing.
     pub fn is_running(&self) -> bool {
-        matches!(
-            self.state,
-            MissionRunState::Running | MissionRunState::WaitingForTool
-        )
-    }
-
-    /// Check if this runner has finished.
-    pub fn is_finished(&self) -> bool {
-        matches!(self.state, MissionRunState::Finished)
+        self.state.is_executing()
     }
 
     /// Update the last activity timestamp.
@@ -1111,12 +1144,14 @@ impl MissionRunner {
     /// Queue a message for this mission.
     pub fn queue_message(&mut self, id: Uuid, content: String, agent: Option<String>) {
         self.queue.push_back(QueuedMessage { id, content, agent });
+        self.state.advance(MissionRunEvent::Queued);
     }
 
     /// Cancel the current execution.
     pub fn cancel(&mut self) {
         if let Some(token) = &self.cancel_token {
             token.cancel();
+            self.state.advance(MissionRunEvent::CancelRequested);
         }
     }
 
@@ -1128,6 +1163,31 @@ impl MissionRunner {
         self.queue.len() < before_len
     }
 
+    /// Move a queued message to `position` (0 = next to run, clamped to the
+    /// end of the queue). Returns true if the message was found.
+    pub fn move_in_queue(&mut self, message_id: Uuid, position: usize) -> bool {
+        let Some(index) = self.queue.iter().position(|qm| qm.id == message_id) else {
+            return false;
+        };
+        if let Some(message) = self.queue.remove(index) {
+            let position = position.min(self.queue.len());
+            self.queue.insert(position, message);
+        }
+        true
+    }
+
+    /// Replace the content of a queued message.
+    /// Returns true if the message was found and edited.
+    pub fn edit_queued_message(&mut self, message_id: Uuid, content: &str) -> bool {
+        match self.queue.iter_mut().find(|qm| qm.id == message_id) {
+            Some(message) => {
+                message.content = content.to_string();
+                true
+            }
+            None => false,
+        }
+    }
+
     /// Clear all queued messages.
     /// Returns the number of messages that were cleared.
     pub fn clear_queue(&mut self) -> usize {
@@ -1164,7 +1224,7 @@ impl MissionRunner {
             None => return false,
         };
 
-        self.state = MissionRunState::Running;
+        self.state.advance(MissionRunEvent::Started);
 
         let cancel = CancellationToken::new();
         self.cancel_token = Some(cancel.clone());
@@ -1175,11 +1235,15 @@ impl MissionRunner {
         let mission_id = self.mission_id;
         let workspace_id = self.workspace_id;
         let agent_override = self.agent_override.clone();
+        let agent_version = self.agent_version.clone();
         let model_override = self.model_override.clone();
         let model_effort = self.model_effort.clone();
         let backend_id = self.backend_id.clone();
         let session_id = self.session_id.clone();
         let config_profile = self.config_profile.clone();
+        let dry_run = self.dry_run;
+        let env_profile = self.env_profile.clone();
+        let automated = self.automated;
         let user_message = msg.content.clone();
         let msg_id = msg.id;
         tracing::info!(
@@ -1225,11 +1289,15 @@ impl MissionRunner {
                 Some(workspace_id),
                 backend_id,
                 agent_override,
+                agent_version,
                 model_override,
                 model_effort,
                 secrets,
                 session_id,
                 config_profile,
+                dry_run,
+                env_profile,
+                automated,
             )
             .await;
             (msg_id, user_message, result)
@@ -1248,7 +1316,9 @@ impl MissionRunner {
             match handle.await {
                 Ok(result) => {
                     self.touch(); // Update last activity
-                    self.state = MissionRunState::Queued; // Ready for next message
+                    self.state.advance(MissionRunEvent::Finished {
+                        queued: !self.queue.is_empty(),
+                    });
 
                     // Check if complete_mission was called
                     if result.2.output.contains("Mission marked as")
@@ -1283,7 +1353,9 @@ impl MissionRunner {
                 }
                 Err(e) => {
                     tracing::error!("Mission runner task failed: {}", e);
-                    self.state = MissionRunState::Finished;
+                    self.state.advance(MissionRunEvent::Finished {
+                        queued: !self.queue.is_empty(),
+                    });
                     None
                 }
             }
@@ -1425,9 +1497,112 @@ pub fn is_session_corruption_error(result: &AgentResult) -> bool {
     || out.contains("No conversation found with session ID")
 }
 
-/// Execute a single turn for a mission.
+/// Run one mission turn. With completion verification enabled, a turn that
+/// claims completion is verified and continued with the verifier's feedback
+/// until it passes or runs out of retries.
 #[allow(clippy::too_many_arguments)]
 async fn run_mission_turn(
+    config: Config,
+    root_agent: AgentRef,
+    mcp: Arc<McpRegistry>,
+    workspaces: workspace::SharedWorkspaceStore,
+    library: SharedLibrary,
+    events_tx: broadcast::Sender<AgentEvent>,
+    tool_hub: Arc<FrontendToolHub>,
+    status: Arc<RwLock<ControlStatus>>,
+    cancel: CancellationToken,
+    history: Vec<(String, String)>,
+    user_message: String,
+    mission_control: Option<crate::tools::mission::MissionControl>,
+    tree_snapshot: Arc<RwLock<Option<AgentTreeNode>>>,
+    progress_snapshot: Arc<RwLock<ExecutionProgress>>,
+    mission_id: Uuid,
+    workspace_id: Option<Uuid>,
+    backend_id: String,
+    agent_override: Option<String>,
+    agent_version: Option<String>,
+    model_override: Option<String>,
+    model_effort: Option<String>,
+    secrets: Option<Arc<SecretsStore>>,
+    session_id: Option<String>,
+    mission_config_profile: Option<String>,
+    dry_run: bool,
+    mission_env_profile: Option<String>,
+    automated: bool,
+) -> AgentResult {
+    let run_turn = |history: Vec<(String, String)>, user_message: String| {
+        run_mission_turn_once(
+            config.clone(),
+            Arc::clone(&root_agent),
+            Arc::clone(&mcp),
+            Arc::clone(&workspaces),
+            library.clone(),
+            events_tx.clone(),
+            Arc::clone(&tool_hub),
+            Arc::clone(&status),
+            cancel.clone(),
+            history,
+            user_message,
+            mission_control.clone(),
+            Arc::clone(&tree_snapshot),
+            Arc::clone(&progress_snapshot),
+            mission_id,
+            workspace_id,
+            backend_id.clone(),
+            agent_override.clone(),
+            agent_version.clone(),
+            model_override.clone(),
+            model_effort.clone(),
+            secrets.clone(),
+            session_id.clone(),
+            mission_config_profile.clone(),
+            dry_run,
+            mission_env_profile.clone(),
+            automated,
+        )
+    };
+    let _context_usage = super::context_usage::ContextUsageReporter::for_turn(
+        &events_tx,
+        &config,
+        mission_id,
+        &history,
+        &user_message,
+    );
+    let result = run_turn(history.clone(), user_message.clone()).await;
+    let Some(verify) = super::completion_check::VerifyConfig::from_env() else {
+        return result.with_classified_failure();
+    };
+
+    let ctx = super::completion_check::VerifyContext {
+        config: &verify,
+        mission_id,
+        events_tx: &events_tx,
+        cancel: &cancel,
+        workspace: super::completion_check::test_workspace(
+            &verify,
+            &workspaces,
+            &config,
+            workspace_id,
+            mission_id,
+        )
+        .await,
+    };
+    let request = super::completion_check::original_request(&history, &user_message);
+    super::completion_check::verify_completion(
+        ctx,
+        &request,
+        history,
+        &user_message,
+        result,
+        |message, history| Box::pin(run_turn(history, message)),
+    )
+    .await
+    .with_classified_failure()
+}
+
+/// Execute a single turn for a mission.
+#[allow(clippy::too_many_arguments)]
+async fn run_mission_turn_once(
     config: Config,
     _root_agent: AgentRef,
     mcp: Arc<McpRegistry>,
@@ -1446,11 +1621,15 @@ async fn run_mission_turn(
     workspace_id: Option<Uuid>,
     backend_id: String,
     agent_override: Option<String>,
+    agent_version: Option<String>,
     model_override: Option<String>,
     model_effort: Option<String>,
     secrets: Option<Arc<SecretsStore>>,
     session_id: Option<String>,
     mission_config_profile: Option<String>,
+    dry_run: bool,
+    mission_env_profile: Option<String>,
+    automated: bool,
 ) -> AgentResult {
     let mut config = config;
     let effective_agent = agent_override.clone();
@@ -1487,9 +1666,9 @@ async fn run_mission_turn(
         // clear the global default so the profile's oh-my-opencode agent
         // models take precedence instead of being overridden.
         config.default_model = None;
-    } else if backend_id == "codex" && model_override.is_none() {
+    } else if (backend_id == "codex" || backend_id == "ollama") && model_override.is_none() {
         // The global DEFAULT_MODEL (e.g. claude-opus-4-6) is not valid for
-        // Codex.  Clear it so Codex uses its own CLI default.
+        // Codex or Ollama.  Clear it so they use their own default.
         config.default_model = None;
     }
     tracing::info!(
@@ -1557,6 +1736,25 @@ async fn run_mission_turn(
     convo.push_str(multi_step_instructions);
     convo.push('\n');
 
+    // Opt-in best-of-N: sample candidate approaches for hard turns and hand
+    // the best one to the agent.
+    if let Some(sampling) = super::best_of_n::BestOfNConfig::from_env() {
+        if sampling.applies_to(&user_message, is_multi_step) {
+            let task = format!(
+                "{}User:\n{}{}",
+                history_context, user_message, deliverable_reminder
+            );
+            if let Some(approach) =
+                super::best_of_n::select_approach(&sampling, &task, &events_tx, &cancel, mission_id)
+                    .await
+            {
+                convo.push_str("\n**Suggested approach** (selected from several sampled candidates; adapt it if it turns out to be wrong):\n");
+                convo.push_str(&approach);
+                convo.push('\n');
+            }
+        }
+    }
+
     // Ensure mission workspace exists and is configured for OpenCode.
     let workspace = workspace::resolve_workspace(&workspaces, &config, workspace_id).await;
     if let Err(e) =
@@ -1598,6 +1796,250 @@ async fn run_mission_turn(
         }
     };
 
+    let dry_run = dry_run || crate::settings::dry_run_enabled();
+    if dry_run && !matches!(backend_id.as_str(), "claudecode" | "opencode" | "mock") {
+        return AgentResult::failure(
+            format!(
+                "Dry-run mode is not supported by the {} backend; use claudecode or opencode",
+                backend_id
+            ),
+            0,
+        )
+        .with_terminal_reason(TerminalReason::LlmError);
+    }
+    if let Err(e) = workspace::write_dry_run_hook(
+        &mission_work_dir,
+        &workspace.path,
+        workspace.workspace_type,
+        dry_run,
+    )
+    .await
+    {
+        if dry_run {
+            // Never run a dry-run mission without the hook in place
+            return AgentResult::failure(format!("Failed to enable dry-run mode: {}", e), 0)
+                .with_terminal_reason(TerminalReason::LlmError);
+        }
+        tracing::warn!("Failed to remove dry-run hook: {}", e);
+    }
+    let approval_required = super::command_approval::approval_required_from_env();
+    if approval_required && !matches!(backend_id.as_str(), "claudecode" | "opencode" | "mock") {
+        return AgentResult::failure(
+            format!(
+                "Command approval is not supported by the {} backend; use claudecode or opencode",
+                backend_id
+            ),
+            0,
+        )
+        .with_terminal_reason(TerminalReason::LlmError);
+    }
+    if let Err(e) = super::command_approval::write_approval_hook(
+        &mission_work_dir,
+        &workspace.path,
+        workspace.workspace_type,
+        approval_required,
+    )
+    .await
+    {
+        if approval_required {
+            // Never run dangerous commands without the hook in place
+            return AgentResult::failure(format!("Failed to enable command approval: {}", e), 0)
+                .with_terminal_reason(TerminalReason::LlmError);
+        }
+        tracing::warn!("Failed to remove command approval hook: {}", e);
+    }
+    // Lets oversized tool results keep the lines relevant to this request.
+    if let Err(e) =
+        crate::tools::output_limit::write_last_request(&mission_work_dir, &user_message).await
+    {
+        tracing::warn!("Failed to record request for tool output limits: {}", e);
+    }
+
+    // Apply the environment profile (mission's takes priority over workspace's)
+    // so the agent and every tool it runs see the right toolchain.
+    let mut workspace = workspace;
+    let env_profile_name = mission_env_profile.or_else(|| workspace.env_profile.clone());
+    let env_profile = match env_profile_name.clone() {
+        Some(name) => match library.read().await.as_ref() {
+            Some(lib) => match lib.get_env_profile(&name).await {
+                Ok(profile) => Some(profile),
+                Err(e) => {
+                    tracing::warn!(
+                        mission_id = %mission_id,
+                        profile = %name,
+                        error = %e,
+                        "Environment profile unavailable, running without it"
+                    );
+                    None
+                }
+            },
+            None => None,
+        },
+        None => None,
+    };
+    if let Err(e) =
+        workspace::apply_env_profile(&mut workspace, &mission_work_dir, env_profile.as_ref()).await
+    {
+        tracing::warn!(
+            mission_id = %mission_id,
+            error = %e,
+            "Failed to apply environment profile"
+        );
+    }
+
+    // Apply the config profile's structured settings (after its inheritance
+    // chain): API key references are resolved from the secrets vault into the
+    // turn's environment and the tool policy extends Claude Code permissions.
+    let profile_lib = library.read().await.clone();
+    let profile_settings = match profile_lib {
+        Some(lib) => {
+            let profile = effective_config_profile.as_deref().unwrap_or("default");
+            match lib.resolve_config_profile_settings(profile).await {
+                Ok(resolved) => resolved.settings,
+                Err(e) => {
+                    tracing::warn!(
+                        mission_id = %mission_id,
+                        profile = %profile,
+                        error = %e,
+                        "Config profile settings unavailable"
+                    );
+                    crate::library::ConfigProfileSettings::default()
+                }
+            }
+        }
+        None => crate::library::ConfigProfileSettings::default(),
+    };
+    for (env_var, reference) in &profile_settings.api_key_refs {
+        let Some((registry, key)) = reference.split_once('/') else {
+            tracing::warn!(env_var = %env_var, reference = %reference, "Invalid API key reference");
+            continue;
+        };
+        let Some(store) = secrets.as_ref() else {
+            tracing::warn!(env_var = %env_var, "Secrets store unavailable for API key reference");
+            continue;
+        };
+        match store.get_secret(registry, key).await {
+            Ok(value) => {
+                workspace.env_vars.insert(env_var.clone(), value);
+            }
+            Err(e) => {
+                tracing::warn!(
+                    mission_id = %mission_id,
+                    env_var = %env_var,
+                    error = %e,
+                    "Failed to resolve API key reference"
+                );
+            }
+        }
+    }
+    if backend_id == "claudecode" {
+        if let Err(e) =
+            workspace::write_tool_policy(&mission_work_dir, &profile_settings.tool_policy).await
+        {
+            tracing::warn!(
+                mission_id = %mission_id,
+                error = %e,
+                "Failed to apply profile tool policy"
+            );
+        }
+    }
+
+    // Route the agent's HTTP traffic through the workspace egress policy for
+    // the duration of the turn.
+    let _egress_proxy = match start_egress_proxy(&mut workspace, mission_id, automated, &events_tx)
+        .await
+    {
+        Ok(proxy) => proxy,
+        Err(message) => {
+            return AgentResult::failure(message, 0).with_terminal_reason(TerminalReason::LlmError);
+        }
+    };
+
+    // Materialize the pinned agent version so library edits made after the
+    // mission was created don't change its behavior.
+    if let (Some(agent), Some(version)) = (effective_agent.as_deref(), agent_version.as_deref()) {
+        let pinned = match library.read().await.as_ref() {
+            Some(lib) => Some(lib.get_library_agent_version(agent, version).await),
+            None => None,
+        };
+        match pinned {
+            Some(Ok(pinned)) => {
+                if let Err(e) = workspace::write_agents_to_workspace(
+                    &mission_work_dir,
+                    &[workspace::AgentContent {
+                        name: pinned.name,
+                        content: pinned.content,
+                    }],
+                )
+                .await
+                {
+                    tracing::warn!(
+                        mission_id = %mission_id,
+                        error = %e,
+                        "Failed to write pinned agent version"
+                    );
+                }
+            }
+            Some(Err(e)) => {
+                tracing::warn!(
+                    mission_id = %mission_id,
+                    agent = %agent,
+                    version = %version,
+                    error = %e,
+                    "Pinned agent version unavailable, using current definition"
+                );
+            }
+            None => {}
+        }
+    }
+
+    // Apply layered system prompt overrides (base → workspace → agent) from the library.
+    let system_prompt = {
+        let lib_guard = library.read().await;
+        match lib_guard.as_ref() {
+            Some(lib) => lib
+                .compose_system_prompt(Some(&workspace.name), effective_agent.as_deref())
+                .await
+                .map(|preview| preview.prompt)
+                .unwrap_or_else(|e| {
+                    tracing::warn!(error = %e, "Failed to compose system prompt overrides");
+                    String::new()
+                }),
+            None => String::new(),
+        }
+    };
+    let system_prompt = match profile_settings.system_prompt_append.as_deref() {
+        Some(append) if system_prompt.is_empty() => append.to_string(),
+        Some(append) => format!("{}\n\n{}", system_prompt, append),
+        None => system_prompt,
+    };
+    if let Err(e) =
+        workspace::write_system_prompt_to_workspace(&mission_work_dir, &backend_id, &system_prompt)
+            .await
+    {
+        tracing::warn!(
+            mission_id = %mission_id,
+            error = %e,
+            "Failed to write system prompt overrides"
+        );
+    }
+
+    let mut environment = super::mission_environment::MissionEnvironment::new(
+        mission_id,
+        &backend_id,
+        &workspace,
+        &mission_work_dir,
+        mcp.list_configs().await,
+    );
+    environment.model = config.default_model.clone();
+    environment.model_effort = model_effort.clone();
+    environment.config_profile = effective_config_profile.clone();
+    environment.agent = effective_agent.clone();
+    environment.agent_version = agent_version.clone();
+    environment.env_profile = env_profile_name;
+    environment.sandbox.dry_run = dry_run;
+    super::mission_environment::capture(&mission_work_dir, &environment).await;
+
     // Session rotation: Prevent OOM by resetting sessions every N turns
     // Calculate turn count (each assistant response = 1 turn)
     const SESSION_ROTATION_INTERVAL: usize = 50;
@@ -1665,7 +2107,15 @@ async fn run_mission_turn(
     // For Claude Code, check if this is a continuation turn (has prior assistant response).
     // Note: history may include the current user message before the turn runs,
     // so we check for assistant messages to determine if this is truly a continuation.
-    let is_continuation = history.iter().any(|(role, _)| role == "assistant");
+    let mut is_continuation = history.iter().any(|(role, _)| role == "assistant");
+    // First turn after a backend handoff: Claude Code and Amp only get the current
+    // message, so carry the conversation over and start a fresh session.
+    if let Some(snapshot) = super::handoff::take_snapshot(&mission_work_dir, &backend_id).await {
+        if matches!(backend_id.as_str(), "claudecode" | "amp") {
+            user_message = snapshot.prompt(&user_message, config.context.max_history_total_chars);
+            is_continuation = false;
+        }
+    }
     let result = match backend_id.as_str() {
         "claudecode" => {
             // Track the effective message and session used for the most recent
@@ -2021,6 +2471,35 @@ async fn run_mission_turn(
                 }
             }
         }
+        "ollama" => {
+            run_ollama_turn(
+                &config,
+                &library,
+                effective_config_profile.as_deref(),
+                &mission_work_dir,
+                &history,
+                &user_message,
+                mission_id,
+                events_tx.clone(),
+                cancel.clone(),
+            )
+            .await
+        }
+        "mock" => {
+            let turn = history
+                .iter()
+                .filter(|(role, _)| role == "assistant")
+                .count();
+            run_mock_turn(
+                config.mock_llm_script.as_deref(),
+                &user_message,
+                turn,
+                mission_id,
+                events_tx.clone(),
+                cancel.clone(),
+            )
+            .await
+        }
         _ => {
             // Don't send Error event - the failure will be emitted as an AssistantMessage
             // with success=false by the caller (control.rs), avoiding duplicate messages.
@@ -3039,7 +3518,8 @@ pub fn run_claudecode_turn<'a>(
             tokio::select! {
                 _ = cancel.cancelled() => {
                     tracing::info!(mission_id = %mission_id, "Claude Code execution cancelled, killing process");
-                    // Kill the process to stop consuming API resources
+                    // Kill the process (and its tools) to stop consuming API resources
+                    terminate_cli_tree(pty.pid(), mission_id, &events_tx).await;
                     pty.kill();
                     reader_handle.abort();
                     return AgentResult::failure("Cancelled".to_string(), 0)
@@ -3231,6 +3711,13 @@ pub fn run_claudecode_turn<'a>(
                                         total_cache_read_tokens +=
                                             usage.cache_read_input_tokens.unwrap_or(0);
                                     }
+                                    if let Some(event) = llm_usage_event(
+                                        &evt,
+                                        observed_model.as_deref().or(model),
+                                        mission_id,
+                                    ) {
+                                        let _ = events_tx.send(event);
+                                    }
                                     for (content_idx, block) in evt.message.content.into_iter().enumerate() {
                                         let content_idx = content_idx as u32;
                                         match block {
@@ -3268,7 +3755,7 @@ pub fn run_claudecode_turn<'a>(
                                                     mission_id: Some(mission_id),
                                                 });
 
-                                                if name == "question" || name == "AskUserQuestion" || name.starts_with("ui_") {
+                                                if super::user_question::is_question_tool(&name) || name.starts_with("ui_") {
                                                     if let Some(ref hub) = tool_hub {
                                                         tracing::info!(
                                                             mission_id = %mission_id,
@@ -3286,26 +3773,53 @@ pub fn run_claudecode_turn<'a>(
                                                             )
                                                             .await;
                                                         }
-                                                        let rx = hub.register(id.clone()).await;
+                                                        let rx = if super::user_question::is_question_tool(&name) {
+                                                            None
+                                                        } else {
+                                                            Some(hub.register(id.clone()).await)
+                                                        };
 
                                                         pty.kill();
                                                         reader_handle.abort();
 
-                                                        let answer = tokio::select! {
-                                                            _ = cancel.cancelled() => {
-                                                                return AgentResult::failure("Cancelled".to_string(), 0)
-                                                                    .with_terminal_reason(TerminalReason::Cancelled);
-                                                            }
-                                                            res = rx => {
-                                                                match res {
-                                                                    Ok(v) => v,
-                                                                    Err(_) => {
-                                                                        return AgentResult::failure(
-                                                                            "Frontend tool result channel closed".to_string(), 0
-                                                                        ).with_terminal_reason(TerminalReason::LlmError);
+                                                        let answer = match rx {
+                                                            None => match super::user_question::ask(
+                                                                &hub,
+                                                                &events_tx,
+                                                                Some(mission_id),
+                                                                &id,
+                                                                &input,
+                                                                Some(&cancel),
+                                                            )
+                                                            .await
+                                                            {
+                                                                super::user_question::Outcome::Answered(v) => v,
+                                                                super::user_question::Outcome::Cancelled => {
+                                                                    return AgentResult::failure("Cancelled".to_string(), 0)
+                                                                        .with_terminal_reason(TerminalReason::Cancelled);
+                                                                }
+                                                                super::user_question::Outcome::Closed => {
+                                                                    return AgentResult::failure(
+                                                                        "Frontend tool result channel closed".to_string(), 0
+                                                                    ).with_terminal_reason(TerminalReason::LlmError);
+                                                                }
+                                                            },
+                                                            Some(rx) => tokio::select! {
+                                                                _ = cancel.cancelled() => {
+                                                                    return AgentResult::failure("Cancelled".to_string(), 0)
+                                                                        .with_terminal_reason(TerminalReason::Cancelled);
+                                                                }
+                                                                res = rx => {
+                                                                    match res {
+                                                                        Ok(v) => v,
+                                                                        Err(_) => {
+                                                                            return AgentResult::failure(
+                                                                                "Frontend tool result channel closed".to_string(), 0
+                                                                            ).with_terminal_reason(TerminalReason::LlmError);
+                                                                        }
                                                                     }
                                                                 }
-                                                            }
+                                                            },
                                                         };
 
                                                         if let Some(ref status_ref) = status {
@@ -3589,7 +4103,7 @@ fn workspace_path_for_env(
     host_path.to_path_buf()
 }
 
-fn strip_ansi_codes(input: &str) -> Cow<'_, str> {
+pub(crate) fn strip_ansi_codes(input: &str) -> Cow<'_, str> {
     let bytes = input.as_bytes();
     if !bytes
         .iter()
@@ -7199,6 +7713,7 @@ pub struct BackendPreflightResult {
 
 /// Check if a backend can run in the given workspace.
 /// This performs a lightweight check without actually installing anything.
+/// `cli_path` overrides the backend CLI (the daemon URL for `ollama`).
 pub async fn check_backend_prerequisites(
     workspace: &Workspace,
     backend_id: &str,
@@ -7221,6 +7736,28 @@ pub async fn check_backend_prerequisites(
             let cli = cli_path.unwrap_or("amp");
             check_amp_prerequisites(&workspace_exec, cwd, cli).await
         }
+        "ollama" => {
+            let client = crate::backend::ollama::OllamaClient::new(
+                cli_path.unwrap_or(crate::backend::ollama::DEFAULT_BASE_URL),
+            );
+            let reachable = client.list_models().await;
+            BackendPreflightResult {
+                backend_id: backend_id.to_string(),
+                available: reachable.is_ok(),
+                cli_available: reachable.is_ok(),
+                auto_install_possible: false,
+                missing_dependencies: Vec::new(),
+                message: reachable.err().map(|e| e.to_string()),
+            }
+        }
+        "mock" => BackendPreflightResult {
+            backend_id: backend_id.to_string(),
+            available: true,
+            cli_available: true,
+            auto_install_possible: false,
+            missing_dependencies: Vec::new(),
+            message: None,
+        },
         _ => BackendPreflightResult {
             backend_id: backend_id.to_string(),
             available: false,
@@ -7228,7 +7765,7 @@ pub async fn check_backend_prerequisites(
             auto_install_possible: false,
             missing_dependencies: vec![format!("unknown backend: {}", backend_id)],
             message: Some(format!(
-                "Unknown backend '{}'. Supported backends: claudecode, opencode, codex, amp",
+                "Unknown backend '{}'. Supported backends: claudecode, opencode, codex, amp, mock",
                 backend_id
             )),
         },
@@ -8359,6 +8896,7 @@ pub async fn run_opencode_turn(
                             // Emit a real-time error event so the frontend
                             // shows the error immediately, not just at the end.
                             let _ = stderr_events_tx.send(AgentEvent::Error {
+                                code: Some(ErrorCode::classify_or(&err_msg, ErrorCode::ProviderError)),
                                 message: err_msg,
                                 mission_id: Some(mission_id_clone),
                                 resumable: true,
@@ -8434,6 +8972,7 @@ pub async fn run_opencode_turn(
         tokio::select! {
             _ = cancel.cancelled() => {
                 tracing::info!(mission_id = %mission_id, "OpenCode execution cancelled, killing process");
+                terminate_cli_tree(child.id(), mission_id, &events_tx).await;
                 let _ = child.kill().await;
                 // Await background tasks so in-flight mutex writes complete
                 // before we return.  Use the same teardown discipline as the
@@ -8514,6 +9053,7 @@ pub async fn run_opencode_turn(
                                 "Model API failed after {} consecutive retries. The model provider may be down or misconfigured.",
                                 consecutive_retries
                             ),
+                            code: Some(ErrorCode::ProviderError),
                             mission_id: Some(mission_id),
                             resumable: true,
                         });
@@ -9529,6 +10069,7 @@ pub async fn run_amp_turn(
         tokio::select! {
             _ = cancel.cancelled() => {
                 tracing::info!(mission_id = %mission_id, "Amp execution cancelled, killing process");
+                terminate_cli_tree(child.id(), mission_id, &events_tx).await;
                 let _ = child.kill().await;
                 if let Some(handle) = stderr_handle {
                     handle.abort();
@@ -9659,6 +10200,11 @@ pub async fn run_amp_turn(
                                     total_cache_creation_tokens += usage.cache_creation_input_tokens.unwrap_or(0);
                                     total_cache_read_tokens += usage.cache_read_input_tokens.unwrap_or(0);
                                 }
+                                if let Some(event) =
+                                    llm_usage_event(&evt, model_used.as_deref(), mission_id)
+                                {
+                                    let _ = events_tx.send(event);
+                                }
 
                                 for (content_idx, block) in evt.message.content.into_iter().enumerate() {
                                     let content_idx = content_idx as u32;
@@ -9969,12 +10515,7 @@ impl From<&MissionRunner> for RunningMissionInfo {
         let seconds_since_activity = runner.last_activity.elapsed().as_secs();
         Self {
             mission_id: runner.mission_id,
-            state: match runner.state {
-                MissionRunState::Queued => "queued".to_string(),
-                MissionRunState::Running => "running".to_string(),
-                MissionRunState::WaitingForTool => "waiting_for_tool".to_string(),
-                MissionRunState::Finished => "finished".to_string(),
-            },
+            state: runner.state.label().to_string(),
             queue_len: runner.queue.len(),
             history_len: runner.history.len(),
             seconds_since_activity,
@@ -10094,15 +10635,15 @@ pub async fn run_codex_turn(
     };
 
     // Send message streaming
-    let (mut event_rx, _handle) = match backend.send_message_streaming(&session, user_message).await
-    {
-        Ok(result) => result,
-        Err(e) => {
-            tracing::error!("Failed to send message to Codex: {}", e);
-            return AgentResult::failure(format!("Codex execution failed: {}", e), 0)
-                .with_terminal_reason(TerminalReason::LlmError);
-        }
-    };
+    let (mut event_rx, stream_handle) =
+        match backend.send_message_streaming(&session, user_message).await {
+            Ok(result) => result,
+            Err(e) => {
+                tracing::error!("Failed to send message to Codex: {}", e);
+                return AgentResult::failure(format!("Codex execution failed: {}", e), 0)
+                    .with_terminal_reason(TerminalReason::LlmError);
+            }
+        };
 
     // Process events until completion or cancellation
     let mut assistant_message = String::new();
@@ -10120,7 +10661,14 @@ pub async fn run_codex_turn(
         tokio::select! {
             _ = cancel.cancelled() => {
                 tracing::info!("Codex turn cancelled for mission {}", mission_id);
-                // Note: Codex process will be cleaned up automatically when the event stream task ends
+                // Dropping the receiver makes the event stream task terminate the Codex
+                // process tree; wait for it so no tool processes outlive the turn.
+                drop(event_rx);
+                let _ = tokio::time::timeout(
+                    crate::process_tree::TERM_GRACE + Duration::from_secs(2),
+                    stream_handle,
+                )
+                .await;
                 return AgentResult::failure("Mission cancelled".to_string(), 0)
                     .with_terminal_reason(TerminalReason::Cancelled);
             }
@@ -10162,6 +10710,15 @@ pub async fn run_codex_turn(
                             mission_id: Some(mission_id),
                         });
                     }
+                    ExecutionEvent::ToolOutputDelta { id, name, stream, content } => {
+                        let _ = events_tx.send(AgentEvent::ToolOutputDelta {
+                            tool_call_id: id,
+                            name,
+                            stream,
+                            content,
+                            mission_id: Some(mission_id),
+                        });
+                    }
                     ExecutionEvent::TurnSummary { content } => {
                         if !content.trim().is_empty() {
                             last_summary = Some(content);
@@ -10278,6 +10835,444 @@ Update it to the latest version (`npm install -g @openai/codex@latest`) and retr
     result
 }
 
+/// Start the workspace egress proxy for a turn and point the workspace
+/// environment at it. Destinations outside the allowlist are reported as
+/// `EgressViolation` events. Fails when the policy asks the proxy to refuse
+/// traffic but the proxy can't be used for this workspace.
+async fn start_egress_proxy(
+    workspace: &mut Workspace,
+    mission_id: Uuid,
+    automated: bool,
+    events_tx: &broadcast::Sender<AgentEvent>,
+) -> Result<Option<crate::egress::EgressProxy>, String> {
+    let Some(policy) = workspace.egress.clone() else {
+        return Ok(None);
+    };
+    let deny = policy.blocks(automated);
+    // The proxy listens on the host loopback, which containers on a private
+    // Tailscale network cannot reach.
+    let unsupported = if !workspace::use_nspawn_for_workspace(workspace) {
+        Some("egress policies apply to container workspaces only")
+    } else if !workspace.shared_network.unwrap_or(true)
+        && crate::nspawn::tailscale_enabled(&workspace.env_vars)
+    {
+        Some("egress policies are not supported with Tailscale networking")
+    } else {
+        None
+    };
+    if let Some(reason) = unsupported {
+        if deny {
+            return Err(format!(
+                "Cannot apply the workspace egress proxy policy: {}",
+                reason
+            ));
+        }
+        tracing::warn!(
+            mission_id = %mission_id,
+            workspace = %workspace.name,
+            "Workspace egress policy not applied: {}",
+            reason
+        );
+        return Ok(None);
+    }
+
+    let (violations_tx, mut violations_rx) = mpsc::unbounded_channel();
+    let proxy = crate::egress::EgressProxy::start(&policy, deny, violations_tx)
+        .await
+        .map_err(|e| format!("Failed to start egress proxy: {}", e))?;
+    workspace.env_vars.extend(proxy.env());
+    tracing::info!(
+        mission_id = %mission_id,
+        proxy = %proxy.url(),
+        deny = deny,
+        "Egress proxy started"
+    );
+
+    let events_tx = events_tx.clone();
+    tokio::spawn(async move {
+        while let Some(violation) = violations_rx.recv().await {
+            tracing::info!(
+                mission_id = %mission_id,
+                host = %violation.host,
+                port = violation.port,
+                blocked = violation.blocked,
+                "Egress outside workspace allowlist"
+            );
+            let _ = events_tx.send(AgentEvent::EgressViolation {
+                mission_id,
+                host: violation.host,
+                port: violation.port,
+                blocked: violation.blocked,
+            });
+        }
+    });
+    Ok(Some(proxy))
+}
+
+/// Terminate a cancelled CLI together with every tool process it spawned, and
+/// report the PIDs that were reaped.
+async fn terminate_cli_tree(
+    pid: Option<u32>,
+    mission_id: Uuid,
+    events_tx: &broadcast::Sender<AgentEvent>,
+) {
+    let Some(pid) = pid else {
+        return;
+    };
+    let report = crate::process_tree::terminate_tree(pid, crate::process_tree::TERM_GRACE).await;
+    if report.reaped.is_empty() && report.survivors.is_empty() {
+        return;
+    }
+    tracing::info!(
+        mission_id = %mission_id,
+        reaped = ?report.reaped,
+        killed = ?report.killed,
+        survivors = ?report.survivors,
+        "Terminated CLI process tree"
+    );
+    let _ = events_tx.send(AgentEvent::ProcessesTerminated {
+        mission_id,
+        reaped: report.reaped,
+        killed: report.killed,
+        survivors: report.survivors,
+    });
+}
+
+/// Run a turn against the deterministic mock backend. `turn` is the number of
+/// assistant replies so far, used to pick sequenced responses.
+pub async fn run_mock_turn(
+    script_path: Option<&std::path::Path>,
+    user_message: &str,
+    turn: usize,
+    mission_id: Uuid,
+    events_tx: broadcast::Sender<AgentEvent>,
+    cancel: CancellationToken,
+) -> AgentResult {
+    use crate::backend::events::ExecutionEvent;
+    use crate::backend::mock::MockScript;
+
+    let script = match script_path {
+        Some(path) => match MockScript::load(path) {
+            Ok(script) => script,
+            Err(e) => {
+                return AgentResult::failure(
+                    format!("Failed to load mock LLM script {}: {}", path.display(), e),
+                    0,
+                )
+                .with_terminal_reason(TerminalReason::LlmError);
+            }
+        },
+        None => MockScript::default(),
+    };
+    let response = script.respond(user_message, turn);
+
+    let mut assistant_message = String::new();
+    let mut error_message: Option<String> = None;
+    let mut thinking_emitted = false;
+    let mut usage = crate::cost::TokenUsage::default();
+
+    for event in response.events(&format!("{}-{}", mission_id, turn)) {
+        if cancel.is_cancelled() {
+            return AgentResult::failure("Mission cancelled".to_string(), 0)
+                .with_terminal_reason(TerminalReason::Cancelled);
+        }
+        match event {
+            ExecutionEvent::Thinking { content } => {
+                let _ = events_tx.send(AgentEvent::Thinking {
+                    content,
+                    done: false,
+                    mission_id: Some(mission_id),
+                });
+                thinking_emitted = true;
+            }
+            ExecutionEvent::ToolCall { id, name, args } => {
+                let _ = events_tx.send(AgentEvent::ToolCall {
+                    tool_call_id: id,
+                    name,
+                    args,
+                    mission_id: Some(mission_id),
+                });
+            }
+            ExecutionEvent::ToolOutputDelta {
+                id,
+                name,
+                stream,
+                content,
+            } => {
+                let _ = events_tx.send(AgentEvent::ToolOutputDelta {
+                    tool_call_id: id,
+                    name,
+                    stream,
+                    content,
+                    mission_id: Some(mission_id),
+                });
+            }
+            ExecutionEvent::ToolResult { id, name, result } => {
+                let _ = events_tx.send(AgentEvent::ToolResult {
+                    tool_call_id: id,
+                    name,
+                    result,
+                    mission_id: Some(mission_id),
+                });
+            }
+            ExecutionEvent::TextDelta { content } => {
+                assistant_message = content;
+                let _ = events_tx.send(AgentEvent::TextDelta {
+                    content: assistant_message.clone(),
+                    mission_id: Some(mission_id),
+                });
+            }
+            ExecutionEvent::Usage {
+                input_tokens,
+                output_tokens,
+            } => {
+                usage.input_tokens = usage.input_tokens.saturating_add(input_tokens);
+                usage.output_tokens = usage.output_tokens.saturating_add(output_tokens);
+            }
+            ExecutionEvent::Error { message } => error_message = Some(message),
+            ExecutionEvent::TurnSummary { .. } | ExecutionEvent::MessageComplete { .. } => {}
+        }
+        // Yield so subscribers see events in order, as with a streaming backend.
+        tokio::task::yield_now().await;
+    }
+
+    if thinking_emitted {
+        let _ = events_tx.send(AgentEvent::Thinking {
+            content: String::new(),
+            done: true,
+            mission_id: Some(mission_id),
+        });
+    }
+
+    let mut result = match error_message {
+        Some(message) => {
+            AgentResult::failure(message, 0).with_terminal_reason(TerminalReason::LlmError)
+        }
+        None => AgentResult::success(assistant_message, 0)
+            .with_terminal_reason(TerminalReason::Completed),
+    };
+    if usage.has_usage() {
+        result = result.with_usage(usage);
+    }
+    result.with_model("mock")
+}
+
+/// Daemon URL and default model for Ollama missions: the config profile's
+/// `.ollama/settings.json`, falling back to `OLLAMA_BASE_URL`.
+async fn resolve_ollama_settings(
+    config: &Config,
+    library: &SharedLibrary,
+    config_profile: Option<&str>,
+) -> (String, Option<String>) {
+    let lib = library.read().await.clone();
+    let profile_config = match lib {
+        Some(lib) => lib
+            .get_ollama_config_for_profile(config_profile.unwrap_or("default"))
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to load Ollama config from library: {}", e);
+                Default::default()
+            }),
+        None => Default::default(),
+    };
+    let base_url = profile_config
+        .base_url
+        .filter(|url| !url.trim().is_empty())
+        .unwrap_or_else(|| config.ollama_base_url.clone());
+    let default_model = profile_config
+        .default_model
+        .filter(|model| !model.trim().is_empty());
+    (base_url, default_model)
+}
+
+/// Run a turn against an Ollama model with the in-process agent loop.
+/// Ollama keeps no session, so the conversation is resent every turn.
+#[allow(clippy::too_many_arguments)]
+pub async fn run_ollama_turn(
+    config: &Config,
+    library: &SharedLibrary,
+    config_profile: Option<&str>,
+    work_dir: &std::path::Path,
+    history: &[(String, String)],
+    user_message: &str,
+    mission_id: Uuid,
+    events_tx: broadcast::Sender<AgentEvent>,
+    cancel: CancellationToken,
+) -> AgentResult {
+    use crate::backend::events::ExecutionEvent;
+    use crate::backend::ollama::{self, AgentLoop, ChatMessage, OllamaClient};
+
+    let (base_url, profile_model) = resolve_ollama_settings(config, library, config_profile).await;
+    let client = OllamaClient::new(&base_url);
+    let model = match config.default_model.clone().or(profile_model) {
+        Some(model) => model,
+        None => match client.list_models().await {
+            Ok(models) if !models.is_empty() => models[0].name.clone(),
+            Ok(_) => {
+                return AgentResult::failure(
+                    format!("No models installed on the Ollama daemon at {}", base_url),
+                    0,
+                )
+                .with_terminal_reason(TerminalReason::LlmError)
+            }
+            Err(e) => {
+                return AgentResult::failure(e.to_string(), 0)
+                    .with_terminal_reason(TerminalReason::LlmError)
+            }
+        },
+    };
+
+    let tools = ollama::mission_tools();
+    let native_tools = client.supports_tools(&model).await;
+    let mut system = format!(
+        "You are an autonomous coding agent. Your working directory is {}; \
+         relative paths resolve from it. Use the tools to inspect and change files \
+         and run commands, then give a concise final answer.",
+        work_dir.display()
+    );
+    if !native_tools {
+        system.push_str("\n\n");
+        system.push_str(&ollama::emulated_tools_prompt(&tools));
+    }
+
+    // Most recent history that fits the budget, oldest first.
+    let prior = match history.last() {
+        Some((role, content)) if role == "user" && content == user_message => {
+            &history[..history.len() - 1]
+        }
+        _ => history,
+    };
+    let mut budget = config.context.max_history_total_chars;
+    let mut messages: Vec<ChatMessage> = Vec::new();
+    for (role, content) in prior.iter().rev() {
+        if content.len() > budget {
+            break;
+        }
+        budget -= content.len();
+        messages.push(ChatMessage::new(role, content.clone()));
+    }
+    messages.push(ChatMessage::new("system", system));
+    messages.reverse();
+    messages.push(ChatMessage::new("user", user_message));
+
+    tracing::info!(
+        mission_id = %mission_id,
+        base_url = %base_url,
+        model = %model,
+        native_tools,
+        history_messages = messages.len() - 2,
+        "Starting Ollama turn"
+    );
+
+    let agent = AgentLoop {
+        client,
+        model: model.clone(),
+        messages,
+        tools,
+        native_tools,
+        working_dir: work_dir.to_path_buf(),
+        max_iterations: config.max_iterations,
+    };
+    let (loop_tx, mut loop_rx) = mpsc::unbounded_channel();
+    let run = tokio::spawn(agent.run(loop_tx, cancel.clone()));
+
+    let mut text = String::new();
+    let mut thinking_emitted = false;
+    let mut usage = crate::cost::TokenUsage::default();
+    while let Some(event) = loop_rx.recv().await {
+        match event {
+            ExecutionEvent::Thinking { content } => {
+                let _ = events_tx.send(AgentEvent::Thinking {
+                    content,
+                    done: false,
+                    mission_id: Some(mission_id),
+                });
+                thinking_emitted = true;
+            }
+            ExecutionEvent::ToolCall { id, name, args } => {
+                let _ = events_tx.send(AgentEvent::ToolCall {
+                    tool_call_id: id,
+                    name,
+                    args,
+                    mission_id: Some(mission_id),
+                });
+            }
+            ExecutionEvent::ToolOutputDelta {
+                id,
+                name,
+                stream,
+                content,
+            } => {
+                let _ = events_tx.send(AgentEvent::ToolOutputDelta {
+                    tool_call_id: id,
+                    name,
+                    stream,
+                    content,
+                    mission_id: Some(mission_id),
+                });
+            }
+            ExecutionEvent::ToolResult { id, name, result } => {
+                let _ = events_tx.send(AgentEvent::ToolResult {
+                    tool_call_id: id,
+                    name,
+                    result,
+                    mission_id: Some(mission_id),
+                });
+            }
+            ExecutionEvent::TextDelta { content } => {
+                // Text deltas carry the accumulated message.
+                text.push_str(&content);
+                let _ = events_tx.send(AgentEvent::TextDelta {
+                    content: text.clone(),
+                    mission_id: Some(mission_id),
+                });
+            }
+            ExecutionEvent::Usage {
+                input_tokens,
+                output_tokens,
+            } => {
+                usage.input_tokens = usage.input_tokens.saturating_add(input_tokens);
+                usage.output_tokens = usage.output_tokens.saturating_add(output_tokens);
+            }
+            ExecutionEvent::Error { .. }
+            | ExecutionEvent::TurnSummary { .. }
+            | ExecutionEvent::MessageComplete { .. } => {}
+        }
+    }
+
+    if thinking_emitted {
+        let _ = events_tx.send(AgentEvent::Thinking {
+            content: String::new(),
+            done: true,
+            mission_id: Some(mission_id),
+        });
+    }
+
+    let mut result = match run.await {
+        Ok(Ok(answer)) => {
+            AgentResult::success(answer, 0).with_terminal_reason(TerminalReason::Completed)
+        }
+        Ok(Err(_)) if cancel.is_cancelled() => {
+            AgentResult::failure("Mission cancelled".to_string(), 0)
+                .with_terminal_reason(TerminalReason::Cancelled)
+        }
+        Ok(Err(e)) => {
+            let reason = match e.downcast_ref::<ollama::LoopStop>() {
+                Some(ollama::LoopStop::IterationLimit(_)) => TerminalReason::MaxIterations,
+                Some(ollama::LoopStop::RepeatedToolCall(_)) => TerminalReason::InfiniteLoop,
+                None => TerminalReason::LlmError,
+            };
+            AgentResult::failure(e.to_string(), 0).with_terminal_reason(reason)
+        }
+        Err(e) => AgentResult::failure(format!("Ollama turn panicked: {}", e), 0)
+            .with_terminal_reason(TerminalReason::LlmError),
+    };
+    if usage.has_usage() {
+        result = result.with_usage(usage);
+    }
+    result.with_model(model)
+}
+
 /// Generate a concise summary of recent conversation turns for session rotation.
 /// Summarizes the last N turns to preserve context when starting a new session.
 fn generate_session_summary(history: &[(String, String)], last_n_turns: usize) -> String {
@@ -10431,8 +11426,8 @@ mod tests {
         preferred_model_for_cost, resolve_cost_cents_and_source, running_health,
         sanitized_opencode_stdout, stall_severity, strip_ansi_codes, strip_opencode_banner_lines,
         strip_think_tags, summarize_recent_opencode_stderr, sync_opencode_agent_config,
-        MissionHealth, MissionRunState, MissionStallSeverity, OpencodeSseState, STALL_SEVERE_SECS,
-        STALL_WARN_SECS,
+        MissionHealth, MissionRunState, MissionRunner, MissionStallSeverity, OpencodeSseState,
+        STALL_SEVERE_SECS, STALL_WARN_SECS,
     };
     use crate::agents::{AgentResult, CostSource, TerminalReason};
     use crate::library::types::CommandParam;
@@ -10441,6 +11436,76 @@ mod tests {
     use std::fs;
     use uuid::Uuid;
 
+    #[tokio::test]
+    async fn mock_turns_replay_the_script() {
+        use crate::backend::mock::{MockResponse, MockScript};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("script.json");
+        MockScript::sequence([
+            MockResponse::text("listed")
+                .with_tool_call("bash", json!({"command": "ls"}), json!("a.txt"))
+                .with_usage(10, 2),
+            MockResponse::error("outage"),
+        ])
+        .write_to(&path)
+        .unwrap();
+
+        let mission_id = Uuid::new_v4();
+        let (events_tx, mut events_rx) = tokio::sync::broadcast::channel(16);
+        let cancel = tokio_util::sync::CancellationToken::new();
+        let result = super::run_mock_turn(
+            Some(&path),
+            "go",
+            0,
+            mission_id,
+            events_tx.clone(),
+            cancel.clone(),
+        )
+        .await;
+        assert!(result.success);
+        assert_eq!(result.output, "listed");
+        assert_eq!(result.model_used.as_deref(), Some("mock"));
+        assert!(matches!(
+            events_rx.try_recv(),
+            Ok(super::AgentEvent::ToolCall { .. })
+        ));
+
+        let result =
+            super::run_mock_turn(Some(&path), "go", 1, mission_id, events_tx, cancel).await;
+        assert!(!result.success);
+        assert_eq!(result.output, "outage");
+        assert_eq!(result.terminal_reason, Some(TerminalReason::LlmError));
+    }
+
+    #[test]
+    fn queued_messages_can_be_moved_and_edited() {
+        let mut runner = MissionRunner::new(
+            Uuid::new_v4(),
+            Uuid::new_v4(),
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        let ids: Vec<Uuid> = (0..3).map(|_| Uuid::new_v4()).collect();
+        for (i, id) in ids.iter().enumerate() {
+            runner.queue_message(*id, format!("message {i}"), None);
+        }
+
+        assert!(runner.move_in_queue(ids[2], 0));
+        assert!(runner.move_in_queue(ids[0], 99));
+        let order: Vec<Uuid> = runner.queue.iter().map(|qm| qm.id).collect();
+        assert_eq!(order, vec![ids[2], ids[1], ids[0]]);
+
+        assert!(runner.edit_queued_message(ids[1], "fixed typo"));
+        assert_eq!(runner.queue[1].content, "fixed typo");
+        assert!(!runner.edit_queued_message(Uuid::new_v4(), "missing"));
+        assert!(!runner.move_in_queue(Uuid::new_v4(), 0));
+    }
+
     #[test]
     fn sync_opencode_agent_config_removes_overrides_when_plugin_enabled() {
         let temp_dir = tempfile::tempdir().expect("temp dir");
@@ -11283,14 +12348,14 @@ mod tests {
     }
 
     #[test]
-    fn running_health_healthy_for_queued_state_even_if_stale() {
-        let health = running_health(MissionRunState::Queued, STALL_SEVERE_SECS + 100);
+    fn running_health_healthy_for_starting_state_even_if_stale() {
+        let health = running_health(MissionRunState::Starting, STALL_SEVERE_SECS + 100);
         assert!(matches!(health, MissionHealth::Healthy));
     }
 
     #[test]
-    fn running_health_healthy_for_finished_state() {
-        let health = running_health(MissionRunState::Finished, STALL_SEVERE_SECS + 100);
+    fn running_health_healthy_for_idle_state() {
+        let health = running_health(MissionRunState::Idle, STALL_SEVERE_SECS + 100);
         assert!(matches!(health, MissionHealth::Healthy));
     }
 
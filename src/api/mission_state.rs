@@ -0,0 +1,354 @@
+//! Typed execution state of a mission and the routing of incoming messages.
+//!
+//! A mission moves through `MissionRunState` only via `MissionRunEvent`s:
+//!
+//! - `Queued`: `Idle` -> `Starting` (no change while a turn executes)
+//! - `Started`: `Idle` / `Starting` -> `Running`
+//! - `ToolRequested` / `ToolAnswered`: `Running` <-> `WaitingForTool`
+//! - `CancelRequested`: `Running` / `WaitingForTool` -> `Draining`
+//! - `Finished`: `Running` / `WaitingForTool` / `Draining` -> `Idle`, or
+//!   `Starting` when messages are still queued
+//!
+//! `route_message` decides, from a snapshot of the control session, where a
+//! user message goes: to a parallel runner, to a newly started parallel
+//! runner, or to the main session (possibly adopting, creating, switching to
+//! or reloading a mission first).
+
+use std::fmt;
+
+use uuid::Uuid;
+
+use super::control::MissionStatus;
+
+/// Execution state of a mission.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MissionRunState {
+    /// Nothing queued or executing
+    #[default]
+    Idle,
+    /// Messages queued, waiting for a turn to start
+    Starting,
+    /// A turn is executing
+    Running,
+    /// Waiting for frontend tool input
+    WaitingForTool,
+    /// Cancelled, waiting for the turn to stop
+    Draining,
+}
+
+/// Something that happened to a mission's execution.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MissionRunEvent {
+    /// A message was queued
+    Queued,
+    /// A turn started executing
+    Started,
+    /// The turn waits for a frontend tool result
+    ToolRequested,
+    /// The frontend tool result arrived
+    ToolAnswered,
+    /// The turn was cancelled
+    CancelRequested,
+    /// The turn ended; `queued` tells whether messages are still waiting
+    Finished { queued: bool },
+}
+
+/// An event that isn't valid in the mission's current state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidTransition {
+    pub from: MissionRunState,
+    pub event: MissionRunEvent,
+}
+
+impl fmt::Display for InvalidTransition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} is not valid while {:?}", self.event, self.from)
+    }
+}
+
+impl MissionRunState {
+    /// The state after `event`.
+    pub fn transition(self, event: MissionRunEvent) -> Result<Self, InvalidTransition> {
+        use MissionRunEvent as E;
+        use MissionRunState as S;
+        let next = match (self, event) {
+            (S::Idle | S::Starting, E::Queued) => S::Starting,
+            (S::Running | S::WaitingForTool | S::Draining, E::Queued) => self,
+            (S::Idle | S::Starting, E::Started) => S::Running,
+            (S::Running, E::ToolRequested) => S::WaitingForTool,
+            (S::WaitingForTool, E::ToolAnswered) => S::Running,
+            (S::Running | S::WaitingForTool | S::Draining, E::CancelRequested) => S::Draining,
+            (S::Running | S::WaitingForTool | S::Draining, E::Finished { queued }) => {
+                if queued {
+                    S::Starting
+                } else {
+                    S::Idle
+                }
+            }
+            (from, event) => return Err(InvalidTransition { from, event }),
+        };
+        Ok(next)
+    }
+
+    /// Apply `event`, keeping the current state (and logging) when it isn't
+    /// valid.
+    pub fn advance(&mut self, event: MissionRunEvent) {
+        match self.transition(event) {
+            Ok(next) => *self = next,
+            Err(e) => tracing::debug!("Ignoring mission state event: {}", e),
+        }
+    }
+
+    /// Whether a turn is executing (including one being cancelled).
+    pub fn is_executing(self) -> bool {
+        matches!(
+            self,
+            MissionRunState::Running | MissionRunState::WaitingForTool | MissionRunState::Draining
+        )
+    }
+
+    /// Name used in API responses.
+    pub fn label(self) -> &'static str {
+        match self {
+            MissionRunState::Idle => "idle",
+            MissionRunState::Starting => "starting",
+            MissionRunState::Running => "running",
+            MissionRunState::WaitingForTool => "waiting_for_tool",
+            MissionRunState::Draining => "draining",
+        }
+    }
+}
+
+/// Whether a mission about to run has to be set back to `active` first.
+pub fn needs_activation(status: MissionStatus) -> bool {
+    matches!(
+        status,
+        MissionStatus::Pending
+            | MissionStatus::Interrupted
+            | MissionStatus::Blocked
+            | MissionStatus::Completed
+            | MissionStatus::Failed
+    )
+}
+
+/// What the control session looks like when a message arrives.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RoutingSnapshot {
+    /// Mission the message was sent to, if any
+    pub target: Option<Uuid>,
+    /// The main session's current mission
+    pub current: Option<Uuid>,
+    /// Mission the main session is executing, if it is
+    pub running: Option<Uuid>,
+    /// Whether the main session is executing a turn
+    pub main_is_running: bool,
+    /// Whether the main session's in-memory history has an assistant reply
+    pub has_assistant_history: bool,
+}
+
+/// How the main session gets ready before the message is queued to it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MainSessionStep {
+    /// Queue to the current mission as is
+    Keep,
+    /// No current mission: make the target current, loading its history
+    Adopt(Uuid),
+    /// No current mission nor target: create a mission
+    CreateMission,
+    /// Save the current mission's history and switch to the target
+    SwitchTo(Uuid),
+    /// The target is current but its history isn't loaded (e.g. after a
+    /// restart): load it
+    ReloadHistory(Uuid),
+}
+
+/// Where a user message goes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageRoute {
+    /// The target already has a parallel runner
+    QueueToParallel(Uuid),
+    /// The main session is busy with another mission: run the target in
+    /// parallel
+    StartParallel(Uuid),
+    /// Queue to the main session
+    Main(MainSessionStep),
+}
+
+impl RoutingSnapshot {
+    /// The mission the message is for. Without an explicit target, a current
+    /// mission that differs from the running one (i.e. a mission was created
+    /// or loaded while another runs) is the target.
+    pub fn effective_target(&self) -> Option<Uuid> {
+        self.target.or_else(|| {
+            let current = self.current?;
+            (self.main_is_running && self.running != Some(current)).then_some(current)
+        })
+    }
+}
+
+/// Route a message; `in_parallel` tells whether a mission has a parallel
+/// runner.
+pub fn route_message(
+    snapshot: &RoutingSnapshot,
+    in_parallel: impl Fn(Uuid) -> bool,
+) -> MessageRoute {
+    let main_mission = snapshot.running.or(snapshot.current);
+    let target = snapshot.effective_target();
+
+    if let Some(tid) = target {
+        if in_parallel(tid) {
+            return MessageRoute::QueueToParallel(tid);
+        }
+        if main_mission != Some(tid) && snapshot.main_is_running {
+            return MessageRoute::StartParallel(tid);
+        }
+    }
+
+    let step = match (snapshot.current, target) {
+        (None, Some(tid)) => MainSessionStep::Adopt(tid),
+        (None, None) => MainSessionStep::CreateMission,
+        (Some(_), Some(_)) if snapshot.main_is_running => MainSessionStep::Keep,
+        (Some(current), Some(tid)) if current != tid => MainSessionStep::SwitchTo(tid),
+        (Some(_), Some(tid)) if !snapshot.has_assistant_history => {
+            MainSessionStep::ReloadHistory(tid)
+        }
+        (Some(_), _) => MainSessionStep::Keep,
+    };
+    MessageRoute::Main(step)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transitions_follow_the_turn_lifecycle() {
+        use MissionRunEvent as E;
+        use MissionRunState as S;
+
+        let mut state = S::Idle;
+        for (event, expected) in [
+            (E::Queued, S::Starting),
+            (E::Started, S::Running),
+            (E::ToolRequested, S::WaitingForTool),
+            (E::Queued, S::WaitingForTool),
+            (E::ToolAnswered, S::Running),
+            (E::CancelRequested, S::Draining),
+            (E::Finished { queued: true }, S::Starting),
+            (E::Started, S::Running),
+            (E::Finished { queued: false }, S::Idle),
+        ] {
+            state = state.transition(event).expect("valid transition");
+            assert_eq!(state, expected, "after {:?}", event);
+        }
+
+        assert_eq!(
+            S::Idle.transition(E::CancelRequested),
+            Err(InvalidTransition {
+                from: S::Idle,
+                event: E::CancelRequested
+            })
+        );
+        let mut resumed = S::Idle;
+        resumed.advance(E::ToolAnswered);
+        assert_eq!(resumed, S::Idle);
+        resumed.advance(E::Started);
+        assert_eq!(resumed, S::Running);
+        assert!(S::Starting.transition(E::CancelRequested).is_err());
+        assert!(S::Running.transition(E::ToolAnswered).is_err());
+        assert!(S::Draining.is_executing());
+        assert!(!S::Starting.is_executing());
+    }
+
+    #[test]
+    fn messages_route_to_parallel_runners_and_the_main_session() {
+        let (a, b, c) = (Uuid::new_v4(), Uuid::new_v4(), Uuid::new_v4());
+        let busy_with_a = RoutingSnapshot {
+            current: Some(a),
+            running: Some(a),
+            main_is_running: true,
+            has_assistant_history: true,
+            ..Default::default()
+        };
+        let no_parallel = |_| false;
+
+        // Case 1: the target already runs in parallel
+        let to_c = RoutingSnapshot {
+            target: Some(c),
+            ..busy_with_a
+        };
+        assert_eq!(
+            route_message(&to_c, |id| id == c),
+            MessageRoute::QueueToParallel(c)
+        );
+        // Case 2: the main session is busy with another mission
+        assert_eq!(
+            route_message(&to_c, no_parallel),
+            MessageRoute::StartParallel(c)
+        );
+        // A mission created while another runs is the implicit target
+        let created_b = RoutingSnapshot {
+            current: Some(b),
+            ..busy_with_a
+        };
+        assert_eq!(created_b.effective_target(), Some(b));
+        assert_eq!(
+            route_message(&created_b, no_parallel),
+            MessageRoute::StartParallel(b)
+        );
+        // Case 3: queue to the running mission
+        assert_eq!(
+            route_message(&busy_with_a, no_parallel),
+            MessageRoute::Main(MainSessionStep::Keep)
+        );
+    }
+
+    #[test]
+    fn idle_main_session_adopts_creates_switches_and_reloads() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let route = |snapshot: RoutingSnapshot| route_message(&snapshot, |_| false);
+
+        assert_eq!(
+            route(RoutingSnapshot {
+                target: Some(a),
+                ..Default::default()
+            }),
+            MessageRoute::Main(MainSessionStep::Adopt(a))
+        );
+        assert_eq!(
+            route(RoutingSnapshot::default()),
+            MessageRoute::Main(MainSessionStep::CreateMission)
+        );
+
+        let idle_on_a = RoutingSnapshot {
+            current: Some(a),
+            has_assistant_history: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            route(RoutingSnapshot {
+                target: Some(b),
+                ..idle_on_a
+            }),
+            MessageRoute::Main(MainSessionStep::SwitchTo(b))
+        );
+        assert_eq!(
+            route(RoutingSnapshot {
+                target: Some(a),
+                ..idle_on_a
+            }),
+            MessageRoute::Main(MainSessionStep::Keep)
+        );
+        assert_eq!(
+            route(RoutingSnapshot {
+                target: Some(a),
+                has_assistant_history: false,
+                ..idle_on_a
+            }),
+            MessageRoute::Main(MainSessionStep::ReloadHistory(a))
+        );
+
+        assert!(needs_activation(MissionStatus::Interrupted));
+        assert!(!needs_activation(MissionStatus::Active));
+    }
+}
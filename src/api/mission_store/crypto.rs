@@ -0,0 +1,195 @@
+//! Optional encryption at rest for mission content.
+//!
+//! When a key is configured, the SQLite store encrypts event content (user
+//! and assistant messages, tool calls and results, including content spilled
+//! to files) and turn journal entries with AES-256-GCM. Mission metadata,
+//! event metadata and costs stay in plaintext so listing and cost queries keep
+//! working.
+//!
+//! The key (32 bytes, hex or base64) is read from the first of:
+//! - `SANDBOXED_SH_MISSION_STORE_KEY`
+//! - `SANDBOXED_SH_MISSION_STORE_KEY_FILE`, a file holding the key
+//! - `SANDBOXED_SH_MISSION_STORE_KEY_COMMAND`, a shell command printing the
+//!   key, for fetching it from a KMS or secrets manager at startup
+//!
+//! The key is resolved once per process ([`StoreCipher::shared`]) and the
+//! cipher shared by every user's store.
+//!
+//! Encrypted values carry the [`ENCRYPTED_PREFIX`] marker, so plaintext rows
+//! from before encryption was enabled stay readable and are encrypted in place
+//! when the store opens.
+
+use std::sync::Arc;
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use rand::RngCore;
+
+pub const KEY_ENV: &str = "SANDBOXED_SH_MISSION_STORE_KEY";
+pub const KEY_FILE_ENV: &str = "SANDBOXED_SH_MISSION_STORE_KEY_FILE";
+pub const KEY_COMMAND_ENV: &str = "SANDBOXED_SH_MISSION_STORE_KEY_COMMAND";
+
+/// Marks a value as `BASE64(nonce || ciphertext)` from this module.
+pub const ENCRYPTED_PREFIX: &str = "sbx-enc:v1:";
+
+const NONCE_LENGTH: usize = 12;
+
+/// Returned in place of content that can't be decrypted.
+const UNREADABLE: &str = "[encrypted content unavailable]";
+
+/// AES-256-GCM cipher for mission content.
+pub struct StoreCipher {
+    cipher: Aes256Gcm,
+}
+
+impl std::fmt::Debug for StoreCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("StoreCipher")
+    }
+}
+
+impl StoreCipher {
+    pub fn new(key: &[u8; 32]) -> Self {
+        Self {
+            cipher: Aes256Gcm::new_from_slice(key).expect("key is 32 bytes"),
+        }
+    }
+
+    /// Load the key from the environment. Returns `None` when encryption is
+    /// not configured, and an error when a configured key can't be loaded (the
+    /// store must not silently fall back to plaintext).
+    pub fn from_env() -> Result<Option<Self>, String> {
+        let non_empty = |name: &str| {
+            std::env::var(name)
+                .ok()
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+
+        let key = if let Some(key) = non_empty(KEY_ENV) {
+            key
+        } else if let Some(path) = non_empty(KEY_FILE_ENV) {
+            std::fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read mission store key file {}: {}", path, e))?
+        } else if let Some(command) = non_empty(KEY_COMMAND_ENV) {
+            let output = std::process::Command::new("sh")
+                .arg("-c")
+                .arg(&command)
+                .output()
+                .map_err(|e| format!("Failed to run mission store key command: {}", e))?;
+            if !output.status.success() {
+                return Err(format!(
+                    "Mission store key command failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ));
+            }
+            String::from_utf8_lossy(&output.stdout).to_string()
+        } else {
+            return Ok(None);
+        };
+
+        let key = crate::library::env_crypto::parse_key_hex(key.trim())
+            .map_err(|e| format!("Invalid mission store key: {}", e))?;
+        Ok(Some(Self::new(&key)))
+    }
+
+    /// The cipher configured in the environment, loaded on first use and
+    /// shared afterwards. A key that fails to load is retried on the next call.
+    pub async fn shared() -> Result<Option<Arc<Self>>, String> {
+        static SHARED: tokio::sync::OnceCell<Option<Arc<StoreCipher>>> =
+            tokio::sync::OnceCell::const_new();
+        SHARED
+            .get_or_try_init(|| async {
+                tokio::task::spawn_blocking(Self::from_env)
+                    .await
+                    .map_err(|e| format!("Task join error: {}", e))?
+                    .map(|cipher| cipher.map(Arc::new))
+            })
+            .await
+            .cloned()
+    }
+
+    /// Encrypt `plaintext`. Values that are already encrypted are returned
+    /// unchanged.
+    pub fn encrypt(&self, plaintext: &str) -> String {
+        if is_encrypted(plaintext) {
+            return plaintext.to_string();
+        }
+        let mut nonce = [0u8; NONCE_LENGTH];
+        rand::thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = self
+            .cipher
+            .encrypt(Nonce::from_slice(&nonce), plaintext.as_bytes())
+            .expect("AES-GCM encryption of an in-memory buffer cannot fail");
+        let mut combined = Vec::with_capacity(NONCE_LENGTH + ciphertext.len());
+        combined.extend_from_slice(&nonce);
+        combined.extend_from_slice(&ciphertext);
+        format!("{}{}", ENCRYPTED_PREFIX, BASE64.encode(combined))
+    }
+
+    fn decrypt(&self, payload: &str) -> Option<String> {
+        let combined = BASE64.decode(payload).ok()?;
+        if combined.len() < NONCE_LENGTH {
+            return None;
+        }
+        let (nonce, ciphertext) = combined.split_at(NONCE_LENGTH);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .ok()?;
+        String::from_utf8(plaintext).ok()
+    }
+}
+
+pub fn is_encrypted(value: &str) -> bool {
+    value.starts_with(ENCRYPTED_PREFIX)
+}
+
+/// Encrypt `value` when a cipher is configured.
+pub fn seal(cipher: Option<&StoreCipher>, value: &str) -> String {
+    match cipher {
+        Some(cipher) => cipher.encrypt(value),
+        None => value.to_string(),
+    }
+}
+
+/// Decrypt `value` if it is encrypted. Plaintext passes through unchanged.
+pub fn open(cipher: Option<&StoreCipher>, value: &str) -> String {
+    let Some(payload) = value.strip_prefix(ENCRYPTED_PREFIX) else {
+        return value.to_string();
+    };
+    match cipher.and_then(|c| c.decrypt(payload)) {
+        Some(plaintext) => plaintext,
+        None => {
+            tracing::warn!(
+                key_configured = cipher.is_some(),
+                "Failed to decrypt mission content"
+            );
+            UNREADABLE.to_string()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_and_passes_plaintext_through() {
+        let cipher = StoreCipher::new(&[7u8; 32]);
+        let sealed = seal(Some(&cipher), "secret code");
+        assert!(is_encrypted(&sealed));
+        assert_ne!(sealed, seal(Some(&cipher), "secret code"));
+        assert_eq!(cipher.encrypt(&sealed), sealed);
+        assert_eq!(open(Some(&cipher), &sealed), "secret code");
+        assert_eq!(open(Some(&cipher), "legacy plaintext"), "legacy plaintext");
+        assert_eq!(seal(None, "plain"), "plain");
+
+        let other = StoreCipher::new(&[8u8; 32]);
+        assert_eq!(open(Some(&other), &sealed), UNREADABLE);
+        assert_eq!(open(None, &sealed), UNREADABLE);
+    }
+}
@@ -1,6 +1,9 @@
 //! In-memory mission store (non-persistent).
 
-use super::{now_string, Mission, MissionHistoryEntry, MissionStatus, MissionStore};
+use super::{
+    now_string, ChecklistItem, FollowUp, Mission, MissionHistoryEntry, MissionStatus, MissionStore,
+};
+use crate::agents::ErrorCode;
 use crate::api::control::{AgentTreeNode, DesktopSessionInfo};
 use async_trait::async_trait;
 use chrono::Utc;
@@ -40,7 +43,11 @@ impl MissionStore for InMemoryMissionStore {
 
     async fn list_missions(&self, limit: usize, offset: usize) -> Result<Vec<Mission>, String> {
         let mut missions: Vec<Mission> = self.missions.read().await.values().cloned().collect();
-        missions.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+        missions.sort_by(|a, b| {
+            b.updated_at
+                .cmp(&a.updated_at)
+                .then_with(|| b.id.cmp(&a.id))
+        });
         let missions = missions.into_iter().skip(offset).take(limit).collect();
         Ok(missions)
     }
@@ -81,6 +88,7 @@ impl MissionStore for InMemoryMissionStore {
             workspace_id: workspace_id.unwrap_or(crate::workspace::DEFAULT_WORKSPACE_ID),
             workspace_name: None,
             agent: agent.map(|s| s.to_string()),
+            agent_version: None,
             model_override: model_override.map(|s| s.to_string()),
             model_effort: model_effort.map(|s| s.to_string()),
             backend: backend.unwrap_or("claudecode").to_string(),
@@ -93,6 +101,17 @@ impl MissionStore for InMemoryMissionStore {
             desktop_sessions: Vec::new(),
             session_id: Some(Uuid::new_v4().to_string()),
             terminal_reason: None,
+            error_code: None,
+            tags: Vec::new(),
+            pinned: false,
+            archived: false,
+            dry_run: false,
+            env_profile: None,
+            assignee: None,
+            watchers: Vec::new(),
+            checklist: Vec::new(),
+            follow_ups: Vec::new(),
+            unread_count: None,
         };
         self.missions
             .write()
@@ -120,6 +139,7 @@ impl MissionStore for InMemoryMissionStore {
         let now = now_string();
         mission.updated_at = now.clone();
         mission.terminal_reason = terminal_reason.map(|s| s.to_string());
+        mission.error_code = terminal_reason.and_then(ErrorCode::for_terminal_reason);
         // Failed missions with LlmError are also resumable (transient API errors)
         if matches!(
             status,
@@ -231,6 +251,137 @@ impl MissionStore for InMemoryMissionStore {
         Ok(())
     }
 
+    async fn update_mission_backend(
+        &self,
+        id: Uuid,
+        backend: &str,
+        model_override: Option<&str>,
+        model_effort: Option<&str>,
+    ) -> Result<(), String> {
+        let mut missions = self.missions.write().await;
+        let mission = missions
+            .get_mut(&id)
+            .ok_or_else(|| format!("Mission {} not found", id))?;
+        mission.backend = backend.to_string();
+        mission.model_override = model_override.map(|m| m.to_string());
+        mission.model_effort = model_effort.map(|e| e.to_string());
+        mission.updated_at = now_string();
+        Ok(())
+    }
+
+    async fn update_mission_agent_version(
+        &self,
+        id: Uuid,
+        version: Option<&str>,
+    ) -> Result<(), String> {
+        let mut missions = self.missions.write().await;
+        let mission = missions
+            .get_mut(&id)
+            .ok_or_else(|| format!("Mission {} not found", id))?;
+        mission.agent_version = version.map(|v| v.to_string());
+        mission.updated_at = now_string();
+        Ok(())
+    }
+
+    async fn update_mission_tags(&self, id: Uuid, tags: &[String]) -> Result<(), String> {
+        let mut missions = self.missions.write().await;
+        let mission = missions
+            .get_mut(&id)
+            .ok_or_else(|| format!("Mission {} not found", id))?;
+        mission.tags = tags.to_vec();
+        mission.updated_at = now_string();
+        Ok(())
+    }
+
+    async fn update_mission_flags(
+        &self,
+        id: Uuid,
+        pinned: Option<bool>,
+        archived: Option<bool>,
+    ) -> Result<(), String> {
+        let mut missions = self.missions.write().await;
+        let mission = missions
+            .get_mut(&id)
+            .ok_or_else(|| format!("Mission {} not found", id))?;
+        if let Some(pinned) = pinned {
+            mission.pinned = pinned;
+        }
+        if let Some(archived) = archived {
+            mission.archived = archived;
+        }
+        Ok(())
+    }
+
+    async fn update_mission_assignee(
+        &self,
+        id: Uuid,
+        assignee: Option<&str>,
+    ) -> Result<(), String> {
+        let mut missions = self.missions.write().await;
+        let mission = missions
+            .get_mut(&id)
+            .ok_or_else(|| format!("Mission {} not found", id))?;
+        mission.assignee = assignee.map(str::to_string);
+        Ok(())
+    }
+
+    async fn update_mission_watchers(&self, id: Uuid, watchers: &[String]) -> Result<(), String> {
+        let mut missions = self.missions.write().await;
+        let mission = missions
+            .get_mut(&id)
+            .ok_or_else(|| format!("Mission {} not found", id))?;
+        mission.watchers = watchers.to_vec();
+        Ok(())
+    }
+
+    async fn update_mission_checklist(
+        &self,
+        id: Uuid,
+        checklist: &[ChecklistItem],
+    ) -> Result<(), String> {
+        let mut missions = self.missions.write().await;
+        let mission = missions
+            .get_mut(&id)
+            .ok_or_else(|| format!("Mission {} not found", id))?;
+        mission.checklist = checklist.to_vec();
+        Ok(())
+    }
+
+    async fn update_mission_follow_ups(
+        &self,
+        id: Uuid,
+        follow_ups: &[FollowUp],
+    ) -> Result<(), String> {
+        let mut missions = self.missions.write().await;
+        let mission = missions
+            .get_mut(&id)
+            .ok_or_else(|| format!("Mission {} not found", id))?;
+        mission.follow_ups = follow_ups.to_vec();
+        Ok(())
+    }
+
+    async fn update_mission_dry_run(&self, id: Uuid, dry_run: bool) -> Result<(), String> {
+        let mut missions = self.missions.write().await;
+        let mission = missions
+            .get_mut(&id)
+            .ok_or_else(|| format!("Mission {} not found", id))?;
+        mission.dry_run = dry_run;
+        Ok(())
+    }
+
+    async fn update_mission_env_profile(
+        &self,
+        id: Uuid,
+        env_profile: Option<&str>,
+    ) -> Result<(), String> {
+        let mut missions = self.missions.write().await;
+        let mission = missions
+            .get_mut(&id)
+            .ok_or_else(|| format!("Mission {} not found", id))?;
+        mission.env_profile = env_profile.map(str::to_string);
+        Ok(())
+    }
+
     async fn update_mission_tree(&self, id: Uuid, tree: &AgentTreeNode) -> Result<(), String> {
         self.trees.write().await.insert(id, tree.clone());
         Ok(())
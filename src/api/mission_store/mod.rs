@@ -5,15 +5,20 @@
 //! - `file`: JSON file-based storage (legacy)
 //! - `sqlite`: SQLite database with full event logging
 
+mod crypto;
 mod file;
 mod memory;
 mod sqlite;
 
+pub use crypto::StoreCipher;
 pub use file::FileMissionStore;
 pub use memory::InMemoryMissionStore;
 pub use sqlite::SqliteMissionStore;
 
-use crate::api::control::{AgentEvent, AgentTreeNode, DesktopSessionInfo, MissionStatus};
+use crate::agents::ErrorCode;
+use crate::api::control::{
+    normalized_title_key, AgentEvent, AgentTreeNode, DesktopSessionInfo, MissionStatus,
+};
 use async_trait::async_trait;
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
@@ -46,6 +51,9 @@ pub struct Mission {
     /// Agent name from library (e.g., "code-reviewer")
     #[serde(skip_serializing_if = "Option::is_none")]
     pub agent: Option<String>,
+    /// Library agent version pinned when the mission was created
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_version: Option<String>,
     /// Optional model override (provider/model)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub model_override: Option<String>,
@@ -76,6 +84,39 @@ pub struct Mission {
     /// Why the mission terminated (for failed/completed missions)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub terminal_reason: Option<String>,
+    /// Machine-readable kind of failure, derived from `terminal_reason`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<ErrorCode>,
+    /// Free-form labels for filtering (normalized by `normalize_tags`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Pinned missions are listed first
+    #[serde(default)]
+    pub pinned: bool,
+    /// Archived missions are hidden from the missions list by default
+    #[serde(default)]
+    pub archived: bool,
+    /// Mutating tools (Bash/Edit/Write) return simulated results instead of running
+    #[serde(default)]
+    pub dry_run: bool,
+    /// Library environment profile (overrides the workspace's profile)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub env_profile: Option<String>,
+    /// User responsible for the mission
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub assignee: Option<String>,
+    /// Users notified when the mission finishes (normalized by `normalize_watchers`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub watchers: Vec<String>,
+    /// Acceptance criteria, checked off by the user or the agent
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub checklist: Vec<ChecklistItem>,
+    /// Follow-ups suggested when the mission completed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub follow_ups: Vec<FollowUp>,
+    /// Unread events for the requesting user (filled in by the missions list)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unread_count: Option<u64>,
 }
 
 fn default_backend() -> String {
@@ -87,10 +128,74 @@ fn default_workspace_id() -> Uuid {
 }
 
 /// A single entry in the mission history.
+///
+/// `role` is `user`, `assistant`, or `tool` for a tool call and its
+/// (compacted) result.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MissionHistoryEntry {
     pub role: String,
     pub content: String,
+    /// Name of the tool, for `tool` entries
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+}
+
+/// Characters of a tool call's arguments kept in a `tool` history entry.
+const TOOL_ARGS_HISTORY_CHARS: usize = 300;
+/// Characters of a tool result kept in a `tool` history entry.
+const TOOL_RESULT_HISTORY_CHARS: usize = 1_500;
+
+/// `text` trimmed to `max` characters; results stored as JSON strings are
+/// unquoted first.
+fn compact(text: &str, max: usize) -> String {
+    let unquoted = serde_json::from_str::<String>(text).ok();
+    let text = unquoted.as_deref().unwrap_or(text).trim();
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(max).collect();
+    format!("{}... [truncated]", kept.trim_end())
+}
+
+impl MissionHistoryEntry {
+    /// A `tool` entry for a call to `tool_name`, with its arguments and
+    /// result compacted.
+    pub fn tool(tool_name: &str, args: Option<&str>, result: &str) -> Self {
+        let result = compact(result, TOOL_RESULT_HISTORY_CHARS);
+        let content = match args.map(str::trim).filter(|a| !a.is_empty() && *a != "{}") {
+            Some(args) => format!("{} -> {}", compact(args, TOOL_ARGS_HISTORY_CHARS), result),
+            None => result,
+        };
+        Self {
+            role: "tool".to_string(),
+            content,
+            tool_name: Some(tool_name.to_string()),
+        }
+    }
+
+    /// Whether this is a user or assistant message (not a tool entry).
+    pub fn is_conversation(&self) -> bool {
+        self.role == "user" || self.role == "assistant"
+    }
+
+    /// The `(role, content)` pair used to rebuild the agent's context; tool
+    /// entries are prefixed with the tool name.
+    pub fn context_pair(&self) -> (String, String) {
+        match &self.tool_name {
+            Some(name) => (self.role.clone(), format!("{}: {}", name, self.content)),
+            None => (self.role.clone(), self.content.clone()),
+        }
+    }
+}
+
+/// The user and assistant messages of `history`, for consumers that ignore
+/// tool entries.
+pub fn conversation_history(history: &[MissionHistoryEntry]) -> Vec<MissionHistoryEntry> {
+    history
+        .iter()
+        .filter(|entry| entry.is_conversation())
+        .cloned()
+        .collect()
 }
 
 /// A stored event with full metadata (for event replay/debugging).
@@ -106,6 +211,377 @@ pub struct StoredEvent {
     pub tool_name: Option<String>,
     pub content: String,
     pub metadata: serde_json::Value,
+    /// Control stream sequence number (SSE event id) the event was published with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_seq: Option<u64>,
+    /// Event schema version (see `event_schema`); events read from the store
+    /// are upgraded to the current version
+    #[serde(default = "super::event_schema::legacy_version")]
+    pub schema_version: u32,
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Mission Report Types
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Structured report generated when a mission completes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissionReport {
+    pub mission_id: Uuid,
+    pub status: MissionStatus,
+    pub generated_at: String,
+    /// What changed: the completion summary, or the agent's final message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub files_touched: Vec<ReportFile>,
+    #[serde(default)]
+    pub tests_run: Vec<ReportTestRun>,
+    #[serde(default)]
+    pub follow_ups: Vec<String>,
+    #[serde(default)]
+    pub tool_calls: usize,
+    #[serde(default)]
+    pub cost_cents: u64,
+}
+
+/// A file the agent created, edited or deleted.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReportFile {
+    pub path: String,
+    /// Tools that touched the file, in first-use order (e.g. "Write", "Edit")
+    pub tools: Vec<String>,
+    pub changes: usize,
+}
+
+/// A test command run by the agent or by completion verification.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ReportTestRun {
+    pub command: String,
+    /// "agent" or "verification"
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
+    /// Whether the run passed, when the exit status is known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub passed: Option<bool>,
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Scheduled Message Types
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Lifecycle of a scheduled message.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduledMessageStatus {
+    Pending,
+    Sent,
+    Cancelled,
+    Failed,
+}
+
+impl ScheduledMessageStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Sent => "sent",
+            Self::Cancelled => "cancelled",
+            Self::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "pending" => Self::Pending,
+            "sent" => Self::Sent,
+            "cancelled" => Self::Cancelled,
+            _ => Self::Failed,
+        }
+    }
+}
+
+/// A one-shot message sent to a mission at a future time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledMessage {
+    pub id: Uuid,
+    pub mission_id: Uuid,
+    pub content: String,
+    /// Optional agent override for the message
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent: Option<String>,
+    /// RFC3339 time the message is due
+    pub send_at: String,
+    pub created_at: String,
+    pub status: ScheduledMessageStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sent_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Automatic retry policy for missions that fail, set on a mission or on an
+/// automation (applying to its mission's runs).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MissionRetryPolicy {
+    /// Retries after the original run (0 disables retrying)
+    pub max_attempts: u32,
+    /// Delay before the first retry, in seconds
+    #[serde(default = "default_retry_delay_seconds")]
+    pub initial_delay_seconds: u64,
+    /// Multiplier applied to the delay for each further retry
+    #[serde(default = "default_backoff_multiplier")]
+    pub backoff_multiplier: f64,
+    /// Terminal reasons that are retried (e.g. "llm_error", "rate_limited")
+    #[serde(default = "default_retry_on")]
+    pub retry_on: Vec<String>,
+}
+
+fn default_retry_delay_seconds() -> u64 {
+    60
+}
+
+/// Transient provider failures.
+fn default_retry_on() -> Vec<String> {
+    [
+        "llm_error",
+        "rate_limited",
+        "capacity_limited",
+        "provider_overloaded",
+    ]
+    .into_iter()
+    .map(String::from)
+    .collect()
+}
+
+impl MissionRetryPolicy {
+    /// Whether a mission that failed with `terminal_reason` is retried.
+    pub fn retries(&self, terminal_reason: Option<&str>) -> bool {
+        terminal_reason.is_some_and(|reason| self.retry_on.iter().any(|r| r == reason))
+    }
+
+    /// Delay before retry number `attempt` (1-based).
+    pub fn delay_for(&self, attempt: u32) -> std::time::Duration {
+        let exponent = attempt.saturating_sub(1).min(32) as i32;
+        let seconds =
+            self.initial_delay_seconds as f64 * self.backoff_multiplier.max(1.0).powi(exponent);
+        std::time::Duration::from_secs_f64(seconds.min(7.0 * 24.0 * 60.0 * 60.0))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MissionRetryStatus {
+    Scheduled,
+    /// The mission was resumed and is running
+    Started,
+    /// The resumed run completed
+    Succeeded,
+    /// The resumed run failed again, or the mission could not be resumed
+    Failed,
+    Cancelled,
+}
+
+impl MissionRetryStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Scheduled => "scheduled",
+            Self::Started => "started",
+            Self::Succeeded => "succeeded",
+            Self::Failed => "failed",
+            Self::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(value: &str) -> Self {
+        match value {
+            "scheduled" => Self::Scheduled,
+            "started" => Self::Started,
+            "succeeded" => Self::Succeeded,
+            "cancelled" => Self::Cancelled,
+            _ => Self::Failed,
+        }
+    }
+}
+
+/// An automatic retry of a failed mission, resumed once `due_at` passes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MissionRetry {
+    pub id: Uuid,
+    /// The failed mission being retried
+    pub mission_id: Uuid,
+    /// 1 for the first retry
+    pub attempt: u32,
+    /// Why the run being retried ended
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terminal_reason: Option<String>,
+    /// RFC3339 time the retry is due
+    pub due_at: String,
+    pub created_at: String,
+    pub status: MissionRetryStatus,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Kind of a [`TurnJournalEntry`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TurnJournalKind {
+    UserMessage,
+    ToolCall,
+    ToolResult,
+}
+
+impl TurnJournalKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::UserMessage => "user_message",
+            Self::ToolCall => "tool_call",
+            Self::ToolResult => "tool_result",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "user_message" => Some(Self::UserMessage),
+            "tool_call" => Some(Self::ToolCall),
+            "tool_result" => Some(Self::ToolResult),
+            _ => None,
+        }
+    }
+}
+
+/// One step of a mission's in-flight turn, journaled as it happens so a
+/// crashed turn can be resumed from its last completed tool call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TurnJournalEntry {
+    pub mission_id: Uuid,
+    pub kind: TurnJournalKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_call_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+    /// User message text, tool arguments or tool result
+    pub content: String,
+    pub created_at: String,
+}
+
+/// Thumbs up or down on an assistant message.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FeedbackRating {
+    Up,
+    Down,
+}
+
+impl FeedbackRating {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Up => "up",
+            Self::Down => "down",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "up" => Some(Self::Up),
+            "down" => Some(Self::Down),
+            _ => None,
+        }
+    }
+}
+
+/// A user's rating of one assistant message, kept for quality analytics.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageFeedback {
+    pub mission_id: Uuid,
+    /// `id` of the rated `assistant_message` event
+    pub message_id: Uuid,
+    pub rating: FeedbackRating,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+    /// Backend and model that produced the message
+    pub backend: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub created_at: String,
+}
+
+/// A human comment on a mission. Comments are kept apart from the mission's
+/// history and never reach the agent.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MissionComment {
+    pub id: Uuid,
+    pub mission_id: Uuid,
+    /// Id of the thread's first comment (the comment's own id for thread roots)
+    pub thread_id: Uuid,
+    pub author: String,
+    /// Markdown
+    pub body: String,
+    /// Users mentioned in the body (`@name`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mentions: Vec<String>,
+    pub created_at: String,
+    /// Set when the body was edited
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+}
+
+impl MissionComment {
+    pub fn is_thread_root(&self) -> bool {
+        self.id == self.thread_id
+    }
+}
+
+/// Latest heartbeat from the process running a mission.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunnerHeartbeat {
+    pub mission_id: Uuid,
+    /// Server instance running the mission
+    pub instance_id: String,
+    pub pid: u32,
+    /// RFC3339 time of the last heartbeat
+    pub beat_at: String,
+}
+
+/// An event published on the control stream, as handed to the event logger.
+#[derive(Debug, Clone)]
+pub struct StreamEvent {
+    pub mission_id: Uuid,
+    /// Control stream sequence number (SSE event id)
+    pub stream_seq: u64,
+    pub event: std::sync::Arc<AgentEvent>,
+}
+
+/// Outcome of a store maintenance pass.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoreMaintenanceReport {
+    /// Database file the pass ran on
+    pub database: String,
+    /// Size of the database and its write-ahead log before and after
+    pub size_before_bytes: u64,
+    pub size_after_bytes: u64,
+    /// Whether the database was rebuilt with VACUUM (otherwise free pages
+    /// were released incrementally)
+    pub vacuumed: bool,
+    pub duration_ms: u64,
+}
+
+/// Result of a store integrity check.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoreIntegrityReport {
+    pub database: String,
+    pub ok: bool,
+    /// Problems reported by the integrity check (empty when `ok`)
+    #[serde(default)]
+    pub problems: Vec<String>,
+    pub foreign_key_violations: u64,
+    pub journal_mode: String,
+    pub size_bytes: u64,
+    /// Share of the database's pages that are free and could be reclaimed
+    pub free_pct: f64,
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -245,6 +721,12 @@ pub struct Automation {
     /// Whether to start a fresh session for each trigger (clears context/history).
     #[serde(default)]
     pub fresh_session: FreshSession,
+    /// Automatic retry policy for the mission's failed runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mission_retry_policy: Option<MissionRetryPolicy>,
+    /// Windows during which the scheduler doesn't trigger this automation
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub blackout_windows: Vec<crate::blackout::BlackoutWindow>,
     /// Number of consecutive failures (used for WhenFailingConsecutively policy).
     /// This is tracked internally and not persisted directly.
     #[serde(default, skip_serializing)]
@@ -290,6 +772,12 @@ pub struct AutomationExecution {
     /// Number of retry attempts made
     #[serde(default)]
     pub retry_count: u32,
+    /// First mission event (`sequence`) this run produced
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub first_event_seq: Option<i64>,
+    /// Last mission event of this run; derived when read
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_event_seq: Option<i64>,
 }
 
 /// Get current timestamp as RFC3339 string.
@@ -314,6 +802,136 @@ pub fn sanitize_filename(value: &str) -> String {
     }
 }
 
+/// Maximum number of tags on a mission.
+pub const MAX_MISSION_TAGS: usize = 20;
+/// Maximum length of a single tag, in characters.
+pub const MAX_MISSION_TAG_LEN: usize = 50;
+
+/// Normalize mission tags: trimmed, lowercased, whitespace collapsed to `-`,
+/// deduplicated (first occurrence wins) and capped at [`MAX_MISSION_TAGS`].
+pub fn normalize_tags<S: AsRef<str>>(tags: &[S]) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag: String = tag
+            .as_ref()
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join("-")
+            .to_lowercase()
+            .chars()
+            .take(MAX_MISSION_TAG_LEN)
+            .collect();
+        if !tag.is_empty() && !normalized.contains(&tag) {
+            normalized.push(tag);
+        }
+        if normalized.len() == MAX_MISSION_TAGS {
+            break;
+        }
+    }
+    normalized
+}
+
+/// An item of a mission's checklist.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChecklistItem {
+    pub id: Uuid,
+    pub text: String,
+    #[serde(default)]
+    pub done: bool,
+}
+
+/// What a suggested follow-up does when the user picks it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FollowUpKind {
+    /// Start a new mission with the text as its first message
+    Mission,
+    /// Send the text as a message to the completed mission
+    Message,
+}
+
+/// A follow-up suggested when a mission completes.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FollowUp {
+    pub kind: FollowUpKind,
+    pub text: String,
+}
+
+/// Event types that count as unread output of a mission.
+pub const UNREAD_EVENT_TYPES: &[&str] = &["assistant_message", "user_question"];
+
+/// Maximum number of watchers on a mission.
+pub const MAX_MISSION_WATCHERS: usize = 50;
+
+/// Normalize mission watchers: trimmed, deduplicated, sorted and capped at
+/// [`MAX_MISSION_WATCHERS`].
+pub fn normalize_watchers<S: AsRef<str>>(watchers: &[S]) -> Vec<String> {
+    let mut normalized: Vec<String> = watchers
+        .iter()
+        .map(|w| w.as_ref().trim().to_string())
+        .filter(|w| !w.is_empty())
+        .collect();
+    normalized.sort();
+    normalized.dedup();
+    normalized.truncate(MAX_MISSION_WATCHERS);
+    normalized
+}
+
+/// Keyset pagination bound for lists ordered by (`sort_key`, `id`) descending.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keyset {
+    /// RFC3339 timestamp the list is ordered by
+    pub sort_key: String,
+    pub id: Uuid,
+}
+
+impl Keyset {
+    /// Whether a row keyed by (`sort_key`, `id`) comes after this bound.
+    pub fn precedes(&self, sort_key: &str, id: Uuid) -> bool {
+        (sort_key, id) < (self.sort_key.as_str(), self.id)
+    }
+}
+
+/// Filter for listing missions.
+#[derive(Debug, Clone, Default)]
+pub struct MissionFilter {
+    /// Missions must carry all of these (normalized) tags
+    pub tags: Vec<String>,
+    pub status: Option<MissionStatus>,
+    pub pinned: Option<bool>,
+    pub archived: Option<bool>,
+    /// Missions assigned to this user; the empty string matches unassigned missions
+    pub assignee: Option<String>,
+    /// Only missions after this (`updated_at`, `id`) bound
+    pub before: Option<Keyset>,
+}
+
+impl MissionFilter {
+    pub fn is_empty(&self) -> bool {
+        self.tags.is_empty()
+            && self.status.is_none()
+            && self.pinned.is_none()
+            && self.archived.is_none()
+            && self.assignee.is_none()
+            && self.before.is_none()
+    }
+
+    pub fn matches(&self, mission: &Mission) -> bool {
+        (self.status.is_none() || self.status == Some(mission.status))
+            && (self.pinned.is_none() || self.pinned == Some(mission.pinned))
+            && (self.archived.is_none() || self.archived == Some(mission.archived))
+            && self
+                .assignee
+                .as_deref()
+                .is_none_or(|assignee| mission.assignee.as_deref().unwrap_or("") == assignee)
+            && self.tags.iter().all(|tag| mission.tags.contains(tag))
+            && self
+                .before
+                .iter()
+                .all(|bound| bound.precedes(&mission.updated_at, mission.id))
+    }
+}
+
 /// Mission store trait - implemented by all storage backends.
 #[allow(clippy::too_many_arguments)]
 #[async_trait]
@@ -321,9 +939,78 @@ pub trait MissionStore: Send + Sync {
     /// Whether this store persists data across restarts.
     fn is_persistent(&self) -> bool;
 
-    /// List missions, ordered by updated_at descending.
+    /// List missions, ordered by updated_at (then id) descending.
     async fn list_missions(&self, limit: usize, offset: usize) -> Result<Vec<Mission>, String>;
 
+    /// List missions matching `filter`, ordered by updated_at descending.
+    async fn list_missions_filtered(
+        &self,
+        filter: &MissionFilter,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<Mission>, String> {
+        const PAGE_SIZE: usize = 200;
+        if filter.is_empty() {
+            return self.list_missions(limit, offset).await;
+        }
+        let mut matched = Vec::new();
+        let mut skipped = 0;
+        let mut page_offset = 0;
+        loop {
+            let page = self.list_missions(PAGE_SIZE, page_offset).await?;
+            let page_len = page.len();
+            for mission in page.into_iter().filter(|m| filter.matches(m)) {
+                if skipped < offset {
+                    skipped += 1;
+                } else if matched.len() < limit {
+                    matched.push(mission);
+                }
+            }
+            if matched.len() >= limit || page_len < PAGE_SIZE {
+                return Ok(matched);
+            }
+            page_offset += page_len;
+        }
+    }
+
+    /// Count missions matching `filter`.
+    async fn count_missions_filtered(&self, filter: &MissionFilter) -> Result<usize, String> {
+        const PAGE_SIZE: usize = 200;
+        let mut count = 0;
+        let mut offset = 0;
+        loop {
+            let page = self.list_missions(PAGE_SIZE, offset).await?;
+            let page_len = page.len();
+            count += page.iter().filter(|m| filter.matches(m)).count();
+            if page_len < PAGE_SIZE {
+                return Ok(count);
+            }
+            offset += page_len;
+        }
+    }
+
+    /// Missions whose title has the dedupe key `key` (see
+    /// `control::normalized_title_key`). Stores with an index on the key
+    /// override this scan.
+    async fn find_missions_by_normalized_title(&self, key: &str) -> Result<Vec<Mission>, String> {
+        const PAGE_SIZE: usize = 200;
+        let mut matched = Vec::new();
+        let mut offset = 0;
+        loop {
+            let page = self.list_missions(PAGE_SIZE, offset).await?;
+            let page_len = page.len();
+            matched.extend(page.into_iter().filter(|m| {
+                m.title
+                    .as_deref()
+                    .is_some_and(|title| normalized_title_key(title) == key)
+            }));
+            if page_len < PAGE_SIZE {
+                return Ok(matched);
+            }
+            offset += page_len;
+        }
+    }
+
     /// Get a single mission by ID.
     async fn get_mission(&self, id: Uuid) -> Result<Option<Mission>, String>;
 
@@ -385,6 +1072,66 @@ pub trait MissionStore: Send + Sync {
     /// Update mission session ID (for backends like Amp that generate their own IDs).
     async fn update_mission_session_id(&self, id: Uuid, session_id: &str) -> Result<(), String>;
 
+    /// Move a mission to another backend, replacing its model settings.
+    async fn update_mission_backend(
+        &self,
+        id: Uuid,
+        backend: &str,
+        model_override: Option<&str>,
+        model_effort: Option<&str>,
+    ) -> Result<(), String>;
+
+    /// Pin the library agent version used by a mission (`None` clears the pin).
+    async fn update_mission_agent_version(
+        &self,
+        id: Uuid,
+        version: Option<&str>,
+    ) -> Result<(), String>;
+
+    /// Replace the mission's tags (already normalized).
+    async fn update_mission_tags(&self, id: Uuid, tags: &[String]) -> Result<(), String>;
+
+    /// Set the mission's pinned and/or archived flags (`None` leaves a flag
+    /// unchanged). Doesn't touch `updated_at`, so list order is preserved.
+    async fn update_mission_flags(
+        &self,
+        id: Uuid,
+        pinned: Option<bool>,
+        archived: Option<bool>,
+    ) -> Result<(), String>;
+
+    /// Set or clear the mission's assignee. Like the flags, doesn't touch
+    /// `updated_at`.
+    async fn update_mission_assignee(&self, id: Uuid, assignee: Option<&str>)
+        -> Result<(), String>;
+
+    /// Replace the mission's watchers (already normalized).
+    async fn update_mission_watchers(&self, id: Uuid, watchers: &[String]) -> Result<(), String>;
+
+    /// Replace the mission's checklist (already validated).
+    async fn update_mission_checklist(
+        &self,
+        id: Uuid,
+        checklist: &[ChecklistItem],
+    ) -> Result<(), String>;
+
+    /// Replace the mission's suggested follow-ups.
+    async fn update_mission_follow_ups(
+        &self,
+        id: Uuid,
+        follow_ups: &[FollowUp],
+    ) -> Result<(), String>;
+
+    /// Turn the mission's dry-run mode on or off.
+    async fn update_mission_dry_run(&self, id: Uuid, dry_run: bool) -> Result<(), String>;
+
+    /// Set or clear the mission's environment profile.
+    async fn update_mission_env_profile(
+        &self,
+        id: Uuid,
+        env_profile: Option<&str>,
+    ) -> Result<(), String>;
+
     /// Update mission agent tree.
     async fn update_mission_tree(&self, id: Uuid, tree: &AgentTreeNode) -> Result<(), String>;
 
@@ -423,6 +1170,39 @@ pub trait MissionStore: Send + Sync {
         Ok(())
     }
 
+    /// Log an event together with its control stream sequence number so it can
+    /// be replayed to SSE clients that reconnect with `Last-Event-ID`.
+    async fn log_stream_event(
+        &self,
+        mission_id: Uuid,
+        stream_seq: u64,
+        event: &AgentEvent,
+    ) -> Result<(), String> {
+        let _ = stream_seq;
+        self.log_event(mission_id, event).await
+    }
+
+    /// Log a batch of control stream events, in order. Persistent stores
+    /// write the batch in a single transaction.
+    async fn log_stream_events(&self, events: &[StreamEvent]) -> Result<(), String> {
+        for event in events {
+            self.log_stream_event(event.mission_id, event.stream_seq, &event.event)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Events across all missions published after `after_seq` on the control
+    /// stream, oldest first.
+    async fn get_events_after_stream_seq(
+        &self,
+        after_seq: u64,
+        limit: usize,
+    ) -> Result<Vec<StoredEvent>, String> {
+        let _ = (after_seq, limit);
+        Ok(vec![])
+    }
+
     /// Get all events for a mission (for replay/debugging).
     async fn get_events(
         &self,
@@ -435,6 +1215,312 @@ pub trait MissionStore: Send + Sync {
         Ok(vec![])
     }
 
+    /// The latest `limit` events of a mission, oldest first.
+    async fn get_recent_events(
+        &self,
+        mission_id: Uuid,
+        limit: usize,
+    ) -> Result<Vec<StoredEvent>, String> {
+        let events = self.get_events(mission_id, None, None, None).await?;
+        let skip = events.len().saturating_sub(limit);
+        Ok(events.into_iter().skip(skip).collect())
+    }
+
+    /// Events of a mission except those of the `excluded` types, paginated
+    /// like `get_events`.
+    async fn get_events_excluding(
+        &self,
+        mission_id: Uuid,
+        excluded: &[&str],
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Result<Vec<StoredEvent>, String> {
+        let events = self.get_events(mission_id, None, None, None).await?;
+        Ok(events
+            .into_iter()
+            .filter(|e| !excluded.contains(&e.event_type.as_str()))
+            .skip(offset.unwrap_or(0))
+            .take(limit.unwrap_or(usize::MAX))
+            .collect())
+    }
+
+    /// Events of a mission with `sequence` between `from_seq` and `to_seq`
+    /// (inclusive), oldest first.
+    async fn get_events_in_range(
+        &self,
+        mission_id: Uuid,
+        from_seq: Option<i64>,
+        to_seq: Option<i64>,
+    ) -> Result<Vec<StoredEvent>, String> {
+        let events = self.get_events(mission_id, None, None, None).await?;
+        Ok(events
+            .into_iter()
+            .filter(|e| from_seq.is_none_or(|from| e.sequence >= from))
+            .filter(|e| to_seq.is_none_or(|to| e.sequence <= to))
+            .collect())
+    }
+
+    /// Store the mission's completion report, replacing any earlier one.
+    async fn update_mission_report(&self, report: &MissionReport) -> Result<(), String> {
+        let _ = report;
+        Ok(())
+    }
+
+    /// Get the mission's completion report.
+    async fn get_mission_report(&self, mission_id: Uuid) -> Result<Option<MissionReport>, String> {
+        let _ = mission_id;
+        Ok(None)
+    }
+
+    // === Scheduled message methods (default unsupported) ===
+
+    /// Store a new scheduled message.
+    async fn create_scheduled_message(
+        &self,
+        message: ScheduledMessage,
+    ) -> Result<ScheduledMessage, String> {
+        let _ = message;
+        Err("Scheduled messages not supported by this store".to_string())
+    }
+
+    /// Get a scheduled message by ID.
+    async fn get_scheduled_message(&self, id: Uuid) -> Result<Option<ScheduledMessage>, String> {
+        let _ = id;
+        Ok(None)
+    }
+
+    /// List scheduled messages ordered by send time, optionally for one
+    /// mission and/or in one status.
+    async fn list_scheduled_messages(
+        &self,
+        mission_id: Option<Uuid>,
+        status: Option<ScheduledMessageStatus>,
+    ) -> Result<Vec<ScheduledMessage>, String> {
+        let _ = (mission_id, status);
+        Ok(vec![])
+    }
+
+    /// Move a scheduled message from `from` to `to`, recording `sent_at` when
+    /// it becomes sent and `error` when it fails. Returns false if the message
+    /// was not in `from` (e.g. already cancelled).
+    async fn transition_scheduled_message(
+        &self,
+        id: Uuid,
+        from: ScheduledMessageStatus,
+        to: ScheduledMessageStatus,
+        error: Option<String>,
+    ) -> Result<bool, String> {
+        let _ = (id, from, to, error);
+        Err("Scheduled messages not supported by this store".to_string())
+    }
+
+    // === Mission retry methods (default unsupported) ===
+
+    /// Retry policy set on the mission itself.
+    async fn get_mission_retry_policy(
+        &self,
+        mission_id: Uuid,
+    ) -> Result<Option<MissionRetryPolicy>, String> {
+        let _ = mission_id;
+        Ok(None)
+    }
+
+    /// Set or (with `None`) clear the mission's retry policy.
+    async fn set_mission_retry_policy(
+        &self,
+        mission_id: Uuid,
+        policy: Option<MissionRetryPolicy>,
+    ) -> Result<(), String> {
+        let _ = (mission_id, policy);
+        Err("Mission retries not supported by this store".to_string())
+    }
+
+    /// Store a new mission retry.
+    async fn create_mission_retry(&self, retry: MissionRetry) -> Result<MissionRetry, String> {
+        let _ = retry;
+        Err("Mission retries not supported by this store".to_string())
+    }
+
+    /// List mission retries ordered by mission and creation time, optionally
+    /// for one mission and/or in one status.
+    async fn list_mission_retries(
+        &self,
+        mission_id: Option<Uuid>,
+        status: Option<MissionRetryStatus>,
+    ) -> Result<Vec<MissionRetry>, String> {
+        let _ = (mission_id, status);
+        Ok(vec![])
+    }
+
+    /// Move a retry from `from` to `to`, recording `started_at` when it
+    /// starts and `error` when it fails. Returns false if the retry was not
+    /// in `from`.
+    async fn transition_mission_retry(
+        &self,
+        id: Uuid,
+        from: MissionRetryStatus,
+        to: MissionRetryStatus,
+        error: Option<String>,
+    ) -> Result<bool, String> {
+        let _ = (id, from, to, error);
+        Err("Mission retries not supported by this store".to_string())
+    }
+
+    // === Turn journal methods (default no-op) ===
+
+    /// Append a step to the mission's in-flight turn journal.
+    async fn append_turn_journal(&self, entry: TurnJournalEntry) -> Result<(), String> {
+        let _ = entry;
+        Ok(())
+    }
+
+    /// Journal of the mission's last unfinished turn, oldest first.
+    async fn get_turn_journal(&self, mission_id: Uuid) -> Result<Vec<TurnJournalEntry>, String> {
+        let _ = mission_id;
+        Ok(Vec::new())
+    }
+
+    /// Drop the mission's turn journal (the turn finished or a new one began).
+    async fn clear_turn_journal(&self, mission_id: Uuid) -> Result<(), String> {
+        let _ = mission_id;
+        Ok(())
+    }
+
+    // === Message feedback methods ===
+
+    /// Record feedback on an assistant message, replacing earlier feedback
+    /// on the same message.
+    async fn set_message_feedback(&self, feedback: MessageFeedback) -> Result<(), String> {
+        let _ = feedback;
+        Err("Message feedback not supported by this store".to_string())
+    }
+
+    /// Feedback newest first, optionally for one mission and/or created at or
+    /// after `since` (RFC3339).
+    async fn list_message_feedback(
+        &self,
+        mission_id: Option<Uuid>,
+        since: Option<&str>,
+    ) -> Result<Vec<MessageFeedback>, String> {
+        let _ = (mission_id, since);
+        Ok(vec![])
+    }
+
+    // === Read marker methods ===
+
+    /// Mark a mission as read by `username` up to its latest stored event.
+    async fn mark_mission_read(
+        &self,
+        mission_id: Uuid,
+        username: &str,
+        read_at: &str,
+    ) -> Result<(), String> {
+        let _ = (mission_id, username, read_at);
+        Err("Read markers not supported by this store".to_string())
+    }
+
+    /// Number of unread events (see [`UNREAD_EVENT_TYPES`]) per mission for
+    /// `username`. Missions without unread events are left out.
+    async fn unread_counts(
+        &self,
+        username: &str,
+        mission_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, u64>, String> {
+        let _ = (username, mission_ids);
+        Ok(HashMap::new())
+    }
+
+    /// Unread count of a mission for each user who has read it before.
+    async fn mission_unread_counts(&self, mission_id: Uuid) -> Result<Vec<(String, u64)>, String> {
+        let _ = mission_id;
+        Ok(vec![])
+    }
+
+    // === Comment methods ===
+
+    async fn create_mission_comment(&self, comment: MissionComment) -> Result<(), String> {
+        let _ = comment;
+        Err("Mission comments not supported by this store".to_string())
+    }
+
+    async fn get_mission_comment(&self, id: Uuid) -> Result<Option<MissionComment>, String> {
+        let _ = id;
+        Ok(None)
+    }
+
+    /// A mission's comments, oldest first.
+    async fn list_mission_comments(&self, mission_id: Uuid) -> Result<Vec<MissionComment>, String> {
+        let _ = mission_id;
+        Ok(vec![])
+    }
+
+    /// Replace a comment's body and mentions. Returns false if it doesn't exist.
+    async fn update_mission_comment(
+        &self,
+        id: Uuid,
+        body: &str,
+        mentions: &[String],
+        updated_at: &str,
+    ) -> Result<bool, String> {
+        let _ = (id, body, mentions, updated_at);
+        Err("Mission comments not supported by this store".to_string())
+    }
+
+    /// Delete a comment; deleting a thread root deletes its replies too.
+    /// Returns the number of comments deleted.
+    async fn delete_mission_comment(&self, id: Uuid) -> Result<usize, String> {
+        let _ = id;
+        Err("Mission comments not supported by this store".to_string())
+    }
+
+    // === Maintenance methods (default: nothing to maintain) ===
+
+    /// Checkpoint, compact and re-analyze the store; `vacuum` rebuilds the
+    /// whole database. Returns `None` for stores without maintenance.
+    async fn run_maintenance(
+        &self,
+        vacuum: bool,
+    ) -> Result<Option<StoreMaintenanceReport>, String> {
+        let _ = vacuum;
+        Ok(None)
+    }
+
+    /// Check the store for corruption. Returns `None` for stores that can't
+    /// be checked.
+    async fn check_integrity(&self) -> Result<Option<StoreIntegrityReport>, String> {
+        Ok(None)
+    }
+
+    // === Runner heartbeat methods (default no-op) ===
+
+    /// Check that the store is reachable.
+    async fn ping(&self) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Record a heartbeat for every mission `instance_id` is running, and drop
+    /// that instance's heartbeats for missions it no longer runs.
+    async fn record_runner_heartbeats(
+        &self,
+        instance_id: &str,
+        pid: u32,
+        mission_ids: &[Uuid],
+    ) -> Result<(), String> {
+        let _ = (instance_id, pid, mission_ids);
+        Ok(())
+    }
+
+    /// List the latest heartbeat of every mission that has one.
+    async fn list_runner_heartbeats(&self) -> Result<Vec<RunnerHeartbeat>, String> {
+        Ok(Vec::new())
+    }
+
+    /// Remove a mission's heartbeat (after its runner was found dead).
+    async fn delete_runner_heartbeat(&self, mission_id: Uuid) -> Result<(), String> {
+        let _ = mission_id;
+        Ok(())
+    }
+
     /// Get total cost in cents across all missions.
     /// Aggregates assistant_message metadata cost across all events.
     async fn get_total_cost_cents(&self) -> Result<u64, String> {
@@ -535,26 +1621,42 @@ pub trait MissionStore: Send + Sync {
         Err("Automation executions not supported by this store".to_string())
     }
 
-    /// Get execution history for an automation.
+    /// Get execution history for an automation, newest first, optionally
+    /// after a (`triggered_at`, `id`) bound.
     async fn get_automation_executions(
         &self,
         automation_id: Uuid,
         limit: Option<usize>,
+        before: Option<&Keyset>,
     ) -> Result<Vec<AutomationExecution>, String> {
-        let _ = (automation_id, limit);
+        let _ = (automation_id, limit, before);
         Ok(vec![])
     }
 
-    /// Get execution history for a mission.
+    /// Get execution history for a mission, newest first, optionally after a
+    /// (`triggered_at`, `id`) bound.
     async fn get_mission_automation_executions(
         &self,
         mission_id: Uuid,
         limit: Option<usize>,
+        before: Option<&Keyset>,
     ) -> Result<Vec<AutomationExecution>, String> {
-        let _ = (mission_id, limit);
+        let _ = (mission_id, limit, before);
         Ok(vec![])
     }
 
+    /// Count executions of an automation.
+    async fn count_automation_executions(&self, automation_id: Uuid) -> Result<usize, String> {
+        let _ = automation_id;
+        Ok(0)
+    }
+
+    /// Count automation executions for a mission.
+    async fn count_mission_automation_executions(&self, mission_id: Uuid) -> Result<usize, String> {
+        let _ = mission_id;
+        Ok(0)
+    }
+
     /// Complete all running automation executions for a mission, setting them
     /// to either Success or Failed based on the agent outcome.
     async fn complete_running_executions_for_mission(
@@ -767,4 +1869,54 @@ mod tests {
         assert_eq!(format!("{}", MissionStatus::Completed), "completed");
         assert_eq!(format!("{}", MissionStatus::Interrupted), "interrupted");
     }
+
+    #[test]
+    fn test_normalize_tags() {
+        assert_eq!(
+            normalize_tags(&["  Deploy ", "deploy", "Big Refactor", ""]),
+            vec!["deploy".to_string(), "big-refactor".to_string()]
+        );
+    }
+
+    /// Test that filtered listing matches all tags and the status.
+    #[tokio::test]
+    async fn test_list_missions_filtered_by_tag_and_status() {
+        let store = InMemoryMissionStore::new();
+        let deploy = store
+            .create_mission(Some("Deploy"), None, None, None, None, None, None)
+            .await
+            .unwrap();
+        let other = store
+            .create_mission(Some("Other"), None, None, None, None, None, None)
+            .await
+            .unwrap();
+        store
+            .update_mission_tags(deploy.id, &normalize_tags(&["deploy", "prod"]))
+            .await
+            .unwrap();
+        store
+            .update_mission_tags(other.id, &normalize_tags(&["deploy"]))
+            .await
+            .unwrap();
+        store
+            .update_mission_status(deploy.id, MissionStatus::Failed)
+            .await
+            .unwrap();
+
+        let filter = MissionFilter {
+            tags: vec!["deploy".to_string()],
+            status: Some(MissionStatus::Failed),
+            ..Default::default()
+        };
+        let missions = store.list_missions_filtered(&filter, 50, 0).await.unwrap();
+        assert_eq!(missions.len(), 1);
+        assert_eq!(missions[0].id, deploy.id);
+
+        let filter = MissionFilter {
+            tags: vec!["deploy".to_string()],
+            ..Default::default()
+        };
+        let missions = store.list_missions_filtered(&filter, 50, 1).await.unwrap();
+        assert_eq!(missions.len(), 1);
+    }
 }
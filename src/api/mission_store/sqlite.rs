@@ -1,11 +1,18 @@
 //! SQLite-based mission store with full event logging.
 
+use super::crypto::{self, StoreCipher};
 use super::{
-    now_string, sanitize_filename, Automation, AutomationExecution, CommandSource, ExecutionStatus,
-    FreshSession, Mission, MissionHistoryEntry, MissionStatus, MissionStore, RetryConfig,
-    StopPolicy, StoredEvent, TriggerType, WebhookConfig,
+    now_string, sanitize_filename, Automation, AutomationExecution, ChecklistItem, CommandSource,
+    ExecutionStatus, FeedbackRating, FollowUp, FreshSession, Keyset, MessageFeedback, Mission,
+    MissionComment, MissionFilter, MissionHistoryEntry, MissionReport, MissionRetry,
+    MissionRetryPolicy, MissionRetryStatus, MissionStatus, MissionStore, RetryConfig,
+    RunnerHeartbeat, ScheduledMessage, ScheduledMessageStatus, StopPolicy, StoreIntegrityReport,
+    StoreMaintenanceReport, StoredEvent, StreamEvent, TriggerType, TurnJournalEntry,
+    TurnJournalKind, WebhookConfig, UNREAD_EVENT_TYPES,
 };
-use crate::api::control::{AgentEvent, AgentTreeNode, DesktopSessionInfo};
+use crate::agents::ErrorCode;
+use crate::api::control::{normalized_title_key, AgentEvent, AgentTreeNode, DesktopSessionInfo};
+use crate::api::event_schema;
 use async_trait::async_trait;
 use chrono::Utc;
 use rusqlite::{params, Connection, OptionalExtension};
@@ -27,7 +34,7 @@ struct AssistantCostMetadata {
 }
 
 #[derive(serde::Serialize)]
-struct AssistantMessageMetadata {
+struct AssistantMessageMetadata<'a> {
     success: bool,
     cost_cents: u64,
     cost: AssistantCostMetadata,
@@ -41,6 +48,10 @@ struct AssistantMessageMetadata {
     shared_files: Option<Vec<crate::api::control::SharedFile>>,
     #[serde(default, skip_serializing_if = "std::ops::Not::not")]
     resumable: bool,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    cancelled: bool,
+    #[serde(skip_serializing_if = "<[_]>::is_empty")]
+    tool_calls: &'a [crate::api::partial_turn::PartialToolCall],
 }
 
 struct AssistantMessageMetadataInput<'a> {
@@ -52,6 +63,8 @@ struct AssistantMessageMetadataInput<'a> {
     model_normalized: &'a Option<String>,
     shared_files: &'a Option<Vec<crate::api::control::SharedFile>>,
     resumable: bool,
+    cancelled: bool,
+    tool_calls: &'a [crate::api::partial_turn::PartialToolCall],
 }
 
 fn assistant_message_metadata(input: AssistantMessageMetadataInput<'_>) -> serde_json::Value {
@@ -68,6 +81,8 @@ fn assistant_message_metadata(input: AssistantMessageMetadataInput<'_>) -> serde
         model_normalized: input.model_normalized.clone(),
         shared_files: input.shared_files.clone(),
         resumable: input.resumable,
+        cancelled: input.cancelled,
+        tool_calls: input.tool_calls,
     };
     serde_json::to_value(metadata).expect("assistant metadata should serialize")
 }
@@ -87,6 +102,46 @@ fn parse_uuid_or_nil(raw: &str) -> Uuid {
     })
 }
 
+/// Connection tuning applied before the schema. `auto_vacuum` only takes
+/// effect on new databases; existing ones switch over on their next VACUUM.
+const TUNING: &str = r#"
+PRAGMA auto_vacuum = INCREMENTAL;
+PRAGMA synchronous = NORMAL;
+PRAGMA temp_store = MEMORY;
+PRAGMA cache_size = -16000;
+PRAGMA journal_size_limit = 67108864;
+"#;
+
+/// How long a statement waits for a lock held by another connection.
+const BUSY_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// Integrity problems listed in a report (the check stops after this many).
+const MAX_INTEGRITY_PROBLEMS: u32 = 100;
+
+/// `UNREAD_EVENT_TYPES` as an SQL list, for `event_type IN (...)`.
+fn unread_types_sql() -> String {
+    UNREAD_EVENT_TYPES
+        .iter()
+        .map(|t| format!("'{}'", t))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Remove the content files of event rows that were not committed.
+fn remove_content_files<'a>(files: impl Iterator<Item = &'a String>) {
+    for file in files {
+        if let Err(e) = std::fs::remove_file(file) {
+            tracing::warn!("Failed to remove content file {}: {}", file, e);
+        }
+    }
+}
+
+/// Size of a database file plus its write-ahead log.
+fn database_size(path: &str) -> u64 {
+    let size = |p: &str| std::fs::metadata(p).map(|m| m.len()).unwrap_or(0);
+    size(path) + size(&format!("{}-wal", path))
+}
+
 const SCHEMA: &str = r#"
 PRAGMA journal_mode = WAL;
 PRAGMA foreign_keys = ON;
@@ -112,7 +167,18 @@ CREATE TABLE IF NOT EXISTS missions (
     interrupted_at TEXT,
     resumable INTEGER NOT NULL DEFAULT 0,
     desktop_sessions TEXT,
-    terminal_reason TEXT
+    terminal_reason TEXT,
+    agent_version TEXT,
+    tags TEXT,
+    pinned INTEGER NOT NULL DEFAULT 0,
+    archived INTEGER NOT NULL DEFAULT 0,
+    dry_run INTEGER NOT NULL DEFAULT 0,
+    env_profile TEXT,
+    assignee TEXT,
+    watchers TEXT,
+    checklist TEXT,
+    follow_ups TEXT,
+    normalized_title TEXT
 );
 
 CREATE INDEX IF NOT EXISTS idx_missions_updated_at ON missions(updated_at DESC);
@@ -126,6 +192,112 @@ CREATE TABLE IF NOT EXISTS mission_trees (
     FOREIGN KEY (mission_id) REFERENCES missions(id) ON DELETE CASCADE
 );
 
+CREATE TABLE IF NOT EXISTS mission_reports (
+    mission_id TEXT PRIMARY KEY NOT NULL,
+    report_json TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (mission_id) REFERENCES missions(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS scheduled_messages (
+    id TEXT PRIMARY KEY NOT NULL,
+    mission_id TEXT NOT NULL,
+    content TEXT NOT NULL,
+    agent TEXT,
+    send_at TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    status TEXT NOT NULL,
+    sent_at TEXT,
+    error TEXT,
+    FOREIGN KEY (mission_id) REFERENCES missions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_scheduled_messages_due ON scheduled_messages(status, send_at);
+CREATE INDEX IF NOT EXISTS idx_scheduled_messages_mission ON scheduled_messages(mission_id, send_at);
+
+CREATE TABLE IF NOT EXISTS mission_retry_policies (
+    mission_id TEXT PRIMARY KEY NOT NULL,
+    policy_json TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    FOREIGN KEY (mission_id) REFERENCES missions(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS mission_retries (
+    id TEXT PRIMARY KEY NOT NULL,
+    mission_id TEXT NOT NULL,
+    attempt INTEGER NOT NULL,
+    terminal_reason TEXT,
+    due_at TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    status TEXT NOT NULL,
+    started_at TEXT,
+    error TEXT,
+    FOREIGN KEY (mission_id) REFERENCES missions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_mission_retries_due ON mission_retries(status, due_at);
+CREATE INDEX IF NOT EXISTS idx_mission_retries_mission ON mission_retries(mission_id, attempt);
+
+CREATE TABLE IF NOT EXISTS runner_heartbeats (
+    mission_id TEXT PRIMARY KEY NOT NULL,
+    instance_id TEXT NOT NULL,
+    pid INTEGER NOT NULL,
+    beat_at TEXT NOT NULL,
+    FOREIGN KEY (mission_id) REFERENCES missions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_runner_heartbeats_instance ON runner_heartbeats(instance_id);
+
+CREATE TABLE IF NOT EXISTS turn_journal (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    mission_id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    tool_call_id TEXT,
+    tool_name TEXT,
+    content TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    FOREIGN KEY (mission_id) REFERENCES missions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_turn_journal_mission ON turn_journal(mission_id, id);
+
+CREATE TABLE IF NOT EXISTS message_feedback (
+    mission_id TEXT NOT NULL,
+    message_id TEXT NOT NULL,
+    rating TEXT NOT NULL,
+    comment TEXT,
+    backend TEXT NOT NULL,
+    model TEXT,
+    created_at TEXT NOT NULL,
+    PRIMARY KEY (mission_id, message_id),
+    FOREIGN KEY (mission_id) REFERENCES missions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_message_feedback_created ON message_feedback(created_at);
+
+CREATE TABLE IF NOT EXISTS mission_reads (
+    mission_id TEXT NOT NULL,
+    username TEXT NOT NULL,
+    last_read_seq INTEGER NOT NULL,
+    read_at TEXT NOT NULL,
+    PRIMARY KEY (mission_id, username),
+    FOREIGN KEY (mission_id) REFERENCES missions(id) ON DELETE CASCADE
+);
+
+CREATE TABLE IF NOT EXISTS mission_comments (
+    id TEXT PRIMARY KEY NOT NULL,
+    mission_id TEXT NOT NULL,
+    thread_id TEXT NOT NULL,
+    author TEXT NOT NULL,
+    body TEXT NOT NULL,
+    mentions TEXT NOT NULL DEFAULT '[]',
+    created_at TEXT NOT NULL,
+    updated_at TEXT,
+    FOREIGN KEY (mission_id) REFERENCES missions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_mission_comments_mission ON mission_comments(mission_id, created_at);
+
 CREATE TABLE IF NOT EXISTS mission_events (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     mission_id TEXT NOT NULL,
@@ -138,6 +310,8 @@ CREATE TABLE IF NOT EXISTS mission_events (
     content TEXT,
     content_file TEXT,
     metadata TEXT,
+    stream_seq INTEGER,
+    schema_version INTEGER NOT NULL DEFAULT 1,
     FOREIGN KEY (mission_id) REFERENCES missions(id) ON DELETE CASCADE
 );
 
@@ -174,6 +348,8 @@ CREATE TABLE IF NOT EXISTS automations (
     retry_max_retries INTEGER NOT NULL DEFAULT 3,
     retry_delay_seconds INTEGER NOT NULL DEFAULT 60,
     retry_backoff_multiplier REAL NOT NULL DEFAULT 2.0,
+    mission_retry_policy TEXT,
+    blackout_windows TEXT,
     FOREIGN KEY (mission_id) REFERENCES missions(id) ON DELETE CASCADE
 );
 
@@ -192,6 +368,7 @@ CREATE TABLE IF NOT EXISTS automation_executions (
     completed_at TEXT,
     error TEXT,
     retry_count INTEGER NOT NULL DEFAULT 0,
+    first_event_seq INTEGER,
     FOREIGN KEY (automation_id) REFERENCES automations(id) ON DELETE CASCADE,
     FOREIGN KEY (mission_id) REFERENCES missions(id) ON DELETE CASCADE
 );
@@ -207,6 +384,21 @@ const CONTENT_SIZE_THRESHOLD: usize = 64 * 1024;
 pub struct SqliteMissionStore {
     conn: Arc<Mutex<Connection>>,
     content_dir: PathBuf,
+    /// Encrypts event content and turn journal entries at rest when set.
+    cipher: Option<Arc<StoreCipher>>,
+}
+
+/// A mission event as written to `mission_events`.
+struct EventRow {
+    mission_id: Uuid,
+    stream_seq: Option<u64>,
+    event_type: String,
+    timestamp: String,
+    event_id: Option<String>,
+    tool_call_id: Option<String>,
+    tool_name: Option<String>,
+    content: String,
+    metadata: String,
 }
 
 impl SqliteMissionStore {
@@ -227,6 +419,8 @@ impl SqliteMissionStore {
         let retry_max_retries: i64 = row.get(12)?;
         let retry_delay_seconds: i64 = row.get(13)?;
         let retry_backoff_multiplier: f64 = row.get(14)?;
+        let mission_retry_policy: Option<String> = row.get(15)?;
+        let blackout_windows: Option<String> = row.get(16)?;
 
         // Parse command source
         let command_source: CommandSource = match command_source_type.as_str() {
@@ -325,6 +519,13 @@ impl SqliteMissionStore {
                 retry_delay_seconds: retry_delay_seconds as u64,
                 backoff_multiplier: retry_backoff_multiplier,
             },
+            mission_retry_policy: mission_retry_policy
+                .as_deref()
+                .and_then(|json| serde_json::from_str(json).ok()),
+            blackout_windows: blackout_windows
+                .as_deref()
+                .and_then(|json| serde_json::from_str(json).ok())
+                .unwrap_or_default(),
             consecutive_failures: 0,
         })
     }
@@ -344,6 +545,8 @@ impl SqliteMissionStore {
         let completed_at: Option<String> = row.get(8)?;
         let error: Option<String> = row.get(9)?;
         let retry_count: i64 = row.get(10)?;
+        let first_event_seq: Option<i64> = row.get(11)?;
+        let last_event_seq: Option<i64> = row.get(12)?;
 
         // Parse status
         let status = match status_str.as_str() {
@@ -378,10 +581,22 @@ impl SqliteMissionStore {
             completed_at,
             error,
             retry_count: retry_count as u32,
+            first_event_seq,
+            last_event_seq,
         })
     }
 
+    /// Open the store, encrypting content at rest when a key is configured
+    /// (see [`super::crypto`]).
     pub async fn new(base_dir: PathBuf, user_id: &str) -> Result<Self, String> {
+        Self::with_cipher(base_dir, user_id, StoreCipher::shared().await?).await
+    }
+
+    pub async fn with_cipher(
+        base_dir: PathBuf,
+        user_id: &str,
+        cipher: Option<Arc<StoreCipher>>,
+    ) -> Result<Self, String> {
         let sanitized = sanitize_filename(user_id);
         let db_path = base_dir.join(format!("missions-{}.db", sanitized));
         let content_dir = base_dir.join("mission_data").join(&sanitized);
@@ -395,10 +610,16 @@ impl SqliteMissionStore {
             .map_err(|e| format!("Failed to create content dir: {}", e))?;
 
         // Open database in blocking task
+        let migrate_cipher = cipher.clone();
         let conn = tokio::task::spawn_blocking(move || {
-            let conn = Connection::open(&db_path)
+            let mut conn = Connection::open(&db_path)
                 .map_err(|e| format!("Failed to open SQLite database: {}", e))?;
 
+            conn.busy_timeout(BUSY_TIMEOUT)
+                .map_err(|e| format!("Failed to set busy timeout: {}", e))?;
+            conn.execute_batch(TUNING)
+                .map_err(|e| format!("Failed to tune SQLite connection: {}", e))?;
+
             // Run schema
             conn.execute_batch(SCHEMA)
                 .map_err(|e| format!("Failed to run schema: {}", e))?;
@@ -406,6 +627,10 @@ impl SqliteMissionStore {
             // Run migrations for existing databases
             Self::run_migrations(&conn)?;
 
+            if let Some(cipher) = migrate_cipher {
+                Self::encrypt_plaintext_content(&mut conn, &cipher)?;
+            }
+
             Ok::<_, String>(conn)
         })
         .await
@@ -414,17 +639,98 @@ impl SqliteMissionStore {
         Ok(Self {
             conn: Arc::new(Mutex::new(conn)),
             content_dir,
+            cipher,
         })
     }
 
+    /// Encrypt content written before encryption was enabled. Already
+    /// encrypted rows are skipped, so this is a no-op after the first run.
+    fn encrypt_plaintext_content(
+        conn: &mut Connection,
+        cipher: &StoreCipher,
+    ) -> Result<(), String> {
+        let pattern = format!("{}%", crypto::ENCRYPTED_PREFIX);
+        let tx = conn.transaction().map_err(|e| e.to_string())?;
+
+        let events: Vec<(i64, Option<String>, Option<String>)> = {
+            let mut stmt = tx
+                .prepare(
+                    "SELECT id, content, content_file FROM mission_events
+                     WHERE (content IS NOT NULL AND content NOT LIKE ?1)
+                        OR content_file IS NOT NULL",
+                )
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map(params![&pattern], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                })
+                .map_err(|e| e.to_string())?;
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?
+        };
+        let mut migrated = 0usize;
+        for (id, content, content_file) in events {
+            if let Some(content) = content.filter(|c| !crypto::is_encrypted(c)) {
+                tx.execute(
+                    "UPDATE mission_events SET content = ?1 WHERE id = ?2",
+                    params![cipher.encrypt(&content), id],
+                )
+                .map_err(|e| e.to_string())?;
+                migrated += 1;
+            }
+            if let Some(path) = content_file {
+                match std::fs::read_to_string(&path) {
+                    Ok(content) if !crypto::is_encrypted(&content) => {
+                        std::fs::write(&path, cipher.encrypt(&content))
+                            .map_err(|e| format!("Failed to encrypt {}: {}", path, e))?;
+                        migrated += 1;
+                    }
+                    Ok(_) => {}
+                    Err(e) => tracing::warn!("Failed to read content file {}: {}", path, e),
+                }
+            }
+        }
+
+        let journal: Vec<(i64, String)> = {
+            let mut stmt = tx
+                .prepare("SELECT id, content FROM turn_journal WHERE content NOT LIKE ?1")
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map(params![&pattern], |row| Ok((row.get(0)?, row.get(1)?)))
+                .map_err(|e| e.to_string())?;
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?
+        };
+        for (id, content) in journal {
+            tx.execute(
+                "UPDATE turn_journal SET content = ?1 WHERE id = ?2",
+                params![cipher.encrypt(&content), id],
+            )
+            .map_err(|e| e.to_string())?;
+            migrated += 1;
+        }
+
+        tx.commit().map_err(|e| e.to_string())?;
+        if migrated > 0 {
+            tracing::info!(
+                migrated = migrated,
+                "Encrypted existing plaintext mission content"
+            );
+        }
+        Ok(())
+    }
+
     /// Store content, either inline or in a file if too large.
     fn store_content(
         content_dir: &std::path::Path,
+        cipher: Option<&StoreCipher>,
         mission_id: Uuid,
         sequence: i64,
         event_type: &str,
         content: &str,
     ) -> (Option<String>, Option<String>) {
+        let sealed = crypto::seal(cipher, content);
+        let content = sealed.as_str();
         if content.len() <= CONTENT_SIZE_THRESHOLD {
             (Some(content.to_string()), None)
         } else {
@@ -445,764 +751,2749 @@ impl SqliteMissionStore {
         }
     }
 
-    /// Load content from inline or file.
-    fn load_content(content: Option<&str>, content_file: Option<&str>) -> String {
+    /// Load content from inline or file, decrypting it if needed.
+    fn load_content(
+        cipher: Option<&StoreCipher>,
+        content: Option<&str>,
+        content_file: Option<&str>,
+    ) -> String {
         if let Some(c) = content {
-            c.to_string()
+            crypto::open(cipher, c)
         } else if let Some(path) = content_file {
-            std::fs::read_to_string(path).unwrap_or_default()
+            crypto::open(cipher, &std::fs::read_to_string(path).unwrap_or_default())
         } else {
             String::new()
         }
     }
 
-    /// Run database migrations for existing databases.
-    /// CREATE TABLE IF NOT EXISTS doesn't add columns to existing tables,
-    /// so we need to handle schema changes manually.
-    fn run_migrations(conn: &Connection) -> Result<(), String> {
-        // Check if 'backend' column exists in missions table
-        let has_backend_column: bool = conn
-            .prepare("SELECT 1 FROM pragma_table_info('missions') WHERE name = 'backend'")
-            .map_err(|e| format!("Failed to check for backend column: {}", e))?
-            .exists([])
-            .map_err(|e| format!("Failed to query table info: {}", e))?;
-
-        if !has_backend_column {
-            tracing::info!("Running migration: adding 'backend' column to missions table");
-            conn.execute(
-                "ALTER TABLE missions ADD COLUMN backend TEXT NOT NULL DEFAULT 'opencode'",
-                [],
-            )
-            .map_err(|e| format!("Failed to add backend column: {}", e))?;
-        }
-
-        // Check if 'session_id' column exists in missions table
-        let has_session_id_column: bool = conn
-            .prepare("SELECT 1 FROM pragma_table_info('missions') WHERE name = 'session_id'")
-            .map_err(|e| format!("Failed to check for session_id column: {}", e))?
-            .exists([])
-            .map_err(|e| format!("Failed to query table info: {}", e))?;
-
-        if !has_session_id_column {
-            tracing::info!("Running migration: adding 'session_id' column to missions table");
-            conn.execute("ALTER TABLE missions ADD COLUMN session_id TEXT", [])
-                .map_err(|e| format!("Failed to add session_id column: {}", e))?;
-        }
-
-        // Add performance indexes if they don't exist (idempotent)
-        conn.execute_batch(
-            "CREATE INDEX IF NOT EXISTS idx_missions_status_updated ON missions(status, updated_at);
-             CREATE INDEX IF NOT EXISTS idx_events_event_type ON mission_events(event_type);",
-        )
-        .map_err(|e| format!("Failed to create performance indexes: {}", e))?;
-
-        // Check if 'terminal_reason' column exists in missions table
-        let has_terminal_reason_column: bool = conn
-            .prepare("SELECT 1 FROM pragma_table_info('missions') WHERE name = 'terminal_reason'")
-            .map_err(|e| format!("Failed to check for terminal_reason column: {}", e))?
-            .exists([])
-            .map_err(|e| format!("Failed to query table info: {}", e))?;
-
-        if !has_terminal_reason_column {
-            tracing::info!("Running migration: adding 'terminal_reason' column to missions table");
-            conn.execute("ALTER TABLE missions ADD COLUMN terminal_reason TEXT", [])
-                .map_err(|e| format!("Failed to add terminal_reason column: {}", e))?;
-        }
-
-        // Check if 'config_profile' column exists in missions table
-        let has_config_profile_column: bool = conn
-            .prepare("SELECT 1 FROM pragma_table_info('missions') WHERE name = 'config_profile'")
-            .map_err(|e| format!("Failed to check for config_profile column: {}", e))?
-            .exists([])
-            .map_err(|e| format!("Failed to query table info: {}", e))?;
-
-        if !has_config_profile_column {
-            tracing::info!("Running migration: adding 'config_profile' column to missions table");
-            conn.execute("ALTER TABLE missions ADD COLUMN config_profile TEXT", [])
-                .map_err(|e| format!("Failed to add config_profile column: {}", e))?;
-        }
-
-        // Check if 'model_effort' column exists in missions table
-        let has_model_effort_column: bool = conn
-            .prepare("SELECT 1 FROM pragma_table_info('missions') WHERE name = 'model_effort'")
-            .map_err(|e| format!("Failed to check for model_effort column: {}", e))?
-            .exists([])
-            .map_err(|e| format!("Failed to query table info: {}", e))?;
-
-        if !has_model_effort_column {
-            tracing::info!("Running migration: adding 'model_effort' column to missions table");
-            conn.execute("ALTER TABLE missions ADD COLUMN model_effort TEXT", [])
-                .map_err(|e| format!("Failed to add model_effort column: {}", e))?;
-        }
-
-        // Check if 'short_description' column exists in missions table
-        let has_short_description_column: bool = conn
-            .prepare("SELECT 1 FROM pragma_table_info('missions') WHERE name = 'short_description'")
-            .map_err(|e| format!("Failed to check for short_description column: {}", e))?
-            .exists([])
-            .map_err(|e| format!("Failed to query table info: {}", e))?;
-
-        if !has_short_description_column {
-            tracing::info!(
-                "Running migration: adding 'short_description' column to missions table"
-            );
-            conn.execute("ALTER TABLE missions ADD COLUMN short_description TEXT", [])
-                .map_err(|e| format!("Failed to add short_description column: {}", e))?;
-        }
-
-        // Check if 'metadata_updated_at' column exists in missions table
-        let has_metadata_updated_at_column: bool = conn
-            .prepare(
-                "SELECT 1 FROM pragma_table_info('missions') WHERE name = 'metadata_updated_at'",
-            )
-            .map_err(|e| format!("Failed to check for metadata_updated_at column: {}", e))?
-            .exists([])
-            .map_err(|e| format!("Failed to query table info: {}", e))?;
-
-        if !has_metadata_updated_at_column {
-            tracing::info!(
-                "Running migration: adding 'metadata_updated_at' column to missions table"
-            );
-            conn.execute(
-                "ALTER TABLE missions ADD COLUMN metadata_updated_at TEXT",
-                [],
+    /// Insert (or update) a mission event row.
+    async fn insert_event(
+        &self,
+        mission_id: Uuid,
+        stream_seq: Option<u64>,
+        event: &AgentEvent,
+    ) -> Result<(), String> {
+        let Some(row) = Self::event_row(mission_id, stream_seq, event) else {
+            return Ok(());
+        };
+        let conn = self.conn.clone();
+        let content_dir = self.content_dir.clone();
+        let cipher = self.cipher.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            Self::write_event_row(
+                &conn,
+                &content_dir,
+                cipher.as_deref(),
+                row,
+                &mut HashMap::new(),
             )
-            .map_err(|e| format!("Failed to add metadata_updated_at column: {}", e))?;
-        }
-
-        let has_metadata_source_column: bool = conn
-            .prepare("SELECT 1 FROM pragma_table_info('missions') WHERE name = 'metadata_source'")
-            .map_err(|e| format!("Failed to check for metadata_source column: {}", e))?
-            .exists([])
-            .map_err(|e| format!("Failed to query table info: {}", e))?;
-
-        if !has_metadata_source_column {
-            tracing::info!("Running migration: adding 'metadata_source' column to missions table");
-            conn.execute("ALTER TABLE missions ADD COLUMN metadata_source TEXT", [])
-                .map_err(|e| format!("Failed to add metadata_source column: {}", e))?;
-        }
-
-        let has_metadata_model_column: bool = conn
-            .prepare("SELECT 1 FROM pragma_table_info('missions') WHERE name = 'metadata_model'")
-            .map_err(|e| format!("Failed to check for metadata_model column: {}", e))?
-            .exists([])
-            .map_err(|e| format!("Failed to query table info: {}", e))?;
+            .map(|_| ())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
 
-        if !has_metadata_model_column {
+    /// The row an event is stored as, or `None` for events that aren't
+    /// persisted.
+    fn event_row(
+        mission_id: Uuid,
+        stream_seq: Option<u64>,
+        event: &AgentEvent,
+    ) -> Option<EventRow> {
+        let (event_type, event_id, tool_call_id, tool_name, content, metadata) = match event {
+            AgentEvent::UserMessage {
+                id,
+                content,
+                queued,
+                ..
+            } => (
+                "user_message",
+                Some(id.to_string()),
+                None,
+                None,
+                content.clone(),
+                serde_json::json!({ "queued": queued }),
+            ),
+            AgentEvent::AssistantMessage {
+                id,
+                content,
+                success,
+                cost_cents,
+                cost_source,
+                usage,
+                model,
+                model_normalized,
+                shared_files,
+                resumable,
+                cancelled,
+                tool_calls,
+                ..
+            } => (
+                "assistant_message",
+                Some(id.to_string()),
+                None,
+                None,
+                content.clone(),
+                assistant_message_metadata(AssistantMessageMetadataInput {
+                    success: *success,
+                    cost_cents: *cost_cents,
+                    cost_source: *cost_source,
+                    usage,
+                    model,
+                    model_normalized,
+                    shared_files,
+                    resumable: *resumable,
+                    cancelled: *cancelled,
+                    tool_calls,
+                }),
+            ),
+            AgentEvent::Thinking { content, done, .. } => (
+                "thinking",
+                None,
+                None,
+                None,
+                content.clone(),
+                serde_json::json!({ "done": done }),
+            ),
+            AgentEvent::ToolCall {
+                tool_call_id,
+                name,
+                args,
+                ..
+            } => (
+                "tool_call",
+                None,
+                Some(tool_call_id.clone()),
+                Some(name.clone()),
+                args.to_string(),
+                serde_json::json!({}),
+            ),
+            AgentEvent::ToolResult {
+                tool_call_id,
+                name,
+                result,
+                ..
+            } => (
+                "tool_result",
+                None,
+                Some(tool_call_id.clone()),
+                Some(name.clone()),
+                result.to_string(),
+                serde_json::json!({}),
+            ),
+            AgentEvent::UserQuestion {
+                tool_call_id,
+                questions,
+                timeout_secs,
+                ..
+            } => (
+                "user_question",
+                None,
+                Some(tool_call_id.clone()),
+                None,
+                serde_json::to_string(questions).unwrap_or_default(),
+                serde_json::json!({ "timeout_secs": timeout_secs }),
+            ),
+            // Stored as rendered text: it is part of the mission history.
+            AgentEvent::UserQuestionAnswered {
+                tool_call_id,
+                questions,
+                answers,
+                timed_out,
+                ..
+            } => (
+                "user_question_answered",
+                None,
+                Some(tool_call_id.clone()),
+                None,
+                crate::api::user_question::history_entry(questions, answers, *timed_out),
+                serde_json::json!({ "answers": answers, "timed_out": timed_out }),
+            ),
+            AgentEvent::Error {
+                message,
+                code,
+                resumable,
+                ..
+            } => (
+                "error",
+                None,
+                None,
+                None,
+                message.clone(),
+                match code {
+                    Some(code) => serde_json::json!({ "resumable": resumable, "code": code }),
+                    None => serde_json::json!({ "resumable": resumable }),
+                },
+            ),
+            AgentEvent::TextDelta { content, .. } => (
+                "text_delta",
+                Some("text_delta_latest".to_string()),
+                None,
+                None,
+                content.clone(),
+                serde_json::json!({}),
+            ),
+            AgentEvent::MissionStatusChanged {
+                status, summary, ..
+            } => (
+                "mission_status_changed",
+                None,
+                None,
+                None,
+                summary.clone().unwrap_or_default(),
+                serde_json::json!({ "status": status.to_string() }),
+            ),
+            AgentEvent::MissionMetadataUpdated {
+                title,
+                short_description,
+                metadata_updated_at,
+                updated_at,
+                metadata_source,
+                metadata_model,
+                metadata_version,
+                ..
+            } => (
+                "mission_metadata_updated",
+                None,
+                None,
+                None,
+                title.clone().unwrap_or_default(),
+                serde_json::json!({
+                    "title": title,
+                    "short_description": short_description,
+                    "metadata_updated_at": metadata_updated_at,
+                    "updated_at": updated_at,
+                    "metadata_source": metadata_source,
+                    "metadata_model": metadata_model,
+                    "metadata_version": metadata_version
+                }),
+            ),
+            AgentEvent::SamplingCandidate {
+                run_id,
+                index,
+                model,
+                temperature,
+                content,
+                cost_cents,
+                error,
+                ..
+            } => (
+                "sampling_candidate",
+                None,
+                None,
+                None,
+                content.clone(),
+                serde_json::json!({
+                    "run_id": run_id,
+                    "index": index,
+                    "model": model,
+                    "temperature": temperature,
+                    "cost_cents": cost_cents,
+                    "error": error,
+                }),
+            ),
+            AgentEvent::SamplingResult {
+                run_id,
+                candidates,
+                selected,
+                scores,
+                rationale,
+                total_cost_cents,
+                budget_exhausted,
+                ..
+            } => (
+                "sampling_result",
+                None,
+                None,
+                None,
+                rationale.clone().unwrap_or_default(),
+                serde_json::json!({
+                    "run_id": run_id,
+                    "candidates": candidates,
+                    "selected": selected,
+                    "scores": scores,
+                    "total_cost_cents": total_cost_cents,
+                    "budget_exhausted": budget_exhausted,
+                }),
+            ),
+            AgentEvent::CompletionVerification {
+                attempt,
+                max_attempts,
+                passed,
+                checklist,
+                gaps,
+                test,
+                ..
+            } => (
+                "completion_verification",
+                None,
+                None,
+                None,
+                gaps.join("\n"),
+                serde_json::json!({
+                    "attempt": attempt,
+                    "max_attempts": max_attempts,
+                    "passed": passed,
+                    "checklist": checklist,
+                    "test": test,
+                }),
+            ),
+            AgentEvent::ConflictDetected {
+                path,
+                tool_call_id,
+                tool_name,
+                other_missions,
+                blocked,
+                ..
+            } => (
+                "conflict_detected",
+                None,
+                Some(tool_call_id.clone()),
+                Some(tool_name.clone()),
+                path.clone(),
+                serde_json::json!({
+                    "other_missions": other_missions,
+                    "blocked": blocked,
+                }),
+            ),
+            AgentEvent::MissionStalled {
+                idle_seconds,
+                nudged,
+                cancel_after_minutes,
+                ..
+            } => (
+                "mission_stalled",
+                None,
+                None,
+                None,
+                String::new(),
+                serde_json::json!({
+                    "idle_seconds": idle_seconds,
+                    "nudged": nudged,
+                    "cancel_after_minutes": cancel_after_minutes,
+                }),
+            ),
+            AgentEvent::ProcessesTerminated {
+                reaped,
+                killed,
+                survivors,
+                ..
+            } => (
+                "processes_terminated",
+                None,
+                None,
+                None,
+                String::new(),
+                serde_json::json!({
+                    "reaped": reaped,
+                    "killed": killed,
+                    "survivors": survivors,
+                }),
+            ),
+            AgentEvent::EgressViolation {
+                host,
+                port,
+                blocked,
+                ..
+            } => (
+                "egress_violation",
+                None,
+                None,
+                None,
+                format!("{}:{}", host, port),
+                serde_json::json!({
+                    "host": host,
+                    "port": port,
+                    "blocked": blocked,
+                }),
+            ),
+            AgentEvent::BackendSwitched {
+                from, to, reason, ..
+            } => (
+                "backend_switched",
+                None,
+                None,
+                None,
+                reason.clone().unwrap_or_default(),
+                serde_json::json!({
+                    "from": from,
+                    "to": to,
+                }),
+            ),
+            AgentEvent::ApprovalRequired {
+                tool_call_id,
+                command,
+                reason,
+                ..
+            } => (
+                "approval_required",
+                None,
+                Some(tool_call_id.clone()),
+                None,
+                command.clone(),
+                serde_json::json!({ "reason": reason }),
+            ),
+            AgentEvent::ApprovalResolved {
+                tool_call_id,
+                approved,
+                ..
+            } => (
+                "approval_resolved",
+                None,
+                Some(tool_call_id.clone()),
+                None,
+                String::new(),
+                serde_json::json!({ "approved": approved }),
+            ),
+            AgentEvent::StatusChangeRequested {
+                status,
+                justification,
+                ..
+            } => (
+                "status_change_requested",
+                None,
+                None,
+                None,
+                justification.reason.clone(),
+                serde_json::json!({ "status": status, "justification": justification }),
+            ),
+            AgentEvent::StatusChangeResolved {
+                status, confirmed, ..
+            } => (
+                "status_change_resolved",
+                None,
+                None,
+                None,
+                String::new(),
+                serde_json::json!({ "status": status, "confirmed": confirmed }),
+            ),
+            // Skip events that are less important for debugging
+            AgentEvent::Status { .. }
+            | AgentEvent::AgentPhase { .. }
+            | AgentEvent::AgentTree { .. }
+            | AgentEvent::Progress { .. }
+            | AgentEvent::SessionIdUpdate { .. }
+            | AgentEvent::MissionActivity { .. }
+            | AgentEvent::UploadProgress { .. }
+            | AgentEvent::ToolOutputDelta { .. }
+            | AgentEvent::LlmUsage { .. }
+            | AgentEvent::ContextUsage { .. }
+            | AgentEvent::Presence { .. }
+            | AgentEvent::MissionNotification { .. }
+            | AgentEvent::MissionUnreadChanged { .. }
+            | AgentEvent::MissionComment { .. }
+            | AgentEvent::MissionTitleChanged { .. }
+            | AgentEvent::FollowUpsSuggested { .. }
+            | AgentEvent::DesktopSession { .. } => return None,
+        };
+
+        Some(EventRow {
+            mission_id,
+            stream_seq,
+            event_type: event_type.to_string(),
+            timestamp: now_string(),
+            event_id,
+            tool_call_id,
+            tool_name,
+            content,
+            metadata: metadata.to_string(),
+        })
+    }
+
+    /// Write an event row. Events are numbered per mission in the order they
+    /// are written; `next_sequence` caches the next number of the missions
+    /// already written by the caller. Returns the file a new row's content was
+    /// spilled to, so a caller whose transaction fails can remove it.
+    fn write_event_row(
+        conn: &Connection,
+        content_dir: &std::path::Path,
+        cipher: Option<&StoreCipher>,
+        row: EventRow,
+        next_sequence: &mut HashMap<Uuid, i64>,
+    ) -> Result<Option<String>, String> {
+        let EventRow {
+            mission_id,
+            stream_seq,
+            event_type,
+            timestamp: now,
+            event_id,
+            tool_call_id,
+            tool_name,
+            content,
+            metadata: metadata_str,
+        } = row;
+        let mid = mission_id.to_string();
+
+        // If this event has an event_id that already exists for this mission,
+        // update the existing row's metadata instead of inserting a duplicate.
+        // This happens when a queued UserMessage is re-emitted with queued: false.
+        if let Some(ref eid) = event_id {
+            let existing: Option<i64> = conn
+                .query_row(
+                    "SELECT id FROM mission_events WHERE mission_id = ?1 AND event_id = ?2",
+                    params![&mid, eid],
+                    |row| row.get(0),
+                )
+                .optional()
+                .unwrap_or(None);
+
+            if let Some(row_id) = existing {
+                let (content_inline, content_file) = SqliteMissionStore::store_content(
+                    content_dir,
+                    cipher,
+                    mission_id,
+                    row_id,
+                    &event_type,
+                    &content,
+                );
+                conn.execute(
+                    "UPDATE mission_events
+                     SET metadata = ?1, timestamp = ?2, content = ?3, content_file = ?4,
+                         stream_seq = COALESCE(?5, stream_seq), schema_version = ?6
+                     WHERE id = ?7",
+                    params![
+                        metadata_str,
+                        now,
+                        content_inline,
+                        content_file,
+                        stream_seq.map(|s| s as i64),
+                        event_schema::CURRENT_VERSION,
+                        row_id
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+                return Ok(None);
+            }
+        }
+
+        // Get next sequence
+        let sequence = match next_sequence.get(&mission_id) {
+            Some(sequence) => *sequence,
+            None => conn
+                .query_row(
+                    "SELECT COALESCE(MAX(sequence), 0) + 1 FROM mission_events WHERE mission_id = ?1",
+                    params![&mid],
+                    |row| row.get(0),
+                )
+                .unwrap_or(1),
+        };
+        next_sequence.insert(mission_id, sequence + 1);
+
+        // Store content
+        let (content_inline, content_file) = SqliteMissionStore::store_content(
+            content_dir,
+            cipher,
+            mission_id,
+            sequence,
+            &event_type,
+            &content,
+        );
+
+        let inserted = conn.execute(
+            "INSERT INTO mission_events
+             (mission_id, sequence, event_type, timestamp, event_id, tool_call_id, tool_name, content, content_file, metadata, stream_seq, schema_version)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+            params![
+                mid,
+                sequence,
+                event_type,
+                now,
+                event_id,
+                tool_call_id,
+                tool_name,
+                content_inline,
+                content_file,
+                metadata_str,
+                stream_seq.map(|s| s as i64),
+                event_schema::CURRENT_VERSION,
+            ],
+        );
+        if let Err(e) = inserted {
+            remove_content_files(content_file.iter());
+            return Err(e.to_string());
+        }
+        Ok(content_file)
+    }
+
+    /// Parse a `mission_events` row (selected with the standard column list).
+    fn parse_event_row(
+        row: &rusqlite::Row<'_>,
+        cipher: Option<&StoreCipher>,
+    ) -> Result<StoredEvent, rusqlite::Error> {
+        let content: Option<String> = row.get(8)?;
+        let content_file: Option<String> = row.get(9)?;
+        let full_content = Self::load_content(cipher, content.as_deref(), content_file.as_deref());
+        let metadata_str: String = row
+            .get::<_, Option<String>>(10)?
+            .unwrap_or_else(|| "{}".to_string());
+        let mid_str: String = row.get(1)?;
+
+        let mut event = StoredEvent {
+            id: row.get(0)?,
+            mission_id: parse_uuid_or_nil(&mid_str),
+            sequence: row.get(2)?,
+            event_type: row.get(3)?,
+            timestamp: row.get(4)?,
+            event_id: row.get(5)?,
+            tool_call_id: row.get(6)?,
+            tool_name: row.get(7)?,
+            content: full_content,
+            metadata: serde_json::from_str(&metadata_str).unwrap_or(serde_json::json!({})),
+            stream_seq: row.get::<_, Option<i64>>(11)?.map(|s| s as u64),
+            schema_version: row.get(12)?,
+        };
+        event_schema::upgrade(&mut event);
+        Ok(event)
+    }
+
+    /// Run database migrations for existing databases.
+    /// CREATE TABLE IF NOT EXISTS doesn't add columns to existing tables,
+    /// so we need to handle schema changes manually.
+    fn run_migrations(conn: &Connection) -> Result<(), String> {
+        // Check if 'backend' column exists in missions table
+        let has_backend_column: bool = conn
+            .prepare("SELECT 1 FROM pragma_table_info('missions') WHERE name = 'backend'")
+            .map_err(|e| format!("Failed to check for backend column: {}", e))?
+            .exists([])
+            .map_err(|e| format!("Failed to query table info: {}", e))?;
+
+        if !has_backend_column {
+            tracing::info!("Running migration: adding 'backend' column to missions table");
+            conn.execute(
+                "ALTER TABLE missions ADD COLUMN backend TEXT NOT NULL DEFAULT 'opencode'",
+                [],
+            )
+            .map_err(|e| format!("Failed to add backend column: {}", e))?;
+        }
+
+        // Check if 'session_id' column exists in missions table
+        let has_session_id_column: bool = conn
+            .prepare("SELECT 1 FROM pragma_table_info('missions') WHERE name = 'session_id'")
+            .map_err(|e| format!("Failed to check for session_id column: {}", e))?
+            .exists([])
+            .map_err(|e| format!("Failed to query table info: {}", e))?;
+
+        if !has_session_id_column {
+            tracing::info!("Running migration: adding 'session_id' column to missions table");
+            conn.execute("ALTER TABLE missions ADD COLUMN session_id TEXT", [])
+                .map_err(|e| format!("Failed to add session_id column: {}", e))?;
+        }
+
+        // Add performance indexes if they don't exist (idempotent)
+        conn.execute_batch(
+            "CREATE INDEX IF NOT EXISTS idx_missions_status_updated ON missions(status, updated_at);
+             CREATE INDEX IF NOT EXISTS idx_events_event_type ON mission_events(event_type);",
+        )
+        .map_err(|e| format!("Failed to create performance indexes: {}", e))?;
+
+        // Check if 'terminal_reason' column exists in missions table
+        let has_terminal_reason_column: bool = conn
+            .prepare("SELECT 1 FROM pragma_table_info('missions') WHERE name = 'terminal_reason'")
+            .map_err(|e| format!("Failed to check for terminal_reason column: {}", e))?
+            .exists([])
+            .map_err(|e| format!("Failed to query table info: {}", e))?;
+
+        if !has_terminal_reason_column {
+            tracing::info!("Running migration: adding 'terminal_reason' column to missions table");
+            conn.execute("ALTER TABLE missions ADD COLUMN terminal_reason TEXT", [])
+                .map_err(|e| format!("Failed to add terminal_reason column: {}", e))?;
+        }
+
+        // Check if 'agent_version' column exists in missions table
+        let has_agent_version_column: bool = conn
+            .prepare("SELECT 1 FROM pragma_table_info('missions') WHERE name = 'agent_version'")
+            .map_err(|e| format!("Failed to check for agent_version column: {}", e))?
+            .exists([])
+            .map_err(|e| format!("Failed to query table info: {}", e))?;
+
+        if !has_agent_version_column {
+            tracing::info!("Running migration: adding 'agent_version' column to missions table");
+            conn.execute("ALTER TABLE missions ADD COLUMN agent_version TEXT", [])
+                .map_err(|e| format!("Failed to add agent_version column: {}", e))?;
+        }
+
+        // Check if 'tags' column exists in missions table
+        let has_tags_column: bool = conn
+            .prepare("SELECT 1 FROM pragma_table_info('missions') WHERE name = 'tags'")
+            .map_err(|e| format!("Failed to check for tags column: {}", e))?
+            .exists([])
+            .map_err(|e| format!("Failed to query table info: {}", e))?;
+
+        if !has_tags_column {
+            tracing::info!("Running migration: adding 'tags' column to missions table");
+            conn.execute("ALTER TABLE missions ADD COLUMN tags TEXT", [])
+                .map_err(|e| format!("Failed to add tags column: {}", e))?;
+        }
+
+        // Check if 'env_profile' column exists in missions table
+        let has_env_profile_column: bool = conn
+            .prepare("SELECT 1 FROM pragma_table_info('missions') WHERE name = 'env_profile'")
+            .map_err(|e| format!("Failed to check for env_profile column: {}", e))?
+            .exists([])
+            .map_err(|e| format!("Failed to query table info: {}", e))?;
+
+        if !has_env_profile_column {
+            tracing::info!("Running migration: adding 'env_profile' column to missions table");
+            conn.execute("ALTER TABLE missions ADD COLUMN env_profile TEXT", [])
+                .map_err(|e| format!("Failed to add env_profile column: {}", e))?;
+        }
+
+        // Check if 'pinned'/'archived'/'dry_run' columns exist in missions table
+        for column in ["pinned", "archived", "dry_run"] {
+            let has_column: bool = conn
+                .prepare(&format!(
+                    "SELECT 1 FROM pragma_table_info('missions') WHERE name = '{}'",
+                    column
+                ))
+                .map_err(|e| format!("Failed to check for {} column: {}", column, e))?
+                .exists([])
+                .map_err(|e| format!("Failed to query table info: {}", e))?;
+
+            if !has_column {
+                tracing::info!(
+                    "Running migration: adding '{}' column to missions table",
+                    column
+                );
+                conn.execute(
+                    &format!(
+                        "ALTER TABLE missions ADD COLUMN {} INTEGER NOT NULL DEFAULT 0",
+                        column
+                    ),
+                    [],
+                )
+                .map_err(|e| format!("Failed to add {} column: {}", column, e))?;
+            }
+        }
+
+        // Check if 'assignee'/'watchers' columns exist in missions table
+        for column in ["assignee", "watchers", "checklist", "follow_ups"] {
+            let has_column: bool = conn
+                .prepare(&format!(
+                    "SELECT 1 FROM pragma_table_info('missions') WHERE name = '{}'",
+                    column
+                ))
+                .map_err(|e| format!("Failed to check for {} column: {}", column, e))?
+                .exists([])
+                .map_err(|e| format!("Failed to query table info: {}", e))?;
+
+            if !has_column {
+                tracing::info!(
+                    "Running migration: adding '{}' column to missions table",
+                    column
+                );
+                conn.execute(
+                    &format!("ALTER TABLE missions ADD COLUMN {} TEXT", column),
+                    [],
+                )
+                .map_err(|e| format!("Failed to add {} column: {}", column, e))?;
+            }
+        }
+
+        // Check if 'config_profile' column exists in missions table
+        let has_config_profile_column: bool = conn
+            .prepare("SELECT 1 FROM pragma_table_info('missions') WHERE name = 'config_profile'")
+            .map_err(|e| format!("Failed to check for config_profile column: {}", e))?
+            .exists([])
+            .map_err(|e| format!("Failed to query table info: {}", e))?;
+
+        if !has_config_profile_column {
+            tracing::info!("Running migration: adding 'config_profile' column to missions table");
+            conn.execute("ALTER TABLE missions ADD COLUMN config_profile TEXT", [])
+                .map_err(|e| format!("Failed to add config_profile column: {}", e))?;
+        }
+
+        // Check if 'model_effort' column exists in missions table
+        let has_model_effort_column: bool = conn
+            .prepare("SELECT 1 FROM pragma_table_info('missions') WHERE name = 'model_effort'")
+            .map_err(|e| format!("Failed to check for model_effort column: {}", e))?
+            .exists([])
+            .map_err(|e| format!("Failed to query table info: {}", e))?;
+
+        if !has_model_effort_column {
+            tracing::info!("Running migration: adding 'model_effort' column to missions table");
+            conn.execute("ALTER TABLE missions ADD COLUMN model_effort TEXT", [])
+                .map_err(|e| format!("Failed to add model_effort column: {}", e))?;
+        }
+
+        // Check if 'short_description' column exists in missions table
+        let has_short_description_column: bool = conn
+            .prepare("SELECT 1 FROM pragma_table_info('missions') WHERE name = 'short_description'")
+            .map_err(|e| format!("Failed to check for short_description column: {}", e))?
+            .exists([])
+            .map_err(|e| format!("Failed to query table info: {}", e))?;
+
+        if !has_short_description_column {
+            tracing::info!(
+                "Running migration: adding 'short_description' column to missions table"
+            );
+            conn.execute("ALTER TABLE missions ADD COLUMN short_description TEXT", [])
+                .map_err(|e| format!("Failed to add short_description column: {}", e))?;
+        }
+
+        // Check if 'metadata_updated_at' column exists in missions table
+        let has_metadata_updated_at_column: bool = conn
+            .prepare(
+                "SELECT 1 FROM pragma_table_info('missions') WHERE name = 'metadata_updated_at'",
+            )
+            .map_err(|e| format!("Failed to check for metadata_updated_at column: {}", e))?
+            .exists([])
+            .map_err(|e| format!("Failed to query table info: {}", e))?;
+
+        if !has_metadata_updated_at_column {
+            tracing::info!(
+                "Running migration: adding 'metadata_updated_at' column to missions table"
+            );
+            conn.execute(
+                "ALTER TABLE missions ADD COLUMN metadata_updated_at TEXT",
+                [],
+            )
+            .map_err(|e| format!("Failed to add metadata_updated_at column: {}", e))?;
+        }
+
+        let has_metadata_source_column: bool = conn
+            .prepare("SELECT 1 FROM pragma_table_info('missions') WHERE name = 'metadata_source'")
+            .map_err(|e| format!("Failed to check for metadata_source column: {}", e))?
+            .exists([])
+            .map_err(|e| format!("Failed to query table info: {}", e))?;
+
+        if !has_metadata_source_column {
+            tracing::info!("Running migration: adding 'metadata_source' column to missions table");
+            conn.execute("ALTER TABLE missions ADD COLUMN metadata_source TEXT", [])
+                .map_err(|e| format!("Failed to add metadata_source column: {}", e))?;
+        }
+
+        let has_metadata_model_column: bool = conn
+            .prepare("SELECT 1 FROM pragma_table_info('missions') WHERE name = 'metadata_model'")
+            .map_err(|e| format!("Failed to check for metadata_model column: {}", e))?
+            .exists([])
+            .map_err(|e| format!("Failed to query table info: {}", e))?;
+
+        if !has_metadata_model_column {
             tracing::info!("Running migration: adding 'metadata_model' column to missions table");
             conn.execute("ALTER TABLE missions ADD COLUMN metadata_model TEXT", [])
                 .map_err(|e| format!("Failed to add metadata_model column: {}", e))?;
         }
 
-        let has_metadata_version_column: bool = conn
-            .prepare("SELECT 1 FROM pragma_table_info('missions') WHERE name = 'metadata_version'")
-            .map_err(|e| format!("Failed to check for metadata_version column: {}", e))?
-            .exists([])
-            .map_err(|e| format!("Failed to query table info: {}", e))?;
+        let has_metadata_version_column: bool = conn
+            .prepare("SELECT 1 FROM pragma_table_info('missions') WHERE name = 'metadata_version'")
+            .map_err(|e| format!("Failed to check for metadata_version column: {}", e))?
+            .exists([])
+            .map_err(|e| format!("Failed to query table info: {}", e))?;
+
+        if !has_metadata_version_column {
+            tracing::info!("Running migration: adding 'metadata_version' column to missions table");
+            conn.execute("ALTER TABLE missions ADD COLUMN metadata_version TEXT", [])
+                .map_err(|e| format!("Failed to add metadata_version column: {}", e))?;
+        }
+
+        // Check if 'normalized_title' column exists in missions table
+        let has_normalized_title_column: bool = conn
+            .prepare("SELECT 1 FROM pragma_table_info('missions') WHERE name = 'normalized_title'")
+            .map_err(|e| format!("Failed to check for normalized_title column: {}", e))?
+            .exists([])
+            .map_err(|e| format!("Failed to query table info: {}", e))?;
+
+        if !has_normalized_title_column {
+            tracing::info!("Running migration: adding 'normalized_title' column to missions table");
+            conn.execute("ALTER TABLE missions ADD COLUMN normalized_title TEXT", [])
+                .map_err(|e| format!("Failed to add normalized_title column: {}", e))?;
+        }
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_missions_normalized_title ON missions(normalized_title) WHERE normalized_title IS NOT NULL",
+            [],
+        )
+        .map_err(|e| format!("Failed to create normalized_title index: {}", e))?;
+        Self::backfill_normalized_titles(conn)?;
+
+        // Check if 'stream_seq' column exists in mission_events table
+        let has_stream_seq_column: bool = conn
+            .prepare("SELECT 1 FROM pragma_table_info('mission_events') WHERE name = 'stream_seq'")
+            .map_err(|e| format!("Failed to check for stream_seq column: {}", e))?
+            .exists([])
+            .map_err(|e| format!("Failed to query table info: {}", e))?;
+
+        if !has_stream_seq_column {
+            tracing::info!("Running migration: adding 'stream_seq' column to mission_events table");
+            conn.execute(
+                "ALTER TABLE mission_events ADD COLUMN stream_seq INTEGER",
+                [],
+            )
+            .map_err(|e| format!("Failed to add stream_seq column: {}", e))?;
+        }
+        conn.execute(
+            "CREATE INDEX IF NOT EXISTS idx_events_stream_seq ON mission_events(stream_seq) WHERE stream_seq IS NOT NULL",
+            [],
+        )
+        .map_err(|e| format!("Failed to create stream_seq index: {}", e))?;
+
+        // Check if 'schema_version' column exists in mission_events table.
+        // Rows written before versioning default to schema version 1 and are
+        // upgraded when read.
+        let has_schema_version_column: bool = conn
+            .prepare(
+                "SELECT 1 FROM pragma_table_info('mission_events') WHERE name = 'schema_version'",
+            )
+            .map_err(|e| format!("Failed to check for schema_version column: {}", e))?
+            .exists([])
+            .map_err(|e| format!("Failed to query table info: {}", e))?;
+
+        if !has_schema_version_column {
+            tracing::info!(
+                "Running migration: adding 'schema_version' column to mission_events table"
+            );
+            conn.execute(
+                "ALTER TABLE mission_events ADD COLUMN schema_version INTEGER NOT NULL DEFAULT 1",
+                [],
+            )
+            .map_err(|e| format!("Failed to add schema_version column: {}", e))?;
+        }
+
+        // Migrate automations table to new schema
+        Self::migrate_automations_table(conn)?;
+        Self::ensure_automation_indexes(conn)?;
+
+        Ok(())
+    }
+
+    /// Fill `normalized_title` for titled missions written before the column
+    /// existed.
+    fn backfill_normalized_titles(conn: &Connection) -> Result<(), String> {
+        let titles: Vec<(String, String)> = conn
+            .prepare(
+                "SELECT id, title FROM missions WHERE title IS NOT NULL AND normalized_title IS NULL",
+            )
+            .map_err(|e| e.to_string())?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?
+            .collect::<Result<_, _>>()
+            .map_err(|e| e.to_string())?;
+        if titles.is_empty() {
+            return Ok(());
+        }
+
+        tracing::info!(
+            "Running migration: indexing {} mission titles for dedupe",
+            titles.len()
+        );
+        let mut stmt = conn
+            .prepare("UPDATE missions SET normalized_title = ?1 WHERE id = ?2")
+            .map_err(|e| e.to_string())?;
+        for (id, title) in titles {
+            stmt.execute(params![normalized_title_key(&title), id])
+                .map_err(|e| format!("Failed to backfill normalized_title: {}", e))?;
+        }
+        Ok(())
+    }
+
+    /// Migrate the automations table from old schema to new schema.
+    fn migrate_automations_table(conn: &Connection) -> Result<(), String> {
+        // Check if the automations table has the old schema
+        let has_command_name: bool = conn
+            .prepare("SELECT 1 FROM pragma_table_info('automations') WHERE name = 'command_name'")
+            .map_err(|e| format!("Failed to check automations schema: {}", e))?
+            .exists([])
+            .map_err(|e| format!("Failed to query table info: {}", e))?;
+
+        if has_command_name {
+            tracing::info!("Running migration: updating automations table to new schema");
+
+            // Read existing automations
+            let mut stmt = conn
+                .prepare("SELECT id, mission_id, command_name, interval_seconds, active, created_at, last_triggered_at FROM automations")
+                .map_err(|e| format!("Failed to read old automations: {}", e))?;
+
+            let old_automations: Vec<LegacyAutomationRow> = stmt
+                .query_map([], |row| {
+                    Ok((
+                        row.get(0)?,
+                        row.get(1)?,
+                        row.get(2)?,
+                        row.get(3)?,
+                        row.get(4)?,
+                        row.get(5)?,
+                        row.get(6)?,
+                    ))
+                })
+                .map_err(|e| format!("Failed to query old automations: {}", e))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| format!("Failed to collect old automations: {}", e))?;
+
+            // Drop the old table
+            conn.execute("DROP TABLE IF EXISTS automations", [])
+                .map_err(|e| format!("Failed to drop old automations table: {}", e))?;
+
+            // Create the new table
+            conn.execute_batch(
+                "CREATE TABLE automations (
+                    id TEXT PRIMARY KEY NOT NULL,
+                    mission_id TEXT NOT NULL,
+                    command_source_type TEXT NOT NULL,
+                    command_source_data TEXT NOT NULL,
+                    trigger_type TEXT NOT NULL,
+                    trigger_data TEXT NOT NULL,
+                    variables TEXT NOT NULL DEFAULT '{}',
+                    active INTEGER NOT NULL DEFAULT 1,
+                    stop_policy TEXT NOT NULL DEFAULT 'consecutive_failures:2',
+                    created_at TEXT NOT NULL,
+                    last_triggered_at TEXT,
+                    retry_max_retries INTEGER NOT NULL DEFAULT 3,
+                    retry_delay_seconds INTEGER NOT NULL DEFAULT 60,
+                    retry_backoff_multiplier REAL NOT NULL DEFAULT 2.0,
+                    FOREIGN KEY (mission_id) REFERENCES missions(id) ON DELETE CASCADE
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_automations_mission ON automations(mission_id);
+                CREATE INDEX IF NOT EXISTS idx_automations_active ON automations(mission_id, active);
+                CREATE INDEX IF NOT EXISTS idx_automations_webhook_id ON automations(json_extract(trigger_data, '$.webhook_id')) WHERE trigger_type = 'webhook';
+
+                CREATE TABLE IF NOT EXISTS automation_executions (
+                    id TEXT PRIMARY KEY NOT NULL,
+                    automation_id TEXT NOT NULL,
+                    mission_id TEXT NOT NULL,
+                    triggered_at TEXT NOT NULL,
+                    trigger_source TEXT NOT NULL,
+                    status TEXT NOT NULL,
+                    webhook_payload TEXT,
+                    variables_used TEXT NOT NULL DEFAULT '{}',
+                    completed_at TEXT,
+                    error TEXT,
+                    retry_count INTEGER NOT NULL DEFAULT 0,
+                    FOREIGN KEY (automation_id) REFERENCES automations(id) ON DELETE CASCADE,
+                    FOREIGN KEY (mission_id) REFERENCES missions(id) ON DELETE CASCADE
+                );
+
+                CREATE INDEX IF NOT EXISTS idx_executions_automation ON automation_executions(automation_id, triggered_at DESC);
+                CREATE INDEX IF NOT EXISTS idx_executions_mission ON automation_executions(mission_id, triggered_at DESC);
+                CREATE INDEX IF NOT EXISTS idx_executions_status ON automation_executions(status);"
+            )
+            .map_err(|e| format!("Failed to create new automations table: {}", e))?;
+
+            // Migrate old data to new schema
+            let automation_count = old_automations.len();
+            for (
+                id,
+                mission_id,
+                command_name,
+                interval_seconds,
+                active,
+                created_at,
+                last_triggered_at,
+            ) in old_automations
+            {
+                // Convert old format to new format
+                let command_source_data = serde_json::json!({
+                    "name": command_name
+                })
+                .to_string();
+
+                let trigger_data = serde_json::json!({
+                    "seconds": interval_seconds
+                })
+                .to_string();
+
+                conn.execute(
+                    "INSERT INTO automations (id, mission_id, command_source_type, command_source_data,
+                                             trigger_type, trigger_data, variables, active, stop_policy,
+                                             fresh_session, created_at, last_triggered_at, retry_max_retries,
+                                             retry_delay_seconds, retry_backoff_multiplier)
+                     VALUES (?, ?, 'library', ?, 'interval', ?, '{}', ?, 'consecutive_failures:2', 'keep', ?, ?, 3, 60, 2.0)",
+                    params![id, mission_id, command_source_data, trigger_data, active, created_at, last_triggered_at],
+                )
+                .map_err(|e| format!("Failed to migrate automation: {}", e))?;
+            }
+
+            tracing::info!(
+                "Successfully migrated {} automations to new schema",
+                automation_count
+            );
+        } else {
+            // Check if automation_executions table exists
+            let has_executions_table: bool = conn
+                .prepare("SELECT 1 FROM sqlite_master WHERE type='table' AND name='automation_executions'")
+                .map_err(|e| format!("Failed to check for automation_executions table: {}", e))?
+                .exists([])
+                .map_err(|e| format!("Failed to query sqlite_master: {}", e))?;
+
+            if !has_executions_table {
+                tracing::info!("Creating automation_executions table");
+                conn.execute_batch(
+                    "CREATE TABLE IF NOT EXISTS automation_executions (
+                        id TEXT PRIMARY KEY NOT NULL,
+                        automation_id TEXT NOT NULL,
+                        mission_id TEXT NOT NULL,
+                        triggered_at TEXT NOT NULL,
+                        trigger_source TEXT NOT NULL,
+                        status TEXT NOT NULL,
+                        webhook_payload TEXT,
+                        variables_used TEXT NOT NULL DEFAULT '{}',
+                        completed_at TEXT,
+                        error TEXT,
+                        retry_count INTEGER NOT NULL DEFAULT 0,
+                        FOREIGN KEY (automation_id) REFERENCES automations(id) ON DELETE CASCADE,
+                        FOREIGN KEY (mission_id) REFERENCES missions(id) ON DELETE CASCADE
+                    );
+
+                    CREATE INDEX IF NOT EXISTS idx_executions_automation ON automation_executions(automation_id, triggered_at DESC);
+                    CREATE INDEX IF NOT EXISTS idx_executions_mission ON automation_executions(mission_id, triggered_at DESC);
+                    CREATE INDEX IF NOT EXISTS idx_executions_status ON automation_executions(status);"
+                )
+                .map_err(|e| format!("Failed to create automation_executions table: {}", e))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn ensure_automation_indexes(conn: &Connection) -> Result<(), String> {
+        let has_trigger_data: bool = conn
+            .prepare("SELECT 1 FROM pragma_table_info('automations') WHERE name = 'trigger_data'")
+            .map_err(|e| format!("Failed to check automations columns: {}", e))?
+            .exists([])
+            .map_err(|e| format!("Failed to query table info: {}", e))?;
+
+        if has_trigger_data {
+            conn.execute(
+                "CREATE INDEX IF NOT EXISTS idx_automations_webhook_id ON automations(json_extract(trigger_data, '$.webhook_id')) WHERE trigger_type = 'webhook'",
+                [],
+            )
+            .map_err(|e| format!("Failed to create automation webhook index: {}", e))?;
+        }
+
+        let has_stop_policy: bool = conn
+            .prepare("SELECT 1 FROM pragma_table_info('automations') WHERE name = 'stop_policy'")
+            .map_err(|e| format!("Failed to check stop_policy column: {}", e))?
+            .exists([])
+            .map_err(|e| format!("Failed to query table info: {}", e))?;
+        if !has_stop_policy {
+            tracing::info!("Running migration: adding 'stop_policy' column to automations table");
+            conn.execute(
+                "ALTER TABLE automations ADD COLUMN stop_policy TEXT NOT NULL DEFAULT 'consecutive_failures:2'",
+                [],
+            )
+            .map_err(|e| format!("Failed to add stop_policy column: {}", e))?;
+        }
+
+        // Migration: add fresh_session column if it doesn't exist
+        let has_fresh_session: bool = conn
+            .query_row(
+                "SELECT 1 FROM pragma_table_info('automations') WHERE name = 'fresh_session'",
+                [],
+                |_| Ok(true),
+            )
+            .unwrap_or(false);
+        if !has_fresh_session {
+            tracing::info!("Running migration: adding 'fresh_session' column to automations table");
+            conn.execute(
+                "ALTER TABLE automations ADD COLUMN fresh_session TEXT NOT NULL DEFAULT 'keep'",
+                [],
+            )
+            .map_err(|e| format!("Failed to add fresh_session column: {}", e))?;
+        }
+
+        // Migration: add mission_retry_policy column if it doesn't exist
+        let has_mission_retry_policy: bool = conn
+            .query_row(
+                "SELECT 1 FROM pragma_table_info('automations') WHERE name = 'mission_retry_policy'",
+                [],
+                |_| Ok(true),
+            )
+            .unwrap_or(false);
+        if !has_mission_retry_policy {
+            tracing::info!(
+                "Running migration: adding 'mission_retry_policy' column to automations table"
+            );
+            conn.execute(
+                "ALTER TABLE automations ADD COLUMN mission_retry_policy TEXT",
+                [],
+            )
+            .map_err(|e| format!("Failed to add mission_retry_policy column: {}", e))?;
+        }
+
+        // Migration: add blackout_windows column if it doesn't exist
+        let has_blackout_windows: bool = conn
+            .query_row(
+                "SELECT 1 FROM pragma_table_info('automations') WHERE name = 'blackout_windows'",
+                [],
+                |_| Ok(true),
+            )
+            .unwrap_or(false);
+        if !has_blackout_windows {
+            tracing::info!(
+                "Running migration: adding 'blackout_windows' column to automations table"
+            );
+            conn.execute(
+                "ALTER TABLE automations ADD COLUMN blackout_windows TEXT",
+                [],
+            )
+            .map_err(|e| format!("Failed to add blackout_windows column: {}", e))?;
+        }
+
+        // Migration: add first_event_seq column if it doesn't exist
+        let has_first_event_seq: bool = conn
+            .query_row(
+                "SELECT 1 FROM pragma_table_info('automation_executions') WHERE name = 'first_event_seq'",
+                [],
+                |_| Ok(true),
+            )
+            .unwrap_or(false);
+        if !has_first_event_seq {
+            tracing::info!(
+                "Running migration: adding 'first_event_seq' column to automation_executions table"
+            );
+            conn.execute(
+                "ALTER TABLE automation_executions ADD COLUMN first_event_seq INTEGER",
+                [],
+            )
+            .map_err(|e| format!("Failed to add first_event_seq column: {}", e))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Columns selected for `mission_from_row`, in index order.
+const MISSION_COLUMNS: &str = "id, status, title, short_description, metadata_updated_at, metadata_source, metadata_model, metadata_version, workspace_id, workspace_name, agent, model_override,
+    model_effort,
+    created_at, updated_at, interrupted_at, resumable, desktop_sessions,
+    COALESCE(backend, 'opencode') as backend, session_id, terminal_reason,
+    config_profile, agent_version, tags, pinned, archived, dry_run, env_profile, assignee, watchers,
+    checklist, follow_ups";
+
+/// Last mission event of an execution (aliased `e`): the event before the
+/// next user message after its trigger message, or the latest event while it
+/// is still the last turn.
+const EXECUTION_LAST_EVENT_SEQ: &str = "CASE WHEN first_event_seq IS NULL THEN NULL ELSE COALESCE(
+        (SELECT MIN(b.sequence) - 1 FROM mission_events b
+         WHERE b.mission_id = e.mission_id AND b.event_type = 'user_message'
+           AND b.sequence > (SELECT MIN(t.sequence) FROM mission_events t
+                             WHERE t.mission_id = e.mission_id AND t.event_type = 'user_message'
+                               AND t.sequence >= e.first_event_seq)),
+        (SELECT MAX(m.sequence) FROM mission_events m
+         WHERE m.mission_id = e.mission_id AND m.sequence >= e.first_event_seq))
+    END";
+
+const SCHEDULED_MESSAGE_COLUMNS: &str =
+    "id, mission_id, content, agent, send_at, created_at, status, sent_at, error";
+
+/// Map a row selected with [`SCHEDULED_MESSAGE_COLUMNS`] to a scheduled message.
+fn scheduled_message_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ScheduledMessage> {
+    let parse_uuid = |idx: usize| -> rusqlite::Result<Uuid> {
+        let value: String = row.get(idx)?;
+        Uuid::parse_str(&value).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+    };
+    let status: String = row.get(6)?;
+    Ok(ScheduledMessage {
+        id: parse_uuid(0)?,
+        mission_id: parse_uuid(1)?,
+        content: row.get(2)?,
+        agent: row.get(3)?,
+        send_at: row.get(4)?,
+        created_at: row.get(5)?,
+        status: ScheduledMessageStatus::parse(&status),
+        sent_at: row.get(7)?,
+        error: row.get(8)?,
+    })
+}
+
+const MISSION_RETRY_COLUMNS: &str =
+    "id, mission_id, attempt, terminal_reason, due_at, created_at, status, started_at, error";
+
+/// Map a row selected with [`MISSION_RETRY_COLUMNS`] to a mission retry.
+fn mission_retry_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<MissionRetry> {
+    let parse_uuid = |idx: usize| -> rusqlite::Result<Uuid> {
+        let value: String = row.get(idx)?;
+        Uuid::parse_str(&value).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+    };
+    let attempt: i64 = row.get(2)?;
+    let status: String = row.get(6)?;
+    Ok(MissionRetry {
+        id: parse_uuid(0)?,
+        mission_id: parse_uuid(1)?,
+        attempt: attempt as u32,
+        terminal_reason: row.get(3)?,
+        due_at: row.get(4)?,
+        created_at: row.get(5)?,
+        status: MissionRetryStatus::parse(&status),
+        started_at: row.get(7)?,
+        error: row.get(8)?,
+    })
+}
+
+const MISSION_COMMENT_COLUMNS: &str =
+    "id, mission_id, thread_id, author, body, mentions, created_at, updated_at";
+
+/// Map a row selected with [`MISSION_COMMENT_COLUMNS`] to a comment (body
+/// still sealed).
+fn mission_comment_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<MissionComment> {
+    let parse_uuid = |idx: usize| -> rusqlite::Result<Uuid> {
+        let value: String = row.get(idx)?;
+        Uuid::parse_str(&value).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+    };
+    Ok(MissionComment {
+        id: parse_uuid(0)?,
+        mission_id: parse_uuid(1)?,
+        thread_id: parse_uuid(2)?,
+        author: row.get(3)?,
+        body: row.get(4)?,
+        mentions: parse_tags(row.get(5)?),
+        created_at: row.get(6)?,
+        updated_at: row.get(7)?,
+    })
+}
+
+/// `WHERE` clause for [`MissionFilter`], bound by [`mission_filter_params`].
+const MISSION_FILTER_WHERE: &str = "(?1 IS NULL OR status = ?1)
+    AND (?2 IS NULL OR pinned = ?2)
+    AND (?3 IS NULL OR archived = ?3)
+    AND NOT EXISTS (
+        SELECT 1 FROM json_each(?4) AS wanted
+        WHERE wanted.value NOT IN (
+            SELECT value FROM json_each(COALESCE(missions.tags, '[]'))
+        )
+    )
+    AND (?5 IS NULL OR updated_at < ?5 OR (updated_at = ?5 AND id < ?6))
+    AND (?7 IS NULL OR COALESCE(assignee, '') = ?7)";
+
+type MissionFilterParams = (
+    Option<&'static str>,
+    Option<bool>,
+    Option<bool>,
+    String,
+    Option<String>,
+    Option<String>,
+    Option<String>,
+);
+
+fn mission_filter_params(filter: &MissionFilter) -> Result<MissionFilterParams, String> {
+    Ok((
+        filter.status.map(status_to_string),
+        filter.pinned,
+        filter.archived,
+        serde_json::to_string(&filter.tags).map_err(|e| e.to_string())?,
+        filter.before.as_ref().map(|bound| bound.sort_key.clone()),
+        filter.before.as_ref().map(|bound| bound.id.to_string()),
+        filter.assignee.clone(),
+    ))
+}
+
+/// Map a row selected with [`MISSION_COLUMNS`] to a mission (history not loaded).
+fn mission_from_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<Mission> {
+    let id_str: String = row.get(0)?;
+    let status_str: String = row.get(1)?;
+    let workspace_id_str: String = row.get(8)?;
+    let desktop_sessions_json: Option<String> = row.get(17)?;
+    let backend: String = row.get(18)?;
+    let session_id: Option<String> = row.get(19)?;
+    let terminal_reason: Option<String> = row.get(20)?;
+    let error_code = terminal_reason
+        .as_deref()
+        .and_then(ErrorCode::for_terminal_reason);
+    let config_profile: Option<String> = row.get(21)?;
+
+    Ok(Mission {
+        id: parse_uuid_or_nil(&id_str),
+        status: parse_status(&status_str),
+        title: row.get(2)?,
+        short_description: row.get(3)?,
+        metadata_updated_at: row.get(4)?,
+        metadata_source: row.get(5)?,
+        metadata_model: row.get(6)?,
+        metadata_version: row.get(7)?,
+        workspace_id: Uuid::parse_str(&workspace_id_str)
+            .unwrap_or(crate::workspace::DEFAULT_WORKSPACE_ID),
+        workspace_name: row.get(9)?,
+        agent: row.get(10)?,
+        agent_version: row.get(22)?,
+        model_override: row.get(11)?,
+        model_effort: row.get(12)?,
+        backend,
+        config_profile,
+        history: vec![], // Loaded separately if needed
+        created_at: row.get(13)?,
+        updated_at: row.get(14)?,
+        interrupted_at: row.get(15)?,
+        resumable: row.get::<_, i32>(16)? != 0,
+        desktop_sessions: desktop_sessions_json
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default(),
+        session_id,
+        terminal_reason,
+        error_code,
+        tags: parse_tags(row.get(23)?),
+        pinned: row.get::<_, i32>(24)? != 0,
+        archived: row.get::<_, i32>(25)? != 0,
+        dry_run: row.get::<_, i32>(26)? != 0,
+        env_profile: row.get(27)?,
+        assignee: row.get(28)?,
+        watchers: parse_tags(row.get(29)?),
+        checklist: row
+            .get::<_, Option<String>>(30)?
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default(),
+        follow_ups: row
+            .get::<_, Option<String>>(31)?
+            .and_then(|s| serde_json::from_str(&s).ok())
+            .unwrap_or_default(),
+        unread_count: None,
+    })
+}
+
+fn parse_tags(json: Option<String>) -> Vec<String> {
+    json.and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn parse_status(s: &str) -> MissionStatus {
+    match s {
+        "pending" => MissionStatus::Pending,
+        "active" => MissionStatus::Active,
+        "completed" => MissionStatus::Completed,
+        "failed" => MissionStatus::Failed,
+        "interrupted" => MissionStatus::Interrupted,
+        "blocked" => MissionStatus::Blocked,
+        "not_feasible" => MissionStatus::NotFeasible,
+        _ => MissionStatus::Pending,
+    }
+}
+
+fn status_to_string(status: MissionStatus) -> &'static str {
+    match status {
+        MissionStatus::Pending => "pending",
+        MissionStatus::Active => "active",
+        MissionStatus::Completed => "completed",
+        MissionStatus::Failed => "failed",
+        MissionStatus::Interrupted => "interrupted",
+        MissionStatus::Blocked => "blocked",
+        MissionStatus::NotFeasible => "not_feasible",
+    }
+}
+
+#[async_trait]
+impl MissionStore for SqliteMissionStore {
+    fn is_persistent(&self) -> bool {
+        true
+    }
+
+    async fn list_missions(&self, limit: usize, offset: usize) -> Result<Vec<Mission>, String> {
+        let conn = self.conn.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT {} FROM missions
+                         ORDER BY updated_at DESC, id DESC
+                         LIMIT ?1 OFFSET ?2",
+                    MISSION_COLUMNS
+                ))
+                .map_err(|e| e.to_string())?;
+
+            let missions = stmt
+                .query_map(params![limit as i64, offset as i64], mission_from_row)
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+
+            Ok(missions)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    async fn find_missions_by_normalized_title(&self, key: &str) -> Result<Vec<Mission>, String> {
+        let conn = self.conn.clone();
+        let key = key.to_string();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT {} FROM missions WHERE normalized_title = ?1",
+                    MISSION_COLUMNS
+                ))
+                .map_err(|e| e.to_string())?;
+
+            let missions = stmt
+                .query_map(params![key], mission_from_row)
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+
+            Ok(missions)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    async fn list_missions_filtered(
+        &self,
+        filter: &MissionFilter,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<Mission>, String> {
+        let conn = self.conn.clone();
+        let (status, pinned, archived, tags_json, before_key, before_id, assignee) =
+            mission_filter_params(filter)?;
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT {} FROM missions
+                     WHERE {}
+                     ORDER BY updated_at DESC, id DESC
+                     LIMIT ?8 OFFSET ?9",
+                    MISSION_COLUMNS, MISSION_FILTER_WHERE
+                ))
+                .map_err(|e| e.to_string())?;
+
+            let missions = stmt
+                .query_map(
+                    params![
+                        status,
+                        pinned,
+                        archived,
+                        tags_json,
+                        before_key,
+                        before_id,
+                        assignee,
+                        limit as i64,
+                        offset as i64
+                    ],
+                    mission_from_row,
+                )
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+
+            Ok(missions)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    async fn count_missions_filtered(&self, filter: &MissionFilter) -> Result<usize, String> {
+        let conn = self.conn.clone();
+        let (status, pinned, archived, tags_json, before_key, before_id, assignee) =
+            mission_filter_params(filter)?;
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let count: i64 = conn
+                .query_row(
+                    &format!(
+                        "SELECT COUNT(*) FROM missions WHERE {}",
+                        MISSION_FILTER_WHERE
+                    ),
+                    params![status, pinned, archived, tags_json, before_key, before_id, assignee],
+                    |row| row.get(0),
+                )
+                .map_err(|e| e.to_string())?;
+            Ok(count as usize)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    async fn get_mission(&self, id: Uuid) -> Result<Option<Mission>, String> {
+        let conn = self.conn.clone();
+        let cipher = self.cipher.clone();
+        let id_str = id.to_string();
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+
+            // Get mission
+            let mut stmt = conn
+                .prepare(
+                    &format!("SELECT {} FROM missions WHERE id = ?1", MISSION_COLUMNS),
+                )
+                .map_err(|e| e.to_string())?;
+
+            let mission: Option<Mission> = stmt
+                .query_row(params![&id_str], mission_from_row)
+                .optional()
+                .map_err(|e| e.to_string())?;
+
+            // Load history from events (limited to the last 400 entries for performance).
+            // Tool results become compacted `tool` entries alongside their call's arguments.
+            // Full history can be retrieved via get_events() if needed
+            if let Some(mut m) = mission {
+                let mut history_stmt = conn
+                    .prepare(
+                        "SELECT event_type, content, content_file, tool_name, args, args_file FROM (
+                             SELECT e.event_type, e.content, e.content_file, e.tool_name, e.sequence,
+                                    c.content AS args, c.content_file AS args_file
+                             FROM mission_events e
+                             LEFT JOIN mission_events c ON c.id = (
+                                 SELECT id FROM mission_events
+                                 WHERE mission_id = e.mission_id
+                                   AND tool_call_id = e.tool_call_id
+                                   AND event_type = 'tool_call'
+                                 LIMIT 1
+                             ) AND e.event_type = 'tool_result'
+                             WHERE e.mission_id = ?1
+                               AND e.event_type IN ('user_message', 'assistant_message', 'user_question_answered', 'tool_result')
+                             ORDER BY e.sequence DESC
+                             LIMIT 400
+                         ) ORDER BY sequence ASC",
+                    )
+                    .map_err(|e| e.to_string())?;
+
+                let history: Vec<MissionHistoryEntry> = history_stmt
+                    .query_map(params![&id_str], |row| {
+                        let event_type: String = row.get(0)?;
+                        let content: Option<String> = row.get(1)?;
+                        let content_file: Option<String> = row.get(2)?;
+                        let full_content = SqliteMissionStore::load_content(
+                            cipher.as_deref(),
+                            content.as_deref(),
+                            content_file.as_deref(),
+                        );
+                        if event_type == "tool_result" {
+                            let tool_name: Option<String> = row.get(3)?;
+                            let args: Option<String> = row.get(4)?;
+                            let args_file: Option<String> = row.get(5)?;
+                            let args = (args.is_some() || args_file.is_some()).then(|| {
+                                SqliteMissionStore::load_content(
+                                    cipher.as_deref(),
+                                    args.as_deref(),
+                                    args_file.as_deref(),
+                                )
+                            });
+                            return Ok(MissionHistoryEntry::tool(
+                                tool_name.as_deref().unwrap_or("tool"),
+                                args.as_deref(),
+                                &full_content,
+                            ));
+                        }
+                        Ok(MissionHistoryEntry {
+                            role: if event_type == "assistant_message" {
+                                "assistant".to_string()
+                            } else {
+                                "user".to_string()
+                            },
+                            content: full_content,
+                            tool_name: None,
+                        })
+                    })
+                    .map_err(|e| e.to_string())?
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| e.to_string())?;
+
+                m.history = history;
+                Ok(Some(m))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    async fn create_mission(
+        &self,
+        title: Option<&str>,
+        workspace_id: Option<Uuid>,
+        agent: Option<&str>,
+        model_override: Option<&str>,
+        model_effort: Option<&str>,
+        backend: Option<&str>,
+        config_profile: Option<&str>,
+    ) -> Result<Mission, String> {
+        let conn = self.conn.clone();
+        let now = now_string();
+        let id = Uuid::new_v4();
+        let workspace_id = workspace_id.unwrap_or(crate::workspace::DEFAULT_WORKSPACE_ID);
+        let backend = backend.unwrap_or("claudecode").to_string();
+        let metadata_source = title.and_then(|value| {
+            let trimmed = value.trim();
+            if trimmed.is_empty() {
+                None
+            } else {
+                Some(METADATA_SOURCE_USER.to_string())
+            }
+        });
+        let metadata_updated_at = metadata_source.as_ref().map(|_| now.clone());
+        // Generate session_id for conversation persistence (used by Claude Code --session-id)
+        let session_id = Uuid::new_v4().to_string();
+
+        let mission = Mission {
+            id,
+            status: MissionStatus::Pending,
+            title: title.map(|s| s.to_string()),
+            short_description: None,
+            metadata_updated_at,
+            metadata_source,
+            metadata_model: None,
+            metadata_version: None,
+            workspace_id,
+            workspace_name: None,
+            agent: agent.map(|s| s.to_string()),
+            agent_version: None,
+            model_override: model_override.map(|s| s.to_string()),
+            model_effort: model_effort.map(|s| s.to_string()),
+            backend: backend.clone(),
+            config_profile: config_profile.map(|s| s.to_string()),
+            history: vec![],
+            created_at: now.clone(),
+            updated_at: now.clone(),
+            interrupted_at: None,
+            resumable: false,
+            desktop_sessions: Vec::new(),
+            session_id: Some(session_id.clone()),
+            terminal_reason: None,
+            error_code: None,
+            tags: Vec::new(),
+            pinned: false,
+            archived: false,
+            dry_run: false,
+            env_profile: None,
+            assignee: None,
+            watchers: Vec::new(),
+            checklist: Vec::new(),
+            follow_ups: Vec::new(),
+            unread_count: None,
+        };
+
+        let m = mission.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            conn.execute(
+                "INSERT INTO missions (id, status, title, short_description, metadata_updated_at, metadata_source, metadata_model, metadata_version, workspace_id, agent, model_override, model_effort, backend, config_profile, created_at, updated_at, resumable, session_id, normalized_title)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18, ?19)",
+                params![
+                    m.id.to_string(),
+                    status_to_string(m.status),
+                    m.title,
+                    m.short_description,
+                    m.metadata_updated_at,
+                    m.metadata_source,
+                    m.metadata_model,
+                    m.metadata_version,
+                    m.workspace_id.to_string(),
+                    m.agent,
+                    m.model_override,
+                    m.model_effort,
+                    m.backend,
+                    m.config_profile,
+                    m.created_at,
+                    m.updated_at,
+                    0,
+                    m.session_id,
+                    m.title.as_deref().map(normalized_title_key),
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok::<_, String>(())
+        })
+        .await
+        .map_err(|e| e.to_string())??;
+
+        Ok(mission)
+    }
+
+    async fn update_mission_status(&self, id: Uuid, status: MissionStatus) -> Result<(), String> {
+        self.update_mission_status_with_reason(id, status, None)
+            .await
+    }
+
+    async fn update_mission_status_with_reason(
+        &self,
+        id: Uuid,
+        status: MissionStatus,
+        terminal_reason: Option<&str>,
+    ) -> Result<(), String> {
+        let conn = self.conn.clone();
+        let now = now_string();
+        let interrupted_at =
+            if matches!(status, MissionStatus::Interrupted | MissionStatus::Blocked) {
+                Some(now.clone())
+            } else {
+                None
+            };
+        // Failed missions with LlmError are also resumable (transient API errors)
+        let resumable = matches!(
+            status,
+            MissionStatus::Interrupted | MissionStatus::Blocked | MissionStatus::Failed
+        );
+        let terminal_reason = terminal_reason.map(|s| s.to_string());
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            conn.execute(
+                "UPDATE missions SET status = ?1, updated_at = ?2, interrupted_at = ?3, resumable = ?4, terminal_reason = ?5 WHERE id = ?6",
+                params![
+                    status_to_string(status),
+                    now,
+                    interrupted_at,
+                    if resumable { 1 } else { 0 },
+                    terminal_reason,
+                    id.to_string(),
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    async fn update_mission_history(
+        &self,
+        id: Uuid,
+        _history: &[MissionHistoryEntry],
+    ) -> Result<(), String> {
+        // For SQLite store, history is derived from events logged via log_event().
+        // This method only updates the mission's updated_at timestamp.
+        // Events are NOT inserted here to avoid race condition duplicates with the
+        // event logger task that also inserts via log_event().
+        let conn = self.conn.clone();
+        let now = now_string();
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+
+            conn.execute(
+                "UPDATE missions SET updated_at = ?1 WHERE id = ?2",
+                params![&now, id.to_string()],
+            )
+            .map_err(|e| e.to_string())?;
+
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
 
-        if !has_metadata_version_column {
-            tracing::info!("Running migration: adding 'metadata_version' column to missions table");
-            conn.execute("ALTER TABLE missions ADD COLUMN metadata_version TEXT", [])
-                .map_err(|e| format!("Failed to add metadata_version column: {}", e))?;
+    async fn update_mission_desktop_sessions(
+        &self,
+        id: Uuid,
+        sessions: &[DesktopSessionInfo],
+    ) -> Result<(), String> {
+        let conn = self.conn.clone();
+        let now = now_string();
+        let sessions_json = serde_json::to_string(sessions).unwrap_or_else(|_| "[]".to_string());
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            conn.execute(
+                "UPDATE missions SET desktop_sessions = ?1, updated_at = ?2 WHERE id = ?3",
+                params![sessions_json, now, id.to_string()],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    async fn update_mission_title(&self, id: Uuid, title: &str) -> Result<(), String> {
+        let conn = self.conn.clone();
+        let now = now_string();
+        let title = title.to_string();
+        let normalized_title = normalized_title_key(&title);
+        let source = "user".to_string();
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            conn.execute(
+                "UPDATE missions
+                 SET title = ?1,
+                     normalized_title = ?2,
+                     metadata_source = ?3,
+                     metadata_model = NULL,
+                     metadata_version = NULL,
+                     metadata_updated_at = ?4,
+                     updated_at = ?5
+                 WHERE id = ?6",
+                params![
+                    title,
+                    normalized_title,
+                    source,
+                    now.clone(),
+                    now,
+                    id.to_string()
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    async fn update_mission_metadata(
+        &self,
+        id: Uuid,
+        title: Option<Option<&str>>,
+        short_description: Option<Option<&str>>,
+        metadata_source: Option<Option<&str>>,
+        metadata_model: Option<Option<&str>>,
+        metadata_version: Option<Option<&str>>,
+    ) -> Result<(), String> {
+        if title.is_none()
+            && short_description.is_none()
+            && metadata_source.is_none()
+            && metadata_model.is_none()
+            && metadata_version.is_none()
+        {
+            return Ok(());
         }
 
-        // Migrate automations table to new schema
-        Self::migrate_automations_table(conn)?;
-        Self::ensure_automation_indexes(conn)?;
+        let conn = self.conn.clone();
+        let now = now_string();
+        let title_set = title.is_some();
+        let short_description_set = short_description.is_some();
+        let metadata_source_set = metadata_source.is_some();
+        let metadata_model_set = metadata_model.is_some();
+        let metadata_version_set = metadata_version.is_some();
+        let title = title.flatten().map(|s| s.to_string());
+        let normalized_title = title.as_deref().map(normalized_title_key);
+        let short_description = short_description.flatten().map(|s| s.to_string());
+        let metadata_source = metadata_source.flatten().map(|s| s.to_string());
+        let metadata_model = metadata_model.flatten().map(|s| s.to_string());
+        let metadata_version = metadata_version.flatten().map(|s| s.to_string());
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            conn.execute(
+                "UPDATE missions
+                 SET title = CASE WHEN ?1 THEN ?2 ELSE title END,
+                     short_description = CASE WHEN ?3 THEN ?4 ELSE short_description END,
+                     metadata_source = CASE WHEN ?5 THEN ?6 ELSE metadata_source END,
+                     metadata_model = CASE WHEN ?7 THEN ?8 ELSE metadata_model END,
+                     metadata_version = CASE WHEN ?9 THEN ?10 ELSE metadata_version END,
+                     metadata_updated_at = ?11,
+                     updated_at = ?11,
+                     normalized_title = CASE WHEN ?1 THEN ?13 ELSE normalized_title END
+                 WHERE id = ?12",
+                params![
+                    title_set,
+                    title,
+                    short_description_set,
+                    short_description,
+                    metadata_source_set,
+                    metadata_source,
+                    metadata_model_set,
+                    metadata_model,
+                    metadata_version_set,
+                    metadata_version,
+                    now,
+                    id.to_string(),
+                    normalized_title
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    async fn update_mission_session_id(&self, id: Uuid, session_id: &str) -> Result<(), String> {
+        let conn = self.conn.clone();
+        let now = now_string();
+        let session_id = session_id.to_string();
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            conn.execute(
+                "UPDATE missions SET session_id = ?1, updated_at = ?2 WHERE id = ?3",
+                params![session_id, now, id.to_string()],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    async fn update_mission_backend(
+        &self,
+        id: Uuid,
+        backend: &str,
+        model_override: Option<&str>,
+        model_effort: Option<&str>,
+    ) -> Result<(), String> {
+        let conn = self.conn.clone();
+        let now = now_string();
+        let backend = backend.to_string();
+        let model_override = model_override.map(|m| m.to_string());
+        let model_effort = model_effort.map(|e| e.to_string());
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let updated = conn
+                .execute(
+                    "UPDATE missions SET backend = ?1, model_override = ?2, model_effort = ?3, updated_at = ?4 WHERE id = ?5",
+                    params![backend, model_override, model_effort, now, id.to_string()],
+                )
+                .map_err(|e| e.to_string())?;
+            if updated == 0 {
+                return Err(format!("Mission {} not found", id));
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    async fn update_mission_agent_version(
+        &self,
+        id: Uuid,
+        version: Option<&str>,
+    ) -> Result<(), String> {
+        let conn = self.conn.clone();
+        let now = now_string();
+        let version = version.map(|v| v.to_string());
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            conn.execute(
+                "UPDATE missions SET agent_version = ?1, updated_at = ?2 WHERE id = ?3",
+                params![version, now, id.to_string()],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    async fn update_mission_tags(&self, id: Uuid, tags: &[String]) -> Result<(), String> {
+        let conn = self.conn.clone();
+        let now = now_string();
+        let tags_json = serde_json::to_string(tags).map_err(|e| e.to_string())?;
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            conn.execute(
+                "UPDATE missions SET tags = ?1, updated_at = ?2 WHERE id = ?3",
+                params![tags_json, now, id.to_string()],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    async fn update_mission_flags(
+        &self,
+        id: Uuid,
+        pinned: Option<bool>,
+        archived: Option<bool>,
+    ) -> Result<(), String> {
+        let conn = self.conn.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let rows = conn
+                .execute(
+                    "UPDATE missions SET pinned = COALESCE(?1, pinned), archived = COALESCE(?2, archived)
+                     WHERE id = ?3",
+                    params![pinned, archived, id.to_string()],
+                )
+                .map_err(|e| e.to_string())?;
+            if rows == 0 {
+                return Err(format!("Mission {} not found", id));
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    async fn update_mission_assignee(
+        &self,
+        id: Uuid,
+        assignee: Option<&str>,
+    ) -> Result<(), String> {
+        let conn = self.conn.clone();
+        let assignee = assignee.map(str::to_string);
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let rows = conn
+                .execute(
+                    "UPDATE missions SET assignee = ?1 WHERE id = ?2",
+                    params![assignee, id.to_string()],
+                )
+                .map_err(|e| e.to_string())?;
+            if rows == 0 {
+                return Err(format!("Mission {} not found", id));
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    async fn update_mission_watchers(&self, id: Uuid, watchers: &[String]) -> Result<(), String> {
+        let conn = self.conn.clone();
+        let watchers_json = serde_json::to_string(watchers).map_err(|e| e.to_string())?;
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let rows = conn
+                .execute(
+                    "UPDATE missions SET watchers = ?1 WHERE id = ?2",
+                    params![watchers_json, id.to_string()],
+                )
+                .map_err(|e| e.to_string())?;
+            if rows == 0 {
+                return Err(format!("Mission {} not found", id));
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    async fn update_mission_checklist(
+        &self,
+        id: Uuid,
+        checklist: &[ChecklistItem],
+    ) -> Result<(), String> {
+        let conn = self.conn.clone();
+        let checklist_json = serde_json::to_string(checklist).map_err(|e| e.to_string())?;
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let rows = conn
+                .execute(
+                    "UPDATE missions SET checklist = ?1 WHERE id = ?2",
+                    params![checklist_json, id.to_string()],
+                )
+                .map_err(|e| e.to_string())?;
+            if rows == 0 {
+                return Err(format!("Mission {} not found", id));
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    async fn update_mission_follow_ups(
+        &self,
+        id: Uuid,
+        follow_ups: &[FollowUp],
+    ) -> Result<(), String> {
+        let conn = self.conn.clone();
+        let follow_ups_json = serde_json::to_string(follow_ups).map_err(|e| e.to_string())?;
 
-        Ok(())
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let rows = conn
+                .execute(
+                    "UPDATE missions SET follow_ups = ?1 WHERE id = ?2",
+                    params![follow_ups_json, id.to_string()],
+                )
+                .map_err(|e| e.to_string())?;
+            if rows == 0 {
+                return Err(format!("Mission {} not found", id));
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
     }
 
-    /// Migrate the automations table from old schema to new schema.
-    fn migrate_automations_table(conn: &Connection) -> Result<(), String> {
-        // Check if the automations table has the old schema
-        let has_command_name: bool = conn
-            .prepare("SELECT 1 FROM pragma_table_info('automations') WHERE name = 'command_name'")
-            .map_err(|e| format!("Failed to check automations schema: {}", e))?
-            .exists([])
-            .map_err(|e| format!("Failed to query table info: {}", e))?;
-
-        if has_command_name {
-            tracing::info!("Running migration: updating automations table to new schema");
+    async fn update_mission_dry_run(&self, id: Uuid, dry_run: bool) -> Result<(), String> {
+        let conn = self.conn.clone();
 
-            // Read existing automations
-            let mut stmt = conn
-                .prepare("SELECT id, mission_id, command_name, interval_seconds, active, created_at, last_triggered_at FROM automations")
-                .map_err(|e| format!("Failed to read old automations: {}", e))?;
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            conn.execute(
+                "UPDATE missions SET dry_run = ?1 WHERE id = ?2",
+                params![dry_run, id.to_string()],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
 
-            let old_automations: Vec<LegacyAutomationRow> = stmt
-                .query_map([], |row| {
-                    Ok((
-                        row.get(0)?,
-                        row.get(1)?,
-                        row.get(2)?,
-                        row.get(3)?,
-                        row.get(4)?,
-                        row.get(5)?,
-                        row.get(6)?,
-                    ))
-                })
-                .map_err(|e| format!("Failed to query old automations: {}", e))?
-                .collect::<Result<Vec<_>, _>>()
-                .map_err(|e| format!("Failed to collect old automations: {}", e))?;
+    async fn update_mission_env_profile(
+        &self,
+        id: Uuid,
+        env_profile: Option<&str>,
+    ) -> Result<(), String> {
+        let conn = self.conn.clone();
+        let env_profile = env_profile.map(str::to_string);
 
-            // Drop the old table
-            conn.execute("DROP TABLE IF EXISTS automations", [])
-                .map_err(|e| format!("Failed to drop old automations table: {}", e))?;
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            conn.execute(
+                "UPDATE missions SET env_profile = ?1 WHERE id = ?2",
+                params![env_profile, id.to_string()],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
 
-            // Create the new table
-            conn.execute_batch(
-                "CREATE TABLE automations (
-                    id TEXT PRIMARY KEY NOT NULL,
-                    mission_id TEXT NOT NULL,
-                    command_source_type TEXT NOT NULL,
-                    command_source_data TEXT NOT NULL,
-                    trigger_type TEXT NOT NULL,
-                    trigger_data TEXT NOT NULL,
-                    variables TEXT NOT NULL DEFAULT '{}',
-                    active INTEGER NOT NULL DEFAULT 1,
-                    stop_policy TEXT NOT NULL DEFAULT 'consecutive_failures:2',
-                    created_at TEXT NOT NULL,
-                    last_triggered_at TEXT,
-                    retry_max_retries INTEGER NOT NULL DEFAULT 3,
-                    retry_delay_seconds INTEGER NOT NULL DEFAULT 60,
-                    retry_backoff_multiplier REAL NOT NULL DEFAULT 2.0,
-                    FOREIGN KEY (mission_id) REFERENCES missions(id) ON DELETE CASCADE
-                );
+    async fn update_mission_tree(&self, id: Uuid, tree: &AgentTreeNode) -> Result<(), String> {
+        let conn = self.conn.clone();
+        let now = now_string();
+        let tree_json = serde_json::to_string(tree).map_err(|e| e.to_string())?;
 
-                CREATE INDEX IF NOT EXISTS idx_automations_mission ON automations(mission_id);
-                CREATE INDEX IF NOT EXISTS idx_automations_active ON automations(mission_id, active);
-                CREATE INDEX IF NOT EXISTS idx_automations_webhook_id ON automations(json_extract(trigger_data, '$.webhook_id')) WHERE trigger_type = 'webhook';
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            conn.execute(
+                "INSERT OR REPLACE INTO mission_trees (mission_id, tree_json, updated_at)
+                 VALUES (?1, ?2, ?3)",
+                params![id.to_string(), tree_json, now],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
 
-                CREATE TABLE IF NOT EXISTS automation_executions (
-                    id TEXT PRIMARY KEY NOT NULL,
-                    automation_id TEXT NOT NULL,
-                    mission_id TEXT NOT NULL,
-                    triggered_at TEXT NOT NULL,
-                    trigger_source TEXT NOT NULL,
-                    status TEXT NOT NULL,
-                    webhook_payload TEXT,
-                    variables_used TEXT NOT NULL DEFAULT '{}',
-                    completed_at TEXT,
-                    error TEXT,
-                    retry_count INTEGER NOT NULL DEFAULT 0,
-                    FOREIGN KEY (automation_id) REFERENCES automations(id) ON DELETE CASCADE,
-                    FOREIGN KEY (mission_id) REFERENCES missions(id) ON DELETE CASCADE
-                );
+    async fn get_mission_tree(&self, id: Uuid) -> Result<Option<AgentTreeNode>, String> {
+        let conn = self.conn.clone();
 
-                CREATE INDEX IF NOT EXISTS idx_executions_automation ON automation_executions(automation_id, triggered_at DESC);
-                CREATE INDEX IF NOT EXISTS idx_executions_mission ON automation_executions(mission_id, triggered_at DESC);
-                CREATE INDEX IF NOT EXISTS idx_executions_status ON automation_executions(status);"
-            )
-            .map_err(|e| format!("Failed to create new automations table: {}", e))?;
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let tree_json: Option<String> = conn
+                .query_row(
+                    "SELECT tree_json FROM mission_trees WHERE mission_id = ?1",
+                    params![id.to_string()],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?;
 
-            // Migrate old data to new schema
-            let automation_count = old_automations.len();
-            for (
-                id,
-                mission_id,
-                command_name,
-                interval_seconds,
-                active,
-                created_at,
-                last_triggered_at,
-            ) in old_automations
-            {
-                // Convert old format to new format
-                let command_source_data = serde_json::json!({
-                    "name": command_name
-                })
-                .to_string();
+            if let Some(json) = tree_json {
+                let tree: AgentTreeNode = serde_json::from_str(&json).map_err(|e| e.to_string())?;
+                Ok(Some(tree))
+            } else {
+                Ok(None)
+            }
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
 
-                let trigger_data = serde_json::json!({
-                    "seconds": interval_seconds
-                })
-                .to_string();
+    async fn delete_mission(&self, id: Uuid) -> Result<bool, String> {
+        let conn = self.conn.clone();
+        let mission_content_dir = self.content_dir.join(id.to_string());
 
-                conn.execute(
-                    "INSERT INTO automations (id, mission_id, command_source_type, command_source_data,
-                                             trigger_type, trigger_data, variables, active, stop_policy,
-                                             fresh_session, created_at, last_triggered_at, retry_max_retries,
-                                             retry_delay_seconds, retry_backoff_multiplier)
-                     VALUES (?, ?, 'library', ?, 'interval', ?, '{}', ?, 'consecutive_failures:2', 'keep', ?, ?, 3, 60, 2.0)",
-                    params![id, mission_id, command_source_data, trigger_data, active, created_at, last_triggered_at],
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let rows = conn
+                .execute(
+                    "DELETE FROM missions WHERE id = ?1",
+                    params![id.to_string()],
                 )
-                .map_err(|e| format!("Failed to migrate automation: {}", e))?;
+                .map_err(|e| e.to_string())?;
+            // Large event content spilled to files isn't covered by the cascade.
+            if rows > 0 && mission_content_dir.exists() {
+                if let Err(e) = std::fs::remove_dir_all(&mission_content_dir) {
+                    tracing::warn!("Failed to remove content files for mission {}: {}", id, e);
+                }
             }
+            Ok(rows > 0)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
 
-            tracing::info!(
-                "Successfully migrated {} automations to new schema",
-                automation_count
-            );
-        } else {
-            // Check if automation_executions table exists
-            let has_executions_table: bool = conn
-                .prepare("SELECT 1 FROM sqlite_master WHERE type='table' AND name='automation_executions'")
-                .map_err(|e| format!("Failed to check for automation_executions table: {}", e))?
-                .exists([])
-                .map_err(|e| format!("Failed to query sqlite_master: {}", e))?;
+    async fn delete_empty_untitled_missions_excluding(
+        &self,
+        exclude: &[Uuid],
+    ) -> Result<usize, String> {
+        let conn = self.conn.clone();
+        let exclude_strs: Vec<String> = exclude.iter().map(|id| id.to_string()).collect();
 
-            if !has_executions_table {
-                tracing::info!("Creating automation_executions table");
-                conn.execute_batch(
-                    "CREATE TABLE IF NOT EXISTS automation_executions (
-                        id TEXT PRIMARY KEY NOT NULL,
-                        automation_id TEXT NOT NULL,
-                        mission_id TEXT NOT NULL,
-                        triggered_at TEXT NOT NULL,
-                        trigger_source TEXT NOT NULL,
-                        status TEXT NOT NULL,
-                        webhook_payload TEXT,
-                        variables_used TEXT NOT NULL DEFAULT '{}',
-                        completed_at TEXT,
-                        error TEXT,
-                        retry_count INTEGER NOT NULL DEFAULT 0,
-                        FOREIGN KEY (automation_id) REFERENCES automations(id) ON DELETE CASCADE,
-                        FOREIGN KEY (mission_id) REFERENCES missions(id) ON DELETE CASCADE
-                    );
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
 
-                    CREATE INDEX IF NOT EXISTS idx_executions_automation ON automation_executions(automation_id, triggered_at DESC);
-                    CREATE INDEX IF NOT EXISTS idx_executions_mission ON automation_executions(mission_id, triggered_at DESC);
-                    CREATE INDEX IF NOT EXISTS idx_executions_status ON automation_executions(status);"
+            // Find missions to delete
+            let mut stmt = conn
+                .prepare(
+                    "SELECT m.id FROM missions m
+                     LEFT JOIN mission_events e ON m.id = e.mission_id AND e.event_type IN ('user_message', 'assistant_message')
+                     WHERE m.status = 'active'
+                       AND (m.title IS NULL OR m.title = '' OR m.title = 'Untitled Mission')
+                     GROUP BY m.id
+                     HAVING COUNT(e.id) = 0",
                 )
-                .map_err(|e| format!("Failed to create automation_executions table: {}", e))?;
-            }
-        }
+                .map_err(|e| e.to_string())?;
 
-        Ok(())
-    }
+            let to_delete: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(0))
+                .map_err(|e| e.to_string())?
+                .filter_map(|r| r.ok())
+                .filter(|id| !exclude_strs.contains(id))
+                .collect();
 
-    fn ensure_automation_indexes(conn: &Connection) -> Result<(), String> {
-        let has_trigger_data: bool = conn
-            .prepare("SELECT 1 FROM pragma_table_info('automations') WHERE name = 'trigger_data'")
-            .map_err(|e| format!("Failed to check automations columns: {}", e))?
-            .exists([])
-            .map_err(|e| format!("Failed to query table info: {}", e))?;
+            let count = to_delete.len();
+            for id in to_delete {
+                conn.execute("DELETE FROM missions WHERE id = ?1", params![id])
+                    .ok();
+            }
 
-        if has_trigger_data {
-            conn.execute(
-                "CREATE INDEX IF NOT EXISTS idx_automations_webhook_id ON automations(json_extract(trigger_data, '$.webhook_id')) WHERE trigger_type = 'webhook'",
-                [],
-            )
-            .map_err(|e| format!("Failed to create automation webhook index: {}", e))?;
-        }
+            Ok(count)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
 
-        let has_stop_policy: bool = conn
-            .prepare("SELECT 1 FROM pragma_table_info('automations') WHERE name = 'stop_policy'")
-            .map_err(|e| format!("Failed to check stop_policy column: {}", e))?
-            .exists([])
-            .map_err(|e| format!("Failed to query table info: {}", e))?;
-        if !has_stop_policy {
-            tracing::info!("Running migration: adding 'stop_policy' column to automations table");
-            conn.execute(
-                "ALTER TABLE automations ADD COLUMN stop_policy TEXT NOT NULL DEFAULT 'consecutive_failures:2'",
-                [],
-            )
-            .map_err(|e| format!("Failed to add stop_policy column: {}", e))?;
+    async fn get_stale_active_missions(&self, stale_hours: u64) -> Result<Vec<Mission>, String> {
+        if stale_hours == 0 {
+            return Ok(Vec::new());
         }
 
-        // Migration: add fresh_session column if it doesn't exist
-        let has_fresh_session: bool = conn
-            .query_row(
-                "SELECT 1 FROM pragma_table_info('automations') WHERE name = 'fresh_session'",
-                [],
-                |_| Ok(true),
-            )
-            .unwrap_or(false);
-        if !has_fresh_session {
-            tracing::info!("Running migration: adding 'fresh_session' column to automations table");
-            conn.execute(
-                "ALTER TABLE automations ADD COLUMN fresh_session TEXT NOT NULL DEFAULT 'keep'",
-                [],
-            )
-            .map_err(|e| format!("Failed to add fresh_session column: {}", e))?;
-        }
+        let conn = self.conn.clone();
+        let cutoff = Utc::now() - chrono::Duration::hours(stale_hours as i64);
+        let cutoff_str = cutoff.to_rfc3339();
 
-        Ok(())
-    }
-}
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id, status, title, workspace_id, workspace_name, agent, model_override,
+                            created_at, updated_at, interrupted_at, resumable, desktop_sessions,
+                            COALESCE(backend, 'opencode') as backend
+                     FROM missions
+                     WHERE status = 'active' AND updated_at < ?1",
+                )
+                .map_err(|e| e.to_string())?;
 
-fn parse_status(s: &str) -> MissionStatus {
-    match s {
-        "pending" => MissionStatus::Pending,
-        "active" => MissionStatus::Active,
-        "completed" => MissionStatus::Completed,
-        "failed" => MissionStatus::Failed,
-        "interrupted" => MissionStatus::Interrupted,
-        "blocked" => MissionStatus::Blocked,
-        "not_feasible" => MissionStatus::NotFeasible,
-        _ => MissionStatus::Pending,
-    }
-}
+            let missions = stmt
+                .query_map(params![cutoff_str], |row| {
+                    let id_str: String = row.get(0)?;
+                    let status_str: String = row.get(1)?;
+                    let workspace_id_str: String = row.get(3)?;
+                    let desktop_sessions_json: Option<String> = row.get(11)?;
+                    let backend: String = row.get(12)?;
 
-fn status_to_string(status: MissionStatus) -> &'static str {
-    match status {
-        MissionStatus::Pending => "pending",
-        MissionStatus::Active => "active",
-        MissionStatus::Completed => "completed",
-        MissionStatus::Failed => "failed",
-        MissionStatus::Interrupted => "interrupted",
-        MissionStatus::Blocked => "blocked",
-        MissionStatus::NotFeasible => "not_feasible",
-    }
-}
+                    Ok(Mission {
+                        id: parse_uuid_or_nil(&id_str),
+                        status: parse_status(&status_str),
+                        title: row.get(2)?,
+                        short_description: None,
+                        metadata_updated_at: None,
+                        metadata_source: None,
+                        metadata_model: None,
+                        metadata_version: None,
+                        workspace_id: Uuid::parse_str(&workspace_id_str)
+                            .unwrap_or(crate::workspace::DEFAULT_WORKSPACE_ID),
+                        workspace_name: row.get(4)?,
+                        agent: row.get(5)?,
+                        agent_version: None,
+                        model_override: row.get(6)?,
+                        model_effort: None, // Not needed for stale mission checks
+                        backend,
+                        config_profile: None, // Not needed for stale mission checks
+                        history: vec![],
+                        created_at: row.get(7)?,
+                        updated_at: row.get(8)?,
+                        interrupted_at: row.get(9)?,
+                        resumable: row.get::<_, i32>(10)? != 0,
+                        desktop_sessions: desktop_sessions_json
+                            .and_then(|s| serde_json::from_str(&s).ok())
+                            .unwrap_or_default(),
+                        session_id: None, // Not needed for stale mission checks
+                        terminal_reason: None,
+                        error_code: None,
+                        tags: Vec::new(),
+                        pinned: false,
+                        archived: false,
+                        dry_run: false,
+                        env_profile: None,
+                        assignee: None,
+                        watchers: Vec::new(),
+                        checklist: Vec::new(),
+                        follow_ups: Vec::new(),
+                        unread_count: None,
+                    })
+                })
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
 
-#[async_trait]
-impl MissionStore for SqliteMissionStore {
-    fn is_persistent(&self) -> bool {
-        true
+            Ok(missions)
+        })
+        .await
+        .map_err(|e| e.to_string())?
     }
 
-    async fn list_missions(&self, limit: usize, offset: usize) -> Result<Vec<Mission>, String> {
+    async fn get_all_active_missions(&self) -> Result<Vec<Mission>, String> {
         let conn = self.conn.clone();
+
         tokio::task::spawn_blocking(move || {
             let conn = conn.blocking_lock();
             let mut stmt = conn
                 .prepare(
-                    "SELECT id, status, title, short_description, metadata_updated_at, metadata_source, metadata_model, metadata_version, workspace_id, workspace_name, agent, model_override,
-                            model_effort,
+                    "SELECT id, status, title, workspace_id, workspace_name, agent, model_override,
                             created_at, updated_at, interrupted_at, resumable, desktop_sessions,
-                            COALESCE(backend, 'opencode') as backend, session_id, terminal_reason,
-                            config_profile
+                            COALESCE(backend, 'opencode') as backend
                      FROM missions
-                     ORDER BY updated_at DESC
-                     LIMIT ?1 OFFSET ?2",
+                     WHERE status = 'active'",
                 )
                 .map_err(|e| e.to_string())?;
 
             let missions = stmt
-                .query_map(params![limit as i64, offset as i64], |row| {
+                .query_map(params![], |row| {
                     let id_str: String = row.get(0)?;
                     let status_str: String = row.get(1)?;
-                    let workspace_id_str: String = row.get(8)?;
-                    let desktop_sessions_json: Option<String> = row.get(17)?;
-                    let backend: String = row.get(18)?;
-                    let session_id: Option<String> = row.get(19)?;
-                    let terminal_reason: Option<String> = row.get(20)?;
-                    let config_profile: Option<String> = row.get(21)?;
+                    let workspace_id_str: String = row.get(3)?;
+                    let desktop_sessions_json: Option<String> = row.get(11)?;
+                    let backend: String = row.get(12)?;
 
                     Ok(Mission {
                         id: parse_uuid_or_nil(&id_str),
                         status: parse_status(&status_str),
                         title: row.get(2)?,
-                        short_description: row.get(3)?,
-                        metadata_updated_at: row.get(4)?,
-                        metadata_source: row.get(5)?,
-                        metadata_model: row.get(6)?,
-                        metadata_version: row.get(7)?,
+                        short_description: None,
+                        metadata_updated_at: None,
+                        metadata_source: None,
+                        metadata_model: None,
+                        metadata_version: None,
                         workspace_id: Uuid::parse_str(&workspace_id_str)
                             .unwrap_or(crate::workspace::DEFAULT_WORKSPACE_ID),
-                        workspace_name: row.get(9)?,
-                        agent: row.get(10)?,
-                        model_override: row.get(11)?,
-                        model_effort: row.get(12)?,
+                        workspace_name: row.get(4)?,
+                        agent: row.get(5)?,
+                        agent_version: None,
+                        model_override: row.get(6)?,
+                        model_effort: None, // Not needed for active mission checks
                         backend,
-                        config_profile,
-                        history: vec![], // Loaded separately if needed
-                        created_at: row.get(13)?,
-                        updated_at: row.get(14)?,
-                        interrupted_at: row.get(15)?,
-                        resumable: row.get::<_, i32>(16)? != 0,
+                        config_profile: None, // Not needed for active mission checks
+                        history: vec![],
+                        created_at: row.get(7)?,
+                        updated_at: row.get(8)?,
+                        interrupted_at: row.get(9)?,
+                        resumable: row.get::<_, i32>(10)? != 0,
                         desktop_sessions: desktop_sessions_json
                             .and_then(|s| serde_json::from_str(&s).ok())
                             .unwrap_or_default(),
-                        session_id,
-                        terminal_reason,
+                        session_id: None,
+                        terminal_reason: None,
+                        error_code: None,
+                        tags: Vec::new(),
+                        pinned: false,
+                        archived: false,
+                        dry_run: false,
+                        env_profile: None,
+                        assignee: None,
+                        watchers: Vec::new(),
+                        checklist: Vec::new(),
+                        follow_ups: Vec::new(),
+                        unread_count: None,
                     })
                 })
-                .map_err(|e| e.to_string())?
-                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+
+            Ok(missions)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    async fn insert_mission_summary(
+        &self,
+        mission_id: Uuid,
+        summary: &str,
+        key_files: &[String],
+        success: bool,
+    ) -> Result<(), String> {
+        let conn = self.conn.clone();
+        let now = now_string();
+        let summary = summary.to_string();
+        let key_files_json = serde_json::to_string(key_files).unwrap_or_else(|_| "[]".to_string());
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            conn.execute(
+                "INSERT INTO mission_summaries (mission_id, summary, key_files, success, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+                params![
+                    mission_id.to_string(),
+                    summary,
+                    key_files_json,
+                    if success { 1 } else { 0 },
+                    now,
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    // === Event logging methods ===
+
+    async fn log_event(&self, mission_id: Uuid, event: &AgentEvent) -> Result<(), String> {
+        self.insert_event(mission_id, None, event).await
+    }
+
+    async fn log_stream_event(
+        &self,
+        mission_id: Uuid,
+        stream_seq: u64,
+        event: &AgentEvent,
+    ) -> Result<(), String> {
+        self.insert_event(mission_id, Some(stream_seq), event).await
+    }
+
+    async fn log_stream_events(&self, events: &[StreamEvent]) -> Result<(), String> {
+        let rows: Vec<EventRow> = events
+            .iter()
+            .filter_map(|e| Self::event_row(e.mission_id, Some(e.stream_seq), &e.event))
+            .collect();
+        if rows.is_empty() {
+            return Ok(());
+        }
+        let conn = self.conn.clone();
+        let content_dir = self.content_dir.clone();
+        let cipher = self.cipher.clone();
+        tokio::task::spawn_blocking(move || {
+            let mut conn = conn.blocking_lock();
+            let tx = conn.transaction().map_err(|e| e.to_string())?;
+            let mut next_sequence = HashMap::new();
+            let mut spilled = Vec::new();
+            let written = rows.into_iter().try_for_each(|row| {
+                let file = Self::write_event_row(
+                    &tx,
+                    &content_dir,
+                    cipher.as_deref(),
+                    row,
+                    &mut next_sequence,
+                )?;
+                spilled.extend(file);
+                Ok(())
+            });
+            // A failed batch is rolled back, which would orphan the content
+            // files its rows spilled to.
+            if let Err(e) = written.and_then(|()| tx.commit().map_err(|e| e.to_string())) {
+                remove_content_files(spilled.iter());
+                return Err(e);
+            }
+            Ok(())
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    async fn get_events_after_stream_seq(
+        &self,
+        after_seq: u64,
+        limit: usize,
+    ) -> Result<Vec<StoredEvent>, String> {
+        let conn = self.conn.clone();
+        let cipher = self.cipher.clone();
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id, mission_id, sequence, event_type, timestamp, event_id, tool_call_id, tool_name, content, content_file, metadata, stream_seq, schema_version
+                     FROM mission_events
+                     WHERE stream_seq > ?1
+                     ORDER BY stream_seq ASC
+                     LIMIT ?2",
+                )
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map(params![after_seq as i64, limit as i64], |row| {
+                    SqliteMissionStore::parse_event_row(row, cipher.as_deref())
+                })
                 .map_err(|e| e.to_string())?;
-
-            Ok(missions)
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())
         })
         .await
         .map_err(|e| e.to_string())?
     }
 
-    async fn get_mission(&self, id: Uuid) -> Result<Option<Mission>, String> {
+    async fn get_events(
+        &self,
+        mission_id: Uuid,
+        event_types: Option<&[&str]>,
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Result<Vec<StoredEvent>, String> {
         let conn = self.conn.clone();
-        let id_str = id.to_string();
+        let cipher = self.cipher.clone();
+        let mid = mission_id.to_string();
+        let types: Option<Vec<String>> =
+            event_types.map(|t| t.iter().map(|s| s.to_string()).collect());
+        let limit = limit.unwrap_or(50000) as i64;
+        let offset = offset.unwrap_or(0) as i64;
 
         tokio::task::spawn_blocking(move || {
             let conn = conn.blocking_lock();
 
-            // Get mission
-            let mut stmt = conn
-                .prepare(
-                    "SELECT id, status, title, short_description, metadata_updated_at, metadata_source, metadata_model, metadata_version, workspace_id, workspace_name, agent, model_override,
-                            model_effort,
-                            created_at, updated_at, interrupted_at, resumable, desktop_sessions,
-                            COALESCE(backend, 'opencode') as backend, session_id, terminal_reason,
-                            config_profile
-                     FROM missions WHERE id = ?1",
-                )
-                .map_err(|e| e.to_string())?;
-
-            let mission: Option<Mission> = stmt
-                .query_row(params![&id_str], |row| {
-                    let id_str: String = row.get(0)?;
-                    let status_str: String = row.get(1)?;
-                    let workspace_id_str: String = row.get(8)?;
-                    let desktop_sessions_json: Option<String> = row.get(17)?;
-                    let backend: String = row.get(18)?;
-                    let session_id: Option<String> = row.get(19)?;
-                    let terminal_reason: Option<String> = row.get(20)?;
-                    let config_profile: Option<String> = row.get(21)?;
+            let query = if types.is_some() {
+                "SELECT id, mission_id, sequence, event_type, timestamp, event_id, tool_call_id, tool_name, content, content_file, metadata, stream_seq, schema_version
+                 FROM mission_events
+                 WHERE mission_id = ?1 AND event_type IN (SELECT value FROM json_each(?2))
+                 ORDER BY sequence ASC
+                 LIMIT ?3 OFFSET ?4"
+            } else {
+                "SELECT id, mission_id, sequence, event_type, timestamp, event_id, tool_call_id, tool_name, content, content_file, metadata, stream_seq, schema_version
+                 FROM mission_events
+                 WHERE mission_id = ?1
+                 ORDER BY sequence ASC
+                 LIMIT ?2 OFFSET ?3"
+            };
 
-                    Ok(Mission {
-                        id: parse_uuid_or_nil(&id_str),
-                        status: parse_status(&status_str),
-                        title: row.get(2)?,
-                        short_description: row.get(3)?,
-                        metadata_updated_at: row.get(4)?,
-                        metadata_source: row.get(5)?,
-                        metadata_model: row.get(6)?,
-                        metadata_version: row.get(7)?,
-                        workspace_id: Uuid::parse_str(&workspace_id_str)
-                            .unwrap_or(crate::workspace::DEFAULT_WORKSPACE_ID),
-                        workspace_name: row.get(9)?,
-                        agent: row.get(10)?,
-                        model_override: row.get(11)?,
-                        model_effort: row.get(12)?,
-                        backend,
-                        config_profile,
-                        history: vec![],
-                        created_at: row.get(13)?,
-                        updated_at: row.get(14)?,
-                        interrupted_at: row.get(15)?,
-                        resumable: row.get::<_, i32>(16)? != 0,
-                        desktop_sessions: desktop_sessions_json
-                            .and_then(|s| serde_json::from_str(&s).ok())
-                            .unwrap_or_default(),
-                        session_id,
-                        terminal_reason,
-                    })
-                })
-                .optional()
-                .map_err(|e| e.to_string())?;
 
-            // Load history from events (limited to last 200 messages for performance)
-            // Full history can be retrieved via get_events() if needed
-            if let Some(mut m) = mission {
-                let mut history_stmt = conn
-                    .prepare(
-                        "SELECT event_type, content, content_file FROM (
-                             SELECT event_type, content, content_file, sequence
-                             FROM mission_events
-                             WHERE mission_id = ?1 AND event_type IN ('user_message', 'assistant_message')
-                             ORDER BY sequence DESC
-                             LIMIT 200
-                         ) ORDER BY sequence ASC",
-                    )
+            let events: Vec<StoredEvent> = if let Some(types) = types {
+                let types_json = serde_json::to_string(&types).unwrap_or_else(|_| "[]".to_string());
+                let mut stmt = conn.prepare(query).map_err(|e| e.to_string())?;
+                let rows = stmt.query_map(params![&mid, &types_json, limit, offset], |row| SqliteMissionStore::parse_event_row(row, cipher.as_deref()))
                     .map_err(|e| e.to_string())?;
-
-                let history: Vec<MissionHistoryEntry> = history_stmt
-                    .query_map(params![&id_str], |row| {
-                        let event_type: String = row.get(0)?;
-                        let content: Option<String> = row.get(1)?;
-                        let content_file: Option<String> = row.get(2)?;
-                        let full_content =
-                            SqliteMissionStore::load_content(content.as_deref(), content_file.as_deref());
-                        Ok(MissionHistoryEntry {
-                            role: if event_type == "user_message" {
-                                "user".to_string()
-                            } else {
-                                "assistant".to_string()
-                            },
-                            content: full_content,
-                        })
-                    })
-                    .map_err(|e| e.to_string())?
-                    .collect::<Result<Vec<_>, _>>()
+                let mut result = Vec::new();
+                for row in rows {
+                    result.push(row.map_err(|e| e.to_string())?);
+                }
+                result
+            } else {
+                let mut stmt = conn.prepare(query).map_err(|e| e.to_string())?;
+                let rows = stmt.query_map(params![&mid, limit, offset], |row| SqliteMissionStore::parse_event_row(row, cipher.as_deref()))
                     .map_err(|e| e.to_string())?;
+                let mut result = Vec::new();
+                for row in rows {
+                    result.push(row.map_err(|e| e.to_string())?);
+                }
+                result
+            };
 
-                m.history = history;
-                Ok(Some(m))
-            } else {
-                Ok(None)
-            }
+            Ok(events)
         })
         .await
         .map_err(|e| e.to_string())?
     }
 
-    async fn create_mission(
+    async fn get_recent_events(
         &self,
-        title: Option<&str>,
-        workspace_id: Option<Uuid>,
-        agent: Option<&str>,
-        model_override: Option<&str>,
-        model_effort: Option<&str>,
-        backend: Option<&str>,
-        config_profile: Option<&str>,
-    ) -> Result<Mission, String> {
+        mission_id: Uuid,
+        limit: usize,
+    ) -> Result<Vec<StoredEvent>, String> {
         let conn = self.conn.clone();
-        let now = now_string();
-        let id = Uuid::new_v4();
-        let workspace_id = workspace_id.unwrap_or(crate::workspace::DEFAULT_WORKSPACE_ID);
-        let backend = backend.unwrap_or("claudecode").to_string();
-        let metadata_source = title.and_then(|value| {
-            let trimmed = value.trim();
-            if trimmed.is_empty() {
-                None
-            } else {
-                Some(METADATA_SOURCE_USER.to_string())
-            }
-        });
-        let metadata_updated_at = metadata_source.as_ref().map(|_| now.clone());
-        // Generate session_id for conversation persistence (used by Claude Code --session-id)
-        let session_id = Uuid::new_v4().to_string();
-
-        let mission = Mission {
-            id,
-            status: MissionStatus::Pending,
-            title: title.map(|s| s.to_string()),
-            short_description: None,
-            metadata_updated_at,
-            metadata_source,
-            metadata_model: None,
-            metadata_version: None,
-            workspace_id,
-            workspace_name: None,
-            agent: agent.map(|s| s.to_string()),
-            model_override: model_override.map(|s| s.to_string()),
-            model_effort: model_effort.map(|s| s.to_string()),
-            backend: backend.clone(),
-            config_profile: config_profile.map(|s| s.to_string()),
-            history: vec![],
-            created_at: now.clone(),
-            updated_at: now.clone(),
-            interrupted_at: None,
-            resumable: false,
-            desktop_sessions: Vec::new(),
-            session_id: Some(session_id.clone()),
-            terminal_reason: None,
-        };
+        let cipher = self.cipher.clone();
+        let mid = mission_id.to_string();
+        let limit = limit.min(i64::MAX as usize) as i64;
 
-        let m = mission.clone();
         tokio::task::spawn_blocking(move || {
             let conn = conn.blocking_lock();
-            conn.execute(
-                "INSERT INTO missions (id, status, title, short_description, metadata_updated_at, metadata_source, metadata_model, metadata_version, workspace_id, agent, model_override, model_effort, backend, config_profile, created_at, updated_at, resumable, session_id)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16, ?17, ?18)",
-                params![
-                    m.id.to_string(),
-                    status_to_string(m.status),
-                    m.title,
-                    m.short_description,
-                    m.metadata_updated_at,
-                    m.metadata_source,
-                    m.metadata_model,
-                    m.metadata_version,
-                    m.workspace_id.to_string(),
-                    m.agent,
-                    m.model_override,
-                    m.model_effort,
-                    m.backend,
-                    m.config_profile,
-                    m.created_at,
-                    m.updated_at,
-                    0,
-                    m.session_id,
-                ],
-            )
-            .map_err(|e| e.to_string())?;
-            Ok::<_, String>(())
+            let mut stmt = conn
+                .prepare(
+                    "SELECT * FROM (
+                         SELECT id, mission_id, sequence, event_type, timestamp, event_id, tool_call_id, tool_name, content, content_file, metadata, stream_seq, schema_version
+                         FROM mission_events
+                         WHERE mission_id = ?1
+                         ORDER BY sequence DESC
+                         LIMIT ?2
+                     ) ORDER BY sequence ASC",
+                )
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map(params![&mid, limit], |row| {
+                    SqliteMissionStore::parse_event_row(row, cipher.as_deref())
+                })
+                .map_err(|e| e.to_string())?;
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())
         })
         .await
-        .map_err(|e| e.to_string())??;
-
-        Ok(mission)
-    }
-
-    async fn update_mission_status(&self, id: Uuid, status: MissionStatus) -> Result<(), String> {
-        self.update_mission_status_with_reason(id, status, None)
-            .await
+        .map_err(|e| e.to_string())?
     }
 
-    async fn update_mission_status_with_reason(
+    async fn get_events_excluding(
         &self,
-        id: Uuid,
-        status: MissionStatus,
-        terminal_reason: Option<&str>,
-    ) -> Result<(), String> {
+        mission_id: Uuid,
+        excluded: &[&str],
+        limit: Option<usize>,
+        offset: Option<usize>,
+    ) -> Result<Vec<StoredEvent>, String> {
         let conn = self.conn.clone();
-        let now = now_string();
-        let interrupted_at =
-            if matches!(status, MissionStatus::Interrupted | MissionStatus::Blocked) {
-                Some(now.clone())
-            } else {
-                None
-            };
-        // Failed missions with LlmError are also resumable (transient API errors)
-        let resumable = matches!(
-            status,
-            MissionStatus::Interrupted | MissionStatus::Blocked | MissionStatus::Failed
-        );
-        let terminal_reason = terminal_reason.map(|s| s.to_string());
+        let cipher = self.cipher.clone();
+        let mid = mission_id.to_string();
+        let excluded_json = serde_json::to_string(excluded).unwrap_or_else(|_| "[]".to_string());
+        let limit = limit.unwrap_or(50000) as i64;
+        let offset = offset.unwrap_or(0) as i64;
 
         tokio::task::spawn_blocking(move || {
             let conn = conn.blocking_lock();
-            conn.execute(
-                "UPDATE missions SET status = ?1, updated_at = ?2, interrupted_at = ?3, resumable = ?4, terminal_reason = ?5 WHERE id = ?6",
-                params![
-                    status_to_string(status),
-                    now,
-                    interrupted_at,
-                    if resumable { 1 } else { 0 },
-                    terminal_reason,
-                    id.to_string(),
-                ],
-            )
-            .map_err(|e| e.to_string())?;
-            Ok(())
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id, mission_id, sequence, event_type, timestamp, event_id, tool_call_id, tool_name, content, content_file, metadata, stream_seq, schema_version
+                     FROM mission_events
+                     WHERE mission_id = ?1 AND event_type NOT IN (SELECT value FROM json_each(?2))
+                     ORDER BY sequence ASC
+                     LIMIT ?3 OFFSET ?4",
+                )
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map(params![&mid, &excluded_json, limit, offset], |row| {
+                    SqliteMissionStore::parse_event_row(row, cipher.as_deref())
+                })
+                .map_err(|e| e.to_string())?;
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())
         })
         .await
         .map_err(|e| e.to_string())?
     }
 
-    async fn update_mission_history(
+    async fn get_events_in_range(
         &self,
-        id: Uuid,
-        _history: &[MissionHistoryEntry],
-    ) -> Result<(), String> {
-        // For SQLite store, history is derived from events logged via log_event().
-        // This method only updates the mission's updated_at timestamp.
-        // Events are NOT inserted here to avoid race condition duplicates with the
-        // event logger task that also inserts via log_event().
+        mission_id: Uuid,
+        from_seq: Option<i64>,
+        to_seq: Option<i64>,
+    ) -> Result<Vec<StoredEvent>, String> {
         let conn = self.conn.clone();
-        let now = now_string();
+        let cipher = self.cipher.clone();
+        let mid = mission_id.to_string();
 
         tokio::task::spawn_blocking(move || {
-            let conn = conn.blocking_lock();
-
-            conn.execute(
-                "UPDATE missions SET updated_at = ?1 WHERE id = ?2",
-                params![&now, id.to_string()],
-            )
-            .map_err(|e| e.to_string())?;
-
-            Ok(())
+            let conn = conn.blocking_lock();
+            let mut stmt = conn
+                .prepare(
+                    "SELECT id, mission_id, sequence, event_type, timestamp, event_id, tool_call_id, tool_name, content, content_file, metadata, stream_seq, schema_version
+                     FROM mission_events
+                     WHERE mission_id = ?1
+                       AND (?2 IS NULL OR sequence >= ?2)
+                       AND (?3 IS NULL OR sequence <= ?3)
+                     ORDER BY sequence ASC",
+                )
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map(params![mid, from_seq, to_seq], |row| {
+                    SqliteMissionStore::parse_event_row(row, cipher.as_deref())
+                })
+                .map_err(|e| e.to_string())?;
+            rows.collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())
         })
         .await
         .map_err(|e| e.to_string())?
     }
 
-    async fn update_mission_desktop_sessions(
-        &self,
-        id: Uuid,
-        sessions: &[DesktopSessionInfo],
-    ) -> Result<(), String> {
+    async fn update_mission_report(&self, report: &MissionReport) -> Result<(), String> {
         let conn = self.conn.clone();
+        let mission_id = report.mission_id;
         let now = now_string();
-        let sessions_json = serde_json::to_string(sessions).unwrap_or_else(|_| "[]".to_string());
+        let report_json = serde_json::to_string(report).map_err(|e| e.to_string())?;
 
         tokio::task::spawn_blocking(move || {
             let conn = conn.blocking_lock();
             conn.execute(
-                "UPDATE missions SET desktop_sessions = ?1, updated_at = ?2 WHERE id = ?3",
-                params![sessions_json, now, id.to_string()],
+                "INSERT OR REPLACE INTO mission_reports (mission_id, report_json, updated_at)
+                 VALUES (?1, ?2, ?3)",
+                params![mission_id.to_string(), report_json, now],
             )
             .map_err(|e| e.to_string())?;
             Ok(())
@@ -1211,687 +3502,892 @@ impl MissionStore for SqliteMissionStore {
         .map_err(|e| e.to_string())?
     }
 
-    async fn update_mission_title(&self, id: Uuid, title: &str) -> Result<(), String> {
+    async fn get_mission_report(&self, mission_id: Uuid) -> Result<Option<MissionReport>, String> {
         let conn = self.conn.clone();
-        let now = now_string();
-        let title = title.to_string();
-        let source = "user".to_string();
 
         tokio::task::spawn_blocking(move || {
             let conn = conn.blocking_lock();
-            conn.execute(
-                "UPDATE missions
-                 SET title = ?1,
-                     metadata_source = ?2,
-                     metadata_model = NULL,
-                     metadata_version = NULL,
-                     metadata_updated_at = ?3,
-                     updated_at = ?4
-                 WHERE id = ?5",
-                params![title, source, now.clone(), now, id.to_string()],
-            )
-            .map_err(|e| e.to_string())?;
-            Ok(())
+            let report_json: Option<String> = conn
+                .query_row(
+                    "SELECT report_json FROM mission_reports WHERE mission_id = ?1",
+                    params![mission_id.to_string()],
+                    |row| row.get(0),
+                )
+                .optional()
+                .map_err(|e| e.to_string())?;
+
+            report_json
+                .map(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+                .transpose()
         })
         .await
         .map_err(|e| e.to_string())?
     }
 
-    async fn update_mission_metadata(
+    async fn create_scheduled_message(
         &self,
-        id: Uuid,
-        title: Option<Option<&str>>,
-        short_description: Option<Option<&str>>,
-        metadata_source: Option<Option<&str>>,
-        metadata_model: Option<Option<&str>>,
-        metadata_version: Option<Option<&str>>,
-    ) -> Result<(), String> {
-        if title.is_none()
-            && short_description.is_none()
-            && metadata_source.is_none()
-            && metadata_model.is_none()
-            && metadata_version.is_none()
-        {
-            return Ok(());
-        }
-
+        message: ScheduledMessage,
+    ) -> Result<ScheduledMessage, String> {
         let conn = self.conn.clone();
-        let now = now_string();
-        let title_set = title.is_some();
-        let short_description_set = short_description.is_some();
-        let metadata_source_set = metadata_source.is_some();
-        let metadata_model_set = metadata_model.is_some();
-        let metadata_version_set = metadata_version.is_some();
-        let title = title.flatten().map(|s| s.to_string());
-        let short_description = short_description.flatten().map(|s| s.to_string());
-        let metadata_source = metadata_source.flatten().map(|s| s.to_string());
-        let metadata_model = metadata_model.flatten().map(|s| s.to_string());
-        let metadata_version = metadata_version.flatten().map(|s| s.to_string());
+        let msg = message.clone();
 
         tokio::task::spawn_blocking(move || {
             let conn = conn.blocking_lock();
             conn.execute(
-                "UPDATE missions
-                 SET title = CASE WHEN ?1 THEN ?2 ELSE title END,
-                     short_description = CASE WHEN ?3 THEN ?4 ELSE short_description END,
-                     metadata_source = CASE WHEN ?5 THEN ?6 ELSE metadata_source END,
-                     metadata_model = CASE WHEN ?7 THEN ?8 ELSE metadata_model END,
-                     metadata_version = CASE WHEN ?9 THEN ?10 ELSE metadata_version END,
-                     metadata_updated_at = ?11,
-                     updated_at = ?11
-                 WHERE id = ?12",
+                "INSERT INTO scheduled_messages (id, mission_id, content, agent, send_at,
+                                                 created_at, status, sent_at, error)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
                 params![
-                    title_set,
-                    title,
-                    short_description_set,
-                    short_description,
-                    metadata_source_set,
-                    metadata_source,
-                    metadata_model_set,
-                    metadata_model,
-                    metadata_version_set,
-                    metadata_version,
-                    now,
-                    id.to_string()
+                    msg.id.to_string(),
+                    msg.mission_id.to_string(),
+                    msg.content,
+                    msg.agent,
+                    msg.send_at,
+                    msg.created_at,
+                    msg.status.as_str(),
+                    msg.sent_at,
+                    msg.error,
                 ],
             )
-            .map_err(|e| e.to_string())?;
-            Ok(())
+            .map_err(|e| e.to_string())
         })
         .await
-        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Task join error: {}", e))??;
+
+        Ok(message)
     }
 
-    async fn update_mission_session_id(&self, id: Uuid, session_id: &str) -> Result<(), String> {
+    async fn get_scheduled_message(&self, id: Uuid) -> Result<Option<ScheduledMessage>, String> {
         let conn = self.conn.clone();
-        let now = now_string();
-        let session_id = session_id.to_string();
 
         tokio::task::spawn_blocking(move || {
             let conn = conn.blocking_lock();
-            conn.execute(
-                "UPDATE missions SET session_id = ?1, updated_at = ?2 WHERE id = ?3",
-                params![session_id, now, id.to_string()],
+            conn.query_row(
+                &format!(
+                    "SELECT {} FROM scheduled_messages WHERE id = ?1",
+                    SCHEDULED_MESSAGE_COLUMNS
+                ),
+                params![id.to_string()],
+                scheduled_message_from_row,
             )
-            .map_err(|e| e.to_string())?;
-            Ok(())
+            .optional()
+            .map_err(|e| e.to_string())
         })
         .await
-        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Task join error: {}", e))?
     }
 
-    async fn update_mission_tree(&self, id: Uuid, tree: &AgentTreeNode) -> Result<(), String> {
+    async fn list_scheduled_messages(
+        &self,
+        mission_id: Option<Uuid>,
+        status: Option<ScheduledMessageStatus>,
+    ) -> Result<Vec<ScheduledMessage>, String> {
         let conn = self.conn.clone();
-        let now = now_string();
-        let tree_json = serde_json::to_string(tree).map_err(|e| e.to_string())?;
+        let mission_id = mission_id.map(|id| id.to_string());
+        let status = status.map(ScheduledMessageStatus::as_str);
 
         tokio::task::spawn_blocking(move || {
             let conn = conn.blocking_lock();
-            conn.execute(
-                "INSERT OR REPLACE INTO mission_trees (mission_id, tree_json, updated_at)
-                 VALUES (?1, ?2, ?3)",
-                params![id.to_string(), tree_json, now],
-            )
-            .map_err(|e| e.to_string())?;
-            Ok(())
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT {} FROM scheduled_messages
+                     WHERE (?1 IS NULL OR mission_id = ?1)
+                       AND (?2 IS NULL OR status = ?2)
+                     ORDER BY send_at ASC, created_at ASC",
+                    SCHEDULED_MESSAGE_COLUMNS
+                ))
+                .map_err(|e| e.to_string())?;
+            let messages = stmt
+                .query_map(params![mission_id, status], scheduled_message_from_row)
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            Ok(messages)
         })
         .await
-        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Task join error: {}", e))?
     }
 
-    async fn get_mission_tree(&self, id: Uuid) -> Result<Option<AgentTreeNode>, String> {
+    async fn transition_scheduled_message(
+        &self,
+        id: Uuid,
+        from: ScheduledMessageStatus,
+        to: ScheduledMessageStatus,
+        error: Option<String>,
+    ) -> Result<bool, String> {
         let conn = self.conn.clone();
+        let sent_at = (to == ScheduledMessageStatus::Sent).then(now_string);
 
         tokio::task::spawn_blocking(move || {
             let conn = conn.blocking_lock();
-            let tree_json: Option<String> = conn
-                .query_row(
-                    "SELECT tree_json FROM mission_trees WHERE mission_id = ?1",
-                    params![id.to_string()],
-                    |row| row.get(0),
+            let updated = conn
+                .execute(
+                    "UPDATE scheduled_messages
+                     SET status = ?1, sent_at = COALESCE(?2, sent_at), error = ?3
+                     WHERE id = ?4 AND status = ?5",
+                    params![to.as_str(), sent_at, error, id.to_string(), from.as_str()],
                 )
-                .optional()
                 .map_err(|e| e.to_string())?;
-
-            if let Some(json) = tree_json {
-                let tree: AgentTreeNode = serde_json::from_str(&json).map_err(|e| e.to_string())?;
-                Ok(Some(tree))
-            } else {
-                Ok(None)
-            }
+            Ok(updated > 0)
         })
         .await
-        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Task join error: {}", e))?
     }
 
-    async fn delete_mission(&self, id: Uuid) -> Result<bool, String> {
+    async fn get_mission_retry_policy(
+        &self,
+        mission_id: Uuid,
+    ) -> Result<Option<MissionRetryPolicy>, String> {
         let conn = self.conn.clone();
 
         tokio::task::spawn_blocking(move || {
             let conn = conn.blocking_lock();
-            let rows = conn
-                .execute(
-                    "DELETE FROM missions WHERE id = ?1",
-                    params![id.to_string()],
+            let json: Option<String> = conn
+                .query_row(
+                    "SELECT policy_json FROM mission_retry_policies WHERE mission_id = ?1",
+                    params![mission_id.to_string()],
+                    |row| row.get(0),
                 )
+                .optional()
                 .map_err(|e| e.to_string())?;
-            Ok(rows > 0)
+            json.map(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+                .transpose()
         })
         .await
-        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Task join error: {}", e))?
     }
 
-    async fn delete_empty_untitled_missions_excluding(
+    async fn set_mission_retry_policy(
         &self,
-        exclude: &[Uuid],
-    ) -> Result<usize, String> {
+        mission_id: Uuid,
+        policy: Option<MissionRetryPolicy>,
+    ) -> Result<(), String> {
         let conn = self.conn.clone();
-        let exclude_strs: Vec<String> = exclude.iter().map(|id| id.to_string()).collect();
+        let json = policy
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| e.to_string())?;
+        let now = now_string();
 
         tokio::task::spawn_blocking(move || {
             let conn = conn.blocking_lock();
-
-            // Find missions to delete
-            let mut stmt = conn
-                .prepare(
-                    "SELECT m.id FROM missions m
-                     LEFT JOIN mission_events e ON m.id = e.mission_id AND e.event_type IN ('user_message', 'assistant_message')
-                     WHERE m.status = 'active'
-                       AND (m.title IS NULL OR m.title = '' OR m.title = 'Untitled Mission')
-                     GROUP BY m.id
-                     HAVING COUNT(e.id) = 0",
-                )
-                .map_err(|e| e.to_string())?;
-
-            let to_delete: Vec<String> = stmt
-                .query_map([], |row| row.get::<_, String>(0))
-                .map_err(|e| e.to_string())?
-                .filter_map(|r| r.ok())
-                .filter(|id| !exclude_strs.contains(id))
-                .collect();
-
-            let count = to_delete.len();
-            for id in to_delete {
-                conn.execute("DELETE FROM missions WHERE id = ?1", params![id])
-                    .ok();
+            match json {
+                Some(json) => conn.execute(
+                    "INSERT INTO mission_retry_policies (mission_id, policy_json, updated_at)
+                     VALUES (?1, ?2, ?3)
+                     ON CONFLICT(mission_id) DO UPDATE SET
+                        policy_json = excluded.policy_json,
+                        updated_at = excluded.updated_at",
+                    params![mission_id.to_string(), json, now],
+                ),
+                None => conn.execute(
+                    "DELETE FROM mission_retry_policies WHERE mission_id = ?1",
+                    params![mission_id.to_string()],
+                ),
             }
-
-            Ok(count)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
         })
         .await
-        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Task join error: {}", e))?
     }
 
-    async fn get_stale_active_missions(&self, stale_hours: u64) -> Result<Vec<Mission>, String> {
-        if stale_hours == 0 {
-            return Ok(Vec::new());
-        }
+    async fn create_mission_retry(&self, retry: MissionRetry) -> Result<MissionRetry, String> {
+        let conn = self.conn.clone();
+        let r = retry.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            conn.execute(
+                "INSERT INTO mission_retries (id, mission_id, attempt, terminal_reason, due_at,
+                                              created_at, status, started_at, error)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    r.id.to_string(),
+                    r.mission_id.to_string(),
+                    r.attempt as i64,
+                    r.terminal_reason,
+                    r.due_at,
+                    r.created_at,
+                    r.status.as_str(),
+                    r.started_at,
+                    r.error,
+                ],
+            )
+            .map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))??;
+
+        Ok(retry)
+    }
 
+    async fn list_mission_retries(
+        &self,
+        mission_id: Option<Uuid>,
+        status: Option<MissionRetryStatus>,
+    ) -> Result<Vec<MissionRetry>, String> {
         let conn = self.conn.clone();
-        let cutoff = Utc::now() - chrono::Duration::hours(stale_hours as i64);
-        let cutoff_str = cutoff.to_rfc3339();
+        let mission_id = mission_id.map(|id| id.to_string());
+        let status = status.map(MissionRetryStatus::as_str);
 
         tokio::task::spawn_blocking(move || {
             let conn = conn.blocking_lock();
             let mut stmt = conn
-                .prepare(
-                    "SELECT id, status, title, workspace_id, workspace_name, agent, model_override,
-                            created_at, updated_at, interrupted_at, resumable, desktop_sessions,
-                            COALESCE(backend, 'opencode') as backend
-                     FROM missions
-                     WHERE status = 'active' AND updated_at < ?1",
-                )
+                .prepare(&format!(
+                    "SELECT {} FROM mission_retries
+                     WHERE (?1 IS NULL OR mission_id = ?1)
+                       AND (?2 IS NULL OR status = ?2)
+                     ORDER BY mission_id ASC, created_at ASC, attempt ASC",
+                    MISSION_RETRY_COLUMNS
+                ))
                 .map_err(|e| e.to_string())?;
-
-            let missions = stmt
-                .query_map(params![cutoff_str], |row| {
-                    let id_str: String = row.get(0)?;
-                    let status_str: String = row.get(1)?;
-                    let workspace_id_str: String = row.get(3)?;
-                    let desktop_sessions_json: Option<String> = row.get(11)?;
-                    let backend: String = row.get(12)?;
-
-                    Ok(Mission {
-                        id: parse_uuid_or_nil(&id_str),
-                        status: parse_status(&status_str),
-                        title: row.get(2)?,
-                        short_description: None,
-                        metadata_updated_at: None,
-                        metadata_source: None,
-                        metadata_model: None,
-                        metadata_version: None,
-                        workspace_id: Uuid::parse_str(&workspace_id_str)
-                            .unwrap_or(crate::workspace::DEFAULT_WORKSPACE_ID),
-                        workspace_name: row.get(4)?,
-                        agent: row.get(5)?,
-                        model_override: row.get(6)?,
-                        model_effort: None, // Not needed for stale mission checks
-                        backend,
-                        config_profile: None, // Not needed for stale mission checks
-                        history: vec![],
-                        created_at: row.get(7)?,
-                        updated_at: row.get(8)?,
-                        interrupted_at: row.get(9)?,
-                        resumable: row.get::<_, i32>(10)? != 0,
-                        desktop_sessions: desktop_sessions_json
-                            .and_then(|s| serde_json::from_str(&s).ok())
-                            .unwrap_or_default(),
-                        session_id: None, // Not needed for stale mission checks
-                        terminal_reason: None,
-                    })
-                })
+            let retries = stmt
+                .query_map(params![mission_id, status], mission_retry_from_row)
                 .map_err(|e| e.to_string())?
                 .collect::<Result<Vec<_>, _>>()
                 .map_err(|e| e.to_string())?;
+            Ok(retries)
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+    }
+
+    async fn transition_mission_retry(
+        &self,
+        id: Uuid,
+        from: MissionRetryStatus,
+        to: MissionRetryStatus,
+        error: Option<String>,
+    ) -> Result<bool, String> {
+        let conn = self.conn.clone();
+        let started_at = (to == MissionRetryStatus::Started).then(now_string);
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let updated = conn
+                .execute(
+                    "UPDATE mission_retries
+                     SET status = ?1, started_at = COALESCE(?2, started_at), error = ?3
+                     WHERE id = ?4 AND status = ?5",
+                    params![
+                        to.as_str(),
+                        started_at,
+                        error,
+                        id.to_string(),
+                        from.as_str()
+                    ],
+                )
+                .map_err(|e| e.to_string())?;
+            Ok(updated > 0)
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+    }
 
-            Ok(missions)
+    async fn append_turn_journal(&self, entry: TurnJournalEntry) -> Result<(), String> {
+        let conn = self.conn.clone();
+        let content = crypto::seal(self.cipher.as_deref(), &entry.content);
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            conn.execute(
+                "INSERT INTO turn_journal (mission_id, kind, tool_call_id, tool_name, content, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    entry.mission_id.to_string(),
+                    entry.kind.as_str(),
+                    entry.tool_call_id,
+                    entry.tool_name,
+                    content,
+                    entry.created_at,
+                ],
+            )
+            .map(|_| ())
+            .map_err(|e| e.to_string())
         })
         .await
-        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Task join error: {}", e))?
     }
 
-    async fn get_all_active_missions(&self) -> Result<Vec<Mission>, String> {
+    async fn get_turn_journal(&self, mission_id: Uuid) -> Result<Vec<TurnJournalEntry>, String> {
         let conn = self.conn.clone();
+        let cipher = self.cipher.clone();
 
         tokio::task::spawn_blocking(move || {
             let conn = conn.blocking_lock();
             let mut stmt = conn
                 .prepare(
-                    "SELECT id, status, title, workspace_id, workspace_name, agent, model_override,
-                            created_at, updated_at, interrupted_at, resumable, desktop_sessions,
-                            COALESCE(backend, 'opencode') as backend
-                     FROM missions
-                     WHERE status = 'active'",
+                    "SELECT kind, tool_call_id, tool_name, content, created_at FROM turn_journal
+                     WHERE mission_id = ?1 ORDER BY id ASC",
                 )
                 .map_err(|e| e.to_string())?;
-
-            let missions = stmt
-                .query_map(params![], |row| {
-                    let id_str: String = row.get(0)?;
-                    let status_str: String = row.get(1)?;
-                    let workspace_id_str: String = row.get(3)?;
-                    let desktop_sessions_json: Option<String> = row.get(11)?;
-                    let backend: String = row.get(12)?;
-
-                    Ok(Mission {
-                        id: parse_uuid_or_nil(&id_str),
-                        status: parse_status(&status_str),
-                        title: row.get(2)?,
-                        short_description: None,
-                        metadata_updated_at: None,
-                        metadata_source: None,
-                        metadata_model: None,
-                        metadata_version: None,
-                        workspace_id: Uuid::parse_str(&workspace_id_str)
-                            .unwrap_or(crate::workspace::DEFAULT_WORKSPACE_ID),
-                        workspace_name: row.get(4)?,
-                        agent: row.get(5)?,
-                        model_override: row.get(6)?,
-                        model_effort: None, // Not needed for active mission checks
-                        backend,
-                        config_profile: None, // Not needed for active mission checks
-                        history: vec![],
-                        created_at: row.get(7)?,
-                        updated_at: row.get(8)?,
-                        interrupted_at: row.get(9)?,
-                        resumable: row.get::<_, i32>(10)? != 0,
-                        desktop_sessions: desktop_sessions_json
-                            .and_then(|s| serde_json::from_str(&s).ok())
-                            .unwrap_or_default(),
-                        session_id: None,
-                        terminal_reason: None,
-                    })
+            let rows = stmt
+                .query_map(params![mission_id.to_string()], |row| {
+                    let kind: String = row.get(0)?;
+                    Ok((
+                        kind,
+                        row.get::<_, Option<String>>(1)?,
+                        row.get::<_, Option<String>>(2)?,
+                        row.get::<_, String>(3)?,
+                        row.get::<_, String>(4)?,
+                    ))
                 })
                 .map_err(|e| e.to_string())?
                 .collect::<Result<Vec<_>, _>>()
                 .map_err(|e| e.to_string())?;
-
-            Ok(missions)
+            Ok(rows
+                .into_iter()
+                .filter_map(|(kind, tool_call_id, tool_name, content, created_at)| {
+                    Some(TurnJournalEntry {
+                        mission_id,
+                        kind: TurnJournalKind::parse(&kind)?,
+                        tool_call_id,
+                        tool_name,
+                        content: crypto::open(cipher.as_deref(), &content),
+                        created_at,
+                    })
+                })
+                .collect())
         })
         .await
-        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Task join error: {}", e))?
     }
 
-    async fn insert_mission_summary(
-        &self,
-        mission_id: Uuid,
-        summary: &str,
-        key_files: &[String],
-        success: bool,
-    ) -> Result<(), String> {
+    async fn clear_turn_journal(&self, mission_id: Uuid) -> Result<(), String> {
         let conn = self.conn.clone();
-        let now = now_string();
-        let summary = summary.to_string();
-        let key_files_json = serde_json::to_string(key_files).unwrap_or_else(|_| "[]".to_string());
 
         tokio::task::spawn_blocking(move || {
             let conn = conn.blocking_lock();
             conn.execute(
-                "INSERT INTO mission_summaries (mission_id, summary, key_files, success, created_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5)",
-                params![
-                    mission_id.to_string(),
-                    summary,
-                    key_files_json,
-                    if success { 1 } else { 0 },
-                    now,
-                ],
+                "DELETE FROM turn_journal WHERE mission_id = ?1",
+                params![mission_id.to_string()],
             )
-            .map_err(|e| e.to_string())?;
-            Ok(())
+            .map(|_| ())
+            .map_err(|e| e.to_string())
         })
         .await
-        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Task join error: {}", e))?
     }
 
-    // === Event logging methods ===
-
-    async fn log_event(&self, mission_id: Uuid, event: &AgentEvent) -> Result<(), String> {
+    async fn set_message_feedback(&self, feedback: MessageFeedback) -> Result<(), String> {
         let conn = self.conn.clone();
-        let content_dir = self.content_dir.clone();
-        let now = now_string();
-        let mid = mission_id.to_string();
+        let comment = feedback
+            .comment
+            .as_deref()
+            .map(|c| crypto::seal(self.cipher.as_deref(), c));
 
-        // Extract event data
-        let (event_type, event_id, tool_call_id, tool_name, content, metadata) = match event {
-            AgentEvent::UserMessage {
-                id,
-                content,
-                queued,
-                ..
-            } => (
-                "user_message",
-                Some(id.to_string()),
-                None,
-                None,
-                content.clone(),
-                serde_json::json!({ "queued": queued }),
-            ),
-            AgentEvent::AssistantMessage {
-                id,
-                content,
-                success,
-                cost_cents,
-                cost_source,
-                usage,
-                model,
-                model_normalized,
-                shared_files,
-                resumable,
-                ..
-            } => (
-                "assistant_message",
-                Some(id.to_string()),
-                None,
-                None,
-                content.clone(),
-                assistant_message_metadata(AssistantMessageMetadataInput {
-                    success: *success,
-                    cost_cents: *cost_cents,
-                    cost_source: *cost_source,
-                    usage,
-                    model,
-                    model_normalized,
-                    shared_files,
-                    resumable: *resumable,
-                }),
-            ),
-            AgentEvent::Thinking { content, done, .. } => (
-                "thinking",
-                None,
-                None,
-                None,
-                content.clone(),
-                serde_json::json!({ "done": done }),
-            ),
-            AgentEvent::ToolCall {
-                tool_call_id,
-                name,
-                args,
-                ..
-            } => (
-                "tool_call",
-                None,
-                Some(tool_call_id.clone()),
-                Some(name.clone()),
-                args.to_string(),
-                serde_json::json!({}),
-            ),
-            AgentEvent::ToolResult {
-                tool_call_id,
-                name,
-                result,
-                ..
-            } => (
-                "tool_result",
-                None,
-                Some(tool_call_id.clone()),
-                Some(name.clone()),
-                result.to_string(),
-                serde_json::json!({}),
-            ),
-            AgentEvent::Error {
-                message, resumable, ..
-            } => (
-                "error",
-                None,
-                None,
-                None,
-                message.clone(),
-                serde_json::json!({ "resumable": resumable }),
-            ),
-            AgentEvent::TextDelta { content, .. } => (
-                "text_delta",
-                Some("text_delta_latest".to_string()),
-                None,
-                None,
-                content.clone(),
-                serde_json::json!({}),
-            ),
-            AgentEvent::MissionStatusChanged {
-                status, summary, ..
-            } => (
-                "mission_status_changed",
-                None,
-                None,
-                None,
-                summary.clone().unwrap_or_default(),
-                serde_json::json!({ "status": status.to_string() }),
-            ),
-            AgentEvent::MissionMetadataUpdated {
-                title,
-                short_description,
-                metadata_updated_at,
-                updated_at,
-                metadata_source,
-                metadata_model,
-                metadata_version,
-                ..
-            } => (
-                "mission_metadata_updated",
-                None,
-                None,
-                None,
-                title.clone().unwrap_or_default(),
-                serde_json::json!({
-                    "title": title,
-                    "short_description": short_description,
-                    "metadata_updated_at": metadata_updated_at,
-                    "updated_at": updated_at,
-                    "metadata_source": metadata_source,
-                    "metadata_model": metadata_model,
-                    "metadata_version": metadata_version
-                }),
-            ),
-            // Skip events that are less important for debugging
-            AgentEvent::Status { .. }
-            | AgentEvent::AgentPhase { .. }
-            | AgentEvent::AgentTree { .. }
-            | AgentEvent::Progress { .. }
-            | AgentEvent::SessionIdUpdate { .. }
-            | AgentEvent::MissionActivity { .. }
-            | AgentEvent::MissionTitleChanged { .. } => return Ok(()),
-        };
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            conn.execute(
+                "INSERT OR REPLACE INTO message_feedback
+                 (mission_id, message_id, rating, comment, backend, model, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    feedback.mission_id.to_string(),
+                    feedback.message_id.to_string(),
+                    feedback.rating.as_str(),
+                    comment,
+                    feedback.backend,
+                    feedback.model,
+                    feedback.created_at,
+                ],
+            )
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+    }
 
-        let event_type = event_type.to_string();
-        let metadata_str = metadata.to_string();
+    async fn mark_mission_read(
+        &self,
+        mission_id: Uuid,
+        username: &str,
+        read_at: &str,
+    ) -> Result<(), String> {
+        let conn = self.conn.clone();
+        let mission_id = mission_id.to_string();
+        let username = username.to_string();
+        let read_at = read_at.to_string();
 
         tokio::task::spawn_blocking(move || {
             let conn = conn.blocking_lock();
+            conn.execute(
+                "INSERT INTO mission_reads (mission_id, username, last_read_seq, read_at)
+                 VALUES (?1, ?2,
+                         (SELECT COALESCE(MAX(sequence), 0) FROM mission_events WHERE mission_id = ?1),
+                         ?3)
+                 ON CONFLICT(mission_id, username) DO UPDATE SET
+                     last_read_seq = excluded.last_read_seq,
+                     read_at = excluded.read_at",
+                params![mission_id, username, read_at],
+            )
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+    }
 
-            // If this event has an event_id that already exists for this mission,
-            // update the existing row's metadata instead of inserting a duplicate.
-            // This happens when a queued UserMessage is re-emitted with queued: false.
-            if let Some(ref eid) = event_id {
-                let existing: Option<i64> = conn
-                    .query_row(
-                        "SELECT id FROM mission_events WHERE mission_id = ?1 AND event_id = ?2",
-                        params![&mid, eid],
-                        |row| row.get(0),
-                    )
-                    .optional()
-                    .unwrap_or(None);
+    async fn unread_counts(
+        &self,
+        username: &str,
+        mission_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, u64>, String> {
+        let conn = self.conn.clone();
+        let username = username.to_string();
+        let mission_ids = mission_ids.to_vec();
 
-                if let Some(row_id) = existing {
-                    let (content_inline, content_file) = SqliteMissionStore::store_content(
-                        &content_dir,
-                        mission_id,
-                        row_id,
-                        &event_type,
-                        &content,
-                    );
-                    conn.execute(
-                        "UPDATE mission_events
-                         SET metadata = ?1, timestamp = ?2, content = ?3, content_file = ?4
-                         WHERE id = ?5",
-                        params![metadata_str, now, content_inline, content_file, row_id],
-                    )
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT COUNT(*) FROM mission_events
+                     WHERE mission_id = ?1 AND event_type IN ({})
+                       AND sequence > COALESCE(
+                           (SELECT last_read_seq FROM mission_reads
+                            WHERE mission_id = ?1 AND username = ?2), 0)",
+                    unread_types_sql()
+                ))
+                .map_err(|e| e.to_string())?;
+            let mut counts = HashMap::new();
+            for mission_id in mission_ids {
+                let count: i64 = stmt
+                    .query_row(params![mission_id.to_string(), username], |row| row.get(0))
                     .map_err(|e| e.to_string())?;
-                    return Ok(());
+                if count > 0 {
+                    counts.insert(mission_id, count as u64);
                 }
             }
+            Ok(counts)
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+    }
 
-            // Get next sequence
-            let sequence: i64 = conn
-                .query_row(
-                    "SELECT COALESCE(MAX(sequence), 0) + 1 FROM mission_events WHERE mission_id = ?1",
-                    params![&mid],
-                    |row| row.get(0),
-                )
-                .unwrap_or(1);
+    async fn mission_unread_counts(&self, mission_id: Uuid) -> Result<Vec<(String, u64)>, String> {
+        let conn = self.conn.clone();
+        let mission_id = mission_id.to_string();
 
-            // Store content
-            let (content_inline, content_file) = SqliteMissionStore::store_content(
-                &content_dir,
-                mission_id,
-                sequence,
-                &event_type,
-                &content,
-            );
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT r.username,
+                            (SELECT COUNT(*) FROM mission_events e
+                             WHERE e.mission_id = r.mission_id AND e.event_type IN ({})
+                               AND e.sequence > r.last_read_seq)
+                     FROM mission_reads r
+                     WHERE r.mission_id = ?1
+                     ORDER BY r.username",
+                    unread_types_sql()
+                ))
+                .map_err(|e| e.to_string())?;
+            let counts = stmt
+                .query_map(params![mission_id], |row| {
+                    Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64))
+                })
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            Ok(counts)
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+    }
 
+    async fn create_mission_comment(&self, comment: MissionComment) -> Result<(), String> {
+        let conn = self.conn.clone();
+        let body = crypto::seal(self.cipher.as_deref(), &comment.body);
+        let mentions = serde_json::to_string(&comment.mentions).map_err(|e| e.to_string())?;
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
             conn.execute(
-                "INSERT INTO mission_events
-                 (mission_id, sequence, event_type, timestamp, event_id, tool_call_id, tool_name, content, content_file, metadata)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                &format!(
+                    "INSERT INTO mission_comments ({}) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    MISSION_COMMENT_COLUMNS
+                ),
                 params![
-                    mid,
-                    sequence,
-                    event_type,
-                    now,
-                    event_id,
-                    tool_call_id,
-                    tool_name,
-                    content_inline,
-                    content_file,
-                    metadata_str,
+                    comment.id.to_string(),
+                    comment.mission_id.to_string(),
+                    comment.thread_id.to_string(),
+                    comment.author,
+                    body,
+                    mentions,
+                    comment.created_at,
+                    comment.updated_at,
                 ],
             )
-            .map_err(|e| e.to_string())?;
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+    }
 
-            Ok(())
+    async fn get_mission_comment(&self, id: Uuid) -> Result<Option<MissionComment>, String> {
+        let conn = self.conn.clone();
+        let cipher = self.cipher.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let comment = conn
+                .query_row(
+                    &format!(
+                        "SELECT {} FROM mission_comments WHERE id = ?1",
+                        MISSION_COMMENT_COLUMNS
+                    ),
+                    params![id.to_string()],
+                    mission_comment_from_row,
+                )
+                .optional()
+                .map_err(|e| e.to_string())?;
+            Ok(comment.map(|mut comment| {
+                comment.body = crypto::open(cipher.as_deref(), &comment.body);
+                comment
+            }))
         })
         .await
-        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Task join error: {}", e))?
     }
 
-    async fn get_events(
+    async fn list_mission_comments(&self, mission_id: Uuid) -> Result<Vec<MissionComment>, String> {
+        let conn = self.conn.clone();
+        let cipher = self.cipher.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let mut stmt = conn
+                .prepare(&format!(
+                    "SELECT {} FROM mission_comments WHERE mission_id = ?1
+                     ORDER BY created_at, rowid",
+                    MISSION_COMMENT_COLUMNS
+                ))
+                .map_err(|e| e.to_string())?;
+            let comments = stmt
+                .query_map(params![mission_id.to_string()], mission_comment_from_row)
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            Ok(comments
+                .into_iter()
+                .map(|mut comment| {
+                    comment.body = crypto::open(cipher.as_deref(), &comment.body);
+                    comment
+                })
+                .collect())
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+    }
+
+    async fn update_mission_comment(
         &self,
-        mission_id: Uuid,
-        event_types: Option<&[&str]>,
-        limit: Option<usize>,
-        offset: Option<usize>,
-    ) -> Result<Vec<StoredEvent>, String> {
+        id: Uuid,
+        body: &str,
+        mentions: &[String],
+        updated_at: &str,
+    ) -> Result<bool, String> {
         let conn = self.conn.clone();
-        let mid = mission_id.to_string();
-        let types: Option<Vec<String>> =
-            event_types.map(|t| t.iter().map(|s| s.to_string()).collect());
-        let limit = limit.unwrap_or(50000) as i64;
-        let offset = offset.unwrap_or(0) as i64;
+        let body = crypto::seal(self.cipher.as_deref(), body);
+        let mentions = serde_json::to_string(mentions).map_err(|e| e.to_string())?;
+        let updated_at = updated_at.to_string();
 
         tokio::task::spawn_blocking(move || {
             let conn = conn.blocking_lock();
+            let rows = conn
+                .execute(
+                    "UPDATE mission_comments SET body = ?1, mentions = ?2, updated_at = ?3
+                     WHERE id = ?4",
+                    params![body, mentions, updated_at, id.to_string()],
+                )
+                .map_err(|e| e.to_string())?;
+            Ok(rows > 0)
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+    }
 
-            let query = if types.is_some() {
-                "SELECT id, mission_id, sequence, event_type, timestamp, event_id, tool_call_id, tool_name, content, content_file, metadata
-                 FROM mission_events
-                 WHERE mission_id = ?1 AND event_type IN (SELECT value FROM json_each(?2))
-                 ORDER BY sequence ASC
-                 LIMIT ?3 OFFSET ?4"
-            } else {
-                "SELECT id, mission_id, sequence, event_type, timestamp, event_id, tool_call_id, tool_name, content, content_file, metadata
-                 FROM mission_events
-                 WHERE mission_id = ?1
-                 ORDER BY sequence ASC
-                 LIMIT ?2 OFFSET ?3"
-            };
+    async fn delete_mission_comment(&self, id: Uuid) -> Result<usize, String> {
+        let conn = self.conn.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            conn.execute(
+                "DELETE FROM mission_comments WHERE id = ?1 OR thread_id = ?1",
+                params![id.to_string()],
+            )
+            .map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+    }
+
+    async fn list_message_feedback(
+        &self,
+        mission_id: Option<Uuid>,
+        since: Option<&str>,
+    ) -> Result<Vec<MessageFeedback>, String> {
+        let conn = self.conn.clone();
+        let cipher = self.cipher.clone();
+        let mission_id = mission_id.map(|id| id.to_string());
+        let since = since.map(str::to_string);
 
-            // Helper closure to parse a row into StoredEvent
-            fn parse_row(row: &rusqlite::Row<'_>) -> Result<StoredEvent, rusqlite::Error> {
-                let content: Option<String> = row.get(8)?;
-                let content_file: Option<String> = row.get(9)?;
-                let full_content = SqliteMissionStore::load_content(content.as_deref(), content_file.as_deref());
-                let metadata_str: String = row.get::<_, Option<String>>(10)?.unwrap_or_else(|| "{}".to_string());
-                let mid_str: String = row.get(1)?;
-
-                Ok(StoredEvent {
-                    id: row.get(0)?,
-                    mission_id: parse_uuid_or_nil(&mid_str),
-                    sequence: row.get(2)?,
-                    event_type: row.get(3)?,
-                    timestamp: row.get(4)?,
-                    event_id: row.get(5)?,
-                    tool_call_id: row.get(6)?,
-                    tool_name: row.get(7)?,
-                    content: full_content,
-                    metadata: serde_json::from_str(&metadata_str).unwrap_or(serde_json::json!({})),
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let mut stmt = conn
+                .prepare(
+                    "SELECT mission_id, message_id, rating, comment, backend, model, created_at
+                     FROM message_feedback
+                     WHERE (?1 IS NULL OR mission_id = ?1) AND (?2 IS NULL OR created_at >= ?2)
+                     ORDER BY created_at DESC",
+                )
+                .map_err(|e| e.to_string())?;
+            let rows = stmt
+                .query_map(params![mission_id, since], |row| {
+                    Ok((
+                        row.get::<_, String>(0)?,
+                        row.get::<_, String>(1)?,
+                        row.get::<_, String>(2)?,
+                        row.get::<_, Option<String>>(3)?,
+                        row.get::<_, String>(4)?,
+                        row.get::<_, Option<String>>(5)?,
+                        row.get::<_, String>(6)?,
+                    ))
                 })
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            Ok(rows
+                .into_iter()
+                .filter_map(
+                    |(mission_id, message_id, rating, comment, backend, model, created_at)| {
+                        Some(MessageFeedback {
+                            mission_id: parse_uuid_or_nil(&mission_id),
+                            message_id: parse_uuid_or_nil(&message_id),
+                            rating: FeedbackRating::parse(&rating)?,
+                            comment: comment.map(|c| crypto::open(cipher.as_deref(), &c)),
+                            backend,
+                            model,
+                            created_at,
+                        })
+                    },
+                )
+                .collect())
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+    }
+
+    async fn ping(&self) -> Result<(), String> {
+        let conn = self.conn.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            conn.query_row("SELECT 1", [], |row| row.get::<_, i64>(0))
+                .map(|_| ())
+                .map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+    }
+
+    async fn run_maintenance(
+        &self,
+        vacuum: bool,
+    ) -> Result<Option<StoreMaintenanceReport>, String> {
+        let conn = self.conn.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let started = std::time::Instant::now();
+            let database = conn.path().unwrap_or_default().to_string();
+            let size_before_bytes = database_size(&database);
+
+            // Fold the write-ahead log back into the database and shrink it.
+            conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+                .map_err(|e| format!("WAL checkpoint failed: {}", e))?;
+            if vacuum {
+                conn.execute_batch("VACUUM")
+                    .map_err(|e| format!("VACUUM failed: {}", e))?;
+            } else {
+                conn.execute_batch("PRAGMA incremental_vacuum")
+                    .map_err(|e| format!("Incremental vacuum failed: {}", e))?;
             }
+            conn.execute_batch("ANALYZE")
+                .map_err(|e| format!("ANALYZE failed: {}", e))?;
+            conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(()))
+                .map_err(|e| format!("WAL checkpoint failed: {}", e))?;
+
+            Ok(Some(StoreMaintenanceReport {
+                size_after_bytes: database_size(&database),
+                database,
+                size_before_bytes,
+                vacuumed: vacuum,
+                duration_ms: started.elapsed().as_millis() as u64,
+            }))
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+    }
 
-            let events: Vec<StoredEvent> = if let Some(types) = types {
-                let types_json = serde_json::to_string(&types).unwrap_or_else(|_| "[]".to_string());
-                let mut stmt = conn.prepare(query).map_err(|e| e.to_string())?;
-                let rows = stmt.query_map(params![&mid, &types_json, limit, offset], parse_row)
+    async fn check_integrity(&self) -> Result<Option<StoreIntegrityReport>, String> {
+        let conn = self.conn.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let database = conn.path().unwrap_or_default().to_string();
+
+            let mut stmt = conn
+                .prepare(&format!(
+                    "PRAGMA integrity_check({})",
+                    MAX_INTEGRITY_PROBLEMS
+                ))
+                .map_err(|e| e.to_string())?;
+            let problems: Vec<String> = stmt
+                .query_map([], |row| row.get::<_, String>(0))
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?
+                .into_iter()
+                .filter(|line| line != "ok")
+                .collect();
+
+            let mut stmt = conn
+                .prepare("PRAGMA foreign_key_check")
+                .map_err(|e| e.to_string())?;
+            let foreign_key_violations = stmt
+                .query_map([], |_| Ok(()))
+                .map_err(|e| e.to_string())?
+                .count() as u64;
+
+            let pragma = |name: &str| -> Result<i64, String> {
+                conn.query_row(&format!("PRAGMA {}", name), [], |row| row.get(0))
+                    .map_err(|e| e.to_string())
+            };
+            let page_count = pragma("page_count")?;
+            let freelist_count = pragma("freelist_count")?;
+            let journal_mode: String = conn
+                .query_row("PRAGMA journal_mode", [], |row| row.get(0))
+                .map_err(|e| e.to_string())?;
+            let free_pct = if page_count > 0 {
+                (freelist_count as f64 / page_count as f64 * 1000.0).round() / 10.0
+            } else {
+                0.0
+            };
+
+            Ok(Some(StoreIntegrityReport {
+                size_bytes: database_size(&database),
+                database,
+                ok: problems.is_empty() && foreign_key_violations == 0,
+                problems,
+                foreign_key_violations,
+                journal_mode,
+                free_pct,
+            }))
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+    }
+
+    async fn record_runner_heartbeats(
+        &self,
+        instance_id: &str,
+        pid: u32,
+        mission_ids: &[Uuid],
+    ) -> Result<(), String> {
+        let conn = self.conn.clone();
+        let instance_id = instance_id.to_string();
+        let mission_ids: Vec<String> = mission_ids.iter().map(Uuid::to_string).collect();
+        let now = now_string();
+
+        tokio::task::spawn_blocking(move || {
+            let mut conn = conn.blocking_lock();
+            let tx = conn.transaction().map_err(|e| e.to_string())?;
+            {
+                let mut stale = tx
+                    .prepare("SELECT mission_id FROM runner_heartbeats WHERE instance_id = ?1")
+                    .map_err(|e| e.to_string())?;
+                let stale: Vec<String> = stale
+                    .query_map(params![instance_id], |row| row.get(0))
+                    .map_err(|e| e.to_string())?
+                    .filter_map(Result::ok)
+                    .filter(|id| !mission_ids.contains(id))
+                    .collect();
+                for mission_id in stale {
+                    tx.execute(
+                        "DELETE FROM runner_heartbeats WHERE mission_id = ?1",
+                        params![mission_id],
+                    )
                     .map_err(|e| e.to_string())?;
-                let mut result = Vec::new();
-                for row in rows {
-                    result.push(row.map_err(|e| e.to_string())?);
                 }
-                result
-            } else {
-                let mut stmt = conn.prepare(query).map_err(|e| e.to_string())?;
-                let rows = stmt.query_map(params![&mid, limit, offset], parse_row)
+                for mission_id in &mission_ids {
+                    tx.execute(
+                        "INSERT INTO runner_heartbeats (mission_id, instance_id, pid, beat_at)
+                         VALUES (?1, ?2, ?3, ?4)
+                         ON CONFLICT(mission_id) DO UPDATE SET
+                            instance_id = excluded.instance_id,
+                            pid = excluded.pid,
+                            beat_at = excluded.beat_at",
+                        params![mission_id, instance_id, pid, now],
+                    )
                     .map_err(|e| e.to_string())?;
-                let mut result = Vec::new();
-                for row in rows {
-                    result.push(row.map_err(|e| e.to_string())?);
                 }
-                result
-            };
+            }
+            tx.commit().map_err(|e| e.to_string())
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+    }
 
-            Ok(events)
+    async fn list_runner_heartbeats(&self) -> Result<Vec<RunnerHeartbeat>, String> {
+        let conn = self.conn.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let mut stmt = conn
+                .prepare(
+                    "SELECT mission_id, instance_id, pid, beat_at FROM runner_heartbeats
+                     ORDER BY beat_at DESC",
+                )
+                .map_err(|e| e.to_string())?;
+            let heartbeats = stmt
+                .query_map([], |row| {
+                    let mission_id: String = row.get(0)?;
+                    Ok(RunnerHeartbeat {
+                        mission_id: Uuid::parse_str(&mission_id)
+                            .map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))?,
+                        instance_id: row.get(1)?,
+                        pid: row.get(2)?,
+                        beat_at: row.get(3)?,
+                    })
+                })
+                .map_err(|e| e.to_string())?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| e.to_string())?;
+            Ok(heartbeats)
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+    }
+
+    async fn delete_runner_heartbeat(&self, mission_id: Uuid) -> Result<(), String> {
+        let conn = self.conn.clone();
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            conn.execute(
+                "DELETE FROM runner_heartbeats WHERE mission_id = ?1",
+                params![mission_id.to_string()],
+            )
+            .map(|_| ())
+            .map_err(|e| e.to_string())
         })
         .await
-        .map_err(|e| e.to_string())?
+        .map_err(|e| format!("Task join error: {}", e))?
     }
 
     async fn get_total_cost_cents(&self) -> Result<u64, String> {
@@ -2091,6 +4587,16 @@ impl MissionStore for SqliteMissionStore {
         // Serialize variables
         let variables_json =
             serde_json::to_string(&automation.variables).map_err(|e| e.to_string())?;
+        let mission_retry_policy = automation
+            .mission_retry_policy
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| e.to_string())?;
+        let blackout_windows = (!automation.blackout_windows.is_empty())
+            .then(|| serde_json::to_string(&automation.blackout_windows))
+            .transpose()
+            .map_err(|e| e.to_string())?;
 
         let a = automation.clone();
         tokio::task::spawn_blocking(move || {
@@ -2108,8 +4614,9 @@ impl MissionStore for SqliteMissionStore {
                 "INSERT INTO automations (id, mission_id, command_source_type, command_source_data,
                                          trigger_type, trigger_data, variables, active, stop_policy,
                                          fresh_session, created_at, last_triggered_at, retry_max_retries,
-                                         retry_delay_seconds, retry_backoff_multiplier)
-                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                                         retry_delay_seconds, retry_backoff_multiplier, mission_retry_policy,
+                                         blackout_windows)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
                 params![
                     a.id.to_string(),
                     a.mission_id.to_string(),
@@ -2126,6 +4633,8 @@ impl MissionStore for SqliteMissionStore {
                     a.retry_config.max_retries as i64,
                     a.retry_config.retry_delay_seconds as i64,
                     a.retry_config.backoff_multiplier,
+                    mission_retry_policy,
+                    blackout_windows,
                 ],
             )
             .map(|_| ())
@@ -2146,7 +4655,7 @@ impl MissionStore for SqliteMissionStore {
             let mut stmt = conn
                 .prepare("SELECT id, mission_id, command_source_type, command_source_data,
                                 trigger_type, trigger_data, variables, active, stop_policy, fresh_session, created_at, last_triggered_at,
-                                retry_max_retries, retry_delay_seconds, retry_backoff_multiplier
+                                retry_max_retries, retry_delay_seconds, retry_backoff_multiplier, mission_retry_policy, blackout_windows
                          FROM automations WHERE mission_id = ? ORDER BY created_at DESC")
                 .map_err(|e| e.to_string())?;
 
@@ -2173,7 +4682,7 @@ impl MissionStore for SqliteMissionStore {
                 .prepare(
                     "SELECT id, mission_id, command_source_type, command_source_data,
                             trigger_type, trigger_data, variables, active, stop_policy, fresh_session, created_at, last_triggered_at,
-                            retry_max_retries, retry_delay_seconds, retry_backoff_multiplier
+                            retry_max_retries, retry_delay_seconds, retry_backoff_multiplier, mission_retry_policy, blackout_windows
                      FROM automations WHERE active = 1 ORDER BY created_at DESC",
                 )
                 .map_err(|e| e.to_string())?;
@@ -2202,7 +4711,7 @@ impl MissionStore for SqliteMissionStore {
                 .query_row(
                     "SELECT id, mission_id, command_source_type, command_source_data,
                             trigger_type, trigger_data, variables, active, stop_policy, fresh_session, created_at, last_triggered_at,
-                            retry_max_retries, retry_delay_seconds, retry_backoff_multiplier
+                            retry_max_retries, retry_delay_seconds, retry_backoff_multiplier, mission_retry_policy, blackout_windows
                      FROM automations WHERE id = ?",
                     [id_str],
                     Self::parse_automation_row,
@@ -2300,6 +4809,16 @@ impl MissionStore for SqliteMissionStore {
         // Serialize variables
         let variables_json =
             serde_json::to_string(&automation.variables).map_err(|e| e.to_string())?;
+        let mission_retry_policy = automation
+            .mission_retry_policy
+            .as_ref()
+            .map(serde_json::to_string)
+            .transpose()
+            .map_err(|e| e.to_string())?;
+        let blackout_windows = (!automation.blackout_windows.is_empty())
+            .then(|| serde_json::to_string(&automation.blackout_windows))
+            .transpose()
+            .map_err(|e| e.to_string())?;
 
         tokio::task::spawn_blocking(move || {
             let conn = conn.blocking_lock();
@@ -2316,7 +4835,7 @@ impl MissionStore for SqliteMissionStore {
                 "UPDATE automations SET command_source_type = ?, command_source_data = ?,
                                        trigger_type = ?, trigger_data = ?, variables = ?, active = ?,
                                        stop_policy = ?, fresh_session = ?, last_triggered_at = ?, retry_max_retries = ?, retry_delay_seconds = ?,
-                                       retry_backoff_multiplier = ?
+                                       retry_backoff_multiplier = ?, mission_retry_policy = ?, blackout_windows = ?
                   WHERE id = ?",
                 params![
                     command_source_type,
@@ -2331,6 +4850,8 @@ impl MissionStore for SqliteMissionStore {
                     automation.retry_config.max_retries as i64,
                     automation.retry_config.retry_delay_seconds as i64,
                     automation.retry_config.backoff_multiplier,
+                    mission_retry_policy,
+                    blackout_windows,
                     automation.id.to_string(),
                 ],
             )
@@ -2354,7 +4875,7 @@ impl MissionStore for SqliteMissionStore {
                 .query_row(
                     "SELECT id, mission_id, command_source_type, command_source_data,
                             trigger_type, trigger_data, variables, active, stop_policy, fresh_session, created_at, last_triggered_at,
-                            retry_max_retries, retry_delay_seconds, retry_backoff_multiplier
+                            retry_max_retries, retry_delay_seconds, retry_backoff_multiplier, mission_retry_policy, blackout_windows
                      FROM automations
                      WHERE trigger_type = 'webhook' AND json_extract(trigger_data, '$.webhook_id') = ?",
                     [webhook_id],
@@ -2398,8 +4919,12 @@ impl MissionStore for SqliteMissionStore {
             conn.execute(
                 "INSERT INTO automation_executions (id, automation_id, mission_id, triggered_at,
                                                     trigger_source, status, webhook_payload, variables_used,
-                                                    completed_at, error, retry_count)
-                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                                                    completed_at, error, retry_count, first_event_seq)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?,
+                         CASE WHEN ?6 = 'skipped' THEN NULL ELSE
+                             (SELECT COALESCE(MAX(sequence), 0) + 1 FROM mission_events
+                              WHERE mission_id = ?3)
+                         END)",
                 params![
                     exec.id.to_string(),
                     exec.automation_id.to_string(),
@@ -2473,28 +4998,35 @@ impl MissionStore for SqliteMissionStore {
         &self,
         automation_id: Uuid,
         limit: Option<usize>,
+        before: Option<&Keyset>,
     ) -> Result<Vec<AutomationExecution>, String> {
         let conn = self.conn.clone();
         let automation_id_str = automation_id.to_string();
         let limit = limit.unwrap_or(100) as i64;
+        let before_key = before.map(|bound| bound.sort_key.clone());
+        let before_id = before.map(|bound| bound.id.to_string());
 
         tokio::task::spawn_blocking(move || {
             let conn = conn.blocking_lock();
             let mut stmt = conn
-                .prepare(
+                .prepare(&format!(
                     "SELECT id, automation_id, mission_id, triggered_at, trigger_source, status,
-                            webhook_payload, variables_used, completed_at, error, retry_count
-                     FROM automation_executions
-                     WHERE automation_id = ?
-                     ORDER BY triggered_at DESC
-                     LIMIT ?",
-                )
+                            webhook_payload, variables_used, completed_at, error, retry_count,
+                            first_event_seq, {EXECUTION_LAST_EVENT_SEQ}
+                     FROM automation_executions e
+                     WHERE automation_id = ?1
+                       AND (?2 IS NULL OR triggered_at < ?2
+                            OR (triggered_at = ?2 AND id < ?3))
+                     ORDER BY triggered_at DESC, id DESC
+                     LIMIT ?4"
+                ))
                 .map_err(|e| e.to_string())?;
 
             let executions = stmt
-                .query_map(params![automation_id_str, limit], |row| {
-                    Self::parse_execution_row(row)
-                })
+                .query_map(
+                    params![automation_id_str, before_key, before_id, limit],
+                    Self::parse_execution_row,
+                )
                 .map_err(|e| e.to_string())?
                 .collect::<Result<Vec<_>, _>>()
                 .map_err(|e| e.to_string())?;
@@ -2505,32 +5037,58 @@ impl MissionStore for SqliteMissionStore {
         .map_err(|e| format!("Task join error: {}", e))?
     }
 
+    async fn count_automation_executions(&self, automation_id: Uuid) -> Result<usize, String> {
+        let conn = self.conn.clone();
+        let automation_id_str = automation_id.to_string();
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let count: i64 = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM automation_executions WHERE automation_id = ?",
+                    params![automation_id_str],
+                    |row| row.get(0),
+                )
+                .map_err(|e| e.to_string())?;
+            Ok(count as usize)
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+    }
+
     async fn get_mission_automation_executions(
         &self,
         mission_id: Uuid,
         limit: Option<usize>,
+        before: Option<&Keyset>,
     ) -> Result<Vec<AutomationExecution>, String> {
         let conn = self.conn.clone();
         let mission_id_str = mission_id.to_string();
         let limit = limit.unwrap_or(100) as i64;
+        let before_key = before.map(|bound| bound.sort_key.clone());
+        let before_id = before.map(|bound| bound.id.to_string());
 
         tokio::task::spawn_blocking(move || {
             let conn = conn.blocking_lock();
             let mut stmt = conn
-                .prepare(
+                .prepare(&format!(
                     "SELECT id, automation_id, mission_id, triggered_at, trigger_source, status,
-                            webhook_payload, variables_used, completed_at, error, retry_count
-                     FROM automation_executions
-                     WHERE mission_id = ?
-                     ORDER BY triggered_at DESC
-                     LIMIT ?",
-                )
+                            webhook_payload, variables_used, completed_at, error, retry_count,
+                            first_event_seq, {EXECUTION_LAST_EVENT_SEQ}
+                     FROM automation_executions e
+                     WHERE mission_id = ?1
+                       AND (?2 IS NULL OR triggered_at < ?2
+                            OR (triggered_at = ?2 AND id < ?3))
+                     ORDER BY triggered_at DESC, id DESC
+                     LIMIT ?4"
+                ))
                 .map_err(|e| e.to_string())?;
 
             let executions = stmt
-                .query_map(params![mission_id_str, limit], |row| {
-                    Self::parse_execution_row(row)
-                })
+                .query_map(
+                    params![mission_id_str, before_key, before_id, limit],
+                    Self::parse_execution_row,
+                )
                 .map_err(|e| e.to_string())?
                 .collect::<Result<Vec<_>, _>>()
                 .map_err(|e| e.to_string())?;
@@ -2541,6 +5099,25 @@ impl MissionStore for SqliteMissionStore {
         .map_err(|e| format!("Task join error: {}", e))?
     }
 
+    async fn count_mission_automation_executions(&self, mission_id: Uuid) -> Result<usize, String> {
+        let conn = self.conn.clone();
+        let mission_id_str = mission_id.to_string();
+
+        tokio::task::spawn_blocking(move || {
+            let conn = conn.blocking_lock();
+            let count: i64 = conn
+                .query_row(
+                    "SELECT COUNT(*) FROM automation_executions WHERE mission_id = ?",
+                    params![mission_id_str],
+                    |row| row.get(0),
+                )
+                .map_err(|e| e.to_string())?;
+            Ok(count as usize)
+        })
+        .await
+        .map_err(|e| format!("Task join error: {}", e))?
+    }
+
     async fn complete_running_executions_for_mission(
         &self,
         mission_id: Uuid,
@@ -2571,12 +5148,19 @@ impl MissionStore for SqliteMissionStore {
 
 #[cfg(test)]
 mod tests {
-    use super::{assistant_message_metadata, AssistantMessageMetadataInput, SqliteMissionStore};
+    use super::{
+        assistant_message_metadata, crypto, normalized_title_key, AssistantMessageMetadataInput,
+        SqliteMissionStore, StoreCipher,
+    };
     use crate::agents::CostSource;
-    use crate::api::mission_store::MissionStore;
+    use crate::api::mission_store::{
+        Keyset, MissionFilter, MissionStore, StoredEvent, StreamEvent,
+    };
     use crate::cost::TokenUsage;
     use rusqlite::params;
     use serde_json::json;
+    use std::sync::Arc;
+    use uuid::Uuid;
 
     #[test]
     fn assistant_message_metadata_uses_normalized_cost_shape() {
@@ -2594,6 +5178,8 @@ mod tests {
             model_normalized: &Some("gpt-4o".to_string()),
             shared_files: &None,
             resumable: false,
+            cancelled: false,
+            tool_calls: &[],
         });
 
         assert_eq!(
@@ -2629,83 +5215,497 @@ mod tests {
             model_normalized: &None,
             shared_files: &None,
             resumable: false,
-        });
+            cancelled: false,
+            tool_calls: &[],
+        });
+
+        assert_eq!(
+            metadata,
+            json!({
+                "success": false,
+                "cost_cents": 0,
+                "cost": {
+                    "amount_cents": 0,
+                    "currency": "USD",
+                    "source": "unknown",
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn assistant_message_metadata_records_cancelled_turns() {
+        let tool_calls = [crate::api::partial_turn::PartialToolCall {
+            tool_call_id: "c1".to_string(),
+            name: "bash".to_string(),
+        }];
+        let metadata = assistant_message_metadata(AssistantMessageMetadataInput {
+            success: false,
+            cost_cents: 0,
+            cost_source: CostSource::Unknown,
+            usage: &None,
+            model: &None,
+            model_normalized: &None,
+            shared_files: &None,
+            resumable: true,
+            cancelled: true,
+            tool_calls: &tool_calls,
+        });
+
+        assert_eq!(metadata["cancelled"], json!(true));
+        assert_eq!(
+            metadata["tool_calls"],
+            json!([{ "tool_call_id": "c1", "name": "bash" }])
+        );
+    }
+
+    #[tokio::test]
+    async fn update_mission_metadata_is_noop_when_fields_missing() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SqliteMissionStore::new(temp_dir.path().to_path_buf(), "test-user")
+            .await
+            .expect("sqlite store");
+        let mission = store
+            .create_mission(Some("Initial"), None, None, None, None, None, None)
+            .await
+            .expect("mission");
+
+        store
+            .update_mission_metadata(
+                mission.id,
+                Some(Some("Renamed")),
+                Some(Some("Short summary")),
+                Some(Some("backend_heuristic")),
+                None,
+                Some(Some("v1")),
+            )
+            .await
+            .expect("set metadata");
+
+        let after_set = store
+            .get_mission(mission.id)
+            .await
+            .expect("get mission")
+            .expect("mission exists");
+        let metadata_updated_at = after_set
+            .metadata_updated_at
+            .clone()
+            .expect("metadata timestamp should be set");
+        let updated_at = after_set.updated_at.clone();
+
+        store
+            .update_mission_metadata(mission.id, None, None, None, None, None)
+            .await
+            .expect("noop metadata update");
+
+        let after_noop = store
+            .get_mission(mission.id)
+            .await
+            .expect("get mission")
+            .expect("mission exists");
+
+        assert_eq!(after_noop.title.as_deref(), Some("Renamed"));
+        assert_eq!(
+            after_noop.short_description.as_deref(),
+            Some("Short summary")
+        );
+        assert_eq!(
+            after_noop.metadata_source.as_deref(),
+            Some("backend_heuristic")
+        );
+        assert_eq!(after_noop.metadata_model.as_deref(), None);
+        assert_eq!(after_noop.metadata_version.as_deref(), Some("v1"));
+        assert_eq!(
+            after_noop.metadata_updated_at.as_deref(),
+            Some(metadata_updated_at.as_str())
+        );
+        assert_eq!(after_noop.updated_at, updated_at);
+    }
+
+    #[tokio::test]
+    async fn missions_are_found_by_normalized_title() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SqliteMissionStore::new(temp_dir.path().to_path_buf(), "test-user")
+            .await
+            .expect("sqlite store");
+        let first = store
+            .create_mission(
+                Some("Fix flaky CI pipeline"),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .expect("mission");
+        let second = store
+            .create_mission(Some("Other"), None, None, None, None, None, None)
+            .await
+            .expect("mission");
+        store
+            .update_mission_title(second.id, "fix the flaky CI pipelines (2)")
+            .await
+            .expect("rename");
+        let third = store
+            .create_mission(None, None, None, None, None, None, None)
+            .await
+            .expect("mission");
+        store
+            .update_mission_metadata(
+                third.id,
+                Some(Some("Refactor sidebar")),
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .expect("set metadata");
+
+        let key = normalized_title_key("Fix flaky CI pipelines");
+        let mut found: Vec<Uuid> = store
+            .find_missions_by_normalized_title(&key)
+            .await
+            .expect("lookup")
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        found.sort();
+        let mut expected = vec![first.id, second.id];
+        expected.sort();
+        assert_eq!(found, expected);
+
+        // Titles written before the column existed are backfilled on startup
+        store
+            .conn
+            .lock()
+            .await
+            .execute("UPDATE missions SET normalized_title = NULL", [])
+            .expect("clear keys");
+        SqliteMissionStore::backfill_normalized_titles(&*store.conn.lock().await)
+            .expect("backfill");
+        let found = store
+            .find_missions_by_normalized_title(&normalized_title_key("Refactor sidebar"))
+            .await
+            .expect("lookup");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].id, third.id);
+    }
+
+    #[tokio::test]
+    async fn events_are_queryable_by_stream_seq() {
+        use crate::api::control::AgentEvent;
+
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SqliteMissionStore::new(temp_dir.path().to_path_buf(), "test-user")
+            .await
+            .expect("sqlite store");
+        let mission = store
+            .create_mission(Some("Replay"), None, None, None, None, None, None)
+            .await
+            .expect("mission");
+
+        for (seq, text) in [(10, "first"), (11, "second"), (12, "third")] {
+            let event = AgentEvent::Error {
+                message: text.to_string(),
+                code: None,
+                mission_id: Some(mission.id),
+                resumable: false,
+            };
+            store
+                .log_stream_event(mission.id, seq, &event)
+                .await
+                .expect("log event");
+        }
+        // Events logged without a stream position are never replayed.
+        store
+            .log_event(
+                mission.id,
+                &AgentEvent::Error {
+                    message: "untracked".to_string(),
+                    code: None,
+                    mission_id: Some(mission.id),
+                    resumable: false,
+                },
+            )
+            .await
+            .expect("log event");
+
+        let events = store
+            .get_events_after_stream_seq(10, 100)
+            .await
+            .expect("replay query");
+        let replayed: Vec<_> = events
+            .iter()
+            .map(|e| (e.stream_seq, e.content.as_str()))
+            .collect();
+        assert_eq!(replayed, vec![(Some(11), "second"), (Some(12), "third")]);
+    }
+
+    #[tokio::test]
+    async fn read_markers_count_unread_output_per_user() {
+        use crate::api::control::AgentEvent;
+
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SqliteMissionStore::new(temp_dir.path().to_path_buf(), "test-user")
+            .await
+            .expect("sqlite store");
+        let mission = store
+            .create_mission(None, None, None, None, None, None, None)
+            .await
+            .expect("mission");
+        let quiet = store
+            .create_mission(None, None, None, None, None, None, None)
+            .await
+            .expect("mission");
+        let reply = |content: &str| AgentEvent::AssistantMessage {
+            id: Uuid::new_v4(),
+            content: content.to_string(),
+            success: true,
+            cost_cents: 0,
+            cost_source: CostSource::Actual,
+            usage: None,
+            model: None,
+            model_normalized: None,
+            mission_id: Some(mission.id),
+            shared_files: None,
+            resumable: false,
+            cancelled: false,
+            tool_calls: Vec::new(),
+        };
+        let log = |event: AgentEvent| {
+            let store = &store;
+            async move {
+                store
+                    .log_event(mission.id, &event)
+                    .await
+                    .expect("log event")
+            }
+        };
+        log(AgentEvent::UserMessage {
+            id: Uuid::new_v4(),
+            content: "hi".to_string(),
+            queued: false,
+            mission_id: Some(mission.id),
+        })
+        .await;
+        log(reply("one")).await;
+        log(reply("two")).await;
+
+        let ids = [mission.id, quiet.id];
+        let counts = store.unread_counts("alice", &ids).await.expect("counts");
+        assert_eq!(counts.get(&mission.id), Some(&2));
+        assert!(!counts.contains_key(&quiet.id));
+
+        store
+            .mark_mission_read(mission.id, "alice", "2026-01-01T00:00:00Z")
+            .await
+            .expect("mark read");
+        log(reply("three")).await;
+
+        let counts = store.unread_counts("alice", &ids).await.expect("counts");
+        assert_eq!(counts.get(&mission.id), Some(&1));
+        let counts = store.unread_counts("bob", &ids).await.expect("counts");
+        assert_eq!(counts.get(&mission.id), Some(&3));
+        assert_eq!(
+            store
+                .mission_unread_counts(mission.id)
+                .await
+                .expect("per user"),
+            vec![("alice".to_string(), 1)]
+        );
+    }
+
+    #[tokio::test]
+    async fn thinking_events_can_be_excluded() {
+        use crate::api::control::AgentEvent;
+
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SqliteMissionStore::new(temp_dir.path().to_path_buf(), "test-user")
+            .await
+            .expect("sqlite store");
+        let mission = store
+            .create_mission(Some("Reasoning"), None, None, None, None, None, None)
+            .await
+            .expect("mission");
+
+        for (n, thinking) in [false, true, false, true].into_iter().enumerate() {
+            let event = if thinking {
+                AgentEvent::Thinking {
+                    content: format!("thought {}", n),
+                    done: true,
+                    mission_id: Some(mission.id),
+                }
+            } else {
+                AgentEvent::Error {
+                    message: format!("error {}", n),
+                    code: None,
+                    mission_id: Some(mission.id),
+                    resumable: false,
+                }
+            };
+            store
+                .log_event(mission.id, &event)
+                .await
+                .expect("log event");
+        }
+
+        assert_eq!(
+            store
+                .get_events(mission.id, None, None, None)
+                .await
+                .expect("all events")
+                .len(),
+            4
+        );
+        let events = store
+            .get_events_excluding(mission.id, &["thinking"], Some(1), Some(1))
+            .await
+            .expect("events without thinking");
+        let contents: Vec<_> = events.iter().map(|e| e.content.as_str()).collect();
+        assert_eq!(contents, vec!["error 2"]);
+
+        let recent = store
+            .get_recent_events(mission.id, 2)
+            .await
+            .expect("recent events");
+        let contents: Vec<_> = recent.iter().map(|e| e.content.as_str()).collect();
+        assert_eq!(contents, vec!["error 2", "thought 3"]);
+    }
+
+    #[tokio::test]
+    async fn event_batches_keep_per_mission_order() {
+        use crate::api::control::AgentEvent;
+
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SqliteMissionStore::new(temp_dir.path().to_path_buf(), "test-user")
+            .await
+            .expect("sqlite store");
+        let a = store
+            .create_mission(Some("A"), None, None, None, None, None, None)
+            .await
+            .expect("mission");
+        let b = store
+            .create_mission(Some("B"), None, None, None, None, None, None)
+            .await
+            .expect("mission");
+        let error = |mission_id: Uuid, text: &str| AgentEvent::Error {
+            message: text.to_string(),
+            code: None,
+            mission_id: Some(mission_id),
+            resumable: false,
+        };
+        store
+            .log_event(a.id, &error(a.id, "before"))
+            .await
+            .expect("log event");
 
-        assert_eq!(
-            metadata,
-            json!({
-                "success": false,
-                "cost_cents": 0,
-                "cost": {
-                    "amount_cents": 0,
-                    "currency": "USD",
-                    "source": "unknown",
-                },
+        let batch: Vec<StreamEvent> = [(a.id, "a1"), (b.id, "b1"), (a.id, "a2"), (b.id, "b2")]
+            .into_iter()
+            .enumerate()
+            .map(|(i, (mission_id, text))| StreamEvent {
+                mission_id,
+                stream_seq: i as u64 + 1,
+                event: Arc::new(error(mission_id, text)),
             })
+            .collect();
+        store.log_stream_events(&batch).await.expect("log batch");
+
+        let sequenced = |events: Vec<StoredEvent>| -> Vec<(i64, String)> {
+            events
+                .into_iter()
+                .map(|e| (e.sequence, e.content))
+                .collect()
+        };
+        let a_events = store.get_events(a.id, None, None, None).await.expect("a");
+        assert_eq!(
+            sequenced(a_events),
+            vec![
+                (1, "before".to_string()),
+                (2, "a1".to_string()),
+                (3, "a2".to_string())
+            ]
+        );
+        let b_events = store.get_events(b.id, None, None, None).await.expect("b");
+        assert_eq!(
+            sequenced(b_events),
+            vec![(1, "b1".to_string()), (2, "b2".to_string())]
         );
     }
 
     #[tokio::test]
-    async fn update_mission_metadata_is_noop_when_fields_missing() {
+    async fn failed_event_batches_leave_no_content_files() {
+        use crate::api::control::AgentEvent;
+
         let temp_dir = tempfile::tempdir().expect("temp dir");
         let store = SqliteMissionStore::new(temp_dir.path().to_path_buf(), "test-user")
             .await
             .expect("sqlite store");
         let mission = store
-            .create_mission(Some("Initial"), None, None, None, None, None, None)
+            .create_mission(Some("A"), None, None, None, None, None, None)
             .await
             .expect("mission");
-
         store
-            .update_mission_metadata(
-                mission.id,
-                Some(Some("Renamed")),
-                Some(Some("Short summary")),
-                Some(Some("backend_heuristic")),
-                None,
-                Some(Some("v1")),
-            )
-            .await
-            .expect("set metadata");
-
-        let after_set = store
-            .get_mission(mission.id)
+            .conn
+            .lock()
             .await
-            .expect("get mission")
-            .expect("mission exists");
-        let metadata_updated_at = after_set
-            .metadata_updated_at
-            .clone()
-            .expect("metadata timestamp should be set");
-        let updated_at = after_set.updated_at.clone();
+            .execute_batch(
+                "CREATE TRIGGER reject_bad BEFORE INSERT ON mission_events
+                 WHEN NEW.content = 'bad' BEGIN SELECT RAISE(ABORT, 'bad row'); END;",
+            )
+            .expect("trigger");
+        let error = |text: String| AgentEvent::Error {
+            message: text,
+            code: None,
+            mission_id: Some(mission.id),
+            resumable: false,
+        };
+        let batch: Vec<StreamEvent> = [
+            "x".repeat(super::CONTENT_SIZE_THRESHOLD + 1),
+            "bad".to_string(),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, text)| StreamEvent {
+            mission_id: mission.id,
+            stream_seq: i as u64 + 1,
+            event: Arc::new(error(text)),
+        })
+        .collect();
+        let spilled = |store: &SqliteMissionStore| {
+            std::fs::read_dir(
+                store
+                    .content_dir
+                    .join(mission.id.to_string())
+                    .join("events"),
+            )
+            .map(|dir| dir.count())
+            .unwrap_or(0)
+        };
 
-        store
-            .update_mission_metadata(mission.id, None, None, None, None, None)
+        assert!(store.log_stream_events(&batch).await.is_err());
+        assert_eq!(spilled(&store), 0);
+        assert!(store
+            .get_events(mission.id, None, None, None)
             .await
-            .expect("noop metadata update");
-
-        let after_noop = store
-            .get_mission(mission.id)
+            .expect("events")
+            .is_empty());
+
+        // One by one, only the bad event is lost.
+        for event in &batch {
+            let _ = store
+                .log_stream_event(mission.id, event.stream_seq, &event.event)
+                .await;
+        }
+        let events = store
+            .get_events(mission.id, None, None, None)
             .await
-            .expect("get mission")
-            .expect("mission exists");
-
-        assert_eq!(after_noop.title.as_deref(), Some("Renamed"));
-        assert_eq!(
-            after_noop.short_description.as_deref(),
-            Some("Short summary")
-        );
-        assert_eq!(
-            after_noop.metadata_source.as_deref(),
-            Some("backend_heuristic")
-        );
-        assert_eq!(after_noop.metadata_model.as_deref(), None);
-        assert_eq!(after_noop.metadata_version.as_deref(), Some("v1"));
-        assert_eq!(
-            after_noop.metadata_updated_at.as_deref(),
-            Some(metadata_updated_at.as_str())
-        );
-        assert_eq!(after_noop.updated_at, updated_at);
+            .expect("events");
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].content.len(), super::CONTENT_SIZE_THRESHOLD + 1);
+        assert_eq!(spilled(&store), 1);
     }
 
     #[tokio::test]
@@ -2871,52 +5871,300 @@ mod tests {
             params![
                 mission.id.to_string(),
                 1i64,
-                "2026-02-21T00:00:00Z",
+                "2026-02-21T00:00:00Z",
+                json!({
+                    "cost": { "amount_cents": 150 },
+                    "cost_cents": 99
+                })
+                .to_string()
+            ],
+        )
+        .expect("insert normalized + legacy");
+        conn.execute(
+            query,
+            params![
+                mission.id.to_string(),
+                2i64,
+                "2026-02-21T00:00:01Z",
+                json!({ "cost_cents": 25 }).to_string()
+            ],
+        )
+        .expect("insert legacy");
+        conn.execute(
+            query,
+            params![
+                mission.id.to_string(),
+                3i64,
+                "2026-02-21T00:00:02Z",
+                json!({ "cost": { "amount_cents": 5 } }).to_string()
+            ],
+        )
+        .expect("insert normalized");
+        drop(conn);
+
+        let total = store
+            .get_total_cost_cents()
+            .await
+            .expect("total cost should calculate");
+        assert_eq!(total, 180);
+    }
+
+    #[tokio::test]
+    async fn legacy_events_are_upgraded_on_read() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SqliteMissionStore::new(temp_dir.path().to_path_buf(), "test-user")
+            .await
+            .expect("sqlite store");
+        let mission = store
+            .create_mission(Some("Legacy mission"), None, None, None, None, None, None)
+            .await
+            .expect("mission");
+
+        // A row written before schema versioning: no schema_version, flat cost.
+        let conn = store.conn.lock().await;
+        conn.execute(
+            "INSERT INTO mission_events (mission_id, sequence, event_type, timestamp, metadata)
+             VALUES (?1, 1, 'assistant_message', '2026-02-21T00:00:00Z', ?2)",
+            params![
+                mission.id.to_string(),
+                json!({ "cost_cents": 25 }).to_string()
+            ],
+        )
+        .expect("insert legacy");
+        let stored_version: i64 = conn
+            .query_row("SELECT schema_version FROM mission_events", [], |row| {
+                row.get(0)
+            })
+            .expect("schema version");
+        assert_eq!(stored_version, 1);
+        drop(conn);
+
+        let events = store
+            .get_events(mission.id, None, None, None)
+            .await
+            .expect("events");
+        assert_eq!(
+            events[0].schema_version,
+            crate::api::event_schema::CURRENT_VERSION
+        );
+        assert_eq!(events[0].metadata["cost"]["amount_cents"], 25);
+        assert_eq!(events[0].metadata["cost"]["source"], "unknown");
+    }
+
+    #[tokio::test]
+    async fn maintenance_and_integrity_check_report_on_the_database() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SqliteMissionStore::new(temp_dir.path().to_path_buf(), "test-user")
+            .await
+            .expect("sqlite store");
+        store
+            .create_mission(
+                Some("Maintained mission"),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .expect("mission");
+
+        for vacuum in [false, true] {
+            let report = store
+                .run_maintenance(vacuum)
+                .await
+                .expect("maintenance")
+                .expect("sqlite report");
+            assert!(report.database.ends_with("missions-test-user.db"));
+            assert!(report.size_after_bytes > 0);
+            assert_eq!(report.vacuumed, vacuum);
+        }
+
+        let integrity = store
+            .check_integrity()
+            .await
+            .expect("integrity check")
+            .expect("sqlite report");
+        assert!(integrity.ok, "{:?}", integrity.problems);
+        assert_eq!(integrity.journal_mode, "wal");
+
+        // New databases release free pages incrementally.
+        let conn = store.conn.lock().await;
+        let auto_vacuum: i64 = conn
+            .query_row("PRAGMA auto_vacuum", [], |row| row.get(0))
+            .expect("auto_vacuum");
+        assert_eq!(auto_vacuum, 2);
+    }
+
+    #[tokio::test]
+    async fn get_total_cost_cents_clamps_negative_values_to_zero() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SqliteMissionStore::new(temp_dir.path().to_path_buf(), "test-user")
+            .await
+            .expect("sqlite store");
+        let mission = store
+            .create_mission(Some("Cost mission"), None, None, None, None, None, None)
+            .await
+            .expect("mission");
+
+        let conn = store.conn.lock().await;
+        let query = r#"
+            INSERT INTO mission_events (
+                mission_id, sequence, event_type, timestamp, metadata
+            ) VALUES (?1, ?2, 'assistant_message', ?3, ?4)
+        "#;
+
+        conn.execute(
+            query,
+            params![
+                mission.id.to_string(),
+                1i64,
+                "2026-02-21T00:00:00Z",
+                json!({ "cost": { "amount_cents": -50 } }).to_string()
+            ],
+        )
+        .expect("insert malformed negative normalized");
+        conn.execute(
+            query,
+            params![
+                mission.id.to_string(),
+                2i64,
+                "2026-02-21T00:00:01Z",
+                json!({ "cost_cents": -10 }).to_string()
+            ],
+        )
+        .expect("insert malformed negative legacy");
+        conn.execute(
+            query,
+            params![
+                mission.id.to_string(),
+                3i64,
+                "2026-02-21T00:00:02Z",
+                json!({ "cost": { "amount_cents": 25 } }).to_string()
+            ],
+        )
+        .expect("insert valid normalized");
+        drop(conn);
+
+        let total = store
+            .get_total_cost_cents()
+            .await
+            .expect("total cost should calculate");
+        assert_eq!(total, 25);
+    }
+
+    #[tokio::test]
+    async fn get_cost_by_source_groups_by_provenance() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SqliteMissionStore::new(temp_dir.path().to_path_buf(), "test-user")
+            .await
+            .expect("sqlite store");
+        let mission = store
+            .create_mission(
+                Some("Cost source mission"),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
+            .await
+            .expect("mission");
+
+        let conn = store.conn.lock().await;
+        let query = r#"
+            INSERT INTO mission_events (
+                mission_id, sequence, event_type, timestamp, metadata
+            ) VALUES (?1, ?2, 'assistant_message', ?3, ?4)
+        "#;
+
+        // Actual cost
+        conn.execute(
+            query,
+            params![
+                mission.id.to_string(),
+                1i64,
+                "2026-02-22T00:00:00Z",
                 json!({
-                    "cost": { "amount_cents": 150 },
-                    "cost_cents": 99
+                    "cost": { "amount_cents": 100, "source": "actual", "currency": "USD" }
                 })
                 .to_string()
             ],
         )
-        .expect("insert normalized + legacy");
+        .expect("insert actual");
+
+        // Estimated cost
         conn.execute(
             query,
             params![
                 mission.id.to_string(),
                 2i64,
-                "2026-02-21T00:00:01Z",
-                json!({ "cost_cents": 25 }).to_string()
+                "2026-02-22T00:00:01Z",
+                json!({
+                    "cost": { "amount_cents": 50, "source": "estimated", "currency": "USD" }
+                })
+                .to_string()
             ],
         )
-        .expect("insert legacy");
+        .expect("insert estimated");
+
+        // Unknown cost (explicit)
         conn.execute(
             query,
             params![
                 mission.id.to_string(),
                 3i64,
-                "2026-02-21T00:00:02Z",
-                json!({ "cost": { "amount_cents": 5 } }).to_string()
+                "2026-02-22T00:00:02Z",
+                json!({
+                    "cost": { "amount_cents": 10, "source": "unknown", "currency": "USD" }
+                })
+                .to_string()
             ],
         )
-        .expect("insert normalized");
+        .expect("insert unknown");
+
+        // Legacy cost (no source field → unknown)
+        conn.execute(
+            query,
+            params![
+                mission.id.to_string(),
+                4i64,
+                "2026-02-22T00:00:03Z",
+                json!({ "cost_cents": 5 }).to_string()
+            ],
+        )
+        .expect("insert legacy");
+
         drop(conn);
 
-        let total = store
-            .get_total_cost_cents()
+        let (actual, estimated, unknown) = store
+            .get_cost_by_source()
             .await
-            .expect("total cost should calculate");
-        assert_eq!(total, 180);
+            .expect("cost by source should calculate");
+        assert_eq!(actual, 100);
+        assert_eq!(estimated, 50);
+        // Unknown (10) + legacy (5) both go into the unknown bucket
+        assert_eq!(unknown, 15);
     }
 
     #[tokio::test]
-    async fn get_total_cost_cents_clamps_negative_values_to_zero() {
+    async fn get_cost_since_filters_by_timestamp() {
         let temp_dir = tempfile::tempdir().expect("temp dir");
         let store = SqliteMissionStore::new(temp_dir.path().to_path_buf(), "test-user")
             .await
             .expect("sqlite store");
         let mission = store
-            .create_mission(Some("Cost mission"), None, None, None, None, None, None)
+            .create_mission(
+                Some("Period cost mission"),
+                None,
+                None,
+                None,
+                None,
+                None,
+                None,
+            )
             .await
             .expect("mission");
 
@@ -2927,229 +6175,837 @@ mod tests {
             ) VALUES (?1, ?2, 'assistant_message', ?3, ?4)
         "#;
 
+        // Old event — outside the "since" window
         conn.execute(
             query,
             params![
                 mission.id.to_string(),
                 1i64,
-                "2026-02-21T00:00:00Z",
-                json!({ "cost": { "amount_cents": -50 } }).to_string()
+                "2026-02-01T00:00:00Z",
+                json!({
+                    "cost": { "amount_cents": 200, "source": "actual" }
+                })
+                .to_string()
             ],
         )
-        .expect("insert malformed negative normalized");
+        .expect("insert old actual");
+
+        // Recent events — inside the "since" window
         conn.execute(
             query,
             params![
                 mission.id.to_string(),
                 2i64,
-                "2026-02-21T00:00:01Z",
-                json!({ "cost_cents": -10 }).to_string()
+                "2026-02-20T00:00:00Z",
+                json!({
+                    "cost": { "amount_cents": 30, "source": "actual" }
+                })
+                .to_string()
             ],
         )
-        .expect("insert malformed negative legacy");
+        .expect("insert recent actual");
         conn.execute(
             query,
             params![
                 mission.id.to_string(),
                 3i64,
-                "2026-02-21T00:00:02Z",
-                json!({ "cost": { "amount_cents": 25 } }).to_string()
+                "2026-02-21T00:00:00Z",
+                json!({
+                    "cost": { "amount_cents": 15, "source": "estimated" }
+                })
+                .to_string()
             ],
         )
-        .expect("insert valid normalized");
+        .expect("insert recent estimated");
+
         drop(conn);
 
-        let total = store
-            .get_total_cost_cents()
+        // All-time totals
+        let all_total = store.get_total_cost_cents().await.expect("all-time total");
+        assert_eq!(all_total, 245); // 200 + 30 + 15
+
+        // Period-filtered: since 2026-02-15 should only include the two recent events
+        let since = "2026-02-15T00:00:00Z";
+        let period_total = store
+            .get_total_cost_cents_since(since)
+            .await
+            .expect("period total");
+        assert_eq!(period_total, 45); // 30 + 15
+
+        let (actual, estimated, unknown) = store
+            .get_cost_by_source_since(since)
+            .await
+            .expect("period cost by source");
+        assert_eq!(actual, 30);
+        assert_eq!(estimated, 15);
+        assert_eq!(unknown, 0);
+    }
+
+    #[tokio::test]
+    async fn list_missions_filtered_by_flags_and_tags() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SqliteMissionStore::new(temp_dir.path().to_path_buf(), "test-user")
+            .await
+            .expect("sqlite store");
+        let pinned = store
+            .create_mission(Some("Pinned"), None, None, None, None, None, None)
+            .await
+            .expect("mission");
+        let archived = store
+            .create_mission(Some("Archived"), None, None, None, None, None, None)
+            .await
+            .expect("mission");
+        store
+            .update_mission_flags(pinned.id, Some(true), None)
+            .await
+            .expect("pin");
+        store
+            .update_mission_flags(archived.id, None, Some(true))
+            .await
+            .expect("archive");
+        store
+            .update_mission_tags(archived.id, &["ops".to_string()])
+            .await
+            .expect("tags");
+        assert!(store
+            .update_mission_flags(uuid::Uuid::new_v4(), Some(true), None)
+            .await
+            .is_err());
+
+        let filter = MissionFilter {
+            pinned: Some(true),
+            ..Default::default()
+        };
+        let missions = store.list_missions_filtered(&filter, 50, 0).await.unwrap();
+        assert_eq!(missions.len(), 1);
+        assert!(missions[0].pinned && !missions[0].archived);
+
+        let filter = MissionFilter {
+            tags: vec!["ops".to_string()],
+            archived: Some(true),
+            ..Default::default()
+        };
+        let missions = store.list_missions_filtered(&filter, 50, 0).await.unwrap();
+        assert_eq!(missions.len(), 1);
+        assert_eq!(missions[0].id, archived.id);
+        assert!(missions[0].archived);
+
+        let filter = MissionFilter {
+            archived: Some(false),
+            ..Default::default()
+        };
+        let missions = store.list_missions_filtered(&filter, 50, 0).await.unwrap();
+        assert_eq!(missions.len(), 1);
+        assert_eq!(missions[0].id, pinned.id);
+
+        store
+            .update_mission_assignee(pinned.id, Some("alice"))
+            .await
+            .expect("assign");
+        store
+            .update_mission_watchers(pinned.id, &["alice".to_string(), "bob".to_string()])
+            .await
+            .expect("watchers");
+        let checklist = vec![crate::api::mission_store::ChecklistItem {
+            id: Uuid::new_v4(),
+            text: "Tests pass".to_string(),
+            done: true,
+        }];
+        store
+            .update_mission_checklist(pinned.id, &checklist)
+            .await
+            .expect("checklist");
+        let follow_ups = vec![crate::api::mission_store::FollowUp {
+            kind: crate::api::mission_store::FollowUpKind::Mission,
+            text: "Add tests for the new module".to_string(),
+        }];
+        store
+            .update_mission_follow_ups(pinned.id, &follow_ups)
+            .await
+            .expect("follow-ups");
+        let filter = MissionFilter {
+            assignee: Some("alice".to_string()),
+            ..Default::default()
+        };
+        let missions = store.list_missions_filtered(&filter, 50, 0).await.unwrap();
+        assert_eq!(missions.len(), 1);
+        assert_eq!(missions[0].assignee.as_deref(), Some("alice"));
+        assert_eq!(missions[0].watchers, vec!["alice", "bob"]);
+        assert_eq!(missions[0].checklist, checklist);
+        assert_eq!(missions[0].follow_ups, follow_ups);
+        let unassigned = MissionFilter {
+            assignee: Some(String::new()),
+            ..Default::default()
+        };
+        assert_eq!(store.count_missions_filtered(&unassigned).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn keyset_pagination_walks_every_mission_once() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SqliteMissionStore::new(temp_dir.path().to_path_buf(), "test-user")
+            .await
+            .expect("sqlite store");
+        for i in 0..5 {
+            store
+                .create_mission(
+                    Some(&format!("Mission {i}")),
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                    None,
+                )
+                .await
+                .expect("mission");
+        }
+
+        let mut filter = MissionFilter::default();
+        assert_eq!(store.count_missions_filtered(&filter).await.unwrap(), 5);
+        let mut seen = Vec::new();
+        loop {
+            let page = store.list_missions_filtered(&filter, 2, 0).await.unwrap();
+            let Some(last) = page.last() else { break };
+            filter.before = Some(Keyset {
+                sort_key: last.updated_at.clone(),
+                id: last.id,
+            });
+            seen.extend(page.iter().map(|m| m.id));
+        }
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), 5);
+    }
+
+    #[tokio::test]
+    async fn scheduled_message_transitions_are_conditional() {
+        use crate::api::mission_store::{ScheduledMessage, ScheduledMessageStatus};
+
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SqliteMissionStore::new(temp_dir.path().to_path_buf(), "test-user")
+            .await
+            .expect("sqlite store");
+        let mission = store
+            .create_mission(Some("Deploy"), None, None, None, None, None, None)
+            .await
+            .expect("mission");
+        let message = store
+            .create_scheduled_message(ScheduledMessage {
+                id: uuid::Uuid::new_v4(),
+                mission_id: mission.id,
+                content: "kick off the deploy".to_string(),
+                agent: None,
+                send_at: "2026-01-01T22:00:00+00:00".to_string(),
+                created_at: "2026-01-01T12:00:00+00:00".to_string(),
+                status: ScheduledMessageStatus::Pending,
+                sent_at: None,
+                error: None,
+            })
+            .await
+            .expect("schedule");
+
+        assert!(store
+            .transition_scheduled_message(
+                message.id,
+                ScheduledMessageStatus::Pending,
+                ScheduledMessageStatus::Sent,
+                None,
+            )
+            .await
+            .unwrap());
+        // A cancel racing the dispatch loses.
+        assert!(!store
+            .transition_scheduled_message(
+                message.id,
+                ScheduledMessageStatus::Pending,
+                ScheduledMessageStatus::Cancelled,
+                None,
+            )
+            .await
+            .unwrap());
+
+        let stored = store
+            .get_scheduled_message(message.id)
+            .await
+            .unwrap()
+            .expect("stored message");
+        assert_eq!(stored.status, ScheduledMessageStatus::Sent);
+        assert!(stored.sent_at.is_some());
+        assert!(store
+            .list_scheduled_messages(None, Some(ScheduledMessageStatus::Pending))
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            store
+                .list_scheduled_messages(Some(mission.id), None)
+                .await
+                .unwrap()
+                .len(),
+            1
+        );
+    }
+
+    #[tokio::test]
+    async fn mission_retries_are_stored_against_the_mission() {
+        use crate::api::mission_store::{MissionRetry, MissionRetryPolicy, MissionRetryStatus};
+
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SqliteMissionStore::new(temp_dir.path().to_path_buf(), "test-user")
+            .await
+            .expect("sqlite store");
+        let mission = store
+            .create_mission(Some("Nightly"), None, None, None, None, None, None)
+            .await
+            .expect("mission");
+
+        let policy: MissionRetryPolicy =
+            serde_json::from_value(json!({ "max_attempts": 3, "retry_on": ["llm_error"] }))
+                .unwrap();
+        store
+            .set_mission_retry_policy(mission.id, Some(policy.clone()))
+            .await
+            .unwrap();
+        assert_eq!(
+            store.get_mission_retry_policy(mission.id).await.unwrap(),
+            Some(policy)
+        );
+
+        let retry = store
+            .create_mission_retry(MissionRetry {
+                id: Uuid::new_v4(),
+                mission_id: mission.id,
+                attempt: 1,
+                terminal_reason: Some("llm_error".to_string()),
+                due_at: "2026-01-01T00:01:00+00:00".to_string(),
+                created_at: "2026-01-01T00:00:00+00:00".to_string(),
+                status: MissionRetryStatus::Scheduled,
+                started_at: None,
+                error: None,
+            })
+            .await
+            .unwrap();
+        assert!(store
+            .transition_mission_retry(
+                retry.id,
+                MissionRetryStatus::Scheduled,
+                MissionRetryStatus::Started,
+                None,
+            )
+            .await
+            .unwrap());
+        assert!(!store
+            .transition_mission_retry(
+                retry.id,
+                MissionRetryStatus::Scheduled,
+                MissionRetryStatus::Cancelled,
+                None,
+            )
+            .await
+            .unwrap());
+
+        let retries = store
+            .list_mission_retries(Some(mission.id), None)
+            .await
+            .unwrap();
+        assert_eq!(retries.len(), 1);
+        assert_eq!(retries[0].status, MissionRetryStatus::Started);
+        assert!(retries[0].started_at.is_some());
+
+        store
+            .set_mission_retry_policy(mission.id, None)
+            .await
+            .unwrap();
+        assert!(store
+            .get_mission_retry_policy(mission.id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn executions_link_the_events_of_their_turn() {
+        use crate::api::control::AgentEvent;
+        use crate::api::mission_store::{Automation, AutomationExecution, ExecutionStatus};
+
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SqliteMissionStore::new(temp_dir.path().to_path_buf(), "test-user")
+            .await
+            .expect("sqlite store");
+        let mission = store
+            .create_mission(Some("Nightly"), None, None, None, None, None, None)
+            .await
+            .expect("mission");
+        let user_message = |content: &str| AgentEvent::UserMessage {
+            id: Uuid::new_v4(),
+            content: content.to_string(),
+            queued: false,
+            mission_id: Some(mission.id),
+        };
+        let error = |message: &str| AgentEvent::Error {
+            message: message.to_string(),
+            code: None,
+            mission_id: Some(mission.id),
+            resumable: false,
+        };
+        let automation: Automation = serde_json::from_value(json!({
+            "id": Uuid::new_v4(),
+            "mission_id": mission.id,
+            "command_source": {"type": "inline", "content": "check"},
+            "trigger": {"type": "interval", "seconds": 60},
+            "active": true,
+            "created_at": "2026-01-01T00:00:00+00:00"
+        }))
+        .unwrap();
+        let automation = store.create_automation(automation).await.unwrap();
+        let execution = |status: ExecutionStatus| AutomationExecution {
+            id: Uuid::new_v4(),
+            automation_id: automation.id,
+            mission_id: mission.id,
+            triggered_at: crate::api::mission_store::now_string(),
+            trigger_source: "interval".to_string(),
+            status,
+            webhook_payload: None,
+            variables_used: Default::default(),
+            completed_at: None,
+            error: None,
+            retry_count: 0,
+            first_event_seq: None,
+            last_event_seq: None,
+        };
+
+        for event in [user_message("earlier"), error("earlier reply")] {
+            store.log_event(mission.id, &event).await.unwrap();
+        }
+        store
+            .create_automation_execution(execution(ExecutionStatus::Skipped))
+            .await
+            .unwrap();
+        store
+            .create_automation_execution(execution(ExecutionStatus::Running))
+            .await
+            .unwrap();
+        for event in [
+            user_message("check"),
+            error("first"),
+            error("second"),
+            user_message("later, by hand"),
+        ] {
+            store.log_event(mission.id, &event).await.unwrap();
+        }
+
+        let executions = store
+            .get_automation_executions(automation.id, None, None)
+            .await
+            .unwrap();
+        let run = executions
+            .iter()
+            .find(|e| e.status == ExecutionStatus::Running)
+            .unwrap();
+        assert_eq!(
+            (run.first_event_seq, run.last_event_seq),
+            (Some(3), Some(5))
+        );
+        let skipped = executions
+            .iter()
+            .find(|e| e.status == ExecutionStatus::Skipped)
+            .unwrap();
+        assert_eq!(skipped.first_event_seq, None);
+
+        let events = store
+            .get_events_in_range(mission.id, run.first_event_seq, run.last_event_seq)
+            .await
+            .unwrap();
+        let contents: Vec<_> = events.iter().map(|e| e.content.as_str()).collect();
+        assert_eq!(contents, vec!["check", "first", "second"]);
+    }
+
+    #[tokio::test]
+    async fn runner_heartbeats_track_each_instance() {
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SqliteMissionStore::new(temp_dir.path().to_path_buf(), "test-user")
+            .await
+            .expect("sqlite store");
+        store.ping().await.expect("ping");
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            let mission = store
+                .create_mission(None, None, None, None, None, None, None)
+                .await
+                .expect("mission");
+            ids.push(mission.id);
+        }
+
+        store
+            .record_runner_heartbeats("a", 1, &ids[..2])
             .await
-            .expect("total cost should calculate");
-        assert_eq!(total, 25);
+            .unwrap();
+        store
+            .record_runner_heartbeats("b", 2, &ids[2..])
+            .await
+            .unwrap();
+        assert_eq!(store.list_runner_heartbeats().await.unwrap().len(), 3);
+
+        // Instance "a" finished its first mission; "b" is untouched.
+        store
+            .record_runner_heartbeats("a", 1, &ids[1..2])
+            .await
+            .unwrap();
+        let mut running: Vec<_> = store
+            .list_runner_heartbeats()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|beat| (beat.mission_id, beat.instance_id))
+            .collect();
+        running.sort();
+        let mut expected = vec![(ids[1], "a".to_string()), (ids[2], "b".to_string())];
+        expected.sort();
+        assert_eq!(running, expected);
+
+        store.delete_runner_heartbeat(ids[2]).await.unwrap();
+        assert_eq!(store.list_runner_heartbeats().await.unwrap().len(), 1);
     }
 
     #[tokio::test]
-    async fn get_cost_by_source_groups_by_provenance() {
+    async fn turn_journal_round_trips_and_clears() {
+        use crate::api::mission_store::{now_string, TurnJournalEntry, TurnJournalKind};
+
         let temp_dir = tempfile::tempdir().expect("temp dir");
         let store = SqliteMissionStore::new(temp_dir.path().to_path_buf(), "test-user")
             .await
             .expect("sqlite store");
         let mission = store
-            .create_mission(
-                Some("Cost source mission"),
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-            )
+            .create_mission(None, None, None, None, None, None, None)
             .await
             .expect("mission");
+        let entry = |kind, content: &str| TurnJournalEntry {
+            mission_id: mission.id,
+            kind,
+            tool_call_id: (kind != TurnJournalKind::UserMessage).then(|| "call-1".to_string()),
+            tool_name: (kind != TurnJournalKind::UserMessage).then(|| "bash".to_string()),
+            content: content.to_string(),
+            created_at: now_string(),
+        };
+        for (kind, content) in [
+            (TurnJournalKind::UserMessage, "run the tests"),
+            (TurnJournalKind::ToolCall, "{\"command\":\"cargo test\"}"),
+            (TurnJournalKind::ToolResult, "ok"),
+        ] {
+            store
+                .append_turn_journal(entry(kind, content))
+                .await
+                .unwrap();
+        }
 
-        let conn = store.conn.lock().await;
-        let query = r#"
-            INSERT INTO mission_events (
-                mission_id, sequence, event_type, timestamp, metadata
-            ) VALUES (?1, ?2, 'assistant_message', ?3, ?4)
-        "#;
+        let journal = store.get_turn_journal(mission.id).await.unwrap();
+        let kinds: Vec<_> = journal.iter().map(|entry| entry.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                TurnJournalKind::UserMessage,
+                TurnJournalKind::ToolCall,
+                TurnJournalKind::ToolResult
+            ]
+        );
+        assert_eq!(journal[1].tool_name.as_deref(), Some("bash"));
 
-        // Actual cost
-        conn.execute(
-            query,
-            params![
-                mission.id.to_string(),
-                1i64,
-                "2026-02-22T00:00:00Z",
-                json!({
-                    "cost": { "amount_cents": 100, "source": "actual", "currency": "USD" }
-                })
-                .to_string()
-            ],
-        )
-        .expect("insert actual");
+        store.clear_turn_journal(mission.id).await.unwrap();
+        assert!(store.get_turn_journal(mission.id).await.unwrap().is_empty());
+    }
 
-        // Estimated cost
-        conn.execute(
-            query,
-            params![
-                mission.id.to_string(),
-                2i64,
-                "2026-02-22T00:00:01Z",
-                json!({
-                    "cost": { "amount_cents": 50, "source": "estimated", "currency": "USD" }
-                })
-                .to_string()
-            ],
-        )
-        .expect("insert estimated");
+    #[tokio::test]
+    async fn message_feedback_replaces_and_filters() {
+        use crate::api::mission_store::{now_string, FeedbackRating, MessageFeedback};
 
-        // Unknown cost (explicit)
-        conn.execute(
-            query,
-            params![
-                mission.id.to_string(),
-                3i64,
-                "2026-02-22T00:00:02Z",
-                json!({
-                    "cost": { "amount_cents": 10, "source": "unknown", "currency": "USD" }
-                })
-                .to_string()
-            ],
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SqliteMissionStore::with_cipher(
+            temp_dir.path().to_path_buf(),
+            "test-user",
+            Some(Arc::new(StoreCipher::new(&[5u8; 32]))),
         )
-        .expect("insert unknown");
+        .await
+        .expect("sqlite store");
+        let mut missions = Vec::new();
+        for _ in 0..2 {
+            missions.push(
+                store
+                    .create_mission(None, None, None, None, None, None, None)
+                    .await
+                    .expect("mission"),
+            );
+        }
+        let message_id = uuid::Uuid::new_v4();
+        let feedback = |mission_id, rating, comment: Option<&str>| MessageFeedback {
+            mission_id,
+            message_id,
+            rating,
+            comment: comment.map(str::to_string),
+            backend: "claudecode".to_string(),
+            model: Some("claude-sonnet-4".to_string()),
+            created_at: now_string(),
+        };
+        store
+            .set_message_feedback(feedback(missions[0].id, FeedbackRating::Up, None))
+            .await
+            .unwrap();
+        store
+            .set_message_feedback(feedback(
+                missions[0].id,
+                FeedbackRating::Down,
+                Some("wrong file"),
+            ))
+            .await
+            .unwrap();
+        store
+            .set_message_feedback(feedback(missions[1].id, FeedbackRating::Up, None))
+            .await
+            .unwrap();
 
-        // Legacy cost (no source field → unknown)
-        conn.execute(
-            query,
-            params![
-                mission.id.to_string(),
-                4i64,
-                "2026-02-22T00:00:03Z",
-                json!({ "cost_cents": 5 }).to_string()
-            ],
+        assert_eq!(
+            store.list_message_feedback(None, None).await.unwrap().len(),
+            2
+        );
+        let first = store
+            .list_message_feedback(Some(missions[0].id), None)
+            .await
+            .unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].rating, FeedbackRating::Down);
+        assert_eq!(first[0].comment.as_deref(), Some("wrong file"));
+        assert!(store
+            .list_message_feedback(None, Some("9999-01-01T00:00:00Z"))
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn mission_comments_are_sealed_and_threads_delete_together() {
+        use crate::api::mission_store::{now_string, MissionComment};
+
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SqliteMissionStore::with_cipher(
+            temp_dir.path().to_path_buf(),
+            "test-user",
+            Some(Arc::new(StoreCipher::new(&[6u8; 32]))),
         )
-        .expect("insert legacy");
+        .await
+        .expect("sqlite store");
+        let mission = store
+            .create_mission(None, None, None, None, None, None, None)
+            .await
+            .expect("mission");
+        let comment = |id, thread_id, body: &str| MissionComment {
+            id,
+            mission_id: mission.id,
+            thread_id,
+            author: "alice".to_string(),
+            body: body.to_string(),
+            mentions: vec!["bob".to_string()],
+            created_at: now_string(),
+            updated_at: None,
+        };
+        let root = uuid::Uuid::new_v4();
+        let other = uuid::Uuid::new_v4();
+        store
+            .create_mission_comment(comment(root, root, "secret plan"))
+            .await
+            .unwrap();
+        store
+            .create_mission_comment(comment(uuid::Uuid::new_v4(), root, "reply"))
+            .await
+            .unwrap();
+        store
+            .create_mission_comment(comment(other, other, "another thread"))
+            .await
+            .unwrap();
 
-        drop(conn);
+        {
+            let conn = store.conn.lock().await;
+            let body: String = conn
+                .query_row(
+                    "SELECT body FROM mission_comments WHERE id = ?1",
+                    params![root.to_string()],
+                    |row| row.get(0),
+                )
+                .unwrap();
+            assert!(!body.contains("secret plan"));
+        }
 
-        let (actual, estimated, unknown) = store
-            .get_cost_by_source()
+        assert!(store
+            .update_mission_comment(root, "edited", &[], &now_string())
             .await
-            .expect("cost by source should calculate");
-        assert_eq!(actual, 100);
-        assert_eq!(estimated, 50);
-        // Unknown (10) + legacy (5) both go into the unknown bucket
-        assert_eq!(unknown, 15);
+            .unwrap());
+        let edited = store.get_mission_comment(root).await.unwrap().unwrap();
+        assert_eq!(edited.body, "edited");
+        assert!(edited.mentions.is_empty());
+        assert!(edited.updated_at.is_some());
+        assert_eq!(
+            store.list_mission_comments(mission.id).await.unwrap().len(),
+            3
+        );
+
+        assert_eq!(store.delete_mission_comment(root).await.unwrap(), 2);
+        let remaining = store.list_mission_comments(mission.id).await.unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].id, other);
+        assert_eq!(remaining[0].mentions, vec!["bob"]);
     }
 
     #[tokio::test]
-    async fn get_cost_since_filters_by_timestamp() {
+    async fn answered_questions_join_mission_history() {
+        use crate::api::control::AgentEvent;
+        use crate::api::user_question::parse_questions;
+
         let temp_dir = tempfile::tempdir().expect("temp dir");
         let store = SqliteMissionStore::new(temp_dir.path().to_path_buf(), "test-user")
             .await
             .expect("sqlite store");
         let mission = store
-            .create_mission(
-                Some("Period cost mission"),
-                None,
-                None,
-                None,
-                None,
-                None,
-                None,
-            )
+            .create_mission(None, None, None, None, None, None, None)
             .await
             .expect("mission");
+        let questions = parse_questions(&serde_json::json!({
+            "questions": [{"question": "Which database?", "options": ["Postgres", "SQLite"]}]
+        }));
+        store
+            .log_event(
+                mission.id,
+                &AgentEvent::UserQuestionAnswered {
+                    tool_call_id: "call-1".to_string(),
+                    questions,
+                    answers: serde_json::json!({"Which database?": "SQLite"}),
+                    timed_out: false,
+                    mission_id: Some(mission.id),
+                },
+            )
+            .await
+            .unwrap();
 
-        let conn = store.conn.lock().await;
-        let query = r#"
-            INSERT INTO mission_events (
-                mission_id, sequence, event_type, timestamp, metadata
-            ) VALUES (?1, ?2, 'assistant_message', ?3, ?4)
-        "#;
+        let history = store
+            .get_mission(mission.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .history;
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].role, "user");
+        assert!(history[0].content.contains("- Which database?\n  SQLite"));
+    }
 
-        // Old event — outside the "since" window
-        conn.execute(
-            query,
-            params![
-                mission.id.to_string(),
-                1i64,
-                "2026-02-01T00:00:00Z",
-                json!({
-                    "cost": { "amount_cents": 200, "source": "actual" }
-                })
-                .to_string()
-            ],
-        )
-        .expect("insert old actual");
+    #[tokio::test]
+    async fn tool_results_join_mission_history() {
+        use crate::api::control::AgentEvent;
 
-        // Recent events — inside the "since" window
-        conn.execute(
-            query,
-            params![
-                mission.id.to_string(),
-                2i64,
-                "2026-02-20T00:00:00Z",
-                json!({
-                    "cost": { "amount_cents": 30, "source": "actual" }
-                })
-                .to_string()
-            ],
-        )
-        .expect("insert recent actual");
-        conn.execute(
-            query,
-            params![
-                mission.id.to_string(),
-                3i64,
-                "2026-02-21T00:00:00Z",
-                json!({
-                    "cost": { "amount_cents": 15, "source": "estimated" }
-                })
-                .to_string()
-            ],
-        )
-        .expect("insert recent estimated");
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let store = SqliteMissionStore::new(temp_dir.path().to_path_buf(), "test-user")
+            .await
+            .expect("sqlite store");
+        let mission = store
+            .create_mission(None, None, None, None, None, None, None)
+            .await
+            .expect("mission");
+        for event in [
+            AgentEvent::ToolCall {
+                tool_call_id: "call-1".to_string(),
+                name: "read_file".to_string(),
+                args: serde_json::json!({"path": "src/main.rs"}),
+                mission_id: Some(mission.id),
+            },
+            AgentEvent::ToolResult {
+                tool_call_id: "call-1".to_string(),
+                name: "read_file".to_string(),
+                result: serde_json::json!("x".repeat(5_000)),
+                mission_id: Some(mission.id),
+            },
+        ] {
+            store.log_event(mission.id, &event).await.unwrap();
+        }
 
-        drop(conn);
+        let history = store
+            .get_mission(mission.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .history;
+        assert_eq!(history.len(), 1);
+        let entry = &history[0];
+        assert_eq!(entry.role, "tool");
+        assert_eq!(entry.tool_name.as_deref(), Some("read_file"));
+        assert!(entry
+            .content
+            .starts_with("{\"path\":\"src/main.rs\"} -> xxx"));
+        assert!(entry.content.ends_with("... [truncated]"));
+        assert!(entry.content.len() < 2_000);
+        assert!(!entry.is_conversation());
+        assert_eq!(
+            entry.context_pair().1,
+            format!("read_file: {}", entry.content)
+        );
+    }
 
-        // All-time totals
-        let all_total = store.get_total_cost_cents().await.expect("all-time total");
-        assert_eq!(all_total, 245); // 200 + 30 + 15
+    #[tokio::test]
+    async fn encryption_migrates_plaintext_and_round_trips() {
+        use crate::api::control::AgentEvent;
 
-        // Period-filtered: since 2026-02-15 should only include the two recent events
-        let since = "2026-02-15T00:00:00Z";
-        let period_total = store
-            .get_total_cost_cents_since(since)
+        let temp_dir = tempfile::tempdir().expect("temp dir");
+        let base = temp_dir.path().to_path_buf();
+        let plain = SqliteMissionStore::with_cipher(base.clone(), "test-user", None)
             .await
-            .expect("period total");
-        assert_eq!(period_total, 45); // 30 + 15
+            .expect("sqlite store");
+        let mission = plain
+            .create_mission(None, None, None, None, None, None, None)
+            .await
+            .expect("mission");
+        let message = |content: &str| AgentEvent::UserMessage {
+            id: uuid::Uuid::new_v4(),
+            content: content.to_string(),
+            queued: false,
+            mission_id: Some(mission.id),
+        };
+        plain
+            .log_event(mission.id, &message("legacy secret"))
+            .await
+            .unwrap();
+        drop(plain);
 
-        let (actual, estimated, unknown) = store
-            .get_cost_by_source_since(since)
+        let store = SqliteMissionStore::with_cipher(
+            base.clone(),
+            "test-user",
+            Some(Arc::new(StoreCipher::new(&[3u8; 32]))),
+        )
+        .await
+        .expect("encrypted store");
+        store
+            .log_event(mission.id, &message("new secret"))
             .await
-            .expect("period cost by source");
-        assert_eq!(actual, 30);
-        assert_eq!(estimated, 15);
-        assert_eq!(unknown, 0);
+            .unwrap();
+
+        let raw: Vec<String> = {
+            let conn = rusqlite::Connection::open(base.join("missions-test-user.db")).unwrap();
+            let mut stmt = conn
+                .prepare("SELECT content FROM mission_events ORDER BY sequence")
+                .unwrap();
+            let rows = stmt.query_map([], |row| row.get(0)).unwrap();
+            rows.collect::<Result<_, _>>().unwrap()
+        };
+        assert_eq!(raw.len(), 2);
+        assert!(raw.iter().all(|c| crypto::is_encrypted(c)));
+
+        let events = store
+            .get_events(mission.id, None, None, None)
+            .await
+            .unwrap();
+        let contents: Vec<_> = events.iter().map(|e| e.content.as_str()).collect();
+        assert_eq!(contents, vec!["legacy secret", "new secret"]);
+        let history = store
+            .get_mission(mission.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .history;
+        assert_eq!(history[1].content, "new secret");
     }
 }
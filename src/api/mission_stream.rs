@@ -0,0 +1,502 @@
+//! Mission-scoped event streams.
+//!
+//! `GET /api/missions/:id/stream`, and the session stream with
+//! `?missions=<id>,<id>`, send only the events of the given missions. They
+//! listen on the per-mission channels of the session's event log, so a client
+//! watching one mission isn't woken up by the others. Resuming with
+//! `Last-Event-ID` works like the session stream: buffered events come from
+//! the log, older ones from the mission store, all tagged `"replayed": true`.
+//!
+//! A client opening `GET /api/missions/:id/stream` without `Last-Event-ID`
+//! first gets a preamble: a `history` event with the mission's conversation,
+//! then its latest stored events, then whatever the log holds that is not
+//! stored yet, before the stream goes live.
+
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{HeaderMap, StatusCode},
+    response::sse::{Event, KeepAlive, Sse},
+    Extension,
+};
+use futures::stream::{self, Stream, StreamExt};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::control::{AgentEvent, ControlState};
+use super::event_bus::{self, EventGap, EventLog, LogItem};
+use super::event_schema;
+use super::mission_store::{conversation_history, StoredEvent};
+use super::routes::AppState;
+use crate::util::internal_error;
+
+/// Most stored events replayed to a reconnecting client.
+const REPLAY_LIMIT: usize = 5000;
+/// Events read from the log per batch while catching up.
+const CATCH_UP_BATCH: usize = 256;
+/// Stored events in the preamble of a new mission stream by default.
+const DEFAULT_PREAMBLE_EVENTS: usize = 200;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MissionStreamQuery {
+    /// Event schema version the client understands (defaults to the current one)
+    #[serde(default)]
+    pub schema_version: Option<u32>,
+    /// Whether a new stream starts with the `history` event (default: true)
+    #[serde(default)]
+    pub history: Option<bool>,
+    /// Stored events replayed before going live (default: 200, `0` for none)
+    #[serde(default)]
+    pub recent_events: Option<usize>,
+}
+
+/// What a mission stream replays before going live.
+#[derive(Debug, Default)]
+pub struct Preamble {
+    /// Payload of the `history` event
+    pub history: Option<Value>,
+    /// Stored events, oldest first
+    pub events: Vec<StoredEvent>,
+}
+
+/// Where a mission stream starts.
+#[derive(Debug)]
+pub enum StreamStart {
+    /// With the next event
+    Live,
+    /// After the event with this cursor's previous sequence number
+    /// (`Last-Event-ID`)
+    Resume(u64),
+    /// With a preamble of the mission's stored history
+    Preamble(Preamble),
+}
+
+impl StreamStart {
+    pub fn resume_or_live(resume_from: Option<u64>) -> Self {
+        resume_from.map_or(StreamStart::Live, StreamStart::Resume)
+    }
+}
+
+/// Parse a comma-separated list of mission ids.
+pub fn parse_mission_ids(value: &str) -> Result<Vec<Uuid>, (StatusCode, String)> {
+    let ids = value
+        .split(',')
+        .map(str::trim)
+        .filter(|id| !id.is_empty())
+        .map(|id| {
+            id.parse::<Uuid>().map_err(|_| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    format!("Invalid mission id: {}", id),
+                )
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if ids.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "missions must list at least one mission id".to_string(),
+        ));
+    }
+    Ok(ids)
+}
+
+fn live_event(seq: u64, event: &AgentEvent, live_from: u64, schema_version: u32) -> Option<Event> {
+    let payload = if seq < live_from {
+        event_bus::replayed_event(event)
+    } else {
+        serde_json::to_value(event).unwrap_or_else(|_| serde_json::json!({}))
+    };
+    let payload = event_schema::for_client(event.event_name(), payload, schema_version)?;
+    Event::default()
+        .id(seq.to_string())
+        .event(event.event_name())
+        .json_data(payload)
+        .map_err(|e| tracing::error!(error = %e, "Failed to serialize mission SSE event"))
+        .ok()
+}
+
+fn stored_event(stored: &StoredEvent, schema_version: u32) -> Option<Event> {
+    let payload = event_schema::for_client(
+        &stored.event_type,
+        event_bus::replayed_stored_event(stored),
+        schema_version,
+    )?;
+    let event = match stored.stream_seq {
+        Some(seq) => Event::default().id(seq.to_string()),
+        None => Event::default(),
+    };
+    event
+        .event(stored.event_type.clone())
+        .json_data(payload)
+        .ok()
+}
+
+fn gap_event(gap: &EventGap) -> Option<Event> {
+    Event::default()
+        .id(gap.to_seq.to_string())
+        .event("gap")
+        .json_data(gap)
+        .ok()
+}
+
+/// Where the log takes over from a preamble's stored events: after the last
+/// one, since the logger may not have written newer events yet.
+fn preamble_cursor(events: &[StoredEvent], first_buffered: u64) -> u64 {
+    events
+        .iter()
+        .filter_map(|e| e.stream_seq)
+        .max()
+        .map_or(0, |seq| seq + 1)
+        .max(first_buffered)
+}
+
+/// What a mission stream has sent, so catching up after a lag doesn't repeat
+/// events the live channels already delivered.
+#[derive(Debug)]
+struct SentCursor {
+    /// Where the next catch-up reads the log from
+    read_from: u64,
+    /// Per mission, the sequence number after the last event sent
+    missions: HashMap<Uuid, u64>,
+    /// Sequence number after the last gap sent (gaps reach every mission
+    /// channel)
+    gaps: u64,
+}
+
+impl SentCursor {
+    fn new(start: u64) -> Self {
+        Self {
+            read_from: start,
+            missions: HashMap::new(),
+            gaps: 0,
+        }
+    }
+
+    /// Whether `item` wasn't sent yet; records it if so.
+    fn admit(&mut self, item: &LogItem) -> bool {
+        match item {
+            LogItem::Event { seq, event } => {
+                let Some(mission_id) = event.mission_id() else {
+                    return false;
+                };
+                if self
+                    .missions
+                    .get(&mission_id)
+                    .is_some_and(|next| seq < next)
+                {
+                    return false;
+                }
+                self.missions.insert(mission_id, seq + 1);
+                true
+            }
+            LogItem::Gap(gap) => {
+                if gap.to_seq < self.gaps {
+                    return false;
+                }
+                self.gaps = gap.to_seq + 1;
+                true
+            }
+        }
+    }
+}
+
+/// The log items of `missions` from `cursor` on: what the log holds first,
+/// then the mission channels, catching up from the log again whenever a
+/// channel lags.
+fn mission_log_items(
+    log: Arc<EventLog>,
+    missions: Vec<Uuid>,
+    receivers: Vec<broadcast::Receiver<LogItem>>,
+    cursor: u64,
+) -> impl Stream<Item = LogItem> {
+    let live = stream::select_all(receivers.into_iter().map(|rx| {
+        stream::unfold(rx, |mut rx| async move {
+            match rx.recv().await {
+                Err(broadcast::error::RecvError::Closed) => None,
+                result => Some((result, rx)),
+            }
+        })
+        .boxed()
+    }));
+
+    async_stream::stream! {
+        let mut sent = SentCursor::new(cursor);
+        let mut live = live;
+        let mut catch_up = true;
+        loop {
+            if catch_up {
+                // Read what the log holds for these missions, up to its head.
+                loop {
+                    let (items, next) =
+                        log.read_missions_from(sent.read_from, &missions, CATCH_UP_BATCH);
+                    let done = next == sent.read_from;
+                    for item in items {
+                        if sent.admit(&item) {
+                            yield item;
+                        }
+                    }
+                    sent.read_from = next;
+                    if done {
+                        break;
+                    }
+                }
+                catch_up = false;
+            }
+
+            let Some(received) = live.next().await else {
+                break;
+            };
+            match received {
+                Ok(item) => {
+                    // Live items before `read_from` were sent by the catch-up.
+                    let seq = match &item {
+                        LogItem::Event { seq, .. } => *seq,
+                        LogItem::Gap(gap) => gap.to_seq,
+                    };
+                    if seq >= sent.read_from && sent.admit(&item) {
+                        yield item;
+                    }
+                }
+                // Lagged: the log still has (or reports as a gap) what this
+                // channel dropped.
+                Err(_) => catch_up = true,
+            }
+        }
+    }
+}
+
+/// SSE stream of the events of `missions` from `start`.
+pub async fn mission_events_sse(
+    control: &ControlState,
+    missions: Vec<Uuid>,
+    start: StreamStart,
+    schema_version: u32,
+    keepalive: std::time::Duration,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let log = Arc::clone(&control.event_log);
+    // Subscribe before reading the log so nothing falls in between.
+    let receivers: Vec<_> = missions
+        .iter()
+        .map(|mission_id| log.subscribe_mission(*mission_id))
+        .collect();
+    let live_from = log.next_seq();
+    let mut cursor = live_from;
+    let mut history = None;
+    let mut backfill = Vec::new();
+    match start {
+        StreamStart::Live => {}
+        StreamStart::Resume(from) => {
+            cursor = from;
+            // Events that already left the ring are backfilled from the mission store.
+            let first_buffered = log.first_seq();
+            if from < first_buffered && control.mission_store.is_persistent() {
+                match control
+                    .mission_store
+                    .get_events_after_stream_seq(from.saturating_sub(1), REPLAY_LIMIT)
+                    .await
+                {
+                    Ok(events) => {
+                        backfill = events
+                            .into_iter()
+                            .filter(|e| missions.contains(&e.mission_id))
+                            .filter(|e| e.stream_seq.is_some_and(|seq| seq < first_buffered))
+                            .collect();
+                        cursor = first_buffered;
+                    }
+                    Err(e) => {
+                        tracing::warn!(error = %e, "Failed to load stored events for mission SSE replay");
+                    }
+                }
+            }
+        }
+        StreamStart::Preamble(preamble) => {
+            cursor = preamble_cursor(&preamble.events, log.first_seq());
+            history = preamble
+                .history
+                .and_then(|payload| event_schema::for_client("history", payload, schema_version));
+            backfill = preamble.events;
+        }
+    }
+
+    let items = mission_log_items(log, missions, receivers, cursor);
+    let stream = async_stream::stream! {
+        if let Some(sse) = history.and_then(|payload| Event::default().event("history").json_data(payload).ok()) {
+            yield Ok(sse);
+        }
+        for stored in backfill {
+            if let Some(sse) = stored_event(&stored, schema_version) {
+                yield Ok(sse);
+            }
+        }
+
+        let mut items = std::pin::pin!(items);
+        while let Some(item) = items.next().await {
+            let sse = match &item {
+                LogItem::Event { seq, event } => live_event(*seq, event, live_from, schema_version),
+                LogItem::Gap(gap) => gap_event(gap),
+            };
+            if let Some(sse) = sse {
+                yield Ok(sse);
+            }
+        }
+    };
+
+    Sse::new(stream).keep_alive(KeepAlive::new().interval(keepalive).text("keepalive"))
+}
+
+/// GET /api/missions/:id/stream - Stream one mission's events via SSE,
+/// starting with its history unless the client resumes.
+pub async fn stream_mission(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+    headers: HeaderMap,
+    Query(query): Query<MissionStreamQuery>,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, (StatusCode, String)> {
+    let schema_version = event_schema::validate_version(
+        query
+            .schema_version
+            .unwrap_or(event_schema::CURRENT_VERSION),
+    )
+    .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    let control = state.control.get_or_spawn(&user).await;
+    let Some(mission) = control
+        .mission_store
+        .get_mission(mission_id)
+        .await
+        .map_err(internal_error)?
+    else {
+        return Err((StatusCode::NOT_FOUND, "Mission not found".to_string()));
+    };
+    let resume_from =
+        event_bus::resume_cursor(headers.get("last-event-id").and_then(|v| v.to_str().ok()));
+    let start = match resume_from {
+        Some(from) => StreamStart::Resume(from),
+        None => {
+            let history = match query.history.unwrap_or(true) {
+                true => Some(json!({
+                    "type": "history",
+                    "mission_id": mission.id,
+                    "history": conversation_history(&mission.history),
+                    "replayed": true,
+                })),
+                false => None,
+            };
+            let limit = query
+                .recent_events
+                .unwrap_or(DEFAULT_PREAMBLE_EVENTS)
+                .min(REPLAY_LIMIT);
+            let events = if limit > 0 {
+                control
+                    .mission_store
+                    .get_recent_events(mission_id, limit)
+                    .await
+                    .map_err(internal_error)?
+            } else {
+                Vec::new()
+            };
+            StreamStart::Preamble(Preamble { history, events })
+        }
+    };
+    Ok(mission_events_sse(
+        &control,
+        vec![mission_id],
+        start,
+        schema_version,
+        state.config.stream.keepalive_interval(),
+    )
+    .await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mission_id_lists_are_parsed() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        assert_eq!(
+            parse_mission_ids(&format!("{}, {},", a, b)).unwrap(),
+            vec![a, b]
+        );
+        assert_eq!(
+            parse_mission_ids("nope").unwrap_err().0,
+            StatusCode::BAD_REQUEST
+        );
+        assert!(parse_mission_ids(" , ").is_err());
+    }
+
+    #[tokio::test]
+    async fn catching_up_after_a_lag_sends_each_event_once() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let message = |mission_id| AgentEvent::TextDelta {
+            content: "hi".to_string(),
+            mission_id: Some(mission_id),
+        };
+        let log = Arc::new(EventLog::new(4096));
+        let missions = vec![a, b];
+        let receivers = missions
+            .iter()
+            .map(|id| log.subscribe_mission(*id))
+            .collect();
+        let items = mission_log_items(Arc::clone(&log), missions, receivers, log.next_seq());
+        let mut items = std::pin::pin!(items);
+
+        // A few events arrive live, then more than a channel holds.
+        for _ in 0..3 {
+            log.push(message(a));
+        }
+        let mut seqs = Vec::new();
+        for _ in 0..3 {
+            if let Some(LogItem::Event { seq, .. }) = items.next().await {
+                seqs.push(seq);
+            }
+        }
+        for n in 0..600 {
+            log.push(message(if n % 2 == 0 { a } else { b }));
+        }
+        let last = log.next_seq() - 1;
+        while seqs.last() != Some(&last) {
+            let item = tokio::time::timeout(std::time::Duration::from_secs(5), items.next())
+                .await
+                .unwrap()
+                .unwrap();
+            match item {
+                LogItem::Event { seq, .. } => seqs.push(seq),
+                LogItem::Gap(gap) => panic!("unexpected gap {:?}", gap),
+            }
+        }
+
+        let expected: Vec<u64> = (0..=last).collect();
+        assert_eq!(seqs, expected);
+    }
+
+    #[test]
+    fn preamble_hands_over_to_the_log_after_the_last_stored_event() {
+        let stored = |stream_seq| StoredEvent {
+            id: 1,
+            mission_id: Uuid::nil(),
+            sequence: 1,
+            event_type: "user_message".to_string(),
+            timestamp: String::new(),
+            event_id: None,
+            tool_call_id: None,
+            tool_name: None,
+            content: String::new(),
+            metadata: Value::Null,
+            stream_seq,
+            schema_version: event_schema::CURRENT_VERSION,
+        };
+        assert_eq!(preamble_cursor(&[stored(Some(40)), stored(None)], 10), 41);
+        // Stored events older than the log: replay everything buffered
+        assert_eq!(preamble_cursor(&[stored(Some(4))], 10), 10);
+        assert_eq!(preamble_cursor(&[], 10), 10);
+    }
+}
@@ -6,6 +6,7 @@
 //! - `GET /api/task/{id}` - Get task status and result
 //! - `GET /api/task/{id}/stream` - Stream task progress via SSE
 //! - `GET /api/health` - Health check
+//! - `GET /api/health/detailed` - Runner liveness, store, MCP and provider health
 //! - `GET /api/providers` - List available providers
 //! - `GET /api/mcp` - List all MCP servers
 //! - `POST /api/mcp` - Add a new MCP server
@@ -14,35 +15,121 @@
 //! - `POST /api/mcp/{id}/disable` - Disable an MCP server
 //! - `GET /api/tools` - List all tools (built-in + MCP)
 //! - `POST /api/tools/{name}/toggle` - Enable/disable a tool
+//! - `GET /api/remote-tools` - List remote (HTTP) tools
+//! - `POST /api/remote-tools` - Register a remote tool
+//! - `POST /api/remote-tools/{name}/call` - Call a remote tool via the server
+//! - `GET /api/rate-limits` - Rate limit budgets and current usage
+//! - `POST /api/control/missions/{id}/upload` - Stream files into a mission workspace
+//! - `GET /api/missions?tag=..&status=..&limit=..&cursor=..` - List missions (cursor-paginated, `X-Total-Count`/`X-Next-Cursor` headers)
+//! - `PUT /api/control/missions/{id}/tags` - Replace a mission's tags
+//! - `POST /api/control/missions/{id}/pin` / `unpin` / `archive` / `unarchive` - Mission flags
+//! - `POST /api/control/missions/{id}/assign` / `unassign` / `watch` / `unwatch`, `PUT .../watchers` - Mission assignee and watchers
+//! - `POST /api/control/missions/archive` - Archive missions in bulk by age and status
+//! - `POST /api/control/missions/{id}/scheduled-messages` - Schedule a message for later
+//! - `GET /api/control/missions/{id}/scheduled-messages` - List a mission's scheduled messages
+//! - `GET /api/control/scheduled-messages?status=..` - List scheduled messages (pending by default)
+//! - `DELETE /api/control/scheduled-messages/{id}` - Cancel a pending scheduled message
+//! - `GET/PUT/DELETE /api/control/missions/{id}/retry-policy` - Automatic retry policy for failed runs
+//! - `GET /api/control/missions/{id}/retries` - A mission's automatic retries
+//! - `GET /api/missions/{id}/timeline` - Mission events folded into phases
+//! - `GET /api/missions/{id}/cost-breakdown` - Cost per agent-tree node and tool call
+//! - `GET /api/missions/{id}/report` - Structured report of a completed mission
+//! - `GET /api/missions/compare?a={id}&b={id}` - Side-by-side comparison of two missions
+//! - `GET/POST /api/missions/{id}/presence` - Who is viewing (or composing in) a mission
+//! - `GET/POST /api/missions/{id}/comments`, `PATCH/DELETE .../comments/{comment_id}` - Mission discussion threads
+//! - `POST /api/workspaces/{id}/calendar` - Subscription link for the workspace's automation calendar (`GET /api/calendar/{token}.ics`, no auth)
+//! - `POST /api/missions/{id}/share` - Create an expiring, redacted read-only link (`GET /api/share/{token}`, no auth)
+//! - `POST /api/admin/eval` - Run an evaluation suite (`GET /api/admin/eval/{id}` for the report)
+//! - `POST /api/admin/store/maintenance` - Checkpoint, vacuum and analyze the mission stores (`GET /api/admin/store/integrity` for an integrity report)
 
 pub mod ai_providers;
 pub mod ampcode;
+mod audit;
 mod auth;
+mod automation_calendar;
+mod automation_throttle;
 pub mod automation_variables;
 pub mod backends;
+mod best_of_n;
+mod body_limit;
 pub mod claudecode;
+mod client;
+mod command_approval;
+mod completion_check;
 mod console;
+mod context_usage;
 pub mod control;
+#[cfg(unix)]
+mod control_socket;
+mod cost_breakdown;
 pub mod deferred_proxy;
 pub mod desktop;
 mod desktop_stream;
+mod email_gateway;
+pub mod eval;
+mod event_bus;
+mod event_schema;
+mod file_conflicts;
+mod file_preview;
+mod follow_ups;
 mod fs;
+mod handoff;
+pub mod headless;
+mod health;
+mod idempotency;
 pub mod library;
+mod llm_client;
 pub mod mcp;
+mod message_feedback;
+mod metadata_llm;
+mod metadata_refresh;
+mod mission_assignment;
+mod mission_checklist;
+mod mission_comments;
+mod mission_compare;
+mod mission_environment;
+mod mission_export;
+mod mission_files;
+mod mission_reads;
+mod mission_report;
+mod mission_retry;
 pub mod mission_runner;
+mod mission_share;
+mod mission_state;
 pub mod mission_store;
+mod mission_stream;
 mod model_routing;
 mod monitoring;
+mod notifications;
 pub mod opencode;
+mod pagination;
+mod partial_turn;
+mod presence;
 mod providers;
 mod proxy;
 mod proxy_keys;
+mod rate_limit;
+mod remote_tools;
 mod routes;
+mod scheduled_messages;
 pub mod secrets;
 pub mod settings;
+mod slack;
+mod stall_watch;
+mod status_requests;
+mod stop_policy_preview;
+mod store_maintenance;
 pub mod system;
+mod task_supervisor;
+mod telegram;
+mod thinking_log;
+mod timeline;
+mod turn_journal;
 pub mod types;
+mod user_data;
+pub mod user_question;
 pub mod workspaces;
 
-pub use routes::serve;
+pub use client::{MissionClient, MissionOptions, Turn};
+pub use routes::{init_state, serve, AppState};
 pub use types::*;
@@ -462,8 +462,12 @@ pub async fn monitoring_ws(
             Some(t) => t,
             None => return (StatusCode::UNAUTHORIZED, "Missing websocket JWT").into_response(),
         };
-        if !auth::verify_token_for_config(&token, &state.config) {
+        let Some(user) = auth::user_for_token(&token, &state.config) else {
             return (StatusCode::UNAUTHORIZED, "Invalid or expired token").into_response();
+        };
+        // Host metrics are outside tenant isolation
+        if user.tenant.is_some() {
+            return (StatusCode::FORBIDDEN, "Not available to tenant users").into_response();
         }
     }
 
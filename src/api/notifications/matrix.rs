@@ -0,0 +1,77 @@
+//! Matrix plugin: posts each notification to a room as an `m.notice`.
+//!
+//! Settings: `homeserver` (e.g. `https://matrix.example.com`), `room_id`
+//! (`!abc:example.com`) and `access_token` of the bot account, which must
+//! already have joined the room.
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use uuid::Uuid;
+
+use super::{Notification, NotificationPlugin, PluginConfig};
+
+pub struct MatrixPlugin {
+    config: PluginConfig,
+    name: String,
+    homeserver: String,
+    room_id: String,
+    access_token: String,
+}
+
+impl MatrixPlugin {
+    pub fn from_config(config: &PluginConfig) -> Result<Box<dyn NotificationPlugin>, String> {
+        Ok(Box::new(Self {
+            config: config.clone(),
+            name: config.display_name(),
+            homeserver: config
+                .setting("homeserver")?
+                .trim_end_matches('/')
+                .to_string(),
+            room_id: config.setting("room_id")?,
+            access_token: config.setting("access_token")?,
+        }))
+    }
+}
+
+#[async_trait]
+impl NotificationPlugin for MatrixPlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn accepts(&self, notification: &Notification) -> bool {
+        self.config.accepts(notification)
+    }
+
+    fn format(&self, notification: &Notification) -> serde_json::Value {
+        serde_json::json!({
+            "msgtype": "m.notice",
+            "body": notification.plain_text(),
+        })
+    }
+
+    async fn send(
+        &self,
+        client: &reqwest::Client,
+        payload: serde_json::Value,
+    ) -> Result<(), String> {
+        // Every message needs a transaction id unique to this access token
+        let url = format!(
+            "{}/_matrix/client/v3/rooms/{}/send/m.room.message/{}",
+            self.homeserver,
+            urlencoding::encode(&self.room_id),
+            Uuid::new_v4()
+        );
+        client
+            .put(&url)
+            .bearer_auth(&self.access_token)
+            .json(&payload)
+            .timeout(Duration::from_secs(15))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
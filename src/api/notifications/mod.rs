@@ -0,0 +1,421 @@
+//! Notification plugins for chat platforms.
+//!
+//! Rather than one hand-written integration per platform, outbound
+//! notifications go through [`NotificationPlugin`]s with a small delivery
+//! contract: [`accepts`](NotificationPlugin::accepts) filters events,
+//! [`format`](NotificationPlugin::format) turns one into the platform's
+//! payload and [`send`](NotificationPlugin::send) delivers it.
+//!
+//! Plugins are configured in `.sandboxed-sh/notifications.json` (override the
+//! path with `SANDBOXED_SH_NOTIFICATIONS_FILE`) and loaded at startup:
+//!
+//! ```json
+//! [
+//!   { "type": "webhook", "url": "https://example.com/hook", "secret": "..." },
+//!   {
+//!     "type": "matrix",
+//!     "name": "ops-room",
+//!     "events": ["mission_failed", "approval_required"],
+//!     "homeserver": "https://matrix.example.com",
+//!     "room_id": "!abc:example.com",
+//!     "access_token": "..."
+//!   }
+//! ]
+//! ```
+//!
+//! `type` selects a factory in the [`NotificationRegistry`]; the remaining
+//! keys are handed to it. `events` limits delivery to some notification kinds
+//! (`mission_<status>` such as `mission_completed`, `user_question` and
+//! `approval_required`); all kinds are delivered when it is omitted. New
+//! platforms register a factory with [`NotificationRegistry::register`].
+
+mod matrix;
+mod webhook;
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::control::{AgentEvent, MissionStatus};
+use super::routes::AppState;
+use crate::i18n::{self, Catalog};
+
+pub use matrix::MatrixPlugin;
+pub use webhook::WebhookPlugin;
+
+/// A mission event worth telling someone about, independent of platform.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Notification {
+    /// `mission_<status>`, `user_question` or `approval_required`
+    pub kind: String,
+    pub mission_id: Uuid,
+    pub mission_title: Option<String>,
+    /// One-line, human-readable description
+    pub text: String,
+    /// Longer details (summary, question, command), if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+    /// Dashboard link to the mission
+    pub url: String,
+}
+
+impl Notification {
+    /// Build a notification from an agent event, worded in the mission's
+    /// language, or `None` for events that aren't notified.
+    pub fn from_event(
+        event: &AgentEvent,
+        mission_title: Option<&str>,
+        public_url: &str,
+        catalog: &Catalog,
+    ) -> Option<Self> {
+        let untitled = catalog.text("notification.untitled", &[]);
+        let title = mission_title.unwrap_or(&untitled);
+        let (kind, mission_id, text, details) = match event {
+            AgentEvent::MissionStatusChanged {
+                mission_id,
+                status,
+                summary,
+            } if !matches!(status, MissionStatus::Pending | MissionStatus::Active) => (
+                format!("mission_{}", status),
+                *mission_id,
+                catalog.text(
+                    "notification.mission_status",
+                    &[("title", title), ("status", &catalog.status(status))],
+                ),
+                summary.clone().filter(|s| !s.trim().is_empty()),
+            ),
+            AgentEvent::UserQuestion {
+                questions,
+                mission_id: Some(mission_id),
+                ..
+            } => (
+                "user_question".to_string(),
+                *mission_id,
+                catalog.text("notification.user_question", &[("title", title)]),
+                Some(
+                    questions
+                        .iter()
+                        .map(|q| q.question.as_str())
+                        .collect::<Vec<_>>()
+                        .join("\n"),
+                ),
+            ),
+            AgentEvent::ApprovalRequired {
+                command,
+                reason,
+                mission_id,
+                ..
+            } => (
+                "approval_required".to_string(),
+                *mission_id,
+                catalog.text(
+                    "notification.approval_required",
+                    &[("title", title), ("reason", reason)],
+                ),
+                Some(command.clone()),
+            ),
+            _ => return None,
+        };
+        Some(Self {
+            kind,
+            mission_id,
+            mission_title: mission_title.map(str::to_string),
+            text,
+            details,
+            url: crate::util::mission_url(public_url, mission_id),
+        })
+    }
+
+    /// Plain-text rendering shared by chat plugins.
+    pub fn plain_text(&self) -> String {
+        let mut text = self.text.clone();
+        if let Some(details) = &self.details {
+            text.push_str("\n\n");
+            text.push_str(details.trim());
+        }
+        text.push_str("\n\n");
+        text.push_str(&self.url);
+        text
+    }
+}
+
+/// A destination for notifications.
+#[async_trait]
+pub trait NotificationPlugin: Send + Sync {
+    /// Name used in logs.
+    fn name(&self) -> &str;
+
+    /// Whether the notification should be delivered.
+    fn accepts(&self, notification: &Notification) -> bool;
+
+    /// The platform payload for a notification.
+    fn format(&self, notification: &Notification) -> serde_json::Value;
+
+    /// Deliver a formatted payload.
+    async fn send(
+        &self,
+        client: &reqwest::Client,
+        payload: serde_json::Value,
+    ) -> Result<(), String>;
+}
+
+/// Settings every plugin entry shares.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PluginConfig {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    /// Notification kinds to deliver (all when empty)
+    #[serde(default)]
+    pub events: Vec<String>,
+    /// Plugin-specific settings
+    #[serde(flatten)]
+    pub settings: serde_json::Map<String, serde_json::Value>,
+}
+
+impl PluginConfig {
+    /// The display name, defaulting to the plugin type.
+    pub fn display_name(&self) -> String {
+        self.name.clone().unwrap_or_else(|| self.kind.clone())
+    }
+
+    /// The `events` filter.
+    pub fn accepts(&self, notification: &Notification) -> bool {
+        self.events.is_empty() || self.events.contains(&notification.kind)
+    }
+
+    /// A required string setting.
+    pub fn setting(&self, key: &str) -> Result<String, String> {
+        self.settings
+            .get(key)
+            .and_then(|v| v.as_str())
+            .map(|v| v.trim().to_string())
+            .filter(|v| !v.is_empty())
+            .ok_or_else(|| format!("missing \"{}\"", key))
+    }
+}
+
+/// Builds a plugin from its configuration.
+pub type PluginFactory = fn(&PluginConfig) -> Result<Box<dyn NotificationPlugin>, String>;
+
+/// Plugin factories by `type`.
+pub struct NotificationRegistry {
+    factories: HashMap<String, PluginFactory>,
+}
+
+impl Default for NotificationRegistry {
+    /// A registry with the built-in `webhook` and `matrix` plugins.
+    fn default() -> Self {
+        let mut registry = Self {
+            factories: HashMap::new(),
+        };
+        registry.register("webhook", WebhookPlugin::from_config);
+        registry.register("matrix", MatrixPlugin::from_config);
+        registry
+    }
+}
+
+impl NotificationRegistry {
+    pub fn register(&mut self, kind: &str, factory: PluginFactory) {
+        self.factories.insert(kind.to_string(), factory);
+    }
+
+    /// Build the configured plugins. Entries of unknown types or with invalid
+    /// settings are skipped with a warning.
+    pub fn build(&self, configs: &[PluginConfig]) -> Vec<Box<dyn NotificationPlugin>> {
+        configs
+            .iter()
+            .filter_map(|config| {
+                let Some(factory) = self.factories.get(&config.kind) else {
+                    tracing::warn!(
+                        plugin = %config.display_name(),
+                        "Ignoring notification plugin of unknown type {}",
+                        config.kind
+                    );
+                    return None;
+                };
+                match factory(config) {
+                    Ok(plugin) => Some(plugin),
+                    Err(e) => {
+                        tracing::warn!(
+                            plugin = %config.display_name(),
+                            "Ignoring misconfigured notification plugin: {}",
+                            e
+                        );
+                        None
+                    }
+                }
+            })
+            .collect()
+    }
+}
+
+fn config_path(working_dir: &Path) -> PathBuf {
+    std::env::var("SANDBOXED_SH_NOTIFICATIONS_FILE")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .map(PathBuf::from)
+        .unwrap_or_else(|| working_dir.join(".sandboxed-sh/notifications.json"))
+}
+
+/// Read the plugin configuration; a missing file means no plugins.
+pub fn load_config(path: &Path) -> Result<Vec<PluginConfig>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str(&contents)
+            .map_err(|e| format!("Invalid {}: {}", path.display(), e)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(format!("Failed to read {}: {}", path.display(), e)),
+    }
+}
+
+/// Load the configured plugins and deliver the instance user's mission
+/// notifications to them for as long as the server runs.
+pub fn start(state: Arc<AppState>) {
+    let path = config_path(&state.config.working_dir);
+    let configs = match load_config(&path) {
+        Ok(configs) => configs,
+        Err(e) => {
+            tracing::warn!("Notification plugins disabled: {}", e);
+            return;
+        }
+    };
+    let plugins: Vec<Arc<dyn NotificationPlugin>> = NotificationRegistry::default()
+        .build(&configs)
+        .into_iter()
+        .map(Arc::from)
+        .collect();
+    if plugins.is_empty() {
+        return;
+    }
+    tracing::info!("Loaded {} notification plugin(s)", plugins.len());
+    let public_url = std::env::var("SANDBOXED_SH_PUBLIC_URL").unwrap_or_default();
+
+    tokio::spawn(async move {
+        let control = state
+            .control
+            .get_or_spawn(&AuthUser::local(&state.config))
+            .await;
+        let mut events = control.events_tx.subscribe();
+        loop {
+            let event = match events.recv().await {
+                Ok(event) => event,
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    tracing::warn!(
+                        missed,
+                        "Notification dispatcher fell behind; some events were not delivered"
+                    );
+                    continue;
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            };
+            let Some(mission_id) = event.mission_id() else {
+                continue;
+            };
+            // Cheap check before looking up the mission
+            if Notification::from_event(&event, None, &public_url, i18n::catalog("en")).is_none() {
+                continue;
+            }
+            let mission = control
+                .mission_store
+                .get_mission(mission_id)
+                .await
+                .ok()
+                .flatten();
+            let catalog = i18n::catalog(
+                &mission
+                    .as_ref()
+                    .map(|m| i18n::workspace_locale(m.workspace_id))
+                    .unwrap_or_else(i18n::default_locale),
+            );
+            let title = mission.and_then(|m| m.title);
+            let Some(notification) =
+                Notification::from_event(&event, title.as_deref(), &public_url, catalog)
+            else {
+                continue;
+            };
+            for plugin in &plugins {
+                if !plugin.accepts(&notification) {
+                    continue;
+                }
+                let payload = plugin.format(&notification);
+                let plugin = Arc::clone(plugin);
+                let client = state.http_client.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = plugin.send(&client, payload).await {
+                        tracing::warn!(plugin = %plugin.name(), "Failed to deliver notification: {}", e);
+                    }
+                });
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_become_notifications() {
+        let mission_id = Uuid::new_v4();
+        let event = AgentEvent::MissionStatusChanged {
+            mission_id,
+            status: MissionStatus::Failed,
+            summary: Some("Tests failed".to_string()),
+        };
+        let notification = Notification::from_event(
+            &event,
+            Some("Fix CI"),
+            "https://box.example.com/",
+            i18n::catalog("en"),
+        )
+        .unwrap();
+        assert_eq!(notification.kind, "mission_failed");
+        assert_eq!(notification.text, "Mission \"Fix CI\" failed");
+        assert_eq!(
+            notification.url,
+            format!("https://box.example.com/control?mission={}", mission_id)
+        );
+        assert!(notification.plain_text().contains("Tests failed"));
+
+        let active = AgentEvent::MissionStatusChanged {
+            mission_id,
+            status: MissionStatus::Active,
+            summary: None,
+        };
+        assert!(Notification::from_event(&active, None, "", i18n::catalog("en")).is_none());
+
+        let german = Notification::from_event(&event, None, "", i18n::catalog("de")).unwrap();
+        assert_eq!(german.text, "Mission „Unbenannte Mission“ fehlgeschlagen");
+    }
+
+    #[test]
+    fn registry_builds_configured_plugins() {
+        let configs: Vec<PluginConfig> = serde_json::from_value(serde_json::json!([
+            { "type": "webhook", "url": "https://example.com/hook", "events": ["mission_failed"] },
+            { "type": "matrix", "homeserver": "https://matrix.example.com", "room_id": "!r:example.com", "access_token": "t" },
+            { "type": "matrix", "room_id": "!r:example.com" },
+            { "type": "irc" }
+        ]))
+        .unwrap();
+        let plugins = NotificationRegistry::default().build(&configs);
+        assert_eq!(plugins.len(), 2);
+
+        let notification = Notification {
+            kind: "mission_completed".to_string(),
+            mission_id: Uuid::new_v4(),
+            mission_title: None,
+            text: "done".to_string(),
+            details: None,
+            url: String::new(),
+        };
+        assert!(!plugins[0].accepts(&notification));
+        assert!(plugins[1].accepts(&notification));
+    }
+}
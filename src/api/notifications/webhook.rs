@@ -0,0 +1,98 @@
+//! Generic webhook plugin: POSTs each notification as JSON.
+//!
+//! Settings: `url` (required), `secret` (signs the body with HMAC-SHA256 in
+//! `X-Webhook-Signature: sha256=<hex>`, the format inbound webhooks accept)
+//! and `headers` (extra request headers).
+
+use std::time::Duration;
+
+use async_trait::async_trait;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use super::{Notification, NotificationPlugin, PluginConfig};
+
+const SIGNATURE_HEADER: &str = "x-webhook-signature";
+
+pub struct WebhookPlugin {
+    config: PluginConfig,
+    name: String,
+    url: String,
+    secret: Option<String>,
+    headers: Vec<(String, String)>,
+}
+
+impl WebhookPlugin {
+    pub fn from_config(config: &PluginConfig) -> Result<Box<dyn NotificationPlugin>, String> {
+        let url = config.setting("url")?;
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            return Err(format!("\"url\" must be an http(s) URL, got {}", url));
+        }
+        let headers = config
+            .settings
+            .get("headers")
+            .and_then(|v| v.as_object())
+            .map(|headers| {
+                headers
+                    .iter()
+                    .filter_map(|(k, v)| Some((k.clone(), v.as_str()?.to_string())))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(Box::new(Self {
+            config: config.clone(),
+            name: config.display_name(),
+            url,
+            secret: config.setting("secret").ok(),
+            headers,
+        }))
+    }
+}
+
+/// `sha256=<hex>` HMAC of the body.
+fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+#[async_trait]
+impl NotificationPlugin for WebhookPlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn accepts(&self, notification: &Notification) -> bool {
+        self.config.accepts(notification)
+    }
+
+    fn format(&self, notification: &Notification) -> serde_json::Value {
+        serde_json::to_value(notification).unwrap_or_default()
+    }
+
+    async fn send(
+        &self,
+        client: &reqwest::Client,
+        payload: serde_json::Value,
+    ) -> Result<(), String> {
+        let body = serde_json::to_vec(&payload).map_err(|e| e.to_string())?;
+        let mut request = client
+            .post(&self.url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .timeout(Duration::from_secs(15));
+        for (name, value) in &self.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        if let Some(secret) = &self.secret {
+            request = request.header(SIGNATURE_HEADER, sign(secret, &body));
+        }
+        request
+            .body(body)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+}
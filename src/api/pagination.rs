@@ -0,0 +1,173 @@
+//! Cursor pagination for list endpoints.
+//!
+//! Paginated lists accept `?limit=` and `?cursor=` and still return a plain
+//! JSON array, so existing clients keep working. Paging metadata travels in
+//! response headers:
+//!
+//! - `X-Total-Count` - rows matching the query, ignoring the cursor
+//! - `X-Next-Cursor` - cursor for the next page, absent on the last page
+//!
+//! Cursors are opaque base64url-encoded keysets over (timestamp, id), so pages
+//! stay stable while rows are inserted or updated between requests.
+
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+use axum::Json;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use super::mission_store::Keyset;
+
+pub const TOTAL_COUNT_HEADER: &str = "x-total-count";
+pub const NEXT_CURSOR_HEADER: &str = "x-next-cursor";
+
+/// A page of results with its pagination headers.
+pub type Page<T> = (HeaderMap, Json<Vec<T>>);
+
+/// Largest page a client can request.
+pub const MAX_PAGE_SIZE: usize = 500;
+
+/// Common `limit`/`cursor` query parameters.
+#[derive(Debug, Default, Deserialize)]
+pub struct PageQuery {
+    pub limit: Option<usize>,
+    pub cursor: Option<String>,
+}
+
+/// Position in a paginated list.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Cursor {
+    #[serde(flatten)]
+    pub keyset: Keyset,
+    /// Whether the keyset falls in the pinned section (mission lists only)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub pinned: bool,
+}
+
+impl Cursor {
+    pub fn new(keyset: Keyset) -> Self {
+        Self {
+            keyset,
+            pinned: false,
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        URL_SAFE_NO_PAD.encode(serde_json::to_vec(self).unwrap_or_default())
+    }
+
+    pub fn decode(value: &str) -> Result<Self, (StatusCode, String)> {
+        URL_SAFE_NO_PAD
+            .decode(value.trim())
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .ok_or_else(|| (StatusCode::BAD_REQUEST, "Invalid cursor".to_string()))
+    }
+}
+
+impl PageQuery {
+    /// Requested page size, defaulting to `default` and capped at [`MAX_PAGE_SIZE`].
+    pub fn limit(&self, default: usize) -> usize {
+        self.limit.unwrap_or(default).clamp(1, MAX_PAGE_SIZE)
+    }
+
+    pub fn cursor(&self) -> Result<Option<Cursor>, (StatusCode, String)> {
+        self.cursor.as_deref().map(Cursor::decode).transpose()
+    }
+}
+
+/// Trim a page fetched with `limit + 1` rows down to `limit`, returning the
+/// keyset of the last kept row when more rows follow.
+pub fn split_page<T>(
+    mut items: Vec<T>,
+    limit: usize,
+    key: impl Fn(&T) -> Keyset,
+) -> (Vec<T>, Option<Keyset>) {
+    if items.len() <= limit {
+        return (items, None);
+    }
+    items.truncate(limit);
+    let next = items.last().map(key);
+    (items, next)
+}
+
+/// Page through an in-memory list ordered by `key` descending.
+pub fn paginate<T>(
+    mut items: Vec<T>,
+    after: Option<&Keyset>,
+    limit: usize,
+    key: impl Fn(&T) -> Keyset,
+) -> (Vec<T>, Option<Keyset>) {
+    items.sort_by_cached_key(|item| {
+        let keyset = key(item);
+        std::cmp::Reverse((keyset.sort_key, keyset.id))
+    });
+    let items = items
+        .into_iter()
+        .filter(|item| {
+            let keyset = key(item);
+            after
+                .iter()
+                .all(|bound| bound.precedes(&keyset.sort_key, keyset.id))
+        })
+        .take(limit + 1)
+        .collect();
+    split_page(items, limit, key)
+}
+
+/// `X-Total-Count` / `X-Next-Cursor` headers for a page.
+pub fn page_headers(total: usize, next: Option<&Cursor>) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(TOTAL_COUNT_HEADER, HeaderValue::from(total));
+    if let Some(value) = next.and_then(|cursor| HeaderValue::from_str(&cursor.encode()).ok()) {
+        headers.insert(NEXT_CURSOR_HEADER, value);
+    }
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn cursor_round_trips_and_rejects_garbage() {
+        let cursor = Cursor {
+            keyset: Keyset {
+                sort_key: "2026-01-01T00:00:00Z".to_string(),
+                id: Uuid::new_v4(),
+            },
+            pinned: true,
+        };
+        assert_eq!(Cursor::decode(&cursor.encode()).unwrap(), cursor);
+        assert_eq!(
+            Cursor::decode("not a cursor").unwrap_err().0,
+            StatusCode::BAD_REQUEST
+        );
+    }
+
+    #[test]
+    fn paginate_walks_ties_by_id() {
+        let items: Vec<Keyset> = (0..5)
+            .map(|i| Keyset {
+                sort_key: if i < 3 { "b" } else { "a" }.to_string(),
+                id: Uuid::new_v4(),
+            })
+            .collect();
+
+        let mut seen = Vec::new();
+        let mut after = None;
+        loop {
+            let (page, next) = paginate(items.clone(), after.as_ref(), 2, Keyset::clone);
+            seen.extend(page);
+            match next {
+                Some(next) => after = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(seen.len(), items.len());
+        assert!(seen
+            .windows(2)
+            .all(|w| (&w[0].sort_key, w[0].id) > (&w[1].sort_key, w[1].id)));
+    }
+}
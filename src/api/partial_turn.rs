@@ -0,0 +1,285 @@
+//! Partial results of cancelled turns.
+//!
+//! Streaming content (`text_delta`, `thinking`) is only shown live: a turn's
+//! reply reaches history once the turn finishes, so cancelling a mission used
+//! to drop everything the agent had written so far. [`PartialTurns`] follows
+//! each running turn on its own subscription to the control stream. When the
+//! turn is cancelled, the content accumulated so far becomes the turn's final
+//! assistant message, flagged `cancelled: true` and carrying the tool calls
+//! that completed before the cancellation.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use super::control::AgentEvent;
+
+/// A tool call that returned before its turn was cancelled.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PartialToolCall {
+    pub tool_call_id: String,
+    pub name: String,
+}
+
+/// Content streamed by a turn that has not finished yet.
+#[derive(Debug, Default)]
+pub struct PartialTurn {
+    text: String,
+    /// Thinking blocks, each holding its accumulated content
+    thinking: Vec<String>,
+    /// Whether the last thinking block is still streaming
+    thinking_open: bool,
+    /// Calls issued but not answered yet, by tool call id
+    pending_tool_calls: HashMap<String, String>,
+    pub tool_calls: Vec<PartialToolCall>,
+}
+
+impl PartialTurn {
+    /// Whether the turn produced anything worth keeping.
+    pub fn is_empty(&self) -> bool {
+        self.content().is_empty() && self.tool_calls.is_empty()
+    }
+
+    /// Message content: the streamed reply, or the reasoning when the turn
+    /// was cancelled before replying.
+    pub fn content(&self) -> String {
+        if self.text.trim().is_empty() {
+            self.thinking.join("\n\n").trim().to_string()
+        } else {
+            self.text.trim().to_string()
+        }
+    }
+
+    /// Content recorded in mission history, so a resumed turn knows which
+    /// tool calls already ran.
+    pub fn history_content(&self) -> String {
+        let mut content = self.content();
+        if !self.tool_calls.is_empty() {
+            let names: Vec<&str> = self.tool_calls.iter().map(|c| c.name.as_str()).collect();
+            if !content.is_empty() {
+                content.push_str("\n\n");
+            }
+            content.push_str(&format!(
+                "[Cancelled after completing tool calls: {}]",
+                names.join(", ")
+            ));
+        }
+        content
+    }
+}
+
+/// Partial turns of every running mission.
+pub struct PartialTurns {
+    events_rx: broadcast::Receiver<AgentEvent>,
+    turns: HashMap<Uuid, PartialTurn>,
+}
+
+impl PartialTurns {
+    pub fn new(events_rx: broadcast::Receiver<AgentEvent>) -> Self {
+        Self {
+            events_rx,
+            turns: HashMap::new(),
+        }
+    }
+
+    /// Fold every event published so far.
+    pub fn sync(&mut self) {
+        loop {
+            match self.events_rx.try_recv() {
+                Ok(event) => self.observe(&event),
+                Err(broadcast::error::TryRecvError::Lagged(skipped)) => {
+                    tracing::warn!("Partial turn tracking skipped {} events", skipped);
+                }
+                Err(_) => break,
+            }
+        }
+    }
+
+    /// Take the partial turn of a mission whose turn was just cancelled.
+    /// Returns `None` when it produced nothing.
+    pub fn take(&mut self, mission_id: Uuid) -> Option<PartialTurn> {
+        self.sync();
+        self.turns
+            .remove(&mission_id)
+            .filter(|turn| !turn.is_empty())
+    }
+
+    fn observe(&mut self, event: &AgentEvent) {
+        match event {
+            AgentEvent::UserMessage {
+                queued: false,
+                mission_id: Some(mission_id),
+                ..
+            } => {
+                self.turns.insert(*mission_id, PartialTurn::default());
+            }
+            AgentEvent::AssistantMessage {
+                mission_id: Some(mission_id),
+                ..
+            } => {
+                self.turns.remove(mission_id);
+            }
+            AgentEvent::TextDelta {
+                content,
+                mission_id: Some(mission_id),
+            } => {
+                // Deltas carry the accumulated text
+                self.turns.entry(*mission_id).or_default().text = content.clone();
+            }
+            AgentEvent::Thinking {
+                content,
+                done,
+                mission_id: Some(mission_id),
+            } => {
+                // Chunks carry their block's accumulated content
+                let turn = self.turns.entry(*mission_id).or_default();
+                if !content.is_empty() {
+                    match turn.thinking.last_mut() {
+                        Some(block) if turn.thinking_open => *block = content.clone(),
+                        _ => turn.thinking.push(content.clone()),
+                    }
+                    turn.thinking_open = true;
+                }
+                if *done {
+                    turn.thinking_open = false;
+                }
+            }
+            AgentEvent::ToolCall {
+                tool_call_id,
+                name,
+                mission_id: Some(mission_id),
+                ..
+            } => {
+                self.turns
+                    .entry(*mission_id)
+                    .or_default()
+                    .pending_tool_calls
+                    .insert(tool_call_id.clone(), name.clone());
+            }
+            AgentEvent::ToolResult {
+                tool_call_id,
+                name,
+                mission_id: Some(mission_id),
+                ..
+            } => {
+                let turn = self.turns.entry(*mission_id).or_default();
+                let name = turn
+                    .pending_tool_calls
+                    .remove(tool_call_id)
+                    .unwrap_or_else(|| name.clone());
+                turn.tool_calls.push(PartialToolCall {
+                    tool_call_id: tool_call_id.clone(),
+                    name,
+                });
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn collects_streamed_content_and_completed_calls() {
+        let (events_tx, events_rx) = broadcast::channel(16);
+        let mut turns = PartialTurns::new(events_rx);
+        let id = Uuid::new_v4();
+        let mission_id = Some(id);
+        let events = [
+            AgentEvent::UserMessage {
+                id: Uuid::new_v4(),
+                content: "fix the build".to_string(),
+                queued: false,
+                mission_id,
+            },
+            AgentEvent::Thinking {
+                content: "Look at the logs".to_string(),
+                done: false,
+                mission_id,
+            },
+            AgentEvent::ToolCall {
+                tool_call_id: "c1".to_string(),
+                name: "bash".to_string(),
+                args: serde_json::json!({"command": "cargo build"}),
+                mission_id,
+            },
+            AgentEvent::ToolResult {
+                tool_call_id: "c1".to_string(),
+                name: "bash".to_string(),
+                result: serde_json::json!("error[E0308]"),
+                mission_id,
+            },
+            AgentEvent::ToolCall {
+                tool_call_id: "c2".to_string(),
+                name: "edit".to_string(),
+                args: serde_json::json!({}),
+                mission_id,
+            },
+            AgentEvent::TextDelta {
+                content: "The build".to_string(),
+                mission_id,
+            },
+            AgentEvent::TextDelta {
+                content: "The build fails on a type mismatch".to_string(),
+                mission_id,
+            },
+        ];
+        for event in events {
+            events_tx.send(event).unwrap();
+        }
+
+        let turn = turns.take(id).unwrap();
+        assert_eq!(turn.content(), "The build fails on a type mismatch");
+        assert_eq!(
+            turn.tool_calls,
+            vec![PartialToolCall {
+                tool_call_id: "c1".to_string(),
+                name: "bash".to_string(),
+            }]
+        );
+        assert_eq!(
+            turn.history_content(),
+            "The build fails on a type mismatch\n\n[Cancelled after completing tool calls: bash]"
+        );
+        assert!(turns.take(id).is_none());
+    }
+
+    #[test]
+    fn falls_back_to_thinking_and_skips_empty_turns() {
+        let (events_tx, events_rx) = broadcast::channel(16);
+        let mut turns = PartialTurns::new(events_rx);
+        let mission_id = Uuid::new_v4();
+        events_tx
+            .send(AgentEvent::UserMessage {
+                id: Uuid::new_v4(),
+                content: "hi".to_string(),
+                queued: false,
+                mission_id: Some(mission_id),
+            })
+            .unwrap();
+        assert!(turns.take(mission_id).is_none());
+
+        for (chunk, done) in [
+            ("Checking", false),
+            ("Checking the repo", false),
+            ("", true),
+            ("Found it", false),
+        ] {
+            events_tx
+                .send(AgentEvent::Thinking {
+                    content: chunk.to_string(),
+                    done,
+                    mission_id: Some(mission_id),
+                })
+                .unwrap();
+        }
+        assert_eq!(
+            turns.take(mission_id).unwrap().content(),
+            "Checking the repo\n\nFound it"
+        );
+    }
+}
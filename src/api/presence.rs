@@ -0,0 +1,313 @@
+//! Who is watching a mission.
+//!
+//! Browser tabs (and teammates sharing a session) announce themselves with
+//! `POST /api/missions/:id/presence`, sending `viewing` as a heartbeat and
+//! `composing` while the user types a message. Whenever the set of viewers or
+//! their composing flags change, the session broadcasts an
+//! [`AgentEvent::Presence`] with the full list; `GET /api/missions/:id/presence`
+//! returns the same snapshot for clients that just connected.
+//!
+//! Presence is kept in memory only. Clients that stop sending heartbeats drop
+//! out after [`PRESENCE_TTL`], and a composing flag that is not refreshed
+//! falls back to viewing after [`COMPOSING_TTL`].
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{broadcast, RwLock};
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::control::{control_for_mission, AgentEvent};
+use super::mission_store::now_string;
+use super::routes::AppState;
+
+/// Viewers without a heartbeat for this long are dropped.
+pub const PRESENCE_TTL: Duration = Duration::from_secs(30);
+
+/// Composing flags not refreshed for this long fall back to viewing.
+pub const COMPOSING_TTL: Duration = Duration::from_secs(10);
+
+/// How often expired viewers are swept.
+const SWEEP_INTERVAL: Duration = Duration::from_secs(5);
+
+const MAX_CLIENT_ID_LEN: usize = 128;
+
+pub type SharedPresence = Arc<RwLock<PresenceTracker>>;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PresenceState {
+    #[default]
+    Viewing,
+    Composing,
+    /// The client closed the mission (tab closed, navigated away)
+    Left,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Viewer {
+    pub client_id: String,
+    pub username: String,
+    pub composing: bool,
+    /// When the client first announced itself
+    pub since: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PresenceRequest {
+    /// Identifies the tab or device; stable across its heartbeats
+    pub client_id: String,
+    #[serde(default)]
+    pub state: PresenceState,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PresenceSnapshot {
+    pub mission_id: Uuid,
+    pub viewers: Vec<Viewer>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    viewer: Viewer,
+    seen: Instant,
+    composing_seen: Option<Instant>,
+}
+
+/// Viewers per mission.
+#[derive(Debug, Default)]
+pub struct PresenceTracker {
+    missions: HashMap<Uuid, HashMap<String, Entry>>,
+}
+
+impl PresenceTracker {
+    /// Record a client's announcement. Returns whether the mission's viewers
+    /// changed.
+    pub fn announce(
+        &mut self,
+        mission_id: Uuid,
+        client_id: &str,
+        username: &str,
+        state: PresenceState,
+        now: Instant,
+    ) -> bool {
+        if state == PresenceState::Left {
+            let Some(clients) = self.missions.get_mut(&mission_id) else {
+                return false;
+            };
+            let removed = clients.remove(client_id).is_some();
+            if clients.is_empty() {
+                self.missions.remove(&mission_id);
+            }
+            return removed;
+        }
+
+        let composing = state == PresenceState::Composing;
+        let clients = self.missions.entry(mission_id).or_default();
+        match clients.get_mut(client_id) {
+            Some(entry) => {
+                entry.seen = now;
+                entry.composing_seen = composing.then_some(now);
+                let changed = entry.viewer.composing != composing;
+                entry.viewer.composing = composing;
+                changed
+            }
+            None => {
+                clients.insert(
+                    client_id.to_string(),
+                    Entry {
+                        viewer: Viewer {
+                            client_id: client_id.to_string(),
+                            username: username.to_string(),
+                            composing,
+                            since: now_string(),
+                        },
+                        seen: now,
+                        composing_seen: composing.then_some(now),
+                    },
+                );
+                true
+            }
+        }
+    }
+
+    /// Drop silent clients and stale composing flags. Returns the missions
+    /// whose viewers changed.
+    pub fn expire(&mut self, now: Instant) -> Vec<Uuid> {
+        let mut changed = Vec::new();
+        for (mission_id, clients) in &mut self.missions {
+            let before = clients.len();
+            clients.retain(|_, entry| now.duration_since(entry.seen) < PRESENCE_TTL);
+            let mut mission_changed = clients.len() != before;
+            for entry in clients.values_mut() {
+                if entry
+                    .composing_seen
+                    .is_some_and(|at| now.duration_since(at) >= COMPOSING_TTL)
+                {
+                    entry.composing_seen = None;
+                    entry.viewer.composing = false;
+                    mission_changed = true;
+                }
+            }
+            if mission_changed {
+                changed.push(*mission_id);
+            }
+        }
+        self.missions.retain(|_, clients| !clients.is_empty());
+        changed
+    }
+
+    /// A mission's viewers, longest-present first.
+    pub fn viewers(&self, mission_id: Uuid) -> Vec<Viewer> {
+        let mut viewers: Vec<Viewer> = self
+            .missions
+            .get(&mission_id)
+            .map(|clients| clients.values().map(|e| e.viewer.clone()).collect())
+            .unwrap_or_default();
+        viewers.sort_by(|a, b| {
+            a.since
+                .cmp(&b.since)
+                .then_with(|| a.client_id.cmp(&b.client_id))
+        });
+        viewers
+    }
+}
+
+/// Background task that expires viewers and broadcasts the changes.
+pub async fn presence_loop(presence: SharedPresence, events_tx: broadcast::Sender<AgentEvent>) {
+    let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+    loop {
+        interval.tick().await;
+        let mut tracker = presence.write().await;
+        for mission_id in tracker.expire(Instant::now()) {
+            let _ = events_tx.send(AgentEvent::Presence {
+                mission_id,
+                viewers: tracker.viewers(mission_id),
+            });
+        }
+    }
+}
+
+/// GET /api/missions/:id/presence - Who is viewing the mission.
+pub async fn get_presence(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+) -> Result<Json<PresenceSnapshot>, (StatusCode, String)> {
+    let control = control_for_mission(&state, &user, mission_id).await?;
+    let viewers = control.presence.read().await.viewers(mission_id);
+    Ok(Json(PresenceSnapshot {
+        mission_id,
+        viewers,
+    }))
+}
+
+/// POST /api/missions/:id/presence - Announce a client viewing or composing.
+pub async fn announce_presence(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+    Json(req): Json<PresenceRequest>,
+) -> Result<Json<PresenceSnapshot>, (StatusCode, String)> {
+    let client_id = req.client_id.trim();
+    if client_id.is_empty() || client_id.len() > MAX_CLIENT_ID_LEN {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!(
+                "client_id must be between 1 and {} characters",
+                MAX_CLIENT_ID_LEN
+            ),
+        ));
+    }
+    let control = control_for_mission(&state, &user, mission_id).await?;
+
+    let mut tracker = control.presence.write().await;
+    let changed = tracker.announce(
+        mission_id,
+        client_id,
+        &user.username,
+        req.state,
+        Instant::now(),
+    );
+    let viewers = tracker.viewers(mission_id);
+    if changed {
+        let _ = control.events_tx.send(AgentEvent::Presence {
+            mission_id,
+            viewers: viewers.clone(),
+        });
+    }
+    Ok(Json(PresenceSnapshot {
+        mission_id,
+        viewers,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn announcements_report_changes_only() {
+        let mut tracker = PresenceTracker::default();
+        let mission_id = Uuid::new_v4();
+        let now = Instant::now();
+
+        assert!(tracker.announce(mission_id, "tab-1", "alice", PresenceState::Viewing, now));
+        // Heartbeats keep the viewer without changing anything
+        assert!(!tracker.announce(mission_id, "tab-1", "alice", PresenceState::Viewing, now));
+        assert!(tracker.announce(mission_id, "tab-1", "alice", PresenceState::Composing, now));
+        assert!(tracker.announce(mission_id, "tab-2", "bob", PresenceState::Viewing, now));
+
+        let viewers = tracker.viewers(mission_id);
+        assert_eq!(viewers.len(), 2);
+        assert!(viewers
+            .iter()
+            .any(|v| v.client_id == "tab-1" && v.composing));
+
+        assert!(tracker.announce(mission_id, "tab-2", "bob", PresenceState::Left, now));
+        assert!(!tracker.announce(mission_id, "tab-2", "bob", PresenceState::Left, now));
+        assert_eq!(tracker.viewers(mission_id).len(), 1);
+        assert!(tracker.viewers(Uuid::new_v4()).is_empty());
+    }
+
+    #[test]
+    fn silent_clients_and_stale_composing_expire() {
+        let mut tracker = PresenceTracker::default();
+        let mission_id = Uuid::new_v4();
+        let start = Instant::now();
+        tracker.announce(
+            mission_id,
+            "tab-1",
+            "alice",
+            PresenceState::Composing,
+            start,
+        );
+        tracker.announce(mission_id, "tab-2", "bob", PresenceState::Viewing, start);
+
+        assert!(tracker.expire(start + Duration::from_secs(1)).is_empty());
+
+        // tab-2 keeps sending heartbeats, tab-1 goes quiet
+        let later = start + COMPOSING_TTL;
+        tracker.announce(mission_id, "tab-2", "bob", PresenceState::Viewing, later);
+        assert_eq!(tracker.expire(later), vec![mission_id]);
+        let viewers = tracker.viewers(mission_id);
+        assert_eq!(viewers.len(), 2);
+        assert!(viewers.iter().all(|v| !v.composing));
+
+        assert_eq!(tracker.expire(start + PRESENCE_TTL), vec![mission_id]);
+        let viewers = tracker.viewers(mission_id);
+        assert_eq!(viewers.len(), 1);
+        assert_eq!(viewers[0].client_id, "tab-2");
+
+        assert_eq!(tracker.expire(later + PRESENCE_TTL), vec![mission_id]);
+        assert!(tracker.missions.is_empty());
+    }
+}
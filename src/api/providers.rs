@@ -1033,6 +1033,17 @@ pub async fn list_backend_model_options(
     State(state): State<Arc<AppState>>,
     Query(query): Query<BackendModelsQuery>,
 ) -> Json<BackendModelOptionsResponse> {
+    Json(BackendModelOptionsResponse {
+        backends: backend_model_options(&state, query.include_all).await,
+    })
+}
+
+/// Model options keyed by backend id. Unless `include_all` is set, only
+/// providers with credentials contribute models.
+pub(super) async fn backend_model_options(
+    state: &AppState,
+    include_all: bool,
+) -> std::collections::HashMap<String, Vec<BackendModelOption>> {
     let working_dir = state.config.working_dir.to_string_lossy().to_string();
     let mut config = load_providers_config(&working_dir);
 
@@ -1046,7 +1057,7 @@ pub async fn list_backend_model_options(
     drop(cached);
 
     let configured = get_configured_provider_ids(state.config.working_dir.as_path());
-    let mut providers = if query.include_all {
+    let mut providers = if include_all {
         config.providers
     } else {
         config
@@ -1064,7 +1075,7 @@ pub async fn list_backend_model_options(
         if !provider.enabled || default_provider_ids.contains(&provider.provider_type.id()) {
             continue;
         }
-        if !query.include_all && !provider.has_credentials() {
+        if !include_all && !provider.has_credentials() {
             continue;
         }
         // Use the canonical provider type ID for known types, sanitized name for Custom
@@ -1144,13 +1155,32 @@ pub async fn list_backend_model_options(
     push_options("opencode", None, true, None);
     backends.entry("amp".to_string()).or_default();
 
+    // Ollama models come from the local daemon; an unreachable daemon just
+    // means no options.
+    let ollama = crate::backend::ollama::OllamaClient::new(&state.config.ollama_base_url);
+    let ollama_options = match ollama.list_models().await {
+        Ok(models) => models
+            .into_iter()
+            .map(|model| BackendModelOption {
+                label: format!("Ollama — {}", model.name),
+                value: model.name,
+                description: None,
+                provider_id: None,
+            })
+            .collect(),
+        Err(e) => {
+            tracing::debug!(error = %e, "Skipping Ollama model options");
+            Vec::new()
+        }
+    };
+    backends.insert("ollama".to_string(), ollama_options);
+
     let codex_candidates: Vec<String> = backends
         .get("codex")
         .map(|opts| opts.iter().map(|o| o.value.clone()).collect())
         .unwrap_or_default();
     if !codex_candidates.is_empty() {
-        if let Some(visible_models) = resolve_visible_codex_models(&state, &codex_candidates).await
-        {
+        if let Some(visible_models) = resolve_visible_codex_models(state, &codex_candidates).await {
             if let Some(options) = backends.get_mut("codex") {
                 let before = options.len();
                 options.retain(|opt| visible_models.contains(&opt.value));
@@ -1187,7 +1217,7 @@ pub async fn list_backend_model_options(
         *opencode_opts = chain_options;
     }
 
-    Json(BackendModelOptionsResponse { backends })
+    backends
 }
 
 /// Validate a model override for a specific backend.
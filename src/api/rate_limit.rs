@@ -0,0 +1,348 @@
+//! Per-user rate limiting for the protected API.
+//!
+//! Each authenticated user gets one token bucket per request class. Cheap reads,
+//! writes, and expensive operations (sending a message, creating a mission,
+//! starting a task, installing packages) have separate budgets so a burst of
+//! polling never starves message submission and vice versa.
+//!
+//! Budgets are requests per minute and can be tuned with
+//! `SANDBOXED_SH_RATE_LIMIT_READ`, `SANDBOXED_SH_RATE_LIMIT_WRITE` and
+//! `SANDBOXED_SH_RATE_LIMIT_EXPENSIVE` (`0` disables a class).
+//!
+//! Rejected requests get `429 Too Many Requests` with a `Retry-After` header;
+//! allowed requests carry `X-RateLimit-Limit` / `X-RateLimit-Remaining`.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use axum::{
+    body::Body,
+    extract::State,
+    http::{HeaderValue, Method, Request, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Json, Response},
+    Extension,
+};
+use serde::Serialize;
+
+use super::auth::AuthUser;
+use super::routes::AppState;
+
+const DEFAULT_READ_PER_MINUTE: u32 = 1200;
+const DEFAULT_WRITE_PER_MINUTE: u32 = 240;
+const DEFAULT_EXPENSIVE_PER_MINUTE: u32 = 30;
+
+/// Cost class of a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RateLimitClass {
+    Read,
+    Write,
+    Expensive,
+}
+
+impl RateLimitClass {
+    const ALL: [RateLimitClass; 3] = [Self::Read, Self::Write, Self::Expensive];
+
+    /// Classify a request by method and path.
+    pub fn for_request(method: &Method, path: &str) -> Self {
+        if *method == Method::POST && is_expensive_path(path) {
+            return Self::Expensive;
+        }
+        match *method {
+            Method::GET | Method::HEAD | Method::OPTIONS => Self::Read,
+            _ => Self::Write,
+        }
+    }
+}
+
+fn is_expensive_path(path: &str) -> bool {
+    matches!(
+        path,
+        "/api/control/message"
+            | "/api/control/missions"
+            | "/api/task"
+            | "/api/library/skill/install"
+            | "/api/library/skills/install"
+            | "/api/library/skill/registry/install"
+    ) || (path.starts_with("/api/control/missions/") && path.ends_with("/parallel"))
+        || (path.starts_with("/api/remote-tools/") && path.ends_with("/call"))
+}
+
+/// Requests-per-minute budget for one class. The bucket holds a full minute of
+/// burst capacity and refills continuously.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct RateLimitPolicy {
+    pub class: RateLimitClass,
+    pub per_minute: u32,
+}
+
+impl RateLimitPolicy {
+    fn refill_per_sec(&self) -> f64 {
+        self.per_minute as f64 / 60.0
+    }
+}
+
+/// Current state of a user's bucket for one class.
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimitStatus {
+    pub class: RateLimitClass,
+    /// Requests per minute (0 = unlimited).
+    pub limit: u32,
+    pub remaining: u32,
+    /// Seconds until the bucket is full again.
+    pub reset_secs: u64,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Bucket {
+    tokens: f64,
+    updated: Instant,
+}
+
+/// Token-bucket limiter keyed by (user, class).
+#[derive(Debug)]
+pub struct RateLimiter {
+    policies: HashMap<RateLimitClass, RateLimitPolicy>,
+    buckets: Mutex<HashMap<(String, RateLimitClass), Bucket>>,
+}
+
+pub type SharedRateLimiter = Arc<RateLimiter>;
+
+impl RateLimiter {
+    pub fn new(read: u32, write: u32, expensive: u32) -> Self {
+        let policies = [
+            (RateLimitClass::Read, read),
+            (RateLimitClass::Write, write),
+            (RateLimitClass::Expensive, expensive),
+        ]
+        .into_iter()
+        .map(|(class, per_minute)| (class, RateLimitPolicy { class, per_minute }))
+        .collect();
+        Self {
+            policies,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Build a limiter from `SANDBOXED_SH_RATE_LIMIT_*` environment variables.
+    pub fn from_env() -> Self {
+        fn budget(var: &str, default: u32) -> u32 {
+            std::env::var(var)
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+                .unwrap_or(default)
+        }
+        Self::new(
+            budget("SANDBOXED_SH_RATE_LIMIT_READ", DEFAULT_READ_PER_MINUTE),
+            budget("SANDBOXED_SH_RATE_LIMIT_WRITE", DEFAULT_WRITE_PER_MINUTE),
+            budget(
+                "SANDBOXED_SH_RATE_LIMIT_EXPENSIVE",
+                DEFAULT_EXPENSIVE_PER_MINUTE,
+            ),
+        )
+    }
+
+    fn policy(&self, class: RateLimitClass) -> RateLimitPolicy {
+        self.policies[&class]
+    }
+
+    fn refill(bucket: &mut Bucket, policy: &RateLimitPolicy, now: Instant) {
+        let elapsed = now.duration_since(bucket.updated).as_secs_f64();
+        bucket.tokens =
+            (bucket.tokens + elapsed * policy.refill_per_sec()).min(policy.per_minute as f64);
+        bucket.updated = now;
+    }
+
+    fn status_for(policy: &RateLimitPolicy, bucket: &Bucket) -> RateLimitStatus {
+        let missing = policy.per_minute as f64 - bucket.tokens;
+        RateLimitStatus {
+            class: policy.class,
+            limit: policy.per_minute,
+            remaining: bucket.tokens.floor() as u32,
+            reset_secs: (missing / policy.refill_per_sec()).ceil() as u64,
+        }
+    }
+
+    /// Take one token. Returns the bucket status, or how long to wait.
+    pub fn check(&self, key: &str, class: RateLimitClass) -> Result<RateLimitStatus, Duration> {
+        self.check_at(key, class, Instant::now())
+    }
+
+    fn check_at(
+        &self,
+        key: &str,
+        class: RateLimitClass,
+        now: Instant,
+    ) -> Result<RateLimitStatus, Duration> {
+        let policy = self.policy(class);
+        if policy.per_minute == 0 {
+            return Ok(RateLimitStatus {
+                class,
+                limit: 0,
+                remaining: 0,
+                reset_secs: 0,
+            });
+        }
+
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        let bucket = buckets.entry((key.to_string(), class)).or_insert(Bucket {
+            tokens: policy.per_minute as f64,
+            updated: now,
+        });
+        Self::refill(bucket, &policy, now);
+
+        if bucket.tokens < 1.0 {
+            let wait = (1.0 - bucket.tokens) / policy.refill_per_sec();
+            return Err(Duration::from_secs_f64(wait));
+        }
+        bucket.tokens -= 1.0;
+        Ok(Self::status_for(&policy, bucket))
+    }
+
+    /// Current bucket state for every class, without consuming tokens.
+    pub fn status(&self, key: &str) -> Vec<RateLimitStatus> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+        RateLimitClass::ALL
+            .iter()
+            .map(|&class| {
+                let policy = self.policy(class);
+                match buckets.get_mut(&(key.to_string(), class)) {
+                    Some(bucket) if policy.per_minute > 0 => {
+                        Self::refill(bucket, &policy, now);
+                        Self::status_for(&policy, bucket)
+                    }
+                    _ => RateLimitStatus {
+                        class,
+                        limit: policy.per_minute,
+                        remaining: policy.per_minute,
+                        reset_secs: 0,
+                    },
+                }
+            })
+            .collect()
+    }
+}
+
+fn user_key(req: &Request<Body>) -> String {
+    req.extensions()
+        .get::<AuthUser>()
+        .map(|u| u.id.clone())
+        .unwrap_or_else(|| "anonymous".to_string())
+}
+
+/// Middleware: must run after `require_auth` so the user is known.
+pub async fn rate_limit(
+    State(state): State<Arc<AppState>>,
+    req: Request<Body>,
+    next: Next,
+) -> Response {
+    let class = RateLimitClass::for_request(req.method(), req.uri().path());
+    let key = user_key(&req);
+
+    match state.rate_limiter.check(&key, class) {
+        Ok(status) => {
+            let mut response = next.run(req).await;
+            if status.limit > 0 {
+                let headers = response.headers_mut();
+                headers.insert("x-ratelimit-limit", HeaderValue::from(status.limit));
+                headers.insert("x-ratelimit-remaining", HeaderValue::from(status.remaining));
+            }
+            response
+        }
+        Err(wait) => {
+            let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+            tracing::debug!(user = %key, ?class, retry_after, "Rate limit exceeded");
+            let mut response = (
+                StatusCode::TOO_MANY_REQUESTS,
+                format!("Rate limit exceeded; retry after {}s", retry_after),
+            )
+                .into_response();
+            let headers = response.headers_mut();
+            headers.insert(
+                axum::http::header::RETRY_AFTER,
+                HeaderValue::from(retry_after),
+            );
+            headers.insert("x-ratelimit-remaining", HeaderValue::from(0u32));
+            response
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RateLimitsResponse {
+    pub policies: Vec<RateLimitPolicy>,
+    pub current: Vec<RateLimitStatus>,
+}
+
+/// GET /api/rate-limits - Configured budgets and the caller's current usage.
+pub async fn get_rate_limits(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+) -> Json<RateLimitsResponse> {
+    let limiter = &state.rate_limiter;
+    Json(RateLimitsResponse {
+        policies: RateLimitClass::ALL
+            .iter()
+            .map(|&c| limiter.policy(c))
+            .collect(),
+        current: limiter.status(&user.id),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_requests() {
+        assert_eq!(
+            RateLimitClass::for_request(&Method::POST, "/api/control/message"),
+            RateLimitClass::Expensive
+        );
+        assert_eq!(
+            RateLimitClass::for_request(&Method::GET, "/api/control/missions"),
+            RateLimitClass::Read
+        );
+        assert_eq!(
+            RateLimitClass::for_request(&Method::DELETE, "/api/control/missions/x"),
+            RateLimitClass::Write
+        );
+    }
+
+    #[test]
+    fn bucket_exhausts_and_refills_per_user() {
+        let limiter = RateLimiter::new(0, 60, 2);
+        let start = Instant::now();
+
+        assert!(limiter
+            .check_at("alice", RateLimitClass::Expensive, start)
+            .is_ok());
+        assert!(limiter
+            .check_at("alice", RateLimitClass::Expensive, start)
+            .is_ok());
+        let wait = limiter
+            .check_at("alice", RateLimitClass::Expensive, start)
+            .unwrap_err();
+        assert!(wait > Duration::from_secs(29) && wait <= Duration::from_secs(30));
+
+        // Other users and classes have their own buckets.
+        assert!(limiter
+            .check_at("bob", RateLimitClass::Expensive, start)
+            .is_ok());
+        assert!(limiter
+            .check_at("alice", RateLimitClass::Write, start)
+            .is_ok());
+        // A disabled class never limits.
+        assert!(limiter
+            .check_at("alice", RateLimitClass::Read, start)
+            .is_ok());
+
+        let later = start + Duration::from_secs(30);
+        assert!(limiter
+            .check_at("alice", RateLimitClass::Expensive, later)
+            .is_ok());
+    }
+}
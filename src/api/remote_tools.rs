@@ -0,0 +1,799 @@
+//! Remote tools — external HTTP endpoints registered as agent tools.
+//!
+//! A remote tool is a JSON schema plus a URL (and optional auth header). The
+//! agent sees it like any other tool; calls are proxied through this server,
+//! which applies per-tool timeouts and retries and records each result.
+//! This is a lighter-weight alternative to writing an MCP server.
+//!
+//! Definitions are persisted to `{working_dir}/.sandboxed-sh/remote_tools.json`.
+//! Auth header values are kept in the secrets vault (registry
+//! [`REMOTE_TOOL_AUTH_REGISTRY`], keyed by tool name); the definition only
+//! names the header.
+//!
+//! The endpoint receives `POST {"tool": "<name>", "arguments": {...}}` and may
+//! respond with `{"output": ...}` / `{"error": "..."}` JSON or plain text.
+
+use std::collections::VecDeque;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::Json,
+    routing::{delete, get, post},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::RwLock;
+use uuid::Uuid;
+
+use super::routes::AppState;
+use crate::secrets::SecretsStore;
+
+/// Secrets registry holding remote tool auth header values, keyed by tool name.
+pub const REMOTE_TOOL_AUTH_REGISTRY: &str = "remote-tools";
+
+/// Maximum number of call records kept in memory.
+const MAX_CALL_RECORDS: usize = 500;
+/// Maximum response size (in bytes) read from a remote tool.
+const MAX_RESPONSE_BYTES: usize = 256 * 1024;
+/// Maximum response size (in bytes) kept in a call record.
+const MAX_RECORDED_OUTPUT: usize = 16 * 1024;
+/// Upper bounds for a tool's per-attempt timeout and retries.
+const MAX_TIMEOUT_SECS: u64 = 300;
+const MAX_RETRIES: u32 = 5;
+/// Delay before the first retry; it doubles per attempt up to the maximum.
+const RETRY_BASE_DELAY: Duration = Duration::from_millis(250);
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Types
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Header sent with every call to a remote tool (e.g. `Authorization`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteToolAuth {
+    pub header: String,
+    /// Key of the header value in [`REMOTE_TOOL_AUTH_REGISTRY`].
+    pub secret: String,
+}
+
+/// Auth header given when registering a remote tool.
+#[derive(Debug, Deserialize)]
+pub struct RegisterRemoteToolAuth {
+    pub header: String,
+    pub value: String,
+}
+
+/// A registered remote tool (persisted to disk).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RemoteTool {
+    pub id: Uuid,
+    /// Tool name exposed to the agent.
+    pub name: String,
+    pub description: String,
+    /// JSON schema for the tool arguments.
+    pub parameters: Value,
+    /// Endpoint that receives tool calls.
+    pub url: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth: Option<RemoteToolAuth>,
+    /// Per-attempt timeout.
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Retries after a failed attempt (network errors, 429 and 5xx).
+    #[serde(default = "default_max_retries")]
+    pub max_retries: u32,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+fn default_timeout_secs() -> u64 {
+    30
+}
+
+fn default_max_retries() -> u32 {
+    2
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Remote tool as returned by the API (auth value redacted).
+#[derive(Debug, Serialize)]
+pub struct RemoteToolSummary {
+    pub id: Uuid,
+    pub name: String,
+    pub description: String,
+    pub parameters: Value,
+    pub url: String,
+    /// Name of the auth header, if one is configured.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auth_header: Option<String>,
+    pub timeout_secs: u64,
+    pub max_retries: u32,
+    pub enabled: bool,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl From<&RemoteTool> for RemoteToolSummary {
+    fn from(tool: &RemoteTool) -> Self {
+        Self {
+            id: tool.id,
+            name: tool.name.clone(),
+            description: tool.description.clone(),
+            parameters: tool.parameters.clone(),
+            url: tool.url.clone(),
+            auth_header: tool.auth.as_ref().map(|a| a.header.clone()),
+            timeout_secs: tool.timeout_secs,
+            max_retries: tool.max_retries,
+            enabled: tool.enabled,
+            created_at: tool.created_at,
+        }
+    }
+}
+
+/// Request body for registering a remote tool.
+#[derive(Debug, Deserialize)]
+pub struct RegisterRemoteToolRequest {
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub parameters: Option<Value>,
+    pub url: String,
+    #[serde(default)]
+    pub auth: Option<RegisterRemoteToolAuth>,
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    #[serde(default)]
+    pub max_retries: Option<u32>,
+}
+
+/// Request body for calling a remote tool.
+#[derive(Debug, Deserialize)]
+pub struct CallRemoteToolRequest {
+    #[serde(default)]
+    pub arguments: Value,
+    /// Mission on whose behalf the call is made (for the call record).
+    #[serde(default)]
+    pub mission_id: Option<Uuid>,
+}
+
+/// Recorded result of a remote tool call.
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteToolCall {
+    pub id: Uuid,
+    pub tool: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mission_id: Option<Uuid>,
+    pub arguments: Value,
+    pub success: bool,
+    /// HTTP status of the last attempt, if a response was received.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    pub attempts: u32,
+    pub duration_ms: u64,
+    pub created_at: chrono::DateTime<chrono::Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CallsQuery {
+    pub tool: Option<String>,
+    pub mission_id: Option<Uuid>,
+    pub limit: Option<usize>,
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Store
+// ─────────────────────────────────────────────────────────────────────────────
+
+pub type SharedRemoteToolStore = Arc<RemoteToolStore>;
+
+pub struct RemoteToolStore {
+    tools: RwLock<Vec<RemoteTool>>,
+    calls: RwLock<VecDeque<RemoteToolCall>>,
+    storage_path: PathBuf,
+    secrets: Option<Arc<SecretsStore>>,
+}
+
+impl RemoteToolStore {
+    pub async fn new(storage_path: PathBuf, secrets: Option<Arc<SecretsStore>>) -> Self {
+        let store = Self {
+            tools: RwLock::new(Vec::new()),
+            calls: RwLock::new(VecDeque::new()),
+            storage_path,
+            secrets,
+        };
+        if let Ok(loaded) = store.load_from_disk() {
+            *store.tools.write().await = loaded;
+        }
+        store
+    }
+
+    fn load_from_disk(&self) -> Result<Vec<RemoteTool>, std::io::Error> {
+        if !self.storage_path.exists() {
+            return Ok(Vec::new());
+        }
+        let contents = std::fs::read_to_string(&self.storage_path)?;
+        serde_json::from_str(&contents)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    fn save_to_disk(&self, tools: &[RemoteTool]) -> Result<(), std::io::Error> {
+        if let Some(parent) = self.storage_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let contents = serde_json::to_string_pretty(tools)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        let tmp_path = self.storage_path.with_extension("tmp");
+        std::fs::write(&tmp_path, &contents)?;
+        std::fs::rename(&tmp_path, &self.storage_path)?;
+        Ok(())
+    }
+
+    /// List all registered tools.
+    pub async fn list(&self) -> Vec<RemoteTool> {
+        self.tools.read().await.clone()
+    }
+
+    /// Get a tool by name.
+    pub async fn get(&self, name: &str) -> Option<RemoteTool> {
+        self.tools
+            .read()
+            .await
+            .iter()
+            .find(|t| t.name == name)
+            .cloned()
+    }
+
+    /// Register a tool, replacing any existing tool with the same name.
+    pub async fn register(&self, req: RegisterRemoteToolRequest) -> Result<RemoteTool, String> {
+        let name = req.name.trim().to_string();
+        if name.is_empty()
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(format!("Invalid tool name: '{}'", req.name));
+        }
+        let url =
+            reqwest::Url::parse(req.url.trim()).map_err(|e| format!("Invalid tool URL: {}", e))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err("Tool URL must use http or https".to_string());
+        }
+        let parameters = req
+            .parameters
+            .unwrap_or_else(|| json!({ "type": "object", "properties": {} }));
+        if !parameters.is_object() {
+            return Err("Tool parameters must be a JSON schema object".to_string());
+        }
+        let auth = match &req.auth {
+            Some(auth) => {
+                reqwest::header::HeaderName::from_bytes(auth.header.as_bytes())
+                    .map_err(|_| format!("Invalid auth header name: '{}'", auth.header))?;
+                let secrets = self
+                    .secrets
+                    .as_ref()
+                    .ok_or_else(|| "Secrets store is not available".to_string())?;
+                secrets
+                    .set_secret(REMOTE_TOOL_AUTH_REGISTRY, &name, &auth.value, None)
+                    .await
+                    .map_err(|e| format!("Failed to store auth header value: {}", e))?;
+                Some(RemoteToolAuth {
+                    header: auth.header.clone(),
+                    secret: name.clone(),
+                })
+            }
+            None => {
+                self.delete_auth_secret(&name).await;
+                None
+            }
+        };
+
+        let tool = RemoteTool {
+            id: Uuid::new_v4(),
+            name,
+            description: req.description,
+            parameters,
+            url: url.to_string(),
+            auth,
+            timeout_secs: req
+                .timeout_secs
+                .unwrap_or_else(default_timeout_secs)
+                .clamp(1, MAX_TIMEOUT_SECS),
+            max_retries: req
+                .max_retries
+                .unwrap_or_else(default_max_retries)
+                .min(MAX_RETRIES),
+            enabled: true,
+            created_at: chrono::Utc::now(),
+        };
+
+        let mut tools = self.tools.write().await;
+        tools.retain(|t| t.name != tool.name);
+        tools.push(tool.clone());
+        self.save_to_disk(&tools)
+            .map_err(|e| format!("Failed to persist remote tool: {}", e))?;
+        Ok(tool)
+    }
+
+    /// Remove a tool by name. Returns true if found and removed.
+    pub async fn remove(&self, name: &str) -> Result<bool, String> {
+        let mut tools = self.tools.write().await;
+        let len_before = tools.len();
+        tools.retain(|t| t.name != name);
+        if tools.len() == len_before {
+            return Ok(false);
+        }
+        self.save_to_disk(&tools)
+            .map_err(|e| format!("Failed to persist remote tool deletion: {}", e))?;
+        drop(tools);
+        self.delete_auth_secret(name).await;
+        Ok(true)
+    }
+
+    /// Remove a tool's auth header value from the vault, if it has one.
+    async fn delete_auth_secret(&self, name: &str) {
+        let Some(secrets) = &self.secrets else {
+            return;
+        };
+        let stored = secrets
+            .list_secrets(REMOTE_TOOL_AUTH_REGISTRY)
+            .await
+            .map(|list| list.iter().any(|s| s.key == name))
+            .unwrap_or(false);
+        if stored {
+            if let Err(e) = secrets.delete_secret(REMOTE_TOOL_AUTH_REGISTRY, name).await {
+                tracing::warn!(tool = %name, "Failed to delete remote tool auth secret: {}", e);
+            }
+        }
+    }
+
+    /// Load the auth header and its value from the vault.
+    async fn auth_header(&self, tool: &RemoteTool) -> Result<Option<(String, String)>, String> {
+        let Some(auth) = &tool.auth else {
+            return Ok(None);
+        };
+        let secrets = self
+            .secrets
+            .as_ref()
+            .ok_or_else(|| "Secrets store is not available".to_string())?;
+        let value = secrets
+            .get_secret(REMOTE_TOOL_AUTH_REGISTRY, &auth.secret)
+            .await
+            .map_err(|e| format!("Failed to load auth header value: {}", e))?;
+        Ok(Some((auth.header.clone(), value)))
+    }
+
+    async fn record(&self, call: RemoteToolCall) {
+        let mut calls = self.calls.write().await;
+        if calls.len() >= MAX_CALL_RECORDS {
+            calls.pop_front();
+        }
+        calls.push_back(call);
+    }
+
+    /// Most recent call records first, optionally filtered.
+    pub async fn recent_calls(
+        &self,
+        tool: Option<&str>,
+        mission_id: Option<Uuid>,
+        limit: usize,
+    ) -> Vec<RemoteToolCall> {
+        self.calls
+            .read()
+            .await
+            .iter()
+            .rev()
+            .filter(|c| !matches!(tool, Some(t) if c.tool != t))
+            .filter(|c| mission_id.is_none() || c.mission_id == mission_id)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Call a remote tool with retries, recording the result.
+    pub async fn call(
+        &self,
+        client: &reqwest::Client,
+        tool: &RemoteTool,
+        arguments: Value,
+        mission_id: Option<Uuid>,
+    ) -> RemoteToolCall {
+        let started = Instant::now();
+        let mut attempts = 0;
+        let mut status = None;
+        let result = match self.auth_header(tool).await {
+            Ok(auth) => loop {
+                attempts += 1;
+                let (outcome, retryable) = call_once(client, tool, auth.as_ref(), &arguments).await;
+                if let Ok((code, _)) | Err((Some(code), _)) = &outcome {
+                    status = Some(*code);
+                }
+                if outcome.is_ok() || !retryable || attempts > tool.max_retries {
+                    break outcome;
+                }
+                tokio::time::sleep(retry_delay(attempts)).await;
+            },
+            Err(e) => Err((None, e)),
+        };
+
+        let (output, error) = match result {
+            Ok((_, output)) => (Some(output), None),
+            Err((_, error)) => (None, Some(error)),
+        };
+        let call = RemoteToolCall {
+            id: Uuid::new_v4(),
+            tool: tool.name.clone(),
+            mission_id,
+            arguments,
+            success: error.is_none(),
+            status,
+            output,
+            error,
+            attempts,
+            duration_ms: started.elapsed().as_millis() as u64,
+            created_at: chrono::Utc::now(),
+        };
+
+        let mut recorded = call.clone();
+        if let Some(output) = recorded.output.as_mut() {
+            if output.len() > MAX_RECORDED_OUTPUT {
+                let end = crate::tools::safe_truncate_index(output, MAX_RECORDED_OUTPUT);
+                output.truncate(end);
+                output.push('…');
+            }
+        }
+        self.record(recorded).await;
+        call
+    }
+}
+
+/// Read the response body, stopping after [`MAX_RESPONSE_BYTES`].
+async fn read_capped(mut response: reqwest::Response) -> String {
+    let mut body = Vec::new();
+    while let Ok(Some(chunk)) = response.chunk().await {
+        let room = MAX_RESPONSE_BYTES - body.len();
+        body.extend_from_slice(&chunk[..chunk.len().min(room)]);
+        if body.len() == MAX_RESPONSE_BYTES {
+            break;
+        }
+    }
+    String::from_utf8_lossy(&body).into_owned()
+}
+
+/// Backoff after the given (1-based) failed attempt.
+fn retry_delay(attempt: u32) -> Duration {
+    let factor = 1u32
+        .checked_shl(attempt.saturating_sub(1))
+        .unwrap_or(u32::MAX);
+    RETRY_BASE_DELAY.saturating_mul(factor).min(MAX_RETRY_DELAY)
+}
+
+type CallOutcome = Result<(u16, String), (Option<u16>, String)>;
+
+/// Perform a single attempt. Returns the outcome and whether a failure is retryable.
+async fn call_once(
+    client: &reqwest::Client,
+    tool: &RemoteTool,
+    auth: Option<&(String, String)>,
+    arguments: &Value,
+) -> (CallOutcome, bool) {
+    let mut request = client
+        .post(&tool.url)
+        .timeout(Duration::from_secs(tool.timeout_secs))
+        .json(&json!({ "tool": tool.name, "arguments": arguments }));
+    if let Some((header, value)) = auth {
+        request = request.header(header.as_str(), value.as_str());
+    }
+
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) => {
+            let message = if e.is_timeout() {
+                format!("Timed out after {}s", tool.timeout_secs)
+            } else {
+                format!("Request failed: {}", e)
+            };
+            return (Err((None, message)), true);
+        }
+    };
+
+    let status = response.status();
+    let code = status.as_u16();
+    let body = read_capped(response).await;
+    if !status.is_success() {
+        let retryable = status.is_server_error() || status == StatusCode::TOO_MANY_REQUESTS;
+        return (
+            Err((Some(code), format!("HTTP {}: {}", code, body.trim()))),
+            retryable,
+        );
+    }
+
+    match serde_json::from_str::<Value>(&body) {
+        Ok(Value::Object(map)) if map.contains_key("error") => {
+            let error = match &map["error"] {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            (Err((Some(code), error)), false)
+        }
+        Ok(Value::Object(map)) if map.contains_key("output") => {
+            let output = match &map["output"] {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            (Ok((code, output)), false)
+        }
+        _ => (Ok((code, body)), false),
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// API Handlers
+// ─────────────────────────────────────────────────────────────────────────────
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/", get(list_tools))
+        .route("/", post(register_tool))
+        .route("/calls", get(list_calls))
+        .route("/:name", delete(remove_tool))
+        .route("/:name/call", post(call_tool))
+}
+
+async fn list_tools(State(state): State<Arc<AppState>>) -> Json<Vec<RemoteToolSummary>> {
+    Json(
+        state
+            .remote_tools
+            .list()
+            .await
+            .iter()
+            .map(RemoteToolSummary::from)
+            .collect(),
+    )
+}
+
+async fn register_tool(
+    State(state): State<Arc<AppState>>,
+    Json(req): Json<RegisterRemoteToolRequest>,
+) -> Result<(StatusCode, Json<RemoteToolSummary>), (StatusCode, String)> {
+    let tool = state
+        .remote_tools
+        .register(req)
+        .await
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    Ok((StatusCode::CREATED, Json(RemoteToolSummary::from(&tool))))
+}
+
+async fn remove_tool(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    match state.remote_tools.remove(&name).await {
+        Ok(true) => Ok(StatusCode::NO_CONTENT),
+        Ok(false) => Err((
+            StatusCode::NOT_FOUND,
+            format!("Remote tool not found: {}", name),
+        )),
+        Err(e) => Err((StatusCode::INTERNAL_SERVER_ERROR, e)),
+    }
+}
+
+async fn call_tool(
+    State(state): State<Arc<AppState>>,
+    Path(name): Path<String>,
+    Json(req): Json<CallRemoteToolRequest>,
+) -> Result<Json<RemoteToolCall>, (StatusCode, String)> {
+    let tool = state
+        .remote_tools
+        .get(&name)
+        .await
+        .filter(|t| t.enabled)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Remote tool not found: {}", name),
+            )
+        })?;
+    let call = state
+        .remote_tools
+        .call(&state.http_client, &tool, req.arguments, req.mission_id)
+        .await;
+    Ok(Json(call))
+}
+
+async fn list_calls(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<CallsQuery>,
+) -> Json<Vec<RemoteToolCall>> {
+    Json(
+        state
+            .remote_tools
+            .recent_calls(
+                query.tool.as_deref(),
+                query.mission_id,
+                query.limit.unwrap_or(100),
+            )
+            .await,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(name: &str, url: &str) -> RegisterRemoteToolRequest {
+        RegisterRemoteToolRequest {
+            name: name.to_string(),
+            description: "test".to_string(),
+            parameters: None,
+            url: url.to_string(),
+            auth: None,
+            timeout_secs: Some(1),
+            max_retries: Some(1),
+        }
+    }
+
+    #[tokio::test]
+    async fn register_validates_and_persists() {
+        let temp = tempfile::tempdir().unwrap();
+        let path = temp.path().join("remote_tools.json");
+        let store = RemoteToolStore::new(path.clone(), None).await;
+
+        assert!(store
+            .register(request("bad name", "http://x"))
+            .await
+            .is_err());
+        assert!(store.register(request("ok", "ftp://x")).await.is_err());
+        store
+            .register(request("lookup", "http://127.0.0.1:1/call"))
+            .await
+            .unwrap();
+        // Re-registering replaces the existing definition.
+        store
+            .register(request("lookup", "http://127.0.0.1:2/call"))
+            .await
+            .unwrap();
+
+        let mut unbounded = request("slow", "http://127.0.0.1:3/call");
+        unbounded.timeout_secs = Some(u64::MAX);
+        unbounded.max_retries = Some(u32::MAX);
+        let slow = store.register(unbounded).await.unwrap();
+        assert_eq!(
+            (slow.timeout_secs, slow.max_retries),
+            (MAX_TIMEOUT_SECS, MAX_RETRIES)
+        );
+        store.remove("slow").await.unwrap();
+
+        let reloaded = RemoteToolStore::new(path, None).await;
+        let tools = reloaded.list().await;
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0].url, "http://127.0.0.1:2/call");
+    }
+
+    #[test]
+    fn retry_delay_doubles_up_to_the_maximum() {
+        assert_eq!(retry_delay(1), Duration::from_millis(250));
+        assert_eq!(retry_delay(3), Duration::from_secs(1));
+        assert_eq!(retry_delay(60), MAX_RETRY_DELAY);
+        assert_eq!(retry_delay(u32::MAX), MAX_RETRY_DELAY);
+    }
+
+    #[tokio::test]
+    async fn failed_calls_are_retried_and_recorded() {
+        let temp = tempfile::tempdir().unwrap();
+        let store = RemoteToolStore::new(temp.path().join("remote_tools.json"), None).await;
+        // Nothing listens on port 1, so every attempt fails to connect.
+        let tool = store
+            .register(request("lookup", "http://127.0.0.1:1/call"))
+            .await
+            .unwrap();
+
+        let mission_id = Uuid::new_v4();
+        let call = store
+            .call(
+                &reqwest::Client::new(),
+                &tool,
+                json!({ "q": "x" }),
+                Some(mission_id),
+            )
+            .await;
+        assert!(!call.success);
+        assert_eq!(call.attempts, 2);
+
+        let recorded = store.recent_calls(None, Some(mission_id), 10).await;
+        assert_eq!(recorded.len(), 1);
+        assert_eq!(recorded[0].tool, "lookup");
+    }
+
+    #[tokio::test]
+    async fn large_responses_are_read_up_to_the_cap() {
+        let app = Router::new().route(
+            "/call",
+            post(|| async { "x".repeat(MAX_RESPONSE_BYTES * 4) }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        let temp = tempfile::tempdir().unwrap();
+        let store = RemoteToolStore::new(temp.path().join("remote_tools.json"), None).await;
+        let tool = store
+            .register(request("big", &format!("http://127.0.0.1:{}/call", port)))
+            .await
+            .unwrap();
+        let call = store
+            .call(&reqwest::Client::new(), &tool, json!({}), None)
+            .await;
+        assert!(call.success);
+        assert_eq!(call.output.unwrap().len(), MAX_RESPONSE_BYTES);
+    }
+
+    #[tokio::test]
+    async fn auth_values_are_kept_in_the_vault() {
+        let app = Router::new().route(
+            "/call",
+            post(|headers: axum::http::HeaderMap| async move {
+                headers
+                    .get("x-api-key")
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default()
+                    .to_string()
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        let temp = tempfile::tempdir().unwrap();
+        let secrets = Arc::new(SecretsStore::new(temp.path()).await.unwrap());
+        secrets.initialize("default").await.unwrap();
+        secrets.unlock("passphrase").await.unwrap();
+        let path = temp.path().join("remote_tools.json");
+        let store = RemoteToolStore::new(path.clone(), Some(Arc::clone(&secrets))).await;
+
+        let mut req = request("lookup", &format!("http://127.0.0.1:{}/call", port));
+        req.auth = Some(RegisterRemoteToolAuth {
+            header: "X-Api-Key".to_string(),
+            value: "s3cret-value".to_string(),
+        });
+        let tool = store.register(req).await.unwrap();
+        assert!(!std::fs::read_to_string(&path)
+            .unwrap()
+            .contains("s3cret-value"));
+
+        let call = store
+            .call(&reqwest::Client::new(), &tool, json!({}), None)
+            .await;
+        assert_eq!(call.output.as_deref(), Some("s3cret-value"));
+
+        store.remove("lookup").await.unwrap();
+        assert!(secrets
+            .get_secret(REMOTE_TOOL_AUTH_REGISTRY, "lookup")
+            .await
+            .is_err());
+
+        // Without the vault, auth can't be stored or sent.
+        let plain = RemoteToolStore::new(temp.path().join("other.json"), None).await;
+        let mut req = request("other", "http://127.0.0.1:1/call");
+        req.auth = Some(RegisterRemoteToolAuth {
+            header: "X-Api-Key".to_string(),
+            value: "v".to_string(),
+        });
+        assert!(plain.register(req).await.is_err());
+    }
+}
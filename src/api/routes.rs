@@ -45,26 +45,63 @@ use super::providers::ModelCatalog;
 
 use super::ai_providers as ai_providers_api;
 use super::ampcode as ampcode_api;
+use super::audit as audit_api;
 use super::auth::{self, AuthUser};
+use super::automation_calendar;
+use super::automation_throttle;
 use super::backends as backends_api;
+use super::body_limit::{self, BodyLimits};
 use super::claudecode as claudecode_api;
+use super::command_approval;
 use super::console;
 use super::control;
+#[cfg(unix)]
+use super::control_socket;
+use super::cost_breakdown;
 use super::deferred_proxy as deferred_proxy_api;
 use super::desktop;
 use super::desktop_stream;
+use super::email_gateway;
+use super::eval;
 use super::fs;
+use super::health;
 use super::library as library_api;
 use super::mcp as mcp_api;
+use super::message_feedback;
+use super::mission_assignment;
+use super::mission_checklist;
+use super::mission_comments;
+use super::mission_compare;
+use super::mission_environment;
+use super::mission_export;
+use super::mission_files;
+use super::mission_reads;
+use super::mission_report;
+use super::mission_retry;
+use super::mission_share;
+use super::mission_stream;
 use super::model_routing as model_routing_api;
 use super::monitoring;
+use super::notifications;
 use super::opencode as opencode_api;
+use super::presence;
 use super::proxy as proxy_api;
 use super::proxy_keys as proxy_keys_api;
+use super::rate_limit;
+use super::remote_tools as remote_tools_api;
+use super::scheduled_messages;
 use super::secrets as secrets_api;
 use super::settings as settings_api;
+use super::slack;
+use super::status_requests;
+use super::stop_policy_preview;
+use super::store_maintenance;
 use super::system as system_api;
+use super::task_supervisor;
+use super::telegram;
+use super::timeline;
 use super::types::*;
+use super::user_data as user_data_api;
 use super::workspaces as workspaces_api;
 
 /// Shared application state.
@@ -79,6 +116,8 @@ pub struct AppState {
     pub mcp: Arc<McpRegistry>,
     /// Configuration library (git-based)
     pub library: library_api::SharedLibrary,
+    /// Per-tenant configuration libraries
+    pub tenant_libraries: crate::tenant::SharedTenantLibraries,
     /// Workspace store
     pub workspaces: workspace::SharedWorkspaceStore,
     /// OpenCode connection store
@@ -114,362 +153,54 @@ pub struct AppState {
     pub proxy_api_keys: super::proxy_keys::SharedProxyApiKeyStore,
     /// Deferred queue for proxy requests that opt into async-on-rate-limit mode
     pub deferred_requests: Arc<deferred_proxy_api::DeferredRequestStore>,
+    /// External HTTP endpoints registered as agent tools
+    pub remote_tools: remote_tools_api::SharedRemoteToolStore,
+    /// Audit log of remote actions taken by missions (SSH commands)
+    pub audit: audit_api::SharedAuditLog,
+    /// Per-user API rate limiter
+    pub rate_limiter: rate_limit::SharedRateLimiter,
+    /// Evaluation runs started through the admin API
+    pub eval_runs: eval::SharedEvalRuns,
+    /// Slack integration settings and mission threads
+    pub slack: slack::SharedSlack,
 }
 
 /// Start the HTTP server.
 pub async fn serve(config: Config) -> anyhow::Result<()> {
-    let mut config = config;
-    // Start monitoring background collector early so clients get history immediately
-    monitoring::init_monitoring();
-
-    // Initialize MCP registry
-    let mcp = Arc::new(McpRegistry::new(&config.working_dir).await);
-    if let Err(e) = crate::opencode_config::ensure_global_config(&mcp).await {
-        tracing::warn!("Failed to ensure OpenCode global config: {}", e);
-    }
-    // Refresh all MCPs in background
-    {
-        let mcp_clone = Arc::clone(&mcp);
-        tokio::spawn(async move {
-            mcp_clone.refresh_all().await;
-        });
-    }
-
-    // Initialize workspace store (loads from disk and recovers orphaned containers)
-    let workspaces = Arc::new(workspace::WorkspaceStore::new(config.working_dir.clone()).await);
-
-    // Enable per-container metrics collection in the monitoring background task
-    monitoring::init_monitoring_workspaces(Arc::clone(&workspaces)).await;
-
-    // Initialize OpenCode connection store
-    let opencode_connections = Arc::new(
-        crate::opencode_config::OpenCodeStore::new(
-            config
-                .working_dir
-                .join(".sandboxed-sh/opencode_connections.json"),
-        )
-        .await,
-    );
-
-    // Initialize AI provider store
-    let ai_providers = Arc::new(
-        crate::ai_providers::AIProviderStore::new(config.working_dir.join(AI_PROVIDERS_PATH)).await,
-    );
-    let pending_oauth = Arc::new(RwLock::new(HashMap::new()));
-
-    // Initialize provider health tracker and model chain store
-    let health_tracker = Arc::new(crate::provider_health::ProviderHealthTracker::new());
-    let chain_store = Arc::new(
-        crate::provider_health::ModelChainStore::new(
-            config.working_dir.join(".sandboxed-sh/model_chains.json"),
-        )
-        .await,
-    );
-
-    // Initialize proxy API key store
-    let proxy_api_keys = Arc::new(
-        super::proxy_keys::ProxyApiKeyStore::new(
-            config.working_dir.join(".sandboxed-sh/proxy_api_keys.json"),
-        )
-        .await,
-    );
-    let deferred_requests = Arc::new(
-        deferred_proxy_api::DeferredRequestStore::new(
-            config
-                .working_dir
-                .join(".sandboxed-sh/deferred_requests.json"),
-        )
-        .await,
-    );
-
-    // Initialize secrets store
-    let secrets = match crate::secrets::SecretsStore::new(&config.working_dir).await {
-        Ok(store) => {
-            tracing::info!("Secrets store initialized");
-            Some(Arc::new(store))
-        }
-        Err(e) => {
-            tracing::warn!("Failed to initialize secrets store: {}", e);
-            None
-        }
-    };
-
-    // Initialize console session pool for WebSocket reconnection
-    let console_pool = Arc::new(console::SessionPool::new());
-    Arc::clone(&console_pool).start_cleanup_task();
-
-    // Initialize global settings store
-    let settings = Arc::new(crate::settings::SettingsStore::new(&config.working_dir).await);
-    settings.init_cached_values();
-
-    // Initialize backend config store (persisted settings).
-    // Probe each CLI binary so backends whose CLI is missing default to disabled.
-    // Persisted configs are preserved — this only affects fresh installs or new backends.
-    let opencode_detected = cli_available("opencode");
-    let claude_detected = cli_available("claude");
-    let amp_detected = cli_available("amp");
-    let codex_detected = cli_available("codex");
-    tracing::info!(
-        opencode = opencode_detected,
-        claude = claude_detected,
-        amp = amp_detected,
-        codex = codex_detected,
-        "CLI detection for backend defaults"
-    );
-
-    let backend_defaults = vec![
-        {
-            let mut entry = BackendConfigEntry::new(
-                "opencode",
-                "OpenCode",
-                serde_json::json!({
-                    "base_url": config.opencode_base_url,
-                    "default_agent": config.opencode_agent,
-                    "permissive": config.opencode_permissive,
-                }),
-            );
-            entry.enabled = opencode_detected;
-            entry
-        },
-        {
-            let mut entry =
-                BackendConfigEntry::new("claudecode", "Claude Code", serde_json::json!({}));
-            entry.enabled = claude_detected;
-            entry
-        },
-        {
-            let mut entry = BackendConfigEntry::new("amp", "Amp", serde_json::json!({}));
-            entry.enabled = amp_detected;
-            entry
-        },
-        {
-            let mut entry = BackendConfigEntry::new("codex", "Codex", serde_json::json!({}));
-            entry.enabled = codex_detected;
-            entry
-        },
-    ];
-    let backend_configs = Arc::new(
-        crate::backend_config::BackendConfigStore::new(
-            config.working_dir.join(".sandboxed-sh/backend_config.json"),
-            backend_defaults,
-        )
-        .await,
-    );
-
-    // Apply persisted OpenCode settings (if present)
-    if let Some(entry) = backend_configs.get("opencode").await {
-        if let Some(settings) = entry.settings.as_object() {
-            if let Some(base_url) = settings.get("base_url").and_then(|v| v.as_str()) {
-                if !base_url.trim().is_empty() {
-                    config.opencode_base_url = base_url.to_string();
-                }
-            }
-            if let Some(agent) = settings.get("default_agent").and_then(|v| v.as_str()) {
-                if !agent.trim().is_empty() {
-                    config.opencode_agent = Some(agent.to_string());
-                }
-            }
-            if let Some(permissive) = settings.get("permissive").and_then(|v| v.as_bool()) {
-                config.opencode_permissive = permissive;
-            }
-        }
-    }
-
-    // Always use OpenCode backend
-    let root_agent: AgentRef = Arc::new(OpenCodeAgent::new(config.clone()));
-
-    // Initialize backend registry with OpenCode and Claude Code backends
-    let opencode_base_url = config.opencode_base_url.clone();
-    let opencode_default_agent = config.opencode_agent.clone();
-    let opencode_permissive = config.opencode_permissive;
-
-    // Determine default backend: env var, or first available with priority claudecode → opencode → amp → codex
-    let default_backend = config.default_backend.clone().unwrap_or_else(|| {
-        if claude_detected {
-            "claudecode".to_string()
-        } else if opencode_detected {
-            "opencode".to_string()
-        } else if amp_detected {
-            "amp".to_string()
-        } else if codex_detected {
-            "codex".to_string()
-        } else {
-            // Fallback to claudecode even if not detected (will show warning in UI)
-            tracing::warn!(
-                "No backend CLIs detected. Defaulting to claudecode. Please install at least one backend."
-            );
-            "claudecode".to_string()
-        }
-    });
-
-    tracing::info!(
-        "Default backend: {} (claudecode={}, opencode={}, amp={}, codex={})",
-        default_backend,
-        claude_detected,
-        opencode_detected,
-        amp_detected,
-        codex_detected
-    );
-
-    let mut backend_registry = BackendRegistry::new(default_backend);
-    backend_registry.register(crate::backend::opencode::registry_entry(
-        opencode_base_url.clone(),
-        opencode_default_agent,
-        opencode_permissive,
-    ));
-    backend_registry.register(crate::backend::claudecode::registry_entry());
-    backend_registry.register(crate::backend::amp::registry_entry());
-    backend_registry.register(crate::backend::codex::registry_entry());
-    let backend_registry = Arc::new(RwLock::new(backend_registry));
-    tracing::info!("Backend registry initialized with {} backends", 4);
-
-    // Note: No central OpenCode server cleanup needed - missions use per-workspace CLI execution
-
-    // Initialize configuration library (optional - can also be configured at runtime)
-    // Must be created before ControlHub so it can be passed to control sessions
-    let library: library_api::SharedLibrary = Arc::new(RwLock::new(None));
-    // Read library_remote from settings (which falls back to env var if not configured)
-    let library_remote = settings.get_library_remote().await;
-    if let Some(library_remote) = library_remote {
-        let library_clone = Arc::clone(&library);
-        let library_path = config.library_path.clone();
-        let workspaces_clone = Arc::clone(&workspaces);
-        tokio::spawn(async move {
-            match crate::library::LibraryStore::new(library_path, &library_remote).await {
-                Ok(store) => {
-                    if let Ok(plugins) = store.get_plugins().await {
-                        if let Err(e) = crate::opencode_config::sync_global_plugins(&plugins).await
-                        {
-                            tracing::warn!("Failed to sync OpenCode plugins: {}", e);
-                        }
-                    }
-                    tracing::info!("Configuration library initialized from {}", library_remote);
-                    *library_clone.write().await = Some(Arc::new(store));
-
-                    let workspaces = workspaces_clone.list().await;
-                    if let Some(library) = library_clone.read().await.as_ref() {
-                        for workspace in workspaces {
-                            let is_default_host = workspace.id == workspace::DEFAULT_WORKSPACE_ID
-                                && workspace.workspace_type == workspace::WorkspaceType::Host;
-                            if is_default_host || !workspace.skills.is_empty() {
-                                if let Err(e) =
-                                    workspace::sync_workspace_skills(&workspace, library).await
-                                {
-                                    tracing::warn!(
-                                        workspace = %workspace.name,
-                                        error = %e,
-                                        "Failed to sync skills after library init"
-                                    );
-                                }
-                            }
-                        }
-                    }
-                }
-                Err(e) => {
-                    tracing::warn!("Failed to initialize configuration library: {}", e);
-                }
-            }
-        });
-    } else {
-        tracing::info!("Configuration library disabled (no remote configured)");
-    }
-
-    // Spawn the single global control session actor.
-    let control_state = control::ControlHub::new(
-        config.clone(),
-        Arc::clone(&root_agent),
-        Arc::clone(&mcp),
-        Arc::clone(&workspaces),
-        Arc::clone(&library),
-        secrets.clone(),
-    );
-
-    let state = Arc::new(AppState {
-        config: config.clone(),
-        tasks: RwLock::new(HashMap::new()),
-        root_agent,
-        control: control_state,
-        mcp,
-        library,
-        workspaces,
-        opencode_connections,
-        opencode_agents_cache: RwLock::new(opencode_api::OpenCodeAgentsCache::default()),
-        ai_providers,
-        pending_oauth,
-        secrets,
-        console_pool,
-        settings,
-        backend_registry,
-        backend_configs,
-        model_catalog: Arc::new(RwLock::new(HashMap::new())),
-        health_tracker,
-        chain_store,
-        http_client: reqwest::Client::builder()
-            // No global timeout — it applies to the full response body including
-            // streaming chunks, which would kill long-running LLM generations.
-            // Per-request timeouts are set in the proxy where needed.
-            .connect_timeout(std::time::Duration::from_secs(10))
-            .build()
-            .unwrap_or_default(),
-        proxy_secret: std::env::var("SANDBOXED_PROXY_SECRET")
-            .ok()
-            .filter(|s| !s.trim().is_empty())
-            .unwrap_or_else(|| {
-                let secret = uuid::Uuid::new_v4().to_string();
-                tracing::info!("No SANDBOXED_PROXY_SECRET set; generated ephemeral proxy secret");
-                // Also set in env so mission_runner can read it for OpenCode config.
-                std::env::set_var("SANDBOXED_PROXY_SECRET", &secret);
-                secret
-            }),
-        proxy_api_keys,
-        deferred_requests,
-    });
-
-    // Start background desktop session cleanup task
-    {
-        let state_clone = Arc::clone(&state);
-        tokio::spawn(async move {
-            desktop::start_cleanup_task(state_clone).await;
-        });
-    }
-
-    // Start background OAuth token refresher task
-    {
-        let ai_providers = Arc::clone(&state.ai_providers);
-        tokio::spawn(async move {
-            oauth_token_refresher_loop(ai_providers).await;
-        });
-    }
-
-    // Start deferred proxy queue worker.
-    deferred_proxy_api::start_worker(Arc::clone(&state));
-
-    // Fetch model catalog from provider APIs in background
-    {
-        let catalog = Arc::clone(&state.model_catalog);
-        let ai_providers = Arc::clone(&state.ai_providers);
-        let working_dir = config.working_dir.clone();
-        tokio::spawn(async move {
-            let fetched = super::providers::fetch_model_catalog(&ai_providers, &working_dir).await;
-            let provider_count = fetched.len();
-            let model_count: usize = fetched.values().map(|v| v.len()).sum();
-            *catalog.write().await = fetched;
-            tracing::info!(
-                "Model catalog populated: {} models from {} providers",
-                model_count,
-                provider_count
-            );
-        });
+    let state = init_state(config).await?;
+    #[cfg(unix)]
+    if let Some(path) = control_socket::socket_path() {
+        control_socket::spawn(Arc::clone(&state), &path)?;
     }
+    let body_limits = BodyLimits::from_env();
 
     let public_routes = Router::new()
         .route("/api/health", get(health))
+        .route("/api/health/detailed", get(health::detailed_health))
         .route("/api/auth/login", post(auth::login))
         // Webhook receiver endpoint (no auth required - uses webhook secret validation)
         .route(
             "/api/webhooks/:mission_id/:webhook_id",
             post(control::webhook_receiver),
         )
+        // Shared mission transcripts (no auth required - the token is signed and expires)
+        .route(
+            "/api/share/:token",
+            get(mission_share::get_shared_transcript),
+        )
+        // Inbound email gateway (no auth required - uses the gateway token)
+        .route(
+            "/api/email/inbound",
+            post(email_gateway::receive_email).layer(DefaultBodyLimit::max(body_limits.email)),
+        )
+        // Slack slash command and Events API (no auth required - requests are signed)
+        .route("/api/slack/commands", post(slack::slash_command))
+        .route("/api/slack/events", post(slack::slack_events))
+        // Automation calendar feeds (no auth required - calendar apps poll a signed link)
+        .route(
+            "/api/calendar/:token",
+            get(automation_calendar::automation_calendar),
+        )
         // WebSocket console uses subprotocol-based auth (browser can't set Authorization header)
         .route("/api/console/ws", get(console::console_ws))
         // WebSocket workspace shell uses subprotocol-based auth
@@ -486,17 +217,25 @@ pub async fn serve(config: Config) -> anyhow::Result<()> {
         .route("/api/monitoring/ws", get(monitoring::monitoring_ws))
         // OpenAI-compatible proxy endpoint (bearer token auth via SANDBOXED_PROXY_SECRET).
         // LLM payloads with tool outputs and long contexts can exceed the default 2MB
-        // body limit, so set a generous 50MB limit for proxy routes.
+        // body limit, so set a generous limit for proxy routes (50MB by default).
         .nest(
             "/v1",
-            proxy_api::routes().layer(DefaultBodyLimit::max(50 * 1024 * 1024)),
+            proxy_api::routes().layer(DefaultBodyLimit::max(body_limits.proxy)),
         );
 
-    // File upload routes with increased body limit (10GB)
+    // File upload routes with increased body limit (10GB by default)
     let upload_route = Router::new()
         .route("/api/fs/upload", post(fs::upload))
         .route("/api/fs/upload-chunk", post(fs::upload_chunk))
-        .layer(DefaultBodyLimit::max(10 * 1024 * 1024 * 1024));
+        .route(
+            "/api/control/missions/:id/upload",
+            post(fs::upload_to_mission),
+        )
+        .route(
+            "/api/desktop/sessions/:display/files",
+            post(desktop::upload_session_files),
+        )
+        .layer(DefaultBodyLimit::max(body_limits.upload));
 
     let protected_routes = Router::new()
         .route("/api/stats", get(get_stats))
@@ -514,8 +253,9 @@ pub async fn serve(config: Config) -> anyhow::Result<()> {
         .route("/api/control/queue", get(control::get_queue))
         .route(
             "/api/control/queue/:id",
-            axum::routing::delete(control::remove_from_queue),
+            axum::routing::delete(control::remove_from_queue).patch(control::edit_queued_message),
         )
+        .route("/api/control/queue/:id/move", post(control::reorder_queue))
         .route(
             "/api/control/queue",
             axum::routing::delete(control::clear_queue),
@@ -531,6 +271,10 @@ pub async fn serve(config: Config) -> anyhow::Result<()> {
         // Mission management endpoints
         .route("/api/control/missions", get(control::list_missions))
         .route("/api/control/missions", post(control::create_mission))
+        .route(
+            "/api/control/missions/archive",
+            post(control::bulk_archive_missions),
+        )
         .route(
             "/api/control/missions/search",
             get(control::search_missions),
@@ -545,24 +289,66 @@ pub async fn serve(config: Config) -> anyhow::Result<()> {
         )
         .route("/api/control/missions/:id", get(control::get_mission))
         .route(
-            "/api/control/missions/:id/tree",
-            get(control::get_mission_tree),
+            "/api/control/missions/:id/tree",
+            get(control::get_mission_tree),
+        )
+        .route(
+            "/api/control/missions/:id/events",
+            get(control::get_mission_events),
+        )
+        .route(
+            "/api/control/missions/:id/load",
+            post(control::load_mission),
+        )
+        .route(
+            "/api/control/missions/:id/status",
+            post(control::set_mission_status),
+        )
+        .route(
+            "/api/control/missions/:id/title",
+            post(control::set_mission_title),
+        )
+        .route(
+            "/api/control/missions/:id/tags",
+            axum::routing::put(control::set_mission_tags),
+        )
+        .route(
+            "/api/control/missions/:id/kubernetes",
+            post(control::mission_kubernetes),
+        )
+        .route("/api/control/missions/:id/ssh", post(control::mission_ssh))
+        .route("/api/control/missions/:id/pin", post(control::pin_mission))
+        .route(
+            "/api/control/missions/:id/unpin",
+            post(control::unpin_mission),
+        )
+        .route(
+            "/api/control/missions/:id/assign",
+            post(mission_assignment::assign_mission),
+        )
+        .route(
+            "/api/control/missions/:id/unassign",
+            post(mission_assignment::unassign_mission),
+        )
+        .route(
+            "/api/control/missions/:id/watch",
+            post(mission_assignment::watch_mission),
         )
         .route(
-            "/api/control/missions/:id/events",
-            get(control::get_mission_events),
+            "/api/control/missions/:id/unwatch",
+            post(mission_assignment::unwatch_mission),
         )
         .route(
-            "/api/control/missions/:id/load",
-            post(control::load_mission),
+            "/api/control/missions/:id/watchers",
+            axum::routing::put(mission_assignment::set_watchers),
         )
         .route(
-            "/api/control/missions/:id/status",
-            post(control::set_mission_status),
+            "/api/control/missions/:id/archive",
+            post(control::archive_mission),
         )
         .route(
-            "/api/control/missions/:id/title",
-            post(control::set_mission_title),
+            "/api/control/missions/:id/unarchive",
+            post(control::unarchive_mission),
         )
         .route(
             "/api/control/missions/:id/cancel",
@@ -576,6 +362,10 @@ pub async fn serve(config: Config) -> anyhow::Result<()> {
             "/api/control/missions/:id/parallel",
             post(control::start_mission_parallel),
         )
+        .route(
+            "/api/control/missions/:id/backend",
+            post(control::switch_mission_backend),
+        )
         .route(
             "/api/control/missions/:id",
             axum::routing::delete(control::delete_mission),
@@ -598,6 +388,33 @@ pub async fn serve(config: Config) -> anyhow::Result<()> {
             "/api/control/automations",
             get(control::list_active_automations),
         )
+        .route(
+            "/api/control/missions/:id/scheduled-messages",
+            get(scheduled_messages::list_mission_scheduled_messages)
+                .post(scheduled_messages::schedule_message),
+        )
+        .route(
+            "/api/control/missions/:id/retry-policy",
+            get(mission_retry::get_retry_policy)
+                .put(mission_retry::set_retry_policy)
+                .delete(mission_retry::clear_retry_policy),
+        )
+        .route(
+            "/api/control/missions/:id/retries",
+            get(mission_retry::list_retries),
+        )
+        .route(
+            "/api/control/scheduled-messages",
+            get(scheduled_messages::list_scheduled_messages),
+        )
+        .route(
+            "/api/control/scheduled-messages/:id",
+            axum::routing::delete(scheduled_messages::cancel_scheduled_message),
+        )
+        .route(
+            "/api/control/automations/throttle",
+            get(automation_throttle::get_throttle_stats),
+        )
         .route("/api/control/automations/:id", get(control::get_automation))
         .route(
             "/api/control/automations/:id",
@@ -607,6 +424,10 @@ pub async fn serve(config: Config) -> anyhow::Result<()> {
             "/api/control/automations/:id",
             axum::routing::delete(control::delete_automation),
         )
+        .route(
+            "/api/control/automations/:id/stop-policy/preview",
+            post(stop_policy_preview::preview_stop_policy),
+        )
         .route(
             "/api/control/automations/:id/executions",
             get(control::get_automation_executions),
@@ -630,6 +451,7 @@ pub async fn serve(config: Config) -> anyhow::Result<()> {
         // Remote file explorer endpoints (use Authorization header)
         .route("/api/fs/list", get(fs::list))
         .route("/api/fs/download", get(fs::download))
+        .route("/api/fs/download-dir", get(fs::download_dir))
         .route("/api/fs/validate", get(fs::validate))
         .merge(upload_route)
         .route("/api/fs/upload-finalize", post(fs::upload_finalize))
@@ -657,6 +479,10 @@ pub async fn serve(config: Config) -> anyhow::Result<()> {
         )
         // Library management endpoints
         .nest("/api/library", library_api::routes())
+        .route(
+            "/api/prompts/preview",
+            get(library_api::preview_system_prompt),
+        )
         // Workspace management endpoints
         .nest("/api/workspaces", workspaces_api::routes())
         // OpenCode connection endpoints
@@ -669,7 +495,8 @@ pub async fn serve(config: Config) -> anyhow::Result<()> {
         )
         .route(
             "/api/opencode/settings",
-            axum::routing::put(opencode_api::update_opencode_settings),
+            axum::routing::put(opencode_api::update_opencode_settings)
+                .route_layer(middleware::from_fn(auth::require_instance_user)),
         )
         .route(
             "/api/opencode/config",
@@ -677,7 +504,8 @@ pub async fn serve(config: Config) -> anyhow::Result<()> {
         )
         .route(
             "/api/opencode/config",
-            axum::routing::put(opencode_api::update_opencode_config),
+            axum::routing::put(opencode_api::update_opencode_config)
+                .route_layer(middleware::from_fn(auth::require_instance_user)),
         )
         .route(
             "/api/claudecode/config",
@@ -685,31 +513,67 @@ pub async fn serve(config: Config) -> anyhow::Result<()> {
         )
         .route(
             "/api/claudecode/config",
-            axum::routing::put(claudecode_api::update_claudecode_config),
+            axum::routing::put(claudecode_api::update_claudecode_config)
+                .route_layer(middleware::from_fn(auth::require_instance_user)),
         )
         .route("/api/amp/config", get(ampcode_api::get_amp_config))
         .route(
             "/api/amp/config",
-            axum::routing::put(ampcode_api::update_amp_config),
+            axum::routing::put(ampcode_api::update_amp_config)
+                .route_layer(middleware::from_fn(auth::require_instance_user)),
         )
         .route(
             "/api/opencode/restart",
-            post(opencode_api::restart_opencode_service),
+            post(opencode_api::restart_opencode_service)
+                .route_layer(middleware::from_fn(auth::require_instance_user)),
         )
         // AI Provider endpoints
-        .nest("/api/ai/providers", ai_providers_api::routes())
+        .nest(
+            "/api/ai/providers",
+            ai_providers_api::routes()
+                .route_layer(middleware::from_fn(auth::require_instance_user)),
+        )
         // Model routing (chains + health)
-        .nest("/api/model-routing", model_routing_api::routes())
+        .nest(
+            "/api/model-routing",
+            model_routing_api::routes()
+                .route_layer(middleware::from_fn(auth::require_instance_user)),
+        )
         // Proxy API key management
-        .nest("/api/proxy-keys", proxy_keys_api::routes())
+        .nest(
+            "/api/proxy-keys",
+            proxy_keys_api::routes().route_layer(middleware::from_fn(auth::require_instance_user)),
+        )
+        // Remote (HTTP) tool registration and proxied calls
+        .nest(
+            "/api/remote-tools",
+            remote_tools_api::routes()
+                .route_layer(middleware::from_fn(auth::require_instance_user)),
+        )
+        // Audit log of remote actions (instance-wide)
+        .nest(
+            "/api/audit",
+            audit_api::routes().route_layer(middleware::from_fn(auth::require_instance_user)),
+        )
+        // Per-user data export and deletion
+        .nest("/api/users", user_data_api::routes())
         // Secrets management endpoints
-        .nest("/api/secrets", secrets_api::routes())
+        .nest(
+            "/api/secrets",
+            secrets_api::routes().route_layer(middleware::from_fn(auth::require_instance_user)),
+        )
         // Global settings endpoints
-        .nest("/api/settings", settings_api::routes())
+        .nest(
+            "/api/settings",
+            settings_api::routes().route_layer(middleware::from_fn(auth::require_instance_user)),
+        )
         // Desktop session management endpoints
         .nest("/api/desktop", desktop::routes())
         // System component management endpoints
-        .nest("/api/system", system_api::routes())
+        .nest(
+            "/api/system",
+            system_api::routes().route_layer(middleware::from_fn(auth::require_instance_user)),
+        )
         // Auth management endpoints
         .route("/api/auth/status", get(auth::auth_status))
         .route("/api/auth/change-password", post(auth::change_password))
@@ -726,34 +590,596 @@ pub async fn serve(config: Config) -> anyhow::Result<()> {
         )
         .route(
             "/api/backends/:id/config",
-            axum::routing::put(backends_api::update_backend_config),
+            axum::routing::put(backends_api::update_backend_config)
+                .route_layer(middleware::from_fn(auth::require_instance_user)),
+        )
+        .route("/api/rate-limits", get(rate_limit::get_rate_limits))
+        .route("/api/missions", get(control::list_missions))
+        .route(
+            "/api/missions/compare",
+            get(mission_compare::compare_missions),
+        )
+        .route(
+            "/api/missions/:id/stream",
+            get(mission_stream::stream_mission),
+        )
+        .route("/api/missions/:id/read", post(mission_reads::mark_read))
+        .route(
+            "/api/missions/:id/checklist",
+            get(mission_checklist::get_checklist).put(mission_checklist::set_checklist),
+        )
+        .route(
+            "/api/missions/:id/checklist/items",
+            post(mission_checklist::add_item),
+        )
+        .route(
+            "/api/missions/:id/checklist/items/:item_id",
+            axum::routing::patch(mission_checklist::update_item)
+                .delete(mission_checklist::delete_item),
+        )
+        .route(
+            "/api/missions/:id/timeline",
+            get(timeline::get_mission_timeline),
+        )
+        .route(
+            "/api/missions/:id/cost-breakdown",
+            get(cost_breakdown::get_mission_cost_breakdown),
+        )
+        .route(
+            "/api/missions/:id/report",
+            get(mission_report::get_mission_report),
+        )
+        .route(
+            "/api/missions/:id/presence",
+            get(presence::get_presence).post(presence::announce_presence),
+        )
+        .route(
+            "/api/missions/:id/approvals",
+            get(command_approval::list_approvals),
+        )
+        .route(
+            "/api/missions/:id/approvals/:tool_call_id",
+            post(command_approval::resolve),
+        )
+        .route(
+            "/api/missions/:id/status-request",
+            get(status_requests::get_status_request).post(status_requests::resolve_status_request),
+        )
+        .route(
+            "/api/missions/:id/share",
+            post(mission_share::create_share_link),
+        )
+        .route(
+            "/api/missions/:id/comments",
+            get(mission_comments::list_comments).post(mission_comments::create_comment),
+        )
+        .route(
+            "/api/missions/:id/comments/:comment_id",
+            axum::routing::patch(mission_comments::update_comment)
+                .delete(mission_comments::delete_comment),
+        )
+        .route(
+            "/api/missions/:id/environment",
+            get(mission_environment::get_mission_environment),
+        )
+        .route(
+            "/api/missions/:id/export",
+            get(mission_export::export_mission),
+        )
+        .route(
+            "/api/missions/:id/files",
+            get(mission_files::list_mission_files),
+        )
+        .route(
+            "/api/missions/:id/files/stat",
+            get(mission_files::stat_mission_file),
+        )
+        .route(
+            "/api/missions/:id/files/content",
+            get(mission_files::read_mission_file),
+        )
+        .route(
+            "/api/missions/:id/files/download",
+            get(mission_files::download_mission_file),
+        )
+        .route(
+            "/api/missions/:id/messages/:msg_id/feedback",
+            post(message_feedback::post_message_feedback),
+        )
+        .route(
+            "/api/analytics/feedback",
+            get(message_feedback::get_feedback_analytics),
+        )
+        .route(
+            "/api/analytics/feedback/dataset",
+            get(message_feedback::get_feedback_dataset),
+        )
+        .route(
+            "/api/admin/eval",
+            get(eval::list_evals)
+                .post(eval::start_eval)
+                .route_layer(middleware::from_fn(auth::require_instance_user)),
+        )
+        .route(
+            "/api/admin/eval/:id",
+            get(eval::get_eval).route_layer(middleware::from_fn(auth::require_instance_user)),
+        )
+        .route(
+            "/api/admin/store/maintenance",
+            post(store_maintenance::run_maintenance)
+                .route_layer(middleware::from_fn(auth::require_instance_user)),
+        )
+        .route(
+            "/api/admin/store/integrity",
+            get(store_maintenance::integrity_report)
+                .route_layer(middleware::from_fn(auth::require_instance_user)),
+        )
+        .route(
+            "/api/admin/tasks",
+            get(task_supervisor::list_tasks)
+                .route_layer(middleware::from_fn(auth::require_instance_user)),
+        )
+        // Rate limiting runs inside auth so buckets are keyed by user
+        .layer(middleware::from_fn_with_state(
+            Arc::clone(&state),
+            rate_limit::rate_limit,
+        ))
+        .layer(middleware::from_fn_with_state(
+            Arc::clone(&state),
+            auth::require_auth,
+        ))
+        // JSON limit for everything else; the upload routes' own limit wins
+        .layer(DefaultBodyLimit::max(body_limits.json));
+
+    let app = Router::new()
+        .merge(public_routes)
+        .merge(protected_routes)
+        .layer(middleware::from_fn_with_state(
+            body_limits,
+            body_limit::reject_oversized,
+        ))
+        .layer(CorsLayer::permissive())
+        .layer(TraceLayer::new_for_http())
+        .with_state(Arc::clone(&state));
+
+    let addr = format!("{}:{}", state.config.host, state.config.port);
+    let listener = tokio::net::TcpListener::bind(&addr).await?;
+
+    tracing::info!("Server listening on {}", addr);
+
+    // Setup graceful shutdown on SIGTERM/SIGINT
+    let shutdown_state = Arc::clone(&state);
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            shutdown_signal(shutdown_state).await;
+        })
+        .await?;
+
+    Ok(())
+}
+
+/// Build the application state and start its background tasks.
+///
+/// Used by the HTTP server and by command-line modes that drive missions
+/// without serving the API.
+pub async fn init_state(config: Config) -> anyhow::Result<Arc<AppState>> {
+    let mut config = config;
+    // Start monitoring background collector early so clients get history immediately
+    monitoring::init_monitoring();
+
+    // Initialize MCP registry
+    let mcp = Arc::new(McpRegistry::new(&config.working_dir).await);
+    if let Err(e) = crate::opencode_config::ensure_global_config(&mcp).await {
+        tracing::warn!("Failed to ensure OpenCode global config: {}", e);
+    }
+    // Refresh all MCPs in background
+    {
+        let mcp_clone = Arc::clone(&mcp);
+        tokio::spawn(async move {
+            mcp_clone.refresh_all().await;
+        });
+    }
+
+    // Initialize workspace store (loads from disk and recovers orphaned containers)
+    let workspaces = Arc::new(workspace::WorkspaceStore::new(config.working_dir.clone()).await);
+
+    // Enable per-container metrics collection in the monitoring background task
+    monitoring::init_monitoring_workspaces(Arc::clone(&workspaces)).await;
+
+    // Initialize OpenCode connection store
+    let opencode_connections = Arc::new(
+        crate::opencode_config::OpenCodeStore::new(
+            config
+                .working_dir
+                .join(".sandboxed-sh/opencode_connections.json"),
+        )
+        .await,
+    );
+
+    // Initialize AI provider store
+    let ai_providers = Arc::new(
+        crate::ai_providers::AIProviderStore::new(config.working_dir.join(AI_PROVIDERS_PATH)).await,
+    );
+    let pending_oauth = Arc::new(RwLock::new(HashMap::new()));
+
+    // Initialize provider health tracker and model chain store
+    let health_tracker = Arc::new(crate::provider_health::ProviderHealthTracker::new());
+    let chain_store = Arc::new(
+        crate::provider_health::ModelChainStore::new(
+            config.working_dir.join(".sandboxed-sh/model_chains.json"),
+        )
+        .await,
+    );
+
+    // Initialize proxy API key store
+    let proxy_api_keys = Arc::new(
+        super::proxy_keys::ProxyApiKeyStore::new(
+            config.working_dir.join(".sandboxed-sh/proxy_api_keys.json"),
+        )
+        .await,
+    );
+    let slack = Arc::new(
+        slack::SlackIntegration::new(
+            slack::SlackConfig::from_env(),
+            config.working_dir.join(".sandboxed-sh/slack_threads.json"),
+        )
+        .await,
+    );
+    let audit = Arc::new(audit_api::AuditLog::new(
+        config.working_dir.join(".sandboxed-sh/audit.jsonl"),
+    ));
+    let deferred_requests = Arc::new(
+        deferred_proxy_api::DeferredRequestStore::new(
+            config
+                .working_dir
+                .join(".sandboxed-sh/deferred_requests.json"),
+        )
+        .await,
+    );
+
+    // Initialize secrets store
+    let secrets = match crate::secrets::SecretsStore::new(&config.working_dir).await {
+        Ok(store) => {
+            tracing::info!("Secrets store initialized");
+            Some(Arc::new(store))
+        }
+        Err(e) => {
+            tracing::warn!("Failed to initialize secrets store: {}", e);
+            None
+        }
+    };
+
+    // Initialize remote tool store (auth header values live in the vault)
+    let remote_tools = Arc::new(
+        remote_tools_api::RemoteToolStore::new(
+            config.working_dir.join(".sandboxed-sh/remote_tools.json"),
+            secrets.clone(),
+        )
+        .await,
+    );
+
+    // Initialize console session pool for WebSocket reconnection
+    let console_pool = Arc::new(console::SessionPool::new());
+    Arc::clone(&console_pool).start_cleanup_task();
+
+    // Initialize global settings store
+    let settings = Arc::new(crate::settings::SettingsStore::new(&config.working_dir).await);
+    settings.init_cached_values();
+
+    // Initialize backend config store (persisted settings).
+    // Probe each CLI binary so backends whose CLI is missing default to disabled.
+    // Persisted configs are preserved — this only affects fresh installs or new backends.
+    let opencode_detected = cli_available("opencode");
+    let claude_detected = cli_available("claude");
+    let amp_detected = cli_available("amp");
+    let codex_detected = cli_available("codex");
+    tracing::info!(
+        opencode = opencode_detected,
+        claude = claude_detected,
+        amp = amp_detected,
+        codex = codex_detected,
+        "CLI detection for backend defaults"
+    );
+
+    let backend_defaults = vec![
+        {
+            let mut entry = BackendConfigEntry::new(
+                "opencode",
+                "OpenCode",
+                serde_json::json!({
+                    "base_url": config.opencode_base_url,
+                    "default_agent": config.opencode_agent,
+                    "permissive": config.opencode_permissive,
+                }),
+            );
+            entry.enabled = opencode_detected;
+            entry
+        },
+        {
+            let mut entry =
+                BackendConfigEntry::new("claudecode", "Claude Code", serde_json::json!({}));
+            entry.enabled = claude_detected;
+            entry
+        },
+        {
+            let mut entry = BackendConfigEntry::new("amp", "Amp", serde_json::json!({}));
+            entry.enabled = amp_detected;
+            entry
+        },
+        {
+            let mut entry = BackendConfigEntry::new("codex", "Codex", serde_json::json!({}));
+            entry.enabled = codex_detected;
+            entry
+        },
+        {
+            // Opt-in: a local daemon has to be running and have models pulled.
+            let mut entry = BackendConfigEntry::new(
+                "ollama",
+                "Ollama",
+                serde_json::json!({ "base_url": config.ollama_base_url }),
+            );
+            entry.enabled = false;
+            entry
+        },
+    ];
+    let backend_configs = Arc::new(
+        crate::backend_config::BackendConfigStore::new(
+            config.working_dir.join(".sandboxed-sh/backend_config.json"),
+            backend_defaults,
         )
-        .layer(middleware::from_fn_with_state(
-            Arc::clone(&state),
-            auth::require_auth,
+        .await,
+    );
+
+    // Apply persisted OpenCode settings (if present)
+    if let Some(entry) = backend_configs.get("opencode").await {
+        if let Some(settings) = entry.settings.as_object() {
+            if let Some(base_url) = settings.get("base_url").and_then(|v| v.as_str()) {
+                if !base_url.trim().is_empty() {
+                    config.opencode_base_url = base_url.to_string();
+                }
+            }
+            if let Some(agent) = settings.get("default_agent").and_then(|v| v.as_str()) {
+                if !agent.trim().is_empty() {
+                    config.opencode_agent = Some(agent.to_string());
+                }
+            }
+            if let Some(permissive) = settings.get("permissive").and_then(|v| v.as_bool()) {
+                config.opencode_permissive = permissive;
+            }
+        }
+    }
+
+    // Apply persisted Ollama daemon URL (if present)
+    if let Some(entry) = backend_configs.get("ollama").await {
+        if let Some(base_url) = entry.settings.get("base_url").and_then(|v| v.as_str()) {
+            if !base_url.trim().is_empty() {
+                config.ollama_base_url = base_url.to_string();
+            }
+        }
+    }
+
+    // Always use OpenCode backend
+    let root_agent: AgentRef = Arc::new(OpenCodeAgent::new(config.clone()));
+
+    // Initialize backend registry with OpenCode and Claude Code backends
+    let opencode_base_url = config.opencode_base_url.clone();
+    let opencode_default_agent = config.opencode_agent.clone();
+    let opencode_permissive = config.opencode_permissive;
+
+    // Determine default backend: env var, or first available with priority claudecode → opencode → amp → codex
+    let default_backend = config.default_backend.clone().unwrap_or_else(|| {
+        if claude_detected {
+            "claudecode".to_string()
+        } else if opencode_detected {
+            "opencode".to_string()
+        } else if amp_detected {
+            "amp".to_string()
+        } else if codex_detected {
+            "codex".to_string()
+        } else {
+            // Fallback to claudecode even if not detected (will show warning in UI)
+            tracing::warn!(
+                "No backend CLIs detected. Defaulting to claudecode. Please install at least one backend."
+            );
+            "claudecode".to_string()
+        }
+    });
+
+    tracing::info!(
+        "Default backend: {} (claudecode={}, opencode={}, amp={}, codex={})",
+        default_backend,
+        claude_detected,
+        opencode_detected,
+        amp_detected,
+        codex_detected
+    );
+
+    let mut backend_registry = BackendRegistry::new(default_backend);
+    backend_registry.register(crate::backend::opencode::registry_entry(
+        opencode_base_url.clone(),
+        opencode_default_agent,
+        opencode_permissive,
+    ));
+    backend_registry.register(crate::backend::claudecode::registry_entry());
+    backend_registry.register(crate::backend::amp::registry_entry());
+    backend_registry.register(crate::backend::codex::registry_entry());
+    backend_registry.register(crate::backend::ollama::registry_entry(
+        &config.ollama_base_url,
+    ));
+    if config.mock_llm_enabled {
+        backend_registry.register(crate::backend::mock::registry_entry(
+            config.mock_llm_script.as_deref(),
         ));
+    }
+    let backend_count = backend_registry.list().len();
+    let backend_registry = Arc::new(RwLock::new(backend_registry));
+    tracing::info!(
+        "Backend registry initialized with {} backends",
+        backend_count
+    );
 
-    let app = Router::new()
-        .merge(public_routes)
-        .merge(protected_routes)
-        .layer(CorsLayer::permissive())
-        .layer(TraceLayer::new_for_http())
-        .with_state(Arc::clone(&state));
+    // Note: No central OpenCode server cleanup needed - missions use per-workspace CLI execution
 
-    let addr = format!("{}:{}", config.host, config.port);
-    let listener = tokio::net::TcpListener::bind(&addr).await?;
+    // Initialize configuration library (optional - can also be configured at runtime)
+    // Must be created before ControlHub so it can be passed to control sessions
+    let library: library_api::SharedLibrary = Arc::new(RwLock::new(None));
+    // Read library_remote from settings (which falls back to env var if not configured)
+    let library_remote = settings.get_library_remote().await;
+    if let Some(library_remote) = library_remote {
+        let library_clone = Arc::clone(&library);
+        let library_path = config.library_path.clone();
+        let workspaces_clone = Arc::clone(&workspaces);
+        tokio::spawn(async move {
+            match crate::library::LibraryStore::new(library_path, &library_remote).await {
+                Ok(store) => {
+                    if let Ok(plugins) = store.get_plugins().await {
+                        if let Err(e) = crate::opencode_config::sync_global_plugins(&plugins).await
+                        {
+                            tracing::warn!("Failed to sync OpenCode plugins: {}", e);
+                        }
+                    }
+                    tracing::info!("Configuration library initialized from {}", library_remote);
+                    *library_clone.write().await = Some(Arc::new(store));
 
-    tracing::info!("Server listening on {}", addr);
+                    let workspaces = workspaces_clone.list().await;
+                    if let Some(library) = library_clone.read().await.as_ref() {
+                        for workspace in workspaces {
+                            let is_default_host = workspace.id == workspace::DEFAULT_WORKSPACE_ID
+                                && workspace.workspace_type == workspace::WorkspaceType::Host;
+                            if is_default_host || !workspace.skills.is_empty() {
+                                if let Err(e) =
+                                    workspace::sync_workspace_skills(&workspace, library).await
+                                {
+                                    tracing::warn!(
+                                        workspace = %workspace.name,
+                                        error = %e,
+                                        "Failed to sync skills after library init"
+                                    );
+                                }
+                            }
+                        }
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to initialize configuration library: {}", e);
+                }
+            }
+        });
+    } else {
+        tracing::info!("Configuration library disabled (no remote configured)");
+    }
 
-    // Setup graceful shutdown on SIGTERM/SIGINT
-    let shutdown_state = Arc::clone(&state);
-    axum::serve(listener, app)
-        .with_graceful_shutdown(async move {
-            shutdown_signal(shutdown_state).await;
-        })
-        .await?;
+    let tenant_libraries: crate::tenant::SharedTenantLibraries = Arc::default();
 
-    Ok(())
+    // Spawn the single global control session actor.
+    let control_state = control::ControlHub::new(
+        config.clone(),
+        Arc::clone(&root_agent),
+        Arc::clone(&mcp),
+        Arc::clone(&workspaces),
+        Arc::clone(&library),
+        Arc::clone(&tenant_libraries),
+        secrets.clone(),
+    );
+
+    let state = Arc::new(AppState {
+        config: config.clone(),
+        tasks: RwLock::new(HashMap::new()),
+        root_agent,
+        control: control_state,
+        mcp,
+        library,
+        tenant_libraries,
+        workspaces,
+        opencode_connections,
+        opencode_agents_cache: RwLock::new(opencode_api::OpenCodeAgentsCache::default()),
+        ai_providers,
+        pending_oauth,
+        secrets,
+        console_pool,
+        settings,
+        backend_registry,
+        backend_configs,
+        model_catalog: Arc::new(RwLock::new(HashMap::new())),
+        health_tracker,
+        chain_store,
+        http_client: reqwest::Client::builder()
+            // No global timeout — it applies to the full response body including
+            // streaming chunks, which would kill long-running LLM generations.
+            // Per-request timeouts are set in the proxy where needed.
+            .connect_timeout(std::time::Duration::from_secs(10))
+            .build()
+            .unwrap_or_default(),
+        proxy_secret: std::env::var("SANDBOXED_PROXY_SECRET")
+            .ok()
+            .filter(|s| !s.trim().is_empty())
+            .unwrap_or_else(|| {
+                let secret = uuid::Uuid::new_v4().to_string();
+                tracing::info!("No SANDBOXED_PROXY_SECRET set; generated ephemeral proxy secret");
+                // Also set in env so mission_runner can read it for OpenCode config.
+                std::env::set_var("SANDBOXED_PROXY_SECRET", &secret);
+                secret
+            }),
+        proxy_api_keys,
+        deferred_requests,
+        remote_tools,
+        audit,
+        rate_limiter: Arc::new(rate_limit::RateLimiter::from_env()),
+        eval_runs: Arc::default(),
+        slack,
+    });
+
+    // Start background desktop session cleanup task
+    {
+        let state_clone = Arc::clone(&state);
+        task_supervisor::global().spawn(
+            "desktop_cleanup",
+            task_supervisor::RestartPolicy::on_panic(),
+            move || desktop::start_cleanup_task(Arc::clone(&state_clone)),
+        );
+    }
+
+    // Start background OAuth token refresher task
+    {
+        let ai_providers = Arc::clone(&state.ai_providers);
+        task_supervisor::global().spawn(
+            "oauth_token_refresher",
+            task_supervisor::RestartPolicy::on_panic(),
+            move || oauth_token_refresher_loop(Arc::clone(&ai_providers)),
+        );
+    }
+
+    // Start deferred proxy queue worker.
+    deferred_proxy_api::start_worker(Arc::clone(&state));
+
+    // Post mission updates to Slack
+    slack::start_notifier(Arc::clone(&state));
+
+    // Telegram bot (long-polls for commands, sends notifications)
+    telegram::start(Arc::clone(&state));
+
+    // Configured notification plugins (webhook, Matrix, ...)
+    notifications::start(Arc::clone(&state));
+
+    // Fetch model catalog from provider APIs in background
+    {
+        let catalog = Arc::clone(&state.model_catalog);
+        let ai_providers = Arc::clone(&state.ai_providers);
+        let working_dir = config.working_dir.clone();
+        tokio::spawn(async move {
+            let fetched = super::providers::fetch_model_catalog(&ai_providers, &working_dir).await;
+            let provider_count = fetched.len();
+            let model_count: usize = fetched.values().map(|v| v.len()).sum();
+            *catalog.write().await = fetched;
+            tracing::info!(
+                "Model catalog populated: {} models from {} providers",
+                model_count,
+                provider_count
+            );
+        });
+    }
+
+    Ok(state)
 }
 
 /// Wait for shutdown signal and mark running missions as interrupted.
@@ -786,7 +1212,6 @@ async fn shutdown_signal(state: Arc<AppState>) {
     let sessions = state.control.all_sessions().await;
     if sessions.is_empty() {
         tracing::info!("No active control sessions to shut down");
-        return;
     }
 
     let mut all_interrupted: Vec<Uuid> = Vec::new();
@@ -821,6 +1246,12 @@ async fn shutdown_signal(state: Arc<AppState>) {
         );
     }
 
+    // Stop background loops last, so the event logger persists the status
+    // changes above
+    task_supervisor::global()
+        .shutdown(task_supervisor::SHUTDOWN_GRACE)
+        .await;
+
     tracing::info!("Graceful shutdown complete");
 }
 
@@ -0,0 +1,270 @@
+//! One-shot scheduled messages.
+//!
+//! A scheduled message is sent to its mission once `send_at` passes ("kick off
+//! the deploy at 22:00") without setting up a full automation. Messages are
+//! stored persistently and dispatched by [`scheduler_loop`], which each control
+//! session runs next to the automation scheduler. A message is claimed
+//! (pending -> sent) before it is dispatched, so cancelling and sending can't
+//! both win.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use tokio::sync::{mpsc, oneshot};
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::control::ControlCommand;
+use super::mission_store::{now_string, MissionStore, ScheduledMessage, ScheduledMessageStatus};
+use super::routes::AppState;
+use crate::util::internal_error;
+
+/// How often the scheduler checks for due messages.
+const CHECK_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+#[derive(Debug, Deserialize)]
+pub struct ScheduleMessageRequest {
+    pub content: String,
+    /// RFC3339 time to send the message at (must be in the future)
+    pub send_at: String,
+    /// Optional agent override for the message
+    pub agent: Option<String>,
+}
+
+/// Parse and validate a `send_at` time, normalized to UTC.
+fn parse_send_at(value: &str, now: DateTime<Utc>) -> Result<DateTime<Utc>, String> {
+    let send_at = DateTime::parse_from_rfc3339(value.trim())
+        .map_err(|e| format!("send_at must be an RFC3339 timestamp: {}", e))?
+        .with_timezone(&Utc);
+    if send_at <= now {
+        return Err("send_at must be in the future".to_string());
+    }
+    Ok(send_at)
+}
+
+/// Whether a pending message is due at `now`. Unparseable times are due so
+/// they surface (as sent or failed) instead of lingering forever.
+fn is_due(message: &ScheduledMessage, now: DateTime<Utc>) -> bool {
+    DateTime::parse_from_rfc3339(&message.send_at)
+        .map(|send_at| send_at <= now)
+        .unwrap_or(true)
+}
+
+/// Schedule a message for a mission.
+pub async fn schedule_message(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+    Json(req): Json<ScheduleMessageRequest>,
+) -> Result<Json<ScheduledMessage>, (StatusCode, String)> {
+    if req.content.trim().is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "content must not be empty".to_string(),
+        ));
+    }
+    let send_at =
+        parse_send_at(&req.send_at, Utc::now()).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    let control = state.control.get_or_spawn(&user).await;
+    let store = &control.mission_store;
+    store
+        .get_mission(mission_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Mission not found".to_string()))?;
+
+    let message = ScheduledMessage {
+        id: Uuid::new_v4(),
+        mission_id,
+        content: req.content,
+        agent: req.agent.filter(|agent| !agent.trim().is_empty()),
+        send_at: send_at.to_rfc3339(),
+        created_at: now_string(),
+        status: ScheduledMessageStatus::Pending,
+        sent_at: None,
+        error: None,
+    };
+    let message = store
+        .create_scheduled_message(message)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(message))
+}
+
+/// List all scheduled messages for a mission, including sent and cancelled ones.
+pub async fn list_mission_scheduled_messages(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+) -> Result<Json<Vec<ScheduledMessage>>, (StatusCode, String)> {
+    let control = state.control.get_or_spawn(&user).await;
+    let messages = control
+        .mission_store
+        .list_scheduled_messages(Some(mission_id), None)
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(messages))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListScheduledMessagesQuery {
+    /// Defaults to pending messages
+    pub status: Option<ScheduledMessageStatus>,
+}
+
+/// List scheduled messages across missions (pending by default).
+pub async fn list_scheduled_messages(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Query(params): Query<ListScheduledMessagesQuery>,
+) -> Result<Json<Vec<ScheduledMessage>>, (StatusCode, String)> {
+    let control = state.control.get_or_spawn(&user).await;
+    let status = params.status.unwrap_or(ScheduledMessageStatus::Pending);
+    let messages = control
+        .mission_store
+        .list_scheduled_messages(None, Some(status))
+        .await
+        .map_err(internal_error)?;
+    Ok(Json(messages))
+}
+
+/// Cancel a pending scheduled message.
+pub async fn cancel_scheduled_message(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ScheduledMessage>, (StatusCode, String)> {
+    let control = state.control.get_or_spawn(&user).await;
+    let store = &control.mission_store;
+    let cancelled = store
+        .transition_scheduled_message(
+            id,
+            ScheduledMessageStatus::Pending,
+            ScheduledMessageStatus::Cancelled,
+            None,
+        )
+        .await
+        .map_err(internal_error)?;
+    let message = store
+        .get_scheduled_message(id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                "Scheduled message not found".to_string(),
+            )
+        })?;
+    if !cancelled {
+        return Err((
+            StatusCode::CONFLICT,
+            format!("Scheduled message is already {}", message.status.as_str()),
+        ));
+    }
+    Ok(Json(message))
+}
+
+/// Background task that sends scheduled messages once they are due.
+pub async fn scheduler_loop(
+    mission_store: Arc<dyn MissionStore>,
+    cmd_tx: mpsc::Sender<ControlCommand>,
+) {
+    tracing::info!("Scheduled message task started");
+    let mut logged_unsupported = false;
+
+    loop {
+        tokio::time::sleep(CHECK_INTERVAL).await;
+
+        let pending = match mission_store
+            .list_scheduled_messages(None, Some(ScheduledMessageStatus::Pending))
+            .await
+        {
+            Ok(pending) => pending,
+            Err(e) => {
+                if !logged_unsupported {
+                    tracing::warn!("Scheduled messages unavailable: {}", e);
+                    logged_unsupported = true;
+                }
+                continue;
+            }
+        };
+
+        let now = Utc::now();
+        for message in pending.into_iter().filter(|m| is_due(m, now)) {
+            match mission_store
+                .transition_scheduled_message(
+                    message.id,
+                    ScheduledMessageStatus::Pending,
+                    ScheduledMessageStatus::Sent,
+                    None,
+                )
+                .await
+            {
+                Ok(true) => {}
+                // Cancelled since we listed it
+                Ok(false) => continue,
+                Err(e) => {
+                    tracing::warn!("Failed to claim scheduled message {}: {}", message.id, e);
+                    continue;
+                }
+            }
+
+            tracing::info!(
+                "Sending scheduled message {} to mission {}",
+                message.id,
+                message.mission_id
+            );
+            let (respond, _) = oneshot::channel();
+            let sent = cmd_tx
+                .send(ControlCommand::UserMessage {
+                    id: message.id,
+                    content: message.content,
+                    agent: message.agent,
+                    target_mission_id: Some(message.mission_id),
+                    respond,
+                })
+                .await;
+            if let Err(e) = sent {
+                tracing::warn!("Failed to send scheduled message {}: {}", message.id, e);
+                if let Err(e) = mission_store
+                    .transition_scheduled_message(
+                        message.id,
+                        ScheduledMessageStatus::Sent,
+                        ScheduledMessageStatus::Failed,
+                        Some("Control session unavailable".to_string()),
+                    )
+                    .await
+                {
+                    tracing::warn!(
+                        "Failed to mark scheduled message {} as failed: {}",
+                        message.id,
+                        e
+                    );
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn send_at_must_be_future_rfc3339() {
+        let now = DateTime::parse_from_rfc3339("2026-01-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let parsed = parse_send_at("2026-01-01T22:00:00+02:00", now).unwrap();
+        assert_eq!(parsed.to_rfc3339(), "2026-01-01T20:00:00+00:00");
+        assert!(parse_send_at("2026-01-01T11:59:59Z", now).is_err());
+        assert!(parse_send_at("22:00", now).is_err());
+    }
+}
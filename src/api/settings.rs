@@ -36,6 +36,9 @@ pub struct SettingsResponse {
     pub sandboxed_repo_path: Option<String>,
     pub rtk_enabled: Option<bool>,
     pub max_parallel_missions: Option<usize>,
+    pub reserved_interactive_slots: Option<usize>,
+    pub dry_run: Option<bool>,
+    pub locale: Option<String>,
 }
 
 impl From<Settings> for SettingsResponse {
@@ -45,6 +48,9 @@ impl From<Settings> for SettingsResponse {
             sandboxed_repo_path: settings.sandboxed_repo_path,
             rtk_enabled: settings.rtk_enabled,
             max_parallel_missions: settings.max_parallel_missions,
+            reserved_interactive_slots: settings.reserved_interactive_slots,
+            dry_run: settings.dry_run,
+            locale: settings.locale,
         }
     }
 }
@@ -60,6 +66,14 @@ pub struct UpdateSettingsRequest {
     pub rtk_enabled: Option<bool>,
     #[serde(default)]
     pub max_parallel_missions: Option<usize>,
+    /// Parallel slots kept free for interactive missions
+    #[serde(default)]
+    pub reserved_interactive_slots: Option<usize>,
+    #[serde(default)]
+    pub dry_run: Option<bool>,
+    /// Default locale for agent-facing metadata (null clears it)
+    #[serde(default)]
+    pub locale: Option<Option<String>>,
 }
 
 /// Request to update library remote specifically.
@@ -115,6 +129,31 @@ async fn update_settings(
         new_settings.max_parallel_missions = Some(value);
         crate::settings::set_max_parallel_missions_cached(value);
     }
+    if let Some(value) = req.reserved_interactive_slots {
+        new_settings.reserved_interactive_slots = Some(value);
+        crate::settings::set_reserved_interactive_slots_cached(value);
+    }
+    if let Some(value) = req.dry_run {
+        new_settings.dry_run = Some(value);
+        crate::settings::set_dry_run_cached(value);
+    }
+    if let Some(value) = req.locale {
+        let value = value
+            .map(|v| crate::i18n::normalize(&v))
+            .filter(|v| !v.is_empty());
+        if let Some(locale) = value.as_deref().filter(|l| !crate::i18n::is_supported(l)) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Unsupported locale {}; available: {}",
+                    locale,
+                    crate::i18n::available_locales().join(", ")
+                ),
+            ));
+        }
+        crate::i18n::set_default_locale_cached(value.as_deref());
+        new_settings.locale = value;
+    }
 
     state
         .settings
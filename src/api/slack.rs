@@ -0,0 +1,746 @@
+//! Slack integration.
+//!
+//! - `POST /api/slack/commands` - slash command. `/sandboxed <prompt>` (or
+//!   `new <prompt>`) starts a mission in the workspace routed to the channel,
+//!   `send <mission id> <message>` queues a message to a mission, `help` lists
+//!   the commands.
+//! - `POST /api/slack/events` - Events API. A reply in the thread of a mission
+//!   started from Slack is sent to that mission as a user message.
+//! - Agent replies of Slack-started missions are posted to their thread, and
+//!   every mission that finishes is announced in its thread or in the channel
+//!   routed to its workspace.
+//!
+//! Requests are verified with the app's signing secret
+//! (`SANDBOXED_SH_SLACK_SIGNING_SECRET`, which also enables the integration);
+//! posting needs a bot token (`SANDBOXED_SH_SLACK_BOT_TOKEN`).
+//! `SANDBOXED_SH_SLACK_CHANNELS` routes channels to workspaces
+//! (`C0123=<workspace id>,...`); other channels use the host workspace, and
+//! missions of unrouted workspaces are announced in
+//! `SANDBOXED_SH_SLACK_DEFAULT_CHANNEL`. Missions belong to the instance user.
+//! Thread-to-mission links are kept in `.sandboxed-sh/slack_threads.json`.
+
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use axum::{
+    body::Bytes,
+    extract::State,
+    http::{HeaderMap, StatusCode},
+    Extension, Json,
+};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use tokio::sync::{broadcast, oneshot, RwLock};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::control::{
+    create_mission, AgentEvent, ControlCommand, CreateMissionRequest, MissionStatus,
+};
+use super::routes::AppState;
+use super::task_supervisor;
+use crate::i18n;
+use crate::util::{env_var_trimmed, internal_error};
+
+const POST_MESSAGE_URL: &str = "https://slack.com/api/chat.postMessage";
+
+/// Requests older than this are rejected as replays.
+const MAX_REQUEST_AGE_SECS: i64 = 5 * 60;
+
+const MAX_TITLE_CHARS: usize = 80;
+
+/// Slack rejects longer messages; agent replies are cut to this.
+const MAX_POST_CHARS: usize = 3500;
+
+const USAGE: &str = "Usage:\n\
+    • `/sandboxed <prompt>` or `/sandboxed new <prompt>` - start a mission in this channel's workspace\n\
+    • `/sandboxed send <mission id> <message>` - send a message to a mission\n\
+    Reply in a mission's thread to talk to it.";
+
+#[derive(Debug, Clone, Default)]
+pub struct SlackConfig {
+    pub signing_secret: String,
+    pub bot_token: Option<String>,
+    /// Channel id to workspace id
+    pub channels: Vec<(String, Uuid)>,
+    pub default_channel: Option<String>,
+    /// Dashboard base URL for mission links (relative links when unset)
+    pub public_url: Option<String>,
+}
+
+impl SlackConfig {
+    /// `None` unless a signing secret is set.
+    pub fn from_env() -> Option<Self> {
+        Some(Self {
+            signing_secret: env_var_trimmed("SANDBOXED_SH_SLACK_SIGNING_SECRET")?,
+            bot_token: env_var_trimmed("SANDBOXED_SH_SLACK_BOT_TOKEN"),
+            channels: env_var_trimmed("SANDBOXED_SH_SLACK_CHANNELS")
+                .map(|v| parse_channel_routes(&v))
+                .unwrap_or_default(),
+            default_channel: env_var_trimmed("SANDBOXED_SH_SLACK_DEFAULT_CHANNEL"),
+            public_url: env_var_trimmed("SANDBOXED_SH_PUBLIC_URL"),
+        })
+    }
+
+    fn workspace_for_channel(&self, channel: &str) -> Option<Uuid> {
+        self.channels
+            .iter()
+            .find(|(c, _)| c == channel)
+            .map(|(_, ws)| *ws)
+    }
+
+    fn channel_for_workspace(&self, workspace_id: Uuid) -> Option<&str> {
+        self.channels
+            .iter()
+            .find(|(_, ws)| *ws == workspace_id)
+            .map(|(c, _)| c.as_str())
+            .or(self.default_channel.as_deref())
+    }
+
+    fn mission_url(&self, mission_id: Uuid) -> String {
+        crate::util::mission_url(self.public_url.as_deref().unwrap_or_default(), mission_id)
+    }
+}
+
+/// Parse `C0123=<workspace id>,C0456=<workspace id>`; malformed entries are
+/// skipped with a warning.
+fn parse_channel_routes(value: &str) -> Vec<(String, Uuid)> {
+    value
+        .split(',')
+        .filter(|entry| !entry.trim().is_empty())
+        .filter_map(|entry| {
+            let route = entry
+                .split_once('=')
+                .and_then(|(channel, ws)| Some((channel.trim(), ws.trim().parse().ok()?)));
+            if route.is_none() {
+                tracing::warn!(entry = %entry, "Ignoring malformed Slack channel route");
+            }
+            route.map(|(channel, ws)| (channel.to_string(), ws))
+        })
+        .collect()
+}
+
+/// A Slack thread that belongs to a mission.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SlackThread {
+    pub channel: String,
+    pub thread_ts: String,
+    pub mission_id: Uuid,
+}
+
+pub struct SlackIntegration {
+    pub config: Option<SlackConfig>,
+    threads: RwLock<Vec<SlackThread>>,
+    storage_path: PathBuf,
+}
+
+pub type SharedSlack = Arc<SlackIntegration>;
+
+impl SlackIntegration {
+    pub async fn new(config: Option<SlackConfig>, storage_path: PathBuf) -> Self {
+        let threads = std::fs::read_to_string(&storage_path)
+            .ok()
+            .and_then(|contents| serde_json::from_str(&contents).ok())
+            .unwrap_or_default();
+        Self {
+            config,
+            threads: RwLock::new(threads),
+            storage_path,
+        }
+    }
+
+    async fn link_thread(&self, thread: SlackThread) {
+        let mut threads = self.threads.write().await;
+        threads.retain(|t| t.mission_id != thread.mission_id);
+        threads.push(thread);
+        if let Err(e) = self.save_to_disk(&threads) {
+            tracing::warn!("Failed to save Slack threads: {}", e);
+        }
+    }
+
+    async fn thread_for_mission(&self, mission_id: Uuid) -> Option<SlackThread> {
+        self.threads
+            .read()
+            .await
+            .iter()
+            .find(|t| t.mission_id == mission_id)
+            .cloned()
+    }
+
+    async fn mission_for_thread(&self, channel: &str, thread_ts: &str) -> Option<Uuid> {
+        self.threads
+            .read()
+            .await
+            .iter()
+            .find(|t| t.channel == channel && t.thread_ts == thread_ts)
+            .map(|t| t.mission_id)
+    }
+
+    fn save_to_disk(&self, threads: &[SlackThread]) -> std::io::Result<()> {
+        if let Some(parent) = self.storage_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let contents = serde_json::to_string_pretty(threads)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        let tmp_path = self.storage_path.with_extension("tmp");
+        std::fs::write(&tmp_path, contents)?;
+        std::fs::rename(&tmp_path, &self.storage_path)
+    }
+}
+
+/// Check `X-Slack-Signature` (`v0=` HMAC-SHA256 of `v0:<timestamp>:<body>`)
+/// and that the request is recent.
+pub fn verify_signature(
+    secret: &str,
+    timestamp: &str,
+    body: &[u8],
+    signature: &str,
+    now: i64,
+) -> bool {
+    let Ok(sent_at) = timestamp.parse::<i64>() else {
+        return false;
+    };
+    if (now - sent_at).abs() > MAX_REQUEST_AGE_SECS {
+        return false;
+    }
+    let Some(Ok(signature)) = signature.strip_prefix("v0=").map(hex::decode) else {
+        return false;
+    };
+    let Ok(mut mac) = Hmac::<Sha256>::new_from_slice(secret.as_bytes()) else {
+        return false;
+    };
+    mac.update(format!("v0:{}:", timestamp).as_bytes());
+    mac.update(body);
+    mac.verify_slice(&signature).is_ok()
+}
+
+fn verify_request<'a>(
+    state: &'a AppState,
+    headers: &HeaderMap,
+    body: &[u8],
+) -> Result<&'a SlackConfig, (StatusCode, String)> {
+    let config = state.slack.config.as_ref().ok_or_else(|| {
+        (
+            StatusCode::NOT_FOUND,
+            "Slack integration is not configured".to_string(),
+        )
+    })?;
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+    };
+    if !verify_signature(
+        &config.signing_secret,
+        header("x-slack-request-timestamp"),
+        body,
+        header("x-slack-signature"),
+        chrono::Utc::now().timestamp(),
+    ) {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            "Invalid Slack signature".to_string(),
+        ));
+    }
+    Ok(config)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SlackCommand {
+    New(String),
+    Send { mission_id: Uuid, message: String },
+    Help,
+}
+
+pub fn parse_command(text: &str) -> Result<SlackCommand, String> {
+    let text = text.trim();
+    let (word, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    let rest = rest.trim();
+    match word.to_ascii_lowercase().as_str() {
+        "" | "help" => Ok(SlackCommand::Help),
+        "new" if rest.is_empty() => {
+            Err("Tell the mission what to do: `/sandboxed new <prompt>`".to_string())
+        }
+        "new" => Ok(SlackCommand::New(rest.to_string())),
+        "send" => {
+            let (id, message) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            let mission_id = id
+                .parse()
+                .map_err(|_| format!("`{}` is not a mission id", id))?;
+            let message = message.trim();
+            if message.is_empty() {
+                return Err("Nothing to send: `/sandboxed send <mission id> <message>`".to_string());
+            }
+            Ok(SlackCommand::Send {
+                mission_id,
+                message: message.to_string(),
+            })
+        }
+        _ => Ok(SlackCommand::New(text.to_string())),
+    }
+}
+
+/// Escape text for Slack's mrkdwn.
+fn escape_mrkdwn(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn truncate_chars(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((cut, _)) => format!("{}…", &text[..cut]),
+        None => text.to_string(),
+    }
+}
+
+/// Post a message, returning its `ts`.
+async fn post_message(
+    state: &AppState,
+    token: &str,
+    channel: &str,
+    thread_ts: Option<&str>,
+    text: &str,
+) -> Option<String> {
+    let mut body = serde_json::json!({ "channel": channel, "text": text });
+    if let Some(ts) = thread_ts {
+        body["thread_ts"] = serde_json::Value::String(ts.to_string());
+    }
+    let response = state
+        .http_client
+        .post(POST_MESSAGE_URL)
+        .bearer_auth(token)
+        .json(&body)
+        .timeout(std::time::Duration::from_secs(15))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status());
+    let reply: serde_json::Value = match response {
+        Ok(response) => response.json().await.ok()?,
+        Err(e) => {
+            tracing::warn!(channel = %channel, error = %e, "Failed to post to Slack");
+            return None;
+        }
+    };
+    if reply["ok"].as_bool() != Some(true) {
+        tracing::warn!(
+            channel = %channel,
+            error = %reply["error"].as_str().unwrap_or("unknown"),
+            "Slack rejected a message"
+        );
+        return None;
+    }
+    reply["ts"].as_str().map(str::to_string)
+}
+
+async fn send_to_mission(
+    state: &AppState,
+    user: &AuthUser,
+    mission_id: Uuid,
+    content: String,
+) -> Result<(), (StatusCode, String)> {
+    let control = state.control.get_or_spawn(user).await;
+    let (respond, _) = oneshot::channel();
+    control
+        .cmd_tx
+        .send(ControlCommand::UserMessage {
+            id: Uuid::new_v4(),
+            content,
+            agent: None,
+            target_mission_id: Some(mission_id),
+            respond,
+        })
+        .await
+        .map_err(|e| internal_error(format!("Failed to queue message: {}", e)))
+}
+
+fn slash_response(in_channel: bool, text: String) -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "response_type": if in_channel { "in_channel" } else { "ephemeral" },
+        "text": text,
+    }))
+}
+
+/// POST /api/slack/commands - Handle the slash command.
+pub async fn slash_command(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    let config = verify_request(&state, &headers, &body)?.clone();
+    let form: std::collections::HashMap<String, String> =
+        url::form_urlencoded::parse(&body).into_owned().collect();
+    let field = |name: &str| form.get(name).map(String::as_str).unwrap_or_default();
+    let channel = field("channel_id").to_string();
+    let slack_user = field("user_id").to_string();
+
+    let command = match parse_command(field("text")) {
+        Ok(command) => command,
+        Err(message) => return Ok(slash_response(false, message)),
+    };
+    let user = AuthUser::local(&state.config);
+    match command {
+        SlackCommand::Help => Ok(slash_response(false, USAGE.to_string())),
+        SlackCommand::Send {
+            mission_id,
+            message,
+        } => {
+            let control = state.control.get_or_spawn(&user).await;
+            let Some(mission) = control
+                .mission_store
+                .get_mission(mission_id)
+                .await
+                .map_err(internal_error)?
+            else {
+                return Ok(slash_response(
+                    false,
+                    format!("Mission {} not found", mission_id),
+                ));
+            };
+            send_to_mission(&state, &user, mission.id, message).await?;
+            Ok(slash_response(
+                false,
+                format!(
+                    "Sent to <{}|{}>",
+                    config.mission_url(mission.id),
+                    escape_mrkdwn(mission.title.as_deref().unwrap_or("the mission"))
+                ),
+            ))
+        }
+        SlackCommand::New(prompt) => {
+            let title: String = prompt
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .chars()
+                .take(MAX_TITLE_CHARS)
+                .collect();
+            let request = CreateMissionRequest {
+                title: Some(title.clone()),
+                workspace_id: config.workspace_for_channel(&channel),
+                agent: None,
+                model_override: None,
+                model_effort: None,
+                config_profile: None,
+                backend: None,
+                agent_version: None,
+                tags: vec!["slack".to_string()],
+                dry_run: false,
+                env_profile: None,
+            };
+            let Json(mission) = create_mission(
+                State(Arc::clone(&state)),
+                Extension(user.clone()),
+                HeaderMap::new(),
+                Some(Json(request)),
+            )
+            .await?;
+            send_to_mission(&state, &user, mission.id, prompt).await?;
+
+            let link = format!(
+                "<{}|{}>",
+                config.mission_url(mission.id),
+                escape_mrkdwn(&title)
+            );
+            let Some(token) = config.bot_token.clone() else {
+                return Ok(slash_response(
+                    true,
+                    format!("<@{}> started mission {}", slack_user, link),
+                ));
+            };
+            // Slack wants an answer within 3 seconds; open the thread afterwards
+            let state = Arc::clone(&state);
+            let mission_id = mission.id;
+            let response = format!("Starting mission {}", link);
+            let text = format!(
+                "<@{}> started mission {}. Reply in this thread to talk to it.",
+                slack_user, link
+            );
+            task_supervisor::global().spawn(
+                format!("slack_thread:{}", mission_id),
+                task_supervisor::RestartPolicy::never(),
+                move || {
+                    let (state, token, channel, text) = (
+                        Arc::clone(&state),
+                        token.clone(),
+                        channel.clone(),
+                        text.clone(),
+                    );
+                    async move {
+                        if let Some(ts) = post_message(&state, &token, &channel, None, &text).await
+                        {
+                            state
+                                .slack
+                                .link_thread(SlackThread {
+                                    channel,
+                                    thread_ts: ts,
+                                    mission_id,
+                                })
+                                .await;
+                        }
+                    }
+                },
+            );
+            Ok(slash_response(false, response))
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct EventEnvelope {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    challenge: Option<String>,
+    #[serde(default)]
+    event: Option<MessageEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageEvent {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    subtype: Option<String>,
+    #[serde(default)]
+    bot_id: Option<String>,
+    #[serde(default)]
+    channel: Option<String>,
+    #[serde(default)]
+    thread_ts: Option<String>,
+    #[serde(default)]
+    text: String,
+}
+
+/// POST /api/slack/events - Handle Events API callbacks.
+pub async fn slack_events(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    verify_request(&state, &headers, &body)?;
+    let envelope: EventEnvelope = serde_json::from_slice(&body)
+        .map_err(|e| (StatusCode::BAD_REQUEST, format!("Invalid event: {}", e)))?;
+    if envelope.kind == "url_verification" {
+        return Ok(Json(serde_json::json!({ "challenge": envelope.challenge })));
+    }
+    // Slack retries slow deliveries; the first delivery was already handled
+    if headers.contains_key("x-slack-retry-num") {
+        return Ok(Json(serde_json::json!({})));
+    }
+
+    let Some(event) = envelope.event else {
+        return Ok(Json(serde_json::json!({})));
+    };
+    let is_user_reply =
+        event.kind == "message" && event.subtype.is_none() && event.bot_id.is_none();
+    if let (true, Some(channel), Some(thread_ts)) = (is_user_reply, event.channel, event.thread_ts)
+    {
+        let text = event.text.trim();
+        if let Some(mission_id) = state.slack.mission_for_thread(&channel, &thread_ts).await {
+            if !text.is_empty() {
+                let user = AuthUser::local(&state.config);
+                send_to_mission(&state, &user, mission_id, text.to_string()).await?;
+            }
+        }
+    }
+    Ok(Json(serde_json::json!({})))
+}
+
+/// Post agent replies and mission outcomes to Slack for as long as the
+/// server runs. Does nothing without a bot token.
+pub fn start_notifier(state: Arc<AppState>) {
+    let Some(token) = state
+        .slack
+        .config
+        .as_ref()
+        .and_then(|c| c.bot_token.clone())
+    else {
+        return;
+    };
+    task_supervisor::global().spawn_with_cancel(
+        "slack_notifier",
+        task_supervisor::RestartPolicy::on_panic(),
+        move |cancel| notifier_loop(Arc::clone(&state), token.clone(), cancel),
+    );
+}
+
+async fn notifier_loop(state: Arc<AppState>, token: String, cancel: CancellationToken) {
+    let control = state
+        .control
+        .get_or_spawn(&AuthUser::local(&state.config))
+        .await;
+    let mut events = control.events_tx.subscribe();
+    loop {
+        let received = tokio::select! {
+            received = events.recv() => received,
+            _ = cancel.cancelled() => break,
+        };
+        match received {
+            Ok(event) => notify(&state, &token, &event).await,
+            Err(broadcast::error::RecvError::Lagged(missed)) => {
+                tracing::warn!(
+                    missed,
+                    "Slack notifier fell behind; some events were not posted"
+                );
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        }
+    }
+}
+
+async fn notify(state: &Arc<AppState>, token: &str, event: &AgentEvent) {
+    let Some(config) = state.slack.config.as_ref() else {
+        return;
+    };
+    match event {
+        AgentEvent::AssistantMessage {
+            content,
+            mission_id: Some(mission_id),
+            ..
+        } => {
+            if let Some(thread) = state.slack.thread_for_mission(*mission_id).await {
+                let text = truncate_chars(content, MAX_POST_CHARS);
+                post_message(
+                    state,
+                    token,
+                    &thread.channel,
+                    Some(&thread.thread_ts),
+                    &text,
+                )
+                .await;
+            }
+        }
+        AgentEvent::MissionStatusChanged {
+            mission_id,
+            status,
+            summary,
+        } if !matches!(status, MissionStatus::Pending | MissionStatus::Active) => {
+            let control = state
+                .control
+                .get_or_spawn(&AuthUser::local(&state.config))
+                .await;
+            let Ok(Some(mission)) = control.mission_store.get_mission(*mission_id).await else {
+                return;
+            };
+            let title = mission.title.as_deref().unwrap_or("Mission");
+            let mut text = format!(
+                "<{}|{}> {}",
+                config.mission_url(mission.id),
+                escape_mrkdwn(title),
+                i18n::catalog(&i18n::workspace_locale(mission.workspace_id)).status(status)
+            );
+            if let Some(summary) = summary.as_deref().filter(|s| !s.trim().is_empty()) {
+                text.push_str(&format!(
+                    ": {}",
+                    escape_mrkdwn(&truncate_chars(summary.trim(), MAX_POST_CHARS))
+                ));
+            }
+            match state.slack.thread_for_mission(mission.id).await {
+                Some(thread) => {
+                    post_message(
+                        state,
+                        token,
+                        &thread.channel,
+                        Some(&thread.thread_ts),
+                        &text,
+                    )
+                    .await;
+                }
+                None => {
+                    if let Some(channel) = config.channel_for_workspace(mission.workspace_id) {
+                        post_message(state, token, channel, None, &text).await;
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signatures_are_checked_and_expire() {
+        let body = b"token=x&text=hello";
+        let mut mac = Hmac::<Sha256>::new_from_slice(b"secret").unwrap();
+        mac.update(b"v0:1700000000:token=x&text=hello");
+        let signature = format!("v0={}", hex::encode(mac.finalize().into_bytes()));
+
+        assert!(verify_signature(
+            "secret",
+            "1700000000",
+            body,
+            &signature,
+            1700000060
+        ));
+        assert!(!verify_signature(
+            "other",
+            "1700000000",
+            body,
+            &signature,
+            1700000060
+        ));
+        assert!(!verify_signature(
+            "secret",
+            "1700000000",
+            b"tampered",
+            &signature,
+            1700000060
+        ));
+        assert!(!verify_signature(
+            "secret",
+            "1700000000",
+            body,
+            &signature,
+            1700001000
+        ));
+        assert!(!verify_signature(
+            "secret",
+            "1700000000",
+            body,
+            "v0=zz",
+            1700000060
+        ));
+    }
+
+    #[test]
+    fn commands_and_channel_routes_are_parsed() {
+        let id = Uuid::new_v4();
+        assert_eq!(parse_command("  "), Ok(SlackCommand::Help));
+        assert_eq!(
+            parse_command("fix the flaky test"),
+            Ok(SlackCommand::New("fix the flaky test".to_string()))
+        );
+        assert_eq!(
+            parse_command("new deploy staging"),
+            Ok(SlackCommand::New("deploy staging".to_string()))
+        );
+        assert_eq!(
+            parse_command(&format!("send {} also update docs", id)),
+            Ok(SlackCommand::Send {
+                mission_id: id,
+                message: "also update docs".to_string()
+            })
+        );
+        assert!(parse_command("send nope hi").is_err());
+        assert!(parse_command(&format!("send {}", id)).is_err());
+
+        let ws = Uuid::new_v4();
+        let config = SlackConfig {
+            channels: parse_channel_routes(&format!("C1={}, bogus ,C2=nope", ws)),
+            default_channel: Some("C9".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(config.channels, [("C1".to_string(), ws)]);
+        assert_eq!(config.workspace_for_channel("C1"), Some(ws));
+        assert_eq!(config.workspace_for_channel("C3"), None);
+        assert_eq!(config.channel_for_workspace(ws), Some("C1"));
+        assert_eq!(config.channel_for_workspace(Uuid::nil()), Some("C9"));
+    }
+}
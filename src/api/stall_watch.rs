@@ -0,0 +1,212 @@
+//! Stall handling for running missions.
+//!
+//! The control actor tracks when each running mission last made progress (tool
+//! calls, LLM output). [`StallWatcher`] turns those idle times into actions:
+//!
+//! 1. After `SANDBOXED_SH_STALL_AFTER_MINUTES` (default 10, `0` disables)
+//!    without progress the mission is flagged and `AgentEvent::MissionStalled`
+//!    is emitted. With `SANDBOXED_SH_STALL_NUDGE=true` the stuck turn is also
+//!    interrupted and [`NUDGE_MESSAGE`] is queued ahead of other messages, so
+//!    the agent resumes its session by reporting status.
+//! 2. If `SANDBOXED_SH_STALL_CANCEL_AFTER_MINUTES` is set and the mission makes
+//!    no progress for that much longer, it is cancelled (status Interrupted,
+//!    resumable).
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+use uuid::Uuid;
+
+/// How often the control actor checks running missions for stalls.
+pub const CHECK_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Message queued for a stalled mission when nudging is enabled.
+pub const NUDGE_MESSAGE: &str = "You have made no progress for a while. \
+Please summarize your current status: what you have done, what you are \
+waiting on, and what you will do next. Then continue.";
+
+/// Activity this soon after a mission is flagged (e.g. the nudge turn
+/// starting) doesn't count as recovering from the stall.
+const PROGRESS_GRACE: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StallPolicy {
+    /// Idle time before a mission counts as stalled
+    pub stall_after: Duration,
+    /// Interrupt the stuck turn and queue [`NUDGE_MESSAGE`]
+    pub nudge: bool,
+    /// Further idle time, after being flagged, before cancelling
+    pub cancel_after: Option<Duration>,
+}
+
+impl StallPolicy {
+    /// Load the policy from the environment; `None` when stall handling is off.
+    pub fn from_env() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).ok();
+        Self::parse(
+            var("SANDBOXED_SH_STALL_AFTER_MINUTES").as_deref(),
+            var("SANDBOXED_SH_STALL_NUDGE").as_deref(),
+            var("SANDBOXED_SH_STALL_CANCEL_AFTER_MINUTES").as_deref(),
+        )
+    }
+
+    fn parse(
+        stall_after: Option<&str>,
+        nudge: Option<&str>,
+        cancel_after: Option<&str>,
+    ) -> Option<Self> {
+        let minutes = |value: &str| {
+            value
+                .trim()
+                .parse::<u64>()
+                .ok()
+                .map(|m| Duration::from_secs(m * 60))
+        };
+        let stall_after = match stall_after {
+            Some(value) => minutes(value).unwrap_or(Duration::from_secs(10 * 60)),
+            None => Duration::from_secs(10 * 60),
+        };
+        if stall_after.is_zero() {
+            return None;
+        }
+        Some(Self {
+            stall_after,
+            nudge: nudge.is_some_and(|v| matches!(v.trim(), "1" | "true" | "yes" | "on")),
+            cancel_after: cancel_after.and_then(minutes).filter(|d| !d.is_zero()),
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StallAction {
+    /// The mission just crossed the stall threshold
+    Stalled { mission_id: Uuid, idle: Duration },
+    /// The mission stayed stalled past `cancel_after`
+    Cancel { mission_id: Uuid, idle: Duration },
+}
+
+/// Tracks which running missions are stalled.
+pub struct StallWatcher {
+    policy: StallPolicy,
+    /// Time since each stalled mission was flagged
+    flagged: HashMap<Uuid, Duration>,
+}
+
+impl StallWatcher {
+    pub fn new(policy: StallPolicy) -> Self {
+        Self {
+            policy,
+            flagged: HashMap::new(),
+        }
+    }
+
+    pub fn policy(&self) -> &StallPolicy {
+        &self.policy
+    }
+
+    /// Advance the watcher by `elapsed` and check the idle time of every
+    /// running mission. Missions missing from `running` are forgotten.
+    pub fn observe(&mut self, elapsed: Duration, running: &[(Uuid, Duration)]) -> Vec<StallAction> {
+        self.flagged
+            .retain(|id, _| running.iter().any(|(mid, _)| mid == id));
+
+        let mut actions = Vec::new();
+        for &(mission_id, idle) in running {
+            match self.flagged.get_mut(&mission_id) {
+                None => {
+                    if idle >= self.policy.stall_after {
+                        self.flagged.insert(mission_id, Duration::ZERO);
+                        actions.push(StallAction::Stalled { mission_id, idle });
+                    }
+                }
+                Some(since_flagged) => {
+                    *since_flagged += elapsed;
+                    if idle + PROGRESS_GRACE < *since_flagged {
+                        // Progress after the stall was flagged
+                        self.flagged.remove(&mission_id);
+                    } else if self
+                        .policy
+                        .cancel_after
+                        .is_some_and(|cancel_after| *since_flagged >= cancel_after)
+                    {
+                        self.flagged.remove(&mission_id);
+                        actions.push(StallAction::Cancel { mission_id, idle });
+                    }
+                }
+            }
+        }
+        actions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIN: Duration = Duration::from_secs(60);
+
+    #[test]
+    fn policy_parsing() {
+        let policy = StallPolicy::parse(None, None, None).unwrap();
+        assert_eq!(policy.stall_after, 10 * MIN);
+        assert!(!policy.nudge);
+        assert_eq!(policy.cancel_after, None);
+
+        assert!(StallPolicy::parse(Some("0"), None, None).is_none());
+        let policy = StallPolicy::parse(Some("5"), Some("true"), Some("15")).unwrap();
+        assert_eq!(policy.stall_after, 5 * MIN);
+        assert!(policy.nudge);
+        assert_eq!(policy.cancel_after, Some(15 * MIN));
+    }
+
+    #[test]
+    fn flags_then_cancels_without_progress() {
+        let mission = Uuid::new_v4();
+        let mut watcher = StallWatcher::new(StallPolicy {
+            stall_after: 10 * MIN,
+            nudge: true,
+            cancel_after: Some(5 * MIN),
+        });
+
+        assert!(watcher.observe(MIN, &[(mission, 9 * MIN)]).is_empty());
+        assert_eq!(
+            watcher.observe(MIN, &[(mission, 10 * MIN)]),
+            vec![StallAction::Stalled {
+                mission_id: mission,
+                idle: 10 * MIN
+            }]
+        );
+        // The nudge turn starting resets idle time, but that isn't progress.
+        assert!(watcher
+            .observe(Duration::from_secs(10), &[(mission, Duration::ZERO)])
+            .is_empty());
+        assert!(watcher.observe(2 * MIN, &[(mission, 2 * MIN)]).is_empty());
+        assert_eq!(
+            watcher.observe(3 * MIN, &[(mission, 5 * MIN)]),
+            vec![StallAction::Cancel {
+                mission_id: mission,
+                idle: 5 * MIN
+            }]
+        );
+    }
+
+    #[test]
+    fn progress_clears_the_stall() {
+        let mission = Uuid::new_v4();
+        let mut watcher = StallWatcher::new(StallPolicy {
+            stall_after: 10 * MIN,
+            nudge: false,
+            cancel_after: Some(5 * MIN),
+        });
+        assert_eq!(watcher.observe(MIN, &[(mission, 10 * MIN)]).len(), 1);
+        assert!(watcher
+            .observe(2 * MIN, &[(mission, Duration::from_secs(5))])
+            .is_empty());
+        // Flagged again only after another full stall period.
+        assert!(watcher.observe(5 * MIN, &[(mission, 5 * MIN)]).is_empty());
+        assert!(matches!(
+            watcher.observe(5 * MIN, &[(mission, 10 * MIN)]).as_slice(),
+            [StallAction::Stalled { .. }]
+        ));
+    }
+}
@@ -0,0 +1,232 @@
+//! Agent-requested mission status changes awaiting the user's confirmation.
+//!
+//! With `SANDBOXED_SH_CONFIRM_STATUS` listing some statuses (e.g.
+//! `blocked,not_feasible`), `complete_mission` can't set them directly. The
+//! control actor records the agent's request with its structured
+//! justification and broadcasts an [`AgentEvent::StatusChangeRequested`].
+//! Resolving it with `POST /api/missions/:id/status-request`:
+//!
+//! - confirm - applies the status as if the agent had set it
+//! - decline - leaves the status unchanged and tells the agent to keep
+//!   working, with the user's optional message
+//!
+//! A mission has at most one pending request; a newer one replaces it.
+//! Pending requests are kept in memory only.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{oneshot, RwLock};
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::control::{control_for_mission, AgentEvent, ControlCommand, MissionStatus};
+use super::mission_store::now_string;
+use super::routes::AppState;
+use crate::tools::mission::{MissionControlCommand, MissionStatusValue, StatusJustification};
+use crate::util::internal_error;
+
+pub type SharedStatusRequests = Arc<RwLock<StatusRequests>>;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct StatusChangeRequest {
+    pub mission_id: Uuid,
+    pub status: MissionStatusValue,
+    pub justification: StatusJustification,
+    pub requested_at: String,
+}
+
+/// Pending status change requests per mission.
+#[derive(Debug, Default)]
+pub struct StatusRequests {
+    pending: HashMap<Uuid, StatusChangeRequest>,
+}
+
+impl StatusRequests {
+    /// Record a request, replacing the mission's previous one.
+    pub fn request(
+        &mut self,
+        mission_id: Uuid,
+        status: MissionStatusValue,
+        justification: StatusJustification,
+    ) -> StatusChangeRequest {
+        let request = StatusChangeRequest {
+            mission_id,
+            status,
+            justification,
+            requested_at: now_string(),
+        };
+        self.pending.insert(mission_id, request.clone());
+        request
+    }
+
+    pub fn pending_for(&self, mission_id: Uuid) -> Option<StatusChangeRequest> {
+        self.pending.get(&mission_id).cloned()
+    }
+
+    pub fn resolve(&mut self, mission_id: Uuid) -> Option<StatusChangeRequest> {
+        self.pending.remove(&mission_id)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ResolveStatusRequest {
+    pub confirm: bool,
+    /// Told to the agent when the request is declined
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+fn decline_message(request: &StatusChangeRequest, message: Option<&str>) -> String {
+    let mut content = format!(
+        "The user declined marking this mission as {}. Keep working on it",
+        request.status
+    );
+    match message.map(str::trim).filter(|m| !m.is_empty()) {
+        Some(message) => {
+            content.push_str(":\n\n");
+            content.push_str(message);
+        }
+        None => content.push('.'),
+    }
+    content
+}
+
+/// GET /api/missions/:id/status-request - The pending status change request.
+pub async fn get_status_request(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+) -> Result<Json<StatusChangeRequest>, (StatusCode, String)> {
+    let control = control_for_mission(&state, &user, mission_id).await?;
+    let request = control
+        .status_requests
+        .read()
+        .await
+        .pending_for(mission_id)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                "No pending status change request for this mission".to_string(),
+            )
+        })?;
+    Ok(Json(request))
+}
+
+/// POST /api/missions/:id/status-request - Confirm or decline the pending
+/// status change.
+pub async fn resolve_status_request(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+    Json(req): Json<ResolveStatusRequest>,
+) -> Result<Json<StatusChangeRequest>, (StatusCode, String)> {
+    let control = control_for_mission(&state, &user, mission_id).await?;
+    let request = control
+        .status_requests
+        .write()
+        .await
+        .resolve(mission_id)
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                "No pending status change request for this mission".to_string(),
+            )
+        })?;
+    let _ = control.events_tx.send(AgentEvent::StatusChangeResolved {
+        mission_id,
+        status: MissionStatus::from(request.status),
+        confirmed: req.confirm,
+    });
+
+    if req.confirm {
+        control
+            .mission_cmd_tx
+            .send(MissionControlCommand::SetStatus {
+                mission_id,
+                status: request.status,
+                summary: Some(request.justification.summary()).filter(|s| !s.is_empty()),
+            })
+            .await
+            .map_err(|e| internal_error(format!("Failed to apply status: {}", e)))?;
+    } else {
+        let (respond, _) = oneshot::channel();
+        control
+            .cmd_tx
+            .send(ControlCommand::UserMessage {
+                id: Uuid::new_v4(),
+                content: decline_message(&request, req.message.as_deref()),
+                agent: None,
+                target_mission_id: Some(mission_id),
+                respond,
+            })
+            .await
+            .map_err(|e| internal_error(format!("Failed to queue message: {}", e)))?;
+    }
+    Ok(Json(request))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tools::mission::parse_status_list;
+
+    #[test]
+    fn newer_requests_replace_pending_ones() {
+        let mut requests = StatusRequests::default();
+        let mission_id = Uuid::new_v4();
+        let justification = |reason: &str| StatusJustification {
+            reason: reason.to_string(),
+            ..Default::default()
+        };
+
+        requests.request(
+            mission_id,
+            MissionStatusValue::Blocked,
+            justification("no access"),
+        );
+        requests.request(
+            mission_id,
+            MissionStatusValue::NotFeasible,
+            justification("wrong repo"),
+        );
+        let pending = requests.pending_for(mission_id).unwrap();
+        assert_eq!(pending.status, MissionStatusValue::NotFeasible);
+        assert_eq!(pending.justification.reason, "wrong repo");
+
+        assert!(requests.resolve(mission_id).is_some());
+        assert!(requests.resolve(mission_id).is_none());
+        assert!(requests.pending_for(mission_id).is_none());
+
+        assert_eq!(
+            decline_message(&pending, Some("  try the staging DB ")),
+            "The user declined marking this mission as not_feasible. Keep working on it:\n\ntry the staging DB"
+        );
+    }
+
+    #[test]
+    fn justifications_and_confirmed_statuses_are_parsed() {
+        assert_eq!(
+            parse_status_list("blocked, NOT_FEASIBLE,bogus,"),
+            vec![MissionStatusValue::Blocked, MissionStatusValue::NotFeasible]
+        );
+        assert!(parse_status_list("").is_empty());
+
+        let justification = StatusJustification {
+            reason: "The API key is missing".to_string(),
+            blocker_type: Some("access_denied".to_string()),
+            attempted: vec!["env vars".to_string(), "secrets store".to_string()],
+            needed: Some("An API key".to_string()),
+        };
+        assert_eq!(
+            justification.summary(),
+            "The API key is missing\nBlocker type: access_denied\nAttempted: env vars, secrets store\nNeeded to proceed: An API key"
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! Dry runs of automation stop policies.
+//!
+//! `POST /api/control/automations/:id/stop-policy/preview` replays a proposed
+//! [`StopPolicy`] over the automation's recorded executions, checking it
+//! before each trigger exactly as the scheduler does, and reports when the
+//! automation would have been disabled and what the mission's status was at
+//! that point. Nothing is saved.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::control::MissionStatus;
+use super::mission_store::{AutomationExecution, ExecutionStatus, StopPolicy};
+use super::routes::AppState;
+use crate::util::internal_error;
+
+/// Most executions replayed, newest first.
+const MAX_REPLAYED_EXECUTIONS: usize = 500;
+
+/// Executions the scheduler looks back over when counting failures.
+const FAILURE_WINDOW: usize = 20;
+
+#[derive(Debug, Deserialize)]
+pub struct StopPolicyPreviewRequest {
+    /// Policy to try; the automation's current policy when omitted
+    #[serde(default)]
+    pub stop_policy: Option<StopPolicy>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StopPolicyPreview {
+    pub stop_policy: StopPolicy,
+    /// False when the policy depends on live external state and cannot be
+    /// replayed
+    pub evaluable: bool,
+    pub executions_evaluated: usize,
+    pub would_stop: bool,
+    /// When the automation would have been disabled
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stopped_at: Option<String>,
+    /// Last execution before the stop
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stopped_after_execution: Option<Uuid>,
+    /// Consecutive failures when it stopped, or the most seen otherwise
+    pub consecutive_failures: u32,
+    /// Recorded executions that would not have run
+    pub runs_prevented: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mission_status_at_stop: Option<MissionStatus>,
+    pub summary: String,
+}
+
+/// Failures the scheduler would count before triggering after `history`
+/// (oldest first): newest executions first, within the failure window, up to
+/// the last success.
+fn consecutive_failures(history: &[AutomationExecution]) -> u32 {
+    let mut count = 0;
+    for exec in history.iter().rev().take(FAILURE_WINDOW) {
+        match exec.status {
+            ExecutionStatus::Failed => count += 1,
+            ExecutionStatus::Success => break,
+            _ => {}
+        }
+    }
+    count
+}
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|t| t.with_timezone(&Utc))
+}
+
+fn format_day(value: &str) -> String {
+    parse_time(value)
+        .map(|t| t.format("%B %-d, %Y").to_string())
+        .unwrap_or_else(|| value.to_string())
+}
+
+/// Replay `policy` over `executions` (oldest first). `timeline` holds the
+/// mission's status changes, oldest first.
+pub fn replay(
+    policy: &StopPolicy,
+    executions: &[AutomationExecution],
+    timeline: &[(DateTime<Utc>, MissionStatus)],
+) -> StopPolicyPreview {
+    let mut preview = StopPolicyPreview {
+        stop_policy: policy.clone(),
+        evaluable: true,
+        executions_evaluated: executions.len(),
+        would_stop: false,
+        stopped_at: None,
+        stopped_after_execution: None,
+        consecutive_failures: 0,
+        runs_prevented: 0,
+        mission_status_at_stop: None,
+        summary: String::new(),
+    };
+
+    let threshold = match policy {
+        StopPolicy::Never => {
+            preview.summary = "Never stops automatically".to_string();
+            return preview;
+        }
+        StopPolicy::WhenAllIssuesClosedAndPRsMerged { repo } => {
+            preview.evaluable = false;
+            preview.summary = format!(
+                "Depends on the current issues and pull requests of {} and cannot be replayed",
+                repo
+            );
+            return preview;
+        }
+        StopPolicy::WhenFailingConsecutively { count } => *count,
+    };
+
+    // The policy is checked before every trigger, including the next one
+    for checked in 0..=executions.len() {
+        let failures = consecutive_failures(&executions[..checked]);
+        preview.consecutive_failures = preview.consecutive_failures.max(failures);
+        if failures < threshold {
+            continue;
+        }
+        preview.would_stop = true;
+        preview.consecutive_failures = failures;
+        preview.runs_prevented = executions.len() - checked;
+        let last = checked.checked_sub(1).map(|i| &executions[i]);
+        preview.stopped_after_execution = last.map(|e| e.id);
+        preview.stopped_at = last.map(|e| e.completed_at.clone().unwrap_or(e.triggered_at.clone()));
+        preview.mission_status_at_stop = match preview.stopped_at.as_deref().and_then(parse_time) {
+            Some(at) => timeline
+                .iter()
+                .take_while(|(changed, _)| *changed <= at)
+                .last()
+                .map(|(_, status)| *status),
+            None => timeline.last().map(|(_, status)| *status),
+        };
+        preview.summary = match (&preview.stopped_at, preview.runs_prevented) {
+            (None, _) => "Would stop before its first run".to_string(),
+            (Some(_), 0) => format!(
+                "Would stop at the next trigger after {} consecutive failures",
+                failures
+            ),
+            (Some(at), prevented) => format!(
+                "Would have stopped on {} after {} consecutive failures, skipping {} later runs",
+                format_day(at),
+                failures,
+                prevented
+            ),
+        };
+        return preview;
+    }
+
+    preview.summary = format!(
+        "Would not have stopped: at most {} consecutive failures in {} executions",
+        preview.consecutive_failures,
+        executions.len()
+    );
+    preview
+}
+
+/// POST /api/control/automations/:id/stop-policy/preview - Replay a stop
+/// policy over the automation's history without saving it.
+pub async fn preview_stop_policy(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(automation_id): Path<Uuid>,
+    Json(req): Json<StopPolicyPreviewRequest>,
+) -> Result<Json<StopPolicyPreview>, (StatusCode, String)> {
+    let control = state.control.get_or_spawn(&user).await;
+    let store = &control.mission_store;
+    let automation = store
+        .get_automation(automation_id)
+        .await
+        .map_err(internal_error)?
+        .ok_or_else(|| {
+            (
+                StatusCode::NOT_FOUND,
+                format!("Automation {} not found", automation_id),
+            )
+        })?;
+    let policy = req.stop_policy.unwrap_or(automation.stop_policy);
+
+    let mut executions = store
+        .get_automation_executions(automation_id, Some(MAX_REPLAYED_EXECUTIONS), None)
+        .await
+        .map_err(internal_error)?;
+    executions.reverse();
+
+    let timeline: Vec<(DateTime<Utc>, MissionStatus)> = store
+        .get_events(
+            automation.mission_id,
+            Some(&["mission_status_changed"]),
+            None,
+            None,
+        )
+        .await
+        .map_err(internal_error)?
+        .into_iter()
+        .filter_map(|event| {
+            let at = parse_time(&event.timestamp)?;
+            let status = serde_json::from_value(event.metadata.get("status")?.clone()).ok()?;
+            Some((at, status))
+        })
+        .collect();
+
+    Ok(Json(replay(&policy, &executions, &timeline)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn execution(day: u32, status: ExecutionStatus) -> AutomationExecution {
+        let at = format!("2026-03-{:02}T12:00:00+00:00", day);
+        AutomationExecution {
+            id: Uuid::new_v4(),
+            automation_id: Uuid::nil(),
+            mission_id: Uuid::nil(),
+            triggered_at: at.clone(),
+            trigger_source: "interval".to_string(),
+            status,
+            webhook_payload: None,
+            variables_used: Default::default(),
+            completed_at: Some(at),
+            error: None,
+            retry_count: 0,
+            first_event_seq: None,
+            last_event_seq: None,
+        }
+    }
+
+    #[test]
+    fn replays_consecutive_failures_like_the_scheduler() {
+        use ExecutionStatus::*;
+        let history = vec![
+            execution(1, Failed),
+            execution(2, Success),
+            execution(3, Failed),
+            execution(4, Skipped),
+            execution(5, Failed),
+            execution(6, Failed),
+            execution(7, Success),
+        ];
+        let timeline = vec![
+            (
+                parse_time("2026-03-01T00:00:00Z").unwrap(),
+                MissionStatus::Active,
+            ),
+            (
+                parse_time("2026-03-05T13:00:00Z").unwrap(),
+                MissionStatus::Failed,
+            ),
+        ];
+
+        let preview = replay(
+            &StopPolicy::WhenFailingConsecutively { count: 3 },
+            &history,
+            &timeline,
+        );
+        assert!(preview.would_stop);
+        assert_eq!(preview.stopped_after_execution, Some(history[5].id));
+        assert_eq!(preview.runs_prevented, 1);
+        assert_eq!(preview.consecutive_failures, 3);
+        assert_eq!(preview.mission_status_at_stop, Some(MissionStatus::Failed));
+        assert!(preview.summary.contains("March 6, 2026"));
+
+        let lenient = replay(
+            &StopPolicy::WhenFailingConsecutively { count: 4 },
+            &history,
+            &timeline,
+        );
+        assert!(!lenient.would_stop);
+        assert_eq!(lenient.consecutive_failures, 3);
+    }
+
+    #[test]
+    fn external_policies_are_not_replayed() {
+        let history = vec![execution(1, ExecutionStatus::Failed)];
+        let github = replay(
+            &StopPolicy::WhenAllIssuesClosedAndPRsMerged {
+                repo: "owner/repo".to_string(),
+            },
+            &history,
+            &[],
+        );
+        assert!(!github.evaluable && !github.would_stop);
+        assert!(!replay(&StopPolicy::Never, &history, &[]).would_stop);
+
+        let next = replay(
+            &StopPolicy::WhenFailingConsecutively { count: 1 },
+            &history,
+            &[],
+        );
+        assert!(next.would_stop);
+        assert_eq!(next.runs_prevented, 0);
+    }
+}
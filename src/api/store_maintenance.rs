@@ -0,0 +1,119 @@
+//! Mission store maintenance.
+//!
+//! Long-lived instances accumulate large SQLite files: every control session
+//! runs a light maintenance pass (WAL checkpoint, incremental vacuum,
+//! ANALYZE) every `STORE_MAINTENANCE_HOURS`, and instance users can run one
+//! on demand, optionally with a full VACUUM, or check the stores' integrity:
+//!
+//! - `POST /api/admin/store/maintenance` `{"vacuum": true}`
+//! - `GET /api/admin/store/integrity`
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{extract::State, Extension, Json};
+use serde::{Deserialize, Serialize};
+
+use super::auth::AuthUser;
+use super::control::ControlState;
+use super::mission_store::{MissionStore, StoreIntegrityReport, StoreMaintenanceReport};
+use super::routes::AppState;
+
+/// Run a maintenance pass on `store` every `hours`.
+pub async fn maintenance_loop(store: Arc<dyn MissionStore>, hours: u64) {
+    let interval = Duration::from_secs(hours * 60 * 60);
+    tracing::info!("Store maintenance task started: every {} hours", hours);
+
+    loop {
+        tokio::time::sleep(interval).await;
+        match store.run_maintenance(false).await {
+            Ok(Some(report)) => {
+                tracing::info!(
+                    database = %report.database,
+                    size_before_bytes = report.size_before_bytes,
+                    size_after_bytes = report.size_after_bytes,
+                    duration_ms = report.duration_ms,
+                    "Store maintenance completed"
+                );
+            }
+            Ok(None) => return,
+            Err(e) => {
+                tracing::warn!("Store maintenance failed: {}", e);
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct MaintenanceRequest {
+    /// Rebuild the databases with a full VACUUM (blocks their sessions while
+    /// it runs)
+    #[serde(default)]
+    pub vacuum: bool,
+}
+
+/// Per-store outcome; `error` is set when the store failed.
+#[derive(Debug, Serialize)]
+pub struct StoreResult<T> {
+    /// Control session the store belongs to
+    pub session: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub report: Option<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Control sessions whose stores are maintained: every loaded session,
+/// including the caller's.
+async fn sessions(state: &AppState, user: &AuthUser) -> Vec<(String, ControlState)> {
+    state.control.get_or_spawn(user).await;
+    let mut sessions = state.control.sessions_by_key().await;
+    sessions.sort_by(|a, b| a.0.cmp(&b.0));
+    sessions
+}
+
+fn store_result<T>(session: String, result: Result<Option<T>, String>) -> Option<StoreResult<T>> {
+    match result {
+        Ok(Some(report)) => Some(StoreResult {
+            session,
+            report: Some(report),
+            error: None,
+        }),
+        // Nothing to maintain (non-persistent store)
+        Ok(None) => None,
+        Err(e) => Some(StoreResult {
+            session,
+            report: None,
+            error: Some(e),
+        }),
+    }
+}
+
+/// POST /api/admin/store/maintenance - Run a maintenance pass on every store.
+pub async fn run_maintenance(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    body: Option<Json<MaintenanceRequest>>,
+) -> Json<Vec<StoreResult<StoreMaintenanceReport>>> {
+    let vacuum = body.map(|Json(req)| req.vacuum).unwrap_or_default();
+
+    let mut results = Vec::new();
+    for (key, session) in sessions(&state, &user).await {
+        let result = session.mission_store.run_maintenance(vacuum).await;
+        results.extend(store_result(key, result));
+    }
+    Json(results)
+}
+
+/// GET /api/admin/store/integrity - Integrity report for every store.
+pub async fn integrity_report(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+) -> Json<Vec<StoreResult<StoreIntegrityReport>>> {
+    let mut results = Vec::new();
+    for (key, session) in sessions(&state, &user).await {
+        let result = session.mission_store.check_integrity().await;
+        results.extend(store_result(key, result));
+    }
+    Json(results)
+}
@@ -1112,8 +1112,7 @@ fn stream_opencode_update() -> impl Stream<Item = Result<Event, std::convert::In
 
         let home = home_dir();
         let source_path = format!("{}/.opencode/bin/opencode", home);
-        // SAFETY: geteuid() is a trivial syscall with no preconditions.
-        let is_root = unsafe { libc::geteuid() } == 0;
+        let is_root = crate::util::running_as_root();
 
         if is_root {
             // Root: copy to system-wide location
@@ -1388,8 +1387,7 @@ fn stream_opencode_uninstall() -> impl Stream<Item = Result<Event, std::convert:
         yield sse("log", "Starting OpenCode uninstall...", Some(0));
 
         let home = home_dir();
-        // SAFETY: geteuid() is a trivial syscall with no preconditions.
-        let is_root = unsafe { libc::geteuid() } == 0;
+        let is_root = crate::util::running_as_root();
 
         // Stop the service first if running as root
         if is_root {
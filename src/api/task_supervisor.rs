@@ -0,0 +1,378 @@
+//! Supervision of long-running background tasks.
+//!
+//! Periodic loops (stale mission cleanup, schedulers, the event logger, ...)
+//! are spawned through the global `TaskSupervisor` instead of bare
+//! `tokio::spawn`, which:
+//!
+//! - restarts a task that panicked, after a growing backoff, according to its
+//!   `RestartPolicy`
+//! - records each task's status, listed by `GET /api/admin/tasks`
+//! - stops every task on shutdown: tasks spawned with `spawn` are dropped
+//!   when their cancellation token fires, tasks spawned with
+//!   `spawn_with_cancel` get the token and a grace period to finish (e.g. to
+//!   flush buffered writes) before they are aborted
+
+use std::collections::BTreeMap;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+
+use axum::Json;
+use futures::FutureExt;
+use serde::Serialize;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+/// Time tasks get to stop on shutdown before they are aborted.
+pub const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+/// Longest wait before restarting a task that keeps panicking.
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(300);
+
+/// How a task that panicked is restarted: up to `max_restarts` times,
+/// waiting `backoff` times the number of restarts so far (at most five
+/// minutes). Tasks that return are never restarted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartPolicy {
+    pub max_restarts: u32,
+    pub backoff: Duration,
+}
+
+impl RestartPolicy {
+    /// The policy for periodic loops.
+    pub const fn on_panic() -> Self {
+        Self {
+            max_restarts: 10,
+            backoff: Duration::from_secs(5),
+        }
+    }
+
+    /// The policy for one-off tasks, which aren't restarted.
+    pub const fn never() -> Self {
+        Self {
+            max_restarts: 0,
+            backoff: Duration::ZERO,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskState {
+    Running,
+    /// Waiting to be restarted after a panic
+    Restarting,
+    /// Returned on its own
+    Completed,
+    /// Panicked and not restarted
+    Failed,
+    /// Stopped by shutdown
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TaskStatus {
+    pub name: String,
+    pub state: TaskState,
+    pub restarts: u32,
+    pub started_at: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stopped_at: Option<String>,
+    /// Message of the last panic
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+}
+
+type Statuses = Arc<Mutex<BTreeMap<String, TaskStatus>>>;
+
+pub struct TaskSupervisor {
+    cancel: CancellationToken,
+    statuses: Statuses,
+    handles: Mutex<Vec<JoinHandle<()>>>,
+}
+
+static GLOBAL: LazyLock<TaskSupervisor> = LazyLock::new(TaskSupervisor::new);
+
+/// The process-wide supervisor.
+pub fn global() -> &'static TaskSupervisor {
+    &GLOBAL
+}
+
+fn update(statuses: &Statuses, name: &str, apply: impl FnOnce(&mut TaskStatus)) {
+    if let Some(status) = statuses
+        .lock()
+        .expect("task status lock poisoned")
+        .get_mut(name)
+    {
+        apply(status);
+    }
+}
+
+fn stop(statuses: &Statuses, name: &str, state: TaskState) {
+    update(statuses, name, |status| {
+        status.state = state;
+        status.stopped_at = Some(chrono::Utc::now().to_rfc3339());
+    });
+}
+
+fn panic_message(payload: &(dyn std::any::Any + Send)) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "panic".to_string())
+}
+
+/// Run `factory`'s tasks until one returns, shutdown, or a panic the policy
+/// doesn't restart.
+async fn supervise<F, Fut>(
+    name: String,
+    policy: RestartPolicy,
+    factory: F,
+    cancel: CancellationToken,
+    statuses: Statuses,
+) where
+    F: Fn(CancellationToken) -> Fut,
+    Fut: Future<Output = ()>,
+{
+    let mut restarts = 0u32;
+    loop {
+        let result = AssertUnwindSafe(factory(cancel.clone()))
+            .catch_unwind()
+            .await;
+        let message = match result {
+            Ok(()) if cancel.is_cancelled() => return stop(&statuses, &name, TaskState::Cancelled),
+            Ok(()) => return stop(&statuses, &name, TaskState::Completed),
+            Err(payload) => panic_message(payload.as_ref()),
+        };
+        tracing::error!(task = %name, "Background task panicked: {}", message);
+        update(&statuses, &name, |status| status.last_error = Some(message));
+
+        if restarts >= policy.max_restarts || cancel.is_cancelled() {
+            return stop(&statuses, &name, TaskState::Failed);
+        }
+        let backoff = policy
+            .backoff
+            .saturating_mul(restarts + 1)
+            .min(MAX_RESTART_BACKOFF);
+        update(&statuses, &name, |status| {
+            status.state = TaskState::Restarting
+        });
+        tokio::select! {
+            _ = tokio::time::sleep(backoff) => {}
+            _ = cancel.cancelled() => return stop(&statuses, &name, TaskState::Cancelled),
+        }
+        restarts += 1;
+        tracing::info!(task = %name, restarts, "Restarting background task");
+        update(&statuses, &name, |status| {
+            status.state = TaskState::Running;
+            status.restarts = restarts;
+        });
+    }
+}
+
+impl TaskSupervisor {
+    pub fn new() -> Self {
+        Self {
+            cancel: CancellationToken::new(),
+            statuses: Arc::default(),
+            handles: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Spawn a task that is dropped at its next `.await` on shutdown.
+    /// `factory` creates the task again for each restart.
+    pub fn spawn<F, Fut>(&self, name: impl Into<String>, policy: RestartPolicy, factory: F)
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        self.spawn_with_cancel(name, policy, move |cancel: CancellationToken| {
+            let task = factory();
+            async move {
+                tokio::select! {
+                    _ = task => {}
+                    _ = cancel.cancelled() => {}
+                }
+            }
+        });
+    }
+
+    /// Spawn a task that stops on its own once the token it gets is
+    /// cancelled. `factory` creates the task again for each restart.
+    pub fn spawn_with_cancel<F, Fut>(
+        &self,
+        name: impl Into<String>,
+        policy: RestartPolicy,
+        factory: F,
+    ) where
+        F: Fn(CancellationToken) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let name = name.into();
+        let now = chrono::Utc::now().to_rfc3339();
+        let cancelled = self.cancel.is_cancelled();
+        self.statuses
+            .lock()
+            .expect("task status lock poisoned")
+            .insert(
+                name.clone(),
+                TaskStatus {
+                    name: name.clone(),
+                    state: if cancelled {
+                        TaskState::Cancelled
+                    } else {
+                        TaskState::Running
+                    },
+                    restarts: 0,
+                    started_at: now.clone(),
+                    stopped_at: cancelled.then_some(now),
+                    last_error: None,
+                },
+            );
+        if cancelled {
+            tracing::debug!(task = %name, "Not starting background task during shutdown");
+            return;
+        }
+
+        let handle = tokio::spawn(supervise(
+            name,
+            policy,
+            factory,
+            self.cancel.child_token(),
+            Arc::clone(&self.statuses),
+        ));
+        let mut handles = self.handles.lock().expect("task handle lock poisoned");
+        handles.retain(|handle| !handle.is_finished());
+        handles.push(handle);
+    }
+
+    /// Status of every task spawned so far, by name.
+    pub fn statuses(&self) -> Vec<TaskStatus> {
+        self.statuses
+            .lock()
+            .expect("task status lock poisoned")
+            .values()
+            .cloned()
+            .collect()
+    }
+
+    /// Cancel every task and wait up to `grace` for them to stop; the rest
+    /// are aborted. Tasks spawned afterwards are not started.
+    pub async fn shutdown(&self, grace: Duration) {
+        self.cancel.cancel();
+        let handles = std::mem::take(&mut *self.handles.lock().expect("task handle lock poisoned"));
+        let deadline = tokio::time::Instant::now() + grace;
+        let mut aborted = 0usize;
+        for mut handle in handles {
+            if tokio::time::timeout_at(deadline, &mut handle)
+                .await
+                .is_err()
+            {
+                handle.abort();
+                aborted += 1;
+            }
+        }
+
+        let mut statuses = self.statuses.lock().expect("task status lock poisoned");
+        let now = chrono::Utc::now().to_rfc3339();
+        for status in statuses.values_mut() {
+            if matches!(status.state, TaskState::Running | TaskState::Restarting) {
+                status.state = TaskState::Cancelled;
+                status.stopped_at = Some(now.clone());
+            }
+        }
+        if aborted > 0 {
+            tracing::warn!(
+                "Aborted {} background tasks that did not stop in time",
+                aborted
+            );
+        }
+    }
+}
+
+impl Default for TaskSupervisor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// GET /api/admin/tasks - Status of the background tasks.
+pub async fn list_tasks() -> Json<Vec<TaskStatus>> {
+    Json(global().statuses())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+
+    fn status(supervisor: &TaskSupervisor, name: &str) -> TaskStatus {
+        supervisor
+            .statuses()
+            .into_iter()
+            .find(|status| status.name == name)
+            .expect("task is recorded")
+    }
+
+    #[tokio::test]
+    async fn panicking_tasks_restart_until_the_policy_gives_up() {
+        let supervisor = TaskSupervisor::new();
+        let runs = Arc::new(AtomicU32::new(0));
+        let counter = Arc::clone(&runs);
+        supervisor.spawn(
+            "flaky",
+            RestartPolicy {
+                max_restarts: 2,
+                backoff: Duration::from_millis(1),
+            },
+            move || {
+                let counter = Arc::clone(&counter);
+                async move {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    panic!("boom");
+                }
+            },
+        );
+        supervisor.spawn("once", RestartPolicy::on_panic(), || async {});
+
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while status(&supervisor, "flaky").state != TaskState::Failed {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .expect("task gives up");
+
+        let flaky = status(&supervisor, "flaky");
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        assert_eq!(flaky.restarts, 2);
+        assert_eq!(flaky.last_error.as_deref(), Some("boom"));
+        assert_eq!(status(&supervisor, "once").state, TaskState::Completed);
+    }
+
+    #[tokio::test]
+    async fn shutdown_cancels_tasks_and_rejects_new_ones() {
+        let supervisor = TaskSupervisor::new();
+        let flushed = Arc::new(AtomicU32::new(0));
+        let counter = Arc::clone(&flushed);
+        supervisor.spawn("loop", RestartPolicy::on_panic(), || {
+            std::future::pending::<()>()
+        });
+        supervisor.spawn_with_cancel("logger", RestartPolicy::on_panic(), move |cancel| {
+            let counter = Arc::clone(&counter);
+            async move {
+                cancel.cancelled().await;
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        supervisor.shutdown(Duration::from_secs(5)).await;
+        supervisor.spawn("late", RestartPolicy::on_panic(), || async {});
+
+        assert_eq!(flushed.load(Ordering::SeqCst), 1);
+        for name in ["loop", "logger", "late"] {
+            assert_eq!(status(&supervisor, name).state, TaskState::Cancelled);
+        }
+    }
+}
@@ -0,0 +1,1103 @@
+//! Telegram bot control channel.
+//!
+//! The bot long-polls `getUpdates`, so no public endpoint is needed. In the
+//! allowed chats:
+//!
+//! - `/new <prompt>` (or any plain message) starts a mission, `/send <mission
+//!   id> <message>` queues a message to a mission and `/help` lists the
+//!   commands. Replying to a mission's message sends the reply to it.
+//! - Finished missions are announced with their summary (or the agent's last
+//!   reply when there is none).
+//! - `AskUserQuestion` prompts are sent with one button per option; free-text
+//!   and file questions are answered by replying to the question.
+//! - Commands held by [`super::command_approval`] come with Approve / Deny
+//!   buttons.
+//!
+//! Configuration: `SANDBOXED_SH_TELEGRAM_BOT_TOKEN` and
+//! `SANDBOXED_SH_TELEGRAM_CHAT_ID` (comma-separated chat ids; updates from
+//! other chats are ignored, notifications go to the first) enable the bot.
+//! `SANDBOXED_SH_TELEGRAM_WORKSPACE` picks the workspace for new missions and
+//! `SANDBOXED_SH_PUBLIC_URL` is used for mission links. Missions belong to
+//! the instance user. Pending buttons and reply links are kept in memory
+//! only.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::{extract::State, http::HeaderMap, Extension, Json};
+use serde::Deserialize;
+use tokio::sync::{broadcast, oneshot, Mutex};
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::command_approval;
+use super::control::{
+    create_mission, AgentEvent, ControlCommand, CreateMissionRequest, MissionStatus,
+};
+use super::routes::AppState;
+use super::task_supervisor;
+use super::user_question::{Question, QuestionKind};
+use crate::i18n;
+use crate::util::env_var_trimmed;
+
+const API_BASE: &str = "https://api.telegram.org";
+
+/// Seconds a `getUpdates` call waits for new updates.
+const POLL_TIMEOUT_SECS: u64 = 50;
+
+/// Pause after a failed poll before trying again.
+const RETRY_DELAY: Duration = Duration::from_secs(5);
+
+const MAX_TITLE_CHARS: usize = 80;
+
+/// Telegram rejects messages over 4096 characters.
+const MAX_MESSAGE_CHARS: usize = 3500;
+
+const MAX_BUTTON_CHARS: usize = 60;
+
+/// Pending prompts and reply links kept before the oldest are dropped.
+const MAX_TRACKED: usize = 500;
+
+const USAGE: &str = "Commands:\n\
+    /new <prompt> - start a mission (or just send the prompt)\n\
+    /send <mission id> <message> - send a message to a mission\n\
+    /help - show this message\n\
+    Reply to a mission's message to talk to it.";
+
+#[derive(Debug, Clone)]
+pub struct TelegramConfig {
+    pub bot_token: String,
+    /// Chats allowed to control the bot; notifications go to the first
+    pub chat_ids: Vec<i64>,
+    pub workspace_id: Option<Uuid>,
+    /// Dashboard base URL for mission links (relative links when unset)
+    pub public_url: Option<String>,
+}
+
+impl TelegramConfig {
+    /// `None` unless a bot token and at least one chat id are set.
+    pub fn from_env() -> Option<Self> {
+        let bot_token = env_var_trimmed("SANDBOXED_SH_TELEGRAM_BOT_TOKEN")?;
+        let chat_ids =
+            parse_chat_ids(&env_var_trimmed("SANDBOXED_SH_TELEGRAM_CHAT_ID").unwrap_or_default());
+        if chat_ids.is_empty() {
+            tracing::warn!(
+                "SANDBOXED_SH_TELEGRAM_BOT_TOKEN is set but SANDBOXED_SH_TELEGRAM_CHAT_ID is not; \
+                 the Telegram bot is disabled"
+            );
+            return None;
+        }
+        Some(Self {
+            bot_token,
+            chat_ids,
+            workspace_id: env_var_trimmed("SANDBOXED_SH_TELEGRAM_WORKSPACE")
+                .and_then(|v| v.parse().ok()),
+            public_url: env_var_trimmed("SANDBOXED_SH_PUBLIC_URL"),
+        })
+    }
+
+    fn mission_url(&self, mission_id: Uuid) -> String {
+        crate::util::mission_url(self.public_url.as_deref().unwrap_or_default(), mission_id)
+    }
+}
+
+/// Parse comma-separated chat ids; malformed entries are skipped with a
+/// warning.
+fn parse_chat_ids(value: &str) -> Vec<i64> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .filter_map(|entry| {
+            let id = entry.parse().ok();
+            if id.is_none() {
+                tracing::warn!(entry = %entry, "Ignoring malformed Telegram chat id");
+            }
+            id
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TelegramCommand {
+    New(String),
+    Send { mission_id: Uuid, message: String },
+    Help,
+}
+
+/// Parse a chat message. Plain text starts a mission; commands may carry the
+/// bot's name (`/new@my_bot`).
+pub fn parse_command(text: &str) -> Result<TelegramCommand, String> {
+    let text = text.trim();
+    let Some(command) = text.strip_prefix('/') else {
+        return if text.is_empty() {
+            Ok(TelegramCommand::Help)
+        } else {
+            Ok(TelegramCommand::New(text.to_string()))
+        };
+    };
+    let (name, rest) = command
+        .split_once(char::is_whitespace)
+        .unwrap_or((command, ""));
+    let name = name.split('@').next().unwrap_or_default();
+    let rest = rest.trim();
+    match name {
+        "new" if !rest.is_empty() => Ok(TelegramCommand::New(rest.to_string())),
+        "new" => Err("Usage: /new <prompt>".to_string()),
+        "send" => {
+            let (id, message) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+            let mission_id = id
+                .parse()
+                .map_err(|_| "Usage: /send <mission id> <message>".to_string())?;
+            let message = message.trim();
+            if message.is_empty() {
+                return Err("Usage: /send <mission id> <message>".to_string());
+            }
+            Ok(TelegramCommand::Send {
+                mission_id,
+                message: message.to_string(),
+            })
+        }
+        "help" | "start" => Ok(TelegramCommand::Help),
+        _ => Err(format!("Unknown command /{}\n\n{}", name, USAGE)),
+    }
+}
+
+/// A button press. Encoded into `callback_data`, which Telegram caps at 64
+/// bytes, so prompts are referenced by a short key.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Callback {
+    /// Pick (or toggle, for multiple choice) an option of a question
+    Option {
+        key: u64,
+        question: usize,
+        option: usize,
+    },
+    /// Finish a multiple-choice question
+    Done {
+        key: u64,
+        question: usize,
+    },
+    Approval {
+        key: u64,
+        approve: bool,
+    },
+}
+
+impl Callback {
+    fn encode(&self) -> String {
+        match self {
+            Self::Option {
+                key,
+                question,
+                option,
+            } => format!("q:{}:{}:{}", key, question, option),
+            Self::Done { key, question } => format!("q:{}:{}:done", key, question),
+            Self::Approval { key, approve } => {
+                format!("a:{}:{}", key, if *approve { "y" } else { "n" })
+            }
+        }
+    }
+
+    fn parse(data: &str) -> Option<Self> {
+        let mut parts = data.split(':');
+        let kind = parts.next()?;
+        let key = parts.next()?.parse().ok()?;
+        let callback = match kind {
+            "q" => {
+                let question = parts.next()?.parse().ok()?;
+                match parts.next()? {
+                    "done" => Self::Done { key, question },
+                    option => Self::Option {
+                        key,
+                        question,
+                        option: option.parse().ok()?,
+                    },
+                }
+            }
+            "a" => Self::Approval {
+                key,
+                approve: match parts.next()? {
+                    "y" => true,
+                    "n" => false,
+                    _ => return None,
+                },
+            },
+            _ => return None,
+        };
+        parts.next().is_none().then_some(callback)
+    }
+}
+
+/// Questions waiting for answers from the chat.
+#[derive(Debug)]
+struct QuestionPrompt {
+    tool_call_id: String,
+    questions: Vec<Question>,
+    selections: Vec<Vec<String>>,
+    answered: Vec<bool>,
+}
+
+impl QuestionPrompt {
+    fn new(tool_call_id: String, questions: Vec<Question>) -> Self {
+        let count = questions.len();
+        Self {
+            tool_call_id,
+            questions,
+            selections: vec![Vec::new(); count],
+            answered: vec![false; count],
+        }
+    }
+
+    /// Apply a button press; returns the text to confirm it with.
+    fn select(&mut self, question: usize, option: usize) -> Option<String> {
+        let q = self.questions.get(question)?;
+        let label = q.options.get(option)?.label.clone();
+        let selection = &mut self.selections[question];
+        if q.kind == QuestionKind::MultiChoice {
+            match selection.iter().position(|s| *s == label) {
+                Some(index) => {
+                    selection.remove(index);
+                }
+                None => selection.push(label),
+            }
+            Some(format!("Selected: {}", selection.join(", ")))
+        } else {
+            *selection = vec![label.clone()];
+            self.answered[question] = true;
+            Some(label)
+        }
+    }
+
+    fn answer_text(&mut self, question: usize, text: String) -> bool {
+        if question >= self.questions.len() {
+            return false;
+        }
+        self.selections[question] = vec![text];
+        self.answered[question] = true;
+        true
+    }
+
+    fn finish(&mut self, question: usize) -> bool {
+        match self.answered.get_mut(question) {
+            Some(answered) => {
+                *answered = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.answered.iter().all(|a| *a)
+    }
+
+    /// The tool result the dashboard would send: one selection list per
+    /// question.
+    fn result(&self) -> serde_json::Value {
+        serde_json::json!({ "answers": self.selections })
+    }
+}
+
+#[derive(Debug)]
+enum Prompt {
+    Question(QuestionPrompt),
+    Approval {
+        mission_id: Uuid,
+        tool_call_id: String,
+    },
+}
+
+/// What a reply to one of the bot's messages goes to.
+#[derive(Debug, Clone, Copy)]
+enum ReplyTarget {
+    Mission(Uuid),
+    Question { key: u64, question: usize },
+}
+
+#[derive(Default)]
+struct BotState {
+    next_key: u64,
+    prompts: BTreeMap<u64, Prompt>,
+    /// `(chat id, message id)` of bot messages to what a reply goes to
+    replies: HashMap<(i64, i64), ReplyTarget>,
+    reply_order: Vec<(i64, i64)>,
+}
+
+impl BotState {
+    fn add_prompt(&mut self, prompt: Prompt) -> u64 {
+        self.next_key += 1;
+        self.prompts.insert(self.next_key, prompt);
+        while self.prompts.len() > MAX_TRACKED {
+            self.prompts.pop_first();
+        }
+        self.next_key
+    }
+
+    fn link_reply(&mut self, chat_id: i64, message_id: i64, target: ReplyTarget) {
+        self.replies.insert((chat_id, message_id), target);
+        self.reply_order.push((chat_id, message_id));
+        if self.reply_order.len() > MAX_TRACKED {
+            let oldest = self.reply_order.remove(0);
+            self.replies.remove(&oldest);
+        }
+    }
+
+    /// Drop prompts for a tool call that was settled elsewhere.
+    fn forget(&mut self, tool_call_id: &str) {
+        self.prompts.retain(|_, prompt| match prompt {
+            Prompt::Question(q) => q.tool_call_id != tool_call_id,
+            Prompt::Approval {
+                tool_call_id: id, ..
+            } => id != tool_call_id,
+        });
+    }
+}
+
+struct TelegramBot {
+    config: TelegramConfig,
+    app: Arc<AppState>,
+    state: Mutex<BotState>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Update {
+    update_id: i64,
+    #[serde(default)]
+    message: Option<Message>,
+    #[serde(default)]
+    callback_query: Option<CallbackQuery>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Message {
+    message_id: i64,
+    chat: Chat,
+    #[serde(default)]
+    text: Option<String>,
+    #[serde(default)]
+    reply_to_message: Option<Box<Message>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Chat {
+    id: i64,
+}
+
+#[derive(Debug, Deserialize)]
+struct CallbackQuery {
+    id: String,
+    #[serde(default)]
+    message: Option<Message>,
+    #[serde(default)]
+    data: Option<String>,
+}
+
+fn truncate_chars(text: &str, max: usize) -> String {
+    match text.char_indices().nth(max) {
+        Some((cut, _)) => format!("{}…", &text[..cut]),
+        None => text.to_string(),
+    }
+}
+
+/// Start the bot for as long as the server runs. Does nothing unless
+/// configured.
+pub fn start(app: Arc<AppState>) {
+    let Some(config) = TelegramConfig::from_env() else {
+        return;
+    };
+    let bot = Arc::new(TelegramBot {
+        config,
+        app,
+        state: Mutex::new(BotState::default()),
+    });
+    let poller = Arc::clone(&bot);
+    task_supervisor::global().spawn_with_cancel(
+        "telegram_poller",
+        task_supervisor::RestartPolicy::on_panic(),
+        move |cancel| Arc::clone(&poller).poll_updates(cancel),
+    );
+    task_supervisor::global().spawn_with_cancel(
+        "telegram_notifier",
+        task_supervisor::RestartPolicy::on_panic(),
+        move |cancel| Arc::clone(&bot).forward_events(cancel),
+    );
+}
+
+impl TelegramBot {
+    fn local_user(&self) -> AuthUser {
+        AuthUser::local(&self.app.config)
+    }
+
+    /// Call a Bot API method, returning its `result`.
+    async fn call(
+        &self,
+        method: &str,
+        body: serde_json::Value,
+        timeout: Duration,
+    ) -> Option<serde_json::Value> {
+        let url = format!("{}/bot{}/{}", API_BASE, self.config.bot_token, method);
+        let response = self
+            .app
+            .http_client
+            .post(&url)
+            .json(&body)
+            .timeout(timeout)
+            .send()
+            .await;
+        let reply: serde_json::Value = match response {
+            Ok(response) => response.json().await.ok()?,
+            Err(e) => {
+                // The error's URL contains the bot token
+                tracing::warn!(method, error = %e.without_url(), "Telegram request failed");
+                return None;
+            }
+        };
+        if reply["ok"].as_bool() != Some(true) {
+            tracing::warn!(
+                method,
+                error = %reply["description"].as_str().unwrap_or("unknown"),
+                "Telegram rejected a request"
+            );
+            return None;
+        }
+        Some(reply["result"].clone())
+    }
+
+    /// Send a message, returning its id.
+    async fn send(
+        &self,
+        chat_id: i64,
+        text: &str,
+        keyboard: Option<serde_json::Value>,
+        reply_to: Option<i64>,
+    ) -> Option<i64> {
+        let mut body = serde_json::json!({
+            "chat_id": chat_id,
+            "text": truncate_chars(text, MAX_MESSAGE_CHARS),
+            "disable_web_page_preview": true,
+        });
+        if let Some(keyboard) = keyboard {
+            body["reply_markup"] = serde_json::json!({ "inline_keyboard": keyboard });
+        }
+        if let Some(id) = reply_to {
+            body["reply_to_message_id"] = serde_json::json!(id);
+        }
+        let result = self
+            .call("sendMessage", body, Duration::from_secs(15))
+            .await?;
+        result["message_id"].as_i64()
+    }
+
+    /// Replace a message's text, dropping its buttons.
+    async fn edit(&self, chat_id: i64, message_id: i64, text: &str) {
+        let body = serde_json::json!({
+            "chat_id": chat_id,
+            "message_id": message_id,
+            "text": truncate_chars(text, MAX_MESSAGE_CHARS),
+            "disable_web_page_preview": true,
+        });
+        self.call("editMessageText", body, Duration::from_secs(15))
+            .await;
+    }
+
+    async fn answer_callback(&self, callback_id: &str, text: &str) {
+        let body = serde_json::json!({
+            "callback_query_id": callback_id,
+            "text": truncate_chars(text, 180),
+        });
+        self.call("answerCallbackQuery", body, Duration::from_secs(15))
+            .await;
+    }
+
+    async fn poll_updates(self: Arc<Self>, cancel: CancellationToken) {
+        let mut offset = 0i64;
+        while !cancel.is_cancelled() {
+            let body = serde_json::json!({
+                "offset": offset,
+                "timeout": POLL_TIMEOUT_SECS,
+                "allowed_updates": ["message", "callback_query"],
+            });
+            let result = tokio::select! {
+                result = self.call(
+                    "getUpdates",
+                    body,
+                    Duration::from_secs(POLL_TIMEOUT_SECS + 10),
+                ) => result,
+                _ = cancel.cancelled() => break,
+            };
+            let updates: Vec<Update> = match result.map(serde_json::from_value) {
+                Some(Ok(updates)) => updates,
+                Some(Err(e)) => {
+                    tracing::warn!("Failed to parse Telegram updates: {}", e);
+                    tokio::time::sleep(RETRY_DELAY).await;
+                    continue;
+                }
+                None => {
+                    tokio::time::sleep(RETRY_DELAY).await;
+                    continue;
+                }
+            };
+            for update in updates {
+                offset = offset.max(update.update_id + 1);
+                if let Some(message) = update.message {
+                    self.handle_message(message).await;
+                } else if let Some(callback) = update.callback_query {
+                    self.handle_callback(callback).await;
+                }
+            }
+        }
+    }
+
+    async fn handle_message(&self, message: Message) {
+        let chat_id = message.chat.id;
+        if !self.config.chat_ids.contains(&chat_id) {
+            tracing::warn!(chat_id, "Ignoring Telegram message from an unknown chat");
+            return;
+        }
+        let Some(text) = message.text.as_deref().map(str::trim) else {
+            return;
+        };
+
+        if let Some(parent) = &message.reply_to_message {
+            let target = self
+                .state
+                .lock()
+                .await
+                .replies
+                .get(&(chat_id, parent.message_id))
+                .copied();
+            match target {
+                Some(ReplyTarget::Mission(mission_id)) => {
+                    let reply = match self.send_to_mission(mission_id, text.to_string()).await {
+                        Ok(()) => "Sent.".to_string(),
+                        Err(e) => e,
+                    };
+                    self.send(chat_id, &reply, None, Some(message.message_id))
+                        .await;
+                    return;
+                }
+                Some(ReplyTarget::Question { key, question }) => {
+                    let reply = self.answer_question_text(key, question, text).await;
+                    self.send(chat_id, &reply, None, Some(message.message_id))
+                        .await;
+                    return;
+                }
+                None => {}
+            }
+        }
+
+        let reply = match parse_command(text) {
+            Ok(TelegramCommand::Help) => USAGE.to_string(),
+            Ok(TelegramCommand::Send {
+                mission_id,
+                message,
+            }) => match self.send_to_mission(mission_id, message).await {
+                Ok(()) => format!("Sent to {}", self.config.mission_url(mission_id)),
+                Err(e) => e,
+            },
+            Ok(TelegramCommand::New(prompt)) => match self.start_mission(prompt).await {
+                Ok((mission_id, title)) => {
+                    let text = format!(
+                        "Started mission \"{}\"\n{}\nReply to this message to talk to it.",
+                        title,
+                        self.config.mission_url(mission_id)
+                    );
+                    if let Some(id) = self.send(chat_id, &text, None, None).await {
+                        self.state.lock().await.link_reply(
+                            chat_id,
+                            id,
+                            ReplyTarget::Mission(mission_id),
+                        );
+                    }
+                    return;
+                }
+                Err(e) => e,
+            },
+            Err(usage) => usage,
+        };
+        self.send(chat_id, &reply, None, Some(message.message_id))
+            .await;
+    }
+
+    async fn handle_callback(&self, callback: CallbackQuery) {
+        let Some(message) = callback.message else {
+            return;
+        };
+        let chat_id = message.chat.id;
+        if !self.config.chat_ids.contains(&chat_id) {
+            return;
+        }
+        let Some(parsed) = callback.data.as_deref().and_then(Callback::parse) else {
+            self.answer_callback(&callback.id, "Unknown action").await;
+            return;
+        };
+        let original = message.text.unwrap_or_default();
+
+        match parsed {
+            Callback::Approval { key, approve } => {
+                let prompt = self.state.lock().await.prompts.remove(&key);
+                let Some(Prompt::Approval {
+                    mission_id,
+                    tool_call_id,
+                }) = prompt
+                else {
+                    self.answer_callback(&callback.id, "This request has expired")
+                        .await;
+                    return;
+                };
+                let control = self.app.control.get_or_spawn(&self.local_user()).await;
+                let outcome = match command_approval::resolve_approval(
+                    &control,
+                    mission_id,
+                    &tool_call_id,
+                    approve,
+                )
+                .await
+                {
+                    Ok(_) if approve => "Approved".to_string(),
+                    Ok(_) => "Denied".to_string(),
+                    Err((_, e)) => e,
+                };
+                self.answer_callback(&callback.id, &outcome).await;
+                self.edit(
+                    chat_id,
+                    message.message_id,
+                    &format!("{}\n\n→ {}", original, outcome),
+                )
+                .await;
+            }
+            Callback::Option {
+                key,
+                question,
+                option,
+            } => {
+                let (confirmation, finished) = {
+                    let mut state = self.state.lock().await;
+                    let Some(Prompt::Question(prompt)) = state.prompts.get_mut(&key) else {
+                        drop(state);
+                        self.answer_callback(&callback.id, "This question has expired")
+                            .await;
+                        return;
+                    };
+                    let confirmation = prompt.select(question, option);
+                    (confirmation, prompt.answered.get(question) == Some(&true))
+                };
+                let confirmation = confirmation.unwrap_or_else(|| "Unknown option".to_string());
+                self.answer_callback(&callback.id, &confirmation).await;
+                if finished {
+                    self.edit(
+                        chat_id,
+                        message.message_id,
+                        &format!("{}\n\n→ {}", original, confirmation),
+                    )
+                    .await;
+                    self.submit_if_complete(key).await;
+                }
+            }
+            Callback::Done { key, question } => {
+                let selection = {
+                    let mut state = self.state.lock().await;
+                    match state.prompts.get_mut(&key) {
+                        Some(Prompt::Question(prompt)) => prompt
+                            .finish(question)
+                            .then(|| prompt.selections[question].join(", ")),
+                        _ => None,
+                    }
+                };
+                let Some(selection) = selection else {
+                    self.answer_callback(&callback.id, "This question has expired")
+                        .await;
+                    return;
+                };
+                let selection = if selection.is_empty() {
+                    "(none)".to_string()
+                } else {
+                    selection
+                };
+                self.answer_callback(&callback.id, "Answered").await;
+                self.edit(
+                    chat_id,
+                    message.message_id,
+                    &format!("{}\n\n→ {}", original, selection),
+                )
+                .await;
+                self.submit_if_complete(key).await;
+            }
+        }
+    }
+
+    async fn answer_question_text(&self, key: u64, question: usize, text: &str) -> String {
+        let answered = match self.state.lock().await.prompts.get_mut(&key) {
+            Some(Prompt::Question(prompt)) => prompt.answer_text(question, text.to_string()),
+            _ => false,
+        };
+        if !answered {
+            return "This question has expired".to_string();
+        }
+        self.submit_if_complete(key).await;
+        "Answered.".to_string()
+    }
+
+    /// Hand the answers to the waiting turn once every question is answered.
+    async fn submit_if_complete(&self, key: u64) {
+        let (tool_call_id, result) = {
+            let mut state = self.state.lock().await;
+            match state.prompts.get(&key) {
+                Some(Prompt::Question(prompt)) if prompt.is_complete() => {}
+                _ => return,
+            }
+            let Some(Prompt::Question(prompt)) = state.prompts.remove(&key) else {
+                return;
+            };
+            (prompt.tool_call_id.clone(), prompt.result())
+        };
+        let control = self.app.control.get_or_spawn(&self.local_user()).await;
+        if let Err(e) = control
+            .cmd_tx
+            .send(ControlCommand::ToolResult {
+                tool_call_id,
+                name: "AskUserQuestion".to_string(),
+                result,
+            })
+            .await
+        {
+            tracing::warn!("Failed to submit Telegram answer: {}", e);
+        }
+    }
+
+    async fn send_to_mission(&self, mission_id: Uuid, content: String) -> Result<(), String> {
+        let control = self.app.control.get_or_spawn(&self.local_user()).await;
+        match control.mission_store.get_mission(mission_id).await {
+            Ok(Some(_)) => {}
+            Ok(None) => return Err(format!("Mission {} not found", mission_id)),
+            Err(e) => return Err(format!("Failed to load mission: {}", e)),
+        }
+        let (respond, _) = oneshot::channel();
+        control
+            .cmd_tx
+            .send(ControlCommand::UserMessage {
+                id: Uuid::new_v4(),
+                content,
+                agent: None,
+                target_mission_id: Some(mission_id),
+                respond,
+            })
+            .await
+            .map_err(|e| format!("Failed to queue message: {}", e))
+    }
+
+    async fn start_mission(&self, prompt: String) -> Result<(Uuid, String), String> {
+        let title: String = prompt
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .chars()
+            .take(MAX_TITLE_CHARS)
+            .collect();
+        let request = CreateMissionRequest {
+            title: Some(title.clone()),
+            workspace_id: self.config.workspace_id,
+            agent: None,
+            model_override: None,
+            model_effort: None,
+            config_profile: None,
+            backend: None,
+            agent_version: None,
+            tags: vec!["telegram".to_string()],
+            dry_run: false,
+            env_profile: None,
+        };
+        let Json(mission) = create_mission(
+            State(Arc::clone(&self.app)),
+            Extension(self.local_user()),
+            HeaderMap::new(),
+            Some(Json(request)),
+        )
+        .await
+        .map_err(|(_, e)| format!("Failed to start mission: {}", e))?;
+        self.send_to_mission(mission.id, prompt).await?;
+        Ok((mission.id, title))
+    }
+
+    async fn forward_events(self: Arc<Self>, cancel: CancellationToken) {
+        let control = self.app.control.get_or_spawn(&self.local_user()).await;
+        let mut events = control.events_tx.subscribe();
+        loop {
+            let received = tokio::select! {
+                received = events.recv() => received,
+                _ = cancel.cancelled() => break,
+            };
+            match received {
+                Ok(event) => self.notify(&event).await,
+                Err(broadcast::error::RecvError::Lagged(missed)) => {
+                    tracing::warn!(
+                        missed,
+                        "Telegram notifier fell behind; some events were not sent"
+                    );
+                }
+                Err(broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    }
+
+    async fn notify(&self, event: &AgentEvent) {
+        let chat_id = self.config.chat_ids[0];
+        match event {
+            AgentEvent::MissionStatusChanged {
+                mission_id,
+                status,
+                summary,
+            } if !matches!(status, MissionStatus::Pending | MissionStatus::Active) => {
+                let control = self.app.control.get_or_spawn(&self.local_user()).await;
+                let Ok(Some(mission)) = control.mission_store.get_mission(*mission_id).await else {
+                    return;
+                };
+                let summary = summary
+                    .as_deref()
+                    .filter(|s| !s.trim().is_empty())
+                    .map(str::to_string)
+                    .or_else(|| {
+                        mission
+                            .history
+                            .iter()
+                            .rev()
+                            .find(|entry| entry.role == "assistant")
+                            .map(|entry| entry.content.clone())
+                    });
+                let catalog = i18n::catalog(&i18n::workspace_locale(mission.workspace_id));
+                let untitled = catalog.text("notification.untitled", &[]);
+                let mut text = format!(
+                    "{}\n{}",
+                    catalog.text(
+                        "notification.mission_status",
+                        &[
+                            ("title", mission.title.as_deref().unwrap_or(&untitled)),
+                            ("status", &catalog.status(status)),
+                        ],
+                    ),
+                    self.config.mission_url(mission.id)
+                );
+                if let Some(summary) = summary {
+                    text.push_str(&format!("\n\n{}", summary.trim()));
+                }
+                if let Some(id) = self.send(chat_id, &text, None, None).await {
+                    self.state.lock().await.link_reply(
+                        chat_id,
+                        id,
+                        ReplyTarget::Mission(mission.id),
+                    );
+                }
+            }
+            AgentEvent::UserQuestion {
+                tool_call_id,
+                questions,
+                ..
+            } if !questions.is_empty() => {
+                let key =
+                    self.state
+                        .lock()
+                        .await
+                        .add_prompt(Prompt::Question(QuestionPrompt::new(
+                            tool_call_id.clone(),
+                            questions.clone(),
+                        )));
+                for (index, question) in questions.iter().enumerate() {
+                    let (text, keyboard) = question_message(key, index, question);
+                    let Some(id) = self.send(chat_id, &text, keyboard, None).await else {
+                        continue;
+                    };
+                    self.state.lock().await.link_reply(
+                        chat_id,
+                        id,
+                        ReplyTarget::Question {
+                            key,
+                            question: index,
+                        },
+                    );
+                }
+            }
+            AgentEvent::UserQuestionAnswered { tool_call_id, .. }
+            | AgentEvent::ApprovalResolved { tool_call_id, .. } => {
+                self.state.lock().await.forget(tool_call_id);
+            }
+            AgentEvent::ApprovalRequired {
+                tool_call_id,
+                command,
+                reason,
+                mission_id,
+            } => {
+                let key = self.state.lock().await.add_prompt(Prompt::Approval {
+                    mission_id: *mission_id,
+                    tool_call_id: tool_call_id.clone(),
+                });
+                let text = format!(
+                    "Approval needed ({}):\n\n{}\n\n{}",
+                    reason,
+                    command,
+                    self.config.mission_url(*mission_id)
+                );
+                let keyboard = serde_json::json!([[
+                    {
+                        "text": "Approve",
+                        "callback_data": Callback::Approval { key, approve: true }.encode(),
+                    },
+                    {
+                        "text": "Deny",
+                        "callback_data": Callback::Approval { key, approve: false }.encode(),
+                    },
+                ]]);
+                self.send(chat_id, &text, Some(keyboard), None).await;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The text and buttons asking one question.
+fn question_message(
+    key: u64,
+    index: usize,
+    question: &Question,
+) -> (String, Option<serde_json::Value>) {
+    let mut text = match &question.header {
+        Some(header) => format!("❓ {}: {}", header, question.question),
+        None => format!("❓ {}", question.question),
+    };
+    for option in &question.options {
+        if let Some(description) = &option.description {
+            text.push_str(&format!("\n• {} - {}", option.label, description));
+        }
+    }
+    let choices = matches!(
+        question.kind,
+        QuestionKind::SingleChoice | QuestionKind::MultiChoice
+    ) && !question.options.is_empty();
+    if !choices {
+        text.push_str("\n\nReply to this message with your answer.");
+        return (text, None);
+    }
+
+    let mut rows: Vec<serde_json::Value> = question
+        .options
+        .iter()
+        .enumerate()
+        .map(|(option, o)| {
+            serde_json::json!([{
+                "text": truncate_chars(&o.label, MAX_BUTTON_CHARS),
+                "callback_data": Callback::Option { key, question: index, option }.encode(),
+            }])
+        })
+        .collect();
+    if question.kind == QuestionKind::MultiChoice {
+        text.push_str("\n\nTap options to select them, then Done.");
+        rows.push(serde_json::json!([{
+            "text": "Done",
+            "callback_data": Callback::Done { key, question: index }.encode(),
+        }]));
+    }
+    (text, Some(serde_json::Value::Array(rows)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::user_question::QuestionOption;
+    use super::*;
+
+    #[test]
+    fn commands_and_callbacks_are_parsed() {
+        let id = Uuid::new_v4();
+        assert_eq!(
+            parse_command("fix the build").unwrap(),
+            TelegramCommand::New("fix the build".to_string())
+        );
+        assert_eq!(
+            parse_command("/new@my_bot add tests").unwrap(),
+            TelegramCommand::New("add tests".to_string())
+        );
+        assert_eq!(
+            parse_command(&format!("/send {} keep going", id)).unwrap(),
+            TelegramCommand::Send {
+                mission_id: id,
+                message: "keep going".to_string()
+            }
+        );
+        assert_eq!(parse_command("/start").unwrap(), TelegramCommand::Help);
+        assert!(parse_command("/send nope hi").is_err());
+        assert!(parse_command("/new").is_err());
+        assert!(parse_command("/frobnicate").is_err());
+        assert_eq!(parse_chat_ids("123, -100456,bad"), vec![123, -100456]);
+
+        for callback in [
+            Callback::Option {
+                key: u64::MAX,
+                question: 12,
+                option: 99,
+            },
+            Callback::Done {
+                key: 7,
+                question: 0,
+            },
+            Callback::Approval {
+                key: 3,
+                approve: false,
+            },
+        ] {
+            let data = callback.encode();
+            assert!(data.len() <= 64);
+            assert_eq!(Callback::parse(&data), Some(callback));
+        }
+        assert_eq!(Callback::parse("a:1:maybe"), None);
+        assert_eq!(Callback::parse("q:1:2:3:4"), None);
+    }
+
+    #[test]
+    fn answers_are_collected_per_question() {
+        let option = |label: &str| QuestionOption {
+            label: label.to_string(),
+            description: None,
+        };
+        let mut prompt = QuestionPrompt::new(
+            "call-1".to_string(),
+            vec![
+                Question {
+                    question: "Which database?".to_string(),
+                    header: None,
+                    kind: QuestionKind::SingleChoice,
+                    options: vec![option("Postgres"), option("SQLite")],
+                    default: None,
+                },
+                Question {
+                    question: "Which targets?".to_string(),
+                    header: None,
+                    kind: QuestionKind::MultiChoice,
+                    options: vec![option("linux"), option("macos"), option("windows")],
+                    default: None,
+                },
+                Question {
+                    question: "Anything else?".to_string(),
+                    header: None,
+                    kind: QuestionKind::FreeText,
+                    options: Vec::new(),
+                    default: None,
+                },
+            ],
+        );
+
+        assert_eq!(prompt.select(0, 1).as_deref(), Some("SQLite"));
+        assert!(prompt.select(0, 5).is_none());
+        prompt.select(1, 0);
+        prompt.select(1, 2);
+        prompt.select(1, 0);
+        assert!(!prompt.is_complete());
+        assert!(prompt.finish(1));
+        assert!(prompt.answer_text(2, "no".to_string()));
+        assert!(prompt.is_complete());
+        assert_eq!(
+            prompt.result(),
+            serde_json::json!({ "answers": [["SQLite"], ["windows"], ["no"]] })
+        );
+    }
+}
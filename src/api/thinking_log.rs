@@ -0,0 +1,247 @@
+//! Persistence of the agent's reasoning (`thinking` events).
+//!
+//! Thinking is streamed as snapshots of the block so far, many per second.
+//! The event logger routes them through a `ThinkingCollector`, which keeps
+//! only the latest snapshot per mission and writes it once the block ends
+//! (a `done` thinking event, or any other event of the mission), so each
+//! block is stored as a single `thinking` event with `done: true`.
+//!
+//! Configuration:
+//!
+//! - `SANDBOXED_SH_PERSIST_THINKING` - set to `false` to keep reasoning out
+//!   of the store entirely (it is still streamed live)
+//! - `SANDBOXED_SH_THINKING_MAX_CHARS` - characters kept per block (default
+//!   20000, `0` keeps everything); longer blocks keep their beginning and
+//!   end
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use uuid::Uuid;
+
+use super::control::AgentEvent;
+use super::mission_store::StreamEvent;
+
+const DEFAULT_MAX_CHARS: usize = 20_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ThinkingPersistence {
+    pub enabled: bool,
+    /// Characters kept per block (`0` = no limit)
+    pub max_chars: usize,
+}
+
+impl Default for ThinkingPersistence {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_chars: DEFAULT_MAX_CHARS,
+        }
+    }
+}
+
+impl ThinkingPersistence {
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+        if let Ok(v) = std::env::var("SANDBOXED_SH_PERSIST_THINKING") {
+            config.enabled = !matches!(
+                v.trim().to_lowercase().as_str(),
+                "0" | "false" | "no" | "off"
+            );
+        }
+        if let Ok(v) = std::env::var("SANDBOXED_SH_THINKING_MAX_CHARS") {
+            if let Ok(n) = v.trim().parse() {
+                config.max_chars = n;
+            }
+        }
+        config
+    }
+}
+
+/// Keep the beginning and end of `text` within `max` characters.
+fn truncate_middle(text: &str, max: usize) -> String {
+    let total = text.chars().count();
+    if max == 0 || total <= max {
+        return text.to_string();
+    }
+    let head = max / 2;
+    let tail = max - head;
+    let omitted = total - max;
+    let start: String = text.chars().take(head).collect();
+    let end: String = text.chars().skip(total - tail).collect();
+    format!(
+        "{}\n\n[... {} characters omitted ...]\n\n{}",
+        start, omitted, end
+    )
+}
+
+/// Collapses thinking snapshots into one event per block before they are
+/// written to the store.
+#[derive(Debug, Default)]
+pub struct ThinkingCollector {
+    config: ThinkingPersistence,
+    /// Latest snapshot of the open block, per mission
+    pending: HashMap<Uuid, StreamEvent>,
+}
+
+impl ThinkingCollector {
+    pub fn new(config: ThinkingPersistence) -> Self {
+        Self {
+            config,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// The block's final event: the latest snapshot (or the `done` event's
+    /// own content), truncated and marked done.
+    fn finish(&self, snapshot: StreamEvent, done_content: Option<&str>) -> Option<StreamEvent> {
+        let AgentEvent::Thinking {
+            content,
+            mission_id,
+            ..
+        } = &*snapshot.event
+        else {
+            return None;
+        };
+        let content = done_content
+            .filter(|c| !c.trim().is_empty())
+            .unwrap_or(content);
+        Some(StreamEvent {
+            mission_id: snapshot.mission_id,
+            stream_seq: snapshot.stream_seq,
+            event: Arc::new(AgentEvent::Thinking {
+                content: truncate_middle(content, self.config.max_chars),
+                done: true,
+                mission_id: *mission_id,
+            }),
+        })
+    }
+
+    /// Route an event headed to the store, appending what should be written
+    /// now to `out`, in stream order.
+    pub fn route(&mut self, event: StreamEvent, out: &mut Vec<StreamEvent>) {
+        let mission_id = event.mission_id;
+        let AgentEvent::Thinking { content, done, .. } = &*event.event else {
+            // Any other event of the mission ends its open block
+            if let Some(snapshot) = self.pending.remove(&mission_id) {
+                out.extend(self.finish(snapshot, None));
+            }
+            out.push(event);
+            return;
+        };
+        if !self.config.enabled {
+            return;
+        }
+        if !done {
+            self.pending.insert(mission_id, event);
+            return;
+        }
+        let content = content.clone();
+        let snapshot = self.pending.remove(&mission_id).unwrap_or(event);
+        out.extend(self.finish(snapshot, Some(&content)));
+    }
+
+    /// Blocks still open (e.g. when the logger stops), in stream order.
+    pub fn drain(&mut self, out: &mut Vec<StreamEvent>) {
+        let mut open: Vec<StreamEvent> = self.pending.drain().map(|(_, event)| event).collect();
+        open.sort_by_key(|event| event.stream_seq);
+        for snapshot in open {
+            out.extend(self.finish(snapshot, None));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream_event(seq: u64, mission_id: Uuid, event: AgentEvent) -> StreamEvent {
+        StreamEvent {
+            mission_id,
+            stream_seq: seq,
+            event: Arc::new(event),
+        }
+    }
+
+    fn thinking(seq: u64, mission_id: Uuid, content: &str, done: bool) -> StreamEvent {
+        stream_event(
+            seq,
+            mission_id,
+            AgentEvent::Thinking {
+                content: content.to_string(),
+                done,
+                mission_id: Some(mission_id),
+            },
+        )
+    }
+
+    fn stored(out: &[StreamEvent]) -> Vec<(u64, String)> {
+        out.iter()
+            .map(|e| match &*e.event {
+                AgentEvent::Thinking { content, done, .. } => {
+                    assert!(done);
+                    (e.stream_seq, content.clone())
+                }
+                other => (e.stream_seq, other.event_name().to_string()),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn snapshots_collapse_into_one_event_per_block() {
+        let (a, b) = (Uuid::new_v4(), Uuid::new_v4());
+        let mut collector = ThinkingCollector::new(ThinkingPersistence::default());
+        let mut out = Vec::new();
+        for event in [
+            thinking(1, a, "Plan", false),
+            thinking(2, a, "Plan the fix", false),
+            thinking(3, b, "Other", false),
+            thinking(4, a, "", true),
+            thinking(5, a, "Second", false),
+            stream_event(
+                6,
+                a,
+                AgentEvent::TextDelta {
+                    content: "Done".to_string(),
+                    mission_id: Some(a),
+                },
+            ),
+        ] {
+            collector.route(event, &mut out);
+        }
+        collector.drain(&mut out);
+
+        assert_eq!(
+            stored(&out),
+            vec![
+                (2, "Plan the fix".to_string()),
+                (5, "Second".to_string()),
+                (6, "text_delta".to_string()),
+                (3, "Other".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn disabled_persistence_and_truncation() {
+        let mission = Uuid::new_v4();
+        let mut out = Vec::new();
+        let mut private = ThinkingCollector::new(ThinkingPersistence {
+            enabled: false,
+            ..Default::default()
+        });
+        private.route(thinking(1, mission, "secret", false), &mut out);
+        private.route(thinking(2, mission, "", true), &mut out);
+        assert!(out.is_empty());
+
+        let mut truncating = ThinkingCollector::new(ThinkingPersistence {
+            enabled: true,
+            max_chars: 10,
+        });
+        truncating.route(thinking(1, mission, &"ab".repeat(20), true), &mut out);
+        let (_, content) = &stored(&out)[0];
+        assert!(content.starts_with("ababa"));
+        assert!(content.contains("[... 30 characters omitted ...]"));
+        assert!(content.ends_with("babab"));
+    }
+}
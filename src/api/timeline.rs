@@ -0,0 +1,416 @@
+//! Mission timeline: stored events folded into phases.
+//!
+//! Raw mission events are too granular for a summary view, so the timeline
+//! groups them into phases:
+//!
+//! - `planning` - the agent reasoning or writing between tool calls
+//! - `tools` - a burst of consecutive tool calls sharing an activity label
+//!   (e.g. "Reading", "Running")
+//! - `waiting_for_user` - from the end of a turn until the next user message
+//! - `completion` - the mission reaching a terminal status
+//!
+//! Each phase has its time span and a share of the turn's cost. Assistant
+//! messages report cost once per turn, so that cost is split across the turn's
+//! planning and tool phases in proportion to their duration.
+//!
+//! Events are read from the store in pages and folded incrementally, so large
+//! missions never need to be loaded at once.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::control::activity_label_from_tool_call;
+use super::mission_store::StoredEvent;
+use super::routes::AppState;
+use crate::util::internal_error;
+
+const PAGE_SIZE: usize = 2000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PhaseKind {
+    Planning,
+    Tools,
+    WaitingForUser,
+    Completion,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimelinePhase {
+    pub kind: PhaseKind,
+    /// Tool burst label (e.g. "Reading") or terminal status for completion.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+    pub started_at: String,
+    pub ended_at: String,
+    pub duration_secs: f64,
+    pub event_count: usize,
+    pub tool_calls: usize,
+    pub errors: usize,
+    pub cost_cents: u64,
+    #[serde(skip)]
+    start: DateTime<Utc>,
+    #[serde(skip)]
+    end: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MissionTimeline {
+    pub mission_id: Uuid,
+    pub phases: Vec<TimelinePhase>,
+    pub total_duration_secs: f64,
+    pub total_cost_cents: u64,
+    pub event_count: usize,
+}
+
+/// Incrementally folds events (in order) into phases.
+#[derive(Debug, Default)]
+pub struct TimelineBuilder {
+    phases: Vec<TimelinePhase>,
+    /// Index of the first phase of the current turn (for cost attribution).
+    turn_start: usize,
+    event_count: usize,
+}
+
+/// Burst label for a tool call: the activity label up to its colon.
+fn tool_burst_label(tool_name: &str, args: &serde_json::Value) -> String {
+    let label = activity_label_from_tool_call(tool_name, args);
+    match label.split_once(':') {
+        Some((prefix, _)) => prefix.trim().to_string(),
+        None => label,
+    }
+}
+
+fn assistant_cost_cents(metadata: &serde_json::Value) -> u64 {
+    metadata
+        .pointer("/cost/amount_cents")
+        .or_else(|| metadata.get("cost_cents"))
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0)
+}
+
+impl TimelineBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn current(&mut self) -> Option<&mut TimelinePhase> {
+        self.phases.last_mut()
+    }
+
+    fn start_phase(&mut self, kind: PhaseKind, label: Option<String>, at: DateTime<Utc>) {
+        // The previous phase runs until this one starts.
+        if let Some(prev) = self.current() {
+            if at > prev.end {
+                prev.end = at;
+            }
+        }
+        self.phases.push(TimelinePhase {
+            kind,
+            label,
+            started_at: String::new(),
+            ended_at: String::new(),
+            duration_secs: 0.0,
+            event_count: 0,
+            tool_calls: 0,
+            errors: 0,
+            cost_cents: 0,
+            start: at,
+            end: at,
+        });
+    }
+
+    /// Make sure the current phase is `kind`/`label`, starting a new one if not.
+    fn ensure_phase(&mut self, kind: PhaseKind, label: Option<String>, at: DateTime<Utc>) {
+        let matches = self
+            .phases
+            .last()
+            .is_some_and(|p| p.kind == kind && p.label == label);
+        if !matches {
+            self.start_phase(kind, label, at);
+        }
+    }
+
+    /// Split a turn's cost over its working phases, weighted by duration.
+    fn attribute_cost(&mut self, cost_cents: u64) {
+        if cost_cents == 0 {
+            return;
+        }
+        let start = self.turn_start.min(self.phases.len());
+        let working: Vec<usize> = (start..self.phases.len())
+            .filter(|&i| matches!(self.phases[i].kind, PhaseKind::Planning | PhaseKind::Tools))
+            .collect();
+        let Some(&last) = working.last() else {
+            if let Some(phase) = self.current() {
+                phase.cost_cents += cost_cents;
+            }
+            return;
+        };
+        let weights: Vec<f64> = working
+            .iter()
+            .map(|&i| {
+                let p = &self.phases[i];
+                (p.end - p.start).num_milliseconds().max(0) as f64
+            })
+            .collect();
+        let total: f64 = weights.iter().sum();
+        if total <= 0.0 {
+            self.phases[last].cost_cents += cost_cents;
+            return;
+        }
+        let mut assigned = 0;
+        for (&i, w) in working.iter().zip(&weights) {
+            let share = (cost_cents as f64 * w / total).floor() as u64;
+            self.phases[i].cost_cents += share;
+            assigned += share;
+        }
+        self.phases[last].cost_cents += cost_cents - assigned;
+    }
+
+    pub fn push(&mut self, event: &StoredEvent) {
+        let Ok(at) = DateTime::parse_from_rfc3339(&event.timestamp) else {
+            return;
+        };
+        let at = at.with_timezone(&Utc);
+        self.event_count += 1;
+
+        match event.event_type.as_str() {
+            "user_message" => {
+                self.start_phase(PhaseKind::Planning, None, at);
+                self.turn_start = self.phases.len() - 1;
+            }
+            "thinking" | "text_delta" => {
+                self.ensure_phase(PhaseKind::Planning, None, at);
+            }
+            "tool_call" => {
+                let args = serde_json::from_str(&event.content).unwrap_or_default();
+                let label = tool_burst_label(event.tool_name.as_deref().unwrap_or(""), &args);
+                if label == "Waiting for input" {
+                    self.ensure_phase(PhaseKind::WaitingForUser, None, at);
+                } else {
+                    self.ensure_phase(PhaseKind::Tools, Some(label), at);
+                }
+                if let Some(phase) = self.current() {
+                    phase.tool_calls += 1;
+                }
+            }
+            "assistant_message" => {
+                if self.phases.is_empty() {
+                    self.start_phase(PhaseKind::Planning, None, at);
+                }
+                if let Some(phase) = self.current() {
+                    phase.event_count += 1;
+                    phase.end = phase.end.max(at);
+                }
+                self.attribute_cost(assistant_cost_cents(&event.metadata));
+                self.start_phase(PhaseKind::WaitingForUser, None, at);
+                return;
+            }
+            "mission_status_changed" => {
+                let status = event
+                    .metadata
+                    .get("status")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default()
+                    .to_string();
+                if !matches!(status.as_str(), "pending" | "active" | "") {
+                    self.start_phase(PhaseKind::Completion, Some(status), at);
+                }
+            }
+            "error" => {
+                if self.phases.is_empty() {
+                    self.start_phase(PhaseKind::Planning, None, at);
+                }
+                if let Some(phase) = self.current() {
+                    phase.errors += 1;
+                }
+            }
+            _ => {
+                if self.phases.is_empty() {
+                    self.start_phase(PhaseKind::Planning, None, at);
+                }
+            }
+        }
+
+        if let Some(phase) = self.current() {
+            phase.event_count += 1;
+            phase.end = phase.end.max(at);
+        }
+    }
+
+    pub fn finish(mut self, mission_id: Uuid) -> MissionTimeline {
+        // A trailing wait with nothing after it has no meaningful length.
+        if self
+            .phases
+            .last()
+            .is_some_and(|p| p.kind == PhaseKind::WaitingForUser && p.event_count == 0)
+        {
+            self.phases.pop();
+        }
+        for phase in &mut self.phases {
+            phase.started_at = phase.start.to_rfc3339();
+            phase.ended_at = phase.end.to_rfc3339();
+            phase.duration_secs =
+                (phase.end - phase.start).num_milliseconds().max(0) as f64 / 1000.0;
+        }
+        let total_duration_secs = match (self.phases.first(), self.phases.last()) {
+            (Some(first), Some(last)) => {
+                (last.end - first.start).num_milliseconds().max(0) as f64 / 1000.0
+            }
+            _ => 0.0,
+        };
+        MissionTimeline {
+            mission_id,
+            total_cost_cents: self.phases.iter().map(|p| p.cost_cents).sum(),
+            total_duration_secs,
+            event_count: self.event_count,
+            phases: self.phases,
+        }
+    }
+}
+
+/// GET /api/missions/:id/timeline - Mission events folded into phases.
+pub async fn get_mission_timeline(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(mission_id): Path<Uuid>,
+) -> Result<Json<MissionTimeline>, (StatusCode, String)> {
+    let control = state.control.get_or_spawn(&user).await;
+    let store = &control.mission_store;
+    if store
+        .get_mission(mission_id)
+        .await
+        .map_err(internal_error)?
+        .is_none()
+    {
+        return Err((StatusCode::NOT_FOUND, "Mission not found".to_string()));
+    }
+
+    let mut builder = TimelineBuilder::new();
+    let mut offset = 0;
+    loop {
+        let page = store
+            .get_events(mission_id, None, Some(PAGE_SIZE), Some(offset))
+            .await
+            .map_err(internal_error)?;
+        for event in &page {
+            builder.push(event);
+        }
+        if page.len() < PAGE_SIZE {
+            break;
+        }
+        offset += page.len();
+    }
+
+    Ok(Json(builder.finish(mission_id)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(
+        secs: i64,
+        event_type: &str,
+        tool: Option<&str>,
+        content: &str,
+        metadata: serde_json::Value,
+    ) -> StoredEvent {
+        let at = DateTime::parse_from_rfc3339("2026-01-01T00:00:00Z").unwrap()
+            + chrono::Duration::seconds(secs);
+        StoredEvent {
+            id: secs,
+            mission_id: Uuid::nil(),
+            sequence: secs,
+            event_type: event_type.to_string(),
+            timestamp: at.to_rfc3339(),
+            event_id: None,
+            tool_call_id: None,
+            tool_name: tool.map(str::to_string),
+            content: content.to_string(),
+            metadata,
+            stream_seq: None,
+            schema_version: crate::api::event_schema::CURRENT_VERSION,
+        }
+    }
+
+    #[test]
+    fn folds_events_into_phases_with_cost() {
+        let events = vec![
+            event(0, "user_message", None, "fix the bug", json!({})),
+            event(10, "thinking", None, "hmm", json!({})),
+            event(
+                20,
+                "tool_call",
+                Some("Read"),
+                r#"{"path":"a.rs"}"#,
+                json!({}),
+            ),
+            event(25, "tool_result", Some("Read"), "ok", json!({})),
+            event(
+                30,
+                "tool_call",
+                Some("Read"),
+                r#"{"path":"b.rs"}"#,
+                json!({}),
+            ),
+            event(
+                40,
+                "tool_call",
+                Some("Bash"),
+                r#"{"command":"cargo test"}"#,
+                json!({}),
+            ),
+            event(
+                60,
+                "assistant_message",
+                None,
+                "done",
+                json!({ "cost": { "amount_cents": 60 } }),
+            ),
+            event(
+                90,
+                "mission_status_changed",
+                None,
+                "",
+                json!({ "status": "completed" }),
+            ),
+        ];
+        let mut builder = TimelineBuilder::new();
+        for e in &events {
+            builder.push(e);
+        }
+        let timeline = builder.finish(Uuid::nil());
+
+        let kinds: Vec<_> = timeline
+            .phases
+            .iter()
+            .map(|p| (p.kind, p.label.as_deref(), p.duration_secs))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (PhaseKind::Planning, None, 20.0),
+                (PhaseKind::Tools, Some("Reading"), 20.0),
+                (PhaseKind::Tools, Some("Running"), 20.0),
+                (PhaseKind::WaitingForUser, None, 30.0),
+                (PhaseKind::Completion, Some("completed"), 0.0),
+            ]
+        );
+        assert_eq!(timeline.phases[1].tool_calls, 2);
+        let costs: Vec<_> = timeline.phases.iter().map(|p| p.cost_cents).collect();
+        assert_eq!(costs, vec![20, 20, 20, 0, 0]);
+        assert_eq!(timeline.total_cost_cents, 60);
+        assert_eq!(timeline.total_duration_secs, 90.0);
+    }
+}
@@ -0,0 +1,246 @@
+//! Crash-safe journaling of in-flight turns.
+//!
+//! The event logger persists events in batches behind the control stream, and
+//! mission history only gains the assistant reply once a turn finishes, so a
+//! process that dies mid-turn used to lose every tool result of that turn.
+//! [`journal_loop`] writes each step of a running turn (the user message, tool
+//! calls issued, results received) to the store as soon as it is emitted. A new
+//! turn replaces the journal and a successful reply clears it, so whatever is
+//! left belongs to an unfinished turn and is rendered into the resume prompt by
+//! [`format_turn_journal`].
+
+use std::sync::Arc;
+
+use tokio::sync::broadcast;
+
+use super::control::{safe_truncate_index, AgentEvent};
+use super::mission_store::{now_string, MissionStore, TurnJournalEntry, TurnJournalKind};
+
+/// Longest tool argument or result stored in the journal.
+const MAX_JOURNAL_CONTENT: usize = 4000;
+
+/// Longest tool argument or result quoted in the resume prompt.
+const MAX_RESUME_CONTENT: usize = 800;
+
+/// What the journal should do for an event.
+#[derive(Debug)]
+enum JournalOp {
+    /// A new turn began: replace the journal with this entry
+    Start(TurnJournalEntry),
+    Append(TurnJournalEntry),
+    /// The turn finished successfully
+    Clear(uuid::Uuid),
+}
+
+fn truncate(content: &str, max: usize) -> String {
+    if content.len() > max {
+        let end = safe_truncate_index(content, max);
+        format!("{}...", &content[..end])
+    } else {
+        content.to_string()
+    }
+}
+
+fn value_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn journal_op(event: &AgentEvent) -> Option<JournalOp> {
+    let entry = |mission_id, kind, tool: Option<(&str, &str)>, content: &str| TurnJournalEntry {
+        mission_id,
+        kind,
+        tool_call_id: tool.map(|(id, _)| id.to_string()),
+        tool_name: tool.map(|(_, name)| name.to_string()),
+        content: truncate(content, MAX_JOURNAL_CONTENT),
+        created_at: now_string(),
+    };
+    match event {
+        AgentEvent::UserMessage {
+            content,
+            queued: false,
+            mission_id: Some(mission_id),
+            ..
+        } => Some(JournalOp::Start(entry(
+            *mission_id,
+            TurnJournalKind::UserMessage,
+            None,
+            content,
+        ))),
+        AgentEvent::ToolCall {
+            tool_call_id,
+            name,
+            args,
+            mission_id: Some(mission_id),
+        } => Some(JournalOp::Append(entry(
+            *mission_id,
+            TurnJournalKind::ToolCall,
+            Some((tool_call_id, name)),
+            &value_text(args),
+        ))),
+        AgentEvent::ToolResult {
+            tool_call_id,
+            name,
+            result,
+            mission_id: Some(mission_id),
+        } => Some(JournalOp::Append(entry(
+            *mission_id,
+            TurnJournalKind::ToolResult,
+            Some((tool_call_id, name)),
+            &value_text(result),
+        ))),
+        AgentEvent::AssistantMessage {
+            success: true,
+            mission_id: Some(mission_id),
+            ..
+        } => Some(JournalOp::Clear(*mission_id)),
+        _ => None,
+    }
+}
+
+/// Background task that journals in-flight turns for every mission.
+pub async fn journal_loop(
+    mission_store: Arc<dyn MissionStore>,
+    mut events_rx: broadcast::Receiver<AgentEvent>,
+) {
+    loop {
+        let event = match events_rx.recv().await {
+            Ok(event) => event,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::warn!("Turn journal fell behind; {} events not journaled", skipped);
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        let Some(op) = journal_op(&event) else {
+            continue;
+        };
+        let result = match op {
+            JournalOp::Start(entry) => {
+                match mission_store.clear_turn_journal(entry.mission_id).await {
+                    Ok(()) => mission_store.append_turn_journal(entry).await,
+                    Err(e) => Err(e),
+                }
+            }
+            JournalOp::Append(entry) => mission_store.append_turn_journal(entry).await,
+            JournalOp::Clear(mission_id) => mission_store.clear_turn_journal(mission_id).await,
+        };
+        if let Err(e) = result {
+            tracing::warn!("Failed to journal turn step: {}", e);
+        }
+    }
+}
+
+/// Render an unfinished turn for the resume prompt. Returns `None` when the
+/// journal has no tool activity worth reporting.
+pub fn format_turn_journal(journal: &[TurnJournalEntry]) -> Option<String> {
+    let calls: Vec<&TurnJournalEntry> = journal
+        .iter()
+        .filter(|entry| entry.kind == TurnJournalKind::ToolCall)
+        .collect();
+    if calls.is_empty() {
+        return None;
+    }
+
+    let mut lines = vec!["\n## Interrupted Turn".to_string()];
+    if let Some(message) = journal
+        .iter()
+        .find(|entry| entry.kind == TurnJournalKind::UserMessage)
+    {
+        lines.push(format!(
+            "The last turn was cut off while working on:\n{}",
+            truncate(&message.content, MAX_RESUME_CONTENT)
+        ));
+    }
+    lines.push("\nTool calls made during that turn, in order:".to_string());
+    for (index, call) in calls.iter().enumerate() {
+        let name = call.tool_name.as_deref().unwrap_or("tool");
+        lines.push(format!(
+            "{}. `{}` {}",
+            index + 1,
+            name,
+            truncate(&call.content, MAX_RESUME_CONTENT)
+        ));
+        let result = journal.iter().find(|entry| {
+            entry.kind == TurnJournalKind::ToolResult && entry.tool_call_id == call.tool_call_id
+        });
+        match result {
+            Some(result) => lines.push(format!(
+                "   Result: {}",
+                truncate(&result.content, MAX_RESUME_CONTENT)
+            )),
+            None => lines.push(
+                "   Result: none recorded (the call may not have finished; verify before relying on it)"
+                    .to_string(),
+            ),
+        }
+    }
+    Some(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    #[test]
+    fn only_running_turn_events_are_journaled() {
+        let mission_id = Uuid::new_v4();
+        let queued = AgentEvent::UserMessage {
+            id: Uuid::new_v4(),
+            content: "later".to_string(),
+            queued: true,
+            mission_id: Some(mission_id),
+        };
+        assert!(journal_op(&queued).is_none());
+
+        let started = AgentEvent::UserMessage {
+            id: Uuid::new_v4(),
+            content: "now".to_string(),
+            queued: false,
+            mission_id: Some(mission_id),
+        };
+        assert!(matches!(journal_op(&started), Some(JournalOp::Start(_))));
+
+        let call = AgentEvent::ToolCall {
+            tool_call_id: "c1".to_string(),
+            name: "bash".to_string(),
+            args: serde_json::json!({"command": "ls"}),
+            mission_id: Some(mission_id),
+        };
+        match journal_op(&call) {
+            Some(JournalOp::Append(entry)) => {
+                assert_eq!(entry.kind, TurnJournalKind::ToolCall);
+                assert_eq!(entry.content, r#"{"command":"ls"}"#);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn resume_section_flags_calls_without_results() {
+        let mission_id = Uuid::new_v4();
+        let entry = |kind, id: Option<&str>, content: &str| TurnJournalEntry {
+            mission_id,
+            kind,
+            tool_call_id: id.map(str::to_string),
+            tool_name: id.map(|_| "bash".to_string()),
+            content: content.to_string(),
+            created_at: now_string(),
+        };
+        assert!(format_turn_journal(&[entry(TurnJournalKind::UserMessage, None, "hi")]).is_none());
+
+        let text = format_turn_journal(&[
+            entry(TurnJournalKind::UserMessage, None, "deploy it"),
+            entry(TurnJournalKind::ToolCall, Some("c1"), "make build"),
+            entry(TurnJournalKind::ToolResult, Some("c1"), "built"),
+            entry(TurnJournalKind::ToolCall, Some("c2"), "make deploy"),
+        ])
+        .unwrap();
+        assert!(text.contains("deploy it"));
+        assert!(text.contains("1. `bash` make build\n   Result: built"));
+        assert!(text.contains("2. `bash` make deploy\n   Result: none recorded"));
+    }
+}
@@ -0,0 +1,460 @@
+//! Per-user data export and deletion.
+//!
+//! - `GET /api/users/:id/export` bundles everything stored for the user into
+//!   a zip: each mission (with its history), all of its events, its turn
+//!   journal, automations, scheduled messages, audit entries, and the files in
+//!   the mission's working directory (artifacts, plus agent memories kept
+//!   there).
+//! - `DELETE /api/users/:id/data` removes the same missions and files.
+//!   `?dry_run=true` returns the report of what would be removed without
+//!   deleting anything.
+//!
+//! Users can only export or delete their own data. Audit entries are exported
+//! but kept on deletion, since the audit log is meant to outlive missions.
+
+use std::io::Write as IoWrite;
+use std::path::{Path as FsPath, PathBuf};
+use std::sync::Arc;
+
+use axum::{
+    body::Body,
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::{IntoResponse, Json},
+    routing::{delete, get},
+    Extension, Router,
+};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::auth::AuthUser;
+use super::control::{self, ControlState, MissionStatus};
+use super::mission_store::{Mission, MissionStore, StoredEvent};
+use super::routes::AppState;
+use crate::util::internal_error;
+
+const PAGE_SIZE: usize = 2000;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/:id/export", get(export_user_data))
+        .route("/:id/data", delete(delete_user_data))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteQuery {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// What is (or would be) removed for one mission.
+#[derive(Debug, Serialize)]
+pub struct MissionDataReport {
+    pub mission_id: Uuid,
+    pub title: Option<String>,
+    pub status: MissionStatus,
+    pub events: usize,
+    pub automations: usize,
+    pub scheduled_messages: usize,
+    /// Mission working directory, when it exists on disk.
+    pub directory: Option<String>,
+    pub files: u64,
+    pub bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserDataReport {
+    pub user_id: String,
+    pub dry_run: bool,
+    pub missions: Vec<MissionDataReport>,
+    pub total_events: usize,
+    pub total_files: u64,
+    pub total_bytes: u64,
+    /// Audit entries referencing the missions. Exported, never deleted.
+    pub audit_entries_retained: usize,
+}
+
+/// A mission owned by the user, with its working directory if present.
+struct UserMission {
+    mission: Mission,
+    directory: Option<PathBuf>,
+}
+
+/// Resolve the user's control session, rejecting access to anyone else's data.
+async fn authorize(
+    state: &Arc<AppState>,
+    user: &AuthUser,
+    user_id: &str,
+) -> Result<ControlState, (StatusCode, String)> {
+    if user.id != user_id {
+        return Err((
+            StatusCode::FORBIDDEN,
+            "You can only access your own data".to_string(),
+        ));
+    }
+    Ok(state.control.get_or_spawn(user).await)
+}
+
+async fn collect_missions(
+    state: &Arc<AppState>,
+    store: &Arc<dyn MissionStore>,
+) -> Result<Vec<UserMission>, String> {
+    let mut missions = Vec::new();
+    let mut offset = 0;
+    loop {
+        let page = store.list_missions(PAGE_SIZE, offset).await?;
+        let page_len = page.len();
+        missions.extend(page);
+        if page_len < PAGE_SIZE {
+            break;
+        }
+        offset += page_len;
+    }
+
+    let mut result = Vec::with_capacity(missions.len());
+    for summary in missions {
+        // Listings may omit history; load the full record.
+        let mission = store.get_mission(summary.id).await?.unwrap_or(summary);
+        let directory = state
+            .workspaces
+            .get(mission.workspace_id)
+            .await
+            .map(|ws| crate::workspace::mission_workspace_dir_for_root(&ws.path, mission.id))
+            .filter(|dir| dir.is_dir());
+        result.push(UserMission { mission, directory });
+    }
+    Ok(result)
+}
+
+async fn all_events(
+    store: &Arc<dyn MissionStore>,
+    mission_id: Uuid,
+) -> Result<Vec<StoredEvent>, String> {
+    let mut events = Vec::new();
+    let mut offset = 0;
+    loop {
+        let page = store
+            .get_events(mission_id, None, Some(PAGE_SIZE), Some(offset))
+            .await?;
+        let page_len = page.len();
+        events.extend(page);
+        if page_len < PAGE_SIZE {
+            break;
+        }
+        offset += page_len;
+    }
+    Ok(events)
+}
+
+/// Count regular files and their total size under `dir`, without following
+/// symlinks.
+fn directory_usage(dir: &FsPath) -> (u64, u64) {
+    walkdir::WalkDir::new(dir)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+        .fold((0, 0), |(files, bytes), e| {
+            (
+                files + 1,
+                bytes + e.metadata().map(|m| m.len()).unwrap_or(0),
+            )
+        })
+}
+
+async fn build_report(
+    state: &Arc<AppState>,
+    store: &Arc<dyn MissionStore>,
+    user_id: &str,
+    missions: &[UserMission],
+    dry_run: bool,
+) -> Result<UserDataReport, String> {
+    let mut report = UserDataReport {
+        user_id: user_id.to_string(),
+        dry_run,
+        missions: Vec::with_capacity(missions.len()),
+        total_events: 0,
+        total_files: 0,
+        total_bytes: 0,
+        audit_entries_retained: 0,
+    };
+
+    for UserMission { mission, directory } in missions {
+        let events = all_events(store, mission.id).await?.len();
+        let automations = store.get_mission_automations(mission.id).await?.len();
+        let scheduled_messages = store
+            .list_scheduled_messages(Some(mission.id), None)
+            .await?
+            .len();
+        let (files, bytes) = match directory.clone() {
+            Some(dir) => tokio::task::spawn_blocking(move || directory_usage(&dir))
+                .await
+                .map_err(|e| e.to_string())?,
+            None => (0, 0),
+        };
+        report.audit_entries_retained += state
+            .audit
+            .recent(None, Some(mission.id), usize::MAX)
+            .await
+            .len();
+
+        report.total_events += events;
+        report.total_files += files;
+        report.total_bytes += bytes;
+        report.missions.push(MissionDataReport {
+            mission_id: mission.id,
+            title: mission.title.clone(),
+            status: mission.status,
+            events,
+            automations,
+            scheduled_messages,
+            directory: directory.as_ref().map(|d| d.display().to_string()),
+            files,
+            bytes,
+        });
+    }
+    Ok(report)
+}
+
+/// DELETE /api/users/:id/data
+/// Delete all missions, events and mission files for the user.
+async fn delete_user_data(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(user_id): Path<String>,
+    Query(query): Query<DeleteQuery>,
+) -> Result<Json<UserDataReport>, (StatusCode, String)> {
+    let control = authorize(&state, &user, &user_id).await?;
+    let store = control.mission_store.clone();
+
+    let missions = collect_missions(&state, &store)
+        .await
+        .map_err(internal_error)?;
+    let report = build_report(&state, &store, &user_id, &missions, query.dry_run)
+        .await
+        .map_err(internal_error)?;
+    if query.dry_run {
+        return Ok(Json(report));
+    }
+
+    let running = control::get_running_missions(&control).await?;
+    if !running.is_empty() {
+        return Err((
+            StatusCode::CONFLICT,
+            "Cannot delete data while missions are running. Cancel them first.".to_string(),
+        ));
+    }
+
+    for UserMission { mission, directory } in &missions {
+        store
+            .delete_mission(mission.id)
+            .await
+            .map_err(internal_error)?;
+        control.metadata_refresh.clear(mission.id);
+        if let Some(dir) = directory {
+            if let Err(e) = tokio::fs::remove_dir_all(dir).await {
+                tracing::warn!(
+                    mission_id = %mission.id,
+                    path = %dir.display(),
+                    error = %e,
+                    "Failed to remove mission directory"
+                );
+            }
+        }
+    }
+
+    tracing::info!(
+        user_id = %user_id,
+        missions = report.missions.len(),
+        events = report.total_events,
+        files = report.total_files,
+        "Deleted user data"
+    );
+    Ok(Json(report))
+}
+
+/// GET /api/users/:id/export
+/// Download all of the user's mission data as a zip archive.
+async fn export_user_data(
+    State(state): State<Arc<AppState>>,
+    Extension(user): Extension<AuthUser>,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let control = authorize(&state, &user, &user_id).await?;
+    let store = control.mission_store.clone();
+
+    let missions = collect_missions(&state, &store)
+        .await
+        .map_err(internal_error)?;
+    let report = build_report(&state, &store, &user_id, &missions, false)
+        .await
+        .map_err(internal_error)?;
+
+    // Gather JSON documents first so the archive can be written off the
+    // async runtime.
+    let mut documents: Vec<(String, Vec<u8>)> = Vec::new();
+    let mut directories: Vec<(String, PathBuf)> = Vec::new();
+    documents.push((
+        "user.json".to_string(),
+        serde_json::to_vec_pretty(&serde_json::json!({
+            "user_id": user.id,
+            "username": user.username,
+            "exported_at": chrono::Utc::now().to_rfc3339(),
+            "summary": report,
+        }))
+        .map_err(internal_error)?,
+    ));
+    for UserMission { mission, directory } in &missions {
+        let prefix = format!("missions/{}", mission.id);
+        let events = all_events(&store, mission.id)
+            .await
+            .map_err(internal_error)?;
+        let journal = store
+            .get_turn_journal(mission.id)
+            .await
+            .map_err(internal_error)?;
+        let automations = store
+            .get_mission_automations(mission.id)
+            .await
+            .map_err(internal_error)?;
+        let scheduled = store
+            .list_scheduled_messages(Some(mission.id), None)
+            .await
+            .map_err(internal_error)?;
+        let feedback = store
+            .list_message_feedback(Some(mission.id), None)
+            .await
+            .map_err(internal_error)?;
+        let comments = store
+            .list_mission_comments(mission.id)
+            .await
+            .map_err(internal_error)?;
+        let audit = state.audit.recent(None, Some(mission.id), usize::MAX).await;
+
+        let entries = [
+            ("mission.json", serde_json::to_vec_pretty(mission)),
+            ("events.json", serde_json::to_vec_pretty(&events)),
+            ("turn_journal.json", serde_json::to_vec_pretty(&journal)),
+            ("automations.json", serde_json::to_vec_pretty(&automations)),
+            (
+                "scheduled_messages.json",
+                serde_json::to_vec_pretty(&scheduled),
+            ),
+            ("feedback.json", serde_json::to_vec_pretty(&feedback)),
+            ("comments.json", serde_json::to_vec_pretty(&comments)),
+            ("audit.json", serde_json::to_vec_pretty(&audit)),
+        ];
+        for (name, bytes) in entries {
+            documents.push((
+                format!("{}/{}", prefix, name),
+                bytes.map_err(internal_error)?,
+            ));
+        }
+        if let Some(dir) = directory {
+            directories.push((format!("{}/files", prefix), dir.clone()));
+        }
+    }
+
+    let zip_buffer = tokio::task::spawn_blocking(move || write_archive(documents, directories))
+        .await
+        .map_err(internal_error)?
+        .map_err(|e| {
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to build export archive: {}", e),
+            )
+        })?;
+
+    let timestamp = chrono::Utc::now().format("%Y%m%d-%H%M%S");
+    let content_disposition = format!(
+        "attachment; filename=\"sandboxed-export-{}.zip\"",
+        timestamp
+    );
+    let headers = [
+        (header::CONTENT_TYPE, "application/zip".to_string()),
+        (header::CONTENT_DISPOSITION, content_disposition),
+    ];
+    Ok((headers, Body::from(zip_buffer)))
+}
+
+fn write_archive(
+    documents: Vec<(String, Vec<u8>)>,
+    directories: Vec<(String, PathBuf)>,
+) -> Result<Vec<u8>, std::io::Error> {
+    let mut zip_buffer = Vec::new();
+    {
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(&mut zip_buffer));
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated)
+            .large_file(true);
+
+        for (name, bytes) in documents {
+            zip.start_file(name, options)
+                .map_err(std::io::Error::other)?;
+            zip.write_all(&bytes)?;
+        }
+
+        for (prefix, dir) in directories {
+            for entry in walkdir::WalkDir::new(&dir)
+                .follow_links(false)
+                .into_iter()
+                .filter_map(|e| e.ok())
+                .filter(|e| e.file_type().is_file())
+            {
+                let Ok(relative) = entry.path().strip_prefix(&dir) else {
+                    continue;
+                };
+                let contents = match std::fs::read(entry.path()) {
+                    Ok(contents) => contents,
+                    Err(e) => {
+                        tracing::warn!(
+                            path = %entry.path().display(),
+                            error = %e,
+                            "Skipping unreadable file in export"
+                        );
+                        continue;
+                    }
+                };
+                zip.start_file(format!("{}/{}", prefix, relative.display()), options)
+                    .map_err(std::io::Error::other)?;
+                zip.write_all(&contents)?;
+            }
+        }
+
+        zip.finish().map_err(std::io::Error::other)?;
+    }
+    Ok(zip_buffer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archive_contains_documents_and_directory_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir_all(dir.path().join("output")).unwrap();
+        std::fs::write(dir.path().join("output/report.md"), "# Report").unwrap();
+
+        let buffer = write_archive(
+            vec![("user.json".to_string(), b"{}".to_vec())],
+            vec![("missions/m/files".to_string(), dir.path().to_path_buf())],
+        )
+        .unwrap();
+
+        let mut archive = zip::ZipArchive::new(std::io::Cursor::new(buffer)).unwrap();
+        let mut names: Vec<String> = archive.file_names().map(String::from).collect();
+        names.sort();
+        assert_eq!(names, ["missions/m/files/output/report.md", "user.json"]);
+        let mut report = String::new();
+        std::io::Read::read_to_string(
+            &mut archive
+                .by_name("missions/m/files/output/report.md")
+                .unwrap(),
+            &mut report,
+        )
+        .unwrap();
+        assert_eq!(report, "# Report");
+        assert_eq!(directory_usage(dir.path()), (1, 8));
+    }
+}
@@ -0,0 +1,424 @@
+//! Structured questions from the agent to the user.
+//!
+//! When a harness calls its question tool (`AskUserQuestion` for Claude Code,
+//! `question` for OpenCode), the tool input is parsed into typed questions —
+//! single choice, multiple choice, free text or a file from the workspace —
+//! and published as a `user_question` event. The turn then blocks on the
+//! [`FrontendToolHub`] until the dashboard posts the answer to
+//! `/api/control/tool_result`.
+//!
+//! A question may carry a `default`. If nobody answers within the timeout the
+//! defaults are used (questions without one are answered with `null`) and the
+//! turn carries on. Every answer is published as `user_question_answered` and
+//! appended to the mission history, so later turns know what was decided.
+//!
+//! Configuration:
+//!
+//! - `SANDBOXED_SH_QUESTION_TIMEOUT_SECS` - seconds to wait for an answer
+//!   (default: wait until the user answers or the mission is cancelled). A
+//!   question's own `timeout_secs` takes precedence.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::broadcast;
+use tokio_util::sync::CancellationToken;
+use uuid::Uuid;
+
+use super::control::{AgentEvent, FrontendToolHub};
+use super::mission_store::{MissionHistoryEntry, MissionStore};
+
+/// How a question is answered.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuestionKind {
+    /// Pick one of `options`
+    SingleChoice,
+    /// Pick any number of `options`
+    MultiChoice,
+    FreeText,
+    /// Pick a file; the answer is its path relative to the workspace
+    File,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct QuestionOption {
+    pub label: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Question {
+    pub question: String,
+    /// Short label for the question (e.g. a chip title)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub header: Option<String>,
+    pub kind: QuestionKind,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<QuestionOption>,
+    /// Answer used when the question times out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<Value>,
+}
+
+/// How a question ended.
+#[derive(Debug)]
+pub enum Outcome {
+    /// The answer to hand back to the harness, from the user or the defaults
+    Answered(Value),
+    Cancelled,
+    /// The tool hub dropped the pending question
+    Closed,
+}
+
+/// Whether `name` is a harness's question tool.
+pub fn is_question_tool(name: &str) -> bool {
+    matches!(name, "AskUserQuestion" | "question")
+}
+
+/// Parse the questions out of a question tool's input. Accepts Claude Code's
+/// `{"questions": [{"question", "header", "options", "multiSelect"}]}`,
+/// OpenCode's `multiple` flag, and explicit `kind`/`default` fields.
+pub fn parse_questions(input: &Value) -> Vec<Question> {
+    let entries = match input.get("questions") {
+        Some(Value::Array(entries)) => entries.clone(),
+        _ if input.get("question").is_some() => vec![input.clone()],
+        _ => Vec::new(),
+    };
+    entries.iter().filter_map(parse_question).collect()
+}
+
+fn parse_question(entry: &Value) -> Option<Question> {
+    let text = entry.get("question").and_then(Value::as_str)?.trim();
+    if text.is_empty() {
+        return None;
+    }
+    let options: Vec<QuestionOption> = entry
+        .get("options")
+        .and_then(Value::as_array)
+        .map(|options| {
+            options
+                .iter()
+                .filter_map(|option| match option {
+                    Value::String(label) => Some(QuestionOption {
+                        label: label.clone(),
+                        description: None,
+                    }),
+                    _ => serde_json::from_value(option.clone()).ok(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let multiple = ["multiSelect", "multiple"]
+        .iter()
+        .any(|key| entry.get(*key).and_then(Value::as_bool) == Some(true));
+    let kind = entry
+        .get("kind")
+        .and_then(|kind| serde_json::from_value(kind.clone()).ok())
+        .unwrap_or(match (multiple, options.is_empty()) {
+            (true, _) => QuestionKind::MultiChoice,
+            (false, false) => QuestionKind::SingleChoice,
+            (false, true) => QuestionKind::FreeText,
+        });
+    Some(Question {
+        question: text.to_string(),
+        header: entry
+            .get("header")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        kind,
+        options,
+        default: entry.get("default").filter(|v| !v.is_null()).cloned(),
+    })
+}
+
+/// Time to wait for an answer: the input's `timeout_secs`, else the
+/// configured default. `None` waits indefinitely.
+pub fn timeout(input: &Value) -> Option<Duration> {
+    let secs = input
+        .get("timeout_secs")
+        .and_then(Value::as_u64)
+        .or_else(|| {
+            std::env::var("SANDBOXED_SH_QUESTION_TIMEOUT_SECS")
+                .ok()
+                .and_then(|v| v.trim().parse().ok())
+        })?;
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// The answer used on timeout: each question's default, keyed by question.
+pub fn default_answer(questions: &[Question]) -> Value {
+    let answers: serde_json::Map<String, Value> = questions
+        .iter()
+        .map(|q| (q.question.clone(), q.default.clone().unwrap_or(Value::Null)))
+        .collect();
+    serde_json::json!({ "answers": answers, "timed_out": true })
+}
+
+/// Publish the question, wait for the answer through `hub`, and publish the
+/// answer. Falls back to [`default_answer`] when the timeout expires.
+pub async fn ask(
+    hub: &FrontendToolHub,
+    events_tx: &broadcast::Sender<AgentEvent>,
+    mission_id: Option<Uuid>,
+    tool_call_id: &str,
+    input: &Value,
+    cancel: Option<&CancellationToken>,
+) -> Outcome {
+    let questions = parse_questions(input);
+    let timeout = timeout(input);
+    let _ = events_tx.send(AgentEvent::UserQuestion {
+        tool_call_id: tool_call_id.to_string(),
+        questions: questions.clone(),
+        timeout_secs: timeout.map(|t| t.as_secs()),
+        mission_id,
+    });
+
+    let rx = hub.register(tool_call_id.to_string()).await;
+    let wait = async {
+        match timeout {
+            Some(timeout) => tokio::time::timeout(timeout, rx).await.ok(),
+            None => Some(rx.await),
+        }
+    };
+    let cancelled = async {
+        match cancel {
+            Some(cancel) => cancel.cancelled().await,
+            None => std::future::pending().await,
+        }
+    };
+    let (answer, timed_out) = tokio::select! {
+        _ = cancelled => {
+            hub.forget(tool_call_id).await;
+            return Outcome::Cancelled;
+        }
+        result = wait => match result {
+            Some(Ok(answer)) => (answer, false),
+            Some(Err(_)) => return Outcome::Closed,
+            None => {
+                hub.forget(tool_call_id).await;
+                tracing::info!(
+                    tool_call_id = %tool_call_id,
+                    mission_id = ?mission_id,
+                    "Question timed out; using defaults"
+                );
+                (default_answer(&questions), true)
+            }
+        },
+    };
+
+    let _ = events_tx.send(AgentEvent::UserQuestionAnswered {
+        tool_call_id: tool_call_id.to_string(),
+        questions,
+        answers: answer.get("answers").cloned().unwrap_or(answer.clone()),
+        timed_out,
+        mission_id,
+    });
+    Outcome::Answered(answer)
+}
+
+/// Render an answered question set as a mission history entry.
+pub fn history_entry(questions: &[Question], answers: &Value, timed_out: bool) -> String {
+    let mut lines = vec![if timed_out {
+        "Answered questions (no reply in time; defaults used):".to_string()
+    } else {
+        "Answered questions:".to_string()
+    }];
+    for question in questions {
+        let answer = answers
+            .get(&question.question)
+            .or_else(|| answers.get(question.header.as_deref().unwrap_or_default()))
+            .map(render_answer)
+            .unwrap_or_else(|| "(no answer)".to_string());
+        lines.push(format!("- {}\n  {}", question.question, answer));
+    }
+    if questions.is_empty() {
+        lines.push(render_answer(answers));
+    }
+    lines.join("\n")
+}
+
+fn render_answer(answer: &Value) -> String {
+    match answer {
+        Value::Null => "(no answer)".to_string(),
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items
+            .iter()
+            .map(render_answer)
+            .collect::<Vec<_>>()
+            .join(", "),
+        other => other.to_string(),
+    }
+}
+
+/// Append every answered question to its mission's history. (The SQLite
+/// store derives history from the logged `user_question_answered` events.)
+pub async fn history_loop(
+    mission_store: Arc<dyn MissionStore>,
+    mut events_rx: broadcast::Receiver<AgentEvent>,
+) {
+    loop {
+        let event = match events_rx.recv().await {
+            Ok(event) => event,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                tracing::warn!(
+                    "Question history fell behind; {} events not checked",
+                    skipped
+                );
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+        let AgentEvent::UserQuestionAnswered {
+            questions,
+            answers,
+            timed_out,
+            mission_id: Some(mission_id),
+            ..
+        } = event
+        else {
+            continue;
+        };
+        let mut entries = match mission_store.get_mission(mission_id).await {
+            Ok(Some(mission)) => mission.history,
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::warn!(
+                    "Failed to load mission {} for question history: {}",
+                    mission_id,
+                    e
+                );
+                continue;
+            }
+        };
+        entries.push(MissionHistoryEntry {
+            role: "user".to_string(),
+            content: history_entry(&questions, &answers, timed_out),
+            tool_name: None,
+        });
+        if let Err(e) = mission_store
+            .update_mission_history(mission_id, &entries)
+            .await
+        {
+            tracing::warn!("Failed to record question answer in history: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parses_claude_code_questions() {
+        let questions = parse_questions(&json!({
+            "questions": [
+                {
+                    "question": "Which database?",
+                    "header": "Database",
+                    "options": [
+                        {"label": "Postgres", "description": "Production default"},
+                        {"label": "SQLite"}
+                    ],
+                    "multiSelect": false
+                },
+                {
+                    "question": "Which features?",
+                    "options": [{"label": "Auth"}, {"label": "Billing"}],
+                    "multiSelect": true,
+                    "default": ["Auth"]
+                },
+                {"question": "Anything else?"},
+                {"question": "Which config file?", "kind": "file"}
+            ]
+        }));
+        let kinds: Vec<QuestionKind> = questions.iter().map(|q| q.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                QuestionKind::SingleChoice,
+                QuestionKind::MultiChoice,
+                QuestionKind::FreeText,
+                QuestionKind::File,
+            ]
+        );
+        assert_eq!(questions[0].header.as_deref(), Some("Database"));
+        assert_eq!(
+            questions[0].options[0].description.as_deref(),
+            Some("Production default")
+        );
+        assert_eq!(questions[1].default, Some(json!(["Auth"])));
+    }
+
+    #[test]
+    fn timeout_answers_with_defaults() {
+        let questions = parse_questions(&json!({
+            "questions": [
+                {"question": "Proceed?", "options": ["Yes", "No"], "default": "Yes"},
+                {"question": "Notes?"}
+            ]
+        }));
+        let answer = default_answer(&questions);
+        assert_eq!(answer["answers"]["Proceed?"], "Yes");
+        assert!(answer["answers"]["Notes?"].is_null());
+
+        let entry = history_entry(&questions, &answer["answers"], true);
+        assert!(entry.contains("defaults used"));
+        assert!(entry.contains("- Proceed?\n  Yes"));
+        assert!(entry.contains("- Notes?\n  (no answer)"));
+    }
+
+    #[tokio::test]
+    async fn ask_returns_the_posted_answer() {
+        let hub = Arc::new(FrontendToolHub::new());
+        let (events_tx, mut events_rx) = broadcast::channel(16);
+        let input = json!({"questions": [{"question": "Proceed?", "options": ["Yes", "No"]}]});
+
+        let resolver = {
+            let hub = Arc::clone(&hub);
+            tokio::spawn(async move {
+                hub.resolve("call-1", json!({"answers": {"Proceed?": "No"}}))
+                    .await
+                    .unwrap();
+            })
+        };
+        let outcome = ask(&hub, &events_tx, None, "call-1", &input, None).await;
+        resolver.await.unwrap();
+
+        let Outcome::Answered(answer) = outcome else {
+            panic!("expected an answer");
+        };
+        assert_eq!(answer["answers"]["Proceed?"], "No");
+        assert!(matches!(
+            events_rx.recv().await,
+            Ok(AgentEvent::UserQuestion { .. })
+        ));
+        assert!(matches!(
+            events_rx.recv().await,
+            Ok(AgentEvent::UserQuestionAnswered {
+                timed_out: false,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn ask_falls_back_to_defaults_on_timeout() {
+        let hub = FrontendToolHub::new();
+        let (events_tx, _events_rx) = broadcast::channel(16);
+        let input = json!({
+            "timeout_secs": 1,
+            "questions": [{"question": "Proceed?", "options": ["Yes", "No"], "default": "Yes"}]
+        });
+        let Outcome::Answered(answer) = ask(&hub, &events_tx, None, "call-2", &input, None).await
+        else {
+            panic!("expected the default answer");
+        };
+        assert_eq!(answer["answers"]["Proceed?"], "Yes");
+        assert_eq!(answer["timed_out"], true);
+    }
+}
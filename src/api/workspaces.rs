@@ -10,7 +10,7 @@ use axum::{
     extract::{Path as AxumPath, State},
     http::StatusCode,
     routing::{delete, get, post, put},
-    Json, Router,
+    Extension, Json, Router,
 };
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -18,8 +18,13 @@ use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use uuid::Uuid;
 
+use super::auth::AuthUser;
+use crate::blackout::{validate_windows, BlackoutWindow};
+use crate::egress::EgressPolicy;
+use crate::kubernetes::KubernetesAccess;
 use crate::library::WorkspaceTemplate;
 use crate::nspawn::NspawnDistro;
+use crate::ssh::SshAccess;
 use crate::util::sanitize_skill_list;
 use crate::workspace::{self, TailscaleMode, Workspace, WorkspaceStatus, WorkspaceType};
 
@@ -34,6 +39,10 @@ pub fn routes() -> Router<Arc<super::routes::AppState>> {
         .route("/:id/build", post(build_workspace))
         .route("/:id/sync", post(sync_workspace))
         .route("/:id/exec", post(exec_workspace_command))
+        .route(
+            "/:id/calendar",
+            post(super::automation_calendar::create_calendar_link),
+        )
         // Debug endpoints for template development
         .route("/:id/debug", get(get_workspace_debug))
         .route("/:id/rerun-init", post(rerun_init_script))
@@ -86,6 +95,14 @@ pub struct CreateWorkspaceRequest {
     pub mcps: Vec<String>,
     /// Optional config profile to apply to this workspace.
     pub config_profile: Option<String>,
+    /// Library environment profile applied to missions in this workspace.
+    pub env_profile: Option<String>,
+    /// Kubernetes cluster access for missions in this workspace.
+    pub kubernetes: Option<KubernetesAccess>,
+    /// SSH host allowlist for missions in this workspace.
+    pub ssh: Option<SshAccess>,
+    /// Advisory (proxy-based) egress policy for mission tool processes.
+    pub egress: Option<EgressPolicy>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -115,6 +132,20 @@ pub struct UpdateWorkspaceRequest {
     pub mcps: Option<Vec<String>>,
     /// Optional config profile to apply to this workspace.
     pub config_profile: Option<String>,
+    /// Library environment profile applied to missions in this workspace.
+    pub env_profile: Option<String>,
+    /// Kubernetes cluster access for missions in this workspace.
+    pub kubernetes: Option<KubernetesAccess>,
+    /// SSH host allowlist for missions in this workspace.
+    pub ssh: Option<SshAccess>,
+    /// Advisory (proxy-based) egress policy for mission tool processes.
+    pub egress: Option<EgressPolicy>,
+    /// Windows during which the workspace's automations aren't triggered
+    /// (`[]` removes them).
+    pub automation_blackouts: Option<Vec<BlackoutWindow>>,
+    /// Language of agent-facing metadata for the workspace's missions
+    /// (empty follows the user default).
+    pub locale: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -137,6 +168,16 @@ pub struct WorkspaceResponse {
     pub tailscale_mode: Option<TailscaleMode>,
     pub mcps: Vec<String>,
     pub config_profile: Option<String>,
+    pub env_profile: Option<String>,
+    pub kubernetes: Option<KubernetesAccess>,
+    pub ssh: Option<SshAccess>,
+    pub egress: Option<EgressPolicy>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub automation_blackouts: Vec<BlackoutWindow>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
 }
 
 impl From<Workspace> for WorkspaceResponse {
@@ -160,6 +201,13 @@ impl From<Workspace> for WorkspaceResponse {
             tailscale_mode: w.tailscale_mode,
             mcps: w.mcps,
             config_profile: w.config_profile,
+            env_profile: w.env_profile,
+            kubernetes: w.kubernetes,
+            ssh: w.ssh,
+            egress: w.egress,
+            automation_blackouts: w.automation_blackouts,
+            locale: w.locale,
+            tenant: w.tenant,
         }
     }
 }
@@ -172,6 +220,64 @@ async fn clone_library(
     guard.as_ref().map(Arc::clone)
 }
 
+/// Clean up Kubernetes access settings from a request. An empty `secret`
+/// removes access; otherwise at least one namespace must be allowlisted.
+fn normalize_kubernetes_access(
+    access: Option<KubernetesAccess>,
+) -> Result<Option<KubernetesAccess>, (StatusCode, String)> {
+    let Some(mut access) = access else {
+        return Ok(None);
+    };
+    access.secret = access.secret.trim().to_string();
+    if access.secret.is_empty() {
+        return Ok(None);
+    }
+    access.context = access
+        .context
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty());
+    let mut namespaces: Vec<String> = Vec::new();
+    for ns in access.namespaces.iter().map(|ns| ns.trim()) {
+        if !ns.is_empty() && !namespaces.iter().any(|n| n == ns) {
+            namespaces.push(ns.to_string());
+        }
+    }
+    if namespaces.is_empty() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Kubernetes access needs at least one allowlisted namespace".to_string(),
+        ));
+    }
+    access.namespaces = namespaces;
+    Ok(Some(access))
+}
+
+/// Validate an SSH host allowlist from a request. An empty host list removes
+/// SSH access.
+fn normalize_ssh_access(
+    access: Option<SshAccess>,
+) -> Result<Option<SshAccess>, (StatusCode, String)> {
+    match access {
+        Some(access) => access
+            .normalized()
+            .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string())),
+        None => Ok(None),
+    }
+}
+
+/// Validate an egress policy from a request. A policy with no allowlist
+/// entries and no block options removes it.
+fn normalize_egress_policy(
+    policy: Option<EgressPolicy>,
+) -> Result<Option<EgressPolicy>, (StatusCode, String)> {
+    match policy {
+        Some(policy) => policy
+            .normalized()
+            .map_err(|e| (StatusCode::BAD_REQUEST, e.to_string())),
+        None => Ok(None),
+    }
+}
+
 /// Apply build-related fields from a completed build back to the latest stored
 /// workspace, preserving any updates (env vars, init script, etc.) that arrived
 /// while the build was running.
@@ -186,14 +292,17 @@ async fn merge_build_result(store: &workspace::WorkspaceStore, built: Workspace)
     }
 }
 
-/// Look up a workspace by ID, returning 404 if it does not exist.
-async fn require_workspace(
+/// Look up a workspace by ID, returning 404 if it does not exist or belongs to
+/// another tenant.
+pub(super) async fn require_workspace(
     store: &workspace::WorkspaceStore,
     id: Uuid,
+    tenant: Option<&str>,
 ) -> Result<Workspace, (StatusCode, String)> {
     store
         .get(id)
         .await
+        .filter(|w| w.tenant.as_deref() == tenant)
         .ok_or_else(|| (StatusCode::NOT_FOUND, format!("Workspace {} not found", id)))
 }
 
@@ -204,9 +313,14 @@ async fn require_workspace(
 /// GET /api/workspaces - List all workspaces.
 async fn list_workspaces(
     State(state): State<Arc<super::routes::AppState>>,
+    Extension(user): Extension<AuthUser>,
 ) -> Result<Json<Vec<WorkspaceResponse>>, (StatusCode, String)> {
     let workspaces = state.workspaces.list().await;
-    let responses: Vec<WorkspaceResponse> = workspaces.into_iter().map(Into::into).collect();
+    let responses: Vec<WorkspaceResponse> = workspaces
+        .into_iter()
+        .filter(|w| w.tenant == user.tenant)
+        .map(Into::into)
+        .collect();
     Ok(Json(responses))
 }
 
@@ -294,11 +408,44 @@ fn path_within(base: &Path, target: &Path) -> bool {
 /// POST /api/workspaces - Create a new workspace.
 async fn create_workspace(
     State(state): State<Arc<super::routes::AppState>>,
+    Extension(user): Extension<AuthUser>,
     Json(req): Json<CreateWorkspaceRequest>,
 ) -> Result<Json<WorkspaceResponse>, (StatusCode, String)> {
     // Validate workspace name for path traversal
     validate_workspace_name(&req.name)?;
 
+    let tenant = user
+        .tenant
+        .as_deref()
+        .and_then(|id| state.config.find_tenant(id));
+    if let Some(limit) = tenant.and_then(|t| t.max_workspaces) {
+        let owned = state
+            .workspaces
+            .list()
+            .await
+            .into_iter()
+            .filter(|w| w.tenant == user.tenant)
+            .count();
+        if owned >= limit {
+            return Err((
+                StatusCode::FORBIDDEN,
+                format!("Tenant workspace quota reached ({} workspaces)", limit),
+            ));
+        }
+    }
+    // Tenant workspaces live under the tenant's directory.
+    let workspace_root = match tenant {
+        Some(tenant) => {
+            let dir = tenant.data_dir(&state.config.working_dir);
+            tokio::fs::create_dir_all(&dir)
+                .await
+                .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e.to_string()))?;
+            dir
+        }
+        None => state.config.working_dir.clone(),
+    };
+    let library_slot = super::library::library_for_tenant(&state, user.tenant.as_deref()).await;
+
     let mut workspace_type = req.workspace_type;
     let mut template_data: Option<WorkspaceTemplate> = None;
 
@@ -306,7 +453,7 @@ async fn create_workspace(
         // Templates always require an isolated (container) workspace
         workspace_type = WorkspaceType::Container;
 
-        let library = clone_library(&state.library).await.ok_or_else(|| {
+        let library = clone_library(&library_slot).await.ok_or_else(|| {
             (
                 StatusCode::SERVICE_UNAVAILABLE,
                 "Library not initialized".to_string(),
@@ -332,7 +479,7 @@ async fn create_workspace(
 
     // Determine path
     let path = match &req.path {
-        Some(custom_path) => resolve_custom_path(&state.config.working_dir, custom_path)?,
+        Some(custom_path) => resolve_custom_path(&workspace_root, custom_path)?,
         None => match workspace_type {
             WorkspaceType::Host => {
                 // This should be unreachable due to the check above, but keeping for safety
@@ -343,11 +490,12 @@ async fn create_workspace(
             }
             WorkspaceType::Container => {
                 // Container workspaces go in a dedicated directory
-                state
-                    .config
-                    .working_dir
-                    .join(".sandboxed-sh/containers")
-                    .join(&req.name)
+                match tenant {
+                    Some(_) => workspace_root.join("containers").join(&req.name),
+                    None => workspace_root
+                        .join(".sandboxed-sh/containers")
+                        .join(&req.name),
+                }
             }
         },
     };
@@ -424,6 +572,17 @@ async fn create_workspace(
         }
     }
 
+    let env_profile = req
+        .env_profile
+        .as_deref()
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string);
+
+    let kubernetes = normalize_kubernetes_access(req.kubernetes)?;
+    let ssh = normalize_ssh_access(req.ssh)?;
+    let egress = normalize_egress_policy(req.egress)?;
+
     let mut workspace = match workspace_type {
         WorkspaceType::Host => Workspace {
             id: Uuid::new_v4(),
@@ -445,6 +604,13 @@ async fn create_workspace(
             tailscale_mode,
             mcps: mcps.clone(),
             config_profile: config_profile.clone(),
+            env_profile: env_profile.clone(),
+            kubernetes: kubernetes.clone(),
+            ssh: ssh.clone(),
+            egress: egress.clone(),
+            automation_blackouts: Vec::new(),
+            locale: None,
+            tenant: user.tenant.clone(),
         },
         WorkspaceType::Container => {
             let mut ws = Workspace::new_container(req.name, path);
@@ -459,6 +625,11 @@ async fn create_workspace(
             ws.tailscale_mode = tailscale_mode;
             ws.mcps = mcps;
             ws.config_profile = config_profile;
+            ws.env_profile = env_profile;
+            ws.kubernetes = kubernetes;
+            ws.ssh = ssh;
+            ws.egress = egress;
+            ws.tenant = user.tenant.clone();
             ws
         }
     };
@@ -466,7 +637,7 @@ async fn create_workspace(
     let id = state.workspaces.add(workspace.clone()).await;
 
     // Sync skills and tools to workspace if any are specified
-    let library_guard = state.library.read().await;
+    let library_guard = library_slot.read().await;
     if let Some(library) = library_guard.as_ref() {
         if !workspace.skills.is_empty() {
             if let Err(e) = workspace::sync_workspace_skills(&workspace, library).await {
@@ -505,7 +676,7 @@ async fn create_workspace(
         let working_dir = state.config.working_dir.clone();
         let mut workspace_for_build = workspace.clone();
         // Get library for init script assembly
-        let library = clone_library(&state.library).await;
+        let library = clone_library(&library_slot).await;
 
         tokio::spawn(async move {
             let result = crate::workspace::build_container_workspace(
@@ -545,9 +716,10 @@ async fn create_workspace(
 /// GET /api/workspaces/:id - Get workspace details.
 async fn get_workspace(
     State(state): State<Arc<super::routes::AppState>>,
+    Extension(user): Extension<AuthUser>,
     AxumPath(id): AxumPath<Uuid>,
 ) -> Result<Json<WorkspaceResponse>, (StatusCode, String)> {
-    require_workspace(&state.workspaces, id)
+    require_workspace(&state.workspaces, id, user.tenant.as_deref())
         .await
         .map(|w| Json(w.into()))
 }
@@ -555,10 +727,11 @@ async fn get_workspace(
 /// PUT /api/workspaces/:id - Update a workspace.
 async fn update_workspace(
     State(state): State<Arc<super::routes::AppState>>,
+    Extension(user): Extension<AuthUser>,
     AxumPath(id): AxumPath<Uuid>,
     Json(req): Json<UpdateWorkspaceRequest>,
 ) -> Result<Json<WorkspaceResponse>, (StatusCode, String)> {
-    let mut workspace = require_workspace(&state.workspaces, id).await?;
+    let mut workspace = require_workspace(&state.workspaces, id, user.tenant.as_deref()).await?;
 
     // Validate name if provided
     if let Some(ref name) = req.name {
@@ -638,11 +811,56 @@ async fn update_workspace(
         }
     }
 
+    if let Some(env_profile) = req.env_profile {
+        let trimmed = env_profile.trim();
+        if trimmed.is_empty() {
+            workspace.env_profile = None;
+        } else {
+            workspace.env_profile = Some(trimmed.to_string());
+        }
+    }
+
+    if let Some(kubernetes) = req.kubernetes {
+        workspace.kubernetes = normalize_kubernetes_access(Some(kubernetes))?;
+    }
+
+    if let Some(ssh) = req.ssh {
+        workspace.ssh = normalize_ssh_access(Some(ssh))?;
+    }
+
+    if let Some(egress) = req.egress {
+        workspace.egress = normalize_egress_policy(Some(egress))?;
+    }
+
+    if let Some(windows) = req.automation_blackouts {
+        validate_windows(&windows).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+        workspace.automation_blackouts = windows;
+    }
+
+    if let Some(locale) = req.locale {
+        let locale = crate::i18n::normalize(&locale);
+        if locale.is_empty() {
+            workspace.locale = None;
+        } else if crate::i18n::is_supported(&locale) {
+            workspace.locale = Some(locale);
+        } else {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                format!(
+                    "Unsupported locale {}; available: {}",
+                    locale,
+                    crate::i18n::available_locales().join(", ")
+                ),
+            ));
+        }
+    }
+
     // Save the updated workspace
     state.workspaces.update(workspace.clone()).await;
 
     // Sync skills and tools if they changed
-    let library_guard = state.library.read().await;
+    let library_slot = super::library::library_for_tenant(&state, user.tenant.as_deref()).await;
+    let library_guard = library_slot.read().await;
     if let Some(library) = library_guard.as_ref() {
         if skills_changed && !workspace.skills.is_empty() {
             if let Err(e) = workspace::sync_workspace_skills(&workspace, library).await {
@@ -668,12 +886,14 @@ async fn update_workspace(
 /// POST /api/workspaces/:id/sync - Manually sync skills and tools to workspace.
 async fn sync_workspace(
     State(state): State<Arc<super::routes::AppState>>,
+    Extension(user): Extension<AuthUser>,
     AxumPath(id): AxumPath<Uuid>,
 ) -> Result<Json<WorkspaceResponse>, (StatusCode, String)> {
-    let workspace = require_workspace(&state.workspaces, id).await?;
+    let workspace = require_workspace(&state.workspaces, id, user.tenant.as_deref()).await?;
 
     // Get library
-    let library_guard = state.library.read().await;
+    let library_slot = super::library::library_for_tenant(&state, user.tenant.as_deref()).await;
+    let library_guard = library_slot.read().await;
     let library = library_guard.as_ref().ok_or_else(|| {
         (
             StatusCode::SERVICE_UNAVAILABLE,
@@ -699,6 +919,7 @@ async fn sync_workspace(
 /// DELETE /api/workspaces/:id - Delete a workspace.
 async fn delete_workspace(
     State(state): State<Arc<super::routes::AppState>>,
+    Extension(user): Extension<AuthUser>,
     AxumPath(id): AxumPath<Uuid>,
 ) -> Result<(StatusCode, String), (StatusCode, String)> {
     if id == crate::workspace::DEFAULT_WORKSPACE_ID {
@@ -708,19 +929,19 @@ async fn delete_workspace(
         ));
     }
 
+    let ws = require_workspace(&state.workspaces, id, user.tenant.as_deref()).await?;
+
     // If it's a container workspace, destroy the container first
-    if let Some(ws) = state.workspaces.get(id).await {
-        if ws.workspace_type == WorkspaceType::Container {
-            if let Err(e) = crate::workspace::destroy_container_workspace(&ws).await {
-                tracing::error!("Failed to destroy container for workspace {}: {}", id, e);
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!(
-                        "Failed to destroy container: {}. Workspace not deleted to prevent orphaned state.",
-                        e
-                    ),
-                ));
-            }
+    if ws.workspace_type == WorkspaceType::Container {
+        if let Err(e) = crate::workspace::destroy_container_workspace(&ws).await {
+            tracing::error!("Failed to destroy container for workspace {}: {}", id, e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!(
+                    "Failed to destroy container: {}. Workspace not deleted to prevent orphaned state.",
+                    e
+                ),
+            ));
         }
     }
 
@@ -795,10 +1016,11 @@ fn shell_escape(s: &str) -> String {
 /// POST /api/workspaces/:id/build - Build a container workspace.
 async fn build_workspace(
     State(state): State<Arc<super::routes::AppState>>,
+    Extension(user): Extension<AuthUser>,
     AxumPath(id): AxumPath<Uuid>,
     body: Option<Json<BuildWorkspaceRequest>>,
 ) -> Result<Json<WorkspaceResponse>, (StatusCode, String)> {
-    let mut workspace = require_workspace(&state.workspaces, id).await?;
+    let mut workspace = require_workspace(&state.workspaces, id, user.tenant.as_deref()).await?;
 
     if workspace.workspace_type != WorkspaceType::Container {
         return Err((
@@ -837,7 +1059,8 @@ async fn build_workspace(
     }
 
     // Get library for init script assembly - require it when workspace has fragments
-    let library = clone_library(&state.library).await;
+    let library_slot = super::library::library_for_tenant(&state, user.tenant.as_deref()).await;
+    let library = clone_library(&library_slot).await;
     if library.is_none() && !workspace.init_scripts.is_empty() {
         return Err((
             StatusCode::SERVICE_UNAVAILABLE,
@@ -979,6 +1202,7 @@ pub struct RerunInitResponse {
 /// POST /api/workspaces/:id/exec - Execute a command in a workspace.
 async fn exec_workspace_command(
     State(state): State<Arc<super::routes::AppState>>,
+    Extension(user): Extension<AuthUser>,
     AxumPath(id): AxumPath<Uuid>,
     Json(req): Json<ExecCommandRequest>,
 ) -> Result<Json<ExecCommandResponse>, (StatusCode, String)> {
@@ -987,7 +1211,7 @@ async fn exec_workspace_command(
     use tokio::io::AsyncWriteExt;
     use tokio::process::Command;
 
-    let workspace = require_workspace(&state.workspaces, id).await?;
+    let workspace = require_workspace(&state.workspaces, id, user.tenant.as_deref()).await?;
 
     // For container workspaces, ensure container is ready
     if workspace.workspace_type == WorkspaceType::Container
@@ -1231,9 +1455,10 @@ async fn exec_workspace_command(
 /// init script issues: directory structure, file existence, sizes, etc.
 async fn get_workspace_debug(
     State(state): State<Arc<super::routes::AppState>>,
+    Extension(user): Extension<AuthUser>,
     AxumPath(id): AxumPath<Uuid>,
 ) -> Result<Json<WorkspaceDebugInfo>, (StatusCode, String)> {
-    let workspace = require_workspace(&state.workspaces, id).await?;
+    let workspace = require_workspace(&state.workspaces, id, user.tenant.as_deref()).await?;
 
     let path = &workspace.path;
     let path_exists = path.exists();
@@ -1319,9 +1544,10 @@ async fn get_workspace_debug(
 /// the init script has logged. Useful for debugging template issues.
 async fn get_init_log(
     State(state): State<Arc<super::routes::AppState>>,
+    Extension(user): Extension<AuthUser>,
     AxumPath(id): AxumPath<Uuid>,
 ) -> Result<Json<InitLogResponse>, (StatusCode, String)> {
-    let workspace = require_workspace(&state.workspaces, id).await?;
+    let workspace = require_workspace(&state.workspaces, id, user.tenant.as_deref()).await?;
 
     let log_path = "/var/log/sandboxed-init.log";
     let host_log_path = workspace.path.join("var/log/sandboxed-init.log");
@@ -1380,9 +1606,10 @@ async fn get_init_log(
 /// waiting for a full container rebuild. The container must already exist.
 async fn rerun_init_script(
     State(state): State<Arc<super::routes::AppState>>,
+    Extension(user): Extension<AuthUser>,
     AxumPath(id): AxumPath<Uuid>,
 ) -> Result<Json<RerunInitResponse>, (StatusCode, String)> {
-    let mut workspace = require_workspace(&state.workspaces, id).await?;
+    let mut workspace = require_workspace(&state.workspaces, id, user.tenant.as_deref()).await?;
 
     // Only works for container workspaces
     if workspace.workspace_type != WorkspaceType::Container {
@@ -1539,8 +1766,9 @@ pub struct WorkspaceMemoryStats {
 async fn get_workspace_memory(
     AxumPath(id): AxumPath<Uuid>,
     State(state): State<Arc<super::routes::AppState>>,
+    Extension(user): Extension<AuthUser>,
 ) -> Result<Json<WorkspaceMemoryStats>, (StatusCode, String)> {
-    let workspace = require_workspace(&state.workspaces, id).await?;
+    let workspace = require_workspace(&state.workspaces, id, user.tenant.as_deref()).await?;
 
     let stats = get_container_memory_stats(&workspace).await;
     Ok(Json(stats))
@@ -1549,11 +1777,12 @@ async fn get_workspace_memory(
 /// Get memory statistics for all workspace containers.
 async fn get_all_workspaces_memory(
     State(state): State<Arc<super::routes::AppState>>,
+    Extension(user): Extension<AuthUser>,
 ) -> Json<Vec<WorkspaceMemoryStats>> {
     let workspaces = state.workspaces.list().await;
     let mut all_stats = Vec::new();
 
-    for workspace in workspaces {
+    for workspace in workspaces.into_iter().filter(|w| w.tenant == user.tenant) {
         let stats = get_container_memory_stats(&workspace).await;
         all_stats.push(stats);
     }
@@ -1709,8 +1938,10 @@ fn parse_systemd_memory_stats(stdout: &str) -> MemoryStats {
 async fn check_backend_preflight(
     AxumPath((workspace_id, backend_id)): AxumPath<(Uuid, String)>,
     State(state): State<Arc<super::routes::AppState>>,
+    Extension(user): Extension<AuthUser>,
 ) -> Result<Json<super::mission_runner::BackendPreflightResult>, (StatusCode, String)> {
-    let workspace = require_workspace(&state.workspaces, workspace_id).await?;
+    let workspace =
+        require_workspace(&state.workspaces, workspace_id, user.tenant.as_deref()).await?;
 
     let cli_path = if backend_id == "claudecode" || backend_id == "codex" || backend_id == "amp" {
         state
@@ -1724,6 +1955,8 @@ async fn check_backend_preflight(
                     .and_then(|v| v.as_str())
                     .map(|s| s.to_string())
             })
+    } else if backend_id == "ollama" {
+        Some(state.config.ollama_base_url.clone())
     } else {
         None
     };
@@ -1767,6 +2000,24 @@ mod tests {
         assert!(!path_within(base, Path::new("subdir/../../etc")));
     }
 
+    #[tokio::test]
+    async fn workspaces_of_other_tenants_are_not_found() {
+        let temp_dir = TempDir::new().unwrap();
+        let store = workspace::WorkspaceStore::new(temp_dir.path().to_path_buf()).await;
+        let mut ws = Workspace::new_container("a-box".to_string(), temp_dir.path().join("a-box"));
+        ws.tenant = Some("a".to_string());
+        let id = store.add(ws).await;
+
+        assert!(require_workspace(&store, id, Some("a")).await.is_ok());
+        let err = require_workspace(&store, id, Some("b")).await.unwrap_err();
+        assert_eq!(err.0, StatusCode::NOT_FOUND);
+        assert!(require_workspace(&store, id, None).await.is_err());
+        // The default host workspace belongs to the instance
+        let host = crate::workspace::DEFAULT_WORKSPACE_ID;
+        assert!(require_workspace(&store, host, Some("a")).await.is_err());
+        assert!(require_workspace(&store, host, None).await.is_ok());
+    }
+
     #[test]
     fn test_path_within_allows_valid_subpaths() {
         let temp_dir = TempDir::new().unwrap();
@@ -1782,6 +2033,7 @@ mod tests {
         assert!(path_within(base, &base.join("newdir/newfile.txt")));
     }
 
+    #[cfg(unix)]
     #[test]
     fn test_path_within_rejects_symlink_escape() {
         let temp_dir = TempDir::new().unwrap();
@@ -1794,12 +2046,9 @@ mod tests {
         let outside = TempDir::new().unwrap();
         let symlink_path = base.join("escape");
 
-        #[cfg(unix)]
-        {
-            std::os::unix::fs::symlink(outside.path(), &symlink_path).unwrap();
-            // A path through the symlink should be rejected
-            assert!(!path_within(base, &symlink_path.join("file.txt")));
-        }
+        std::os::unix::fs::symlink(outside.path(), &symlink_path).unwrap();
+        // A path through the symlink should be rejected
+        assert!(!path_within(base, &symlink_path.join("file.txt")));
     }
 
     #[test]
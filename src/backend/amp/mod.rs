@@ -10,7 +10,7 @@ use tracing::debug;
 
 use crate::backend::events::ExecutionEvent;
 use crate::backend::shared::convert_cli_event;
-use crate::backend::{AgentInfo, Backend, Session, SessionConfig};
+use crate::backend::{AgentInfo, Backend, BackendCapabilities, Session, SessionConfig};
 
 use client::{AmpClient, AmpConfig};
 
@@ -66,6 +66,14 @@ impl Backend for AmpBackend {
         &self.name
     }
 
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities {
+            // Amp picks its model from the mode (smart/rush)
+            session_resume: true,
+            ..BackendCapabilities::default()
+        }
+    }
+
     async fn list_agents(&self) -> Result<Vec<AgentInfo>, Error> {
         // Amp has built-in modes rather than agents
         Ok(vec![
@@ -10,7 +10,7 @@ use tracing::{debug, warn};
 
 use crate::backend::events::ExecutionEvent;
 use crate::backend::shared::convert_cli_event;
-use crate::backend::{AgentInfo, Backend, Session, SessionConfig};
+use crate::backend::{AgentInfo, Backend, BackendCapabilities, Session, SessionConfig};
 
 use client::{ClaudeCodeClient, ClaudeCodeConfig};
 
@@ -66,6 +66,15 @@ impl Backend for ClaudeCodeBackend {
         &self.name
     }
 
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities {
+            model_override: true,
+            session_resume: true,
+            config_profiles: true,
+            ..BackendCapabilities::default()
+        }
+    }
+
     async fn list_agents(&self) -> Result<Vec<AgentInfo>, Error> {
         // Claude Code has built-in agents
         Ok(vec![
@@ -8,7 +8,8 @@ use tokio::task::JoinHandle;
 use tracing::debug;
 
 use crate::backend::events::ExecutionEvent;
-use crate::backend::{AgentInfo, Backend, Session, SessionConfig};
+use crate::backend::{AgentInfo, Backend, BackendCapabilities, Session, SessionConfig};
+use crate::tools::OutputStream;
 
 use client::{CodexClient, CodexConfig, CodexEvent};
 
@@ -79,6 +80,14 @@ impl Backend for CodexBackend {
         &self.name
     }
 
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities {
+            effort_levels: ["low", "medium", "high"].map(String::from).to_vec(),
+            model_override: true,
+            ..BackendCapabilities::default()
+        }
+    }
+
     async fn list_agents(&self) -> Result<Vec<AgentInfo>, Error> {
         // Codex doesn't have separate agent types like Claude Code
         // Return a single general-purpose agent
@@ -127,13 +136,27 @@ impl Backend for CodexBackend {
             let mut item_content_cache: std::collections::HashMap<String, String> =
                 std::collections::HashMap::new();
 
-            'outer: while let Some(event) = codex_rx.recv().await {
+            loop {
+                let event = tokio::select! {
+                    event = codex_rx.recv() => match event {
+                        Some(event) => event,
+                        None => break,
+                    },
+                    // The turn was cancelled: stop Codex and its tool processes
+                    // instead of letting them run until the next event.
+                    _ = tx.closed() => {
+                        debug!("ExecutionEvent receiver dropped; stopping Codex");
+                        codex_handle.kill().await;
+                        return;
+                    }
+                };
                 let exec_events = convert_codex_event(event, &mut item_content_cache);
 
                 for exec_event in exec_events {
                     if tx.send(exec_event).await.is_err() {
                         debug!("ExecutionEvent receiver dropped");
-                        break 'outer;
+                        codex_handle.kill().await;
+                        return;
                     }
                 }
             }
@@ -206,6 +229,33 @@ fn convert_codex_event(
         }
     }
 
+    /// Emit the part of a running command's output snapshot not sent yet.
+    fn emit_output_delta(
+        results: &mut Vec<ExecutionEvent>,
+        item_content_cache: &mut std::collections::HashMap<String, String>,
+        item_id: &str,
+        name: &str,
+        output: &str,
+    ) {
+        let key = format!("output:{}", item_id);
+        let sent = item_content_cache
+            .get(&key)
+            .map(|v| v.as_str())
+            .unwrap_or("");
+        // A snapshot that does not extend what was sent (e.g. it was
+        // truncated) is streamed again in full.
+        let delta = output.strip_prefix(sent).unwrap_or(output);
+        if !delta.is_empty() {
+            results.push(ExecutionEvent::ToolOutputDelta {
+                id: item_id.to_string(),
+                name: name.to_string(),
+                stream: OutputStream::Combined,
+                content: delta.to_string(),
+            });
+        }
+        item_content_cache.insert(key, output.to_string());
+    }
+
     let mut results = vec![];
 
     fn mcp_tool_name(
@@ -446,15 +496,33 @@ fn convert_codex_event(
                     }
                 }
                 "command" | "tool" | "tool_call" | "function_call" => {
-                    // Extract tool/command execution
+                    // Extract tool/command execution. Updates of a running
+                    // command carry its output so far, streamed as deltas.
                     if let Some(name) = tool_name(&item.data) {
-                        let args = tool_args(&item.data)
-                            .unwrap_or_else(|| serde_json::Value::Object(serde_json::Map::new()));
-                        results.push(ExecutionEvent::ToolCall {
-                            id: item.id.clone(),
-                            name,
-                            args,
-                        });
+                        if !mark_tool_call_emitted(item_content_cache, &item.id) {
+                            let args = tool_args(&item.data).unwrap_or_else(|| {
+                                serde_json::Value::Object(serde_json::Map::new())
+                            });
+                            results.push(ExecutionEvent::ToolCall {
+                                id: item.id.clone(),
+                                name: name.clone(),
+                                args,
+                            });
+                        }
+                        let output = item
+                            .data
+                            .get("aggregated_output")
+                            .or_else(|| item.data.get("output"))
+                            .and_then(|v| v.as_str());
+                        if let Some(output) = output {
+                            emit_output_delta(
+                                &mut results,
+                                item_content_cache,
+                                &item.id,
+                                &name,
+                                output,
+                            );
+                        }
                     }
                 }
                 "mcp_tool_call" => {
@@ -1069,6 +1137,47 @@ mod tests {
         }
     }
 
+    #[test]
+    fn convert_codex_event_command_updates_stream_output() {
+        let update = |output: &str| -> CodexEvent {
+            serde_json::from_value(json!({
+                "type": "item.updated",
+                "item": {
+                    "id": "cmd_build",
+                    "type": "command",
+                    "command": "cargo build",
+                    "aggregated_output": output
+                }
+            }))
+            .unwrap()
+        };
+        let mut cache = HashMap::new();
+        let outputs = |events: Vec<ExecutionEvent>| -> Vec<String> {
+            events
+                .into_iter()
+                .map(|event| match event {
+                    ExecutionEvent::ToolCall { name, .. } => format!("call:{}", name),
+                    ExecutionEvent::ToolOutputDelta { content, .. } => content,
+                    other => panic!("unexpected {:?}", other),
+                })
+                .collect()
+        };
+
+        assert_eq!(
+            outputs(convert_codex_event(update("Compiling a\n"), &mut cache)),
+            vec!["call:cargo build", "Compiling a\n"]
+        );
+        // The call is reported once; later snapshots only stream new output.
+        assert_eq!(
+            outputs(convert_codex_event(
+                update("Compiling a\nCompiling b\n"),
+                &mut cache
+            )),
+            vec!["Compiling b\n"]
+        );
+        assert!(convert_codex_event(update("Compiling a\nCompiling b\n"), &mut cache).is_empty());
+    }
+
     #[test]
     fn convert_codex_event_text_snapshot_updates() {
         // Two ItemUpdated events where the second text extends the first.
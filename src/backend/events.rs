@@ -1,5 +1,7 @@
 use serde_json::Value;
 
+use crate::tools::OutputStream;
+
 /// Backend-agnostic execution events.
 #[derive(Debug, Clone)]
 pub enum ExecutionEvent {
@@ -11,6 +13,13 @@ pub enum ExecutionEvent {
         name: String,
         args: Value,
     },
+    /// Partial output from a tool that is still running.
+    ToolOutputDelta {
+        id: String,
+        name: String,
+        stream: OutputStream,
+        content: String,
+    },
     /// Tool execution completed.
     ToolResult {
         id: String,
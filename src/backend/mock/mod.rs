@@ -0,0 +1,399 @@
+//! Deterministic mock LLM backend.
+//!
+//! `MockLlm` replays scripted responses instead of calling a model, so the
+//! control actor and mission lifecycle can be exercised in CI without API keys
+//! and the dashboard can be demoed offline. Enable it with `MOCK_LLM=true` (or
+//! `DEFAULT_BACKEND=mock`) and optionally point `MOCK_LLM_SCRIPT` at a JSON
+//! [`MockScript`].
+//!
+//! A turn's response is picked in this order:
+//! 1. `prompts[prompt_hash(user message)]`
+//! 2. `sequence[n]` for the mission's n-th turn (0-based)
+//! 3. `fallback`, or an echo of the message
+
+use anyhow::Error;
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+use crate::backend::events::ExecutionEvent;
+use crate::backend::{AgentInfo, Backend, Session, SessionConfig};
+use crate::tools::OutputStream;
+
+/// Longest message excerpt echoed by the default response.
+const ECHO_LIMIT: usize = 200;
+
+/// A scripted tool call and the result reported for it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MockToolCall {
+    pub name: String,
+    #[serde(default)]
+    pub args: Value,
+    /// Stdout chunks streamed while the call runs
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub output: Vec<String>,
+    #[serde(default)]
+    pub result: Value,
+}
+
+/// One scripted assistant turn.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MockResponse {
+    /// Final assistant message
+    #[serde(default)]
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thinking: Option<String>,
+    /// Tool calls emitted (with results) before the final message
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<MockToolCall>,
+    /// Fail the turn with this error instead of answering
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(default)]
+    pub input_tokens: u64,
+    #[serde(default)]
+    pub output_tokens: u64,
+}
+
+impl MockResponse {
+    pub fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            error: Some(message.into()),
+            ..Default::default()
+        }
+    }
+
+    pub fn with_thinking(mut self, thinking: impl Into<String>) -> Self {
+        self.thinking = Some(thinking.into());
+        self
+    }
+
+    pub fn with_tool_call(mut self, name: impl Into<String>, args: Value, result: Value) -> Self {
+        self.tool_calls.push(MockToolCall {
+            name: name.into(),
+            args,
+            output: Vec::new(),
+            result,
+        });
+        self
+    }
+
+    /// Stream `chunks` as output of the most recently added tool call.
+    pub fn with_tool_output<S: Into<String>>(
+        mut self,
+        chunks: impl IntoIterator<Item = S>,
+    ) -> Self {
+        if let Some(call) = self.tool_calls.last_mut() {
+            call.output.extend(chunks.into_iter().map(Into::into));
+        }
+        self
+    }
+
+    pub fn with_usage(mut self, input_tokens: u64, output_tokens: u64) -> Self {
+        self.input_tokens = input_tokens;
+        self.output_tokens = output_tokens;
+        self
+    }
+
+    /// Execution events for this response, ending with `MessageComplete`.
+    /// Tool call ids are derived from the session and position, so replays
+    /// are identical.
+    pub fn events(&self, session_id: &str) -> Vec<ExecutionEvent> {
+        let mut events = Vec::new();
+        if let Some(thinking) = &self.thinking {
+            events.push(ExecutionEvent::Thinking {
+                content: thinking.clone(),
+            });
+        }
+        for (index, call) in self.tool_calls.iter().enumerate() {
+            let id = format!("mock-{}-{}", session_id, index);
+            events.push(ExecutionEvent::ToolCall {
+                id: id.clone(),
+                name: call.name.clone(),
+                args: call.args.clone(),
+            });
+            for chunk in &call.output {
+                events.push(ExecutionEvent::ToolOutputDelta {
+                    id: id.clone(),
+                    name: call.name.clone(),
+                    stream: OutputStream::Stdout,
+                    content: chunk.clone(),
+                });
+            }
+            events.push(ExecutionEvent::ToolResult {
+                id,
+                name: call.name.clone(),
+                result: call.result.clone(),
+            });
+        }
+        match &self.error {
+            Some(message) => events.push(ExecutionEvent::Error {
+                message: message.clone(),
+            }),
+            None => events.push(ExecutionEvent::TextDelta {
+                content: self.text.clone(),
+            }),
+        }
+        if self.input_tokens > 0 || self.output_tokens > 0 {
+            events.push(ExecutionEvent::Usage {
+                input_tokens: self.input_tokens,
+                output_tokens: self.output_tokens,
+            });
+        }
+        events.push(ExecutionEvent::MessageComplete {
+            session_id: session_id.to_string(),
+        });
+        events
+    }
+}
+
+/// Scripted responses for the mock backend.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MockScript {
+    /// Responses keyed by [`prompt_hash`] of the user message
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub prompts: HashMap<String, MockResponse>,
+    /// Responses for successive turns of a mission
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sequence: Vec<MockResponse>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<MockResponse>,
+}
+
+/// Stable key for a prompt: hex SHA-256 of the trimmed text.
+pub fn prompt_hash(prompt: &str) -> String {
+    hex::encode(Sha256::digest(prompt.trim().as_bytes()))
+}
+
+impl MockScript {
+    /// Script answering each turn with the next response in order.
+    pub fn sequence(responses: impl IntoIterator<Item = MockResponse>) -> Self {
+        Self {
+            sequence: responses.into_iter().collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Answer `prompt` with `response` regardless of turn order.
+    pub fn with_prompt(mut self, prompt: &str, response: MockResponse) -> Self {
+        self.prompts.insert(prompt_hash(prompt), response);
+        self
+    }
+
+    pub fn with_fallback(mut self, response: MockResponse) -> Self {
+        self.fallback = Some(response);
+        self
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let content = std::fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Write the script as JSON (for fixtures pointed to by `MOCK_LLM_SCRIPT`).
+    pub fn write_to(&self, path: &Path) -> Result<(), Error> {
+        std::fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// The response for the `turn`-th message (0-based) of a mission.
+    pub fn respond(&self, prompt: &str, turn: usize) -> MockResponse {
+        if let Some(response) = self.prompts.get(&prompt_hash(prompt)) {
+            return response.clone();
+        }
+        if let Some(response) = self.sequence.get(turn) {
+            return response.clone();
+        }
+        self.fallback.clone().unwrap_or_else(|| {
+            let prompt = prompt.trim();
+            let excerpt: String = prompt.chars().take(ECHO_LIMIT).collect();
+            MockResponse::text(format!("Mock response to: {}", excerpt))
+        })
+    }
+}
+
+/// Backend that replays a [`MockScript`].
+pub struct MockLlmBackend {
+    id: String,
+    name: String,
+    script: Arc<MockScript>,
+    /// Turns sent per session, for sequence lookups
+    turns: Mutex<HashMap<String, usize>>,
+}
+
+impl MockLlmBackend {
+    pub fn new(script: MockScript) -> Self {
+        Self {
+            id: "mock".to_string(),
+            name: "Mock LLM".to_string(),
+            script: Arc::new(script),
+            turns: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl Backend for MockLlmBackend {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    async fn list_agents(&self) -> Result<Vec<AgentInfo>, Error> {
+        Ok(Vec::new())
+    }
+
+    async fn create_session(&self, config: SessionConfig) -> Result<Session, Error> {
+        Ok(Session {
+            id: uuid::Uuid::new_v4().to_string(),
+            directory: config.directory,
+            model: config.model,
+            agent: config.agent,
+        })
+    }
+
+    async fn send_message_streaming(
+        &self,
+        session: &Session,
+        message: &str,
+    ) -> Result<(mpsc::Receiver<ExecutionEvent>, JoinHandle<()>), Error> {
+        let turn = {
+            let mut turns = self.turns.lock().unwrap_or_else(|e| e.into_inner());
+            let turn = turns.entry(session.id.clone()).or_insert(0);
+            *turn += 1;
+            *turn - 1
+        };
+        let events = self.script.respond(message, turn).events(&session.id);
+
+        let (tx, rx) = mpsc::channel(events.len().max(1));
+        let handle = tokio::spawn(async move {
+            for event in events {
+                if tx.send(event).await.is_err() {
+                    break;
+                }
+            }
+        });
+        Ok((rx, handle))
+    }
+}
+
+/// Mock backend with the script from `script_path`, or echo responses.
+pub fn registry_entry(script_path: Option<&Path>) -> Arc<dyn Backend> {
+    let script = match script_path {
+        Some(path) => MockScript::load(path).unwrap_or_else(|e| {
+            tracing::warn!("Failed to load mock LLM script {}: {}", path.display(), e);
+            MockScript::default()
+        }),
+        None => MockScript::default(),
+    };
+    Arc::new(MockLlmBackend::new(script))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn run_turn(backend: &MockLlmBackend, session: &Session, message: &str) -> Vec<String> {
+        let (mut rx, handle) = backend
+            .send_message_streaming(session, message)
+            .await
+            .unwrap();
+        let mut seen = Vec::new();
+        while let Some(event) = rx.recv().await {
+            seen.push(match event {
+                ExecutionEvent::Thinking { content } => format!("thinking:{}", content),
+                ExecutionEvent::ToolCall { name, .. } => format!("call:{}", name),
+                ExecutionEvent::ToolOutputDelta { content, .. } => format!("output:{}", content),
+                ExecutionEvent::ToolResult { result, .. } => format!("result:{}", result),
+                ExecutionEvent::TextDelta { content } => format!("text:{}", content),
+                ExecutionEvent::Error { message } => format!("error:{}", message),
+                ExecutionEvent::Usage { .. } => "usage".to_string(),
+                ExecutionEvent::TurnSummary { .. } => "summary".to_string(),
+                ExecutionEvent::MessageComplete { .. } => "complete".to_string(),
+            });
+        }
+        handle.await.unwrap();
+        seen
+    }
+
+    #[tokio::test]
+    async fn replays_prompts_then_sequence_then_fallback() {
+        let script = MockScript::sequence([
+            MockResponse::text("first")
+                .with_thinking("planning")
+                .with_tool_call("bash", serde_json::json!({"command": "ls"}), "a.txt".into())
+                .with_tool_output(["a.", "txt"]),
+            MockResponse::error("overloaded"),
+        ])
+        .with_prompt("status?", MockResponse::text("all good"))
+        .with_fallback(MockResponse::text("done"));
+        let backend = MockLlmBackend::new(script);
+        let session = backend
+            .create_session(SessionConfig {
+                directory: "/tmp".to_string(),
+                title: None,
+                model: None,
+                agent: None,
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(
+            run_turn(&backend, &session, "build it").await,
+            vec![
+                "thinking:planning",
+                "call:bash",
+                "output:a.",
+                "output:txt",
+                "result:\"a.txt\"",
+                "text:first",
+                "complete"
+            ]
+        );
+        assert_eq!(
+            run_turn(&backend, &session, "deploy").await,
+            vec!["error:overloaded", "complete"]
+        );
+        // Prompt matches win regardless of position, but still count as a turn.
+        assert_eq!(
+            run_turn(&backend, &session, "  status?\n").await,
+            vec!["text:all good", "complete"]
+        );
+        assert_eq!(
+            run_turn(&backend, &session, "next").await,
+            vec!["text:done", "complete"]
+        );
+    }
+
+    #[test]
+    fn scripts_round_trip_through_json() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("script.json");
+        let script = MockScript::sequence([MockResponse::text("hi").with_usage(10, 2)])
+            .with_prompt("ping", MockResponse::text("pong"));
+        script.write_to(&path).unwrap();
+        assert_eq!(MockScript::load(&path).unwrap(), script);
+
+        assert_eq!(
+            MockScript::default().respond("hello there", 0).text,
+            "Mock response to: hello there"
+        );
+    }
+}
@@ -2,12 +2,15 @@ pub mod amp;
 pub mod claudecode;
 pub mod codex;
 pub mod events;
+pub mod mock;
+pub mod ollama;
 pub mod opencode;
 pub mod registry;
 pub mod shared;
 
 use anyhow::Error;
 use async_trait::async_trait;
+use serde::Serialize;
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 
@@ -35,10 +38,31 @@ pub struct Session {
     pub agent: Option<String>,
 }
 
+/// What a backend supports. Clients and mission validation read this instead
+/// of special-casing backend ids.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BackendCapabilities {
+    /// Accepted `model_effort` values (empty when effort is not supported)
+    pub effort_levels: Vec<String>,
+    /// Model overrides are honoured
+    pub model_override: bool,
+    /// Model overrides are `provider/model` rather than raw model ids
+    pub provider_model_ids: bool,
+    /// Resumes its own session across turns instead of being sent the history
+    pub session_resume: bool,
+    /// Agents come from the library and are validated before a mission starts
+    pub library_agents: bool,
+    /// Library config profiles apply to this backend
+    pub config_profiles: bool,
+}
+
 #[async_trait]
 pub trait Backend: Send + Sync {
     fn id(&self) -> &str;
     fn name(&self) -> &str;
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities::default()
+    }
     async fn list_agents(&self) -> Result<Vec<AgentInfo>, Error>;
     async fn create_session(&self, config: SessionConfig) -> Result<Session, Error>;
     async fn send_message_streaming(
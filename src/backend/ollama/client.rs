@@ -0,0 +1,214 @@
+//! HTTP client for the Ollama daemon (`/api/tags`, `/api/show`, `/api/chat`).
+
+use anyhow::{anyhow, Error};
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Timeout for metadata requests; chat requests stream without one.
+const METADATA_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A model available on the daemon.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaModel {
+    pub name: String,
+    #[serde(default)]
+    pub size: u64,
+    #[serde(default)]
+    pub details: Value,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChatMessage {
+    pub role: String,
+    #[serde(default)]
+    pub content: String,
+    /// Reasoning emitted by thinking models
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thinking: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tool_calls: Vec<ChatToolCall>,
+    /// Tool a `tool` message answers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_name: Option<String>,
+}
+
+impl ChatMessage {
+    pub fn new(role: &str, content: impl Into<String>) -> Self {
+        Self {
+            role: role.to_string(),
+            content: content.into(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatToolCall {
+    pub function: ChatToolFunction,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChatToolFunction {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: Value,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ChatRequest {
+    pub model: String,
+    pub messages: Vec<ChatMessage>,
+    /// Function schemas (`{"type": "function", "function": {...}}`), for
+    /// models with native tool support
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tools: Vec<Value>,
+    pub stream: bool,
+}
+
+/// One line of a streamed `/api/chat` response.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ChatChunk {
+    #[serde(default)]
+    pub message: Option<ChatMessage>,
+    #[serde(default)]
+    pub done: bool,
+    #[serde(default)]
+    pub prompt_eval_count: Option<u64>,
+    #[serde(default)]
+    pub eval_count: Option<u64>,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+#[derive(Clone)]
+pub struct OllamaClient {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl OllamaClient {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub async fn list_models(&self) -> Result<Vec<OllamaModel>, Error> {
+        #[derive(Deserialize)]
+        struct Tags {
+            #[serde(default)]
+            models: Vec<OllamaModel>,
+        }
+
+        let resp = self
+            .http
+            .get(format!("{}/api/tags", self.base_url))
+            .timeout(METADATA_TIMEOUT)
+            .send()
+            .await
+            .map_err(|e| anyhow!("Ollama daemon unreachable at {}: {}", self.base_url, e))?;
+        if !resp.status().is_success() {
+            return Err(anyhow!("Ollama returned status {}", resp.status()));
+        }
+        let mut models = resp.json::<Tags>().await?.models;
+        models.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(models)
+    }
+
+    /// Whether `model` advertises native tool calling. Daemons too old to
+    /// report capabilities are treated as not supporting tools.
+    pub async fn supports_tools(&self, model: &str) -> bool {
+        let resp = self
+            .http
+            .post(format!("{}/api/show", self.base_url))
+            .json(&serde_json::json!({ "model": model }))
+            .timeout(METADATA_TIMEOUT)
+            .send()
+            .await;
+        let Ok(resp) = resp else {
+            return false;
+        };
+        let Ok(body) = resp.json::<Value>().await else {
+            return false;
+        };
+        body.get("capabilities")
+            .and_then(|c| c.as_array())
+            .is_some_and(|caps| caps.iter().any(|c| c.as_str() == Some("tools")))
+    }
+
+    /// Start a streaming chat. Chunks arrive on the returned channel until
+    /// the `done` chunk, an error, or the receiver is dropped.
+    pub async fn chat_stream(
+        &self,
+        request: &ChatRequest,
+    ) -> Result<mpsc::Receiver<Result<ChatChunk, Error>>, Error> {
+        let resp = self
+            .http
+            .post(format!("{}/api/chat", self.base_url))
+            .json(request)
+            .send()
+            .await
+            .map_err(|e| anyhow!("Ollama daemon unreachable at {}: {}", self.base_url, e))?;
+        if !resp.status().is_success() {
+            let status = resp.status();
+            let body = resp.text().await.unwrap_or_default();
+            return Err(anyhow!(
+                "Ollama returned status {}: {}",
+                status,
+                body.trim()
+            ));
+        }
+
+        let (tx, rx) = mpsc::channel(64);
+        let mut stream = resp.bytes_stream();
+        tokio::spawn(async move {
+            let mut buffer = Vec::new();
+            while let Some(bytes) = stream.next().await {
+                let bytes = match bytes {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        let _ = tx.send(Err(e.into())).await;
+                        return;
+                    }
+                };
+                buffer.extend_from_slice(&bytes);
+                // The response is newline-delimited JSON.
+                while let Some(pos) = buffer.iter().position(|b| *b == b'\n') {
+                    let line: Vec<u8> = buffer.drain(..=pos).collect();
+                    if let Some(chunk) = parse_chunk(&line) {
+                        if tx.send(chunk).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+            if let Some(chunk) = parse_chunk(&buffer) {
+                let _ = tx.send(chunk).await;
+            }
+        });
+        Ok(rx)
+    }
+}
+
+fn parse_chunk(line: &[u8]) -> Option<Result<ChatChunk, Error>> {
+    let line = std::str::from_utf8(line).ok()?.trim();
+    if line.is_empty() {
+        return None;
+    }
+    Some(
+        serde_json::from_str::<ChatChunk>(line)
+            .map_err(|e| anyhow!("Invalid Ollama response line: {}", e))
+            .and_then(|chunk| match chunk.error {
+                Some(error) => Err(anyhow!("Ollama error: {}", error)),
+                None => Ok(chunk),
+            }),
+    )
+}
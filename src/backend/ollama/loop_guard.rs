@@ -0,0 +1,234 @@
+//! Loop detection and an adaptive iteration budget for [`super::AgentLoop`].
+//!
+//! A tool call repeated with identical arguments while nothing in the working
+//! directory changes is a loop: the model is retrying something that cannot
+//! start working. The guard answers the first detections with a reflection
+//! prompt and ends the turn if the model keeps going round.
+//!
+//! Changes to the working directory count as verifiable progress. When the
+//! iteration budget runs out shortly after such a change, the budget is
+//! extended instead of ending a turn that is still getting somewhere.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+
+use serde_json::Value;
+
+/// Identical calls without a file change that count as a loop.
+const REPEAT_THRESHOLD: usize = 3;
+/// Reflection prompts before a loop ends the turn.
+const MAX_REFLECTIONS: usize = 2;
+/// A file change this many iterations before the budget runs out earns an extension.
+const PROGRESS_WINDOW: usize = 5;
+/// The extended budget never exceeds this multiple of the configured one.
+const MAX_BUDGET_FACTOR: usize = 3;
+/// Files hashed at most when fingerprinting the working directory.
+const MAX_FINGERPRINT_FILES: usize = 10_000;
+/// Directories left out of the fingerprint: VCS metadata, build output, and
+/// our own state (saved tool outputs would otherwise look like progress).
+const SKIPPED_DIRS: &[&str] = &[".git", "node_modules", "target", ".sandboxed-sh"];
+
+/// Why the agent loop stopped without a final answer.
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum LoopStop {
+    #[error("Stopped after {0} tool-calling iterations without a final answer")]
+    IterationLimit(usize),
+    #[error("Stopped: `{0}` kept being called with the same arguments without any file changes")]
+    RepeatedToolCall(String),
+}
+
+/// What the agent loop should do after an iteration.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Verdict {
+    Continue,
+    /// Send this reflection prompt to the model before the next iteration
+    Reflect(String),
+    /// End the turn
+    Stop(LoopStop),
+}
+
+pub struct LoopGuard {
+    base: usize,
+    budget: usize,
+    fingerprint: u64,
+    /// Iteration of the last file change, if any
+    last_progress: Option<usize>,
+    /// Calls per signature since the last file change or reflection
+    repeats: HashMap<String, (String, usize)>,
+    reflections: usize,
+}
+
+impl LoopGuard {
+    /// `fingerprint` is the working directory's state before the first iteration.
+    pub fn new(max_iterations: usize, fingerprint: u64) -> Self {
+        let base = max_iterations.max(1);
+        Self {
+            base,
+            budget: base,
+            fingerprint,
+            last_progress: None,
+            repeats: HashMap::new(),
+            reflections: 0,
+        }
+    }
+
+    /// Iterations currently allowed.
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    /// Record a tool call made during the current iteration.
+    pub fn record_call(&mut self, name: &str, arguments: &Value) {
+        let signature = format!("{}:{}", name, arguments);
+        self.repeats
+            .entry(signature)
+            .or_insert_with(|| (name.to_string(), 0))
+            .1 += 1;
+    }
+
+    /// Close `iteration` given the working directory's state after its tool calls.
+    pub fn end_iteration(&mut self, iteration: usize, fingerprint: u64) -> Verdict {
+        if fingerprint != self.fingerprint {
+            self.fingerprint = fingerprint;
+            self.last_progress = Some(iteration);
+            self.repeats.clear();
+            return Verdict::Continue;
+        }
+        let Some((tool, count)) = self
+            .repeats
+            .values()
+            .filter(|(_, count)| *count >= REPEAT_THRESHOLD)
+            .max_by_key(|(_, count)| *count)
+            .cloned()
+        else {
+            return Verdict::Continue;
+        };
+        if self.reflections >= MAX_REFLECTIONS {
+            return Verdict::Stop(LoopStop::RepeatedToolCall(tool));
+        }
+        self.reflections += 1;
+        self.repeats.clear();
+        Verdict::Reflect(format!(
+            "You have called `{}` with the same arguments {} times and nothing in the \
+             working directory changed. Repeating it will not give a different result. \
+             Stop and work out why it is not working, then try a different approach, \
+             or give your final answer explaining what is blocking you.",
+            tool, count
+        ))
+    }
+
+    /// Called when `iterations` have run and the budget is used up. Extends the
+    /// budget if the working directory changed recently; returns the new budget.
+    pub fn try_extend(&mut self, iterations: usize) -> Option<usize> {
+        let recent = self
+            .last_progress
+            .is_some_and(|at| at + PROGRESS_WINDOW >= iterations);
+        let cap = self.base.saturating_mul(MAX_BUDGET_FACTOR);
+        if !recent || self.budget >= cap {
+            return None;
+        }
+        self.budget = (self.budget + (self.base / 2).max(PROGRESS_WINDOW)).min(cap);
+        Some(self.budget)
+    }
+}
+
+/// Hash of the paths, sizes and modification times of files under `dir`.
+pub fn fingerprint(dir: &Path) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    let entries = walkdir::WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|entry| {
+            !(entry.file_type().is_dir()
+                && SKIPPED_DIRS
+                    .iter()
+                    .any(|skipped| entry.file_name() == *skipped))
+        })
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .take(MAX_FINGERPRINT_FILES);
+    for entry in entries {
+        entry.path().hash(&mut hasher);
+        if let Ok(meta) = entry.metadata() {
+            meta.len().hash(&mut hasher);
+            if let Ok(modified) = meta.modified() {
+                modified.hash(&mut hasher);
+            }
+        }
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn repeat(guard: &mut LoopGuard, iteration: usize, fingerprint: u64) -> Verdict {
+        guard.record_call("run_command", &json!({"command": "cargo test"}));
+        guard.end_iteration(iteration, fingerprint)
+    }
+
+    #[test]
+    fn repeated_calls_reflect_then_stop() {
+        let mut guard = LoopGuard::new(50, 0);
+        let mut verdicts = (0..9).map(|i| repeat(&mut guard, i, 0));
+        for _ in 0..MAX_REFLECTIONS {
+            assert_eq!(verdicts.next(), Some(Verdict::Continue));
+            assert_eq!(verdicts.next(), Some(Verdict::Continue));
+            assert!(matches!(verdicts.next(), Some(Verdict::Reflect(_))));
+        }
+        assert_eq!(verdicts.next(), Some(Verdict::Continue));
+        assert_eq!(verdicts.next(), Some(Verdict::Continue));
+        assert_eq!(
+            verdicts.next(),
+            Some(Verdict::Stop(LoopStop::RepeatedToolCall(
+                "run_command".to_string()
+            )))
+        );
+    }
+
+    #[test]
+    fn file_changes_reset_repeat_counts() {
+        let mut guard = LoopGuard::new(50, 0);
+        for i in 0..10 {
+            // Re-running tests after each edit is not a loop.
+            assert_eq!(repeat(&mut guard, i, i as u64 + 1), Verdict::Continue);
+        }
+    }
+
+    #[test]
+    fn budget_extends_only_after_recent_progress() {
+        let mut guard = LoopGuard::new(10, 0);
+        assert_eq!(guard.try_extend(10), None);
+
+        guard.end_iteration(8, 1);
+        assert_eq!(guard.try_extend(10), Some(15));
+        // No change since iteration 8.
+        assert_eq!(guard.try_extend(15), None);
+
+        for (iteration, budget) in [(14, 20), (19, 25), (24, 30)] {
+            guard.end_iteration(iteration, iteration as u64);
+            assert_eq!(guard.try_extend(budget - 5), Some(budget));
+        }
+        // Capped at three times the configured budget.
+        guard.end_iteration(29, 29);
+        assert_eq!(guard.try_extend(30), None);
+    }
+
+    #[test]
+    fn fingerprint_tracks_files_but_not_skipped_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "a").unwrap();
+        let before = fingerprint(dir.path());
+
+        std::fs::create_dir_all(dir.path().join(".sandboxed-sh/tool-outputs")).unwrap();
+        std::fs::write(dir.path().join(".sandboxed-sh/tool-outputs/1.txt"), "x").unwrap();
+        assert_eq!(fingerprint(dir.path()), before);
+
+        std::fs::write(dir.path().join("b.txt"), "b").unwrap();
+        assert_ne!(fingerprint(dir.path()), before);
+    }
+}
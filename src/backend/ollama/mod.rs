@@ -0,0 +1,450 @@
+//! Ollama backend for fully offline missions.
+//!
+//! Missions talk to a local (or LAN) Ollama daemon over HTTP instead of a
+//! harness CLI. The daemon URL comes from `OLLAMA_BASE_URL`, overridable per
+//! config profile in `.ollama/settings.json` (see
+//! [`crate::library::OllamaConfig`]).
+//!
+//! Turns run an in-process agent loop over a small set of built-in tools.
+//! Models that advertise native tool calling get the tools as function
+//! schemas; for the rest, calls are emulated: the system prompt asks for a
+//! fenced `tool_call` block, which is parsed out of the reply, executed, and
+//! answered with a user message carrying the result.
+//!
+//! The loop is watched by a [`LoopGuard`]: repeated identical tool calls get
+//! a reflection prompt, and the iteration budget grows while the working
+//! directory keeps changing.
+
+mod client;
+mod loop_guard;
+
+pub use client::{
+    ChatMessage, ChatRequest, ChatToolCall, ChatToolFunction, OllamaClient, OllamaModel,
+};
+pub use loop_guard::LoopStop;
+
+use anyhow::{anyhow, Error};
+use async_trait::async_trait;
+use serde_json::Value;
+use std::path::PathBuf;
+use std::sync::Arc;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use crate::backend::events::ExecutionEvent;
+use crate::backend::{AgentInfo, Backend, BackendCapabilities, Session, SessionConfig};
+use crate::tools::{Tool, ToolRegistry};
+use loop_guard::{LoopGuard, Verdict};
+
+pub const DEFAULT_BASE_URL: &str = "http://127.0.0.1:11434";
+
+/// Built-in tools offered to Ollama models.
+pub const TOOL_NAMES: &[&str] = &[
+    "read_file",
+    "write_file",
+    "list_directory",
+    "search_files",
+    "grep_search",
+    "run_command",
+    "fetch_url",
+];
+
+/// Fence language marking an emulated tool call.
+const TOOL_CALL_FENCE: &str = "```tool_call";
+
+/// The built-in tools from [`TOOL_NAMES`].
+pub fn mission_tools() -> Vec<Arc<dyn Tool>> {
+    let registry = ToolRegistry::new();
+    TOOL_NAMES
+        .iter()
+        .filter_map(|name| registry.get(name))
+        .collect()
+}
+
+fn tool_schema(tool: &dyn Tool) -> Value {
+    serde_json::json!({
+        "type": "function",
+        "function": {
+            "name": tool.name(),
+            "description": tool.description(),
+            "parameters": tool.parameters_schema(),
+        }
+    })
+}
+
+/// System prompt section teaching a model without native tools how to call them.
+pub fn emulated_tools_prompt(tools: &[Arc<dyn Tool>]) -> String {
+    let mut prompt = String::from(
+        "You can call tools. To call one, reply with a fenced block and nothing after it:\n\n\
+         ```tool_call\n{\"name\": \"<tool name>\", \"arguments\": {<arguments>}}\n```\n\n\
+         You may include several blocks to call several tools. Tool results arrive in the \
+         next message. When you are done, answer without a tool_call block.\n\nTools:\n",
+    );
+    for tool in tools {
+        prompt.push_str(&format!(
+            "- {}: {}\n  parameters: {}\n",
+            tool.name(),
+            tool.description(),
+            tool.parameters_schema()
+        ));
+    }
+    prompt
+}
+
+/// Split a reply into its visible text and the emulated tool calls it makes.
+/// Blocks that don't parse as `{"name", "arguments"}` are left in the text.
+pub fn parse_emulated_tool_calls(reply: &str) -> (String, Vec<ChatToolCall>) {
+    let mut text = String::new();
+    let mut calls = Vec::new();
+    let mut rest = reply;
+    while let Some(start) = rest.find(TOOL_CALL_FENCE) {
+        let body_start = start + TOOL_CALL_FENCE.len();
+        let Some(len) = rest[body_start..].find("```") else {
+            break;
+        };
+        let body = rest[body_start..body_start + len].trim();
+        match serde_json::from_str::<ChatToolFunction>(body) {
+            Ok(function) if !function.name.is_empty() => {
+                text.push_str(&rest[..start]);
+                calls.push(ChatToolCall { function });
+            }
+            _ => text.push_str(&rest[..body_start + len + 3]),
+        }
+        rest = &rest[body_start + len + 3..];
+    }
+    text.push_str(rest);
+    (text.trim().to_string(), calls)
+}
+
+/// One mission turn against an Ollama model.
+pub struct AgentLoop {
+    pub client: OllamaClient,
+    pub model: String,
+    /// Conversation so far, ending with the user's message
+    pub messages: Vec<ChatMessage>,
+    pub tools: Vec<Arc<dyn Tool>>,
+    /// Send tools as function schemas instead of emulating calls
+    pub native_tools: bool,
+    pub working_dir: PathBuf,
+    /// Initial iteration budget; extended while files keep changing
+    pub max_iterations: usize,
+}
+
+impl AgentLoop {
+    /// Chat until the model answers without calling a tool, streaming events
+    /// to `events`. Returns the final answer, or a [`LoopStop`] error when
+    /// the budget runs out or the model is stuck repeating a tool call.
+    pub async fn run(
+        mut self,
+        events: mpsc::UnboundedSender<ExecutionEvent>,
+        cancel: CancellationToken,
+    ) -> Result<String, Error> {
+        let schemas: Vec<Value> = if self.native_tools {
+            self.tools.iter().map(|t| tool_schema(t.as_ref())).collect()
+        } else {
+            Vec::new()
+        };
+
+        let mut guard = LoopGuard::new(self.max_iterations, self.fingerprint().await);
+        let mut iteration = 0;
+        loop {
+            if iteration >= guard.budget() {
+                let Some(budget) = guard.try_extend(iteration) else {
+                    return Err(LoopStop::IterationLimit(iteration).into());
+                };
+                tracing::info!(
+                    model = %self.model,
+                    budget,
+                    "Files changed recently; extending iteration budget"
+                );
+            }
+            let request = ChatRequest {
+                model: self.model.clone(),
+                messages: self.messages.clone(),
+                tools: schemas.clone(),
+                stream: true,
+            };
+            let mut chunks = self.client.chat_stream(&request).await?;
+            let mut reply = ChatMessage::new("assistant", "");
+            loop {
+                let chunk = tokio::select! {
+                    _ = cancel.cancelled() => return Err(anyhow!("Mission cancelled")),
+                    chunk = chunks.recv() => chunk,
+                };
+                let Some(chunk) = chunk else {
+                    break;
+                };
+                let chunk = chunk?;
+                if let Some(message) = chunk.message {
+                    if let Some(thinking) = message.thinking.filter(|t| !t.is_empty()) {
+                        let _ = events.send(ExecutionEvent::Thinking { content: thinking });
+                    }
+                    // Emulated calls are parsed out once the reply is complete.
+                    if self.native_tools && !message.content.is_empty() {
+                        let _ = events.send(ExecutionEvent::TextDelta {
+                            content: message.content.clone(),
+                        });
+                    }
+                    reply.content.push_str(&message.content);
+                    reply.tool_calls.extend(message.tool_calls);
+                }
+                if chunk.done {
+                    if chunk.prompt_eval_count.is_some() || chunk.eval_count.is_some() {
+                        let _ = events.send(ExecutionEvent::Usage {
+                            input_tokens: chunk.prompt_eval_count.unwrap_or(0),
+                            output_tokens: chunk.eval_count.unwrap_or(0),
+                        });
+                    }
+                    break;
+                }
+            }
+
+            let (answer, calls) = if self.native_tools {
+                (reply.content.trim().to_string(), reply.tool_calls.clone())
+            } else {
+                let (text, calls) = parse_emulated_tool_calls(&reply.content);
+                if !text.is_empty() {
+                    let _ = events.send(ExecutionEvent::TextDelta {
+                        content: text.clone(),
+                    });
+                }
+                (text, calls)
+            };
+            self.messages.push(reply);
+            if calls.is_empty() {
+                return Ok(answer);
+            }
+
+            for (index, call) in calls.into_iter().enumerate() {
+                if cancel.is_cancelled() {
+                    return Err(anyhow!("Mission cancelled"));
+                }
+                let id = format!("ollama-{}-{}", iteration, index);
+                guard.record_call(&call.function.name, &call.function.arguments);
+                let output = self.call_tool(&id, &call.function, &events).await;
+                self.messages.push(if self.native_tools {
+                    ChatMessage {
+                        tool_name: Some(call.function.name.clone()),
+                        ..ChatMessage::new("tool", output)
+                    }
+                } else {
+                    ChatMessage::new(
+                        "user",
+                        format!(
+                            "Result of tool `{}`:\n```\n{}\n```",
+                            call.function.name, output
+                        ),
+                    )
+                });
+            }
+
+            match guard.end_iteration(iteration, self.fingerprint().await) {
+                Verdict::Continue => {}
+                Verdict::Reflect(prompt) => {
+                    tracing::warn!(model = %self.model, iteration, "Tool call loop detected");
+                    self.messages.push(ChatMessage::new("user", prompt));
+                }
+                Verdict::Stop(stop) => return Err(stop.into()),
+            }
+            iteration += 1;
+        }
+    }
+
+    async fn fingerprint(&self) -> u64 {
+        let dir = self.working_dir.clone();
+        tokio::task::spawn_blocking(move || loop_guard::fingerprint(&dir))
+            .await
+            .unwrap_or_default()
+    }
+
+    async fn call_tool(
+        &self,
+        id: &str,
+        function: &ChatToolFunction,
+        events: &mpsc::UnboundedSender<ExecutionEvent>,
+    ) -> String {
+        let _ = events.send(ExecutionEvent::ToolCall {
+            id: id.to_string(),
+            name: function.name.clone(),
+            args: function.arguments.clone(),
+        });
+        let output = match self.tools.iter().find(|t| t.name() == function.name) {
+            Some(tool) => {
+                let (sink, mut deltas) = mpsc::unbounded_channel::<crate::tools::ToolOutputDelta>();
+                let forward = {
+                    let events = events.clone();
+                    let id = id.to_string();
+                    let name = function.name.clone();
+                    tokio::spawn(async move {
+                        while let Some(delta) = deltas.recv().await {
+                            let _ = events.send(ExecutionEvent::ToolOutputDelta {
+                                id: id.clone(),
+                                name: name.clone(),
+                                stream: delta.stream,
+                                content: delta.content,
+                            });
+                        }
+                    })
+                };
+                let result = crate::tools::execute_tool(
+                    tool.as_ref(),
+                    function.arguments.clone(),
+                    &self.working_dir,
+                    Some(sink),
+                )
+                .await;
+                let _ = forward.await;
+                match result {
+                    Ok(output) => output,
+                    Err(e) => format!("Error: {}", e),
+                }
+            }
+            None => format!("Error: unknown tool '{}'", function.name),
+        };
+        let _ = events.send(ExecutionEvent::ToolResult {
+            id: id.to_string(),
+            name: function.name.clone(),
+            result: Value::String(output.clone()),
+        });
+        output
+    }
+}
+
+pub struct OllamaBackend {
+    id: String,
+    name: String,
+    client: OllamaClient,
+}
+
+impl OllamaBackend {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            id: "ollama".to_string(),
+            name: "Ollama".to_string(),
+            client: OllamaClient::new(base_url),
+        }
+    }
+
+    pub async fn list_models(&self) -> Result<Vec<OllamaModel>, Error> {
+        self.client.list_models().await
+    }
+}
+
+#[async_trait]
+impl Backend for OllamaBackend {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities {
+            model_override: true,
+            config_profiles: true,
+            ..BackendCapabilities::default()
+        }
+    }
+
+    async fn list_agents(&self) -> Result<Vec<AgentInfo>, Error> {
+        Ok(Vec::new())
+    }
+
+    async fn create_session(&self, config: SessionConfig) -> Result<Session, Error> {
+        Ok(Session {
+            id: uuid::Uuid::new_v4().to_string(),
+            directory: config.directory,
+            model: config.model,
+            agent: config.agent,
+        })
+    }
+
+    async fn send_message_streaming(
+        &self,
+        session: &Session,
+        message: &str,
+    ) -> Result<(mpsc::Receiver<ExecutionEvent>, JoinHandle<()>), Error> {
+        let model = match session.model.clone() {
+            Some(model) => model,
+            None => self
+                .client
+                .list_models()
+                .await?
+                .into_iter()
+                .next()
+                .map(|m| m.name)
+                .ok_or_else(|| anyhow!("No models installed on the Ollama daemon"))?,
+        };
+        let agent = AgentLoop {
+            client: self.client.clone(),
+            model,
+            messages: vec![ChatMessage::new("user", message)],
+            tools: Vec::new(),
+            native_tools: false,
+            working_dir: PathBuf::from(&session.directory),
+            max_iterations: 1,
+        };
+        let session_id = session.id.clone();
+        let (events_tx, mut events_rx) = mpsc::unbounded_channel();
+        let (tx, rx) = mpsc::channel(64);
+        let handle = tokio::spawn(async move {
+            let run = tokio::spawn(agent.run(events_tx, CancellationToken::new()));
+            while let Some(event) = events_rx.recv().await {
+                if tx.send(event).await.is_err() {
+                    return;
+                }
+            }
+            let last = match run.await {
+                Ok(Ok(_)) => ExecutionEvent::MessageComplete { session_id },
+                Ok(Err(e)) => ExecutionEvent::Error {
+                    message: e.to_string(),
+                },
+                Err(e) => ExecutionEvent::Error {
+                    message: e.to_string(),
+                },
+            };
+            let _ = tx.send(last).await;
+        });
+        Ok((rx, handle))
+    }
+}
+
+pub fn registry_entry(base_url: &str) -> Arc<dyn Backend> {
+    Arc::new(OllamaBackend::new(base_url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_emulated_tool_calls_out_of_reply() {
+        let reply = "Let me look.\n```tool_call\n{\"name\": \"read_file\", \"arguments\": {\"path\": \"a.txt\"}}\n```\n\
+                     ```tool_call\n{\"name\": \"list_directory\", \"arguments\": {}}\n```";
+        let (text, calls) = parse_emulated_tool_calls(reply);
+        assert_eq!(text, "Let me look.");
+        assert_eq!(calls.len(), 2);
+        assert_eq!(calls[0].function.name, "read_file");
+        assert_eq!(calls[0].function.arguments["path"], "a.txt");
+        assert_eq!(calls[1].function.name, "list_directory");
+
+        // Malformed blocks and plain code stay in the answer.
+        let reply = "Done.\n```tool_call\nnot json\n```\n```rust\nfn main() {}\n```";
+        let (text, calls) = parse_emulated_tool_calls(reply);
+        assert!(calls.is_empty());
+        assert_eq!(text, reply.trim());
+    }
+
+    #[test]
+    fn offers_the_mission_tools() {
+        let tools = mission_tools();
+        assert_eq!(tools.len(), TOOL_NAMES.len());
+        let prompt = emulated_tools_prompt(&tools);
+        for name in TOOL_NAMES {
+            assert!(prompt.contains(&format!("- {}:", name)));
+        }
+    }
+}
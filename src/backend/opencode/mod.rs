@@ -8,7 +8,7 @@ use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 
 use crate::backend::events::ExecutionEvent;
-use crate::backend::{AgentInfo, Backend, Session, SessionConfig};
+use crate::backend::{AgentInfo, Backend, BackendCapabilities, Session, SessionConfig};
 use client::OpenCodeClient;
 
 pub struct OpenCodeBackend {
@@ -95,6 +95,16 @@ impl Backend for OpenCodeBackend {
         &self.name
     }
 
+    fn capabilities(&self) -> BackendCapabilities {
+        BackendCapabilities {
+            model_override: true,
+            provider_model_ids: true,
+            library_agents: true,
+            config_profiles: true,
+            ..BackendCapabilities::default()
+        }
+    }
+
     async fn list_agents(&self) -> Result<Vec<AgentInfo>, Error> {
         match self.fetch_agents().await {
             Ok(payload) => Ok(Self::parse_agents(payload)),
@@ -1,12 +1,13 @@
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use super::Backend;
+use super::{Backend, BackendCapabilities};
 
 #[derive(Debug, Clone)]
 pub struct BackendInfo {
     pub id: String,
     pub name: String,
+    pub capabilities: BackendCapabilities,
 }
 
 pub struct BackendRegistry {
@@ -33,6 +34,7 @@ impl BackendRegistry {
             .map(|backend| BackendInfo {
                 id: backend.id().to_string(),
                 name: backend.name().to_string(),
+                capabilities: backend.capabilities(),
             })
             .collect();
         list.sort_by(|a, b| a.name.cmp(&b.name));
@@ -48,9 +48,18 @@ impl ProcessHandle {
         }
     }
 
-    /// Kill the underlying CLI process.
+    /// Kill the underlying CLI process and the tool processes it spawned.
     pub async fn kill(&self) {
         if let Some(mut child) = self.child.lock().await.take() {
+            if let Some(pid) = child.id() {
+                let report =
+                    crate::process_tree::terminate_tree(pid, crate::process_tree::TERM_GRACE).await;
+                info!(
+                    reaped = ?report.reaped,
+                    survivors = ?report.survivors,
+                    "Terminated CLI process tree"
+                );
+            }
             if let Err(e) = child.kill().await {
                 warn!("Failed to kill CLI process: {}", e);
             } else {
@@ -14,6 +14,7 @@ use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 
 use sandboxed_sh::tools::desktop::find_browser_command;
+use sandboxed_sh::tools::desktop_macos::{self, Region};
 
 /// Global counter for display numbers to avoid conflicts
 static DISPLAY_COUNTER: AtomicU32 = AtomicU32::new(99);
@@ -193,6 +194,10 @@ fn kill_process(pid: u32) {
 }
 
 fn tool_start_session(args: &Value) -> Result<String, String> {
+    if desktop_macos::macos_backend() {
+        return desktop_macos::start_session(args, &get_working_dir()).map_err(|e| e.to_string());
+    }
+
     let display_num = DISPLAY_COUNTER.fetch_add(1, Ordering::SeqCst);
     let display_id = format!(":{}", display_num);
     let resolution = get_resolution();
@@ -397,6 +402,11 @@ fn tool_stop_session(args: &Value) -> Result<String, String> {
         .and_then(|v| v.as_str())
         .ok_or("Missing 'display' argument")?;
 
+    if desktop_macos::is_macos_display(display_id) {
+        return desktop_macos::stop_session(display_id, &get_working_dir())
+            .map_err(|e| e.to_string());
+    }
+
     let display_num: u32 = display_id
         .trim_start_matches(':')
         .parse()
@@ -411,13 +421,9 @@ fn tool_stop_session(args: &Value) -> Result<String, String> {
             if let Ok(session_info) = serde_json::from_str::<Value>(&content) {
                 for pid_key in ["xvfb_pid", "i3_pid", "browser_pid"] {
                     if let Some(pid) = session_info.get(pid_key).and_then(|v| v.as_u64()) {
-                        let pid = pid as i32;
-                        // SAFETY: PIDs are read from a session file we wrote;
-                        // SIGTERM is a safe signal to send to any process.
-                        unsafe {
-                            libc::kill(pid, libc::SIGTERM);
-                        }
-                        killed_pids.push(pid);
+                        // PIDs are read from a session file we wrote.
+                        sandboxed_sh::process_tree::terminate_process(pid as u32);
+                        killed_pids.push(pid as i32);
                     }
                 }
             }
@@ -476,35 +482,37 @@ fn tool_screenshot(args: &Value) -> Result<(String, Option<String>), String> {
         .map_err(|e| format!("Failed to create screenshots dir: {}", e))?;
 
     let filepath = screenshots_dir.join(&filename);
+    let region = Region::from_args(args);
 
-    // Build scrot command
-    let mut scrot_args = vec!["-o".to_string(), filepath.to_string_lossy().to_string()];
+    if desktop_macos::is_macos_display(display_id) {
+        desktop_macos::capture_screen(&filepath, region).map_err(|e| e.to_string())?;
+    } else {
+        // Build scrot command
+        let mut scrot_args = vec!["-o".to_string(), filepath.to_string_lossy().to_string()];
 
-    // Add region if specified
-    if let Some(region) = args.get("region") {
-        if region.is_object() {
-            let x = region.get("x").and_then(|v| v.as_i64()).unwrap_or(0);
-            let y = region.get("y").and_then(|v| v.as_i64()).unwrap_or(0);
-            let w = region.get("width").and_then(|v| v.as_i64()).unwrap_or(100);
-            let h = region.get("height").and_then(|v| v.as_i64()).unwrap_or(100);
+        // Add region if specified
+        if let Some(region) = region {
             scrot_args.push("-a".to_string());
-            scrot_args.push(format!("{},{},{},{}", x, y, w, h));
+            scrot_args.push(format!(
+                "{},{},{},{}",
+                region.x, region.y, region.width, region.height
+            ));
         }
-    }
 
-    let scrot_args_refs: Vec<&str> = scrot_args.iter().map(|s| s.as_str()).collect();
-    let (_stdout, stderr, exit_code) = run_with_display(display_id, "scrot", &scrot_args_refs)?;
+        let scrot_args_refs: Vec<&str> = scrot_args.iter().map(|s| s.as_str()).collect();
+        let (_stdout, stderr, exit_code) = run_with_display(display_id, "scrot", &scrot_args_refs)?;
 
-    if exit_code != 0 {
-        // Try import as fallback
-        let (_, _, import_exit) = run_with_display(
-            display_id,
-            "import",
-            &["-window", "root", filepath.to_string_lossy().as_ref()],
-        )?;
-
-        if import_exit != 0 {
-            return Err(format!("Screenshot failed. scrot error: {}", stderr));
+        if exit_code != 0 {
+            // Try import as fallback
+            let (_, _, import_exit) = run_with_display(
+                display_id,
+                "import",
+                &["-window", "root", filepath.to_string_lossy().as_ref()],
+            )?;
+
+            if import_exit != 0 {
+                return Err(format!("Screenshot failed. scrot error: {}", stderr));
+            }
         }
     }
 
@@ -560,15 +568,24 @@ fn tool_type_text(args: &Value) -> Result<String, String> {
         return Err("Either 'text' or 'key' must be provided".to_string());
     };
 
-    let delay_str = delay_ms.to_string();
-    let (_stdout, stderr, exit_code) = run_with_display(
-        display_id,
-        "xdotool",
-        &[command, "--delay", &delay_str, &input],
-    )?;
+    if desktop_macos::is_macos_display(display_id) {
+        let (text, key) = if command == "type" {
+            (Some(input.as_str()), None)
+        } else {
+            (None, Some(input.as_str()))
+        };
+        desktop_macos::type_text(text, key, delay_ms).map_err(|e| e.to_string())?;
+    } else {
+        let delay_str = delay_ms.to_string();
+        let (_stdout, stderr, exit_code) = run_with_display(
+            display_id,
+            "xdotool",
+            &[command, "--delay", &delay_str, &input],
+        )?;
 
-    if exit_code != 0 {
-        return Err(format!("xdotool failed: {}", stderr));
+        if exit_code != 0 {
+            return Err(format!("xdotool failed: {}", stderr));
+        }
     }
 
     Ok(format!(
@@ -614,6 +631,18 @@ fn tool_click(args: &Value) -> Result<String, String> {
         .unwrap_or(false);
     let repeat = if double { "2" } else { "1" };
 
+    if desktop_macos::is_macos_display(display_id) {
+        let button_name = args
+            .get("button")
+            .and_then(|v| v.as_str())
+            .unwrap_or("left");
+        desktop_macos::click(x, y, button_name, double).map_err(|e| e.to_string())?;
+        return Ok(format!(
+            "{{\"success\": true, \"x\": {}, \"y\": {}, \"button\": \"{}\", \"double\": {}}}",
+            x, y, button_name, double
+        ));
+    }
+
     // Move to position first
     let x_str = x.to_string();
     let y_str = y.to_string();
@@ -667,6 +696,11 @@ fn tool_mouse_move(args: &Value) -> Result<String, String> {
         .and_then(|v| v.as_i64())
         .ok_or("Missing 'y' argument")?;
 
+    if desktop_macos::is_macos_display(display_id) {
+        desktop_macos::mouse_move(x, y).map_err(|e| e.to_string())?;
+        return Ok(format!("{{\"success\": true, \"x\": {}, \"y\": {}}}", x, y));
+    }
+
     let x_str = x.to_string();
     let y_str = y.to_string();
     let (_, stderr, exit_code) =
@@ -694,6 +728,14 @@ fn tool_scroll(args: &Value) -> Result<String, String> {
         .and_then(|v| v.as_i64())
         .ok_or("Missing 'amount' argument")?;
 
+    if desktop_macos::is_macos_display(display_id) {
+        return Err(desktop_macos::unsupported(
+            "desktop_scroll",
+            "Use desktop_type with key 'Page_Down' or 'Page_Up' instead.",
+        )
+        .to_string());
+    }
+
     // Move to position if specified
     if let (Some(x), Some(y)) = (
         args.get("x").and_then(|v| v.as_i64()),
@@ -750,6 +792,14 @@ fn tool_i3_command(args: &Value) -> Result<String, String> {
         .and_then(|v| v.as_str())
         .ok_or("Missing 'command' argument")?;
 
+    if desktop_macos::is_macos_display(display_id) {
+        return Err(desktop_macos::unsupported(
+            "desktop_i3_command",
+            "Launch apps with `open -a <App>` from the shell instead.",
+        )
+        .to_string());
+    }
+
     let (stdout, stderr, exit_code) = run_with_display(display_id, "i3-msg", &[command])?;
 
     if exit_code != 0 {
@@ -780,6 +830,11 @@ fn tool_get_text(args: &Value) -> Result<String, String> {
         .ok_or("Missing 'display' argument")?;
 
     let working_dir = get_working_dir();
+    if desktop_macos::is_macos_display(display_id) {
+        let text = desktop_macos::ocr_text(&working_dir).map_err(|e| e.to_string())?;
+        return Ok(format!("--- OCR Text ---\n{}", text.trim()));
+    }
+
     let screenshots_dir = working_dir.join("screenshots");
     std::fs::create_dir_all(&screenshots_dir)
         .map_err(|e| format!("Failed to create screenshots dir: {}", e))?;
@@ -828,7 +883,7 @@ fn get_tool_definitions() -> Vec<ToolDefinition> {
     vec![
         ToolDefinition {
             name: "desktop_start_session".to_string(),
-            description: "Start a virtual desktop session (Xvfb + i3 window manager). Returns the DISPLAY identifier (e.g., ':99') needed for other desktop_* tools. Call this before using any other desktop tools. Optionally launches Chromium browser.".to_string(),
+            description: "Start a virtual desktop session (Xvfb + i3 window manager). Returns the DISPLAY identifier (e.g., ':99') needed for other desktop_* tools. Call this before using any other desktop tools. Optionally launches Chromium browser. On macOS hosts the session is the host screen and the identifier looks like 'macos:1'.".to_string(),
             input_schema: json!({
                 "type": "object",
                 "properties": {
@@ -487,6 +487,411 @@ impl Tool for UpdateSkillTool {
     }
 }
 
+/// Tool: list_skills
+///
+/// Lists library skills with their manifest inputs so the agent can pick one
+/// to invoke by name.
+struct ListSkillsTool;
+
+#[async_trait]
+impl Tool for ListSkillsTool {
+    fn name(&self) -> &str {
+        "list_skills"
+    }
+
+    fn description(&self) -> &str {
+        "List skills installed in the library, including the inputs and required tools \
+         declared by each skill's manifest. Use invoke_skill to run one by name."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {}
+        })
+    }
+
+    async fn execute(&self, _args: Value, _working_dir: &Path) -> anyhow::Result<String> {
+        let api_base = std::env::var("SANDBOXED_SH_API_URL")
+            .unwrap_or_else(|_| "http://127.0.0.1:3000".to_string());
+        let auth_token = std::env::var("SANDBOXED_SH_API_TOKEN").ok();
+
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()?;
+
+        let mut request = client.get(format!("{}/api/library/skill", api_base));
+        if let Some(token) = auth_token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        let body = response.text().await.unwrap_or_default();
+        if !status.is_success() {
+            return Err(anyhow::anyhow!(
+                "Failed to list skills: {} - {}",
+                status,
+                body
+            ));
+        }
+
+        let skills: Vec<Value> = serde_json::from_str(&body)?;
+        let summary: Vec<Value> = skills
+            .iter()
+            .map(|skill| {
+                json!({
+                    "name": skill["name"],
+                    "description": skill["description"],
+                    "inputs": skill["manifest"]["inputs"],
+                    "required_tools": skill["manifest"]["required_tools"],
+                })
+            })
+            .collect();
+        Ok(serde_json::to_string_pretty(&summary)?)
+    }
+}
+
+/// Tool: invoke_skill
+///
+/// Renders a library skill's prompt with the given inputs.
+struct InvokeSkillTool;
+
+#[async_trait]
+impl Tool for InvokeSkillTool {
+    fn name(&self) -> &str {
+        "invoke_skill"
+    }
+
+    fn description(&self) -> &str {
+        "Invoke a library skill by name. Returns the skill's instructions with the provided \
+         inputs filled in; follow them to complete the task."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "skill_name": {
+                    "type": "string",
+                    "description": "Name of the skill to invoke (see list_skills)"
+                },
+                "inputs": {
+                    "type": "object",
+                    "description": "Values for the inputs declared by the skill manifest",
+                    "additionalProperties": { "type": "string" }
+                }
+            },
+            "required": ["skill_name"]
+        })
+    }
+
+    async fn execute(&self, args: Value, _working_dir: &Path) -> anyhow::Result<String> {
+        let skill_name = args["skill_name"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing 'skill_name' argument"))?;
+
+        // Validate skill name (prevent path traversal)
+        if skill_name.contains("..") || skill_name.contains('/') || skill_name.contains('\\') {
+            return Err(anyhow::anyhow!(
+                "Invalid skill name: contains path separators or '..'"
+            ));
+        }
+
+        let api_base = std::env::var("SANDBOXED_SH_API_URL")
+            .unwrap_or_else(|_| "http://127.0.0.1:3000".to_string());
+        let auth_token = std::env::var("SANDBOXED_SH_API_TOKEN").ok();
+
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()?;
+
+        let mut request = client
+            .post(format!(
+                "{}/api/library/skill/{}/invoke",
+                api_base, skill_name
+            ))
+            .json(&json!({ "inputs": args.get("inputs").cloned().unwrap_or(json!({})) }));
+        if let Some(token) = auth_token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            let body: Value = response.json().await?;
+            Ok(body["prompt"].as_str().unwrap_or_default().to_string())
+        } else {
+            let error_text = response.text().await.unwrap_or_default();
+            Err(anyhow::anyhow!(
+                "Failed to invoke skill: {} - {}",
+                status,
+                error_text
+            ))
+        }
+    }
+}
+
+/// Tool: set_mission_tags
+///
+/// Replaces the current mission's tags via the backend API.
+struct SetMissionTagsTool;
+
+#[async_trait]
+impl Tool for SetMissionTagsTool {
+    fn name(&self) -> &str {
+        "set_mission_tags"
+    }
+
+    fn description(&self) -> &str {
+        "Set free-form tags on the current mission (e.g. 'deploy', 'bugfix', a project name) so it can be filtered later. Replaces any existing tags."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "tags": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Tags for this mission (lowercased; spaces become '-')"
+                }
+            },
+            "required": ["tags"]
+        })
+    }
+
+    async fn execute(&self, args: Value, _working_dir: &Path) -> anyhow::Result<String> {
+        let tags = args["tags"]
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("Missing 'tags' argument"))?;
+        let mission_id = std::env::var("SANDBOXED_SH_MISSION_ID")
+            .map_err(|_| anyhow::anyhow!("No mission ID available for this workspace"))?;
+
+        let api_base = std::env::var("SANDBOXED_SH_API_URL")
+            .unwrap_or_else(|_| "http://127.0.0.1:3000".to_string());
+        let auth_token = std::env::var("SANDBOXED_SH_API_TOKEN").ok();
+
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(30))
+            .build()?;
+
+        let mut request = client
+            .put(format!(
+                "{}/api/control/missions/{}/tags",
+                api_base, mission_id
+            ))
+            .json(&json!({ "tags": tags }));
+        if let Some(token) = auth_token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            let mission: Value = response.json().await?;
+            Ok(format!("Mission tags set: {}", mission["tags"]))
+        } else {
+            let error_text = response.text().await.unwrap_or_default();
+            Err(anyhow::anyhow!(
+                "Failed to set mission tags: {} - {}",
+                status,
+                error_text
+            ))
+        }
+    }
+}
+
+/// Tool: kubernetes
+///
+/// Runs Kubernetes operations through the backend API, which holds the
+/// workspace's kubeconfig and enforces its namespace allowlist.
+struct KubernetesTool;
+
+#[async_trait]
+impl Tool for KubernetesTool {
+    fn name(&self) -> &str {
+        "kubernetes"
+    }
+
+    fn description(&self) -> &str {
+        "Inspect the Kubernetes cluster configured for this workspace and get structured JSON back \
+         (like kubectl get/describe/logs). Verbs: get (list or single object), describe (object + \
+         events), logs (pod logs), events, and - only if the workspace allows writes - delete, \
+         scale, restart. Only allowlisted namespaces are reachable; Secrets are not readable."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "verb": {
+                    "type": "string",
+                    "enum": ["get", "describe", "logs", "events", "delete", "scale", "restart"]
+                },
+                "resource": {
+                    "type": "string",
+                    "description": "Resource type, e.g. pods, deployments, svc, jobs (default: pods)"
+                },
+                "name": {
+                    "type": "string",
+                    "description": "Object name (omit with 'get' to list)"
+                },
+                "namespace": {
+                    "type": "string",
+                    "description": "Namespace (default: the workspace's first allowlisted namespace)"
+                },
+                "label_selector": {
+                    "type": "string",
+                    "description": "Label selector for listing, e.g. 'app=web'"
+                },
+                "container": {
+                    "type": "string",
+                    "description": "Container name for logs"
+                },
+                "tail_lines": {
+                    "type": "integer",
+                    "description": "Log lines to return (default: 200)"
+                },
+                "since_seconds": {
+                    "type": "integer",
+                    "description": "Only logs newer than this many seconds"
+                },
+                "previous": {
+                    "type": "boolean",
+                    "description": "Logs of the previous container instance"
+                },
+                "replicas": {
+                    "type": "integer",
+                    "description": "Replica count for scale"
+                }
+            },
+            "required": ["verb"]
+        })
+    }
+
+    async fn execute(&self, args: Value, _working_dir: &Path) -> anyhow::Result<String> {
+        let mission_id = std::env::var("SANDBOXED_SH_MISSION_ID")
+            .map_err(|_| anyhow::anyhow!("No mission ID available for this workspace"))?;
+
+        let api_base = std::env::var("SANDBOXED_SH_API_URL")
+            .unwrap_or_else(|_| "http://127.0.0.1:3000".to_string());
+        let auth_token = std::env::var("SANDBOXED_SH_API_TOKEN").ok();
+
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(60))
+            .build()?;
+
+        let mut request = client
+            .post(format!(
+                "{}/api/control/missions/{}/kubernetes",
+                api_base, mission_id
+            ))
+            .json(&args);
+        if let Some(token) = auth_token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            let result: Value = response.json().await?;
+            Ok(serde_json::to_string_pretty(&result)?)
+        } else {
+            let error_text = response.text().await.unwrap_or_default();
+            Err(anyhow::anyhow!(
+                "Kubernetes request failed: {} - {}",
+                status,
+                error_text
+            ))
+        }
+    }
+}
+
+/// Tool: ssh_exec
+///
+/// Runs a command on an allowlisted remote host through the backend API,
+/// which holds the SSH keys and records every command in the audit log.
+struct SshExecTool;
+
+#[async_trait]
+impl Tool for SshExecTool {
+    fn name(&self) -> &str {
+        "ssh_exec"
+    }
+
+    fn description(&self) -> &str {
+        "Run a shell command on a remote host over SSH. Only hosts allowlisted in this \
+         workspace's SSH settings are reachable, by name. Returns exit code, stdout and stderr. \
+         Every command is recorded in the audit log."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "host": {
+                    "type": "string",
+                    "description": "Allowlisted host name"
+                },
+                "command": {
+                    "type": "string",
+                    "description": "Shell command to run on the remote host"
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "description": "Kill the command after this many seconds (default: 120, max: 3600)"
+                }
+            },
+            "required": ["host", "command"]
+        })
+    }
+
+    async fn execute(&self, args: Value, _working_dir: &Path) -> anyhow::Result<String> {
+        let mission_id = std::env::var("SANDBOXED_SH_MISSION_ID")
+            .map_err(|_| anyhow::anyhow!("No mission ID available for this workspace"))?;
+
+        let api_base = std::env::var("SANDBOXED_SH_API_URL")
+            .unwrap_or_else(|_| "http://127.0.0.1:3000".to_string());
+        let auth_token = std::env::var("SANDBOXED_SH_API_TOKEN").ok();
+
+        // Leave headroom over the remote timeout for connection setup.
+        let timeout_secs = args
+            .get("timeout_secs")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(120)
+            .min(3600)
+            + 30;
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(timeout_secs))
+            .build()?;
+
+        let mut request = client
+            .post(format!(
+                "{}/api/control/missions/{}/ssh",
+                api_base, mission_id
+            ))
+            .json(&args);
+        if let Some(token) = auth_token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        if status.is_success() {
+            let result: Value = response.json().await?;
+            Ok(serde_json::to_string_pretty(&result)?)
+        } else {
+            let error_text = response.text().await.unwrap_or_default();
+            Err(anyhow::anyhow!(
+                "SSH request failed: {} - {}",
+                status,
+                error_text
+            ))
+        }
+    }
+}
+
 /// Tool: update_init_script
 ///
 /// Updates an init script fragment in the library directory and triggers
@@ -580,6 +985,99 @@ impl Tool for UpdateInitScriptTool {
     }
 }
 
+/// Tool: remote HTTP tool registered with the backend.
+///
+/// Calls are proxied through `/api/remote-tools/:name/call`, which applies the
+/// tool's timeout/retry policy and records the result.
+struct RemoteToolProxy {
+    name: String,
+    description: String,
+    parameters: Value,
+}
+
+#[async_trait]
+impl Tool for RemoteToolProxy {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters_schema(&self) -> Value {
+        self.parameters.clone()
+    }
+
+    async fn execute(&self, args: Value, _working_dir: &Path) -> anyhow::Result<String> {
+        let api_base = std::env::var("SANDBOXED_SH_API_URL")
+            .unwrap_or_else(|_| "http://127.0.0.1:3000".to_string());
+        let auth_token = std::env::var("SANDBOXED_SH_API_TOKEN").ok();
+        let mission_id = std::env::var("SANDBOXED_SH_MISSION_ID").ok();
+
+        // The backend enforces the per-tool timeout; this only guards against a hung server.
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(600))
+            .build()?;
+
+        let mut request = client
+            .post(format!("{}/api/remote-tools/{}/call", api_base, self.name))
+            .json(&json!({ "arguments": args, "mission_id": mission_id }));
+        if let Some(token) = auth_token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        if !status.is_success() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(anyhow::anyhow!(
+                "Failed to call remote tool: {} - {}",
+                status,
+                error_text
+            ));
+        }
+
+        let call: Value = response.json().await?;
+        if call["success"].as_bool().unwrap_or(false) {
+            Ok(call["output"].as_str().unwrap_or_default().to_string())
+        } else {
+            Err(anyhow::anyhow!(
+                "{}",
+                call["error"].as_str().unwrap_or("Remote tool call failed")
+            ))
+        }
+    }
+}
+
+/// Fetch remote tool definitions from the backend.
+async fn load_remote_tools() -> anyhow::Result<Vec<RemoteToolProxy>> {
+    let api_base = std::env::var("SANDBOXED_SH_API_URL")
+        .unwrap_or_else(|_| "http://127.0.0.1:3000".to_string());
+    let auth_token = std::env::var("SANDBOXED_SH_API_TOKEN").ok();
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()?;
+    let mut request = client.get(format!("{}/api/remote-tools", api_base));
+    if let Some(token) = auth_token {
+        request = request.header("Authorization", format!("Bearer {}", token));
+    }
+
+    let defs: Vec<Value> = request.send().await?.error_for_status()?.json().await?;
+    Ok(defs
+        .into_iter()
+        .filter(|d| d["enabled"].as_bool().unwrap_or(true))
+        .filter_map(|d| {
+            Some(RemoteToolProxy {
+                name: d["name"].as_str()?.to_string(),
+                description: d["description"].as_str().unwrap_or_default().to_string(),
+                parameters: d["parameters"].clone(),
+            })
+        })
+        .collect())
+}
+
 fn tool_set() -> HashMap<String, Arc<dyn Tool>> {
     let mut tools: HashMap<String, Arc<dyn Tool>> = HashMap::new();
 
@@ -590,12 +1088,44 @@ fn tool_set() -> HashMap<String, Arc<dyn Tool>> {
     tools.insert("search_files".to_string(), Arc::new(tools::SearchFiles));
     tools.insert("grep_search".to_string(), Arc::new(tools::GrepSearch));
     tools.insert("fetch_url".to_string(), Arc::new(tools::FetchUrl));
+    tools.insert("docker".to_string(), Arc::new(tools::docker::Docker));
+    tools.insert(
+        "http_request".to_string(),
+        Arc::new(tools::http_request::HttpRequest),
+    );
+    tools.insert(
+        "notebook_execute".to_string(),
+        Arc::new(tools::notebook::ExecuteNotebook),
+    );
+    tools.insert(
+        "notebook_kernel".to_string(),
+        Arc::new(tools::notebook::NotebookKernel),
+    );
+    tools.insert(
+        "visual_diff".to_string(),
+        Arc::new(tools::visual_diff::VisualDiff),
+    );
+    tools.insert(
+        "git_create_branch".to_string(),
+        Arc::new(tools::git::CreateBranch),
+    );
+    tools.insert("git_commit".to_string(), Arc::new(tools::git::Commit));
+    tools.insert("git_push".to_string(), Arc::new(tools::git::Push));
     tools.insert("update_skill".to_string(), Arc::new(UpdateSkillTool));
+    tools.insert("list_skills".to_string(), Arc::new(ListSkillsTool));
+    tools.insert("invoke_skill".to_string(), Arc::new(InvokeSkillTool));
+    tools.insert("set_mission_tags".to_string(), Arc::new(SetMissionTagsTool));
+    tools.insert("kubernetes".to_string(), Arc::new(KubernetesTool));
+    tools.insert("ssh_exec".to_string(), Arc::new(SshExecTool));
     tools.insert(
         "update_init_script".to_string(),
         Arc::new(UpdateInitScriptTool),
     );
 
+    if let Some(dir) = tools::plugin::plugins_dir() {
+        tools::plugin::register_plugins(&mut tools, &dir);
+    }
+
     tools
 }
 
@@ -628,7 +1158,12 @@ fn execute_tool(
         };
     };
 
-    let result = runtime.block_on(tool.execute(args.clone(), working_dir));
+    let result = runtime.block_on(tools::execute_tool(
+        tool.as_ref(),
+        args.clone(),
+        working_dir,
+        None,
+    ));
     match result {
         Ok(text) => ToolResult {
             content: vec![ToolContent::Text { text }],
@@ -719,7 +1254,18 @@ fn main() {
         .build()
         .expect("Failed to start tokio runtime");
 
-    let tools = tool_set();
+    let mut tools = tool_set();
+    match runtime.block_on(load_remote_tools()) {
+        Ok(remote) => {
+            for tool in remote {
+                // Built-in tools take precedence over remote tools with the same name.
+                if !tools.contains_key(&tool.name) {
+                    tools.insert(tool.name.clone(), Arc::new(tool));
+                }
+            }
+        }
+        Err(e) => eprintln!("[workspace-mcp] Remote tools unavailable: {}", e),
+    }
     let workspace = Arc::new(RwLock::new(hydrate_workspace_env(None)));
 
     let stdin = std::io::stdin();
@@ -0,0 +1,248 @@
+//! Blackout windows for automations.
+//!
+//! An automation (or every automation in a workspace) can list windows during
+//! which the scheduler does not trigger it: recurring weekly windows such as
+//! weekdays 09:00–18:00, or one-off periods such as a deploy freeze. A run that
+//! falls due inside a window is recorded once as a `skipped` execution and
+//! happens when the window ends.
+
+use chrono::{DateTime, Datelike, FixedOffset, NaiveTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+
+/// Most windows accepted per automation or workspace.
+pub const MAX_BLACKOUT_WINDOWS: usize = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BlackoutWindow {
+    /// Recurring window on days of the week.
+    Weekly {
+        /// Days the window starts on; empty means every day
+        #[serde(default)]
+        days: Vec<Weekday>,
+        /// `HH:MM`
+        start: String,
+        /// `HH:MM`; earlier than `start` for windows past midnight, equal to
+        /// `start` for the whole day
+        end: String,
+        /// Offset the times are in (e.g. `+02:00`); UTC when omitted
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        utc_offset: Option<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+    /// One-off period.
+    Period {
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        reason: Option<String>,
+    },
+}
+
+fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+        .map_err(|_| format!("Invalid time '{}': expected HH:MM", value))
+}
+
+fn parse_offset(value: Option<&str>) -> Result<FixedOffset, String> {
+    match value {
+        None => Ok(FixedOffset::east_opt(0).expect("zero offset")),
+        Some(value) => value
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid utc_offset '{}': expected e.g. +02:00", value)),
+    }
+}
+
+impl BlackoutWindow {
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            BlackoutWindow::Weekly {
+                start,
+                end,
+                utc_offset,
+                ..
+            } => {
+                parse_time(start)?;
+                parse_time(end)?;
+                parse_offset(utc_offset.as_deref())?;
+            }
+            BlackoutWindow::Period { start, end, .. } => {
+                if end <= start {
+                    return Err("Blackout period must end after it starts".to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `at` falls inside the window. Invalid windows contain nothing.
+    pub fn contains(&self, at: DateTime<Utc>) -> bool {
+        match self {
+            BlackoutWindow::Weekly {
+                days,
+                start,
+                end,
+                utc_offset,
+                ..
+            } => {
+                let (Ok(start), Ok(end), Ok(offset)) = (
+                    parse_time(start),
+                    parse_time(end),
+                    parse_offset(utc_offset.as_deref()),
+                ) else {
+                    return false;
+                };
+                let local = at.with_timezone(&offset);
+                let time = local.time();
+                let on = |day: Weekday| days.is_empty() || days.contains(&day);
+                let today = local.weekday();
+                if start < end {
+                    on(today) && time >= start && time < end
+                } else if start > end {
+                    // Past midnight: the late part of the start day, or the
+                    // early part of the day after
+                    (on(today) && time >= start) || (on(today.pred()) && time < end)
+                } else {
+                    on(today)
+                }
+            }
+            BlackoutWindow::Period { start, end, .. } => at >= *start && at < *end,
+        }
+    }
+
+    /// Short description for execution records.
+    pub fn describe(&self) -> String {
+        let (label, reason) = match self {
+            BlackoutWindow::Weekly {
+                days,
+                start,
+                end,
+                utc_offset,
+                reason,
+            } => {
+                let days = if days.is_empty() {
+                    "daily".to_string()
+                } else {
+                    days.iter()
+                        .map(|d| d.to_string())
+                        .collect::<Vec<_>>()
+                        .join(",")
+                };
+                let offset = utc_offset.as_deref().unwrap_or("UTC");
+                (format!("{} {}-{} {}", days, start, end, offset), reason)
+            }
+            BlackoutWindow::Period { start, end, reason } => (
+                format!("{} to {}", start.to_rfc3339(), end.to_rfc3339()),
+                reason,
+            ),
+        };
+        match reason {
+            Some(reason) => format!("{} ({})", label, reason),
+            None => label,
+        }
+    }
+}
+
+/// Validate a list of windows from a request.
+pub fn validate_windows(windows: &[BlackoutWindow]) -> Result<(), String> {
+    if windows.len() > MAX_BLACKOUT_WINDOWS {
+        return Err(format!(
+            "At most {} blackout windows are allowed",
+            MAX_BLACKOUT_WINDOWS
+        ));
+    }
+    windows.iter().try_for_each(BlackoutWindow::validate)
+}
+
+/// The first window containing `at`.
+pub fn active_window<'a>(
+    windows: impl IntoIterator<Item = &'a BlackoutWindow>,
+    at: DateTime<Utc>,
+) -> Option<&'a BlackoutWindow> {
+    windows.into_iter().find(|w| w.contains(at))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    fn weekly(days: Vec<Weekday>, start: &str, end: &str, offset: Option<&str>) -> BlackoutWindow {
+        BlackoutWindow::Weekly {
+            days,
+            start: start.to_string(),
+            end: end.to_string(),
+            utc_offset: offset.map(str::to_string),
+            reason: None,
+        }
+    }
+
+    #[test]
+    fn weekly_windows_respect_days_offsets_and_midnight() {
+        let weekdays = vec![
+            Weekday::Mon,
+            Weekday::Tue,
+            Weekday::Wed,
+            Weekday::Thu,
+            Weekday::Fri,
+        ];
+        let office: BlackoutWindow = serde_json::from_value(serde_json::json!({
+            "type": "weekly",
+            "days": ["mon", "tue", "wed", "thu", "fri"],
+            "start": "09:00",
+            "end": "18:00",
+            "utc_offset": "+02:00"
+        }))
+        .unwrap();
+        assert_eq!(office, weekly(weekdays, "09:00", "18:00", Some("+02:00")));
+        // 2026-10-19 is a Monday
+        assert!(office.contains(at("2026-10-19T07:00:00Z")));
+        assert!(!office.contains(at("2026-10-19T16:00:00Z")));
+        assert!(!office.contains(at("2026-10-18T10:00:00Z")));
+
+        let nights = weekly(vec![Weekday::Fri], "22:00", "02:00", None);
+        assert!(nights.contains(at("2026-10-23T23:00:00Z")));
+        assert!(nights.contains(at("2026-10-24T01:30:00Z")));
+        assert!(!nights.contains(at("2026-10-24T02:00:00Z")));
+        assert!(!nights.contains(at("2026-10-22T23:00:00Z")));
+
+        let sundays = weekly(vec![Weekday::Sun], "00:00", "00:00", None);
+        assert!(sundays.contains(at("2026-10-18T13:00:00Z")));
+        assert!(!sundays.contains(at("2026-10-19T13:00:00Z")));
+    }
+
+    #[test]
+    fn periods_and_validation() {
+        let freeze = BlackoutWindow::Period {
+            start: at("2026-12-20T00:00:00Z"),
+            end: at("2027-01-05T00:00:00Z"),
+            reason: Some("holiday freeze".to_string()),
+        };
+        assert!(freeze.contains(at("2026-12-25T12:00:00Z")));
+        assert!(!freeze.contains(at("2027-01-05T00:00:00Z")));
+        assert_eq!(
+            active_window([&freeze], at("2026-12-21T00:00:00Z")),
+            Some(&freeze)
+        );
+        assert!(freeze.describe().ends_with("(holiday freeze)"));
+
+        assert!(validate_windows(std::slice::from_ref(&freeze)).is_ok());
+        assert!(weekly(vec![], "9am", "18:00", None).validate().is_err());
+        assert!(weekly(vec![], "09:00", "18:00", Some("CEST"))
+            .validate()
+            .is_err());
+        let backwards = BlackoutWindow::Period {
+            start: at("2027-01-05T00:00:00Z"),
+            end: at("2026-12-20T00:00:00Z"),
+            reason: None,
+        };
+        assert!(backwards.validate().is_err());
+    }
+}
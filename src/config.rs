@@ -11,6 +11,7 @@
 //! - `OPENCODE_AGENT` - Optional. Default OpenCode agent name (e.g., `Sisyphus`, `oracle`).
 //! - `OPENCODE_PERMISSIVE` - Optional. If true, auto-allows all permissions for OpenCode sessions (default: true).
 //! - `SANDBOXED_USERS` or `SANDBOXED_SH_USERS` (legacy) - Optional. JSON array of user accounts for multi-user auth.
+//! - `SANDBOXED_TENANTS` - Optional. JSON array of tenants (see `crate::tenant`). Users join one via their `tenant` field.
 //! - `LIBRARY_GIT_SSH_KEY` - Optional. SSH key path for library git operations. If set to a path, uses that key.
 //!   If set to empty string, ignores ~/.ssh/config (useful when the config specifies a non-existent key).
 //!   If unset, uses default SSH behavior.
@@ -19,6 +20,8 @@
 //!   If not set, defaults to: https://github.com/Th0rgal/sandboxed-library-template.git
 //! - `DEFAULT_BACKEND` - Optional. Default backend to use (claudecode, opencode, or amp).
 //!   If not set, defaults to the first available backend with priority: claudecode → opencode → amp.
+//! - `SSE_KEEPALIVE_SECS`, `EVENT_BROADCAST_CAPACITY`, `SSE_COALESCE_TEXT_DELTA_MS` - Optional.
+//!   Control stream tuning (see `StreamConfig`).
 //!
 //! Note: The agent has **full system access**. It can read/write any file, execute any command,
 //! and search anywhere on the machine. The `WORKING_DIR` is just the default for relative paths.
@@ -27,6 +30,8 @@ use serde::Deserialize;
 use std::path::PathBuf;
 use thiserror::Error;
 
+use crate::tenant::TenantConfig;
+
 #[derive(Debug, Error)]
 pub enum ConfigError {
     #[error("Missing required environment variable: {0}")]
@@ -181,6 +186,68 @@ impl ContextConfig {
     }
 }
 
+/// Control stream (SSE) tuning, for deployments behind proxies that close
+/// idle connections early or struggle with many small events.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StreamConfig {
+    /// Seconds between SSE keepalive comments
+    pub keepalive_secs: u64,
+    /// Capacity of each control session's event broadcast channel
+    pub broadcast_capacity: usize,
+    /// Milliseconds a stream waits to merge a burst of `text_delta` events
+    /// into the latest one per mission (0 = every delta is sent)
+    pub coalesce_text_delta_ms: u64,
+}
+
+impl Default for StreamConfig {
+    fn default() -> Self {
+        Self {
+            keepalive_secs: 15,
+            broadcast_capacity: 1024,
+            coalesce_text_delta_ms: 0,
+        }
+    }
+}
+
+impl StreamConfig {
+    /// Load from environment variables, falling back to defaults. Zero
+    /// keepalive intervals and capacities are ignored.
+    pub fn from_env() -> Self {
+        let mut config = Self::default();
+
+        if let Ok(v) = std::env::var("SSE_KEEPALIVE_SECS") {
+            if let Ok(n) = v.trim().parse::<u64>() {
+                if n > 0 {
+                    config.keepalive_secs = n;
+                }
+            }
+        }
+        if let Ok(v) = std::env::var("EVENT_BROADCAST_CAPACITY") {
+            if let Ok(n) = v.trim().parse::<usize>() {
+                if n > 0 {
+                    config.broadcast_capacity = n;
+                }
+            }
+        }
+        if let Ok(v) = std::env::var("SSE_COALESCE_TEXT_DELTA_MS") {
+            if let Ok(n) = v.trim().parse() {
+                config.coalesce_text_delta_ms = n;
+            }
+        }
+
+        config
+    }
+
+    pub fn keepalive_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.keepalive_secs.max(1))
+    }
+
+    pub fn coalesce_window(&self) -> Option<std::time::Duration> {
+        (self.coalesce_text_delta_ms > 0)
+            .then(|| std::time::Duration::from_millis(self.coalesce_text_delta_ms))
+    }
+}
+
 /// Agent configuration.
 #[derive(Debug, Clone)]
 pub struct Config {
@@ -203,6 +270,9 @@ pub struct Config {
     /// Hours of inactivity after which an active mission is auto-closed (0 = disabled)
     pub stale_mission_hours: u64,
 
+    /// Hours between background maintenance passes on the mission store (0 = disabled)
+    pub store_maintenance_hours: u64,
+
     /// Maximum number of missions that can run in parallel (1 = sequential only)
     pub max_parallel_missions: usize,
 
@@ -215,6 +285,9 @@ pub struct Config {
     /// Context injection configuration
     pub context: ContextConfig,
 
+    /// Control stream (SSE) tuning
+    pub stream: StreamConfig,
+
     /// DEPRECATED: OpenCode server base URL (no longer used for mission execution)
     pub opencode_base_url: String,
 
@@ -233,6 +306,30 @@ pub struct Config {
 
     /// Whether mission automations are enabled
     pub automations_enabled: bool,
+
+    /// Running missions at which interval automations are deferred (0 = disabled)
+    pub automation_throttle_running: usize,
+
+    /// Seconds interval automations are deferred after a provider rate limit (0 = disabled)
+    pub automation_rate_limit_backoff_secs: u64,
+
+    /// Whether the deterministic mock LLM backend is registered
+    /// (`MOCK_LLM=true`, or implied by `DEFAULT_BACKEND=mock` / `MOCK_LLM_SCRIPT`)
+    pub mock_llm_enabled: bool,
+
+    /// JSON script of responses for the mock LLM backend
+    pub mock_llm_script: Option<PathBuf>,
+
+    /// Ollama daemon URL for the `ollama` backend (`OLLAMA_BASE_URL`); config
+    /// profiles can override it
+    pub ollama_base_url: String,
+
+    /// Tenants hosted on this instance (`SANDBOXED_TENANTS`)
+    pub tenants: Vec<TenantConfig>,
+
+    /// Tenant a per-session copy of the config is scoped to (`None` at the
+    /// instance level and for users without a tenant)
+    pub tenant: Option<TenantConfig>,
 }
 
 /// API auth configuration.
@@ -278,6 +375,9 @@ pub struct UserAccount {
     pub id: String,
     pub username: String,
     pub password: String,
+    /// Tenant the user belongs to (an id from `SANDBOXED_TENANTS`).
+    #[serde(default)]
+    pub tenant: Option<String>,
 }
 
 impl AuthConfig {
@@ -361,6 +461,16 @@ impl Config {
                 ConfigError::InvalidValue("STALE_MISSION_HOURS".to_string(), format!("{}", e))
             })?;
 
+        // Hours between background maintenance passes on the mission store
+        // (WAL checkpoint, incremental vacuum, ANALYZE). Default: 24. Set to 0
+        // to disable.
+        let store_maintenance_hours = std::env::var("STORE_MAINTENANCE_HOURS")
+            .unwrap_or_else(|_| "24".to_string())
+            .parse()
+            .map_err(|e| {
+                ConfigError::InvalidValue("STORE_MAINTENANCE_HOURS".to_string(), format!("{}", e))
+            })?;
+
         // Maximum parallel missions (default: 1 = sequential)
         let max_parallel_missions = std::env::var("MAX_PARALLEL_MISSIONS")
             .unwrap_or_else(|_| "1".to_string())
@@ -402,6 +512,26 @@ impl Config {
             })
             .collect::<Vec<_>>();
 
+        let tenants = std::env::var("SANDBOXED_TENANTS")
+            .ok()
+            .filter(|raw| !raw.trim().is_empty())
+            .map(|raw| {
+                crate::tenant::parse_tenants(&raw)
+                    .map_err(|e| ConfigError::InvalidValue("SANDBOXED_TENANTS".to_string(), e))
+            })
+            .transpose()?
+            .unwrap_or_default();
+        if let Some(user) = users.iter().find(|u| {
+            u.tenant
+                .as_deref()
+                .is_some_and(|t| !tenants.iter().any(|tenant| tenant.id == t))
+        }) {
+            return Err(ConfigError::InvalidValue(
+                "SANDBOXED_USERS/SANDBOXED_SH_USERS".to_string(),
+                format!("user '{}' references an unknown tenant", user.username),
+            ));
+        }
+
         let auth = AuthConfig {
             dashboard_password: std::env::var("DASHBOARD_PASSWORD").ok(),
             jwt_secret: std::env::var("JWT_SECRET").ok(),
@@ -461,6 +591,7 @@ impl Config {
         }
 
         let context = ContextConfig::from_env();
+        let stream = StreamConfig::from_env();
 
         // Library configuration
         // Note: library_remote is now managed via the settings module (persisted to disk)
@@ -471,10 +602,11 @@ impl Config {
         // Default backend configuration
         let default_backend = std::env::var("DEFAULT_BACKEND").ok().and_then(|v| {
             let backend = v.trim().to_lowercase();
-            if backend.is_empty() || !["claudecode", "opencode", "amp"].contains(&backend.as_str())
+            if backend.is_empty()
+                || !["claudecode", "opencode", "amp", "mock"].contains(&backend.as_str())
             {
                 tracing::warn!(
-                    "Invalid DEFAULT_BACKEND '{}'. Expected one of: claudecode, opencode, amp",
+                    "Invalid DEFAULT_BACKEND '{}'. Expected one of: claudecode, opencode, amp, mock",
                     v
                 );
                 None
@@ -492,6 +624,48 @@ impl Config {
             .transpose()?
             .unwrap_or(true);
 
+        // Defer interval automations while the session is loaded: at this many
+        // running missions (default: 0 = disabled), and for this long after a
+        // provider rate limit (default: 300 seconds, 0 = disabled).
+        let automation_throttle_running = std::env::var("AUTOMATION_THROTTLE_RUNNING")
+            .unwrap_or_else(|_| "0".to_string())
+            .parse()
+            .map_err(|e| {
+                ConfigError::InvalidValue(
+                    "AUTOMATION_THROTTLE_RUNNING".to_string(),
+                    format!("{}", e),
+                )
+            })?;
+        let automation_rate_limit_backoff_secs =
+            std::env::var("AUTOMATION_RATE_LIMIT_BACKOFF_SECS")
+                .unwrap_or_else(|_| "300".to_string())
+                .parse()
+                .map_err(|e| {
+                    ConfigError::InvalidValue(
+                        "AUTOMATION_RATE_LIMIT_BACKOFF_SECS".to_string(),
+                        format!("{}", e),
+                    )
+                })?;
+
+        let mock_llm_script = std::env::var("MOCK_LLM_SCRIPT")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .map(PathBuf::from);
+        let mock_llm_enabled = std::env::var("MOCK_LLM")
+            .ok()
+            .map(|v| {
+                parse_bool(&v).map_err(|e| ConfigError::InvalidValue("MOCK_LLM".to_string(), e))
+            })
+            .transpose()?
+            .unwrap_or(false)
+            || mock_llm_script.is_some()
+            || default_backend.as_deref() == Some("mock");
+
+        let ollama_base_url = std::env::var("OLLAMA_BASE_URL")
+            .ok()
+            .filter(|v| !v.trim().is_empty())
+            .unwrap_or_else(|| crate::backend::ollama::DEFAULT_BASE_URL.to_string());
+
         Ok(Self {
             default_model,
             working_dir,
@@ -499,16 +673,25 @@ impl Config {
             port,
             max_iterations,
             stale_mission_hours,
+            store_maintenance_hours,
             max_parallel_missions,
             dev_mode,
             auth,
             context,
+            stream,
             opencode_base_url,
             opencode_agent,
             opencode_permissive,
             library_path,
             default_backend,
             automations_enabled,
+            automation_throttle_running,
+            automation_rate_limit_backoff_secs,
+            mock_llm_enabled,
+            mock_llm_script,
+            ollama_base_url,
+            tenants,
+            tenant: None,
         })
     }
 
@@ -522,18 +705,32 @@ impl Config {
             port: 3000,
             max_iterations: 50,
             stale_mission_hours: 2,
+            store_maintenance_hours: 24,
             max_parallel_missions: 1,
             dev_mode: true,
             auth: AuthConfig::default(),
             context: ContextConfig::default(),
+            stream: StreamConfig::default(),
             opencode_base_url: "http://127.0.0.1:4096".to_string(),
             opencode_agent: None,
             opencode_permissive: true,
             library_path,
             default_backend: None,
             automations_enabled: true,
+            automation_throttle_running: 0,
+            automation_rate_limit_backoff_secs: 300,
+            mock_llm_enabled: false,
+            mock_llm_script: None,
+            ollama_base_url: crate::backend::ollama::DEFAULT_BASE_URL.to_string(),
+            tenants: Vec::new(),
+            tenant: None,
         }
     }
+
+    /// Look up a configured tenant by id.
+    pub fn find_tenant(&self, id: &str) -> Option<&TenantConfig> {
+        self.tenants.iter().find(|t| t.id == id)
+    }
 }
 
 fn parse_bool(value: &str) -> Result<bool, String> {
@@ -274,6 +274,23 @@ pub fn pricing_for_model(model: &str) -> Option<ModelPricing> {
     }
 }
 
+/// Context window (input + output tokens) of a model, for reporting how full
+/// a call left it. Returns None if model is unknown.
+pub fn context_window_for_model(model: &str) -> Option<u64> {
+    match normalize_model(model) {
+        "claude-3-5-sonnet" | "claude-sonnet-5" | "claude-sonnet-4" | "claude-3-5-haiku"
+        | "claude-3-opus" | "claude-opus-4-6" | "claude-opus-4" => Some(200_000),
+        "gpt-4o" | "gpt-4o-mini" | "gpt-4-turbo" => Some(128_000),
+        "gpt-4" => Some(8_192),
+        "gpt-5" => Some(400_000),
+        "o3" | "o4-mini" => Some(200_000),
+        "gemini-3.1-pro" | "gemini-3-pro" | "gemini-3-flash" | "gemini-2.5-pro"
+        | "gemini-2.5-flash" | "gemini-2.0-flash" | "gemini-1.5-flash" => Some(1_048_576),
+        "gemini-1.5-pro" => Some(2_097_152),
+        _ => None,
+    }
+}
+
 /// Calculate cost in cents from token usage and model.
 ///
 /// Returns 0 if:
@@ -0,0 +1,523 @@
+//! Advisory network egress policy for mission tool processes.
+//!
+//! A workspace can list the hosts its missions are expected to reach. The
+//! allowlist holds domains (`github.com`, `*.githubusercontent.com`) and CIDRs
+//! (`10.0.0.0/8`, `192.168.1.20`). While a mission turn runs in a container
+//! workspace, the agent CLI and every tool it spawns get `HTTP_PROXY` /
+//! `HTTPS_PROXY` pointing at a filtering proxy on the host loopback. The proxy
+//! handles `CONNECT` tunnels and plain HTTP requests, checks the target, and
+//! reports each destination outside the allowlist once per turn.
+//!
+//! With `proxy_block`, the proxy refuses destinations outside the allowlist.
+//! Without it they are let through and only reported, which is useful for
+//! building an allowlist. `proxy_block_automations` does the same for missions
+//! driven by active automations, whose input usually comes from outside.
+//!
+//! This is not a sandbox: only traffic sent through the proxy is checked.
+//! Clients that ignore the proxy variables (raw sockets, `--noproxy`, git over
+//! SSH, DNS) reach the network unfiltered. Loopback addresses and the model
+//! provider hosts in [`BUILTIN_ALLOW`] are always reachable so the agent
+//! itself keeps working.
+
+use std::collections::HashSet;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// Hosts the agent CLIs need to talk to their model providers.
+pub const BUILTIN_ALLOW: &[&str] = &[
+    "api.anthropic.com",
+    "statsig.anthropic.com",
+    "api.openai.com",
+    "chatgpt.com",
+    "auth.openai.com",
+    "ampcode.com",
+    "openrouter.ai",
+    "generativelanguage.googleapis.com",
+];
+
+/// Largest request head the proxy reads before giving up.
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Per-workspace egress settings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EgressPolicy {
+    /// Domains (optionally `*.` wildcards) and CIDRs that may be reached.
+    #[serde(default)]
+    pub allow: Vec<String>,
+    /// Have the proxy refuse connections to anything not on the allowlist.
+    #[serde(default, alias = "default_deny")]
+    pub proxy_block: bool,
+    /// Have the proxy refuse non-allowlisted connections for missions with
+    /// active automations, even when `proxy_block` is off.
+    #[serde(default, alias = "deny_automations")]
+    pub proxy_block_automations: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Rule {
+    Domain(String),
+    /// `*.example.com`, stored as `example.com`; matches subdomains only.
+    Wildcard(String),
+    Cidr(IpAddr, u8),
+}
+
+impl Rule {
+    fn parse(entry: &str) -> anyhow::Result<Self> {
+        let entry = entry.trim().to_ascii_lowercase();
+        if let Some((addr, prefix)) = entry.split_once('/') {
+            let addr: IpAddr = addr
+                .parse()
+                .map_err(|_| anyhow::anyhow!("Invalid CIDR '{}'", entry))?;
+            let max = if addr.is_ipv4() { 32 } else { 128 };
+            let prefix: u8 = prefix
+                .parse()
+                .ok()
+                .filter(|p| *p <= max)
+                .ok_or_else(|| anyhow::anyhow!("Invalid CIDR '{}'", entry))?;
+            return Ok(Rule::Cidr(addr, prefix));
+        }
+        if let Ok(addr) = entry.parse::<IpAddr>() {
+            let prefix = if addr.is_ipv4() { 32 } else { 128 };
+            return Ok(Rule::Cidr(addr, prefix));
+        }
+        let (wildcard, domain) = match entry.strip_prefix("*.") {
+            Some(rest) => (true, rest),
+            None => (false, entry.as_str()),
+        };
+        let valid = !domain.is_empty()
+            && domain
+                .split('.')
+                .all(|label| !label.is_empty() && label.chars().all(is_domain_char));
+        if !valid {
+            anyhow::bail!("Invalid egress allowlist entry '{}'", entry);
+        }
+        Ok(if wildcard {
+            Rule::Wildcard(domain.to_string())
+        } else {
+            Rule::Domain(domain.to_string())
+        })
+    }
+
+    fn matches_host(&self, host: &str) -> bool {
+        match self {
+            Rule::Domain(domain) => host == domain,
+            Rule::Wildcard(domain) => host
+                .strip_suffix(domain.as_str())
+                .is_some_and(|prefix| prefix.ends_with('.') && prefix.len() > 1),
+            Rule::Cidr(..) => false,
+        }
+    }
+
+    fn matches_addr(&self, addr: IpAddr) -> bool {
+        let Rule::Cidr(net, prefix) = self else {
+            return false;
+        };
+        match (net, addr) {
+            (IpAddr::V4(net), IpAddr::V4(addr)) => {
+                let mask = u32::MAX.checked_shl(32 - *prefix as u32).unwrap_or(0);
+                u32::from(*net) & mask == u32::from(addr) & mask
+            }
+            (IpAddr::V6(net), IpAddr::V6(addr)) => {
+                let mask = u128::MAX.checked_shl(128 - *prefix as u32).unwrap_or(0);
+                u128::from(*net) & mask == u128::from(addr) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+fn is_domain_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '-' || c == '_'
+}
+
+impl EgressPolicy {
+    /// Validate and normalize entries. Returns `None` when the policy has no
+    /// effect (empty allowlist and no block options).
+    pub fn normalized(mut self) -> anyhow::Result<Option<Self>> {
+        let mut allow = Vec::new();
+        for entry in &self.allow {
+            let entry = entry.trim().to_ascii_lowercase();
+            if entry.is_empty() {
+                continue;
+            }
+            Rule::parse(&entry)?;
+            if !allow.contains(&entry) {
+                allow.push(entry);
+            }
+        }
+        self.allow = allow;
+        Ok(
+            if self.allow.is_empty() && !self.proxy_block && !self.proxy_block_automations {
+                None
+            } else {
+                Some(self)
+            },
+        )
+    }
+
+    /// Whether the proxy refuses non-allowlisted connections for a mission.
+    pub fn blocks(&self, automated: bool) -> bool {
+        self.proxy_block || (automated && self.proxy_block_automations)
+    }
+}
+
+/// A connection to a destination outside the allowlist.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EgressViolation {
+    pub host: String,
+    pub port: u16,
+    /// The connection was refused (false when the policy only reports).
+    pub blocked: bool,
+}
+
+/// Matches destinations against a policy.
+#[derive(Debug, Clone)]
+struct Matcher {
+    rules: Vec<Rule>,
+}
+
+impl Matcher {
+    fn new(policy: &EgressPolicy) -> Self {
+        let mut rules: Vec<Rule> = policy
+            .allow
+            .iter()
+            .filter_map(|entry| Rule::parse(entry).ok())
+            .collect();
+        rules.extend(BUILTIN_ALLOW.iter().filter_map(|h| Rule::parse(h).ok()));
+        Self { rules }
+    }
+
+    /// Whether `host` (a name or IP literal) resolving to `addrs` is allowed.
+    /// A name is allowed when it matches a domain rule or when every address
+    /// it resolved to is inside an allowed CIDR.
+    fn allows(&self, host: &str, addrs: &[IpAddr]) -> bool {
+        let host = host.trim_end_matches('.').to_ascii_lowercase();
+        if self.rules.iter().any(|rule| rule.matches_host(&host)) {
+            return true;
+        }
+        !addrs.is_empty()
+            && addrs.iter().all(|addr| {
+                addr.is_loopback() || self.rules.iter().any(|rule| rule.matches_addr(*addr))
+            })
+    }
+}
+
+/// Filtering HTTP proxy for one mission turn. The listener stops when this is
+/// dropped.
+pub struct EgressProxy {
+    addr: SocketAddr,
+    task: JoinHandle<()>,
+}
+
+impl EgressProxy {
+    /// Start a proxy on `127.0.0.1` enforcing `policy`. Destinations outside
+    /// the allowlist are sent to `violations` once per host and port.
+    pub async fn start(
+        policy: &EgressPolicy,
+        deny: bool,
+        violations: mpsc::UnboundedSender<EgressViolation>,
+    ) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", 0)).await?;
+        let addr = listener.local_addr()?;
+        let matcher = Arc::new(Matcher::new(policy));
+        let reported = Arc::new(Mutex::new(HashSet::new()));
+        let task = tokio::spawn(async move {
+            loop {
+                let Ok((stream, _)) = listener.accept().await else {
+                    continue;
+                };
+                let matcher = Arc::clone(&matcher);
+                let reported = Arc::clone(&reported);
+                let violations = violations.clone();
+                tokio::spawn(async move {
+                    let report = |host: &str, port: u16| {
+                        let new = reported
+                            .lock()
+                            .map(|mut seen| seen.insert((host.to_string(), port)))
+                            .unwrap_or(false);
+                        if new {
+                            let _ = violations.send(EgressViolation {
+                                host: host.to_string(),
+                                port,
+                                blocked: deny,
+                            });
+                        }
+                    };
+                    if let Err(e) = handle_client(stream, &matcher, deny, report).await {
+                        tracing::debug!(error = %e, "Egress proxy connection failed");
+                    }
+                });
+            }
+        });
+        Ok(Self { addr, task })
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// Environment variables that route HTTP clients through the proxy.
+    pub fn env(&self) -> Vec<(String, String)> {
+        let url = self.url();
+        let no_proxy = "localhost,127.0.0.1,::1".to_string();
+        vec![
+            ("HTTP_PROXY".to_string(), url.clone()),
+            ("HTTPS_PROXY".to_string(), url.clone()),
+            ("ALL_PROXY".to_string(), url.clone()),
+            ("http_proxy".to_string(), url.clone()),
+            ("https_proxy".to_string(), url.clone()),
+            ("all_proxy".to_string(), url),
+            ("NO_PROXY".to_string(), no_proxy.clone()),
+            ("no_proxy".to_string(), no_proxy),
+        ]
+    }
+}
+
+impl Drop for EgressProxy {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Parsed first line of a proxied request.
+#[derive(Debug, PartialEq)]
+struct Target {
+    host: String,
+    port: u16,
+    /// `CONNECT` tunnel rather than a plain HTTP request.
+    tunnel: bool,
+    /// Request line rewritten to origin form (plain HTTP only).
+    request_line: Option<String>,
+}
+
+fn split_host_port(authority: &str, default_port: u16) -> Option<(String, u16)> {
+    if let Some(rest) = authority.strip_prefix('[') {
+        let (host, tail) = rest.split_once(']')?;
+        let port = match tail.strip_prefix(':') {
+            Some(port) => port.parse().ok()?,
+            None => default_port,
+        };
+        return Some((host.to_string(), port));
+    }
+    match authority.rsplit_once(':') {
+        Some((host, port)) => Some((host.to_string(), port.parse().ok()?)),
+        None => Some((authority.to_string(), default_port)),
+    }
+}
+
+fn parse_request_line(line: &str) -> Option<Target> {
+    let mut parts = line.split_whitespace();
+    let method = parts.next()?;
+    let uri = parts.next()?;
+    let version = parts.next()?;
+    if method.eq_ignore_ascii_case("CONNECT") {
+        let (host, port) = split_host_port(uri, 443)?;
+        return Some(Target {
+            host,
+            port,
+            tunnel: true,
+            request_line: None,
+        });
+    }
+    let rest = uri.strip_prefix("http://")?;
+    let (authority, path) = match rest.find('/') {
+        Some(idx) => (&rest[..idx], &rest[idx..]),
+        None => (rest, "/"),
+    };
+    let authority = authority.rsplit('@').next()?;
+    let (host, port) = split_host_port(authority, 80)?;
+    Some(Target {
+        host,
+        port,
+        tunnel: false,
+        request_line: Some(format!("{} {} {}", method, path, version)),
+    })
+}
+
+async fn handle_client<F>(
+    mut client: TcpStream,
+    matcher: &Matcher,
+    deny: bool,
+    report: F,
+) -> anyhow::Result<()>
+where
+    F: Fn(&str, u16),
+{
+    let mut buf = Vec::with_capacity(4096);
+    let head_end = loop {
+        if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos + 4;
+        }
+        if buf.len() > MAX_HEAD_BYTES {
+            anyhow::bail!("request head too large");
+        }
+        let mut chunk = [0u8; 4096];
+        let n = client.read(&mut chunk).await?;
+        if n == 0 {
+            return Ok(());
+        }
+        buf.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&buf[..head_end]).to_string();
+    let first_line = head.lines().next().unwrap_or_default();
+    let Some(target) = parse_request_line(first_line) else {
+        client
+            .write_all(b"HTTP/1.1 400 Bad Request\r\nContent-Length: 0\r\n\r\n")
+            .await?;
+        return Ok(());
+    };
+
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((target.host.as_str(), target.port))
+        .await
+        .map(|addrs| addrs.collect())
+        .unwrap_or_default();
+    let ips: Vec<IpAddr> = addrs.iter().map(|a| a.ip()).collect();
+    if !matcher.allows(&target.host, &ips) {
+        report(&target.host, target.port);
+        if deny {
+            let body = format!(
+                "Egress to {}:{} is blocked by the workspace network policy\n",
+                target.host, target.port
+            );
+            let response = format!(
+                "HTTP/1.1 403 Forbidden\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            client.write_all(response.as_bytes()).await?;
+            return Ok(());
+        }
+    }
+
+    let Some(addr) = addrs.first() else {
+        client
+            .write_all(b"HTTP/1.1 502 Bad Gateway\r\nContent-Length: 0\r\n\r\n")
+            .await?;
+        return Ok(());
+    };
+    let mut upstream = match tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect(addr)).await {
+        Ok(Ok(stream)) => stream,
+        _ => {
+            client
+                .write_all(b"HTTP/1.1 502 Bad Gateway\r\nContent-Length: 0\r\n\r\n")
+                .await?;
+            return Ok(());
+        }
+    };
+
+    if target.tunnel {
+        client
+            .write_all(b"HTTP/1.1 200 Connection Established\r\n\r\n")
+            .await?;
+        if buf.len() > head_end {
+            upstream.write_all(&buf[head_end..]).await?;
+        }
+    } else {
+        let request_line = target.request_line.unwrap_or_default();
+        let rest = head
+            .split_once("\r\n")
+            .map(|(_, rest)| rest)
+            .unwrap_or_default();
+        upstream
+            .write_all(format!("{}\r\n{}", request_line, rest).as_bytes())
+            .await?;
+        upstream.write_all(&buf[head_end..]).await?;
+    }
+    tokio::io::copy_bidirectional(&mut client, &mut upstream).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(allow: &[&str]) -> EgressPolicy {
+        EgressPolicy {
+            allow: allow.iter().map(|s| s.to_string()).collect(),
+            proxy_block: true,
+            proxy_block_automations: false,
+        }
+    }
+
+    #[test]
+    fn allowlist_matches_domains_and_cidrs() {
+        let matcher = Matcher::new(&policy(&["github.com", "*.example.org", "10.0.0.0/8"]));
+        assert!(matcher.allows("github.com", &[]));
+        assert!(matcher.allows("GitHub.com.", &[]));
+        assert!(!matcher.allows("api.github.com", &[]));
+        assert!(matcher.allows("cdn.example.org", &[]));
+        assert!(!matcher.allows("example.org", &[]));
+        assert!(!matcher.allows("badexample.org", &[]));
+        assert!(matcher.allows("api.anthropic.com", &[]));
+
+        let inside: IpAddr = "10.1.2.3".parse().unwrap();
+        let outside: IpAddr = "11.0.0.1".parse().unwrap();
+        assert!(matcher.allows("internal.corp", &[inside]));
+        assert!(!matcher.allows("internal.corp", &[inside, outside]));
+        assert!(!matcher.allows("unknown.corp", &[]));
+        assert!(matcher.allows("localhost", &["127.0.0.1".parse().unwrap()]));
+    }
+
+    #[test]
+    fn normalized_validates_entries() {
+        assert_eq!(EgressPolicy::default().normalized().unwrap(), None);
+        let normalized = EgressPolicy {
+            allow: vec![" GitHub.com ".into(), "github.com".into(), "".into()],
+            ..Default::default()
+        }
+        .normalized()
+        .unwrap()
+        .unwrap();
+        assert_eq!(normalized.allow, vec!["github.com"]);
+        assert!(policy(&["10.0.0.0/33"]).normalized().is_err());
+        assert!(policy(&["foo..com"]).normalized().is_err());
+        assert!(policy(&["http://x.com"]).normalized().is_err());
+        assert!(policy(&["::1/128", "192.168.1.20"]).normalized().is_ok());
+
+        let automations = EgressPolicy {
+            proxy_block_automations: true,
+            ..Default::default()
+        };
+        assert!(automations.blocks(true));
+        assert!(!automations.blocks(false));
+
+        // Policies stored before the rename keep their settings.
+        let legacy: EgressPolicy =
+            serde_json::from_value(serde_json::json!({ "default_deny": true })).unwrap();
+        assert!(legacy.proxy_block);
+    }
+
+    #[test]
+    fn parses_proxy_request_lines() {
+        assert_eq!(
+            parse_request_line("CONNECT github.com:443 HTTP/1.1"),
+            Some(Target {
+                host: "github.com".into(),
+                port: 443,
+                tunnel: true,
+                request_line: None,
+            })
+        );
+        assert_eq!(
+            parse_request_line("GET http://example.com:8080/a?b=1 HTTP/1.1"),
+            Some(Target {
+                host: "example.com".into(),
+                port: 8080,
+                tunnel: false,
+                request_line: Some("GET /a?b=1 HTTP/1.1".into()),
+            })
+        );
+        let v6 = parse_request_line("CONNECT [::1]:8443 HTTP/1.1").unwrap();
+        assert_eq!((v6.host.as_str(), v6.port), ("::1", 8443));
+        assert_eq!(parse_request_line("GET /relative HTTP/1.1"), None);
+    }
+}
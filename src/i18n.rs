@@ -0,0 +1,255 @@
+//! Localization of agent-facing metadata.
+//!
+//! Activity labels, notification templates and the word lists used by the
+//! title heuristics come from message catalogs: one JSON file per language in
+//! `locales/` (bundled into the binary), optionally overridden or extended by
+//! `<locale>.json` files in `SANDBOXED_SH_LOCALES_DIR`. Keys missing from a
+//! catalog fall back to English.
+//!
+//! A mission's locale is its workspace's `locale`, else the user default from
+//! settings (`locale`), else `SANDBOXED_SH_LOCALE`, else `en`. Locale tags are
+//! reduced to their language (`de-CH` → `de`).
+
+use std::collections::HashMap;
+use std::sync::{LazyLock, RwLock};
+
+use uuid::Uuid;
+
+pub const DEFAULT_LOCALE: &str = "en";
+
+const BUNDLED: &[(&str, &str)] = &[
+    ("de", include_str!("../locales/de.json")),
+    ("en", include_str!("../locales/en.json")),
+    ("es", include_str!("../locales/es.json")),
+    ("fr", include_str!("../locales/fr.json")),
+];
+
+/// Messages of one language.
+#[derive(Debug, Default)]
+pub struct Catalog {
+    locale: String,
+    messages: HashMap<String, serde_json::Value>,
+}
+
+static CATALOGS: LazyLock<HashMap<String, Catalog>> = LazyLock::new(load_catalogs);
+
+/// Locale per workspace, kept in sync by the workspace store.
+static WORKSPACE_LOCALES: LazyLock<RwLock<HashMap<Uuid, String>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// The user default locale from settings (`None` until set).
+static DEFAULT_LOCALE_CACHED: RwLock<Option<String>> = RwLock::new(None);
+
+fn parse_catalog(locale: &str, contents: &str) -> Result<Catalog, String> {
+    let messages = serde_json::from_str(contents).map_err(|e| e.to_string())?;
+    Ok(Catalog {
+        locale: locale.to_string(),
+        messages,
+    })
+}
+
+fn load_catalogs() -> HashMap<String, Catalog> {
+    let mut catalogs: HashMap<String, Catalog> = BUNDLED
+        .iter()
+        .map(|(locale, contents)| {
+            let catalog = parse_catalog(locale, contents).expect("bundled catalogs are valid");
+            (locale.to_string(), catalog)
+        })
+        .collect();
+
+    let Some(dir) = std::env::var("SANDBOXED_SH_LOCALES_DIR")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+    else {
+        return catalogs;
+    };
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) => {
+            tracing::warn!("Failed to read locales directory {}: {}", dir, e);
+            return catalogs;
+        }
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(locale) = path.file_stem().and_then(|s| s.to_str()).map(normalize) else {
+            continue;
+        };
+        let parsed = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|contents| parse_catalog(&locale, &contents));
+        match parsed {
+            // Files extend the bundled catalog of the same language
+            Ok(catalog) => catalogs
+                .entry(locale.clone())
+                .or_insert_with(|| Catalog {
+                    locale,
+                    ..Default::default()
+                })
+                .messages
+                .extend(catalog.messages),
+            Err(e) => tracing::warn!("Ignoring locale file {}: {}", path.display(), e),
+        }
+    }
+    catalogs
+}
+
+/// Reduce a locale tag to its lowercase language (`pt-BR` → `pt`).
+pub fn normalize(tag: &str) -> String {
+    tag.trim()
+        .split(['-', '_', '.'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// Languages with a catalog, sorted.
+pub fn available_locales() -> Vec<&'static str> {
+    let mut locales: Vec<&str> = CATALOGS.keys().map(String::as_str).collect();
+    locales.sort_unstable();
+    locales
+}
+
+pub fn is_supported(tag: &str) -> bool {
+    CATALOGS.contains_key(&normalize(tag))
+}
+
+/// The catalog for a locale tag, or English when there is none.
+pub fn catalog(tag: &str) -> &'static Catalog {
+    CATALOGS
+        .get(&normalize(tag))
+        .or_else(|| CATALOGS.get(DEFAULT_LOCALE))
+        .expect("the English catalog is bundled")
+}
+
+/// The user default locale: the setting, else `SANDBOXED_SH_LOCALE`, else `en`.
+pub fn default_locale() -> String {
+    let cached = DEFAULT_LOCALE_CACHED
+        .read()
+        .ok()
+        .and_then(|locale| locale.clone());
+    cached
+        .or_else(|| {
+            std::env::var("SANDBOXED_SH_LOCALE")
+                .ok()
+                .map(|v| normalize(&v))
+                .filter(|v| !v.is_empty())
+        })
+        .unwrap_or_else(|| DEFAULT_LOCALE.to_string())
+}
+
+/// Update the cached user default locale.
+/// Called during startup and when the setting is changed via the API.
+pub fn set_default_locale_cached(locale: Option<&str>) {
+    if let Ok(mut cached) = DEFAULT_LOCALE_CACHED.write() {
+        *cached = locale.map(normalize).filter(|l| !l.is_empty());
+    }
+}
+
+/// Record a workspace's locale (`None` follows the user default).
+pub fn set_workspace_locale(workspace_id: Uuid, locale: Option<&str>) {
+    if let Ok(mut locales) = WORKSPACE_LOCALES.write() {
+        match locale.map(normalize).filter(|l| !l.is_empty()) {
+            Some(locale) => locales.insert(workspace_id, locale),
+            None => locales.remove(&workspace_id),
+        };
+    }
+}
+
+/// The locale of missions in a workspace.
+pub fn workspace_locale(workspace_id: Uuid) -> String {
+    WORKSPACE_LOCALES
+        .read()
+        .ok()
+        .and_then(|locales| locales.get(&workspace_id).cloned())
+        .unwrap_or_else(default_locale)
+}
+
+impl Catalog {
+    pub fn locale(&self) -> &str {
+        &self.locale
+    }
+
+    fn lookup(&self, key: &str) -> Option<&serde_json::Value> {
+        self.messages.get(key).or_else(|| {
+            CATALOGS
+                .get(DEFAULT_LOCALE)
+                .filter(|english| !std::ptr::eq(*english, self))
+                .and_then(|english| english.messages.get(key))
+        })
+    }
+
+    /// The message for `key` with `{name}` placeholders filled from `args`;
+    /// the key itself when no catalog has it.
+    pub fn text(&self, key: &str, args: &[(&str, &str)]) -> String {
+        let Some(template) = self.lookup(key).and_then(|v| v.as_str()) else {
+            return key.to_string();
+        };
+        args.iter()
+            .fold(template.to_string(), |text, (name, value)| {
+                text.replace(&format!("{{{}}}", name), value)
+            })
+    }
+
+    /// A list-valued message (empty when missing).
+    pub fn list(&self, key: &str) -> Vec<&str> {
+        self.lookup(key)
+            .and_then(|v| v.as_array())
+            .map(|items| items.iter().filter_map(|v| v.as_str()).collect())
+            .unwrap_or_default()
+    }
+
+    /// A mission status in this language.
+    pub fn status(&self, status: impl std::fmt::Display) -> String {
+        let status = status.to_string();
+        match self
+            .lookup(&format!("status.{}", status))
+            .and_then(|v| v.as_str())
+        {
+            Some(text) => text.to_string(),
+            None => status,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_catalogs_cover_english_keys() {
+        let english = catalog("en");
+        for locale in available_locales() {
+            let catalog = catalog(locale);
+            for key in english.messages.keys() {
+                assert!(
+                    catalog.messages.contains_key(key),
+                    "{} is missing {}",
+                    locale,
+                    key
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn messages_fall_back_and_fill_placeholders() {
+        assert_eq!(catalog("de-CH").locale(), "de");
+        assert_eq!(catalog("xx").locale(), "en");
+        assert_eq!(
+            catalog("de").text("activity.reading", &[("file", "main.rs")]),
+            "Liest: main.rs"
+        );
+        assert_eq!(catalog("fr").status("not_feasible"), "irréalisable");
+        assert_eq!(catalog("es").text("missing.key", &[]), "missing.key");
+        assert!(catalog("en").list("metadata.stopwords").contains(&"the"));
+
+        let workspace = Uuid::new_v4();
+        set_workspace_locale(workspace, Some("FR_fr"));
+        assert_eq!(workspace_locale(workspace), "fr");
+        set_workspace_locale(workspace, None);
+        assert_eq!(workspace_locale(workspace), default_locale());
+    }
+}
@@ -0,0 +1,908 @@
+//! Kubernetes access for missions.
+//!
+//! A workspace can be granted access to a cluster with a kubeconfig stored in
+//! the secrets vault (registry [`KUBECONFIG_REGISTRY`]). Requests are made
+//! from the backend against the Kubernetes REST API, so the credential never
+//! enters the workspace, and the results come back as structured JSON.
+//!
+//! Policy is enforced here on top of the cluster's own RBAC:
+//! - only namespaces on the workspace allowlist are reachable,
+//! - mutating verbs (`delete`, `scale`, `restart`) require `allow_write`,
+//! - Secrets and cluster-scoped resources are not exposed at all.
+
+use std::time::Duration;
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// Secrets registry holding kubeconfigs, keyed by [`KubernetesAccess::secret`].
+pub const KUBECONFIG_REGISTRY: &str = "kubeconfigs";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+/// Upper bound on list results returned to the agent.
+const MAX_LIST_ITEMS: usize = 200;
+const DEFAULT_TAIL_LINES: u32 = 200;
+const MAX_TAIL_LINES: u32 = 5000;
+
+/// Per-workspace Kubernetes access settings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct KubernetesAccess {
+    /// Key of the kubeconfig in the `kubeconfigs` secrets registry.
+    pub secret: String,
+    /// kubeconfig context to use (defaults to `current-context`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// Namespaces missions may access. The first one is the default.
+    #[serde(default)]
+    pub namespaces: Vec<String>,
+    /// Allow mutating verbs. Access is read-only unless this is set.
+    #[serde(default)]
+    pub allow_write: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum KubeVerb {
+    Get,
+    Describe,
+    Logs,
+    Events,
+    Delete,
+    Scale,
+    Restart,
+}
+
+impl KubeVerb {
+    pub fn is_write(self) -> bool {
+        matches!(self, KubeVerb::Delete | KubeVerb::Scale | KubeVerb::Restart)
+    }
+}
+
+/// A single operation requested by a mission.
+#[derive(Debug, Clone, Deserialize)]
+pub struct KubeRequest {
+    pub verb: KubeVerb,
+    /// Resource type (`pods`, `deploy`, `svc`, ...). Defaults to pods.
+    #[serde(default)]
+    pub resource: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub namespace: Option<String>,
+    #[serde(default)]
+    pub label_selector: Option<String>,
+    /// Container for `logs` (required for multi-container pods).
+    #[serde(default)]
+    pub container: Option<String>,
+    #[serde(default)]
+    pub tail_lines: Option<u32>,
+    #[serde(default)]
+    pub since_seconds: Option<u64>,
+    /// Logs of the previous container instance (after a restart).
+    #[serde(default)]
+    pub previous: bool,
+    /// Target replica count for `scale`.
+    #[serde(default)]
+    pub replicas: Option<u32>,
+}
+
+/// A namespaced resource type reachable through this module.
+#[derive(Debug, PartialEq)]
+pub struct ResourceKind {
+    pub plural: &'static str,
+    aliases: &'static [&'static str],
+    /// API prefix, e.g. `api/v1` or `apis/apps/v1`.
+    api: &'static str,
+    scalable: bool,
+    restartable: bool,
+}
+
+const fn kind(
+    plural: &'static str,
+    aliases: &'static [&'static str],
+    api: &'static str,
+    scalable: bool,
+    restartable: bool,
+) -> ResourceKind {
+    ResourceKind {
+        plural,
+        aliases,
+        api,
+        scalable,
+        restartable,
+    }
+}
+
+const RESOURCES: &[ResourceKind] = &[
+    kind("pods", &["pod", "po"], "api/v1", false, false),
+    kind("services", &["service", "svc"], "api/v1", false, false),
+    kind("configmaps", &["configmap", "cm"], "api/v1", false, false),
+    kind("persistentvolumeclaims", &["pvc"], "api/v1", false, false),
+    kind(
+        "serviceaccounts",
+        &["serviceaccount", "sa"],
+        "api/v1",
+        false,
+        false,
+    ),
+    kind("events", &["event", "ev"], "api/v1", false, false),
+    kind(
+        "deployments",
+        &["deployment", "deploy"],
+        "apis/apps/v1",
+        true,
+        true,
+    ),
+    kind(
+        "statefulsets",
+        &["statefulset", "sts"],
+        "apis/apps/v1",
+        true,
+        true,
+    ),
+    kind(
+        "daemonsets",
+        &["daemonset", "ds"],
+        "apis/apps/v1",
+        false,
+        true,
+    ),
+    kind(
+        "replicasets",
+        &["replicaset", "rs"],
+        "apis/apps/v1",
+        true,
+        false,
+    ),
+    kind("jobs", &["job"], "apis/batch/v1", false, false),
+    kind(
+        "cronjobs",
+        &["cronjob", "cj"],
+        "apis/batch/v1",
+        false,
+        false,
+    ),
+    kind(
+        "ingresses",
+        &["ingress", "ing"],
+        "apis/networking.k8s.io/v1",
+        false,
+        false,
+    ),
+];
+
+fn resolve_resource(name: Option<&str>) -> anyhow::Result<&'static ResourceKind> {
+    let name = name.unwrap_or("pods").trim().to_ascii_lowercase();
+    let name = name.split('.').next().unwrap_or_default();
+    RESOURCES
+        .iter()
+        .find(|r| r.plural == name || r.aliases.contains(&name))
+        .ok_or_else(|| {
+            let supported: Vec<&str> = RESOURCES.iter().map(|r| r.plural).collect();
+            anyhow::anyhow!(
+                "Resource '{}' is not accessible; supported: {}",
+                name,
+                supported.join(", ")
+            )
+        })
+}
+
+/// Check a request against the workspace policy, returning the namespace and
+/// resource it targets.
+pub fn authorize(
+    access: &KubernetesAccess,
+    req: &KubeRequest,
+) -> anyhow::Result<(String, &'static ResourceKind)> {
+    if req.verb.is_write() && !access.allow_write {
+        anyhow::bail!(
+            "'{:?}' is not allowed: Kubernetes access for this workspace is read-only",
+            req.verb
+        );
+    }
+    let namespace =
+        match req.namespace.as_deref().map(str::trim) {
+            Some(ns) if !ns.is_empty() => ns.to_string(),
+            _ => access.namespaces.first().cloned().ok_or_else(|| {
+                anyhow::anyhow!("No namespaces are allowlisted for this workspace")
+            })?,
+        };
+    if !access.namespaces.iter().any(|ns| ns == &namespace) {
+        anyhow::bail!(
+            "Namespace '{}' is not allowlisted (allowed: {})",
+            namespace,
+            access.namespaces.join(", ")
+        );
+    }
+    let resource = match req.verb {
+        KubeVerb::Logs => resolve_resource(Some("pods"))?,
+        KubeVerb::Events => resolve_resource(Some("events"))?,
+        _ => resolve_resource(req.resource.as_deref())?,
+    };
+    match req.verb {
+        KubeVerb::Scale if !resource.scalable => {
+            anyhow::bail!("{} cannot be scaled", resource.plural)
+        }
+        KubeVerb::Restart if !resource.restartable => {
+            anyhow::bail!("{} cannot be restarted", resource.plural)
+        }
+        KubeVerb::Describe
+        | KubeVerb::Logs
+        | KubeVerb::Delete
+        | KubeVerb::Scale
+        | KubeVerb::Restart
+            if req.name.as_deref().unwrap_or("").is_empty() =>
+        {
+            anyhow::bail!("'name' is required for {:?}", req.verb)
+        }
+        _ => {}
+    }
+    Ok((namespace, resource))
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// kubeconfig
+// ─────────────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Deserialize)]
+struct Kubeconfig {
+    #[serde(rename = "current-context", default)]
+    current_context: Option<String>,
+    #[serde(default)]
+    clusters: Vec<NamedCluster>,
+    #[serde(default)]
+    contexts: Vec<NamedContext>,
+    #[serde(default)]
+    users: Vec<NamedUser>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NamedCluster {
+    name: String,
+    cluster: ClusterEntry,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClusterEntry {
+    server: String,
+    #[serde(rename = "certificate-authority-data", default)]
+    certificate_authority_data: Option<String>,
+    /// CA file path, which can't be read from a kubeconfig in the vault
+    #[serde(rename = "certificate-authority", default)]
+    certificate_authority: Option<String>,
+    #[serde(rename = "insecure-skip-tls-verify", default)]
+    insecure_skip_tls_verify: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct NamedContext {
+    name: String,
+    context: ContextEntry,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContextEntry {
+    cluster: String,
+    #[serde(default)]
+    user: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct NamedUser {
+    name: String,
+    user: UserEntry,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct UserEntry {
+    #[serde(default)]
+    token: Option<String>,
+    #[serde(default)]
+    username: Option<String>,
+    #[serde(default)]
+    password: Option<String>,
+    /// Other fields, checked against [`UNSUPPORTED_AUTH`]
+    #[serde(flatten)]
+    other: std::collections::BTreeMap<String, serde_yaml::Value>,
+}
+
+/// kubeconfig user fields of auth modes the client doesn't implement, and
+/// how errors name them.
+const UNSUPPORTED_AUTH: &[(&str, &str)] = &[
+    ("exec", "exec credential plugins"),
+    ("auth-provider", "auth-provider plugins"),
+    (
+        "client-certificate-data",
+        "client certificate authentication",
+    ),
+    ("client-key-data", "client certificate authentication"),
+    ("client-certificate", "client certificate authentication"),
+    ("client-key", "client certificate authentication"),
+    ("tokenFile", "token files"),
+];
+
+fn decode_data(field: &str, data: &str) -> anyhow::Result<Vec<u8>> {
+    base64::engine::general_purpose::STANDARD
+        .decode(data.trim())
+        .map_err(|e| anyhow::anyhow!("Invalid {} in kubeconfig: {}", field, e))
+}
+
+/// HTTP client for one cluster, built from a kubeconfig context.
+pub struct KubeClient {
+    http: reqwest::Client,
+    server: String,
+    authorization: Option<String>,
+}
+
+impl KubeClient {
+    /// Build a client from kubeconfig YAML. Only embedded token or basic
+    /// credentials are supported (`kubectl config view --raw --flatten`
+    /// embeds them); other auth modes ([`UNSUPPORTED_AUTH`]) and file
+    /// references are rejected with an error naming them.
+    pub fn from_kubeconfig(yaml: &str, context: Option<&str>) -> anyhow::Result<Self> {
+        let config: Kubeconfig =
+            serde_yaml::from_str(yaml).map_err(|e| anyhow::anyhow!("Invalid kubeconfig: {}", e))?;
+        let context_name = context
+            .map(str::to_string)
+            .or(config.current_context.clone())
+            .ok_or_else(|| anyhow::anyhow!("kubeconfig has no current-context"))?;
+        let ctx = config
+            .contexts
+            .iter()
+            .find(|c| c.name == context_name)
+            .ok_or_else(|| anyhow::anyhow!("Context '{}' not found in kubeconfig", context_name))?;
+        let cluster = config
+            .clusters
+            .iter()
+            .find(|c| c.name == ctx.context.cluster)
+            .map(|c| &c.cluster)
+            .ok_or_else(|| {
+                anyhow::anyhow!("Cluster '{}' not found in kubeconfig", ctx.context.cluster)
+            })?;
+        let default_user = UserEntry::default();
+        let user = match &ctx.context.user {
+            Some(name) => config
+                .users
+                .iter()
+                .find(|u| &u.name == name)
+                .map(|u| &u.user)
+                .ok_or_else(|| anyhow::anyhow!("User '{}' not found in kubeconfig", name))?,
+            None => &default_user,
+        };
+        if let Some((field, mode)) = UNSUPPORTED_AUTH
+            .iter()
+            .find(|(field, _)| user.other.contains_key(*field))
+        {
+            anyhow::bail!(
+                "kubeconfig user uses {} (`{}`), which is not supported; \
+                 use an embedded service account token or basic credentials",
+                mode,
+                field
+            );
+        }
+        if cluster.certificate_authority.is_some() {
+            anyhow::bail!(
+                "kubeconfig cluster references a `certificate-authority` file, which is not \
+                 supported; embed it as `certificate-authority-data`"
+            );
+        }
+
+        let mut builder = reqwest::Client::builder().timeout(REQUEST_TIMEOUT);
+        if let Some(ca) = &cluster.certificate_authority_data {
+            let pem = decode_data("certificate-authority-data", ca)?;
+            builder = builder.add_root_certificate(reqwest::Certificate::from_pem(&pem)?);
+        }
+        if cluster.insecure_skip_tls_verify {
+            builder = builder.danger_accept_invalid_certs(true);
+        }
+        let authorization = match (&user.token, &user.username, &user.password) {
+            (Some(token), _, _) => Some(format!("Bearer {}", token.trim())),
+            (None, Some(username), Some(password)) => Some(format!(
+                "Basic {}",
+                base64::engine::general_purpose::STANDARD
+                    .encode(format!("{}:{}", username, password))
+            )),
+            _ => None,
+        };
+
+        Ok(Self {
+            http: builder.build()?,
+            server: cluster.server.trim_end_matches('/').to_string(),
+            authorization,
+        })
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let mut request = self
+            .http
+            .request(method, format!("{}/{}", self.server, path));
+        if let Some(auth) = &self.authorization {
+            request = request.header(reqwest::header::AUTHORIZATION, auth);
+        }
+        request
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> anyhow::Result<String> {
+        let response = request.send().await?;
+        let status = response.status();
+        let text = response.text().await?;
+        if status.is_success() {
+            return Ok(text);
+        }
+        // The API server reports failures as a `Status` object.
+        let message = serde_json::from_str::<Value>(&text)
+            .ok()
+            .and_then(|v| v.get("message").and_then(Value::as_str).map(str::to_string))
+            .unwrap_or(text);
+        anyhow::bail!("Kubernetes API error ({}): {}", status, message)
+    }
+
+    async fn send_json(&self, request: reqwest::RequestBuilder) -> anyhow::Result<Value> {
+        let text = self.send(request).await?;
+        Ok(serde_json::from_str(&text)?)
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Execution
+// ─────────────────────────────────────────────────────────────────────────────
+
+fn collection_path(resource: &ResourceKind, namespace: &str) -> String {
+    format!(
+        "{}/namespaces/{}/{}",
+        resource.api,
+        urlencoding::encode(namespace),
+        resource.plural
+    )
+}
+
+fn object_path(resource: &ResourceKind, namespace: &str, name: &str) -> String {
+    format!(
+        "{}/{}",
+        collection_path(resource, namespace),
+        urlencoding::encode(name)
+    )
+}
+
+/// Drop bookkeeping fields that are noise for an agent.
+fn strip_object(mut object: Value) -> Value {
+    if let Some(metadata) = object.get_mut("metadata").and_then(Value::as_object_mut) {
+        metadata.remove("managedFields");
+        if let Some(annotations) = metadata
+            .get_mut("annotations")
+            .and_then(Value::as_object_mut)
+        {
+            annotations.remove("kubectl.kubernetes.io/last-applied-configuration");
+        }
+    }
+    object
+}
+
+/// One-line view of an object, similar to the columns of `kubectl get`.
+fn summarize(resource: &ResourceKind, object: &Value) -> Value {
+    let mut summary = json!({
+        "name": object.pointer("/metadata/name"),
+        "created": object.pointer("/metadata/creationTimestamp"),
+    });
+    let status = object.get("status").unwrap_or(&Value::Null);
+    let spec = object.get("spec").unwrap_or(&Value::Null);
+    let extra = match resource.plural {
+        "pods" => {
+            let containers = status
+                .get("containerStatuses")
+                .and_then(Value::as_array)
+                .cloned()
+                .unwrap_or_default();
+            let ready = containers
+                .iter()
+                .filter(|c| c.get("ready").and_then(Value::as_bool) == Some(true))
+                .count();
+            let restarts: u64 = containers
+                .iter()
+                .filter_map(|c| c.get("restartCount").and_then(Value::as_u64))
+                .sum();
+            json!({
+                "phase": status.get("phase"),
+                "ready": format!("{}/{}", ready, containers.len()),
+                "restarts": restarts,
+                "node": spec.get("nodeName"),
+                "pod_ip": status.get("podIP"),
+            })
+        }
+        "deployments" | "statefulsets" | "replicasets" => json!({
+            "replicas": spec.get("replicas"),
+            "ready": status.get("readyReplicas").cloned().unwrap_or(json!(0)),
+            "updated": status.get("updatedReplicas"),
+            "available": status.get("availableReplicas"),
+        }),
+        "daemonsets" => json!({
+            "desired": status.get("desiredNumberScheduled"),
+            "ready": status.get("numberReady"),
+        }),
+        "services" => {
+            let ports: Vec<String> = spec
+                .get("ports")
+                .and_then(Value::as_array)
+                .map(|ports| {
+                    ports
+                        .iter()
+                        .map(|p| {
+                            format!(
+                                "{}/{}",
+                                p.get("port").unwrap_or(&Value::Null),
+                                p.get("protocol").and_then(Value::as_str).unwrap_or("TCP")
+                            )
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            json!({
+                "type": spec.get("type"),
+                "cluster_ip": spec.get("clusterIP"),
+                "ports": ports,
+            })
+        }
+        "jobs" => json!({
+            "active": status.get("active"),
+            "succeeded": status.get("succeeded"),
+            "failed": status.get("failed"),
+        }),
+        "cronjobs" => json!({
+            "schedule": spec.get("schedule"),
+            "suspend": spec.get("suspend"),
+            "last_schedule": status.get("lastScheduleTime"),
+        }),
+        "events" => return summarize_event(object),
+        _ => json!({ "labels": object.pointer("/metadata/labels") }),
+    };
+    if let (Some(summary), Some(extra)) = (summary.as_object_mut(), extra.as_object()) {
+        summary.extend(extra.clone());
+    }
+    summary
+}
+
+fn summarize_event(event: &Value) -> Value {
+    json!({
+        "type": event.get("type"),
+        "reason": event.get("reason"),
+        "object": format!(
+            "{}/{}",
+            event.pointer("/involvedObject/kind").and_then(Value::as_str).unwrap_or("?"),
+            event.pointer("/involvedObject/name").and_then(Value::as_str).unwrap_or("?"),
+        ),
+        "message": event.get("message"),
+        "count": event.get("count"),
+        "last_seen": event
+            .get("lastTimestamp")
+            .filter(|v| !v.is_null())
+            .or_else(|| event.get("eventTime")),
+    })
+}
+
+async fn list_events(
+    client: &KubeClient,
+    namespace: &str,
+    object_name: Option<&str>,
+) -> anyhow::Result<Vec<Value>> {
+    let events = resolve_resource(Some("events"))?;
+    let mut request = client.request(reqwest::Method::GET, &collection_path(events, namespace));
+    if let Some(name) = object_name {
+        request = request.query(&[("fieldSelector", format!("involvedObject.name={}", name))]);
+    }
+    let list = client.send_json(request).await?;
+    let mut items: Vec<Value> = list
+        .get("items")
+        .and_then(Value::as_array)
+        .map(|items| items.iter().map(summarize_event).collect())
+        .unwrap_or_default();
+    items.sort_by(|a, b| {
+        let key = |v: &Value| v["last_seen"].as_str().unwrap_or("").to_string();
+        key(b).cmp(&key(a))
+    });
+    items.truncate(MAX_LIST_ITEMS);
+    Ok(items)
+}
+
+/// Run an authorized request and return a structured result.
+pub async fn execute(
+    client: &KubeClient,
+    access: &KubernetesAccess,
+    req: &KubeRequest,
+) -> anyhow::Result<Value> {
+    let (namespace, resource) = authorize(access, req)?;
+    let name = req.name.as_deref().filter(|n| !n.is_empty());
+
+    match req.verb {
+        KubeVerb::Get => match name {
+            Some(name) => {
+                let object = client
+                    .send_json(client.request(
+                        reqwest::Method::GET,
+                        &object_path(resource, &namespace, name),
+                    ))
+                    .await?;
+                Ok(json!({
+                    "namespace": namespace,
+                    "resource": resource.plural,
+                    "object": strip_object(object),
+                }))
+            }
+            None => {
+                let mut request =
+                    client.request(reqwest::Method::GET, &collection_path(resource, &namespace));
+                request = request.query(&[("limit", MAX_LIST_ITEMS.to_string())]);
+                if let Some(selector) = &req.label_selector {
+                    request = request.query(&[("labelSelector", selector)]);
+                }
+                let list = client.send_json(request).await?;
+                let items: Vec<Value> = list
+                    .get("items")
+                    .and_then(Value::as_array)
+                    .map(|items| items.iter().map(|o| summarize(resource, o)).collect())
+                    .unwrap_or_default();
+                Ok(json!({
+                    "namespace": namespace,
+                    "resource": resource.plural,
+                    "count": items.len(),
+                    "truncated": list.pointer("/metadata/continue").and_then(Value::as_str).is_some_and(|c| !c.is_empty()),
+                    "items": items,
+                }))
+            }
+        },
+        KubeVerb::Describe => {
+            let name = name.unwrap_or_default();
+            let object = client
+                .send_json(client.request(
+                    reqwest::Method::GET,
+                    &object_path(resource, &namespace, name),
+                ))
+                .await?;
+            let events = list_events(client, &namespace, Some(name)).await?;
+            Ok(json!({
+                "namespace": namespace,
+                "resource": resource.plural,
+                "summary": summarize(resource, &object),
+                "object": strip_object(object),
+                "events": events,
+            }))
+        }
+        KubeVerb::Events => {
+            let events = list_events(client, &namespace, name).await?;
+            Ok(json!({ "namespace": namespace, "events": events }))
+        }
+        KubeVerb::Logs => {
+            let name = name.unwrap_or_default();
+            let tail = req
+                .tail_lines
+                .unwrap_or(DEFAULT_TAIL_LINES)
+                .clamp(1, MAX_TAIL_LINES);
+            let mut request = client
+                .request(
+                    reqwest::Method::GET,
+                    &format!("{}/log", object_path(resource, &namespace, name)),
+                )
+                .query(&[("tailLines", tail.to_string())]);
+            if let Some(container) = &req.container {
+                request = request.query(&[("container", container)]);
+            }
+            if let Some(since) = req.since_seconds {
+                request = request.query(&[("sinceSeconds", since.to_string())]);
+            }
+            if req.previous {
+                request = request.query(&[("previous", "true")]);
+            }
+            let text = client.send(request).await?;
+            let lines: Vec<&str> = text.lines().collect();
+            Ok(json!({
+                "namespace": namespace,
+                "pod": name,
+                "container": req.container,
+                "lines": lines,
+            }))
+        }
+        KubeVerb::Delete => {
+            let name = name.unwrap_or_default();
+            client
+                .send(client.request(
+                    reqwest::Method::DELETE,
+                    &object_path(resource, &namespace, name),
+                ))
+                .await?;
+            Ok(json!({ "namespace": namespace, "resource": resource.plural, "deleted": name }))
+        }
+        KubeVerb::Scale => {
+            let name = name.unwrap_or_default();
+            let replicas = req
+                .replicas
+                .ok_or_else(|| anyhow::anyhow!("'replicas' is required for scale"))?;
+            let scale = client
+                .send_json(
+                    client
+                        .request(
+                            reqwest::Method::PATCH,
+                            &format!("{}/scale", object_path(resource, &namespace, name)),
+                        )
+                        .header(
+                            reqwest::header::CONTENT_TYPE,
+                            "application/merge-patch+json",
+                        )
+                        .body(json!({ "spec": { "replicas": replicas } }).to_string()),
+                )
+                .await?;
+            Ok(json!({
+                "namespace": namespace,
+                "resource": resource.plural,
+                "name": name,
+                "replicas": scale.pointer("/spec/replicas"),
+            }))
+        }
+        KubeVerb::Restart => {
+            let name = name.unwrap_or_default();
+            // Same mechanism as `kubectl rollout restart`.
+            let patch = json!({
+                "spec": { "template": { "metadata": { "annotations": {
+                    "kubectl.kubernetes.io/restartedAt": chrono::Utc::now().to_rfc3339(),
+                } } } }
+            });
+            client
+                .send(
+                    client
+                        .request(
+                            reqwest::Method::PATCH,
+                            &object_path(resource, &namespace, name),
+                        )
+                        .header(
+                            reqwest::header::CONTENT_TYPE,
+                            "application/merge-patch+json",
+                        )
+                        .body(patch.to_string()),
+                )
+                .await?;
+            Ok(json!({ "namespace": namespace, "resource": resource.plural, "restarted": name }))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn access() -> KubernetesAccess {
+        KubernetesAccess {
+            secret: "staging".to_string(),
+            context: None,
+            namespaces: vec!["app".to_string(), "jobs".to_string()],
+            allow_write: false,
+        }
+    }
+
+    fn request(verb: KubeVerb, resource: Option<&str>, namespace: Option<&str>) -> KubeRequest {
+        serde_json::from_value(json!({
+            "verb": verb,
+            "resource": resource,
+            "namespace": namespace,
+            "name": "web",
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn policy_enforces_namespaces_verbs_and_resources() {
+        let mut access = access();
+        let (ns, res) = authorize(&access, &request(KubeVerb::Get, Some("deploy"), None)).unwrap();
+        assert_eq!((ns.as_str(), res.plural), ("app", "deployments"));
+
+        let err = authorize(&access, &request(KubeVerb::Get, None, Some("kube-system")));
+        assert!(err.unwrap_err().to_string().contains("not allowlisted"));
+        let err = authorize(&access, &request(KubeVerb::Get, Some("secrets"), None));
+        assert!(err.unwrap_err().to_string().contains("not accessible"));
+        let err = authorize(&access, &request(KubeVerb::Delete, Some("pods"), None));
+        assert!(err.unwrap_err().to_string().contains("read-only"));
+
+        access.allow_write = true;
+        assert!(authorize(
+            &access,
+            &request(KubeVerb::Delete, Some("pods"), Some("jobs"))
+        )
+        .is_ok());
+        let err = authorize(&access, &request(KubeVerb::Scale, Some("svc"), None));
+        assert!(err.unwrap_err().to_string().contains("cannot be scaled"));
+
+        access.namespaces.clear();
+        assert!(authorize(&access, &request(KubeVerb::Get, None, None)).is_err());
+    }
+
+    #[test]
+    fn kubeconfig_context_selection() {
+        let yaml = r#"
+apiVersion: v1
+kind: Config
+current-context: staging
+clusters:
+  - name: staging
+    cluster: { server: "https://10.0.0.1:6443/" }
+contexts:
+  - name: staging
+    context: { cluster: staging, user: ci }
+  - name: broken
+    context: { cluster: missing, user: ci }
+users:
+  - name: ci
+    user: { token: " abc " }
+"#;
+        let client = KubeClient::from_kubeconfig(yaml, None).unwrap();
+        assert_eq!(client.server, "https://10.0.0.1:6443");
+        assert_eq!(client.authorization.as_deref(), Some("Bearer abc"));
+        assert!(KubeClient::from_kubeconfig(yaml, Some("broken")).is_err());
+        assert!(KubeClient::from_kubeconfig(yaml, Some("nope")).is_err());
+    }
+
+    #[test]
+    fn unsupported_auth_modes_are_named() {
+        let with_user = |user: &str| {
+            format!(
+                r#"
+current-context: c
+clusters:
+  - name: c
+    cluster: {{ server: "https://10.0.0.1:6443" }}
+contexts:
+  - name: c
+    context: {{ cluster: c, user: u }}
+users:
+  - name: u
+    user: {}
+"#,
+                user
+            )
+        };
+        let error = |user: &str| {
+            KubeClient::from_kubeconfig(&with_user(user), None)
+                .err()
+                .expect("rejected")
+                .to_string()
+        };
+        assert!(error("{ exec: { command: aws } }").contains("exec credential plugins"));
+        assert!(
+            error("{ client-certificate-data: abc, client-key-data: def }")
+                .contains("client certificate authentication")
+        );
+        assert!(error("{ client-certificate: /tmp/c.pem }").contains("`client-certificate`"));
+        assert!(error("{ auth-provider: { name: gcp } }").contains("auth-provider"));
+        assert!(KubeClient::from_kubeconfig(&with_user("{ token: t }"), None).is_ok());
+
+        let ca_file = with_user("{ token: t }").replace(
+            r#"server: "https://10.0.0.1:6443""#,
+            r#"server: "https://10.0.0.1:6443", certificate-authority: /tmp/ca.pem"#,
+        );
+        assert!(KubeClient::from_kubeconfig(&ca_file, None)
+            .err()
+            .expect("rejected")
+            .to_string()
+            .contains("certificate-authority-data"));
+    }
+
+    #[test]
+    fn summaries_follow_kubectl_columns() {
+        let pod = json!({
+            "metadata": { "name": "web-1", "creationTimestamp": "2026-01-01T00:00:00Z" },
+            "spec": { "nodeName": "n1" },
+            "status": {
+                "phase": "Running",
+                "containerStatuses": [
+                    { "ready": true, "restartCount": 2 },
+                    { "ready": false, "restartCount": 1 }
+                ]
+            }
+        });
+        let summary = summarize(resolve_resource(Some("po")).unwrap(), &pod);
+        assert_eq!(summary["ready"], "1/2");
+        assert_eq!(summary["restarts"], 3);
+        assert_eq!(summary["node"], "n1");
+
+        let stripped = strip_object(json!({
+            "metadata": { "name": "x", "managedFields": [{}] }
+        }));
+        assert!(stripped["metadata"].get("managedFields").is_none());
+    }
+}
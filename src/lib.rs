@@ -28,6 +28,10 @@
 //! 3. Stream real-time events (thinking, tool calls, results)
 //! 4. Store logs and return result
 //!
+//! ## Embedding
+//! `api::MissionClient` runs missions from other Rust programs through the
+//! same control session as the HTTP API, without serving it.
+//!
 //! ## Modules
 //! - `agents`: OpenCodeAgent for task delegation
 //! - `task`: Task definitions and lightweight cost tracking
@@ -38,25 +42,33 @@ pub mod ai_providers;
 pub mod api;
 pub mod backend;
 pub mod backend_config;
+pub mod blackout;
 pub mod config;
 pub mod cost;
+pub mod egress;
+pub mod i18n;
+pub mod kubernetes;
 pub mod library;
 pub mod mcp;
 pub mod nspawn;
 pub mod opencode;
 pub mod opencode_config;
 pub mod pkg_manager;
+pub mod process_tree;
 pub mod provider_health;
 pub mod secrets;
 pub mod settings;
 pub mod skills_registry;
+pub mod ssh;
 pub mod task;
+pub mod tenant;
 pub mod tools;
 pub mod util;
 pub mod workspace;
 pub mod workspace_exec;
 
 pub use ai_providers::{AIProvider, AIProviderStore, ProviderType};
+pub use api::{MissionClient, MissionOptions};
 pub use config::Config;
 pub use opencode_config::{OpenCodeConnection, OpenCodeStore};
 pub use settings::{Settings, SettingsStore};
@@ -6,6 +6,7 @@
 //! - Commands/prompts (`command/*.md`)
 //! - Plugins registry (`plugins.json`)
 //! - Library agents (`agent/*.md`)
+//! - System prompt layers (`prompt/base.md`, `prompt/workspace/*.md`, `prompt/agent/*.md`)
 //! - Library tools (`tool/*.ts`)
 //! - Config profiles (`configs/<profile>/`) with harness-specific settings:
 //!   - `.opencode/` - OpenCode settings (settings.json, oh-my-opencode.json)
@@ -19,6 +20,7 @@ pub mod rename;
 pub mod types;
 
 use anyhow::{Context, Result};
+use futures::StreamExt;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::path::{Path, PathBuf};
@@ -64,12 +66,24 @@ struct WorkspaceTemplateConfig {
 
 // Directory constants (OpenCode-aligned structure)
 const SKILL_DIR: &str = "skill";
+/// Optional skill package manifest, next to SKILL.md.
+const SKILL_MANIFEST_FILE: &str = "skill.json";
+/// Upper bound on a downloaded or unpacked skill package.
+const MAX_SKILL_PACKAGE_BYTES: u64 = 50 * 1024 * 1024;
 const COMMAND_DIR: &str = "command";
 const AGENT_DIR: &str = "agent";
+/// Version snapshots live in a hidden subdirectory so agent listing ignores them.
+const AGENT_VERSIONS_DIR: &str = ".versions";
 const INIT_SCRIPT_DIR: &str = "init-script";
+const ENV_PROFILE_DIR: &str = "env-profile";
 const PLUGINS_FILE: &str = "plugins.json";
 const WORKSPACE_TEMPLATE_DIR: &str = "workspace-template";
 const CONFIGS_DIR: &str = "configs";
+/// Structured profile settings, under `.sandboxed-sh/` in the profile.
+const PROFILE_SETTINGS_FILE: &str = "profile.json";
+/// Longest `extends` chain accepted when resolving profile settings.
+const MAX_PROFILE_DEPTH: usize = 8;
+const PROMPT_DIR: &str = "prompt";
 const DEFAULT_PROFILE: &str = "default";
 
 /// Store for managing the configuration library.
@@ -297,12 +311,15 @@ impl LibraryStore {
                 SkillSource::default()
             };
 
+            let manifest = Self::read_skill_manifest(&entry_path).await.ok().flatten();
+
             skills.push(SkillSummary {
                 name,
                 description,
                 path: format!("{}/{}", SKILL_DIR, entry.file_name().to_string_lossy()),
                 source,
                 setup_commands,
+                manifest,
             });
         }
 
@@ -356,6 +373,13 @@ impl LibraryStore {
         // Extract setup_commands from frontmatter
         let setup_commands = extract_string_array(&frontmatter, "setup_commands");
 
+        let manifest = Self::read_skill_manifest(&skill_dir)
+            .await
+            .unwrap_or_else(|e| {
+                tracing::warn!(skill = %name, error = %e, "Ignoring invalid skill manifest");
+                None
+            });
+
         Ok(Skill {
             name: name.to_string(),
             description,
@@ -365,6 +389,7 @@ impl LibraryStore {
             files,
             references,
             setup_commands,
+            manifest,
         })
     }
 
@@ -771,6 +796,235 @@ impl LibraryStore {
         self.get_skill(target_name).await
     }
 
+    /// Read and validate a skill package manifest, if present.
+    async fn read_skill_manifest(skill_dir: &Path) -> Result<Option<SkillManifest>> {
+        let manifest_path = skill_dir.join(SKILL_MANIFEST_FILE);
+        if !manifest_path.exists() {
+            return Ok(None);
+        }
+        let raw = fs::read_to_string(&manifest_path)
+            .await
+            .context("Failed to read skill.json")?;
+        let manifest: SkillManifest =
+            serde_json::from_str(&raw).context("Invalid skill manifest (skill.json)")?;
+        manifest.validate()?;
+        Ok(Some(manifest))
+    }
+
+    /// Install a packaged skill from a git URL or a `.tar.gz`/`.tgz` tarball.
+    ///
+    /// Only https URLs and git-over-SSH remotes (`ssh://`, `git@host:repo`) are
+    /// accepted; local paths are not. The package is unpacked into a scratch
+    /// directory of its own and validated before it is copied into the library:
+    /// it must contain a SKILL.md, a well-formed manifest (if any), no links,
+    /// and stay under the package size limit.
+    pub async fn install_skill_package(
+        &self,
+        source: &str,
+        skill_path: Option<&str>,
+        target_name: Option<&str>,
+    ) -> Result<Skill> {
+        let is_tarball = source.ends_with(".tar.gz") || source.ends_with(".tgz");
+        let is_ssh =
+            source.starts_with("ssh://") || (source.starts_with("git@") && source.contains(':'));
+        if !source.starts_with("https://") && (is_tarball || !is_ssh) {
+            anyhow::bail!("Skill packages must come from an https or git URL");
+        }
+
+        fs::create_dir_all(&self.path).await?;
+        let temp_dir = tempfile::Builder::new()
+            .prefix(".tmp-install-")
+            .tempdir_in(&self.path)
+            .context("Failed to create install directory")?;
+        let unpack_dir = temp_dir.path().join("package");
+        if is_tarball {
+            let archive = Self::download_skill_tarball(source).await?;
+            let dest = unpack_dir.clone();
+            tokio::task::spawn_blocking(move || Self::unpack_skill_tarball(&archive[..], &dest))
+                .await??;
+        } else if let Some(path) = skill_path {
+            git::sparse_clone(&unpack_dir, source, path).await?;
+        } else {
+            git::clone(&unpack_dir, source).await?;
+        }
+
+        self.install_unpacked_skill_package(&unpack_dir, source, skill_path, target_name)
+            .await
+    }
+
+    /// Validate an unpacked package and copy it into the library.
+    async fn install_unpacked_skill_package(
+        &self,
+        unpack_dir: &Path,
+        source: &str,
+        skill_path: Option<&str>,
+        target_name: Option<&str>,
+    ) -> Result<Skill> {
+        let mut source_dir = unpack_dir.to_path_buf();
+        if let Some(path) = skill_path {
+            let canonical_root = unpack_dir.canonicalize()?;
+            let canonical_source = unpack_dir
+                .join(path)
+                .canonicalize()
+                .map_err(|_| anyhow::anyhow!("Skill path '{}' not found in package", path))?;
+            if !canonical_source.starts_with(&canonical_root) {
+                anyhow::bail!("Invalid skill path: path traversal detected");
+            }
+            source_dir = canonical_source;
+        } else if !source_dir.join("SKILL.md").exists() {
+            // Tarballs usually wrap the package in a single top-level folder.
+            let mut entries = fs::read_dir(unpack_dir).await?;
+            let mut dirs = Vec::new();
+            while let Some(entry) = entries.next_entry().await? {
+                if entry.file_name() != ".git" && entry.path().is_dir() {
+                    dirs.push(entry.path());
+                }
+            }
+            if dirs.len() == 1 {
+                source_dir = dirs.remove(0);
+            }
+        }
+
+        let manifest = Self::validate_skill_package(&source_dir).await?;
+
+        let name = match target_name.or(manifest.as_ref().and_then(|m| m.name.as_deref())) {
+            Some(name) => name.to_string(),
+            None => source_dir
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .ok_or_else(|| anyhow::anyhow!("Unable to determine skill name"))?,
+        };
+        Self::validate_name(&name)?;
+
+        let target_dir = self.skills_dir().join(&name);
+        if target_dir.exists() {
+            anyhow::bail!("Skill '{}' already exists", name);
+        }
+        Self::copy_dir_recursive(&source_dir, &target_dir).await?;
+
+        let skill_source = SkillSource::Package {
+            url: source.to_string(),
+            path: skill_path.map(|p| p.to_string()),
+            version: manifest.and_then(|m| m.version),
+            installed_at: Some(chrono::Utc::now().to_rfc3339()),
+        };
+        fs::write(
+            target_dir.join(".skill-source.json"),
+            serde_json::to_string_pretty(&skill_source)?,
+        )
+        .await
+        .context("Failed to write skill source metadata")?;
+
+        self.encrypt_skill_file(&name).await?;
+        self.get_skill(&name).await
+    }
+
+    async fn download_skill_tarball(url: &str) -> Result<Vec<u8>> {
+        let response = reqwest::get(url)
+            .await
+            .with_context(|| format!("Failed to download skill package: {}", url))?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Failed to download skill package: HTTP {}",
+                response.status()
+            );
+        }
+        if response
+            .content_length()
+            .is_some_and(|len| len > MAX_SKILL_PACKAGE_BYTES)
+        {
+            anyhow::bail!("Skill package exceeds size limit");
+        }
+        let mut archive = Vec::new();
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            archive.extend_from_slice(&chunk?);
+            if archive.len() as u64 > MAX_SKILL_PACKAGE_BYTES {
+                anyhow::bail!("Skill package exceeds size limit");
+            }
+        }
+        Ok(archive)
+    }
+
+    /// Unpack a gzipped tarball entry by entry. Only regular files and
+    /// directories are accepted, and the unpacked size is capped as it goes;
+    /// `unpack_in` rejects absolute paths and `..` entries.
+    fn unpack_skill_tarball(archive: impl std::io::Read, dest: &Path) -> Result<()> {
+        std::fs::create_dir_all(dest)?;
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(archive));
+        archive.set_preserve_permissions(false);
+        archive.set_preserve_mtime(false);
+        let mut total: u64 = 0;
+        for entry in archive.entries().context("Invalid skill tarball")? {
+            let mut entry = entry.context("Invalid skill tarball")?;
+            let path = entry.path()?.display().to_string();
+            match entry.header().entry_type() {
+                tar::EntryType::Regular | tar::EntryType::Directory => {}
+                // Extended headers carry metadata for the next entry.
+                tar::EntryType::XHeader | tar::EntryType::XGlobalHeader => continue,
+                _ => anyhow::bail!("Skill package contains an unsupported entry: {}", path),
+            }
+            total += entry.size();
+            if total > MAX_SKILL_PACKAGE_BYTES {
+                anyhow::bail!("Skill package exceeds size limit");
+            }
+            if !entry.unpack_in(dest)? {
+                anyhow::bail!("Invalid skill tarball: unsafe entry '{}'", path);
+            }
+        }
+        Ok(())
+    }
+
+    /// Validate an unpacked skill package and return its manifest, if any.
+    async fn validate_skill_package(dir: &Path) -> Result<Option<SkillManifest>> {
+        if !dir.join("SKILL.md").exists() {
+            anyhow::bail!("No SKILL.md found in skill package");
+        }
+
+        let mut total: u64 = 0;
+        for entry in walkdir::WalkDir::new(dir)
+            .into_iter()
+            .filter_entry(|e| e.file_name() != ".git")
+        {
+            let entry = entry?;
+            if entry.path_is_symlink() {
+                anyhow::bail!(
+                    "Skill package contains a symlink: {}",
+                    entry
+                        .path()
+                        .strip_prefix(dir)
+                        .unwrap_or(entry.path())
+                        .display()
+                );
+            }
+            if entry.file_type().is_file() {
+                total += entry.metadata()?.len();
+                if total > MAX_SKILL_PACKAGE_BYTES {
+                    anyhow::bail!("Skill package exceeds size limit");
+                }
+            }
+        }
+
+        Self::read_skill_manifest(dir).await
+    }
+
+    /// Render a skill for invocation by name.
+    ///
+    /// Uses the manifest prompt template (or the SKILL.md body) with `{{input}}`
+    /// placeholders filled from `inputs`.
+    pub async fn invoke_skill(
+        &self,
+        name: &str,
+        inputs: &HashMap<String, String>,
+    ) -> Result<String> {
+        let skill = self.get_skill(name).await?;
+        let (_, body) = parse_frontmatter(&skill.content);
+        skill
+            .manifest
+            .unwrap_or_default()
+            .render_prompt(body, inputs)
+    }
+
     /// Encrypt unversioned <encrypted> tags in a skill's SKILL.md file.
     /// This is called after importing or syncing to ensure secrets are encrypted on disk.
     async fn encrypt_skill_file(&self, name: &str) -> Result<()> {
@@ -1036,10 +1290,12 @@ impl LibraryStore {
                 .unwrap_or((None, ""));
 
             let description = extract_description(&frontmatter);
+            let version = extract_version(&frontmatter);
 
             agents.push(LibraryAgentSummary {
                 name,
                 description,
+                version,
                 path: format!("{}/{}", AGENT_DIR, file_name),
             });
         }
@@ -1066,6 +1322,7 @@ impl LibraryStore {
         let model = extract_model(&frontmatter);
         let tools = extract_tools(&frontmatter);
         let permissions = extract_permissions(&frontmatter);
+        let version = extract_version(&frontmatter);
 
         Ok(LibraryAgent {
             name: name.to_string(),
@@ -1075,26 +1332,91 @@ impl LibraryStore {
             model,
             tools,
             permissions,
+            version,
         })
     }
 
-    /// Save a library agent definition.
-    pub async fn save_library_agent(&self, name: &str, agent: &LibraryAgent) -> Result<()> {
+    /// Save a library agent definition and record it as a new version.
+    ///
+    /// The version is taken from the `version` frontmatter field. When it is
+    /// missing, or unchanged while the content changed, the patch component of
+    /// the latest recorded version is bumped. Recorded versions are immutable:
+    /// saving different content under an older existing version fails.
+    /// Returns the version that was saved.
+    pub async fn save_library_agent(&self, name: &str, agent: &LibraryAgent) -> Result<String> {
         Self::validate_name(name)?;
         let agents_dir = self.path.join(AGENT_DIR);
         let agent_path = agents_dir.join(format!("{}.md", name));
 
+        let (frontmatter, _) = parse_frontmatter(&agent.content);
+        let requested = match extract_version(&frontmatter) {
+            Some(raw) => Some(
+                SemVer::parse(&raw)
+                    .ok_or_else(|| anyhow::anyhow!("Invalid agent version: {}", raw))?,
+            ),
+            None => None,
+        };
+        let current = match fs::read_to_string(&agent_path).await {
+            Ok(existing) => {
+                extract_version(&parse_frontmatter(&existing).0).and_then(|v| SemVer::parse(&v))
+            }
+            Err(_) => None,
+        };
+        let latest = self
+            .list_library_agent_versions(name)
+            .await?
+            .iter()
+            .filter_map(|v| SemVer::parse(&v.version))
+            .max();
+        let next = latest.map(SemVer::bump_patch).unwrap_or(SemVer {
+            major: 1,
+            minor: 0,
+            patch: 0,
+        });
+
+        let version = match requested {
+            None => next,
+            Some(requested) => {
+                let candidate = set_frontmatter_version(&agent.content, &requested.to_string());
+                match self
+                    .read_agent_version_content(name, &requested.to_string())
+                    .await
+                {
+                    Some(recorded) if recorded != candidate => {
+                        if Some(requested) == current {
+                            next
+                        } else {
+                            anyhow::bail!(
+                                "Agent {} version {} already exists with different content",
+                                name,
+                                requested
+                            );
+                        }
+                    }
+                    _ => requested,
+                }
+            }
+        };
+        let version = version.to_string();
+        let content = set_frontmatter_version(&agent.content, &version);
+
         fs::create_dir_all(&agents_dir).await?;
 
         // Write the full content (should include frontmatter)
-        fs::write(&agent_path, &agent.content)
+        fs::write(&agent_path, &content)
             .await
             .context("Failed to write agent file")?;
 
-        Ok(())
+        let versions_dir = self.agent_versions_dir(name);
+        fs::create_dir_all(&versions_dir).await?;
+        fs::write(versions_dir.join(format!("{}.md", version)), &content)
+            .await
+            .context("Failed to write agent version file")?;
+
+        Ok(version)
     }
 
-    /// Delete a library agent.
+    /// Delete a library agent and its recorded versions.
     pub async fn delete_library_agent(&self, name: &str) -> Result<()> {
         Self::validate_name(name)?;
         let agent_path = self.path.join(AGENT_DIR).join(format!("{}.md", name));
@@ -1105,9 +1427,262 @@ impl LibraryStore {
                 .context("Failed to delete agent file")?;
         }
 
+        let versions_dir = self.agent_versions_dir(name);
+        if versions_dir.exists() {
+            fs::remove_dir_all(&versions_dir)
+                .await
+                .context("Failed to delete agent versions")?;
+        }
+
+        Ok(())
+    }
+
+    fn agent_versions_dir(&self, name: &str) -> PathBuf {
+        self.path
+            .join(AGENT_DIR)
+            .join(AGENT_VERSIONS_DIR)
+            .join(name)
+    }
+
+    async fn read_agent_version_content(&self, name: &str, version: &str) -> Option<String> {
+        fs::read_to_string(
+            self.agent_versions_dir(name)
+                .join(format!("{}.md", version)),
+        )
+        .await
+        .ok()
+    }
+
+    /// List recorded versions of a library agent, newest first.
+    pub async fn list_library_agent_versions(
+        &self,
+        name: &str,
+    ) -> Result<Vec<LibraryAgentVersion>> {
+        Self::validate_name(name)?;
+        let versions_dir = self.agent_versions_dir(name);
+        if !versions_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let current = fs::read_to_string(self.path.join(AGENT_DIR).join(format!("{}.md", name)))
+            .await
+            .ok()
+            .and_then(|c| extract_version(&parse_frontmatter(&c).0))
+            .and_then(|v| SemVer::parse(&v));
+
+        let mut versions = Vec::new();
+        let mut entries = fs::read_dir(&versions_dir).await?;
+        while let Some(entry) = entries.next_entry().await? {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let Some(raw) = file_name.strip_suffix(".md") else {
+                continue;
+            };
+            let Some(version) = SemVer::parse(raw) else {
+                continue;
+            };
+            versions.push(version);
+        }
+        versions.sort_by(|a, b| b.cmp(a));
+
+        Ok(versions
+            .into_iter()
+            .map(|version| LibraryAgentVersion {
+                version: version.to_string(),
+                path: format!(
+                    "{}/{}/{}/{}.md",
+                    AGENT_DIR, AGENT_VERSIONS_DIR, name, version
+                ),
+                current: Some(version) == current,
+            })
+            .collect())
+    }
+
+    /// Get a specific recorded version of a library agent.
+    pub async fn get_library_agent_version(
+        &self,
+        name: &str,
+        version: &str,
+    ) -> Result<LibraryAgent> {
+        Self::validate_name(name)?;
+        let version = SemVer::parse(version)
+            .ok_or_else(|| anyhow::anyhow!("Invalid agent version: {}", version))?
+            .to_string();
+        let content = self
+            .read_agent_version_content(name, &version)
+            .await
+            .ok_or_else(|| {
+                anyhow::anyhow!("Library agent version not found: {}@{}", name, version)
+            })?;
+
+        let (frontmatter, _body) = parse_frontmatter(&content);
+        Ok(LibraryAgent {
+            name: name.to_string(),
+            description: extract_description(&frontmatter),
+            path: format!(
+                "{}/{}/{}/{}.md",
+                AGENT_DIR, AGENT_VERSIONS_DIR, name, version
+            ),
+            model: extract_model(&frontmatter),
+            tools: extract_tools(&frontmatter),
+            permissions: extract_permissions(&frontmatter),
+            version: Some(version),
+            content,
+        })
+    }
+
+    /// Restore a recorded version as the current agent definition.
+    pub async fn rollback_library_agent(&self, name: &str, version: &str) -> Result<LibraryAgent> {
+        let snapshot = self.get_library_agent_version(name, version).await?;
+        let agent_path = self.path.join(AGENT_DIR).join(format!("{}.md", name));
+        fs::write(&agent_path, &snapshot.content)
+            .await
+            .context("Failed to write agent file")?;
+        self.get_library_agent(name).await
+    }
+
+    // ─────────────────────────────────────────────────────────────────────────
+    // System Prompts (prompt/base.md, prompt/{workspace,agent}/*.md)
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// Resolve the relative path of a prompt layer.
+    fn prompt_layer_path(scope: PromptScope, name: Option<&str>) -> Result<String> {
+        match (scope, name) {
+            (PromptScope::Base, _) => Ok(format!("{}/base.md", PROMPT_DIR)),
+            (scope, Some(name)) => {
+                Self::validate_name(name)?;
+                Ok(format!("{}/{}/{}.md", PROMPT_DIR, scope.as_str(), name))
+            }
+            (scope, None) => anyhow::bail!("A name is required for {} prompts", scope.as_str()),
+        }
+    }
+
+    /// List all prompt layers stored in the library.
+    pub async fn list_prompt_layers(&self) -> Result<Vec<PromptLayer>> {
+        let mut layers = Vec::new();
+
+        if let Some(base) = self.get_prompt_layer(PromptScope::Base, None).await? {
+            layers.push(base);
+        }
+
+        for scope in [PromptScope::Workspace, PromptScope::Agent] {
+            let dir = self.path.join(PROMPT_DIR).join(scope.as_str());
+            if !dir.exists() {
+                continue;
+            }
+            let mut scoped = Vec::new();
+            let mut entries = fs::read_dir(&dir).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                let Some(name) = file_name.strip_suffix(".md") else {
+                    continue;
+                };
+                if let Some(layer) = self.get_prompt_layer(scope, Some(name)).await? {
+                    scoped.push(layer);
+                }
+            }
+            scoped.sort_by(|a, b| a.name.cmp(&b.name));
+            layers.extend(scoped);
+        }
+
+        Ok(layers)
+    }
+
+    /// Get a single prompt layer. Returns `None` if the layer is not configured.
+    pub async fn get_prompt_layer(
+        &self,
+        scope: PromptScope,
+        name: Option<&str>,
+    ) -> Result<Option<PromptLayer>> {
+        let rel_path = Self::prompt_layer_path(scope, name)?;
+        let full_path = self.path.join(&rel_path);
+
+        if !full_path.exists() {
+            return Ok(None);
+        }
+
+        let content = fs::read_to_string(&full_path)
+            .await
+            .context("Failed to read prompt file")?;
+
+        Ok(Some(PromptLayer {
+            scope,
+            name: match scope {
+                PromptScope::Base => None,
+                _ => name.map(str::to_string),
+            },
+            path: rel_path,
+            content,
+        }))
+    }
+
+    /// Save a prompt layer.
+    pub async fn save_prompt_layer(
+        &self,
+        scope: PromptScope,
+        name: Option<&str>,
+        content: &str,
+    ) -> Result<()> {
+        let rel_path = Self::prompt_layer_path(scope, name)?;
+        let full_path = self.path.join(&rel_path);
+
+        if let Some(parent) = full_path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+
+        fs::write(&full_path, content)
+            .await
+            .context("Failed to write prompt file")?;
+
+        Ok(())
+    }
+
+    /// Delete a prompt layer.
+    pub async fn delete_prompt_layer(&self, scope: PromptScope, name: Option<&str>) -> Result<()> {
+        let rel_path = Self::prompt_layer_path(scope, name)?;
+        let full_path = self.path.join(&rel_path);
+
+        if full_path.exists() {
+            fs::remove_file(&full_path)
+                .await
+                .context("Failed to delete prompt file")?;
+        }
+
         Ok(())
     }
 
+    /// Merge the base, workspace and agent prompt layers into a single system prompt.
+    /// Missing layers are skipped.
+    pub async fn compose_system_prompt(
+        &self,
+        workspace: Option<&str>,
+        agent: Option<&str>,
+    ) -> Result<PromptPreview> {
+        let mut layers = Vec::new();
+
+        if let Some(base) = self.get_prompt_layer(PromptScope::Base, None).await? {
+            layers.push(base);
+        }
+        if let Some(workspace) = workspace.filter(|w| !w.is_empty()) {
+            if let Some(layer) = self
+                .get_prompt_layer(PromptScope::Workspace, Some(workspace))
+                .await?
+            {
+                layers.push(layer);
+            }
+        }
+        if let Some(agent) = agent.filter(|a| !a.is_empty()) {
+            if let Some(layer) = self
+                .get_prompt_layer(PromptScope::Agent, Some(agent))
+                .await?
+            {
+                layers.push(layer);
+            }
+        }
+
+        let prompt = merge_prompt_layers(&layers);
+        Ok(PromptPreview { layers, prompt })
+    }
+
     // ─────────────────────────────────────────────────────────────────────────
     // Workspace Templates (workspace-template/*.json)
     // ─────────────────────────────────────────────────────────────────────────
@@ -1637,6 +2212,124 @@ impl LibraryStore {
         None
     }
 
+    // ─────────────────────────────────────────────────────────────────────────
+    // Environment Profiles (env-profile/*.json)
+    // ─────────────────────────────────────────────────────────────────────────
+
+    /// List all environment profiles with their summaries.
+    pub async fn list_env_profiles(&self) -> Result<Vec<EnvProfileSummary>> {
+        let profiles_dir = self.path.join(ENV_PROFILE_DIR);
+
+        if !profiles_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut profiles = Vec::new();
+        let mut entries = fs::read_dir(&profiles_dir).await?;
+
+        while let Some(entry) = entries.next_entry().await? {
+            let entry_path = entry.path();
+
+            // Only process .json files
+            let Some(ext) = entry_path.extension() else {
+                continue;
+            };
+            if ext != "json" {
+                continue;
+            }
+
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let name = file_name.trim_end_matches(".json").to_string();
+            let description = fs::read_to_string(&entry_path)
+                .await
+                .ok()
+                .and_then(|c| serde_json::from_str::<EnvProfile>(&c).ok())
+                .and_then(|profile| profile.description);
+
+            profiles.push(EnvProfileSummary {
+                name,
+                description,
+                path: format!("{}/{}", ENV_PROFILE_DIR, file_name),
+            });
+        }
+
+        profiles.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(profiles)
+    }
+
+    /// Get an environment profile by name.
+    pub async fn get_env_profile(&self, name: &str) -> Result<EnvProfile> {
+        Self::validate_name(name)?;
+        let profile_path = self
+            .path
+            .join(ENV_PROFILE_DIR)
+            .join(format!("{}.json", name));
+
+        if !profile_path.exists() {
+            anyhow::bail!("Environment profile not found: {}", name);
+        }
+
+        let content = fs::read_to_string(&profile_path)
+            .await
+            .context("Failed to read environment profile file")?;
+        let mut profile: EnvProfile =
+            serde_json::from_str(&content).context("Failed to parse environment profile file")?;
+        profile.name = name.to_string();
+        profile.path = format!("{}/{}.json", ENV_PROFILE_DIR, name);
+        Ok(profile)
+    }
+
+    /// Save an environment profile.
+    /// Variable names must be valid shell identifiers; PATH is managed through `path_prepend`.
+    pub async fn save_env_profile(&self, name: &str, profile: &EnvProfile) -> Result<()> {
+        Self::validate_name(name)?;
+        for key in profile.env.keys() {
+            let valid = key
+                .chars()
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if !valid {
+                anyhow::bail!("Invalid environment variable name: {}", key);
+            }
+            if key == "PATH" {
+                anyhow::bail!("Use path_prepend instead of setting PATH directly");
+            }
+        }
+
+        let profiles_dir = self.path.join(ENV_PROFILE_DIR);
+        fs::create_dir_all(&profiles_dir).await?;
+
+        let profile = EnvProfile {
+            name: name.to_string(),
+            path: format!("{}/{}.json", ENV_PROFILE_DIR, name),
+            ..profile.clone()
+        };
+        let content = serde_json::to_string_pretty(&profile)?;
+        fs::write(profiles_dir.join(format!("{}.json", name)), content)
+            .await
+            .context("Failed to write environment profile file")?;
+
+        Ok(())
+    }
+
+    /// Delete an environment profile.
+    pub async fn delete_env_profile(&self, name: &str) -> Result<()> {
+        Self::validate_name(name)?;
+        let profile_path = self
+            .path
+            .join(ENV_PROFILE_DIR)
+            .join(format!("{}.json", name));
+
+        if profile_path.exists() {
+            fs::remove_file(&profile_path)
+                .await
+                .context("Failed to delete environment profile file")?;
+        }
+
+        Ok(())
+    }
+
     // ─────────────────────────────────────────────────────────────────────────
     // OpenCode Settings (delegates to default profile)
     // ─────────────────────────────────────────────────────────────────────────
@@ -1754,7 +2447,7 @@ impl LibraryStore {
     }
 
     /// Get a config profile by name with full content.
-    /// Uses new directory structure: .opencode/, .claudecode/, .ampcode/, .sandboxed-sh/
+    /// Uses new directory structure: .opencode/, .claudecode/, .ampcode/, .ollama/, .sandboxed-sh/
     pub async fn get_config_profile(&self, name: &str) -> Result<ConfigProfile> {
         Self::validate_name(name)?;
 
@@ -1764,6 +2457,7 @@ impl LibraryStore {
         let opencode_settings_path = profile_dir.join(".opencode").join("settings.json");
         let claudecode_settings_path = profile_dir.join(".claudecode").join("settings.json");
         let ampcode_settings_path = profile_dir.join(".ampcode").join("settings.json");
+        let ollama_settings_path = profile_dir.join(".ollama").join("settings.json");
         let sandboxed_config_path = profile_dir.join(".sandboxed-sh").join("config.json");
 
         // Legacy paths for backward compatibility
@@ -1857,6 +2551,20 @@ impl LibraryStore {
             AmpCodeConfig::default()
         };
 
+        // Load Ollama config (new only)
+        let ollama_config = if ollama_settings_path.exists() {
+            let content = fs::read_to_string(&ollama_settings_path)
+                .await
+                .context("Failed to read ollama config")?;
+            files.push(ConfigProfileFile {
+                path: ".ollama/settings.json".to_string(),
+                content: content.clone(),
+            });
+            serde_json::from_str(&content).unwrap_or_default()
+        } else {
+            OllamaConfig::default()
+        };
+
         Ok(ConfigProfile {
             name: name.to_string(),
             is_default: name == DEFAULT_PROFILE,
@@ -1866,6 +2574,7 @@ impl LibraryStore {
             sandboxed_config,
             claudecode_config,
             ampcode_config,
+            ollama_config,
         })
     }
 
@@ -1881,11 +2590,13 @@ impl LibraryStore {
         let sandboxed_dir = profile_dir.join(".sandboxed-sh");
         let claudecode_dir = profile_dir.join(".claudecode");
         let ampcode_dir = profile_dir.join(".ampcode");
+        let ollama_dir = profile_dir.join(".ollama");
 
         fs::create_dir_all(&opencode_dir).await?;
         fs::create_dir_all(&sandboxed_dir).await?;
         fs::create_dir_all(&claudecode_dir).await?;
         fs::create_dir_all(&ampcode_dir).await?;
+        fs::create_dir_all(&ollama_dir).await?;
 
         // Save OpenCode settings
         let opencode_content = serde_json::to_string_pretty(&profile.opencode_settings)?;
@@ -1911,6 +2622,12 @@ impl LibraryStore {
             .await
             .context("Failed to write ampcode config")?;
 
+        // Save Ollama config
+        let ollama_content = serde_json::to_string_pretty(&profile.ollama_config)?;
+        fs::write(ollama_dir.join("settings.json"), ollama_content)
+            .await
+            .context("Failed to write ollama config")?;
+
         Ok(())
     }
 
@@ -1923,6 +2640,15 @@ impl LibraryStore {
             anyhow::bail!("Cannot delete the default profile");
         }
 
+        let children = self.config_profile_children(name).await?;
+        if !children.is_empty() {
+            anyhow::bail!(
+                "Cannot delete profile '{}': extended by {}",
+                name,
+                children.join(", ")
+            );
+        }
+
         let profile_dir = self.path.join(CONFIGS_DIR).join(name);
 
         if profile_dir.exists() {
@@ -1962,6 +2688,7 @@ impl LibraryStore {
                 sandboxed_config: base.sandboxed_config,
                 claudecode_config: base.claudecode_config,
                 ampcode_config: base.ampcode_config,
+                ollama_config: base.ollama_config,
             };
             self.save_config_profile(name, &new_profile).await?;
             Ok(new_profile)
@@ -1977,6 +2704,7 @@ impl LibraryStore {
                 sandboxed_config: SandboxedConfig::default(),
                 claudecode_config: ClaudeCodeConfig::default(),
                 ampcode_config: AmpCodeConfig::default(),
+                ollama_config: OllamaConfig::default(),
             })
         }
     }
@@ -2176,6 +2904,174 @@ impl LibraryStore {
         Ok(())
     }
 
+    /// Get Ollama config from a specific profile.
+    pub async fn get_ollama_config_for_profile(&self, profile: &str) -> Result<OllamaConfig> {
+        Self::validate_name(profile)?;
+
+        let profile_dir = self.path.join(CONFIGS_DIR).join(profile);
+        let path = profile_dir.join(".ollama").join("settings.json");
+
+        if !path.exists() {
+            return Ok(OllamaConfig::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .await
+            .context("Failed to read ollama config")?;
+
+        serde_json::from_str(&content).context("Failed to parse ollama config")
+    }
+
+    /// Save Ollama config to a specific profile.
+    pub async fn save_ollama_config_for_profile(
+        &self,
+        profile: &str,
+        config: &OllamaConfig,
+    ) -> Result<()> {
+        Self::validate_name(profile)?;
+
+        let profile_dir = self.path.join(CONFIGS_DIR).join(profile);
+        let ollama_dir = profile_dir.join(".ollama");
+
+        fs::create_dir_all(&ollama_dir).await?;
+
+        let content = serde_json::to_string_pretty(config)?;
+        fs::write(ollama_dir.join("settings.json"), content)
+            .await
+            .context("Failed to write ollama config")?;
+
+        Ok(())
+    }
+
+    /// Whether a config profile exists. `default` always exists: it falls
+    /// back to library defaults.
+    pub fn config_profile_exists(&self, name: &str) -> bool {
+        name == DEFAULT_PROFILE
+            || (Self::validate_name(name).is_ok()
+                && self.path.join(CONFIGS_DIR).join(name).is_dir())
+    }
+
+    /// Get a profile's own structured settings (without inheritance).
+    pub async fn get_config_profile_settings(
+        &self,
+        profile: &str,
+    ) -> Result<ConfigProfileSettings> {
+        Self::validate_name(profile)?;
+        if !self.config_profile_exists(profile) {
+            anyhow::bail!("Config profile '{}' not found", profile);
+        }
+
+        let path = self
+            .path
+            .join(CONFIGS_DIR)
+            .join(profile)
+            .join(".sandboxed-sh")
+            .join(PROFILE_SETTINGS_FILE);
+        if !path.exists() {
+            return Ok(ConfigProfileSettings::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .await
+            .context("Failed to read profile settings")?;
+        serde_json::from_str(&content).context("Failed to parse profile settings")
+    }
+
+    /// Save a profile's structured settings. Fails if `extends` names a
+    /// missing profile or would create an inheritance cycle.
+    pub async fn save_config_profile_settings(
+        &self,
+        profile: &str,
+        settings: &ConfigProfileSettings,
+    ) -> Result<()> {
+        Self::validate_name(profile)?;
+        if !self.config_profile_exists(profile) {
+            anyhow::bail!("Config profile '{}' not found", profile);
+        }
+
+        let mut seen = vec![profile.to_string()];
+        let mut parent = settings.extends.clone();
+        while let Some(name) = parent {
+            if seen.contains(&name) {
+                anyhow::bail!(
+                    "Invalid profile inheritance: cycle through '{}'",
+                    seen.join("' -> '")
+                );
+            }
+            if !self.config_profile_exists(&name) {
+                anyhow::bail!("Parent profile '{}' not found", name);
+            }
+            parent = self.get_config_profile_settings(&name).await?.extends;
+            seen.push(name);
+        }
+
+        let dir = self
+            .path
+            .join(CONFIGS_DIR)
+            .join(profile)
+            .join(".sandboxed-sh");
+        fs::create_dir_all(&dir).await?;
+        let content = serde_json::to_string_pretty(settings)?;
+        fs::write(dir.join(PROFILE_SETTINGS_FILE), content)
+            .await
+            .context("Failed to write profile settings")?;
+
+        Ok(())
+    }
+
+    /// Resolve a profile's settings through its `extends` chain.
+    pub async fn resolve_config_profile_settings(
+        &self,
+        profile: &str,
+    ) -> Result<ResolvedConfigProfileSettings> {
+        let mut chain: Vec<(String, ConfigProfileSettings)> = Vec::new();
+        let mut next = Some(profile.to_string());
+        while let Some(name) = next {
+            if chain.iter().any(|(seen, _)| *seen == name) {
+                anyhow::bail!("Invalid profile inheritance: cycle through '{}'", name);
+            }
+            if chain.len() >= MAX_PROFILE_DEPTH {
+                anyhow::bail!(
+                    "Profile inheritance deeper than {} levels",
+                    MAX_PROFILE_DEPTH
+                );
+            }
+            if !chain.is_empty() && !self.config_profile_exists(&name) {
+                anyhow::bail!("Parent profile '{}' not found", name);
+            }
+            let settings = self.get_config_profile_settings(&name).await?;
+            next = settings.extends.clone();
+            chain.push((name, settings));
+        }
+
+        let mut resolved = ConfigProfileSettings::default();
+        let mut names = Vec::with_capacity(chain.len());
+        for (name, settings) in chain.into_iter().rev() {
+            resolved = resolved.merged_with(&settings);
+            names.push(name);
+        }
+        resolved.extends = None;
+        Ok(ResolvedConfigProfileSettings {
+            chain: names,
+            settings: resolved,
+        })
+    }
+
+    /// Profiles whose settings extend `profile`.
+    async fn config_profile_children(&self, profile: &str) -> Result<Vec<String>> {
+        let mut children = Vec::new();
+        for summary in self.list_config_profiles().await? {
+            if summary.name == profile {
+                continue;
+            }
+            let settings = self.get_config_profile_settings(&summary.name).await?;
+            if settings.extends.as_deref() == Some(profile) {
+                children.push(summary.name);
+            }
+        }
+        Ok(children)
+    }
+
     /// Get a specific file from a config profile.
     pub async fn get_config_profile_file(&self, profile: &str, file_path: &str) -> Result<String> {
         Self::validate_name(profile)?;
@@ -2528,6 +3424,84 @@ This is the body."#;
     fn test_validate_name_rejects_empty() {
         assert!(LibraryStore::validate_name("").is_err());
     }
+
+    #[tokio::test]
+    async fn test_config_profile_settings_inheritance() {
+        use std::collections::BTreeMap;
+
+        let temp = tempfile::tempdir().expect("tempdir");
+        let store = LibraryStore::with_test_store(temp.path().to_path_buf()).await;
+        store.create_config_profile("team", None).await.unwrap();
+        store.create_config_profile("alice", None).await.unwrap();
+
+        let team = ConfigProfileSettings {
+            models: BTreeMap::from([
+                ("claudecode".to_string(), "claude-sonnet-4".to_string()),
+                ("codex".to_string(), "gpt-5-codex".to_string()),
+            ]),
+            system_prompt_append: Some("Follow the team style guide.".to_string()),
+            tool_policy: ToolPolicy {
+                allow: vec![],
+                deny: vec!["WebFetch".to_string()],
+            },
+            ..Default::default()
+        };
+        store
+            .save_config_profile_settings("team", &team)
+            .await
+            .unwrap();
+        let alice = ConfigProfileSettings {
+            extends: Some("team".to_string()),
+            models: BTreeMap::from([("claudecode".to_string(), "claude-opus-4".to_string())]),
+            system_prompt_append: Some("Answer tersely.".to_string()),
+            tool_policy: ToolPolicy {
+                allow: vec![],
+                deny: vec!["Bash(rm:*)".to_string()],
+            },
+            ..Default::default()
+        };
+        store
+            .save_config_profile_settings("alice", &alice)
+            .await
+            .unwrap();
+
+        let resolved = store
+            .resolve_config_profile_settings("alice")
+            .await
+            .unwrap();
+        assert_eq!(resolved.chain, vec!["team", "alice"]);
+        assert_eq!(resolved.settings.models["claudecode"], "claude-opus-4");
+        assert_eq!(resolved.settings.models["codex"], "gpt-5-codex");
+        assert_eq!(
+            resolved.settings.system_prompt_append.as_deref(),
+            Some("Follow the team style guide.\n\nAnswer tersely.")
+        );
+        assert_eq!(
+            resolved.settings.tool_policy.deny,
+            vec!["WebFetch", "Bash(rm:*)"]
+        );
+
+        // Cycles and missing parents are rejected; extended profiles can't be deleted.
+        let cyclic = ConfigProfileSettings {
+            extends: Some("alice".to_string()),
+            ..Default::default()
+        };
+        assert!(store
+            .save_config_profile_settings("team", &cyclic)
+            .await
+            .is_err());
+        let orphan = ConfigProfileSettings {
+            extends: Some("missing".to_string()),
+            ..Default::default()
+        };
+        assert!(store
+            .save_config_profile_settings("alice", &orphan)
+            .await
+            .is_err());
+        assert!(store.delete_config_profile("team").await.is_err());
+        assert!(store.config_profile_exists("default"));
+        assert!(!store.config_profile_exists("missing"));
+    }
 }
 
 #[cfg(test)]
@@ -2726,4 +3700,248 @@ Secret: <encrypted>my-api-key</encrypted>
             "Should not create additional encrypted tags"
         );
     }
+
+    #[tokio::test]
+    async fn test_compose_system_prompt_merges_layers_in_order() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let store = LibraryStore::with_test_store(temp.path().to_path_buf()).await;
+
+        store
+            .save_prompt_layer(PromptScope::Agent, Some("reviewer"), "Agent persona.\n")
+            .await
+            .unwrap();
+        store
+            .save_prompt_layer(PromptScope::Base, None, "Base rules.")
+            .await
+            .unwrap();
+        store
+            .save_prompt_layer(PromptScope::Workspace, Some("api"), "  \n")
+            .await
+            .unwrap();
+
+        let preview = store
+            .compose_system_prompt(Some("api"), Some("reviewer"))
+            .await
+            .unwrap();
+        assert_eq!(preview.layers.len(), 3);
+        assert_eq!(preview.prompt, "Base rules.\n\nAgent persona.");
+
+        let preview = store
+            .compose_system_prompt(Some("unknown"), None)
+            .await
+            .unwrap();
+        assert_eq!(preview.prompt, "Base rules.");
+
+        assert!(store
+            .save_prompt_layer(PromptScope::Agent, Some("../escape"), "x")
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_env_profile_roundtrip_and_script() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let store = LibraryStore::with_test_store(temp.path().to_path_buf()).await;
+        let profile = EnvProfile {
+            description: Some("Java 17".to_string()),
+            env: [("JAVA_HOME".to_string(), "/opt/jdk 17".to_string())]
+                .into_iter()
+                .collect(),
+            path_prepend: vec!["/opt/jdk/bin".to_string(), "/opt/gradle/bin".to_string()],
+            init: Some("echo 'ready'".to_string()),
+            ..Default::default()
+        };
+
+        store.save_env_profile("java", &profile).await.unwrap();
+        let listed = store.list_env_profiles().await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].description.as_deref(), Some("Java 17"));
+
+        let loaded = store.get_env_profile("java").await.unwrap();
+        assert_eq!(loaded.name, "java");
+        assert_eq!(loaded.path, "env-profile/java.json");
+        let script = loaded.to_shell_script();
+        assert!(script.contains("export JAVA_HOME='/opt/jdk 17'\n"));
+        let gradle = script.find("'/opt/gradle/bin'").unwrap();
+        let jdk = script.find("'/opt/jdk/bin'").unwrap();
+        assert!(gradle < jdk, "first entry must end up first on PATH");
+        assert!(script.ends_with("echo 'ready'\n"));
+
+        let bad = EnvProfile {
+            env: [("NOT-VALID".to_string(), "x".to_string())]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+        assert!(store.save_env_profile("bad", &bad).await.is_err());
+
+        store.delete_env_profile("java").await.unwrap();
+        assert!(store.get_env_profile("java").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_library_agent_versions_and_rollback() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let store = LibraryStore::with_test_store(temp.path().to_path_buf()).await;
+        let agent = |content: &str| LibraryAgent {
+            name: "reviewer".to_string(),
+            description: None,
+            path: String::new(),
+            content: content.to_string(),
+            model: None,
+            tools: HashMap::new(),
+            permissions: HashMap::new(),
+            version: None,
+        };
+
+        let v1 = store
+            .save_library_agent("reviewer", &agent("---\ndescription: v1\n---\nFirst."))
+            .await
+            .unwrap();
+        assert_eq!(v1, "1.0.0");
+
+        // Editing without touching the version bumps the patch component.
+        let current = store.get_library_agent("reviewer").await.unwrap();
+        let edited = current.content.replace("First.", "Second.");
+        let v2 = store
+            .save_library_agent("reviewer", &agent(&edited))
+            .await
+            .unwrap();
+        assert_eq!(v2, "1.0.1");
+
+        let versions = store.list_library_agent_versions("reviewer").await.unwrap();
+        assert_eq!(
+            versions
+                .iter()
+                .map(|v| v.version.as_str())
+                .collect::<Vec<_>>(),
+            vec!["1.0.1", "1.0.0"]
+        );
+        assert!(versions[0].current && !versions[1].current);
+
+        // Recorded versions are immutable.
+        assert!(store
+            .save_library_agent("reviewer", &agent("---\nversion: 1.0.0\n---\nRewritten."))
+            .await
+            .is_err());
+
+        let restored = store
+            .rollback_library_agent("reviewer", "1.0.0")
+            .await
+            .unwrap();
+        assert_eq!(restored.version.as_deref(), Some("1.0.0"));
+        assert!(restored.content.contains("First."));
+        assert!(store
+            .get_library_agent_version("reviewer", "2.0.0")
+            .await
+            .is_err());
+    }
+
+    /// Gzipped tarball of `(path, contents)` files, plus an optional symlink.
+    fn skill_tarball(files: &[(&str, &str)], symlink: Option<&str>) -> Vec<u8> {
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::default(),
+        ));
+        for (path, contents) in files {
+            // Written by hand so that unsafe paths end up in the archive.
+            let mut header = tar::Header::new_gnu();
+            header.as_gnu_mut().unwrap().name[..path.len()].copy_from_slice(path.as_bytes());
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append(&header, contents.as_bytes()).unwrap();
+        }
+        if let Some(path) = symlink {
+            let mut header = tar::Header::new_gnu();
+            header.set_entry_type(tar::EntryType::Symlink);
+            header.set_size(0);
+            header.set_cksum();
+            builder
+                .append_link(&mut header, path, "/etc/passwd")
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_install_skill_package_from_tarball() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let store = LibraryStore::with_test_store(temp.path().join("library")).await;
+
+        let files = [
+            (
+                "review-kit/SKILL.md",
+                "---\ndescription: Code review\n---\nReview things.",
+            ),
+            (
+                "review-kit/skill.json",
+                r#"{"version": "0.2.0", "inputs": [{"name": "target", "required": true}],
+                "required_tools": ["grep_search"], "prompt": "Review {{target}} carefully."}"#,
+            ),
+        ];
+        let unpack_dir = temp.path().join("unpacked");
+        LibraryStore::unpack_skill_tarball(&skill_tarball(&files, None)[..], &unpack_dir).unwrap();
+        let source = "https://example.com/review-kit.tar.gz";
+
+        let skill = store
+            .install_unpacked_skill_package(&unpack_dir, source, None, None)
+            .await
+            .unwrap();
+        assert_eq!(skill.name, "review-kit");
+        assert!(matches!(
+            skill.source,
+            SkillSource::Package { ref version, .. } if version.as_deref() == Some("0.2.0")
+        ));
+        assert_eq!(skill.manifest.unwrap().required_tools, vec!["grep_search"]);
+
+        // Installing the same package twice is rejected.
+        assert!(store
+            .install_unpacked_skill_package(&unpack_dir, source, None, None)
+            .await
+            .is_err());
+
+        assert!(store
+            .invoke_skill("review-kit", &HashMap::new())
+            .await
+            .is_err());
+        let inputs = HashMap::from([("target".to_string(), "src/lib.rs".to_string())]);
+        let prompt = store.invoke_skill("review-kit", &inputs).await.unwrap();
+        assert_eq!(
+            prompt,
+            "Review src/lib.rs carefully.\n\nRequired tools: grep_search"
+        );
+
+        // Links and entries escaping the package are rejected while unpacking.
+        let linked = skill_tarball(&files, Some("review-kit/leak"));
+        assert!(
+            LibraryStore::unpack_skill_tarball(&linked[..], &temp.path().join("linked")).is_err()
+        );
+        let escaping = skill_tarball(&[("review-kit/../../outside", "x")], None);
+        assert!(
+            LibraryStore::unpack_skill_tarball(&escaping[..], &temp.path().join("escaping"))
+                .is_err()
+        );
+        assert!(!temp.path().join("outside").exists());
+    }
+
+    #[tokio::test]
+    async fn test_install_skill_package_rejects_local_and_plain_http_sources() {
+        let temp = tempfile::tempdir().expect("tempdir");
+        let store = LibraryStore::with_test_store(temp.path().join("library")).await;
+        for source in [
+            "/tmp/review-kit.tar.gz",
+            "../review-kit.tgz",
+            "http://example.com/review-kit.tar.gz",
+            "file:///srv/skills.git",
+            "/srv/skills.git",
+            "ssh://example.com/review-kit.tar.gz",
+        ] {
+            let err = store
+                .install_skill_package(source, None, None)
+                .await
+                .unwrap_err();
+            assert!(err.to_string().contains("https or git URL"), "{}", source);
+        }
+    }
 }
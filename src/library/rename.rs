@@ -341,7 +341,7 @@ impl LibraryStore {
     async fn execute_rename(
         &self,
         item_type: ItemType,
-        _old_name: &str,
+        old_name: &str,
         new_name: &str,
         old_path: &Path,
         new_path: &Path,
@@ -366,6 +366,17 @@ impl LibraryStore {
             .await
             .context("Failed to rename file/directory")?;
 
+        // Carry the agent's version history over to the new name
+        if item_type == ItemType::Agent {
+            let versions_root = self.path.join("agent").join(".versions");
+            let old_versions = versions_root.join(old_name);
+            if old_versions.exists() {
+                fs::rename(&old_versions, versions_root.join(new_name))
+                    .await
+                    .context("Failed to rename agent version history")?;
+            }
+        }
+
         Ok(())
     }
 
@@ -1,7 +1,7 @@
 //! Types for the configuration library.
 
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet};
+use std::collections::{BTreeMap, HashMap, HashSet};
 
 use crate::workspace::TailscaleMode;
 
@@ -84,6 +84,9 @@ pub struct LibraryAgentSummary {
     /// Description from frontmatter
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
+    /// Semantic version from frontmatter (e.g., "1.2.0")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
     /// Path relative to library root
     pub path: String,
 }
@@ -110,6 +113,151 @@ pub struct LibraryAgent {
     /// Permission levels: {"bash": "ask", "write": "allow"}
     #[serde(default)]
     pub permissions: HashMap<String, String>,
+    /// Semantic version from frontmatter (e.g., "1.2.0")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+}
+
+/// A recorded version of a library agent.
+/// Every save snapshots the agent under `agent/.versions/<name>/<version>.md`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryAgentVersion {
+    /// Semantic version (e.g., "1.2.0")
+    pub version: String,
+    /// Path relative to library root
+    pub path: String,
+    /// Whether this is the version currently in `agent/<name>.md`
+    #[serde(default)]
+    pub current: bool,
+}
+
+/// A parsed `major.minor.patch` semantic version.
+/// Pre-release and build suffixes are not supported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct SemVer {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl SemVer {
+    /// Parse a version string, accepting an optional leading `v`.
+    /// Missing minor/patch components default to zero ("1.2" → 1.2.0).
+    pub fn parse(raw: &str) -> Option<Self> {
+        let trimmed = raw.trim();
+        let trimmed = trimmed.strip_prefix('v').unwrap_or(trimmed);
+        let mut parts = trimmed.split('.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = match parts.next() {
+            Some(p) => p.parse().ok()?,
+            None => 0,
+        };
+        let patch = match parts.next() {
+            Some(p) => p.parse().ok()?,
+            None => 0,
+        };
+        if parts.next().is_some() {
+            return None;
+        }
+        Some(Self {
+            major,
+            minor,
+            patch,
+        })
+    }
+
+    /// Next patch version.
+    pub fn bump_patch(self) -> Self {
+        Self {
+            patch: self.patch + 1,
+            ..self
+        }
+    }
+}
+
+impl std::fmt::Display for SemVer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// System Prompt Types
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Layer a system prompt fragment belongs to.
+/// Layers are merged in declaration order: base, then workspace, then agent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptScope {
+    /// Global base prompt applied to every mission (`prompt/base.md`)
+    Base,
+    /// Workspace-level additions such as project conventions (`prompt/workspace/<name>.md`)
+    Workspace,
+    /// Agent-level persona (`prompt/agent/<name>.md`)
+    Agent,
+}
+
+impl PromptScope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PromptScope::Base => "base",
+            PromptScope::Workspace => "workspace",
+            PromptScope::Agent => "agent",
+        }
+    }
+}
+
+impl std::str::FromStr for PromptScope {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "base" => Ok(PromptScope::Base),
+            "workspace" => Ok(PromptScope::Workspace),
+            "agent" => Ok(PromptScope::Agent),
+            other => anyhow::bail!("Unknown prompt scope: {}", other),
+        }
+    }
+}
+
+/// A single system prompt fragment stored in the library.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptLayer {
+    /// Layer this fragment belongs to
+    pub scope: PromptScope,
+    /// Workspace or agent name (None for the base layer)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Path relative to library root
+    pub path: String,
+    /// Markdown content of the fragment
+    pub content: String,
+}
+
+/// Result of merging the prompt layers for a workspace/agent pair.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptPreview {
+    /// Layers that contributed to the merged prompt, in merge order
+    pub layers: Vec<PromptLayer>,
+    /// Final merged prompt (empty when no layers are configured)
+    pub prompt: String,
+}
+
+/// Merge prompt layers deterministically.
+///
+/// Layers are ordered by scope (base → workspace → agent), empty fragments are
+/// dropped, and the remaining fragments are joined with a blank line so that
+/// later layers can refine earlier ones.
+pub fn merge_prompt_layers(layers: &[PromptLayer]) -> String {
+    let mut ordered: Vec<&PromptLayer> = layers.iter().collect();
+    ordered.sort_by_key(|layer| layer.scope);
+    ordered
+        .iter()
+        .map(|layer| layer.content.trim())
+        .filter(|content| !content.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n\n")
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -215,6 +363,80 @@ pub struct InitScript {
     pub content: String,
 }
 
+// ─────────────────────────────────────────────────────────────────────────────
+// Environment Profile Types
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Environment profile summary for listing.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnvProfileSummary {
+    /// Profile name (file stem, e.g., "node-20")
+    pub name: String,
+    /// Optional description
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Path relative to library root (e.g., "env-profile/node-20.json")
+    pub path: String,
+}
+
+/// Toolchain environment applied to every process a mission spawns.
+/// Selected per workspace or per mission (the mission's choice wins).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EnvProfile {
+    /// Profile name
+    #[serde(default)]
+    pub name: String,
+    /// Optional description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Path relative to library root
+    #[serde(default)]
+    pub path: String,
+    /// Environment variables to set (e.g., JAVA_HOME)
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Directories prepended to PATH, highest priority first
+    #[serde(default)]
+    pub path_prepend: Vec<String>,
+    /// Shell snippet sourced after the variables are set (e.g., `nvm use 20`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub init: Option<String>,
+}
+
+impl EnvProfile {
+    /// Render the profile as a bash script suitable for `BASH_ENV`.
+    ///
+    /// PATH entries are only added when missing, so sourcing the script from
+    /// nested shells does not keep growing PATH.
+    pub fn to_shell_script(&self) -> String {
+        let mut script = format!(
+            "# Environment profile: {}\n# Generated by sandboxed.sh; edit the library profile instead.\n",
+            self.name
+        );
+        for (key, value) in &self.env {
+            script.push_str(&format!("export {}={}\n", key, shell_quote(value)));
+        }
+        for dir in self.path_prepend.iter().rev() {
+            let dir = shell_quote(dir);
+            script.push_str(&format!(
+                "case \":$PATH:\" in *:{dir}:*) ;; *) export PATH={dir}:\"$PATH\" ;; esac\n"
+            ));
+        }
+        if let Some(init) = self.init.as_deref().map(str::trim) {
+            if !init.is_empty() {
+                script.push_str(init);
+                script.push('\n');
+            }
+        }
+        script
+    }
+}
+
+/// Single-quote a value for POSIX shells.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Skill Types (supports multiple .md files per skill)
 // ─────────────────────────────────────────────────────────────────────────────
@@ -254,6 +476,112 @@ pub enum SkillSource {
         #[serde(skip_serializing_if = "Option::is_none")]
         updated_at: Option<String>,
     },
+    /// Skill package installed from a git URL or tarball
+    Package {
+        /// Git URL or tarball URL the package was fetched from
+        url: String,
+        /// Subdirectory within the source that holds the skill
+        #[serde(skip_serializing_if = "Option::is_none")]
+        path: Option<String>,
+        /// Package version from the manifest
+        #[serde(skip_serializing_if = "Option::is_none")]
+        version: Option<String>,
+        /// When the skill was installed
+        #[serde(skip_serializing_if = "Option::is_none")]
+        installed_at: Option<String>,
+    },
+}
+
+/// An input declared by a skill manifest.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SkillInput {
+    /// Input name, referenced as `{{name}}` in the skill prompt
+    pub name: String,
+    /// Description shown to the agent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Whether the input must be provided on invocation
+    #[serde(default)]
+    pub required: bool,
+    /// Value used when the input is omitted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+
+/// Skill package manifest (`skill.json` next to SKILL.md).
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct SkillManifest {
+    /// Package name (used as the install name when none is given)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Package version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Short description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Inputs accepted when invoking the skill
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub inputs: Vec<SkillInput>,
+    /// Tools the skill expects the agent to have
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub required_tools: Vec<String>,
+    /// Prompt template; defaults to the SKILL.md body when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt: Option<String>,
+}
+
+impl SkillManifest {
+    /// Check that inputs and required tools are well-formed.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        let mut seen = HashSet::new();
+        for input in &self.inputs {
+            if input.name.is_empty()
+                || !input
+                    .name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+            {
+                anyhow::bail!("Invalid skill input name: '{}'", input.name);
+            }
+            if !seen.insert(input.name.as_str()) {
+                anyhow::bail!("Duplicate skill input: '{}'", input.name);
+            }
+        }
+        if let Some(tool) = self.required_tools.iter().find(|t| t.trim().is_empty()) {
+            anyhow::bail!("Invalid required tool name: '{}'", tool);
+        }
+        Ok(())
+    }
+
+    /// Render the skill prompt by substituting `{{input}}` placeholders.
+    ///
+    /// `body` is used when the manifest has no explicit prompt. Missing required
+    /// inputs are an error; unknown inputs are ignored.
+    pub fn render_prompt(
+        &self,
+        body: &str,
+        values: &HashMap<String, String>,
+    ) -> anyhow::Result<String> {
+        let mut prompt = self.prompt.clone().unwrap_or_else(|| body.to_string());
+        for input in &self.inputs {
+            let value = match values.get(&input.name).or(input.default.as_ref()) {
+                Some(value) => value.as_str(),
+                None if input.required => {
+                    anyhow::bail!("Missing required skill input: '{}'", input.name)
+                }
+                None => "",
+            };
+            prompt = prompt.replace(&format!("{{{{{}}}}}", input.name), value);
+        }
+        if !self.required_tools.is_empty() {
+            prompt.push_str(&format!(
+                "\n\nRequired tools: {}",
+                self.required_tools.join(", ")
+            ));
+        }
+        Ok(prompt.trim().to_string())
+    }
 }
 
 /// Skill summary for listing (without full content).
@@ -272,6 +600,9 @@ pub struct SkillSummary {
     /// Shell commands to run during workspace setup (e.g., install dependencies)
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub setup_commands: Vec<String>,
+    /// Package manifest (`skill.json`), if the skill ships one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<SkillManifest>,
 }
 
 /// Full skill with content.
@@ -298,6 +629,9 @@ pub struct Skill {
     /// Shell commands to run during workspace setup (e.g., install dependencies)
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub setup_commands: Vec<String>,
+    /// Package manifest (`skill.json`), if the skill ships one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub manifest: Option<SkillManifest>,
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -510,10 +844,98 @@ pub struct AmpCodeConfig {
     pub default_mode: Option<String>,
 }
 
+/// Ollama configuration stored in the Library.
+/// Points the `ollama` backend at a daemon and picks its default model.
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct OllamaConfig {
+    /// Daemon URL (e.g. "http://gpu-box:11434"); defaults to `OLLAMA_BASE_URL`.
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// Default model for Ollama missions (e.g. "qwen2.5-coder:14b").
+    #[serde(default)]
+    pub default_model: Option<String>,
+}
+
 // ─────────────────────────────────────────────────────────────────────────────
 // Config Profile Types
 // ─────────────────────────────────────────────────────────────────────────────
 
+/// Tools a profile allows or denies. Applied to Claude Code missions as
+/// `permissions` in `.claude/settings.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct ToolPolicy {
+    /// Permission rules to allow (e.g. "Bash(git status)"). A child profile's
+    /// non-empty list replaces its parent's.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+    /// Permission rules to deny (e.g. "WebFetch"). Denies accumulate down the
+    /// inheritance chain, so a personal profile can't lift a team deny.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub deny: Vec<String>,
+}
+
+/// Structured profile settings, stored in `.sandboxed-sh/profile.json`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Default)]
+pub struct ConfigProfileSettings {
+    /// Parent profile whose settings this one inherits and overrides
+    /// (e.g. a team base profile under personal overrides).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    /// Default model per backend id (e.g. {"claudecode": "claude-sonnet-4-20250514"}),
+    /// used when a mission has no model override.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub models: BTreeMap<String, String>,
+    /// Environment variables resolved from the secrets vault at turn start,
+    /// as `"registry/key"` references (e.g. {"OPENAI_API_KEY": "openai/api_key"}).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub api_key_refs: BTreeMap<String, String>,
+    /// Text appended to the mission system prompt. Inherited prompts are
+    /// concatenated, parent first.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt_append: Option<String>,
+    #[serde(default)]
+    pub tool_policy: ToolPolicy,
+}
+
+impl ConfigProfileSettings {
+    /// Layer `child` over `self` (the parent).
+    pub fn merged_with(mut self, child: &ConfigProfileSettings) -> Self {
+        self.extends = child.extends.clone();
+        self.models
+            .extend(child.models.iter().map(|(k, v)| (k.clone(), v.clone())));
+        self.api_key_refs.extend(
+            child
+                .api_key_refs
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone())),
+        );
+        self.system_prompt_append = match (
+            self.system_prompt_append.take(),
+            child.system_prompt_append.as_deref(),
+        ) {
+            (Some(parent), Some(child)) => Some(format!("{}\n\n{}", parent, child)),
+            (parent, child) => child.map(str::to_string).or(parent),
+        };
+        if !child.tool_policy.allow.is_empty() {
+            self.tool_policy.allow = child.tool_policy.allow.clone();
+        }
+        for rule in &child.tool_policy.deny {
+            if !self.tool_policy.deny.contains(rule) {
+                self.tool_policy.deny.push(rule.clone());
+            }
+        }
+        self
+    }
+}
+
+/// A profile's settings after applying its inheritance chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedConfigProfileSettings {
+    /// Profiles applied, root first, ending with the requested profile.
+    pub chain: Vec<String>,
+    pub settings: ConfigProfileSettings,
+}
+
 /// Config profile summary for listing.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConfigProfileSummary {
@@ -567,6 +989,9 @@ pub struct ConfigProfile {
     /// Amp Code config
     #[serde(default)]
     pub ampcode_config: AmpCodeConfig,
+    /// Ollama config
+    #[serde(default)]
+    pub ollama_config: OllamaConfig,
 }
 
 // ─────────────────────────────────────────────────────────────────────────────
@@ -741,6 +1166,52 @@ pub fn extract_model(frontmatter: &Option<serde_yaml::Value>) -> Option<String>
     })
 }
 
+/// Extract version from YAML frontmatter.
+/// Accepts both string (`version: "1.2.0"`) and numeric (`version: 1.2`) values.
+pub fn extract_version(frontmatter: &Option<serde_yaml::Value>) -> Option<String> {
+    frontmatter
+        .as_ref()
+        .and_then(|fm| fm.get("version"))
+        .and_then(|v| match v {
+            serde_yaml::Value::String(s) => Some(s.clone()),
+            serde_yaml::Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        })
+}
+
+/// Set the `version` field in YAML frontmatter, adding a frontmatter block if needed.
+pub fn set_frontmatter_version(content: &str, version: &str) -> String {
+    let line = format!("version: {}", version);
+
+    if let Some(rest) = content.strip_prefix("---\n") {
+        if let Some(close) = rest.find("\n---") {
+            let (yaml, tail) = rest.split_at(close);
+            let mut replaced = false;
+            let lines: Vec<String> = yaml
+                .lines()
+                .map(|l| {
+                    if !replaced && l.starts_with("version:") {
+                        replaced = true;
+                        line.clone()
+                    } else {
+                        l.to_string()
+                    }
+                })
+                .collect();
+            let mut yaml = lines.join("\n");
+            if !replaced {
+                if !yaml.is_empty() {
+                    yaml.push('\n');
+                }
+                yaml.push_str(&line);
+            }
+            return format!("---\n{}{}", yaml, tail);
+        }
+    }
+
+    format!("---\n{}\n---\n\n{}", line, content)
+}
+
 /// Extract tools map from YAML frontmatter.
 pub fn extract_tools(frontmatter: &Option<serde_yaml::Value>) -> HashMap<String, bool> {
     frontmatter
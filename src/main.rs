@@ -1,12 +1,82 @@
 //! sandboxed.sh - HTTP Server Entry Point
 //!
 //! Starts the HTTP server that exposes the agent API.
+//!
+//! Command-line modes run without serving the API, print their result as
+//! JSON on stdout (logs go to stderr) and exit non-zero on failure:
+//!
+//! - `--task "<prompt>" [--workspace <id>] [--timeout <secs>]` runs a single
+//!   mission (see `api::headless`)
+//! - `--eval <suite>` runs an evaluation suite and fails unless every task
+//!   passed (see `api::eval`)
+//!
+//! Both take `--backend <id>` and `--model <model>`.
+
+use std::path::PathBuf;
+use std::time::Duration;
 
+use anyhow::Context;
 use sandboxed_sh::{api, config::Config, library::env_crypto};
 use tracing::{info, warn};
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use uuid::Uuid;
+
+/// Command-line options; without any the HTTP server starts.
+#[derive(Debug, Default, PartialEq)]
+struct Args {
+    task: Option<String>,
+    eval: Option<PathBuf>,
+    backend: Option<String>,
+    model: Option<String>,
+    workspace: Option<Uuid>,
+    timeout_secs: Option<u64>,
+}
+
+impl Args {
+    /// Whether a command-line mode runs instead of the server.
+    fn is_command(&self) -> bool {
+        self.task.is_some() || self.eval.is_some()
+    }
+}
+
+fn parse_args(args: impl IntoIterator<Item = String>) -> anyhow::Result<Args> {
+    let mut parsed = Args::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .with_context(|| format!("{} requires a value", arg))
+        };
+        match arg.as_str() {
+            "--task" => parsed.task = Some(value()?),
+            "--eval" => parsed.eval = Some(PathBuf::from(value()?)),
+            "--backend" => parsed.backend = Some(value()?),
+            "--model" => parsed.model = Some(value()?),
+            "--workspace" => {
+                parsed.workspace = Some(value()?.parse().context("--workspace must be a UUID")?)
+            }
+            "--timeout" => {
+                parsed.timeout_secs = Some(
+                    value()?
+                        .parse()
+                        .context("--timeout must be a number of seconds")?,
+                )
+            }
+            other => anyhow::bail!("Unknown argument: {}", other),
+        }
+    }
+    if parsed.task.is_some() && parsed.eval.is_some() {
+        anyhow::bail!("--task and --eval cannot be combined");
+    }
+    if parsed.task.as_deref().is_some_and(|t| t.trim().is_empty()) {
+        anyhow::bail!("--task needs a prompt");
+    }
+    Ok(parsed)
+}
 
 fn main() -> anyhow::Result<()> {
+    let args = parse_args(std::env::args().skip(1))?;
     // Use a custom tokio runtime with larger worker thread stacks (16 MB instead of default 2 MB).
     // Deep async call chains in the mission runner (workspace prep → config write → nspawn exec)
     // can overflow the default 2 MB worker stack.
@@ -14,17 +84,22 @@ fn main() -> anyhow::Result<()> {
         .enable_all()
         .thread_stack_size(16 * 1024 * 1024)
         .build()?;
-    runtime.block_on(async_main())
+    runtime.block_on(async_main(args))
 }
 
-async fn async_main() -> anyhow::Result<()> {
-    // Initialize logging
+async fn async_main(args: Args) -> anyhow::Result<()> {
+    // Initialize logging (on stderr in command-line modes, stdout carries the result)
+    let log_writer = if args.is_command() {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
     tracing_subscriber::registry()
         .with(
             tracing_subscriber::EnvFilter::try_from_default_env()
                 .unwrap_or_else(|_| "sandboxed_sh=debug,tower_http=debug".into()),
         )
-        .with(tracing_subscriber::fmt::layer())
+        .with(tracing_subscriber::fmt::layer().with_writer(log_writer))
         .init();
 
     // Load configuration
@@ -63,6 +138,31 @@ async fn async_main() -> anyhow::Result<()> {
         ),
     }
 
+    if let Some(task) = args.task {
+        let state = api::init_state(config).await?;
+        let options = api::MissionOptions {
+            title: None,
+            workspace_id: args.workspace,
+            backend: args.backend,
+            model: args.model,
+        };
+        let timeout = args.timeout_secs.map(Duration::from_secs);
+        let result = api::headless::run_local(&state, &task, &options, timeout)
+            .await
+            .map_err(anyhow::Error::msg)?;
+        println!("{}", serde_json::to_string_pretty(&result)?);
+        std::process::exit(if result.success { 0 } else { 1 });
+    }
+
+    if let Some(suite) = args.eval {
+        let state = api::init_state(config).await?;
+        let report = api::eval::run_local(&state, &suite, args.backend, args.model)
+            .await
+            .map_err(anyhow::Error::msg)?;
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        std::process::exit(if report.all_passed() { 0 } else { 1 });
+    }
+
     // Start HTTP server
     let addr = format!("{}:{}", config.host, config.port);
     info!("Starting server on {}", addr);
@@ -71,3 +171,33 @@ async fn async_main() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> anyhow::Result<Args> {
+        parse_args(list.iter().map(|s| s.to_string()))
+    }
+
+    #[test]
+    fn parses_eval_flags() {
+        assert_eq!(args(&[]).unwrap(), Args::default());
+        let parsed = args(&["--eval", "evals/smoke", "--backend", "claudecode"]).unwrap();
+        assert_eq!(parsed.eval, Some(PathBuf::from("evals/smoke")));
+        assert_eq!(parsed.backend.as_deref(), Some("claudecode"));
+        assert!(args(&["--eval"]).is_err());
+        assert!(args(&["--serve"]).is_err());
+    }
+
+    #[test]
+    fn parses_task_flags() {
+        let parsed = args(&["--task", "fix the lint errors", "--timeout", "600"]).unwrap();
+        assert_eq!(parsed.task.as_deref(), Some("fix the lint errors"));
+        assert_eq!(parsed.timeout_secs, Some(600));
+        assert!(parsed.is_command());
+        assert!(args(&["--task", "x", "--workspace", "nope"]).is_err());
+        assert!(args(&["--task", " "]).is_err());
+        assert!(args(&["--task", "x", "--eval", "evals"]).is_err());
+    }
+}
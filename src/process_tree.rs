@@ -0,0 +1,272 @@
+//! Terminating a CLI process together with the tool processes it spawned.
+//!
+//! Agent CLIs run tool commands (builds, dev servers, test watchers) as child
+//! processes, often in their own process groups. Killing only the CLI leaves
+//! those running after a mission is cancelled. [`terminate_tree`] snapshots
+//! every descendant of the CLI from `/proc`, signals each process group they
+//! belong to with SIGTERM, and after a grace period sends SIGKILL to whatever
+//! is still alive.
+//!
+//! Processes that daemonized (re-parented to init) before the snapshot are no
+//! longer descendants and are not found.
+//!
+//! On Windows there are no signals or process groups; the tree is ended with
+//! `taskkill /T /F`, which walks the parent chain itself.
+
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// How long processes get to exit after SIGTERM before SIGKILL.
+pub const TERM_GRACE: Duration = Duration::from_secs(3);
+
+#[cfg(unix)]
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A process as seen in `/proc/<pid>/stat`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcInfo {
+    pub pid: u32,
+    pub ppid: u32,
+    pub pgid: u32,
+}
+
+/// Outcome of [`terminate_tree`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReapReport {
+    /// Processes that were signalled and have exited
+    pub reaped: Vec<u32>,
+    /// Subset of `reaped` that ignored SIGTERM and needed SIGKILL
+    pub killed: Vec<u32>,
+    /// Processes still alive after SIGKILL (e.g. stuck in uninterruptible IO)
+    pub survivors: Vec<u32>,
+}
+
+/// Parse the pid, ppid and pgid out of a `/proc/<pid>/stat` line. The command
+/// name is parenthesized and may itself contain spaces or parentheses.
+pub fn parse_stat(stat: &str) -> Option<ProcInfo> {
+    let pid = stat.split_whitespace().next()?.parse().ok()?;
+    let rest = &stat[stat.rfind(')')? + 1..];
+    let mut fields = rest.split_whitespace();
+    let _state = fields.next()?;
+    let ppid = fields.next()?.parse().ok()?;
+    let pgid = fields.next()?.parse().ok()?;
+    Some(ProcInfo { pid, ppid, pgid })
+}
+
+#[cfg(unix)]
+fn read_proc(pid: u32) -> Option<ProcInfo> {
+    parse_stat(&std::fs::read_to_string(format!("/proc/{}/stat", pid)).ok()?)
+}
+
+#[cfg(unix)]
+fn list_procs() -> Vec<ProcInfo> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(read_proc)
+        .collect()
+}
+
+/// `root` and all of its descendants in `procs`, parents before children.
+pub fn collect_tree(procs: &[ProcInfo], root: u32) -> Vec<ProcInfo> {
+    let mut children: HashMap<u32, Vec<ProcInfo>> = HashMap::new();
+    for proc in procs {
+        children.entry(proc.ppid).or_default().push(*proc);
+    }
+    let mut tree: Vec<ProcInfo> = procs.iter().filter(|p| p.pid == root).copied().collect();
+    let mut index = 0;
+    while index < tree.len() {
+        if let Some(kids) = children.get(&tree[index].pid) {
+            tree.extend(kids.iter().filter(|kid| kid.pid != root));
+        }
+        index += 1;
+    }
+    tree
+}
+
+#[cfg(unix)]
+fn alive(pid: u32) -> bool {
+    // Zombies still have a /proc entry but are already dead.
+    std::fs::read_to_string(format!("/proc/{}/stat", pid))
+        .ok()
+        .and_then(|stat| {
+            let rest = &stat[stat.rfind(')')? + 1..];
+            rest.split_whitespace().next().map(|state| state != "Z")
+        })
+        .unwrap_or(false)
+}
+
+/// Signal every process group in `tree`. Processes sharing this server's
+/// group are signalled individually so the server never signals itself.
+#[cfg(unix)]
+fn signal_tree(tree: &[ProcInfo], signal: i32) {
+    // SAFETY: getpgrp has no preconditions.
+    let own_pgid = unsafe { libc::getpgrp() } as u32;
+    let mut groups = std::collections::HashSet::new();
+    for proc in tree {
+        if proc.pid == 0 {
+            continue;
+        }
+        if proc.pgid != 0 && proc.pgid != own_pgid {
+            if groups.insert(proc.pgid) {
+                // SAFETY: pgid is non-zero and not our own group.
+                unsafe {
+                    libc::killpg(proc.pgid as i32, signal);
+                }
+            }
+        } else {
+            // SAFETY: pid is non-zero, so this targets a single process.
+            unsafe {
+                libc::kill(proc.pid as i32, signal);
+            }
+        }
+    }
+}
+
+/// Terminate `root_pid` and every process it spawned: SIGTERM to all of their
+/// process groups, then SIGKILL after `grace` to anything still running.
+#[cfg(unix)]
+pub async fn terminate_tree(root_pid: u32, grace: Duration) -> ReapReport {
+    let tree = tokio::task::spawn_blocking(move || collect_tree(&list_procs(), root_pid))
+        .await
+        .unwrap_or_default();
+    if tree.is_empty() {
+        return ReapReport::default();
+    }
+
+    signal_tree(&tree, libc::SIGTERM);
+    let deadline = tokio::time::Instant::now() + grace;
+    while tree.iter().any(|proc| alive(proc.pid)) && tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+
+    let stubborn: Vec<ProcInfo> = tree.iter().filter(|p| alive(p.pid)).copied().collect();
+    if !stubborn.is_empty() {
+        signal_tree(&stubborn, libc::SIGKILL);
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(1);
+        while stubborn.iter().any(|proc| alive(proc.pid)) && tokio::time::Instant::now() < deadline
+        {
+            tokio::time::sleep(POLL_INTERVAL).await;
+        }
+    }
+
+    let mut report = ReapReport::default();
+    for proc in &tree {
+        if alive(proc.pid) {
+            report.survivors.push(proc.pid);
+        } else {
+            report.reaped.push(proc.pid);
+            if stubborn.iter().any(|p| p.pid == proc.pid) {
+                report.killed.push(proc.pid);
+            }
+        }
+    }
+    report
+}
+
+/// Terminate `root_pid` and every process it spawned with `taskkill /T /F`.
+/// Windows has no graceful equivalent of SIGTERM for console programs, so
+/// `grace` is unused.
+#[cfg(windows)]
+pub async fn terminate_tree(root_pid: u32, _grace: Duration) -> ReapReport {
+    let status = tokio::process::Command::new("taskkill")
+        .args(["/PID", &root_pid.to_string(), "/T", "/F"])
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()
+        .await;
+    let mut report = ReapReport::default();
+    if status.is_ok_and(|status| status.success()) {
+        report.reaped.push(root_pid);
+        report.killed.push(root_pid);
+    } else {
+        report.survivors.push(root_pid);
+    }
+    report
+}
+
+/// Ask a single process to exit: SIGTERM on Unix, `taskkill /F` on Windows.
+pub fn terminate_process(pid: u32) {
+    if pid == 0 {
+        return;
+    }
+    #[cfg(unix)]
+    // SAFETY: pid is non-zero, so this targets a single process and never
+    // the caller's process group.
+    unsafe {
+        libc::kill(pid as i32, libc::SIGTERM);
+    }
+    #[cfg(windows)]
+    {
+        let _ = std::process::Command::new("taskkill")
+            .args(["/PID", &pid.to_string(), "/F"])
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_stat_with_awkward_command_names() {
+        assert_eq!(
+            parse_stat("4242 (npm run (dev)) S 4200 4242 4200 0 -1 4194560"),
+            Some(ProcInfo {
+                pid: 4242,
+                ppid: 4200,
+                pgid: 4242
+            })
+        );
+        assert_eq!(parse_stat("garbage"), None);
+    }
+
+    #[test]
+    fn collects_descendants_only() {
+        let proc = |pid, ppid, pgid| ProcInfo { pid, ppid, pgid };
+        let procs = [
+            proc(1, 0, 1),
+            proc(10, 1, 10),  // CLI
+            proc(11, 10, 10), // bash tool
+            proc(12, 11, 12), // dev server in its own group
+            proc(13, 12, 12),
+            proc(20, 1, 20), // unrelated
+        ];
+        let pids: Vec<u32> = collect_tree(&procs, 10).iter().map(|p| p.pid).collect();
+        assert_eq!(pids, vec![10, 11, 12, 13]);
+        assert!(collect_tree(&procs, 99).is_empty());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn terminates_grandchildren_in_other_groups() {
+        let mut child = tokio::process::Command::new("sh")
+            .arg("-c")
+            .arg("setsid sleep 300 & sleep 300 & wait")
+            .process_group(0)
+            .spawn()
+            .unwrap();
+        let root = child.id().unwrap();
+        // Let the shell start its children.
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        let before = collect_tree(&list_procs(), root);
+        assert!(
+            before.len() >= 3,
+            "expected sleeps under {}: {:?}",
+            root,
+            before
+        );
+
+        let report = terminate_tree(root, Duration::from_secs(2)).await;
+        let _ = child.wait().await;
+        assert!(report.survivors.is_empty(), "{:?}", report);
+        for proc in before {
+            assert!(report.reaped.contains(&proc.pid));
+            assert!(!alive(proc.pid));
+        }
+    }
+}
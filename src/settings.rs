@@ -15,6 +15,11 @@ static RTK_ENABLED_CACHED: AtomicBool = AtomicBool::new(false);
 /// Global cached max parallel missions value.
 /// A value of 0 means "unset" and callers should fall back to their default.
 static MAX_PARALLEL_MISSIONS_CACHED: AtomicUsize = AtomicUsize::new(0);
+/// Global cached reserved interactive slots.
+/// `usize::MAX` means "unset" and callers fall back to the env var.
+static RESERVED_INTERACTIVE_SLOTS_CACHED: AtomicUsize = AtomicUsize::new(usize::MAX);
+/// Global cached dry-run state, updated when settings change.
+static DRY_RUN_CACHED: AtomicBool = AtomicBool::new(false);
 
 /// Default repo path for sandboxed.sh source (used for self-updates).
 pub const DEFAULT_SANDBOXED_REPO_PATH: &str = "/opt/sandboxed-sh/vaduz-v1";
@@ -50,6 +55,19 @@ pub struct Settings {
     /// When None, falls back to the MAX_PARALLEL_MISSIONS env var (default: 1).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub max_parallel_missions: Option<usize>,
+    /// Parallel slots kept free for interactive missions; automations only
+    /// use the rest. When None, falls back to the RESERVED_INTERACTIVE_SLOTS
+    /// env var (default: 0).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reserved_interactive_slots: Option<usize>,
+    /// Whether every mission runs in dry-run mode (Bash/Edit/Write simulated).
+    /// When None, falls back to the SANDBOXED_SH_DRY_RUN env var (default: false).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dry_run: Option<bool>,
+    /// Default language of agent-facing metadata (activity labels, notification
+    /// templates). When None, falls back to the SANDBOXED_SH_LOCALE env var (default: en).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
 }
 
 /// In-memory store for global settings with disk persistence.
@@ -123,6 +141,9 @@ impl SettingsStore {
             auth: None,
             rtk_enabled,
             max_parallel_missions,
+            reserved_interactive_slots: None,
+            dry_run: crate::util::env_var_bool("SANDBOXED_SH_DRY_RUN", false).then_some(true),
+            locale: None,
         }
     }
 
@@ -290,6 +311,13 @@ impl SettingsStore {
             if let Some(limit) = settings.max_parallel_missions {
                 set_max_parallel_missions_cached(limit);
             }
+            if let Some(slots) = settings.reserved_interactive_slots {
+                set_reserved_interactive_slots_cached(slots);
+            }
+            if let Some(enabled) = settings.dry_run {
+                set_dry_run_cached(enabled);
+            }
+            crate::i18n::set_default_locale_cached(settings.locale.as_deref());
         }
     }
 }
@@ -326,3 +354,33 @@ pub fn max_parallel_missions_cached_or(default: usize) -> usize {
 pub fn set_max_parallel_missions_cached(max_parallel_missions: usize) {
     MAX_PARALLEL_MISSIONS_CACHED.store(max_parallel_missions.max(1), Ordering::Relaxed);
 }
+
+/// Parallel slots reserved for interactive missions: the cached setting, or
+/// the `RESERVED_INTERACTIVE_SLOTS` env var when the setting was never changed.
+pub fn reserved_interactive_slots() -> usize {
+    match RESERVED_INTERACTIVE_SLOTS_CACHED.load(Ordering::Relaxed) {
+        usize::MAX => std::env::var("RESERVED_INTERACTIVE_SLOTS")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(0),
+        slots => slots,
+    }
+}
+
+/// Update the cached reserved interactive slots.
+pub fn set_reserved_interactive_slots_cached(slots: usize) {
+    RESERVED_INTERACTIVE_SLOTS_CACHED.store(slots.min(usize::MAX - 1), Ordering::Relaxed);
+}
+
+/// Whether dry-run mode is on globally: the cached setting, or the
+/// `SANDBOXED_SH_DRY_RUN` env var when the setting was never changed.
+pub fn dry_run_enabled() -> bool {
+    DRY_RUN_CACHED.load(Ordering::Relaxed)
+        || crate::util::env_var_bool("SANDBOXED_SH_DRY_RUN", false)
+}
+
+/// Update the cached dry-run state.
+/// Called during startup and when the setting is changed via the API.
+pub fn set_dry_run_cached(enabled: bool) {
+    DRY_RUN_CACHED.store(enabled, Ordering::Relaxed);
+}
@@ -0,0 +1,325 @@
+//! SSH remote execution for missions.
+//!
+//! A workspace can list the remote hosts its missions may reach. Each host
+//! names a private key stored in the secrets vault (registry
+//! [`SSH_KEY_REGISTRY`]). Commands run from the backend with the system `ssh`
+//! client, so keys never enter the workspace. The key and the pinned host key
+//! are written to a private temp directory for the duration of the call.
+//!
+//! Only hosts on the allowlist are reachable, and they are matched by exact
+//! name. Authentication is non-interactive (`BatchMode`). Host keys are
+//! verified against `host_key` when it is set; otherwise the first key seen is
+//! accepted for that call only.
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+/// Secrets registry holding SSH private keys, keyed by [`SshHost::key_secret`].
+pub const SSH_KEY_REGISTRY: &str = "ssh-keys";
+
+pub const DEFAULT_TIMEOUT_SECS: u64 = 120;
+pub const MAX_TIMEOUT_SECS: u64 = 3600;
+const CONNECT_TIMEOUT_SECS: u64 = 15;
+/// Output beyond this many bytes per stream is truncated.
+const MAX_OUTPUT_BYTES: usize = 100_000;
+
+fn default_port() -> u16 {
+    22
+}
+
+/// A remote host missions in a workspace may run commands on.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SshHost {
+    /// Name the agent uses to pick the host (defaults to `host`).
+    #[serde(default)]
+    pub name: String,
+    pub host: String,
+    #[serde(default = "default_port")]
+    pub port: u16,
+    pub user: String,
+    /// Key of the private key in the `ssh-keys` secrets registry.
+    pub key_secret: String,
+    /// Expected host public key (`ssh-ed25519 AAAA...`), as in `known_hosts`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub host_key: Option<String>,
+}
+
+impl SshHost {
+    /// `user@host:port`, for logs and audit entries.
+    pub fn target(&self) -> String {
+        format!("{}@{}:{}", self.user, self.host, self.port)
+    }
+
+    /// `known_hosts` line pinning this host's key.
+    fn known_hosts_line(&self, key: &str) -> String {
+        if self.port == 22 {
+            format!("{} {}", self.host, key.trim())
+        } else {
+            format!("[{}]:{} {}", self.host, self.port, key.trim())
+        }
+    }
+}
+
+/// Per-workspace SSH settings.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SshAccess {
+    #[serde(default)]
+    pub hosts: Vec<SshHost>,
+}
+
+impl SshAccess {
+    /// Look up an allowlisted host by name.
+    pub fn host(&self, name: &str) -> anyhow::Result<&SshHost> {
+        let name = name.trim();
+        self.hosts.iter().find(|h| h.name == name).ok_or_else(|| {
+            let allowed: Vec<&str> = self.hosts.iter().map(|h| h.name.as_str()).collect();
+            anyhow::anyhow!(
+                "Host '{}' is not allowlisted for this workspace (allowed: {})",
+                name,
+                allowed.join(", ")
+            )
+        })
+    }
+
+    /// Validate and fill defaults. Returns `None` when no hosts are listed.
+    pub fn normalized(mut self) -> anyhow::Result<Option<Self>> {
+        for host in &mut self.hosts {
+            host.host = host.host.trim().to_string();
+            host.user = host.user.trim().to_string();
+            host.key_secret = host.key_secret.trim().to_string();
+            host.name = host.name.trim().to_string();
+            if host.name.is_empty() {
+                host.name = host.host.clone();
+            }
+            host.host_key = host
+                .host_key
+                .take()
+                .map(|k| k.trim().to_string())
+                .filter(|k| !k.is_empty());
+            if host.host.is_empty() || host.user.is_empty() || host.key_secret.is_empty() {
+                anyhow::bail!("SSH hosts need 'host', 'user' and 'key_secret'");
+            }
+            if host.host.starts_with('-') || host.user.starts_with('-') {
+                anyhow::bail!("Invalid SSH host '{}'", host.name);
+            }
+        }
+        let mut names: Vec<&str> = self.hosts.iter().map(|h| h.name.as_str()).collect();
+        names.sort_unstable();
+        if names.windows(2).any(|w| w[0] == w[1]) {
+            anyhow::bail!("SSH host names must be unique");
+        }
+        Ok(if self.hosts.is_empty() {
+            None
+        } else {
+            Some(self)
+        })
+    }
+}
+
+/// Result of one remote command.
+#[derive(Debug, Clone, Serialize)]
+pub struct SshOutput {
+    pub host: String,
+    pub exit_code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+    pub duration_ms: u64,
+    pub timed_out: bool,
+}
+
+fn truncate_output(bytes: &[u8]) -> String {
+    let text = String::from_utf8_lossy(bytes);
+    if text.len() <= MAX_OUTPUT_BYTES {
+        return text.into_owned();
+    }
+    let end = crate::tools::safe_truncate_index(&text, MAX_OUTPUT_BYTES);
+    format!(
+        "{}\n... [truncated, {} bytes total]",
+        &text[..end],
+        text.len()
+    )
+}
+
+/// `ssh` arguments for running `command` on `host`.
+fn ssh_args(host: &SshHost, key_path: &Path, known_hosts: &Path, command: &str) -> Vec<String> {
+    let strict = if host.host_key.is_some() {
+        "yes"
+    } else {
+        "accept-new"
+    };
+    vec![
+        "-i".to_string(),
+        key_path.display().to_string(),
+        "-p".to_string(),
+        host.port.to_string(),
+        "-o".to_string(),
+        "BatchMode=yes".to_string(),
+        "-o".to_string(),
+        "IdentitiesOnly=yes".to_string(),
+        "-o".to_string(),
+        format!("ConnectTimeout={}", CONNECT_TIMEOUT_SECS),
+        "-o".to_string(),
+        format!("StrictHostKeyChecking={}", strict),
+        "-o".to_string(),
+        format!("UserKnownHostsFile={}", known_hosts.display()),
+        "-o".to_string(),
+        "LogLevel=ERROR".to_string(),
+        "-l".to_string(),
+        host.user.clone(),
+        "--".to_string(),
+        host.host.clone(),
+        command.to_string(),
+    ]
+}
+
+/// Private temp directory for a key and known_hosts file, removed on drop.
+struct CallDir(PathBuf);
+
+impl CallDir {
+    fn create() -> anyhow::Result<Self> {
+        let dir = std::env::temp_dir().join(format!("sandboxed-ssh-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir(&dir)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(0o700))?;
+        }
+        Ok(Self(dir))
+    }
+
+    fn write_private(&self, name: &str, contents: &str) -> anyhow::Result<PathBuf> {
+        let path = self.0.join(name);
+        std::fs::write(&path, contents)?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o600))?;
+        }
+        Ok(path)
+    }
+}
+
+impl Drop for CallDir {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Run `command` on `host` with `private_key`, killing ssh after `timeout`.
+pub async fn execute(
+    host: &SshHost,
+    private_key: &str,
+    command: &str,
+    timeout: Duration,
+) -> anyhow::Result<SshOutput> {
+    let dir = CallDir::create()?;
+    // OpenSSH rejects keys without a trailing newline.
+    let mut key = private_key.trim().to_string();
+    key.push('\n');
+    let key_path = dir.write_private("id", &key)?;
+    let known_hosts = dir.write_private(
+        "known_hosts",
+        &host
+            .host_key
+            .as_deref()
+            .map(|k| format!("{}\n", host.known_hosts_line(k)))
+            .unwrap_or_default(),
+    )?;
+
+    let started = Instant::now();
+    let child = Command::new("ssh")
+        .args(ssh_args(host, &key_path, &known_hosts, command))
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    let result = tokio::time::timeout(timeout, child).await;
+    let duration_ms = started.elapsed().as_millis() as u64;
+
+    match result {
+        Ok(Ok(output)) => Ok(SshOutput {
+            host: host.name.clone(),
+            exit_code: output.status.code(),
+            stdout: truncate_output(&output.stdout),
+            stderr: truncate_output(&output.stderr),
+            duration_ms,
+            timed_out: false,
+        }),
+        Ok(Err(e)) => Err(anyhow::anyhow!("Failed to run ssh: {}", e)),
+        Err(_) => Ok(SshOutput {
+            host: host.name.clone(),
+            exit_code: None,
+            stdout: String::new(),
+            stderr: format!("Timed out after {}s", timeout.as_secs()),
+            duration_ms,
+            timed_out: true,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host(name: &str) -> SshHost {
+        SshHost {
+            name: name.to_string(),
+            host: "staging.example.com".to_string(),
+            port: 22,
+            user: "deploy".to_string(),
+            key_secret: "staging".to_string(),
+            host_key: None,
+        }
+    }
+
+    #[test]
+    fn allowlist_is_exact_and_validated() {
+        let access = SshAccess {
+            hosts: vec![host(""), host("db")],
+        }
+        .normalized()
+        .unwrap()
+        .unwrap();
+        assert_eq!(access.hosts[0].name, "staging.example.com");
+        assert!(access.host("db").is_ok());
+        assert!(access.host("staging.example.com").is_ok());
+        assert!(access.host("staging").is_err());
+        assert!(access.host("db.example.com").is_err());
+
+        assert_eq!(SshAccess::default().normalized().unwrap(), None);
+        let duplicate = SshAccess {
+            hosts: vec![host("a"), host("a")],
+        };
+        assert!(duplicate.normalized().is_err());
+        let mut bad = host("x");
+        bad.host = "-oProxyCommand=evil".to_string();
+        assert!(SshAccess { hosts: vec![bad] }.normalized().is_err());
+    }
+
+    #[test]
+    fn ssh_args_pin_host_keys() {
+        let mut h = host("web");
+        let args = ssh_args(&h, Path::new("/k"), Path::new("/kh"), "uptime");
+        assert!(args.contains(&"StrictHostKeyChecking=accept-new".to_string()));
+        assert!(args.contains(&"BatchMode=yes".to_string()));
+        assert_eq!(
+            &args[args.len() - 3..],
+            ["--", "staging.example.com", "uptime"]
+        );
+
+        h.host_key = Some("ssh-ed25519 AAAA".to_string());
+        let args = ssh_args(&h, Path::new("/k"), Path::new("/kh"), "uptime");
+        assert!(args.contains(&"StrictHostKeyChecking=yes".to_string()));
+        assert_eq!(
+            h.known_hosts_line("ssh-ed25519 AAAA"),
+            "staging.example.com ssh-ed25519 AAAA"
+        );
+        h.port = 2222;
+        assert_eq!(
+            h.known_hosts_line("ssh-ed25519 AAAA"),
+            "[staging.example.com]:2222 ssh-ed25519 AAAA"
+        );
+    }
+}
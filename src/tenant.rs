@@ -0,0 +1,262 @@
+//! Tenants: isolated namespaces above users.
+//!
+//! Tenants are configured with `SANDBOXED_TENANTS` (a JSON array of
+//! [`TenantConfig`]) and users are assigned to one with the `tenant` field of
+//! their `SANDBOXED_USERS` entry. A JWT's `tnt` claim carries the tenant; the
+//! auth middleware resolves it and rejects tokens for unknown tenants.
+//!
+//! Each tenant gets its own directory under `.sandboxed-sh/tenants/<id>/`
+//! holding its mission stores and library checkout. Workspaces created by a
+//! tenant's users are tagged with the tenant and hidden from everyone else.
+//! Users without a tenant keep the instance-level stores, as before.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use crate::api::library::SharedLibrary;
+use crate::config::Config;
+
+/// Trusted request header carrying the caller's tenant id. The auth
+/// middleware overwrites any client-supplied value.
+pub const TENANT_HEADER: &str = "x-sandboxed-tenant";
+
+/// One tenant and its overrides of instance configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantConfig {
+    /// Stable identifier (letters, digits, `-` and `_`).
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Library repo for the tenant. Defaults to the instance library remote,
+    /// checked out separately for the tenant.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub library_remote: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_backend: Option<String>,
+    /// Missions the tenant's users can run at once (per user session),
+    /// capped by the instance limit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_parallel_missions: Option<usize>,
+    /// Stored missions allowed per user of the tenant.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_missions: Option<usize>,
+    /// Workspaces the tenant can own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_workspaces: Option<usize>,
+}
+
+impl TenantConfig {
+    pub fn data_dir(&self, working_dir: &Path) -> PathBuf {
+        working_dir
+            .join(".sandboxed-sh")
+            .join("tenants")
+            .join(&self.id)
+    }
+
+    pub fn missions_dir(&self, working_dir: &Path) -> PathBuf {
+        self.data_dir(working_dir).join("missions")
+    }
+
+    pub fn library_path(&self, working_dir: &Path) -> PathBuf {
+        self.data_dir(working_dir).join("library")
+    }
+
+    /// Copy of the instance config with this tenant's overrides applied, for
+    /// the control sessions of the tenant's users.
+    pub fn scoped_config(&self, config: &Config) -> Config {
+        let mut scoped = config.clone();
+        if let Some(model) = &self.default_model {
+            scoped.default_model = Some(model.clone());
+        }
+        if let Some(backend) = &self.default_backend {
+            scoped.default_backend = Some(backend.clone());
+        }
+        if let Some(limit) = self.max_parallel_missions {
+            scoped.max_parallel_missions = limit.clamp(1, config.max_parallel_missions.max(1));
+        }
+        scoped.library_path = self.library_path(&config.working_dir);
+        scoped.tenant = Some(self.clone());
+        scoped
+    }
+}
+
+pub fn validate_tenant_id(id: &str) -> Result<(), String> {
+    if id.is_empty() || id.len() > 64 {
+        return Err("tenant id must be 1-64 characters".to_string());
+    }
+    if !id
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "tenant id '{}' may only contain letters, digits, '-' and '_'",
+            id
+        ));
+    }
+    Ok(())
+}
+
+/// Parse and validate the `SANDBOXED_TENANTS` JSON array.
+pub fn parse_tenants(raw: &str) -> Result<Vec<TenantConfig>, String> {
+    let tenants: Vec<TenantConfig> = serde_json::from_str(raw).map_err(|e| e.to_string())?;
+    let mut seen = std::collections::HashSet::new();
+    for tenant in &tenants {
+        validate_tenant_id(&tenant.id)?;
+        if !seen.insert(tenant.id.as_str()) {
+            return Err(format!("duplicate tenant id '{}'", tenant.id));
+        }
+    }
+    Ok(tenants)
+}
+
+/// Key for per-user state (control sessions), namespaced by tenant so the same
+/// user id in two tenants never shares a session.
+pub fn session_key(user_id: &str, tenant: Option<&str>) -> String {
+    match tenant {
+        Some(tenant) => format!("{}/{}", tenant, user_id),
+        None => user_id.to_string(),
+    }
+}
+
+/// Parallel mission limit: the instance setting, capped by the tenant quota.
+pub fn effective_max_parallel(configured: usize, tenant: Option<&TenantConfig>) -> usize {
+    let limit = crate::settings::max_parallel_missions_cached_or(configured);
+    match tenant.and_then(|t| t.max_parallel_missions) {
+        Some(cap) => limit.min(cap.max(1)),
+        None => limit,
+    }
+}
+
+/// How the parallel mission limit is split between interactive missions and
+/// missions driven by an active automation.
+///
+/// `reserved_interactive` slots are only ever used by interactive missions,
+/// so automations can never fill every slot. At least one slot always stays
+/// open to automations.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SlotAllocation {
+    pub max_parallel_missions: usize,
+    pub reserved_interactive_slots: usize,
+    /// Slots automations may use
+    pub automation_slots: usize,
+    pub running_interactive: usize,
+    pub running_automated: usize,
+}
+
+impl SlotAllocation {
+    pub fn new(
+        max_parallel: usize,
+        reserved_interactive: usize,
+        running_interactive: usize,
+        running_automated: usize,
+    ) -> Self {
+        let max_parallel = max_parallel.max(1);
+        let reserved_interactive = reserved_interactive.min(max_parallel - 1);
+        Self {
+            max_parallel_missions: max_parallel,
+            reserved_interactive_slots: reserved_interactive,
+            automation_slots: max_parallel - reserved_interactive,
+            running_interactive,
+            running_automated,
+        }
+    }
+
+    pub fn running(&self) -> usize {
+        self.running_interactive + self.running_automated
+    }
+
+    /// Whether another mission of the given kind may start.
+    pub fn can_start(&self, automated: bool) -> bool {
+        self.running() < self.max_parallel_missions
+            && (!automated || self.running_automated < self.automation_slots)
+    }
+}
+
+/// Lazily created library slots, one per tenant.
+#[derive(Default)]
+pub struct TenantLibraries {
+    slots: RwLock<HashMap<String, SharedLibrary>>,
+}
+
+pub type SharedTenantLibraries = Arc<TenantLibraries>;
+
+impl TenantLibraries {
+    pub async fn slot(&self, tenant_id: &str) -> SharedLibrary {
+        if let Some(slot) = self.slots.read().await.get(tenant_id) {
+            return Arc::clone(slot);
+        }
+        let mut slots = self.slots.write().await;
+        Arc::clone(
+            slots
+                .entry(tenant_id.to_string())
+                .or_insert_with(|| Arc::new(RwLock::new(None))),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_and_validates_tenants() {
+        let tenants = parse_tenants(
+            r#"[{"id": "team-a", "max_missions": 10}, {"id": "team_b", "default_backend": "codex"}]"#,
+        )
+        .unwrap();
+        assert_eq!(tenants.len(), 2);
+        assert_eq!(tenants[0].max_missions, Some(10));
+
+        assert!(parse_tenants(r#"[{"id": "a"}, {"id": "a"}]"#).is_err());
+        assert!(parse_tenants(r#"[{"id": "../etc"}]"#).is_err());
+        assert!(parse_tenants(r#"[{"id": ""}]"#).is_err());
+    }
+
+    #[test]
+    fn scoped_config_applies_overrides_within_instance_limits() {
+        let mut config = Config::new(PathBuf::from("/srv"));
+        config.max_parallel_missions = 4;
+        let tenant = TenantConfig {
+            id: "team-a".to_string(),
+            name: None,
+            library_remote: None,
+            default_model: Some("claude-sonnet-4".to_string()),
+            default_backend: None,
+            max_parallel_missions: Some(8),
+            max_missions: None,
+            max_workspaces: None,
+        };
+        let scoped = tenant.scoped_config(&config);
+        assert_eq!(scoped.max_parallel_missions, 4);
+        assert_eq!(scoped.default_model.as_deref(), Some("claude-sonnet-4"));
+        assert_eq!(
+            scoped.library_path,
+            PathBuf::from("/srv/.sandboxed-sh/tenants/team-a/library")
+        );
+        assert_eq!(session_key("alice", Some("team-a")), "team-a/alice");
+        assert_eq!(session_key("alice", None), "alice");
+    }
+
+    #[test]
+    fn reserved_slots_hold_back_automations_only() {
+        let slots = SlotAllocation::new(3, 1, 0, 2);
+        assert_eq!(slots.automation_slots, 2);
+        assert!(!slots.can_start(true));
+        assert!(slots.can_start(false));
+
+        let full = SlotAllocation::new(3, 1, 1, 2);
+        assert!(!full.can_start(false));
+
+        // Automations keep one slot even when everything is reserved
+        let clamped = SlotAllocation::new(2, 5, 0, 0);
+        assert_eq!(clamped.reserved_interactive_slots, 1);
+        assert!(clamped.can_start(true));
+        assert!(!SlotAllocation::new(2, 5, 0, 1).can_start(true));
+    }
+}
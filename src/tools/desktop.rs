@@ -7,8 +7,11 @@
 //! - Mouse operations (clicking)
 //! - Extracting visible text (AT-SPI + OCR)
 //!
-//! Requires: Xvfb, i3, xdotool, scrot, tesseract, AT-SPI2
+//! Requires: Xvfb, i3, xdotool, scrot, tesseract, AT-SPI2 (xclip for the
+//! clipboard API)
 //! Only available when DESKTOP_ENABLED=true
+//!
+//! On macOS hosts sessions use the host screen instead; see [`super::desktop_macos`].
 
 use std::path::{Path, PathBuf};
 use std::process::Stdio;
@@ -18,6 +21,7 @@ use async_trait::async_trait;
 use serde_json::{json, Value};
 use tokio::process::Command;
 
+use super::desktop_macos::{self, Region};
 use super::Tool;
 use crate::util::env_var_bool;
 
@@ -31,14 +35,7 @@ pub(crate) fn desktop_enabled() -> bool {
 }
 
 fn kill_pid(pid: u32) {
-    if pid == 0 {
-        return;
-    }
-    // SAFETY: Sending SIGTERM to a valid PID. The pid == 0 guard above
-    // prevents accidentally signalling the caller's process group.
-    unsafe {
-        libc::kill(pid as i32, libc::SIGTERM);
-    }
+    crate::process_tree::terminate_process(pid);
 }
 
 /// Get the configured resolution
@@ -126,6 +123,18 @@ fn is_snap_stub(path: &Path) -> bool {
     let lower = content.to_lowercase();
     lower.contains("snap install") || (lower.contains("snap") && lower.contains("chromium"))
 }
+
+/// Run a blocking macOS backend call off the async runtime.
+async fn run_macos<T, F>(f: F) -> anyhow::Result<T>
+where
+    T: Send + 'static,
+    F: FnOnce() -> anyhow::Result<T> + Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| anyhow::anyhow!("macOS desktop task failed: {}", e))?
+}
+
 /// Run a command with DISPLAY environment variable set
 async fn run_with_display(
     display: &str,
@@ -169,7 +178,7 @@ impl Tool for StartSession {
     }
 
     fn description(&self) -> &str {
-        "Start a virtual desktop session (Xvfb + i3 window manager). Returns the DISPLAY identifier (e.g., ':99') needed for other desktop_* tools. Call this before using any other desktop tools. Optionally launches Chromium browser."
+        "Start a virtual desktop session (Xvfb + i3 window manager). Returns the DISPLAY identifier (e.g., ':99') needed for other desktop_* tools. Call this before using any other desktop tools. Optionally launches Chromium browser. On macOS hosts the session is the host screen and the identifier looks like 'macos:1'."
     }
 
     fn parameters_schema(&self) -> Value {
@@ -196,6 +205,11 @@ impl Tool for StartSession {
             ));
         }
 
+        if desktop_macos::macos_backend() {
+            let working_dir = working_dir.to_path_buf();
+            return run_macos(move || desktop_macos::start_session(&args, &working_dir)).await;
+        }
+
         // Get next display number
         let display_num = DISPLAY_COUNTER.fetch_add(1, Ordering::SeqCst);
         let display_id = format!(":{}", display_num);
@@ -372,6 +386,10 @@ impl Tool for StopSession {
             .as_str()
             .ok_or_else(|| anyhow::anyhow!("Missing 'display' argument"))?;
 
+        if desktop_macos::is_macos_display(display_id) {
+            return desktop_macos::stop_session(display_id, working_dir);
+        }
+
         // Extract display number
         let display_num: u32 = display_id
             .trim_start_matches(':')
@@ -390,13 +408,9 @@ impl Tool for StopSession {
                     // Kill processes by PID
                     for pid_key in ["xvfb_pid", "i3_pid", "browser_pid"] {
                         if let Some(pid) = session_info[pid_key].as_u64() {
-                            let pid = pid as i32;
-                            // SAFETY: PIDs are read from a session file we wrote;
-                            // SIGTERM is a safe signal to send to any process.
-                            unsafe {
-                                libc::kill(pid, libc::SIGTERM);
-                            }
-                            killed_pids.push(pid);
+                            // PIDs are read from a session file we wrote.
+                            crate::process_tree::terminate_process(pid as u32);
+                            killed_pids.push(pid as i32);
                         }
                     }
                 }
@@ -508,47 +522,7 @@ Set return_image=true to SEE the screenshot yourself (vision). This lets you ver
 
         tracing::info!(display = %display_id, path = %filepath.display(), "Taking screenshot");
 
-        // Build scrot command
-        let mut scrot_args = vec!["-o".to_string(), filepath.to_string_lossy().to_string()];
-
-        // Add region if specified
-        if let Some(region) = args.get("region") {
-            if region.is_object() {
-                let x = region["x"].as_i64().unwrap_or(0);
-                let y = region["y"].as_i64().unwrap_or(0);
-                let w = region["width"].as_i64().unwrap_or(100);
-                let h = region["height"].as_i64().unwrap_or(100);
-                scrot_args.push("-a".to_string());
-                scrot_args.push(format!("{},{},{},{}", x, y, w, h));
-            }
-        }
-
-        let (_stdout, stderr, exit_code) = run_with_display(
-            display_id,
-            "scrot",
-            &scrot_args.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
-            30,
-        )
-        .await?;
-
-        if exit_code != 0 {
-            // Try import as fallback
-            let import_result = run_with_display(
-                display_id,
-                "import",
-                &["-window", "root", filepath.to_string_lossy().as_ref()],
-                30,
-            )
-            .await;
-
-            if let Err(e) = import_result {
-                return Err(anyhow::anyhow!(
-                    "Screenshot failed. scrot error: {}. import error: {}",
-                    stderr,
-                    e
-                ));
-            }
-        }
+        capture_display(display_id, &filepath, Region::from_args(&args)).await?;
 
         // Verify file exists
         if !filepath.exists() {
@@ -574,6 +548,103 @@ Set return_image=true to SEE the screenshot yourself (vision). This lets you ver
     }
 }
 
+/// Capture `display_id` (or `region` of it) to `filepath` as a PNG.
+pub(crate) async fn capture_display(
+    display_id: &str,
+    filepath: &Path,
+    region: Option<Region>,
+) -> anyhow::Result<()> {
+    if desktop_macos::is_macos_display(display_id) {
+        let path = filepath.to_path_buf();
+        return run_macos(move || desktop_macos::capture_screen(&path, region)).await;
+    }
+    capture_x11(display_id, filepath, region).await
+}
+
+/// Capture an X11 display with scrot, falling back to ImageMagick's import.
+async fn capture_x11(
+    display_id: &str,
+    filepath: &Path,
+    region: Option<Region>,
+) -> anyhow::Result<()> {
+    // Build scrot command
+    let mut scrot_args = vec!["-o".to_string(), filepath.to_string_lossy().to_string()];
+
+    // Add region if specified
+    if let Some(region) = region {
+        scrot_args.push("-a".to_string());
+        scrot_args.push(format!(
+            "{},{},{},{}",
+            region.x, region.y, region.width, region.height
+        ));
+    }
+
+    let (_stdout, stderr, exit_code) = run_with_display(
+        display_id,
+        "scrot",
+        &scrot_args.iter().map(|s| s.as_str()).collect::<Vec<_>>(),
+        30,
+    )
+    .await?;
+
+    if exit_code != 0 {
+        // Try import as fallback
+        let import_result = run_with_display(
+            display_id,
+            "import",
+            &["-window", "root", filepath.to_string_lossy().as_ref()],
+            30,
+        )
+        .await;
+
+        if let Err(e) = import_result {
+            return Err(anyhow::anyhow!(
+                "Screenshot failed. scrot error: {}. import error: {}",
+                stderr,
+                e
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Put `text` on the clipboard of `display_id`.
+pub(crate) async fn set_clipboard(display_id: &str, text: &str) -> anyhow::Result<()> {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    if desktop_macos::is_macos_display(display_id) {
+        let text = text.to_string();
+        return run_macos(move || desktop_macos::set_clipboard(&text)).await;
+    }
+
+    let mut child = Command::new("xclip")
+        .args(["-selection", "clipboard"])
+        .env("DISPLAY", display_id)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to execute xclip: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes()).await?;
+    }
+
+    // xclip forks to keep serving the selection, so only the parent is
+    // awaited; its stderr is read only when it failed before forking.
+    let status = tokio::time::timeout(std::time::Duration::from_secs(10), child.wait())
+        .await
+        .map_err(|_| anyhow::anyhow!("Command xclip timed out"))??;
+    if !status.success() {
+        let mut stderr = String::new();
+        if let Some(mut pipe) = child.stderr.take() {
+            let _ = pipe.read_to_string(&mut stderr).await;
+        }
+        return Err(anyhow::anyhow!("xclip failed: {}", stderr.trim()));
+    }
+    Ok(())
+}
+
 /// Send keyboard input to the desktop.
 pub struct TypeText;
 
@@ -631,6 +702,23 @@ impl Tool for TypeText {
 
         tracing::info!(display = %display_id, command = %command, "Sending keyboard input");
 
+        if desktop_macos::is_macos_display(display_id) {
+            let typed = input.clone();
+            run_macos(move || {
+                if command == "type" {
+                    desktop_macos::type_text(Some(&typed), None, delay_ms)
+                } else {
+                    desktop_macos::type_text(None, Some(&typed), delay_ms)
+                }
+            })
+            .await?;
+            return Ok(format!(
+                "{{\"success\": true, \"command\": \"{}\", \"input\": \"{}\"}}",
+                command,
+                input.replace('\"', "\\\"").replace('\n', "\\n")
+            ));
+        }
+
         let (_stdout, stderr, exit_code) = run_with_display(
             display_id,
             "xdotool",
@@ -722,33 +810,38 @@ impl Tool for Click {
 
         tracing::info!(display = %display_id, x = x, y = y, button = button, "Clicking");
 
-        // Move to position first
-        let (_, stderr, exit_code) = run_with_display(
-            display_id,
-            "xdotool",
-            &["mousemove", &x.to_string(), &y.to_string()],
-            10,
-        )
-        .await?;
+        if desktop_macos::is_macos_display(display_id) {
+            let button_name = args["button"].as_str().unwrap_or("left").to_string();
+            run_macos(move || desktop_macos::click(x, y, &button_name, double)).await?;
+        } else {
+            // Move to position first
+            let (_, stderr, exit_code) = run_with_display(
+                display_id,
+                "xdotool",
+                &["mousemove", &x.to_string(), &y.to_string()],
+                10,
+            )
+            .await?;
 
-        if exit_code != 0 {
-            return Err(anyhow::anyhow!("xdotool mousemove failed: {}", stderr));
-        }
+            if exit_code != 0 {
+                return Err(anyhow::anyhow!("xdotool mousemove failed: {}", stderr));
+            }
 
-        // Small delay to ensure move completes
-        tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            // Small delay to ensure move completes
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
 
-        // Click
-        let (_, stderr, exit_code) = run_with_display(
-            display_id,
-            "xdotool",
-            &["click", "--repeat", repeat, button],
-            10,
-        )
-        .await?;
+            // Click
+            let (_, stderr, exit_code) = run_with_display(
+                display_id,
+                "xdotool",
+                &["click", "--repeat", repeat, button],
+                10,
+            )
+            .await?;
 
-        if exit_code != 0 {
-            return Err(anyhow::anyhow!("xdotool click failed: {}", stderr));
+            if exit_code != 0 {
+                return Err(anyhow::anyhow!("xdotool click failed: {}", stderr));
+            }
         }
 
         Ok(format!(
@@ -801,7 +894,11 @@ impl Tool for GetText {
             .as_str()
             .ok_or_else(|| anyhow::anyhow!("Missing 'display' argument"))?;
 
-        let method = args["method"].as_str().unwrap_or("accessibility");
+        let mut method = args["method"].as_str().unwrap_or("accessibility");
+        if desktop_macos::is_macos_display(display_id) {
+            // AT-SPI is Linux-only; macOS sessions are read with OCR.
+            method = "ocr";
+        }
         let max_depth = args["max_depth"].as_u64().unwrap_or(10);
 
         tracing::info!(display = %display_id, method = %method, "Extracting text");
@@ -930,6 +1027,11 @@ except Exception as e:
 
 /// Extract text using OCR (Tesseract)
 async fn get_ocr_text(display: &str, working_dir: &Path) -> anyhow::Result<String> {
+    if desktop_macos::is_macos_display(display) {
+        let working_dir = working_dir.to_path_buf();
+        return run_macos(move || desktop_macos::ocr_text(&working_dir)).await;
+    }
+
     // Take a screenshot first
     let screenshots_dir = working_dir.join("screenshots");
     std::fs::create_dir_all(&screenshots_dir)?;
@@ -1024,6 +1126,11 @@ impl Tool for MouseMove {
 
         tracing::info!(display = %display_id, x = x, y = y, "Moving mouse");
 
+        if desktop_macos::is_macos_display(display_id) {
+            run_macos(move || desktop_macos::mouse_move(x, y)).await?;
+            return Ok(format!("{{\"success\": true, \"x\": {}, \"y\": {}}}", x, y));
+        }
+
         let (_, stderr, exit_code) = run_with_display(
             display_id,
             "xdotool",
@@ -1087,6 +1194,13 @@ impl Tool for Scroll {
             .as_i64()
             .ok_or_else(|| anyhow::anyhow!("Missing 'amount' argument"))?;
 
+        if desktop_macos::is_macos_display(display_id) {
+            return Err(desktop_macos::unsupported(
+                "desktop_scroll",
+                "Use desktop_type with key 'Page_Down' or 'Page_Up' instead.",
+            ));
+        }
+
         // Move to position if specified
         if let (Some(x), Some(y)) = (args["x"].as_i64(), args["y"].as_i64()) {
             let (_, stderr, exit_code) = run_with_display(
@@ -1190,6 +1304,13 @@ Layout example (chrome left, terminal right):
             .as_str()
             .ok_or_else(|| anyhow::anyhow!("Missing 'command' argument"))?;
 
+        if desktop_macos::is_macos_display(display_id) {
+            return Err(desktop_macos::unsupported(
+                "desktop_i3_command",
+                "Launch apps with `open -a <App>` from the shell instead.",
+            ));
+        }
+
         tracing::info!(display = %display_id, command = %command, "Executing i3 command");
 
         let (stdout, stderr, exit_code) =
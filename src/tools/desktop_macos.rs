@@ -0,0 +1,434 @@
+//! macOS backend for the desktop tools.
+//!
+//! A Mac host has no Xvfb or i3: a desktop session is the logged-in user's
+//! own screen. Screenshots come from `screencapture`, browsers are opened
+//! with `open`, and keyboard/mouse input goes through `cliclick`
+//! (`brew install cliclick`). Sessions are identified as `macos:<n>` so the
+//! desktop API can tell them apart from X11 displays.
+//!
+//! The backend is chosen with `SANDBOXED_SH_DESKTOP_BACKEND` (`macos` or
+//! `x11`) and defaults to `macos` when running on macOS. The helpers are
+//! synchronous so the `desktop-mcp` binary can share them with the in-process
+//! tools.
+//!
+//! Requires: screencapture (Screen Recording permission), cliclick for input,
+//! tesseract for OCR
+
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+
+use serde_json::{json, Value};
+
+/// Prefix of macOS session identifiers (`macos:1`, `macos:2`, ...).
+pub const DISPLAY_PREFIX: &str = "macos:";
+
+/// Whether new desktop sessions use the macOS backend.
+pub fn macos_backend() -> bool {
+    match std::env::var("SANDBOXED_SH_DESKTOP_BACKEND")
+        .ok()
+        .map(|value| value.trim().to_ascii_lowercase())
+        .as_deref()
+    {
+        Some("macos") | Some("mac") => true,
+        Some("x11") | Some("xvfb") => false,
+        _ => cfg!(target_os = "macos"),
+    }
+}
+
+/// Whether `display` identifies a macOS session rather than an X11 display.
+pub fn is_macos_display(display: &str) -> bool {
+    display
+        .strip_prefix(DISPLAY_PREFIX)
+        .is_some_and(|num| num.parse::<u32>().is_ok())
+}
+
+fn session_file(working_dir: &Path, display: &str) -> PathBuf {
+    let num = display.trim_start_matches(DISPLAY_PREFIX);
+    working_dir.join(format!(".desktop_session_macos_{}", num))
+}
+
+/// Whether a macOS session was started and not yet stopped.
+pub fn session_active(display: &str, working_dir: &Path) -> bool {
+    is_macos_display(display) && session_file(working_dir, display).exists()
+}
+
+/// A capture rectangle in screen points.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub x: i64,
+    pub y: i64,
+    pub width: i64,
+    pub height: i64,
+}
+
+impl Region {
+    /// The `region` argument of `desktop_screenshot`, if given.
+    pub fn from_args(args: &Value) -> Option<Self> {
+        let region = args.get("region").filter(|region| region.is_object())?;
+        Some(Self {
+            x: region["x"].as_i64().unwrap_or(0),
+            y: region["y"].as_i64().unwrap_or(0),
+            width: region["width"].as_i64().unwrap_or(100),
+            height: region["height"].as_i64().unwrap_or(100),
+        })
+    }
+}
+
+fn run(program: &str, args: &[String]) -> anyhow::Result<(String, String, i32)> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .output()
+        .map_err(|e| {
+            if e.kind() == std::io::ErrorKind::NotFound && program == "cliclick" {
+                anyhow::anyhow!(
+                    "cliclick is not installed. Install it with `brew install cliclick` \
+                     to send keyboard and mouse input on macOS."
+                )
+            } else {
+                anyhow::anyhow!("Failed to execute {}: {}", program, e)
+            }
+        })?;
+
+    Ok((
+        String::from_utf8_lossy(&output.stdout).to_string(),
+        String::from_utf8_lossy(&output.stderr).to_string(),
+        output.status.code().unwrap_or(-1),
+    ))
+}
+
+fn run_cliclick(actions: Vec<String>) -> anyhow::Result<()> {
+    let (_, stderr, exit_code) = run("cliclick", &actions)?;
+    if exit_code != 0 {
+        return Err(anyhow::anyhow!("cliclick failed: {}", stderr.trim()));
+    }
+    Ok(())
+}
+
+/// Error for tools that only exist on the X11 backend.
+pub fn unsupported(tool: &str, hint: &str) -> anyhow::Error {
+    anyhow::anyhow!(
+        "{} is not supported on macOS desktop sessions. {}",
+        tool,
+        hint
+    )
+}
+
+/// Start a session on the host screen, optionally opening a browser.
+pub fn start_session(args: &Value, working_dir: &Path) -> anyhow::Result<String> {
+    let screenshots_dir = working_dir.join("screenshots");
+    std::fs::create_dir_all(&screenshots_dir)?;
+
+    let mut num = 1;
+    while working_dir
+        .join(format!(".desktop_session_macos_{}", num))
+        .exists()
+    {
+        num += 1;
+    }
+    let display_id = format!("{}{}", DISPLAY_PREFIX, num);
+
+    tracing::info!(display = %display_id, "Starting macOS desktop session");
+
+    let mut session_info = json!({
+        "display": display_id,
+        "backend": "macos",
+        "screenshots_dir": screenshots_dir.to_string_lossy(),
+    });
+
+    if args["launch_browser"].as_bool().unwrap_or(false) {
+        let url = args["url"].as_str().unwrap_or("about:blank");
+        let app = std::env::var("DESKTOP_MACOS_BROWSER")
+            .ok()
+            .filter(|app| !app.trim().is_empty());
+        let (_, stderr, exit_code) = run("open", &open_args(app.as_deref(), url))?;
+        if exit_code != 0 {
+            return Err(anyhow::anyhow!("Failed to open browser: {}", stderr.trim()));
+        }
+        // `open` returns once the app is asked to load the URL; give it time to render.
+        std::thread::sleep(std::time::Duration::from_secs(2));
+        session_info["browser"] = json!(app.unwrap_or_else(|| "default".to_string()));
+        session_info["url"] = json!(url);
+    }
+
+    std::fs::write(
+        session_file(working_dir, &display_id),
+        serde_json::to_string_pretty(&session_info)?,
+    )?;
+
+    session_info["success"] = json!(true);
+    Ok(session_info.to_string())
+}
+
+/// Stop a session. Apps opened during the session belong to the user's
+/// desktop and are left running.
+pub fn stop_session(display_id: &str, working_dir: &Path) -> anyhow::Result<String> {
+    if !is_macos_display(display_id) {
+        return Err(anyhow::anyhow!("Invalid display format: {}", display_id));
+    }
+    tracing::info!(display = %display_id, "Stopping macOS desktop session");
+    let _ = std::fs::remove_file(session_file(working_dir, display_id));
+    Ok(json!({ "success": true, "display": display_id, "killed_pids": [] }).to_string())
+}
+
+fn open_args(app: Option<&str>, url: &str) -> Vec<String> {
+    let mut args = Vec::new();
+    if let Some(app) = app {
+        args.push("-a".to_string());
+        args.push(app.to_string());
+    }
+    args.push(url.to_string());
+    args
+}
+
+fn screencapture_args(path: &Path, region: Option<Region>) -> Vec<String> {
+    // -x: no shutter sound
+    let mut args = vec!["-x".to_string(), "-t".to_string(), "png".to_string()];
+    if let Some(region) = region {
+        args.push("-R".to_string());
+        args.push(format!(
+            "{},{},{},{}",
+            region.x, region.y, region.width, region.height
+        ));
+    }
+    args.push(path.to_string_lossy().to_string());
+    args
+}
+
+/// Capture the main screen (or `region` of it) to `path`.
+pub fn capture_screen(path: &Path, region: Option<Region>) -> anyhow::Result<()> {
+    let (_, stderr, exit_code) = run("screencapture", &screencapture_args(path, region))?;
+    if exit_code != 0 || !path.exists() {
+        return Err(anyhow::anyhow!(
+            "screencapture failed: {}. Grant Screen Recording permission to the process \
+             running sandboxed.sh in System Settings > Privacy & Security.",
+            stderr.trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Put `text` on the pasteboard with `pbcopy`.
+pub fn set_clipboard(text: &str) -> anyhow::Result<()> {
+    use std::io::Write;
+
+    let mut child = std::process::Command::new("pbcopy")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to execute pbcopy: {}", e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(text.as_bytes())?;
+    }
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "pbcopy failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Capture the screen and read it with Tesseract.
+pub fn ocr_text(working_dir: &Path) -> anyhow::Result<String> {
+    let screenshots_dir = working_dir.join("screenshots");
+    std::fs::create_dir_all(&screenshots_dir)?;
+    let screenshot_path = screenshots_dir.join("_ocr_temp.png");
+    capture_screen(&screenshot_path, None)?;
+
+    let output = std::process::Command::new("tesseract")
+        .arg(&screenshot_path)
+        .args(["stdout", "-l", "eng"])
+        .output()
+        .map_err(|e| anyhow::anyhow!("Failed to run tesseract: {}", e));
+    let _ = std::fs::remove_file(&screenshot_path);
+    let output = output?;
+
+    if !output.status.success() {
+        return Err(anyhow::anyhow!(
+            "Tesseract failed: {}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Map an xdotool-style key name to a cliclick key-press name.
+fn cliclick_key(name: &str) -> Option<String> {
+    let key = match name.to_ascii_lowercase().as_str() {
+        "return" | "enter" | "kp_enter" => "return",
+        "tab" => "tab",
+        "escape" | "esc" => "esc",
+        "backspace" => "delete",
+        "delete" => "fwd-delete",
+        "space" => "space",
+        "up" => "arrow-up",
+        "down" => "arrow-down",
+        "left" => "arrow-left",
+        "right" => "arrow-right",
+        "home" => "home",
+        "end" => "end",
+        "page_up" | "prior" => "page-up",
+        "page_down" | "next" => "page-down",
+        other => {
+            let number = other.strip_prefix('f')?.parse::<u32>().ok()?;
+            if !(1..=16).contains(&number) {
+                return None;
+            }
+            return Some(format!("f{}", number));
+        }
+    };
+    Some(key.to_string())
+}
+
+/// cliclick actions for an xdotool-style combination such as `ctrl+shift+t`.
+/// `super`/`cmd` map to the Command key.
+fn key_actions(combo: &str) -> anyhow::Result<Vec<String>> {
+    let parts: Vec<&str> = combo.split('+').map(str::trim).collect();
+    let (key, modifiers) = parts
+        .split_last()
+        .filter(|(key, _)| !key.is_empty())
+        .ok_or_else(|| anyhow::anyhow!("Invalid key combination: {}", combo))?;
+
+    let modifiers = modifiers
+        .iter()
+        .map(|modifier| match modifier.to_ascii_lowercase().as_str() {
+            "ctrl" | "control" => Ok("ctrl"),
+            "alt" | "option" => Ok("alt"),
+            "shift" => Ok("shift"),
+            "super" | "cmd" | "command" | "meta" => Ok("cmd"),
+            "fn" => Ok("fn"),
+            other => Err(anyhow::anyhow!("Unsupported modifier on macOS: {}", other)),
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?
+        .join(",");
+
+    let press = match cliclick_key(key) {
+        Some(name) => format!("kp:{}", name),
+        None if key.chars().count() == 1 => format!("t:{}", key),
+        None => return Err(anyhow::anyhow!("Unsupported key on macOS: {}", key)),
+    };
+
+    if modifiers.is_empty() {
+        return Ok(vec![press]);
+    }
+    Ok(vec![
+        format!("kd:{}", modifiers),
+        press,
+        format!("ku:{}", modifiers),
+    ])
+}
+
+/// Type `text` or press `key` (see [`key_actions`]).
+pub fn type_text(text: Option<&str>, key: Option<&str>, delay_ms: u64) -> anyhow::Result<()> {
+    let mut actions = vec!["-w".to_string(), delay_ms.to_string()];
+    match (text, key) {
+        (Some(text), _) => actions.push(format!("t:{}", text)),
+        (None, Some(key)) => actions.extend(key_actions(key)?),
+        (None, None) => return Err(anyhow::anyhow!("Either 'text' or 'key' must be provided")),
+    }
+    run_cliclick(actions)
+}
+
+fn click_action(x: i64, y: i64, button: &str, double: bool) -> anyhow::Result<String> {
+    let command = match (button, double) {
+        ("left", false) => "c",
+        ("left", true) => "dc",
+        ("right", false) => "rc",
+        ("right", true) => {
+            return Err(anyhow::anyhow!(
+                "Double right-click is not supported on macOS"
+            ))
+        }
+        ("middle", _) => return Err(anyhow::anyhow!("Middle click is not supported on macOS")),
+        (other, _) => return Err(anyhow::anyhow!("Invalid button: {}", other)),
+    };
+    Ok(format!("{}:{},{}", command, x, y))
+}
+
+/// Click at screen point (`x`, `y`).
+pub fn click(x: i64, y: i64, button: &str, double: bool) -> anyhow::Result<()> {
+    run_cliclick(vec![click_action(x, y, button, double)?])
+}
+
+/// Move the pointer to screen point (`x`, `y`).
+pub fn mouse_move(x: i64, y: i64) -> anyhow::Result<()> {
+    run_cliclick(vec![format!("m:{},{}", x, y)])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn macos_displays_are_recognized() {
+        assert!(is_macos_display("macos:1"));
+        assert!(!is_macos_display(":99"));
+        assert!(!is_macos_display("macos:"));
+        assert!(!is_macos_display("macos:../x"));
+    }
+
+    #[test]
+    fn screencapture_and_open_arguments() {
+        let region = Region {
+            x: 10,
+            y: 20,
+            width: 300,
+            height: 200,
+        };
+        assert_eq!(
+            screencapture_args(Path::new("/tmp/shot.png"), Some(region)),
+            vec!["-x", "-t", "png", "-R", "10,20,300,200", "/tmp/shot.png"]
+        );
+        assert_eq!(
+            Region::from_args(&json!({"region": {"x": 10, "y": 20, "width": 300, "height": 200}})),
+            Some(region)
+        );
+        assert_eq!(Region::from_args(&json!({})), None);
+
+        assert_eq!(open_args(None, "https://x.test"), vec!["https://x.test"]);
+        assert_eq!(
+            open_args(Some("Safari"), "https://x.test"),
+            vec!["-a", "Safari", "https://x.test"]
+        );
+    }
+
+    #[test]
+    fn xdotool_keys_map_to_cliclick() {
+        assert_eq!(key_actions("Return").unwrap(), vec!["kp:return"]);
+        assert_eq!(
+            key_actions("ctrl+shift+t").unwrap(),
+            vec!["kd:ctrl,shift", "t:t", "ku:ctrl,shift"]
+        );
+        assert_eq!(
+            key_actions("super+Page_Down").unwrap(),
+            vec!["kd:cmd", "kp:page-down", "ku:cmd"]
+        );
+        assert_eq!(key_actions("F5").unwrap(), vec!["kp:f5"]);
+        assert!(key_actions("hyper+a").is_err());
+        assert!(key_actions("ctrl+").is_err());
+
+        assert_eq!(click_action(5, 6, "left", true).unwrap(), "dc:5,6");
+        assert!(click_action(5, 6, "middle", false).is_err());
+    }
+
+    #[test]
+    fn sessions_are_tracked_by_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let started: Value =
+            serde_json::from_str(&start_session(&json!({}), dir.path()).unwrap()).unwrap();
+        let display = started["display"].as_str().unwrap().to_string();
+        assert_eq!(display, "macos:1");
+        assert!(session_active(&display, dir.path()));
+
+        let second: Value =
+            serde_json::from_str(&start_session(&json!({}), dir.path()).unwrap()).unwrap();
+        assert_eq!(second["display"], "macos:2");
+
+        stop_session(&display, dir.path()).unwrap();
+        assert!(!session_active(&display, dir.path()));
+    }
+}
@@ -0,0 +1,928 @@
+//! Docker tool: images, containers and compose stacks for the current mission.
+//!
+//! Everything the tool starts is labelled `sandboxed.mission=<mission id>` so it
+//! can be found again and removed when the mission ends
+//! ([`cleanup_mission_containers`]). Containers and compose services get
+//! resource caps (memory, CPUs, PIDs) that a call may lower but not raise:
+//! - `SANDBOXED_SH_DOCKER_MAX_MEMORY` (default `4g`)
+//! - `SANDBOXED_SH_DOCKER_MAX_CPUS` (default `2`)
+//! - `SANDBOXED_SH_DOCKER_MAX_PIDS` (default `1024`)
+//!
+//! Bind mounts, build contexts and compose files must live inside the
+//! workspace, and only containers carrying the mission's label can be
+//! inspected or removed. Compose services may not ask for host privileges
+//! (`privileged`, `cap_add`, devices, host namespaces).
+
+use std::path::{Path, PathBuf};
+use std::process::Output;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+use tokio::process::Command;
+use uuid::Uuid;
+
+use super::Tool;
+
+/// Label carrying the owning mission id.
+pub const MISSION_LABEL: &str = "sandboxed.mission";
+/// Label value used when the tool runs outside a mission.
+const NO_MISSION: &str = "none";
+
+const DEFAULT_MAX_MEMORY: &str = "4g";
+const DEFAULT_MAX_CPUS: f64 = 2.0;
+const DEFAULT_MAX_PIDS: u64 = 1024;
+
+const BUILD_TIMEOUT: Duration = Duration::from_secs(1800);
+const COMMAND_TIMEOUT: Duration = Duration::from_secs(300);
+/// Kept short so an unresponsive daemon cannot stall mission shutdown.
+const CLEANUP_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_LOG_LINES: u64 = 200;
+/// Lines of build/compose output kept in the tool result.
+const OUTPUT_TAIL_LINES: usize = 80;
+
+const COMPOSE_FILES: &[&str] = &[
+    "compose.yaml",
+    "compose.yml",
+    "docker-compose.yaml",
+    "docker-compose.yml",
+];
+
+fn mission_id() -> String {
+    std::env::var("SANDBOXED_SH_MISSION_ID")
+        .ok()
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+        .unwrap_or_else(|| NO_MISSION.to_string())
+}
+
+fn mission_filter(mission_id: &str) -> String {
+    format!("label={}={}", MISSION_LABEL, mission_id)
+}
+
+/// Compose project name for a mission: `sbx-<first 8 of id>-<dir>`.
+fn compose_project(mission_id: &str, project_dir: &Path) -> String {
+    let short: String = mission_id.chars().take(8).collect();
+    let dir: String = project_dir
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("stack")
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '-'
+            }
+        })
+        .collect();
+    format!("sbx-{}-{}", short, dir.trim_matches('-'))
+}
+
+fn compose_project_prefix(mission_id: &str) -> String {
+    let short: String = mission_id.chars().take(8).collect();
+    format!("sbx-{}-", short)
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Resource caps
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Parse a Docker memory size (`512m`, `2g`, `1048576`) into bytes.
+fn parse_memory(value: &str) -> Option<u64> {
+    let value = value.trim().to_ascii_lowercase();
+    let value = value.strip_suffix('b').unwrap_or(&value);
+    let (digits, multiplier) = match value.chars().last()? {
+        'k' => (&value[..value.len() - 1], 1u64 << 10),
+        'm' => (&value[..value.len() - 1], 1 << 20),
+        'g' => (&value[..value.len() - 1], 1 << 30),
+        't' => (&value[..value.len() - 1], 1 << 40),
+        _ => (value, 1),
+    };
+    digits.trim().parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct ResourceCaps {
+    memory_bytes: u64,
+    cpus: f64,
+    pids: u64,
+}
+
+impl ResourceCaps {
+    fn from_env() -> Self {
+        let memory_bytes = std::env::var("SANDBOXED_SH_DOCKER_MAX_MEMORY")
+            .ok()
+            .and_then(|v| parse_memory(&v))
+            .or_else(|| parse_memory(DEFAULT_MAX_MEMORY))
+            .unwrap_or(u64::MAX);
+        let cpus = std::env::var("SANDBOXED_SH_DOCKER_MAX_CPUS")
+            .ok()
+            .and_then(|v| v.trim().parse::<f64>().ok())
+            .filter(|c| *c > 0.0)
+            .unwrap_or(DEFAULT_MAX_CPUS);
+        let pids = std::env::var("SANDBOXED_SH_DOCKER_MAX_PIDS")
+            .ok()
+            .and_then(|v| v.trim().parse::<u64>().ok())
+            .filter(|p| *p > 0)
+            .unwrap_or(DEFAULT_MAX_PIDS);
+        Self {
+            memory_bytes,
+            cpus,
+            pids,
+        }
+    }
+
+    /// Apply the caller's requested limits, never exceeding the caps.
+    fn limit(&self, args: &Value) -> anyhow::Result<Self> {
+        let memory_bytes = match args["memory"].as_str() {
+            Some(requested) => parse_memory(requested)
+                .ok_or_else(|| anyhow::anyhow!("Invalid memory size '{}'", requested))?
+                .min(self.memory_bytes),
+            None => self.memory_bytes,
+        };
+        let cpus = args["cpus"]
+            .as_f64()
+            .filter(|c| *c > 0.0)
+            .map_or(self.cpus, |c| c.min(self.cpus));
+        Ok(Self {
+            memory_bytes,
+            cpus,
+            pids: self.pids,
+        })
+    }
+
+    fn run_flags(&self) -> Vec<String> {
+        vec![
+            format!("--memory={}", self.memory_bytes),
+            format!("--cpus={}", self.cpus),
+            format!("--pids-limit={}", self.pids),
+        ]
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Helpers
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Resolve a path argument and require it to stay inside the workspace.
+fn workspace_path(path: &str, working_dir: &Path) -> anyhow::Result<PathBuf> {
+    let resolved = super::resolve_path_simple(path, working_dir);
+    let canonical = crate::util::canonicalize_path(&resolved)
+        .map_err(|e| anyhow::anyhow!("{}: {}", resolved.display(), e))?;
+    let root = crate::util::canonicalize_path(working_dir).unwrap_or(working_dir.to_path_buf());
+    if !canonical.starts_with(&root) {
+        anyhow::bail!("'{}' is outside the workspace", path);
+    }
+    Ok(canonical)
+}
+
+/// Require an argument docker takes positionally (image, name, container) not
+/// to look like a flag, which would be parsed as one.
+fn positional<'a>(value: &'a str, what: &str) -> anyhow::Result<&'a str> {
+    if value.is_empty() || value.starts_with('-') {
+        anyhow::bail!("Invalid {} '{}'", what, value);
+    }
+    Ok(value)
+}
+
+/// Turn `host:container[:ro]` volume specs into bind mounts rooted in the
+/// workspace. Named volumes and paths outside the workspace are rejected.
+fn bind_mounts(volumes: &[Value], working_dir: &Path) -> anyhow::Result<Vec<String>> {
+    volumes
+        .iter()
+        .map(|spec| {
+            let spec = spec
+                .as_str()
+                .ok_or_else(|| anyhow::anyhow!("Volume entries must be strings"))?;
+            let mut parts = spec.splitn(3, ':');
+            let (Some(host), Some(target)) = (parts.next(), parts.next()) else {
+                anyhow::bail!("Volume '{}' must be 'host_path:container_path[:ro]'", spec);
+            };
+            let mode = parts.next();
+            if !matches!(mode, None | Some("ro") | Some("rw")) {
+                anyhow::bail!("Unsupported volume mode in '{}'", spec);
+            }
+            let host = workspace_path(host, working_dir)?;
+            let mut mount = format!("{}:{}", host.display(), target);
+            if let Some(mode) = mode {
+                mount.push(':');
+                mount.push_str(mode);
+            }
+            Ok(mount)
+        })
+        .collect()
+}
+
+/// Run docker with a timeout; the child is killed if it overruns.
+async fn docker(args: &[String], dir: Option<&Path>, timeout: Duration) -> anyhow::Result<Output> {
+    let mut command = Command::new("docker");
+    command.args(args).kill_on_drop(true);
+    if let Some(dir) = dir {
+        command.current_dir(dir);
+    }
+    match tokio::time::timeout(timeout, command.output()).await {
+        Ok(Ok(output)) => Ok(output),
+        Ok(Err(e)) => Err(anyhow::anyhow!("Failed to run docker: {}", e)),
+        Err(_) => Err(anyhow::anyhow!(
+            "docker {} timed out after {}s",
+            args.first().map(String::as_str).unwrap_or_default(),
+            timeout.as_secs()
+        )),
+    }
+}
+
+/// Run docker and return stdout, or an error carrying stderr.
+async fn docker_ok(
+    args: &[String],
+    dir: Option<&Path>,
+    timeout: Duration,
+) -> anyhow::Result<String> {
+    let output = docker(args, dir, timeout).await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        anyhow::bail!(
+            "docker {} failed: {}",
+            args.first().map(String::as_str).unwrap_or_default(),
+            tail(stderr.trim(), OUTPUT_TAIL_LINES)
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+fn tail(text: &str, lines: usize) -> String {
+    let all: Vec<&str> = text.lines().collect();
+    let start = all.len().saturating_sub(lines);
+    all[start..].join("\n")
+}
+
+fn strings(args: &[&str]) -> Vec<String> {
+    args.iter().map(|s| s.to_string()).collect()
+}
+
+/// Parse `--format '{{json .}}'` output (one JSON object per line).
+fn json_lines(text: &str) -> Vec<Value> {
+    text.lines()
+        .filter_map(|line| serde_json::from_str(line.trim()).ok())
+        .collect()
+}
+
+/// Refuse to touch containers that this mission did not start.
+async fn ensure_owned(container: &str, mission_id: &str) -> anyhow::Result<()> {
+    let format = format!("{{{{index .Config.Labels \"{}\"}}}}", MISSION_LABEL);
+    let owner = docker_ok(
+        &strings(&["inspect", "--format", &format, container]),
+        None,
+        COMMAND_TIMEOUT,
+    )
+    .await?;
+    if owner != mission_id {
+        anyhow::bail!("Container '{}' was not started by this mission", container);
+    }
+    Ok(())
+}
+
+fn find_compose_file(args: &Value, working_dir: &Path) -> anyhow::Result<PathBuf> {
+    if let Some(file) = args["file"].as_str() {
+        return workspace_path(file, working_dir);
+    }
+    let dir = match args["project_dir"].as_str() {
+        Some(dir) => workspace_path(dir, working_dir)?,
+        None => working_dir.to_path_buf(),
+    };
+    COMPOSE_FILES
+        .iter()
+        .map(|name| dir.join(name))
+        .find(|path| path.is_file())
+        .ok_or_else(|| anyhow::anyhow!("No compose file found in {}", dir.display()))
+}
+
+/// Service keys granting access to the host; none of them may be set.
+const COMPOSE_HOST_KEYS: &[&str] = &[
+    "privileged",
+    "cap_add",
+    "devices",
+    "device_cgroup_rules",
+    "security_opt",
+    "volumes_from",
+    "cgroup_parent",
+];
+/// Service namespace keys that may not join the host or a foreign container.
+const COMPOSE_NAMESPACE_KEYS: &[&str] = &["network_mode", "pid", "ipc", "uts", "userns_mode"];
+
+/// Check the resolved compose config (`docker compose config --format json`)
+/// against the rules `run` enforces: no host privileges or namespaces, and
+/// bind mounts and build contexts inside the workspace.
+fn check_compose_config(config: &Value, working_dir: &Path) -> anyhow::Result<()> {
+    let services = config["services"].as_object().cloned().unwrap_or_default();
+    for (name, service) in &services {
+        for key in COMPOSE_HOST_KEYS {
+            match service.get(*key) {
+                None | Some(Value::Null) | Some(Value::Bool(false)) => {}
+                Some(Value::Array(items)) if items.is_empty() => {}
+                Some(_) => anyhow::bail!("Compose service '{}' may not set '{}'", name, key),
+            }
+        }
+        for key in COMPOSE_NAMESPACE_KEYS {
+            if let Some(mode) = service[*key].as_str() {
+                if mode == "host" || mode.starts_with("container:") {
+                    anyhow::bail!("Compose service '{}' may not set '{}: {}'", name, key, mode);
+                }
+            }
+        }
+        for volume in service["volumes"].as_array().into_iter().flatten() {
+            if volume["type"].as_str() == Some("bind") {
+                let source = volume["source"].as_str().unwrap_or_default();
+                workspace_path(source, working_dir)
+                    .map_err(|e| anyhow::anyhow!("Compose service '{}': {}", name, e))?;
+            }
+        }
+        if let Some(context) = service["build"]["context"].as_str() {
+            // Remote contexts (git URLs) are resolved by the daemon.
+            if context.starts_with('/') {
+                workspace_path(context, working_dir)
+                    .map_err(|e| anyhow::anyhow!("Compose service '{}': {}", name, e))?;
+            }
+        }
+    }
+    // Local-driver volumes with `device` options are bind mounts in disguise.
+    let volumes = config["volumes"].as_object().cloned().unwrap_or_default();
+    for (name, volume) in &volumes {
+        if volume["driver_opts"].get("device").is_some() {
+            anyhow::bail!("Compose volume '{}' may not set a device", name);
+        }
+    }
+    Ok(())
+}
+
+/// Compose override adding the mission label and resource caps to every
+/// service, so compose containers are capped and cleaned up like `run` ones.
+fn compose_override(services: &[String], mission_id: &str, caps: &ResourceCaps) -> Value {
+    let mut entries = Map::new();
+    for service in services {
+        entries.insert(
+            service.clone(),
+            json!({
+                "labels": { MISSION_LABEL: mission_id },
+                "mem_limit": caps.memory_bytes,
+                "cpus": caps.cpus,
+                "pids_limit": caps.pids,
+            }),
+        );
+    }
+    json!({ "services": entries })
+}
+
+/// Base `docker compose` arguments for a mission's stack, along with its
+/// resolved config. The override file is written to the OS temp dir so the
+/// workspace is left untouched.
+async fn compose_args(
+    compose_file: &Path,
+    mission_id: &str,
+    caps: &ResourceCaps,
+) -> anyhow::Result<(Vec<String>, PathBuf, Value)> {
+    let project_dir = compose_file
+        .parent()
+        .unwrap_or(Path::new("."))
+        .to_path_buf();
+    let project = compose_project(mission_id, &project_dir);
+    let file = compose_file.display().to_string();
+    let config = docker_ok(
+        &strings(&["compose", "-f", &file, "config", "--format", "json"]),
+        Some(&project_dir),
+        COMMAND_TIMEOUT,
+    )
+    .await?;
+    let config: Value = serde_json::from_str(&config)
+        .map_err(|e| anyhow::anyhow!("Unreadable compose config: {}", e))?;
+    let services: Vec<String> = config["services"]
+        .as_object()
+        .map(|services| services.keys().cloned().collect())
+        .unwrap_or_default();
+    let override_path = std::env::temp_dir().join(format!("{}.override.yml", project));
+    let override_yaml = serde_yaml::to_string(&compose_override(&services, mission_id, caps))?;
+    tokio::fs::write(&override_path, override_yaml).await?;
+    let args = strings(&[
+        "compose",
+        "-p",
+        &project,
+        "-f",
+        &file,
+        "-f",
+        &override_path.display().to_string(),
+    ]);
+    Ok((args, project_dir, config))
+}
+
+/// Remove every container (and compose network) started for a mission.
+/// Called when a mission ends; a missing Docker install is not an error.
+pub async fn cleanup_mission_containers(mission_id: Uuid) {
+    let mission_id = mission_id.to_string();
+    let Ok(ids) = docker_ok(
+        &strings(&["ps", "-aq", "--filter", &mission_filter(&mission_id)]),
+        None,
+        CLEANUP_TIMEOUT,
+    )
+    .await
+    else {
+        return;
+    };
+    let ids: Vec<String> = ids.lines().map(str::to_string).collect();
+    if !ids.is_empty() {
+        let mut args = strings(&["rm", "-f", "-v"]);
+        args.extend(ids.iter().cloned());
+        match docker_ok(&args, None, CLEANUP_TIMEOUT).await {
+            Ok(_) => tracing::info!(
+                mission_id = %mission_id,
+                count = ids.len(),
+                "Removed mission containers"
+            ),
+            Err(e) => tracing::warn!(
+                mission_id = %mission_id,
+                error = %e,
+                "Failed to remove mission containers"
+            ),
+        }
+    }
+
+    let prefix = compose_project_prefix(&mission_id);
+    let Ok(networks) = docker_ok(
+        &strings(&[
+            "network",
+            "ls",
+            "--filter",
+            "label=com.docker.compose.project",
+            "--format",
+            "{{.Name}}\t{{.Label \"com.docker.compose.project\"}}",
+        ]),
+        None,
+        CLEANUP_TIMEOUT,
+    )
+    .await
+    else {
+        return;
+    };
+    let stale: Vec<String> = networks
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .filter(|(_, project)| project.starts_with(&prefix))
+        .map(|(name, _)| name.to_string())
+        .collect();
+    if !stale.is_empty() {
+        let mut args = strings(&["network", "rm"]);
+        args.extend(stale);
+        if let Err(e) = docker_ok(&args, None, CLEANUP_TIMEOUT).await {
+            tracing::warn!(mission_id = %mission_id, error = %e, "Failed to remove compose networks");
+        }
+    }
+}
+
+// ─────────────────────────────────────────────────────────────────────────────
+// Tool
+// ─────────────────────────────────────────────────────────────────────────────
+
+/// Build images, run containers and manage compose stacks for the mission.
+pub struct Docker;
+
+#[async_trait]
+impl Tool for Docker {
+    fn name(&self) -> &str {
+        "docker"
+    }
+
+    fn description(&self) -> &str {
+        "Manage Docker for this mission. Actions: build (image from a workspace context), run (container, detached by default), ps (this mission's containers), logs, stop (stop and remove), compose_up / compose_down (stack from a compose file in the workspace). Containers get memory/CPU/PID caps and are removed automatically when the mission ends. Bind mounts must be inside the workspace, and compose services may not use privileged mode, added capabilities, devices or host namespaces."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["build", "run", "ps", "logs", "stop", "compose_up", "compose_down"]
+                },
+                "tag": {
+                    "type": "string",
+                    "description": "build: image tag (e.g. 'myapp:dev')"
+                },
+                "context": {
+                    "type": "string",
+                    "description": "build: context directory in the workspace (default: '.')"
+                },
+                "dockerfile": {
+                    "type": "string",
+                    "description": "build: Dockerfile path relative to the context"
+                },
+                "image": {
+                    "type": "string",
+                    "description": "run: image to start"
+                },
+                "name": {
+                    "type": "string",
+                    "description": "run: container name"
+                },
+                "command": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "run: command and arguments"
+                },
+                "env": {
+                    "type": "object",
+                    "description": "run: environment variables"
+                },
+                "ports": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "run: port mappings like '8080:80'"
+                },
+                "volumes": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "run: bind mounts 'workspace_path:container_path[:ro]'"
+                },
+                "detach": {
+                    "type": "boolean",
+                    "description": "run: return immediately (default: true). When false, waits and returns the output"
+                },
+                "memory": {
+                    "type": "string",
+                    "description": "run/compose_up: memory limit like '512m' (capped by the server)"
+                },
+                "cpus": {
+                    "type": "number",
+                    "description": "run/compose_up: CPU limit (capped by the server)"
+                },
+                "container": {
+                    "type": "string",
+                    "description": "logs/stop: container name or id"
+                },
+                "tail": {
+                    "type": "integer",
+                    "description": "logs: number of lines (default: 200)"
+                },
+                "since": {
+                    "type": "string",
+                    "description": "logs: only logs since this time or duration (e.g. '10m')"
+                },
+                "file": {
+                    "type": "string",
+                    "description": "compose: compose file path (default: compose.yaml / docker-compose.yml)"
+                },
+                "project_dir": {
+                    "type": "string",
+                    "description": "compose: directory to look for the compose file in"
+                },
+                "build": {
+                    "type": "boolean",
+                    "description": "compose_up: build images before starting"
+                },
+                "remove_volumes": {
+                    "type": "boolean",
+                    "description": "compose_down: also remove the stack's volumes"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(&self, args: Value, working_dir: &Path) -> anyhow::Result<String> {
+        let action = args["action"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing 'action' argument"))?;
+        let mission_id = mission_id();
+        let label = format!("{}={}", MISSION_LABEL, mission_id);
+
+        match action {
+            "build" => {
+                let tag = args["tag"]
+                    .as_str()
+                    .ok_or_else(|| anyhow::anyhow!("Missing 'tag' argument"))?;
+                let tag = positional(tag, "tag")?;
+                let context = workspace_path(args["context"].as_str().unwrap_or("."), working_dir)?;
+                let mut cmd = strings(&["build", "-t", tag, "--label", &label]);
+                if let Some(dockerfile) = args["dockerfile"].as_str() {
+                    let dockerfile = workspace_path(dockerfile, &context)?;
+                    cmd.push("-f".to_string());
+                    cmd.push(dockerfile.display().to_string());
+                }
+                cmd.push(context.display().to_string());
+                tracing::info!(tag = %tag, context = %context.display(), "docker build");
+                let output = docker(&cmd, Some(&context), BUILD_TIMEOUT).await?;
+                // BuildKit writes progress to stderr.
+                let log = format!(
+                    "{}{}",
+                    String::from_utf8_lossy(&output.stdout),
+                    String::from_utf8_lossy(&output.stderr)
+                );
+                Ok(serde_json::to_string_pretty(&json!({
+                    "success": output.status.success(),
+                    "tag": tag,
+                    "output": tail(log.trim(), OUTPUT_TAIL_LINES),
+                }))?)
+            }
+            "run" => {
+                let image = args["image"]
+                    .as_str()
+                    .ok_or_else(|| anyhow::anyhow!("Missing 'image' argument"))?;
+                let image = positional(image, "image")?;
+                let caps = ResourceCaps::from_env().limit(&args)?;
+                let detach = args["detach"].as_bool().unwrap_or(true);
+                let mut cmd = strings(&["run", "--label", &label]);
+                cmd.extend(caps.run_flags());
+                cmd.push(if detach { "-d" } else { "--rm" }.to_string());
+                if let Some(name) = args["name"].as_str() {
+                    cmd.push(format!("--name={}", positional(name, "name")?));
+                }
+                if let Some(env) = args["env"].as_object() {
+                    for (key, value) in env {
+                        let value = value
+                            .as_str()
+                            .map(str::to_string)
+                            .unwrap_or_else(|| value.to_string());
+                        cmd.extend(["-e".to_string(), format!("{}={}", key, value)]);
+                    }
+                }
+                for port in args["ports"].as_array().into_iter().flatten() {
+                    if let Some(port) = port.as_str() {
+                        cmd.extend(strings(&["-p", port]));
+                    }
+                }
+                let volumes = args["volumes"].as_array().cloned().unwrap_or_default();
+                for mount in bind_mounts(&volumes, working_dir)? {
+                    cmd.extend(["-v".to_string(), mount]);
+                }
+                cmd.push(image.to_string());
+                for part in args["command"].as_array().into_iter().flatten() {
+                    if let Some(part) = part.as_str() {
+                        cmd.push(part.to_string());
+                    }
+                }
+                tracing::info!(image = %image, detach, "docker run");
+                if detach {
+                    let id = docker_ok(&cmd, Some(working_dir), COMMAND_TIMEOUT).await?;
+                    Ok(serde_json::to_string_pretty(&json!({
+                        "container_id": id,
+                        "memory_bytes": caps.memory_bytes,
+                        "cpus": caps.cpus,
+                    }))?)
+                } else {
+                    let output = docker(&cmd, Some(working_dir), COMMAND_TIMEOUT).await?;
+                    Ok(serde_json::to_string_pretty(&json!({
+                        "exit_code": output.status.code(),
+                        "stdout": tail(&String::from_utf8_lossy(&output.stdout), OUTPUT_TAIL_LINES * 4),
+                        "stderr": tail(&String::from_utf8_lossy(&output.stderr), OUTPUT_TAIL_LINES),
+                    }))?)
+                }
+            }
+            "ps" => {
+                let text = docker_ok(
+                    &strings(&[
+                        "ps",
+                        "-a",
+                        "--filter",
+                        &mission_filter(&mission_id),
+                        "--format",
+                        "{{json .}}",
+                    ]),
+                    None,
+                    COMMAND_TIMEOUT,
+                )
+                .await?;
+                let containers: Vec<Value> = json_lines(&text)
+                    .into_iter()
+                    .map(|c| {
+                        json!({
+                            "id": c.get("ID"),
+                            "name": c.get("Names"),
+                            "image": c.get("Image"),
+                            "state": c.get("State"),
+                            "status": c.get("Status"),
+                            "ports": c.get("Ports"),
+                        })
+                    })
+                    .collect();
+                Ok(serde_json::to_string_pretty(
+                    &json!({ "containers": containers }),
+                )?)
+            }
+            "logs" => {
+                let container = args["container"]
+                    .as_str()
+                    .ok_or_else(|| anyhow::anyhow!("Missing 'container' argument"))?;
+                let container = positional(container, "container")?;
+                ensure_owned(container, &mission_id).await?;
+                let lines = args["tail"].as_u64().unwrap_or(DEFAULT_LOG_LINES);
+                let mut cmd = strings(&["logs", "--tail", &lines.to_string()]);
+                if let Some(since) = args["since"].as_str() {
+                    cmd.extend(strings(&["--since", since]));
+                }
+                cmd.push(container.to_string());
+                let output = docker(&cmd, None, COMMAND_TIMEOUT).await?;
+                // Container stdout and stderr are passed through as-is.
+                Ok(format!(
+                    "{}{}",
+                    String::from_utf8_lossy(&output.stdout),
+                    String::from_utf8_lossy(&output.stderr)
+                ))
+            }
+            "stop" => {
+                let container = args["container"]
+                    .as_str()
+                    .ok_or_else(|| anyhow::anyhow!("Missing 'container' argument"))?;
+                let container = positional(container, "container")?;
+                ensure_owned(container, &mission_id).await?;
+                docker_ok(&strings(&["rm", "-f", container]), None, COMMAND_TIMEOUT).await?;
+                Ok(format!("Stopped and removed container '{}'", container))
+            }
+            "compose_up" => {
+                let compose_file = find_compose_file(&args, working_dir)?;
+                let caps = ResourceCaps::from_env().limit(&args)?;
+                let (base, project_dir, config) =
+                    compose_args(&compose_file, &mission_id, &caps).await?;
+                check_compose_config(&config, working_dir)?;
+                let mut cmd = base.clone();
+                cmd.extend(strings(&["up", "-d", "--remove-orphans"]));
+                if args["build"].as_bool().unwrap_or(false) {
+                    cmd.push("--build".to_string());
+                }
+                tracing::info!(file = %compose_file.display(), "docker compose up");
+                docker_ok(&cmd, Some(&project_dir), BUILD_TIMEOUT).await?;
+
+                let mut ps = base;
+                ps.extend(strings(&["ps", "--format", "json"]));
+                let text = docker_ok(&ps, Some(&project_dir), COMMAND_TIMEOUT).await?;
+                // Newer compose prints one object per line, older ones an array.
+                let services = match serde_json::from_str::<Value>(&text) {
+                    Ok(Value::Array(items)) => items,
+                    _ => json_lines(&text),
+                };
+                let services: Vec<Value> = services
+                    .iter()
+                    .map(|s| {
+                        json!({
+                            "service": s.get("Service"),
+                            "name": s.get("Name"),
+                            "state": s.get("State"),
+                            "status": s.get("Status"),
+                            "ports": s.get("Publishers").or_else(|| s.get("Ports")),
+                        })
+                    })
+                    .collect();
+                Ok(serde_json::to_string_pretty(&json!({
+                    "project": compose_project(&mission_id, &project_dir),
+                    "services": services,
+                }))?)
+            }
+            "compose_down" => {
+                let compose_file = find_compose_file(&args, working_dir)?;
+                let caps = ResourceCaps::from_env();
+                let (mut cmd, project_dir, _) =
+                    compose_args(&compose_file, &mission_id, &caps).await?;
+                cmd.extend(strings(&["down", "--remove-orphans"]));
+                if args["remove_volumes"].as_bool().unwrap_or(false) {
+                    cmd.push("--volumes".to_string());
+                }
+                docker_ok(&cmd, Some(&project_dir), COMMAND_TIMEOUT).await?;
+                Ok(format!(
+                    "Stopped compose project '{}'",
+                    compose_project(&mission_id, &project_dir)
+                ))
+            }
+            other => Err(anyhow::anyhow!("Unknown action '{}'", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_sizes_and_caps() {
+        assert_eq!(parse_memory("512m"), Some(512 << 20));
+        assert_eq!(parse_memory("2G"), Some(2 << 30));
+        assert_eq!(parse_memory("1kb"), Some(1024));
+        assert_eq!(parse_memory("4096"), Some(4096));
+        assert_eq!(parse_memory("lots"), None);
+
+        let caps = ResourceCaps {
+            memory_bytes: 1 << 30,
+            cpus: 2.0,
+            pids: 256,
+        };
+        let lower = caps
+            .limit(&json!({ "memory": "256m", "cpus": 0.5 }))
+            .unwrap();
+        assert_eq!((lower.memory_bytes, lower.cpus), (256 << 20, 0.5));
+        let higher = caps.limit(&json!({ "memory": "8g", "cpus": 16 })).unwrap();
+        assert_eq!(higher, caps);
+        assert!(caps.limit(&json!({ "memory": "huge" })).is_err());
+        assert_eq!(
+            caps.run_flags(),
+            vec!["--memory=1073741824", "--cpus=2", "--pids-limit=256"]
+        );
+    }
+
+    #[test]
+    fn bind_mounts_stay_in_workspace() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::create_dir(temp.path().join("data")).unwrap();
+        let root = crate::util::canonicalize_path(temp.path()).unwrap();
+
+        let mounts = bind_mounts(&[json!("data:/data:ro")], temp.path()).unwrap();
+        assert_eq!(
+            mounts,
+            vec![format!("{}:/data:ro", root.join("data").display())]
+        );
+        assert!(bind_mounts(&[json!("/etc:/host-etc")], temp.path()).is_err());
+        assert!(bind_mounts(&[json!("../:/up")], temp.path()).is_err());
+        assert!(bind_mounts(&[json!("data")], temp.path()).is_err());
+        assert!(bind_mounts(&[json!("data:/data:z")], temp.path()).is_err());
+    }
+
+    #[test]
+    fn flag_like_positionals_are_rejected() {
+        assert_eq!(positional("alpine:3", "image").unwrap(), "alpine:3");
+        assert!(positional("--privileged", "image").is_err());
+        assert!(positional("-v=/:/host", "image").is_err());
+        assert!(positional("", "name").is_err());
+    }
+
+    #[tokio::test]
+    async fn run_rejects_a_flag_as_image() {
+        let temp = tempfile::tempdir().unwrap();
+        let err = Docker
+            .execute(
+                json!({ "action": "run", "image": "--privileged", "command": ["alpine"] }),
+                temp.path(),
+            )
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("Invalid image"));
+    }
+
+    #[test]
+    fn compose_config_may_not_reach_the_host() {
+        let temp = tempfile::tempdir().unwrap();
+        std::fs::create_dir(temp.path().join("data")).unwrap();
+        let data = temp.path().join("data").display().to_string();
+        let service = |extra: Value| {
+            let mut web = json!({
+                "image": "nginx",
+                "volumes": [{ "type": "bind", "source": data, "target": "/data" }],
+                "build": { "context": temp.path().display().to_string() },
+            });
+            web.as_object_mut()
+                .unwrap()
+                .extend(extra.as_object().unwrap().clone());
+            json!({ "services": { "web": web } })
+        };
+
+        assert!(check_compose_config(&service(json!({})), temp.path()).is_ok());
+        assert!(
+            check_compose_config(&service(json!({ "privileged": false })), temp.path()).is_ok()
+        );
+        for denied in [
+            json!({ "privileged": true }),
+            json!({ "cap_add": ["SYS_ADMIN"] }),
+            json!({ "network_mode": "host" }),
+            json!({ "pid": "host" }),
+            json!({ "ipc": "container:other" }),
+            json!({ "devices": [{ "source": "/dev/kvm", "target": "/dev/kvm" }] }),
+            json!({ "volumes": [{ "type": "bind", "source": "/", "target": "/host" }] }),
+            json!({ "build": { "context": "/etc" } }),
+        ] {
+            assert!(
+                check_compose_config(&service(denied.clone()), temp.path()).is_err(),
+                "{} was allowed",
+                denied
+            );
+        }
+        let device_volume = json!({
+            "services": {},
+            "volumes": { "root": { "driver_opts": { "type": "none", "o": "bind", "device": "/" } } },
+        });
+        assert!(check_compose_config(&device_volume, temp.path()).is_err());
+    }
+
+    #[test]
+    fn compose_projects_are_scoped_to_the_mission() {
+        let id = "1234abcd-0000-0000-0000-000000000000";
+        let project = compose_project(id, Path::new("/work/My App"));
+        assert_eq!(project, "sbx-1234abcd-my-app");
+        assert!(project.starts_with(&compose_project_prefix(id)));
+
+        let caps = ResourceCaps {
+            memory_bytes: 1024,
+            cpus: 1.0,
+            pids: 64,
+        };
+        let override_file = compose_override(&["web".to_string()], id, &caps);
+        assert_eq!(
+            override_file["services"]["web"]["labels"][MISSION_LABEL],
+            id
+        );
+        assert_eq!(override_file["services"]["web"]["mem_limit"], 1024);
+    }
+}
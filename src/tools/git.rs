@@ -0,0 +1,396 @@
+//! Git tools: per-mission branches, commits and pushes.
+//!
+//! The guardrails are enforced here rather than left to the prompt:
+//! - commits and pushes are refused on protected branches (`main`, `master`,
+//!   `develop`, `release/*`, plus `SANDBOXED_SH_GIT_PROTECTED_BRANCHES`)
+//! - pushes never force: no `--force`/`--force-with-lease`, and the refspec
+//!   always names the current branch on both sides
+//!
+//! Commit messages carry a `Mission-Id:` trailer (from `SANDBOXED_SH_MISSION_ID`)
+//! so commits can be traced back to the mission that made them.
+
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::process::Command;
+
+use super::Tool;
+
+/// Branches that are always protected.
+const DEFAULT_PROTECTED_BRANCHES: &[&str] = &["main", "master", "develop", "release/*"];
+
+/// Maximum number of changed files listed in a generated commit subject.
+const SUBJECT_FILES: usize = 3;
+
+/// Protected branch patterns: the defaults plus the comma-separated
+/// `SANDBOXED_SH_GIT_PROTECTED_BRANCHES`. A trailing `*` matches any suffix.
+fn protected_patterns() -> Vec<String> {
+    let mut patterns: Vec<String> = DEFAULT_PROTECTED_BRANCHES
+        .iter()
+        .map(|p| p.to_string())
+        .collect();
+    if let Ok(extra) = std::env::var("SANDBOXED_SH_GIT_PROTECTED_BRANCHES") {
+        patterns.extend(
+            extra
+                .split(',')
+                .map(str::trim)
+                .filter(|p| !p.is_empty())
+                .map(str::to_string),
+        );
+    }
+    patterns
+}
+
+fn is_protected(branch: &str, patterns: &[String]) -> bool {
+    patterns
+        .iter()
+        .any(|pattern| match pattern.strip_suffix('*') {
+            Some(prefix) => branch.starts_with(prefix),
+            None => branch == pattern,
+        })
+}
+
+fn mission_id() -> Option<String> {
+    std::env::var("SANDBOXED_SH_MISSION_ID")
+        .ok()
+        .filter(|id| !id.trim().is_empty())
+}
+
+/// Default branch name for the current mission (`mission/<first 8 of id>`).
+fn mission_branch_name(mission_id: &str) -> String {
+    let short: String = mission_id.chars().take(8).collect();
+    format!("mission/{}", short)
+}
+
+/// Build a commit message, generating the subject from the staged changes
+/// when none is given, and appending the mission trailer.
+fn commit_message(message: Option<&str>, staged: &[String], mission_id: Option<&str>) -> String {
+    let mut text = match message.map(str::trim).filter(|m| !m.is_empty()) {
+        Some(message) => message.to_string(),
+        None => {
+            let names: Vec<&str> = staged
+                .iter()
+                .take(SUBJECT_FILES)
+                .map(|path| {
+                    Path::new(path)
+                        .file_name()
+                        .and_then(|name| name.to_str())
+                        .unwrap_or(path)
+                })
+                .collect();
+            let mut subject = format!("Update {}", names.join(", "));
+            if staged.len() > SUBJECT_FILES {
+                subject.push_str(&format!(" (+{} more)", staged.len() - SUBJECT_FILES));
+            }
+            subject
+        }
+    };
+    if let Some(id) = mission_id {
+        text.push_str(&format!("\n\nMission-Id: {}", id));
+    }
+    text
+}
+
+/// Run git in `dir`, returning trimmed stdout or an error with stderr.
+async fn git(dir: &Path, args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to run git: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(anyhow::anyhow!(
+            "git {} failed: {}",
+            args.first().copied().unwrap_or_default(),
+            stderr.trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Resolve the repository root from the optional `repo_path` argument.
+async fn repo_root(args: &Value, working_dir: &Path) -> anyhow::Result<PathBuf> {
+    let dir = match args["repo_path"].as_str() {
+        Some(path) => super::resolve_path_simple(path, working_dir),
+        None => working_dir.to_path_buf(),
+    };
+    let root = git(&dir, &["rev-parse", "--show-toplevel"])
+        .await
+        .map_err(|_| anyhow::anyhow!("Not a git repository: {}", dir.display()))?;
+    Ok(PathBuf::from(root))
+}
+
+async fn current_branch(root: &Path) -> anyhow::Result<String> {
+    git(root, &["symbolic-ref", "--quiet", "--short", "HEAD"])
+        .await
+        .map_err(|_| anyhow::anyhow!("HEAD is detached; create a branch with git_create_branch"))
+}
+
+/// Current branch, refusing protected ones.
+async fn writable_branch(root: &Path, action: &str) -> anyhow::Result<String> {
+    let branch = current_branch(root).await?;
+    if is_protected(&branch, &protected_patterns()) {
+        return Err(anyhow::anyhow!(
+            "Refusing to {} on protected branch '{}'; create a mission branch with git_create_branch first",
+            action,
+            branch
+        ));
+    }
+    Ok(branch)
+}
+
+fn repo_path_schema() -> Value {
+    json!({
+        "type": "string",
+        "description": "Optional: path inside the repository (defaults to the working directory)"
+    })
+}
+
+/// Create (or switch to) a branch for the current mission.
+pub struct CreateBranch;
+
+#[async_trait]
+impl Tool for CreateBranch {
+    fn name(&self) -> &str {
+        "git_create_branch"
+    }
+
+    fn description(&self) -> &str {
+        "Create a git branch for this mission and switch to it (switches if it already exists). Defaults to 'mission/<mission id>'. Protected branches (main, master, develop, release/*) cannot be created or used."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Optional: branch name (defaults to 'mission/<mission id>')"
+                },
+                "base": {
+                    "type": "string",
+                    "description": "Optional: commit or branch to start from (defaults to HEAD)"
+                },
+                "repo_path": repo_path_schema()
+            }
+        })
+    }
+
+    async fn execute(&self, args: Value, working_dir: &Path) -> anyhow::Result<String> {
+        let root = repo_root(&args, working_dir).await?;
+        let name = match args["name"]
+            .as_str()
+            .map(str::trim)
+            .filter(|n| !n.is_empty())
+        {
+            Some(name) => name.to_string(),
+            None => mission_id()
+                .map(|id| mission_branch_name(&id))
+                .ok_or_else(|| {
+                    anyhow::anyhow!("No mission ID available; pass an explicit 'name'")
+                })?,
+        };
+        if is_protected(&name, &protected_patterns()) {
+            return Err(anyhow::anyhow!(
+                "Refusing to use protected branch '{}' as a mission branch",
+                name
+            ));
+        }
+        git(&root, &["check-ref-format", "--branch", &name])
+            .await
+            .map_err(|_| anyhow::anyhow!("Invalid branch name: {}", name))?;
+
+        let ref_name = format!("refs/heads/{}", name);
+        let exists = git(&root, &["rev-parse", "--verify", "--quiet", &ref_name])
+            .await
+            .is_ok();
+        if exists {
+            git(&root, &["switch", &name]).await?;
+            return Ok(format!("Switched to existing branch '{}'", name));
+        }
+        match args["base"].as_str() {
+            Some(base) if base.starts_with('-') => {
+                return Err(anyhow::anyhow!("Invalid base: {}", base));
+            }
+            Some(base) => git(&root, &["switch", "-c", &name, base]).await?,
+            None => git(&root, &["switch", "-c", &name]).await?,
+        };
+        Ok(format!("Created and switched to branch '{}'", name))
+    }
+}
+
+/// Stage changes and commit them with a message referencing the mission.
+pub struct Commit;
+
+#[async_trait]
+impl Tool for Commit {
+    fn name(&self) -> &str {
+        "git_commit"
+    }
+
+    fn description(&self) -> &str {
+        "Stage and commit changes on the current (non-protected) branch. The message gets a 'Mission-Id' trailer; if omitted, a subject is generated from the staged files."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "message": {
+                    "type": "string",
+                    "description": "Optional: commit message (generated from the staged files if omitted)"
+                },
+                "paths": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Optional: paths to stage (defaults to all changes)"
+                },
+                "repo_path": repo_path_schema()
+            }
+        })
+    }
+
+    async fn execute(&self, args: Value, working_dir: &Path) -> anyhow::Result<String> {
+        let root = repo_root(&args, working_dir).await?;
+        let branch = writable_branch(&root, "commit").await?;
+
+        let paths: Vec<&str> = args["paths"]
+            .as_array()
+            .map(|paths| paths.iter().filter_map(|p| p.as_str()).collect())
+            .unwrap_or_default();
+        if paths.is_empty() {
+            git(&root, &["add", "-A"]).await?;
+        } else {
+            let mut add = vec!["add", "--"];
+            add.extend(paths);
+            git(&root, &add).await?;
+        }
+
+        let staged: Vec<String> = git(&root, &["diff", "--cached", "--name-only"])
+            .await?
+            .lines()
+            .map(str::to_string)
+            .collect();
+        if staged.is_empty() {
+            return Ok("Nothing to commit".to_string());
+        }
+
+        let message = commit_message(args["message"].as_str(), &staged, mission_id().as_deref());
+        git(&root, &["commit", "--quiet", "-m", &message]).await?;
+        let sha = git(&root, &["rev-parse", "--short", "HEAD"]).await?;
+        let subject = message.lines().next().unwrap_or_default();
+        Ok(format!(
+            "Committed {} on '{}' ({} file(s)): {}",
+            sha,
+            branch,
+            staged.len(),
+            subject
+        ))
+    }
+}
+
+/// Push the current branch without ever forcing.
+pub struct Push;
+
+#[async_trait]
+impl Tool for Push {
+    fn name(&self) -> &str {
+        "git_push"
+    }
+
+    fn description(&self) -> &str {
+        "Push the current (non-protected) branch to a remote branch of the same name and set it as upstream. Never force-pushes; rejected non-fast-forward pushes are reported as errors."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "remote": {
+                    "type": "string",
+                    "description": "Optional: remote name (defaults to 'origin')"
+                },
+                "repo_path": repo_path_schema()
+            }
+        })
+    }
+
+    async fn execute(&self, args: Value, working_dir: &Path) -> anyhow::Result<String> {
+        let root = repo_root(&args, working_dir).await?;
+        let branch = writable_branch(&root, "push").await?;
+        let remote = args["remote"].as_str().unwrap_or("origin");
+        if remote.starts_with('-') {
+            return Err(anyhow::anyhow!("Invalid remote name: {}", remote));
+        }
+        let refspec = format!("refs/heads/{0}:refs/heads/{0}", branch);
+        git(&root, &["push", "--set-upstream", remote, &refspec]).await?;
+        Ok(format!("Pushed '{}' to {}", branch, remote))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn protected_patterns_match_exact_and_prefix() {
+        let patterns: Vec<String> = DEFAULT_PROTECTED_BRANCHES
+            .iter()
+            .map(|p| p.to_string())
+            .collect();
+        assert!(is_protected("main", &patterns));
+        assert!(is_protected("release/1.2", &patterns));
+        assert!(!is_protected("mission/abcd1234", &patterns));
+        assert!(!is_protected("maintenance", &patterns));
+    }
+
+    #[test]
+    fn commit_message_generates_subject_and_trailer() {
+        let staged: Vec<String> = ["src/a.rs", "src/b.rs", "c.md", "d.txt"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(
+            commit_message(None, &staged, Some("1234")),
+            "Update a.rs, b.rs, c.md (+1 more)\n\nMission-Id: 1234"
+        );
+        assert_eq!(commit_message(Some("Fix bug"), &staged, None), "Fix bug");
+    }
+
+    #[tokio::test]
+    async fn commit_refuses_protected_branch() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        git(root, &["init", "--quiet", "--initial-branch", "main"])
+            .await
+            .unwrap();
+        git(root, &["config", "user.email", "test@example.com"])
+            .await
+            .unwrap();
+        git(root, &["config", "user.name", "Test"]).await.unwrap();
+        std::fs::write(root.join("a.txt"), "a").unwrap();
+
+        let err = Commit
+            .execute(json!({}), root)
+            .await
+            .expect_err("commit on main must be refused");
+        assert!(err.to_string().contains("protected branch 'main'"));
+
+        CreateBranch
+            .execute(json!({ "name": "mission/test" }), root)
+            .await
+            .unwrap();
+        let out = Commit
+            .execute(json!({ "message": "Add a" }), root)
+            .await
+            .unwrap();
+        assert!(out.contains("on 'mission/test'"));
+        assert!(CreateBranch
+            .execute(json!({ "name": "master" }), root)
+            .await
+            .is_err());
+    }
+}
@@ -0,0 +1,1114 @@
+//! HTTP request tool with OpenAPI awareness.
+//!
+//! `http_request` can load an OpenAPI 3 spec from the workspace or a URL,
+//! resolve an operation by `operationId` or method + path, validate the
+//! supplied parameters and body against the spec's schemas, and attach
+//! credentials according to the spec's security schemes. Credentials are read
+//! from an environment variable so they never appear in tool arguments, and
+//! sensitive headers/query values are redacted from logged events.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use base64::Engine;
+use serde_json::{json, Map, Value};
+
+use super::{resolve_path_simple, safe_truncate_index, Tool};
+
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+const MAX_TIMEOUT_SECS: u64 = 300;
+/// Response bodies beyond this many bytes are truncated in the tool result.
+const MAX_BODY_BYTES: usize = 100_000;
+const METHODS: [&str; 8] = [
+    "get", "put", "post", "delete", "options", "head", "patch", "trace",
+];
+/// Replacement for secret values in logged arguments and request echoes.
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Header and query parameter names whose values are always treated as secret.
+const SENSITIVE_NAMES: &[&str] = &[
+    "authorization",
+    "proxy-authorization",
+    "cookie",
+    "set-cookie",
+    "x-api-key",
+    "api-key",
+    "apikey",
+    "api_key",
+    "access_token",
+];
+
+fn is_sensitive_name(name: &str) -> bool {
+    let lower = name.to_ascii_lowercase();
+    SENSITIVE_NAMES.contains(&lower.as_str())
+        || lower.contains("token")
+        || lower.contains("secret")
+        || lower.contains("password")
+        || lower.ends_with("-key")
+        || lower.ends_with("_key")
+}
+
+fn is_http_request_tool(name: &str) -> bool {
+    name == "http_request" || name.ends_with("__http_request")
+}
+
+/// Redact credentials from the arguments of an `http_request` tool call.
+///
+/// Applied to every `ToolCall` event before it reaches the event log, so
+/// neither SSE subscribers nor the persisted mission history see auth values.
+pub fn redact_tool_args(tool_name: &str, args: &mut Value) {
+    if !is_http_request_tool(tool_name) {
+        return;
+    }
+    for field in ["headers", "query"] {
+        if let Some(map) = args.get_mut(field).and_then(Value::as_object_mut) {
+            for (name, value) in map.iter_mut() {
+                if is_sensitive_name(name) {
+                    *value = json!(REDACTED);
+                }
+            }
+        }
+    }
+    if let Some(url) = args.get("url").and_then(Value::as_str) {
+        let redacted = redact_url(url);
+        args["url"] = json!(redacted);
+    }
+}
+
+/// Replace sensitive query parameter values in a URL.
+fn redact_url(url: &str) -> String {
+    let Ok(mut parsed) = reqwest::Url::parse(url) else {
+        return url.to_string();
+    };
+    if parsed.query().is_none() {
+        return url.to_string();
+    }
+    let pairs: Vec<(String, String)> = parsed
+        .query_pairs()
+        .map(|(k, v)| {
+            let v = if is_sensitive_name(&k) {
+                REDACTED.to_string()
+            } else {
+                v.into_owned()
+            };
+            (k.into_owned(), v)
+        })
+        .collect();
+    parsed.query_pairs_mut().clear().extend_pairs(pairs);
+    parsed.to_string()
+}
+
+// ---------------------------------------------------------------------------
+// Spec loading and operation lookup
+// ---------------------------------------------------------------------------
+
+/// Parse an OpenAPI document from JSON or YAML text.
+fn parse_spec(text: &str) -> anyhow::Result<Value> {
+    let spec: Value = match serde_json::from_str(text) {
+        Ok(value) => value,
+        Err(_) => serde_yaml::from_str(text)
+            .map_err(|e| anyhow::anyhow!("Spec is neither valid JSON nor YAML: {}", e))?,
+    };
+    if spec.get("swagger").is_some() {
+        anyhow::bail!("Swagger 2.0 specs are not supported; convert the spec to OpenAPI 3");
+    }
+    if !spec.get("openapi").is_some_and(Value::is_string) {
+        anyhow::bail!("Not an OpenAPI document (missing 'openapi' version field)");
+    }
+    Ok(spec)
+}
+
+/// Load a spec from a workspace path or an http(s) URL.
+async fn load_spec(
+    client: &reqwest::Client,
+    source: &str,
+    working_dir: &Path,
+) -> anyhow::Result<(Value, Option<reqwest::Url>)> {
+    if source.starts_with("http://") || source.starts_with("https://") {
+        let url = reqwest::Url::parse(source)?;
+        let response = client.get(url.clone()).send().await?;
+        if !response.status().is_success() {
+            anyhow::bail!(
+                "Failed to fetch spec {}: HTTP {}",
+                source,
+                response.status()
+            );
+        }
+        let text = response.text().await?;
+        Ok((parse_spec(&text)?, Some(url)))
+    } else {
+        let path = resolve_path_simple(source, working_dir);
+        let text = tokio::fs::read_to_string(&path)
+            .await
+            .map_err(|e| anyhow::anyhow!("Failed to read spec {}: {}", path.display(), e))?;
+        Ok((parse_spec(&text)?, None))
+    }
+}
+
+/// Follow local `$ref` pointers (`#/components/...`).
+fn resolve_ref<'a>(spec: &'a Value, mut value: &'a Value) -> &'a Value {
+    for _ in 0..16 {
+        let Some(reference) = value.get("$ref").and_then(Value::as_str) else {
+            break;
+        };
+        match reference
+            .strip_prefix('#')
+            .and_then(|pointer| spec.pointer(pointer))
+        {
+            Some(target) => value = target,
+            None => break,
+        }
+    }
+    value
+}
+
+struct Operation<'a> {
+    method: String,
+    path: String,
+    op: &'a Value,
+    path_item: &'a Value,
+}
+
+fn operations(spec: &Value) -> Vec<Operation<'_>> {
+    let mut ops = Vec::new();
+    let Some(paths) = spec.get("paths").and_then(Value::as_object) else {
+        return ops;
+    };
+    for (path, item) in paths {
+        let item = resolve_ref(spec, item);
+        for method in METHODS {
+            if let Some(op) = item.get(method).filter(|op| op.is_object()) {
+                ops.push(Operation {
+                    method: method.to_string(),
+                    path: path.clone(),
+                    op,
+                    path_item: item,
+                });
+            }
+        }
+    }
+    ops
+}
+
+/// Match a concrete path against a template like `/pets/{id}`, returning the
+/// extracted path parameters.
+fn match_template(template: &str, path: &str) -> Option<BTreeMap<String, String>> {
+    let template: Vec<&str> = template.trim_end_matches('/').split('/').collect();
+    let path: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+    if template.len() != path.len() {
+        return None;
+    }
+    let mut params = BTreeMap::new();
+    for (t, p) in template.iter().zip(path.iter()) {
+        if let Some(name) = t.strip_prefix('{').and_then(|t| t.strip_suffix('}')) {
+            if p.is_empty() {
+                return None;
+            }
+            // An unfilled `{name}` in the caller's path is the template itself.
+            if *p != *t {
+                params.insert(name.to_string(), p.to_string());
+            }
+        } else if t != p {
+            return None;
+        }
+    }
+    Some(params)
+}
+
+/// Find an operation by `operationId` or by method + path. Returns the
+/// operation and any path parameters taken from a concrete path.
+fn find_operation<'a>(
+    spec: &'a Value,
+    operation_id: Option<&str>,
+    method: Option<&str>,
+    path: Option<&str>,
+) -> anyhow::Result<(Operation<'a>, BTreeMap<String, String>)> {
+    let ops = operations(spec);
+    if let Some(id) = operation_id {
+        return ops
+            .into_iter()
+            .find(|op| op.op.get("operationId").and_then(Value::as_str) == Some(id))
+            .map(|op| (op, BTreeMap::new()))
+            .ok_or_else(|| anyhow::anyhow!("No operation with operationId '{}' in spec", id));
+    }
+    let (Some(method), Some(path)) = (method, path) else {
+        anyhow::bail!("Provide 'operation_id', or 'method' and 'path'");
+    };
+    let method = method.to_ascii_lowercase();
+    let mut fallback = None;
+    for op in ops.into_iter().filter(|op| op.method == method) {
+        if op.path == path {
+            return Ok((op, BTreeMap::new()));
+        }
+        if fallback.is_none() {
+            if let Some(params) = match_template(&op.path, path) {
+                fallback = Some((op, params));
+            }
+        }
+    }
+    fallback.ok_or_else(|| {
+        anyhow::anyhow!(
+            "No {} operation matching '{}' in spec",
+            method.to_uppercase(),
+            path
+        )
+    })
+}
+
+/// Merge path-level and operation-level parameters (operation wins).
+fn operation_parameters<'a>(spec: &'a Value, op: &Operation<'a>) -> Vec<&'a Value> {
+    let mut params: Vec<&Value> = Vec::new();
+    for source in [op.path_item, op.op] {
+        let Some(list) = source.get("parameters").and_then(Value::as_array) else {
+            continue;
+        };
+        for param in list {
+            let param = resolve_ref(spec, param);
+            let key = (param.get("name"), param.get("in"));
+            params.retain(|p| (p.get("name"), p.get("in")) != key);
+            params.push(param);
+        }
+    }
+    params
+}
+
+/// Summarize the operations in a spec so the agent can pick one.
+fn list_operations(spec: &Value) -> Value {
+    let ops: Vec<Value> = operations(spec)
+        .iter()
+        .map(|op| {
+            let params: Vec<Value> = operation_parameters(spec, op)
+                .iter()
+                .map(|p| {
+                    json!({
+                        "name": p.get("name"),
+                        "in": p.get("in"),
+                        "required": p.get("required").and_then(Value::as_bool).unwrap_or(false),
+                    })
+                })
+                .collect();
+            json!({
+                "operation_id": op.op.get("operationId"),
+                "method": op.method.to_uppercase(),
+                "path": op.path,
+                "summary": op.op.get("summary"),
+                "parameters": params,
+                "has_body": op.op.get("requestBody").is_some(),
+            })
+        })
+        .collect();
+    json!({
+        "title": spec.pointer("/info/title"),
+        "version": spec.pointer("/info/version"),
+        "servers": spec.get("servers"),
+        "operations": ops,
+    })
+}
+
+/// Resolve the base URL from the spec's first server entry.
+fn server_url(spec: &Value, spec_url: Option<&reqwest::Url>) -> Option<String> {
+    let server = spec.pointer("/servers/0");
+    let mut url = server
+        .and_then(|s| s.get("url"))
+        .and_then(Value::as_str)
+        .unwrap_or("/")
+        .to_string();
+    if let Some(vars) = server
+        .and_then(|s| s.get("variables"))
+        .and_then(Value::as_object)
+    {
+        for (name, var) in vars {
+            if let Some(default) = var.get("default").and_then(Value::as_str) {
+                url = url.replace(&format!("{{{}}}", name), default);
+            }
+        }
+    }
+    if url.starts_with("http://") || url.starts_with("https://") {
+        return Some(url);
+    }
+    spec_url
+        .and_then(|base| base.join(&url).ok())
+        .map(|u| u.to_string())
+}
+
+// ---------------------------------------------------------------------------
+// Validation
+// ---------------------------------------------------------------------------
+
+fn json_type(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn type_matches(expected: &str, value: &Value) -> bool {
+    match expected {
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|f| f.fract() == 0.0)
+        }
+        "number" => value.is_number(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+/// Validate a value against the JSON Schema subset OpenAPI specs commonly use:
+/// `type`, `enum`, `required`, `properties`, `additionalProperties: false`,
+/// `items`, `nullable`, `allOf`/`anyOf`/`oneOf` and local `$ref`s.
+fn validate_value(
+    spec: &Value,
+    schema: &Value,
+    value: &Value,
+    at: &str,
+    errors: &mut Vec<String>,
+    depth: usize,
+) {
+    if depth > 32 {
+        return;
+    }
+    let schema = resolve_ref(spec, schema);
+    if value.is_null() && schema.get("nullable").and_then(Value::as_bool) == Some(true) {
+        return;
+    }
+    if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+        for sub in all {
+            validate_value(spec, sub, value, at, errors, depth + 1);
+        }
+    }
+    for key in ["anyOf", "oneOf"] {
+        if let Some(options) = schema.get(key).and_then(Value::as_array) {
+            let matched = options.iter().any(|sub| {
+                let mut sub_errors = Vec::new();
+                validate_value(spec, sub, value, at, &mut sub_errors, depth + 1);
+                sub_errors.is_empty()
+            });
+            if !matched {
+                errors.push(format!("{}: does not match any allowed schema", at));
+            }
+        }
+    }
+
+    let types: Vec<&str> = match schema.get("type") {
+        Some(Value::String(t)) => vec![t.as_str()],
+        Some(Value::Array(ts)) => ts.iter().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    };
+    if !types.is_empty() && !types.iter().any(|t| type_matches(t, value)) {
+        errors.push(format!(
+            "{}: expected {}, got {}",
+            at,
+            types.join(" or "),
+            json_type(value)
+        ));
+        return;
+    }
+    if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+        if !allowed.contains(value) {
+            errors.push(format!(
+                "{}: {} is not one of {}",
+                at,
+                value,
+                Value::Array(allowed.clone())
+            ));
+        }
+    }
+
+    match value {
+        Value::Object(map) => {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            if let Some(required) = schema.get("required").and_then(Value::as_array) {
+                for name in required.iter().filter_map(Value::as_str) {
+                    if !map.contains_key(name) {
+                        errors.push(format!("{}.{}: required property is missing", at, name));
+                    }
+                }
+            }
+            let closed = schema.get("additionalProperties") == Some(&Value::Bool(false));
+            for (name, item) in map {
+                match properties.and_then(|p| p.get(name)) {
+                    Some(sub) => validate_value(
+                        spec,
+                        sub,
+                        item,
+                        &format!("{}.{}", at, name),
+                        errors,
+                        depth + 1,
+                    ),
+                    None if closed => {
+                        errors.push(format!("{}.{}: property is not allowed", at, name))
+                    }
+                    None => {}
+                }
+            }
+        }
+        Value::Array(items) => {
+            if let Some(item_schema) = schema.get("items") {
+                for (i, item) in items.iter().enumerate() {
+                    validate_value(
+                        spec,
+                        item_schema,
+                        item,
+                        &format!("{}[{}]", at, i),
+                        errors,
+                        depth + 1,
+                    );
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Parameters arrive as JSON but travel as strings, so accept e.g. `"5"` for
+/// an integer parameter by converting it before validation.
+fn coerce_param(spec: &Value, schema: &Value, value: &Value) -> Value {
+    let schema = resolve_ref(spec, schema);
+    let Some(text) = value.as_str() else {
+        return value.clone();
+    };
+    match schema.get("type").and_then(Value::as_str) {
+        Some("integer") => text
+            .parse::<i64>()
+            .map(Value::from)
+            .unwrap_or(value.clone()),
+        Some("number") => text
+            .parse::<f64>()
+            .map(Value::from)
+            .unwrap_or(value.clone()),
+        Some("boolean") => text
+            .parse::<bool>()
+            .map(Value::from)
+            .unwrap_or(value.clone()),
+        _ => value.clone(),
+    }
+}
+
+/// Render a parameter value for a path segment, query string or header.
+fn param_strings(value: &Value) -> Vec<String> {
+    match value {
+        Value::String(s) => vec![s.clone()],
+        Value::Array(items) => items.iter().flat_map(param_strings).collect(),
+        Value::Null => Vec::new(),
+        other => vec![other.to_string()],
+    }
+}
+
+fn encode_path_segment(segment: &str) -> String {
+    let mut out = String::with_capacity(segment.len());
+    for byte in segment.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                out.push(byte as char)
+            }
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+/// Caller-supplied request inputs, grouped by location.
+#[derive(Default)]
+struct RequestInputs {
+    path_params: Map<String, Value>,
+    query: Map<String, Value>,
+    headers: Map<String, Value>,
+    body: Option<Value>,
+}
+
+/// Check the inputs against an operation, returning every problem found.
+fn validate_request(spec: &Value, op: &Operation<'_>, inputs: &RequestInputs) -> Vec<String> {
+    let mut errors = Vec::new();
+    let params = operation_parameters(spec, op);
+
+    for param in &params {
+        let Some(name) = param.get("name").and_then(Value::as_str) else {
+            continue;
+        };
+        let location = param.get("in").and_then(Value::as_str).unwrap_or("query");
+        let supplied = match location {
+            "path" => inputs.path_params.get(name),
+            "query" => inputs.query.get(name),
+            "header" => inputs
+                .headers
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v),
+            _ => continue,
+        };
+        let required =
+            location == "path" || param.get("required").and_then(Value::as_bool) == Some(true);
+        match (supplied, param.get("schema")) {
+            (None, _) if required => {
+                errors.push(format!("{} parameter '{}' is required", location, name))
+            }
+            (Some(value), Some(schema)) => {
+                let value = coerce_param(spec, schema, value);
+                validate_value(
+                    spec,
+                    schema,
+                    &value,
+                    &format!("{} parameter '{}'", location, name),
+                    &mut errors,
+                    0,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    for name in inputs.query.keys() {
+        let known = params.iter().any(|p| {
+            p.get("in").and_then(Value::as_str) == Some("query")
+                && p.get("name").and_then(Value::as_str) == Some(name.as_str())
+        });
+        if !known {
+            errors.push(format!(
+                "query parameter '{}' is not defined for this operation",
+                name
+            ));
+        }
+    }
+
+    let request_body = op.op.get("requestBody").map(|b| resolve_ref(spec, b));
+    match (request_body, &inputs.body) {
+        (None, Some(_)) => errors.push("operation does not accept a request body".to_string()),
+        (Some(rb), None) if rb.get("required").and_then(Value::as_bool) == Some(true) => {
+            errors.push("request body is required".to_string())
+        }
+        (Some(rb), Some(body)) => {
+            if let Some(schema) = json_body_schema(rb) {
+                validate_value(spec, schema, body, "body", &mut errors, 0);
+            }
+        }
+        _ => {}
+    }
+    errors
+}
+
+fn json_body_schema(request_body: &Value) -> Option<&Value> {
+    let content = request_body.get("content")?.as_object()?;
+    content
+        .iter()
+        .find(|(media, _)| media.starts_with("application/json") || media.ends_with("+json"))
+        .and_then(|(_, media)| media.get("schema"))
+}
+
+// ---------------------------------------------------------------------------
+// Authentication
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, PartialEq)]
+enum AuthPlacement {
+    Header(String, String),
+    Query(String, String),
+    Cookie(String, String),
+}
+
+/// Decide how to attach a credential, based on the operation's (or spec's)
+/// security requirement. `scheme_name` picks a specific scheme; otherwise the
+/// first scheme of the first requirement is used. Without a spec, or when
+/// the spec declares no usable scheme, the credential is sent as a bearer token.
+fn auth_placement(
+    spec: Option<&Value>,
+    op: Option<&Value>,
+    scheme_name: Option<&str>,
+    credential: &str,
+) -> anyhow::Result<AuthPlacement> {
+    let bearer = || {
+        AuthPlacement::Header(
+            "Authorization".to_string(),
+            format!("Bearer {}", credential),
+        )
+    };
+    let Some(spec) = spec else {
+        return Ok(bearer());
+    };
+    let schemes = spec.pointer("/components/securitySchemes");
+    let name = match scheme_name {
+        Some(name) => Some(name.to_string()),
+        None => op
+            .and_then(|op| op.get("security"))
+            .or_else(|| spec.get("security"))
+            .and_then(|s| s.get(0))
+            .and_then(Value::as_object)
+            .and_then(|req| req.keys().next().cloned()),
+    };
+    let Some(name) = name else {
+        return Ok(bearer());
+    };
+    let scheme = schemes
+        .and_then(|s| s.get(&name))
+        .map(|s| resolve_ref(spec, s))
+        .ok_or_else(|| anyhow::anyhow!("Security scheme '{}' is not defined in spec", name))?;
+
+    match scheme.get("type").and_then(Value::as_str) {
+        Some("http") => {
+            let kind = scheme
+                .get("scheme")
+                .and_then(Value::as_str)
+                .unwrap_or("bearer")
+                .to_ascii_lowercase();
+            if kind == "basic" {
+                let encoded = base64::engine::general_purpose::STANDARD.encode(credential);
+                Ok(AuthPlacement::Header(
+                    "Authorization".to_string(),
+                    format!("Basic {}", encoded),
+                ))
+            } else {
+                Ok(bearer())
+            }
+        }
+        Some("apiKey") => {
+            let param = scheme
+                .get("name")
+                .and_then(Value::as_str)
+                .ok_or_else(|| anyhow::anyhow!("apiKey scheme '{}' has no 'name'", name))?
+                .to_string();
+            match scheme.get("in").and_then(Value::as_str) {
+                Some("query") => Ok(AuthPlacement::Query(param, credential.to_string())),
+                Some("cookie") => Ok(AuthPlacement::Cookie(param, credential.to_string())),
+                _ => Ok(AuthPlacement::Header(param, credential.to_string())),
+            }
+        }
+        // oauth2 / openIdConnect: the caller supplies an already-issued token.
+        _ => Ok(bearer()),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tool
+// ---------------------------------------------------------------------------
+
+/// Make an HTTP request, optionally described by an OpenAPI spec.
+pub struct HttpRequest;
+
+#[async_trait]
+impl Tool for HttpRequest {
+    fn name(&self) -> &str {
+        "http_request"
+    }
+
+    fn description(&self) -> &str {
+        "Call an HTTP API. With 'spec' (OpenAPI 3 JSON/YAML, workspace path or URL), pass only 'spec' to list operations, or pick one by 'operation_id' (or 'method' + 'path'); parameters and body are validated against the spec before sending and the base URL comes from the spec's servers. Without a spec, pass 'method' and a full 'url'. For authenticated APIs set 'auth_env' to the name of an environment variable holding the credential; it is applied per the spec's security scheme (bearer by default) and is never echoed back."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "spec": {
+                    "type": "string",
+                    "description": "OpenAPI 3 spec: path relative to the workspace or an http(s) URL"
+                },
+                "operation_id": {
+                    "type": "string",
+                    "description": "operationId to call (requires 'spec')"
+                },
+                "method": {
+                    "type": "string",
+                    "description": "HTTP method (GET, POST, ...). Used with 'path' (spec) or 'url' (no spec)"
+                },
+                "path": {
+                    "type": "string",
+                    "description": "Operation path from the spec, either the template ('/pets/{id}') or a concrete path ('/pets/42')"
+                },
+                "url": {
+                    "type": "string",
+                    "description": "Full URL to call when no spec is used"
+                },
+                "base_url": {
+                    "type": "string",
+                    "description": "Override the spec's server URL"
+                },
+                "path_params": {
+                    "type": "object",
+                    "description": "Values for '{name}' placeholders in the path"
+                },
+                "query": {
+                    "type": "object",
+                    "description": "Query parameters"
+                },
+                "headers": {
+                    "type": "object",
+                    "description": "Extra request headers"
+                },
+                "body": {
+                    "description": "Request body. Objects/arrays are sent as JSON; strings are sent as-is"
+                },
+                "auth_env": {
+                    "type": "string",
+                    "description": "Name of the environment variable holding the API credential"
+                },
+                "auth_scheme": {
+                    "type": "string",
+                    "description": "Security scheme name from the spec to use with 'auth_env' (default: the operation's first requirement)"
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "description": "Request timeout in seconds (default: 30, max: 300)"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, args: Value, working_dir: &Path) -> anyhow::Result<String> {
+        let timeout = args["timeout_secs"]
+            .as_u64()
+            .unwrap_or(DEFAULT_TIMEOUT_SECS)
+            .clamp(1, MAX_TIMEOUT_SECS);
+        let client = reqwest::Client::builder()
+            .user_agent("Mozilla/5.0 (compatible; Sandboxed/1.0)")
+            .timeout(Duration::from_secs(timeout))
+            .build()?;
+
+        let object = |key: &str| args[key].as_object().cloned().unwrap_or_default();
+        let mut inputs = RequestInputs {
+            path_params: object("path_params"),
+            query: object("query"),
+            headers: object("headers"),
+            body: args.get("body").filter(|b| !b.is_null()).cloned(),
+        };
+
+        let spec = match args["spec"].as_str() {
+            Some(source) => Some(load_spec(&client, source, working_dir).await?),
+            None => None,
+        };
+
+        let method;
+        let mut url;
+        let mut operation = None;
+        match &spec {
+            Some((spec, spec_url)) => {
+                if args["operation_id"].is_null() && args["method"].is_null() {
+                    return Ok(serde_json::to_string_pretty(&list_operations(spec))?);
+                }
+                let (op, extracted) = find_operation(
+                    spec,
+                    args["operation_id"].as_str(),
+                    args["method"].as_str(),
+                    args["path"].as_str(),
+                )?;
+                for (name, value) in extracted {
+                    inputs.path_params.entry(name).or_insert(json!(value));
+                }
+                let errors = validate_request(spec, &op, &inputs);
+                if !errors.is_empty() {
+                    anyhow::bail!(
+                        "Request does not match the spec for {} {}:\n- {}",
+                        op.method.to_uppercase(),
+                        op.path,
+                        errors.join("\n- ")
+                    );
+                }
+                let base = match args["base_url"].as_str() {
+                    Some(base) => base.to_string(),
+                    None => server_url(spec, spec_url.as_ref()).ok_or_else(|| {
+                        anyhow::anyhow!("Spec has no absolute server URL; pass 'base_url'")
+                    })?,
+                };
+                let mut path = op.path.clone();
+                for (name, value) in &inputs.path_params {
+                    let rendered = param_strings(value).join(",");
+                    path = path.replace(&format!("{{{}}}", name), &encode_path_segment(&rendered));
+                }
+                url = reqwest::Url::parse(&format!("{}{}", base.trim_end_matches('/'), path))?;
+                method = op.method.to_uppercase();
+                operation = Some(op.op);
+            }
+            None => {
+                let raw = args["url"].as_str().ok_or_else(|| {
+                    anyhow::anyhow!("Provide 'url' (or 'spec' to call an OpenAPI operation)")
+                })?;
+                url = reqwest::Url::parse(raw)?;
+                method = args["method"].as_str().unwrap_or("GET").to_uppercase();
+            }
+        }
+
+        for (name, value) in &inputs.query {
+            for rendered in param_strings(value) {
+                url.query_pairs_mut().append_pair(name, &rendered);
+            }
+        }
+
+        let mut headers: Vec<(String, String)> = inputs
+            .headers
+            .iter()
+            .flat_map(|(name, value)| {
+                param_strings(value)
+                    .into_iter()
+                    .map(move |v| (name.clone(), v))
+            })
+            .collect();
+
+        if let Some(env_name) = args["auth_env"].as_str() {
+            let credential = std::env::var(env_name)
+                .map_err(|_| anyhow::anyhow!("Environment variable '{}' is not set", env_name))?;
+            let placement = auth_placement(
+                spec.as_ref().map(|(s, _)| s),
+                operation,
+                args["auth_scheme"].as_str(),
+                &credential,
+            )?;
+            match placement {
+                AuthPlacement::Header(name, value) => headers.push((name, value)),
+                AuthPlacement::Query(name, value) => {
+                    url.query_pairs_mut().append_pair(&name, &value);
+                }
+                AuthPlacement::Cookie(name, value) => {
+                    headers.push(("Cookie".to_string(), format!("{}={}", name, value)))
+                }
+            }
+        }
+
+        let method = reqwest::Method::from_bytes(method.as_bytes())
+            .map_err(|_| anyhow::anyhow!("Invalid HTTP method '{}'", method))?;
+        tracing::info!(method = %method, url = %redact_url(url.as_str()), "http_request");
+
+        let mut request = client.request(method.clone(), url.clone());
+        for (name, value) in &headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        match &inputs.body {
+            Some(Value::String(text)) => request = request.body(text.clone()),
+            Some(body) => request = request.json(body),
+            None => {}
+        }
+
+        let response = request.send().await?;
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("")
+            .to_string();
+        let text = response.text().await?;
+        let body = if content_type.contains("json") {
+            serde_json::from_str(&text).unwrap_or(Value::String(text))
+        } else if text.len() > MAX_BODY_BYTES {
+            let end = safe_truncate_index(&text, MAX_BODY_BYTES);
+            Value::String(format!(
+                "{}\n... [truncated, {} bytes total]",
+                &text[..end],
+                text.len()
+            ))
+        } else {
+            Value::String(text)
+        };
+
+        let echoed_headers: Map<String, Value> = headers
+            .iter()
+            .map(|(name, value)| {
+                let value = if is_sensitive_name(name) {
+                    REDACTED
+                } else {
+                    value
+                };
+                (name.clone(), json!(value))
+            })
+            .collect();
+        let result = json!({
+            "request": {
+                "method": method.as_str(),
+                "url": redact_url(url.as_str()),
+                "headers": echoed_headers,
+            },
+            "status": status.as_u16(),
+            "ok": status.is_success(),
+            "content_type": content_type,
+            "body": body,
+        });
+        Ok(serde_json::to_string_pretty(&result)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPEC: &str = r##"
+openapi: 3.0.3
+info: { title: Pets, version: "1" }
+servers:
+  - url: "{scheme}://127.0.0.1:{port}/v1"
+    variables:
+      scheme: { default: http }
+      port: { default: "8080" }
+components:
+  securitySchemes:
+    key: { type: apiKey, in: header, name: X-Api-Key }
+    token: { type: http, scheme: bearer }
+  schemas:
+    Pet:
+      type: object
+      required: [name]
+      additionalProperties: false
+      properties:
+        name: { type: string }
+        kind: { type: string, enum: [cat, dog] }
+        tags: { type: array, items: { type: string } }
+security:
+  - token: []
+paths:
+  /pets/{id}:
+    parameters:
+      - { name: id, in: path, required: true, schema: { type: integer } }
+    get:
+      operationId: getPet
+      security:
+        - key: []
+      parameters:
+        - { name: verbose, in: query, schema: { type: boolean } }
+    put:
+      operationId: updatePet
+      requestBody:
+        required: true
+        content:
+          application/json:
+            schema: { $ref: "#/components/schemas/Pet" }
+"##;
+
+    fn spec() -> Value {
+        parse_spec(SPEC).unwrap()
+    }
+
+    #[test]
+    fn finds_operations_and_validates_inputs() {
+        let spec = spec();
+        let (op, params) = find_operation(&spec, None, Some("GET"), Some("/pets/42")).unwrap();
+        assert_eq!(op.op["operationId"], "getPet");
+        assert_eq!(params.get("id").map(String::as_str), Some("42"));
+        assert!(find_operation(&spec, Some("deletePet"), None, None).is_err());
+        assert_eq!(
+            server_url(&spec, None).as_deref(),
+            Some("http://127.0.0.1:8080/v1")
+        );
+
+        let mut inputs = RequestInputs::default();
+        inputs.path_params.insert("id".into(), json!("42"));
+        inputs.query.insert("verbose".into(), json!("yes"));
+        inputs.query.insert("page".into(), json!(1));
+        let errors = validate_request(&spec, &op, &inputs);
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors[0].contains("'verbose'"));
+        assert!(errors[1].contains("'page' is not defined"));
+
+        let (op, _) = find_operation(&spec, Some("updatePet"), None, None).unwrap();
+        let mut inputs = RequestInputs::default();
+        let errors = validate_request(&spec, &op, &inputs);
+        assert!(errors.iter().any(|e| e.contains("'id' is required")));
+        assert!(errors.iter().any(|e| e == "request body is required"));
+
+        inputs.path_params.insert("id".into(), json!(1));
+        inputs.body = Some(json!({ "kind": "bird", "tags": ["a", 2], "age": 3 }));
+        let errors = validate_request(&spec, &op, &inputs);
+        assert!(errors.contains(&"body.name: required property is missing".to_string()));
+        assert!(errors.iter().any(|e| e.starts_with("body.kind:")));
+        assert!(errors.contains(&"body.tags[1]: expected string, got integer".to_string()));
+        assert!(errors.contains(&"body.age: property is not allowed".to_string()));
+    }
+
+    #[test]
+    fn auth_follows_security_schemes() {
+        let spec = spec();
+        let (get, _) = find_operation(&spec, Some("getPet"), None, None).unwrap();
+        let (put, _) = find_operation(&spec, Some("updatePet"), None, None).unwrap();
+
+        assert_eq!(
+            auth_placement(Some(&spec), Some(get.op), None, "k").unwrap(),
+            AuthPlacement::Header("X-Api-Key".into(), "k".into())
+        );
+        // Falls back to the spec-level requirement.
+        assert_eq!(
+            auth_placement(Some(&spec), Some(put.op), None, "t").unwrap(),
+            AuthPlacement::Header("Authorization".into(), "Bearer t".into())
+        );
+        assert!(auth_placement(Some(&spec), None, Some("missing"), "t").is_err());
+        assert_eq!(
+            auth_placement(None, None, None, "t").unwrap(),
+            AuthPlacement::Header("Authorization".into(), "Bearer t".into())
+        );
+    }
+
+    #[test]
+    fn redacts_credentials_in_tool_args() {
+        let mut args = json!({
+            "url": "https://api.example.com/x?api_key=secret&page=2",
+            "headers": { "Authorization": "Bearer abc", "Accept": "application/json" },
+            "query": { "access_token": "abc", "q": "pets" },
+        });
+        redact_tool_args("mcp__workspace__http_request", &mut args);
+        assert_eq!(args["headers"]["Authorization"], REDACTED);
+        assert_eq!(args["headers"]["Accept"], "application/json");
+        assert_eq!(args["query"]["access_token"], REDACTED);
+        assert_eq!(args["query"]["q"], "pets");
+        let url = args["url"].as_str().unwrap();
+        assert!(!url.contains("secret") && url.contains("page=2"), "{}", url);
+
+        let mut other = json!({ "headers": { "Authorization": "x" } });
+        redact_tool_args("fetch_url", &mut other);
+        assert_eq!(other["headers"]["Authorization"], "x");
+    }
+
+    #[tokio::test]
+    async fn calls_operation_from_workspace_spec() {
+        use axum::{extract::Path as UrlPath, http::HeaderMap, routing::get, Router};
+
+        let app = Router::new().route(
+            "/v1/pets/:id",
+            get(|UrlPath(id): UrlPath<u32>, headers: HeaderMap| async move {
+                let key = headers
+                    .get("x-api-key")
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or("")
+                    .to_string();
+                axum::Json(json!({ "id": id, "key": key }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let _ = axum::serve(listener, app).await;
+        });
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("openapi.yaml"), SPEC).unwrap();
+        std::env::set_var("HTTP_REQUEST_TEST_KEY", "s3cret");
+
+        let out = HttpRequest
+            .execute(
+                json!({
+                    "spec": "openapi.yaml",
+                    "operation_id": "getPet",
+                    "path_params": { "id": 7 },
+                    "base_url": format!("http://127.0.0.1:{}/v1", port),
+                    "auth_env": "HTTP_REQUEST_TEST_KEY",
+                }),
+                dir.path(),
+            )
+            .await
+            .unwrap();
+        let result: Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(result["status"], 200);
+        assert_eq!(result["body"], json!({ "id": 7, "key": "s3cret" }));
+        assert_eq!(result["request"]["headers"]["X-Api-Key"], REDACTED);
+
+        let listing = HttpRequest
+            .execute(json!({ "spec": "openapi.yaml" }), dir.path())
+            .await
+            .unwrap();
+        assert!(listing.contains("updatePet"));
+    }
+}
@@ -1,17 +1,21 @@
 //! Mission control tool - allows the agent to complete or fail the current mission.
+//!
+//! Statuses listed in `SANDBOXED_SH_CONFIRM_STATUS` (comma-separated, e.g.
+//! `blocked,not_feasible`; none by default) are only requested: the control
+//! session keeps the request and its justification until the user confirms it.
 
 use async_trait::async_trait;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::path::Path;
 use std::sync::Arc;
-use tokio::sync::{mpsc, RwLock};
+use tokio::sync::{mpsc, oneshot, RwLock};
 use uuid::Uuid;
 
 use super::Tool;
 
 /// Command sent by the mission tool to the control session.
-#[derive(Debug, Clone)]
+#[derive(Debug)]
 pub enum MissionControlCommand {
     SetStatus {
         /// The mission ID captured at send time (from the runner's own
@@ -22,10 +26,75 @@ pub enum MissionControlCommand {
         status: MissionStatusValue,
         summary: Option<String>,
     },
+    /// Ask the user to confirm a status change before it is applied.
+    RequestStatus {
+        mission_id: uuid::Uuid,
+        status: MissionStatusValue,
+        justification: StatusJustification,
+    },
+    /// Change the mission's checklist; responds with the updated checklist
+    /// as text.
+    UpdateChecklist {
+        mission_id: uuid::Uuid,
+        changes: ChecklistChanges,
+        respond: oneshot::Sender<Result<String, String>>,
+    },
+}
+
+/// Changes the agent makes to the mission's checklist. Items are named by id
+/// or by their text.
+#[derive(Debug, Clone, Default, Deserialize, PartialEq, Eq)]
+pub struct ChecklistChanges {
+    /// New items
+    #[serde(default)]
+    pub add: Vec<String>,
+    /// Items to mark done
+    #[serde(default)]
+    pub check: Vec<String>,
+    /// Items to mark not done
+    #[serde(default)]
+    pub uncheck: Vec<String>,
+}
+
+/// Why the agent wants to change the mission's status.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct StatusJustification {
+    /// What blocked the agent, why the task isn't feasible, or what was done
+    pub reason: String,
+    /// Kind of blocker (`type_mismatch`, `access_denied`, ...)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocker_type: Option<String>,
+    /// Approaches tried before giving up
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attempted: Vec<String>,
+    /// What would be needed to proceed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub needed: Option<String>,
+}
+
+impl StatusJustification {
+    /// The justification as a mission summary.
+    pub fn summary(&self) -> String {
+        let mut parts = vec![];
+        if !self.reason.is_empty() {
+            parts.push(self.reason.clone());
+        }
+        if let Some(ref blocker_type) = self.blocker_type {
+            parts.push(format!("Blocker type: {}", blocker_type));
+        }
+        if !self.attempted.is_empty() {
+            parts.push(format!("Attempted: {}", self.attempted.join(", ")));
+        }
+        if let Some(ref needed) = self.needed {
+            parts.push(format!("Needed to proceed: {}", needed));
+        }
+        parts.join("\n")
+    }
 }
 
 /// Mission status values (mirrors api::control::MissionStatus but simplified for tool use).
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
 pub enum MissionStatusValue {
     /// Task was fully completed with real deliverables
     Completed,
@@ -48,6 +117,33 @@ impl std::fmt::Display for MissionStatusValue {
     }
 }
 
+impl MissionStatusValue {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "completed" => Some(Self::Completed),
+            "failed" => Some(Self::Failed),
+            "blocked" => Some(Self::Blocked),
+            "not_feasible" => Some(Self::NotFeasible),
+            _ => None,
+        }
+    }
+}
+
+/// Statuses the agent may only request (`SANDBOXED_SH_CONFIRM_STATUS`).
+pub fn statuses_needing_confirmation() -> Vec<MissionStatusValue> {
+    std::env::var("SANDBOXED_SH_CONFIRM_STATUS")
+        .map(|v| parse_status_list(&v))
+        .unwrap_or_default()
+}
+
+/// Parse a comma-separated status list, ignoring unknown names.
+pub fn parse_status_list(value: &str) -> Vec<MissionStatusValue> {
+    value
+        .split(',')
+        .filter_map(MissionStatusValue::parse)
+        .collect()
+}
+
 /// Shared state for mission control, passed to the tool.
 #[derive(Clone)]
 pub struct MissionControl {
@@ -88,6 +184,8 @@ struct CompleteMissionArgs {
     blocker_type: Option<String>,
     /// List of approaches attempted before giving up
     attempted: Option<Vec<String>>,
+    /// What would be needed to proceed (for blocked/not_feasible)
+    needed: Option<String>,
 }
 
 #[async_trait]
@@ -128,6 +226,10 @@ IMPORTANT: Use 'blocked' or 'not_feasible' instead of producing fake/placeholder
                     "type": "array",
                     "items": { "type": "string" },
                     "description": "For blocked/not_feasible: list of approaches you tried before giving up"
+                },
+                "needed": {
+                    "type": "string",
+                    "description": "For blocked/not_feasible: what would be needed to proceed"
                 }
             },
             "required": ["status"]
@@ -138,17 +240,11 @@ IMPORTANT: Use 'blocked' or 'not_feasible' instead of producing fake/placeholder
         let args: CompleteMissionArgs = serde_json::from_value(args)
             .map_err(|e| anyhow::anyhow!("Invalid arguments: {}", e))?;
 
-        let status = match args.status.to_lowercase().as_str() {
-            "completed" => MissionStatusValue::Completed,
-            "failed" => MissionStatusValue::Failed,
-            "blocked" => MissionStatusValue::Blocked,
-            "not_feasible" => MissionStatusValue::NotFeasible,
-            other => {
-                return Err(anyhow::anyhow!(
+        let Some(status) = MissionStatusValue::parse(&args.status) else {
+            return Err(anyhow::anyhow!(
                 "Invalid status '{}'. Must be 'completed', 'failed', 'blocked', or 'not_feasible'.",
-                other
-            ))
-            }
+                args.status
+            ));
         };
 
         let Some(control) = &self.control else {
@@ -205,24 +301,19 @@ IMPORTANT: Use 'blocked' or 'not_feasible' instead of producing fake/placeholder
             }
         }
 
+        let justification = StatusJustification {
+            reason: args.summary.clone().unwrap_or_default(),
+            blocker_type: args.blocker_type,
+            attempted: args.attempted.unwrap_or_default(),
+            needed: args.needed,
+        };
+
         // Build enhanced summary for blocked/not_feasible
         let enhanced_summary = if matches!(
             status,
             MissionStatusValue::Blocked | MissionStatusValue::NotFeasible
         ) {
-            let mut parts = vec![];
-            if let Some(ref summary) = args.summary {
-                parts.push(summary.clone());
-            }
-            if let Some(ref blocker_type) = args.blocker_type {
-                parts.push(format!("Blocker type: {}", blocker_type));
-            }
-            if let Some(ref attempted) = args.attempted {
-                if !attempted.is_empty() {
-                    parts.push(format!("Attempted: {}", attempted.join(", ")));
-                }
-            }
-            Some(parts.join("\n"))
+            Some(justification.summary())
         } else {
             args.summary.clone()
         };
@@ -239,6 +330,22 @@ IMPORTANT: Use 'blocked' or 'not_feasible' instead of producing fake/placeholder
             );
         }
 
+        if statuses_needing_confirmation().contains(&status) {
+            control
+                .cmd_tx
+                .send(MissionControlCommand::RequestStatus {
+                    mission_id,
+                    status,
+                    justification,
+                })
+                .await
+                .map_err(|_| anyhow::anyhow!("Failed to send mission control command"))?;
+            return Ok(format!(
+                "Requested marking the mission as {}. The user has to confirm it before the status changes; stop here and explain the outcome in your reply.",
+                status
+            ));
+        }
+
         // Send the command with the captured mission_id so the handler targets
         // the correct mission even if the user created a new one in the meantime.
         control
@@ -258,3 +365,86 @@ IMPORTANT: Use 'blocked' or 'not_feasible' instead of producing fake/placeholder
         Ok(format!("Mission marked as {}.{}", status, summary_msg))
     }
 }
+
+/// Tool that lets the agent read and check off the mission's checklist.
+pub struct ChecklistUpdate {
+    pub control: Option<MissionControl>,
+}
+
+impl ChecklistUpdate {
+    pub fn new(control: Option<MissionControl>) -> Self {
+        Self { control }
+    }
+}
+
+#[async_trait]
+impl Tool for ChecklistUpdate {
+    fn name(&self) -> &str {
+        "checklist_update"
+    }
+
+    fn description(&self) -> &str {
+        r#"Read and update the mission's checklist of acceptance criteria. The mission's progress is the share of checked items.
+- Call without arguments to see the checklist
+- 'check' an item once it is verifiably done; 'uncheck' it if it regressed
+- 'add' criteria the user asked for that aren't listed yet
+Returns the updated checklist."#
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "add": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Texts of new items"
+                },
+                "check": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Items to mark done, by id or exact text"
+                },
+                "uncheck": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "description": "Items to mark not done, by id or exact text"
+                }
+            }
+        })
+    }
+
+    async fn execute(&self, args: Value, _working_dir: &Path) -> anyhow::Result<String> {
+        let changes: ChecklistChanges = if args.is_null() {
+            ChecklistChanges::default()
+        } else {
+            serde_json::from_value(args).map_err(|e| anyhow::anyhow!("Invalid arguments: {}", e))?
+        };
+
+        let Some(control) = &self.control else {
+            return Ok(
+                "Mission control not available in this context. The checklist was not changed."
+                    .to_string(),
+            );
+        };
+        let Some(mission_id) = *control.current_mission_id.read().await else {
+            return Ok("No active mission. Start a mission first.".to_string());
+        };
+
+        let (respond, response) = oneshot::channel();
+        control
+            .cmd_tx
+            .send(MissionControlCommand::UpdateChecklist {
+                mission_id,
+                changes,
+                respond,
+            })
+            .await
+            .map_err(|_| anyhow::anyhow!("Failed to send mission control command"))?;
+        match response.await {
+            Ok(Ok(checklist)) => Ok(checklist),
+            Ok(Err(e)) => Ok(format!("The checklist was not changed: {}", e)),
+            Err(_) => Err(anyhow::anyhow!("Mission control did not respond")),
+        }
+    }
+}
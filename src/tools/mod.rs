@@ -14,13 +14,21 @@
 
 mod composite;
 pub mod desktop;
+pub mod desktop_macos;
 mod directory;
+pub mod docker;
 mod file_ops;
+pub mod git;
+pub mod http_request;
 mod index;
 pub mod mission;
+pub mod notebook;
+pub mod output_limit;
+pub mod plugin;
 mod search;
 pub mod terminal;
 mod ui;
+pub mod visual_diff;
 mod web;
 
 pub use directory::{ListDirectory, SearchFiles};
@@ -83,10 +91,10 @@ pub fn resolve_path(path_str: &str, workspace: &Path) -> PathResolution {
     };
 
     // Canonicalize for accurate comparison (handles .., symlinks, etc.)
-    let canonical_resolved = resolved.canonicalize().unwrap_or_else(|_| resolved.clone());
-    let canonical_workspace = workspace
-        .canonicalize()
-        .unwrap_or_else(|_| workspace.to_path_buf());
+    let canonical_resolved =
+        crate::util::canonicalize_path(&resolved).unwrap_or_else(|_| resolved.clone());
+    let canonical_workspace =
+        crate::util::canonicalize_path(workspace).unwrap_or_else(|_| workspace.to_path_buf());
 
     let is_outside_workspace = !canonical_resolved.starts_with(&canonical_workspace);
 
@@ -120,12 +128,107 @@ pub fn safe_truncate_index(s: &str, max: usize) -> usize {
 }
 
 // ============================================================================
-// Tool Trait and Registry
+// Timeouts and Streaming Output
 // ============================================================================
 
+use std::time::Duration;
+
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+/// Which output stream a chunk of tool output came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+    /// Interleaved stdout and stderr (when the source does not separate them)
+    Combined,
+}
+
+/// A chunk of output produced while a tool is still running.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToolOutputDelta {
+    pub stream: OutputStream,
+    pub content: String,
+}
+
+/// Receives partial output from a running tool.
+pub type OutputSink = tokio::sync::mpsc::UnboundedSender<ToolOutputDelta>;
+
+/// Timeout requested by the caller via `timeout_ms`, `timeout_secs` or
+/// `timeout` (seconds, float allowed) in the tool arguments.
+pub fn call_timeout(args: &Value) -> Option<Duration> {
+    if let Some(ms) = args.get("timeout_ms").and_then(|v| v.as_u64()) {
+        return Some(Duration::from_millis(ms.max(1)));
+    }
+    if let Some(secs) = args.get("timeout_secs").and_then(|v| v.as_u64()) {
+        return Some(Duration::from_secs(secs.max(1)));
+    }
+    args.get("timeout")
+        .and_then(|v| v.as_f64())
+        .filter(|secs| *secs > 0.0)
+        .map(Duration::from_secs_f64)
+}
+
+/// Timeout configured for `tool_name` in `SANDBOXED_SH_TOOL_TIMEOUTS`, a JSON
+/// object of seconds per tool name, with `"*"` applying to every other tool
+/// (e.g. `{"run_command": 1800, "fetch_url": 30, "*": 300}`).
+pub fn configured_timeout(tool_name: &str) -> Option<Duration> {
+    let raw = std::env::var("SANDBOXED_SH_TOOL_TIMEOUTS").ok()?;
+    let timeouts: HashMap<String, f64> = serde_json::from_str(&raw).ok()?;
+    timeouts
+        .get(tool_name)
+        .or_else(|| timeouts.get("*"))
+        .filter(|secs| **secs > 0.0)
+        .map(|secs| Duration::from_secs_f64(*secs))
+}
+
+/// Run `tool`, streaming partial output to `sink` when given. Unless the tool
+/// enforces its own timeout, the call is cut off after the per-call timeout
+/// or the configured one for the tool (no limit if neither is set). Oversized
+/// results are shrunk by [`output_limit::limit_output`].
+pub async fn execute_tool(
+    tool: &dyn Tool,
+    args: Value,
+    working_dir: &Path,
+    sink: Option<OutputSink>,
+) -> anyhow::Result<String> {
+    let timeout = if tool.manages_timeout() {
+        None
+    } else {
+        call_timeout(&args).or_else(|| configured_timeout(tool.name()))
+    };
+    let run = async {
+        match sink {
+            Some(sink) => tool.execute_streaming(args, working_dir, sink).await,
+            None => tool.execute(args, working_dir).await,
+        }
+    };
+    let output = match timeout {
+        Some(timeout) => tokio::time::timeout(timeout, run).await.map_err(|_| {
+            anyhow::anyhow!(
+                "Tool {} timed out after {} seconds",
+                tool.name(),
+                timeout.as_secs_f64()
+            )
+        })??,
+        None => run.await?,
+    };
+    Ok(output_limit::limit_output(
+        tool.name(),
+        output,
+        working_dir,
+        output_limit::output_limit(),
+    )
+    .await)
+}
+
+// ============================================================================
+// Tool Trait and Registry
+// ============================================================================
+
 /// Information about a tool for display purposes.
 #[derive(Debug, Clone)]
 pub struct ToolInfo {
@@ -150,6 +253,23 @@ pub trait Tool: Send + Sync {
     /// The `working_dir` is the default directory for relative paths.
     /// Tools can accept absolute paths to operate anywhere on the system.
     async fn execute(&self, args: Value, working_dir: &Path) -> anyhow::Result<String>;
+
+    /// Execute the tool, sending output to `sink` as it is produced. Tools
+    /// without incremental output just run [`Tool::execute`].
+    async fn execute_streaming(
+        &self,
+        args: Value,
+        working_dir: &Path,
+        _sink: OutputSink,
+    ) -> anyhow::Result<String> {
+        self.execute(args, working_dir).await
+    }
+
+    /// Whether the tool applies timeouts itself (returning partial output
+    /// when they fire) rather than being cut off by [`execute_tool`].
+    fn manages_timeout(&self) -> bool {
+        false
+    }
 }
 
 /// Registry of available tools.
@@ -201,9 +321,36 @@ impl ToolRegistry {
         // Search
         tools.insert("grep_search".to_string(), Arc::new(search::GrepSearch));
 
+        // Git (mission branches and commits, with protected-branch guardrails)
+        tools.insert("git_create_branch".to_string(), Arc::new(git::CreateBranch));
+        tools.insert("git_commit".to_string(), Arc::new(git::Commit));
+        tools.insert("git_push".to_string(), Arc::new(git::Push));
+
         // Web (fetch only; web search removed in favor of OMO/Exa)
         tools.insert("fetch_url".to_string(), Arc::new(web::FetchUrl));
 
+        // Docker (images, capped containers and compose stacks per mission)
+        tools.insert("docker".to_string(), Arc::new(docker::Docker));
+
+        // HTTP APIs (OpenAPI-validated requests with credential redaction)
+        tools.insert(
+            "http_request".to_string(),
+            Arc::new(http_request::HttpRequest),
+        );
+
+        // Jupyter notebooks (cell execution in persistent kernels)
+        tools.insert(
+            "notebook_execute".to_string(),
+            Arc::new(notebook::ExecuteNotebook),
+        );
+        tools.insert(
+            "notebook_kernel".to_string(),
+            Arc::new(notebook::NotebookKernel),
+        );
+
+        // Visual regression checks (headless browser or desktop screenshots)
+        tools.insert("visual_diff".to_string(), Arc::new(visual_diff::VisualDiff));
+
         // Frontend Tool UI (schemas for rich rendering in the dashboard)
         tools.insert("ui_optionList".to_string(), Arc::new(ui::UiOptionList));
         tools.insert("ui_dataTable".to_string(), Arc::new(ui::UiDataTable));
@@ -248,13 +395,26 @@ impl ToolRegistry {
             );
         }
 
-        // Mission control (allows agent to complete/fail missions)
+        // Mission control (allows agent to complete/fail missions and check off
+        // the mission's checklist)
+        tools.insert(
+            "checklist_update".to_string(),
+            Arc::new(mission::ChecklistUpdate::new(mission_control.clone())),
+        );
         let mission_tool: Arc<dyn Tool> = match mission_control {
             Some(ctrl) => Arc::new(mission::CompleteMission::with_control(ctrl)),
             None => Arc::new(mission::CompleteMission::new()),
         };
         tools.insert("complete_mission".to_string(), mission_tool);
 
+        // WASM tool plugins (never shadow built-in tools)
+        if let Some(dir) = plugin::plugins_dir() {
+            let loaded = plugin::register_plugins(&mut tools, &dir);
+            if !loaded.is_empty() {
+                tracing::info!("Registry {} loaded tool plugins: {:?}", registry_id, loaded);
+            }
+        }
+
         tracing::info!(
             "Registry {} complete with {} total tools",
             registry_id,
@@ -279,6 +439,11 @@ impl ToolRegistry {
         self.tools.contains_key(name)
     }
 
+    /// Look up a tool by name.
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Tool>> {
+        self.tools.get(name).cloned()
+    }
+
     /// Execute a tool by name.
     ///
     /// The `working_dir` is the default directory for relative paths.
@@ -288,13 +453,24 @@ impl ToolRegistry {
         name: &str,
         args: Value,
         working_dir: &Path,
+    ) -> anyhow::Result<String> {
+        self.execute_streaming(name, args, working_dir, None).await
+    }
+
+    /// Execute a tool by name, streaming partial output to `sink` if given.
+    pub async fn execute_streaming(
+        &self,
+        name: &str,
+        args: Value,
+        working_dir: &Path,
+        sink: Option<OutputSink>,
     ) -> anyhow::Result<String> {
         let tool = self
             .tools
             .get(name)
             .ok_or_else(|| anyhow::anyhow!("Unknown tool: {}", name))?;
 
-        tool.execute(args, working_dir).await
+        execute_tool(tool.as_ref(), args, working_dir, sink).await
     }
 }
 
@@ -0,0 +1,626 @@
+//! Jupyter notebook execution.
+//!
+//! `NotebookEdit` can change `.ipynb` cells; these tools run them. Each
+//! notebook gets its own kernel, started on first use in the notebook's
+//! directory and kept alive between calls so state carries over like in
+//! Jupyter. Outputs are written back into the notebook and returned as text.
+//!
+//! Kernels are driven by a small Python helper speaking JSON lines over
+//! stdin/stdout on top of `jupyter_client`, so the host (or container) needs
+//! `jupyter_client` and a kernel such as `ipykernel`. The interpreter is
+//! `SANDBOXED_SH_JUPYTER_PYTHON` (default `python3`).
+//!
+//! Kernels belong to the mission workspace they were started from and are
+//! shut down when the mission ends ([`shutdown_mission_kernels`]). The helper
+//! also shuts its kernel down when its stdin closes, which covers MCP server
+//! processes exiting with their CLI.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, OnceLock};
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, Lines};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::Mutex;
+use uuid::Uuid;
+
+use super::{resolve_path_simple, Tool};
+
+/// Per-cell execution timeout when none is given.
+const DEFAULT_CELL_TIMEOUT_SECS: u64 = 120;
+
+/// How long a kernel may take to start.
+const KERNEL_START_TIMEOUT_SECS: u64 = 90;
+
+const KERNEL_HELPER: &str = r#"
+import json, queue, sys
+
+def reply(payload):
+    sys.stdout.write(json.dumps(payload) + "\n")
+    sys.stdout.flush()
+
+try:
+    from jupyter_client.manager import KernelManager
+except ImportError as e:
+    reply({"error": "jupyter_client is not installed (pip install jupyter_client ipykernel): %s" % e})
+    sys.exit(1)
+
+try:
+    km = KernelManager(kernel_name=sys.argv[1])
+    km.start_kernel(cwd=sys.argv[2])
+    kc = km.client()
+    kc.start_channels()
+    kc.wait_for_ready(timeout=60)
+except Exception as e:
+    reply({"error": "failed to start kernel '%s': %s" % (sys.argv[1], e)})
+    sys.exit(1)
+reply({"ready": True})
+
+for line in sys.stdin:
+    try:
+        req = json.loads(line)
+    except ValueError:
+        continue
+    op = req.get("op")
+    if op == "execute":
+        outputs = []
+        def hook(msg):
+            kind, content = msg["msg_type"], msg["content"]
+            if kind == "stream":
+                outputs.append({"output_type": "stream", "name": content["name"], "text": content["text"]})
+            elif kind in ("execute_result", "display_data"):
+                out = {"output_type": kind, "data": content["data"], "metadata": content.get("metadata", {})}
+                if kind == "execute_result":
+                    out["execution_count"] = content.get("execution_count")
+                outputs.append(out)
+            elif kind == "error":
+                outputs.append({"output_type": "error", "ename": content["ename"],
+                                "evalue": content["evalue"], "traceback": content["traceback"]})
+            elif kind == "clear_output":
+                del outputs[:]
+        try:
+            result = kc.execute_interactive(req["code"], timeout=req.get("timeout"),
+                                            output_hook=hook, allow_stdin=False)
+            reply({"status": result["content"]["status"],
+                   "execution_count": result["content"].get("execution_count"),
+                   "outputs": outputs})
+        except (TimeoutError, queue.Empty):
+            km.interrupt_kernel()
+            reply({"status": "timeout", "execution_count": None, "outputs": outputs})
+    elif op == "restart":
+        km.restart_kernel()
+        kc.wait_for_ready(timeout=60)
+        reply({"status": "ok"})
+    elif op == "shutdown":
+        break
+
+kc.stop_channels()
+km.shutdown_kernel(now=True)
+"#;
+
+/// The helper process driving a running kernel.
+struct Kernel {
+    child: Child,
+    stdin: ChildStdin,
+    stdout: Lines<BufReader<ChildStdout>>,
+}
+
+impl Kernel {
+    async fn start(kernel_name: &str, cwd: &Path) -> anyhow::Result<Self> {
+        let python = std::env::var("SANDBOXED_SH_JUPYTER_PYTHON")
+            .ok()
+            .filter(|python| !python.trim().is_empty())
+            .unwrap_or_else(|| "python3".to_string());
+        let mut child = Command::new(&python)
+            .arg("-c")
+            .arg(KERNEL_HELPER)
+            .arg(kernel_name)
+            .arg(cwd)
+            .current_dir(cwd)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("Failed to start {}: {}", python, e))?;
+
+        let stdin = child.stdin.take().expect("piped stdin");
+        let stdout = BufReader::new(child.stdout.take().expect("piped stdout")).lines();
+        let mut kernel = Self {
+            child,
+            stdin,
+            stdout,
+        };
+        kernel.read_reply(KERNEL_START_TIMEOUT_SECS).await?;
+        Ok(kernel)
+    }
+
+    /// Next JSON reply from the helper. Non-JSON lines (stray prints from
+    /// kernel machinery) are skipped.
+    async fn read_reply(&mut self, timeout_secs: u64) -> anyhow::Result<Value> {
+        let read = async {
+            while let Some(line) = self.stdout.next_line().await? {
+                let Ok(reply) = serde_json::from_str::<Value>(&line) else {
+                    continue;
+                };
+                if let Some(error) = reply["error"].as_str() {
+                    return Err(anyhow::anyhow!("{}", error));
+                }
+                return Ok(reply);
+            }
+            Err(anyhow::anyhow!("Kernel helper exited"))
+        };
+        tokio::time::timeout(std::time::Duration::from_secs(timeout_secs), read)
+            .await
+            .map_err(|_| anyhow::anyhow!("Kernel did not respond within {}s", timeout_secs))?
+    }
+
+    async fn request(&mut self, request: Value, timeout_secs: u64) -> anyhow::Result<Value> {
+        let mut line = request.to_string();
+        line.push('\n');
+        self.stdin.write_all(line.as_bytes()).await?;
+        self.stdin.flush().await?;
+        self.read_reply(timeout_secs).await
+    }
+
+    async fn shutdown(mut self) {
+        let _ = self.stdin.write_all(b"{\"op\": \"shutdown\"}\n").await;
+        drop(self.stdin);
+        if tokio::time::timeout(std::time::Duration::from_secs(10), self.child.wait())
+            .await
+            .is_err()
+        {
+            let _ = self.child.kill().await;
+        }
+    }
+}
+
+/// A notebook's kernel and the mission workspace it was started from.
+struct KernelEntry {
+    kernel_name: String,
+    working_dir: PathBuf,
+    kernel: Arc<Mutex<Kernel>>,
+}
+
+type KernelMap = HashMap<PathBuf, KernelEntry>;
+
+/// Running kernels keyed by notebook path.
+fn kernels() -> &'static Mutex<KernelMap> {
+    static KERNELS: OnceLock<Mutex<KernelMap>> = OnceLock::new();
+    KERNELS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// The notebook's kernel (and its name), started on first use in the
+/// notebook's directory.
+async fn kernel_for(
+    notebook: &Path,
+    kernel_name: &str,
+    working_dir: &Path,
+) -> anyhow::Result<(Arc<Mutex<Kernel>>, String)> {
+    let mut map = kernels().lock().await;
+    if let Some(entry) = map.get(notebook) {
+        return Ok((entry.kernel.clone(), entry.kernel_name.clone()));
+    }
+    let cwd = notebook.parent().unwrap_or(working_dir);
+    tracing::info!(notebook = %notebook.display(), kernel = %kernel_name, "Starting notebook kernel");
+    let kernel = Arc::new(Mutex::new(Kernel::start(kernel_name, cwd).await?));
+    map.insert(
+        notebook.to_path_buf(),
+        KernelEntry {
+            kernel_name: kernel_name.to_string(),
+            working_dir: working_dir.to_path_buf(),
+            kernel: kernel.clone(),
+        },
+    );
+    Ok((kernel, kernel_name.to_string()))
+}
+
+/// Shut down the kernels matching `filter` and return their notebooks.
+async fn remove_kernels(filter: impl Fn(&Path, &KernelEntry) -> bool) -> Vec<PathBuf> {
+    let removed: Vec<(PathBuf, KernelEntry)> = {
+        let mut map = kernels().lock().await;
+        let notebooks: Vec<PathBuf> = map
+            .iter()
+            .filter(|(notebook, entry)| filter(notebook, entry))
+            .map(|(notebook, _)| notebook.clone())
+            .collect();
+        notebooks
+            .into_iter()
+            .filter_map(|notebook| map.remove_entry(&notebook))
+            .collect()
+    };
+
+    let mut paths = Vec::new();
+    for (notebook, entry) in removed {
+        match Arc::try_unwrap(entry.kernel) {
+            Ok(kernel) => kernel.into_inner().shutdown().await,
+            // Still executing a cell: the request in flight holds the last
+            // reference and the helper exits once its stdin is dropped.
+            Err(_) => tracing::debug!(notebook = %notebook.display(), "Kernel busy at shutdown"),
+        }
+        paths.push(notebook);
+    }
+    paths
+}
+
+/// Whether `dir` is inside the workspace directory of `mission_id`.
+fn in_mission_dir(dir: &Path, mission_id: Uuid) -> bool {
+    let mission_dir = crate::workspace::mission_workspace_dir_for_root(Path::new(""), mission_id);
+    let Some(name) = mission_dir.file_name() else {
+        return false;
+    };
+    dir.components()
+        .any(|component| component.as_os_str() == name)
+}
+
+/// Shut down the kernels started from a mission's workspace.
+pub async fn shutdown_mission_kernels(mission_id: Uuid) {
+    let stopped = remove_kernels(|_, entry| in_mission_dir(&entry.working_dir, mission_id)).await;
+    if !stopped.is_empty() {
+        tracing::info!(mission_id = %mission_id, count = stopped.len(), "Shut down notebook kernels");
+    }
+}
+
+/// Cell source, which nbformat stores as a string or a list of lines.
+fn cell_source(cell: &Value) -> String {
+    match &cell["source"] {
+        Value::String(source) => source.clone(),
+        Value::Array(lines) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+/// Indices of the code cells to run: `requested` (validated) or all of them.
+fn select_cells(cells: &[Value], requested: Option<&Vec<Value>>) -> anyhow::Result<Vec<usize>> {
+    let Some(requested) = requested else {
+        return Ok(cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| cell["cell_type"] == "code")
+            .map(|(index, _)| index)
+            .collect());
+    };
+    requested
+        .iter()
+        .map(|value| {
+            let index = value
+                .as_u64()
+                .ok_or_else(|| anyhow::anyhow!("Cell indices must be non-negative integers"))?
+                as usize;
+            match cells.get(index) {
+                Some(cell) if cell["cell_type"] == "code" => Ok(index),
+                Some(_) => Err(anyhow::anyhow!("Cell {} is not a code cell", index)),
+                None => Err(anyhow::anyhow!(
+                    "Cell {} does not exist (notebook has {} cells)",
+                    index,
+                    cells.len()
+                )),
+            }
+        })
+        .collect()
+}
+
+/// Text rendering of cell outputs for the model. Rich outputs other than
+/// plain text are summarised by MIME type.
+fn render_outputs(outputs: &[Value]) -> String {
+    let mut text = String::new();
+    for output in outputs {
+        match output["output_type"].as_str() {
+            Some("stream") => text.push_str(output["text"].as_str().unwrap_or("")),
+            Some("execute_result") | Some("display_data") => {
+                let data = &output["data"];
+                if let Some(plain) = data["text/plain"].as_str() {
+                    text.push_str(plain);
+                    text.push('\n');
+                }
+                if let Some(data) = data.as_object() {
+                    for mime in data.keys().filter(|mime| *mime != "text/plain") {
+                        text.push_str(&format!("[{} output]\n", mime));
+                    }
+                }
+            }
+            Some("error") => {
+                let traceback = output["traceback"].as_array().cloned().unwrap_or_default();
+                for line in &traceback {
+                    let line = line.as_str().unwrap_or("");
+                    text.push_str(&crate::api::mission_runner::strip_ansi_codes(line));
+                    text.push('\n');
+                }
+                if traceback.is_empty() {
+                    text.push_str(&format!(
+                        "{}: {}\n",
+                        output["ename"].as_str().unwrap_or("Error"),
+                        output["evalue"].as_str().unwrap_or("")
+                    ));
+                }
+            }
+            _ => {}
+        }
+    }
+    text
+}
+
+/// Serialize a notebook the way Jupyter does (one-space indent).
+fn write_notebook(path: &Path, notebook: &Value) -> anyhow::Result<()> {
+    let mut bytes = Vec::new();
+    let formatter = serde_json::ser::PrettyFormatter::with_indent(b" ");
+    let mut serializer = serde_json::Serializer::with_formatter(&mut bytes, formatter);
+    serde::Serialize::serialize(notebook, &mut serializer)?;
+    bytes.push(b'\n');
+    std::fs::write(path, bytes)?;
+    Ok(())
+}
+
+/// Execute notebook cells in a persistent Jupyter kernel.
+pub struct ExecuteNotebook;
+
+#[async_trait]
+impl Tool for ExecuteNotebook {
+    fn name(&self) -> &str {
+        "notebook_execute"
+    }
+
+    fn description(&self) -> &str {
+        "Execute code cells of a Jupyter notebook (.ipynb) in a kernel that stays alive between calls, like a running Jupyter session. Outputs are saved into the notebook and returned as text. Use after editing cells to check they run; use notebook_kernel to restart or stop the kernel."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "notebook_path": {
+                    "type": "string",
+                    "description": "Path to the .ipynb file"
+                },
+                "cells": {
+                    "type": "array",
+                    "items": { "type": "integer" },
+                    "description": "0-based indices of the cells to run, in order (default: every code cell)"
+                },
+                "timeout_secs": {
+                    "type": "integer",
+                    "description": "Per-cell timeout in seconds; a cell that runs longer is interrupted (default: 120)"
+                },
+                "stop_on_error": {
+                    "type": "boolean",
+                    "description": "Stop at the first cell that raises (default: true)"
+                },
+                "kernel": {
+                    "type": "string",
+                    "description": "Kernel name (default: the notebook's kernelspec, else 'python3'). Only used when the kernel is started."
+                }
+            },
+            "required": ["notebook_path"]
+        })
+    }
+
+    async fn execute(&self, args: Value, working_dir: &Path) -> anyhow::Result<String> {
+        let path = args["notebook_path"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing 'notebook_path' argument"))?;
+        let path = resolve_path_simple(path, working_dir);
+        let path = crate::util::canonicalize_path(&path)
+            .map_err(|e| anyhow::anyhow!("Cannot open {}: {}", path.display(), e))?;
+
+        let mut notebook: Value = serde_json::from_str(&std::fs::read_to_string(&path)?)
+            .map_err(|e| anyhow::anyhow!("{} is not a valid notebook: {}", path.display(), e))?;
+        let kernel_name = args["kernel"]
+            .as_str()
+            .or_else(|| notebook["metadata"]["kernelspec"]["name"].as_str())
+            .unwrap_or("python3")
+            .to_string();
+        let cells = notebook["cells"]
+            .as_array()
+            .ok_or_else(|| anyhow::anyhow!("{} has no cells", path.display()))?;
+        let selected = select_cells(cells, args["cells"].as_array())?;
+        let timeout_secs = args["timeout_secs"]
+            .as_u64()
+            .unwrap_or(DEFAULT_CELL_TIMEOUT_SECS);
+        let stop_on_error = args["stop_on_error"].as_bool().unwrap_or(true);
+
+        let (kernel, kernel_name) = kernel_for(&path, &kernel_name, working_dir).await?;
+        let mut kernel = kernel.lock().await;
+
+        let mut report = String::new();
+        let mut failed = false;
+        for index in selected {
+            let code = cell_source(&notebook["cells"][index]);
+            let reply = kernel
+                .request(
+                    json!({ "op": "execute", "code": code, "timeout": timeout_secs }),
+                    timeout_secs + 30,
+                )
+                .await?;
+
+            let status = reply["status"].as_str().unwrap_or("error").to_string();
+            let outputs = reply["outputs"].as_array().cloned().unwrap_or_default();
+            let cell = &mut notebook["cells"][index];
+            cell["execution_count"] = reply["execution_count"].clone();
+            cell["outputs"] = Value::Array(outputs.clone());
+
+            report.push_str(&format!("--- cell {} [{}] ---\n", index, status));
+            report.push_str(&render_outputs(&outputs));
+            if !report.ends_with('\n') {
+                report.push('\n');
+            }
+            if status != "ok" {
+                failed = true;
+                if stop_on_error {
+                    report.push_str("Stopped at the first failing cell.\n");
+                    break;
+                }
+            }
+        }
+
+        write_notebook(&path, &notebook)?;
+        report.push_str(&format!(
+            "{} (kernel {}, outputs saved to {})",
+            if failed {
+                "Finished with errors"
+            } else {
+                "All cells succeeded"
+            },
+            kernel_name,
+            path.display()
+        ));
+        Ok(report)
+    }
+}
+
+/// Inspect, restart or stop notebook kernels.
+pub struct NotebookKernel;
+
+#[async_trait]
+impl Tool for NotebookKernel {
+    fn name(&self) -> &str {
+        "notebook_kernel"
+    }
+
+    fn description(&self) -> &str {
+        "Manage the Jupyter kernels started by notebook_execute: 'status' lists running kernels, 'restart' clears a notebook's kernel state, 'shutdown' stops it."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "action": {
+                    "type": "string",
+                    "enum": ["status", "restart", "shutdown"],
+                    "description": "What to do"
+                },
+                "notebook_path": {
+                    "type": "string",
+                    "description": "Notebook whose kernel to restart or shut down (required for those actions)"
+                }
+            },
+            "required": ["action"]
+        })
+    }
+
+    async fn execute(&self, args: Value, working_dir: &Path) -> anyhow::Result<String> {
+        let action = args["action"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing 'action' argument"))?;
+
+        if action == "status" {
+            let map = kernels().lock().await;
+            let running: Vec<Value> = map
+                .iter()
+                .map(|(notebook, entry)| {
+                    json!({
+                        "notebook": notebook,
+                        "kernel": entry.kernel_name,
+                        "busy": entry.kernel.try_lock().is_err(),
+                    })
+                })
+                .collect();
+            return Ok(json!({ "kernels": running }).to_string());
+        }
+
+        let path = args["notebook_path"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("'notebook_path' is required for {}", action))?;
+        let path = resolve_path_simple(path, working_dir);
+        let path = crate::util::canonicalize_path(&path).unwrap_or(path);
+
+        match action {
+            "restart" => {
+                let kernel = kernels()
+                    .lock()
+                    .await
+                    .get(&path)
+                    .map(|entry| entry.kernel.clone())
+                    .ok_or_else(|| anyhow::anyhow!("No kernel running for {}", path.display()))?;
+                kernel
+                    .lock()
+                    .await
+                    .request(json!({ "op": "restart" }), KERNEL_START_TIMEOUT_SECS)
+                    .await?;
+                Ok(format!("Restarted the kernel for {}", path.display()))
+            }
+            "shutdown" => {
+                let stopped = remove_kernels(|notebook, _| notebook == path).await;
+                if stopped.is_empty() {
+                    Ok(format!("No kernel running for {}", path.display()))
+                } else {
+                    Ok(format!("Shut down the kernel for {}", path.display()))
+                }
+            }
+            other => Err(anyhow::anyhow!("Unknown action: {}", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn notebook() -> Value {
+        json!({
+            "cells": [
+                { "cell_type": "markdown", "source": ["# Title"] },
+                { "cell_type": "code", "source": ["import math\n", "math.pi"], "outputs": [] },
+                { "cell_type": "code", "source": "print('hi')", "outputs": [] }
+            ],
+            "metadata": { "kernelspec": { "name": "python3" } },
+            "nbformat": 4,
+            "nbformat_minor": 5
+        })
+    }
+
+    #[test]
+    fn code_cells_are_selected_and_sources_joined() {
+        let nb = notebook();
+        let cells = nb["cells"].as_array().unwrap();
+        assert_eq!(select_cells(cells, None).unwrap(), vec![1, 2]);
+        assert_eq!(select_cells(cells, Some(&vec![json!(2)])).unwrap(), vec![2]);
+        assert!(select_cells(cells, Some(&vec![json!(0)])).is_err());
+        assert!(select_cells(cells, Some(&vec![json!(9)])).is_err());
+        assert_eq!(cell_source(&cells[1]), "import math\nmath.pi");
+        assert_eq!(cell_source(&cells[2]), "print('hi')");
+    }
+
+    #[test]
+    fn outputs_render_as_text() {
+        let outputs = vec![
+            json!({ "output_type": "stream", "name": "stdout", "text": "hi\n" }),
+            json!({
+                "output_type": "execute_result",
+                "data": { "text/plain": "3.14", "image/png": "iVBOR" },
+                "execution_count": 1
+            }),
+            json!({
+                "output_type": "error",
+                "ename": "ZeroDivisionError",
+                "evalue": "division by zero",
+                "traceback": ["\u{1b}[0;31mZeroDivisionError\u{1b}[0m: division by zero"]
+            }),
+        ];
+        assert_eq!(
+            render_outputs(&outputs),
+            "hi\n3.14\n[image/png output]\nZeroDivisionError: division by zero\n"
+        );
+    }
+
+    #[test]
+    fn notebooks_are_written_with_jupyter_indent() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("nb.ipynb");
+        write_notebook(&path, &json!({ "cells": [] })).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "{\n \"cells\": []\n}\n"
+        );
+    }
+
+    #[test]
+    fn kernels_are_matched_to_mission_workspaces() {
+        let mission_id = Uuid::new_v4();
+        let dir = crate::workspace::mission_workspace_dir(Path::new("/srv/ws"), mission_id);
+        assert!(in_mission_dir(&dir.join("notebooks"), mission_id));
+        assert!(!in_mission_dir(&dir, Uuid::new_v4()));
+    }
+}
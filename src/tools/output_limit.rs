@@ -0,0 +1,254 @@
+//! Size management for tool results.
+//!
+//! A result larger than [`output_limit`] bytes would flood the model's context
+//! (a `cat` of a multi-megabyte log, a large API response). Instead of the
+//! whole output the model gets its head and tail, the lines in between that
+//! mention terms from the user's current request, and a note pointing at the
+//! full output, which is saved under `.sandboxed-sh/tool-outputs/` in the
+//! working directory so it can be read in ranges or grepped.
+//!
+//! The current request is read from [`LAST_REQUEST_FILE`], which the mission
+//! runner writes into the mission directory at the start of every turn.
+
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Default result size limit in bytes (`SANDBOXED_SH_TOOL_OUTPUT_LIMIT`).
+pub const DEFAULT_OUTPUT_LIMIT: usize = 30_000;
+
+/// The user message of the running turn, relative to the working directory.
+pub const LAST_REQUEST_FILE: &str = ".sandboxed-sh/last_request.txt";
+
+/// Where full outputs are saved, relative to the working directory.
+const ARTIFACT_DIR: &str = ".sandboxed-sh/tool-outputs";
+
+/// Most matching lines quoted from the omitted middle of an output.
+const MAX_RELEVANT_LINES: usize = 40;
+
+/// Request words too common to pick out relevant lines.
+const STOPWORDS: &[&str] = &[
+    "about", "after", "also", "been", "before", "could", "does", "from", "have", "into", "just",
+    "make", "more", "only", "please", "should", "some", "than", "that", "their", "them", "then",
+    "there", "these", "they", "this", "what", "when", "where", "which", "while", "will", "with",
+    "would", "your",
+];
+
+/// Result size limit in bytes. `SANDBOXED_SH_TOOL_OUTPUT_LIMIT=0` disables it.
+pub fn output_limit() -> usize {
+    match std::env::var("SANDBOXED_SH_TOOL_OUTPUT_LIMIT")
+        .ok()
+        .and_then(|raw| raw.trim().parse::<usize>().ok())
+    {
+        Some(0) => usize::MAX,
+        Some(limit) => limit,
+        None => DEFAULT_OUTPUT_LIMIT,
+    }
+}
+
+/// Record the user message of a starting turn for [`limit_output`].
+pub async fn write_last_request(working_dir: &Path, message: &str) -> std::io::Result<()> {
+    let path = working_dir.join(LAST_REQUEST_FILE);
+    if let Some(parent) = path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(path, message).await
+}
+
+/// Distinctive words of `request` (lowercased), used to find relevant lines.
+fn focus_terms(request: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    request
+        .split(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '.' | '/')))
+        .map(|word| word.trim_matches(|c: char| matches!(c, '-' | '.' | '/')))
+        .filter(|word| word.chars().count() >= 4)
+        .map(str::to_lowercase)
+        .filter(|word| !STOPWORDS.contains(&word.as_str()))
+        .filter(|word| seen.insert(word.clone()))
+        .take(12)
+        .collect()
+}
+
+/// `output` cut down to about `limit` bytes: the head and tail plus up to
+/// [`MAX_RELEVANT_LINES`] lines from the middle containing any of `terms`.
+pub fn shrink_output(output: &str, limit: usize, terms: &[String]) -> String {
+    let lines: Vec<&str> = output.lines().collect();
+    let head_budget = limit * 2 / 5;
+    let tail_budget = limit * 2 / 5;
+    let relevant_budget = limit - head_budget - tail_budget;
+
+    let mut head_end = 0;
+    let mut used = 0;
+    while head_end < lines.len() && used + lines[head_end].len() < head_budget {
+        used += lines[head_end].len() + 1;
+        head_end += 1;
+    }
+    let mut tail_start = lines.len();
+    used = 0;
+    while tail_start > head_end && used + lines[tail_start - 1].len() < tail_budget {
+        used += lines[tail_start - 1].len() + 1;
+        tail_start -= 1;
+    }
+
+    let mut text = String::new();
+    if head_end == 0 && tail_start == lines.len() {
+        // A few huge lines (minified JSON, base64): fall back to bytes.
+        let head = super::safe_truncate_index(output, head_budget);
+        text.push_str(&output[..head]);
+        text.push_str(&format!(
+            "\n... [{} bytes omitted] ...\n",
+            output.len() - head
+        ));
+        return text;
+    }
+
+    for line in &lines[..head_end] {
+        text.push_str(line);
+        text.push('\n');
+    }
+    let omitted = tail_start - head_end;
+    text.push_str(&format!("... [{} lines omitted] ...\n", omitted));
+
+    if !terms.is_empty() {
+        let mut relevant = Vec::new();
+        used = 0;
+        for (index, line) in lines[head_end..tail_start].iter().enumerate() {
+            let lower = line.to_lowercase();
+            if !terms.iter().any(|term| lower.contains(term.as_str())) {
+                continue;
+            }
+            let quoted = format!("{}: {}", head_end + index + 1, line);
+            if relevant.len() >= MAX_RELEVANT_LINES || used + quoted.len() > relevant_budget {
+                break;
+            }
+            used += quoted.len() + 1;
+            relevant.push(quoted);
+        }
+        if !relevant.is_empty() {
+            text.push_str("[Omitted lines relevant to the current request:]\n");
+            for line in relevant {
+                text.push_str(&line);
+                text.push('\n');
+            }
+            text.push_str("...\n");
+        }
+    }
+
+    for line in &lines[tail_start..] {
+        text.push_str(line);
+        text.push('\n');
+    }
+    text
+}
+
+/// Return `output` unchanged if it fits in `limit` bytes. Otherwise save it
+/// in full under the working directory and return a shrunk version with a
+/// note on where the rest is and how to read it.
+pub async fn limit_output(
+    tool_name: &str,
+    output: String,
+    working_dir: &Path,
+    limit: usize,
+) -> String {
+    if output.len() <= limit {
+        return output;
+    }
+
+    let request = tokio::fs::read_to_string(working_dir.join(LAST_REQUEST_FILE))
+        .await
+        .unwrap_or_default();
+    let mut text = shrink_output(&output, limit, &focus_terms(&request));
+
+    let dir = working_dir.join(ARTIFACT_DIR);
+    let file_name = format!(
+        "{}-{}-{}.txt",
+        tool_name.replace(|c: char| !c.is_ascii_alphanumeric() && c != '_', "-"),
+        chrono::Utc::now().format("%Y%m%d-%H%M%S"),
+        &uuid::Uuid::new_v4().simple().to_string()[..8]
+    );
+    let path = dir.join(file_name);
+    let saved = match tokio::fs::create_dir_all(&dir).await {
+        Ok(()) => tokio::fs::write(&path, &output).await,
+        Err(e) => Err(e),
+    };
+
+    let total_lines = output.lines().count();
+    match saved {
+        Ok(()) => text.push_str(&format!(
+            "\n[Output truncated: {} bytes, {} lines. The full output is saved at {}. \
+             Read it in line ranges (e.g. read_file with start_line/end_line) or grep it \
+             instead of reading it whole.]",
+            output.len(),
+            total_lines,
+            path.display()
+        )),
+        Err(e) => {
+            tracing::warn!(
+                "Failed to save full {} output to {}: {}",
+                tool_name,
+                path.display(),
+                e
+            );
+            text.push_str(&format!(
+                "\n[Output truncated: {} bytes, {} lines. The full output could not be saved; \
+                 narrow the request (filters, line ranges) to see the omitted part.]",
+                output.len(),
+                total_lines
+            ));
+        }
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn focus_terms_skip_short_and_common_words() {
+        assert_eq!(
+            focus_terms("Why does the build fail with ECONNREFUSED in api/server.ts? Please fix"),
+            vec!["build", "fail", "econnrefused", "api/server.ts"]
+        );
+    }
+
+    #[test]
+    fn shrinking_keeps_head_tail_and_relevant_lines() {
+        let mut output = String::new();
+        for i in 1..=1000 {
+            if i == 500 {
+                output.push_str("error: ECONNREFUSED 127.0.0.1:5432\n");
+            } else {
+                output.push_str(&format!("line {} ok\n", i));
+            }
+        }
+        let terms = focus_terms("why is it ECONNREFUSED");
+        let text = shrink_output(&output, 1000, &terms);
+        assert!(text.len() < 1200);
+        assert!(text.starts_with("line 1 ok\n"));
+        assert!(text.ends_with("line 1000 ok\n"));
+        assert!(text.contains("lines omitted"));
+        assert!(text.contains("500: error: ECONNREFUSED 127.0.0.1:5432"));
+    }
+
+    #[tokio::test]
+    async fn oversized_output_is_saved_in_full() {
+        let dir = tempfile::tempdir().unwrap();
+        write_last_request(dir.path(), "find the panic")
+            .await
+            .unwrap();
+        let output: String = (0..500).map(|i| format!("row {}\n", i)).collect();
+
+        let small = limit_output("grep_search", "tiny".to_string(), dir.path(), 100).await;
+        assert_eq!(small, "tiny");
+
+        let text = limit_output("grep_search", output.clone(), dir.path(), 400).await;
+        assert!(text.contains("[Output truncated:"));
+        let saved: Vec<_> = std::fs::read_dir(dir.path().join(ARTIFACT_DIR))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(saved.len(), 1);
+        assert!(text.contains(&saved[0].display().to_string()));
+        assert_eq!(std::fs::read_to_string(&saved[0]).unwrap(), output);
+    }
+}
@@ -0,0 +1,403 @@
+//! WASM tool plugins.
+//!
+//! Plugins let in-house tools ship without forking the crate. Each plugin is a
+//! directory under `SANDBOXED_SH_TOOL_PLUGINS_DIR` containing a `plugin.json`
+//! manifest and a WASI module:
+//!
+//! ```json
+//! {
+//!   "name": "jira_lookup",
+//!   "description": "Look up a Jira issue",
+//!   "parameters": { "type": "object", "properties": { "key": { "type": "string" } } },
+//!   "module": "jira_lookup.wasm",
+//!   "filesystem": "none",
+//!   "timeout_secs": 30
+//! }
+//! ```
+//!
+//! Modules run under a WASI runtime (`wasmtime` by default, override with
+//! `SANDBOXED_SH_WASM_RUNTIME`). The tool-call ABI is JSON over stdio: the
+//! module reads `{"tool": "<name>", "args": {...}}` from stdin and writes
+//! `{"output": "..."}` or `{"error": "..."}` to stdout. Plain-text stdout is
+//! passed through as the tool output.
+//!
+//! Modules get no environment and no filesystem access unless the manifest sets
+//! `"filesystem": "workspace"`, which preopens the working directory at
+//! `/workspace`.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use async_trait::async_trait;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+
+use super::Tool;
+
+const MANIFEST_FILE: &str = "plugin.json";
+const DEFAULT_RUNTIME: &str = "wasmtime";
+const DEFAULT_TIMEOUT_SECS: u64 = 30;
+const MAX_OUTPUT_BYTES: usize = 256 * 1024;
+
+/// Filesystem access granted to a plugin module.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginFilesystem {
+    /// No preopened directories.
+    #[default]
+    None,
+    /// The working directory is preopened at `/workspace`.
+    Workspace,
+}
+
+/// `plugin.json` contents.
+#[derive(Debug, Clone, Deserialize)]
+struct PluginManifest {
+    name: String,
+    description: String,
+    #[serde(default = "default_parameters")]
+    parameters: Value,
+    module: String,
+    #[serde(default)]
+    filesystem: PluginFilesystem,
+    #[serde(default)]
+    timeout_secs: Option<u64>,
+}
+
+fn default_parameters() -> Value {
+    json!({ "type": "object", "properties": {} })
+}
+
+/// A tool backed by a WASI module.
+#[derive(Debug, Clone)]
+pub struct WasmPlugin {
+    name: String,
+    description: String,
+    parameters: Value,
+    module: PathBuf,
+    filesystem: PluginFilesystem,
+    timeout: Duration,
+    runtime: String,
+}
+
+impl WasmPlugin {
+    /// Load a plugin from a directory containing `plugin.json`.
+    pub fn load(dir: &Path) -> anyhow::Result<Self> {
+        let raw = std::fs::read_to_string(dir.join(MANIFEST_FILE))?;
+        let manifest: PluginManifest = serde_json::from_str(&raw)?;
+
+        if manifest.name.is_empty()
+            || !manifest
+                .name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            anyhow::bail!("Invalid plugin tool name: '{}'", manifest.name);
+        }
+        if !manifest.parameters.is_object() {
+            anyhow::bail!(
+                "Plugin '{}' parameters must be a JSON schema object",
+                manifest.name
+            );
+        }
+
+        // The module must live inside the plugin directory.
+        let canonical_dir = dir.canonicalize()?;
+        let module = dir
+            .join(&manifest.module)
+            .canonicalize()
+            .map_err(|_| anyhow::anyhow!("Plugin module not found: {}", manifest.module))?;
+        if !module.starts_with(&canonical_dir) {
+            anyhow::bail!("Plugin module must be inside the plugin directory");
+        }
+
+        Ok(Self {
+            name: manifest.name,
+            description: manifest.description,
+            parameters: manifest.parameters,
+            module,
+            filesystem: manifest.filesystem,
+            timeout: Duration::from_secs(manifest.timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS)),
+            runtime: std::env::var("SANDBOXED_SH_WASM_RUNTIME")
+                .unwrap_or_else(|_| DEFAULT_RUNTIME.to_string()),
+        })
+    }
+
+    fn command(&self, working_dir: &Path) -> tokio::process::Command {
+        let mut cmd = tokio::process::Command::new(&self.runtime);
+        cmd.arg("run");
+        if self.filesystem == PluginFilesystem::Workspace {
+            cmd.arg("--dir")
+                .arg(format!("{}::/workspace", working_dir.display()));
+        }
+        cmd.arg(&self.module)
+            .env_clear()
+            .env("PATH", std::env::var("PATH").unwrap_or_default())
+            .current_dir(working_dir)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        cmd
+    }
+}
+
+#[async_trait]
+impl Tool for WasmPlugin {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters_schema(&self) -> Value {
+        self.parameters.clone()
+    }
+
+    async fn execute(&self, args: Value, working_dir: &Path) -> anyhow::Result<String> {
+        let mut child = self.command(working_dir).spawn().map_err(|e| {
+            anyhow::anyhow!("Failed to start WASM runtime '{}': {}", self.runtime, e)
+        })?;
+
+        let request = serde_json::to_vec(&json!({ "tool": self.name, "args": args }))?;
+        let stdin = child.stdin.take();
+        let mut stdout = child.stdout.take().expect("stdout is piped");
+        let mut stderr = child.stderr.take().expect("stderr is piped");
+        // Everything, including feeding the request to a module that never
+        // reads it, counts against the timeout.
+        let run = async {
+            let write = async {
+                if let Some(mut stdin) = stdin {
+                    // A module may exit without reading its input.
+                    let _ = stdin.write_all(&request).await;
+                }
+            };
+            let ((), stdout, stderr, status) = tokio::join!(
+                write,
+                read_capped(&mut stdout),
+                read_capped(&mut stderr),
+                child.wait()
+            );
+            Ok::<_, std::io::Error>((status?, stdout?, stderr?))
+        };
+        let (status, stdout, stderr) =
+            tokio::time::timeout(self.timeout, run)
+                .await
+                .map_err(|_| {
+                    anyhow::anyhow!(
+                        "Plugin '{}' timed out after {}s",
+                        self.name,
+                        self.timeout.as_secs()
+                    )
+                })??;
+
+        let stdout = String::from_utf8_lossy(&stdout).trim().to_string();
+        if !status.success() {
+            let stderr = String::from_utf8_lossy(&stderr);
+            anyhow::bail!(
+                "Plugin '{}' failed ({}): {}",
+                self.name,
+                status,
+                stderr.trim()
+            );
+        }
+
+        match serde_json::from_str::<Value>(&stdout) {
+            Ok(Value::Object(map)) if map.contains_key("error") => {
+                let error = &map["error"];
+                anyhow::bail!(
+                    "{}",
+                    error
+                        .as_str()
+                        .map(str::to_string)
+                        .unwrap_or_else(|| error.to_string())
+                )
+            }
+            Ok(Value::Object(map)) if map.contains_key("output") => Ok(match &map["output"] {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            }),
+            _ => Ok(stdout),
+        }
+    }
+}
+
+/// Read at most [`MAX_OUTPUT_BYTES`] of a pipe, discarding the rest so the
+/// module doesn't block on a full pipe.
+async fn read_capped(pipe: &mut (impl AsyncRead + Unpin)) -> std::io::Result<Vec<u8>> {
+    let mut kept = Vec::new();
+    (&mut *pipe)
+        .take(MAX_OUTPUT_BYTES as u64)
+        .read_to_end(&mut kept)
+        .await?;
+    tokio::io::copy(pipe, &mut tokio::io::sink()).await?;
+    Ok(kept)
+}
+
+/// Directory plugins are loaded from, if configured.
+pub fn plugins_dir() -> Option<PathBuf> {
+    std::env::var("SANDBOXED_SH_TOOL_PLUGINS_DIR")
+        .ok()
+        .filter(|s| !s.trim().is_empty())
+        .map(PathBuf::from)
+}
+
+/// Load every plugin under `dir`. Invalid plugins are skipped with a warning.
+pub fn load_plugins(dir: &Path) -> Vec<WasmPlugin> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut plugins = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if !path.join(MANIFEST_FILE).is_file() {
+            continue;
+        }
+        match WasmPlugin::load(&path) {
+            Ok(plugin) => plugins.push(plugin),
+            Err(e) => tracing::warn!(
+                plugin = %path.display(),
+                error = %e,
+                "Skipping invalid tool plugin"
+            ),
+        }
+    }
+    plugins.sort_by(|a, b| a.name.cmp(&b.name));
+    plugins
+}
+
+/// Register plugins into a tool map without shadowing existing tools.
+/// Returns the names of the plugins that were registered.
+pub fn register_plugins(tools: &mut HashMap<String, Arc<dyn Tool>>, dir: &Path) -> Vec<String> {
+    let mut registered = Vec::new();
+    for plugin in load_plugins(dir) {
+        if tools.contains_key(&plugin.name) {
+            tracing::warn!(
+                tool = %plugin.name,
+                "Tool plugin conflicts with an existing tool; skipping"
+            );
+            continue;
+        }
+        registered.push(plugin.name.clone());
+        tools.insert(plugin.name.clone(), Arc::new(plugin));
+    }
+    registered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_plugin(root: &Path, dir: &str, manifest: Value) -> PathBuf {
+        let path = root.join(dir);
+        std::fs::create_dir_all(&path).unwrap();
+        std::fs::write(path.join("tool.wasm"), b"\0asm").unwrap();
+        std::fs::write(path.join(MANIFEST_FILE), manifest.to_string()).unwrap();
+        path
+    }
+
+    #[test]
+    fn register_plugins_skips_invalid_and_conflicting() {
+        let temp = tempfile::tempdir().unwrap();
+        write_plugin(
+            temp.path(),
+            "lookup",
+            json!({ "name": "jira_lookup", "description": "Lookup", "module": "tool.wasm" }),
+        );
+        write_plugin(
+            temp.path(),
+            "shadow",
+            json!({ "name": "read_file", "description": "Shadow", "module": "tool.wasm" }),
+        );
+        write_plugin(
+            temp.path(),
+            "escape",
+            json!({ "name": "escape", "description": "Escape", "module": "../lookup/tool.wasm" }),
+        );
+
+        let mut tools: HashMap<String, Arc<dyn Tool>> = HashMap::new();
+        tools.insert("read_file".to_string(), Arc::new(crate::tools::ReadFile));
+        let registered = register_plugins(&mut tools, temp.path());
+
+        assert_eq!(registered, vec!["jira_lookup"]);
+        assert_eq!(
+            tools["read_file"].description(),
+            crate::tools::ReadFile.description()
+        );
+        assert_eq!(
+            tools["jira_lookup"].parameters_schema(),
+            default_parameters()
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn execute_uses_json_stdio_abi() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = tempfile::tempdir().unwrap();
+        let dir = write_plugin(
+            temp.path(),
+            "echo",
+            json!({ "name": "echo", "description": "Echo", "module": "tool.wasm" }),
+        );
+        // Stand-in runtime: echoes the request back as the tool output.
+        let runtime = temp.path().join("fake-runtime");
+        std::fs::write(
+            &runtime,
+            "#!/bin/sh\nread -r req\nprintf '{\"output\": %s}' \"$req\"\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&runtime, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let mut plugin = WasmPlugin::load(&dir).unwrap();
+        plugin.runtime = runtime.to_string_lossy().to_string();
+        let out = plugin
+            .execute(json!({ "key": "ABC-1" }), temp.path())
+            .await
+            .unwrap();
+        let echoed: Value = serde_json::from_str(&out).unwrap();
+        assert_eq!(echoed["tool"], "echo");
+        assert_eq!(echoed["args"]["key"], "ABC-1");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn execute_caps_output_and_times_out_unread_input() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let temp = tempfile::tempdir().unwrap();
+        let dir = write_plugin(
+            temp.path(),
+            "noisy",
+            json!({ "name": "noisy", "description": "Noisy", "module": "tool.wasm", "timeout_secs": 1 }),
+        );
+        let runtime = |name: &str, script: &str| {
+            let path = temp.path().join(name);
+            std::fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+            std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+            path.to_string_lossy().to_string()
+        };
+        let mut plugin = WasmPlugin::load(&dir).unwrap();
+
+        plugin.runtime = runtime("flood", "head -c 1000000 /dev/zero | tr '\\0' a");
+        let out = plugin.execute(json!({}), temp.path()).await.unwrap();
+        assert_eq!(out.len(), MAX_OUTPUT_BYTES);
+
+        // A request larger than the pipe buffer, never read.
+        plugin.runtime = runtime("deaf", "exec sleep 30");
+        let started = std::time::Instant::now();
+        let err = plugin
+            .execute(json!({ "blob": "x".repeat(1 << 20) }), temp.path())
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("timed out"));
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+}
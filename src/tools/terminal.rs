@@ -15,16 +15,20 @@
 use std::collections::HashMap;
 use std::env;
 use std::path::{Path, PathBuf};
-use std::process::{Output, Stdio};
+use std::process::{ExitStatus, Stdio};
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use async_trait::async_trait;
 use serde_json::{json, Value};
-use tokio::io::AsyncWriteExt;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
 use tokio::process::Command;
 
-use super::{resolve_path_simple as resolve_path, Tool};
+use super::{
+    call_timeout, configured_timeout, resolve_path_simple as resolve_path, safe_truncate_index,
+    OutputSink, OutputStream, Tool, ToolOutputDelta,
+};
 use crate::nspawn;
 
 static RTK_COMMANDS_PROCESSED: AtomicU64 = AtomicU64::new(0);
@@ -494,6 +498,7 @@ mod tests {
     fn parse_timeout_defaults() {
         let _guard = env_lock().lock().unwrap();
         env::remove_var("SANDBOXED_SH_COMMAND_TIMEOUT_SECS");
+        env::remove_var("SANDBOXED_SH_TOOL_TIMEOUTS");
         let args = json!({});
         assert_eq!(
             parse_timeout(&args),
@@ -501,6 +506,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_timeout_uses_configured_tool_timeout() {
+        let _guard = env_lock().lock().unwrap();
+        env::set_var(
+            "SANDBOXED_SH_TOOL_TIMEOUTS",
+            r#"{"run_command": 1800, "*": 60}"#,
+        );
+        assert_eq!(parse_timeout(&json!({})), Duration::from_secs(1800));
+        // Per-call timeouts still win.
+        assert_eq!(
+            parse_timeout(&json!({"timeout_secs": 5})),
+            Duration::from_secs(5)
+        );
+        assert_eq!(
+            configured_timeout("fetch_url"),
+            Some(Duration::from_secs(60))
+        );
+        env::remove_var("SANDBOXED_SH_TOOL_TIMEOUTS");
+    }
+
+    // ── run_shell_command ─────────────────────────────────────────────
+
+    #[tokio::test]
+    async fn timed_out_commands_return_partial_output() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut options = parse_command_options(&json!({"timeout_ms": 500}));
+        options.sink = Some(tx);
+        let args = vec!["-c".to_string(), "echo started; sleep 30".to_string()];
+
+        let output = run_shell_command("/bin/sh", &args, None, &options)
+            .await
+            .unwrap();
+        assert!(output.timed_out);
+        assert_eq!(output.exit_code(), -1);
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "started\n");
+
+        let delta = rx.recv().await.unwrap();
+        assert_eq!(delta.stream, OutputStream::Stdout);
+        assert_eq!(delta.content, "started\n");
+    }
+
+    #[test]
+    fn truncated_output_is_tagged() {
+        assert_eq!(truncate_output("short".to_string(), 10), "short");
+        // Never cuts a multi-byte character in half.
+        assert_eq!(
+            truncate_output("aé-rest".to_string(), 2),
+            "a\n... [output truncated: showing 1 of 8 bytes]"
+        );
+    }
+
     // ── parse_env ─────────────────────────────────────────────────────
 
     #[test]
@@ -560,6 +616,60 @@ mod tests {
         // On most systems /bin/bash or /bin/sh exists
         assert!(shell == "/bin/bash" || shell == "/bin/sh");
     }
+
+    // ── Windows shells ────────────────────────────────────────────────
+
+    #[test]
+    fn windows_shell_recognizes_names_and_paths() {
+        assert_eq!(
+            WindowsShell::parse(r"C:\Windows\System32\cmd.exe"),
+            Some(WindowsShell::Cmd)
+        );
+        assert_eq!(WindowsShell::parse("PWSH"), Some(WindowsShell::Pwsh));
+        assert_eq!(
+            WindowsShell::parse("powershell.exe"),
+            Some(WindowsShell::PowerShell)
+        );
+        assert_eq!(WindowsShell::parse("/bin/sh"), None);
+        assert_eq!(WindowsShell::resolve(Some("cmd")), WindowsShell::Cmd);
+    }
+
+    #[test]
+    fn windows_shell_invocations_pass_the_command_through() {
+        let (program, args) = WindowsShell::Cmd.invocation("dir /b");
+        assert_eq!(program, "cmd.exe");
+        assert_eq!(args, vec!["/D", "/S", "/C", "dir /b"]);
+
+        let (program, args) = WindowsShell::PowerShell.invocation("npm test");
+        assert_eq!(program, "powershell.exe");
+        assert_eq!(
+            &args[..4],
+            ["-NoLogo", "-NoProfile", "-NonInteractive", "-Command"]
+        );
+        assert!(args[4].contains("\nnpm test\n"));
+        assert!(args[4].contains("exit $LASTEXITCODE"));
+    }
+
+    #[cfg(windows)]
+    #[tokio::test]
+    async fn windows_host_commands_report_output_and_exit_code() {
+        let dir = tempfile::tempdir().unwrap();
+        let options = parse_command_options(&json!({}));
+        for (shell, command) in [
+            ("powershell", "echo hello; cmd /c exit 3"),
+            ("cmd", "echo hello & exit 3"),
+        ] {
+            let options = CommandOptions {
+                shell: Some(shell.to_string()),
+                ..options.without_sink()
+            };
+            let output = run_host_command(dir.path(), command, &options)
+                .await
+                .unwrap();
+            assert!(String::from_utf8_lossy(&output.stdout).contains("hello"));
+            assert_eq!(output.exit_code(), 3, "{}", shell);
+        }
+    }
 }
 
 /// Read context information from the local context file or fall back to env vars.
@@ -796,11 +906,28 @@ struct CommandOptions {
     shell: Option<String>,
     max_output_chars: usize,
     raw_output: bool,
+    /// Receives stdout/stderr chunks while the command runs
+    sink: Option<OutputSink>,
+}
+
+impl CommandOptions {
+    /// Options for helper commands whose output is not part of the result.
+    fn without_sink(&self) -> Self {
+        Self {
+            sink: None,
+            ..self.clone()
+        }
+    }
 }
 
 const DEFAULT_MAX_OUTPUT_CHARS: usize = 10_000;
 const MAX_OUTPUT_CHARS_LIMIT: usize = 50_000;
 const DEFAULT_COMMAND_TIMEOUT_SECS: f64 = 300.0;
+/// Output kept per stream; anything beyond is streamed but not returned.
+const MAX_CAPTURED_BYTES: usize = 1024 * 1024;
+/// How long to wait for pipes to close after the command exits. Background
+/// processes that inherited them would otherwise block the result.
+const PIPE_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
 
 fn default_timeout_from_env() -> Duration {
     if let Ok(raw) = env::var("SANDBOXED_SH_COMMAND_TIMEOUT_SECS") {
@@ -814,18 +941,9 @@ fn default_timeout_from_env() -> Duration {
 }
 
 fn parse_timeout(args: &Value) -> Duration {
-    if let Some(ms) = args.get("timeout_ms").and_then(|v| v.as_u64()) {
-        return Duration::from_millis(ms.max(1));
-    }
-    if let Some(secs) = args.get("timeout_secs").and_then(|v| v.as_u64()) {
-        return Duration::from_secs(secs.max(1));
-    }
-    if let Some(secs) = args.get("timeout").and_then(|v| v.as_f64()) {
-        if secs > 0.0 {
-            return Duration::from_secs_f64(secs);
-        }
-    }
-    default_timeout_from_env()
+    call_timeout(args)
+        .or_else(|| configured_timeout("run_command"))
+        .unwrap_or_else(default_timeout_from_env)
 }
 
 fn parse_env(args: &Value) -> HashMap<String, String> {
@@ -916,6 +1034,7 @@ fn parse_command_options(args: &Value) -> CommandOptions {
             .map(|s| s.to_string()),
         max_output_chars: parse_max_output_chars(args),
         raw_output: args.get("raw").and_then(|v| v.as_bool()).unwrap_or(false),
+        sink: None,
     }
 }
 
@@ -954,13 +1073,137 @@ fn resolve_shell(shell: Option<&str>, container_root: Option<&Path>) -> String {
     "/bin/sh".to_string()
 }
 
+/// Shells `run_command` can use on Windows hosts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum WindowsShell {
+    /// Windows PowerShell 5.1 (`powershell.exe`), present on every install
+    PowerShell,
+    /// PowerShell 7+ (`pwsh.exe`)
+    Pwsh,
+    /// `cmd.exe`
+    Cmd,
+}
+
+impl WindowsShell {
+    /// Recognize a shell by name or path (`pwsh`, `C:\Windows\System32\cmd.exe`).
+    fn parse(shell: &str) -> Option<Self> {
+        let name = crate::util::path_file_name(shell).to_ascii_lowercase();
+        match name.trim_end_matches(".exe") {
+            "powershell" => Some(Self::PowerShell),
+            "pwsh" => Some(Self::Pwsh),
+            "cmd" => Some(Self::Cmd),
+            _ => None,
+        }
+    }
+
+    /// The shell for a call: its `shell` argument, else `SANDBOXED_SH_WINDOWS_SHELL`,
+    /// else PowerShell. Unix shell paths such as the `/bin/sh` default are ignored.
+    fn resolve(requested: Option<&str>) -> Self {
+        requested
+            .and_then(Self::parse)
+            .or_else(|| {
+                env::var("SANDBOXED_SH_WINDOWS_SHELL")
+                    .ok()
+                    .and_then(|shell| Self::parse(shell.trim()))
+            })
+            .unwrap_or(Self::PowerShell)
+    }
+
+    /// Program and arguments that run `command`.
+    ///
+    /// PowerShell only reports success or failure through its exit code, so a
+    /// failing native program's own exit code is passed through explicitly.
+    fn invocation(self, command: &str) -> (String, Vec<String>) {
+        let program = match self {
+            Self::PowerShell => "powershell.exe",
+            Self::Pwsh => "pwsh.exe",
+            Self::Cmd => {
+                return (
+                    "cmd.exe".to_string(),
+                    vec!["/D".into(), "/S".into(), "/C".into(), command.to_string()],
+                )
+            }
+        };
+        let script = format!(
+            "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8\n{}\n\
+             if (-not $?) {{ if ($LASTEXITCODE) {{ exit $LASTEXITCODE }} else {{ exit 1 }} }}",
+            command
+        );
+        let args = ["-NoLogo", "-NoProfile", "-NonInteractive", "-Command"]
+            .iter()
+            .map(|arg| arg.to_string())
+            .chain([script])
+            .collect();
+        (program.to_string(), args)
+    }
+}
+
+/// Captured output of a command. When `timed_out` is set the command was
+/// killed and the output is whatever it produced until then.
+#[derive(Debug)]
+struct CommandOutput {
+    stdout: Vec<u8>,
+    stderr: Vec<u8>,
+    status: Option<ExitStatus>,
+    timed_out: bool,
+}
+
+impl CommandOutput {
+    fn success(&self) -> bool {
+        self.status.is_some_and(|status| status.success())
+    }
+
+    fn exit_code(&self) -> i32 {
+        self.status.and_then(|status| status.code()).unwrap_or(-1)
+    }
+}
+
+/// Read `reader` to the end into `captured` (up to [`MAX_CAPTURED_BYTES`]),
+/// forwarding every chunk to `sink`.
+async fn pump_output<R: AsyncRead + Unpin>(
+    mut reader: R,
+    stream: OutputStream,
+    sink: Option<OutputSink>,
+    captured: Arc<Mutex<Vec<u8>>>,
+) {
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = match reader.read(&mut buf).await {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+        if let Some(sink) = &sink {
+            let _ = sink.send(ToolOutputDelta {
+                stream,
+                content: String::from_utf8_lossy(&buf[..n]).into_owned(),
+            });
+        }
+        let mut captured = captured.lock().unwrap_or_else(|e| e.into_inner());
+        let room = MAX_CAPTURED_BYTES.saturating_sub(captured.len());
+        captured.extend_from_slice(&buf[..n.min(room)]);
+    }
+}
+
 async fn run_shell_command(
     program: &str,
     args: &[String],
     cwd: Option<&Path>,
     options: &CommandOptions,
-) -> anyhow::Result<Output> {
+) -> anyhow::Result<CommandOutput> {
     let mut cmd = Command::new(program);
+    #[cfg(windows)]
+    match args.split_last() {
+        // cmd.exe parses its own command line instead of following the quoting
+        // rules `args` applies, so hand it the command verbatim.
+        Some((command, flags)) if WindowsShell::parse(program) == Some(WindowsShell::Cmd) => {
+            cmd.args(flags);
+            cmd.raw_arg(format!("\"{}\"", command));
+        }
+        _ => {
+            cmd.args(args);
+        }
+    }
+    #[cfg(not(windows))]
     cmd.args(args);
     if let Some(cwd) = cwd {
         cmd.current_dir(cwd);
@@ -988,32 +1231,73 @@ async fn run_shell_command(
         }
     }
 
-    let output = tokio::time::timeout(options.timeout, child.wait_with_output()).await;
+    let stdout = Arc::new(Mutex::new(Vec::new()));
+    let stderr = Arc::new(Mutex::new(Vec::new()));
+    let mut pumps = Vec::new();
+    if let Some(out) = child.stdout.take() {
+        pumps.push(tokio::spawn(pump_output(
+            out,
+            OutputStream::Stdout,
+            options.sink.clone(),
+            stdout.clone(),
+        )));
+    }
+    if let Some(err) = child.stderr.take() {
+        pumps.push(tokio::spawn(pump_output(
+            err,
+            OutputStream::Stderr,
+            options.sink.clone(),
+            stderr.clone(),
+        )));
+    }
+
+    let (status, timed_out) = match tokio::time::timeout(options.timeout, child.wait()).await {
+        Ok(Ok(status)) => (Some(status), false),
+        Ok(Err(e)) => return Err(anyhow::anyhow!("Failed to execute command: {}", e)),
+        Err(_) => {
+            // Take down anything the command started as well, so a hung build
+            // does not keep running (and holding the pipes) after we give up.
+            if let Some(pid) = child.id() {
+                crate::process_tree::terminate_tree(pid, crate::process_tree::TERM_GRACE).await;
+            }
+            let _ = child.kill().await;
+            (None, true)
+        }
+    };
 
-    match output {
-        Ok(Ok(output)) => Ok(output),
-        Ok(Err(e)) => Err(anyhow::anyhow!("Failed to execute command: {}", e)),
-        Err(_) => Err(anyhow::anyhow!(
-            "Command timed out after {} seconds",
-            options.timeout.as_secs_f64()
-        )),
+    for mut pump in pumps {
+        if tokio::time::timeout(PIPE_DRAIN_TIMEOUT, &mut pump)
+            .await
+            .is_err()
+        {
+            pump.abort();
+        }
     }
+
+    let take = |buf: &Arc<Mutex<Vec<u8>>>| {
+        std::mem::take(&mut *buf.lock().unwrap_or_else(|e| e.into_inner()))
+    };
+    Ok(CommandOutput {
+        stdout: take(&stdout),
+        stderr: take(&stderr),
+        status,
+        timed_out,
+    })
 }
 
 async fn run_host_command(
     cwd: &Path,
     command: &str,
     options: &CommandOptions,
-) -> anyhow::Result<Output> {
-    let (shell, shell_arg) = if cfg!(target_os = "windows") {
-        ("cmd".to_string(), "/C".to_string())
+) -> anyhow::Result<CommandOutput> {
+    let (shell, args) = if cfg!(windows) {
+        WindowsShell::resolve(options.shell.as_deref()).invocation(command)
     } else {
         (
             resolve_shell(options.shell.as_deref(), None),
-            "-c".to_string(),
+            vec!["-c".to_string(), command.to_string()],
         )
     };
-    let args = vec![shell_arg, command.to_string()];
     run_shell_command(&shell, &args, Some(cwd), options).await
 }
 
@@ -1072,7 +1356,7 @@ async fn run_container_command(
     cwd: &Path,
     command: &str,
     options: &CommandOptions,
-) -> anyhow::Result<Output> {
+) -> anyhow::Result<CommandOutput> {
     let root = container_root
         .canonicalize()
         .unwrap_or_else(|_| container_root.to_path_buf());
@@ -1186,7 +1470,13 @@ async fn run_container_command(
                     } else {
                         "machinectl"
                     };
-                    let _ = run_shell_command(machinectl, &terminate_args, None, options).await;
+                    let _ = run_shell_command(
+                        machinectl,
+                        &terminate_args,
+                        None,
+                        &options.without_sink(),
+                    )
+                    .await;
                 }
             }
         }
@@ -1218,10 +1508,10 @@ async fn running_container_leader(machine_name: &str, options: &CommandOptions)
         "Leader".to_string(),
         "--value".to_string(),
     ];
-    let output = run_shell_command(machinectl, &args, None, options)
+    let output = run_shell_command(machinectl, &args, None, &options.without_sink())
         .await
         .ok()?;
-    if !output.status.success() {
+    if !output.success() {
         return None;
     }
     let leader = String::from_utf8_lossy(&output.stdout).trim().to_string();
@@ -1266,7 +1556,7 @@ async fn run_nsenter_command(
     rel_str: &str,
     command: &str,
     options: &CommandOptions,
-) -> anyhow::Result<Output> {
+) -> anyhow::Result<CommandOutput> {
     let nsenter = if Path::new("/usr/bin/nsenter").exists() {
         "/usr/bin/nsenter"
     } else {
@@ -1339,7 +1629,7 @@ impl Tool for RunCommand {
                 },
                 "shell": {
                     "type": "string",
-                    "description": "Optional: shell executable path (default: /bin/sh)."
+                    "description": "Optional: shell executable path (default: /bin/sh). On Windows hosts: powershell (default), pwsh or cmd."
                 },
                 "max_output_chars": {
                     "type": "integer",
@@ -1355,6 +1645,30 @@ impl Tool for RunCommand {
     }
 
     async fn execute(&self, args: Value, working_dir: &Path) -> anyhow::Result<String> {
+        self.run(args, working_dir, None).await
+    }
+
+    async fn execute_streaming(
+        &self,
+        args: Value,
+        working_dir: &Path,
+        sink: OutputSink,
+    ) -> anyhow::Result<String> {
+        self.run(args, working_dir, Some(sink)).await
+    }
+
+    fn manages_timeout(&self) -> bool {
+        true
+    }
+}
+
+impl RunCommand {
+    async fn run(
+        &self,
+        args: Value,
+        working_dir: &Path,
+        sink: Option<OutputSink>,
+    ) -> anyhow::Result<String> {
         let command = args["command"]
             .as_str()
             .ok_or_else(|| anyhow::anyhow!("Missing 'command' argument"))?;
@@ -1372,7 +1686,8 @@ impl Tool for RunCommand {
             .as_str()
             .map(|p| resolve_path(p, working_dir))
             .unwrap_or_else(|| working_dir.to_path_buf());
-        let options = parse_command_options(&args);
+        let mut options = parse_command_options(&args);
+        options.sink = sink;
 
         let (final_command, rtk_used) = if rtk_enabled() {
             if let Some(rtk_path) = rtk_binary_path() {
@@ -1416,7 +1731,7 @@ impl Tool for RunCommand {
 
         let stdout = sanitize_output(&output.stdout);
         let stderr = sanitize_output(&output.stderr);
-        let exit_code = output.status.code().unwrap_or(-1);
+        let exit_code = output.exit_code();
 
         tracing::debug!(
             "Command completed: exit={}, stdout_len={}, stderr_len={}",
@@ -1448,10 +1763,17 @@ impl Tool for RunCommand {
         } else {
             let mut result = String::new();
 
-            result.push_str(&format!("Exit code: {}\n", exit_code));
+            if output.timed_out {
+                result.push_str(&format!(
+                    "Timed out after {} seconds; the command was killed. Output up to that point is below.\n",
+                    options.timeout.as_secs_f64()
+                ));
+            } else {
+                result.push_str(&format!("Exit code: {}\n", exit_code));
+            }
 
             // Add hint when non-zero exit but output exists (common with tools that warn but succeed)
-            if exit_code != 0 && !stdout.is_empty() {
+            if !output.timed_out && exit_code != 0 && !stdout.is_empty() {
                 result.push_str("Note: Non-zero exit code but output was produced. The command may have succeeded with warnings - verify output files exist.\n");
             }
 
@@ -1468,12 +1790,29 @@ impl Tool for RunCommand {
             result
         };
 
-        let mut result = result;
-        if result.len() > options.max_output_chars {
-            result.truncate(options.max_output_chars);
-            result.push_str("\n... [output truncated]");
+        let mut result = truncate_output(result, options.max_output_chars);
+        if options.raw_output && output.timed_out {
+            result.push_str(&format!(
+                "\n... [timed out after {} seconds; output is partial]",
+                options.timeout.as_secs_f64()
+            ));
         }
 
         Ok(result)
     }
 }
+
+/// Cut `output` to at most `max` bytes, tagging how much was dropped.
+fn truncate_output(mut output: String, max: usize) -> String {
+    if output.len() <= max {
+        return output;
+    }
+    let total = output.len();
+    output.truncate(safe_truncate_index(&output, max));
+    output.push_str(&format!(
+        "\n... [output truncated: showing {} of {} bytes]",
+        output.len(),
+        total
+    ));
+    output
+}
@@ -0,0 +1,637 @@
+//! Visual regression checks.
+//!
+//! `visual_diff` captures a URL (headless Chromium) or a desktop display,
+//! compares it with a named baseline and reports how much changed, so an
+//! agent can check a UI change it just made. The first run for a name
+//! records the baseline.
+//!
+//! The comparison follows pixelmatch: two pixels differ when their YIQ colour
+//! distance exceeds `threshold` (0 = exact, 1 = anything goes). The diff image
+//! shows differing pixels in red over a faded copy of the baseline.
+//!
+//! Baselines are kept in `screenshots/baselines/<name>.png` under the working
+//! directory. Each run also writes `<name>-<timestamp>-current.png` and
+//! `-diff.png` to `screenshots/visual-diff/`, which can be shared in a reply
+//! with `<image path="..." />`.
+
+use std::io::{Read, Write};
+use std::path::Path;
+use std::process::Stdio;
+
+use async_trait::async_trait;
+use serde_json::{json, Value};
+use tokio::process::Command;
+
+use super::desktop::{capture_display, find_browser_command};
+use super::desktop_macos::Region;
+use super::Tool;
+
+const BASELINE_DIR: &str = "screenshots/baselines";
+const RUN_DIR: &str = "screenshots/visual-diff";
+
+/// Per-pixel colour distance threshold (pixelmatch's default).
+const DEFAULT_THRESHOLD: f64 = 0.1;
+
+/// Largest changed-pixel ratio that still counts as a pass.
+const DEFAULT_MAX_DIFF_RATIO: f64 = 0.001;
+
+/// Maximum possible YIQ delta between two pixels.
+const MAX_YIQ_DELTA: f64 = 35215.0;
+
+/// Decoded 8-bit RGBA image.
+#[derive(Debug, Clone, PartialEq)]
+struct Image {
+    width: u32,
+    height: u32,
+    rgba: Vec<u8>,
+}
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Decode a non-interlaced 8-bit PNG (greyscale, RGB, palette, with or
+/// without alpha), which covers what scrot, screencapture and Chromium write.
+fn decode_png(bytes: &[u8]) -> anyhow::Result<Image> {
+    if bytes.len() < 8 || bytes[..8] != PNG_SIGNATURE {
+        return Err(anyhow::anyhow!("not a PNG file"));
+    }
+
+    let mut pos = 8;
+    let mut header = None;
+    let mut palette: &[u8] = &[];
+    let mut compressed = Vec::new();
+    while pos + 8 <= bytes.len() {
+        let len = u32::from_be_bytes(bytes[pos..pos + 4].try_into()?) as usize;
+        let kind = &bytes[pos + 4..pos + 8];
+        let data = bytes
+            .get(pos + 8..pos + 8 + len)
+            .ok_or_else(|| anyhow::anyhow!("truncated PNG chunk"))?;
+        match kind {
+            b"IHDR" if len >= 13 => {
+                header = Some((
+                    u32::from_be_bytes(data[0..4].try_into()?),
+                    u32::from_be_bytes(data[4..8].try_into()?),
+                    data[8],
+                    data[9],
+                    data[12],
+                ))
+            }
+            b"PLTE" => palette = data,
+            b"IDAT" => compressed.extend_from_slice(data),
+            b"IEND" => break,
+            _ => {}
+        }
+        pos += 12 + len;
+    }
+
+    let (width, height, bit_depth, color_type, interlace) =
+        header.ok_or_else(|| anyhow::anyhow!("PNG has no IHDR chunk"))?;
+    if bit_depth != 8 || interlace != 0 {
+        return Err(anyhow::anyhow!(
+            "unsupported PNG (bit depth {}, interlace {}); only 8-bit non-interlaced images are supported",
+            bit_depth,
+            interlace
+        ));
+    }
+    let channels = match color_type {
+        0 | 3 => 1,
+        4 => 2,
+        2 => 3,
+        6 => 4,
+        other => return Err(anyhow::anyhow!("unsupported PNG colour type {}", other)),
+    };
+
+    let mut raw = Vec::new();
+    flate2::read::ZlibDecoder::new(compressed.as_slice()).read_to_end(&mut raw)?;
+
+    let stride = width as usize * channels;
+    if raw.len() < (stride + 1) * height as usize {
+        return Err(anyhow::anyhow!("PNG image data is truncated"));
+    }
+    let mut pixels = vec![0u8; stride * height as usize];
+    for row in 0..height as usize {
+        let filter = raw[row * (stride + 1)];
+        let line = &raw[row * (stride + 1) + 1..(row + 1) * (stride + 1)];
+        let (done, rest) = pixels.split_at_mut(row * stride);
+        let prev = if row == 0 {
+            None
+        } else {
+            Some(&done[(row - 1) * stride..])
+        };
+        let out = &mut rest[..stride];
+        for i in 0..stride {
+            let left = if i >= channels { out[i - channels] } else { 0 };
+            let up = prev.map_or(0, |p| p[i]);
+            let up_left = match prev {
+                Some(p) if i >= channels => p[i - channels],
+                _ => 0,
+            };
+            let predictor = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((left as u16 + up as u16) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                other => return Err(anyhow::anyhow!("invalid PNG filter type {}", other)),
+            };
+            out[i] = line[i].wrapping_add(predictor);
+        }
+    }
+
+    let mut rgba = Vec::with_capacity(width as usize * height as usize * 4);
+    for px in pixels.chunks_exact(channels) {
+        match color_type {
+            0 => rgba.extend_from_slice(&[px[0], px[0], px[0], 255]),
+            4 => rgba.extend_from_slice(&[px[0], px[0], px[0], px[1]]),
+            2 => rgba.extend_from_slice(&[px[0], px[1], px[2], 255]),
+            6 => rgba.extend_from_slice(px),
+            _ => {
+                let entry = palette
+                    .get(px[0] as usize * 3..px[0] as usize * 3 + 3)
+                    .ok_or_else(|| anyhow::anyhow!("PNG palette index out of range"))?;
+                rgba.extend_from_slice(&[entry[0], entry[1], entry[2], 255]);
+            }
+        }
+    }
+
+    Ok(Image {
+        width,
+        height,
+        rgba,
+    })
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let p = left as i16 + up as i16 - up_left as i16;
+    let (pa, pb, pc) = (
+        (p - left as i16).abs(),
+        (p - up as i16).abs(),
+        (p - up_left as i16).abs(),
+    );
+    if pa <= pb && pa <= pc {
+        left
+    } else if pb <= pc {
+        up
+    } else {
+        up_left
+    }
+}
+
+/// Encode an RGBA image as an unfiltered PNG.
+fn encode_png(image: &Image) -> anyhow::Result<Vec<u8>> {
+    fn chunk(out: &mut Vec<u8>, kind: &[u8], data: &[u8]) {
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        out.extend_from_slice(kind);
+        out.extend_from_slice(data);
+        let mut crc = flate2::Crc::new();
+        crc.update(kind);
+        crc.update(data);
+        out.extend_from_slice(&crc.sum().to_be_bytes());
+    }
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&image.width.to_be_bytes());
+    header.extend_from_slice(&image.height.to_be_bytes());
+    header.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let stride = image.width as usize * 4;
+    let mut encoder = flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+    for row in image.rgba.chunks_exact(stride.max(1)) {
+        encoder.write_all(&[0])?;
+        encoder.write_all(row)?;
+    }
+
+    let mut out = PNG_SIGNATURE.to_vec();
+    chunk(&mut out, b"IHDR", &header);
+    chunk(&mut out, b"IDAT", &encoder.finish()?);
+    chunk(&mut out, b"IEND", &[]);
+    Ok(out)
+}
+
+/// Pixel blended onto white by its alpha.
+fn blend(px: &[u8]) -> [f64; 3] {
+    let alpha = px[3] as f64 / 255.0;
+    [0, 1, 2].map(|c| 255.0 + (px[c] as f64 - 255.0) * alpha)
+}
+
+fn luma([r, g, b]: [f64; 3]) -> f64 {
+    r * 0.29889531 + g * 0.58662247 + b * 0.11448223
+}
+
+/// Squared YIQ distance between two RGBA pixels, as in pixelmatch.
+fn color_delta(a: &[u8], b: &[u8]) -> f64 {
+    let (a, b) = (blend(a), blend(b));
+    let y = luma(a) - luma(b);
+    let i = (a[0] - b[0]) * 0.59597799 - (a[1] - b[1]) * 0.2741761 - (a[2] - b[2]) * 0.32180189;
+    let q = (a[0] - b[0]) * 0.21147017 - (a[1] - b[1]) * 0.52261711 + (a[2] - b[2]) * 0.31114694;
+    0.5053 * y * y + 0.299 * i * i + 0.1957 * q * q
+}
+
+/// Count pixels whose colour distance exceeds `threshold` and render a diff
+/// image. Both images must have the same dimensions.
+fn compare_images(baseline: &Image, current: &Image, threshold: f64) -> (u64, Image) {
+    let max_delta = MAX_YIQ_DELTA * threshold * threshold;
+    let mut diff_pixels = 0;
+    let mut rgba = Vec::with_capacity(baseline.rgba.len());
+    for (a, b) in baseline
+        .rgba
+        .chunks_exact(4)
+        .zip(current.rgba.chunks_exact(4))
+    {
+        if color_delta(a, b) > max_delta {
+            diff_pixels += 1;
+            rgba.extend_from_slice(&[255, 0, 0, 255]);
+        } else {
+            let faded = (255.0 + (luma(blend(a)) - 255.0) * 0.1) as u8;
+            rgba.extend_from_slice(&[faded, faded, faded, 255]);
+        }
+    }
+    (
+        diff_pixels,
+        Image {
+            width: baseline.width,
+            height: baseline.height,
+            rgba,
+        },
+    )
+}
+
+/// Baseline names become file names.
+fn validate_name(name: &str) -> anyhow::Result<()> {
+    if name.is_empty()
+        || name.len() > 100
+        || name.starts_with('.')
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+    {
+        return Err(anyhow::anyhow!(
+            "Invalid baseline name '{}': use letters, digits, '-', '_' and '.'",
+            name
+        ));
+    }
+    Ok(())
+}
+
+/// Screenshot `url` with headless Chromium.
+async fn capture_url(
+    url: &str,
+    path: &Path,
+    width: u64,
+    height: u64,
+    wait_ms: u64,
+) -> anyhow::Result<()> {
+    let browser = find_browser_command().ok_or_else(|| {
+        anyhow::anyhow!(
+            "Failed to find a Chromium-compatible browser in PATH. \
+             Set CHROMIUM_BIN or BROWSER, or install chromium/chromium-browser."
+        )
+    })?;
+
+    let mut args = vec![
+        "--headless".to_string(),
+        "--no-sandbox".to_string(),
+        "--disable-gpu".to_string(),
+        "--hide-scrollbars".to_string(),
+        format!("--window-size={},{}", width, height),
+        format!("--screenshot={}", path.display()),
+    ];
+    if wait_ms > 0 {
+        args.push(format!("--virtual-time-budget={}", wait_ms));
+    }
+    args.push(url.to_string());
+
+    let output = tokio::time::timeout(
+        std::time::Duration::from_secs(60 + wait_ms / 1000),
+        Command::new(&browser)
+            .args(&args)
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .output(),
+    )
+    .await
+    .map_err(|_| anyhow::anyhow!("Headless browser timed out capturing {}", url))?
+    .map_err(|e| anyhow::anyhow!("Failed to start {}: {}", browser, e))?;
+
+    if !output.status.success() || !path.exists() {
+        return Err(anyhow::anyhow!(
+            "Headless browser failed to capture {}: {}",
+            url,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Capture a URL or desktop display and compare it with a stored baseline.
+pub struct VisualDiff;
+
+#[async_trait]
+impl Tool for VisualDiff {
+    fn name(&self) -> &str {
+        "visual_diff"
+    }
+
+    fn description(&self) -> &str {
+        "Visual regression check: screenshot a URL (headless Chromium) or a desktop display/region and compare it pixel by pixel with a stored baseline of the same name. The first run for a name saves the baseline. Returns the changed-pixel count and ratio, whether it passed, and paths to the current and diff images (differences in red). Use update_baseline=true to accept an intended change."
+    }
+
+    fn parameters_schema(&self) -> Value {
+        json!({
+            "type": "object",
+            "properties": {
+                "name": {
+                    "type": "string",
+                    "description": "Baseline name, e.g. 'login-page' (letters, digits, '-', '_', '.')"
+                },
+                "url": {
+                    "type": "string",
+                    "description": "URL to capture with headless Chromium (provide either 'url' OR 'display')"
+                },
+                "display": {
+                    "type": "string",
+                    "description": "Desktop display to capture (e.g., ':99' or 'macos:1') from desktop_start_session"
+                },
+                "region": {
+                    "type": "object",
+                    "description": "Optional region of the display to capture (x, y, width, height)",
+                    "properties": {
+                        "x": { "type": "integer" },
+                        "y": { "type": "integer" },
+                        "width": { "type": "integer" },
+                        "height": { "type": "integer" }
+                    }
+                },
+                "width": {
+                    "type": "integer",
+                    "description": "Viewport width for URL captures (default: 1280)"
+                },
+                "height": {
+                    "type": "integer",
+                    "description": "Viewport height for URL captures (default: 720)"
+                },
+                "wait_ms": {
+                    "type": "integer",
+                    "description": "Time to let the page or desktop settle before capturing, in milliseconds (default: 0)"
+                },
+                "threshold": {
+                    "type": "number",
+                    "description": "Per-pixel colour difference tolerance from 0 (exact) to 1 (default: 0.1)"
+                },
+                "max_diff_ratio": {
+                    "type": "number",
+                    "description": "Largest fraction of changed pixels that still passes (default: 0.001)"
+                },
+                "update_baseline": {
+                    "type": "boolean",
+                    "description": "Replace the baseline with this capture after comparing (default: false)"
+                },
+                "return_image": {
+                    "type": "boolean",
+                    "description": "If true, include the diff image in your context (requires vision model). Default: false"
+                }
+            },
+            "required": ["name"]
+        })
+    }
+
+    async fn execute(&self, args: Value, working_dir: &Path) -> anyhow::Result<String> {
+        let name = args["name"]
+            .as_str()
+            .ok_or_else(|| anyhow::anyhow!("Missing 'name' argument"))?;
+        validate_name(name)?;
+
+        let threshold = args["threshold"]
+            .as_f64()
+            .unwrap_or(DEFAULT_THRESHOLD)
+            .clamp(0.0, 1.0);
+        let max_diff_ratio = args["max_diff_ratio"]
+            .as_f64()
+            .unwrap_or(DEFAULT_MAX_DIFF_RATIO);
+        let wait_ms = args["wait_ms"].as_u64().unwrap_or(0);
+
+        let run_dir = working_dir.join(RUN_DIR);
+        let baseline_dir = working_dir.join(BASELINE_DIR);
+        std::fs::create_dir_all(&run_dir)?;
+        std::fs::create_dir_all(&baseline_dir)?;
+
+        let stamp = chrono::Utc::now().format("%Y%m%d_%H%M%S");
+        let current_path = run_dir.join(format!("{}-{}-current.png", name, stamp));
+        let diff_path = run_dir.join(format!("{}-{}-diff.png", name, stamp));
+        let baseline_path = baseline_dir.join(format!("{}.png", name));
+
+        match (args["url"].as_str(), args["display"].as_str()) {
+            (Some(url), None) => {
+                let width = args["width"].as_u64().unwrap_or(1280);
+                let height = args["height"].as_u64().unwrap_or(720);
+                tracing::info!(name = %name, url = %url, "Capturing URL for visual diff");
+                capture_url(url, &current_path, width, height, wait_ms).await?;
+            }
+            (None, Some(display_id)) => {
+                if wait_ms > 0 {
+                    tokio::time::sleep(std::time::Duration::from_millis(wait_ms)).await;
+                }
+                tracing::info!(name = %name, display = %display_id, "Capturing display for visual diff");
+                capture_display(display_id, &current_path, Region::from_args(&args)).await?;
+            }
+            _ => return Err(anyhow::anyhow!("Provide exactly one of 'url' or 'display'")),
+        }
+
+        let update_baseline = args["update_baseline"].as_bool().unwrap_or(false);
+        let return_image = args["return_image"].as_bool().unwrap_or(false);
+        let result = compare_with_baseline(
+            &baseline_path,
+            &current_path,
+            &diff_path,
+            threshold,
+            max_diff_ratio,
+            update_baseline,
+        )?;
+
+        let vision_marker = match result["diff"].as_str() {
+            Some(diff) if return_image => format!("\n\n[VISION_IMAGE:file://{}]", diff),
+            _ => String::new(),
+        };
+        Ok(format!("{}{}", result, vision_marker))
+    }
+}
+
+fn display_path(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+/// Compare `current_path` with the baseline (creating it on the first run),
+/// write the diff image and build the tool result.
+fn compare_with_baseline(
+    baseline_path: &Path,
+    current_path: &Path,
+    diff_path: &Path,
+    threshold: f64,
+    max_diff_ratio: f64,
+    update_baseline: bool,
+) -> anyhow::Result<Value> {
+    let current = decode_png(&std::fs::read(current_path)?)
+        .map_err(|e| anyhow::anyhow!("Failed to read capture: {}", e))?;
+
+    if !baseline_path.exists() {
+        std::fs::copy(current_path, baseline_path)?;
+        return Ok(json!({
+            "success": true,
+            "baseline_created": true,
+            "passed": true,
+            "baseline": display_path(baseline_path),
+            "current": display_path(current_path),
+            "width": current.width,
+            "height": current.height,
+            "diff_pixels": 0,
+            "diff_ratio": 0.0,
+        }));
+    }
+
+    let baseline = decode_png(&std::fs::read(baseline_path)?)
+        .map_err(|e| anyhow::anyhow!("Failed to read baseline: {}", e))?;
+
+    let mut result = json!({
+        "success": true,
+        "baseline_created": false,
+        "baseline": display_path(baseline_path),
+        "current": display_path(current_path),
+        "width": current.width,
+        "height": current.height,
+        "threshold": threshold,
+        "max_diff_ratio": max_diff_ratio,
+    });
+
+    if (baseline.width, baseline.height) != (current.width, current.height) {
+        result["passed"] = json!(false);
+        result["size_mismatch"] = json!(true);
+        result["baseline_size"] = json!([baseline.width, baseline.height]);
+        result["diff_ratio"] = json!(1.0);
+    } else {
+        let (diff_pixels, diff_image) = compare_images(&baseline, &current, threshold);
+        std::fs::write(diff_path, encode_png(&diff_image)?)?;
+        let total_pixels = current.width as u64 * current.height as u64;
+        let diff_ratio = if total_pixels == 0 {
+            0.0
+        } else {
+            diff_pixels as f64 / total_pixels as f64
+        };
+        result["passed"] = json!(diff_ratio <= max_diff_ratio);
+        result["diff"] = json!(display_path(diff_path));
+        result["diff_pixels"] = json!(diff_pixels);
+        result["total_pixels"] = json!(total_pixels);
+        result["diff_ratio"] = json!(diff_ratio);
+    }
+
+    if update_baseline {
+        std::fs::copy(current_path, baseline_path)?;
+        result["baseline_updated"] = json!(true);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid(width: u32, height: u32, px: [u8; 4]) -> Image {
+        Image {
+            width,
+            height,
+            rgba: px.repeat((width * height) as usize),
+        }
+    }
+
+    #[test]
+    fn png_roundtrip_and_filters() {
+        let mut image = solid(3, 2, [10, 20, 30, 255]);
+        image.rgba[4..8].copy_from_slice(&[200, 100, 50, 128]);
+        let decoded = decode_png(&encode_png(&image).unwrap()).unwrap();
+        assert_eq!(decoded, image);
+
+        // Paeth-filtered greyscale 2x2 image: rows [1, 2] and [3, 4].
+        let raw = [4u8, 1, 1, 4, 2, 1];
+        let mut encoder =
+            flate2::write::ZlibEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(&raw).unwrap();
+        let idat = encoder.finish().unwrap();
+        let mut png = PNG_SIGNATURE.to_vec();
+        for (kind, data) in [
+            (&b"IHDR"[..], vec![0, 0, 0, 2, 0, 0, 0, 2, 8, 0, 0, 0, 0]),
+            (&b"IDAT"[..], idat),
+            (&b"IEND"[..], vec![]),
+        ] {
+            png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+            png.extend_from_slice(kind);
+            png.extend_from_slice(&data);
+            png.extend_from_slice(&[0; 4]);
+        }
+        let grey = decode_png(&png).unwrap();
+        let values: Vec<u8> = grey.rgba.chunks_exact(4).map(|px| px[0]).collect();
+        assert_eq!(values, vec![1, 2, 3, 4]);
+
+        assert!(decode_png(b"not a png").is_err());
+    }
+
+    #[test]
+    fn comparison_respects_threshold() {
+        let baseline = solid(10, 10, [255, 255, 255, 255]);
+        let mut current = baseline.clone();
+        // One pixel turns black, one becomes a barely different off-white.
+        current.rgba[0..4].copy_from_slice(&[0, 0, 0, 255]);
+        current.rgba[4..8].copy_from_slice(&[250, 250, 250, 255]);
+
+        let (diff, image) = compare_images(&baseline, &current, DEFAULT_THRESHOLD);
+        assert_eq!(diff, 1);
+        assert_eq!(&image.rgba[0..4], &[255, 0, 0, 255]);
+        assert_ne!(&image.rgba[4..8], &[255, 0, 0, 255]);
+
+        let (diff, _) = compare_images(&baseline, &current, 0.0);
+        assert_eq!(diff, 2);
+    }
+
+    #[test]
+    fn baselines_are_created_then_compared() {
+        let dir = tempfile::tempdir().unwrap();
+        let baseline = dir.path().join("home.png");
+        let current = dir.path().join("current.png");
+        let diff = dir.path().join("diff.png");
+
+        std::fs::write(
+            &current,
+            encode_png(&solid(4, 4, [0, 0, 255, 255])).unwrap(),
+        )
+        .unwrap();
+        let first = compare_with_baseline(&baseline, &current, &diff, 0.1, 0.0, false).unwrap();
+        assert_eq!(first["baseline_created"], true);
+        assert!(baseline.exists());
+
+        let mut changed = solid(4, 4, [0, 0, 255, 255]);
+        changed.rgba[0..8].copy_from_slice(&[255, 255, 0, 255, 255, 255, 0, 255]);
+        std::fs::write(&current, encode_png(&changed).unwrap()).unwrap();
+        let second = compare_with_baseline(&baseline, &current, &diff, 0.1, 0.1, false).unwrap();
+        assert_eq!(second["diff_pixels"], 2);
+        assert_eq!(second["diff_ratio"], 0.125);
+        assert_eq!(second["passed"], false);
+        assert!(decode_png(&std::fs::read(&diff).unwrap()).is_ok());
+
+        std::fs::write(&current, encode_png(&solid(2, 2, [0; 4])).unwrap()).unwrap();
+        let resized = compare_with_baseline(&baseline, &current, &diff, 0.1, 0.1, true).unwrap();
+        assert_eq!(resized["size_mismatch"], true);
+        assert_eq!(resized["baseline_updated"], true);
+        assert_eq!(
+            decode_png(&std::fs::read(&baseline).unwrap())
+                .unwrap()
+                .width,
+            2
+        );
+    }
+
+    #[test]
+    fn baseline_names_are_file_safe() {
+        assert!(validate_name("login-page_v2.dark").is_ok());
+        assert!(validate_name("../etc").is_err());
+        assert!(validate_name("a/b").is_err());
+        assert!(validate_name("").is_err());
+    }
+}
@@ -17,11 +17,70 @@ pub fn env_var_bool(name: &str, default: bool) -> bool {
     }
 }
 
+/// Read an environment variable, trimmed; `None` when unset or blank.
+pub fn env_var_trimmed(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Dashboard link to a mission, under the server's public URL (a relative
+/// link when it is empty).
+pub fn mission_url(public_url: &str, mission_id: uuid::Uuid) -> String {
+    format!(
+        "{}/control?mission={}",
+        public_url.trim_end_matches('/'),
+        mission_id
+    )
+}
+
+/// Whether this server runs with root privileges (never on Windows).
+pub fn running_as_root() -> bool {
+    #[cfg(unix)]
+    {
+        // SAFETY: geteuid() is a trivial syscall with no preconditions.
+        unsafe { libc::geteuid() == 0 }
+    }
+    #[cfg(not(unix))]
+    {
+        false
+    }
+}
+
 /// Return the value of `$HOME`, falling back to `/root`.
 pub fn home_dir() -> String {
     std::env::var("HOME").unwrap_or_else(|_| "/root".to_string())
 }
 
+/// Canonicalize a path without the `\\?\` prefix Windows adds, so the result
+/// compares equal to (and displays like) the paths users and agents write.
+pub fn canonicalize_path(path: &std::path::Path) -> std::io::Result<std::path::PathBuf> {
+    path.canonicalize().map(strip_verbatim_prefix)
+}
+
+/// Turn a Windows verbatim path into its plain form: `\\?\C:\x` becomes
+/// `C:\x` and `\\?\UNC\host\share` becomes `\\host\share`. Other paths
+/// are returned unchanged.
+pub fn strip_verbatim_prefix(path: std::path::PathBuf) -> std::path::PathBuf {
+    let Some(raw) = path.to_str() else {
+        return path;
+    };
+    if let Some(rest) = raw.strip_prefix(r"\\?\UNC\") {
+        return format!(r"\\{}", rest).into();
+    }
+    match raw.strip_prefix(r"\\?\") {
+        Some(rest) if rest.as_bytes().get(1) == Some(&b':') => rest.into(),
+        _ => path,
+    }
+}
+
+/// Last component of a path written with `/` or `\` separators.
+pub fn path_file_name(path: &str) -> &str {
+    let trimmed = path.trim_end_matches(['/', '\\']);
+    trimmed.rsplit(['/', '\\']).next().unwrap_or(trimmed)
+}
+
 /// Build a truncated context string from conversation history.
 ///
 /// Walks `history` from most-recent to oldest, accumulating entries until
@@ -368,4 +427,26 @@ mod tests {
         ];
         assert_eq!(sanitize_skill_list(skills), vec!["foo", "bar"]);
     }
+
+    #[test]
+    fn windows_paths_are_normalized() {
+        use std::path::PathBuf;
+        assert_eq!(
+            strip_verbatim_prefix(PathBuf::from(r"\\?\C:\work\repo")),
+            PathBuf::from(r"C:\work\repo")
+        );
+        assert_eq!(
+            strip_verbatim_prefix(PathBuf::from(r"\\?\UNC\host\share\x")),
+            PathBuf::from(r"\\host\share\x")
+        );
+        assert_eq!(
+            strip_verbatim_prefix(PathBuf::from("/root/work")),
+            PathBuf::from("/root/work")
+        );
+
+        assert_eq!(path_file_name(r"C:\out\chart.png"), "chart.png");
+        assert_eq!(path_file_name("./out/report.pdf"), "report.pdf");
+        assert_eq!(path_file_name("out/dir/"), "dir");
+        assert_eq!(path_file_name("plain.txt"), "plain.txt");
+    }
 }
@@ -24,7 +24,7 @@ use uuid::Uuid;
 use crate::ai_providers::{AIProvider, ProviderType};
 use crate::config::Config;
 use crate::library::env_crypto::strip_encrypted_tags;
-use crate::library::LibraryStore;
+use crate::library::{EnvProfile, LibraryStore, ToolPolicy};
 use crate::mcp::{McpRegistry, McpScope, McpServerConfig, McpTransport};
 use crate::nspawn::{self, NspawnDistro};
 use crate::tools::terminal::{rtk_binary_path, rtk_enabled};
@@ -174,6 +174,30 @@ pub struct Workspace {
     /// Defaults to "default" if not specified.
     #[serde(default)]
     pub config_profile: Option<String>,
+    /// Library environment profile applied to missions in this workspace
+    /// (a mission's own profile takes priority).
+    #[serde(default)]
+    pub env_profile: Option<String>,
+    /// Kubernetes cluster access granted to missions in this workspace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kubernetes: Option<crate::kubernetes::KubernetesAccess>,
+    /// Remote hosts missions in this workspace may run commands on over SSH.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh: Option<crate::ssh::SshAccess>,
+    /// Advisory (proxy-based) egress allowlist for mission tool processes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub egress: Option<crate::egress::EgressPolicy>,
+    /// Windows during which automations of missions in this workspace aren't
+    /// triggered.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub automation_blackouts: Vec<crate::blackout::BlackoutWindow>,
+    /// Language of agent-facing metadata for missions in this workspace
+    /// (`None` follows the user default, see [`crate::i18n`]).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// Tenant that owns the workspace (`None` for instance-level workspaces).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tenant: Option<String>,
 }
 
 impl Workspace {
@@ -199,6 +223,13 @@ impl Workspace {
             tailscale_mode: None,
             mcps: Vec::new(),
             config_profile: None,
+            env_profile: None,
+            kubernetes: None,
+            ssh: None,
+            egress: None,
+            automation_blackouts: Vec::new(),
+            locale: None,
+            tenant: None,
         }
     }
 
@@ -220,6 +251,13 @@ impl Workspace {
             created_at: Utc::now(),
             skills: Vec::new(),
             config_profile: None,
+            env_profile: None,
+            kubernetes: None,
+            ssh: None,
+            egress: None,
+            automation_blackouts: Vec::new(),
+            locale: None,
+            tenant: None,
             plugins: Vec::new(),
             shared_network: None,
             tailscale_mode: None,
@@ -318,6 +356,9 @@ impl WorkspaceStore {
     async fn save_to_disk(&self) -> Result<(), std::io::Error> {
         let workspaces = self.workspaces.read().await;
         let workspaces_vec: Vec<&Workspace> = workspaces.values().collect();
+        for workspace in &workspaces_vec {
+            crate::i18n::set_workspace_locale(workspace.id, workspace.locale.as_deref());
+        }
 
         // Ensure parent directory exists
         if let Some(parent) = self.storage_path.parent() {
@@ -415,6 +456,13 @@ impl WorkspaceStore {
                     tailscale_mode: None,
                     mcps: Vec::new(),
                     config_profile: None,
+                    env_profile: None,
+                    kubernetes: None,
+                    ssh: None,
+                    egress: None,
+                    automation_blackouts: Vec::new(),
+                    locale: None,
+                    tenant: None,
                 };
 
                 orphaned.push(workspace);
@@ -1402,6 +1450,280 @@ jq -n --arg cmd "$NEW_CMD" '{
     })))
 }
 
+/// File name of the dry-run hook script in `.claude/hooks/`.
+const DRY_RUN_HOOK_NAME: &str = "dry-run.sh";
+
+/// Tools the dry-run hook simulates.
+const DRY_RUN_TOOL_MATCHER: &str = "Bash|Edit|MultiEdit|Write|NotebookEdit";
+
+const DRY_RUN_HOOK_SCRIPT: &str = r#"#!/bin/bash
+# Dry-run PreToolUse hook: blocks mutating tools and reports what they would
+# have done, so the agent can carry on without touching the system.
+INPUT=$(cat)
+
+if ! command -v jq >/dev/null 2>&1; then
+  echo "[dry-run] Tool call simulated: nothing was executed." >&2
+  exit 2
+fi
+
+SUMMARY=$(echo "$INPUT" | jq -r '
+  .tool_input as $in |
+  if .tool_name == "Bash" then "Would run: \($in.command // "")"
+  elif .tool_name == "Write" then "Would write \(($in.content // "") | length) characters to \($in.file_path // "?")"
+  elif .tool_name == "Edit" then "Would edit \($in.file_path // "?"): replace \(($in.old_string // "") | tojson) with \(($in.new_string // "") | tojson)"
+  elif .tool_name == "MultiEdit" then "Would apply \(($in.edits // []) | length) edits to \($in.file_path // "?")"
+  elif .tool_name == "NotebookEdit" then "Would edit notebook \($in.notebook_path // "?")"
+  else "Would call \(.tool_name)" end')
+
+jq -n --arg reason "[dry-run] $SUMMARY. Nothing was executed: this mission runs in dry-run mode. Treat the call as if it succeeded and continue." '{
+  hookSpecificOutput: {
+    hookEventName: "PreToolUse",
+    permissionDecision: "deny",
+    permissionDecisionReason: $reason
+  }
+}'
+"#;
+
+/// Add (or with `None`, remove) the `PreToolUse` hook whose script is named
+/// `hook_name` in a Claude Code settings object, leaving other hooks alone.
+fn apply_pre_tool_use_hook(
+    settings: &mut serde_json::Value,
+    hook_name: &str,
+    matcher: &str,
+    hook_command: Option<&str>,
+) {
+    let Some(obj) = settings.as_object_mut() else {
+        return;
+    };
+    let hooks = obj
+        .entry("hooks")
+        .or_insert_with(|| json!({}))
+        .as_object_mut();
+    let Some(hooks) = hooks else {
+        return;
+    };
+    let mut entries: Vec<serde_json::Value> = hooks
+        .get("PreToolUse")
+        .and_then(|v| v.as_array())
+        .cloned()
+        .unwrap_or_default();
+    entries.retain(|entry| {
+        !entry
+            .get("hooks")
+            .and_then(|v| v.as_array())
+            .is_some_and(|hooks| {
+                hooks.iter().any(|hook| {
+                    hook.get("command")
+                        .and_then(|c| c.as_str())
+                        .is_some_and(|c| c.ends_with(hook_name))
+                })
+            })
+    });
+    if let Some(command) = hook_command {
+        entries.push(json!({
+            "matcher": matcher,
+            "hooks": [{ "type": "command", "command": command }]
+        }));
+    }
+    if entries.is_empty() {
+        hooks.remove("PreToolUse");
+    } else {
+        hooks.insert("PreToolUse".to_string(), serde_json::Value::Array(entries));
+    }
+    if hooks.is_empty() {
+        obj.remove("hooks");
+    }
+}
+
+/// Turn dry-run mode on or off for a mission directory.
+///
+/// When enabled, writes a Claude Code `PreToolUse` hook that denies Bash, Edit
+/// and Write calls with a description of what they would have done. This
+/// covers Claude Code and OpenCode (whose oh-my-opencode layer wraps Claude
+/// Code). When disabled, removes the hook left by an earlier turn.
+pub async fn write_dry_run_hook(
+    mission_dir: &Path,
+    workspace_root: &Path,
+    workspace_type: WorkspaceType,
+    enabled: bool,
+) -> anyhow::Result<()> {
+    write_pre_tool_use_hook(
+        mission_dir,
+        workspace_root,
+        workspace_type,
+        DRY_RUN_HOOK_NAME,
+        DRY_RUN_TOOL_MATCHER,
+        enabled.then_some(DRY_RUN_HOOK_SCRIPT),
+    )
+    .await
+}
+
+/// Install (or with `None`, remove) a Claude Code `PreToolUse` hook script
+/// named `hook_name` in a mission directory's `.claude/hooks/` for the tools
+/// matching `matcher`. Like the dry-run hook, it covers Claude Code and
+/// OpenCode.
+pub async fn write_pre_tool_use_hook(
+    mission_dir: &Path,
+    workspace_root: &Path,
+    workspace_type: WorkspaceType,
+    hook_name: &str,
+    matcher: &str,
+    script: Option<&str>,
+) -> anyhow::Result<()> {
+    let claude_dir = mission_dir.join(".claude");
+    let hook_path = claude_dir.join("hooks").join(hook_name);
+    let local_settings_path = claude_dir.join("settings.local.json");
+
+    let hook_command = if let Some(script) = script {
+        tokio::fs::create_dir_all(claude_dir.join("hooks")).await?;
+        tokio::fs::write(&hook_path, script).await?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&hook_path, std::fs::Permissions::from_mode(0o755))?;
+        }
+        let is_container = workspace_type == WorkspaceType::Container && nspawn::nspawn_available();
+        Some(match hook_path.strip_prefix(workspace_root) {
+            Ok(rel) if is_container => format!("/{}", rel.to_string_lossy()),
+            _ => hook_path.to_string_lossy().to_string(),
+        })
+    } else {
+        if !hook_path.exists() {
+            return Ok(());
+        }
+        let _ = tokio::fs::remove_file(&hook_path).await;
+        None
+    };
+
+    // settings.json is only written for Claude Code; settings.local.json is
+    // created if needed so OpenCode's wrapped Claude Code sees the hook.
+    for path in [local_settings_path, claude_dir.join("settings.json")] {
+        let existing = tokio::fs::read_to_string(&path).await.ok();
+        if existing.is_none() && (hook_command.is_none() || path.ends_with("settings.json")) {
+            continue;
+        }
+        let mut settings: serde_json::Value = existing
+            .as_deref()
+            .and_then(|content| serde_json::from_str(content).ok())
+            .unwrap_or_else(|| json!({}));
+        apply_pre_tool_use_hook(&mut settings, hook_name, matcher, hook_command.as_deref());
+        tokio::fs::write(&path, serde_json::to_string_pretty(&settings)?).await?;
+    }
+    Ok(())
+}
+
+/// Generated script for the mission's environment profile, relative to the mission directory.
+const ENV_PROFILE_SCRIPT: &str = ".sandboxed-sh/env.sh";
+
+/// Apply a library environment profile to a mission turn.
+///
+/// Writes the profile as `.sandboxed-sh/env.sh` in the mission directory and
+/// points `BASH_ENV` at it, so every non-interactive bash the agent or its
+/// tools start sources the toolchain setup (nvm, pyenv, ...). The profile's
+/// variables are also merged into `workspace.env_vars`, which every process
+/// spawned for the turn inherits. PATH prepends are applied directly as well
+/// for processes that run on the host. Passing `None` removes the script left
+/// by an earlier turn.
+pub async fn apply_env_profile(
+    workspace: &mut Workspace,
+    mission_dir: &Path,
+    profile: Option<&EnvProfile>,
+) -> anyhow::Result<()> {
+    let script_path = mission_dir.join(ENV_PROFILE_SCRIPT);
+    let Some(profile) = profile else {
+        if script_path.exists() {
+            tokio::fs::remove_file(&script_path).await?;
+        }
+        return Ok(());
+    };
+
+    if let Some(parent) = script_path.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+    }
+    tokio::fs::write(&script_path, profile.to_shell_script()).await?;
+
+    let is_container =
+        workspace.workspace_type == WorkspaceType::Container && nspawn::nspawn_available();
+    let bash_env = match script_path.strip_prefix(&workspace.path) {
+        Ok(rel) if is_container => format!("/{}", rel.to_string_lossy()),
+        _ => script_path.to_string_lossy().to_string(),
+    };
+
+    for (key, value) in &profile.env {
+        workspace.env_vars.insert(key.clone(), value.clone());
+    }
+    if !is_container && !profile.path_prepend.is_empty() {
+        let current = workspace
+            .env_vars
+            .get("PATH")
+            .cloned()
+            .or_else(|| std::env::var("PATH").ok())
+            .unwrap_or_default();
+        let mut path = profile.path_prepend.join(":");
+        if !current.is_empty() {
+            path.push(':');
+            path.push_str(&current);
+        }
+        workspace.env_vars.insert("PATH".to_string(), path);
+    }
+    workspace.env_vars.insert("BASH_ENV".to_string(), bash_env);
+    workspace
+        .env_vars
+        .insert("SANDBOXED_SH_ENV_PROFILE".to_string(), profile.name.clone());
+    Ok(())
+}
+
+/// Apply a config profile's tool policy to a Claude Code mission: its rules
+/// are added to `permissions.allow` / `permissions.deny` in the settings files
+/// written for this turn.
+pub async fn write_tool_policy(mission_dir: &Path, policy: &ToolPolicy) -> anyhow::Result<()> {
+    if policy.allow.is_empty() && policy.deny.is_empty() {
+        return Ok(());
+    }
+    let claude_dir = mission_dir.join(".claude");
+    for path in [
+        claude_dir.join("settings.json"),
+        claude_dir.join("settings.local.json"),
+    ] {
+        let Ok(existing) = tokio::fs::read_to_string(&path).await else {
+            continue;
+        };
+        let mut settings: serde_json::Value =
+            serde_json::from_str(&existing).unwrap_or_else(|_| json!({}));
+        apply_tool_policy(&mut settings, policy);
+        tokio::fs::write(&path, serde_json::to_string_pretty(&settings)?).await?;
+    }
+    Ok(())
+}
+
+fn apply_tool_policy(settings: &mut serde_json::Value, policy: &ToolPolicy) {
+    let Some(obj) = settings.as_object_mut() else {
+        return;
+    };
+    let Some(permissions) = obj
+        .entry("permissions")
+        .or_insert_with(|| json!({}))
+        .as_object_mut()
+    else {
+        return;
+    };
+    for (key, rules) in [("allow", &policy.allow), ("deny", &policy.deny)] {
+        let mut list: Vec<serde_json::Value> = permissions
+            .get(key)
+            .and_then(|v| v.as_array())
+            .cloned()
+            .unwrap_or_default();
+        for rule in rules {
+            if !list.iter().any(|v| v.as_str() == Some(rule)) {
+                list.push(json!(rule));
+            }
+        }
+        if !list.is_empty() {
+            permissions.insert(key.to_string(), json!(list));
+        }
+    }
+}
+
 /// Write Claude Code configuration to the workspace.
 /// Generates `.claude/settings.local.json` and `CLAUDE.md` files.
 #[allow(clippy::too_many_arguments)]
@@ -2201,6 +2523,8 @@ pub async fn write_backend_config(
             )
             .await
         }
+        // The mock backend never reads workspace config
+        "mock" => Ok(()),
         _ => {
             // Unknown backend - write OpenCode config as fallback
             tracing::warn!(
@@ -2547,6 +2871,67 @@ pub async fn write_claudecode_commands_to_workspace(
     Ok(())
 }
 
+const SYSTEM_PROMPT_START_MARKER: &str = "<!-- sandboxed.sh:system-prompt:start -->";
+const SYSTEM_PROMPT_END_MARKER: &str = "<!-- sandboxed.sh:system-prompt:end -->";
+
+/// Write the merged library system prompt into the harness context file.
+///
+/// Claude Code reads `CLAUDE.md`; OpenCode, Amp and Codex read `AGENTS.md`.
+/// The prompt is kept between marker comments so repeated turns replace the
+/// previous section instead of appending to it. The section is appended after
+/// a single newline, which is removed with it, so an empty prompt restores the
+/// file as it was.
+/// The file is only rewritten when the section changes, so an unchanged prompt
+/// leaves the file (and its modification time) untouched across turns.
+pub async fn write_system_prompt_to_workspace(
+    workspace_dir: &Path,
+    backend_id: &str,
+    prompt: &str,
+) -> anyhow::Result<()> {
+    let file_name = if backend_id == "claudecode" {
+        "CLAUDE.md"
+    } else {
+        "AGENTS.md"
+    };
+    let path = workspace_dir.join(file_name);
+    let existing = tokio::fs::read_to_string(&path).await.unwrap_or_default();
+
+    let mut updated = match (
+        existing.find(SYSTEM_PROMPT_START_MARKER),
+        existing.find(SYSTEM_PROMPT_END_MARKER),
+    ) {
+        (Some(start), Some(end)) if end > start => {
+            let head = &existing[..start];
+            let tail = &existing[end + SYSTEM_PROMPT_END_MARKER.len()..];
+            format!(
+                "{}{}",
+                head.strip_suffix('\n').unwrap_or(head),
+                tail.strip_prefix('\n').unwrap_or(tail)
+            )
+        }
+        _ => existing.clone(),
+    };
+
+    let prompt = prompt.trim();
+    if !prompt.is_empty() {
+        if !updated.is_empty() {
+            updated.push('\n');
+        }
+        updated.push_str(SYSTEM_PROMPT_START_MARKER);
+        updated.push('\n');
+        updated.push_str(prompt);
+        updated.push('\n');
+        updated.push_str(SYSTEM_PROMPT_END_MARKER);
+        updated.push('\n');
+    }
+
+    if updated == existing {
+        return Ok(());
+    }
+    tokio::fs::write(&path, updated).await?;
+    Ok(())
+}
+
 /// Write commands as skills to the workspace's `.opencode/skill/` directory.
 /// For OpenCode, commands are treated as skills since OpenCode doesn't have a separate command system.
 pub async fn write_commands_as_opencode_skills(
@@ -3015,7 +3400,7 @@ async fn prepare_workspace_dir(path: &Path) -> anyhow::Result<PathBuf> {
 /// - Non-empty `workspace_mcps` → include only MCPs whose name is in the list
 ///
 /// In both cases, globally disabled MCPs are excluded.
-fn filter_mcp_configs_for_workspace(
+pub(crate) fn filter_mcp_configs_for_workspace(
     configs: Vec<McpServerConfig>,
     workspace_mcps: &[String],
 ) -> Vec<McpServerConfig> {
@@ -3500,9 +3885,12 @@ pub async fn write_runtime_workspace_state(
                 let _ = tokio::fs::create_dir_all(&context_link).await;
             }
         }
-        #[cfg(not(unix))]
+        #[cfg(windows)]
         {
-            let _ = tokio::fs::create_dir_all(&context_link).await;
+            // Directory symlinks need Developer Mode or admin rights on Windows.
+            if std::os::windows::fs::symlink_dir(target, &context_link).is_err() {
+                let _ = tokio::fs::create_dir_all(&context_link).await;
+            }
         }
     }
 
@@ -4462,3 +4850,167 @@ fn patch_opencode_agent_models_for_oauth(content: &str) -> String {
         content.to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn env_profile_is_written_and_merged_into_workspace_env() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut workspace = Workspace::default_host(dir.path().to_path_buf());
+        workspace
+            .env_vars
+            .insert("PATH".to_string(), "/usr/bin".to_string());
+        let profile = EnvProfile {
+            name: "node".to_string(),
+            env: [("NVM_DIR".to_string(), "/opt/nvm".to_string())]
+                .into_iter()
+                .collect(),
+            path_prepend: vec!["/opt/node/bin".to_string()],
+            init: Some("nvm use 20".to_string()),
+            ..Default::default()
+        };
+
+        apply_env_profile(&mut workspace, dir.path(), Some(&profile))
+            .await
+            .unwrap();
+        let script_path = dir.path().join(ENV_PROFILE_SCRIPT);
+        let script = std::fs::read_to_string(&script_path).unwrap();
+        assert!(script.contains("export NVM_DIR='/opt/nvm'"));
+        assert!(script.contains("nvm use 20"));
+        assert_eq!(workspace.env_vars["NVM_DIR"], "/opt/nvm");
+        assert_eq!(workspace.env_vars["PATH"], "/opt/node/bin:/usr/bin");
+        assert_eq!(
+            workspace.env_vars["BASH_ENV"],
+            script_path.to_string_lossy()
+        );
+        assert_eq!(workspace.env_vars["SANDBOXED_SH_ENV_PROFILE"], "node");
+
+        apply_env_profile(&mut workspace, dir.path(), None)
+            .await
+            .unwrap();
+        assert!(!script_path.exists());
+    }
+
+    #[tokio::test]
+    async fn system_prompt_section_is_only_rewritten_when_it_changes() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("CLAUDE.md");
+        std::fs::write(&path, "# Project\n").unwrap();
+
+        write_system_prompt_to_workspace(dir.path(), "claudecode", "Be brief.")
+            .await
+            .unwrap();
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(
+            written,
+            format!(
+                "# Project\n\n{}\nBe brief.\n{}\n",
+                SYSTEM_PROMPT_START_MARKER, SYSTEM_PROMPT_END_MARKER
+            )
+        );
+
+        // An unchanged prompt leaves the file alone.
+        let earlier = std::time::SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(1_000_000);
+        std::fs::File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(earlier)
+            .unwrap();
+        write_system_prompt_to_workspace(dir.path(), "claudecode", "Be brief.")
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::metadata(&path).unwrap().modified().unwrap(),
+            earlier
+        );
+
+        write_system_prompt_to_workspace(dir.path(), "claudecode", "")
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "# Project\n");
+
+        // Clearing the prompt gives back the original bytes, whatever they
+        // end with, and keeps anything added after the section.
+        for original in ["", "# Project", "# Project\n\n", "notes\r\n"] {
+            std::fs::write(&path, original).unwrap();
+            write_system_prompt_to_workspace(dir.path(), "claudecode", "Be brief.")
+                .await
+                .unwrap();
+            write_system_prompt_to_workspace(dir.path(), "claudecode", "")
+                .await
+                .unwrap();
+            assert_eq!(std::fs::read_to_string(&path).unwrap(), original);
+        }
+        write_system_prompt_to_workspace(dir.path(), "claudecode", "Be brief.")
+            .await
+            .unwrap();
+        let mut edited = std::fs::read_to_string(&path).unwrap();
+        edited.push_str("## Later\n");
+        std::fs::write(&path, edited).unwrap();
+        write_system_prompt_to_workspace(dir.path(), "claudecode", "")
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "notes\r\n## Later\n"
+        );
+
+        write_system_prompt_to_workspace(dir.path(), "opencode", "")
+            .await
+            .unwrap();
+        assert!(!dir.path().join("AGENTS.md").exists());
+    }
+
+    #[test]
+    fn dry_run_hook_is_added_and_removed_alongside_other_hooks() {
+        let rtk = json!({
+            "matcher": "Bash",
+            "hooks": [{ "type": "command", "command": "/x/.claude/hooks/rtk-wrap.sh" }]
+        });
+        let mut settings = json!({
+            "permissions": { "allow": ["Bash"] },
+            "hooks": { "PreToolUse": [rtk.clone()] }
+        });
+
+        apply_pre_tool_use_hook(
+            &mut settings,
+            DRY_RUN_HOOK_NAME,
+            DRY_RUN_TOOL_MATCHER,
+            Some("/x/.claude/hooks/dry-run.sh"),
+        );
+        apply_pre_tool_use_hook(
+            &mut settings,
+            DRY_RUN_HOOK_NAME,
+            DRY_RUN_TOOL_MATCHER,
+            Some("/x/.claude/hooks/dry-run.sh"),
+        );
+        let entries = settings["hooks"]["PreToolUse"].as_array().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1]["matcher"], DRY_RUN_TOOL_MATCHER);
+
+        apply_pre_tool_use_hook(&mut settings, DRY_RUN_HOOK_NAME, DRY_RUN_TOOL_MATCHER, None);
+        assert_eq!(settings["hooks"]["PreToolUse"], json!([rtk]));
+
+        let mut bare = json!({});
+        apply_pre_tool_use_hook(&mut bare, DRY_RUN_HOOK_NAME, DRY_RUN_TOOL_MATCHER, None);
+        assert_eq!(bare, json!({}));
+    }
+
+    #[test]
+    fn tool_policy_extends_generated_permissions() {
+        let mut settings = json!({ "permissions": { "allow": ["Bash", "Read"] } });
+        let policy = ToolPolicy {
+            allow: vec!["Read".to_string(), "WebSearch".to_string()],
+            deny: vec!["WebFetch".to_string()],
+        };
+        apply_tool_policy(&mut settings, &policy);
+        apply_tool_policy(&mut settings, &policy);
+        assert_eq!(
+            settings["permissions"],
+            json!({ "allow": ["Bash", "Read", "WebSearch"], "deny": ["WebFetch"] })
+        );
+    }
+}
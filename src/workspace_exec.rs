@@ -169,6 +169,15 @@ impl PtyChild {
         }
     }
 
+    /// OS process id of the child, if still known.
+    pub fn pid(&self) -> Option<u32> {
+        match &self.child {
+            PtyChildProcess::PortablePty(c) => c.process_id(),
+            #[cfg(unix)]
+            PtyChildProcess::Std(c) => Some(c.id()),
+        }
+    }
+
     /// Wait for the child process to exit. Must be called from a blocking context.
     pub fn wait(&mut self) -> std::io::Result<portable_pty::ExitStatus> {
         match &mut self.child {
@@ -786,6 +795,10 @@ impl WorkspaceExec {
             )
             .await
             .context("Failed to build workspace command")?;
+        // Own process group, so cancelling can signal the CLI together with
+        // the tool processes it starts.
+        #[cfg(unix)]
+        cmd.process_group(0);
 
         let child = cmd.spawn().context("Failed to spawn workspace command")?;
         Ok(child)